@@ -0,0 +1,125 @@
+//! Layout compilation from Move module ABIs
+//!
+//! Each resource a module declares becomes a storage entry labelled with
+//! the struct's name. The entry's type is the full struct tag, with generic
+//! parameters written `T0`, `T1`, ... as in the ABI, and its "slot" is only
+//! the resource's position in the module, since Aptos addresses state by
+//! key hash rather than slot.
+
+use crate::module::{display_address, parse_address, MoveModuleAbi, MoveStructAbi};
+use crate::AptosError;
+use traverse_core::{LayoutInfo, StorageEntry, TypeInfo, ZeroSemantics};
+
+#[cfg(feature = "std")]
+use std::path::Path;
+
+#[cfg(feature = "std")]
+use traverse_core::{LayoutCompiler, TraverseError};
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Encoding recorded for resources, which are stored as their BCS bytes
+pub const RESOURCE_ENCODING: &str = "bcs";
+
+/// Layout compiler for Move modules
+///
+/// # Usage
+///
+/// ```rust,ignore
+/// use traverse_aptos::AptosLayoutCompiler;
+/// use traverse_core::LayoutCompiler;
+///
+/// let layout = AptosLayoutCompiler.compile_layout(Path::new("coin.abi.json"))?;
+/// assert_eq!(layout.contract_name, "0x1::coin");
+/// ```
+pub struct AptosLayoutCompiler;
+
+impl AptosLayoutCompiler {
+    /// Compile the resources of a parsed module ABI
+    pub fn compile_module(module: &MoveModuleAbi) -> Result<LayoutInfo, AptosError> {
+        let module_id = format!("{}::{}", display_address(&parse_address(&module.address)?), module.name);
+        let mut storage = Vec::new();
+        let mut types = Vec::new();
+
+        for (index, resource) in module.resources().enumerate() {
+            let type_name = Self::resource_type(&module_id, resource);
+            storage.push(StorageEntry {
+                label: resource.name.clone(),
+                slot: index.to_string(),
+                offset: 0,
+                type_name: type_name.clone(),
+                // A resource that was never moved to an account is absent, not zero
+                zero_semantics: ZeroSemantics::NeverWritten,
+            });
+            types.push(TypeInfo {
+                label: type_name,
+                // 0 for resources with variable-size fields
+                number_of_bytes: resource.fixed_size().unwrap_or(0).to_string(),
+                encoding: RESOURCE_ENCODING.to_string(),
+                base: None,
+                key: None,
+                value: None,
+            });
+        }
+
+        Ok(LayoutInfo { contract_name: module_id, storage, types, code_variables: Vec::new() })
+    }
+
+    /// Struct tag of a resource with its generic parameters as `T0`, `T1`, ...
+    fn resource_type(module_id: &str, resource: &MoveStructAbi) -> String {
+        let params: Vec<_> = (0..resource.generic_type_params.len()).map(|i| format!("T{}", i)).collect();
+        if params.is_empty() {
+            format!("{}::{}", module_id, resource.name)
+        } else {
+            format!("{}::{}<{}>", module_id, resource.name, params.join(", "))
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl LayoutCompiler for AptosLayoutCompiler {
+    /// Compile a layout from a Move module ABI file
+    ///
+    /// Accepts the bare ABI or the `{ bytecode, abi }` object the REST API
+    /// returns for `/accounts/{address}/module/{name}`.
+    fn compile_layout(&self, abi_path: &Path) -> Result<LayoutInfo, TraverseError> {
+        let content = std::fs::read_to_string(abi_path)?;
+        let module = MoveModuleAbi::from_json(&content)?;
+        Ok(Self::compile_module(&module)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compile_module_resources() {
+        let module = MoveModuleAbi::from_json(
+            r#"{
+                "address": "0x1",
+                "name": "coin",
+                "structs": [
+                    { "name": "CoinInfo", "abilities": ["key"], "generic_type_params": [{ "constraints": [] }],
+                      "fields": [{ "name": "name", "type": "0x1::string::String" },
+                                 { "name": "decimals", "type": "u8" }] },
+                    { "name": "Coin", "abilities": ["store"], "fields": [{ "name": "value", "type": "u64" }] },
+                    { "name": "Paused", "abilities": ["key"], "fields": [{ "name": "paused", "type": "bool" }] }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let layout = AptosLayoutCompiler::compile_module(&module).unwrap();
+        assert_eq!(layout.contract_name, "0x1::coin");
+        let entries: Vec<_> = layout.storage.iter().map(|e| (e.label.as_str(), e.type_name.as_str())).collect();
+        assert_eq!(entries, [("CoinInfo", "0x1::coin::CoinInfo<T0>"), ("Paused", "0x1::coin::Paused")]);
+        assert_eq!(layout.types[0].number_of_bytes, "0");
+        assert_eq!(layout.types[1].number_of_bytes, "1");
+    }
+}
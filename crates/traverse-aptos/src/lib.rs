@@ -0,0 +1,98 @@
+//! Move module analysis and resource proofs for Aptos
+//!
+//! Aptos keeps all state in one Jellyfish Merkle tree keyed by state key
+//! hash: a resource lives under an access path made of the account address
+//! and the resource's struct tag, and the tree's root at each state
+//! checkpoint is recorded in the ledger. This crate maps that model onto the
+//! traverse core types.
+//!
+//! # Features
+//!
+//! - **Layout Compilation**: Turn a Move module ABI into a layout of its resources
+//! - **Query Resolution**: Resolve `Resource<TypeArgs>[account]` queries to state keys
+//! - **Proof Verification**: Check sparse Merkle proofs against a state root
+//! - **Proof Fetching**: Read resources and their proofs over the REST API (`client`)
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! use traverse_aptos::{AptosKeyResolver, AptosLayoutCompiler};
+//! use traverse_core::{KeyResolver, LayoutCompiler};
+//!
+//! let layout = AptosLayoutCompiler.compile_layout(Path::new("coin.abi.json"))?;
+//! let path = AptosKeyResolver.resolve(&layout, "CoinStore<0x1::aptos_coin::AptosCoin>[0xa11ce]")?;
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(not(feature = "std"))]
+use alloc::string::{String, ToString};
+
+pub mod layout;
+pub mod module;
+pub mod resolver;
+pub mod smt;
+
+#[cfg(feature = "client")]
+pub mod proof;
+
+pub use layout::AptosLayoutCompiler;
+pub use module::{display_address, parse_address, MoveModuleAbi, MoveStructAbi, StructTag, TypeTag};
+pub use resolver::{resource_state_key, AptosKeyResolver, ResourceKey};
+pub use smt::{state_key_hash, state_value_hash, SparseMerkleLeaf, SparseMerkleProof};
+
+#[cfg(feature = "client")]
+pub use proof::{
+    verify_state_proof, AptosChainConfig, AptosProofFetcher, AptosStateProof, LedgerInfo,
+    StateCheckpoint,
+};
+
+/// Error types specific to Aptos
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+#[derive(Debug)]
+pub enum AptosError {
+    #[cfg_attr(feature = "std", error("Invalid Move module ABI: {0}"))]
+    InvalidAbi(String),
+
+    #[cfg_attr(feature = "std", error("Invalid type tag: {0}"))]
+    InvalidTypeTag(String),
+
+    #[cfg_attr(feature = "std", error("Invalid account address: {0}"))]
+    InvalidAddress(String),
+
+    #[cfg_attr(feature = "std", error("Proof verification failed: {0}"))]
+    ProofVerification(String),
+
+    #[cfg_attr(feature = "std", error("REST API error: {0}"))]
+    Api(String),
+
+    #[cfg(feature = "client")]
+    #[cfg_attr(feature = "std", error("Network error: {0}"))]
+    Network(#[from] reqwest::Error),
+
+    #[cfg_attr(feature = "std", error("JSON error: {0}"))]
+    Json(#[cfg_attr(feature = "std", from)] serde_json::Error),
+
+    #[cfg_attr(feature = "std", error("Traverse core error: {0}"))]
+    TraverseCore(#[cfg_attr(feature = "std", from)] traverse_core::TraverseError),
+}
+
+impl From<AptosError> for traverse_core::TraverseError {
+    fn from(err: AptosError) -> Self {
+        use traverse_core::TraverseError;
+
+        match err {
+            AptosError::InvalidAbi(msg) => TraverseError::LayoutCompilation(msg),
+            AptosError::InvalidTypeTag(msg) | AptosError::InvalidAddress(msg) => TraverseError::InvalidInput(msg),
+            AptosError::ProofVerification(msg) => TraverseError::Validation(msg),
+            AptosError::Api(msg) => TraverseError::ExternalService(msg),
+            #[cfg(feature = "client")]
+            AptosError::Network(err) => TraverseError::ExternalService(err.to_string()),
+            AptosError::Json(err) => TraverseError::Serialization(err.to_string()),
+            AptosError::TraverseCore(err) => err,
+        }
+    }
+}
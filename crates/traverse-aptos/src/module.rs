@@ -0,0 +1,373 @@
+//! Move module ABIs and type tags
+//!
+//! The REST API describes a published module as JSON: its address, name and
+//! structs, each with its abilities and fields. Structs with the `key`
+//! ability are resources and can be stored under an account. Type tags name
+//! a resource type with its type arguments, e.g.
+//! `0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>`, and their BCS
+//! encoding is part of the resource's state key.
+
+use crate::AptosError;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// ABI of a published Move module, as returned by the REST API
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MoveModuleAbi {
+    /// Address the module is published at
+    pub address: String,
+    /// Module name
+    pub name: String,
+    /// Structs declared by the module
+    #[serde(default)]
+    pub structs: Vec<MoveStructAbi>,
+}
+
+/// A struct declared by a Move module
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MoveStructAbi {
+    pub name: String,
+    #[serde(default)]
+    pub is_native: bool,
+    /// Abilities, e.g. `key` and `store`
+    #[serde(default)]
+    pub abilities: Vec<String>,
+    /// Generic type parameters; only their number matters here
+    #[serde(default)]
+    pub generic_type_params: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub fields: Vec<MoveFieldAbi>,
+}
+
+/// A field of a Move struct
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MoveFieldAbi {
+    pub name: String,
+    /// Move type, e.g. `u64` or `0x1::coin::Coin<T0>`
+    #[serde(rename = "type")]
+    pub type_name: String,
+}
+
+impl MoveModuleAbi {
+    /// Parse a module ABI from the bare ABI or from a `{ bytecode, abi }` module response
+    pub fn from_json(content: &str) -> Result<Self, AptosError> {
+        let value: serde_json::Value = serde_json::from_str(content).map_err(AptosError::Json)?;
+        let abi = value.get("abi").cloned().unwrap_or(value);
+        let module: Self = serde_json::from_value(abi)
+            .map_err(|e| AptosError::InvalidAbi(format!("Expected a Move module ABI: {}", e)))?;
+        parse_address(&module.address)?;
+        Ok(module)
+    }
+
+    /// Structs that can be stored under an account
+    pub fn resources(&self) -> impl Iterator<Item = &MoveStructAbi> {
+        self.structs.iter().filter(|s| s.is_resource())
+    }
+}
+
+impl MoveStructAbi {
+    /// Whether the struct has the `key` ability
+    pub fn is_resource(&self) -> bool {
+        self.abilities.iter().any(|a| a == "key")
+    }
+
+    /// Size of the struct's BCS encoding, if every field has a fixed size
+    pub fn fixed_size(&self) -> Option<usize> {
+        self.fields.iter().map(|f| primitive_size(&f.type_name)).sum()
+    }
+}
+
+/// BCS size of a primitive Move type; `None` for anything variable or nested
+fn primitive_size(type_name: &str) -> Option<usize> {
+    match type_name {
+        "bool" | "u8" => Some(1),
+        "u16" => Some(2),
+        "u32" => Some(4),
+        "u64" => Some(8),
+        "u128" => Some(16),
+        "u256" | "address" => Some(32),
+        _ => None,
+    }
+}
+
+/// Parse an account address, left-padding short forms such as `0x1`
+pub fn parse_address(address: &str) -> Result<[u8; 32], AptosError> {
+    let digits = address
+        .strip_prefix("0x")
+        .ok_or_else(|| AptosError::InvalidAddress(format!("'{}' does not start with 0x", address)))?;
+    if digits.is_empty() || digits.len() > 64 {
+        return Err(AptosError::InvalidAddress(format!("'{}' is not 1 to 64 hex digits", address)));
+    }
+    let padded = format!("{:0>64}", digits);
+    let mut bytes = [0u8; 32];
+    hex::decode_to_slice(&padded, &mut bytes)
+        .map_err(|e| AptosError::InvalidAddress(format!("'{}': {}", address, e)))?;
+    Ok(bytes)
+}
+
+/// Display an address the way the REST API does (AIP-40)
+///
+/// Special addresses `0x0` to `0xf` take their short form, everything else
+/// all 64 digits.
+pub fn display_address(address: &[u8; 32]) -> String {
+    if address[..31].iter().all(|b| *b == 0) && address[31] < 0x10 {
+        format!("0x{:x}", address[31])
+    } else {
+        format!("0x{}", hex::encode(address))
+    }
+}
+
+/// A Move type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeTag {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    Address,
+    Signer,
+    Vector(Box<TypeTag>),
+    Struct(Box<StructTag>),
+}
+
+/// A struct type with its type arguments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructTag {
+    pub address: [u8; 32],
+    pub module: String,
+    pub name: String,
+    pub type_args: Vec<TypeTag>,
+}
+
+impl TypeTag {
+    /// Parse a type such as `u64`, `vector<u8>` or `0x1::string::String`
+    pub fn parse(type_tag: &str) -> Result<Self, AptosError> {
+        let type_tag = type_tag.trim();
+        Ok(match type_tag {
+            "bool" => Self::Bool,
+            "u8" => Self::U8,
+            "u16" => Self::U16,
+            "u32" => Self::U32,
+            "u64" => Self::U64,
+            "u128" => Self::U128,
+            "u256" => Self::U256,
+            "address" => Self::Address,
+            "signer" => Self::Signer,
+            _ => match type_tag.strip_prefix("vector<").and_then(|t| t.strip_suffix('>')) {
+                Some(element) => Self::Vector(Box::new(Self::parse(element)?)),
+                None => Self::Struct(Box::new(StructTag::parse(type_tag)?)),
+            },
+        })
+    }
+
+    /// BCS encoding, with variant indices in the order Move declares them
+    pub fn bcs(&self, out: &mut Vec<u8>) {
+        match self {
+            Self::Bool => out.push(0),
+            Self::U8 => out.push(1),
+            Self::U64 => out.push(2),
+            Self::U128 => out.push(3),
+            Self::Address => out.push(4),
+            Self::Signer => out.push(5),
+            Self::Vector(element) => {
+                out.push(6);
+                element.bcs(out);
+            }
+            Self::Struct(tag) => {
+                out.push(7);
+                tag.bcs(out);
+            }
+            Self::U16 => out.push(8),
+            Self::U32 => out.push(9),
+            Self::U256 => out.push(10),
+        }
+    }
+}
+
+impl StructTag {
+    /// Parse a struct type such as `0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>`
+    pub fn parse(type_tag: &str) -> Result<Self, AptosError> {
+        let invalid = || AptosError::InvalidTypeTag(format!("'{}' is not address::module::Name", type_tag));
+        let (base, type_args) = split_type_args(type_tag.trim())?;
+        let mut parts = base.split("::");
+        let (Some(address), Some(module), Some(name), None) = (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        if !is_identifier(module) || !is_identifier(name) {
+            return Err(invalid());
+        }
+        Ok(Self {
+            address: parse_address(address)?,
+            module: module.to_string(),
+            name: name.to_string(),
+            type_args: type_args.into_iter().map(TypeTag::parse).collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// BCS encoding: address, module and name, then the type arguments
+    pub fn bcs(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.address);
+        write_bytes(out, self.module.as_bytes());
+        write_bytes(out, self.name.as_bytes());
+        write_uleb128(out, self.type_args.len() as u64);
+        for arg in &self.type_args {
+            arg.bcs(out);
+        }
+    }
+}
+
+impl fmt::Display for TypeTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool => write!(f, "bool"),
+            Self::U8 => write!(f, "u8"),
+            Self::U16 => write!(f, "u16"),
+            Self::U32 => write!(f, "u32"),
+            Self::U64 => write!(f, "u64"),
+            Self::U128 => write!(f, "u128"),
+            Self::U256 => write!(f, "u256"),
+            Self::Address => write!(f, "address"),
+            Self::Signer => write!(f, "signer"),
+            Self::Vector(element) => write!(f, "vector<{}>", element),
+            Self::Struct(tag) => write!(f, "{}", tag),
+        }
+    }
+}
+
+impl fmt::Display for StructTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}::{}::{}", display_address(&self.address), self.module, self.name)?;
+        if !self.type_args.is_empty() {
+            write!(f, "<")?;
+            for (i, arg) in self.type_args.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}", arg)?;
+            }
+            write!(f, ">")?;
+        }
+        Ok(())
+    }
+}
+
+/// Split `Name<A, B<C>>` into `Name` and its top-level type arguments
+pub fn split_type_args(type_tag: &str) -> Result<(&str, Vec<&str>), AptosError> {
+    let Some(open) = type_tag.find('<') else {
+        return Ok((type_tag, Vec::new()));
+    };
+    let inner = type_tag[open + 1..]
+        .strip_suffix('>')
+        .ok_or_else(|| AptosError::InvalidTypeTag(format!("'{}' has unbalanced brackets", type_tag)))?;
+
+    let mut args = Vec::new();
+    let (mut depth, mut start) = (0usize, 0);
+    for (i, c) in inner.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or_else(|| AptosError::InvalidTypeTag(format!("'{}' has unbalanced brackets", type_tag)))?;
+            }
+            ',' if depth == 0 => {
+                args.push(inner[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return Err(AptosError::InvalidTypeTag(format!("'{}' has unbalanced brackets", type_tag)));
+    }
+    args.push(inner[start..].trim());
+    if args.iter().any(|arg| arg.is_empty()) {
+        return Err(AptosError::InvalidTypeTag(format!("'{}' has an empty type argument", type_tag)));
+    }
+    Ok((&type_tag[..open], args))
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Append a BCS byte string: ULEB128 length, then the bytes
+pub(crate) fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    write_uleb128(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+pub(crate) fn write_uleb128(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_struct_tag_round_trip_and_bcs() {
+        let tag = StructTag::parse("0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>").unwrap();
+        assert_eq!(tag.address, parse_address("0x0000000000000000000000000000000000000000000000000000000000000001").unwrap());
+        assert_eq!(tag.to_string(), "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>");
+
+        let mut bcs = Vec::new();
+        tag.bcs(&mut bcs);
+        let mut expected = [[0u8; 31].as_slice(), &[1], &[4], b"coin", &[9], b"CoinStore", &[1, 7]].concat();
+        expected.extend([[0u8; 31].as_slice(), &[1], &[10], b"aptos_coin", &[9], b"AptosCoin", &[0]].concat());
+        assert_eq!(bcs, expected);
+
+        let nested = TypeTag::parse("0x1::table::Table<address, vector<0x1::string::String>>").unwrap();
+        assert_eq!(nested.to_string(), "0x1::table::Table<address, vector<0x1::string::String>>");
+        assert_eq!(display_address(&parse_address("0xa11ce").unwrap()), format!("0x{:0>64}", "a11ce"));
+
+        assert!(StructTag::parse("0x1::coin").is_err());
+        assert!(StructTag::parse("0x1::coin::CoinStore<u64").is_err());
+        assert!(parse_address("a11ce").is_err());
+    }
+
+    #[test]
+    fn test_module_abi_resources() {
+        let response = r#"{
+            "bytecode": "0xa11ceb0b",
+            "abi": {
+                "address": "0x1",
+                "name": "coin",
+                "friends": [],
+                "exposed_functions": [],
+                "structs": [
+                    { "name": "Coin", "is_native": false, "abilities": ["store"],
+                      "generic_type_params": [{ "constraints": [] }],
+                      "fields": [{ "name": "value", "type": "u64" }] },
+                    { "name": "CoinStore", "is_native": false, "abilities": ["key"],
+                      "generic_type_params": [{ "constraints": [] }],
+                      "fields": [{ "name": "coin", "type": "0x1::coin::Coin<T0>" },
+                                 { "name": "frozen", "type": "bool" }] }
+                ]
+            }
+        }"#;
+        let module = MoveModuleAbi::from_json(response).unwrap();
+        let resources: Vec<_> = module.resources().map(|s| s.name.as_str()).collect();
+        assert_eq!(resources, ["CoinStore"]);
+        assert_eq!(module.structs[0].fixed_size(), Some(8));
+        assert_eq!(module.structs[1].fixed_size(), None);
+    }
+}
@@ -0,0 +1,384 @@
+//! Resource proofs over the Aptos REST API
+//!
+//! A proof is pinned to a state checkpoint: the last transaction of a block
+//! records the root of the state tree after it as `state_checkpoint_hash`.
+//! The resource is read at that version as raw BCS, and a sparse Merkle
+//! proof of its state key is requested for the same version, so the value
+//! and the proof verify against that root.
+//!
+//! Fullnodes do not serve sparse Merkle proofs through the public REST
+//! API, so the proof endpoint is configurable: [`AptosChainConfig::proof_path`]
+//! is requested as `GET {rest_url}/{proof_path}/{state key hash}?ledger_version=`
+//! and must answer with `{ "leaf": { "key", "value_hash" } | null, "siblings": [...] }`,
+//! hashes in `0x` hex and siblings from the leaf up.
+
+use crate::module::{display_address, StructTag};
+use crate::resolver::resource_state_key;
+use crate::smt::{state_key_hash, state_value_hash, SparseMerkleLeaf, SparseMerkleProof};
+use crate::AptosError;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use traverse_core::{ProofFetcher, SemanticStorageProof, StorageSemantics, TraverseError, ZeroSemantics};
+
+/// Chain-specific configuration for proof fetching
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AptosChainConfig {
+    /// Path of the state proof endpoint, relative to the REST URL
+    pub proof_path: String,
+}
+
+impl Default for AptosChainConfig {
+    fn default() -> Self {
+        Self { proof_path: "state_proof".to_string() }
+    }
+}
+
+/// Ledger summary from the REST API's index route
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LedgerInfo {
+    pub chain_id: u8,
+    pub ledger_version: u64,
+    pub block_height: u64,
+}
+
+/// A version whose state root is recorded in the ledger
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateCheckpoint {
+    pub version: u64,
+    pub root: [u8; 32],
+}
+
+/// A resource, or its absence, with a proof against a state checkpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AptosStateProof {
+    pub account: [u8; 32],
+    /// Resource type, as displayed by the REST API
+    pub resource: String,
+    /// Encoded state key
+    pub state_key: Vec<u8>,
+    /// BCS bytes of the resource; `None` if the account does not hold it
+    pub value: Option<Vec<u8>>,
+    pub proof: SparseMerkleProof,
+    pub version: u64,
+    /// State root at `version`
+    pub root: [u8; 32],
+}
+
+impl AptosStateProof {
+    /// JSON form with hashes and bytes in `0x` hex
+    pub fn to_json(&self) -> Value {
+        json!({
+            "account": display_address(&self.account),
+            "resource": self.resource,
+            "state_key": format!("0x{}", hex::encode(&self.state_key)),
+            "state_key_hash": hex_hash(&state_key_hash(&self.state_key)),
+            "value": self.value.as_ref().map(|v| format!("0x{}", hex::encode(v))),
+            "version": self.version,
+            "root": hex_hash(&self.root),
+            "proof": proof_json(&self.proof),
+        })
+    }
+
+    /// Parse the JSON written by [`Self::to_json`]
+    pub fn from_json(value: &Value) -> Result<Self, AptosError> {
+        let field = |name: &str| {
+            value
+                .get(name)
+                .ok_or_else(|| AptosError::Api(format!("Proof is missing '{}'", name)))
+        };
+        Ok(Self {
+            account: crate::parse_address(field("account")?.as_str().unwrap_or_default())?,
+            resource: field("resource")?.as_str().unwrap_or_default().to_string(),
+            state_key: parse_bytes(field("state_key")?)?,
+            value: match field("value")? {
+                Value::Null => None,
+                value => Some(parse_bytes(value)?),
+            },
+            proof: parse_proof(field("proof")?)?,
+            version: field("version")?
+                .as_u64()
+                .ok_or_else(|| AptosError::Api("Proof version is not a number".into()))?,
+            root: parse_hash(field("root")?)?,
+        })
+    }
+}
+
+/// Check a resource proof against its state root
+///
+/// The state key must encode the named resource under the named account,
+/// and the proof must show its value, or its absence, under the root.
+pub fn verify_state_proof(proof: &AptosStateProof) -> Result<(), AptosError> {
+    let resource = StructTag::parse(&proof.resource)?;
+    if resource_state_key(&proof.account, &resource) != proof.state_key {
+        return Err(AptosError::ProofVerification(format!(
+            "the state key is not that of {} under {}",
+            proof.resource,
+            display_address(&proof.account)
+        )));
+    }
+    let value_hash = proof.value.as_deref().map(state_value_hash);
+    proof.proof.verify(&proof.root, &state_key_hash(&proof.state_key), value_hash.as_ref())
+}
+
+/// Resource proof fetcher for an Aptos REST endpoint
+#[derive(Debug, Clone)]
+pub struct AptosProofFetcher {
+    /// REST API URL, including `/v1`
+    pub rest_url: String,
+    pub config: AptosChainConfig,
+}
+
+impl AptosProofFetcher {
+    pub fn new(rest_url: String, config: AptosChainConfig) -> Self {
+        Self { rest_url: rest_url.trim_end_matches('/').to_string(), config }
+    }
+
+    pub fn with_defaults(rest_url: String) -> Self {
+        Self::new(rest_url, AptosChainConfig::default())
+    }
+
+    /// Fetch a resource under `account` with its proof
+    ///
+    /// `version` must be a state checkpoint; `None` uses the checkpoint of
+    /// the latest block.
+    pub async fn fetch_proof(
+        &self,
+        account: &[u8; 32],
+        resource: &StructTag,
+        version: Option<u64>,
+    ) -> Result<AptosStateProof, AptosError> {
+        let checkpoint = self.state_checkpoint(version).await?;
+        if version.is_some_and(|version| version != checkpoint.version) {
+            return Err(AptosError::Api(format!(
+                "Version {} is not a state checkpoint; the block's checkpoint is {}",
+                version.unwrap_or_default(),
+                checkpoint.version
+            )));
+        }
+
+        let state_key = resource_state_key(account, resource);
+        let value = self.fetch_resource(account, resource, checkpoint.version).await?;
+        let proof = self.fetch_sparse_proof(&state_key_hash(&state_key), checkpoint.version).await?;
+        Ok(AptosStateProof {
+            account: *account,
+            resource: resource.to_string(),
+            state_key,
+            value,
+            proof,
+            version: checkpoint.version,
+            root: checkpoint.root,
+        })
+    }
+
+    /// Chain ID, ledger version and block height of the node
+    pub async fn ledger_info(&self) -> Result<LedgerInfo, AptosError> {
+        let info = self.get_json(&self.rest_url).await?;
+        let number = |name: &str| {
+            info.get(name)
+                .and_then(|v| v.as_str().and_then(|s| s.parse().ok()).or_else(|| v.as_u64()))
+                .ok_or_else(|| AptosError::Api(format!("No {} in ledger info", name)))
+        };
+        Ok(LedgerInfo {
+            chain_id: u8::try_from(number("chain_id")?)
+                .map_err(|_| AptosError::Api("Chain ID does not fit in a byte".into()))?,
+            ledger_version: number("ledger_version")?,
+            block_height: number("block_height")?,
+        })
+    }
+
+    /// Chain ID the node serves
+    pub async fn chain_id(&self) -> Result<String, AptosError> {
+        Ok(self.ledger_info().await?.chain_id.to_string())
+    }
+
+    /// State checkpoint of the block containing `version`, or of the latest block
+    pub async fn state_checkpoint(&self, version: Option<u64>) -> Result<StateCheckpoint, AptosError> {
+        let version = match version {
+            Some(version) => version,
+            None => self.ledger_info().await?.ledger_version,
+        };
+        let block = self
+            .get_json(&format!("{}/blocks/by_version/{}?with_transactions=true", self.rest_url, version))
+            .await?;
+        let checkpoint = block
+            .get("transactions")
+            .and_then(|t| t.as_array())
+            .and_then(|transactions| {
+                transactions
+                    .iter()
+                    .rev()
+                    .find(|t| t.get("state_checkpoint_hash").is_some_and(|h| !h.is_null()))
+            })
+            .ok_or_else(|| AptosError::Api(format!("The block containing version {} has no state checkpoint", version)))?;
+
+        Ok(StateCheckpoint {
+            version: checkpoint
+                .get("version")
+                .and_then(|v| v.as_str())
+                .and_then(|v| v.parse().ok())
+                .ok_or_else(|| AptosError::Api("Checkpoint transaction has no version".into()))?,
+            root: parse_hash(&checkpoint["state_checkpoint_hash"])?,
+        })
+    }
+
+    /// BCS bytes of a resource at `version`; `None` if the account does not hold it
+    pub async fn fetch_resource(
+        &self,
+        account: &[u8; 32],
+        resource: &StructTag,
+        version: u64,
+    ) -> Result<Option<Vec<u8>>, AptosError> {
+        let url = format!(
+            "{}/accounts/{}/resource/{}?ledger_version={}",
+            self.rest_url,
+            display_address(account),
+            resource,
+            version
+        );
+        let response = reqwest::Client::new()
+            .get(&url)
+            .header(reqwest::header::ACCEPT, "application/x-bcs")
+            .send()
+            .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(AptosError::Api(format!("{} returned {}: {}", url, response.status(), response.text().await?)));
+        }
+        Ok(Some(response.bytes().await?.to_vec()))
+    }
+
+    /// Sparse Merkle proof of the state key hash `key` at `version`
+    pub async fn fetch_sparse_proof(&self, key: &[u8; 32], version: u64) -> Result<SparseMerkleProof, AptosError> {
+        let proof = self
+            .get_json(&format!(
+                "{}/{}/{}?ledger_version={}",
+                self.rest_url,
+                self.config.proof_path.trim_matches('/'),
+                hex_hash(key),
+                version
+            ))
+            .await?;
+        parse_proof(&proof)
+    }
+
+    async fn get_json(&self, url: &str) -> Result<Value, AptosError> {
+        let response = reqwest::Client::new().get(url).send().await?;
+        if !response.status().is_success() {
+            return Err(AptosError::Api(format!("{} returned {}: {}", url, response.status(), response.text().await?)));
+        }
+        Ok(response.json().await?)
+    }
+}
+
+impl ProofFetcher for AptosProofFetcher {
+    /// Fetch the proof of a state key hash at the latest state checkpoint
+    ///
+    /// The proof's value is the hash of the stored value, which is what the
+    /// leaf commits to, or zero when nothing is stored under the key.
+    fn fetch(&self, key: &[u8; 32], zero_semantics: ZeroSemantics) -> Result<SemanticStorageProof, TraverseError> {
+        let fetcher = self.clone();
+        let key = *key;
+        let run = move || -> Result<SparseMerkleProof, AptosError> {
+            let runtime = tokio::runtime::Runtime::new()
+                .map_err(|e| AptosError::Api(format!("Failed to create runtime: {}", e)))?;
+            runtime.block_on(async {
+                let checkpoint = fetcher.state_checkpoint(None).await?;
+                fetcher.fetch_sparse_proof(&key, checkpoint.version).await
+            })
+        };
+        // Inside a runtime, block on a thread with its own
+        let proof = match tokio::runtime::Handle::try_current() {
+            Ok(_) => std::thread::spawn(run)
+                .join()
+                .map_err(|_| TraverseError::ProofGeneration("Thread panicked".to_string()))?,
+            Err(_) => run(),
+        }?;
+
+        let value = match proof.leaf {
+            Some(leaf) if leaf.key == key => leaf.value_hash,
+            _ => [0u8; 32],
+        };
+        Ok(SemanticStorageProof {
+            key,
+            value,
+            proof: proof.siblings,
+            semantics: StorageSemantics::new(zero_semantics),
+        })
+    }
+}
+
+fn hex_hash(hash: &[u8; 32]) -> String {
+    format!("0x{}", hex::encode(hash))
+}
+
+fn parse_bytes(value: &Value) -> Result<Vec<u8>, AptosError> {
+    let hex_str = value.as_str().ok_or_else(|| AptosError::Api(format!("Expected hex, found {}", value)))?;
+    hex::decode(hex_str.trim_start_matches("0x")).map_err(|e| AptosError::Api(format!("Invalid hex '{}': {}", hex_str, e)))
+}
+
+fn parse_hash(value: &Value) -> Result<[u8; 32], AptosError> {
+    parse_bytes(value)?
+        .try_into()
+        .map_err(|_| AptosError::Api(format!("Expected a 32-byte hash, found {}", value)))
+}
+
+fn parse_proof(value: &Value) -> Result<SparseMerkleProof, AptosError> {
+    let leaf = match value.get("leaf") {
+        None | Some(Value::Null) => None,
+        Some(leaf) => Some(SparseMerkleLeaf { key: parse_hash(&leaf["key"])?, value_hash: parse_hash(&leaf["value_hash"])? }),
+    };
+    let siblings = value
+        .get("siblings")
+        .and_then(|s| s.as_array())
+        .ok_or_else(|| AptosError::Api("Proof has no siblings".into()))?
+        .iter()
+        .map(parse_hash)
+        .collect::<Result<_, _>>()?;
+    Ok(SparseMerkleProof { leaf, siblings })
+}
+
+fn proof_json(proof: &SparseMerkleProof) -> Value {
+    json!({
+        "leaf": proof.leaf.map(|leaf| json!({ "key": hex_hash(&leaf.key), "value_hash": hex_hash(&leaf.value_hash) })),
+        "siblings": proof.siblings.iter().map(hex_hash).collect::<Vec<_>>(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_address;
+
+    #[test]
+    fn test_state_proof_json_and_verification() {
+        let account = parse_address("0xa11ce").unwrap();
+        let resource = StructTag::parse("0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>").unwrap();
+        let state_key = resource_state_key(&account, &resource);
+        let value = vec![42u8; 8];
+
+        // A tree holding only this resource: the root is its leaf
+        let leaf = SparseMerkleLeaf { key: state_key_hash(&state_key), value_hash: state_value_hash(&value) };
+        let proof = AptosStateProof {
+            account,
+            resource: resource.to_string(),
+            state_key,
+            value: Some(value),
+            proof: SparseMerkleProof { leaf: Some(leaf), siblings: Vec::new() },
+            version: 7,
+            root: leaf.hash(),
+        };
+        verify_state_proof(&proof).unwrap();
+        let round_trip = AptosStateProof::from_json(&proof.to_json()).unwrap();
+        assert_eq!(round_trip, proof);
+
+        let mut other_account = proof.clone();
+        other_account.account = parse_address("0xb0b").unwrap();
+        assert!(verify_state_proof(&other_account).is_err());
+
+        let mut other_value = proof;
+        other_value.value = Some(vec![0; 8]);
+        assert!(verify_state_proof(&other_value).is_err());
+    }
+}
@@ -0,0 +1,219 @@
+//! Resource key resolution
+//!
+//! A query names a resource of the layout's module, its type arguments if
+//! it is generic, and the account it is stored under:
+//! `CoinStore<0x1::aptos_coin::AptosCoin>[0xa11ce]`. Without an account the
+//! module's own address is used, where configuration resources usually
+//! live. A trailing field path such as `.coin.value` is accepted, but the
+//! key, like the proof, covers the whole resource.
+//!
+//! The state key is the BCS encoding of the access path, tagged as such,
+//! and the tree position is its hash (see [`crate::smt::state_key_hash`]).
+//! Members of resource groups are stored under their group's tag and are
+//! not resolved here.
+
+use crate::module::{parse_address, split_type_args, write_bytes, StructTag};
+use crate::smt::state_key_hash;
+use crate::AptosError;
+use traverse_core::{Key, KeyResolver, LayoutInfo, StaticKeyPath, StorageEntry, TraverseError};
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+/// `StateKey` variant of access paths
+const STATE_KEY_ACCESS_PATH: u8 = 0;
+/// `Path` variant of resources
+const PATH_RESOURCE: u8 = 1;
+
+/// Key resolver for Move resources
+///
+/// # Usage
+///
+/// ```rust,ignore
+/// use traverse_aptos::AptosKeyResolver;
+/// use traverse_core::KeyResolver;
+///
+/// let path = AptosKeyResolver.resolve(&layout, "CoinStore<0x1::aptos_coin::AptosCoin>[0xa11ce]")?;
+/// ```
+pub struct AptosKeyResolver;
+
+/// A resource resolved to its place in the state tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceKey {
+    /// Account the resource is stored under
+    pub account: [u8; 32],
+    /// Resource type with its type arguments
+    pub resource: StructTag,
+    /// Encoded state key
+    pub state_key: Vec<u8>,
+    /// Position of the state key in the tree
+    pub hash: [u8; 32],
+    /// Field path after the resource, if the query has one
+    pub field: Option<String>,
+}
+
+/// Encoded state key of the resource `resource` under `account`
+pub fn resource_state_key(account: &[u8; 32], resource: &StructTag) -> Vec<u8> {
+    let mut path = vec![PATH_RESOURCE];
+    resource.bcs(&mut path);
+
+    let mut state_key = Vec::with_capacity(1 + 32 + 2 + path.len());
+    state_key.push(STATE_KEY_ACCESS_PATH);
+    state_key.extend_from_slice(account);
+    write_bytes(&mut state_key, &path);
+    state_key
+}
+
+impl AptosKeyResolver {
+    /// Resolve a query to the resource it names and the layout entry declaring it
+    pub fn resource_key<'a>(
+        layout: &'a LayoutInfo,
+        query: &str,
+    ) -> Result<(ResourceKey, &'a StorageEntry), AptosError> {
+        let (head, account, field) = Self::split_query(query)?;
+        let (name, type_args) = split_type_args(head)?;
+        let entry = layout
+            .storage
+            .iter()
+            .find(|entry| entry.label == name)
+            .ok_or_else(|| AptosError::InvalidTypeTag(format!("{} declares no resource {}", layout.contract_name, name)))?;
+
+        let (base, params) = split_type_args(&entry.type_name)?;
+        if type_args.len() != params.len() {
+            return Err(AptosError::InvalidTypeTag(format!(
+                "{} takes {} type arguments, but the query gives {}",
+                name,
+                params.len(),
+                type_args.len()
+            )));
+        }
+        let resource = if type_args.is_empty() {
+            StructTag::parse(base)?
+        } else {
+            StructTag::parse(&format!("{}<{}>", base, type_args.join(", ")))?
+        };
+
+        let account = match account {
+            Some(account) => parse_address(account)?,
+            None => parse_address(layout.contract_name.split("::").next().unwrap_or_default())?,
+        };
+        let state_key = resource_state_key(&account, &resource);
+        let hash = state_key_hash(&state_key);
+        Ok((ResourceKey { account, resource, state_key, hash, field: field.map(str::to_string) }, entry))
+    }
+
+    /// Split `Name<Args>[account].field` into its resource, account and field path
+    fn split_query(query: &str) -> Result<(&str, Option<&str>, Option<&str>), AptosError> {
+        let Some(open) = query.find('[') else {
+            return Ok(match query.split_once('.') {
+                Some((head, field)) => (head, None, Some(field)),
+                None => (query, None, None),
+            });
+        };
+        let close = query[open..]
+            .find(']')
+            .map(|close| open + close)
+            .ok_or_else(|| AptosError::InvalidTypeTag(format!("'{}' is missing a closing bracket", query)))?;
+        let field = match &query[close + 1..] {
+            "" => None,
+            rest => Some(rest.strip_prefix('.').ok_or_else(|| {
+                AptosError::InvalidTypeTag(format!("'{}' must end with the account or a .field path", query))
+            })?),
+        };
+        Ok((&query[..open], Some(query[open + 1..close].trim()), field))
+    }
+
+    fn static_path(layout: &LayoutInfo, key: &ResourceKey, entry: &StorageEntry) -> StaticKeyPath {
+        let size = layout
+            .types
+            .iter()
+            .find(|t| t.label == entry.type_name)
+            .and_then(|t| t.number_of_bytes.parse::<u8>().ok())
+            .filter(|size| *size > 0);
+        StaticKeyPath {
+            name: "aptos_resource",
+            key: Key::Fixed(key.hash),
+            offset: None,
+            field_size: size,
+            layout_commitment: layout.commitment(),
+            zero_semantics: entry.zero_semantics,
+        }
+    }
+}
+
+impl KeyResolver for AptosKeyResolver {
+    /// Resolve a resource query to the hash of its state key
+    fn resolve(&self, layout: &LayoutInfo, query: &str) -> Result<StaticKeyPath, TraverseError> {
+        let (key, entry) = Self::resource_key(layout, query)?;
+        Ok(Self::static_path(layout, &key, entry))
+    }
+
+    /// Resolve every non-generic resource at the module's own address
+    fn resolve_all(&self, layout: &LayoutInfo) -> Result<Vec<StaticKeyPath>, TraverseError> {
+        let mut paths = Vec::new();
+        for entry in &layout.storage {
+            if entry.type_name.contains('<') {
+                continue;
+            }
+            let (key, entry) = Self::resource_key(layout, &entry.label)?;
+            paths.push(Self::static_path(layout, &key, entry));
+        }
+        Ok(paths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AptosLayoutCompiler, MoveModuleAbi};
+
+    fn coin_layout() -> LayoutInfo {
+        let module = MoveModuleAbi::from_json(
+            r#"{
+                "address": "0x1",
+                "name": "coin",
+                "structs": [
+                    { "name": "CoinStore", "abilities": ["key"], "generic_type_params": [{ "constraints": [] }],
+                      "fields": [{ "name": "coin", "type": "0x1::coin::Coin<T0>" }] },
+                    { "name": "SupplyConfig", "abilities": ["key"],
+                      "fields": [{ "name": "allow_upgrades", "type": "bool" }] }
+                ]
+            }"#,
+        )
+        .unwrap();
+        AptosLayoutCompiler::compile_module(&module).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_resource_queries() {
+        let layout = coin_layout();
+        let (key, _) =
+            AptosKeyResolver::resource_key(&layout, "CoinStore<0x1::aptos_coin::AptosCoin>[0xa11ce].coin.value")
+                .unwrap();
+        assert_eq!(key.resource.to_string(), "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>");
+        assert_eq!(key.account, parse_address("0xa11ce").unwrap());
+        assert_eq!(key.field.as_deref(), Some("coin.value"));
+
+        // Tag, address, then the BCS path with its length prefix
+        let mut path = vec![PATH_RESOURCE];
+        key.resource.bcs(&mut path);
+        assert_eq!(key.state_key[0], STATE_KEY_ACCESS_PATH);
+        assert_eq!(&key.state_key[1..33], &key.account);
+        assert_eq!(key.state_key[33] as usize, path.len());
+        assert_eq!(&key.state_key[34..], &path[..]);
+        assert_eq!(key.hash, state_key_hash(&key.state_key));
+
+        let path = AptosKeyResolver.resolve(&layout, "SupplyConfig").unwrap();
+        assert_eq!(path.field_size, Some(1));
+        assert_eq!(AptosKeyResolver.resolve_all(&layout).unwrap(), vec![path]);
+
+        assert!(AptosKeyResolver.resolve(&layout, "CoinStore[0xa11ce]").is_err());
+        assert!(AptosKeyResolver.resolve(&layout, "Missing[0xa11ce]").is_err());
+        assert!(AptosKeyResolver.resolve(&layout, "SupplyConfig[0xa11ce]x").is_err());
+    }
+}
@@ -0,0 +1,186 @@
+//! Sparse Merkle proofs of Aptos state
+//!
+//! Aptos state is a sparse Merkle tree over 256-bit state key hashes.
+//! Every hash is SHA3-256 prefixed with a per-type seed,
+//! `SHA3-256("APTOS::" || type name)`, so a leaf cannot be passed off as an
+//! internal node. Subtrees holding a single leaf are collapsed into that
+//! leaf and empty subtrees hash to a fixed placeholder, so a proof carries
+//! one sibling per level down to where the key's leaf, or the leaf of
+//! another key sharing its path, sits.
+
+use crate::AptosError;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, vec::Vec};
+
+/// Hash of an empty subtree
+pub const SPARSE_MERKLE_PLACEHOLDER_HASH: [u8; 32] = *b"SPARSE_MERKLE_PLACEHOLDER_HASH__";
+
+/// SHA3-256 of `parts` under the seed of the Aptos type `type_name`
+pub fn aptos_hash(type_name: &str, parts: &[&[u8]]) -> [u8; 32] {
+    let seed = Sha3_256::new()
+        .chain_update(b"APTOS::")
+        .chain_update(type_name.as_bytes())
+        .finalize();
+    let mut hasher = Sha3_256::new().chain_update(seed);
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+/// Position of an encoded state key in the tree
+pub fn state_key_hash(state_key: &[u8]) -> [u8; 32] {
+    aptos_hash("StateKey", &[state_key])
+}
+
+/// Hash of a state value as committed in its leaf
+pub fn state_value_hash(value: &[u8]) -> [u8; 32] {
+    aptos_hash("StateValue", &[value])
+}
+
+fn internal_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    aptos_hash("SparseMerkleInternal", &[left, right])
+}
+
+/// A leaf of the tree: a state key hash and the hash of its value
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SparseMerkleLeaf {
+    pub key: [u8; 32],
+    pub value_hash: [u8; 32],
+}
+
+impl SparseMerkleLeaf {
+    pub fn hash(&self) -> [u8; 32] {
+        aptos_hash("SparseMerkleLeafNode", &[&self.key, &self.value_hash])
+    }
+}
+
+/// Proof that a key holds a value, or nothing, under a state root
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SparseMerkleProof {
+    /// Leaf found on the key's path: the key's own, another key's or none
+    pub leaf: Option<SparseMerkleLeaf>,
+    /// Siblings from the leaf's level up to the root
+    pub siblings: Vec<[u8; 32]>,
+}
+
+impl SparseMerkleProof {
+    /// Root the proof implies for `key`
+    pub fn root(&self, key: &[u8; 32]) -> [u8; 32] {
+        let leaf_hash = self.leaf.map_or(SPARSE_MERKLE_PLACEHOLDER_HASH, |leaf| leaf.hash());
+        let depth = self.siblings.len();
+        self.siblings.iter().enumerate().fold(leaf_hash, |hash, (level, sibling)| {
+            // The first sibling is at the deepest level, which branches on bit `depth - 1`
+            if bit(key, depth - 1 - level) {
+                internal_hash(sibling, &hash)
+            } else {
+                internal_hash(&hash, sibling)
+            }
+        })
+    }
+
+    /// Check that `key` holds a value hashing to `value_hash`, or nothing if `None`
+    pub fn verify(&self, root: &[u8; 32], key: &[u8; 32], value_hash: Option<&[u8; 32]>) -> Result<(), AptosError> {
+        if self.siblings.len() > 256 {
+            return Err(AptosError::ProofVerification(format!(
+                "{} siblings is more than the tree's 256 levels",
+                self.siblings.len()
+            )));
+        }
+        match (value_hash, &self.leaf) {
+            (Some(value_hash), Some(leaf)) => {
+                if leaf.key != *key {
+                    return Err(AptosError::ProofVerification(format!(
+                        "the proof's leaf is for key {}, not {}",
+                        hex::encode(leaf.key),
+                        hex::encode(key)
+                    )));
+                }
+                if leaf.value_hash != *value_hash {
+                    return Err(AptosError::ProofVerification("the value does not match the proof's leaf".into()));
+                }
+            }
+            (Some(_), None) => {
+                return Err(AptosError::ProofVerification("the proof shows the key is absent".into()));
+            }
+            (None, Some(leaf)) => {
+                // Another key's leaf proves absence only if it sits on this key's path
+                if leaf.key == *key {
+                    return Err(AptosError::ProofVerification("the proof shows the key is present".into()));
+                }
+                if common_prefix_bits(key, &leaf.key) < self.siblings.len() {
+                    return Err(AptosError::ProofVerification(
+                        "the proof's leaf is not on the key's path".into(),
+                    ));
+                }
+            }
+            (None, None) => {}
+        }
+
+        let computed = self.root(key);
+        if computed != *root {
+            return Err(AptosError::ProofVerification(format!(
+                "the proof implies root {}, not {}",
+                hex::encode(computed),
+                hex::encode(root)
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Bit `index` of a hash, most significant first
+fn bit(hash: &[u8; 32], index: usize) -> bool {
+    hash[index / 8] & (0x80 >> (index % 8)) != 0
+}
+
+fn common_prefix_bits(a: &[u8; 32], b: &[u8; 32]) -> usize {
+    (0..256).take_while(|i| bit(a, *i) == bit(b, *i)).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Root and proofs of a tree with three keys: two share their first bit
+    fn tree() -> ([u8; 32], [SparseMerkleLeaf; 3]) {
+        let leaf = |first: u8, value: &[u8]| {
+            let mut key = [0u8; 32];
+            key[0] = first;
+            SparseMerkleLeaf { key, value_hash: state_value_hash(value) }
+        };
+        // 0b00.., 0b01.. under the left child, 0b1... alone on the right
+        let leaves = [leaf(0x00, b"a"), leaf(0x40, b"b"), leaf(0x80, b"c")];
+        let left = internal_hash(&leaves[0].hash(), &leaves[1].hash());
+        (internal_hash(&left, &leaves[2].hash()), leaves)
+    }
+
+    #[test]
+    fn test_membership_and_non_membership() {
+        let (root, leaves) = tree();
+        let proof = SparseMerkleProof {
+            leaf: Some(leaves[1]),
+            siblings: vec![leaves[0].hash(), leaves[2].hash()],
+        };
+        proof.verify(&root, &leaves[1].key, Some(&state_value_hash(b"b"))).unwrap();
+        assert!(proof.verify(&root, &leaves[1].key, Some(&state_value_hash(b"x"))).is_err());
+        assert!(proof.verify(&root, &leaves[1].key, None).is_err());
+
+        // 0b11.. shares its path with the collapsed leaf 0b10..
+        let mut absent = [0u8; 32];
+        absent[0] = 0xc0;
+        let proof = SparseMerkleProof {
+            leaf: Some(leaves[2]),
+            siblings: vec![internal_hash(&leaves[0].hash(), &leaves[1].hash())],
+        };
+        proof.verify(&root, &absent, None).unwrap();
+        assert!(proof.verify(&root, &leaves[0].key, None).is_err());
+
+        let mut tampered = proof.clone();
+        tampered.siblings[0][0] ^= 1;
+        assert!(tampered.verify(&root, &absent, None).is_err());
+    }
+}
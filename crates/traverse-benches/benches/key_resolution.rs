@@ -0,0 +1,36 @@
+//! Storage key derivation: one keccak per mapping level
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use traverse_benches::fixtures::{balance_queries, erc20_layout, holder};
+use traverse_core::KeyResolver;
+use traverse_ethereum::EthereumKeyResolver;
+
+fn key_resolution(c: &mut Criterion) {
+    let layout = erc20_layout();
+    let mapping = format!("_balances[{}]", holder(0));
+    let nested = format!("_allowances[{}][{}]", holder(0), holder(1));
+
+    let mut group = c.benchmark_group("key_resolution");
+    group.bench_function("plain", |b| b.iter(|| EthereumKeyResolver.resolve(&layout, black_box("totalSupply"))));
+    group.bench_function("mapping", |b| b.iter(|| EthereumKeyResolver.resolve(&layout, black_box(&mapping))));
+    group.bench_function("nested_mapping", |b| b.iter(|| EthereumKeyResolver.resolve(&layout, black_box(&nested))));
+
+    let queries = balance_queries(100);
+    group.throughput(Throughput::Elements(queries.len() as u64));
+    group.bench_function("mapping_batch_100", |b| {
+        b.iter(|| {
+            queries
+                .iter()
+                .map(|query| EthereumKeyResolver.resolve(&layout, query))
+                .collect::<Result<Vec<_>, _>>()
+        })
+    });
+    // Includes the layout commitment, which every resolution recomputes
+    group.bench_function("layout_commitment", |b| {
+        b.iter_batched(erc20_layout, |layout| layout.commitment(), BatchSize::SmallInput)
+    });
+    group.finish();
+}
+
+criterion_group!(benches, key_resolution);
+criterion_main!(benches);
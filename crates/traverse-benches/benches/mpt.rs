@@ -0,0 +1,24 @@
+//! Merkle-Patricia proof verification at mainnet trie depths
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use traverse_benches::fixtures::{account_proof, storage_proof, ACCOUNT_PROOF_DEPTH, STORAGE_PROOF_DEPTH};
+use traverse_ethereum::mpt::{verify_account_proof, verify_storage_proof};
+
+fn mpt(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mpt");
+    for depth in [2, STORAGE_PROOF_DEPTH, 10] {
+        let fixture = storage_proof([depth as u8; 32], depth);
+        group.bench_with_input(BenchmarkId::new("storage_proof", depth), &fixture, |b, fixture| {
+            b.iter(|| verify_storage_proof(&fixture.root, black_box(&fixture.slot), &fixture.nodes))
+        });
+    }
+
+    let account = account_proof(ACCOUNT_PROOF_DEPTH);
+    group.bench_function("account_proof", |b| {
+        b.iter(|| verify_account_proof(&account.root, black_box(&account.address), &account.nodes))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, mpt);
+criterion_main!(benches);
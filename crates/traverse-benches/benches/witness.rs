@@ -0,0 +1,68 @@
+//! Controller-side witness creation and circuit-side witness parsing
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use traverse_benches::fixtures::{balance_batch, erc20_layout, storage_request, STORAGE_PROOF_DEPTH};
+use traverse_valence::circuit::{CircuitProcessor, FieldType, ZeroSemantics};
+use traverse_valence::controller;
+use valence_coprocessor::Witness;
+
+fn witness_bytes(witness: Witness) -> Vec<u8> {
+    match witness {
+        Witness::Data(data) => data,
+        _ => panic!("controller returned a state proof witness"),
+    }
+}
+
+fn creation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("witness_creation");
+    let request = storage_request(&erc20_layout(), "totalSupply", STORAGE_PROOF_DEPTH);
+    group.bench_function("single", |b| b.iter(|| controller::create_witness_from_request(black_box(&request))));
+
+    for size in [10u32, 100] {
+        let batch = balance_batch(size);
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::new("batch", size), &batch, |b, batch| {
+            b.iter(|| controller::create_witnesses_from_batch_request(batch))
+        });
+    }
+    group.finish();
+}
+
+fn parsing(c: &mut Criterion) {
+    let layout = erc20_layout();
+    let batch = balance_batch(100);
+    let witnesses: Vec<Vec<u8>> = controller::create_witnesses_from_batch_request(&batch)
+        .expect("benchmark batch creates witnesses")
+        .into_iter()
+        .map(witness_bytes)
+        .collect();
+    let processor = CircuitProcessor::new(
+        layout.commitment(),
+        vec![FieldType::Uint256; layout.storage.len()],
+        layout.storage.iter().map(|_| ZeroSemantics::ValidZero).collect(),
+    );
+
+    let mut group = c.benchmark_group("witness_parsing");
+    group.bench_function("single", |b| {
+        b.iter(|| CircuitProcessor::parse_witness_from_bytes(black_box(&witnesses[0])))
+    });
+    group.throughput(Throughput::Elements(witnesses.len() as u64));
+    group.bench_function("batch_100", |b| {
+        b.iter(|| {
+            witnesses
+                .iter()
+                .map(|data| CircuitProcessor::parse_witness_from_bytes(data))
+                .collect::<Result<Vec<_>, _>>()
+        })
+    });
+
+    let parsed: Vec<_> = witnesses
+        .iter()
+        .map(|data| CircuitProcessor::parse_witness_from_bytes(data).expect("benchmark witness parses"))
+        .collect();
+    group.bench_function("process_batch_100", |b| b.iter(|| processor.process_batch(black_box(&parsed))));
+    group.finish();
+}
+
+criterion_group!(benches, creation, parsing);
+criterion_main!(benches);
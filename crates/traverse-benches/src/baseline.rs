@@ -0,0 +1,244 @@
+//! Recorded baselines and regression checks
+//!
+//! Criterion writes each benchmark's estimates to
+//! `<target>/criterion/<group>/<bench>/new/estimates.json`, next to a
+//! `benchmark.json` naming it. [`read_estimates`] collects the mean of every
+//! benchmark there, and [`compare`] judges them against a [`Baselines`] file:
+//! a benchmark regresses when its mean grows by more than its threshold.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Threshold used when a baselines file does not set one
+pub const DEFAULT_THRESHOLD_PERCENT: f64 = 10.0;
+
+/// The recorded mean of one benchmark
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    /// Mean time per iteration in nanoseconds
+    pub mean_ns: f64,
+    /// Allowed slowdown in percent, overriding the file's threshold
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold_percent: Option<f64>,
+}
+
+/// Recorded means by benchmark ID, as stored in `baselines.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Baselines {
+    /// Allowed slowdown in percent for benchmarks without their own threshold
+    pub threshold_percent: f64,
+    /// Baselines by criterion ID, e.g. `key_resolution/mapping`
+    pub benchmarks: BTreeMap<String, Baseline>,
+}
+
+impl Default for Baselines {
+    fn default() -> Self {
+        Self { threshold_percent: DEFAULT_THRESHOLD_PERCENT, benchmarks: BTreeMap::new() }
+    }
+}
+
+impl Baselines {
+    /// Load a baselines file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&content).with_context(|| format!("Invalid baselines file {}", path.display()))
+    }
+
+    /// Write the baselines file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)? + "\n")
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Replace the recorded means with `current`, keeping per-benchmark thresholds
+    pub fn record(&mut self, current: &BTreeMap<String, f64>) {
+        for (id, mean_ns) in current {
+            let threshold_percent = self.benchmarks.get(id).and_then(|b| b.threshold_percent);
+            // Digits below a tenth of a nanosecond are noise
+            let mean_ns = (mean_ns * 10.0).round() / 10.0;
+            self.benchmarks.insert(id.clone(), Baseline { mean_ns, threshold_percent });
+        }
+    }
+}
+
+/// How a benchmark's current mean compares with its baseline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// Faster by more than the threshold
+    Improved,
+    /// Within the threshold either way
+    Unchanged,
+    /// Slower by more than the threshold
+    Regressed,
+    /// Measured but not recorded
+    New,
+    /// Recorded but not measured in this run
+    Missing,
+}
+
+impl std::fmt::Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Verdict::Improved => "improved",
+            Verdict::Unchanged => "unchanged",
+            Verdict::Regressed => "REGRESSED",
+            Verdict::New => "new",
+            Verdict::Missing => "missing",
+        };
+        f.pad(name)
+    }
+}
+
+/// One benchmark's comparison
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub id: String,
+    pub baseline_ns: Option<f64>,
+    pub current_ns: Option<f64>,
+    pub threshold_percent: f64,
+    pub verdict: Verdict,
+}
+
+impl Comparison {
+    /// Change of the mean in percent, positive when slower
+    pub fn change_percent(&self) -> Option<f64> {
+        match (self.baseline_ns, self.current_ns) {
+            (Some(baseline), Some(current)) if baseline > 0.0 => Some((current - baseline) / baseline * 100.0),
+            _ => None,
+        }
+    }
+}
+
+/// Compare current means with the baselines, by benchmark ID
+///
+/// `threshold_override` replaces every threshold, for one-off stricter or
+/// looser runs.
+pub fn compare(baselines: &Baselines, current: &BTreeMap<String, f64>, threshold_override: Option<f64>) -> Vec<Comparison> {
+    let mut ids: Vec<&String> = baselines.benchmarks.keys().chain(current.keys()).collect();
+    ids.sort();
+    ids.dedup();
+
+    ids.into_iter()
+        .map(|id| {
+            let baseline = baselines.benchmarks.get(id);
+            let threshold_percent = threshold_override
+                .or_else(|| baseline.and_then(|b| b.threshold_percent))
+                .unwrap_or(baselines.threshold_percent);
+            let mut comparison = Comparison {
+                id: id.clone(),
+                baseline_ns: baseline.map(|b| b.mean_ns),
+                current_ns: current.get(id).copied(),
+                threshold_percent,
+                verdict: Verdict::Unchanged,
+            };
+            comparison.verdict = match (comparison.baseline_ns, comparison.current_ns) {
+                (None, _) => Verdict::New,
+                (_, None) => Verdict::Missing,
+                _ => match comparison.change_percent() {
+                    Some(change) if change > threshold_percent => Verdict::Regressed,
+                    Some(change) if change < -threshold_percent => Verdict::Improved,
+                    _ => Verdict::Unchanged,
+                },
+            };
+            comparison
+        })
+        .collect()
+}
+
+/// Mean time in nanoseconds of every benchmark criterion has results for
+pub fn read_estimates(criterion_dir: &Path) -> Result<BTreeMap<String, f64>> {
+    let mut estimates = BTreeMap::new();
+    collect_estimates(criterion_dir, &mut estimates)
+        .with_context(|| format!("Failed to read criterion results in {}", criterion_dir.display()))?;
+    Ok(estimates)
+}
+
+fn collect_estimates(dir: &Path, estimates: &mut BTreeMap<String, f64>) -> Result<()> {
+    let new = dir.join("new");
+    if new.join("estimates.json").is_file() && new.join("benchmark.json").is_file() {
+        let benchmark: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(new.join("benchmark.json"))?)?;
+        let estimate: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(new.join("estimates.json"))?)?;
+        if let (Some(id), Some(mean)) = (benchmark["full_id"].as_str(), estimate["mean"]["point_estimate"].as_f64()) {
+            estimates.insert(id.to_string(), mean);
+        }
+        return Ok(());
+    }
+
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        // `report` holds HTML; `base` and `change` mirror `new` for the previous run
+        if path.is_dir() && !matches!(path.file_name().and_then(|n| n.to_str()), Some("report")) {
+            collect_estimates(&path, estimates)?;
+        }
+    }
+    Ok(())
+}
+
+/// Human-readable duration for a mean in nanoseconds
+pub fn format_ns(ns: f64) -> String {
+    if ns >= 1_000_000.0 {
+        format!("{:.2} ms", ns / 1_000_000.0)
+    } else if ns >= 1_000.0 {
+        format!("{:.2} µs", ns / 1_000.0)
+    } else {
+        format!("{:.1} ns", ns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn baselines() -> Baselines {
+        let mut baselines = Baselines::default();
+        baselines.record(&BTreeMap::from([
+            ("mpt/storage".to_string(), 1000.0),
+            ("witness/parse".to_string(), 200.0),
+            ("key_resolution/plain".to_string(), 50.0),
+        ]));
+        baselines.benchmarks.get_mut("witness/parse").unwrap().threshold_percent = Some(50.0);
+        baselines
+    }
+
+    #[test]
+    fn test_compare_applies_thresholds() {
+        let current = BTreeMap::from([
+            ("mpt/storage".to_string(), 1200.0),
+            ("witness/parse".to_string(), 280.0),
+            ("key_resolution/mapping".to_string(), 300.0),
+        ]);
+        let verdicts: Vec<(String, Verdict)> =
+            compare(&baselines(), &current, None).into_iter().map(|c| (c.id, c.verdict)).collect();
+        assert_eq!(
+            verdicts,
+            [
+                ("key_resolution/mapping".to_string(), Verdict::New),
+                ("key_resolution/plain".to_string(), Verdict::Missing),
+                ("mpt/storage".to_string(), Verdict::Regressed),
+                ("witness/parse".to_string(), Verdict::Unchanged),
+            ]
+        );
+
+        // An override applies to every benchmark, including ones with their own threshold
+        let strict = compare(&baselines(), &current, Some(5.0));
+        assert_eq!(strict.iter().find(|c| c.id == "witness/parse").unwrap().verdict, Verdict::Regressed);
+        let faster = BTreeMap::from([("mpt/storage".to_string(), 500.0)]);
+        assert_eq!(compare(&baselines(), &faster, None)[1].verdict, Verdict::Improved);
+    }
+
+    #[test]
+    fn test_read_criterion_estimates() {
+        let dir = std::env::temp_dir().join(format!("traverse-benches-{}", std::process::id()));
+        let new = dir.join("mpt/storage_proof/6/new");
+        std::fs::create_dir_all(&new).unwrap();
+        std::fs::create_dir_all(dir.join("report")).unwrap();
+        std::fs::write(new.join("benchmark.json"), r#"{"full_id": "mpt/storage_proof/6"}"#).unwrap();
+        std::fs::write(new.join("estimates.json"), r#"{"mean": {"point_estimate": 1234.5}}"#).unwrap();
+
+        let estimates = read_estimates(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(estimates, BTreeMap::from([("mpt/storage_proof/6".to_string(), 1234.5)]));
+    }
+}
@@ -0,0 +1,73 @@
+//! Compare criterion results with the recorded baselines
+//!
+//! Exits with status 2 when any benchmark regressed past its threshold.
+
+use anyhow::Result;
+use clap::Parser;
+use std::path::PathBuf;
+use traverse_benches::baseline::{compare, format_ns, read_estimates, Baselines, Verdict};
+
+#[derive(Parser)]
+#[command(name = "bench-compare", about = "Compare criterion results with recorded baselines")]
+struct Args {
+    /// Criterion output directory (defaults to `criterion` in the target directory)
+    #[arg(long)]
+    criterion_dir: Option<PathBuf>,
+    /// Baselines file
+    #[arg(long, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/baselines.json"))]
+    baselines: PathBuf,
+    /// Allowed slowdown in percent for every benchmark, overriding the file
+    #[arg(long)]
+    threshold: Option<f64>,
+    /// Record the current results as the new baselines instead of comparing
+    #[arg(long)]
+    record: bool,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let criterion_dir = args.criterion_dir.unwrap_or_else(|| {
+        std::env::var_os("CARGO_TARGET_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../../target")))
+            .join("criterion")
+    });
+
+    let current = read_estimates(&criterion_dir)?;
+    if current.is_empty() {
+        return Err(anyhow::anyhow!(
+            "No criterion results in {}. Run `cargo bench -p traverse-benches` first",
+            criterion_dir.display()
+        ));
+    }
+
+    let mut baselines = if args.baselines.exists() { Baselines::load(&args.baselines)? } else { Baselines::default() };
+    if args.record {
+        baselines.record(&current);
+        baselines.save(&args.baselines)?;
+        println!("Recorded {} baselines in {}", current.len(), args.baselines.display());
+        return Ok(());
+    }
+
+    let comparisons = compare(&baselines, &current, args.threshold);
+    let width = comparisons.iter().map(|c| c.id.len()).max().unwrap_or(0);
+    println!("{:width$}  {:>12}  {:>12}  {:>8}  verdict", "benchmark", "baseline", "current", "change");
+    for comparison in &comparisons {
+        println!(
+            "{:width$}  {:>12}  {:>12}  {:>8}  {} (±{}%)",
+            comparison.id,
+            comparison.baseline_ns.map(format_ns).unwrap_or_else(|| "-".to_string()),
+            comparison.current_ns.map(format_ns).unwrap_or_else(|| "-".to_string()),
+            comparison.change_percent().map(|c| format!("{:+.1}%", c)).unwrap_or_else(|| "-".to_string()),
+            comparison.verdict,
+            comparison.threshold_percent,
+        );
+    }
+
+    let regressed = comparisons.iter().filter(|c| c.verdict == Verdict::Regressed).count();
+    if regressed > 0 {
+        eprintln!("{} of {} benchmarks regressed", regressed, comparisons.len());
+        std::process::exit(2);
+    }
+    Ok(())
+}
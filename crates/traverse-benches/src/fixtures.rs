@@ -0,0 +1,188 @@
+//! Benchmark inputs
+//!
+//! Layouts, storage verification requests and MPT proofs shared by the
+//! suites. Proofs are synthetic but shaped like mainnet ones: every level
+//! above the leaf is a full 17-item branch of 32-byte child hashes, so node
+//! sizes and hashing work match a real trie of the same depth.
+
+use rlp::RlpStream;
+use traverse_core::hash::{keccak256, nibbles};
+use traverse_core::{Key, KeyResolver, LayoutInfo};
+use traverse_ethereum::EthereumKeyResolver;
+use traverse_valence::{BatchStorageVerificationRequest, CoprocessorStorageQuery, StorageProof, StorageVerificationRequest};
+
+/// An ERC20 layout with a plain slot, a mapping and a nested mapping
+pub const ERC20_LAYOUT: &str = r#"{
+    "contract_name": "ERC20",
+    "storage": [
+        { "label": "totalSupply", "slot": "2", "offset": 0, "type_name": "t_uint256", "zero_semantics": "ExplicitlyZero" },
+        { "label": "_balances", "slot": "0", "offset": 0, "type_name": "t_mapping_address_uint256", "zero_semantics": "ValidZero" },
+        { "label": "_allowances", "slot": "1", "offset": 0, "type_name": "t_mapping_address_mapping_address_uint256", "zero_semantics": "ValidZero" }
+    ],
+    "types": [
+        { "label": "t_uint256", "number_of_bytes": "32", "encoding": "inplace", "base": null, "key": null, "value": null },
+        { "label": "t_address", "number_of_bytes": "20", "encoding": "inplace", "base": null, "key": null, "value": null },
+        { "label": "t_mapping_address_uint256", "number_of_bytes": "32", "encoding": "mapping", "base": null, "key": "t_address", "value": "t_uint256" },
+        { "label": "t_mapping_address_mapping_address_uint256", "number_of_bytes": "32", "encoding": "mapping", "base": null, "key": "t_address", "value": "t_mapping_address_uint256" }
+    ]
+}"#;
+
+/// Depth of a mainnet storage proof for a busy contract
+pub const STORAGE_PROOF_DEPTH: usize = 6;
+
+/// Depth of a mainnet account proof
+pub const ACCOUNT_PROOF_DEPTH: usize = 8;
+
+/// The ERC20 layout, parsed
+pub fn erc20_layout() -> LayoutInfo {
+    serde_json::from_str(ERC20_LAYOUT).expect("benchmark layout parses")
+}
+
+/// A distinct holder address for index `i`
+pub fn holder(i: u32) -> String {
+    format!("0x{:0>40}", format!("{:x}", 0x742d_35ccu64 * (i as u64 + 1)))
+}
+
+/// `_balances[holder]` queries for `count` holders
+pub fn balance_queries(count: u32) -> Vec<String> {
+    (0..count).map(|i| format!("_balances[{}]", holder(i))).collect()
+}
+
+/// A storage verification request for `query`, with a proof of `depth` nodes
+pub fn storage_request(layout: &LayoutInfo, query: &str, depth: usize) -> StorageVerificationRequest {
+    let path = EthereumKeyResolver.resolve(layout, query).expect("benchmark query resolves");
+    let slot: [u8; 32] = match &path.key {
+        Key::Fixed(bytes) => *bytes,
+        Key::Variable(bytes) => bytes.as_slice().try_into().expect("32-byte key"),
+    };
+    let proof = storage_proof(slot, depth);
+
+    StorageVerificationRequest {
+        storage_query: CoprocessorStorageQuery {
+            query: query.to_string(),
+            storage_key: hex::encode(slot),
+            layout_commitment: hex::encode(path.layout_commitment),
+            field_size: path.field_size,
+            offset: path.offset,
+        },
+        storage_proof: StorageProof {
+            key: format!("0x{}", hex::encode(slot)),
+            value: format!("0x{}", hex::encode(proof.value)),
+            proof: proof.nodes.iter().map(|node| format!("0x{}", hex::encode(node))).collect(),
+        },
+        contract_address: None,
+        block_number: Some(20_000_000),
+    }
+}
+
+/// A batch of balance requests for `count` holders
+pub fn balance_batch(count: u32) -> BatchStorageVerificationRequest {
+    let layout = erc20_layout();
+    BatchStorageVerificationRequest {
+        storage_batch: balance_queries(count)
+            .iter()
+            .map(|query| storage_request(&layout, query, STORAGE_PROOF_DEPTH))
+            .collect(),
+        contract_address: Some(holder(u32::MAX)),
+        block_number: Some(20_000_000),
+    }
+}
+
+/// A storage proof together with what it proves
+pub struct StorageFixture {
+    pub root: [u8; 32],
+    pub slot: [u8; 32],
+    pub value: [u8; 32],
+    pub nodes: Vec<Vec<u8>>,
+}
+
+/// A proof of a nonzero value at `slot` through `depth` branch nodes
+pub fn storage_proof(slot: [u8; 32], depth: usize) -> StorageFixture {
+    let mut value = [0u8; 32];
+    value[24..].copy_from_slice(&1_000_000_000_000u64.to_be_bytes());
+    let trimmed: Vec<u8> = value.iter().copied().skip_while(|b| *b == 0).collect();
+    let (root, nodes) = proof_path(&keccak256(&slot), &rlp::encode(&trimmed), depth);
+    StorageFixture { root, slot, value, nodes }
+}
+
+/// An account proof together with what it proves
+pub struct AccountFixture {
+    pub root: [u8; 32],
+    pub address: [u8; 20],
+    pub nodes: Vec<Vec<u8>>,
+}
+
+/// A proof of a contract account through `depth` branch nodes
+pub fn account_proof(depth: usize) -> AccountFixture {
+    let address = [0xa0u8; 20];
+    let mut account = RlpStream::new_list(4);
+    account.append(&1u64);
+    account.append(&vec![0x0du8, 0xe0, 0xb6, 0xb3, 0xa7, 0x64, 0x00, 0x00]);
+    account.append(&keccak256(b"storage").to_vec());
+    account.append(&keccak256(b"code").to_vec());
+    let (root, nodes) = proof_path(&keccak256(&address), &account.out(), depth);
+    AccountFixture { root, address, nodes }
+}
+
+/// Root and nodes of a trie path to a leaf holding `leaf_value`
+fn proof_path(trie_key: &[u8; 32], leaf_value: &[u8], depth: usize) -> ([u8; 32], Vec<Vec<u8>>) {
+    let path = nibbles(trie_key);
+    let mut leaf = RlpStream::new_list(2);
+    leaf.append(&compact_leaf_path(&path[depth..]));
+    leaf.append(&leaf_value.to_vec());
+    let mut nodes = vec![leaf.out().to_vec()];
+
+    for level in (0..depth).rev() {
+        let child = keccak256(&nodes[0]);
+        let mut branch = RlpStream::new_list(17);
+        for nibble in 0..16u8 {
+            if nibble == path[level] {
+                branch.append(&child.to_vec());
+            } else {
+                branch.append(&keccak256(&[level as u8, nibble]).to_vec());
+            }
+        }
+        branch.append_empty_data();
+        nodes.insert(0, branch.out().to_vec());
+    }
+
+    (keccak256(&nodes[0]), nodes)
+}
+
+/// Hex-prefix encoding of a leaf's remaining path
+fn compact_leaf_path(path: &[u8]) -> Vec<u8> {
+    let (mut out, rest) = if path.len() % 2 == 1 {
+        (vec![0x30 | path[0]], &path[1..])
+    } else {
+        (vec![0x20], path)
+    };
+    out.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use traverse_ethereum::mpt::{verify_account_proof, verify_storage_proof};
+
+    #[test]
+    fn test_fixture_proofs_verify() {
+        for depth in [0, 3, STORAGE_PROOF_DEPTH] {
+            let fixture = storage_proof([7u8; 32], depth);
+            assert_eq!(fixture.nodes.len(), depth + 1);
+            assert_eq!(verify_storage_proof(&fixture.root, &fixture.slot, &fixture.nodes).unwrap(), fixture.value);
+        }
+
+        let account = account_proof(ACCOUNT_PROOF_DEPTH);
+        let state = verify_account_proof(&account.root, &account.address, &account.nodes).unwrap().unwrap();
+        assert_eq!(state.nonce, 1);
+    }
+
+    #[test]
+    fn test_batch_requests_resolve() {
+        let batch = balance_batch(4);
+        assert_eq!(batch.storage_batch.len(), 4);
+        let keys: std::collections::HashSet<_> = batch.storage_batch.iter().map(|r| &r.storage_query.storage_key).collect();
+        assert_eq!(keys.len(), 4);
+    }
+}
@@ -0,0 +1,26 @@
+//! Benchmarks for the proof pipeline hot paths
+//!
+//! The criterion suites under `benches/` cover:
+//!
+//! - `key_resolution`: keccak-heavy storage key derivation for plain slots,
+//!   mappings and nested mappings
+//! - `witness`: batch witness creation and circuit-side witness parsing
+//! - `mpt`: account and storage proof verification at realistic trie depths
+//!
+//! Inputs are built by [`fixtures`] so every suite measures the same data.
+//! Recorded means live in `baselines.json`; after `cargo bench -p
+//! traverse-benches`, `bench-compare` compares criterion's estimates with
+//! them and exits with status 2 when any benchmark is slower than its
+//! threshold allows:
+//!
+//! ```text
+//! cargo bench -p traverse-benches
+//! cargo run -p traverse-benches --bin bench-compare
+//! cargo run -p traverse-benches --bin bench-compare -- --record   # accept new numbers
+//! ```
+//!
+//! Baselines are machine-specific: record them on the machine that runs the
+//! comparison before using them to judge a change.
+
+pub mod baseline;
+pub mod fixtures;
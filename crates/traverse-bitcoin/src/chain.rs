@@ -0,0 +1,231 @@
+//! Header chain validation
+//!
+//! Each header must name the one before it, meet its own target, and use
+//! the target consensus requires at its height: the previous block's, a
+//! recomputed one every [`RETARGET_INTERVAL`] blocks, or on the test
+//! networks the limit after 20 minutes without a block. A retarget is
+//! recomputed exactly when the chain holds the first header of the period
+//! before it; otherwise it is only held to the factor of four an adjustment
+//! may move the target by. Nothing checks that the first header is on the
+//! best chain; the work built on it is what a verifier weighs.
+
+use crate::header::{bits_to_target, display_hex, target_to_bits, BlockHeader};
+use crate::network::{Network, RETARGET_INTERVAL, TARGET_SPACING, TARGET_TIMESPAN};
+use crate::BitcoinError;
+use ruint::aliases::U256;
+
+#[cfg(not(feature = "std"))]
+use alloc::format;
+
+/// What a valid header chain proves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainSummary {
+    /// Hash of the last header, in internal byte order
+    pub tip_hash: [u8; 32],
+    pub tip_height: u64,
+    /// Work of every header in the chain, the first included
+    pub chain_work: U256,
+}
+
+/// Check that `headers`, the first at `start_height`, form a valid chain
+pub fn verify_header_chain(
+    headers: &[BlockHeader],
+    start_height: u64,
+    network: Network,
+) -> Result<ChainSummary, BitcoinError> {
+    let last = headers
+        .last()
+        .ok_or_else(|| BitcoinError::InvalidHeaderChain("The chain has no headers".into()))?;
+    let pow_limit = bits_to_target(network.pow_limit_bits())?;
+    let mut chain_work = U256::ZERO;
+
+    for (index, header) in headers.iter().enumerate() {
+        let height = start_height + index as u64;
+        if header.target()? > pow_limit {
+            return Err(BitcoinError::InvalidHeaderChain(format!(
+                "Header {} has a target above the network's limit",
+                height
+            )));
+        }
+        header.check_proof_of_work()?;
+        if index > 0 {
+            let prev = &headers[index - 1];
+            if header.prev_blockhash != prev.block_hash() {
+                return Err(BitcoinError::InvalidHeaderChain(format!(
+                    "Header {} does not follow block {}",
+                    height,
+                    display_hex(&prev.block_hash())
+                )));
+            }
+            check_bits(headers, index, start_height, network, pow_limit)?;
+        }
+        chain_work += header.work()?;
+    }
+
+    Ok(ChainSummary {
+        tip_hash: last.block_hash(),
+        tip_height: start_height + headers.len() as u64 - 1,
+        chain_work,
+    })
+}
+
+/// The target a period's blocks use, given the time the period before took
+pub fn next_target(prev_target: U256, timespan: u32, pow_limit: U256) -> U256 {
+    let timespan = timespan.clamp(TARGET_TIMESPAN / 4, TARGET_TIMESPAN * 4);
+    let target = prev_target.saturating_mul(U256::from(timespan)) / U256::from(TARGET_TIMESPAN);
+    target.min(pow_limit)
+}
+
+/// Check the bits of `headers[index]` against those consensus requires
+fn check_bits(
+    headers: &[BlockHeader],
+    index: usize,
+    start_height: u64,
+    network: Network,
+    pow_limit: U256,
+) -> Result<(), BitcoinError> {
+    let header = &headers[index];
+    let prev = &headers[index - 1];
+    let height = start_height + index as u64;
+    let mismatch = |expected: u32| {
+        BitcoinError::InvalidHeaderChain(format!(
+            "Header {} has bits {:#010x}, not {:#010x}",
+            height, header.bits, expected
+        ))
+    };
+
+    if !height.is_multiple_of(RETARGET_INTERVAL) {
+        if !network.allows_min_difficulty() {
+            return if header.bits == prev.bits { Ok(()) } else { Err(mismatch(prev.bits)) };
+        }
+        let limit_bits = network.pow_limit_bits();
+        if header.time > prev.time.saturating_add(2 * TARGET_SPACING) {
+            return if header.bits == limit_bits { Ok(()) } else { Err(mismatch(limit_bits)) };
+        }
+        // Otherwise the bits of the last block that did not use the limit
+        let regular = headers[..index]
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(i, h)| h.bits != limit_bits || (start_height + *i as u64).is_multiple_of(RETARGET_INTERVAL));
+        return match regular {
+            Some((_, regular)) if header.bits != regular.bits => Err(mismatch(regular.bits)),
+            // When the chain starts among limit blocks, the bits cannot be told
+            _ => Ok(()),
+        };
+    }
+
+    if !network.retargets() {
+        return if header.bits == prev.bits { Ok(()) } else { Err(mismatch(prev.bits)) };
+    }
+    let target = header.target()?;
+    let prev_target = prev.target()?;
+    match index.checked_sub(RETARGET_INTERVAL as usize) {
+        Some(first) => {
+            let first = &headers[first];
+            let timespan = prev.time.saturating_sub(first.time);
+            // BIP94 adjusts testnet4 from the period's first block, which cannot use the limit
+            let base = if network == Network::Testnet4 { first.target()? } else { prev_target };
+            let expected = target_to_bits(next_target(base, timespan, pow_limit));
+            if header.bits != expected {
+                return Err(mismatch(expected));
+            }
+        }
+        // Only the bounds of an adjustment, as the test networks' previous block may have used the limit
+        None if !network.allows_min_difficulty() => {
+            let lowest = bits_to_target(target_to_bits(next_target(prev_target, 0, pow_limit)))?;
+            let highest = next_target(prev_target, u32::MAX, pow_limit);
+            if target < lowest || target > highest {
+                return Err(BitcoinError::InvalidHeaderChain(format!(
+                    "Header {} moves the target further than an adjustment can",
+                    height
+                )));
+            }
+        }
+        None => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::header::tests::MAINNET_HEADERS;
+
+    /// A header with the given bits, mined on `prev`
+    pub(crate) fn mine(prev: &BlockHeader, merkle_root: [u8; 32], time: u32, bits: u32) -> BlockHeader {
+        let mut header =
+            BlockHeader { version: 0x2000_0000, prev_blockhash: prev.block_hash(), merkle_root, time, bits, nonce: 0 };
+        while header.check_proof_of_work().is_err() {
+            header.nonce += 1;
+        }
+        header
+    }
+
+    /// A regtest chain of `length` headers ten minutes apart
+    pub(crate) fn regtest_chain(length: usize, bits: u32) -> Vec<BlockHeader> {
+        let genesis = BlockHeader {
+            version: 1,
+            prev_blockhash: [0; 32],
+            merkle_root: [7; 32],
+            time: 1_700_000_000,
+            bits,
+            nonce: 0,
+        };
+        let mut headers = vec![mine(&genesis, [7; 32], genesis.time, bits)];
+        while headers.len() < length {
+            let prev = headers.last().unwrap();
+            headers.push(mine(prev, [headers.len() as u8; 32], prev.time + TARGET_SPACING, bits));
+        }
+        headers
+    }
+
+    #[test]
+    fn test_mainnet_header_chain() {
+        let headers: Vec<_> = MAINNET_HEADERS.iter().map(|h| BlockHeader::from_hex(h).unwrap()).collect();
+        let summary = verify_header_chain(&headers, 0, Network::Bitcoin).unwrap();
+        assert_eq!(summary.tip_height, 2);
+        assert_eq!(summary.tip_hash, headers[2].block_hash());
+        assert_eq!(summary.chain_work, U256::from(3 * 0x1_0001_0001u64));
+
+        assert!(verify_header_chain(&[headers[0], headers[2]], 0, Network::Bitcoin).is_err());
+        assert!(verify_header_chain(&[], 0, Network::Bitcoin).is_err());
+        // Block 1 came days after genesis, so the test networks want their limit there
+        verify_header_chain(&headers, 0, Network::Testnet).unwrap();
+        assert!(verify_header_chain(&headers, 0, Network::Regtest).is_err());
+    }
+
+    #[test]
+    fn test_min_difficulty_blocks() {
+        let regular = 0x2000ffff;
+        let mut headers = regtest_chain(3, regular);
+        verify_header_chain(&headers, 1, Network::Regtest).unwrap();
+
+        // The limit is only allowed after 20 minutes without a block
+        let prev = headers[2];
+        let early = mine(&prev, [3; 32], prev.time + 2 * TARGET_SPACING, 0x207fffff);
+        assert!(verify_header_chain(&[&headers[..], &[early]].concat(), 1, Network::Regtest).is_err());
+        let late = mine(&prev, [3; 32], prev.time + 2 * TARGET_SPACING + 1, 0x207fffff);
+        headers.push(late);
+        verify_header_chain(&headers, 1, Network::Regtest).unwrap();
+
+        // The block after goes back to the bits before the limit
+        let back = mine(&late, [4; 32], late.time + 60, regular);
+        let stuck = mine(&late, [4; 32], late.time + 60, 0x207fffff);
+        verify_header_chain(&[&headers[..], &[back]].concat(), 1, Network::Regtest).unwrap();
+        assert!(verify_header_chain(&[&headers[..], &[stuck]].concat(), 1, Network::Regtest).is_err());
+        // Without a regular block in the chain, it cannot be told
+        verify_header_chain(&[late, stuck], 4, Network::Regtest).unwrap();
+    }
+
+    #[test]
+    fn test_retarget_bounds() {
+        let limit = bits_to_target(0x1d00ffff).unwrap();
+        let target = bits_to_target(0x1b0404cb).unwrap();
+        assert_eq!(next_target(target, TARGET_TIMESPAN, limit), target);
+        assert_eq!(next_target(target, TARGET_TIMESPAN / 2, limit), target / U256::from(2u8));
+        assert_eq!(next_target(target, 1, limit), target / U256::from(4u8));
+        assert_eq!(next_target(target, u32::MAX, limit), target * U256::from(4u8));
+        assert_eq!(next_target(limit, TARGET_TIMESPAN * 2, limit), limit);
+    }
+}
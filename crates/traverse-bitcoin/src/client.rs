@@ -0,0 +1,116 @@
+//! Inclusion proofs from Bitcoin Core over JSON-RPC
+//!
+//! `getrawtransaction` gives the transaction and its block, `gettxoutproof`
+//! the branch as a merkle block, and `getblockhash` with `getblockheader`
+//! the headers built on it. The node must index transactions (`-txindex`)
+//! unless the block is given.
+
+use crate::header::{display_hex, parse_display_hash, BlockHeader};
+use crate::merkle::MerkleProof;
+use crate::network::Network;
+use crate::proof::{str_field, u64_field, BitcoinTxProof};
+use crate::BitcoinError;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Chain-specific configuration for proof fetching
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BitcoinChainConfig {
+    /// Credentials for the node's RPC, when it uses `rpcuser` and `rpcpassword`
+    pub rpc_user: Option<String>,
+    pub rpc_password: Option<String>,
+    /// Headers to include, the transaction's block first, when the chain has them
+    pub max_headers: u32,
+}
+
+impl Default for BitcoinChainConfig {
+    fn default() -> Self {
+        Self { rpc_user: None, rpc_password: None, max_headers: 6 }
+    }
+}
+
+/// Inclusion proof fetcher for a Bitcoin Core node
+#[derive(Debug, Clone)]
+pub struct BitcoinProofFetcher {
+    pub rpc_url: String,
+    pub config: BitcoinChainConfig,
+}
+
+impl BitcoinProofFetcher {
+    pub fn new(rpc_url: String, config: BitcoinChainConfig) -> Self {
+        Self { rpc_url, config }
+    }
+
+    pub fn with_defaults(rpc_url: String) -> Self {
+        Self::new(rpc_url, BitcoinChainConfig::default())
+    }
+
+    /// Fetch the proof that `txid` (in internal byte order) was mined, with the headers built on its block
+    pub async fn fetch_proof(
+        &self,
+        txid: &[u8; 32],
+        block_hash: Option<[u8; 32]>,
+    ) -> Result<BitcoinTxProof, BitcoinError> {
+        let network = self.network().await?;
+        let mut params = vec![json!(display_hex(txid)), json!(true)];
+        if let Some(block_hash) = &block_hash {
+            params.push(json!(display_hex(block_hash)));
+        }
+        let transaction = self.call("getrawtransaction", json!(params)).await?;
+        let block_hash = match transaction.get("blockhash").and_then(Value::as_str) {
+            Some(hash) => parse_display_hash(hash)?,
+            None => {
+                return Err(BitcoinError::Rpc(format!("Transaction {} is not in a block yet", display_hex(txid))))
+            }
+        };
+        let raw = hex::decode(str_field(&transaction, "hex")?).map_err(|e| BitcoinError::Rpc(e.to_string()))?;
+
+        let block = self.call("getblockheader", json!([display_hex(&block_hash), true])).await?;
+        if block.get("confirmations").and_then(Value::as_i64).unwrap_or(-1) < 1 {
+            return Err(BitcoinError::Rpc(format!("Block {} is not on the best chain", display_hex(&block_hash))));
+        }
+        let block_height = u64_field(&block, "height")?;
+
+        let merkle_block = self
+            .call("gettxoutproof", json!([[display_hex(txid)], display_hex(&block_hash)]))
+            .await?;
+        let merkle_block =
+            hex::decode(merkle_block.as_str().unwrap_or_default()).map_err(|e| BitcoinError::Rpc(e.to_string()))?;
+        let (header, merkle) = MerkleProof::from_merkle_block(&merkle_block, txid)?;
+        if header.block_hash() != block_hash {
+            return Err(BitcoinError::Rpc("gettxoutproof answered for another block".into()));
+        }
+
+        let tip = self.call("getblockcount", json!([])).await?.as_u64().unwrap_or_default();
+        let last = tip.min(block_height + self.config.max_headers.max(1) as u64 - 1);
+        let mut headers = vec![header];
+        for height in block_height + 1..=last {
+            let hash = self.call("getblockhash", json!([height])).await?;
+            let header = self.call("getblockheader", json!([hash, false])).await?;
+            headers.push(BlockHeader::from_hex(header.as_str().unwrap_or_default())?);
+        }
+
+        let proof = BitcoinTxProof { network, transaction: raw, block_height, merkle, headers };
+        proof.verify()?;
+        Ok(proof)
+    }
+
+    /// Network the node follows
+    pub async fn network(&self) -> Result<Network, BitcoinError> {
+        let info = self.call("getblockchaininfo", json!([])).await?;
+        Network::from_chain_name(str_field(&info, "chain")?)
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value, BitcoinError> {
+        let request = json!({ "jsonrpc": "1.0", "id": "traverse", "method": method, "params": params });
+        let mut builder = reqwest::Client::new().post(&self.rpc_url).json(&request);
+        if let Some(user) = &self.config.rpc_user {
+            builder = builder.basic_auth(user, self.config.rpc_password.as_ref());
+        }
+        let response: Value = builder.send().await?.json().await?;
+        if let Some(error) = response.get("error").filter(|error| !error.is_null()) {
+            return Err(BitcoinError::Rpc(format!("{}: {}", method, error)));
+        }
+        Ok(response["result"].clone())
+    }
+}
@@ -0,0 +1,73 @@
+//! Reading Bitcoin's consensus encoding
+
+use crate::BitcoinError;
+
+#[cfg(not(feature = "std"))]
+use alloc::format;
+
+/// A cursor over serialized data
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    pub(crate) fn position(&self) -> usize {
+        self.position
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.position == self.bytes.len()
+    }
+
+    pub(crate) fn take(&mut self, length: usize) -> Result<&'a [u8], BitcoinError> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| BitcoinError::InvalidTransaction(format!("Data ends before {} more bytes", length)))?;
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+
+    pub(crate) fn array<const N: usize>(&mut self) -> Result<[u8; N], BitcoinError> {
+        Ok(self.take(N)?.try_into().expect("N bytes"))
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, BitcoinError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, BitcoinError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, BitcoinError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    /// A CompactSize length, which must use its shortest form
+    pub(crate) fn compact_size(&mut self) -> Result<u64, BitcoinError> {
+        let (value, minimum) = match self.u8()? {
+            0xfd => (u16::from_le_bytes(self.array()?) as u64, 0xfd),
+            0xfe => (u32::from_le_bytes(self.array()?) as u64, 0x1_0000),
+            0xff => (self.u64()?, 0x1_0000_0000),
+            small => return Ok(small as u64),
+        };
+        if value < minimum {
+            return Err(BitcoinError::InvalidTransaction(format!("Non-canonical length {}", value)));
+        }
+        Ok(value)
+    }
+
+    /// A CompactSize length followed by that many bytes
+    pub(crate) fn var_bytes(&mut self) -> Result<&'a [u8], BitcoinError> {
+        let length = self.compact_size()?;
+        self.take(usize::try_from(length).unwrap_or(usize::MAX))
+    }
+}
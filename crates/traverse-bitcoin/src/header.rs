@@ -0,0 +1,215 @@
+//! Block headers and proof of work
+//!
+//! A header is 80 bytes: version, the previous block's hash, the Merkle
+//! root of the block's transactions, time, the target in compact form
+//! (`bits`) and a nonce, integers little-endian. Its hash is the double
+//! SHA-256 of those bytes, and meets the proof of work when, read as a
+//! little-endian number, it is at most the target. Hashes are kept in that
+//! internal byte order; RPCs and explorers show them reversed.
+
+use crate::BitcoinError;
+use ruint::aliases::U256;
+use sha2::{Digest, Sha256};
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::String};
+
+/// Size of a serialized header
+pub const HEADER_SIZE: usize = 80;
+
+/// A block header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockHeader {
+    pub version: i32,
+    pub prev_blockhash: [u8; 32],
+    pub merkle_root: [u8; 32],
+    pub time: u32,
+    pub bits: u32,
+    pub nonce: u32,
+}
+
+/// SHA-256 applied twice, as Bitcoin hashes headers, transactions and Merkle nodes
+pub fn double_sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(Sha256::digest(data)).into()
+}
+
+impl BlockHeader {
+    /// Parse a serialized header
+    pub fn parse(bytes: &[u8]) -> Result<Self, BitcoinError> {
+        if bytes.len() != HEADER_SIZE {
+            return Err(BitcoinError::InvalidHeader(format!(
+                "A header is {} bytes, not {}",
+                HEADER_SIZE,
+                bytes.len()
+            )));
+        }
+        let u32_at = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().expect("four bytes"));
+        let hash_at = |at: usize| -> [u8; 32] { bytes[at..at + 32].try_into().expect("32 bytes") };
+        Ok(Self {
+            version: u32_at(0) as i32,
+            prev_blockhash: hash_at(4),
+            merkle_root: hash_at(36),
+            time: u32_at(68),
+            bits: u32_at(72),
+            nonce: u32_at(76),
+        })
+    }
+
+    /// Parse a header from hex, as `getblockheader <hash> false` returns it
+    pub fn from_hex(hex_str: &str) -> Result<Self, BitcoinError> {
+        let bytes = hex::decode(hex_str).map_err(|e| BitcoinError::InvalidHeader(format!("{}", e)))?;
+        Self::parse(&bytes)
+    }
+
+    /// The 80 bytes that are hashed
+    pub fn serialize(&self) -> [u8; HEADER_SIZE] {
+        let mut bytes = [0u8; HEADER_SIZE];
+        bytes[0..4].copy_from_slice(&self.version.to_le_bytes());
+        bytes[4..36].copy_from_slice(&self.prev_blockhash);
+        bytes[36..68].copy_from_slice(&self.merkle_root);
+        bytes[68..72].copy_from_slice(&self.time.to_le_bytes());
+        bytes[72..76].copy_from_slice(&self.bits.to_le_bytes());
+        bytes[76..80].copy_from_slice(&self.nonce.to_le_bytes());
+        bytes
+    }
+
+    /// The block hash, in internal byte order
+    pub fn block_hash(&self) -> [u8; 32] {
+        double_sha256(&self.serialize())
+    }
+
+    /// The target the header's hash must meet
+    pub fn target(&self) -> Result<U256, BitcoinError> {
+        bits_to_target(self.bits)
+    }
+
+    /// Expected number of hashes needed to find a header with this target
+    pub fn work(&self) -> Result<U256, BitcoinError> {
+        let target = self.target()?;
+        // 2^256 / (target + 1), without leaving 256 bits
+        Ok((!target / (target + U256::from(1u8))) + U256::from(1u8))
+    }
+
+    /// Check that the header's hash meets its own target
+    pub fn check_proof_of_work(&self) -> Result<(), BitcoinError> {
+        let target = self.target()?;
+        if U256::from_le_bytes(self.block_hash()) > target {
+            return Err(BitcoinError::InvalidHeader(format!(
+                "Block {} does not meet its target",
+                display_hex(&self.block_hash())
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Expand a compact target; negative, zero and overflowing targets are rejected
+pub fn bits_to_target(bits: u32) -> Result<U256, BitcoinError> {
+    let exponent = bits >> 24;
+    let mantissa = bits & 0x007f_ffff;
+    let invalid = || BitcoinError::InvalidHeader(format!("Invalid target bits {:#010x}", bits));
+    if bits & 0x0080_0000 != 0 || mantissa == 0 {
+        return Err(invalid());
+    }
+    let target = if exponent <= 3 {
+        U256::from(mantissa >> (8 * (3 - exponent)))
+    } else {
+        let shift = 8 * (exponent as usize - 3);
+        let target = U256::from(mantissa).checked_shl(shift).ok_or_else(invalid)?;
+        if target >> shift != U256::from(mantissa) {
+            return Err(invalid());
+        }
+        target
+    };
+    if target.is_zero() {
+        return Err(invalid());
+    }
+    Ok(target)
+}
+
+/// Compact form of a target, losing the precision a compact target cannot hold
+pub fn target_to_bits(target: U256) -> u32 {
+    let mut size = target.bit_len().div_ceil(8) as u32;
+    let mut compact = if size <= 3 {
+        target.to::<u64>() << (8 * (3 - size))
+    } else {
+        (target >> (8 * (size as usize - 3))).to::<u64>()
+    } as u32;
+    if compact & 0x0080_0000 != 0 {
+        compact >>= 8;
+        size += 1;
+    }
+    compact | (size << 24)
+}
+
+/// A hash in the reversed byte order RPCs and explorers show
+pub fn display_hex(hash: &[u8; 32]) -> String {
+    let mut reversed = *hash;
+    reversed.reverse();
+    hex::encode(reversed)
+}
+
+/// Parse a hash shown in reversed byte order into internal order
+pub fn parse_display_hash(hex_str: &str) -> Result<[u8; 32], BitcoinError> {
+    let mut hash = [0u8; 32];
+    hex::decode_to_slice(hex_str.strip_prefix("0x").unwrap_or(hex_str), &mut hash)
+        .map_err(|_| BitcoinError::InvalidHeader(format!("Invalid hash '{}'", hex_str)))?;
+    hash.reverse();
+    Ok(hash)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Headers of mainnet blocks 0, 1 and 2
+    pub(crate) const MAINNET_HEADERS: [&str; 3] = [
+        "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c",
+        "010000006fe28c0ab6f1b372c1a6a246ae63f74f931e8365e15a089c68d6190000000000982051fd1e4ba744bbbe680e1fee14677ba1a3c3540bf7b1cdb606e857233e0e61bc6649ffff001d01e36299",
+        "010000004860eb18bf1b1620e37e9490fc8a427514416fd75159ab86688e9a8300000000d5fdcc541e25de1c7a5addedf24858b8bb665c9f36ef744ee42c316022c90f9bb0bc6649ffff001d08d2bd61",
+    ];
+
+    #[test]
+    fn test_mainnet_headers() {
+        let genesis = BlockHeader::from_hex(MAINNET_HEADERS[0]).unwrap();
+        assert_eq!(
+            display_hex(&genesis.block_hash()),
+            "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"
+        );
+        assert_eq!(
+            display_hex(&genesis.merkle_root),
+            "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"
+        );
+        assert_eq!(hex::encode(genesis.serialize()), MAINNET_HEADERS[0]);
+        genesis.check_proof_of_work().unwrap();
+        assert_eq!(genesis.work().unwrap(), U256::from(0x1_0001_0001u64));
+
+        let block_1 = BlockHeader::from_hex(MAINNET_HEADERS[1]).unwrap();
+        assert_eq!(block_1.prev_blockhash, genesis.block_hash());
+        assert_eq!(
+            parse_display_hash("00000000839a8e6886ab5951d76f411475428afc90947ee320161bbf18eb6048").unwrap(),
+            block_1.block_hash()
+        );
+
+        let mut other_nonce = block_1;
+        other_nonce.nonce += 1;
+        assert!(other_nonce.check_proof_of_work().is_err());
+        assert!(BlockHeader::parse(&genesis.serialize()[..79]).is_err());
+    }
+
+    #[test]
+    fn test_compact_targets() {
+        let limit = bits_to_target(0x1d00ffff).unwrap();
+        assert_eq!(limit, U256::from(0xffffu64) << 208);
+        for bits in [0x1d00ffffu32, 0x1b0404cb, 0x207fffff, 0x1e0377ae, 0x03123456, 0x02008000] {
+            assert_eq!(target_to_bits(bits_to_target(bits).unwrap()), bits);
+        }
+        // A mantissa with its top bit set takes another byte
+        assert_eq!(target_to_bits(U256::from(0x80u64)), 0x02008000);
+
+        assert!(bits_to_target(0x1d80ffff).is_err());
+        assert!(bits_to_target(0x1d000000).is_err());
+        assert!(bits_to_target(0x01003456).is_err());
+        assert!(bits_to_target(0x23000001).is_err());
+    }
+}
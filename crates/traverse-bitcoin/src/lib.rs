@@ -0,0 +1,109 @@
+//! SPV header chains and transaction inclusion proofs for Bitcoin
+//!
+//! Bitcoin has no contract storage to lay out; what a light client can
+//! prove is that a transaction was mined, and how much work has been built
+//! on it since. A transaction's txid is a leaf of the Merkle tree whose root
+//! is in its block's header, and each header commits to the one before it
+//! and carries the proof of work its hash meets. A proof is therefore the
+//! raw transaction, its Merkle branch, and the headers from its block to a
+//! tip, which together support statements like "txid T confirmed with N
+//! headers of work W". This crate checks such proofs and fetches them.
+//!
+//! # Features
+//!
+//! - **Header Chains**: Check linkage, proof of work and difficulty adjustments
+//! - **Inclusion Proofs**: Check Merkle branches, including those in `gettxoutproof` output
+//! - **Transactions**: Parse raw transactions, segwit included, for their txid and outputs
+//! - **Proof Fetching**: Read proofs from Bitcoin Core over JSON-RPC (`client`)
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! use traverse_bitcoin::BitcoinTxProof;
+//!
+//! let proof = BitcoinTxProof::from_json(&proof_json)?;
+//! let confirmation = proof.verify()?;
+//! assert!(confirmation.confirmations >= 6);
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(not(feature = "std"))]
+use alloc::string::{String, ToString};
+
+pub mod chain;
+mod encode;
+pub mod header;
+pub mod merkle;
+pub mod network;
+pub mod proof;
+pub mod transaction;
+
+#[cfg(feature = "client")]
+pub mod client;
+
+pub use chain::{verify_header_chain, ChainSummary};
+pub use header::{
+    bits_to_target, display_hex, double_sha256, parse_display_hash, target_to_bits, BlockHeader, HEADER_SIZE,
+};
+pub use merkle::{merkle_root, MerkleProof};
+pub use network::Network;
+pub use proof::{BitcoinTxProof, TxConfirmation};
+pub use ruint::aliases::U256;
+pub use transaction::{Transaction, TxIn, TxOut};
+
+#[cfg(feature = "client")]
+pub use client::{BitcoinChainConfig, BitcoinProofFetcher};
+
+/// Error types specific to Bitcoin
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+#[derive(Debug)]
+pub enum BitcoinError {
+    #[cfg_attr(feature = "std", error("Invalid block header: {0}"))]
+    InvalidHeader(String),
+
+    #[cfg_attr(feature = "std", error("Invalid transaction: {0}"))]
+    InvalidTransaction(String),
+
+    #[cfg_attr(feature = "std", error("Invalid header chain: {0}"))]
+    InvalidHeaderChain(String),
+
+    #[cfg_attr(feature = "std", error("Proof verification failed: {0}"))]
+    ProofVerification(String),
+
+    #[cfg_attr(feature = "std", error("RPC error: {0}"))]
+    Rpc(String),
+
+    #[cfg(feature = "client")]
+    #[cfg_attr(feature = "std", error("Network error: {0}"))]
+    Network(#[from] reqwest::Error),
+
+    #[cfg_attr(feature = "std", error("JSON error: {0}"))]
+    Json(#[cfg_attr(feature = "std", from)] serde_json::Error),
+
+    #[cfg_attr(feature = "std", error("Traverse core error: {0}"))]
+    TraverseCore(#[cfg_attr(feature = "std", from)] traverse_core::TraverseError),
+}
+
+impl From<BitcoinError> for traverse_core::TraverseError {
+    fn from(err: BitcoinError) -> Self {
+        use traverse_core::TraverseError;
+
+        match err {
+            BitcoinError::InvalidHeader(msg) | BitcoinError::InvalidTransaction(msg) => {
+                TraverseError::InvalidInput(msg)
+            }
+            BitcoinError::InvalidHeaderChain(msg) | BitcoinError::ProofVerification(msg) => {
+                TraverseError::Validation(msg)
+            }
+            BitcoinError::Rpc(msg) => TraverseError::ExternalService(msg),
+            #[cfg(feature = "client")]
+            BitcoinError::Network(err) => TraverseError::ExternalService(err.to_string()),
+            BitcoinError::Json(err) => TraverseError::Serialization(err.to_string()),
+            BitcoinError::TraverseCore(err) => err,
+        }
+    }
+}
@@ -0,0 +1,344 @@
+//! Transaction Merkle trees
+//!
+//! A block's Merkle root is built from its txids by hashing pairs with
+//! double SHA-256, pairing the last node of an odd level with itself. A
+//! branch holds the sibling at each level from the txid up. An inner node
+//! is itself the hash of 64 bytes, so a branch could be cut short to pass a
+//! node off as a txid; the branch must therefore be exactly as deep as a
+//! tree of the block's transaction count, which `gettxoutproof` returns
+//! alongside the branch. The count is not committed to by the header, which
+//! is why [`crate::BitcoinTxProof`] also refuses 64-byte transactions.
+
+use crate::encode::Reader;
+use crate::header::{display_hex, double_sha256, BlockHeader, HEADER_SIZE};
+use crate::BitcoinError;
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, vec::Vec};
+
+/// A transaction's place in its block's Merkle tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleProof {
+    /// Position of the transaction in its block
+    pub index: u32,
+    /// Number of transactions in the block
+    pub tx_count: u32,
+    /// Sibling at each level, from the txid up, in internal byte order
+    pub branch: Vec<[u8; 32]>,
+}
+
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut data = [0u8; 64];
+    data[..32].copy_from_slice(left);
+    data[32..].copy_from_slice(right);
+    double_sha256(&data)
+}
+
+/// Levels above the txids in a tree of `tx_count` transactions
+fn tree_depth(tx_count: u32) -> usize {
+    let mut width = tx_count;
+    let mut depth = 0;
+    while width > 1 {
+        width = width.div_ceil(2);
+        depth += 1;
+    }
+    depth
+}
+
+/// Number of nodes at `height` above the txids
+fn width_at(tx_count: u32, height: usize) -> u32 {
+    ((tx_count as u64 + (1u64 << height) - 1) >> height) as u32
+}
+
+/// The Merkle root of a block with these txids
+pub fn merkle_root(txids: &[[u8; 32]]) -> Result<[u8; 32], BitcoinError> {
+    let mut level = txids.to_vec();
+    if level.is_empty() {
+        return Err(BitcoinError::ProofVerification("A block has at least one transaction".into()));
+    }
+    while level.len() > 1 {
+        level = level.chunks(2).map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&pair[0]))).collect();
+    }
+    Ok(level[0])
+}
+
+impl MerkleProof {
+    /// Branch of the transaction at `index` among a block's txids
+    pub fn from_txids(txids: &[[u8; 32]], index: u32) -> Result<Self, BitcoinError> {
+        if index as usize >= txids.len() {
+            return Err(BitcoinError::ProofVerification(format!("The block has no transaction {}", index)));
+        }
+        let mut level = txids.to_vec();
+        let mut position = index as usize;
+        let mut branch = Vec::new();
+        while level.len() > 1 {
+            branch.push(*level.get(position ^ 1).unwrap_or(&level[position]));
+            level = level.chunks(2).map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&pair[0]))).collect();
+            position /= 2;
+        }
+        Ok(Self { index, tx_count: txids.len() as u32, branch })
+    }
+
+    /// The Merkle root `txid` leads to through the branch
+    pub fn root(&self, txid: &[u8; 32]) -> Result<[u8; 32], BitcoinError> {
+        if self.index >= self.tx_count {
+            return Err(BitcoinError::ProofVerification(format!(
+                "Transaction {} is not in a block of {}",
+                self.index, self.tx_count
+            )));
+        }
+        if self.branch.len() != tree_depth(self.tx_count) {
+            return Err(BitcoinError::ProofVerification(format!(
+                "A block of {} transactions needs a branch of {}, not {}",
+                self.tx_count,
+                tree_depth(self.tx_count),
+                self.branch.len()
+            )));
+        }
+        let mut hash = *txid;
+        let mut position = self.index;
+        for (height, sibling) in self.branch.iter().enumerate() {
+            if position.is_multiple_of(2) {
+                // The last node of an odd level is paired with itself
+                if position + 1 == width_at(self.tx_count, height) && *sibling != hash {
+                    return Err(BitcoinError::ProofVerification(format!(
+                        "The branch pairs a lone node at level {} with another",
+                        height
+                    )));
+                }
+                hash = hash_pair(&hash, sibling);
+            } else {
+                hash = hash_pair(sibling, &hash);
+            }
+            position /= 2;
+        }
+        Ok(hash)
+    }
+
+    /// The header and branch of `txid` in a `merkleblock`, as `gettxoutproof` returns it
+    ///
+    /// The partial tree is walked as BIP37 lays it out, and its root checked
+    /// against the header's.
+    pub fn from_merkle_block(bytes: &[u8], txid: &[u8; 32]) -> Result<(BlockHeader, Self), BitcoinError> {
+        let mut reader = Reader::new(bytes);
+        let header = BlockHeader::parse(reader.take(HEADER_SIZE)?)?;
+        let tx_count = reader.u32()?;
+        if tx_count == 0 {
+            return Err(BitcoinError::ProofVerification("The merkle block has no transactions".into()));
+        }
+        let mut hashes = Vec::new();
+        for _ in 0..reader.compact_size()? {
+            hashes.push(reader.array::<32>()?);
+        }
+        let flags = reader.var_bytes()?;
+        if !reader.is_empty() {
+            return Err(BitcoinError::ProofVerification("Data continues after the merkle block's flags".into()));
+        }
+
+        let mut tree = PartialTree { tx_count, hashes: &hashes, flags, used_hashes: 0, used_bits: 0, nodes: Vec::new() };
+        let height = tree_depth(tx_count);
+        let root = tree.walk(height, 0)?;
+        if root != header.merkle_root {
+            return Err(BitcoinError::ProofVerification("The merkle block's tree does not match its header".into()));
+        }
+        if tree.used_hashes != hashes.len() || tree.used_bits.div_ceil(8) != flags.len() {
+            return Err(BitcoinError::ProofVerification("The merkle block has unused hashes or flags".into()));
+        }
+
+        let index = tree
+            .nodes
+            .iter()
+            .find(|(node_height, _, hash, matched)| *node_height == 0 && *matched && hash == txid)
+            .map(|(_, position, _, _)| *position)
+            .ok_or_else(|| {
+                BitcoinError::ProofVerification(format!("The merkle block does not match {}", display_hex(txid)))
+            })?;
+        let branch = (0..height)
+            .map(|level| {
+                let position = index >> level;
+                let sibling = if position ^ 1 < width_at(tx_count, level) { position ^ 1 } else { position };
+                tree.node(level, sibling)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok((header, Self { index, tx_count, branch }))
+    }
+}
+
+/// A BIP37 partial Merkle tree being walked
+struct PartialTree<'a> {
+    tx_count: u32,
+    hashes: &'a [[u8; 32]],
+    flags: &'a [u8],
+    used_hashes: usize,
+    used_bits: usize,
+    /// Every node reached: height, position, hash, and whether it is a matched txid
+    nodes: Vec<(usize, u32, [u8; 32], bool)>,
+}
+
+impl PartialTree<'_> {
+    fn walk(&mut self, height: usize, position: u32) -> Result<[u8; 32], BitcoinError> {
+        let malformed = || BitcoinError::ProofVerification("The merkle block's tree is malformed".into());
+        let byte = self.flags.get(self.used_bits / 8).ok_or_else(malformed)?;
+        let flag = byte >> (self.used_bits % 8) & 1 == 1;
+        self.used_bits += 1;
+
+        let hash = if height == 0 || !flag {
+            let hash = *self.hashes.get(self.used_hashes).ok_or_else(malformed)?;
+            self.used_hashes += 1;
+            hash
+        } else {
+            let left = self.walk(height - 1, position * 2)?;
+            let right = if position * 2 + 1 < width_at(self.tx_count, height - 1) {
+                let right = self.walk(height - 1, position * 2 + 1)?;
+                // Equal children would let a duplicated txid stand in for a real one
+                if right == left {
+                    return Err(malformed());
+                }
+                right
+            } else {
+                left
+            };
+            hash_pair(&left, &right)
+        };
+        self.nodes.push((height, position, hash, height == 0 && flag));
+        Ok(hash)
+    }
+
+    fn node(&self, height: usize, position: u32) -> Result<[u8; 32], BitcoinError> {
+        self.nodes
+            .iter()
+            .find(|(h, p, _, _)| *h == height && *p == position)
+            .map(|(_, _, hash, _)| *hash)
+            .ok_or_else(|| BitcoinError::ProofVerification("The merkle block leaves out a sibling".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::parse_display_hash;
+
+    /// Txids of mainnet block 100000
+    const BLOCK_100000_TXIDS: [&str; 4] = [
+        "8c14f0db3df150123e6f3dbbf30f8b955a8249b62ac1d1ff16284aefa3d06d87",
+        "fff2525b8931402dd09222c50775608f75787bd2b87e56995a7bdd30f79702c4",
+        "6359f0868171b1d194cbee1af2f16ea598ae8fad666d9b012c8ed2b79a236ec4",
+        "e9a66845e05d5abc0ad04ec80f774a7e585c6e8db975962d069a522137b80c1d",
+    ];
+
+    fn txids(count: u8) -> Vec<[u8; 32]> {
+        (0..count).map(|i| double_sha256(&[i])).collect()
+    }
+
+    /// A merkle block matching the txids at `matched`, as Bitcoin Core builds one
+    fn merkle_block(txids: &[[u8; 32]], matched: &[u32]) -> Vec<u8> {
+        fn build(
+            txids: &[[u8; 32]],
+            matched: &[u32],
+            height: usize,
+            position: u32,
+            hashes: &mut Vec<[u8; 32]>,
+            bits: &mut Vec<bool>,
+        ) {
+            let count = txids.len() as u32;
+            let first = position << height;
+            let last = ((position + 1) << height).min(count);
+            let parent_of_match = matched.iter().any(|m| (first..last).contains(m));
+            bits.push(parent_of_match);
+            if height == 0 || !parent_of_match {
+                let node = MerkleProof::from_txids(txids, first).unwrap();
+                let mut hash = txids[first as usize];
+                for (level, sibling) in node.branch.iter().take(height).enumerate() {
+                    hash = if (first >> level).is_multiple_of(2) { hash_pair(&hash, sibling) } else { hash_pair(sibling, &hash) };
+                }
+                hashes.push(hash);
+            } else {
+                build(txids, matched, height - 1, position * 2, hashes, bits);
+                if position * 2 + 1 < width_at(count, height - 1) {
+                    build(txids, matched, height - 1, position * 2 + 1, hashes, bits);
+                }
+            }
+        }
+
+        let (mut hashes, mut bits) = (Vec::new(), Vec::new());
+        build(txids, matched, tree_depth(txids.len() as u32), 0, &mut hashes, &mut bits);
+        let header = BlockHeader {
+            version: 1,
+            prev_blockhash: [0; 32],
+            merkle_root: merkle_root(txids).unwrap(),
+            time: 0,
+            bits: 0x207fffff,
+            nonce: 0,
+        };
+        let mut bytes = header.serialize().to_vec();
+        bytes.extend_from_slice(&(txids.len() as u32).to_le_bytes());
+        bytes.push(hashes.len() as u8);
+        hashes.iter().for_each(|hash| bytes.extend_from_slice(hash));
+        let flags: Vec<u8> = bits
+            .chunks(8)
+            .map(|chunk| chunk.iter().enumerate().fold(0, |byte, (i, bit)| byte | (*bit as u8) << i))
+            .collect();
+        bytes.push(flags.len() as u8);
+        bytes.extend_from_slice(&flags);
+        bytes
+    }
+
+    #[test]
+    fn test_mainnet_merkle_root() {
+        let txids: Vec<_> = BLOCK_100000_TXIDS.iter().map(|t| parse_display_hash(t).unwrap()).collect();
+        let root = merkle_root(&txids).unwrap();
+        assert_eq!(display_hex(&root), "f3e94742aca4b5ef85488dc37c06c3282295ffec960994b2c0d5ac2a25a95766");
+        for index in 0..4 {
+            let proof = MerkleProof::from_txids(&txids, index).unwrap();
+            assert_eq!(proof.root(&txids[index as usize]).unwrap(), root);
+        }
+    }
+
+    #[test]
+    fn test_merkle_branches() {
+        for count in [1u8, 2, 3, 5, 7, 12] {
+            let txids = txids(count);
+            let root = merkle_root(&txids).unwrap();
+            for index in 0..count as u32 {
+                let proof = MerkleProof::from_txids(&txids, index).unwrap();
+                assert_eq!(proof.root(&txids[index as usize]).unwrap(), root);
+                if count > 1 {
+                    assert_ne!(proof.root(&txids[(index as usize + 1) % count as usize]).ok(), Some(root));
+                }
+            }
+        }
+
+        // An inner node cannot be passed off as a txid by cutting the branch short
+        let txids = txids(4);
+        let proof = MerkleProof::from_txids(&txids, 2).unwrap();
+        let inner = hash_pair(&txids[2], &txids[3]);
+        let short = MerkleProof { index: 1, tx_count: 4, branch: proof.branch[1..].to_vec() };
+        assert!(short.root(&inner).is_err());
+
+        // A lone node must be paired with itself
+        let txids = txids[..3].to_vec();
+        let mut proof = MerkleProof::from_txids(&txids, 2).unwrap();
+        proof.branch[0] = txids[0];
+        assert!(proof.root(&txids[2]).is_err());
+    }
+
+    #[test]
+    fn test_merkle_block() {
+        let txids = txids(7);
+        for matched in [&[0u32][..], &[3], &[6], &[2, 5]] {
+            let bytes = merkle_block(&txids, matched);
+            for index in matched {
+                let (header, proof) = MerkleProof::from_merkle_block(&bytes, &txids[*index as usize]).unwrap();
+                assert_eq!(proof, MerkleProof::from_txids(&txids, *index).unwrap());
+                assert_eq!(proof.root(&txids[*index as usize]).unwrap(), header.merkle_root);
+            }
+        }
+
+        let bytes = merkle_block(&txids, &[3]);
+        assert!(MerkleProof::from_merkle_block(&bytes, &txids[4]).is_err());
+        assert!(MerkleProof::from_merkle_block(&bytes[..bytes.len() - 1], &txids[3]).is_err());
+        let mut other_root = bytes.clone();
+        other_root[40] ^= 1;
+        assert!(MerkleProof::from_merkle_block(&other_root, &txids[3]).is_err());
+    }
+}
@@ -0,0 +1,64 @@
+//! Consensus parameters of each network
+//!
+//! Only the parameters headers are checked against: the proof of work
+//! limit, and whether the target is adjusted every 2016 blocks or, on the
+//! test networks, may drop to the limit after 20 minutes without a block.
+
+use crate::BitcoinError;
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(feature = "std"))]
+use alloc::format;
+
+/// Blocks between difficulty adjustments
+pub const RETARGET_INTERVAL: u64 = 2016;
+
+/// Time the blocks between adjustments are meant to take, in seconds
+pub const TARGET_TIMESPAN: u32 = 14 * 24 * 60 * 60;
+
+/// Time a block is meant to take, in seconds
+pub const TARGET_SPACING: u32 = 10 * 60;
+
+/// A Bitcoin network
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    Bitcoin,
+    Testnet,
+    Testnet4,
+    Signet,
+    Regtest,
+}
+
+impl Network {
+    /// Network of a chain name as `getblockchaininfo` reports it
+    pub fn from_chain_name(chain: &str) -> Result<Self, BitcoinError> {
+        match chain {
+            "main" => Ok(Network::Bitcoin),
+            "test" => Ok(Network::Testnet),
+            "testnet4" => Ok(Network::Testnet4),
+            "signet" => Ok(Network::Signet),
+            "regtest" => Ok(Network::Regtest),
+            other => Err(BitcoinError::Rpc(format!("Unknown chain '{}'", other))),
+        }
+    }
+
+    /// The easiest target a header may have, in compact form
+    pub fn pow_limit_bits(self) -> u32 {
+        match self {
+            Network::Bitcoin | Network::Testnet | Network::Testnet4 => 0x1d00ffff,
+            Network::Signet => 0x1e0377ae,
+            Network::Regtest => 0x207fffff,
+        }
+    }
+
+    /// Whether the target is recomputed every [`RETARGET_INTERVAL`] blocks
+    pub fn retargets(self) -> bool {
+        self != Network::Regtest
+    }
+
+    /// Whether a block found 20 minutes after the last may use the limit
+    pub fn allows_min_difficulty(self) -> bool {
+        matches!(self, Network::Testnet | Network::Testnet4 | Network::Regtest)
+    }
+}
@@ -0,0 +1,260 @@
+//! Transaction inclusion proofs
+//!
+//! A proof holds the raw transaction, its branch in its block's Merkle
+//! tree, and the headers from its block to a tip. Verifying it yields the
+//! txid, the block, how many headers confirm it and the work they carry.
+//! An output of the transaction is proven to have been created; whether it
+//! is still unspent is beyond what SPV can show.
+//!
+//! A 64-byte transaction serializes to the same length as an inner Merkle
+//! node, which would let a node pass for a transaction, so such
+//! transactions are refused. Bitcoin Core has not relayed them since 0.16.
+
+use crate::chain::{verify_header_chain, ChainSummary};
+use crate::header::{display_hex, parse_display_hash, BlockHeader};
+use crate::merkle::MerkleProof;
+use crate::network::Network;
+use crate::transaction::{Transaction, TxOut};
+use crate::BitcoinError;
+use ruint::aliases::U256;
+use serde_json::{json, Value};
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, vec::Vec};
+
+/// A transaction with the proof that it was mined and then built on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitcoinTxProof {
+    pub network: Network,
+    /// The raw transaction, with witnesses if it has any
+    pub transaction: Vec<u8>,
+    /// Height of the block holding the transaction
+    pub block_height: u64,
+    pub merkle: MerkleProof,
+    /// The transaction's block first, then each block built on it
+    pub headers: Vec<BlockHeader>,
+}
+
+/// What a valid proof establishes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxConfirmation {
+    /// Txid, in internal byte order
+    pub txid: [u8; 32],
+    /// Hash of the transaction's block, in internal byte order
+    pub block_hash: [u8; 32],
+    pub block_height: u64,
+    pub merkle_root: [u8; 32],
+    /// Headers from the transaction's block to the tip, both included
+    pub confirmations: u32,
+    pub tip_hash: [u8; 32],
+    pub tip_height: u64,
+    /// Work of the confirming headers
+    pub chain_work: U256,
+}
+
+impl BitcoinTxProof {
+    /// The transaction the proof is for
+    pub fn parse_transaction(&self) -> Result<Transaction, BitcoinError> {
+        Transaction::parse(&self.transaction)
+    }
+
+    /// Check the transaction against its block and the headers against each other
+    pub fn verify(&self) -> Result<TxConfirmation, BitcoinError> {
+        let transaction = self.parse_transaction()?;
+        if transaction.stripped_size == 64 {
+            return Err(BitcoinError::ProofVerification(
+                "A 64-byte transaction cannot be told from a Merkle node".into(),
+            ));
+        }
+        let block = self
+            .headers
+            .first()
+            .ok_or_else(|| BitcoinError::ProofVerification("The proof has no headers".into()))?;
+        if self.merkle.root(&transaction.txid)? != block.merkle_root {
+            return Err(BitcoinError::ProofVerification(format!(
+                "Transaction {} is not in block {}",
+                display_hex(&transaction.txid),
+                display_hex(&block.block_hash())
+            )));
+        }
+        if (self.merkle.index == 0) != transaction.is_coinbase() {
+            return Err(BitcoinError::ProofVerification(
+                "Only the first transaction of a block is a coinbase".into(),
+            ));
+        }
+        let ChainSummary { tip_hash, tip_height, chain_work } =
+            verify_header_chain(&self.headers, self.block_height, self.network)?;
+
+        Ok(TxConfirmation {
+            txid: transaction.txid,
+            block_hash: block.block_hash(),
+            block_height: self.block_height,
+            merkle_root: block.merkle_root,
+            confirmations: u32::try_from(self.headers.len())
+                .map_err(|_| BitcoinError::ProofVerification("The proof has too many headers".into()))?,
+            tip_hash,
+            tip_height,
+            chain_work,
+        })
+    }
+
+    /// Check the proof and return the output at `vout`
+    pub fn verify_output(&self, vout: u32) -> Result<(TxConfirmation, TxOut), BitcoinError> {
+        let confirmation = self.verify()?;
+        let output = self.parse_transaction()?.output(vout)?.clone();
+        Ok((confirmation, output))
+    }
+
+    /// JSON form with hashes in display order and raw data in hex
+    ///
+    /// Alongside the raw data it writes what a reader without SHA-256 needs:
+    /// the txid, the block hash, the headers' work and the outputs. They are
+    /// derived from the raw data, and only as good as the proof's verification.
+    pub fn to_json(&self) -> Value {
+        let transaction = Transaction::parse(&self.transaction).ok();
+        let chain_work = self
+            .headers
+            .iter()
+            .try_fold(U256::ZERO, |work, header| Ok::<_, BitcoinError>(work + header.work()?))
+            .unwrap_or_default();
+        json!({
+            "network": self.network,
+            "txid": transaction.as_ref().map(|tx| display_hex(&tx.txid)).unwrap_or_default(),
+            "transaction": hex::encode(&self.transaction),
+            "block_hash": self.headers.first().map(|h| display_hex(&h.block_hash())).unwrap_or_default(),
+            "block_height": self.block_height,
+            "merkle": {
+                "index": self.merkle.index,
+                "tx_count": self.merkle.tx_count,
+                "branch": self.merkle.branch.iter().map(display_hex).collect::<Vec<_>>(),
+            },
+            "headers": self.headers.iter().map(|h| hex::encode(h.serialize())).collect::<Vec<_>>(),
+            "chain_work": format!("{:#x}", chain_work),
+            "outputs": transaction.iter().flat_map(|tx| &tx.outputs).map(|output| json!({
+                "value": output.value,
+                "script_pubkey": hex::encode(&output.script_pubkey),
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    /// Parse the JSON written by [`Self::to_json`]
+    pub fn from_json(value: &Value) -> Result<Self, BitcoinError> {
+        let merkle = field(value, "merkle")?;
+        let transaction = hex::decode(str_field(value, "transaction")?)
+            .map_err(|e| BitcoinError::InvalidTransaction(format!("{}", e)))?;
+        Ok(Self {
+            network: serde_json::from_value(field(value, "network")?.clone()).map_err(BitcoinError::Json)?,
+            transaction,
+            block_height: u64_field(value, "block_height")?,
+            merkle: MerkleProof {
+                index: u32::try_from(u64_field(merkle, "index")?)
+                    .map_err(|_| BitcoinError::Rpc("Proof field 'index' is out of range".into()))?,
+                tx_count: u32::try_from(u64_field(merkle, "tx_count")?)
+                    .map_err(|_| BitcoinError::Rpc("Proof field 'tx_count' is out of range".into()))?,
+                branch: list(merkle, "branch")?
+                    .iter()
+                    .map(|hash| parse_display_hash(hash.as_str().unwrap_or_default()))
+                    .collect::<Result<_, _>>()?,
+            },
+            headers: list(value, "headers")?
+                .iter()
+                .map(|header| BlockHeader::from_hex(header.as_str().unwrap_or_default()))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+pub(crate) fn field<'a>(value: &'a Value, name: &str) -> Result<&'a Value, BitcoinError> {
+    value.get(name).ok_or_else(|| BitcoinError::Rpc(format!("Missing '{}' in {}", name, value)))
+}
+
+pub(crate) fn str_field<'a>(value: &'a Value, name: &str) -> Result<&'a str, BitcoinError> {
+    field(value, name)?
+        .as_str()
+        .ok_or_else(|| BitcoinError::Rpc(format!("Field '{}' is not a string", name)))
+}
+
+pub(crate) fn u64_field(value: &Value, name: &str) -> Result<u64, BitcoinError> {
+    field(value, name)?
+        .as_u64()
+        .ok_or_else(|| BitcoinError::Rpc(format!("Field '{}' is not a number", name)))
+}
+
+fn list<'a>(value: &'a Value, name: &str) -> Result<&'a Vec<Value>, BitcoinError> {
+    field(value, name)?
+        .as_array()
+        .ok_or_else(|| BitcoinError::Rpc(format!("Field '{}' is not a list", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::tests::{mine, regtest_chain};
+    use crate::header::double_sha256;
+    use crate::network::TARGET_SPACING;
+    use crate::transaction::tests::BLOCK_1_COINBASE;
+
+    /// A regtest proof of a payment, second in its block and confirmed by `confirmations` headers
+    fn payment_proof(confirmations: usize) -> BitcoinTxProof {
+        // Spends block 1's coinbase output to a P2WPKH script
+        let mut payment = hex::decode("0100000001").unwrap();
+        payment.extend_from_slice(&Transaction::parse(&hex::decode(BLOCK_1_COINBASE).unwrap()).unwrap().txid);
+        payment.extend_from_slice(&[0, 0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 2]);
+        for (value, key) in [(4_000_000_000u64, 0x11u8), (999_990_000, 0x22)] {
+            payment.extend_from_slice(&value.to_le_bytes());
+            payment.extend_from_slice(&[22, 0x00, 0x14]);
+            payment.extend_from_slice(&[key; 20]);
+        }
+        payment.extend_from_slice(&[0, 0, 0, 0]);
+        let txid = Transaction::parse(&payment).unwrap().txid;
+
+        let txids = [double_sha256(b"coinbase"), txid, double_sha256(b"other")];
+        let merkle = MerkleProof::from_txids(&txids, 1).unwrap();
+        let base = regtest_chain(1, 0x207fffff);
+        let mut headers = vec![mine(&base[0], crate::merkle::merkle_root(&txids).unwrap(), base[0].time + 600, 0x207fffff)];
+        while headers.len() < confirmations {
+            let prev = headers.last().unwrap();
+            headers.push(mine(prev, [headers.len() as u8; 32], prev.time + TARGET_SPACING, 0x207fffff));
+        }
+        BitcoinTxProof { network: Network::Regtest, transaction: payment, block_height: 101, merkle, headers }
+    }
+
+    #[test]
+    fn test_proof_verification_and_json() {
+        let proof = payment_proof(6);
+        let (confirmation, output) = proof.verify_output(1).unwrap();
+        assert_eq!(confirmation.confirmations, 6);
+        assert_eq!((confirmation.block_height, confirmation.tip_height), (101, 106));
+        assert_eq!(confirmation.block_hash, proof.headers[0].block_hash());
+        assert_eq!(confirmation.chain_work, U256::from(12u8));
+        assert_eq!(output.value, 999_990_000);
+        assert!(proof.verify_output(2).is_err());
+
+        let json = proof.to_json();
+        assert_eq!(BitcoinTxProof::from_json(&json).unwrap(), proof);
+        assert_eq!(json["txid"], display_hex(&confirmation.txid));
+        assert_eq!(json["chain_work"], "0xc");
+        assert_eq!(json["outputs"][1]["value"], 999_990_000);
+
+        let mut other_tx = proof.clone();
+        other_tx.transaction[proof.transaction.len() - 5] ^= 1;
+        assert!(other_tx.verify().is_err());
+
+        let mut other_index = proof.clone();
+        other_index.merkle.index = 2;
+        assert!(other_index.verify().is_err());
+
+        // A coinbase cannot be claimed at any position but the first
+        let mut coinbase = proof.clone();
+        coinbase.transaction = hex::decode(BLOCK_1_COINBASE).unwrap();
+        assert!(coinbase.verify().is_err());
+
+        let mut broken_chain = proof.clone();
+        broken_chain.headers.remove(3);
+        assert!(broken_chain.verify().is_err());
+
+        let mut no_headers = proof;
+        no_headers.headers.clear();
+        assert!(no_headers.verify().is_err());
+    }
+}
@@ -0,0 +1,153 @@
+//! Raw transactions
+//!
+//! A transaction's txid is the double SHA-256 of its serialization without
+//! segwit's marker, flag and witnesses, so a segwit transaction is parsed
+//! for the ranges around them. Only the txid and the outputs matter to an
+//! inclusion proof; scripts are kept as bytes.
+
+use crate::encode::Reader;
+use crate::header::double_sha256;
+use crate::BitcoinError;
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, vec::Vec};
+
+/// A transaction input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxIn {
+    /// Txid of the spent output's transaction, in internal byte order
+    pub prev_txid: [u8; 32],
+    pub prev_vout: u32,
+    pub script_sig: Vec<u8>,
+    pub sequence: u32,
+}
+
+/// A transaction output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxOut {
+    /// Value in satoshis
+    pub value: u64,
+    pub script_pubkey: Vec<u8>,
+}
+
+/// A parsed transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    pub version: i32,
+    pub inputs: Vec<TxIn>,
+    pub outputs: Vec<TxOut>,
+    pub lock_time: u32,
+    /// Txid, in internal byte order
+    pub txid: [u8; 32],
+    /// Size of the serialization the txid hashes
+    pub stripped_size: usize,
+}
+
+impl Transaction {
+    /// Parse a raw transaction, as `getrawtransaction` returns it
+    pub fn parse(bytes: &[u8]) -> Result<Self, BitcoinError> {
+        let mut reader = Reader::new(bytes);
+        let version = reader.u32()? as i32;
+        let body_start = reader.position();
+
+        let mut input_count = reader.compact_size()?;
+        let segwit = input_count == 0 && bytes.get(reader.position()) == Some(&1);
+        let body_start = if segwit {
+            reader.u8()?;
+            let start = reader.position();
+            input_count = reader.compact_size()?;
+            start
+        } else {
+            body_start
+        };
+
+        let mut inputs = Vec::new();
+        for _ in 0..input_count {
+            inputs.push(TxIn {
+                prev_txid: reader.array()?,
+                prev_vout: reader.u32()?,
+                script_sig: reader.var_bytes()?.to_vec(),
+                sequence: reader.u32()?,
+            });
+        }
+        let mut outputs = Vec::new();
+        for _ in 0..reader.compact_size()? {
+            outputs.push(TxOut { value: reader.u64()?, script_pubkey: reader.var_bytes()?.to_vec() });
+        }
+        let body_end = reader.position();
+
+        if segwit {
+            for _ in 0..inputs.len() {
+                for _ in 0..reader.compact_size()? {
+                    reader.var_bytes()?;
+                }
+            }
+        }
+        let lock_time = reader.u32()?;
+        if !reader.is_empty() {
+            return Err(BitcoinError::InvalidTransaction("Data continues after the lock time".into()));
+        }
+        if inputs.is_empty() || outputs.is_empty() {
+            return Err(BitcoinError::InvalidTransaction("A transaction needs inputs and outputs".into()));
+        }
+
+        let mut stripped = Vec::with_capacity(4 + body_end - body_start + 4);
+        stripped.extend_from_slice(&bytes[..4]);
+        stripped.extend_from_slice(&bytes[body_start..body_end]);
+        stripped.extend_from_slice(&lock_time.to_le_bytes());
+
+        Ok(Self { version, inputs, outputs, lock_time, txid: double_sha256(&stripped), stripped_size: stripped.len() })
+    }
+
+    /// Whether the transaction is a coinbase, spending no previous output
+    pub fn is_coinbase(&self) -> bool {
+        self.inputs.len() == 1 && self.inputs[0].prev_txid == [0; 32] && self.inputs[0].prev_vout == u32::MAX
+    }
+
+    /// The output at `vout`
+    pub fn output(&self, vout: u32) -> Result<&TxOut, BitcoinError> {
+        self.outputs.get(vout as usize).ok_or_else(|| {
+            BitcoinError::InvalidTransaction(format!("The transaction has {} outputs, not {}", self.outputs.len(), vout + 1))
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::header::display_hex;
+
+    /// The coinbase of mainnet block 1
+    pub(crate) const BLOCK_1_COINBASE: &str = "01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff0704ffff001d0104ffffffff0100f2052a0100000043410496b538e853519c726a2c91e61ec11600ae1390813a627c66fb8be7947be63c52da7589379515d4e0a604f8141781e62294721166bf621e73a82cbf2342c858eeac00000000";
+
+    #[test]
+    fn test_parse_legacy_transaction() {
+        let tx = Transaction::parse(&hex::decode(BLOCK_1_COINBASE).unwrap()).unwrap();
+        assert_eq!(display_hex(&tx.txid), "0e3e2357e806b6cdb1f70b54c3a3a17b6714ee1f0e68bebb44a74b1efd512098");
+        assert!(tx.is_coinbase());
+        assert_eq!(tx.output(0).unwrap().value, 5_000_000_000);
+        assert_eq!(tx.output(0).unwrap().script_pubkey.len(), 67);
+        assert!(tx.output(1).is_err());
+        assert_eq!(tx.stripped_size, BLOCK_1_COINBASE.len() / 2);
+    }
+
+    #[test]
+    fn test_parse_segwit_transaction() {
+        let legacy = hex::decode(BLOCK_1_COINBASE).unwrap();
+        let txid = Transaction::parse(&legacy).unwrap().txid;
+
+        // The same transaction with a marker, flag and one witness item
+        let mut segwit = legacy[..4].to_vec();
+        segwit.extend_from_slice(&[0x00, 0x01]);
+        segwit.extend_from_slice(&legacy[4..legacy.len() - 4]);
+        segwit.extend_from_slice(&[0x01, 0x20]);
+        segwit.extend_from_slice(&[0u8; 32]);
+        segwit.extend_from_slice(&legacy[legacy.len() - 4..]);
+        let tx = Transaction::parse(&segwit).unwrap();
+        assert_eq!(tx.txid, txid);
+        assert_eq!(tx.stripped_size, legacy.len());
+
+        assert!(Transaction::parse(&segwit[..segwit.len() - 1]).is_err());
+        assert!(Transaction::parse(&[&legacy[..], &[0]].concat()).is_err());
+    }
+}
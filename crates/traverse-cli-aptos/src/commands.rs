@@ -0,0 +1,245 @@
+//! Aptos module compilation and resource proof commands
+//!
+//! Layouts are compiled from Move module ABIs, read from a file or fetched
+//! from a node, and queries resolve to the state keys of resources. Proofs
+//! are pinned to a state checkpoint and verified before they are written.
+
+use anyhow::Result;
+use std::path::Path;
+use traverse_cli_core::{formatters::{write_output, write_result}, OutputFormat};
+
+#[cfg(feature = "aptos")]
+use serde_json::{json, Value};
+#[cfg(feature = "aptos")]
+use traverse_aptos::{AptosKeyResolver, AptosLayoutCompiler, MoveModuleAbi};
+#[cfg(feature = "aptos")]
+use traverse_core::LayoutInfo;
+
+/// Compile a layout from a module ABI file, or from `address::module` on a node
+#[cfg(feature = "aptos")]
+pub async fn cmd_aptos_compile_layout(
+    input: Option<&Path>,
+    module: Option<&str>,
+    rpc: Option<&str>,
+    output: Option<&Path>,
+) -> Result<()> {
+    let abi = match (input, module) {
+        (Some(input), None) => std::fs::read_to_string(input)
+            .map_err(|e| anyhow::anyhow!("Failed to read ABI file '{}': {}", input.display(), e))?,
+        (None, Some(module)) => {
+            let rpc = rpc.ok_or_else(|| anyhow::anyhow!("Fetching a module ABI requires an RPC endpoint"))?;
+            fetch_module_abi(rpc, module).await?
+        }
+        _ => return Err(anyhow::anyhow!("Pass either an ABI file or --module")),
+    };
+
+    let module = MoveModuleAbi::from_json(&abi)?;
+    let layout = AptosLayoutCompiler::compile_module(&module)?;
+    write_output(&serde_json::to_string_pretty(&layout)?, output)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "aptos"))]
+pub async fn cmd_aptos_compile_layout(
+    _input: Option<&Path>,
+    _module: Option<&str>,
+    _rpc: Option<&str>,
+    _output: Option<&Path>,
+) -> Result<()> {
+    Err(anyhow::anyhow!("Aptos support not enabled. Build with --features aptos"))
+}
+
+/// Fetch the ABI of `address::module` from the REST API
+#[cfg(feature = "aptos")]
+async fn fetch_module_abi(rpc: &str, module: &str) -> Result<String> {
+    let (address, name) = module
+        .split_once("::")
+        .ok_or_else(|| anyhow::anyhow!("Module '{}' must be written address::name", module))?;
+    let address = traverse_aptos::display_address(&traverse_aptos::parse_address(address)?);
+    let url = format!("{}/accounts/{}/module/{}", rpc.trim_end_matches('/'), address, name);
+    let response = reqwest::get(&url).await?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("{} returned {}: {}", url, response.status(), response.text().await?));
+    }
+    Ok(response.text().await?)
+}
+
+/// Resolve a resource query to its state key
+#[cfg(feature = "aptos")]
+pub fn cmd_aptos_resolve_query(
+    query: &str,
+    layout_file: &Path,
+    format: &OutputFormat,
+    template: Option<&Path>,
+    output: Option<&Path>,
+) -> Result<()> {
+    let layout = load_layout(layout_file)?;
+    let (key, entry) = AptosKeyResolver::resource_key(&layout, query)?;
+    let path = traverse_core::KeyResolver::resolve(&AptosKeyResolver, &layout, query)?;
+
+    let result = json!({
+        "query": query,
+        "resource": key.resource.to_string(),
+        "account": traverse_aptos::display_address(&key.account),
+        "state_key": format!("0x{}", hex::encode(&key.state_key)),
+        "storage_key": hex::encode(key.hash),
+        "field": key.field,
+        "layout_commitment": hex::encode(layout.commitment()),
+        "zero_semantics": entry.zero_semantics,
+        "field_size": path.field_size,
+    });
+    write_result(&result, format, template, output)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "aptos"))]
+pub fn cmd_aptos_resolve_query(
+    _query: &str,
+    _layout_file: &Path,
+    _format: &OutputFormat,
+    _template: Option<&Path>,
+    _output: Option<&Path>,
+) -> Result<()> {
+    Err(anyhow::anyhow!("Aptos support not enabled. Build with --features aptos"))
+}
+
+/// Fetch a resource with its proof and verify it against the checkpoint's state root
+#[cfg(all(feature = "aptos", feature = "client"))]
+#[allow(clippy::too_many_arguments)]
+pub async fn cmd_aptos_generate_proof(
+    query: &str,
+    account: Option<&str>,
+    layout_file: &Path,
+    rpc: &str,
+    version: Option<u64>,
+    format: &OutputFormat,
+    template: Option<&Path>,
+    output: Option<&Path>,
+) -> Result<()> {
+    use traverse_aptos::{verify_state_proof, AptosProofFetcher};
+
+    let layout = load_layout(layout_file)?;
+    // An explicit account takes the place of one written in the query
+    let query = match account {
+        Some(account) if !query.contains('[') => match query.split_once('.') {
+            Some((head, field)) => format!("{}[{}].{}", head, account, field),
+            None => format!("{}[{}]", query, account),
+        },
+        _ => query.to_string(),
+    };
+    let (key, entry) = AptosKeyResolver::resource_key(&layout, &query)?;
+
+    let fetcher = AptosProofFetcher::with_defaults(rpc.to_string());
+    let proof = fetcher.fetch_proof(&key.account, &key.resource, version).await?;
+    verify_state_proof(&proof)?;
+
+    let mut result = proof.to_json();
+    result["query"] = json!(query);
+    result["layout_commitment"] = json!(hex::encode(layout.commitment()));
+    result["zero_semantics"] = json!(entry.zero_semantics);
+    result["verified"] = json!(true);
+    write_result(&result, format, template, output)?;
+    Ok(())
+}
+
+#[cfg(not(all(feature = "aptos", feature = "client")))]
+#[allow(clippy::too_many_arguments)]
+pub async fn cmd_aptos_generate_proof(
+    _query: &str,
+    _account: Option<&str>,
+    _layout_file: &Path,
+    _rpc: &str,
+    _version: Option<u64>,
+    _format: &OutputFormat,
+    _template: Option<&Path>,
+    _output: Option<&Path>,
+) -> Result<()> {
+    Err(anyhow::anyhow!("Aptos client support not enabled. Build with --features aptos,client"))
+}
+
+/// Verify a proof written by `generate-proof`, returning whether it failed
+#[cfg(all(feature = "aptos", feature = "client"))]
+pub fn cmd_aptos_verify_proof(proof_file: &Path, format: &OutputFormat, output: Option<&Path>) -> Result<bool> {
+    use traverse_aptos::{verify_state_proof, AptosStateProof};
+
+    let document: Value = serde_json::from_str(
+        &std::fs::read_to_string(proof_file)
+            .map_err(|e| anyhow::anyhow!("Failed to read proof file '{}': {}", proof_file.display(), e))?,
+    )?;
+    let proof = AptosStateProof::from_json(&document)?;
+    let failure = verify_state_proof(&proof).err();
+
+    let result = json!({
+        "account": traverse_aptos::display_address(&proof.account),
+        "resource": proof.resource,
+        "version": proof.version,
+        "present": proof.value.is_some(),
+        "valid": failure.is_none(),
+        "error": failure.as_ref().map(|e| e.to_string()),
+    });
+    write_result(&result, format, None, output)?;
+    Ok(failure.is_some())
+}
+
+#[cfg(not(all(feature = "aptos", feature = "client")))]
+pub fn cmd_aptos_verify_proof(_proof_file: &Path, _format: &OutputFormat, _output: Option<&Path>) -> Result<bool> {
+    Err(anyhow::anyhow!("Aptos client support not enabled. Build with --features aptos,client"))
+}
+
+/// Chain ID an endpoint serves
+#[cfg(all(feature = "aptos", feature = "client"))]
+pub async fn fetch_chain_id(rpc_url: &str) -> Result<String> {
+    Ok(traverse_aptos::AptosProofFetcher::with_defaults(rpc_url.to_string()).chain_id().await?)
+}
+
+#[cfg(not(all(feature = "aptos", feature = "client")))]
+pub async fn fetch_chain_id(_rpc_url: &str) -> Result<String> {
+    Err(anyhow::anyhow!("Aptos client support not enabled. Build with --features aptos,client"))
+}
+
+#[cfg(feature = "aptos")]
+fn load_layout(layout_file: &Path) -> Result<LayoutInfo> {
+    let content = std::fs::read_to_string(layout_file)
+        .map_err(|e| anyhow::anyhow!("Failed to read layout file '{}': {}", layout_file.display(), e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("Failed to parse layout file '{}': {}", layout_file.display(), e))
+}
+
+#[cfg(all(test, feature = "aptos", feature = "client"))]
+mod tests {
+    use super::*;
+    use traverse_aptos::{
+        parse_address, resource_state_key, state_key_hash, state_value_hash, AptosStateProof, SparseMerkleLeaf,
+        SparseMerkleProof, StructTag,
+    };
+
+    #[test]
+    fn test_verify_proof_file() {
+        let account = parse_address("0xa11ce").unwrap();
+        let resource = StructTag::parse("0x1::account::Account").unwrap();
+        let state_key = resource_state_key(&account, &resource);
+        let value = vec![7u8; 16];
+        let leaf = SparseMerkleLeaf { key: state_key_hash(&state_key), value_hash: state_value_hash(&value) };
+        let mut proof = AptosStateProof {
+            account,
+            resource: resource.to_string(),
+            state_key,
+            value: Some(value),
+            proof: SparseMerkleProof { leaf: Some(leaf), siblings: Vec::new() },
+            version: 1,
+            root: leaf.hash(),
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        let proof_file = dir.path().join("proof.json");
+        let report = dir.path().join("report.json");
+        std::fs::write(&proof_file, proof.to_json().to_string()).unwrap();
+        assert!(!cmd_aptos_verify_proof(&proof_file, &OutputFormat::CoprocessorJson, Some(&report)).unwrap());
+
+        proof.root[0] ^= 1;
+        std::fs::write(&proof_file, proof.to_json().to_string()).unwrap();
+        assert!(cmd_aptos_verify_proof(&proof_file, &OutputFormat::CoprocessorJson, Some(&report)).unwrap());
+        let report: Value = serde_json::from_str(&std::fs::read_to_string(&report).unwrap()).unwrap();
+        assert_eq!(report["valid"], json!(false));
+    }
+}
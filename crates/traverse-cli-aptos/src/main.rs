@@ -0,0 +1,160 @@
+//! Aptos-specific CLI binary
+//!
+//! This binary provides CLI access to Move resource layouts and proofs
+//! while using the shared core CLI infrastructure.
+
+use clap::{Parser, Subcommand};
+use std::path::Path;
+use std::process;
+use traverse_cli_core::{chain_id, CliResult, CommonArgs};
+
+mod commands;
+
+/// Aptos-specific CLI arguments
+#[derive(Parser)]
+#[command(name = "traverse-aptos")]
+#[command(about = "Aptos ZK storage path generator")]
+#[command(version)]
+struct AptosArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+
+    #[command(subcommand)]
+    command: AptosCommand,
+}
+
+/// Aptos-specific commands
+#[derive(Subcommand)]
+enum AptosCommand {
+    /// Compile the resource layout of a Move module
+    CompileLayout {
+        /// Module ABI file (the bare ABI or the REST API's module response)
+        #[arg(required_unless_present = "module")]
+        input: Option<String>,
+        /// Fetch the ABI of `address::module` from the node instead
+        #[arg(long, conflicts_with = "input")]
+        module: Option<String>,
+        /// REST endpoint, including `/v1` (defaults to the profile's endpoint)
+        #[arg(long)]
+        rpc: Option<String>,
+        /// Output layout file path
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+
+    /// Resolve a resource query to its state key
+    ResolveQuery {
+        /// Resource query, e.g. `CoinStore<0x1::aptos_coin::AptosCoin>[0xa11ce]`
+        query: String,
+        /// Layout file path
+        #[arg(short, long)]
+        layout: String,
+        /// Render the result through a Tera template instead of `--format`
+        #[arg(long)]
+        template: Option<String>,
+    },
+
+    /// Fetch a resource with its state proof
+    GenerateProof {
+        /// Account address or address book name (overrides an account in the query)
+        #[arg(long, alias = "account")]
+        address: Option<String>,
+        /// Resource query, e.g. `CoinStore<0x1::aptos_coin::AptosCoin>.coin.value`
+        #[arg(long)]
+        query: String,
+        /// Layout file (defaults to the address book entry's layout)
+        #[arg(short, long)]
+        layout: Option<String>,
+        /// REST endpoint, including `/v1` (defaults to the profile's endpoint)
+        #[arg(long)]
+        rpc: Option<String>,
+        /// State checkpoint version to prove against (defaults to the latest)
+        #[arg(long)]
+        version: Option<u64>,
+        /// Render the result through a Tera template instead of `--format`
+        #[arg(long)]
+        template: Option<String>,
+    },
+
+    /// Verify a proof written by `generate-proof`
+    ///
+    /// Exits with status 2 when the proof does not verify.
+    VerifyProof {
+        /// Proof file path
+        proof: String,
+    },
+}
+
+async fn handle_command(args: AptosArgs) -> CliResult<()> {
+    // Set verbose mode
+    if args.common.verbose {
+        std::env::set_var("VERBOSE", "1");
+    }
+
+    let profile = args.common.load_profile()?;
+    let format = args.common.output_format(&profile);
+    args.common.install_cache(&profile);
+    args.common.install_chain_id(&profile, "aptos");
+    let output = args.common.output.as_deref().map(Path::new);
+    let processing = |e: anyhow::Error| traverse_cli_core::CliError::Processing(e.to_string());
+
+    match args.command {
+        AptosCommand::CompileLayout { input, module, rpc, output: layout_output } => {
+            let rpc = match module {
+                Some(_) => {
+                    let rpc = profile.require_rpc(rpc.as_deref(), "aptos")?;
+                    chain_id::verify(&rpc, || commands::fetch_chain_id(&rpc)).await?;
+                    Some(rpc)
+                }
+                None => None,
+            };
+            commands::cmd_aptos_compile_layout(input.as_deref().map(Path::new), module.as_deref(), rpc.as_deref(), layout_output.as_deref().map(Path::new).or(output))
+                .await
+                .map_err(processing)?;
+        }
+
+        AptosCommand::ResolveQuery { query, layout, template } => {
+            commands::cmd_aptos_resolve_query(&query, Path::new(&layout), &format, template.as_deref().map(Path::new), output)
+                .map_err(processing)?;
+        }
+
+        AptosCommand::GenerateProof { address, query, layout, rpc, version, template } => {
+            let rpc = profile.require_rpc(rpc.as_deref(), "aptos")?;
+            chain_id::verify(&rpc, || commands::fetch_chain_id(&rpc)).await?;
+            let address = address.map(|a| profile.resolve_address(&a, "aptos")).transpose()?;
+            let layout = layout.or_else(|| address.as_ref().and_then(|e| e.layout.clone())).ok_or_else(|| {
+                traverse_cli_core::CliError::InvalidArgument("No layout for this query. Pass --layout".to_string())
+            })?;
+            commands::cmd_aptos_generate_proof(
+                &query,
+                address.as_ref().map(|e| e.address.as_str()),
+                Path::new(&layout),
+                &rpc,
+                version,
+                &format,
+                template.as_deref().map(Path::new),
+                output,
+            )
+            .await
+            .map_err(processing)?;
+        }
+
+        AptosCommand::VerifyProof { proof } => {
+            if commands::cmd_aptos_verify_proof(Path::new(&proof), &format, output).map_err(processing)? {
+                process::exit(2);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() {
+    let args = AptosArgs::parse();
+
+    if let Err(e) = handle_command(args).await {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,181 @@
+//! Binary artifact format for `--format binary` and `--format base64`
+//!
+//! An artifact is a fixed header followed by a CBOR payload:
+//!
+//! ```text
+//! offset  size  field
+//! 0       4     magic "TRVS"
+//! 4       1     format version
+//! 5       4     payload length (u32, big-endian)
+//! 9       n     CBOR-encoded payload
+//! ```
+//!
+//! `binary` writes these bytes as-is; `base64` is the standard base64 encoding
+//! of the same bytes, so either form can be read back with [`read_file`].
+
+use crate::{CliError, CliResult};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{de::DeserializeOwned, Serialize};
+use std::io::Write;
+use std::path::Path;
+
+/// Magic bytes at the start of every artifact
+pub const ARTIFACT_MAGIC: [u8; 4] = *b"TRVS";
+
+/// Current artifact format version
+pub const ARTIFACT_VERSION: u8 = 1;
+
+/// Size of the header preceding the payload
+pub const HEADER_LEN: usize = 9;
+
+/// Encode a value as an artifact
+pub fn encode<T: Serialize + ?Sized>(value: &T) -> CliResult<Vec<u8>> {
+    let mut payload = Vec::new();
+    ciborium::into_writer(value, &mut payload)
+        .map_err(|e| CliError::Processing(format!("CBOR encoding failed: {}", e)))?;
+
+    let len = u32::try_from(payload.len())
+        .map_err(|_| CliError::Processing("Artifact payload exceeds 4 GiB".to_string()))?;
+
+    let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len());
+    bytes.extend_from_slice(&ARTIFACT_MAGIC);
+    bytes.push(ARTIFACT_VERSION);
+    bytes.extend_from_slice(&len.to_be_bytes());
+    bytes.extend_from_slice(&payload);
+    Ok(bytes)
+}
+
+/// Decode an artifact produced by [`encode`]
+pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> CliResult<T> {
+    if bytes.len() < HEADER_LEN {
+        return Err(CliError::InvalidArgument(format!(
+            "Artifact too short: {} bytes",
+            bytes.len()
+        )));
+    }
+    if bytes[..4] != ARTIFACT_MAGIC {
+        return Err(CliError::InvalidArgument(
+            "Not a traverse artifact (bad magic)".to_string(),
+        ));
+    }
+    if bytes[4] != ARTIFACT_VERSION {
+        return Err(CliError::InvalidArgument(format!(
+            "Unsupported artifact version {} (expected {})",
+            bytes[4], ARTIFACT_VERSION
+        )));
+    }
+
+    let len = u32::from_be_bytes([bytes[5], bytes[6], bytes[7], bytes[8]]) as usize;
+    let payload = &bytes[HEADER_LEN..];
+    if payload.len() != len {
+        return Err(CliError::InvalidArgument(format!(
+            "Artifact length mismatch: header says {} bytes, found {}",
+            len,
+            payload.len()
+        )));
+    }
+
+    ciborium::from_reader(payload)
+        .map_err(|e| CliError::Processing(format!("CBOR decoding failed: {}", e)))
+}
+
+/// Encode a value as a base64 artifact
+pub fn to_base64<T: Serialize + ?Sized>(value: &T) -> CliResult<String> {
+    Ok(STANDARD.encode(encode(value)?))
+}
+
+/// Write an artifact to a file, or to stdout when no path is given
+pub fn write<T: Serialize + ?Sized>(value: &T, output_path: Option<&Path>) -> CliResult<()> {
+    let bytes = encode(value)?;
+    match output_path {
+        Some(path) => {
+            std::fs::write(path, &bytes).map_err(CliError::Io)?;
+            if std::env::var("VERBOSE").is_ok() {
+                eprintln!("Artifact written to: {} ({} bytes)", path.display(), bytes.len());
+            }
+        }
+        None => {
+            let mut stdout = std::io::stdout().lock();
+            stdout.write_all(&bytes).map_err(CliError::Io)?;
+            stdout.flush().map_err(CliError::Io)?;
+        }
+    }
+    Ok(())
+}
+
+/// Read an artifact file in either raw or base64 form
+pub fn read_file<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> CliResult<T> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return Err(CliError::FileNotFound(path.to_string_lossy().to_string()));
+    }
+
+    let bytes = std::fs::read(path).map_err(CliError::Io)?;
+    if bytes.starts_with(&ARTIFACT_MAGIC) {
+        return decode(&bytes);
+    }
+
+    let text = std::str::from_utf8(&bytes)
+        .map_err(|_| CliError::InvalidArgument("Not a traverse artifact (bad magic)".to_string()))?;
+    let decoded = STANDARD
+        .decode(text.trim())
+        .map_err(|e| CliError::InvalidArgument(format!("Invalid base64 artifact: {}", e)))?;
+    decode(&decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_round_trip() {
+        let value = json!({
+            "query": "_balances[0x742d35cc6634c0532925a3b8d97c2e0d8b2d9c53]",
+            "storage_key": "a1b2c3",
+            "offset": null,
+            "field_size": 32
+        });
+
+        let bytes = encode(&value).unwrap();
+        assert_eq!(&bytes[..4], b"TRVS");
+        assert_eq!(bytes[4], ARTIFACT_VERSION);
+        assert_eq!(
+            u32::from_be_bytes(bytes[5..9].try_into().unwrap()) as usize,
+            bytes.len() - HEADER_LEN
+        );
+
+        let decoded: Value = decode(&bytes).unwrap();
+        assert_eq!(decoded, value);
+    }
+
+    #[test]
+    fn test_rejects_malformed_artifacts() {
+        let bytes = encode(&json!({"a": 1})).unwrap();
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert!(decode::<Value>(&bad_magic).is_err());
+
+        let mut bad_version = bytes.clone();
+        bad_version[4] = 99;
+        assert!(decode::<Value>(&bad_version).is_err());
+
+        assert!(decode::<Value>(&bytes[..bytes.len() - 1]).is_err());
+        assert!(decode::<Value>(&bytes[..4]).is_err());
+    }
+
+    #[test]
+    fn test_read_raw_and_base64_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let value = json!({"contract_name": "Token", "storage": []});
+
+        let raw = dir.path().join("layout.bin");
+        write(&value, Some(&raw)).unwrap();
+        assert_eq!(read_file::<Value, _>(&raw).unwrap(), value);
+
+        let encoded = dir.path().join("layout.b64");
+        std::fs::write(&encoded, format!("{}\n", to_base64(&value).unwrap())).unwrap();
+        assert_eq!(read_file::<Value, _>(&encoded).unwrap(), value);
+    }
+}
@@ -0,0 +1,181 @@
+//! Batch query input
+//!
+//! `batch-resolve` reads queries either as plain lines or as CSV rows of
+//! `contract,query,zero_means`. CSV lets a single run cover several contracts
+//! and override the declared zero semantics per query, which is how large query
+//! sets are usually assembled in spreadsheets.
+
+use crate::{CliError, CliResult};
+use clap::ValueEnum;
+use traverse_core::ZeroSemantics;
+
+/// Format of a batch query file
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BatchInput {
+    /// One query per line; blank lines and `#` comments are skipped
+    #[value(name = "lines")]
+    #[default]
+    Lines,
+    /// CSV rows of `contract,query,zero_means` with an optional header
+    #[value(name = "csv")]
+    Csv,
+}
+
+/// One data row of a CSV batch file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchRow {
+    /// 1-based line number in the file
+    pub line: usize,
+    /// Contract name, matched against the compiled layouts
+    pub contract: String,
+    /// Storage query
+    pub query: String,
+    /// Zero semantics override; the layout's declaration applies when empty
+    pub zero_means: Option<ZeroSemantics>,
+}
+
+/// A CSV row that could not be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchRowError {
+    /// 1-based line number in the file
+    pub line: usize,
+    /// Query column if it could be read
+    pub query: String,
+    pub error: String,
+}
+
+/// Read the queries of a line-based batch file
+pub fn parse_lines(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Parse a zero semantics name
+///
+/// Accepts the serialized variant names (`ValidZero`) as well as snake and
+/// kebab case (`valid_zero`, `valid-zero`).
+pub fn parse_zero_semantics(value: &str) -> CliResult<ZeroSemantics> {
+    let normalized: String = value
+        .chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect();
+    match normalized.as_str() {
+        "neverwritten" => Ok(ZeroSemantics::NeverWritten),
+        "explicitlyzero" => Ok(ZeroSemantics::ExplicitlyZero),
+        "cleared" => Ok(ZeroSemantics::Cleared),
+        "validzero" => Ok(ZeroSemantics::ValidZero),
+        _ => Err(CliError::InvalidArgument(format!(
+            "Unknown zero semantics '{}'. Expected never_written, explicitly_zero, cleared or valid_zero",
+            value
+        ))),
+    }
+}
+
+/// Parse a CSV batch file
+///
+/// A first row naming the columns (`contract`, `query`, optionally
+/// `zero_means`) is treated as a header and may reorder them; otherwise
+/// columns are positional. Rows are parsed independently so one bad row does
+/// not discard the rest of the file.
+pub fn parse_csv(content: &str) -> Vec<Result<BatchRow, BatchRowError>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .comment(Some(b'#'))
+        .from_reader(content.as_bytes());
+
+    let mut columns = [0usize, 1, 2];
+    let mut rows = Vec::new();
+
+    for (index, record) in reader.records().enumerate() {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map_or(index + 1, |p| p.line() as usize);
+                rows.push(Err(BatchRowError { line, query: String::new(), error: e.to_string() }));
+                continue;
+            }
+        };
+
+        if index == 0 {
+            let names: Vec<String> = record.iter().map(str::to_lowercase).collect();
+            if let Some(header) = header_columns(&names) {
+                columns = header;
+                continue;
+            }
+        }
+
+        let column = |i: usize| record.get(columns[i]).unwrap_or_default().to_string();
+        let (contract, query, zero_means) = (column(0), column(1), column(2));
+        let line = record.position().map_or(index + 1, |p| p.line() as usize);
+
+        let row = if contract.is_empty() || query.is_empty() {
+            Err("Row needs a contract and a query".to_string())
+        } else if zero_means.is_empty() {
+            Ok(None)
+        } else {
+            parse_zero_semantics(&zero_means).map(Some).map_err(|e| e.to_string())
+        };
+
+        rows.push(match row {
+            Ok(zero_means) => Ok(BatchRow { line, contract, query, zero_means }),
+            Err(error) => Err(BatchRowError { line, query, error }),
+        });
+    }
+
+    rows
+}
+
+fn header_columns(names: &[String]) -> Option<[usize; 3]> {
+    let find = |name: &str| names.iter().position(|n| n == name);
+    let contract = find("contract")?;
+    let query = find("query")?;
+    // A missing zero_means column maps past the end and reads as empty
+    let zero_means = find("zero_means").unwrap_or(usize::MAX);
+    Some([contract, query, zero_means])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv_with_header_and_row_errors() {
+        let content = "query,contract,zero_means\n\
+                       totalSupply,Token,valid_zero\n\
+                       \"balances[0x742d35Cc6634C0532925a3b8D97C2e0D8b2D9C53]\",Token,\n\
+                       # skipped\n\
+                       owner,Vault,sometimes\n\
+                       ,Vault,\n";
+        let rows = parse_csv(content);
+        assert_eq!(rows.len(), 4);
+
+        let first = rows[0].as_ref().unwrap();
+        assert_eq!((first.contract.as_str(), first.query.as_str()), ("Token", "totalSupply"));
+        assert_eq!(first.zero_means, Some(ZeroSemantics::ValidZero));
+        assert_eq!(first.line, 2);
+        assert_eq!(rows[1].as_ref().unwrap().zero_means, None);
+
+        let bad = rows[2].as_ref().unwrap_err();
+        assert_eq!(bad.query, "owner");
+        assert!(bad.error.contains("sometimes"));
+        assert!(rows[3].is_err());
+    }
+
+    #[test]
+    fn test_parse_positional_csv_and_semantics_names() {
+        let rows = parse_csv("Token,totalSupply,NeverWritten\nToken,owner\n");
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].as_ref().unwrap().zero_means, Some(ZeroSemantics::NeverWritten));
+        assert_eq!(rows[1].as_ref().unwrap().zero_means, None);
+
+        assert_eq!(parse_zero_semantics("explicitly-zero").unwrap(), ZeroSemantics::ExplicitlyZero);
+        assert_eq!(parse_lines("a\n\n# c\n b \n"), vec!["a", "b"]);
+    }
+}
@@ -0,0 +1,306 @@
+//! Disk cache for RPC responses
+//!
+//! Proofs, ABIs and account data are stored content-addressed: an entry's key
+//! (the request that produced it, as JSON) is hashed with SHA-256 and the
+//! response lives in `<dir>/<kind>/<hash>.json`. Each file records when it was
+//! written, and entries older than the TTL are treated as missing, so repeated
+//! `auto-generate` and `--dry-run` iterations only go to the network once per
+//! TTL.
+//!
+//! The cache is process-wide. A CLI installs it once when `--cache` or the
+//! profile's `[cache]` section enables it, and fetch paths go through
+//! [`cached`], which falls through to the network when no cache is installed.
+//! Watch loops, which exist to observe new state, bypass it.
+
+use crate::config::CacheSettings;
+use crate::{CliError, CliResult, CliUtils};
+use clap::Subcommand;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Cache directory used when the profile does not set one
+pub const DEFAULT_CACHE_DIR: &str = ".traverse-cache";
+
+/// TTL used when the profile does not set one
+pub const DEFAULT_CACHE_TTL_SECS: u64 = 3600;
+
+static ACTIVE: OnceLock<DiskCache> = OnceLock::new();
+
+/// Content-addressed response cache in a directory
+#[derive(Debug, Clone)]
+pub struct DiskCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+/// One cached response as stored on disk
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    kind: String,
+    key: Value,
+    /// Seconds since the Unix epoch
+    stored_at: u64,
+    value: Value,
+}
+
+/// Entry counts and sizes for `cache stats`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CacheStats {
+    pub dir: String,
+    pub ttl_secs: u64,
+    pub entries: usize,
+    /// Entries past their TTL or unreadable, removed by `cache clear --expired`
+    pub expired: usize,
+    pub bytes: u64,
+    pub kinds: BTreeMap<String, KindStats>,
+}
+
+/// Per-kind totals in [`CacheStats`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct KindStats {
+    pub entries: usize,
+    pub expired: usize,
+    pub bytes: u64,
+}
+
+impl DiskCache {
+    pub fn new(dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+        Self { dir: dir.into(), ttl }
+    }
+
+    /// Cache at the profile's directory and TTL, or the defaults
+    pub fn from_settings(settings: &CacheSettings) -> Self {
+        Self::new(
+            settings.dir.as_deref().unwrap_or(DEFAULT_CACHE_DIR),
+            Duration::from_secs(settings.ttl_secs.unwrap_or(DEFAULT_CACHE_TTL_SECS)),
+        )
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, kind: &str, key: &Value) -> PathBuf {
+        let digest = Sha256::digest(key.to_string().as_bytes());
+        self.dir.join(kind).join(format!("{}.json", hex::encode(digest)))
+    }
+
+    fn is_fresh(&self, stored_at: u64) -> bool {
+        now_secs().saturating_sub(stored_at) < self.ttl.as_secs()
+    }
+
+    /// Cached response for `key`, if present and within the TTL
+    pub fn get<T: DeserializeOwned>(&self, kind: &str, key: &Value) -> Option<T> {
+        let content = fs::read_to_string(self.path(kind, key)).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&content).ok()?;
+        // A hash collision would need a different key under the same name
+        if entry.key != *key || !self.is_fresh(entry.stored_at) {
+            return None;
+        }
+        serde_json::from_value(entry.value).ok()
+    }
+
+    /// Store a response for `key`, replacing any previous entry
+    pub fn put<T: Serialize>(&self, kind: &str, key: &Value, value: &T) -> CliResult<()> {
+        let entry = CacheEntry {
+            kind: kind.to_string(),
+            key: key.clone(),
+            stored_at: now_secs(),
+            value: serde_json::to_value(value)?,
+        };
+        let path = self.path(kind, key);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Write then rename so a concurrent reader never sees a partial entry
+        let partial = path.with_extension("json.partial");
+        fs::write(&partial, serde_json::to_vec(&entry)?)?;
+        fs::rename(&partial, &path)?;
+        Ok(())
+    }
+
+    /// Count entries by kind
+    pub fn stats(&self) -> CliResult<CacheStats> {
+        let mut stats = CacheStats {
+            dir: self.dir.display().to_string(),
+            ttl_secs: self.ttl.as_secs(),
+            ..CacheStats::default()
+        };
+        for (kind, path, size) in self.files()? {
+            let expired = !self.entry_is_fresh(&path);
+            let kind_stats = stats.kinds.entry(kind).or_default();
+            kind_stats.entries += 1;
+            kind_stats.bytes += size;
+            stats.entries += 1;
+            stats.bytes += size;
+            if expired {
+                kind_stats.expired += 1;
+                stats.expired += 1;
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Remove entries, or only expired ones, returning how many were removed
+    pub fn clear(&self, expired_only: bool) -> CliResult<usize> {
+        let mut removed = 0;
+        for (_, path, _) in self.files()? {
+            if expired_only && self.entry_is_fresh(&path) {
+                continue;
+            }
+            fs::remove_file(&path)?;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+
+    fn entry_is_fresh(&self, path: &Path) -> bool {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<CacheEntry>(&content).ok())
+            .is_some_and(|entry| self.is_fresh(entry.stored_at))
+    }
+
+    /// `(kind, path, size)` of every entry file
+    fn files(&self) -> CliResult<Vec<(String, PathBuf, u64)>> {
+        let mut files = Vec::new();
+        let Ok(kinds) = fs::read_dir(&self.dir) else {
+            return Ok(files);
+        };
+        for kind in kinds {
+            let kind = kind?;
+            if !kind.file_type()?.is_dir() {
+                continue;
+            }
+            let kind_name = kind.file_name().to_string_lossy().into_owned();
+            for file in fs::read_dir(kind.path())? {
+                let file = file?;
+                let path = file.path();
+                if path.extension().is_some_and(|ext| ext == "json") {
+                    files.push((kind_name.clone(), path, file.metadata()?.len()));
+                }
+            }
+        }
+        Ok(files)
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// Install the process-wide cache; returns false if one is already installed
+pub fn install(cache: DiskCache) -> bool {
+    ACTIVE.set(cache).is_ok()
+}
+
+/// The installed cache, if caching is enabled for this run
+pub fn active() -> Option<&'static DiskCache> {
+    ACTIVE.get()
+}
+
+/// Return the cached response for `key`, or fetch and cache it
+///
+/// Fetches wait for a slot under the `--max-concurrency` limit, cached or
+/// not. Failed fetches are not cached, and a cache that cannot be written
+/// only costs a warning.
+pub async fn cached<T, E, F, Fut>(kind: &str, key: Value, fetch: F) -> Result<T, E>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let cache = active();
+    if let Some(hit) = cache.and_then(|cache| cache.get(kind, &key)) {
+        tracing::debug!("Cache hit for {} {}", kind, key);
+        #[cfg(feature = "telemetry")]
+        traverse_core::telemetry::record_cache(kind, true);
+        return Ok(hit);
+    }
+    #[cfg(feature = "telemetry")]
+    if cache.is_some() {
+        traverse_core::telemetry::record_cache(kind, false);
+    }
+
+    #[cfg(feature = "std")]
+    let _permit = crate::pool::rpc_permit().await;
+    let value = fetch().await?;
+    let Some(cache) = cache else {
+        return Ok(value);
+    };
+    if let Err(e) = cache.put(kind, &key, &value) {
+        tracing::warn!("Failed to cache {} response: {}", kind, e);
+    }
+    Ok(value)
+}
+
+/// `cache` subcommands shared by the ecosystem CLIs
+#[derive(Subcommand, Debug, Clone)]
+pub enum CacheCommand {
+    /// Show entry counts and sizes per kind
+    Stats,
+    /// Remove cached entries
+    Clear {
+        /// Only remove entries past their TTL
+        #[arg(long)]
+        expired: bool,
+    },
+}
+
+/// Run a `cache` subcommand against the profile's cache directory
+///
+/// Works whether or not caching is enabled for normal runs.
+pub fn run_cache_command(command: &CacheCommand, settings: &CacheSettings, output: Option<&str>) -> CliResult<()> {
+    let cache = DiskCache::from_settings(settings);
+    match command {
+        CacheCommand::Stats => {
+            let stats = serde_json::to_string_pretty(&cache.stats()?).map_err(CliError::Json)?;
+            CliUtils::write_output(&format!("{}\n", stats), output)?;
+        }
+        CacheCommand::Clear { expired } => {
+            let removed = cache.clear(*expired)?;
+            eprintln!("Removed {} entries from {}", removed, cache.dir().display());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_disk_cache_round_trip_and_ttl() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = DiskCache::new(dir.path(), Duration::from_secs(60));
+        let key = json!({ "method": "eth_getProof", "params": ["0x00", ["0x01"], "0x64"] });
+
+        assert_eq!(cache.get::<Value>("proof", &key), None);
+        cache.put("proof", &key, &json!({ "value": "0x5" })).unwrap();
+        assert_eq!(cache.get::<Value>("proof", &key), Some(json!({ "value": "0x5" })));
+        assert_eq!(cache.get::<Value>("proof", &json!({ "other": 1 })), None);
+        cache.put("abi", &json!("0xab"), &json!([])).unwrap();
+
+        let stats = cache.stats().unwrap();
+        assert_eq!((stats.entries, stats.expired), (2, 0));
+        assert_eq!(stats.kinds["proof"].entries, 1);
+
+        // With a zero TTL everything is expired but still on disk
+        let expired = DiskCache::new(dir.path(), Duration::ZERO);
+        assert_eq!(expired.get::<Value>("proof", &key), None);
+        assert_eq!(expired.stats().unwrap().expired, 2);
+
+        assert_eq!(cache.clear(true).unwrap(), 0);
+        assert_eq!(expired.clear(true).unwrap(), 2);
+        assert_eq!(cache.stats().unwrap().entries, 0);
+    }
+}
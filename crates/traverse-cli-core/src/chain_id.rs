@@ -0,0 +1,107 @@
+//! Refuse endpoints that serve a different chain than expected
+//!
+//! With `--expect-chain-id`, or `chain_id.<chain>` in the active profile, the
+//! CLIs ask each RPC endpoint which chain it serves before sending it any
+//! other request and abort on a mismatch, so a profile pointing at a testnet
+//! or a fork cannot quietly produce proofs for the wrong chain. What counts
+//! as the chain ID is up to each chain: `eth_chainId` on Ethereum, the genesis
+//! hash on Solana and the node's network name on Cosmos.
+//!
+//! Like the cache, the expectation is process-wide and each endpoint is only
+//! asked once per run.
+
+use crate::{CliError, CliResult};
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+
+static EXPECTED: OnceLock<String> = OnceLock::new();
+static VERIFIED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// Set the chain ID endpoints must serve; returns false if one is already set
+pub fn expect(chain_id: impl Into<String>) -> bool {
+    EXPECTED.set(chain_id.into()).is_ok()
+}
+
+/// The expected chain ID, if checking is enabled for this run
+pub fn expected() -> Option<&'static str> {
+    EXPECTED.get().map(String::as_str)
+}
+
+/// Whether two chain IDs name the same chain
+///
+/// IDs that both parse as numbers, in decimal or `0x` hex, are compared by
+/// value so `1` matches the `0x1` an Ethereum node reports; anything else
+/// must match exactly.
+pub fn same_chain(expected: &str, actual: &str) -> bool {
+    match (parse_number(expected), parse_number(actual)) {
+        (Some(expected), Some(actual)) => expected == actual,
+        _ => expected == actual,
+    }
+}
+
+fn parse_number(id: &str) -> Option<u128> {
+    match id.strip_prefix("0x").or_else(|| id.strip_prefix("0X")) {
+        Some(hex) => u128::from_str_radix(hex, 16).ok(),
+        None => id.parse().ok(),
+    }
+}
+
+/// Fail unless `actual`, as reported by `endpoint`, is the expected chain
+pub fn check(expected: &str, endpoint: &str, actual: &str) -> CliResult<()> {
+    if same_chain(expected, actual) {
+        return Ok(());
+    }
+    Err(CliError::Configuration(format!(
+        "RPC endpoint {} serves chain {}, but chain {} is expected. Check the endpoint or --expect-chain-id",
+        endpoint, actual, expected
+    )))
+}
+
+/// Check `endpoint` against the expected chain ID, fetching its ID with `fetch`
+///
+/// Does nothing when no chain ID is expected or the endpoint already passed.
+pub async fn verify<F, Fut, E>(endpoint: &str, fetch: F) -> CliResult<()>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<String, E>>,
+    E: Display,
+{
+    let Some(expected) = expected() else {
+        return Ok(());
+    };
+    let verified = || VERIFIED.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if verified().contains(endpoint) {
+        return Ok(());
+    }
+
+    let actual = {
+        #[cfg(feature = "std")]
+        let _permit = crate::pool::rpc_permit().await;
+        fetch()
+            .await
+            .map_err(|e| CliError::Processing(format!("Failed to read the chain ID of {}: {}", endpoint, e)))?
+    };
+    check(expected, endpoint, &actual)?;
+    tracing::debug!("{} serves chain {}", endpoint, actual);
+    verified().insert(endpoint.to_string());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_id_comparison() {
+        assert!(same_chain("1", "0x1"));
+        assert!(same_chain("0xaa36a7", "11155111"));
+        assert!(!same_chain("1", "0xaa36a7"));
+        assert!(same_chain("neutron-1", "neutron-1"));
+        assert!(!same_chain("neutron-1", "pion-1"));
+
+        let error = check("1", "https://sepolia.example", "0xaa36a7").unwrap_err().to_string();
+        assert!(error.contains("https://sepolia.example serves chain 0xaa36a7"));
+    }
+}
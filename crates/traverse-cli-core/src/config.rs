@@ -0,0 +1,437 @@
+//! `traverse.toml` configuration file with named profiles
+//!
+//! A profile bundles RPC endpoints, API keys, an output format and cache
+//! settings so they don't have to be repeated on every command line. Each
+//! chain has its own CLI, so profiles do not pick a chain.
+//! Environment variables override profile values, which keeps credentials out
+//! of both the config file and shell history.
+//!
+//! The address book maps names to a chain, an address and optionally a compiled
+//! layout. Any command taking an address also accepts an address book name.
+//! Top-level entries are shared by all profiles; a profile's own entries take
+//! precedence, e.g. to point the same name at a testnet deployment. Relative
+//! layout paths are resolved against the config file's directory.
+//!
+//! ```toml
+//! default_profile = "mainnet"
+//!
+//! [profiles.mainnet]
+//! format = "coprocessor-json"
+//!
+//! [profiles.mainnet.rpc]
+//! ethereum = "https://eth.llamarpc.com"
+//! solana = "https://api.mainnet-beta.solana.com"
+//!
+//! [profiles.mainnet.rpc_fallbacks]
+//! ethereum = ["https://ethereum-rpc.publicnode.com", "https://cloudflare-eth.com"]
+//!
+//! [profiles.mainnet.retry.ethereum]
+//! max_retries = 5
+//! initial_backoff_ms = 200
+//!
+//! [profiles.mainnet.api_keys]
+//! etherscan = "..."
+//!
+//! [profiles.mainnet.cache]
+//! enabled = true
+//! dir = ".traverse-cache"
+//! ttl_secs = 3600
+//!
+//! [address_book.usdt-mainnet]
+//! chain = "ethereum"
+//! address = "0xdAC17F958D2ee523a2206206994597C13D831ec7"
+//! layout = "layouts/usdt.json"
+//! ```
+//!
+//! Environment overrides (applied after the profile is selected):
+//!
+//! - `TRAVERSE_CONFIG`: config file path
+//! - `TRAVERSE_PROFILE`: profile name
+//! - `TRAVERSE_RPC_<CHAIN>`: RPC endpoint for a chain, e.g. `TRAVERSE_RPC_ETHEREUM`
+//! - `TRAVERSE_API_KEY_<NAME>`: API key, e.g. `TRAVERSE_API_KEY_ETHERSCAN`
+//! - `TRAVERSE_FORMAT`
+//! - `TRAVERSE_CACHE`, `TRAVERSE_CACHE_DIR`, `TRAVERSE_CACHE_TTL`
+
+use crate::{CliError, CliResult, OutputFormat};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Default config file name looked up in the working directory
+pub const CONFIG_FILE_NAME: &str = "traverse.toml";
+
+/// Profile used when none is selected and the file does not name a default
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Parsed `traverse.toml`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TraverseConfig {
+    /// Profile used when `--profile` and `TRAVERSE_PROFILE` are not given
+    #[serde(default)]
+    pub default_profile: Option<String>,
+    /// Named profiles
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+    /// Address book entries shared by all profiles
+    #[serde(default)]
+    pub address_book: BTreeMap<String, AddressEntry>,
+}
+
+/// A named set of defaults for CLI runs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Profile {
+    /// RPC endpoints keyed by chain name
+    #[serde(default)]
+    pub rpc: BTreeMap<String, String>,
+    /// Endpoints tried in order when a chain's RPC endpoint fails, keyed by chain name
+    #[serde(default)]
+    pub rpc_fallbacks: BTreeMap<String, Vec<String>>,
+    /// Retries of transient RPC failures, keyed by chain name
+    #[serde(default)]
+    pub retry: BTreeMap<String, RetrySettings>,
+    /// API keys keyed by service name (etherscan, alchemy, ...)
+    #[serde(default)]
+    pub api_keys: BTreeMap<String, String>,
+    /// Chain IDs RPC endpoints must serve, keyed by chain name
+    #[serde(default)]
+    pub chain_id: BTreeMap<String, String>,
+    /// Output format used when `--format` is not given
+    #[serde(default)]
+    pub format: Option<OutputFormat>,
+    /// Cache settings
+    #[serde(default)]
+    pub cache: CacheSettings,
+    /// Address book entries, overriding the shared ones
+    #[serde(default)]
+    pub address_book: BTreeMap<String, AddressEntry>,
+}
+
+/// A named address in the address book
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressEntry {
+    /// Chain the address lives on (ethereum, solana, ...)
+    pub chain: String,
+    /// Contract address, program ID or account
+    pub address: String,
+    /// Compiled layout for the contract
+    #[serde(default)]
+    pub layout: Option<String>,
+}
+
+/// Cache settings for a profile
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheSettings {
+    /// Whether caching is enabled
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    /// Cache directory
+    #[serde(default)]
+    pub dir: Option<String>,
+    /// Time-to-live for cached entries in seconds
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+}
+
+/// Retry settings for a chain's RPC requests; unset values use the chain CLI's defaults
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetrySettings {
+    /// Rounds over all endpoints after the first one fails
+    #[serde(default)]
+    pub max_retries: Option<u32>,
+    /// Pause before the first retry, doubled for each later one
+    #[serde(default)]
+    pub initial_backoff_ms: Option<u64>,
+    /// Longest pause between retries
+    #[serde(default)]
+    pub max_backoff_ms: Option<u64>,
+}
+
+impl TraverseConfig {
+    /// Parse a config from TOML text
+    pub fn parse(content: &str) -> CliResult<Self> {
+        toml::from_str(content)
+            .map_err(|e| CliError::Configuration(format!("Invalid {}: {}", CONFIG_FILE_NAME, e)))
+    }
+
+    /// Load the config file
+    ///
+    /// An explicit path must exist. Otherwise `TRAVERSE_CONFIG`, `./traverse.toml`
+    /// and `$HOME/.config/traverse/traverse.toml` are tried in order, and an
+    /// empty config is returned when none of them exist.
+    pub fn load(path: Option<&Path>) -> CliResult<Self> {
+        let Some(path) = path.map(Path::to_path_buf).or_else(Self::discover) else {
+            return Ok(Self::default());
+        };
+
+        let mut config = Self::parse(&crate::CliUtils::read_file(&path)?)?;
+        config.resolve_layout_paths(path.parent().unwrap_or(Path::new("")));
+        Ok(config)
+    }
+
+    /// Make relative address book layout paths relative to `base`
+    fn resolve_layout_paths(&mut self, base: &Path) {
+        let books = std::iter::once(&mut self.address_book)
+            .chain(self.profiles.values_mut().map(|p| &mut p.address_book));
+        for entry in books.flat_map(BTreeMap::values_mut) {
+            if let Some(layout) = entry.layout.as_mut().filter(|l| Path::new(l.as_str()).is_relative()) {
+                *layout = base.join(layout.as_str()).to_string_lossy().into_owned();
+            }
+        }
+    }
+
+    /// Find the config file from the environment and standard locations
+    fn discover() -> Option<PathBuf> {
+        if let Ok(path) = std::env::var("TRAVERSE_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+
+        let local = PathBuf::from(CONFIG_FILE_NAME);
+        if local.exists() {
+            return Some(local);
+        }
+
+        let home = std::env::var("HOME").ok()?;
+        let user = Path::new(&home).join(".config").join("traverse").join(CONFIG_FILE_NAME);
+        user.exists().then_some(user)
+    }
+
+    /// Select a profile and apply environment overrides
+    ///
+    /// The profile name comes from `name`, then `TRAVERSE_PROFILE`, then
+    /// `default_profile`. Naming a profile that does not exist is an error;
+    /// falling back to an absent `default` profile yields an empty one.
+    pub fn profile(&self, name: Option<&str>) -> CliResult<Profile> {
+        let requested = name
+            .map(str::to_string)
+            .or_else(|| std::env::var("TRAVERSE_PROFILE").ok())
+            .or_else(|| self.default_profile.clone());
+
+        let mut profile = match requested {
+            Some(name) => self.profiles.get(&name).cloned().ok_or_else(|| {
+                CliError::Configuration(format!("Profile '{}' not found in {}", name, CONFIG_FILE_NAME))
+            })?,
+            None => self.profiles.get(DEFAULT_PROFILE).cloned().unwrap_or_default(),
+        };
+
+        for (name, entry) in &self.address_book {
+            profile.address_book.entry(name.clone()).or_insert_with(|| entry.clone());
+        }
+
+        profile.apply_env_overrides(std::env::vars())?;
+        Ok(profile)
+    }
+}
+
+impl Profile {
+    /// Apply `TRAVERSE_*` overrides from an iterator of environment variables
+    pub fn apply_env_overrides<I>(&mut self, vars: I) -> CliResult<()>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        for (key, value) in vars {
+            if let Some(chain) = key.strip_prefix("TRAVERSE_RPC_") {
+                self.rpc.insert(chain.to_lowercase(), value);
+            } else if let Some(name) = key.strip_prefix("TRAVERSE_API_KEY_") {
+                self.api_keys.insert(name.to_lowercase(), value);
+            } else if let Some(chain) = key.strip_prefix("TRAVERSE_CHAIN_ID_") {
+                self.chain_id.insert(chain.to_lowercase(), value);
+            } else {
+                match key.as_str() {
+                    "TRAVERSE_FORMAT" => {
+                        let format = OutputFormat::from_str(&value, true).map_err(|_| {
+                            CliError::Configuration(format!("Invalid TRAVERSE_FORMAT: {}", value))
+                        })?;
+                        self.format = Some(format);
+                    }
+                    "TRAVERSE_CACHE" => {
+                        self.cache.enabled = Some(matches!(value.as_str(), "1" | "true" | "yes"));
+                    }
+                    "TRAVERSE_CACHE_DIR" => self.cache.dir = Some(value),
+                    "TRAVERSE_CACHE_TTL" => {
+                        let ttl = value.parse().map_err(|_| {
+                            CliError::Configuration(format!("Invalid TRAVERSE_CACHE_TTL: {}", value))
+                        })?;
+                        self.cache.ttl_secs = Some(ttl);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// RPC endpoint for a chain
+    pub fn rpc_url(&self, chain: &str) -> Option<&str> {
+        self.rpc.get(chain).map(String::as_str)
+    }
+
+    /// Fallback RPC endpoints for a chain, in the order they are tried
+    pub fn rpc_fallbacks(&self, chain: &str) -> &[String] {
+        self.rpc_fallbacks.get(chain).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Retry settings for a chain's RPC requests
+    pub fn retry(&self, chain: &str) -> RetrySettings {
+        self.retry.get(chain).cloned().unwrap_or_default()
+    }
+
+    /// API key for a service
+    pub fn api_key(&self, service: &str) -> Option<&str> {
+        self.api_keys.get(service).map(String::as_str)
+    }
+
+    /// Chain ID the endpoints for a chain must serve
+    pub fn chain_id(&self, chain: &str) -> Option<&str> {
+        self.chain_id.get(chain).map(String::as_str)
+    }
+
+    /// Resolve an RPC endpoint from an explicit argument or the profile
+    pub fn require_rpc(&self, explicit: Option<&str>, chain: &str) -> CliResult<String> {
+        explicit
+            .or_else(|| self.rpc_url(chain))
+            .map(str::to_string)
+            .ok_or_else(|| {
+                CliError::InvalidArgument(format!(
+                    "No RPC endpoint for {}. Pass --rpc or set rpc.{} in the active profile",
+                    chain, chain
+                ))
+            })
+    }
+
+    /// Resolve an address book name, or pass a literal address through
+    ///
+    /// Values that are not in the address book are returned unchanged with no
+    /// layout. Naming an entry that belongs to another chain is an error.
+    pub fn resolve_address(&self, value: &str, chain: &str) -> CliResult<AddressEntry> {
+        match self.address_book.get(value) {
+            Some(entry) if entry.chain == chain => Ok(entry.clone()),
+            Some(entry) => Err(CliError::InvalidArgument(format!(
+                "Address book entry '{}' is on {}, not {}",
+                value, entry.chain, chain
+            ))),
+            None => Ok(AddressEntry {
+                chain: chain.to_string(),
+                address: value.to_string(),
+                layout: None,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+default_profile = "mainnet"
+
+[profiles.mainnet]
+format = "coprocessor-json"
+
+[profiles.mainnet.rpc]
+ethereum = "https://eth.example"
+
+[profiles.mainnet.rpc_fallbacks]
+ethereum = ["https://fallback-1.example", "https://fallback-2.example"]
+
+[profiles.mainnet.retry.ethereum]
+max_retries = 5
+initial_backoff_ms = 100
+
+[profiles.mainnet.chain_id]
+ethereum = "1"
+
+[profiles.mainnet.cache]
+enabled = true
+ttl_secs = 60
+
+[profiles.testnet.rpc]
+ethereum = "https://sepolia.example"
+
+[profiles.testnet.address_book.usdt]
+chain = "ethereum"
+address = "0x7169D38820dfd117C3FA1f22a697dBA58d90BA06"
+
+[address_book.usdt]
+chain = "ethereum"
+address = "0xdAC17F958D2ee523a2206206994597C13D831ec7"
+layout = "layouts/usdt.json"
+"#;
+
+    #[test]
+    fn test_parse_profiles() {
+        let config = TraverseConfig::parse(SAMPLE).unwrap();
+        assert_eq!(config.default_profile.as_deref(), Some("mainnet"));
+
+        let mainnet = &config.profiles["mainnet"];
+        assert_eq!(mainnet.rpc_url("ethereum"), Some("https://eth.example"));
+        assert!(matches!(mainnet.format, Some(OutputFormat::CoprocessorJson)));
+        assert_eq!(mainnet.cache.enabled, Some(true));
+        assert_eq!(mainnet.cache.ttl_secs, Some(60));
+        assert_eq!(mainnet.chain_id("ethereum"), Some("1"));
+        assert_eq!(mainnet.rpc_fallbacks("ethereum"), ["https://fallback-1.example", "https://fallback-2.example"]);
+        assert_eq!(mainnet.retry("ethereum").max_retries, Some(5));
+        assert_eq!(mainnet.retry("ethereum").max_backoff_ms, None);
+
+        let testnet = &config.profiles["testnet"];
+        assert_eq!(testnet.rpc_url("ethereum"), Some("https://sepolia.example"));
+        assert!(testnet.rpc_fallbacks("ethereum").is_empty());
+        assert_eq!(testnet.retry("ethereum"), RetrySettings::default());
+        assert!(testnet.format.is_none());
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let mut profile = TraverseConfig::parse(SAMPLE).unwrap().profiles["mainnet"].clone();
+        profile
+            .apply_env_overrides(vec![
+                ("TRAVERSE_RPC_ETHEREUM".to_string(), "https://override.example".to_string()),
+                ("TRAVERSE_API_KEY_ETHERSCAN".to_string(), "secret".to_string()),
+                ("TRAVERSE_FORMAT".to_string(), "toml".to_string()),
+                ("TRAVERSE_CACHE_TTL".to_string(), "120".to_string()),
+                ("TRAVERSE_CHAIN_ID_SOLANA".to_string(), "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d".to_string()),
+                ("UNRELATED".to_string(), "ignored".to_string()),
+            ])
+            .unwrap();
+
+        assert_eq!(profile.rpc_url("ethereum"), Some("https://override.example"));
+        assert_eq!(profile.api_key("etherscan"), Some("secret"));
+        assert!(matches!(profile.format, Some(OutputFormat::Toml)));
+        assert_eq!(profile.cache.ttl_secs, Some(120));
+        assert_eq!(profile.chain_id("solana"), Some("5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d"));
+
+        let invalid = profile.apply_env_overrides(vec![("TRAVERSE_FORMAT".to_string(), "xml".to_string())]);
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn test_missing_profile_and_rpc_fallback() {
+        let config = TraverseConfig::parse(SAMPLE).unwrap();
+        assert!(config.profile(Some("staging")).is_err());
+
+        let profile = Profile::default();
+        assert!(profile.require_rpc(None, "solana").is_err());
+        assert_eq!(profile.require_rpc(Some("http://x"), "solana").unwrap(), "http://x");
+    }
+
+    #[test]
+    fn test_address_book() {
+        let mut config = TraverseConfig::parse(SAMPLE).unwrap();
+        config.resolve_layout_paths(Path::new("conf"));
+
+        let mainnet = config.profile(Some("mainnet")).unwrap();
+        let usdt = mainnet.resolve_address("usdt", "ethereum").unwrap();
+        assert_eq!(usdt.address, "0xdAC17F958D2ee523a2206206994597C13D831ec7");
+        assert_eq!(usdt.layout.as_deref().map(Path::new), Some(Path::new("conf/layouts/usdt.json")));
+        assert!(mainnet.resolve_address("usdt", "solana").is_err());
+
+        let raw = mainnet.resolve_address("0x1234", "ethereum").unwrap();
+        assert_eq!((raw.address.as_str(), raw.layout), ("0x1234", None));
+
+        let testnet = config.profile(Some("testnet")).unwrap();
+        let usdt = testnet.resolve_address("usdt", "ethereum").unwrap();
+        assert_eq!(usdt.address, "0x7169D38820dfd117C3FA1f22a697dBA58d90BA06");
+        assert!(usdt.layout.is_none());
+    }
+}
@@ -7,6 +7,7 @@ use crate::OutputFormat;
 use anyhow::Result;
 use base64::{engine::general_purpose::STANDARD, Engine};
 use serde::{Deserialize, Serialize};
+use std::io::Write;
 use std::path::Path;
 use traverse_core::{Key, LayoutInfo, StaticKeyPath};
 
@@ -36,6 +37,23 @@ pub fn write_output(content: &str, output_path: Option<&Path>) -> Result<()> {
     Ok(())
 }
 
+/// Write a command result as `format`, or through `template` when one is given
+///
+/// Binary output is written as raw artifact bytes; every other format, and
+/// any template, is text written with [`write_output`]. Commands write their
+/// results through this so every `--format` works for every command.
+pub fn write_result(
+    value: &serde_json::Value,
+    format: &OutputFormat,
+    template: Option<&Path>,
+    output_path: Option<&Path>,
+) -> Result<()> {
+    match (template, format) {
+        (None, OutputFormat::Binary) => Ok(crate::artifact::write(value, output_path)?),
+        _ => write_output(&crate::template::render_or_format(value, format, template)?, output_path),
+    }
+}
+
 /// Helper function to load layout from file
 pub fn load_layout(layout_path: &Path) -> Result<LayoutInfo> {
     let content = std::fs::read_to_string(layout_path)?;
@@ -127,6 +145,10 @@ pub fn format_storage_path(
                 Ok(format!("{}: {}", path.name, STANDARD.encode(&binary_data)))
             }
         },
+        OutputFormat::Ndjson => {
+            let coprocessor_payload = path_to_coprocessor_query(path, query);
+            serde_json::to_string(&coprocessor_payload).map_err(Into::into)
+        }
     }
 }
 
@@ -155,29 +177,82 @@ pub fn format_storage_paths(paths: &[StaticKeyPath], format: &OutputFormat) -> R
             };
             toml::to_string_pretty(&output).map_err(Into::into)
         }
-        OutputFormat::Binary => {
-            let coprocessor_payloads: Vec<CoprocessorStorageQuery> = paths
-                .iter()
-                .map(|path| path_to_coprocessor_query(path, path.name))
-                .collect();
-            let binary_data = bincode::serialize(&coprocessor_payloads)?;
-            Ok(format!(
-                "Binary data: {} bytes\nBase64: {}",
-                binary_data.len(),
-                STANDARD.encode(&binary_data)
-            ))
-        }
+        OutputFormat::Binary => Err(anyhow::anyhow!(
+            "Binary output is raw bytes; write it with artifact::write"
+        )),
         OutputFormat::Base64 => {
             let coprocessor_payloads: Vec<CoprocessorStorageQuery> = paths
                 .iter()
                 .map(|path| path_to_coprocessor_query(path, path.name))
                 .collect();
-            let binary_data = bincode::serialize(&coprocessor_payloads)?;
-            Ok(format!(
-                "Binary data: {} bytes\nBase64: {}",
-                binary_data.len(),
-                STANDARD.encode(&binary_data)
-            ))
+            crate::artifact::to_base64(&coprocessor_payloads).map_err(Into::into)
         }
+        OutputFormat::Ndjson => {
+            let mut lines = String::new();
+            for path in paths {
+                lines.push_str(&serde_json::to_string(&path_to_coprocessor_query(path, path.name))?);
+                lines.push('\n');
+            }
+            Ok(lines)
+        }
+    }
+}
+
+/// Streaming writer for newline-delimited JSON
+///
+/// Each record is written as a single line and flushed immediately, so
+/// consumers can process results while a batch is still running and records
+/// written before a failure are kept.
+pub struct NdjsonWriter {
+    out: Box<dyn Write + Send>,
+}
+
+impl NdjsonWriter {
+    /// Create a writer for a file, or stdout when no path is given
+    pub fn create(output_path: Option<&Path>) -> Result<Self> {
+        let out: Box<dyn Write + Send> = match output_path {
+            Some(path) => Box::new(std::fs::File::create(path)?),
+            None => Box::new(std::io::stdout()),
+        };
+        Ok(Self { out })
+    }
+
+    /// Wrap an arbitrary writer
+    pub fn from_writer<W: Write + Send + 'static>(writer: W) -> Self {
+        Self { out: Box::new(writer) }
+    }
+
+    /// Write one record as a JSON line
+    pub fn write_record<T: Serialize>(&mut self, record: &T) -> Result<()> {
+        serde_json::to_writer(&mut self.out, record)?;
+        self.out.write_all(b"\n")?;
+        self.out.flush()?;
+        Ok(())
     }
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_write_result_handles_every_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let value = json!({ "query": "totalSupply", "storage_key": "00" });
+
+        let binary = dir.path().join("result.bin");
+        write_result(&value, &OutputFormat::Binary, None, Some(&binary)).unwrap();
+        assert_eq!(crate::artifact::read_file::<serde_json::Value, _>(&binary).unwrap(), value);
+
+        let toml = dir.path().join("result.toml");
+        write_result(&value, &OutputFormat::Toml, None, Some(&toml)).unwrap();
+        assert!(std::fs::read_to_string(&toml).unwrap().contains("query = \"totalSupply\""));
+
+        // A template takes precedence over the format, binary included
+        let template = dir.path().join("result.txt");
+        std::fs::write(&template, "{{ query }}").unwrap();
+        let rendered = dir.path().join("rendered.txt");
+        write_result(&value, &OutputFormat::Binary, Some(&template), Some(&rendered)).unwrap();
+        assert_eq!(std::fs::read_to_string(&rendered).unwrap(), "totalSupply");
+    }
+}
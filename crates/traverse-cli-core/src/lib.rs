@@ -5,15 +5,36 @@
 //! keeping the CLI binaries isolated to prevent dependency conflicts.
 
 use clap::{Parser, Subcommand};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::fs;
 use std::path::Path;
-use base64::Engine;
 
+pub mod artifact;
+pub mod batch;
+pub mod cache;
+pub mod chain_id;
+pub mod config;
 pub mod formatters;
+pub mod plan;
+#[cfg(feature = "std")]
+pub mod pool;
+pub mod project;
+pub mod schema;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod spot_check;
+#[cfg(feature = "telemetry")]
+pub mod telemetry;
+pub mod template;
+#[cfg(feature = "std")]
+pub mod watch;
+pub mod witness;
+
+pub use config::{AddressEntry, Profile, RetrySettings, TraverseConfig};
 
 /// Common CLI arguments shared across all ecosystems
-#[derive(Parser)]
+#[derive(Debug, Parser)]
 #[command(name = "traverse-cli")]
 #[command(about = "Chain-independent ZK storage path generator")]
 #[command(version)]
@@ -22,17 +43,79 @@ pub struct CommonArgs {
     #[arg(short, long)]
     pub verbose: bool,
     
-    /// Output format (traverse, coprocessor-json, toml, binary, base64)
-    #[arg(short, long, default_value = "traverse")]
-    pub format: OutputFormat,
+    /// Output format (traverse, coprocessor-json, toml, binary, base64, ndjson)
+    ///
+    /// Defaults to the active profile's format, then `traverse`.
+    #[arg(short, long)]
+    pub format: Option<OutputFormat>,
     
     /// Output file (stdout if not specified)
     #[arg(short, long)]
     pub output: Option<String>,
+
+    /// Configuration profile from traverse.toml
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+
+    /// Path to the configuration file (defaults to ./traverse.toml)
+    #[arg(long, global = true)]
+    pub config: Option<String>,
+
+    /// Cache RPC responses on disk, overriding the profile's `cache.enabled`
+    #[arg(long, global = true, overrides_with = "no_cache")]
+    pub cache: bool,
+
+    /// Don't use the RPC cache for this run
+    #[arg(long, global = true, overrides_with = "cache")]
+    pub no_cache: bool,
+
+    /// Abort unless RPC endpoints serve this chain, overriding the profile's `chain_id`
+    #[arg(long, global = true)]
+    pub expect_chain_id: Option<String>,
+}
+
+impl CommonArgs {
+    /// Load the selected profile from the configuration file
+    pub fn load_profile(&self) -> CliResult<Profile> {
+        let config = TraverseConfig::load(self.config.as_deref().map(Path::new))?;
+        config.profile(self.profile.as_deref())
+    }
+
+    /// `--cache` or `--no-cache`, if either was given
+    pub fn cache_flag(&self) -> Option<bool> {
+        match (self.cache, self.no_cache) {
+            (true, _) => Some(true),
+            (_, true) => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Install the disk cache when the flags or the profile enable it
+    pub fn install_cache(&self, profile: &Profile) {
+        if self.cache_flag().or(profile.cache.enabled).unwrap_or(false) {
+            cache::install(cache::DiskCache::from_settings(&profile.cache));
+        }
+    }
+
+    /// Expect `--expect-chain-id`, else the profile's chain ID for `chain`
+    pub fn install_chain_id(&self, profile: &Profile, chain: &str) {
+        if let Some(chain_id) = self.expect_chain_id.as_deref().or_else(|| profile.chain_id(chain)) {
+            chain_id::expect(chain_id);
+        }
+    }
+
+    /// Output format from `--format`, falling back to the profile
+    pub fn output_format(&self, profile: &Profile) -> OutputFormat {
+        self.format
+            .clone()
+            .or_else(|| profile.format.clone())
+            .unwrap_or_default()
+    }
 }
 
 /// Output format options
-#[derive(Clone, Debug, clap::ValueEnum, Default)]
+#[derive(Clone, Debug, clap::ValueEnum, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum OutputFormat {
     /// Traverse native format
     #[value(name = "traverse")]
@@ -44,12 +127,15 @@ pub enum OutputFormat {
     /// TOML format
     #[value(name = "toml")]
     Toml,
-    /// Binary format for performance
+    /// CBOR artifact with a `TRVS` header, written as raw bytes
     #[value(name = "binary")]
     Binary,
-    /// Base64 encoded binary format
+    /// Base64-encoded binary artifact
     #[value(name = "base64")]
     Base64,
+    /// Newline-delimited JSON, one record per line (streamed by batch commands)
+    #[value(name = "ndjson")]
+    Ndjson,
 }
 
 /// Common subcommands available across ecosystems
@@ -168,19 +254,29 @@ impl CliUtils {
                 };
                 Ok(toml_value)
             }
-            OutputFormat::Binary => {
-                // For binary output, serialize to binary and encode as base64
-                let binary_data = bincode::serialize(value).map_err(|e| CliError::Processing(e.to_string()))?;
-                Ok(format!("Binary data: {} bytes\nBase64: {}", binary_data.len(), base64::engine::general_purpose::STANDARD.encode(&binary_data)))
-            }
-            OutputFormat::Base64 => {
-                // For base64 output, serialize to binary and encode as base64
-                let binary_data = bincode::serialize(value).map_err(|e| CliError::Processing(e.to_string()))?;
-                Ok(base64::engine::general_purpose::STANDARD.encode(&binary_data))
+            OutputFormat::Binary => Err(CliError::InvalidArgument(
+                "Binary output is raw bytes; write it with CliUtils::write_value".to_string(),
+            )),
+            OutputFormat::Base64 => artifact::to_base64(value),
+            OutputFormat::Ndjson => {
+                let mut line = serde_json::to_string(value).map_err(CliError::Json)?;
+                line.push('\n');
+                Ok(line)
             }
         }
     }
     
+    /// Format a value and write it to a file or stdout
+    ///
+    /// Binary output is written as raw artifact bytes; every other format goes
+    /// through [`CliUtils::format_json`].
+    pub fn write_value(value: &Value, format: &OutputFormat, output_path: Option<&str>) -> CliResult<()> {
+        match format {
+            OutputFormat::Binary => artifact::write(value, output_path.map(Path::new)),
+            _ => Self::write_output(&Self::format_json(value, format)?, output_path),
+        }
+    }
+    
     /// Load configuration from a JSON file
     pub fn load_config<P: AsRef<Path>>(path: P) -> CliResult<Value> {
         let content = Self::read_file(path)?;
@@ -0,0 +1,100 @@
+//! Dry-run plans of external requests
+//!
+//! With `--dry-run`, commands record every RPC request the real run would
+//! send instead of sending it, so reviewers can approve the exact external
+//! interactions before a production run. Each call carries the endpoint, the
+//! JSON-RPC method and the params it would be sent with.
+//!
+//! Some params are only known from an earlier response, such as the block
+//! number `latest` resolves to. These are written as references of the form
+//! `$<call>.<path>`, e.g. `$0.number` for the `number` field of call 0's
+//! result, optionally followed by a `+N` or `-N` offset. Plans assume a cold
+//! cache; with `--cache`, some of the calls may be served from disk instead.
+
+use crate::CliResult;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// File name of the plan written next to a dry run's other outputs
+pub const PLAN_FILE: &str = "plan.json";
+
+/// Requests a run would make, in the order it would make them
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RpcPlan {
+    pub calls: Vec<PlannedCall>,
+}
+
+/// One JSON-RPC request in a plan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedCall {
+    pub endpoint: String,
+    pub method: String,
+    pub params: Value,
+    /// What the response is used for
+    pub purpose: String,
+}
+
+impl RpcPlan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a call, returning its index for references from later calls
+    pub fn push(&mut self, endpoint: &str, method: &str, params: Value, purpose: impl Into<String>) -> usize {
+        self.calls.push(PlannedCall {
+            endpoint: endpoint.to_string(),
+            method: method.to_string(),
+            params,
+            purpose: purpose.into(),
+        });
+        self.calls.len() - 1
+    }
+
+    /// Reference to a field of an earlier call's result
+    pub fn reference(call: usize, path: &str) -> String {
+        format!("${}.{}", call, path)
+    }
+
+    /// The plan with per-endpoint and per-method totals, as written to disk
+    pub fn to_value(&self) -> Value {
+        let mut endpoints: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut methods: BTreeMap<&str, usize> = BTreeMap::new();
+        for call in &self.calls {
+            *endpoints.entry(&call.endpoint).or_default() += 1;
+            *methods.entry(&call.method).or_default() += 1;
+        }
+        json!({
+            "dry_run": true,
+            "total_calls": self.calls.len(),
+            "endpoints": endpoints,
+            "methods": methods,
+            "calls": self.calls,
+        })
+    }
+
+    pub fn write(&self, path: &Path) -> CliResult<()> {
+        std::fs::write(path, serde_json::to_string_pretty(&self.to_value())?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_references_and_totals() {
+        let mut plan = RpcPlan::new();
+        let header = plan.push("http://rpc", "eth_getBlockByNumber", json!(["latest", false]), "Pin the block");
+        let number = RpcPlan::reference(header, "number");
+        plan.push("http://rpc", "eth_getProof", json!(["0xaa", ["0x01"], number]), "Storage proof for totalSupply");
+
+        let value = plan.to_value();
+        assert_eq!(value["total_calls"], 2);
+        assert_eq!(value["endpoints"]["http://rpc"], 2);
+        assert_eq!(value["methods"]["eth_getProof"], 1);
+        assert_eq!(value["calls"][1]["params"][2], "$0.number");
+    }
+}
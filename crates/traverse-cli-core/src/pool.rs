@@ -0,0 +1,504 @@
+//! Bounded task pool for `batch-generate`
+//!
+//! Entries run on up to `parallel` workers, each with its own progress bar.
+//! Outcomes are collected into a [`BatchSummary`] that groups failed entries
+//! by error message, so a rate-limited or misconfigured endpoint shows up as
+//! one line instead of one per entry.
+//!
+//! Worker count and RPC load are bounded separately: `max_concurrency` caps
+//! how many requests are in flight across all workers, and every network
+//! fetch takes a permit with [`rpc_permit`] first. A pool of eight workers
+//! under a limit of two still overlaps layout compilation and key resolution
+//! with the requests it is allowed to make.
+//!
+//! Long batches also keep a checkpoint, `batch-checkpoint.json`, rewritten
+//! as each entry succeeds. With `resume`, entries the checkpoint lists are
+//! skipped as long as their config is unchanged and their output directory
+//! still holds a summary, so a batch that crashed or was aborted by rate
+//! limits picks up where it stopped. Failed entries are always retried.
+//!
+//! With `ndjson`, each entry's report is also written to stdout as one
+//! NDJSON line the moment the entry finishes, so a pipeline can consume a
+//! batch while it runs instead of waiting for the summary.
+
+use crate::formatters::NdjsonWriter;
+use crate::project::{entry_name, EntryReport};
+use crate::{CliError, CliResult};
+use futures_util::stream::{self, StreamExt};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use sha2::{Digest, Sha256};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// File name of the batch summary inside the output directory
+pub const BATCH_SUMMARY: &str = "batch-summary.json";
+
+/// File name of the checkpoint inside the output directory
+pub const BATCH_CHECKPOINT: &str = "batch-checkpoint.json";
+
+static RPC_LIMIT: OnceLock<Semaphore> = OnceLock::new();
+
+/// How a batch is spread over workers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolOptions {
+    /// Entries generated at once
+    pub parallel: usize,
+    /// RPC requests in flight at once, across all workers
+    pub max_concurrency: Option<usize>,
+    /// Draw per-entry progress bars on stderr
+    pub progress: bool,
+    /// Skip entries a previous run's checkpoint records as completed
+    pub resume: bool,
+    /// Stream each entry's report to stdout as an NDJSON line when it finishes
+    pub ndjson: bool,
+}
+
+impl Default for PoolOptions {
+    fn default() -> Self {
+        Self { parallel: 1, max_concurrency: None, progress: false, resume: false, ndjson: false }
+    }
+}
+
+/// Outcome of a batch, written to `batch-summary.json`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BatchSummary {
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub parallel: usize,
+    pub max_concurrency: Option<usize>,
+    pub elapsed_ms: u64,
+    /// Entries taken from the checkpoint instead of being generated again
+    #[serde(default)]
+    pub resumed: usize,
+    pub entries: Vec<EntryReport>,
+    /// Names of failed entries grouped by error message
+    pub errors: BTreeMap<String, Vec<String>>,
+}
+
+impl BatchSummary {
+    pub fn is_ok(&self) -> bool {
+        self.failed == 0
+    }
+}
+
+/// Entries a batch has completed, written to `batch-checkpoint.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Checkpoint {
+    /// Settings the batch ran with; a checkpoint from other settings is not resumed
+    pub settings: Value,
+    /// Completed entries by name
+    pub completed: BTreeMap<String, CompletedEntry>,
+}
+
+/// One entry recorded in a [`Checkpoint`]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CompletedEntry {
+    /// SHA-256 of the entry's config, so edited entries are generated again
+    pub fingerprint: String,
+    pub report: EntryReport,
+}
+
+impl Checkpoint {
+    /// Checkpoint in `output_dir` written under `settings`, if there is one
+    pub fn load(output_dir: &Path, settings: &Value) -> Option<Self> {
+        let content = fs::read_to_string(output_dir.join(BATCH_CHECKPOINT)).ok()?;
+        let checkpoint: Self = match serde_json::from_str(&content) {
+            Ok(checkpoint) => checkpoint,
+            Err(e) => {
+                eprintln!("Warning: ignoring unreadable checkpoint: {}", e);
+                return None;
+            }
+        };
+        if checkpoint.settings != *settings {
+            eprintln!("Warning: ignoring checkpoint written with different settings");
+            return None;
+        }
+        Some(checkpoint)
+    }
+
+    /// The recorded report for `entry`, if it completed with the same config
+    /// and its outputs are still on disk
+    fn completed(&self, name: &str, entry: &Value) -> Option<&EntryReport> {
+        let completed = self.completed.get(name)?;
+        let summary = Path::new(&completed.report.output_dir).join("summary.json");
+        (completed.fingerprint == fingerprint(entry) && summary.exists()).then_some(&completed.report)
+    }
+
+    /// Rewrite the checkpoint, replacing it atomically
+    fn save(&self, output_dir: &Path) -> CliResult<()> {
+        fs::create_dir_all(output_dir)?;
+        let path = output_dir.join(BATCH_CHECKPOINT);
+        let partial = path.with_extension("json.partial");
+        fs::write(&partial, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&partial, &path)?;
+        Ok(())
+    }
+}
+
+fn fingerprint(entry: &Value) -> String {
+    hex::encode(Sha256::digest(entry.to_string().as_bytes()))
+}
+
+/// Cap the RPC requests in flight for the rest of the process
+///
+/// Returns false if a limit was already set.
+pub fn limit_rpc(max_concurrency: usize) -> bool {
+    RPC_LIMIT.set(Semaphore::new(max_concurrency.max(1))).is_ok()
+}
+
+/// Wait for a slot under the RPC limit; `None` when there is no limit
+pub async fn rpc_permit() -> Option<SemaphorePermit<'static>> {
+    RPC_LIMIT.get()?.acquire().await.ok()
+}
+
+/// Entries of a batch config for `chain`
+///
+/// A batch config is either a JSON list of the chain's `auto-generate`
+/// entries or a project config, whose other sections are skipped with a
+/// warning since they need their own CLI.
+pub fn batch_entries(config: &Value, chain: &str) -> CliResult<Vec<Value>> {
+    if let Value::Array(entries) = config {
+        return Ok(entries.clone());
+    }
+    let project = crate::project::ProjectConfig::from_value(config)?.ok_or_else(|| {
+        CliError::Configuration("A batch config must be a list of entries or a project config".to_string())
+    })?;
+    for other in crate::project::PROJECT_CHAINS.iter().filter(|other| **other != chain) {
+        let skipped = project.section(other).len();
+        if skipped > 0 {
+            eprintln!(
+                "Warning: skipping {} {} entries; run traverse-{} batch-generate for them",
+                skipped, other, other
+            );
+        }
+    }
+    Ok(project.section(chain).to_vec())
+}
+
+/// Run every entry on the pool and write `<output>/batch-summary.json`
+///
+/// Each entry is generated into `<output>/<name>/` by `run_entry`. Failures
+/// are recorded rather than stopping the batch, and the summary keeps the
+/// entries in config order whatever order they finished in. `settings`
+/// describes whatever else shapes the outputs, such as `--dry-run`, and is
+/// stored in the checkpoint so `resume` never mixes outputs from different
+/// settings.
+pub async fn run_batch<F, Fut>(
+    entries: &[Value],
+    output_dir: &Path,
+    options: &PoolOptions,
+    settings: Value,
+    run_entry: F,
+) -> CliResult<BatchSummary>
+where
+    F: Fn(Value, PathBuf) -> Fut,
+    Fut: Future<Output = CliResult<()>>,
+{
+    let records = options.ndjson.then(|| NdjsonWriter::from_writer(std::io::stdout()));
+    run_batch_streaming(entries, output_dir, options, settings, records, run_entry).await
+}
+
+/// [`run_batch`], streaming each finished entry's report to `records`
+async fn run_batch_streaming<F, Fut>(
+    entries: &[Value],
+    output_dir: &Path,
+    options: &PoolOptions,
+    settings: Value,
+    records: Option<NdjsonWriter>,
+    run_entry: F,
+) -> CliResult<BatchSummary>
+where
+    F: Fn(Value, PathBuf) -> Fut,
+    Fut: Future<Output = CliResult<()>>,
+{
+    let started = Instant::now();
+    let previous = if options.resume {
+        let previous = Checkpoint::load(output_dir, &settings);
+        if previous.is_none() {
+            eprintln!("No usable checkpoint in {}; starting from the beginning", output_dir.display());
+        }
+        previous.unwrap_or_default()
+    } else {
+        Checkpoint::default()
+    };
+    // The new checkpoint starts with the entries being resumed, so entries
+    // dropped from the config fall out of it, and is saved straight away so
+    // a stale one never outlives a fresh run
+    let mut checkpoint = Checkpoint { settings, completed: BTreeMap::new() };
+    for (index, entry) in entries.iter().enumerate() {
+        let name = entry_name(entry, index);
+        if let Some(report) = previous.completed(&name, entry) {
+            let completed = CompletedEntry { fingerprint: fingerprint(entry), report: report.clone() };
+            checkpoint.completed.insert(name, completed);
+        }
+    }
+    checkpoint.save(output_dir)?;
+    let checkpoint = &Mutex::new(checkpoint);
+    let previous = &previous;
+    let records = &records.map(Mutex::new);
+
+    let progress = options.progress.then(MultiProgress::new);
+    let overall = progress.as_ref().map(|multi| {
+        let bar = multi.add(ProgressBar::new(entries.len() as u64));
+        bar.set_style(
+            ProgressStyle::with_template("{bar:30} {pos}/{len} entries ({elapsed})")
+                .unwrap_or_else(|_| ProgressStyle::default_bar()),
+        );
+        bar
+    });
+    let run_entry = &run_entry;
+    let overall = overall.as_ref();
+
+    let mut reports: Vec<(usize, EntryReport, bool)> = stream::iter(entries.iter().enumerate())
+        .map(|(index, entry)| {
+            let name = entry_name(entry, index);
+            let entry_dir = output_dir.join(&name);
+            let resumed = previous.completed(&name, entry).cloned();
+            // Bars are added as entries start, so only running ones spin
+            let bar = progress.as_ref().filter(|_| resumed.is_none()).map(|multi| entry_bar(multi, &name));
+            async move {
+                if let Some(report) = resumed {
+                    if let Some(overall) = overall {
+                        overall.inc(1);
+                    }
+                    stream_record(records, &report);
+                    return (index, report, true);
+                }
+                let entry_started = Instant::now();
+                let error = run_entry(entry.clone(), entry_dir.clone()).await.err().map(|e| e.to_string());
+                if let Some(bar) = bar {
+                    match &error {
+                        None => bar.finish_with_message(format!("✓ {} ({:.1?})", name, entry_started.elapsed())),
+                        Some(e) => bar.finish_with_message(format!("✗ {}: {}", name, e)),
+                    }
+                }
+                if let Some(overall) = overall {
+                    overall.inc(1);
+                }
+                let summary = fs::read_to_string(entry_dir.join("summary.json"))
+                    .ok()
+                    .and_then(|content| serde_json::from_str(&content).ok());
+                let report = EntryReport { name, output_dir: entry_dir.display().to_string(), error, summary };
+                if report.error.is_none() {
+                    record(checkpoint, output_dir, entry, &report);
+                }
+                stream_record(records, &report);
+                (index, report, false)
+            }
+        })
+        .buffer_unordered(options.parallel.max(1))
+        .collect()
+        .await;
+    if let Some(overall) = overall {
+        overall.finish();
+    }
+    reports.sort_by_key(|(index, _, _)| *index);
+
+    let resumed = reports.iter().filter(|(_, _, resumed)| *resumed).count();
+    let entries: Vec<EntryReport> = reports.into_iter().map(|(_, report, _)| report).collect();
+    let mut errors: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for entry in &entries {
+        if let Some(error) = &entry.error {
+            errors.entry(error.clone()).or_default().push(entry.name.clone());
+        }
+    }
+    let failed = errors.values().map(Vec::len).sum();
+    let summary = BatchSummary {
+        total: entries.len(),
+        succeeded: entries.len() - failed,
+        failed,
+        parallel: options.parallel.max(1),
+        max_concurrency: options.max_concurrency,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        resumed,
+        entries,
+        errors,
+    };
+
+    fs::create_dir_all(output_dir)?;
+    fs::write(output_dir.join(BATCH_SUMMARY), serde_json::to_string_pretty(&summary)?)?;
+    Ok(summary)
+}
+
+/// Add a completed entry to the checkpoint and save it
+///
+/// A checkpoint that cannot be written only costs a warning; the batch
+/// itself is unaffected.
+fn record(checkpoint: &Mutex<Checkpoint>, output_dir: &Path, entry: &Value, report: &EntryReport) {
+    let mut checkpoint = checkpoint.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let completed = CompletedEntry { fingerprint: fingerprint(entry), report: report.clone() };
+    checkpoint.completed.insert(report.name.clone(), completed);
+    if let Err(e) = checkpoint.save(output_dir) {
+        eprintln!("Warning: failed to write checkpoint: {}", e);
+    }
+}
+
+/// Write a finished entry's report as one NDJSON line
+///
+/// Like the checkpoint, a record that cannot be written only costs a warning.
+fn stream_record(records: &Option<Mutex<NdjsonWriter>>, report: &EntryReport) {
+    let Some(records) = records else {
+        return;
+    };
+    let mut records = records.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Err(e) = records.write_record(report) {
+        eprintln!("Warning: failed to stream the report for {}: {}", report.name, e);
+    }
+}
+
+fn entry_bar(multi: &MultiProgress, name: &str) -> ProgressBar {
+    let bar = multi.add(ProgressBar::new_spinner());
+    bar.set_style(
+        ProgressStyle::with_template("{spinner} {msg} ({elapsed})").unwrap_or_else(|_| ProgressStyle::default_spinner()),
+    );
+    bar.set_message(name.to_string());
+    bar.enable_steady_tick(Duration::from_millis(100));
+    bar
+}
+
+/// Print the batch outcome with failures grouped by error
+pub fn print_summary(summary: &BatchSummary, output_dir: &Path) {
+    eprintln!(
+        "Generated {}/{} entries in {:.1}s with {} workers",
+        summary.succeeded,
+        summary.total,
+        summary.elapsed_ms as f64 / 1000.0,
+        summary.parallel
+    );
+    if summary.resumed > 0 {
+        eprintln!("  {} entries resumed from {}", summary.resumed, output_dir.join(BATCH_CHECKPOINT).display());
+    }
+    for (error, names) in &summary.errors {
+        eprintln!("  ✗ {} entries: {}", names.len(), error);
+        eprintln!("    {}", names.join(", "));
+    }
+    eprintln!("Summary written to {}", output_dir.join(BATCH_SUMMARY).display());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_run_batch_bounds_workers_and_groups_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let entries: Vec<Value> = (0..6).map(|i| json!({ "name": format!("e{}", i), "fail": i % 2 == 1 })).collect();
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let options = PoolOptions { parallel: 3, ..PoolOptions::default() };
+
+        let summary = run_batch(&entries, dir.path(), &options, Value::Null, |entry, _| {
+            let (running, peak) = (&running, &peak);
+            async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                if entry["fail"] == true {
+                    return Err(CliError::Processing("rate limited".to_string()));
+                }
+                Ok(())
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+        assert_eq!((summary.succeeded, summary.failed), (3, 3));
+        let names: Vec<&str> = summary.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["e0", "e1", "e2", "e3", "e4", "e5"]);
+        assert_eq!(summary.errors["Processing error: rate limited"], ["e1", "e3", "e5"]);
+        assert!(dir.path().join(BATCH_SUMMARY).exists());
+    }
+
+    #[tokio::test]
+    async fn test_run_batch_resumes_from_checkpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let entries: Vec<Value> = (0..4).map(|i| json!({ "name": format!("e{}", i) })).collect();
+        let runs = AtomicUsize::new(0);
+        let run = |fail: &'static str| {
+            let runs = &runs;
+            move |entry: Value, entry_dir: PathBuf| async move {
+                runs.fetch_add(1, Ordering::SeqCst);
+                if entry["name"] == fail {
+                    return Err(CliError::Processing("rate limited".to_string()));
+                }
+                fs::create_dir_all(&entry_dir)?;
+                fs::write(entry_dir.join("summary.json"), "{}")?;
+                Ok(())
+            }
+        };
+        let resume = PoolOptions { resume: true, ..PoolOptions::default() };
+
+        let first = run_batch(&entries, dir.path(), &resume, json!({ "dry_run": false }), run("e2")).await.unwrap();
+        assert_eq!((first.failed, first.resumed), (1, 0));
+
+        // Only the failed entry runs again
+        let second = run_batch(&entries, dir.path(), &resume, json!({ "dry_run": false }), run("")).await.unwrap();
+        assert_eq!((second.succeeded, second.resumed), (4, 3));
+        assert_eq!(runs.load(Ordering::SeqCst), 5);
+
+        // Different settings or an edited entry start over
+        let third = run_batch(&entries, dir.path(), &resume, json!({ "dry_run": true }), run("")).await.unwrap();
+        assert_eq!(third.resumed, 0);
+        let mut edited = entries.clone();
+        edited[0]["queries"] = json!(["totalSupply"]);
+        let fourth = run_batch(&edited, dir.path(), &resume, json!({ "dry_run": true }), run("")).await.unwrap();
+        assert_eq!(fourth.resumed, 3);
+    }
+
+    /// Writer whose bytes stay readable after it is handed off
+    #[derive(Clone, Default)]
+    struct SharedBuffer(std::sync::Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_batch_streams_reports() {
+        let dir = tempfile::tempdir().unwrap();
+        let entries: Vec<Value> = (0..3).map(|i| json!({ "name": format!("e{}", i) })).collect();
+        let buffer = SharedBuffer::default();
+        let records = NdjsonWriter::from_writer(buffer.clone());
+        let options = PoolOptions { ndjson: true, ..PoolOptions::default() };
+
+        let summary = run_batch_streaming(&entries, dir.path(), &options, Value::Null, Some(records), |entry, _| async move {
+            if entry["name"] == "e1" {
+                return Err(CliError::Processing("rate limited".to_string()));
+            }
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let streamed: Vec<EntryReport> = output.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(streamed, summary.entries);
+        assert_eq!(streamed[1].error.as_deref(), Some("Processing error: rate limited"));
+    }
+
+    #[test]
+    fn test_batch_entries() {
+        assert_eq!(batch_entries(&json!([{ "name": "a" }]), "solana").unwrap().len(), 1);
+        let project = json!({ "solana": [{ "name": "a" }, { "name": "b" }], "ethereum": [{}] });
+        assert_eq!(batch_entries(&project, "solana").unwrap().len(), 2);
+        assert!(batch_entries(&json!({ "idl": "x.json" }), "solana").is_err());
+    }
+}
@@ -0,0 +1,364 @@
+//! Multi-chain project configuration
+//!
+//! An `auto-generate` config usually describes one contract on one chain. A
+//! project config instead groups entries by chain so a cross-chain
+//! coprocessor app has a single source of truth:
+//!
+//! ```json
+//! {
+//!   "name": "cross-chain-vault",
+//!   "ethereum": [
+//!     { "name": "usdc", "abi": "abi/erc20.json", "contract": "usdc", "queries": ["totalSupply"] }
+//!   ],
+//!   "cosmos": [
+//!     { "name": "vault", "schema": "schema/vault.json", "contract": "vault",
+//!       "chain_id": "neutron-1", "queries": ["config"] }
+//!   ],
+//!   "solana": [
+//!     { "idl": "idl/vault.json", "program_id": "vault", "queries": ["vault[seed].balance"] }
+//!   ]
+//! }
+//! ```
+//!
+//! Entries use the chain's own `auto-generate` format, plus an optional
+//! `name` and `rpc`. The chain CLIs cannot be linked into one binary, so the
+//! CLI given a project runs its own section and dispatches every other
+//! section to the sibling `traverse-<chain>` binary with `--only <chain>`.
+//! Each entry is generated into `<output>/<chain>/<name>/`, each section
+//! leaves a `section.json` report, and the dispatching CLI combines the
+//! reports into `<output>/manifest.json`.
+
+use crate::{CliError, CliResult};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Chains a project config can have sections for
+pub const PROJECT_CHAINS: [&str; 3] = ["ethereum", "cosmos", "solana"];
+
+/// File name of a section report inside `<output>/<chain>/`
+pub const SECTION_REPORT: &str = "section.json";
+
+/// File name of the combined manifest inside the output directory
+pub const PROJECT_MANIFEST: &str = "manifest.json";
+
+/// `auto-generate` config describing entries on several chains
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectConfig {
+    /// Project name, recorded in the manifest
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Entries in the Ethereum `auto-generate` format
+    #[serde(default)]
+    pub ethereum: Vec<Value>,
+    /// Entries in the Cosmos `auto-generate` format
+    #[serde(default)]
+    pub cosmos: Vec<Value>,
+    /// Entries in the Solana `auto-generate` format
+    #[serde(default)]
+    pub solana: Vec<Value>,
+}
+
+impl ProjectConfig {
+    /// Read a project config, or `None` for a single-chain config
+    ///
+    /// A config is a project when it has a section for any chain. Entry names
+    /// must be unique within a section since they name output directories.
+    pub fn from_value(value: &Value) -> CliResult<Option<Self>> {
+        if !PROJECT_CHAINS.iter().any(|chain| value.get(chain).is_some()) {
+            return Ok(None);
+        }
+        let project: Self = serde_json::from_value(value.clone())
+            .map_err(|e| CliError::Configuration(format!("Invalid project config: {}", e)))?;
+
+        for chain in PROJECT_CHAINS {
+            let mut names = HashSet::new();
+            for (index, entry) in project.section(chain).iter().enumerate() {
+                let name = entry_name(entry, index);
+                if !names.insert(name.clone()) {
+                    return Err(CliError::Configuration(format!(
+                        "Duplicate {} entry name '{}' in project config",
+                        chain, name
+                    )));
+                }
+            }
+        }
+        Ok(Some(project))
+    }
+
+    /// Entries of one chain's section
+    pub fn section(&self, chain: &str) -> &[Value] {
+        match chain {
+            "ethereum" => &self.ethereum,
+            "cosmos" => &self.cosmos,
+            "solana" => &self.solana,
+            _ => &[],
+        }
+    }
+}
+
+/// Name of an entry's output directory: its `name`, else its position
+pub fn entry_name(entry: &Value, index: usize) -> String {
+    entry
+        .get("name")
+        .and_then(Value::as_str)
+        .map_or_else(|| index.to_string(), str::to_string)
+}
+
+/// Outcome of one generated entry
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EntryReport {
+    pub name: String,
+    pub output_dir: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The entry's `summary.json`, when the pipeline wrote one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<Value>,
+}
+
+/// Outcome of one chain's section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SectionReport {
+    pub chain: String,
+    pub entries: Vec<EntryReport>,
+    /// Why the section could not be run at all
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl SectionReport {
+    fn failed(chain: &str, error: String) -> Self {
+        Self { chain: chain.to_string(), entries: Vec::new(), error: Some(error) }
+    }
+
+    /// Whether the section ran and every entry succeeded
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none() && self.entries.iter().all(|e| e.error.is_none())
+    }
+}
+
+/// Combined result of a project run, written to `manifest.json`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProjectManifest {
+    pub name: Option<String>,
+    pub config: String,
+    pub dry_run: bool,
+    pub sections: Vec<SectionReport>,
+}
+
+impl ProjectManifest {
+    /// Whether every section and entry succeeded
+    pub fn is_ok(&self) -> bool {
+        self.sections.iter().all(SectionReport::is_ok)
+    }
+}
+
+/// Options forwarded to the CLIs that sections are dispatched to
+#[derive(Debug, Clone, Default)]
+pub struct DispatchOptions {
+    pub dry_run: bool,
+    pub profile: Option<String>,
+    pub config: Option<String>,
+    /// `--cache` or `--no-cache` given to the dispatching CLI
+    pub cache: Option<bool>,
+}
+
+/// Run one chain's section, one entry at a time
+///
+/// `run_entry` receives each entry and its output directory. Failures are
+/// recorded in the report rather than stopping the section, and the report
+/// is written to `<output>/<chain>/section.json`.
+pub async fn run_section<F, Fut>(
+    chain: &str,
+    project: &ProjectConfig,
+    output_dir: &Path,
+    mut run_entry: F,
+) -> CliResult<SectionReport>
+where
+    F: FnMut(Value, PathBuf) -> Fut,
+    Fut: Future<Output = CliResult<()>>,
+{
+    let section_dir = output_dir.join(chain);
+    let mut entries = Vec::new();
+
+    for (index, entry) in project.section(chain).iter().enumerate() {
+        let name = entry_name(entry, index);
+        let entry_dir = section_dir.join(&name);
+        let error = run_entry(entry.clone(), entry_dir.clone()).await.err().map(|e| e.to_string());
+        let summary = fs::read_to_string(entry_dir.join("summary.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok());
+
+        entries.push(EntryReport {
+            name,
+            output_dir: entry_dir.display().to_string(),
+            error,
+            summary,
+        });
+    }
+
+    let report = SectionReport { chain: chain.to_string(), entries, error: None };
+    fs::create_dir_all(&section_dir)?;
+    fs::write(section_dir.join(SECTION_REPORT), serde_json::to_string_pretty(&report)?)?;
+    Ok(report)
+}
+
+/// Binary that handles `chain`: next to the running CLI, else on `PATH`
+pub fn sibling_binary(chain: &str) -> PathBuf {
+    let name = format!("traverse-{}{}", chain, std::env::consts::EXE_SUFFIX);
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(&name)))
+        .filter(|path| path.exists())
+        .unwrap_or_else(|| PathBuf::from(name))
+}
+
+/// Run another chain's section with its CLI and read back its report
+pub fn dispatch_section(
+    chain: &str,
+    config_path: &Path,
+    output_dir: &Path,
+    options: &DispatchOptions,
+) -> SectionReport {
+    let binary = sibling_binary(chain);
+    let mut command = Command::new(&binary);
+    command
+        .arg("auto-generate")
+        .arg(config_path)
+        .arg("--output-dir")
+        .arg(output_dir)
+        .args(["--only", chain]);
+    if options.dry_run {
+        command.arg("--dry-run");
+    }
+    if let Some(profile) = &options.profile {
+        command.args(["--profile", profile]);
+    }
+    if let Some(config) = &options.config {
+        command.args(["--config", config]);
+    }
+    match options.cache {
+        Some(true) => command.arg("--cache"),
+        Some(false) => command.arg("--no-cache"),
+        None => &mut command,
+    };
+
+    let status = match command.status() {
+        Ok(status) => status,
+        Err(e) => {
+            return SectionReport::failed(chain, format!("Could not run {}: {}", binary.display(), e))
+        }
+    };
+
+    let report_path = output_dir.join(chain).join(SECTION_REPORT);
+    match fs::read_to_string(&report_path).ok().and_then(|c| serde_json::from_str(&c).ok()) {
+        Some(report) => report,
+        None => SectionReport::failed(
+            chain,
+            format!("{} exited with {} without writing {}", binary.display(), status, report_path.display()),
+        ),
+    }
+}
+
+/// Dispatch the remaining sections and write `<output>/manifest.json`
+///
+/// `own` is the section this CLI already ran. Sections without entries are
+/// left out of the manifest.
+pub fn write_manifest(
+    project: &ProjectConfig,
+    config_path: &Path,
+    output_dir: &Path,
+    own: SectionReport,
+    options: &DispatchOptions,
+) -> CliResult<ProjectManifest> {
+    let mut own = Some(own);
+    let mut sections = Vec::new();
+    for chain in PROJECT_CHAINS {
+        if project.section(chain).is_empty() {
+            continue;
+        }
+        let report = match own.take_if(|report| report.chain == chain) {
+            Some(report) => report,
+            None => dispatch_section(chain, config_path, output_dir, options),
+        };
+        sections.push(report);
+    }
+
+    let manifest = ProjectManifest {
+        name: project.name.clone(),
+        config: config_path.display().to_string(),
+        dry_run: options.dry_run,
+        sections,
+    };
+    fs::create_dir_all(output_dir)?;
+    fs::write(output_dir.join(PROJECT_MANIFEST), serde_json::to_string_pretty(&manifest)?)?;
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_project_detection_and_names() {
+        let single = json!({ "abi": "erc20.json", "contract": "0x00", "queries": [] });
+        assert!(ProjectConfig::from_value(&single).unwrap().is_none());
+
+        let project = json!({
+            "name": "vault",
+            "ethereum": [{ "name": "usdc", "abi": "erc20.json" }, { "abi": "weth.json" }],
+            "solana": [{ "idl": "vault.json" }]
+        });
+        let project = ProjectConfig::from_value(&project).unwrap().unwrap();
+        assert_eq!(project.section("ethereum").len(), 2);
+        assert!(project.section("cosmos").is_empty());
+        assert_eq!(entry_name(&project.ethereum[0], 0), "usdc");
+        assert_eq!(entry_name(&project.ethereum[1], 1), "1");
+
+        let duplicate = json!({ "cosmos": [{ "name": "a" }, { "name": "a" }] });
+        assert!(ProjectConfig::from_value(&duplicate).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_section_and_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = ProjectConfig::from_value(&json!({
+            "ethereum": [{ "name": "ok" }, { "name": "bad" }]
+        }))
+        .unwrap()
+        .unwrap();
+
+        let report = run_section("ethereum", &project, dir.path(), |entry, entry_dir| async move {
+            if entry["name"] == "bad" {
+                return Err(CliError::Processing("no RPC".to_string()));
+            }
+            fs::create_dir_all(&entry_dir)?;
+            fs::write(entry_dir.join("summary.json"), r#"{"resolved": 1}"#)?;
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(report.entries[0].summary, Some(json!({ "resolved": 1 })));
+        assert!(report.entries[1].error.as_deref().unwrap().contains("no RPC"));
+        assert!(!report.is_ok());
+        assert!(dir.path().join("ethereum").join(SECTION_REPORT).exists());
+
+        let manifest = write_manifest(
+            &project,
+            Path::new("project.json"),
+            dir.path(),
+            report.clone(),
+            &DispatchOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(manifest.sections, vec![report]);
+        assert!(dir.path().join(PROJECT_MANIFEST).exists());
+    }
+}
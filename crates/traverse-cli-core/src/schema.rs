@@ -0,0 +1,418 @@
+//! JSON Schemas for CLI output formats
+//!
+//! Each schema is stamped with the version of the tool that produced it, so a
+//! team receiving layouts or query files can validate them against exactly the
+//! contract their counterpart's CLI writes.
+
+use crate::{CliError, CliResult, CliUtils};
+use clap::Subcommand;
+use serde_json::{json, Map, Value};
+use std::path::{Path, PathBuf};
+
+/// JSON Schema dialect used by every exported schema
+pub const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Version of the tool the schemas describe
+pub const SCHEMA_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Names of the exported schemas, in export order
+pub const SCHEMA_NAMES: &[&str] = &[
+    "layout",
+    "static-key-path",
+    "coprocessor-query",
+    "storage-proof",
+    "batch-manifest",
+];
+
+/// `schema` subcommands shared by the ecosystem CLIs
+#[derive(Subcommand, Debug, Clone)]
+pub enum SchemaCommand {
+    /// Write JSON Schemas for the CLI output formats
+    Export {
+        /// Directory to write `<name>.schema.json` files into (stdout if not specified)
+        #[arg(short = 'd', long)]
+        output_dir: Option<String>,
+        /// Export a single schema instead of all of them
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// List the available schema names
+    List,
+}
+
+/// Run a `schema` subcommand
+pub fn run_schema_command(command: &SchemaCommand, output: Option<&str>) -> CliResult<()> {
+    match command {
+        SchemaCommand::Export { output_dir, name } => {
+            let names: Vec<&str> = match name {
+                Some(name) => vec![name.as_str()],
+                None => SCHEMA_NAMES.to_vec(),
+            };
+
+            match output_dir {
+                Some(dir) => {
+                    for path in export(Path::new(dir), &names)? {
+                        eprintln!("Wrote {}", path.display());
+                    }
+                }
+                None => {
+                    let value = if names.len() == 1 {
+                        schema(names[0])?
+                    } else {
+                        let mut all = Map::new();
+                        for name in &names {
+                            all.insert(name.to_string(), schema(name)?);
+                        }
+                        Value::Object(all)
+                    };
+                    let content = serde_json::to_string_pretty(&value).map_err(CliError::Json)?;
+                    CliUtils::write_output(&content, output)?;
+                }
+            }
+        }
+        SchemaCommand::List => {
+            CliUtils::write_output(&format!("{}\n", SCHEMA_NAMES.join("\n")), output)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write the named schemas into a directory, returning the written paths
+pub fn export(dir: &Path, names: &[&str]) -> CliResult<Vec<PathBuf>> {
+    CliUtils::ensure_output_dir(dir)?;
+
+    let mut written = Vec::with_capacity(names.len());
+    for name in names {
+        let content = serde_json::to_string_pretty(&schema(name)?).map_err(CliError::Json)?;
+        let path = dir.join(format!("{}.schema.json", name));
+        std::fs::write(&path, content).map_err(CliError::Io)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Schema for one output format
+pub fn schema(name: &str) -> CliResult<Value> {
+    let (title, description, body) = match name {
+        "layout" => (
+            "LayoutInfo",
+            "Canonical storage layout written by compile-layout",
+            layout_schema(),
+        ),
+        "static-key-path" => (
+            "StaticKeyPath",
+            "Resolved query in traverse format",
+            static_key_path_schema(),
+        ),
+        "coprocessor-query" => (
+            "CoprocessorStorageQuery",
+            "Resolved query in coprocessor-json format",
+            coprocessor_query_schema(),
+        ),
+        "storage-proof" => (
+            "StorageVerificationRequest",
+            "Storage query paired with an eth_getProof result",
+            storage_proof_schema(),
+        ),
+        "batch-manifest" => (
+            "BatchResolveManifest",
+            "Summary written by batch-resolve",
+            batch_manifest_schema(),
+        ),
+        _ => {
+            return Err(CliError::InvalidArgument(format!(
+                "Unknown schema '{}'. Available: {}",
+                name,
+                SCHEMA_NAMES.join(", ")
+            )))
+        }
+    };
+
+    let mut schema = json!({
+        "$schema": SCHEMA_DIALECT,
+        "$id": format!("urn:traverse:schema:{}:{}", name, SCHEMA_VERSION),
+        "title": title,
+        "description": description,
+        "x-traverse-version": SCHEMA_VERSION,
+    });
+    if let (Value::Object(schema), Value::Object(body)) = (&mut schema, body) {
+        schema.extend(body);
+    }
+    Ok(schema)
+}
+
+fn hex_string(bytes: Option<usize>) -> Value {
+    match bytes {
+        Some(n) => json!({ "type": "string", "pattern": format!("^(0x)?[0-9a-fA-F]{{{}}}$", n * 2) }),
+        None => json!({ "type": "string", "pattern": "^(0x)?[0-9a-fA-F]*$" }),
+    }
+}
+
+fn byte_array(len: Option<usize>) -> Value {
+    let mut schema = json!({
+        "type": "array",
+        "items": { "type": "integer", "minimum": 0, "maximum": 255 }
+    });
+    if let Some(len) = len {
+        schema["minItems"] = json!(len);
+        schema["maxItems"] = json!(len);
+    }
+    schema
+}
+
+/// A `u64` written as a decimal string, or as a number by older documents
+fn optional_decimal() -> Value {
+    json!({ "type": ["string", "integer", "null"], "pattern": "^[0-9]+$", "minimum": 0 })
+}
+
+fn optional_u8() -> Value {
+    json!({ "type": ["integer", "null"], "minimum": 0, "maximum": 255 })
+}
+
+fn zero_semantics() -> Value {
+    json!({ "enum": ["NeverWritten", "ExplicitlyZero", "Cleared", "ValidZero"] })
+}
+
+fn layout_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["contract_name", "storage", "types"],
+        "properties": {
+            "contract_name": { "type": "string" },
+            "storage": { "type": "array", "items": { "$ref": "#/$defs/StorageEntry" } },
+            "types": { "type": "array", "items": { "$ref": "#/$defs/TypeInfo" } },
+            "code_variables": { "type": "array", "items": { "$ref": "#/$defs/CodeVariable" } }
+        },
+        "$defs": {
+            "StorageEntry": {
+                "type": "object",
+                "required": ["label", "slot", "offset", "type_name", "zero_semantics"],
+                "properties": {
+                    "label": { "type": "string" },
+                    "slot": { "type": "string", "description": "Slot number as a decimal string" },
+                    "offset": { "type": "integer", "minimum": 0, "maximum": 31 },
+                    "type_name": { "type": "string" },
+                    "zero_semantics": zero_semantics()
+                }
+            },
+            "TypeInfo": {
+                "type": "object",
+                "required": ["label", "number_of_bytes", "encoding"],
+                "properties": {
+                    "label": { "type": "string" },
+                    "number_of_bytes": { "type": "string" },
+                    "encoding": { "type": "string" },
+                    "base": { "type": ["string", "null"] },
+                    "key": { "type": ["string", "null"] },
+                    "value": { "type": ["string", "null"] }
+                }
+            },
+            "CodeVariable": {
+                "type": "object",
+                "required": ["label", "type_name", "kind"],
+                "properties": {
+                    "label": { "type": "string" },
+                    "type_name": { "type": "string" },
+                    "kind": { "enum": ["constant", "immutable"] }
+                }
+            }
+        }
+    })
+}
+
+fn static_key_path_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["name", "key", "offset", "field_size", "layout_commitment", "zero_semantics"],
+        "properties": {
+            "name": { "type": "string" },
+            "key": {
+                "oneOf": [
+                    {
+                        "type": "object",
+                        "required": ["Fixed"],
+                        "properties": { "Fixed": byte_array(Some(32)) },
+                        "additionalProperties": false
+                    },
+                    {
+                        "type": "object",
+                        "required": ["Variable"],
+                        "properties": { "Variable": byte_array(None) },
+                        "additionalProperties": false
+                    }
+                ]
+            },
+            "offset": optional_u8(),
+            "field_size": optional_u8(),
+            "layout_commitment": byte_array(Some(32)),
+            "zero_semantics": zero_semantics()
+        }
+    })
+}
+
+fn coprocessor_query_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["query", "storage_key", "layout_commitment"],
+        "properties": {
+            "query": { "type": "string" },
+            "storage_key": hex_string(Some(32)),
+            "layout_commitment": hex_string(Some(32)),
+            "field_size": optional_u8(),
+            "offset": optional_u8()
+        }
+    })
+}
+
+fn storage_proof_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["storage_query", "storage_proof"],
+        "properties": {
+            "storage_query": coprocessor_query_schema(),
+            "storage_proof": {
+                "type": "object",
+                "required": ["key", "value", "proof"],
+                "properties": {
+                    "key": hex_string(None),
+                    "value": hex_string(None),
+                    "proof": { "type": "array", "items": hex_string(None) }
+                }
+            },
+            "contract_address": { "type": ["string", "null"] },
+            "block_number": optional_decimal()
+        }
+    })
+}
+
+fn batch_manifest_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["total_queries", "resolved", "failed", "results"],
+        "properties": {
+            "layout_commitment": hex_string(Some(32)),
+            "layouts": {
+                "description": "Commitment per contract name (CSV input)",
+                "type": "object",
+                "additionalProperties": hex_string(Some(32))
+            },
+            "total_queries": { "type": "integer", "minimum": 0 },
+            "resolved": { "type": "integer", "minimum": 0 },
+            "failed": { "type": "integer", "minimum": 0 },
+            "results": {
+                "type": "array",
+                "items": {
+                    "oneOf": [
+                        {
+                            "type": "object",
+                            "required": ["query", "status", "storage_key", "layout_commitment"],
+                            "properties": {
+                                "query": { "type": "string" },
+                                "status": { "const": "resolved" },
+                                "storage_key": hex_string(Some(32)),
+                                "layout_commitment": hex_string(Some(32)),
+                                "field_size": optional_u8(),
+                                "offset": optional_u8(),
+                                "line": { "type": "integer", "minimum": 1 },
+                                "contract": { "type": "string" },
+                                "zero_semantics": { "enum": ["NeverWritten", "ExplicitlyZero", "Cleared", "ValidZero", null] }
+                            }
+                        },
+                        {
+                            "type": "object",
+                            "required": ["query", "status", "error"],
+                            "properties": {
+                                "query": { "type": "string" },
+                                "status": { "const": "failed" },
+                                "error": { "type": "string" },
+                                "line": { "type": "integer", "minimum": 1 },
+                                "contract": { "type": "string" }
+                            }
+                        }
+                    ]
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use traverse_core::{
+        CodeVariable, CodeVariableKind, Key, LayoutInfo, StaticKeyPath, StorageEntry, TypeInfo, ZeroSemantics,
+    };
+
+    /// Check that every required property of `schema` is present in `value`
+    fn assert_required_present(schema: &Value, value: &Value) {
+        for key in schema["required"].as_array().unwrap() {
+            let key = key.as_str().unwrap();
+            assert!(value.get(key).is_some(), "missing required field '{}'", key);
+        }
+    }
+
+    #[test]
+    fn test_schemas_match_serialized_output() {
+        let layout = LayoutInfo {
+            contract_name: "Token".to_string(),
+            storage: vec![StorageEntry {
+                label: "totalSupply".to_string(),
+                slot: "0".to_string(),
+                offset: 0,
+                type_name: "t_uint256".to_string(),
+                zero_semantics: ZeroSemantics::ValidZero,
+            }],
+            types: vec![TypeInfo {
+                label: "t_uint256".to_string(),
+                number_of_bytes: "32".to_string(),
+                encoding: "inplace".to_string(),
+                base: None,
+                key: None,
+                value: None,
+            }],
+            code_variables: vec![CodeVariable {
+                label: "DECIMALS".to_string(),
+                type_name: "t_uint8".to_string(),
+                kind: CodeVariableKind::Constant,
+            }],
+        };
+        let layout_json = serde_json::to_value(&layout).unwrap();
+        let layout_schema = schema("layout").unwrap();
+        assert_required_present(&layout_schema, &layout_json);
+        assert_required_present(&layout_schema["$defs"]["StorageEntry"], &layout_json["storage"][0]);
+        assert_required_present(&layout_schema["$defs"]["TypeInfo"], &layout_json["types"][0]);
+        assert_required_present(&layout_schema["$defs"]["CodeVariable"], &layout_json["code_variables"][0]);
+        assert_eq!(layout_json["code_variables"][0]["kind"], "constant");
+
+        let path = StaticKeyPath {
+            name: "totalSupply",
+            key: Key::Fixed([0u8; 32]),
+            offset: None,
+            field_size: Some(32),
+            layout_commitment: layout.commitment(),
+            zero_semantics: ZeroSemantics::ValidZero,
+        };
+        let path_json = serde_json::to_value(&path).unwrap();
+        assert_required_present(&schema("static-key-path").unwrap(), &path_json);
+
+        let query = crate::formatters::path_to_coprocessor_query(&path, "totalSupply");
+        let query_json = serde_json::to_value(&query).unwrap();
+        assert_required_present(&schema("coprocessor-query").unwrap(), &query_json);
+    }
+
+    #[test]
+    fn test_export_writes_versioned_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let written = export(dir.path(), SCHEMA_NAMES).unwrap();
+        assert_eq!(written.len(), SCHEMA_NAMES.len());
+
+        for path in written {
+            let content: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+            assert_eq!(content["$schema"], SCHEMA_DIALECT);
+            assert_eq!(content["x-traverse-version"], SCHEMA_VERSION);
+            assert!(content["$id"].as_str().unwrap().ends_with(SCHEMA_VERSION));
+        }
+
+        assert!(schema("unknown").is_err());
+    }
+}
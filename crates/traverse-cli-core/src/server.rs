@@ -0,0 +1,364 @@
+//! HTTP API server mode
+//!
+//! Exposes the CLI operations as REST endpoints so services can use traverse
+//! without shelling out to the binary. Each ecosystem CLI registers a
+//! [`ChainHandler`] for the chains it supports; requests are routed by the
+//! chain segment of the path:
+//!
+//! - `GET  /health`
+//! - `GET  /v1/chains`
+//! - `POST /v1/{chain}/compile-layout`
+//! - `POST /v1/{chain}/resolve-query`  `{ "layout": ..., "query": "..." }`
+//! - `POST /v1/{chain}/batch-resolve`  `{ "layout": ..., "queries": [...] }`
+//! - `POST /v1/{chain}/generate-proof` `{ "layout": ..., "query": "...", "address": "..." }`
+//!
+//! The server uses the same profile as the CLI. `generate-proof` fetches and
+//! checks the proof as the CLI's `generate-proof` does, always from the
+//! profile's RPC endpoint for the chain: requests cannot name an endpoint, so
+//! callers cannot make the server send requests to hosts of their choosing.
+//!
+//! With the `telemetry` feature the server also answers `GET /metrics` with
+//! Prometheus metrics: request counts and latency per chain and operation,
+//! batch sizes and durations, and whatever the chain crates record for RPC
+//! calls, proofs and the response cache.
+
+use crate::{CliError, CliResult, Profile};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+pub use futures_util::future::BoxFuture;
+
+/// Default bind address for `serve`
+pub const DEFAULT_BIND: &str = "127.0.0.1:8080";
+
+/// Chain-specific implementation of the API operations
+///
+/// Request bodies are passed through as JSON so each chain can accept its own
+/// input shape (an ABI for Ethereum, an IDL for Solana, ...).
+pub trait ChainHandler: Send + Sync + 'static {
+    /// Compile a storage layout from the chain's contract description
+    fn compile_layout(&self, request: &Value) -> CliResult<Value>;
+
+    /// Resolve a single query against a layout
+    fn resolve_query(&self, layout: &Value, query: &str) -> CliResult<Value>;
+
+    /// Resolve many queries against one layout
+    fn batch_resolve(&self, layout: &Value, queries: &[String]) -> CliResult<Value>;
+
+    /// Fetch and check a proof for a query at a contract address from `rpc`
+    ///
+    /// Returns what the CLI's `generate-proof` writes for the same inputs.
+    fn generate_proof<'a>(&'a self, request: &'a Value, rpc: &'a str) -> BoxFuture<'a, CliResult<Value>>;
+}
+
+/// API server configuration
+pub struct ApiServer {
+    profile: Profile,
+    chains: BTreeMap<String, Arc<dyn ChainHandler>>,
+}
+
+struct ServerState {
+    profile: Profile,
+    chains: BTreeMap<String, Arc<dyn ChainHandler>>,
+}
+
+impl ApiServer {
+    /// Create a server using the given profile
+    pub fn new(profile: Profile) -> Self {
+        Self {
+            profile,
+            chains: BTreeMap::new(),
+        }
+    }
+
+    /// Register a handler for a chain
+    pub fn with_chain(mut self, chain: &str, handler: impl ChainHandler) -> Self {
+        self.chains.insert(chain.to_string(), Arc::new(handler));
+        self
+    }
+
+    /// Build the router without binding a socket
+    pub fn router(self) -> Router {
+        let state = Arc::new(ServerState {
+            profile: self.profile,
+            chains: self.chains,
+        });
+
+        let router = Router::new()
+            .route("/health", get(health))
+            .route("/v1/chains", get(list_chains))
+            .route("/v1/:chain/:operation", post(dispatch));
+
+        #[cfg(feature = "telemetry")]
+        let router = match crate::telemetry::install() {
+            Ok(handle) => router.route("/metrics", get(move || metrics(handle))),
+            Err(e) => {
+                tracing::warn!("Serving without /metrics: {}", e);
+                router
+            }
+        };
+
+        router.with_state(state)
+    }
+
+    /// Bind to `addr` and serve until the process is stopped
+    pub async fn serve(self, addr: &str) -> CliResult<()> {
+        let addr: SocketAddr = addr
+            .parse()
+            .map_err(|e| CliError::InvalidArgument(format!("Invalid bind address '{}': {}", addr, e)))?;
+        let listener = tokio::net::TcpListener::bind(addr).await.map_err(CliError::Io)?;
+
+        eprintln!("Listening on http://{}", addr);
+        axum::serve(listener, self.router()).await.map_err(CliError::Io)
+    }
+}
+
+/// Error response with an HTTP status
+struct ApiError(StatusCode, String);
+
+impl From<CliError> for ApiError {
+    fn from(error: CliError) -> Self {
+        let status = match error {
+            CliError::InvalidArgument(_) | CliError::Json(_) | CliError::Configuration(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            CliError::FileNotFound(_) => StatusCode::NOT_FOUND,
+            CliError::Processing(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            CliError::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        ApiError(status, error.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+async fn health() -> Json<Value> {
+    Json(json!({ "status": "ok", "version": env!("CARGO_PKG_VERSION") }))
+}
+
+async fn list_chains(State(state): State<Arc<ServerState>>) -> Json<Value> {
+    Json(json!({ "chains": state.chains.keys().collect::<Vec<_>>() }))
+}
+
+#[cfg(feature = "telemetry")]
+async fn metrics(handle: &'static metrics_exporter_prometheus::PrometheusHandle) -> impl IntoResponse {
+    (
+        [(axum::http::header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        crate::telemetry::render(handle),
+    )
+}
+
+async fn dispatch(
+    State(state): State<Arc<ServerState>>,
+    Path((chain, operation)): Path<(String, String)>,
+    Json(body): Json<Value>,
+) -> Result<Json<Value>, ApiError> {
+    #[cfg(feature = "telemetry")]
+    let started = std::time::Instant::now();
+    let result = run_operation(&state, &chain, &operation, &body).await;
+    #[cfg(feature = "telemetry")]
+    crate::telemetry::record_request(
+        &chain,
+        &operation,
+        result.as_ref().map_or_else(|e| e.0, |_| StatusCode::OK).as_u16(),
+        started.elapsed(),
+    );
+    result.map(Json)
+}
+
+#[cfg_attr(
+    feature = "telemetry",
+    tracing::instrument(name = "api_request", skip(state, body), fields(chain = %chain, operation = %operation))
+)]
+async fn run_operation(state: &ServerState, chain: &str, operation: &str, body: &Value) -> Result<Value, ApiError> {
+    let handler = state.chains.get(chain).ok_or_else(|| {
+        ApiError(StatusCode::NOT_FOUND, format!("Unsupported chain '{}'", chain))
+    })?;
+
+    let result = match operation {
+        "compile-layout" => handler.compile_layout(body)?,
+        "resolve-query" => handler.resolve_query(field(body, "layout")?, str_field(body, "query")?)?,
+        "batch-resolve" => {
+            let queries: Vec<String> = serde_json::from_value(field(body, "queries")?.clone())
+                .map_err(|_| CliError::InvalidArgument("'queries' must be an array of strings".to_string()))?;
+            #[cfg(feature = "telemetry")]
+            let started = std::time::Instant::now();
+            let result = handler.batch_resolve(field(body, "layout")?, &queries)?;
+            #[cfg(feature = "telemetry")]
+            traverse_core::telemetry::record_batch(chain, queries.len(), started.elapsed());
+            result
+        }
+        "generate-proof" => {
+            if body.get("rpc").is_some() {
+                return Err(CliError::InvalidArgument(
+                    "'rpc' is not accepted; proofs are fetched from the server profile's endpoint".to_string(),
+                )
+                .into());
+            }
+            let rpc = state.profile.rpc_url(chain).ok_or_else(|| {
+                CliError::Configuration(format!("No RPC endpoint for {} in the server's profile. Set rpc.{}", chain, chain))
+            })?;
+            handler.generate_proof(body, rpc).await?
+        }
+        _ => {
+            return Err(ApiError(
+                StatusCode::NOT_FOUND,
+                format!("Unknown operation '{}'", operation),
+            ))
+        }
+    };
+
+    Ok(result)
+}
+
+/// Required field of a request body
+pub fn field<'a>(body: &'a Value, name: &str) -> CliResult<&'a Value> {
+    body.get(name)
+        .ok_or_else(|| CliError::InvalidArgument(format!("Missing field '{}'", name)))
+}
+
+/// Required string field of a request body
+pub fn str_field<'a>(body: &'a Value, name: &str) -> CliResult<&'a str> {
+    field(body, name)?
+        .as_str()
+        .ok_or_else(|| CliError::InvalidArgument(format!("Field '{}' must be a string", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    struct EchoHandler;
+
+    impl ChainHandler for EchoHandler {
+        fn compile_layout(&self, request: &Value) -> CliResult<Value> {
+            Ok(json!({ "compiled": request }))
+        }
+
+        fn resolve_query(&self, _layout: &Value, query: &str) -> CliResult<Value> {
+            if query.is_empty() {
+                return Err(CliError::Processing("empty query".to_string()));
+            }
+            Ok(json!({ "query": query }))
+        }
+
+        fn batch_resolve(&self, _layout: &Value, queries: &[String]) -> CliResult<Value> {
+            Ok(json!({ "total_queries": queries.len() }))
+        }
+
+        fn generate_proof<'a>(&'a self, _request: &'a Value, rpc: &'a str) -> BoxFuture<'a, CliResult<Value>> {
+            Box::pin(async move { Ok(json!({ "rpc": rpc })) })
+        }
+    }
+
+    async fn call(router: Router, uri: &str, body: Value) -> (StatusCode, Value) {
+        let request = Request::post(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_routes_by_chain_and_operation() {
+        let mut profile = Profile::default();
+        profile.rpc.insert("ethereum".to_string(), "https://eth.example".to_string());
+        let server = || ApiServer::new(profile.clone()).with_chain("ethereum", EchoHandler);
+
+        let (status, body) = call(
+            server().router(),
+            "/v1/ethereum/resolve-query",
+            json!({ "layout": {}, "query": "totalSupply" }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["query"], "totalSupply");
+
+        let (status, body) = call(
+            server().router(),
+            "/v1/ethereum/batch-resolve",
+            json!({ "layout": {}, "queries": ["a", "b"] }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total_queries"], 2);
+
+        let (_, body) = call(server().router(), "/v1/ethereum/generate-proof", json!({})).await;
+        assert_eq!(body["rpc"], "https://eth.example");
+
+        // Only the profile's endpoint is ever used
+        let (status, body) = call(
+            server().router(),
+            "/v1/ethereum/generate-proof",
+            json!({ "rpc": "http://169.254.169.254" }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("rpc"));
+    }
+
+    #[tokio::test]
+    async fn test_error_statuses() {
+        let server = || ApiServer::new(Profile::default()).with_chain("ethereum", EchoHandler);
+
+        let (status, _) = call(server().router(), "/v1/solana/resolve-query", json!({})).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, body) = call(server().router(), "/v1/ethereum/resolve-query", json!({ "layout": {} })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("query"));
+
+        let (status, _) = call(
+            server().router(),
+            "/v1/ethereum/resolve-query",
+            json!({ "layout": {}, "query": "" }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let (status, _) = call(server().router(), "/v1/ethereum/generate-proof", json!({})).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[cfg(feature = "telemetry")]
+    #[tokio::test]
+    async fn test_metrics_endpoint() {
+        let server = || ApiServer::new(Profile::default()).with_chain("metered", EchoHandler);
+        call(server().router(), "/v1/metered/batch-resolve", json!({ "layout": {}, "queries": ["a", "b", "c"] })).await;
+        call(server().router(), "/v1/metered/resolve-query", json!({ "layout": {} })).await;
+
+        let request = Request::get("/metrics").body(Body::empty()).unwrap();
+        let response = server().router().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let text = String::from_utf8(bytes.to_vec()).unwrap();
+
+        assert!(text.contains(
+            r#"traverse_api_requests_total{chain="metered",operation="batch-resolve",status="200"} 1"#
+        ));
+        assert!(text.contains(
+            r#"traverse_api_requests_total{chain="metered",operation="resolve-query",status="400"} 1"#
+        ));
+        assert!(text.contains(r#"traverse_batch_queries_bucket{chain="metered",le="5"} 1"#));
+        assert!(text.contains("# TYPE traverse_api_request_duration_seconds histogram"));
+    }
+}
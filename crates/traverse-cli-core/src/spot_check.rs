@@ -0,0 +1,170 @@
+//! Live spot checks of storage layouts
+//!
+//! `verify-layout` reads the current value of every entry a layout declares
+//! and checks it against the declaration: the value must fit the declared
+//! size, decode as the declared type and not contradict the declared zero
+//! semantics. Locating and decoding a value is up to each chain; this module
+//! holds the per-entry results, the zero-semantics rule they share and the
+//! report the CLIs write.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use traverse_core::ZeroSemantics;
+
+/// What a read found at an entry's location
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Observed {
+    /// Nothing was ever stored
+    Absent,
+    /// A zero or default value that may or may not have been written
+    Zero,
+    /// A zero or default value that is known to have been written
+    WrittenZero,
+    /// Anything else
+    Value,
+}
+
+/// One layout entry checked against its live value
+#[derive(Debug, Clone, Serialize)]
+pub struct SpotCheck {
+    pub field: String,
+    pub type_name: String,
+    /// Where the value was read, in the chain's own terms
+    pub location: String,
+    pub zero_semantics: ZeroSemantics,
+    /// The value as read; `None` when nothing is stored
+    pub value: Option<Value>,
+    /// Declarations the value contradicts
+    pub mismatches: Vec<String>,
+    /// Why the entry was not read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
+}
+
+impl SpotCheck {
+    pub fn new(
+        field: impl Into<String>,
+        type_name: impl Into<String>,
+        location: impl Into<String>,
+        zero_semantics: ZeroSemantics,
+    ) -> Self {
+        Self {
+            field: field.into(),
+            type_name: type_name.into(),
+            location: location.into(),
+            zero_semantics,
+            value: None,
+            mismatches: Vec::new(),
+            skipped: None,
+        }
+    }
+
+    /// Record the value read and check it against the declared zero semantics
+    pub fn observe(&mut self, value: Option<Value>, observed: Observed) {
+        self.value = value;
+        if let Some(mismatch) = zero_semantics_mismatch(self.zero_semantics, observed) {
+            self.mismatches.push(mismatch);
+        }
+    }
+
+    /// Record a declaration the value contradicts
+    pub fn mismatch(&mut self, description: impl Into<String>) {
+        self.mismatches.push(description.into());
+    }
+
+    /// Mark the entry as not checked
+    pub fn skip(&mut self, reason: impl Into<String>) {
+        self.skipped = Some(reason.into());
+    }
+}
+
+/// How a read contradicts the declared zero semantics, if it does
+///
+/// Zero semantics only say what a zero means, so a non-zero value never
+/// contradicts them, and one read cannot tell a cleared zero from an
+/// explicit one. What it can show, on chains whose reads tell absent keys
+/// from stored zeros, is a `NeverWritten` entry holding a written zero or an
+/// `ExplicitlyZero` or `Cleared` entry nothing was ever stored under.
+pub fn zero_semantics_mismatch(declared: ZeroSemantics, observed: Observed) -> Option<String> {
+    match (declared, observed) {
+        (ZeroSemantics::NeverWritten, Observed::WrittenZero) => {
+            Some("declared NeverWritten, but a zero was written".to_string())
+        }
+        (ZeroSemantics::ExplicitlyZero | ZeroSemantics::Cleared, Observed::Absent) => {
+            Some(format!("declared {:?}, but nothing was ever stored", declared))
+        }
+        _ => None,
+    }
+}
+
+/// Spot checks of the entries of one layout
+#[derive(Debug, Clone, Serialize)]
+pub struct SpotCheckReport {
+    pub chain: String,
+    /// What the layout was checked against: contract, accounts, block or height
+    pub target: Value,
+    pub checks: Vec<SpotCheck>,
+}
+
+impl SpotCheckReport {
+    pub fn new(chain: impl Into<String>, target: Value) -> Self {
+        Self { chain: chain.into(), target, checks: Vec::new() }
+    }
+
+    /// Number of entries whose value contradicts the layout
+    pub fn mismatched(&self) -> usize {
+        self.checks.iter().filter(|check| !check.mismatches.is_empty()).count()
+    }
+
+    /// The report with a summary of checked, skipped and mismatched entries
+    pub fn to_value(&self) -> Value {
+        let skipped = self.checks.iter().filter(|check| check.skipped.is_some()).count();
+        json!({
+            "chain": self.chain,
+            "target": self.target,
+            "summary": {
+                "checked": self.checks.len() - skipped,
+                "skipped": skipped,
+                "mismatched": self.mismatched(),
+            },
+            "checks": self.checks,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zero_semantics_and_summary() {
+        let mut report = SpotCheckReport::new("cosmos", json!({ "contract": "neutron1..." }));
+
+        let mut owner = SpotCheck::new("owner", "t_address", "owner", ZeroSemantics::NeverWritten);
+        owner.observe(Some(json!("")), Observed::WrittenZero);
+        report.checks.push(owner);
+
+        let mut paused = SpotCheck::new("paused", "t_bool", "paused", ZeroSemantics::ExplicitlyZero);
+        paused.observe(None, Observed::Absent);
+        report.checks.push(paused);
+
+        let mut supply = SpotCheck::new("supply", "t_uint128", "supply", ZeroSemantics::Cleared);
+        supply.observe(Some(json!("0")), Observed::WrittenZero);
+        report.checks.push(supply);
+
+        let mut admin = SpotCheck::new("admin", "t_address", "admin", ZeroSemantics::NeverWritten);
+        admin.observe(Some(json!("neutron1abc")), Observed::Value);
+        report.checks.push(admin);
+
+        let mut balances = SpotCheck::new("balances[key]", "t_mapping", "balances", ZeroSemantics::ValidZero);
+        balances.skip("map entries are read per key");
+        report.checks.push(balances);
+
+        let value = report.to_value();
+        assert_eq!(value["summary"], json!({ "checked": 4, "skipped": 1, "mismatched": 2 }));
+        assert_eq!(value["checks"][0]["mismatches"][0], "declared NeverWritten, but a zero was written");
+        assert_eq!(value["checks"][1]["mismatches"][0], "declared ExplicitlyZero, but nothing was ever stored");
+        assert!(value["checks"][2]["mismatches"].as_array().unwrap().is_empty());
+        assert!(value["checks"][3]["mismatches"].as_array().unwrap().is_empty());
+    }
+}
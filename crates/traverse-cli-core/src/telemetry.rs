@@ -0,0 +1,73 @@
+//! Prometheus metrics for serve mode
+//!
+//! With the `telemetry` feature, [`install`] sets a Prometheus recorder as the
+//! process-wide `metrics` recorder, so the counters and histograms recorded by
+//! `traverse_core::telemetry` in the chain crates and by the server itself are
+//! collected, and the server renders them at `GET /metrics`. Histograms use
+//! fixed buckets so they can be aggregated across instances.
+
+use crate::{CliError, CliResult};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use std::sync::OnceLock;
+use std::time::Duration;
+use traverse_core::telemetry::{BATCH_QUERIES, PROOF_SIZE_BYTES};
+
+pub const API_REQUESTS_TOTAL: &str = "traverse_api_requests_total";
+pub const API_REQUEST_DURATION_SECONDS: &str = "traverse_api_request_duration_seconds";
+
+/// Buckets for every `*_seconds` histogram, from 1ms to 30s
+const LATENCY_BUCKETS: &[f64] = &[0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// Buckets for proof sizes, from 256 bytes to 1 MiB
+const PROOF_SIZE_BUCKETS: &[f64] = &[256.0, 1024.0, 4096.0, 16384.0, 65536.0, 262144.0, 1048576.0];
+
+/// Buckets for queries per batch
+const BATCH_QUERY_BUCKETS: &[f64] = &[1.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 1000.0];
+
+static HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// Install the Prometheus recorder, once per process
+///
+/// Later calls return the handle of the first. Fails when another `metrics`
+/// recorder was installed first.
+pub fn install() -> CliResult<&'static PrometheusHandle> {
+    if let Some(handle) = HANDLE.get() {
+        return Ok(handle);
+    }
+
+    let handle = PrometheusBuilder::new()
+        .set_buckets_for_metric(Matcher::Suffix("_seconds".to_string()), LATENCY_BUCKETS)
+        .and_then(|builder| builder.set_buckets_for_metric(Matcher::Full(PROOF_SIZE_BYTES.to_string()), PROOF_SIZE_BUCKETS))
+        .and_then(|builder| builder.set_buckets_for_metric(Matcher::Full(BATCH_QUERIES.to_string()), BATCH_QUERY_BUCKETS))
+        .and_then(|builder| builder.install_recorder())
+        .map_err(|e| CliError::Configuration(format!("Failed to install metrics recorder: {}", e)))?;
+
+    traverse_core::telemetry::describe();
+    metrics::describe_counter!(API_REQUESTS_TOTAL, "API requests served, by response status");
+    metrics::describe_histogram!(API_REQUEST_DURATION_SECONDS, metrics::Unit::Seconds, "API request latency");
+    Ok(HANDLE.get_or_init(|| handle))
+}
+
+/// Render the current metrics in the Prometheus text format
+pub fn render(handle: &PrometheusHandle) -> String {
+    // Histograms are drained into their buckets on upkeep; scraping is the natural time
+    handle.run_upkeep();
+    handle.render()
+}
+
+/// Record one API request that answered with `status` after `elapsed`
+pub fn record_request(chain: &str, operation: &str, status: u16, elapsed: Duration) {
+    metrics::counter!(
+        API_REQUESTS_TOTAL,
+        "chain" => chain.to_string(),
+        "operation" => operation.to_string(),
+        "status" => status.to_string()
+    )
+    .increment(1);
+    metrics::histogram!(
+        API_REQUEST_DURATION_SECONDS,
+        "chain" => chain.to_string(),
+        "operation" => operation.to_string()
+    )
+    .record(elapsed.as_secs_f64());
+}
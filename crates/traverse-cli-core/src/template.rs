@@ -0,0 +1,79 @@
+//! User templates for command results
+//!
+//! `--template <file>` renders a command's structured result through a Tera
+//! template instead of one of the output formats, so a Markdown summary or an
+//! HTML dashboard can be produced without a post-processing script. The
+//! result's top-level fields are available by name, e.g. `{{ storage_key }}`,
+//! and the whole result as `result`, which is how array results are reached.
+//! Templates ending in `.html` or `.htm` are autoescaped.
+
+use crate::{CliError, CliResult, CliUtils, OutputFormat};
+use serde_json::Value;
+use std::error::Error;
+use std::path::Path;
+use tera::{Context, Tera};
+
+/// Render `value` through the template at `path`
+pub fn render(path: &Path, value: &Value) -> CliResult<String> {
+    let template = CliUtils::read_file(path)?;
+    let mut context = Context::new();
+    if let Value::Object(fields) = value {
+        for (name, field) in fields {
+            context.insert(name.as_str(), field);
+        }
+    }
+    context.insert("result", value);
+
+    let autoescape = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| matches!(ext.to_ascii_lowercase().as_str(), "html" | "htm"));
+    Tera::one_off(&template, &context, autoescape).map_err(|e| {
+        // Tera puts the useful part of parse and render errors in the source chain
+        let mut message = e.to_string();
+        let mut source = e.source();
+        while let Some(cause) = source {
+            message.push_str(": ");
+            message.push_str(&cause.to_string());
+            source = cause.source();
+        }
+        CliError::Processing(format!("Failed to render template '{}': {}", path.display(), message))
+    })
+}
+
+/// Render through `template` when one is given, otherwise format as `format`
+pub fn render_or_format(value: &Value, format: &OutputFormat, template: Option<&Path>) -> CliResult<String> {
+    match template {
+        Some(template) => render(template, value),
+        None => CliUtils::format_json(value, format),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_fields_and_result() {
+        let dir = tempfile::tempdir().unwrap();
+        let value = json!({ "query": "_balances[0x01]", "storage_key": "ab", "results": [1, 2] });
+
+        let markdown = dir.path().join("report.md");
+        std::fs::write(&markdown, "# {{ query }}\n{% for r in result.results %}- {{ r }}\n{% endfor %}").unwrap();
+        assert_eq!(render(&markdown, &value).unwrap(), "# _balances[0x01]\n- 1\n- 2\n");
+
+        // HTML templates escape values, other templates write them as is
+        let html = dir.path().join("report.html");
+        std::fs::write(&html, "<td>{{ query }}</td>").unwrap();
+        let quoted = json!({ "query": "a<b" });
+        assert_eq!(render(&html, &quoted).unwrap(), "<td>a&lt;b</td>");
+        let plain = dir.path().join("report.txt");
+        std::fs::write(&plain, "{{ query }}").unwrap();
+        assert_eq!(render(&plain, &quoted).unwrap(), "a<b");
+
+        std::fs::write(&markdown, "{{ missing }}").unwrap();
+        let error = render(&markdown, &value).unwrap_err().to_string();
+        assert!(error.contains("missing"), "{}", error);
+    }
+}
@@ -0,0 +1,335 @@
+//! Regenerate outputs when their inputs change
+//!
+//! `watch` follows a batch config and the input file each entry names (an
+//! ABI, IDL or schema) and regenerates only the entries a change affects.
+//! Files are watched through their directories, since editors commonly save
+//! by replacing the file, and events are debounced and then checked against
+//! SHA-256 content hashes, so a touch or a save without edits does nothing.
+//!
+//! An entry's fingerprint covers its config and its input's hash. After each
+//! change, entries whose fingerprint moved, or that are new, are generated
+//! again into `<output>/<name>/`, and a [`ChangeReport`] is POSTed to the
+//! webhook, or written to stdout as NDJSON when no webhook is set. Failed
+//! entries are retried on the next change.
+
+use crate::pool::batch_entries;
+use crate::project::entry_name;
+use crate::{CliError, CliResult, CliUtils};
+use notify::{RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// What a chain's `watch` follows and where changes are delivered
+#[derive(Debug, Clone)]
+pub struct WatchOptions {
+    /// Section of a project config to take entries from
+    pub chain: &'static str,
+    /// Entry field naming its input file, relative to the config
+    pub input_key: &'static str,
+    pub webhook: Option<String>,
+    /// Quiet period after an event before inputs are rehashed
+    pub debounce: Duration,
+}
+
+/// One watched file whose content changed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileChange {
+    pub path: String,
+    /// SHA-256 before the change; `None` if the file did not exist
+    pub previous: Option<String>,
+    /// SHA-256 after the change; `None` if the file was removed
+    pub current: Option<String>,
+}
+
+/// A change and what was regenerated for it, as delivered to the webhook
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChangeReport {
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub changed_files: Vec<FileChange>,
+    pub regenerated: Vec<String>,
+    /// Error by entry name
+    pub failed: BTreeMap<String, String>,
+    /// Entries no longer in the config; their outputs are left in place
+    pub removed: Vec<String>,
+}
+
+/// Hashes of the watched files and fingerprints of the entries
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WatchState {
+    /// Content hash by path; `None` for a file that does not exist
+    pub files: BTreeMap<PathBuf, Option<String>>,
+    /// Fingerprint by entry name
+    pub entries: BTreeMap<String, String>,
+}
+
+/// Entries to regenerate and drop between two states
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StateDiff {
+    pub changed_files: Vec<FileChange>,
+    pub affected: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl StateDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changed_files.is_empty() && self.affected.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl WatchState {
+    /// Hash the config and every entry's input, returning the state with the
+    /// named entries it was built from
+    pub fn scan(config_path: &Path, options: &WatchOptions) -> CliResult<(Self, Vec<(String, Value)>)> {
+        let mut state = Self::default();
+        state.files.insert(config_path.to_path_buf(), hash_file(config_path));
+
+        let config = CliUtils::load_config(config_path)?;
+        let base = config_path.parent().unwrap_or(Path::new(""));
+        let mut entries = Vec::new();
+        for (index, entry) in batch_entries(&config, options.chain)?.into_iter().enumerate() {
+            let name = entry_name(&entry, index);
+            let input_hash = match entry.get(options.input_key).and_then(Value::as_str) {
+                Some(input) => {
+                    let path = base.join(input);
+                    let hash = hash_file(&path);
+                    state.files.insert(path, hash.clone());
+                    hash
+                }
+                None => None,
+            };
+            let fingerprint = Sha256::new()
+                .chain_update(entry.to_string())
+                .chain_update(input_hash.unwrap_or_default())
+                .finalize();
+            state.entries.insert(name.clone(), hex::encode(fingerprint));
+            entries.push((name, entry));
+        }
+        Ok((state, entries))
+    }
+
+    /// Files whose hash changed and entries whose fingerprint changed since `self`
+    ///
+    /// Files only `self` watched belong to removed entries and are not
+    /// reported as changed.
+    pub fn diff(&self, next: &Self) -> StateDiff {
+        let changed_files = next
+            .files
+            .iter()
+            .filter_map(|(path, current)| {
+                let previous = self.files.get(path).cloned().flatten();
+                (previous != *current).then(|| FileChange {
+                    path: path.display().to_string(),
+                    previous,
+                    current: current.clone(),
+                })
+            })
+            .collect();
+        let affected = next
+            .entries
+            .iter()
+            .filter(|(name, fingerprint)| self.entries.get(*name) != Some(*fingerprint))
+            .map(|(name, _)| name.clone())
+            .collect();
+        let removed = self.entries.keys().filter(|name| !next.entries.contains_key(*name)).cloned().collect();
+        StateDiff { changed_files, affected, removed }
+    }
+
+    /// Directories to watch so every file in the state is seen
+    fn directories(&self) -> BTreeSet<PathBuf> {
+        self.files
+            .keys()
+            .map(|path| match path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+                _ => PathBuf::from("."),
+            })
+            .collect()
+    }
+}
+
+/// SHA-256 of a file's content, or `None` if it cannot be read
+pub fn hash_file(path: &Path) -> Option<String> {
+    fs::read(path).ok().map(|content| hex::encode(Sha256::digest(content)))
+}
+
+/// Watch `config_path` and regenerate affected entries until interrupted
+///
+/// Every entry is generated once at startup. After that only changes are
+/// acted on, and a config that fails to parse mid-edit is reported and
+/// skipped until the next save.
+pub async fn run_watch<F, Fut>(config_path: &Path, output_dir: &Path, options: &WatchOptions, run_entry: F) -> CliResult<()>
+where
+    F: Fn(Value, PathBuf) -> Fut,
+    Fut: Future<Output = CliResult<()>>,
+{
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| CliError::Processing(format!("Failed to build webhook client: {}", e)))?;
+
+    let (mut state, entries) = WatchState::scan(config_path, options)?;
+    let failed = regenerate(&entries, output_dir, &run_entry).await;
+    for name in failed.keys() {
+        state.entries.remove(name);
+    }
+    eprintln!("Generated {}/{} entries into {}", entries.len() - failed.len(), entries.len(), output_dir.display());
+
+    let (events, mut changes) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event {
+            let _ = events.send(event);
+        }
+    })
+    .map_err(|e| CliError::Processing(format!("Failed to start file watcher: {}", e)))?;
+    let mut watched = BTreeSet::new();
+    watch_directories(&mut watcher, &mut watched, &state);
+    eprintln!("Watching {} files; press Ctrl-C to stop", state.files.len());
+
+    while changes.recv().await.is_some() {
+        // Let a burst of events from one save settle before rehashing
+        tokio::time::sleep(options.debounce).await;
+        while changes.try_recv().is_ok() {}
+
+        let (next, entries) = match WatchState::scan(config_path, options) {
+            Ok(scanned) => scanned,
+            Err(e) => {
+                eprintln!("Warning: {}; waiting for the next change", e);
+                continue;
+            }
+        };
+        let diff = state.diff(&next);
+        if diff.changed_files.is_empty() && diff.affected.is_empty() {
+            continue;
+        }
+
+        let affected: Vec<(String, Value)> =
+            entries.into_iter().filter(|(name, _)| diff.affected.contains(name)).collect();
+        let failed = regenerate(&affected, output_dir, &run_entry).await;
+        let report = ChangeReport {
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()),
+            changed_files: diff.changed_files,
+            regenerated: affected.iter().map(|(name, _)| name.clone()).filter(|name| !failed.contains_key(name)).collect(),
+            failed,
+            removed: diff.removed,
+        };
+        state = next;
+        for name in report.failed.keys() {
+            state.entries.remove(name);
+        }
+        watch_directories(&mut watcher, &mut watched, &state);
+
+        eprintln!(
+            "{} files changed: regenerated {}, failed {}, removed {}",
+            report.changed_files.len(),
+            report.regenerated.len(),
+            report.failed.len(),
+            report.removed.len()
+        );
+        if let Err(e) = deliver(&client, options.webhook.as_deref(), &report).await {
+            eprintln!("Warning: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Generate each entry into `<output>/<name>/`, returning the failures
+async fn regenerate<F, Fut>(entries: &[(String, Value)], output_dir: &Path, run_entry: &F) -> BTreeMap<String, String>
+where
+    F: Fn(Value, PathBuf) -> Fut,
+    Fut: Future<Output = CliResult<()>>,
+{
+    let mut failed = BTreeMap::new();
+    for (name, entry) in entries {
+        if let Err(e) = run_entry(entry.clone(), output_dir.join(name)).await {
+            eprintln!("✗ {}: {}", name, e);
+            failed.insert(name.clone(), e.to_string());
+        }
+    }
+    failed
+}
+
+/// Start watching directories the state needs that are not watched yet
+///
+/// A directory that cannot be watched, e.g. because it does not exist yet,
+/// is retried after the next change.
+fn watch_directories(watcher: &mut impl Watcher, watched: &mut BTreeSet<PathBuf>, state: &WatchState) {
+    for dir in state.directories() {
+        if watched.contains(&dir) {
+            continue;
+        }
+        match watcher.watch(&dir, RecursiveMode::NonRecursive) {
+            Ok(()) => {
+                watched.insert(dir);
+            }
+            Err(e) => eprintln!("Warning: cannot watch {}: {}", dir.display(), e),
+        }
+    }
+}
+
+/// POST a report to the webhook, or print it as one NDJSON line
+async fn deliver(client: &reqwest::Client, webhook: Option<&str>, report: &ChangeReport) -> CliResult<()> {
+    let Some(url) = webhook else {
+        println!("{}", serde_json::to_string(report)?);
+        return Ok(());
+    };
+    let response = client
+        .post(url)
+        .json(report)
+        .send()
+        .await
+        .map_err(|e| CliError::Processing(format!("Webhook delivery failed: {}", e)))?;
+    if !response.status().is_success() {
+        return Err(CliError::Processing(format!("Webhook returned {}", response.status())));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_scan_and_diff_track_content_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join("batch.json");
+        let options = WatchOptions { chain: "ethereum", input_key: "abi", webhook: None, debounce: Duration::ZERO };
+        fs::write(dir.path().join("a.json"), "[]").unwrap();
+        fs::write(dir.path().join("b.json"), "[]").unwrap();
+        let entries = json!([
+            { "name": "a", "abi": "a.json", "contract": "0x01", "queries": ["x"] },
+            { "name": "b", "abi": "b.json", "contract": "0x02", "queries": ["y"] }
+        ]);
+        fs::write(&config, entries.to_string()).unwrap();
+        let (initial, named) = WatchState::scan(&config, &options).unwrap();
+        assert_eq!(named.len(), 2);
+        assert_eq!(initial.files.len(), 3);
+
+        // Rewriting identical content is not a change
+        fs::write(dir.path().join("a.json"), "[]").unwrap();
+        assert!(initial.diff(&WatchState::scan(&config, &options).unwrap().0).is_empty());
+
+        // An input edit affects only the entry that reads it
+        fs::write(dir.path().join("b.json"), "[{}]").unwrap();
+        let (edited, _) = WatchState::scan(&config, &options).unwrap();
+        let diff = initial.diff(&edited);
+        assert_eq!(diff.affected, vec!["b"]);
+        assert_eq!(diff.changed_files.len(), 1);
+        assert!(diff.changed_files[0].path.ends_with("b.json"));
+
+        // Dropping an entry from the config removes it without regenerating the other
+        fs::write(&config, json!([entries[0]]).to_string()).unwrap();
+        let (dropped, _) = WatchState::scan(&config, &options).unwrap();
+        let diff = edited.diff(&dropped);
+        assert!(diff.affected.is_empty());
+        assert_eq!(diff.removed, vec!["b"]);
+        assert_eq!(diff.changed_files.len(), 1);
+        assert!(diff.changed_files[0].path.ends_with("batch.json"));
+    }
+}
@@ -0,0 +1,926 @@
+//! Controller witness inspection
+//!
+//! Decodes the witness bytes produced by the valence controller so they can be
+//! checked without writing Rust. Each layout is parsed by the
+//! `traverse_valence` module that writes and checks it, which accounts for
+//! every byte. Witnesses carry no tag, so the layout is the first one the
+//! bytes parse as, or is named with `--layout` when a witness fits more
+//! than one:
+//!
+//! - Extended storage witness (176 + proof bytes): key, layout commitment,
+//!   value, zero semantics, semantic source, block height and hash, proof,
+//!   field index and expected slot. This is what the controller writes today.
+//! - Legacy storage witness (102 + proof bytes): the same fields without the
+//!   block binding, field index or expected slot.
+//! - Solana account witness (156 bytes): account, owner, extracted value,
+//!   lamports, rent epoch, slot, block hash and field offset.
+//! - Sui object witness (64 + BCS bytes): checkpoint and its digest, field
+//!   offset and size, and the BCS of the object, the effects of the
+//!   transaction that wrote it, and the checkpoint's contents and summary.
+//! - Substrate storage witness (60 + header, key and proof bytes): block
+//!   hash, SCALE header, storage key, child trie key, the main and child
+//!   trie proof items, and the field offset and size.
+//! - Starknet storage witness (273 + trie node bytes): block number, state
+//!   commitment, contracts and classes roots, contract address and leaf,
+//!   contracts trie nodes, storage address, and the storage trie nodes of
+//!   each felt of the value.
+//! - Bitcoin transaction witness (30 + header, branch and transaction
+//!   bytes): block height, the headers from the transaction's block to the
+//!   tip, the Merkle index, transaction count and branch, the raw
+//!   transaction and the output index.
+//! - TON account witness (216 + bag of cells, location and type bytes): the
+//!   masterchain and shard block IDs, the account, the bags of cells of the
+//!   shard proof, account proof and account state, and the field's location
+//!   in the account's data and its TL-B type.
+//!
+//! All integers are little-endian. Bitcoin hashes are printed in the reversed
+//! byte order RPCs and explorers show.
+
+use crate::{CliError, CliResult, CliUtils, OutputFormat};
+use clap::{Subcommand, ValueEnum};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::path::Path;
+use traverse_core::{SemanticSource, ZeroSemantics};
+use traverse_valence::witness as valence_witness;
+use traverse_valence::{bitcoin, starknet, substrate, sui, ton};
+
+/// Witness layouts, in the order detection tries them
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WitnessLayout {
+    Extended,
+    Legacy,
+    Substrate,
+    Bitcoin,
+    Solana,
+    Sui,
+    Starknet,
+    Ton,
+}
+
+impl WitnessLayout {
+    const ALL: [WitnessLayout; 8] = [
+        WitnessLayout::Extended,
+        WitnessLayout::Legacy,
+        WitnessLayout::Substrate,
+        WitnessLayout::Bitcoin,
+        WitnessLayout::Solana,
+        WitnessLayout::Sui,
+        WitnessLayout::Starknet,
+        WitnessLayout::Ton,
+    ];
+}
+
+/// `witness` subcommands shared by the ecosystem CLIs
+#[derive(Subcommand, Debug, Clone)]
+pub enum WitnessCommand {
+    /// Decode a controller witness and print its fields
+    Inspect {
+        /// Witness file (raw bytes, or hex text)
+        file: String,
+        /// Layout to decode with, when the length alone is ambiguous
+        #[arg(long, value_enum)]
+        layout: Option<WitnessLayout>,
+    },
+}
+
+/// Run a `witness` subcommand
+///
+/// The traverse format prints a text report; other formats write the decoded
+/// fields as a structured value.
+pub fn run_witness_command(command: &WitnessCommand, format: &OutputFormat, output: Option<&str>) -> CliResult<()> {
+    match command {
+        WitnessCommand::Inspect { file, layout } => {
+            let bytes = read_witness_file(Path::new(file))?;
+            let witness = match layout {
+                Some(layout) => decode_as(&bytes, *layout)?,
+                None => decode(&bytes)?,
+            };
+            match format {
+                OutputFormat::Traverse => CliUtils::write_output(&witness.report(), output),
+                _ => {
+                    let value = serde_json::to_value(&witness).map_err(CliError::Json)?;
+                    CliUtils::write_value(&value, format, output)
+                }
+            }
+        }
+    }
+}
+
+/// Block a storage witness is bound to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BlockBinding {
+    pub height: u64,
+    pub hash: String,
+}
+
+/// Decoded storage witness (extended or legacy)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StorageWitness {
+    pub storage_key: String,
+    pub layout_commitment: String,
+    pub value: String,
+    pub zero_semantics: ZeroSemantics,
+    pub semantic_source: SemanticSource,
+    /// Absent in legacy witnesses
+    pub block: Option<BlockBinding>,
+    pub proof_len: usize,
+    /// Absent in legacy witnesses
+    pub field_index: Option<u16>,
+    /// Absent in legacy witnesses
+    pub expected_slot: Option<String>,
+}
+
+/// Decoded Solana account witness
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SolanaAccountWitness {
+    pub account_address: String,
+    pub owner: String,
+    pub value: String,
+    pub lamports: u64,
+    pub rent_epoch: u64,
+    pub slot: u64,
+    pub block_hash: String,
+    pub field_offset: u32,
+}
+
+/// Decoded Sui object witness
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SuiObjectWitness {
+    pub checkpoint: u64,
+    pub checkpoint_digest: String,
+    pub field_offset: u32,
+    /// Zero when the witness proves the object ID
+    pub field_size: u32,
+    pub object: String,
+    pub effects_len: usize,
+    pub checkpoint_contents_len: usize,
+    pub checkpoint_summary_len: usize,
+}
+
+/// Decoded Substrate storage witness
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SubstrateStorageWitness {
+    pub block_hash: String,
+    pub header: String,
+    pub storage_key: String,
+    /// Absent for values in the main trie
+    pub child_storage_key: Option<String>,
+    pub proof_items: usize,
+    pub child_proof_items: usize,
+    pub field_offset: u32,
+    /// Zero when the witness takes the whole value
+    pub field_size: u32,
+}
+
+/// Decoded Starknet storage witness
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StarknetStorageWitness {
+    pub block_number: u64,
+    pub state_commitment: String,
+    pub contracts_root: String,
+    pub classes_root: String,
+    pub contract_address: String,
+    pub class_hash: String,
+    pub nonce: String,
+    pub storage_root: String,
+    pub contract_nodes: usize,
+    pub storage_address: String,
+    /// Storage trie nodes of each felt of the value
+    pub value_nodes: Vec<usize>,
+}
+
+/// Decoded Bitcoin transaction witness
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BitcoinTxWitness {
+    pub block_hash: String,
+    pub block_height: u64,
+    pub block_header: String,
+    pub tip_hash: String,
+    pub confirmations: u32,
+    pub merkle_index: u32,
+    pub tx_count: u32,
+    pub merkle_branch: Vec<String>,
+    pub transaction: String,
+    /// Absent when the witness proves only the transaction's inclusion
+    pub vout: Option<u32>,
+}
+
+/// Decoded TON block ID
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TonBlock {
+    pub workchain: i32,
+    pub shard: String,
+    pub seqno: u32,
+    pub root_hash: String,
+    pub file_hash: String,
+}
+
+/// Decoded TON account witness
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TonAccountWitness {
+    pub mc_block: TonBlock,
+    pub shard_block: TonBlock,
+    pub workchain: i32,
+    pub account_id: String,
+    pub shard_proof_len: usize,
+    pub proof_len: usize,
+    pub state_len: usize,
+    /// Reference indexes from the data cell to the field's cell
+    pub refs: Vec<u8>,
+    pub bit: u16,
+    pub next_ref: u8,
+    pub field_type: String,
+}
+
+/// A decoded witness, tagged by the layout it was read with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "format", rename_all = "snake_case")]
+pub enum DecodedWitness {
+    Extended(StorageWitness),
+    Legacy(StorageWitness),
+    SolanaAccount(SolanaAccountWitness),
+    SuiObject(SuiObjectWitness),
+    SubstrateStorage(SubstrateStorageWitness),
+    StarknetStorage(StarknetStorageWitness),
+    BitcoinTx(BitcoinTxWitness),
+    TonAccount(TonAccountWitness),
+}
+
+/// Read a witness file, accepting raw bytes or hex text (with or without `0x`)
+pub fn read_witness_file(path: &Path) -> CliResult<Vec<u8>> {
+    if !path.exists() {
+        return Err(CliError::FileNotFound(path.to_string_lossy().to_string()));
+    }
+    let bytes = std::fs::read(path).map_err(CliError::Io)?;
+
+    let text = std::str::from_utf8(&bytes).map(str::trim).unwrap_or_default();
+    let text = text.strip_prefix("0x").unwrap_or(text);
+    if !text.is_empty() && text.len().is_multiple_of(2) && text.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return hex::decode(text).map_err(|e| CliError::InvalidArgument(e.to_string()));
+    }
+    Ok(bytes)
+}
+
+/// Decode witness bytes, detecting the layout
+///
+/// The first layout the bytes parse as, in [`WitnessLayout`] order, is used.
+pub fn decode(bytes: &[u8]) -> CliResult<DecodedWitness> {
+    WitnessLayout::ALL
+        .into_iter()
+        .find_map(|layout| decode_as(bytes, layout).ok())
+        .ok_or_else(|| {
+            CliError::InvalidArgument(format!(
+                "{} bytes is not a known witness layout (extended storage is {}+proof, legacy storage is {}+proof, \
+                 Substrate storage is {}+header, key and proof, Bitcoin transaction is {}+headers, branch and transaction, Solana account is {}, Sui object is {}+BCS, \
+                 Starknet storage is {}+trie nodes, TON account is {}+bags of cells, location and type)",
+                bytes.len(),
+                valence_witness::STORAGE_WITNESS_FIXED_LEN,
+                valence_witness::LEGACY_WITNESS_FIXED_LEN,
+                substrate::WITNESS_FIXED_LEN,
+                bitcoin::WITNESS_FIXED_LEN,
+                valence_witness::SOLANA_WITNESS_LEN,
+                sui::WITNESS_FIXED_LEN,
+                starknet::WITNESS_FIXED_LEN,
+                ton::WITNESS_FIXED_LEN
+            ))
+        })
+}
+
+/// Decode witness bytes with a given layout
+///
+/// The bytes are parsed by the module that checks the layout, so every byte
+/// must be accounted for.
+pub fn decode_as(bytes: &[u8], layout: WitnessLayout) -> CliResult<DecodedWitness> {
+    use valence_witness::{SolanaAccountWitness as Solana, StorageWitness as Storage};
+    let witness = match layout {
+        WitnessLayout::Extended => Storage::parse(bytes).map(|w| DecodedWitness::Extended(w.into())),
+        WitnessLayout::Legacy => Storage::parse_legacy(bytes).map(|w| DecodedWitness::Legacy(w.into())),
+        WitnessLayout::Solana => Solana::parse(bytes).map(|w| DecodedWitness::SolanaAccount(w.into())),
+        WitnessLayout::Sui => sui::SuiWitness::parse(bytes).map(|w| DecodedWitness::SuiObject(w.into())),
+        WitnessLayout::Substrate => {
+            substrate::SubstrateWitness::parse(bytes).map(|w| DecodedWitness::SubstrateStorage(w.into()))
+        }
+        WitnessLayout::Starknet => {
+            starknet::StarknetWitness::parse(bytes).map(|w| DecodedWitness::StarknetStorage(w.into()))
+        }
+        WitnessLayout::Bitcoin => {
+            bitcoin::BitcoinWitness::parse(bytes).and_then(|w| w.try_into().map(DecodedWitness::BitcoinTx))
+        }
+        WitnessLayout::Ton => ton::TonWitness::parse(bytes).map(|w| DecodedWitness::TonAccount(w.into())),
+    };
+    witness.map_err(|e| CliError::InvalidArgument(format!("{} bytes is not a {:?} witness: {}", bytes.len(), layout, e)))
+}
+
+/// A Bitcoin hash, in the byte order RPCs show
+fn reversed_hex(hash: &[u8]) -> String {
+    let mut bytes = hash.to_vec();
+    bytes.reverse();
+    hex::encode(bytes)
+}
+
+/// Hash of a Bitcoin block header, in the byte order RPCs show
+fn bitcoin_block_hash(header: &[u8]) -> String {
+    reversed_hex(&Sha256::digest(Sha256::digest(header)))
+}
+
+impl From<valence_witness::StorageWitness> for StorageWitness {
+    fn from(w: valence_witness::StorageWitness) -> Self {
+        let zero_semantics = match w.zero_semantics {
+            0 => ZeroSemantics::NeverWritten,
+            1 => ZeroSemantics::ExplicitlyZero,
+            2 => ZeroSemantics::Cleared,
+            _ => ZeroSemantics::ValidZero,
+        };
+        let semantic_source = match w.semantic_source {
+            0 => SemanticSource::Declared,
+            1 => SemanticSource::EventValidated,
+            _ => SemanticSource::DeclaredOverride,
+        };
+        StorageWitness {
+            storage_key: hex::encode(w.storage_key),
+            layout_commitment: hex::encode(w.layout_commitment),
+            value: hex::encode(w.value),
+            zero_semantics,
+            semantic_source,
+            block: w.block.map(|block| BlockBinding {
+                height: block.height,
+                hash: hex::encode(block.hash),
+            }),
+            proof_len: w.proof.len(),
+            field_index: w.field.map(|field| field.index),
+            expected_slot: w.field.map(|field| hex::encode(field.expected_slot)),
+        }
+    }
+}
+
+impl From<valence_witness::SolanaAccountWitness> for SolanaAccountWitness {
+    fn from(w: valence_witness::SolanaAccountWitness) -> Self {
+        SolanaAccountWitness {
+            account_address: hex::encode(w.account_address),
+            owner: hex::encode(w.owner),
+            value: hex::encode(w.value),
+            lamports: w.lamports,
+            rent_epoch: w.rent_epoch,
+            slot: w.slot,
+            block_hash: hex::encode(w.block_hash),
+            field_offset: w.field_offset,
+        }
+    }
+}
+
+impl From<sui::SuiWitness> for SuiObjectWitness {
+    fn from(w: sui::SuiWitness) -> Self {
+        SuiObjectWitness {
+            checkpoint: w.checkpoint,
+            checkpoint_digest: hex::encode(w.checkpoint_digest),
+            field_offset: w.field_offset,
+            field_size: w.field_size,
+            object: hex::encode(&w.object),
+            effects_len: w.effects.len(),
+            checkpoint_contents_len: w.checkpoint_contents.len(),
+            checkpoint_summary_len: w.checkpoint_summary.len(),
+        }
+    }
+}
+
+impl From<substrate::SubstrateWitness> for SubstrateStorageWitness {
+    fn from(w: substrate::SubstrateWitness) -> Self {
+        SubstrateStorageWitness {
+            block_hash: hex::encode(w.block_hash),
+            header: hex::encode(&w.header),
+            storage_key: hex::encode(&w.storage_key),
+            child_storage_key: w.child_storage_key.map(hex::encode),
+            proof_items: w.proof.len(),
+            child_proof_items: w.child_proof.len(),
+            field_offset: w.field_offset,
+            field_size: w.field_size,
+        }
+    }
+}
+
+impl From<starknet::StarknetWitness> for StarknetStorageWitness {
+    fn from(w: starknet::StarknetWitness) -> Self {
+        StarknetStorageWitness {
+            block_number: w.block_number,
+            state_commitment: hex::encode(w.state_commitment.to_bytes_be()),
+            contracts_root: hex::encode(w.contracts_root.to_bytes_be()),
+            classes_root: hex::encode(w.classes_root.to_bytes_be()),
+            contract_address: hex::encode(w.contract_address.to_bytes_be()),
+            class_hash: hex::encode(w.contract.class_hash.to_bytes_be()),
+            nonce: hex::encode(w.contract.nonce.to_bytes_be()),
+            storage_root: hex::encode(w.contract.storage_root.to_bytes_be()),
+            contract_nodes: w.contract_nodes.len(),
+            storage_address: hex::encode(w.storage_address.to_bytes_be()),
+            value_nodes: w.value_nodes.iter().map(Vec::len).collect(),
+        }
+    }
+}
+
+impl TryFrom<bitcoin::BitcoinWitness> for BitcoinTxWitness {
+    type Error = &'static str;
+
+    fn try_from(w: bitcoin::BitcoinWitness) -> Result<Self, Self::Error> {
+        let (Some(block_header), Some(tip_header)) = (w.headers.first(), w.headers.last()) else {
+            return Err("Bitcoin witness has no headers");
+        };
+        Ok(BitcoinTxWitness {
+            block_hash: bitcoin_block_hash(block_header),
+            block_height: w.block_height,
+            block_header: hex::encode(block_header),
+            tip_hash: bitcoin_block_hash(tip_header),
+            confirmations: w.headers.len() as u32,
+            merkle_index: w.merkle_index,
+            tx_count: w.tx_count,
+            merkle_branch: w.branch.iter().map(|hash| reversed_hex(hash)).collect(),
+            transaction: hex::encode(&w.transaction),
+            vout: w.vout,
+        })
+    }
+}
+
+impl From<ton::BlockIdExt> for TonBlock {
+    fn from(block: ton::BlockIdExt) -> Self {
+        TonBlock {
+            workchain: block.workchain,
+            shard: format!("{:016x}", block.shard),
+            seqno: block.seqno,
+            root_hash: hex::encode(block.root_hash),
+            file_hash: hex::encode(block.file_hash),
+        }
+    }
+}
+
+impl From<ton::TonWitness> for TonAccountWitness {
+    fn from(w: ton::TonWitness) -> Self {
+        TonAccountWitness {
+            mc_block: w.mc_block.into(),
+            shard_block: w.shard_block.into(),
+            workchain: w.workchain,
+            account_id: hex::encode(w.account_id),
+            shard_proof_len: w.shard_proof.len(),
+            proof_len: w.proof.len(),
+            state_len: w.state.len(),
+            refs: w.location.refs,
+            bit: w.location.bit,
+            next_ref: w.location.next_ref,
+            field_type: w.location.type_name,
+        }
+    }
+}
+
+impl DecodedWitness {
+    /// Human-readable summary of the witness fields
+    pub fn report(&self) -> String {
+        let mut out = String::new();
+        match self {
+            DecodedWitness::Extended(w) | DecodedWitness::Legacy(w) => {
+                let format = if matches!(self, DecodedWitness::Extended(_)) { "extended storage" } else { "legacy storage" };
+                let _ = writeln!(out, "Format:            {}", format);
+                let _ = writeln!(out, "Storage key:       0x{}", w.storage_key);
+                let _ = writeln!(out, "Layout commitment: 0x{}", w.layout_commitment);
+                let _ = writeln!(out, "Value:             0x{}", w.value);
+                let _ = writeln!(out, "Zero semantics:    {:?}", w.zero_semantics);
+                let _ = writeln!(out, "Semantic source:   {:?}", w.semantic_source);
+                match &w.block {
+                    Some(block) => {
+                        let _ = writeln!(out, "Block height:      {}", block.height);
+                        let _ = writeln!(out, "Block hash:        0x{}", block.hash);
+                    }
+                    None => {
+                        let _ = writeln!(out, "Block binding:     none");
+                    }
+                }
+                let _ = writeln!(out, "Proof length:      {} bytes", w.proof_len);
+                if let (Some(index), Some(slot)) = (w.field_index, &w.expected_slot) {
+                    let _ = writeln!(out, "Field index:       {}", index);
+                    let _ = writeln!(out, "Expected slot:     0x{}", slot);
+                }
+            }
+            DecodedWitness::SolanaAccount(w) => {
+                let _ = writeln!(out, "Format:            solana account");
+                let _ = writeln!(out, "Account:           0x{}", w.account_address);
+                let _ = writeln!(out, "Owner:             0x{}", w.owner);
+                let _ = writeln!(out, "Value:             0x{}", w.value);
+                let _ = writeln!(out, "Lamports:          {}", w.lamports);
+                let _ = writeln!(out, "Rent epoch:        {}", w.rent_epoch);
+                let _ = writeln!(out, "Slot:              {}", w.slot);
+                let _ = writeln!(out, "Block hash:        0x{}", w.block_hash);
+                let _ = writeln!(out, "Field offset:      {}", w.field_offset);
+            }
+            DecodedWitness::SuiObject(w) => {
+                let _ = writeln!(out, "Format:            sui object");
+                let _ = writeln!(out, "Checkpoint:        {}", w.checkpoint);
+                let _ = writeln!(out, "Checkpoint digest: 0x{}", w.checkpoint_digest);
+                match w.field_size {
+                    0 => {
+                        let _ = writeln!(out, "Field:             object ID");
+                    }
+                    size => {
+                        let _ = writeln!(out, "Field:             {} bytes at {}", size, w.field_offset);
+                    }
+                }
+                let _ = writeln!(out, "Object:            0x{}", w.object);
+                let _ = writeln!(out, "Effects:           {} bytes", w.effects_len);
+                let _ = writeln!(out, "Checkpoint data:   {} + {} bytes", w.checkpoint_contents_len, w.checkpoint_summary_len);
+            }
+            DecodedWitness::SubstrateStorage(w) => {
+                let _ = writeln!(out, "Format:            substrate storage");
+                let _ = writeln!(out, "Block hash:        0x{}", w.block_hash);
+                let _ = writeln!(out, "Header:            0x{}", w.header);
+                let _ = writeln!(out, "Storage key:       0x{}", w.storage_key);
+                match &w.child_storage_key {
+                    Some(key) => {
+                        let _ = writeln!(out, "Child trie key:    0x{}", key);
+                    }
+                    None => {
+                        let _ = writeln!(out, "Child trie key:    none");
+                    }
+                }
+                let _ = writeln!(out, "Proof items:       {} + {} in the child trie", w.proof_items, w.child_proof_items);
+                match w.field_size {
+                    0 => {
+                        let _ = writeln!(out, "Field:             whole value");
+                    }
+                    size => {
+                        let _ = writeln!(out, "Field:             {} bytes at {}", size, w.field_offset);
+                    }
+                }
+            }
+            DecodedWitness::StarknetStorage(w) => {
+                let _ = writeln!(out, "Format:            starknet storage");
+                let _ = writeln!(out, "Block number:      {}", w.block_number);
+                let _ = writeln!(out, "State commitment:  0x{}", w.state_commitment);
+                let _ = writeln!(out, "Contracts root:    0x{}", w.contracts_root);
+                let _ = writeln!(out, "Classes root:      0x{}", w.classes_root);
+                let _ = writeln!(out, "Contract:          0x{}", w.contract_address);
+                let _ = writeln!(out, "Class hash:        0x{}", w.class_hash);
+                let _ = writeln!(out, "Nonce:             0x{}", w.nonce);
+                let _ = writeln!(out, "Storage root:      0x{}", w.storage_root);
+                let _ = writeln!(out, "Contract nodes:    {}", w.contract_nodes);
+                let _ = writeln!(out, "Storage address:   0x{}", w.storage_address);
+                let _ = writeln!(out, "Felt count:        {}", w.value_nodes.len());
+                let counts: Vec<String> = w.value_nodes.iter().map(|count| count.to_string()).collect();
+                let _ = writeln!(out, "Value nodes:       {}", counts.join(" + "));
+            }
+            DecodedWitness::BitcoinTx(w) => {
+                let _ = writeln!(out, "Format:            bitcoin transaction");
+                let _ = writeln!(out, "Block height:      {}", w.block_height);
+                let _ = writeln!(out, "Block hash:        {}", w.block_hash);
+                let _ = writeln!(out, "Tip hash:          {}", w.tip_hash);
+                let _ = writeln!(out, "Confirmations:     {}", w.confirmations);
+                let _ = writeln!(out, "Merkle index:      {} of {}", w.merkle_index, w.tx_count);
+                let _ = writeln!(out, "Merkle branch:     {} hashes", w.merkle_branch.len());
+                let _ = writeln!(out, "Transaction:       {}", w.transaction);
+                match w.vout {
+                    Some(vout) => {
+                        let _ = writeln!(out, "Output:            {}", vout);
+                    }
+                    None => {
+                        let _ = writeln!(out, "Output:            none");
+                    }
+                }
+            }
+            DecodedWitness::TonAccount(w) => {
+                let _ = writeln!(out, "Format:            ton account");
+                for (name, block) in [("Masterchain", &w.mc_block), ("Shard", &w.shard_block)] {
+                    let _ = writeln!(
+                        out,
+                        "{:<19}{}:{}:{}",
+                        format!("{} block:", name),
+                        block.workchain,
+                        block.shard,
+                        block.seqno
+                    );
+                    let _ = writeln!(out, "{:<19}0x{}", format!("{} root:", name), block.root_hash);
+                }
+                let _ = writeln!(out, "Account:           {}:{}", w.workchain, w.account_id);
+                let _ = writeln!(out, "Shard proof:       {} bytes", w.shard_proof_len);
+                let _ = writeln!(out, "Account proof:     {} bytes", w.proof_len);
+                let _ = writeln!(out, "Account state:     {} bytes", w.state_len);
+                let refs: Vec<String> = w.refs.iter().map(|index| index.to_string()).collect();
+                let _ = writeln!(out, "Field location:    refs [{}], bit {}, ref {}", refs.join(", "), w.bit, w.next_ref);
+                let _ = writeln!(out, "Field type:        {}", w.field_type);
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn storage_witness(extended: bool, proof: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&[0x11; 32]);
+        bytes.extend_from_slice(&[0x22; 32]);
+        bytes.extend_from_slice(&[0x00; 32]);
+        bytes.push(3);
+        bytes.push(1);
+        if extended {
+            bytes.extend_from_slice(&19_000_000u64.to_le_bytes());
+            bytes.extend_from_slice(&[0xbb; 32]);
+        }
+        bytes.extend_from_slice(&(proof.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(proof);
+        if extended {
+            bytes.extend_from_slice(&7u16.to_le_bytes());
+            bytes.extend_from_slice(&[0x11; 32]);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_decode_storage_witnesses() {
+        let DecodedWitness::Extended(w) = decode(&storage_witness(true, &[1, 2, 3])).unwrap() else {
+            panic!("expected extended witness");
+        };
+        assert_eq!(w.zero_semantics, ZeroSemantics::ValidZero);
+        assert_eq!(w.semantic_source, SemanticSource::EventValidated);
+        assert_eq!(w.block.as_ref().unwrap().height, 19_000_000);
+        assert_eq!(w.proof_len, 3);
+        assert_eq!(w.field_index, Some(7));
+        assert_eq!(w.expected_slot, Some(w.storage_key.clone()));
+
+        let DecodedWitness::Legacy(w) = decode(&storage_witness(false, &[9; 40])).unwrap() else {
+            panic!("expected legacy witness");
+        };
+        assert_eq!(w.proof_len, 40);
+        assert_eq!(w.block, None);
+
+        let mut bad = storage_witness(true, &[]);
+        bad[96] = 9;
+        assert!(decode(&bad).is_err());
+        assert!(decode(&[0u8; 50]).is_err());
+    }
+
+    #[test]
+    fn test_decode_solana_witness_and_hex_input() {
+        let mut bytes = vec![0xaa; 96];
+        bytes.extend_from_slice(&1_000u64.to_le_bytes());
+        bytes.extend_from_slice(&0u64.to_le_bytes());
+        bytes.extend_from_slice(&250_000_000u64.to_le_bytes());
+        bytes.extend_from_slice(&[0xcc; 32]);
+        bytes.extend_from_slice(&8u32.to_le_bytes());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("witness.hex");
+        std::fs::write(&path, format!("0x{}\n", hex::encode(&bytes))).unwrap();
+
+        let DecodedWitness::SolanaAccount(w) = decode(&read_witness_file(&path).unwrap()).unwrap() else {
+            panic!("expected solana witness");
+        };
+        assert_eq!(w.lamports, 1_000);
+        assert_eq!(w.slot, 250_000_000);
+        assert_eq!(w.field_offset, 8);
+        assert!(DecodedWitness::SolanaAccount(w).report().contains("Slot:              250000000"));
+    }
+
+    #[test]
+    fn test_decode_chain_witnesses() {
+        let mut sui = 42u64.to_le_bytes().to_vec();
+        sui.extend_from_slice(&[0xd2; 32]);
+        sui.extend_from_slice(&16u32.to_le_bytes());
+        sui.extend_from_slice(&8u32.to_le_bytes());
+        for part in [&[0xd1; 3][..], &[0xd3; 70], &[0xd4; 5], &[0xd5; 9]] {
+            sui.extend_from_slice(&(part.len() as u32).to_le_bytes());
+            sui.extend_from_slice(part);
+        }
+        let DecodedWitness::SuiObject(w) = decode(&sui).unwrap() else {
+            panic!("expected sui witness");
+        };
+        assert_eq!((w.checkpoint, w.field_offset, w.field_size), (42, 16, 8));
+        assert_eq!((w.object.as_str(), w.effects_len, w.checkpoint_summary_len), ("d1d1d1", 70, 9));
+
+        let mut substrate = vec![0xaa; 32];
+        for part in [&[0xbb; 70][..], &[1, 2, 3], &[]] {
+            substrate.extend_from_slice(&(part.len() as u32).to_le_bytes());
+            substrate.extend_from_slice(part);
+        }
+        substrate.extend_from_slice(&2u32.to_le_bytes());
+        for item in [&[0xcc; 40][..], &[0xdd; 33]] {
+            substrate.extend_from_slice(&(item.len() as u32).to_le_bytes());
+            substrate.extend_from_slice(item);
+        }
+        substrate.extend_from_slice(&0u32.to_le_bytes());
+        substrate.extend_from_slice(&0u32.to_le_bytes());
+        substrate.extend_from_slice(&0u32.to_le_bytes());
+        let DecodedWitness::SubstrateStorage(w) = decode(&substrate).unwrap() else {
+            panic!("expected substrate witness");
+        };
+        assert_eq!((w.storage_key.as_str(), w.proof_items, w.field_size), ("010203", 2, 0));
+        assert_eq!(w.child_storage_key, None);
+
+        let mut starknet = 600_000u64.to_le_bytes().to_vec();
+        starknet.extend_from_slice(&[0x02; 224]);
+        starknet.extend_from_slice(&1u32.to_le_bytes());
+        starknet.push(0);
+        starknet.extend_from_slice(&[0x04; 64]);
+        starknet.extend_from_slice(&[0x03; 32]);
+        starknet.push(2);
+        starknet.extend_from_slice(&1u32.to_le_bytes());
+        starknet.push(1);
+        starknet.extend_from_slice(&[0x05; 65]);
+        starknet.extend_from_slice(&0u32.to_le_bytes());
+        let DecodedWitness::StarknetStorage(w) = decode(&starknet).unwrap() else {
+            panic!("expected starknet witness");
+        };
+        assert_eq!((w.block_number, w.contract_nodes, w.value_nodes.clone()), (600_000, 1, vec![1, 0]));
+        assert_eq!(w.storage_address, "03".repeat(32));
+
+        let genesis = hex::decode(
+            "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c",
+        )
+        .unwrap();
+        let mut bitcoin = 0u64.to_le_bytes().to_vec();
+        bitcoin.extend_from_slice(&2u32.to_le_bytes());
+        bitcoin.extend_from_slice(&genesis);
+        bitcoin.extend_from_slice(&genesis);
+        bitcoin.extend_from_slice(&0u32.to_le_bytes());
+        bitcoin.extend_from_slice(&2u32.to_le_bytes());
+        bitcoin.push(1);
+        bitcoin.extend_from_slice(&[0xab; 32]);
+        bitcoin.extend_from_slice(&3u32.to_le_bytes());
+        bitcoin.extend_from_slice(&[1, 2, 3]);
+        bitcoin.push(1);
+        bitcoin.extend_from_slice(&0u32.to_le_bytes());
+        let DecodedWitness::BitcoinTx(w) = decode(&bitcoin).unwrap() else {
+            panic!("expected bitcoin witness");
+        };
+        assert_eq!(w.block_hash, "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f");
+        assert_eq!((w.confirmations, w.tx_count, w.merkle_branch.len(), w.vout), (2, 2, 1, Some(0)));
+        assert_eq!(w.transaction, "010203");
+
+        let mut ton = Vec::new();
+        for (workchain, seqno) in [(-1i32, 100u32), (0, 5000)] {
+            ton.extend_from_slice(&workchain.to_le_bytes());
+            ton.extend_from_slice(&0x8000_0000_0000_0000u64.to_le_bytes());
+            ton.extend_from_slice(&seqno.to_le_bytes());
+            ton.extend_from_slice(&[0x0a; 64]);
+        }
+        ton.extend_from_slice(&0i32.to_le_bytes());
+        ton.extend_from_slice(&[0x0c; 32]);
+        for len in [3u32, 2, 1] {
+            ton.extend_from_slice(&len.to_le_bytes());
+            ton.extend(std::iter::repeat_n(0xbc, len as usize));
+        }
+        ton.extend_from_slice(&[1, 0, 32, 0, 0]);
+        ton.extend_from_slice(&6u32.to_le_bytes());
+        ton.extend_from_slice(b"uint16");
+        let DecodedWitness::TonAccount(w) = decode(&ton).unwrap() else {
+            panic!("expected ton witness");
+        };
+        assert_eq!((w.shard_block.seqno, w.proof_len, w.refs.clone(), w.bit), (5000, 2, vec![0], 32));
+        assert_eq!(w.mc_block.shard, "8000000000000000");
+        assert!(DecodedWitness::TonAccount(w).report().contains("refs [0], bit 32, ref 0"));
+
+        // A field type that is not text is rejected rather than decoded as another layout
+        let last = ton.len() - 1;
+        ton[last] = 0xff;
+        assert!(decode(&ton).is_err());
+        assert!(decode_as(&sui, WitnessLayout::Starknet).is_err());
+    }
+
+    #[test]
+    fn test_controller_witnesses_round_trip() {
+        use starknet_types_core::felt::Felt;
+        use traverse_valence::witness::{BlockBinding, FieldBinding};
+
+        let storage = valence_witness::StorageWitness {
+            storage_key: [0x11; 32],
+            layout_commitment: [0x22; 32],
+            value: [0x33; 32],
+            zero_semantics: 2,
+            semantic_source: 2,
+            block: Some(BlockBinding { height: 19_000_000, hash: [0xbb; 32] }),
+            proof: vec![0xf9; 532],
+            field: Some(FieldBinding { index: 3, expected_slot: [0x11; 32] }),
+        };
+        let DecodedWitness::Extended(w) = decode(&storage.to_bytes()).unwrap() else {
+            panic!("expected extended witness");
+        };
+        assert_eq!(w, StorageWitness::from(storage.clone()));
+        assert_eq!((w.zero_semantics, w.semantic_source), (ZeroSemantics::Cleared, SemanticSource::DeclaredOverride));
+        assert_eq!(w.proof_len, 532);
+
+        let legacy = valence_witness::StorageWitness { block: None, field: None, ..storage };
+        let DecodedWitness::Legacy(w) = decode(&legacy.to_bytes()).unwrap() else {
+            panic!("expected legacy witness");
+        };
+        assert_eq!((w.block, w.field_index), (None, None));
+
+        let solana = valence_witness::SolanaAccountWitness {
+            account_address: [0xa1; 32],
+            owner: [0xa2; 32],
+            value: [0xa3; 32],
+            lamports: 2_039_280,
+            rent_epoch: u64::MAX,
+            slot: 250_000_000,
+            block_hash: [0xa4; 32],
+            field_offset: 64,
+        };
+        let DecodedWitness::SolanaAccount(w) = decode(&solana.to_bytes()).unwrap() else {
+            panic!("expected solana witness");
+        };
+        assert_eq!((w.rent_epoch, w.field_offset), (u64::MAX, 64));
+
+        let sui = sui::SuiWitness {
+            checkpoint: 42,
+            checkpoint_digest: [0xd2; 32],
+            field_offset: 0,
+            field_size: 0,
+            object: vec![0xd1; 90],
+            effects: vec![0xd3; 300],
+            checkpoint_contents: vec![0xd4; 80],
+            checkpoint_summary: vec![0xd5; 200],
+        };
+        let DecodedWitness::SuiObject(w) = decode(&sui.to_bytes()).unwrap() else {
+            panic!("expected sui witness");
+        };
+        assert_eq!((w.effects_len, w.checkpoint_contents_len, w.checkpoint_summary_len), (300, 80, 200));
+
+        let substrate = substrate::SubstrateWitness {
+            block_hash: [0xaa; 32],
+            header: vec![0xbb; 120],
+            storage_key: vec![0x26; 48],
+            child_storage_key: Some(b":child_storage:default:pool".to_vec()),
+            proof: vec![vec![0x80; 100], vec![0x9e; 70], vec![0x5f; 40]],
+            child_proof: vec![vec![0x44; 36]],
+            field_offset: 16,
+            field_size: 16,
+        };
+        let DecodedWitness::SubstrateStorage(w) = decode(&substrate.to_bytes()).unwrap() else {
+            panic!("expected substrate witness");
+        };
+        assert_eq!(w.child_storage_key, Some(hex::encode(b":child_storage:default:pool")));
+        assert_eq!((w.proof_items, w.child_proof_items, w.field_size), (3, 1, 16));
+
+        let binary = starknet::TrieNode::Binary { left: Felt::from(1u64), right: Felt::from(2u64) };
+        let edge = starknet::TrieNode::Edge { child: Felt::from(3u64), path: Felt::from(5u64), length: 3 };
+        let starknet = starknet::StarknetWitness {
+            block_number: 600_000,
+            state_commitment: Felt::from(10u64),
+            contracts_root: Felt::from(11u64),
+            classes_root: Felt::from(12u64),
+            contract_address: Felt::from(13u64),
+            contract: starknet::ContractLeaf {
+                class_hash: Felt::from(14u64),
+                nonce: Felt::from(1u64),
+                storage_root: Felt::from(15u64),
+            },
+            contract_nodes: vec![binary.clone(), edge.clone()],
+            storage_address: Felt::from(16u64),
+            value_nodes: vec![vec![edge], vec![binary.clone(), binary]],
+        };
+        let DecodedWitness::StarknetStorage(w) = decode(&starknet.to_bytes()).unwrap() else {
+            panic!("expected starknet witness");
+        };
+        assert_eq!((w.contract_nodes, w.value_nodes), (2, vec![1, 2]));
+        assert_eq!(w.storage_address, format!("{:064x}", 16));
+
+        let genesis: [u8; bitcoin::HEADER_SIZE] = hex::decode(
+            "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c",
+        )
+        .unwrap()
+        .try_into()
+        .unwrap();
+        let bitcoin = bitcoin::BitcoinWitness {
+            block_height: 0,
+            headers: vec![genesis; 6],
+            merkle_index: 1,
+            tx_count: 3,
+            branch: vec![[0x01; 32], [0x02; 32]],
+            transaction: vec![0x02; 120],
+            vout: None,
+        };
+        let DecodedWitness::BitcoinTx(w) = decode(&bitcoin.to_bytes()).unwrap() else {
+            panic!("expected bitcoin witness");
+        };
+        assert_eq!((w.confirmations, w.merkle_branch.len(), w.vout), (6, 2, None));
+
+        let block = |workchain, seqno| ton::BlockIdExt {
+            workchain,
+            shard: ton::FULL_SHARD,
+            seqno,
+            root_hash: [0x0a; 32],
+            file_hash: [0x0b; 32],
+        };
+        let ton = ton::TonWitness {
+            mc_block: block(ton::MASTERCHAIN, 100),
+            shard_block: block(0, 5000),
+            workchain: 0,
+            account_id: [0x0c; 32],
+            shard_proof: vec![0xb5; 400],
+            proof: vec![0xb5; 900],
+            state: vec![0xb5; 150],
+            location: ton::FieldLocation { refs: vec![1, 0], bit: 267, next_ref: 1, type_name: "Coins".into() },
+        };
+        let DecodedWitness::TonAccount(w) = decode(&ton.to_bytes()).unwrap() else {
+            panic!("expected ton witness");
+        };
+        assert_eq!(w.mc_block, TonBlock::from(ton.mc_block));
+        assert_eq!((w.refs, w.bit, w.field_type.as_str()), (vec![1, 0], 267, "Coins"));
+    }
+}
@@ -4,7 +4,7 @@
 //! storage layout compilation, and query generation.
 
 use anyhow::Result;
-use log::info;
+use tracing::{info, warn};
 use serde_json::{json, Value};
 use std::path::Path;
 use traverse_cli_core::OutputFormat;
@@ -16,6 +16,13 @@ use base64::{Engine as _, engine::general_purpose};
 #[cfg(feature = "cosmos")]
 use traverse_cosmos::{CosmosKeyResolver, CosmosLayoutCompiler};
 
+#[cfg(all(feature = "cosmos", feature = "client"))]
+use traverse_cli_core::{
+    cache,
+    formatters::write_result,
+    plan::{RpcPlan, PLAN_FILE},
+};
+
 /// Write output to file or stdout
 fn write_output(content: &str, output_path: Option<&Path>) -> Result<()> {
     match output_path {
@@ -123,6 +130,7 @@ pub fn cmd_cosmos_resolve_query(
     query: &str,
     layout_file: &Path,
     format: &OutputFormat,
+    template: Option<&Path>,
     output: Option<&Path>,
 ) -> Result<()> {
     info!("Resolving CosmWasm storage query: {}", query);
@@ -146,9 +154,10 @@ pub fn cmd_cosmos_resolve_query(
         }
     });
 
-    let output_str = match format {
-        OutputFormat::CoprocessorJson => serde_json::to_string_pretty(&result)?,
-        _ => serde_json::to_string_pretty(&result)?, // YAML not available, use JSON
+    let output_str = match (template, format) {
+        (Some(template), _) => traverse_cli_core::template::render(template, &result)?,
+        (None, OutputFormat::CoprocessorJson) => serde_json::to_string_pretty(&result)?,
+        (None, _) => serde_json::to_string_pretty(&result)?, // YAML not available, use JSON
     };
 
     write_output(&output_str, output)?;
@@ -214,80 +223,827 @@ pub fn cmd_cosmos_generate_queries(
     Ok(())
 }
 
+/// GraphQL indexer of a contract's store writes, for `--validate-semantics`
+///
+/// The project follows traverse-indexer's conventions with the wasm storage
+/// key, 0x-prefixed, as the slot and the stored bytes in hex as the value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoreIndexer {
+    /// A SubQuery project's query URL
+    SubQuery(String),
+    /// A subgraph's query URL
+    Subgraph(String),
+}
+
+/// Execute cosmos generate-proof command
+///
+/// The query is resolved to the contract's raw storage key and fetched from
+/// the wasm store with `abci_query` at `height`. Both proof layers are checked
+/// against the app hash of the following header before the artifact, which
+/// records that header, is written. With an `indexer`, the layout's zero
+/// semantics for the key are checked against its write history up to the
+/// proof height and the result added as `semantic_validation`.
+#[cfg(all(feature = "cosmos", feature = "client"))]
+#[allow(clippy::too_many_arguments)]
+pub async fn cmd_cosmos_generate_proof(
+    contract_address: &str,
+    query: &str,
+    layout_file: Option<&Path>,
+    rpc: &str,
+    chain_id: &str,
+    height: Option<u64>,
+    indexer: Option<&StoreIndexer>,
+    dry_run: bool,
+    format: &OutputFormat,
+    template: Option<&Path>,
+    output: Option<&Path>,
+) -> Result<()> {
+    info!("Generating CosmWasm storage proof for {} on {}", query, contract_address);
+
+    if dry_run && template.is_some() {
+        return Err(anyhow::anyhow!("--template renders proofs, not the plan --dry-run prints"));
+    }
+    let layout = match layout_file {
+        Some(layout_file) => {
+            let layout_content = std::fs::read_to_string(layout_file)?;
+            Some(serde_json::from_str::<traverse_core::LayoutInfo>(&layout_content)?)
+        }
+        None => None,
+    };
+
+    let artifact =
+        generate_storage_proof(contract_address, query, layout.as_ref(), rpc, chain_id, height, indexer, dry_run)
+            .await?;
+    write_result(&artifact, format, template, output)?;
+
+    if !dry_run {
+        info!(
+            "Proof generated at height {} (header {}); key exists: {}",
+            artifact["height"], artifact["header"]["height"], artifact["exists"]
+        );
+    }
+    Ok(())
+}
+
+/// The artifact `generate-proof` writes, or with `dry_run` the plan of its requests
+///
+/// Shared by the CLI command and the HTTP API server.
+#[cfg(all(feature = "cosmos", feature = "client"))]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn generate_storage_proof(
+    contract_address: &str,
+    query: &str,
+    layout: Option<&traverse_core::LayoutInfo>,
+    rpc: &str,
+    chain_id: &str,
+    height: Option<u64>,
+    indexer: Option<&StoreIndexer>,
+    dry_run: bool,
+) -> Result<Value> {
+    let fetcher = proof_fetcher(rpc, chain_id);
+    if dry_run {
+        if indexer.is_some() {
+            return Err(anyhow::anyhow!(
+                "--dry-run cannot plan --validate-semantics, whose requests depend on the key's history"
+            ));
+        }
+        if let Some(layout) = layout {
+            CosmosKeyResolver.resolve(layout, query)?;
+        }
+        return Ok(plan_proofs(&fetcher, contract_address, &[query], height)?.to_value());
+    }
+    let mut artifact = proof_artifact(&fetcher, contract_address, query, layout, height).await?;
+    if let Some(indexer) = indexer {
+        artifact["semantic_validation"] = serde_json::to_value(validate_semantics(indexer, &artifact).await?)?;
+    }
+    Ok(artifact)
+}
+
+/// Check an artifact's declared zero semantics against the key's write
+/// history up to the proof height
+#[cfg(all(feature = "cosmos", feature = "client"))]
+async fn validate_semantics(indexer: &StoreIndexer, artifact: &Value) -> Result<traverse_cosmos::ValidationResult> {
+    use traverse_cosmos::{GraphIndexer, SemanticValidator, SubQueryIndexer};
+
+    let declared: traverse_core::ZeroSemantics = match artifact.get("zero_semantics") {
+        Some(declared) => serde_json::from_value(declared.clone())?,
+        None => return Err(anyhow::anyhow!("--validate-semantics needs a --layout declaring the entry's semantics")),
+    };
+    let contract = artifact["contract_address"].as_str().unwrap_or_default();
+    let slot = format!("0x{}", artifact["storage_key"].as_str().unwrap_or_default());
+    let value = artifact["value"]
+        .as_str()
+        .map(|value| general_purpose::STANDARD.decode(value))
+        .transpose()?;
+    let value = store_word(value.as_deref());
+    let height = artifact["height"].as_u64();
+
+    let result = match indexer {
+        StoreIndexer::SubQuery(url) => {
+            SemanticValidator::new(SubQueryIndexer::new(url.as_str()))
+                .validate_semantics_at_block(contract, &slot, declared, Some(&value), height)
+                .await?
+        }
+        StoreIndexer::Subgraph(url) => {
+            SemanticValidator::new(GraphIndexer::new(url.as_str()))
+                .validate_semantics_at_block(contract, &slot, declared, Some(&value), height)
+                .await?
+        }
+    };
+    if !result.is_valid {
+        warn!("Semantic validation failed: declared {:?}, detected {:?}", declared, result.detected_semantics);
+    }
+    Ok(result)
+}
+
+/// A store value as the word indexers compare: `0x` for an absent key, `0x0`
+/// for a stored JSON zero and the stored bytes in hex otherwise
+#[cfg(all(feature = "cosmos", feature = "client"))]
+fn store_word(value: Option<&[u8]>) -> String {
+    let Some(value) = value else {
+        return "0x".to_string();
+    };
+    match serde_json::from_slice::<Value>(value) {
+        Ok(Value::Number(n)) if n.as_f64() == Some(0.0) => "0x0".to_string(),
+        Ok(Value::String(s)) if s == "0" => "0x0".to_string(),
+        _ => format!("0x{}", hex::encode(value)),
+    }
+}
+
+/// Proof fetcher for the wasm store of `chain_id`
+#[cfg(all(feature = "cosmos", feature = "client"))]
+fn proof_fetcher(rpc: &str, chain_id: &str) -> traverse_cosmos::CosmosProofFetcher {
+    let config = traverse_cosmos::CosmosChainConfig {
+        chain_id: chain_id.to_string(),
+        ..traverse_cosmos::CosmosChainConfig::default()
+    };
+    traverse_cosmos::CosmosProofFetcher::new(rpc.to_string(), config)
+}
+
+/// Network name the node at `rpc` reports
+#[cfg(all(feature = "cosmos", feature = "client"))]
+pub async fn fetch_chain_id(rpc: &str) -> Result<String> {
+    Ok(traverse_cosmos::CosmosProofFetcher::with_defaults(rpc.to_string()).chain_id().await?)
+}
+
+#[cfg(not(all(feature = "cosmos", feature = "client")))]
+pub async fn fetch_chain_id(_rpc: &str) -> Result<String> {
+    Err(anyhow::anyhow!("Cosmos RPC access not enabled. Build with --features client"))
+}
+
+/// Plan the requests proving `queries` at one height makes on a cold cache
+///
+/// Without a height, `status` is read first and the proofs are pinned one
+/// below its latest height. Each query is then an `abci_query` followed by
+/// the `block` whose app hash commits to the result.
+#[cfg(all(feature = "cosmos", feature = "client"))]
+fn plan_proofs(
+    fetcher: &traverse_cosmos::CosmosProofFetcher,
+    contract_address: &str,
+    queries: &[&str],
+    height: Option<u64>,
+) -> Result<RpcPlan> {
+    let rpc = fetcher.rpc_url.as_str();
+    let mut plan = RpcPlan::new();
+    let height: Value = match height {
+        Some(height) => height.to_string().into(),
+        None => {
+            let status = plan.push(rpc, "status", json!({}), "Latest height to pin the proofs below");
+            format!("{}-1", RpcPlan::reference(status, "sync_info.latest_block_height")).into()
+        }
+    };
+    for query in queries {
+        let storage_key = CosmosKeyResolver::raw_storage_key(query)?;
+        let key = traverse_cosmos::contract_store_key(contract_address, &storage_key)?;
+        let proof = plan.push(
+            rpc,
+            "abci_query",
+            fetcher.abci_query_params("wasm", &key, height.clone()),
+            format!("Store proof for {}", query),
+        );
+        let header = format!("{}+1", RpcPlan::reference(proof, "response.height"));
+        plan.push(
+            rpc,
+            "block",
+            traverse_cosmos::CosmosProofFetcher::block_params(header.into()),
+            format!("Header whose app hash commits to the proof of {}", query),
+        );
+    }
+    Ok(plan)
+}
+
+/// Fetch and verify the proof of one query and describe it as JSON
+///
+/// With a layout, the query must name a declared storage entry and the
+/// artifact records the layout commitment and zero semantics. Verified
+/// artifacts at a given height go through the disk cache, so a hit skips
+/// both the fetch and the verification; without a height the proof is of
+/// the latest state and always fetched.
+#[cfg(all(feature = "cosmos", feature = "client"))]
+async fn proof_artifact(
+    fetcher: &traverse_cosmos::CosmosProofFetcher,
+    contract_address: &str,
+    query: &str,
+    layout: Option<&traverse_core::LayoutInfo>,
+    height: Option<u64>,
+) -> Result<Value> {
+    let resolved_path = layout.map(|layout| CosmosKeyResolver.resolve(layout, query)).transpose()?;
+    let storage_key = CosmosKeyResolver::raw_storage_key(query)?;
+    let mut artifact = match height {
+        Some(height) => {
+            let cache_key = json!({
+                "rpc": fetcher.rpc_url,
+                "chain_id": fetcher.config.chain_id,
+                "contract": contract_address,
+                "query": query,
+                "height": height,
+            });
+            cache::cached("proof", cache_key, || {
+                verified_artifact(fetcher, contract_address, query, &storage_key, Some(height))
+            })
+            .await?
+        }
+        None => verified_artifact(fetcher, contract_address, query, &storage_key, None).await?,
+    };
+
+    if let Some(path) = resolved_path {
+        artifact["layout_commitment"] = hex::encode(path.layout_commitment).into();
+        artifact["zero_semantics"] = serde_json::to_value(path.zero_semantics)?;
+    }
+    Ok(artifact)
+}
+
+/// Fetch the proof of a storage key and check it against the app hash
+#[cfg(all(feature = "cosmos", feature = "client"))]
+async fn verified_artifact(
+    fetcher: &traverse_cosmos::CosmosProofFetcher,
+    contract_address: &str,
+    query: &str,
+    storage_key: &[u8],
+    height: Option<u64>,
+) -> Result<Value> {
+    let key = traverse_cosmos::contract_store_key(contract_address, storage_key)?;
+    let proof = fetcher.fetch_proof("wasm", &key, height).await?;
+
+    let chain_id = &fetcher.config.chain_id;
+    if &proof.header.chain_id != chain_id {
+        return Err(anyhow::anyhow!(
+            "RPC endpoint serves chain '{}', expected '{}'",
+            proof.header.chain_id,
+            chain_id
+        ));
+    }
+    if !fetcher.verify_proof(&proof, proof.value.as_deref())? {
+        return Err(anyhow::anyhow!(
+            "Proof for '{}' does not verify against the app hash at height {}",
+            query,
+            proof.header.height
+        ));
+    }
+
+    Ok(json!({
+        "chain_id": chain_id,
+        "contract_address": contract_address,
+        "query": query,
+        "store": proof.store_key,
+        "key": hex::encode(&proof.key),
+        "storage_key": hex::encode(storage_key),
+        "exists": proof.value.is_some(),
+        "value": proof.value.as_ref().map(|v| general_purpose::STANDARD.encode(v)),
+        "decoded_value": proof.value.as_deref().and_then(|v| serde_json::from_slice::<Value>(v).ok()),
+        "height": proof.height,
+        "store_root": hex::encode(&proof.root),
+        "proof": {
+            "iavl": general_purpose::STANDARD.encode(proof.proof_bytes()),
+            "store": general_purpose::STANDARD.encode(proof.store_proof_bytes()),
+        },
+        "header": {
+            "height": proof.header.height,
+            "chain_id": proof.header.chain_id,
+            "block_hash": hex::encode(&proof.header.block_hash),
+            "app_hash": hex::encode(&proof.header.app_hash),
+        },
+    }))
+}
+
+/// Execute cosmos generate-proof command
+#[cfg(all(feature = "cosmos", not(feature = "client")))]
+#[allow(clippy::too_many_arguments)]
+pub async fn cmd_cosmos_generate_proof(
+    _contract_address: &str,
+    _query: &str,
+    _layout_file: Option<&Path>,
+    _rpc: &str,
+    _chain_id: &str,
+    _height: Option<u64>,
+    _indexer: Option<&StoreIndexer>,
+    _dry_run: bool,
+    _format: &OutputFormat,
+    _template: Option<&Path>,
+    _output: Option<&Path>,
+) -> Result<()> {
+    Err(anyhow::anyhow!("Cosmos proof fetching not enabled. Build with --features client"))
+}
+
+/// Spot-check a layout against a contract's current storage
+///
+/// Every item the layout declares is read from the wasm store at one height,
+/// with its proof checked as for generate-proof, and compared with its
+/// declaration: the value must be JSON as cw-storage-plus writes it, of the
+/// declared type and, for integers, within the declared width. Absent keys
+/// and stored zeros are told apart, so both sides of the zero semantics are
+/// checked. Maps are stored per key and skipped. Returns whether any entry
+/// mismatched.
+#[cfg(all(feature = "cosmos", feature = "client"))]
+pub async fn cmd_cosmos_verify_layout(
+    contract_address: &str,
+    layout_file: &Path,
+    rpc: &str,
+    chain_id: &str,
+    height: Option<u64>,
+    output: Option<&Path>,
+) -> Result<bool> {
+    use traverse_cli_core::spot_check::{SpotCheck, SpotCheckReport};
+
+    let layout_content = std::fs::read_to_string(layout_file)?;
+    let layout: traverse_core::LayoutInfo = serde_json::from_str(&layout_content)?;
+    let fetcher = proof_fetcher(rpc, chain_id);
+    let height = match height {
+        Some(height) => height,
+        None => fetcher.latest_height().await?.saturating_sub(1),
+    };
+
+    let mut report = SpotCheckReport::new(
+        "cosmos",
+        json!({ "contract": contract_address, "chain_id": chain_id, "height": height }),
+    );
+    for entry in &layout.storage {
+        let mut check = SpotCheck::new(&entry.label, &entry.type_name, &entry.label, entry.zero_semantics);
+        // Compiled layouts declare a map both by its name and as `name[key]`
+        let map_prefix = format!("{}[", entry.label);
+        let mapping = layout
+            .types
+            .iter()
+            .any(|t| t.label == entry.type_name && t.encoding == "mapping")
+            || layout.storage.iter().any(|other| other.label.starts_with(&map_prefix));
+        if mapping || CosmosKeyResolver::raw_storage_key(&entry.label).is_err() {
+            check.skip("map entries are stored per key; prove a key with generate-proof");
+            report.checks.push(check);
+            continue;
+        }
+
+        let artifact = proof_artifact(&fetcher, contract_address, &entry.label, None, Some(height)).await?;
+        check.location = format!("wasm/{}", artifact["key"].as_str().unwrap_or_default());
+        let value = artifact["value"]
+            .as_str()
+            .map(|value| general_purpose::STANDARD.decode(value))
+            .transpose()?;
+        inspect_item(&mut check, value.as_deref());
+        report.checks.push(check);
+    }
+
+    let mismatched = report.mismatched();
+    if mismatched > 0 {
+        eprintln!("Warning: {} of {} entries do not match their live values", mismatched, report.checks.len());
+    }
+    write_output(&serde_json::to_string_pretty(&report.to_value())?, output)?;
+    Ok(mismatched > 0)
+}
+
+#[cfg(all(feature = "cosmos", not(feature = "client")))]
+pub async fn cmd_cosmos_verify_layout(
+    _contract_address: &str,
+    _layout_file: &Path,
+    _rpc: &str,
+    _chain_id: &str,
+    _height: Option<u64>,
+    _output: Option<&Path>,
+) -> Result<bool> {
+    Err(anyhow::anyhow!("Cosmos proof fetching not enabled. Build with --features client"))
+}
+
+/// Verify a generate-proof artifact offline against a trusted app hash
+///
+/// Rebuilds the proof from the artifact, checks the key proof against the
+/// module store root, then the store root against `app_hash`. The app hash
+/// is the one the caller trusts for the block after the proof height, not
+/// the one the artifact carries. With a layout, the artifact's query must
+/// name a declared entry and derive the proven key. Returns whether the
+/// whole artifact verified.
+#[cfg(all(feature = "cosmos", feature = "client"))]
+pub fn cmd_cosmos_verify_proof(
+    proof_file: &Path,
+    layout_file: Option<&Path>,
+    app_hash: &str,
+    format: &OutputFormat,
+    output: Option<&Path>,
+) -> Result<bool> {
+    use traverse_cosmos::{contract_store_key, verify_iavl_proof, verify_store_proof, HeaderBinding, IavlProof};
+
+    let content = std::fs::read(proof_file)
+        .map_err(|e| anyhow::anyhow!("Failed to read proof file '{}': {}", proof_file.display(), e))?;
+    // generate-proof writes JSON, or an artifact with --format binary or base64
+    let artifact: Value = match serde_json::from_slice(&content) {
+        Ok(artifact) => artifact,
+        Err(_) => traverse_cli_core::artifact::read_file(proof_file)?,
+    };
+    let field = |name: &str| artifact[name].as_str().ok_or_else(|| anyhow::anyhow!("Missing {}", name));
+    let hex_field = |name: &str| -> Result<Vec<u8>> {
+        hex::decode(field(name)?).map_err(|e| anyhow::anyhow!("Invalid {}: {}", name, e))
+    };
+    let proof_field = |name: &str| -> Result<_> {
+        let encoded = artifact["proof"][name].as_str().ok_or_else(|| anyhow::anyhow!("Missing proof.{}", name))?;
+        Ok(IavlProof::decode_proof(&general_purpose::STANDARD.decode(encoded)?)?)
+    };
+    let trusted = hex::decode(app_hash.trim_start_matches("0x"))
+        .map_err(|e| anyhow::anyhow!("Invalid app hash '{}': {}", app_hash, e))?;
+
+    let proof = IavlProof {
+        key: hex_field("key")?,
+        value: artifact["value"].as_str().map(|value| general_purpose::STANDARD.decode(value)).transpose()?,
+        proof: proof_field("iavl")?,
+        height: artifact["height"].as_u64().ok_or_else(|| anyhow::anyhow!("Missing height"))?,
+        root: hex_field("store_root")?,
+        store_key: field("store")?.to_string(),
+        store_proof: proof_field("store")?,
+        header: HeaderBinding {
+            height: artifact["header"]["height"].as_u64().unwrap_or_default(),
+            chain_id: artifact["header"]["chain_id"].as_str().unwrap_or_default().to_string(),
+            block_hash: Vec::new(),
+            app_hash: trusted,
+        },
+    };
+
+    let key_verified = verify_iavl_proof(&proof, proof.value.as_deref(), None)?;
+    let store_verified = verify_store_proof(&proof);
+    let mut valid = key_verified && store_verified;
+    let mut report = json!({
+        "query": artifact["query"],
+        "contract_address": artifact["contract_address"],
+        "height": proof.height,
+        "app_hash": hex::encode(&proof.header.app_hash),
+        "key_proof": { "verified": key_verified, "exists": proof.value.is_some() },
+        "store_proof": { "verified": store_verified, "store": proof.store_key },
+    });
+    if !store_verified && artifact["header"]["app_hash"].as_str() != Some(hex::encode(&proof.header.app_hash).as_str()) {
+        report["store_proof"]["error"] = json!("the artifact was taken against a different app hash");
+    }
+
+    if let Some(layout_file) = layout_file {
+        let layout = traverse_cli_core::formatters::load_layout(layout_file)
+            .map_err(|e| anyhow::anyhow!("Failed to load layout '{}': {}", layout_file.display(), e))?;
+        let query = field("query")?;
+        let derived = (|| -> Result<Vec<u8>> {
+            CosmosKeyResolver.resolve(&layout, query)?;
+            Ok(contract_store_key(field("contract_address")?, &CosmosKeyResolver::raw_storage_key(query)?)?)
+        })();
+        let mut check = json!({ "verified": false });
+        match derived {
+            Ok(key) if key == proof.key => check["verified"] = json!(true),
+            Ok(key) => check["error"] = json!(format!("query derives key {}, not the proven key", hex::encode(key))),
+            Err(e) => check["error"] = json!(e.to_string()),
+        }
+        valid &= check["verified"] == true;
+        report["layout_commitment"] = json!(hex::encode(layout.commitment()));
+        report["layout"] = check;
+    }
+
+    report["valid"] = json!(valid);
+    write_result(&report, format, None, output)?;
+    Ok(valid)
+}
+
+#[cfg(all(feature = "cosmos", not(feature = "client")))]
+pub fn cmd_cosmos_verify_proof(
+    _proof_file: &Path,
+    _layout_file: Option<&Path>,
+    _app_hash: &str,
+    _format: &OutputFormat,
+    _output: Option<&Path>,
+) -> Result<bool> {
+    Err(anyhow::anyhow!("Cosmos proof verification not enabled. Build with --features client"))
+}
+
+/// Check a stored item against its declared type
+#[cfg(all(feature = "cosmos", feature = "client"))]
+fn inspect_item(check: &mut traverse_cli_core::spot_check::SpotCheck, value: Option<&[u8]>) {
+    use traverse_cli_core::spot_check::Observed;
+
+    let Some(value) = value else {
+        check.observe(None, Observed::Absent);
+        return;
+    };
+    let Ok(value) = serde_json::from_slice::<Value>(value) else {
+        check.observe(Some(hex::encode(value).into()), Observed::Value);
+        check.mismatch("the stored value is not JSON");
+        return;
+    };
+
+    let observed = match &value {
+        Value::Null | Value::Bool(false) => Observed::WrittenZero,
+        Value::Number(n) if n.as_f64() == Some(0.0) => Observed::WrittenZero,
+        Value::String(s) if s.is_empty() || s == "0" => Observed::WrittenZero,
+        Value::Array(items) if items.is_empty() => Observed::WrittenZero,
+        Value::Object(fields) if fields.is_empty() => Observed::WrittenZero,
+        _ => Observed::Value,
+    };
+    check.observe(Some(value.clone()), observed);
+
+    let type_name = check.type_name.clone();
+    match type_name.as_str() {
+        "t_bool" if !value.is_boolean() => check.mismatch(format!("expected a bool, found {}", value)),
+        "t_address" | "t_string" if !value.is_string() => {
+            check.mismatch(format!("expected a string, found {}", value))
+        }
+        _ => {
+            let Some(bits) = type_name.strip_prefix("t_uint").and_then(|bits| bits.parse::<u32>().ok()) else {
+                return;
+            };
+            // Uint64 and wider serialize as decimal strings, narrower ones as numbers
+            let number = match &value {
+                Value::String(s) if !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) => Some(s.parse::<u128>().ok()),
+                Value::Number(n) => n.as_u64().map(|n| Some(u128::from(n))),
+                _ => None,
+            };
+            match number {
+                None => check.mismatch(format!("expected an unsigned integer, found {}", value)),
+                Some(Some(n)) if bits < 128 && n >> bits != 0 => {
+                    check.mismatch(format!("{} does not fit in {} bits", n, bits))
+                }
+                Some(None) if bits <= 128 => check.mismatch(format!("{} does not fit in {} bits", value, bits)),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Input for `auto-generate`
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct AutoGenerateConfig {
+    /// Contract message schema, relative to the config file
+    pub schema: String,
+    /// Contract address or address book name
+    pub contract: String,
+    /// Chain ID the proofs are checked against
+    pub chain_id: String,
+    /// Storage queries, e.g. `balances[neutron1...]`
+    pub queries: Vec<String>,
+    /// Height to prove at; the latest committed state when omitted
+    #[serde(default)]
+    pub height: Option<u64>,
+    /// RPC endpoint for this entry, overriding `--rpc`
+    #[serde(default)]
+    pub rpc: Option<String>,
+}
+
 /// Execute cosmos auto-generate command
+///
+/// Mirrors the Ethereum pipeline: compiles the layout, generates queries for
+/// the referenced storage entries, resolves every query to its wasm store
+/// key and, unless `dry_run`, proves them all at one height. Writes
+/// `layout.json`, `queries.json`, `resolved.json`, `proofs.json` and
+/// `summary.json` to `output_dir`; a dry run writes the requests the proofs
+/// would make to `plan.json` in place of `proofs.json`.
 #[cfg(feature = "cosmos")]
-#[allow(dead_code)]
+#[allow(clippy::too_many_arguments)]
 pub async fn cmd_cosmos_auto_generate(
     schema_file: &Path,
     rpc: &str,
     contract: &str,
+    chain_id: &str,
     queries: &str,
+    height: Option<u64>,
     output_dir: &Path,
-    _cache: bool,
     dry_run: bool,
 ) -> Result<()> {
-    info!("Auto-generating CosmWasm contract analysis");
+    info!("Running CosmWasm auto-generation for {}", contract);
 
-    if dry_run {
-        println!("DRY RUN: Would generate analysis for:");
-        println!("  • Schema file: {}", schema_file.display());
-        println!("  • RPC endpoint: {}", rpc);
-        println!("  • Contract: {}", contract);
-        println!("  • Queries: {}", queries);
-        println!("  • Output directory: {}", output_dir.display());
-        return Ok(());
+    if !schema_file.exists() {
+        return Err(anyhow::anyhow!(
+            "Schema file does not exist: {}",
+            schema_file.display()
+        ));
+    }
+    if rpc.trim().is_empty() {
+        return Err(anyhow::anyhow!(
+            "RPC URL cannot be empty. Please provide a valid Cosmos RPC endpoint."
+        ));
+    }
+    if contract.trim().is_empty() {
+        return Err(anyhow::anyhow!(
+            "Contract address cannot be empty. Please provide a valid CosmWasm contract address."
+        ));
+    }
+    if queries.trim().is_empty() {
+        return Err(anyhow::anyhow!(
+            "Queries cannot be empty. Please provide comma-separated query patterns."
+        ));
     }
 
-    // Ensure output directory exists
     std::fs::create_dir_all(output_dir)?;
 
-    // Step 1: Analyze contract
-    let analysis_output = output_dir.join("analysis.json");
-    cmd_cosmos_analyze_contract(
-        schema_file,
-        Some(&analysis_output),
-        true,  // validate_storage
-        Some(contract),
-        Some(rpc),
-    ).await?;
-
-    // Step 2: Compile layout
-    let layout_output = output_dir.join("layout.json");
-    cmd_cosmos_compile_layout(
-        schema_file,
-        Some(&layout_output),
-        &OutputFormat::CoprocessorJson,
-    )?;
-
-    // Step 3: Generate queries
-    let queries_output = output_dir.join("queries.json");
-    cmd_cosmos_generate_queries(
-        &layout_output,
-        queries,
-        Some(&queries_output),
-        true,  // include_examples
-    )?;
-
-    // Step 4: Resolve each query
-    let query_list: Vec<&str> = queries.split(',').map(|q| q.trim()).collect();
-    for (i, query) in query_list.iter().enumerate() {
-        let resolved_output = output_dir.join(format!("resolved_query_{}.json", i + 1));
-        cmd_cosmos_resolve_query(
-            query,
-            &layout_output,
-            &OutputFormat::CoprocessorJson,
-            Some(&resolved_output),
-        )?;
+    // Step 1: Compile layout
+    info!("Step 1: Compiling layout...");
+    let layout_file = output_dir.join("layout.json");
+    cmd_cosmos_compile_layout(schema_file, Some(&layout_file), &OutputFormat::CoprocessorJson)?;
+    let layout: traverse_core::LayoutInfo = serde_json::from_str(&std::fs::read_to_string(&layout_file)?)?;
+
+    // Step 2: Generate queries for the storage entries the queries reference
+    info!("Step 2: Generating queries...");
+    let query_list: Vec<&str> = queries.split(',').map(|q| q.trim()).filter(|q| !q.is_empty()).collect();
+    let mut entries: Vec<&str> = Vec::new();
+    for query in &query_list {
+        let entry = query.split(['[', '.']).next().unwrap_or(query);
+        if !entries.contains(&entry) {
+            entries.push(entry);
+        }
     }
+    let queries_file = output_dir.join("queries.json");
+    cmd_cosmos_generate_queries(&layout_file, &entries.join(","), Some(&queries_file), true)?;
+
+    // Step 3: Resolve queries to wasm store keys
+    info!("Step 3: Resolving queries...");
+    let resolved_queries: Vec<Value> = query_list
+        .iter()
+        .map(|query| match resolve_store_key(&layout, contract, query) {
+            Ok(mut resolved) => {
+                resolved["status"] = "resolved".into();
+                resolved
+            }
+            Err(e) => {
+                warn!("Failed to resolve query '{}': {}", query, e);
+                json!({ "query": query, "status": "failed", "error": e.to_string() })
+            }
+        })
+        .collect();
+    let resolved_count = resolved_queries.iter().filter(|q| q["status"] == "resolved").count();
 
-    println!("Auto-generation completed");
-    println!("  • Contract: {}", contract);
-    println!("  • Generated {} files", 3 + query_list.len());
-    println!("  • Output directory: {}", output_dir.display());
+    let resolved_file = output_dir.join("resolved.json");
+    std::fs::write(&resolved_file, serde_json::to_string_pretty(&json!({
+        "contract": contract,
+        "queries": resolved_queries,
+        "total_queries": query_list.len(),
+        "resolved": resolved_count
+    }))?)?;
+
+    // Step 4: Prove every resolved query (if not dry run)
+    let proofs_file = output_dir.join("proofs.json");
+    let mut plan_file = None;
+    let mut proof_failures = Vec::new();
+    let resolved: Vec<&str> = resolved_queries
+        .iter()
+        .filter(|q| q["status"] == "resolved")
+        .filter_map(|q| q["query"].as_str())
+        .collect();
+    if dry_run {
+        plan_file = write_plan(rpc, chain_id, contract, &resolved, height, output_dir)?;
+    } else {
+        info!("Step 4: Generating proofs...");
+        let (proofs, failures) = prove_queries(rpc, chain_id, contract, &layout, &resolved, height).await?;
+        proof_failures = failures;
 
+        std::fs::write(&proofs_file, serde_json::to_string_pretty(&json!({
+            "chain_id": chain_id,
+            "contract_address": contract,
+            "height": proofs.first().map(|p| p["height"].clone()),
+            "layout_commitment": hex::encode(layout.commitment()),
+            "proofs": proofs
+        }))?)?;
+    }
+
+    let summary = json!({
+        "contract": contract,
+        "chain_id": chain_id,
+        "schema_file": schema_file.display().to_string(),
+        "output_dir": output_dir.display().to_string(),
+        "dry_run": dry_run,
+        "total_queries": query_list.len(),
+        "resolved": resolved_count,
+        "proof_failures": proof_failures,
+        "files_generated": {
+            "layout": layout_file.display().to_string(),
+            "queries": queries_file.display().to_string(),
+            "resolved": resolved_file.display().to_string(),
+            "proofs": if dry_run { "skipped".to_string() } else { proofs_file.display().to_string() },
+            "plan": plan_file.map_or("skipped".to_string(), |path| path.display().to_string())
+        },
+        "next_steps": [
+            "Review generated files",
+            "Submit proofs.json to the coprocessor",
+            "Integrate with your ZK application"
+        ]
+    });
+
+    let summary_file = output_dir.join("summary.json");
+    std::fs::write(&summary_file, serde_json::to_string_pretty(&summary)?)?;
+
+    info!("Auto-generation complete. Summary written to {}", summary_file.display());
     Ok(())
 }
 
+/// Resolve a query against the layout to its key in the wasm store
+#[cfg(feature = "cosmos")]
+fn resolve_store_key(layout: &traverse_core::LayoutInfo, contract: &str, query: &str) -> Result<Value> {
+    let path = CosmosKeyResolver.resolve(layout, query)?;
+    let storage_key = CosmosKeyResolver::raw_storage_key(query)?;
+
+    Ok(json!({
+        "query": query,
+        "storage_key": hex::encode(&storage_key),
+        "key": store_key_hex(contract, &storage_key)?,
+        "layout_commitment": hex::encode(path.layout_commitment),
+        "zero_semantics": path.zero_semantics,
+    }))
+}
+
+/// Hex key of a contract storage entry in the wasm store
+#[cfg(all(feature = "cosmos", feature = "client"))]
+fn store_key_hex(contract: &str, storage_key: &[u8]) -> Result<Option<String>> {
+    Ok(Some(hex::encode(traverse_cosmos::contract_store_key(contract, storage_key)?)))
+}
+
+/// Hex key of a contract storage entry in the wasm store
+///
+/// Decoding the contract address needs the client feature.
+#[cfg(all(feature = "cosmos", not(feature = "client")))]
+fn store_key_hex(_contract: &str, _storage_key: &[u8]) -> Result<Option<String>> {
+    Ok(None)
+}
+
+/// Write the plan for proving `queries` to `<output_dir>/plan.json`
+#[cfg(all(feature = "cosmos", feature = "client"))]
+fn write_plan(
+    rpc: &str,
+    chain_id: &str,
+    contract: &str,
+    queries: &[&str],
+    height: Option<u64>,
+    output_dir: &Path,
+) -> Result<Option<std::path::PathBuf>> {
+    let plan = plan_proofs(&proof_fetcher(rpc, chain_id), contract, queries, height)?;
+    let plan_file = output_dir.join(PLAN_FILE);
+    plan.write(&plan_file)?;
+    Ok(Some(plan_file))
+}
+
+/// Write the plan for proving `queries` to `<output_dir>/plan.json`
+///
+/// Store keys embed the decoded contract address, which needs the client
+/// feature, so without it there is no plan.
+#[cfg(all(feature = "cosmos", not(feature = "client")))]
+fn write_plan(
+    _rpc: &str,
+    _chain_id: &str,
+    _contract: &str,
+    _queries: &[&str],
+    _height: Option<u64>,
+    _output_dir: &Path,
+) -> Result<Option<std::path::PathBuf>> {
+    warn!("Skipping the RPC plan: building it needs the client feature");
+    Ok(None)
+}
+
+/// Prove each query at one height, collecting artifacts and failures
+#[cfg(all(feature = "cosmos", feature = "client"))]
+async fn prove_queries(
+    rpc: &str,
+    chain_id: &str,
+    contract: &str,
+    layout: &traverse_core::LayoutInfo,
+    queries: &[&str],
+    height: Option<u64>,
+) -> Result<(Vec<Value>, Vec<Value>)> {
+    let fetcher = proof_fetcher(rpc, chain_id);
+    // Pin the height so every proof binds to the same header
+    let height = match height {
+        Some(height) => height,
+        None => fetcher.latest_height().await?.saturating_sub(1),
+    };
+
+    let mut proofs = Vec::new();
+    let mut failures = Vec::new();
+    for query in queries {
+        match proof_artifact(&fetcher, contract, query, Some(layout), Some(height)).await {
+            Ok(artifact) => proofs.push(artifact),
+            Err(e) => failures.push(json!({ "query": query, "error": e.to_string() })),
+        }
+    }
+    Ok((proofs, failures))
+}
+
+/// Prove each query at one height, collecting artifacts and failures
+#[cfg(all(feature = "cosmos", not(feature = "client")))]
+async fn prove_queries(
+    _rpc: &str,
+    _chain_id: &str,
+    _contract: &str,
+    _layout: &traverse_core::LayoutInfo,
+    _queries: &[&str],
+    _height: Option<u64>,
+) -> Result<(Vec<Value>, Vec<Value>)> {
+    Err(anyhow::anyhow!("Cosmos proof fetching not enabled. Build with --features client, or pass --dry-run"))
+}
+
 /// Perform live analysis of a CosmWasm contract
 #[cfg(feature = "cosmos")]
 async fn perform_live_cosmos_analysis(contract_address: &str, rpc_url: &str) -> Result<Value> {
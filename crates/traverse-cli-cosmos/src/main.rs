@@ -5,10 +5,17 @@
 //! avoid the k256 version conflict with Ethereum.
 
 use anyhow::Result;
-use clap::{Args, Parser, Subcommand};
+use clap::{Parser, Subcommand};
 use serde_json::{json, Value};
 use std::path::Path;
-use traverse_cli_core::OutputFormat;
+use traverse_cli_core::{
+    cache::{self, run_cache_command, CacheCommand},
+    chain_id,
+    pool, project,
+    schema::{run_schema_command, SchemaCommand},
+    watch::{self, WatchOptions},
+    CommonArgs, OutputFormat, TraverseConfig,
+};
 
 // Note: These imports are currently unused but may be needed for future features
 // #[cfg(feature = "cosmos")]
@@ -18,6 +25,8 @@ use traverse_cli_core::OutputFormat;
 use traverse_cosmos::CosmosProofFetcher;
 
 mod commands;
+#[cfg(all(feature = "cosmos", feature = "client", feature = "server"))]
+mod server;
 
 /// Cosmos-specific CLI arguments
 #[derive(Debug, Parser)]
@@ -32,12 +41,6 @@ struct CosmosArgs {
     command: CosmosCommand,
 }
 
-#[derive(Debug, Args)]
-struct CommonArgs {
-    #[arg(long, global = true)]
-    verbose: bool,
-}
-
 /// Cosmos-specific commands
 #[derive(Debug, Subcommand)]
 enum CosmosCommand {
@@ -45,7 +48,7 @@ enum CosmosCommand {
     AnalyzeContract {
         /// Path to the contract schema file
         schema_file: String,
-        /// Contract address (optional)
+        /// Contract address or address book name (optional)
         #[arg(long)]
         address: Option<String>,
         /// Enable deep analysis
@@ -77,34 +80,181 @@ enum CosmosCommand {
         /// Layout file path
         #[arg(short, long)]
         layout: String,
-        /// Contract address
+        /// Contract address or address book name
         #[arg(long)]
         address: Option<String>,
+        /// Render the result through a Tera template
+        #[arg(long)]
+        template: Option<String>,
     },
     
     /// Generate Cosmos contract proof
     GenerateProof {
-        /// Contract address
-        #[arg(long)]
+        /// Contract address or address book name
+        #[arg(long, alias = "contract")]
         address: String,
-        /// Contract query
+        /// Contract query, e.g. `balances[neutron1...]`
         #[arg(long)]
         query: String,
-        /// RPC endpoint
+        /// Layout file the query is checked against
+        #[arg(short, long)]
+        layout: Option<String>,
+        /// RPC endpoint (defaults to the profile's endpoint)
         #[arg(long)]
-        rpc: String,
+        rpc: Option<String>,
         /// Chain ID
         #[arg(long)]
         chain_id: String,
+        /// Block height to prove at (defaults to the latest committed state)
+        #[arg(long)]
+        height: Option<u64>,
+        /// Check the layout's zero semantics for the key against its write history
+        #[arg(long, requires = "store_indexer")]
+        validate_semantics: bool,
+        /// SubQuery project indexing the contract's store writes
+        #[arg(long, group = "store_indexer")]
+        subquery: Option<String>,
+        /// Subgraph indexing the contract's store writes
+        #[arg(long, group = "store_indexer")]
+        subgraph: Option<String>,
+        /// Print the RPC requests the proof needs instead of sending them
+        #[arg(long)]
+        dry_run: bool,
+        /// Render the result through a Tera template
+        #[arg(long)]
+        template: Option<String>,
+        /// Output file path
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    
+    /// Verify a generate-proof artifact offline against a trusted app hash
+    ///
+    /// Exits with status 2 when the artifact does not verify.
+    VerifyProof {
+        /// Proof artifact written by generate-proof
+        #[arg(long)]
+        proof: String,
+        /// Layout file the artifact's query must resolve against
+        #[arg(short, long)]
+        layout: Option<String>,
+        /// App hash of the block after the proof height, from a source you trust
+        #[arg(long)]
+        app_hash: String,
+        /// Output file path
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    
+    /// Spot-check a layout against a contract's live storage
+    ///
+    /// Exits with status 2 when any item's value contradicts the layout.
+    VerifyLayout {
+        /// Contract address or address book name
+        #[arg(long, alias = "contract")]
+        address: String,
+        /// Layout file (defaults to the address book entry's layout)
+        #[arg(short, long)]
+        layout: Option<String>,
+        /// RPC endpoint (defaults to the profile's endpoint)
+        #[arg(long)]
+        rpc: Option<String>,
+        /// Chain ID
+        #[arg(long)]
+        chain_id: String,
+        /// Block height to read at (defaults to the latest committed state)
+        #[arg(long)]
+        height: Option<u64>,
+        /// Output file path
+        #[arg(short, long)]
+        output: Option<String>,
     },
     
     /// Auto-generate for Cosmos contracts
     AutoGenerate {
-        /// Configuration file path
-        config: String,
+        /// Configuration file path (JSON with `schema`, `contract`, `chain_id` and `queries`, or a multi-chain project)
+        #[arg(value_name = "CONFIG")]
+        config_file: String,
         /// Output directory
         #[arg(short, long, default_value = "output")]
         output_dir: String,
+        /// RPC endpoint (defaults to the profile's endpoint)
+        #[arg(long)]
+        rpc: Option<String>,
+        /// Resolve queries and write the RPC plan instead of fetching proofs
+        #[arg(long)]
+        dry_run: bool,
+        /// Run only this chain's section of a project config
+        #[arg(long, hide = true)]
+        only: Option<String>,
+    },
+
+    /// Auto-generate many contracts on a bounded worker pool
+    ///
+    /// With `--format ndjson`, each entry\'s report is streamed to stdout as it finishes.
+    BatchGenerate {
+        /// Batch config: a JSON list of auto-generate entries, or a project config
+        #[arg(value_name = "CONFIG")]
+        config_file: String,
+        /// Output directory; each entry is generated into `<output-dir>/<name>`
+        #[arg(short, long, default_value = "output")]
+        output_dir: String,
+        /// RPC endpoint for entries without their own (defaults to the profile's endpoint)
+        #[arg(long)]
+        rpc: Option<String>,
+        /// Resolve queries and write each entry's RPC plan instead of fetching proofs
+        #[arg(long)]
+        dry_run: bool,
+        /// Number of entries to generate at once
+        #[arg(long, default_value_t = 1)]
+        parallel: usize,
+        /// Maximum RPC requests in flight across all workers
+        #[arg(long)]
+        max_concurrency: Option<usize>,
+        /// Skip entries a previous run of this batch completed, per its checkpoint
+        #[arg(long)]
+        resume: bool,
+    },
+
+    /// Regenerate batch entries whenever the config or their inputs change
+    Watch {
+        /// Batch config: a JSON list of auto-generate entries, or a project config
+        #[arg(value_name = "CONFIG")]
+        config_file: String,
+        /// Output directory; each entry is generated into `<output-dir>/<name>`
+        #[arg(short, long, default_value = "output")]
+        output_dir: String,
+        /// RPC endpoint for entries without their own (defaults to the profile's endpoint)
+        #[arg(long)]
+        rpc: Option<String>,
+        /// Resolve queries and write each entry's RPC plan instead of fetching proofs
+        #[arg(long)]
+        dry_run: bool,
+        /// URL change reports are POSTed to (stdout if not specified)
+        #[arg(long)]
+        webhook: Option<String>,
+        /// Milliseconds to let a burst of file events settle before rehashing
+        #[arg(long, default_value_t = 300)]
+        debounce_ms: u64,
+    },
+    
+    /// Export JSON Schemas for the CLI output formats
+    Schema {
+        #[command(subcommand)]
+        action: SchemaCommand,
+    },
+
+    /// Inspect or clear the RPC response cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheCommand,
+    },
+
+    /// Serve the Cosmos commands over HTTP
+    Serve {
+        /// Address to bind
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        bind: String,
     },
 }
 
@@ -170,13 +320,14 @@ fn compile_layout(input: &str, output: Option<&str>) -> CliResult<()> {
 }
 
 #[cfg(feature = "cosmos")]
-fn resolve_query(query: &str, layout_file: &str, address: Option<&str>) -> CliResult<Value> {
+fn resolve_query(query: &str, layout_file: &str, address: Option<&str>, template: Option<&str>) -> CliResult<Value> {
     use std::path::Path;
     
     let result = commands::cmd_cosmos_resolve_query(
         query,
         Path::new(layout_file),
         &OutputFormat::CoprocessorJson,
+        template.map(Path::new),
         None, // output handled by caller
     );
     
@@ -194,11 +345,156 @@ fn resolve_query(query: &str, layout_file: &str, address: Option<&str>) -> CliRe
     }
 }
 
+/// Run `auto-generate` for a single-contract config or a project config
+///
+/// For a project, the Cosmos section runs here and the other sections are
+/// dispatched to their CLIs unless `only` restricts the run to this chain.
+#[cfg(feature = "cosmos")]
+async fn auto_generate(
+    config: &str,
+    profile: &traverse_cli_core::Profile,
+    rpc: Option<&str>,
+    output_dir: &str,
+    dry_run: bool,
+    only: Option<&str>,
+    dispatch: &project::DispatchOptions,
+) -> CliResult<()> {
+    let config_path = Path::new(config);
+    let value = traverse_cli_core::CliUtils::load_config(config_path)?;
+    let Some(project) = project::ProjectConfig::from_value(&value)? else {
+        return Ok(auto_generate_entry(value, config_path, profile, rpc, Path::new(output_dir).to_path_buf(), dry_run).await?);
+    };
+    if let Some(chain) = only.filter(|chain| *chain != "cosmos") {
+        return Err(format!("traverse-cosmos cannot run the {} section", chain).into());
+    }
+
+    let own = project::run_section("cosmos", &project, Path::new(output_dir), |entry, entry_dir| {
+        auto_generate_entry(entry, config_path, profile, rpc, entry_dir, dry_run)
+    })
+    .await?;
+    if only.is_some() {
+        return Ok(());
+    }
+
+    let manifest = project::write_manifest(&project, config_path, Path::new(output_dir), own, dispatch)?;
+    if !manifest.is_ok() {
+        return Err(format!(
+            "Some project entries failed; see {}",
+            Path::new(output_dir).join(project::PROJECT_MANIFEST).display()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Run `batch-generate`, failing if any entry failed
+#[cfg(feature = "cosmos")]
+async fn batch_generate(
+    config: &str,
+    profile: &traverse_cli_core::Profile,
+    rpc: Option<&str>,
+    output_dir: &str,
+    dry_run: bool,
+    options: &pool::PoolOptions,
+) -> CliResult<()> {
+    let config_path = Path::new(config);
+    let entries = pool::batch_entries(&traverse_cli_core::CliUtils::load_config(config_path)?, "cosmos")?;
+    if let Some(max_concurrency) = options.max_concurrency {
+        pool::limit_rpc(max_concurrency);
+    }
+
+    let output_dir = Path::new(output_dir);
+    let settings = serde_json::json!({ "dry_run": dry_run, "rpc": rpc });
+    let summary = pool::run_batch(&entries, output_dir, options, settings, |entry, entry_dir| {
+        auto_generate_entry(entry, config_path, profile, rpc, entry_dir, dry_run)
+    })
+    .await?;
+    pool::print_summary(&summary, output_dir);
+    if !summary.is_ok() {
+        return Err(format!("{} of {} entries failed", summary.failed, summary.total).into());
+    }
+    Ok(())
+}
+
+/// Run `watch`, regenerating entries as the config and their inputs change
+#[cfg(feature = "cosmos")]
+async fn watch_entries(
+    config: &str,
+    profile: &traverse_cli_core::Profile,
+    rpc: Option<&str>,
+    output_dir: &str,
+    dry_run: bool,
+    options: &WatchOptions,
+) -> CliResult<()> {
+    let config_path = Path::new(config);
+    watch::run_watch(config_path, Path::new(output_dir), options, |entry, entry_dir| {
+        auto_generate_entry(entry, config_path, profile, rpc, entry_dir, dry_run)
+    })
+    .await?;
+    Ok(())
+}
+
+#[cfg(feature = "cosmos")]
+async fn auto_generate_entry(
+    entry: Value,
+    config_path: &Path,
+    profile: &traverse_cli_core::Profile,
+    rpc: Option<&str>,
+    output_dir: std::path::PathBuf,
+    dry_run: bool,
+) -> traverse_cli_core::CliResult<()> {
+    use traverse_cli_core::CliError;
+
+    let config: commands::AutoGenerateConfig = serde_json::from_value(entry)
+        .map_err(|e| CliError::Configuration(format!("Invalid auto-generate config: {}", e)))?;
+    let rpc = profile.require_rpc(config.rpc.as_deref().or(rpc), "cosmos")?;
+    if !dry_run {
+        chain_id::verify(&rpc, || commands::fetch_chain_id(&rpc)).await?;
+    }
+    let contract = profile.resolve_address(&config.contract, "cosmos")?.address;
+    let schema = config_path.parent().unwrap_or(Path::new("")).join(&config.schema);
+
+    commands::cmd_cosmos_auto_generate(
+        &schema,
+        &rpc,
+        &contract,
+        &config.chain_id,
+        &config.queries.join(","),
+        config.height,
+        &output_dir,
+        dry_run,
+    )
+    .await
+    .map_err(|e| CliError::Processing(e.to_string()))
+}
+
+#[cfg(all(feature = "cosmos", feature = "client", feature = "server"))]
+async fn serve(profile: traverse_cli_core::Profile, bind: &str) -> CliResult<()> {
+    traverse_cli_core::server::ApiServer::new(profile)
+        .with_chain("cosmos", server::CosmosHandler)
+        .serve(bind)
+        .await?;
+    Ok(())
+}
+
+#[cfg(not(all(feature = "cosmos", feature = "client", feature = "server")))]
+async fn serve(_profile: traverse_cli_core::Profile, _bind: &str) -> CliResult<()> {
+    Err("Server mode not enabled. Build with --features cosmos,client,server".into())
+}
+
 async fn handle_command(args: CosmosArgs) -> CliResult<()> {
+    let config = TraverseConfig::load(args.common.config.as_deref().map(Path::new))?;
+    let profile = config.profile(args.common.profile.as_deref())?;
+    if args.common.cache_flag().or(profile.cache.enabled).unwrap_or(false) {
+        cache::install(cache::DiskCache::from_settings(&profile.cache));
+    }
+    args.common.install_chain_id(&profile, "cosmos");
+
     match args.command {
         CosmosCommand::AnalyzeContract { schema_file, address, deep } => {
             #[cfg(feature = "cosmos")]
             {
+                let address = address.map(|a| profile.resolve_address(&a, "cosmos")).transpose()?.map(|e| e.address);
                 let result = analyze_contract(&schema_file, address.as_deref(), deep).await?;
                 println!("{}", serde_json::to_string_pretty(&result)?);
             }
@@ -248,11 +544,91 @@ async fn handle_command(args: CosmosArgs) -> CliResult<()> {
             }
         }
         
-        CosmosCommand::ResolveQuery { query, layout, address } => {
+        CosmosCommand::ResolveQuery { query, layout, address, template } => {
             #[cfg(feature = "cosmos")]
             {
-                let result = resolve_query(&query, &layout, address.as_deref())?;
-                println!("{}", serde_json::to_string_pretty(&result)?);
+                let address = address.map(|a| profile.resolve_address(&a, "cosmos")).transpose()?.map(|e| e.address);
+                let result = resolve_query(&query, &layout, address.as_deref(), template.as_deref())?;
+                if template.is_none() {
+                    println!("{}", serde_json::to_string_pretty(&result)?);
+                }
+            }
+            
+            #[cfg(not(feature = "cosmos"))]
+            {
+                eprintln!("Error: Cosmos support not enabled.");
+                eprintln!("This binary was built without Cosmos support.");
+                eprintln!("Please use a build with the 'cosmos' feature enabled.");
+                std::process::exit(1);
+            }
+        }
+        
+        CosmosCommand::GenerateProof {
+            address,
+            query,
+            layout,
+            rpc,
+            chain_id,
+            height,
+            validate_semantics,
+            subquery,
+            subgraph,
+            dry_run,
+            template,
+            output,
+        } => {
+            #[cfg(feature = "cosmos")]
+            {
+                let rpc = profile.require_rpc(rpc.as_deref(), "cosmos")?;
+                if !dry_run {
+                    chain_id::verify(&rpc, || commands::fetch_chain_id(&rpc)).await?;
+                }
+                let contract = profile.resolve_address(&address, "cosmos")?;
+                let layout = layout.or(contract.layout);
+                let indexer = match (subquery, subgraph) {
+                    (Some(url), _) => Some(commands::StoreIndexer::SubQuery(url)),
+                    (None, Some(url)) => Some(commands::StoreIndexer::Subgraph(url)),
+                    (None, None) => None,
+                }
+                .filter(|_| validate_semantics);
+                commands::cmd_cosmos_generate_proof(
+                    &contract.address,
+                    &query,
+                    layout.as_deref().map(Path::new),
+                    &rpc,
+                    &chain_id,
+                    height,
+                    indexer.as_ref(),
+                    dry_run,
+                    &args.common.output_format(&profile),
+                    template.as_deref().map(Path::new),
+                    output.as_deref().map(Path::new),
+                )
+                .await?;
+            }
+            
+            #[cfg(not(feature = "cosmos"))]
+            {
+                eprintln!("Error: Cosmos support not enabled.");
+                eprintln!("This binary was built without Cosmos support.");
+                eprintln!("Please use a build with the 'cosmos' feature enabled.");
+                std::process::exit(1);
+            }
+        }
+        
+        CosmosCommand::VerifyProof { proof, layout, app_hash, output } => {
+            #[cfg(feature = "cosmos")]
+            {
+                let valid = commands::cmd_cosmos_verify_proof(
+                    Path::new(&proof),
+                    layout.as_deref().map(Path::new),
+                    &app_hash,
+                    &args.common.output_format(&profile),
+                    output.as_deref().map(Path::new),
+                )?;
+                if !valid {
+                    std::process::exit(2);
+                }
             }
             
             #[cfg(not(feature = "cosmos"))]
@@ -264,12 +640,27 @@ async fn handle_command(args: CosmosArgs) -> CliResult<()> {
             }
         }
         
-        CosmosCommand::GenerateProof { address, query, rpc, chain_id } => {
+        CosmosCommand::VerifyLayout { address, layout, rpc, chain_id, height, output } => {
             #[cfg(feature = "cosmos")]
             {
-                println!("Generating proof for contract {} with query: {}", address, query);
-                println!("RPC: {}, Chain ID: {}", rpc, chain_id);
-                // Implementation would go here
+                let rpc = profile.require_rpc(rpc.as_deref(), "cosmos")?;
+                chain_id::verify(&rpc, || commands::fetch_chain_id(&rpc)).await?;
+                let contract = profile.resolve_address(&address, "cosmos")?;
+                let layout = layout.or(contract.layout).ok_or_else(|| {
+                    traverse_cli_core::CliError::InvalidArgument(format!("No layout for {}. Pass --layout", address))
+                })?;
+                let mismatched = commands::cmd_cosmos_verify_layout(
+                    &contract.address,
+                    Path::new(&layout),
+                    &rpc,
+                    &chain_id,
+                    height,
+                    output.as_deref().map(Path::new),
+                )
+                .await?;
+                if mismatched {
+                    std::process::exit(2);
+                }
             }
             
             #[cfg(not(feature = "cosmos"))]
@@ -281,11 +672,16 @@ async fn handle_command(args: CosmosArgs) -> CliResult<()> {
             }
         }
         
-        CosmosCommand::AutoGenerate { config, output_dir } => {
+        CosmosCommand::AutoGenerate { config_file, output_dir, rpc, dry_run, only } => {
             #[cfg(feature = "cosmos")]
             {
-                println!("Auto-generating from config: {} to {}", config, output_dir);
-                // Implementation would go here
+                let dispatch = project::DispatchOptions {
+                    dry_run,
+                    profile: args.common.profile.clone(),
+                    config: args.common.config.clone(),
+                    cache: args.common.cache_flag(),
+                };
+                auto_generate(&config_file, &profile, rpc.as_deref(), &output_dir, dry_run, only.as_deref(), &dispatch).await?;
             }
             
             #[cfg(not(feature = "cosmos"))]
@@ -296,6 +692,55 @@ async fn handle_command(args: CosmosArgs) -> CliResult<()> {
                 std::process::exit(1);
             }
         }
+        
+        CosmosCommand::BatchGenerate { config_file, output_dir, rpc, dry_run, parallel, max_concurrency, resume } => {
+            #[cfg(feature = "cosmos")]
+            {
+                let options = pool::PoolOptions { parallel, max_concurrency, progress: true, resume, ndjson: matches!(args.common.output_format(&profile), OutputFormat::Ndjson) };
+                batch_generate(&config_file, &profile, rpc.as_deref(), &output_dir, dry_run, &options).await?;
+            }
+
+            #[cfg(not(feature = "cosmos"))]
+            {
+                eprintln!("Error: Cosmos support not enabled.");
+                eprintln!("This binary was built without Cosmos support.");
+                eprintln!("Please use a build with the 'cosmos' feature enabled.");
+                std::process::exit(1);
+            }
+        }
+
+        CosmosCommand::Watch { config_file, output_dir, rpc, dry_run, webhook, debounce_ms } => {
+            #[cfg(feature = "cosmos")]
+            {
+                let options = WatchOptions {
+                    chain: "cosmos",
+                    input_key: "schema",
+                    webhook,
+                    debounce: std::time::Duration::from_millis(debounce_ms),
+                };
+                watch_entries(&config_file, &profile, rpc.as_deref(), &output_dir, dry_run, &options).await?;
+            }
+
+            #[cfg(not(feature = "cosmos"))]
+            {
+                eprintln!("Error: Cosmos support not enabled.");
+                eprintln!("This binary was built without Cosmos support.");
+                eprintln!("Please use a build with the 'cosmos' feature enabled.");
+                std::process::exit(1);
+            }
+        }
+
+        CosmosCommand::Schema { action } => {
+            run_schema_command(&action, None)?;
+        }
+
+        CosmosCommand::Cache { action } => {
+            run_cache_command(&action, &profile.cache, None)?;
+        }
+
+        CosmosCommand::Serve { bind } => {
+            serve(profile, &bind).await?;
+        }
     }
     
     Ok(())
@@ -0,0 +1,185 @@
+//! Cosmos handler for the HTTP API server
+//!
+//! `compile-layout` takes a CosmWasm schema under `schema`, with an optional
+//! `contract_name`, and the query endpoints take a compiled layout under
+//! `layout`. `generate-proof` takes the contract `address`, `query` and
+//! `chain_id`, with the optional `layout`, `height` and `dry_run` of the CLI
+//! command; `validate_semantics` needs a `subquery` or `subgraph` URL.
+
+use crate::commands::{generate_storage_proof, StoreIndexer};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+use traverse_cli_core::{
+    server::{field, str_field, BoxFuture, ChainHandler},
+    CliError, CliResult,
+};
+use traverse_core::{Key, KeyResolver, LayoutCompiler, LayoutInfo};
+use traverse_cosmos::{CosmosKeyResolver, CosmosLayoutCompiler};
+
+/// Serves Cosmos requests for `traverse-cosmos serve`
+pub struct CosmosHandler;
+
+fn parse_layout(value: &Value) -> CliResult<LayoutInfo> {
+    serde_json::from_value(value.clone())
+        .map_err(|e| CliError::InvalidArgument(format!("Invalid layout: {}", e)))
+}
+
+fn resolve(layout: &LayoutInfo, query: &str) -> CliResult<Value> {
+    let resolved = CosmosKeyResolver
+        .resolve(layout, query)
+        .map_err(|e| CliError::Processing(e.to_string()))?;
+    Ok(json!({
+        "query": query,
+        "storage_key": match &resolved.key {
+            Key::Fixed(key) => hex::encode(key),
+            _ => "dynamic".to_string(),
+        },
+        "layout_commitment": hex::encode(resolved.layout_commitment),
+        "field_size": resolved.field_size,
+        "offset": resolved.offset
+    }))
+}
+
+impl ChainHandler for CosmosHandler {
+    fn compile_layout(&self, request: &Value) -> CliResult<Value> {
+        let schema = field(request, "schema")?;
+        // The compiler reads from a path, so stage the schema in a temp file
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "traverse-serve-{}-{}.json",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::write(&path, schema.to_string()).map_err(CliError::Io)?;
+        let compiled = CosmosLayoutCompiler.compile_layout(&path);
+        let _ = std::fs::remove_file(&path);
+
+        let mut layout = compiled.map_err(|e| CliError::Processing(e.to_string()))?;
+        layout.contract_name = request.get("contract_name").and_then(Value::as_str).unwrap_or("Contract").to_string();
+        Ok(json!({
+            "layout": layout,
+            "layout_commitment": hex::encode(layout.commitment())
+        }))
+    }
+
+    fn resolve_query(&self, layout: &Value, query: &str) -> CliResult<Value> {
+        resolve(&parse_layout(layout)?, query)
+    }
+
+    fn batch_resolve(&self, layout: &Value, queries: &[String]) -> CliResult<Value> {
+        let layout = parse_layout(layout)?;
+        let results: Vec<Value> = queries
+            .iter()
+            .map(|query| match resolve(&layout, query) {
+                Ok(resolved) => json!({ "status": "resolved", "resolved": resolved, "query": query }),
+                Err(e) => json!({ "status": "failed", "query": query, "error": e.to_string() }),
+            })
+            .collect();
+        let failed = results.iter().filter(|r| r["status"] == "failed").count();
+
+        Ok(json!({
+            "layout_commitment": hex::encode(layout.commitment()),
+            "total_queries": results.len(),
+            "resolved": results.len() - failed,
+            "failed": failed,
+            "results": results
+        }))
+    }
+
+    fn generate_proof<'a>(&'a self, request: &'a Value, rpc: &'a str) -> BoxFuture<'a, CliResult<Value>> {
+        Box::pin(async move {
+            let address = str_field(request, "address")?;
+            let query = str_field(request, "query")?;
+            let chain_id = str_field(request, "chain_id")?;
+            let layout = request.get("layout").map(parse_layout).transpose()?;
+            let height = match request.get("height") {
+                Some(height) => Some(
+                    height
+                        .as_u64()
+                        .ok_or_else(|| CliError::InvalidArgument("'height' must be a block height".to_string()))?,
+                ),
+                None => None,
+            };
+            let flag = |name: &str| request.get(name).and_then(Value::as_bool).unwrap_or(false);
+            let url = |name: &str| request.get(name).and_then(Value::as_str).map(str::to_string);
+            let indexer = match (url("subquery"), url("subgraph")) {
+                _ if !flag("validate_semantics") => None,
+                (Some(url), None) => Some(StoreIndexer::SubQuery(url)),
+                (None, Some(url)) => Some(StoreIndexer::Subgraph(url)),
+                _ => {
+                    return Err(CliError::InvalidArgument(
+                        "'validate_semantics' needs one of 'subquery' and 'subgraph'".to_string(),
+                    ))
+                }
+            };
+
+            let dry_run = flag("dry_run");
+            generate_storage_proof(address, query, layout.as_ref(), rpc, chain_id, height, indexer.as_ref(), dry_run)
+                .await
+                .map_err(|e| CliError::Processing(e.to_string()))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use traverse_core::{StorageEntry, TypeInfo, ZeroSemantics};
+
+    fn layout() -> Value {
+        serde_json::to_value(LayoutInfo {
+            contract_name: "Token".to_string(),
+            storage: vec![StorageEntry {
+                label: "config".to_string(),
+                slot: "0".to_string(),
+                offset: 0,
+                type_name: "t_config".to_string(),
+                zero_semantics: ZeroSemantics::NeverWritten,
+            }],
+            types: vec![TypeInfo {
+                label: "t_config".to_string(),
+                number_of_bytes: "32".to_string(),
+                encoding: "inplace".to_string(),
+                base: None,
+                key: None,
+                value: None,
+            }],
+            code_variables: Vec::new(),
+        })
+        .unwrap()
+    }
+
+    const CONTRACT: &str = "neutron1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5z5tpwxqergd3c8g7rusqpsa9eu";
+
+    #[tokio::test]
+    async fn test_handler_resolves_and_plans_proof() {
+        let resolved = CosmosHandler.resolve_query(&layout(), "config").unwrap();
+        assert_eq!(resolved["storage_key"].as_str().unwrap().len(), 64);
+        let batch = CosmosHandler.batch_resolve(&layout(), &["config".to_string(), "missing".to_string()]).unwrap();
+        assert_eq!((batch["resolved"].as_u64(), batch["failed"].as_u64()), (Some(1), Some(1)));
+
+        let request = json!({
+            "address": CONTRACT,
+            "query": "config",
+            "chain_id": "neutron-1",
+            "layout": layout(),
+            "height": 100,
+            "dry_run": true
+        });
+        let plan = CosmosHandler.generate_proof(&request, "https://cosmos.example").await.unwrap();
+        assert_eq!(plan["calls"][0]["method"], "abci_query");
+        assert_eq!(plan["calls"][1]["method"], "block");
+
+        let without_chain = json!({ "address": CONTRACT, "query": "config" });
+        assert!(CosmosHandler.generate_proof(&without_chain, "https://cosmos.example").await.is_err());
+
+        // Semantic validation needs an indexer, and reads history a plan cannot
+        let mut validated = request.clone();
+        validated["validate_semantics"] = true.into();
+        let error = CosmosHandler.generate_proof(&validated, "https://cosmos.example").await.unwrap_err();
+        assert!(error.to_string().contains("needs one of 'subquery' and 'subgraph'"));
+        validated["subquery"] = "https://subquery.example".into();
+        let error = CosmosHandler.generate_proof(&validated, "https://cosmos.example").await.unwrap_err();
+        assert!(error.to_string().contains("--dry-run cannot plan --validate-semantics"));
+    }
+}
@@ -3,10 +3,17 @@
 //! This module provides comprehensive Ethereum-specific CLI commands for contract analysis,
 //! storage layout compilation, query generation, and end-to-end automation.
 
-use traverse_cli_core::{formatters::write_output, OutputFormat};
+use traverse_cli_core::{
+    artifact,
+    cache,
+    chain_id,
+    formatters::{load_layout, write_output, write_result, NdjsonWriter},
+    plan::{RpcPlan, PLAN_FILE},
+    spot_check::{Observed, SpotCheck, SpotCheckReport},
+    template,
+    OutputFormat, Profile,
+};
 use anyhow::Result;
-use base64::{engine::general_purpose::STANDARD, Engine};
-use bincode;
 use hex;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
@@ -14,19 +21,51 @@ use std::path::Path;
 use tracing::{info, warn};
 use reqwest;
 use chrono;
-use traverse_core::{KeyResolver, LayoutCompiler, LayoutInfo, Key};
+use traverse_core::{KeyResolver, LayoutCompiler, LayoutConflicts, LayoutDiff, LayoutInfo, Key, ZeroSemantics};
 
 #[cfg(feature = "ethereum")]
-use traverse_ethereum::{EthereumKeyResolver, EthereumLayoutCompiler};
+use traverse_ethereum::{
+    AbiFetcher, BlockSelection, BlockTag, EthereumKeyResolver, EthereumLayoutCompiler, EvmChainAdapter,
+    RetryPolicy, RpcTransport, VyperLayoutCompiler,
+};
 
 /// Helper function to convert Key to bytes for hex encoding
-fn key_to_bytes(key: &Key) -> &[u8] {
+pub(crate) fn key_to_bytes(key: &Key) -> &[u8] {
     match key {
         Key::Fixed(bytes) => bytes,
         Key::Variable(bytes) => bytes,
     }
 }
 
+/// Layout a command resolves against: a compiled layout file or a built-in preset
+#[derive(Debug, Clone, Copy)]
+pub enum LayoutSource<'a> {
+    File(&'a Path),
+    /// Name of one of `traverse_ethereum::presets::PRESETS`
+    Preset(&'a str),
+}
+
+#[cfg(feature = "ethereum")]
+impl LayoutSource<'_> {
+    pub fn load(self) -> Result<LayoutInfo> {
+        match self {
+            LayoutSource::File(path) => {
+                if !path.exists() {
+                    return Err(anyhow::anyhow!("Layout file does not exist: {}", path.display()));
+                }
+                load_layout(path).map_err(|e| anyhow::anyhow!("Failed to load layout '{}': {}", path.display(), e))
+            }
+            LayoutSource::Preset(name) => traverse_ethereum::presets::preset(name).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unknown preset '{}'. Available presets: {}",
+                    name,
+                    traverse_ethereum::presets::PRESETS.join(", ")
+                )
+            }),
+        }
+    }
+}
+
 /// Simplified structure for TOML serialization
 #[derive(Serialize, Deserialize)]
 struct SimpleLayoutInfo {
@@ -292,12 +331,16 @@ fn generate_function_signature(name: &str, inputs: &[Value]) -> String {
 }
 
 /// Compile Ethereum storage layout
+///
+/// `contract` picks a contract from solc output or a Hardhat build-info file
+/// by name or fully qualified name; other inputs ignore it.
 #[cfg(feature = "ethereum")]
 pub fn cmd_ethereum_compile_layout(
     abi_file: &Path,
     output: Option<&Path>,
     format: &OutputFormat,
     validate: bool,
+    contract: Option<&str>,
 ) -> Result<()> {
     info!("Compiling Ethereum storage layout from {}", abi_file.display());
 
@@ -312,12 +355,17 @@ pub fn cmd_ethereum_compile_layout(
     // Read ABI file
     let abi_content = std::fs::read_to_string(abi_file)
         .map_err(|e| anyhow::anyhow!("Failed to read ABI file '{}': {}", abi_file.display(), e))?;
-    let _abi: Value = serde_json::from_str(&abi_content)
+    let abi: Value = serde_json::from_str(&abi_content)
         .map_err(|e| anyhow::anyhow!("Failed to parse ABI file '{}': {}", abi_file.display(), e))?;
 
-    // Create compiler and compile layout
-    let compiler = EthereumLayoutCompiler;
-    let layout = compiler.compile_layout(abi_file)?;
+    // `vyper -f layout` output, or a Solidity layout or ABI
+    let layout = if VyperLayoutCompiler::is_vyper_layout(&abi) {
+        VyperLayoutCompiler.compile_layout(abi_file)?
+    } else if let Some(contract) = contract {
+        EthereumLayoutCompiler.compile_solc_output(&abi, Some(contract))?
+    } else {
+        EthereumLayoutCompiler.compile_layout(abi_file)?
+    };
 
     if validate {
         info!("Validating layout for conflicts...");
@@ -361,14 +409,9 @@ pub fn cmd_ethereum_compile_layout(
             };
             toml::to_string_pretty(&simplified)?
         }
-        OutputFormat::Binary => {
-            let binary_data = bincode::serialize(&layout)?;
-            format!("Binary layout: {} bytes\nBase64: {}", binary_data.len(), STANDARD.encode(&binary_data))
-        }
-        OutputFormat::Base64 => {
-            let binary_data = bincode::serialize(&layout)?;
-            STANDARD.encode(&binary_data)
-        }
+        OutputFormat::Binary => return Ok(artifact::write(&layout, output)?),
+        OutputFormat::Base64 => artifact::to_base64(&layout)?,
+        OutputFormat::Ndjson => serde_json::to_string(&layout)?,
     };
 
     write_output(&output_str, output)?;
@@ -381,21 +424,179 @@ pub fn cmd_ethereum_compile_layout(
     _output: Option<&Path>,
     _format: &OutputFormat,
     _validate: bool,
+    _contract: Option<&str>,
+) -> Result<()> {
+    Err(anyhow::anyhow!("Ethereum support not enabled. Build with --features ethereum"))
+}
+
+/// Where `compile-layout --address` caches the fetched ABI
+///
+/// Next to the output layout (`usdt.json` caches `usdt.abi.json`), or in the
+/// working directory named after the address when printing to stdout.
+pub fn abi_cache_path(address: &str, output: Option<&Path>) -> std::path::PathBuf {
+    match output {
+        Some(output) => output.with_extension("abi.json"),
+        None => std::path::PathBuf::from(format!("{}.abi.json", address.to_lowercase())),
+    }
+}
+
+/// Fetch a contract ABI from Etherscan or Sourcify into `cache`
+///
+/// An existing cache file is reused unless `refresh` is set, so recompiling a
+/// layout does not depend on the explorer returning the same ABI again. With
+/// `proxy_rpc`, an EIP-1967 proxy's implementation ABI is fetched instead,
+/// since the implementation lays out the proxy's storage.
+#[cfg(feature = "ethereum")]
+pub async fn cmd_ethereum_fetch_abi(
+    address: &str,
+    chain: &str,
+    etherscan_api_key: Option<&str>,
+    cache: &Path,
+    refresh: bool,
+    proxy_rpc: Option<&str>,
+) -> Result<()> {
+    if cache.exists() && !refresh {
+        info!("Using cached ABI {}", cache.display());
+        return Ok(());
+    }
+
+    let fetcher = AbiFetcher::for_chain(chain, etherscan_api_key.map(str::to_string))?;
+    let fetch = || async {
+        let (abi_json, source) = match proxy_rpc {
+            Some(rpc) => {
+                let (abi_json, source, proxy) = fetcher.fetch_storage_abi(address, rpc, "latest").await?;
+                if let Some(implementation) = proxy.and_then(|info| info.implementation) {
+                    info!("{} is an EIP-1967 proxy for {}", address, implementation);
+                }
+                (abi_json, source)
+            }
+            None => fetcher.fetch_abi(address).await?,
+        };
+        info!("Fetched ABI for {} on {} from {:?}", address, chain, source);
+        Ok::<_, anyhow::Error>(abi_json)
+    };
+    let abi_json = if refresh {
+        fetch().await?
+    } else {
+        let key = json!({ "chain": chain, "address": address.to_lowercase(), "follow_proxy": proxy_rpc.is_some() });
+        cache::cached("abi", key, fetch).await?
+    };
+
+    let abi: Value = serde_json::from_str(&abi_json)
+        .map_err(|e| anyhow::anyhow!("Fetched ABI for {} is not valid JSON: {}", address, e))?;
+    if let Some(parent) = cache.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(cache, serde_json::to_string_pretty(&abi)?)
+        .map_err(|e| anyhow::anyhow!("Failed to write ABI cache '{}': {}", cache.display(), e))?;
+    Ok(())
+}
+
+#[cfg(not(feature = "ethereum"))]
+pub async fn cmd_ethereum_fetch_abi(
+    _address: &str,
+    _chain: &str,
+    _etherscan_api_key: Option<&str>,
+    _cache: &Path,
+    _refresh: bool,
+    _proxy_rpc: Option<&str>,
+) -> Result<()> {
+    Err(anyhow::anyhow!("Ethereum support not enabled. Build with --features ethereum"))
+}
+
+/// Fetch a verified contract's exact storage layout into `cache`
+///
+/// Like [`cmd_ethereum_fetch_abi`], but the cache holds the canonical layout
+/// Sourcify compiled, or the one recompiled from Etherscan sources with
+/// `solc`, instead of an ABI to infer a layout from.
+#[cfg(feature = "ethereum")]
+pub async fn cmd_ethereum_fetch_verified_layout(
+    address: &str,
+    chain: &str,
+    etherscan_api_key: Option<&str>,
+    solc: Option<&Path>,
+    cache: &Path,
+    refresh: bool,
+) -> Result<()> {
+    if cache.exists() && !refresh {
+        info!("Using cached layout {}", cache.display());
+        return Ok(());
+    }
+
+    let mut fetcher = AbiFetcher::for_chain(chain, etherscan_api_key.map(str::to_string))?;
+    if let Some(solc) = solc {
+        fetcher = fetcher.with_solc(solc);
+    }
+    let fetch = || async {
+        let (layout, source) = fetcher.fetch_verified_layout(address).await?;
+        info!("Fetched the verified layout of {} on {} from {:?}", address, chain, source);
+        Ok::<_, anyhow::Error>(layout)
+    };
+    let layout = if refresh {
+        fetch().await?
+    } else {
+        let key = json!({ "chain": chain, "address": address.to_lowercase() });
+        cache::cached("verified-layout", key, fetch).await?
+    };
+
+    if let Some(parent) = cache.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(cache, serde_json::to_string_pretty(&layout)?)
+        .map_err(|e| anyhow::anyhow!("Failed to write layout cache '{}': {}", cache.display(), e))?;
+    Ok(())
+}
+
+#[cfg(not(feature = "ethereum"))]
+pub async fn cmd_ethereum_fetch_verified_layout(
+    _address: &str,
+    _chain: &str,
+    _etherscan_api_key: Option<&str>,
+    _solc: Option<&Path>,
+    _cache: &Path,
+    _refresh: bool,
 ) -> Result<()> {
     Err(anyhow::anyhow!("Ethereum support not enabled. Build with --features ethereum"))
 }
 
-fn validate_layout(layout: &LayoutInfo) -> Result<()> {
+/// Propose a partial layout from a contract's deployed bytecode
+///
+/// For unverified contracts. The layout is written with a `provenance`
+/// block marking it heuristic; see [`traverse_ethereum::inference`].
+#[cfg(feature = "ethereum")]
+pub async fn cmd_ethereum_infer_layout(address: &str, rpc_url: &str, output: Option<&Path>) -> Result<()> {
+    let client = reqwest::Client::new();
+    let code = rpc_call(&client, rpc_url, "eth_getCode", json!([address, "latest"])).await?;
+    let code = hex::decode(code.as_str().unwrap_or_default().trim_start_matches("0x"))
+        .map_err(|e| anyhow::anyhow!("Invalid eth_getCode result: {}", e))?;
+    if code.is_empty() {
+        return Err(anyhow::anyhow!("No contract code at {}", address));
+    }
+
+    let inferred = traverse_ethereum::infer_layout(address, &code);
+    info!(
+        "Inferred {} storage entries for {} from {} bytes of code",
+        inferred.layout.storage.len(),
+        address,
+        code.len()
+    );
+    write_output(&serde_json::to_string_pretty(&inferred.to_json())?, output)
+}
+
+#[cfg(not(feature = "ethereum"))]
+pub async fn cmd_ethereum_infer_layout(_address: &str, _rpc_url: &str, _output: Option<&Path>) -> Result<()> {
+    Err(anyhow::anyhow!("Ethereum support not enabled. Build with --features ethereum"))
+}
+
+pub(crate) fn validate_layout(layout: &LayoutInfo) -> Result<()> {
     let mut validation_errors = Vec::new();
     
-    // Check for storage slot conflicts
-    let mut used_slots = std::collections::HashSet::new();
-    for entry in &layout.storage {
-        let slot = &entry.slot;
-        if used_slots.contains(slot) {
-            validation_errors.push(format!("Storage slot conflict: slot '{}' is used by multiple entries", slot));
-        }
-        used_slots.insert(slot.clone());
+    // Check for storage slot conflicts; packed fields share a slot but not bytes
+    for collision in LayoutConflicts::check(&[layout]).collisions {
+        validation_errors.push(format!(
+            "Storage slot conflict: slot '{}' is used by both '{}' and '{}'",
+            collision.second.slot, collision.first.label, collision.second.label
+        ));
     }
     
     // Check for type consistency
@@ -516,27 +717,16 @@ pub fn cmd_ethereum_generate_queries(
 #[cfg(feature = "ethereum")]
 pub async fn cmd_ethereum_resolve_query(
     query: &str,
-    layout_file: &Path,
+    layout: LayoutSource<'_>,
     format: &OutputFormat,
+    template: Option<&Path>,
     output: Option<&Path>,
     contract_address: Option<&str>,
     rpc: Option<&str>,
 ) -> Result<()> {
     info!("Resolving storage query: {}", query);
 
-    // Check if the file exists before attempting to read it
-    if !layout_file.exists() {
-        return Err(anyhow::anyhow!(
-            "Layout file does not exist: {}",
-            layout_file.display()
-        ));
-    }
-
-    // Load layout
-    let layout_content = std::fs::read_to_string(layout_file)
-        .map_err(|e| anyhow::anyhow!("Failed to read layout file '{}': {}", layout_file.display(), e))?;
-    let layout: LayoutInfo = serde_json::from_str(&layout_content)
-        .map_err(|e| anyhow::anyhow!("Failed to parse layout file '{}': {}", layout_file.display(), e))?;
+    let layout = layout.load()?;
 
     // Create resolver
     let resolver = EthereumKeyResolver;
@@ -565,6 +755,11 @@ pub async fn cmd_ethereum_resolve_query(
         }
     }
 
+    if let Some(template) = template {
+        write_output(&template::render(template, &result_data)?, output)?;
+        return Ok(());
+    }
+
     let output_str = match format {
         OutputFormat::Traverse => serde_json::to_string_pretty(&resolved)?,
         OutputFormat::CoprocessorJson => {
@@ -579,14 +774,9 @@ pub async fn cmd_ethereum_resolve_query(
             });
             toml::to_string_pretty(&simplified)?
         }
-        OutputFormat::Binary => {
-            let binary_data = bincode::serialize(&resolved)?;
-            format!("Binary query result: {} bytes\nBase64: {}", binary_data.len(), STANDARD.encode(&binary_data))
-        }
-        OutputFormat::Base64 => {
-            let binary_data = bincode::serialize(&resolved)?;
-            STANDARD.encode(&binary_data)
-        }
+        OutputFormat::Binary => return Ok(artifact::write(&resolved, output)?),
+        OutputFormat::Base64 => artifact::to_base64(&resolved)?,
+        OutputFormat::Ndjson => serde_json::to_string(&result_data)?,
     };
 
     write_output(&output_str, output)?;
@@ -596,84 +786,1949 @@ pub async fn cmd_ethereum_resolve_query(
 #[cfg(not(feature = "ethereum"))]
 pub async fn cmd_ethereum_resolve_query(
     _query: &str,
-    _layout_file: &Path,
+    _layout: LayoutSource<'_>,
     _format: &OutputFormat,
+    _template: Option<&Path>,
     _output: Option<&Path>,
     _contract_address: Option<&str>,
     _rpc: Option<&str>,
 ) -> Result<()> {
-    Err(anyhow::anyhow!("Ethereum support not enabled. Build with --features ethereum"))
+    Err(anyhow::anyhow!("Ethereum support not enabled. Build with --features ethereum"))
+}
+
+/// Explain how a query's storage key is derived
+///
+/// Walks the resolver's derivation trace: the entry's base slot, each padded
+/// key, every keccak preimage with its digest and any index addition, then
+/// the final key with its field location and zero semantics. With `json` the
+/// trace is written in `format` instead of as text.
+#[cfg(feature = "ethereum")]
+pub fn cmd_ethereum_explain(
+    query: &str,
+    layout: LayoutSource<'_>,
+    json: bool,
+    format: &OutputFormat,
+    output: Option<&Path>,
+) -> Result<()> {
+    use traverse_core::TracingKeyResolver;
+
+    let layout = layout.load()?;
+    let trace = EthereumKeyResolver.resolve_traced(&layout, query)?;
+    if json {
+        write_result(&trace_json(&trace), format, None, output)?;
+    } else {
+        write_output(&render_trace(&trace), output)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "ethereum"))]
+pub fn cmd_ethereum_explain(
+    _query: &str,
+    _layout: LayoutSource<'_>,
+    _json: bool,
+    _format: &OutputFormat,
+    _output: Option<&Path>,
+) -> Result<()> {
+    Err(anyhow::anyhow!("Ethereum support not enabled. Build with --features ethereum"))
+}
+
+/// Sources `codegen` can generate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CodegenTarget {
+    /// Storage key computation for the layout's fields
+    Query,
+    /// Witness verification for the layout's fields
+    Verifier,
+    /// Query and verification in one library
+    Combined,
+    /// The layout as `const` data, for embedding in no_std controllers
+    ConstLayout,
+    /// A no_std controller with the layout embedded as `const` data
+    ConstController,
+    /// TypeScript types for the coprocessor request JSON (takes no layout)
+    Typescript,
+}
+
+/// Generate Rust sources for a layout, or TypeScript types for the request JSON
+///
+/// With `queries`, the query and verifier targets only contain the fields,
+/// hash paths and value types those queries need. `zod` adds zod validators
+/// to the TypeScript target.
+#[cfg(all(feature = "ethereum", feature = "codegen"))]
+pub fn cmd_ethereum_codegen(
+    layout: Option<LayoutSource<'_>>,
+    target: CodegenTarget,
+    queries: &[String],
+    zod: bool,
+    output: Option<&Path>,
+) -> Result<()> {
+    use traverse_valence::{minimal_codegen, typescript_codegen};
+
+    if zod && target != CodegenTarget::Typescript {
+        return Err(anyhow::anyhow!("--zod only applies to the typescript target"));
+    }
+    if target == CodegenTarget::Typescript {
+        if layout.is_some() || !queries.is_empty() {
+            return Err(anyhow::anyhow!("The typescript target takes no layout or queries"));
+        }
+        return write_output(&typescript_codegen::generate_typescript_types(zod), output);
+    }
+
+    let layout = layout.ok_or_else(|| anyhow::anyhow!("--layout or --preset is required for {:?}", target))?.load()?;
+    let queries: Vec<&str> = queries.iter().map(String::as_str).collect();
+    let code = match (target, queries.is_empty()) {
+        (CodegenTarget::Query, true) => minimal_codegen::generate_minimal_query_code(&layout),
+        (CodegenTarget::Query, false) => minimal_codegen::generate_minimal_query_code_for_queries(&layout, &queries)?,
+        (CodegenTarget::Verifier, true) => minimal_codegen::generate_minimal_verifier_code(&layout),
+        (CodegenTarget::Verifier, false) => {
+            minimal_codegen::generate_minimal_verifier_code_for_queries(&layout, &queries)?
+        }
+        (CodegenTarget::Combined, true) => minimal_codegen::generate_minimal_combined_code(&layout),
+        (CodegenTarget::ConstLayout, true) => minimal_codegen::generate_const_layout_code(&layout)?,
+        (CodegenTarget::ConstController, true) => minimal_codegen::generate_const_controller_code(&layout)?,
+        (target, _) => {
+            return Err(anyhow::anyhow!(
+                "--query only applies to the query and verifier targets, not {:?}",
+                target
+            ))
+        }
+    };
+    write_output(&code, output)
+}
+
+#[cfg(not(all(feature = "ethereum", feature = "codegen")))]
+pub fn cmd_ethereum_codegen(
+    _layout: Option<LayoutSource<'_>>,
+    _target: CodegenTarget,
+    _queries: &[String],
+    _zod: bool,
+    _output: Option<&Path>,
+) -> Result<()> {
+    Err(anyhow::anyhow!("Code generation not enabled. Build with --features codegen"))
+}
+
+#[cfg(feature = "ethereum")]
+fn trace_json(trace: &traverse_core::DerivationTrace) -> Value {
+    use traverse_core::DerivationStep;
+
+    let word = |bytes: &[u8]| format!("0x{}", hex::encode(bytes));
+    let steps: Vec<Value> = trace
+        .steps
+        .iter()
+        .map(|step| match step {
+            DerivationStep::BaseSlot { field, slot } => {
+                json!({ "step": "base_slot", "field": field, "slot": word(slot) })
+            }
+            DerivationStep::PadKey { key, padded } => {
+                json!({ "step": "pad_key", "key": word(key), "padded": word(padded) })
+            }
+            DerivationStep::Keccak { preimage, digest } => {
+                json!({ "step": "keccak256", "preimage": word(preimage), "digest": word(digest) })
+            }
+            DerivationStep::AddIndex { base, index, result } => {
+                json!({ "step": "add_index", "base": word(base), "index": index, "result": word(result) })
+            }
+        })
+        .collect();
+    json!({
+        "query": trace.query,
+        "field": trace.field,
+        "type": trace.type_name,
+        "steps": steps,
+        "storage_key": word(&key_to_bytes(&trace.path.key)),
+        "offset": trace.path.offset.unwrap_or(0),
+        "field_size": trace.path.field_size,
+        "zero_semantics": trace.path.zero_semantics,
+        "layout_commitment": hex::encode(trace.path.layout_commitment)
+    })
+}
+
+/// Text walk-through of a derivation trace for `explain`
+#[cfg(feature = "ethereum")]
+fn render_trace(trace: &traverse_core::DerivationTrace) -> String {
+    use std::fmt::Write;
+    use traverse_core::DerivationStep;
+
+    let word = |bytes: &[u8]| format!("0x{}", hex::encode(bytes));
+    let mut out = String::new();
+    let _ = writeln!(out, "Query: {}", trace.query);
+    let _ = writeln!(out, "Field: {} ({})", trace.field, trace.type_name);
+    for (number, step) in trace.steps.iter().enumerate() {
+        let _ = writeln!(out);
+        match step {
+            DerivationStep::BaseSlot { field, slot } => {
+                let _ = writeln!(out, "{}. Base slot of {}", number + 1, field);
+                let _ = writeln!(out, "   {}", word(slot));
+            }
+            DerivationStep::PadKey { key, padded } => {
+                let _ = writeln!(out, "{}. Left-pad key {} to 32 bytes", number + 1, word(key));
+                let _ = writeln!(out, "   {}", word(padded));
+            }
+            DerivationStep::Keccak { preimage, digest } => {
+                let _ = writeln!(out, "{}. keccak256 of", number + 1);
+                for chunk in preimage.chunks(32) {
+                    let _ = writeln!(out, "     {}", word(chunk));
+                }
+                let _ = writeln!(out, "   = {}", word(digest));
+            }
+            DerivationStep::AddIndex { base, index, result } => {
+                let _ = writeln!(out, "{}. Add slot offset {} to {}", number + 1, index, word(base));
+                let _ = writeln!(out, "   = {}", word(result));
+            }
+        }
+    }
+
+    let path = &trace.path;
+    let _ = writeln!(out);
+    let _ = writeln!(out, "Storage key:    {}", word(&key_to_bytes(&path.key)));
+    let _ = writeln!(out, "Offset:         {} bytes", path.offset.unwrap_or(0));
+    let size = path.field_size.map_or("unknown".to_string(), |size| format!("{} bytes", size));
+    let _ = writeln!(out, "Size:           {}", size);
+    let _ = write!(out, "Zero semantics: {:?}", path.zero_semantics);
+    out
+}
+
+/// Resolve one query of a batch into a `resolved` or `failed` record
+#[cfg(feature = "ethereum")]
+pub(crate) fn batch_resolve_entry(layout: &LayoutInfo, query: &str) -> Value {
+    match EthereumKeyResolver.resolve(layout, query) {
+        Ok(resolved) => json!({
+            "query": query,
+            "status": "resolved",
+            "storage_key": hex::encode(key_to_bytes(&resolved.key)),
+            "layout_commitment": hex::encode(resolved.layout_commitment),
+            "field_size": resolved.field_size,
+            "offset": resolved.offset
+        }),
+        Err(e) => {
+            warn!("Failed to resolve query '{}': {}", query, e);
+            json!({
+                "query": query,
+                "status": "failed",
+                "error": e.to_string()
+            })
+        }
+    }
+}
+
+/// Resolve a batch of queries into the batch manifest document
+#[cfg(feature = "ethereum")]
+pub(crate) fn batch_resolve_summary(layout: &LayoutInfo, queries: &[String]) -> Value {
+    let results: Vec<Value> = queries.iter().map(|q| batch_resolve_entry(layout, q)).collect();
+    let failed = results.iter().filter(|r| r["status"] == "failed").count();
+    json!({
+        "layout_commitment": hex::encode(layout.commitment()),
+        "total_queries": results.len(),
+        "resolved": results.len() - failed,
+        "failed": failed,
+        "results": results
+    })
+}
+
+/// Resolve a batch of storage queries against one layout
+///
+/// With `OutputFormat::Ndjson` each record is streamed as soon as its query is
+/// resolved; other formats write a single document once the batch completes.
+/// Failed queries produce a record with an `error` field instead of aborting.
+#[cfg(feature = "ethereum")]
+pub fn cmd_ethereum_batch_resolve(
+    layout_file: &Path,
+    queries: &[String],
+    format: &OutputFormat,
+    template: Option<&Path>,
+    output: Option<&Path>,
+) -> Result<()> {
+    info!("Resolving {} storage queries", queries.len());
+
+    if !layout_file.exists() {
+        return Err(anyhow::anyhow!(
+            "Layout file does not exist: {}",
+            layout_file.display()
+        ));
+    }
+
+    let layout_content = std::fs::read_to_string(layout_file)
+        .map_err(|e| anyhow::anyhow!("Failed to read layout file '{}': {}", layout_file.display(), e))?;
+    let layout: LayoutInfo = serde_json::from_str(&layout_content)
+        .map_err(|e| anyhow::anyhow!("Failed to parse layout file '{}': {}", layout_file.display(), e))?;
+
+    if let (OutputFormat::Ndjson, None) = (format, template) {
+        let mut writer = NdjsonWriter::create(output)?;
+        for query in queries {
+            writer.write_record(&batch_resolve_entry(&layout, query))?;
+        }
+        return Ok(());
+    }
+
+    let summary = batch_resolve_summary(&layout, queries);
+
+    write_result(&summary, format, template, output)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "ethereum"))]
+pub fn cmd_ethereum_batch_resolve(
+    _layout_file: &Path,
+    _queries: &[String],
+    _format: &OutputFormat,
+    _template: Option<&Path>,
+    _output: Option<&Path>,
+) -> Result<()> {
+    Err(anyhow::anyhow!("Ethereum support not enabled. Build with --features ethereum"))
+}
+
+/// Resolve a CSV batch of `contract,query,zero_means` rows
+///
+/// Each row's contract is matched against the `contract_name` of the given
+/// layouts. Rows that cannot be read, name an unknown contract or fail to
+/// resolve become `failed` records carrying their line number; the rest of the
+/// file is still resolved. Resolved records report the row's zero semantics
+/// override, or the field's declared semantics when the row leaves it empty.
+#[cfg(feature = "ethereum")]
+pub fn cmd_ethereum_batch_resolve_csv(
+    layout_files: &[&Path],
+    csv_file: &Path,
+    format: &OutputFormat,
+    template: Option<&Path>,
+    output: Option<&Path>,
+) -> Result<()> {
+    use std::collections::BTreeMap;
+    use traverse_cli_core::batch::{self, BatchRow, BatchRowError};
+
+    let mut layouts: BTreeMap<String, LayoutInfo> = BTreeMap::new();
+    for path in layout_files {
+        let layout = load_layout(path)
+            .map_err(|e| anyhow::anyhow!("Failed to load layout '{}': {}", path.display(), e))?;
+        if layouts.contains_key(&layout.contract_name) {
+            return Err(anyhow::anyhow!(
+                "Layout '{}' has the same contract name as another layout: {}",
+                path.display(),
+                layout.contract_name
+            ));
+        }
+        layouts.insert(layout.contract_name.clone(), layout);
+    }
+
+    let content = std::fs::read_to_string(csv_file)
+        .map_err(|e| anyhow::anyhow!("Failed to read query file '{}': {}", csv_file.display(), e))?;
+    let rows = batch::parse_csv(&content);
+    info!("Resolving {} CSV rows against {} layouts", rows.len(), layouts.len());
+
+    let resolve_row = |row: Result<BatchRow, BatchRowError>| -> Value {
+        let row = match row {
+            Ok(row) => row,
+            Err(e) => {
+                warn!("Skipping line {}: {}", e.line, e.error);
+                return json!({ "line": e.line, "query": e.query, "status": "failed", "error": e.error });
+            }
+        };
+
+        let mut record = match layouts.get(&row.contract) {
+            Some(layout) => {
+                let mut record = batch_resolve_entry(layout, &row.query);
+                if record["status"] == "resolved" {
+                    let field = row.query.split(['[', '.']).next().unwrap_or_default();
+                    let semantics = row.zero_means.or_else(|| {
+                        layout.storage.iter().find(|e| e.label == field).map(|e| e.zero_semantics)
+                    });
+                    record["zero_semantics"] = json!(semantics);
+                }
+                record
+            }
+            None => json!({
+                "query": row.query,
+                "status": "failed",
+                "error": format!(
+                    "Unknown contract '{}'. Loaded layouts: {}",
+                    row.contract,
+                    layouts.keys().cloned().collect::<Vec<_>>().join(", ")
+                )
+            }),
+        };
+        record["line"] = json!(row.line);
+        record["contract"] = json!(row.contract);
+        record
+    };
+
+    if let (OutputFormat::Ndjson, None) = (format, template) {
+        let mut writer = NdjsonWriter::create(output)?;
+        for row in rows {
+            writer.write_record(&resolve_row(row))?;
+        }
+        return Ok(());
+    }
+
+    let results: Vec<Value> = rows.into_iter().map(resolve_row).collect();
+    let failed = results.iter().filter(|r| r["status"] == "failed").count();
+    let manifest = json!({
+        "layouts": layouts
+            .iter()
+            .map(|(name, layout)| (name.clone(), json!(hex::encode(layout.commitment()))))
+            .collect::<serde_json::Map<_, _>>(),
+        "total_queries": results.len(),
+        "resolved": results.len() - failed,
+        "failed": failed,
+        "results": results
+    });
+
+    write_result(&manifest, format, template, output)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "ethereum"))]
+pub fn cmd_ethereum_batch_resolve_csv(
+    _layout_files: &[&Path],
+    _csv_file: &Path,
+    _format: &OutputFormat,
+    _template: Option<&Path>,
+    _output: Option<&Path>,
+) -> Result<()> {
+    Err(anyhow::anyhow!("Ethereum support not enabled. Build with --features ethereum"))
+}
+
+/// Compare two compiled layouts field by field
+///
+/// The traverse format prints a readable report; other formats write the diff
+/// document. Returns `true` when the diff should fail a CI gate: any breaking
+/// change, or any change at all when `strict` is set.
+pub fn cmd_ethereum_diff_layouts(
+    old_file: &Path,
+    new_file: &Path,
+    format: &OutputFormat,
+    output: Option<&Path>,
+    strict: bool,
+) -> Result<bool> {
+    let old = load_layout(old_file)
+        .map_err(|e| anyhow::anyhow!("Failed to load layout '{}': {}", old_file.display(), e))?;
+    let new = load_layout(new_file)
+        .map_err(|e| anyhow::anyhow!("Failed to load layout '{}': {}", new_file.display(), e))?;
+
+    let diff = LayoutDiff::between(&old, &new);
+    let breaking = diff.changes.iter().filter(|c| c.is_breaking()).count();
+
+    if let OutputFormat::Traverse = format {
+        let mut report = format!(
+            "old commitment: {}\nnew commitment: {}{}\n",
+            hex::encode(diff.old_commitment),
+            hex::encode(diff.new_commitment),
+            if diff.commitment_changed() { " (changed)" } else { "" }
+        );
+        for change in &diff.changes {
+            let marker = if change.is_breaking() { "!" } else { " " };
+            report.push_str(&format!("{} {}\n", marker, change));
+        }
+        report.push_str(&format!("{} changes, {} breaking", diff.changes.len(), breaking));
+        write_output(&report, output)?;
+    } else {
+        let document = json!({
+            "old_commitment": hex::encode(diff.old_commitment),
+            "new_commitment": hex::encode(diff.new_commitment),
+            "commitment_changed": diff.commitment_changed(),
+            "breaking": diff.is_breaking(),
+            "changes": diff.changes
+        });
+        write_result(&document, format, None, output)?;
+    }
+
+    Ok(diff.is_breaking() || (strict && !diff.is_empty()))
+}
+
+/// Check layouts sharing storage for overlapping fields and reordered bases
+///
+/// Layouts are given in upgrade order, or in any order for contracts that
+/// run against one storage, such as a proxy and its implementation. Returns
+/// `true` when any conflict was found.
+pub fn cmd_ethereum_check_layouts(files: &[&Path], format: &OutputFormat, output: Option<&Path>) -> Result<bool> {
+    let layouts = files
+        .iter()
+        .map(|file| {
+            load_layout(file).map_err(|e| anyhow::anyhow!("Failed to load layout '{}': {}", file.display(), e))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let conflicts = LayoutConflicts::check(&layouts.iter().collect::<Vec<_>>());
+
+    if let OutputFormat::Traverse = format {
+        let mut report = String::new();
+        for collision in &conflicts.collisions {
+            report.push_str(&format!("! {}\n", collision));
+        }
+        for reorder in &conflicts.reorders {
+            report.push_str(&format!("! {}\n", reorder));
+        }
+        report.push_str(&format!(
+            "{} layouts, {} collisions, {} reorders",
+            layouts.len(),
+            conflicts.collisions.len(),
+            conflicts.reorders.len()
+        ));
+        write_output(&report, output)?;
+    } else {
+        let document = json!({
+            "layouts": layouts.iter().map(|l| l.contract_name.as_str()).collect::<Vec<_>>(),
+            "collisions": conflicts.collisions,
+            "reorders": conflicts.reorders
+        });
+        write_result(&document, format, None, output)?;
+    }
+
+    Ok(!conflicts.is_empty())
+}
+
+/// Parse a hex word or quantity into 32 big-endian bytes
+#[cfg(feature = "ethereum")]
+fn parse_hex_word(value: &str, what: &str) -> Result<[u8; 32]> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    let digits = if digits.len() % 2 == 1 { format!("0{}", digits) } else { digits.to_string() };
+    let bytes = hex::decode(&digits).map_err(|e| anyhow::anyhow!("Invalid {} '{}': {}", what, value, e))?;
+    if bytes.len() > 32 {
+        return Err(anyhow::anyhow!("Invalid {} '{}': longer than 32 bytes", what, value));
+    }
+    let mut word = [0u8; 32];
+    word[32 - bytes.len()..].copy_from_slice(&bytes);
+    Ok(word)
+}
+
+/// Parse a hex address into its 20 bytes
+#[cfg(feature = "ethereum")]
+fn parse_address_bytes(address: &str) -> Result<[u8; 20]> {
+    hex::decode(address.strip_prefix("0x").unwrap_or(address))?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Address must be 20 bytes"))
+}
+
+#[cfg(feature = "ethereum")]
+fn parse_proof_nodes(nodes: &Value, what: &str) -> Result<Vec<Vec<u8>>> {
+    nodes
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("Missing {} array", what))?
+        .iter()
+        .map(|node| {
+            let node = node.as_str().ok_or_else(|| anyhow::anyhow!("{} nodes must be hex strings", what))?;
+            hex::decode(node.strip_prefix("0x").unwrap_or(node))
+                .map_err(|e| anyhow::anyhow!("Invalid {} node: {}", what, e))
+        })
+        .collect()
+}
+
+/// Verify an `eth_getProof` response offline against a state root
+///
+/// Checks the account proof against `state_root`, then every storage proof
+/// against the proven storage root, and labels storage keys with the layout
+/// fields (or `queries`) they resolve from. Every query must be covered by a
+/// storage proof. Returns whether the whole artifact verified.
+#[cfg(feature = "ethereum")]
+pub fn cmd_ethereum_verify_proof(
+    proof_file: &Path,
+    layout_file: &Path,
+    state_root: &str,
+    queries: &[String],
+    format: &OutputFormat,
+    output: Option<&Path>,
+) -> Result<bool> {
+    use std::collections::HashMap;
+    use traverse_ethereum::mpt::{verify_account_proof, verify_storage_proof};
+
+    let layout = load_layout(layout_file)
+        .map_err(|e| anyhow::anyhow!("Failed to load layout '{}': {}", layout_file.display(), e))?;
+    let document: Value = serde_json::from_str(&std::fs::read_to_string(proof_file).map_err(|e| {
+        anyhow::anyhow!("Failed to read proof file '{}': {}", proof_file.display(), e)
+    })?)?;
+    // Accept either the bare result or the full JSON-RPC response
+    let proof = document.get("result").unwrap_or(&document);
+
+    let state_root = parse_hex_word(state_root, "state root")?;
+    let address_str = proof["address"].as_str().ok_or_else(|| anyhow::anyhow!("Missing address"))?;
+    let address = parse_address_bytes(address_str)?;
+    let storage_hash = parse_hex_word(
+        proof["storageHash"].as_str().ok_or_else(|| anyhow::anyhow!("Missing storageHash"))?,
+        "storageHash",
+    )?;
+
+    // Storage keys the layout (and any explicit queries) resolve to
+    let mut fields: HashMap<[u8; 32], String> = HashMap::new();
+    for entry in &layout.storage {
+        if let Ok(resolved) = EthereumKeyResolver.resolve(&layout, &entry.label) {
+            if let Key::Fixed(key) = resolved.key {
+                fields.insert(key, entry.label.clone());
+            }
+        }
+    }
+    let mut query_keys = Vec::with_capacity(queries.len());
+    for query in queries {
+        let resolved = EthereumKeyResolver.resolve(&layout, query)?;
+        let key: [u8; 32] = key_to_bytes(&resolved.key)
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("Query '{}' did not resolve to a 32-byte key", query))?;
+        fields.insert(key, query.clone());
+        query_keys.push((query, key));
+    }
+
+    let mut valid = true;
+    let account = match verify_account_proof(&state_root, &address, &parse_proof_nodes(&proof["accountProof"], "accountProof")?) {
+        Ok(Some(account)) => {
+            let matches = account.storage_root == storage_hash;
+            valid &= matches;
+            let mut report = json!({
+                "verified": matches,
+                "nonce": account.nonce,
+                "balance": format!("0x{}", hex::encode(account.balance)),
+                "storage_root": format!("0x{}", hex::encode(account.storage_root)),
+                "code_hash": format!("0x{}", hex::encode(account.code_hash))
+            });
+            if !matches {
+                report["error"] = json!("storageHash does not match the proven account storage root");
+            }
+            report
+        }
+        Ok(None) => {
+            valid = false;
+            json!({ "verified": false, "error": "account is not present under the state root" })
+        }
+        Err(e) => {
+            valid = false;
+            json!({ "verified": false, "error": e.to_string() })
+        }
+    };
+
+    let mut storage = Vec::new();
+    let mut proven_keys = Vec::new();
+    for item in proof["storageProof"].as_array().cloned().unwrap_or_default() {
+        let key = parse_hex_word(item["key"].as_str().unwrap_or_default(), "storage key")?;
+        let claimed = parse_hex_word(item["value"].as_str().unwrap_or("0x0"), "storage value")?;
+        let nodes = parse_proof_nodes(&item["proof"], "storage proof")?;
+
+        let mut entry = json!({
+            "key": format!("0x{}", hex::encode(key)),
+            "value": format!("0x{}", hex::encode(claimed)),
+            "field": fields.get(&key),
+        });
+        match verify_storage_proof(&storage_hash, &key, &nodes) {
+            Ok(proven) if proven == claimed => entry["verified"] = json!(true),
+            Ok(proven) => {
+                valid = false;
+                entry["verified"] = json!(false);
+                entry["error"] = json!(format!("proof shows value 0x{}", hex::encode(proven)));
+            }
+            Err(e) => {
+                valid = false;
+                entry["verified"] = json!(false);
+                entry["error"] = json!(e.to_string());
+            }
+        }
+        proven_keys.push(key);
+        storage.push(entry);
+    }
+
+    let missing: Vec<&String> = query_keys
+        .iter()
+        .filter(|(_, key)| !proven_keys.contains(key))
+        .map(|(query, _)| *query)
+        .collect();
+    valid &= missing.is_empty();
+
+    let report = json!({
+        "valid": valid,
+        "address": address_str,
+        "state_root": format!("0x{}", hex::encode(state_root)),
+        "layout_commitment": hex::encode(layout.commitment()),
+        "account": account,
+        "storage": storage,
+        "missing_queries": missing
+    });
+
+    write_result(&report, format, None, output)?;
+    Ok(valid)
+}
+
+#[cfg(not(feature = "ethereum"))]
+pub fn cmd_ethereum_verify_proof(
+    _proof_file: &Path,
+    _layout_file: &Path,
+    _state_root: &str,
+    _queries: &[String],
+    _format: &OutputFormat,
+    _output: Option<&Path>,
+) -> Result<bool> {
+    Err(anyhow::anyhow!("Ethereum support not enabled. Build with --features ethereum"))
+}
+
+/// Normalize a `--block` argument into an RPC block parameter
+///
+/// Accepts the named tags (`latest`, `safe`, `finalized`, `pending`,
+/// `earliest`), decimal numbers and `0x` quantities.
+pub(crate) fn parse_block_param(block: &str) -> Result<String> {
+    match block {
+        "latest" | "safe" | "finalized" | "pending" | "earliest" => Ok(block.to_string()),
+        _ => {
+            let number = match block.strip_prefix("0x") {
+                Some(hex) => u64::from_str_radix(hex, 16),
+                None => block.parse(),
+            }
+            .map_err(|_| anyhow::anyhow!("Invalid block '{}': expected a number or a block tag", block))?;
+            Ok(format!("0x{:x}", number))
+        }
+    }
+}
+
+/// Fallback endpoints and retry policy for this run's RPC requests
+#[cfg(feature = "ethereum")]
+static RPC_FAILOVER: std::sync::OnceLock<(Vec<String>, RetryPolicy)> = std::sync::OnceLock::new();
+
+/// Fail over to the profile's fallback endpoints and retry as its `retry` section says
+///
+/// Like the cache, this is process-wide: every RPC request of the run goes
+/// through [`rpc_transport`], whichever endpoint it starts at. Returns false
+/// if failover was already installed.
+#[cfg(feature = "ethereum")]
+pub fn install_rpc_failover(profile: &Profile) -> bool {
+    let settings = profile.retry("ethereum");
+    let defaults = RetryPolicy::default();
+    let retry = RetryPolicy {
+        max_retries: settings.max_retries.unwrap_or(defaults.max_retries),
+        initial_backoff: settings.initial_backoff_ms.map_or(defaults.initial_backoff, std::time::Duration::from_millis),
+        max_backoff: settings.max_backoff_ms.map_or(defaults.max_backoff, std::time::Duration::from_millis),
+    };
+    RPC_FAILOVER.set((profile.rpc_fallbacks("ethereum").to_vec(), retry)).is_ok()
+}
+
+#[cfg(not(feature = "ethereum"))]
+pub fn install_rpc_failover(_profile: &Profile) -> bool {
+    false
+}
+
+/// Transport starting at `rpc_url` with the installed fallbacks and retries
+#[cfg(feature = "ethereum")]
+fn rpc_transport(rpc_url: &str) -> RpcTransport {
+    match RPC_FAILOVER.get() {
+        Some((fallbacks, retry)) => RpcTransport::new(rpc_url).with_fallbacks(fallbacks).with_retry(*retry),
+        None => RpcTransport::new(rpc_url),
+    }
+}
+
+/// Proof fetcher on `rpc_url` with the installed fallbacks and retries
+#[cfg(feature = "ethereum")]
+fn proof_fetcher(rpc_url: &str) -> traverse_ethereum::EthereumProofFetcher {
+    let fetcher = traverse_ethereum::EthereumProofFetcher::new(rpc_url, "");
+    match RPC_FAILOVER.get() {
+        Some((fallbacks, retry)) => fetcher.with_fallbacks(fallbacks.clone()).with_retry(*retry),
+        None => fetcher,
+    }
+}
+
+#[cfg(feature = "ethereum")]
+async fn rpc_call(client: &reqwest::Client, rpc_url: &str, method: &str, params: Value) -> Result<Value> {
+    Ok(rpc_transport(rpc_url).call(client, method, params).await?)
+}
+
+/// Whether `params` name a block by tag, so the answer moves with the chain
+#[cfg(feature = "ethereum")]
+fn names_block_tag(params: &Value) -> bool {
+    params
+        .as_array()
+        .is_some_and(|params| params.iter().any(|param| param.as_str().and_then(BlockTag::parse).is_some()))
+}
+
+/// [`rpc_call`] through the disk cache, keyed by endpoint, method and params
+///
+/// Requests naming a block by tag, such as `latest`, always go to the
+/// endpoint; only answers pinned to a block number are cached.
+#[cfg(feature = "ethereum")]
+async fn cached_rpc_call(
+    kind: &str,
+    client: &reqwest::Client,
+    rpc_url: &str,
+    method: &str,
+    params: Value,
+) -> Result<Value> {
+    if names_block_tag(&params) {
+        return rpc_call(client, rpc_url, method, params).await;
+    }
+    let key = json!({ "rpc": rpc_url, "method": method, "params": params.clone() });
+    cache::cached(kind, key, || rpc_call(client, rpc_url, method, params)).await
+}
+
+/// Fetch a block header, returning its number alongside the header
+#[cfg(feature = "ethereum")]
+async fn fetch_block_header(client: &reqwest::Client, rpc_url: &str, block_param: &str) -> Result<(u64, Value)> {
+    let header = rpc_call(client, rpc_url, "eth_getBlockByNumber", json!([block_param, false])).await?;
+    let number = header["number"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Block header has no number"))?;
+    let number = u64::from_str_radix(number.trim_start_matches("0x"), 16)
+        .map_err(|e| anyhow::anyhow!("Invalid block number '{}': {}", number, e))?;
+    Ok((number, header))
+}
+
+/// [`fetch_block_header`], through the disk cache when `block_param` is a number
+#[cfg(feature = "ethereum")]
+async fn cached_block_header(client: &reqwest::Client, rpc_url: &str, block_param: &str) -> Result<(u64, Value)> {
+    if BlockTag::parse(block_param).is_some() {
+        return fetch_block_header(client, rpc_url, block_param).await;
+    }
+    let key = json!({ "rpc": rpc_url, "method": "eth_getBlockByNumber", "params": [block_param] });
+    cache::cached("rpc", key, || fetch_block_header(client, rpc_url, block_param)).await
+}
+
+/// How the header a block parameter names is reached on one chain
+#[cfg(feature = "ethereum")]
+enum HeaderFetch {
+    /// Read the block at a tag the chain serves
+    Tag(BlockTag),
+    /// Read the head, then the block this many below it
+    BehindHead(u64),
+    /// Read the head to check the block is within the proof window, then the block
+    Number(u64),
+}
+
+/// Choose how to reach the header `block_param` names, applying the chain's quirks
+#[cfg(feature = "ethereum")]
+fn header_fetch(adapter: &EvmChainAdapter, block_param: &str) -> Result<HeaderFetch> {
+    match BlockTag::parse(block_param) {
+        Some(tag) => match adapter.select_block(tag)? {
+            BlockSelection::Tag(selected) => {
+                if selected != tag {
+                    info!("{} has no '{}' tag; reading '{}'", adapter.profile.name, tag.as_str(), selected.as_str());
+                }
+                Ok(HeaderFetch::Tag(selected))
+            }
+            BlockSelection::BehindHead(depth) => Ok(HeaderFetch::BehindHead(depth)),
+        },
+        None => Ok(HeaderFetch::Number(u64::from_str_radix(block_param.trim_start_matches("0x"), 16)?)),
+    }
+}
+
+/// Fetch the header a block parameter names, applying the chain's quirks
+///
+/// Tags the chain lacks are substituted as its adapter selects, and a
+/// numbered block is checked against how far back full nodes keep state.
+#[cfg(feature = "ethereum")]
+async fn fetch_block_with_quirks(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    adapter: &EvmChainAdapter,
+    block_param: &str,
+) -> Result<(u64, Value)> {
+    let block_param = match header_fetch(adapter, block_param)? {
+        HeaderFetch::Tag(tag) => tag.as_str().to_string(),
+        HeaderFetch::BehindHead(depth) => {
+            let (head, _) = fetch_block_header(client, rpc_url, "latest").await?;
+            format!("0x{:x}", head.saturating_sub(depth))
+        }
+        HeaderFetch::Number(number) => {
+            let (head, _) = fetch_block_header(client, rpc_url, "latest").await?;
+            adapter.check_proof_depth(head, number)?;
+            format!("0x{:x}", number)
+        }
+    };
+    cached_block_header(client, rpc_url, &block_param).await
+}
+
+/// Plan the requests [`fetch_block_with_quirks`] makes, returning the header's call
+#[cfg(feature = "ethereum")]
+fn plan_block_with_quirks(
+    plan: &mut RpcPlan,
+    rpc_url: &str,
+    adapter: &EvmChainAdapter,
+    block_param: &str,
+) -> Result<usize> {
+    let block = match header_fetch(adapter, block_param)? {
+        HeaderFetch::Tag(tag) => Value::from(tag.as_str()),
+        HeaderFetch::BehindHead(depth) => {
+            let head = plan.push(rpc_url, "eth_getBlockByNumber", json!(["latest", false]), "Chain head, to count confirmations from");
+            format!("{}-{}", RpcPlan::reference(head, "number"), depth).into()
+        }
+        HeaderFetch::Number(number) => {
+            plan.push(
+                rpc_url,
+                "eth_getBlockByNumber",
+                json!(["latest", false]),
+                "Chain head, to check the block is within the node's proof window",
+            );
+            format!("0x{:x}", number).into()
+        }
+    };
+    Ok(plan.push(rpc_url, "eth_getBlockByNumber", json!([block, false]), "Pin the block the proof is taken at"))
+}
+
+/// The quirk adapter a dry run plans with: the expected chain's, else a generic EVM chain's
+#[cfg(feature = "ethereum")]
+fn planned_chain_adapter() -> EvmChainAdapter {
+    let chain_id = chain_id::expected().and_then(|id| match id.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => id.parse().ok(),
+    });
+    match chain_id {
+        Some(chain_id) => EvmChainAdapter::for_chain_id(chain_id),
+        None => EvmChainAdapter::new(traverse_ethereum::QuirkProfile::generic(0)),
+    }
+}
+
+/// The quirk adapter for the chain an endpoint serves
+#[cfg(feature = "ethereum")]
+async fn fetch_chain_adapter(client: &reqwest::Client, rpc_url: &str) -> Result<EvmChainAdapter> {
+    let chain_id = cached_rpc_call("rpc", client, rpc_url, "eth_chainId", json!([])).await?;
+    let chain_id = chain_id
+        .as_str()
+        .and_then(|id| u64::from_str_radix(id.trim_start_matches("0x"), 16).ok())
+        .ok_or_else(|| anyhow::anyhow!("eth_chainId returned {}", chain_id))?;
+    Ok(EvmChainAdapter::for_chain_id(chain_id))
+}
+
+/// Chain ID an endpoint serves, as its `eth_chainId` quantity
+#[cfg(feature = "ethereum")]
+pub async fn fetch_chain_id(rpc_url: &str) -> Result<String> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .build()?;
+    let chain_id = rpc_call(&client, rpc_url, "eth_chainId", json!([])).await?;
+    chain_id
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow::anyhow!("eth_chainId returned {}", chain_id))
+}
+
+#[cfg(not(feature = "ethereum"))]
+pub async fn fetch_chain_id(_rpc_url: &str) -> Result<String> {
+    Err(anyhow::anyhow!("Ethereum support not enabled. Build with --features ethereum"))
+}
+
+/// Left-pad a resolved key to a 32-byte storage word
+fn storage_word(key: &Key) -> [u8; 32] {
+    let bytes = key_to_bytes(key);
+    let mut word = [0u8; 32];
+    let len = bytes.len().min(32);
+    word[32 - len..].copy_from_slice(&bytes[bytes.len() - len..]);
+    word
+}
+
+/// Fetch a storage proof bound to a block
+///
+/// The block (`latest` by default) is resolved to a concrete header first and
+/// the proof is fetched at that header's number, so the reported block hash and
+/// state root are the ones the proof verifies against. The query is resolved
+/// through `layout_file` when given, otherwise it must be a storage slot.
+///
+/// With `validate_semantics`, the declared zero semantics (`zero_means`, or
+/// the field's declaration in the layout) are checked against the slot's
+/// history up to that block and the result is included in the output. An
+/// Alchemy endpoint reports the exact writes; other nodes are sampled.
+#[cfg(feature = "ethereum")]
+#[allow(clippy::too_many_arguments)]
+pub async fn cmd_ethereum_generate_proof(
+    address: &str,
+    query: &str,
+    layout: Option<LayoutSource<'_>>,
+    rpc_url: &str,
+    block: Option<&str>,
+    zero_means: Option<ZeroSemantics>,
+    validate_semantics: bool,
+    dry_run: bool,
+    format: &OutputFormat,
+    template: Option<&Path>,
+    output: Option<&Path>,
+) -> Result<()> {
+    if dry_run && template.is_some() {
+        return Err(anyhow::anyhow!("--template renders proofs, not the plan --dry-run prints"));
+    }
+    let layout = layout.map(LayoutSource::load).transpose()?;
+    let result =
+        generate_storage_proof(address, query, layout.as_ref(), rpc_url, block, zero_means, validate_semantics, dry_run)
+            .await?;
+    write_result(&result, format, template, output)?;
+    Ok(())
+}
+
+/// The proof `generate-proof` writes, or with `dry_run` the plan of its requests
+///
+/// Shared by the CLI command and the HTTP API server.
+#[cfg(feature = "ethereum")]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn generate_storage_proof(
+    address: &str,
+    query: &str,
+    layout: Option<&LayoutInfo>,
+    rpc_url: &str,
+    block: Option<&str>,
+    zero_means: Option<ZeroSemantics>,
+    validate_semantics: bool,
+    dry_run: bool,
+) -> Result<Value> {
+    use traverse_ethereum::{AlchemyIndexer, RpcIndexerService, SemanticValidator};
+
+    let (storage_key, declared) = match layout {
+        Some(layout) => {
+            let key = storage_word(&EthereumKeyResolver.resolve(layout, query)?.key);
+            let field = query.split(['[', '.']).next().unwrap_or_default();
+            let declared = layout.storage.iter().find(|e| e.label == field).map(|e| e.zero_semantics);
+            (key, zero_means.or(declared))
+        }
+        None => (parse_hex_word(query, "storage slot")?, zero_means),
+    };
+    let storage_key_hex = format!("0x{}", hex::encode(storage_key));
+    let block_param = parse_block_param(block.unwrap_or("latest"))?;
+
+    if dry_run {
+        if validate_semantics {
+            return Err(anyhow::anyhow!(
+                "--dry-run cannot plan --validate-semantics, whose requests depend on the slot's history"
+            ));
+        }
+        // Mirrors the requests below: the chain's quirks, then the header they select
+        let mut plan = RpcPlan::new();
+        plan.push(rpc_url, "eth_chainId", json!([]), "Chain ID, selecting the chain's quirks");
+        let header = plan_block_with_quirks(&mut plan, rpc_url, &planned_chain_adapter(), &block_param)?;
+        plan.push(
+            rpc_url,
+            "eth_getProof",
+            json!([address, [storage_key_hex], RpcPlan::reference(header, "number")]),
+            format!("Storage proof for {}", query),
+        );
+        return Ok(plan.to_value());
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .build()?;
+
+    let adapter = fetch_chain_adapter(&client, rpc_url).await?;
+    if adapter.is_precompile(&parse_address_bytes(address)?) {
+        return Err(anyhow::anyhow!(
+            "{} is a precompile on {}; it has no storage to prove",
+            address,
+            adapter.profile.name
+        ));
+    }
+    let (block_number, header) = fetch_block_with_quirks(&client, rpc_url, &adapter, &block_param).await?;
+    info!("Fetching proof for {} at block {}", storage_key_hex, block_number);
+
+    let proof = cached_rpc_call(
+        "proof",
+        &client,
+        rpc_url,
+        "eth_getProof",
+        json!([address, [storage_key_hex], format!("0x{:x}", block_number)]),
+    )
+    .await?;
+    let value = proof["storageProof"][0]["value"].as_str().unwrap_or("0x0").to_string();
+
+    let validation = if validate_semantics {
+        let declared = declared.ok_or_else(|| {
+            anyhow::anyhow!("--validate-semantics needs --zero-means or a --layout declaring the field's semantics")
+        })?;
+        let value_word = format!("0x{}", hex::encode(parse_hex_word(&value, "storage value")?));
+        let result = if AlchemyIndexer::is_alchemy_url(rpc_url) {
+            SemanticValidator::new(AlchemyIndexer::from_url(rpc_url))
+                .validate_semantics_at_block(address, &storage_key_hex, declared, Some(&value_word), Some(block_number))
+                .await?
+        } else {
+            SemanticValidator::new(RpcIndexerService::new(rpc_url).with_transport(rpc_transport(rpc_url)))
+                .validate_semantics_at_block(address, &storage_key_hex, declared, Some(&value_word), Some(block_number))
+                .await?
+        };
+        if !result.is_valid {
+            warn!(
+                "Semantic validation failed: declared {:?}, detected {:?}",
+                declared, result.detected_semantics
+            );
+        }
+        Some(result)
+    } else {
+        None
+    };
+
+    Ok(json!({
+        "address": address,
+        "query": query,
+        "storage_key": hex::encode(storage_key),
+        "layout_commitment": layout.map(|l| hex::encode(l.commitment())),
+        "block": {
+            "number": block_number,
+            "hash": header["hash"],
+            "state_root": header["stateRoot"]
+        },
+        "value": value,
+        "zero_semantics": declared,
+        "semantic_validation": validation,
+        "proof": proof
+    }))
+}
+
+#[cfg(not(feature = "ethereum"))]
+#[allow(clippy::too_many_arguments)]
+pub async fn cmd_ethereum_generate_proof(
+    _address: &str,
+    _query: &str,
+    _layout: Option<LayoutSource<'_>>,
+    _rpc_url: &str,
+    _block: Option<&str>,
+    _zero_means: Option<ZeroSemantics>,
+    _validate_semantics: bool,
+    _dry_run: bool,
+    _format: &OutputFormat,
+    _template: Option<&Path>,
+    _output: Option<&Path>,
+) -> Result<()> {
+    Err(anyhow::anyhow!("Ethereum support not enabled. Build with --features ethereum"))
+}
+
+/// How often `watch-proof` regenerates its proofs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchInterval {
+    /// Every `n` blocks, detected by polling the chain head
+    Blocks(u64),
+    /// Every `n` seconds
+    Seconds(u64),
+}
+
+impl std::str::FromStr for WatchInterval {
+    type Err = anyhow::Error;
+
+    /// Parse `10-blocks`, `1-block`, `30s`, `5m` or `1h`
+    fn from_str(spec: &str) -> Result<Self> {
+        let invalid = || anyhow::anyhow!("Invalid interval '{}': expected e.g. 10-blocks, 30s or 5m", spec);
+        let parse = |n: &str| n.parse::<u64>().ok().filter(|n| *n > 0).ok_or_else(invalid);
+
+        if let Some(n) = spec.strip_suffix("-blocks").or_else(|| spec.strip_suffix("-block")) {
+            return Ok(WatchInterval::Blocks(parse(n)?));
+        }
+        let split = spec.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+        let (n, unit) = spec.split_at(split);
+        let scale = match unit {
+            "s" => 1,
+            "m" => 60,
+            "h" => 3600,
+            _ => return Err(invalid()),
+        };
+        parse(n)?.checked_mul(scale).map(WatchInterval::Seconds).ok_or_else(invalid)
+    }
+}
+
+/// Proofs regenerated by `watch-proof`
+#[derive(Debug, Clone, Deserialize)]
+pub struct WatchConfig {
+    pub proofs: Vec<WatchTarget>,
+}
+
+/// Queries against one contract
+#[derive(Debug, Clone, Deserialize)]
+pub struct WatchTarget {
+    /// Contract address or address book name
+    pub address: String,
+    /// Compiled layout file, relative to the watch config; defaults to the
+    /// address book entry's layout
+    #[serde(default)]
+    pub layout: Option<String>,
+    /// Storage queries to prove
+    pub queries: Vec<String>,
+}
+
+/// A watch target with its queries resolved up front
+#[cfg(feature = "ethereum")]
+struct ResolvedWatchTarget {
+    address: String,
+    storage_queries: Vec<Value>,
+}
+
+#[cfg(feature = "ethereum")]
+fn resolve_watch_targets(config_file: &Path, profile: &Profile) -> Result<Vec<ResolvedWatchTarget>> {
+    let content = std::fs::read_to_string(config_file)
+        .map_err(|e| anyhow::anyhow!("Failed to read watch config '{}': {}", config_file.display(), e))?;
+    let config: WatchConfig = serde_json::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("Failed to parse watch config '{}': {}", config_file.display(), e))?;
+    let base = config_file.parent().unwrap_or(Path::new("."));
+
+    config
+        .proofs
+        .into_iter()
+        .map(|target| {
+            let entry = profile.resolve_address(&target.address, "ethereum")?;
+            let layout_path = match (target.layout, entry.layout) {
+                (Some(layout), _) => base.join(layout),
+                (None, Some(layout)) => layout.into(),
+                (None, None) => anyhow::bail!("No layout for {}. Set layout in the watch config or address book", target.address),
+            };
+            let layout = load_layout(&layout_path)
+                .map_err(|e| anyhow::anyhow!("Failed to load layout '{}': {}", layout_path.display(), e))?;
+            let storage_queries = target
+                .queries
+                .iter()
+                .map(|query| {
+                    let resolved = EthereumKeyResolver
+                        .resolve(&layout, query)
+                        .map_err(|e| anyhow::anyhow!("Failed to resolve '{}' for {}: {}", query, entry.address, e))?;
+                    Ok(json!({
+                        "query": query,
+                        "storage_key": hex::encode(storage_word(&resolved.key)),
+                        "layout_commitment": hex::encode(resolved.layout_commitment),
+                        "field_size": resolved.field_size,
+                        "offset": resolved.offset
+                    }))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(ResolvedWatchTarget { address: entry.address, storage_queries })
+        })
+        .collect()
+}
+
+/// Storage keys of every target's queries, in query order
+#[cfg(feature = "ethereum")]
+fn watch_keys(targets: &[ResolvedWatchTarget]) -> Result<Vec<Vec<[u8; 32]>>> {
+    targets
+        .iter()
+        .map(|target| {
+            target
+                .storage_queries
+                .iter()
+                .map(|q| parse_hex_word(q["storage_key"].as_str().unwrap_or_default(), "storage key"))
+                .collect()
+        })
+        .collect()
+}
+
+/// Every target's (address, key) pairs, flattened for one multi-contract fetch
+#[cfg(feature = "ethereum")]
+fn watch_slots<'a>(targets: &'a [ResolvedWatchTarget], keys: &[Vec<[u8; 32]>]) -> Vec<(&'a str, [u8; 32])> {
+    targets
+        .iter()
+        .zip(keys)
+        .flat_map(|(target, keys)| keys.iter().map(|key| (target.address.as_str(), *key)))
+        .collect()
+}
+
+/// Fetch every target's proofs for a round and pair them with the queries
+///
+/// All targets' keys go out together: grouped per contract into
+/// `eth_getProof` calls of at most the adapter's key limit, sent as JSON-RPC
+/// batches. Returns one verification request per target, in order.
+#[cfg(feature = "ethereum")]
+async fn watch_round_batches(
+    rpc_url: &str,
+    adapter: &EvmChainAdapter,
+    targets: &[ResolvedWatchTarget],
+    block_number: u64,
+) -> Result<Vec<Value>> {
+    let keys = watch_keys(targets)?;
+    let fetcher = proof_fetcher(rpc_url);
+    let proofs = fetcher
+        .fetch_proofs(&watch_slots(targets, &keys), &format!("0x{:x}", block_number), adapter)
+        .await?;
+    watch_batches(targets, &keys, &proofs, block_number)
+}
+
+/// Pair fetched proofs with each target's queries as batch verification requests
+///
+/// Each batch also carries the contract's account proof and storage hash, so
+/// it reads as a multi-slot verification request sharing one account proof.
+#[cfg(feature = "ethereum")]
+fn watch_batches(
+    targets: &[ResolvedWatchTarget],
+    keys: &[Vec<[u8; 32]>],
+    proofs: &[traverse_ethereum::Eip1186Proof],
+    block_number: u64,
+) -> Result<Vec<Value>> {
+    targets
+        .iter()
+        .zip(keys)
+        .map(|(target, keys)| {
+            let address = target.address.to_lowercase();
+            let proof = proofs
+                .iter()
+                .find(|proof| format!("0x{}", hex::encode(proof.address)) == address)
+                .ok_or_else(|| anyhow::anyhow!("No proof returned for {}", target.address))?;
+            let storage_batch = target
+                .storage_queries
+                .iter()
+                .zip(keys)
+                .map(|(query, key)| {
+                    let storage_proof = proof
+                        .storage_proofs
+                        .iter()
+                        .find(|storage_proof| storage_proof.key == *key)
+                        .ok_or_else(|| anyhow::anyhow!("No storage proof returned for 0x{}", hex::encode(key)))?;
+                    Ok(json!({
+                        "storage_query": query,
+                        "storage_proof": {
+                            "key": format!("0x{}", hex::encode(storage_proof.key)),
+                            "value": format!("0x{}", hex::encode(storage_proof.value)),
+                            "proof": storage_proof.proof.iter().map(|node| format!("0x{}", hex::encode(node))).collect::<Vec<_>>()
+                        },
+                        "contract_address": target.address,
+                        "block_number": block_number.to_string()
+                    }))
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            Ok(json!({
+                "storage_batch": storage_batch,
+                "contract_address": target.address,
+                "block_number": block_number.to_string(),
+                "account_proof": proof.account_proof.iter().map(|node| format!("0x{}", hex::encode(node))).collect::<Vec<_>>(),
+                "storage_hash": format!("0x{}", hex::encode(proof.storage_hash))
+            }))
+        })
+        .collect()
+}
+
+/// Prove log `log_index` of `tx_hash` through its block's receipts trie
+///
+/// The proof is written as JSON with the decoded log next to the encoded
+/// receipt and trie nodes, so a consumer can check the event it expects
+/// before verifying the proof against a trusted `receiptsRoot`.
+#[cfg(feature = "ethereum")]
+pub async fn cmd_ethereum_prove_log(tx_hash: &str, log_index: usize, rpc_url: &str, output: Option<&Path>) -> Result<()> {
+    let fetcher = proof_fetcher(rpc_url);
+    let proof = fetcher.fetch_log_proof(tx_hash, log_index).await?;
+    let log = proof.verify(&proof.receipts_root)?;
+    info!(
+        "Proved log {} of transaction {} at index {} of block {}",
+        log_index, tx_hash, proof.tx_index, proof.block_number
+    );
+
+    let result = json!({
+        "block_hash": format!("0x{}", hex::encode(proof.block_hash)),
+        "block_number": proof.block_number,
+        "receipts_root": format!("0x{}", hex::encode(proof.receipts_root)),
+        "tx_index": proof.tx_index,
+        "log_index": proof.log_index,
+        "log": {
+            "address": format!("0x{}", hex::encode(log.address)),
+            "topics": log.topics.iter().map(|topic| format!("0x{}", hex::encode(topic))).collect::<Vec<_>>(),
+            "data": format!("0x{}", hex::encode(&log.data)),
+        },
+        "receipt": format!("0x{}", hex::encode(&proof.receipt)),
+        "proof": proof.proof.iter().map(|node| format!("0x{}", hex::encode(node))).collect::<Vec<_>>(),
+    });
+    write_output(&serde_json::to_string_pretty(&result)?, output)
+}
+
+#[cfg(not(feature = "ethereum"))]
+pub async fn cmd_ethereum_prove_log(_tx_hash: &str, _log_index: usize, _rpc_url: &str, _output: Option<&Path>) -> Result<()> {
+    Err(anyhow::anyhow!("Ethereum support not enabled. Build with --features ethereum"))
+}
+
+/// Prove that `tx_hash` is included in its block through the transactions trie
+#[cfg(feature = "ethereum")]
+pub async fn cmd_ethereum_prove_transaction(tx_hash: &str, rpc_url: &str, output: Option<&Path>) -> Result<()> {
+    let fetcher = proof_fetcher(rpc_url);
+    let proof = fetcher.fetch_transaction_proof(tx_hash).await?;
+    info!("Proved transaction {} at index {} of block {}", tx_hash, proof.tx_index, proof.block_number);
+
+    let result = json!({
+        "block_hash": format!("0x{}", hex::encode(proof.block_hash)),
+        "block_number": proof.block_number,
+        "transactions_root": format!("0x{}", hex::encode(proof.transactions_root)),
+        "tx_hash": format!("0x{}", hex::encode(proof.tx_hash)),
+        "tx_index": proof.tx_index,
+        "transaction": format!("0x{}", hex::encode(&proof.transaction)),
+        "proof": proof.proof.iter().map(|node| format!("0x{}", hex::encode(node))).collect::<Vec<_>>(),
+    });
+    write_output(&serde_json::to_string_pretty(&result)?, output)
+}
+
+#[cfg(not(feature = "ethereum"))]
+pub async fn cmd_ethereum_prove_transaction(_tx_hash: &str, _rpc_url: &str, _output: Option<&Path>) -> Result<()> {
+    Err(anyhow::anyhow!("Ethereum support not enabled. Build with --features ethereum"))
+}
+
+/// Prove `slots` of `address` on an Arbitrum chain at the block its rollup last confirmed
+///
+/// The rollup is read through `l1_rpc` at `l1_block`; `rpc_url` is the
+/// Arbitrum chain's endpoint. The output is the combined verification
+/// request of [`traverse_ethereum::AnchoredProof::to_json`].
+#[cfg(feature = "ethereum")]
+#[allow(clippy::too_many_arguments)]
+pub async fn cmd_ethereum_prove_arbitrum(
+    address: &str,
+    slots: &[String],
+    rollup: &str,
+    l1_rpc: &str,
+    l1_block: &str,
+    bold: bool,
+    rpc_url: &str,
+    output: Option<&Path>,
+) -> Result<()> {
+    use traverse_ethereum::{ArbitrumRollup, RollupProtocol};
+
+    let keys = slots.iter().map(|slot| parse_hex_word(slot, "slot")).collect::<Result<Vec<_>>>()?;
+    let protocol = if bold { RollupProtocol::Bold } else { RollupProtocol::Classic };
+    let rollup = ArbitrumRollup::new(l1_rpc, rollup).with_protocol(protocol).at_l1_block(parse_block_param(l1_block)?);
+
+    let mut fetcher = proof_fetcher(rpc_url);
+    fetcher.contract_address = address.to_string();
+    let proof = fetcher.fetch_anchored_proof(&rollup, &keys).await?;
+    info!(
+        "Proved {} slot(s) of {} at Arbitrum block {}, confirmed in L1 block {}",
+        keys.len(),
+        address,
+        proof.block.number,
+        proof.commitment.assertion.confirmed_in_block
+    );
+    write_output(&serde_json::to_string_pretty(&proof.to_json(&rollup))?, output)
+}
+
+#[cfg(not(feature = "ethereum"))]
+#[allow(clippy::too_many_arguments)]
+pub async fn cmd_ethereum_prove_arbitrum(
+    _address: &str,
+    _slots: &[String],
+    _rollup: &str,
+    _l1_rpc: &str,
+    _l1_block: &str,
+    _bold: bool,
+    _rpc_url: &str,
+    _output: Option<&Path>,
+) -> Result<()> {
+    Err(anyhow::anyhow!("Ethereum support not enabled. Build with --features ethereum"))
+}
+
+/// Prove `slots` of `address` on an OP Stack chain with the block's output root preimage
+///
+/// Without `block` the latest block is used, which has no posted output
+/// root yet; pass the L2 block of a root posted on L1, and that root as
+/// `output_root` to check the proof against it before writing.
+#[cfg(feature = "ethereum")]
+pub async fn cmd_ethereum_prove_optimism(
+    address: &str,
+    slots: &[String],
+    block: Option<&str>,
+    output_root: Option<&str>,
+    rpc_url: &str,
+    output: Option<&Path>,
+) -> Result<()> {
+    let keys = slots.iter().map(|slot| parse_hex_word(slot, "slot")).collect::<Result<Vec<_>>>()?;
+    let mut fetcher = proof_fetcher(rpc_url);
+    fetcher.contract_address = address.to_string();
+    if let Some(block) = block {
+        let block = parse_block_param(block)?;
+        let number = block
+            .strip_prefix("0x")
+            .and_then(|hex| u64::from_str_radix(hex, 16).ok())
+            .ok_or_else(|| anyhow::anyhow!("Output roots are posted for numbered blocks, not '{}'", block))?;
+        fetcher = fetcher.at_block(number);
+    }
+
+    let mut anchor = traverse_ethereum::OpStackAnchor::new();
+    if let Some(output_root) = output_root {
+        anchor = anchor.with_output_root(parse_hex_word(output_root, "output root")?);
+    }
+    let proof = fetcher.fetch_anchored_proof(&anchor, &keys).await?;
+    info!(
+        "Proved {} slot(s) of {} at L2 block {} with output root 0x{}",
+        keys.len(),
+        address,
+        proof.block.number,
+        hex::encode(proof.output_root())
+    );
+    write_output(&serde_json::to_string_pretty(&proof.to_json(&anchor))?, output)
+}
+
+#[cfg(not(feature = "ethereum"))]
+pub async fn cmd_ethereum_prove_optimism(
+    _address: &str,
+    _slots: &[String],
+    _block: Option<&str>,
+    _output_root: Option<&str>,
+    _rpc_url: &str,
+    _output: Option<&Path>,
+) -> Result<()> {
+    Err(anyhow::anyhow!("Ethereum support not enabled. Build with --features ethereum"))
+}
+
+/// Prove `slots` of `address` at the execution block of the latest finalized beacon checkpoint
+///
+/// `beacon_url` is a beacon node API; `rpc_url` is the execution endpoint
+/// the proof is read from. With `checkpoint_root`, the proof is only written
+/// if the beacon node's finalized checkpoint is that root.
+#[cfg(feature = "ethereum")]
+pub async fn cmd_ethereum_prove_finalized(
+    address: &str,
+    slots: &[String],
+    beacon_url: &str,
+    checkpoint_root: Option<&str>,
+    rpc_url: &str,
+    output: Option<&Path>,
+) -> Result<()> {
+    let keys = slots.iter().map(|slot| parse_hex_word(slot, "slot")).collect::<Result<Vec<_>>>()?;
+    let mut fetcher = proof_fetcher(rpc_url);
+    fetcher.contract_address = address.to_string();
+
+    let mut anchor = traverse_ethereum::BeaconAnchor::new(beacon_url);
+    if let Some(root) = checkpoint_root {
+        anchor = anchor.with_checkpoint_root(parse_hex_word(root, "checkpoint root")?);
+    }
+    let proof = fetcher.fetch_anchored_proof(&anchor, &keys).await?;
+    info!(
+        "Proved {} slot(s) of {} at block {}, finalized in epoch {}",
+        keys.len(),
+        address,
+        proof.block.number,
+        proof.commitment.finalized_epoch
+    );
+    write_output(&serde_json::to_string_pretty(&proof.to_json(&anchor))?, output)
+}
+
+#[cfg(not(feature = "ethereum"))]
+pub async fn cmd_ethereum_prove_finalized(
+    _address: &str,
+    _slots: &[String],
+    _beacon_url: &str,
+    _checkpoint_root: Option<&str>,
+    _rpc_url: &str,
+    _output: Option<&Path>,
+) -> Result<()> {
+    Err(anyhow::anyhow!("Ethereum support not enabled. Build with --features ethereum"))
+}
+
+/// Blocks a history proof samples: `blocks` as given, or `from` to `to` every `step`
+pub(crate) fn history_blocks(blocks: &[u64], from: Option<u64>, to: Option<u64>, step: u64) -> Result<Vec<u64>> {
+    match (from, to) {
+        (None, None) if !blocks.is_empty() => Ok(blocks.to_vec()),
+        (Some(from), Some(to)) if blocks.is_empty() => {
+            if from > to || step == 0 {
+                return Err(anyhow::anyhow!("Block range needs --from <= --to and a nonzero --step"));
+            }
+            Ok((from..=to).step_by(step as usize).collect())
+        }
+        _ => Err(anyhow::anyhow!("Pass either --block (repeatable) or both --from and --to")),
+    }
+}
+
+/// Prove `slots` of `address` at each of `blocks`, oldest first
+///
+/// Every proof is verified against its block's state root before the batch
+/// is written; blocks past a full node's history need an archive endpoint.
+#[cfg(feature = "ethereum")]
+pub async fn cmd_ethereum_prove_history(
+    address: &str,
+    slots: &[String],
+    blocks: &[u64],
+    rpc_url: &str,
+    output: Option<&Path>,
+) -> Result<()> {
+    let keys = slots.iter().map(|slot| parse_hex_word(slot, "slot")).collect::<Result<Vec<_>>>()?;
+    let mut fetcher = proof_fetcher(rpc_url);
+    fetcher.contract_address = address.to_string();
+    let series = fetcher.fetch_time_series(&keys, blocks).await?;
+    info!("Proved {} slot(s) of {} at {} block(s)", keys.len(), address, series.points.len());
+    write_output(&serde_json::to_string_pretty(&series.to_json())?, output)
+}
+
+#[cfg(not(feature = "ethereum"))]
+pub async fn cmd_ethereum_prove_history(
+    _address: &str,
+    _slots: &[String],
+    _blocks: &[u64],
+    _rpc_url: &str,
+    _output: Option<&Path>,
+) -> Result<()> {
+    Err(anyhow::anyhow!("Ethereum support not enabled. Build with --features ethereum"))
+}
+
+/// Prove `slots` of `address` on zkSync Era against an L1 batch's tree root
+#[cfg(feature = "zksync")]
+pub async fn cmd_ethereum_prove_zksync(
+    address: &str,
+    slots: &[String],
+    l1_batch: Option<u64>,
+    rpc_url: &str,
+    output: Option<&Path>,
+) -> Result<()> {
+    let keys = slots.iter().map(|slot| parse_hex_word(slot, "slot")).collect::<Result<Vec<_>>>()?;
+    let mut fetcher = proof_fetcher(rpc_url);
+    fetcher.contract_address = address.to_string();
+    let proof = fetcher.fetch_era_proof(&keys, l1_batch).await?;
+    info!(
+        "Proved {} slot(s) of {} against the root of L1 batch {}",
+        keys.len(),
+        address,
+        proof.l1_batch
+    );
+    write_output(&serde_json::to_string_pretty(&proof.to_json())?, output)
+}
+
+#[cfg(not(feature = "zksync"))]
+pub async fn cmd_ethereum_prove_zksync(
+    _address: &str,
+    _slots: &[String],
+    _l1_batch: Option<u64>,
+    _rpc_url: &str,
+    _output: Option<&Path>,
+) -> Result<()> {
+    Err(anyhow::anyhow!("zkSync Era support not enabled. Build with --features zksync"))
+}
+
+/// Prove `slots` of `address` from the execution witness of a Verkle `block`
+///
+/// The witness is fetched and its shape checked, but deriving tree keys and
+/// verifying the multiproof need a Bandersnatch backend, which traverse does
+/// not ship yet, so this fails with an error saying so.
+#[cfg(feature = "verkle")]
+pub async fn cmd_ethereum_prove_verkle(
+    address: &str,
+    slots: &[String],
+    block: &str,
+    rpc_url: &str,
+    output: Option<&Path>,
+) -> Result<()> {
+    use traverse_ethereum::verkle::UnavailableBandersnatch;
+
+    let address: [u8; 20] = hex::decode(address.trim_start_matches("0x"))
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| anyhow::anyhow!("Invalid address: {}", address))?;
+    let keys = slots.iter().map(|slot| parse_hex_word(slot, "slot")).collect::<Result<Vec<_>>>()?;
+    let witness = proof_fetcher(rpc_url).fetch_verkle_witness(block).await?;
+    info!("Fetched execution witness of block {} covering {} stems", block, witness.state_diff.len());
+
+    let values = keys
+        .iter()
+        .map(|key| {
+            let value = witness.storage_value(&UnavailableBandersnatch, &address, key)?;
+            Ok(json!({ "slot": format!("0x{}", hex::encode(key)), "value": format!("0x{}", hex::encode(value)) }))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    write_output(&serde_json::to_string_pretty(&json!({ "block": block, "storage": values }))?, output)
+}
+
+#[cfg(not(feature = "verkle"))]
+pub async fn cmd_ethereum_prove_verkle(
+    _address: &str,
+    _slots: &[String],
+    _block: &str,
+    _rpc_url: &str,
+    _output: Option<&Path>,
+) -> Result<()> {
+    Err(anyhow::anyhow!("Verkle support not enabled. Build with --features verkle"))
+}
+
+/// POST a batch verification request to `webhook`, or print it as an NDJSON line
+#[cfg(feature = "ethereum")]
+async fn deliver_batch(client: &reqwest::Client, webhook: Option<&str>, batch: &Value) -> Result<()> {
+    match webhook {
+        Some(url) => {
+            let response = client
+                .post(url)
+                .json(batch)
+                .send()
+                .await
+                .map_err(|e| anyhow::anyhow!("Webhook delivery failed: {}", e))?;
+            if !response.status().is_success() {
+                return Err(anyhow::anyhow!("Webhook returned {}", response.status()));
+            }
+        }
+        None => println!("{}", serde_json::to_string(batch)?),
+    }
+    Ok(())
+}
+
+/// Regenerate proofs on an interval and deliver them to a webhook
+///
+/// Every target in the watch config becomes one batch verification request per
+/// round, fetched at a single block so the batch is consistent. Requests are
+/// POSTed to `webhook`, or written to stdout as NDJSON when no webhook is set.
+/// Delivery and RPC failures are logged and retried next round; `once` stops
+/// after the first round and returns any error instead.
+///
+/// With `subscribe`, rounds follow a `newHeads` WebSocket subscription
+/// instead of polling the chain head.
+#[cfg(feature = "ethereum")]
+#[allow(clippy::too_many_arguments)]
+pub async fn cmd_ethereum_watch_proof(
+    config_file: &Path,
+    profile: &Profile,
+    rpc_url: &str,
+    every: WatchInterval,
+    webhook: Option<&str>,
+    poll_secs: u64,
+    once: bool,
+    subscribe: Option<&str>,
+) -> Result<()> {
+    let targets = resolve_watch_targets(config_file, profile)?;
+    info!("Watching {} contracts ({:?})", targets.len(), every);
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .build()?;
+    let adapter = fetch_chain_adapter(&client, rpc_url).await?;
+    if let Some(ws_url) = subscribe {
+        return watch_subscribed(ws_url, rpc_url, &client, &adapter, &targets, every, webhook, poll_secs, once).await;
+    }
+    let mut last_round: Option<u64> = None;
+
+    loop {
+        let round = async {
+            let (head, _) = fetch_block_header(&client, rpc_url, "latest").await?;
+            let due = match (every, last_round) {
+                (WatchInterval::Blocks(n), Some(last)) => head >= last + n,
+                _ => true,
+            };
+            if !due {
+                return Ok(false);
+            }
+
+            let batches = watch_round_batches(rpc_url, &adapter, &targets, head).await?;
+            for (target, batch) in targets.iter().zip(batches) {
+                deliver_batch(&client, webhook, &batch).await?;
+                info!("Delivered {} proofs for {} at block {}", target.storage_queries.len(), target.address, head);
+            }
+            last_round = Some(head);
+            Ok::<bool, anyhow::Error>(true)
+        }
+        .await;
+
+        match round {
+            Ok(true) if once => return Ok(()),
+            Err(e) if once => return Err(e),
+            Err(e) => warn!("Proof round failed: {}", e),
+            _ => {}
+        }
+
+        let pause = match every {
+            WatchInterval::Blocks(_) => poll_secs,
+            WatchInterval::Seconds(secs) => secs,
+        };
+        tokio::time::sleep(std::time::Duration::from_secs(pause)).await;
+    }
+}
+
+#[cfg(not(feature = "ethereum"))]
+#[allow(clippy::too_many_arguments)]
+pub async fn cmd_ethereum_watch_proof(
+    _config_file: &Path,
+    _profile: &Profile,
+    _rpc_url: &str,
+    _every: WatchInterval,
+    _webhook: Option<&str>,
+    _poll_secs: u64,
+    _once: bool,
+    _subscribe: Option<&str>,
+) -> Result<()> {
+    Err(anyhow::anyhow!("Ethereum support not enabled. Build with --features ethereum"))
+}
+
+/// Deliver a round for every due head of a `newHeads` subscription
+///
+/// Proofs are fetched over `rpc_url` and checked against each head's state
+/// root. A dropped subscription is reconnected after `poll_secs`.
+#[cfg(feature = "websocket")]
+#[allow(clippy::too_many_arguments)]
+async fn watch_subscribed(
+    ws_url: &str,
+    rpc_url: &str,
+    client: &reqwest::Client,
+    adapter: &EvmChainAdapter,
+    targets: &[ResolvedWatchTarget],
+    every: WatchInterval,
+    webhook: Option<&str>,
+    poll_secs: u64,
+    once: bool,
+) -> Result<()> {
+    let WatchInterval::Blocks(blocks) = every else {
+        anyhow::bail!("--subscribe refreshes on new heads; use a block interval such as 1-block");
+    };
+    let keys = watch_keys(targets)?;
+    let subscription = watch_slots(targets, &keys).into_iter().fold(
+        traverse_ethereum::ProofSubscription::new(rpc_url, adapter.clone()).every_blocks(blocks),
+        |subscription, (address, key)| subscription.watch(address, key),
+    );
+
+    loop {
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(16);
+        let run = subscription.run(ws_url, sender);
+        tokio::pin!(run);
+
+        let ended = loop {
+            tokio::select! {
+                ended = &mut run => break ended,
+                Some(refresh) = receiver.recv() => {
+                    let round = async {
+                        let refresh = refresh?;
+                        let head = refresh.block.number;
+                        let batches = watch_batches(targets, &keys, &refresh.proofs, head)?;
+                        for (target, batch) in targets.iter().zip(batches) {
+                            deliver_batch(client, webhook, &batch).await?;
+                            info!("Delivered {} proofs for {} at block {}", target.storage_queries.len(), target.address, head);
+                        }
+                        Ok::<(), anyhow::Error>(())
+                    }
+                    .await;
+
+                    match round {
+                        Ok(()) if once => return Ok(()),
+                        Err(e) if once => return Err(e),
+                        Err(e) => warn!("Proof refresh failed: {}", e),
+                        _ => {}
+                    }
+                }
+            }
+        };
+
+        match ended {
+            Err(e) if once => return Err(e.into()),
+            Err(e) => warn!("Subscription to {} ended: {}", ws_url, e),
+            Ok(()) => return Ok(()),
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(poll_secs)).await;
+    }
+}
+
+#[cfg(all(feature = "ethereum", not(feature = "websocket")))]
+#[allow(clippy::too_many_arguments)]
+async fn watch_subscribed(
+    _ws_url: &str,
+    _rpc_url: &str,
+    _client: &reqwest::Client,
+    _adapter: &EvmChainAdapter,
+    _targets: &[ResolvedWatchTarget],
+    _every: WatchInterval,
+    _webhook: Option<&str>,
+    _poll_secs: u64,
+    _once: bool,
+) -> Result<()> {
+    Err(anyhow::anyhow!("WebSocket subscriptions not enabled. Build with --features websocket"))
 }
 
-/// Verify storage layout correctness
+/// Spot-check a layout against a deployed contract
+///
+/// Every entry's slot is read with `eth_getStorageAt` at one block (`latest`
+/// by default) and compared with its declaration: a value alone in its slot
+/// must fit the declared size, booleans must be 0 or 1, a mapping's own slot
+/// must be empty and strings must carry a valid length. Returns whether any
+/// entry mismatched.
 #[cfg(feature = "ethereum")]
 pub async fn cmd_ethereum_verify_layout(
     layout_file: &Path,
-    contract_address: Option<&str>,
-    rpc: Option<&str>,
-    comprehensive: bool,
-) -> Result<()> {
-    info!("Verifying storage layout from {}", layout_file.display());
+    address: &str,
+    rpc_url: &str,
+    block: Option<&str>,
+    format: &OutputFormat,
+    output: Option<&Path>,
+) -> Result<bool> {
+    let layout = load_layout(layout_file)?;
+    layout
+        .validate()
+        .map_err(|e| anyhow::anyhow!("Invalid layout '{}': {}", layout_file.display(), e))?;
 
-    // Check if the file exists before attempting to read it
-    if !layout_file.exists() {
-        return Err(anyhow::anyhow!(
-            "Layout file does not exist: {}",
-            layout_file.display()
-        ));
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .build()?;
+    let block_param = parse_block_param(block.unwrap_or("latest"))?;
+    let (block_number, header) = cached_block_header(&client, rpc_url, &block_param).await?;
+    info!("Spot-checking {} entries of {} at block {}", layout.storage.len(), address, block_number);
+
+    let mut report = SpotCheckReport::new(
+        "ethereum",
+        json!({
+            "address": address,
+            "block": { "number": block_number, "hash": header["hash"] },
+            "layout_commitment": hex::encode(layout.commitment()),
+        }),
+    );
+    for entry in &layout.storage {
+        // validate() has checked every slot parses and every type exists
+        let slot = format!("0x{:064x}", entry.slot.parse::<u64>()?);
+        let type_info = layout.types.iter().find(|t| t.label == entry.type_name).unwrap();
+        let value = cached_rpc_call(
+            "rpc",
+            &client,
+            rpc_url,
+            "eth_getStorageAt",
+            json!([address, slot, format!("0x{:x}", block_number)]),
+        )
+        .await?;
+        let word = parse_hex_word(value.as_str().unwrap_or_default(), "storage value")?;
+        let packed = layout.storage.iter().filter(|other| other.slot == entry.slot).count() > 1;
+        report.checks.push(inspect_slot(entry, type_info, packed, &word));
     }
 
-    // Load layout
-    let layout_content = std::fs::read_to_string(layout_file)
-        .map_err(|e| anyhow::anyhow!("Failed to read layout file '{}': {}", layout_file.display(), e))?;
-    let layout: LayoutInfo = serde_json::from_str(&layout_content)
-        .map_err(|e| anyhow::anyhow!("Failed to parse layout file '{}': {}", layout_file.display(), e))?;
-
-    // Perform basic validation
-    validate_layout(&layout)?;
+    let mismatched = report.mismatched();
+    if mismatched > 0 {
+        warn!("{} of {} entries do not match their live values", mismatched, report.checks.len());
+    }
+    write_result(&report.to_value(), format, None, output)?;
+    Ok(mismatched > 0)
+}
 
-    let mut verification_result = json!({
-        "layout_file": layout_file.display().to_string(),
-        "basic_validation": "passed",
-        "timestamp": std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs()
-    });
+#[cfg(not(feature = "ethereum"))]
+pub async fn cmd_ethereum_verify_layout(
+    _layout_file: &Path,
+    _address: &str,
+    _rpc_url: &str,
+    _block: Option<&str>,
+    _format: &OutputFormat,
+    _output: Option<&Path>,
+) -> Result<bool> {
+    Err(anyhow::anyhow!("Ethereum support not enabled. Build with --features ethereum"))
+}
 
-    // Perform live verification if contract address and RPC are provided
-    if let (Some(address), Some(rpc_url)) = (contract_address, rpc) {
-        info!("Performing live verification for contract {}", address);
-        match perform_live_ethereum_verification(address, rpc_url, &layout).await {
-            Ok(live_verification) => {
-                verification_result["live_verification"] = live_verification;
+/// Check one entry against the word stored in its slot
+///
+/// Packed entries are read from their own bytes, `offset` bytes up from the
+/// low end of the word; an entry alone in its slot is checked against the
+/// whole word, so stray high bytes show as an oversized value.
+#[cfg(feature = "ethereum")]
+fn inspect_slot(
+    entry: &traverse_core::StorageEntry,
+    type_info: &traverse_core::TypeInfo,
+    packed: bool,
+    word: &[u8; 32],
+) -> SpotCheck {
+    let mut check = SpotCheck::new(
+        &entry.label,
+        &entry.type_name,
+        format!("slot {} offset {}", entry.slot, entry.offset),
+        entry.zero_semantics,
+    );
+    let size = type_info.number_of_bytes.parse::<usize>().unwrap_or(32).min(32);
+    let end = 32usize.saturating_sub(entry.offset as usize).max(size);
+    let value = if packed { &word[end - size..end] } else { &word[..] };
+    let observed = if value.iter().all(|b| *b == 0) { Observed::Zero } else { Observed::Value };
+    check.observe(Some(json!(format!("0x{}", hex::encode(value)))), observed);
+
+    match type_info.encoding.as_str() {
+        "mapping" if observed == Observed::Value => {
+            check.mismatch("a mapping's own slot is never written, but this one holds a value");
+        }
+        "bytes" => {
+            // Short values keep twice their length in the low byte, long ones
+            // twice their length plus one
+            let length = word[31];
+            if length.is_multiple_of(2) && (length > 62 || word[length as usize / 2..31].iter().any(|b| *b != 0)) {
+                check.mismatch("the slot does not hold a valid string or bytes length");
             }
-            Err(e) => {
-                warn!("Live verification failed: {}", e);
-                verification_result["live_verification_error"] = json!(e.to_string());
+        }
+        "inplace" => {
+            if !packed {
+                let (high, low) = word.split_at(32 - size);
+                let sign_extended = entry.type_name.starts_with("t_int")
+                    && low.first().is_some_and(|b| b & 0x80 != 0)
+                    && high.iter().all(|b| *b == 0xff);
+                if !sign_extended && high.iter().any(|b| *b != 0) {
+                    check.mismatch(format!("the value does not fit the declared {} bytes", size));
+                }
+            }
+            if entry.type_name == "t_bool" && word[end - 1] > 1 {
+                check.mismatch(format!("a bool must be 0 or 1, found {}", word[end - 1]));
             }
         }
+        _ => {}
     }
-
-    if comprehensive {
-        info!("Running comprehensive verification tests...");
-        // Add comprehensive tests here
-        verification_result["comprehensive_tests"] = json!("not_implemented");
-    }
-
-    let output_str = serde_json::to_string_pretty(&verification_result)?;
-    println!("{}", output_str);
-    Ok(())
+    check
 }
 
-#[cfg(not(feature = "ethereum"))]
-pub async fn cmd_ethereum_verify_layout(
-    _layout_file: &Path,
-    _contract_address: Option<&str>,
-    _rpc: Option<&str>,
-    _comprehensive: bool,
-) -> Result<()> {
-    Err(anyhow::anyhow!("Ethereum support not enabled. Build with --features ethereum"))
+/// Input for `auto-generate`
+#[derive(Debug, Clone, Deserialize)]
+pub struct AutoGenerateConfig {
+    /// Contract ABI, relative to the config file
+    pub abi: String,
+    /// Contract address or address book name
+    pub contract: String,
+    /// Storage queries, e.g. `balances[0x...]`
+    pub queries: Vec<String>,
+    /// RPC endpoint for this entry, overriding `--rpc`
+    #[serde(default)]
+    pub rpc: Option<String>,
 }
 
 /// End-to-end automation for Ethereum
@@ -739,7 +2794,7 @@ pub async fn cmd_ethereum_auto_generate(
     // Step 1: Compile layout
     info!("Step 1: Compiling layout...");
     let layout_file = output_dir.join("layout.json");
-    cmd_ethereum_compile_layout(abi_file, Some(&layout_file), &OutputFormat::Traverse, true)?;
+    cmd_ethereum_compile_layout(abi_file, Some(&layout_file), &OutputFormat::Traverse, true, None)?;
 
     // Step 2: Generate queries
     info!("Step 2: Generating queries...");
@@ -751,17 +2806,31 @@ pub async fn cmd_ethereum_auto_generate(
     let query_list: Vec<&str> = queries.split(',').map(|q| q.trim()).collect();
     let resolved_file = output_dir.join("resolved.json");
     
+    // A dry run skips live verification and plans its eth_getStorageAt reads instead
+    let live = (!dry_run).then_some((contract_trimmed, rpc_trimmed));
+    let layout = load_layout(&layout_file)?;
+    let mut plan = RpcPlan::new();
     let mut resolved_queries = Vec::new();
     for query in &query_list {
         match cmd_ethereum_resolve_query(
             query, 
-            &layout_file, 
+            LayoutSource::File(&layout_file), 
             &OutputFormat::CoprocessorJson, 
             None,
-            Some(contract_trimmed), // Pass contract address for live verification
-            Some(rpc_trimmed)       // Pass RPC URL for live verification
+            None,
+            live.map(|(contract, _)| contract),
+            live.map(|(_, rpc)| rpc),
         ).await {
             Ok(_) => {
+                if dry_run {
+                    let key = hex::encode(key_to_bytes(&EthereumKeyResolver.resolve(&layout, query)?.key));
+                    plan.push(
+                        rpc_trimmed,
+                        "eth_getStorageAt",
+                        storage_read_params(contract_trimmed, &key),
+                        format!("Live verification of {}", query),
+                    );
+                }
                 resolved_queries.push(json!({
                     "query": query,
                     "status": "resolved"
@@ -786,8 +2855,10 @@ pub async fn cmd_ethereum_auto_generate(
 
     std::fs::write(&resolved_file, serde_json::to_string_pretty(&resolved_output)?)?;
 
-    // Step 4: Generate proof templates (if not dry run)
-    if !dry_run {
+    let plan_file = output_dir.join(PLAN_FILE);
+    if dry_run {
+        plan.write(&plan_file)?;
+    } else {
         info!("Step 4: Generating proof templates...");
         let proof_template = json!({
             "contract": contract,
@@ -810,7 +2881,8 @@ pub async fn cmd_ethereum_auto_generate(
             "layout": layout_file.display().to_string(),
             "queries": queries_file.display().to_string(),
             "resolved": resolved_file.display().to_string(),
-            "proof_template": if dry_run { "skipped" } else { "generated" }
+            "proof_template": if dry_run { "skipped" } else { "generated" },
+            "plan": if dry_run { plan_file.display().to_string() } else { "skipped".to_string() }
         },
         "next_steps": [
             "Review generated files",
@@ -856,34 +2928,9 @@ async fn perform_live_storage_key_verification(
         .build()?;
     let storage_key = hex::encode(key_to_bytes(&resolved.key));
     
-    // Prepare eth_getStorageAt request
-    let request_body = json!({
-        "jsonrpc": "2.0",
-        "method": "eth_getStorageAt",
-        "params": [
-            contract_address,
-            format!("0x{}", storage_key),
-            "latest"
-        ],
-        "id": 1
-    });
-
-    let response = client
-        .post(rpc_url)
-        .json(&request_body)
-        .send()
-        .await?;
-
-    let response_json: serde_json::Value = response.json().await?;
-    
-    if let Some(error) = response_json.get("error") {
-        return Err(anyhow::anyhow!("RPC error: {}", error));
-    }
-
-    let storage_value = response_json
-        .get("result")
-        .and_then(|r| r.as_str())
-        .unwrap_or("0x0");
+    let params = storage_read_params(contract_address, &storage_key);
+    let result = cached_rpc_call("rpc", &client, rpc_url, "eth_getStorageAt", params).await?;
+    let storage_value = result.as_str().unwrap_or("0x0");
 
     Ok(json!({
         "contract_address": contract_address,
@@ -897,6 +2944,12 @@ async fn perform_live_storage_key_verification(
     }))
 }
 
+/// `eth_getStorageAt` params for reading a hex-encoded key at the latest block
+#[cfg(feature = "ethereum")]
+fn storage_read_params(contract_address: &str, storage_key: &str) -> Value {
+    json!([contract_address, format!("0x{}", storage_key), "latest"])
+}
+
 #[cfg(feature = "ethereum")]
 async fn perform_live_ethereum_analysis(contract_address: &str, rpc_url: &str) -> Result<Value> {
     // Basic RPC call to get contract code and validate it exists
@@ -937,73 +2990,11 @@ async fn perform_live_ethereum_analysis(contract_address: &str, rpc_url: &str) -
     }))
 }
 
-#[cfg(feature = "ethereum")]
-async fn perform_live_ethereum_verification(
-    contract_address: &str,
-    rpc_url: &str,
-    layout: &LayoutInfo,
-) -> Result<Value> {
-    // Basic verification: check a few storage slots to see if contract has expected structure
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()?;
-    let mut verified_slots = 0;
-    let mut total_slots = 0;
-    
-    // Check first few storage entries
-    for (i, entry) in layout.storage.iter().enumerate().take(3) {
-        total_slots += 1;
-        let slot_hex = format!("0x{:064x}", i); // Simple slot numbering for basic verification
-        
-        let rpc_request = json!({
-            "jsonrpc": "2.0",
-            "method": "eth_getStorageAt",
-            "params": [contract_address, slot_hex, "latest"],
-            "id": i + 1
-        });
-        
-        if let Ok(response) = client.post(rpc_url).json(&rpc_request).send().await {
-            if let Ok(rpc_response) = response.json::<Value>().await {
-                if rpc_response.get("result").is_some() {
-                    verified_slots += 1;
-                    info!("Verified storage slot for field: {}", entry.label);
-                }
-            }
-        }
-    }
-    
-    let verification_ratio = if total_slots > 0 { 
-        verified_slots as f64 / total_slots as f64 
-    } else { 
-        0.0 
-    };
-    
-    Ok(json!({
-        "contract_address": contract_address,
-        "rpc_url": rpc_url,
-        "layout_commitment": hex::encode(&layout.commitment()),
-        "status": if verification_ratio > 0.5 { "verification_passed" } else { "verification_failed" },
-        "verified_slots": verified_slots,
-        "total_slots": total_slots,
-        "verification_ratio": verification_ratio,
-        "timestamp": chrono::Utc::now().to_rfc3339()
-    }))
-}
-
 #[cfg(not(feature = "ethereum"))]
 async fn perform_live_ethereum_analysis(_contract_address: &str, _rpc_url: &str) -> Result<Value> {
     Err(anyhow::anyhow!("Ethereum support not enabled"))
 }
 
-#[cfg(not(feature = "ethereum"))]
-async fn perform_live_ethereum_verification(
-    _contract_address: &str,
-    _rpc_url: &str,
-    _layout: &LayoutInfo,
-) -> Result<Value> {
-    Err(anyhow::anyhow!("Ethereum support not enabled"))
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1078,55 +3069,61 @@ mod tests {
     }
 
     #[cfg(feature = "ethereum")]
-    #[tokio::test]
-    async fn test_perform_live_ethereum_verification_returns_result() {
-        // Create a minimal test layout
-        let layout = LayoutInfo {
-            contract_name: "TestContract".to_string(),
-            storage: vec![
-                StorageEntry {
-                    label: "balance".to_string(),
-                    slot: "0".to_string(),
-                    offset: 0,
-                    type_name: "uint256".to_string(),
-                    zero_semantics: ZeroSemantics::ValidZero,
-                },
-            ],
-            types: vec![
-                TypeInfo {
-                    label: "uint256".to_string(),
-                    number_of_bytes: "32".to_string(),
-                    encoding: "inplace".to_string(),
-                    base: None,
-                    key: None,
-                    value: None,
-                },
-            ],
+    #[test]
+    fn test_inspect_slot_spot_checks() {
+        let entry = |label: &str, offset: u8, type_name: &str, zero_semantics| StorageEntry {
+            label: label.to_string(),
+            slot: "0".to_string(),
+            offset,
+            type_name: type_name.to_string(),
+            zero_semantics,
         };
-
-        let result = perform_live_ethereum_verification(
-            "0x1234567890123456789012345678901234567890",
-            "http://localhost:8545",
-            &layout
-        ).await;
-
-        // The function should return either Ok or Err, both are valid
-        match result {
-            Ok(v) => {
-                // If it succeeds, verify the response structure
-                assert!(v.get("contract_address").is_some());
-                assert!(v.get("rpc_url").is_some());
-                assert!(v.get("total_slots").is_some());
-                assert!(v.get("verified_slots").is_some());
-                assert!(v.get("status").is_some());
-                assert!(v.get("layout_commitment").is_some());
-                assert!(v.get("verification_ratio").is_some());
-            },
-            Err(_) => {
-                // Network errors are also valid (no RPC server running)
-                // This is expected in most test environments
-            }
-        }
+        let type_info = |label: &str, size: &str, encoding: &str| TypeInfo {
+            label: label.to_string(),
+            number_of_bytes: size.to_string(),
+            encoding: encoding.to_string(),
+            base: None,
+            key: None,
+            value: None,
+        };
+        let uint8 = type_info("t_uint8", "1", "inplace");
+        let bool_type = type_info("t_bool", "1", "inplace");
+
+        // A packed bool at offset 1 reads only its own byte
+        let mut word = [0u8; 32];
+        word[31] = 0xff;
+        word[30] = 1;
+        let flag = entry("flag", 1, "t_bool", ZeroSemantics::ValidZero);
+        let check = inspect_slot(&flag, &bool_type, true, &word);
+        assert_eq!(check.value, Some(json!("0x01")));
+        assert!(check.mismatches.is_empty(), "{:?}", check.mismatches);
+        word[30] = 2;
+        assert_eq!(inspect_slot(&flag, &bool_type, true, &word).mismatches, ["a bool must be 0 or 1, found 2"]);
+
+        // Alone in its slot, the same word overflows a uint8
+        let decimals = entry("decimals", 0, "t_uint8", ZeroSemantics::NeverWritten);
+        let check = inspect_slot(&decimals, &uint8, false, &word);
+        assert_eq!(check.mismatches, ["the value does not fit the declared 1 bytes"]);
+
+        // Negative small ints are sign-extended across the slot
+        let delta = entry("delta", 0, "t_int8", ZeroSemantics::ValidZero);
+        let check = inspect_slot(&delta, &type_info("t_int8", "1", "inplace"), false, &[0xff; 32]);
+        assert!(check.mismatches.is_empty(), "{:?}", check.mismatches);
+
+        let balances = entry("balances", 0, "t_mapping", ZeroSemantics::ValidZero);
+        let mapping = type_info("t_mapping", "32", "mapping");
+        assert!(inspect_slot(&balances, &mapping, false, &[0; 32]).mismatches.is_empty());
+        assert_eq!(inspect_slot(&balances, &mapping, false, &word).mismatches.len(), 1);
+
+        // "abc" as a short string, then with its length byte corrupted
+        let name = entry("name", 0, "t_string", ZeroSemantics::ExplicitlyZero);
+        let string = type_info("t_string", "32", "bytes");
+        let mut word = [0u8; 32];
+        word[..3].copy_from_slice(b"abc");
+        word[31] = 6;
+        assert!(inspect_slot(&name, &string, false, &word).mismatches.is_empty());
+        word[31] = 4;
+        assert_eq!(inspect_slot(&name, &string, false, &word).mismatches.len(), 1);
     }
 
     #[test]
@@ -1196,6 +3193,7 @@ mod tests {
                     value: None,
                 },
             ],
+            code_variables: Vec::new(),
         };
 
         let result = validate_layout(&layout_with_conflict);
@@ -1226,6 +3224,7 @@ mod tests {
                     value: None,
                 },
             ],
+            code_variables: Vec::new(),
         };
 
         let result = validate_layout(&layout_with_unknown_type);
@@ -1256,12 +3255,63 @@ mod tests {
                     value: None,
                 },
             ],
+            code_variables: Vec::new(),
         };
 
         let result = validate_layout(&valid_layout);
         assert!(result.is_ok());
     }
 
+    #[cfg(feature = "ethereum")]
+    #[tokio::test]
+    async fn test_watch_round_is_one_batched_request() {
+        let account = |address: &str, keys: &[&str]| {
+            json!({
+                "address": address,
+                "nonce": "0x0",
+                "balance": "0x0",
+                "storageHash": format!("0x{}", "11".repeat(32)),
+                "codeHash": format!("0x{}", "22".repeat(32)),
+                "accountProof": [],
+                "storageProof": keys.iter().map(|key| json!({ "key": key, "value": "0x2a", "proof": ["0xc0"] })).collect::<Vec<_>>()
+            })
+        };
+        let key = |byte: u8| format!("0x{}", hex::encode([byte; 32]));
+        let (a, b) = (format!("0x{}", "aa".repeat(20)), format!("0x{}", "bb".repeat(20)));
+
+        // Responses out of order, as batch responses may be
+        let body = json!([
+            { "jsonrpc": "2.0", "id": 1, "result": account(&b, &[&key(3)]) },
+            { "jsonrpc": "2.0", "id": 0, "result": account(&a, &[&key(1), &key(2)]) }
+        ]);
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::PartialJson(json!([{ "method": "eth_getProof" }, { "method": "eth_getProof" }])))
+            .with_body(body.to_string())
+            .expect(1)
+            .create_async()
+            .await;
+
+        let query = |byte: u8| json!({ "storage_key": hex::encode([byte; 32]) });
+        let targets = vec![
+            ResolvedWatchTarget { address: a.clone(), storage_queries: vec![query(1)] },
+            ResolvedWatchTarget { address: b.clone(), storage_queries: vec![query(3)] },
+            ResolvedWatchTarget { address: a.clone(), storage_queries: vec![query(2), query(1)] },
+        ];
+        let batches = watch_round_batches(&server.url(), &EvmChainAdapter::for_chain_id(1), &targets, 100)
+            .await
+            .unwrap();
+        mock.assert_async().await;
+
+        assert_eq!(batches.len(), 3);
+        assert_eq!(batches[1]["contract_address"], b);
+        assert_eq!(batches[2]["storage_batch"][0]["storage_proof"]["key"], key(2));
+        assert_eq!(batches[2]["storage_batch"][1]["storage_proof"]["value"], format!("0x{}2a", "00".repeat(31)));
+        assert_eq!(batches[2]["storage_hash"], format!("0x{}", "11".repeat(32)));
+        assert_eq!(batches[2]["account_proof"], json!([]));
+    }
+
     #[cfg(feature = "ethereum")]
     #[tokio::test]
     async fn test_resolve_query_with_live_verification() {
@@ -1298,9 +3348,10 @@ mod tests {
         // Test without live verification (should work)
         let result = cmd_ethereum_resolve_query(
             "balance",
-            temp_file.path(),
+            LayoutSource::File(temp_file.path()),
             &OutputFormat::CoprocessorJson,
             None,
+            None,
             None, // no contract address
             None, // no rpc
         ).await;
@@ -1309,9 +3360,10 @@ mod tests {
         // Test with live verification parameters (will fail with network error, but should not panic)
         let result = cmd_ethereum_resolve_query(
             "balance",
-            temp_file.path(),
+            LayoutSource::File(temp_file.path()),
             &OutputFormat::CoprocessorJson,
             None,
+            None,
             Some("0x1234567890123456789012345678901234567890"), // contract address
             Some("http://localhost:8545"), // rpc (will fail, but should not panic)
         ).await;
@@ -1433,4 +3485,398 @@ mod tests {
         // Should succeed in dry run mode
         assert!(result.is_ok());
     }
-} 
\ No newline at end of file
+
+    #[cfg(feature = "ethereum")]
+    #[tokio::test]
+    async fn test_generate_proof_plan_matches_fetches() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let plan_path = temp_dir.path().join("plan.json");
+        let methods = |block: &'static str| {
+            let plan_path = plan_path.clone();
+            async move {
+                let address = "0x1234567890123456789012345678901234567890";
+                let format = OutputFormat::Traverse;
+                cmd_ethereum_generate_proof(
+                    address, "0x0", None, "http://localhost:8545", Some(block), None, false, true, &format, None,
+                    Some(&plan_path),
+                )
+                .await
+                .unwrap();
+                let plan: Value = serde_json::from_str(&std::fs::read_to_string(&plan_path).unwrap()).unwrap();
+                plan["calls"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|call| format!("{} {}", call["method"].as_str().unwrap(), call["params"][0]))
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(
+            methods("latest").await,
+            ["eth_chainId null", "eth_getBlockByNumber \"latest\"", "eth_getProof \"0x1234567890123456789012345678901234567890\""]
+        );
+        // A numbered block is checked against the head first
+        assert_eq!(methods("100").await[1..3], ["eth_getBlockByNumber \"latest\"", "eth_getBlockByNumber \"0x64\""]);
+
+        // Chains counting confirmations read the head, then a block below it
+        let counted = EvmChainAdapter::new(traverse_ethereum::QuirkProfile {
+            block_tags: &[BlockTag::Latest],
+            finality: traverse_ethereum::EvmFinality::Confirmations(64),
+            ..traverse_ethereum::QuirkProfile::generic(8453)
+        });
+        let mut plan = RpcPlan::new();
+        let header = plan_block_with_quirks(&mut plan, "http://localhost:8545", &counted, "finalized").unwrap();
+        assert_eq!(header, 1);
+        assert_eq!(plan.to_value()["calls"][1]["params"][0], "$0.number-63");
+    }
+
+    #[cfg(feature = "ethereum")]
+    #[tokio::test]
+    async fn test_resolve_query_array_element() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let layout_path = temp_dir.path().join("layout.json");
+        let layout = json!({
+            "contract_name": "Vault",
+            "storage": [
+                { "label": "checkpoints", "slot": "1", "offset": 0, "type_name": "t_array(t_uint32)dyn_storage", "zero_semantics": "NeverWritten" }
+            ],
+            "types": [
+                { "label": "t_array(t_uint32)dyn_storage", "number_of_bytes": "32", "encoding": "dynamic_array", "base": "t_uint32", "key": null, "value": null },
+                { "label": "t_uint32", "number_of_bytes": "4", "encoding": "inplace", "base": null, "key": null, "value": null }
+            ]
+        });
+        std::fs::write(&layout_path, layout.to_string()).unwrap();
+
+        let resolve = |query: &'static str| {
+            let out_path = temp_dir.path().join("resolved.json");
+            let layout_path = layout_path.clone();
+            async move {
+                cmd_ethereum_resolve_query(query, LayoutSource::File(&layout_path), &OutputFormat::CoprocessorJson, None, Some(&out_path), None, None)
+                    .await
+                    .unwrap();
+                serde_json::from_str::<Value>(&std::fs::read_to_string(&out_path).unwrap()).unwrap()
+            }
+        };
+
+        // Eight uint32 per slot: elements 8 and 9 share the second slot
+        let eighth = resolve("checkpoints[8]").await;
+        let ninth = resolve("checkpoints[9]").await;
+        assert_eq!(eighth["storage_key"], ninth["storage_key"]);
+        assert_ne!(resolve("checkpoints[7]").await["storage_key"], ninth["storage_key"]);
+        assert_eq!(eighth["offset"], 0);
+        assert_eq!(ninth["offset"], 4);
+        assert_eq!(ninth["field_size"], 4);
+    }
+
+    #[cfg(feature = "verkle")]
+    #[tokio::test]
+    async fn test_prove_verkle_reports_missing_backend() {
+        let word = format!("0x{}", "11".repeat(32));
+        let witness = json!({
+            "stateDiff": [],
+            "verkleProof": {
+                "otherStems": [],
+                "depthExtensionPresent": "0x",
+                "commitmentsByPath": [],
+                "d": word,
+                "ipaProof": { "cl": vec![&word; 8], "cr": vec![&word; 8], "finalEvaluation": word }
+            }
+        });
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::PartialJson(json!({ "method": "eth_getBlockByNumber" })))
+            .with_body(json!({ "jsonrpc": "2.0", "id": 1, "result": { "executionWitness": witness } }).to_string())
+            .create_async()
+            .await;
+
+        let address = format!("0x{}", "aa".repeat(20));
+        let error = cmd_ethereum_prove_verkle(&address, &["0x0".to_string()], "latest", &server.url(), None)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("Bandersnatch backend unavailable"), "{}", error);
+    }
+
+    #[cfg(feature = "ethereum")]
+    #[tokio::test]
+    async fn test_resolve_query_preset() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let out_path = temp_dir.path().join("resolved.json");
+        let preset = LayoutSource::Preset("erc721");
+        cmd_ethereum_resolve_query("_owners[1]", preset, &OutputFormat::CoprocessorJson, None, Some(&out_path), None, None)
+            .await
+            .unwrap();
+        let resolved: Value = serde_json::from_str(&std::fs::read_to_string(&out_path).unwrap()).unwrap();
+        assert_eq!(resolved["field_size"], 20);
+        assert_eq!(resolved["layout_commitment"], hex::encode(traverse_ethereum::presets::erc721().commitment()));
+
+        let error = LayoutSource::Preset("erc4626").load().unwrap_err();
+        assert!(error.to_string().contains("erc20, erc721, erc1155, chainlink-proxy, safe"));
+    }
+
+    #[cfg(all(feature = "ethereum", feature = "codegen"))]
+    #[test]
+    fn test_codegen_targets() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let out_path = temp_dir.path().join("lib.rs");
+        let erc20 = LayoutSource::Preset("erc20");
+        let generate = |target, queries: &[&str]| {
+            let queries: Vec<String> = queries.iter().map(|q| q.to_string()).collect();
+            cmd_ethereum_codegen(Some(erc20), target, &queries, false, Some(&out_path))
+                .map(|_| std::fs::read_to_string(&out_path).unwrap())
+        };
+
+        let pruned = generate(CodegenTarget::Verifier, &["_totalSupply"]).unwrap();
+        assert!(pruned.contains("pub const FIELD__TOTALSUPPLY: u16 = 2;"));
+        assert!(!pruned.contains("FIELD__BALANCES"));
+        let controller = generate(CodegenTarget::ConstController, &[]).unwrap();
+        assert!(controller.contains("pub const RESOLVED_KEYS"));
+
+        assert!(generate(CodegenTarget::Query, &["_owners[1]"]).is_err());
+        assert!(generate(CodegenTarget::ConstLayout, &["_totalSupply"]).is_err());
+
+        // The request types need no layout
+        assert!(generate(CodegenTarget::Typescript, &[]).is_err());
+        cmd_ethereum_codegen(None, CodegenTarget::Typescript, &[], true, Some(&out_path)).unwrap();
+        let typescript = std::fs::read_to_string(&out_path).unwrap();
+        assert!(typescript.contains("export interface StorageVerificationRequest"));
+        assert!(typescript.contains("export const StorageVerificationRequestSchema"));
+    }
+
+    #[cfg(feature = "ethereum")]
+    #[test]
+    fn test_batch_resolve_streams_ndjson() {
+        use tempfile::{NamedTempFile, TempDir};
+
+        let layout = LayoutInfo {
+            contract_name: "TestContract".to_string(),
+            storage: vec![StorageEntry {
+                label: "balance".to_string(),
+                slot: "0".to_string(),
+                offset: 0,
+                type_name: "t_uint256".to_string(),
+                zero_semantics: ZeroSemantics::ValidZero,
+            }],
+            types: vec![TypeInfo {
+                label: "t_uint256".to_string(),
+                number_of_bytes: "32".to_string(),
+                encoding: "inplace".to_string(),
+                base: None,
+                key: None,
+                value: None,
+            }],
+            code_variables: Vec::new(),
+        };
+        let layout_file = NamedTempFile::new().expect("Failed to create temp file");
+        std::fs::write(layout_file.path(), serde_json::to_string(&layout).unwrap()).unwrap();
+
+        let out_dir = TempDir::new().expect("Failed to create temp dir");
+        let out_path = out_dir.path().join("results.ndjson");
+        let queries = vec!["balance".to_string(), "missing_field".to_string()];
+
+        cmd_ethereum_batch_resolve(layout_file.path(), &queries, &OutputFormat::Ndjson, None, Some(&out_path))
+            .expect("batch resolve should not abort on per-query failures");
+
+        let content = std::fs::read_to_string(&out_path).unwrap();
+        let records: Vec<Value> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["status"], "resolved");
+        assert_eq!(records[0]["query"], "balance");
+        assert_eq!(records[1]["status"], "failed");
+        assert!(records[1]["error"].is_string());
+    }
+
+    #[cfg(feature = "ethereum")]
+    #[test]
+    fn test_batch_resolve_csv_manifest() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let write_layout = |name: &str, label: &str| {
+            let path = temp_dir.path().join(format!("{}.json", name));
+            let layout = LayoutInfo {
+                contract_name: name.to_string(),
+                storage: vec![StorageEntry {
+                    label: label.to_string(),
+                    slot: "1".to_string(),
+                    offset: 0,
+                    type_name: "t_uint256".to_string(),
+                    zero_semantics: ZeroSemantics::NeverWritten,
+                }],
+                types: vec![TypeInfo {
+                    label: "t_uint256".to_string(),
+                    number_of_bytes: "32".to_string(),
+                    encoding: "inplace".to_string(),
+                    base: None,
+                    key: None,
+                    value: None,
+                }],
+                code_variables: Vec::new(),
+            };
+            std::fs::write(&path, serde_json::to_string(&layout).unwrap()).unwrap();
+            path
+        };
+        let token = write_layout("Token", "totalSupply");
+        let vault = write_layout("Vault", "fee");
+
+        let csv = temp_dir.path().join("queries.csv");
+        std::fs::write(
+            &csv,
+            "contract,query,zero_means\nToken,totalSupply,valid_zero\nVault,fee,\nPool,reserve,\nVault,fee,maybe\n",
+        )
+        .unwrap();
+        let out = temp_dir.path().join("manifest.json");
+
+        cmd_ethereum_batch_resolve_csv(&[&token, &vault], &csv, &OutputFormat::Traverse, None, Some(&out)).unwrap();
+
+        let manifest: Value = serde_json::from_str(&std::fs::read_to_string(&out).unwrap()).unwrap();
+        assert_eq!(manifest["total_queries"], 4);
+        assert_eq!(manifest["resolved"], 2);
+        assert!(manifest["layouts"]["Vault"].is_string());
+
+        let results = manifest["results"].as_array().unwrap();
+        assert_eq!(results[0]["zero_semantics"], "ValidZero");
+        assert_eq!(results[1]["zero_semantics"], "NeverWritten");
+        assert_eq!(results[1]["contract"], "Vault");
+        assert!(results[2]["error"].as_str().unwrap().contains("Unknown contract 'Pool'"));
+        assert_eq!(results[3]["line"], 5);
+    }
+
+    #[test]
+    fn test_parse_block_param() {
+        assert_eq!(parse_block_param("latest").unwrap(), "latest");
+        assert_eq!(parse_block_param("18500000").unwrap(), "0x11a49a0");
+        assert_eq!(parse_block_param("0x10").unwrap(), "0x10");
+        assert!(parse_block_param("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_history_blocks() {
+        assert_eq!(history_blocks(&[], Some(100), Some(350), 100).unwrap(), vec![100, 200, 300]);
+        assert_eq!(history_blocks(&[5, 1], None, None, 100).unwrap(), vec![5, 1]);
+        assert!(history_blocks(&[], None, None, 100).is_err());
+        assert!(history_blocks(&[5], Some(1), Some(9), 1).is_err());
+        assert!(history_blocks(&[], Some(9), Some(1), 1).is_err());
+        assert!(history_blocks(&[], Some(1), Some(9), 0).is_err());
+    }
+
+    #[test]
+    fn test_abi_cache_path() {
+        assert_eq!(
+            abi_cache_path("0xAB", Some(Path::new("layouts/usdt.json"))),
+            Path::new("layouts/usdt.abi.json")
+        );
+        assert_eq!(abi_cache_path("0xAB", None), Path::new("0xab.abi.json"));
+    }
+
+    #[test]
+    fn test_watch_interval_parsing() {
+        assert_eq!("10-blocks".parse::<WatchInterval>().unwrap(), WatchInterval::Blocks(10));
+        assert_eq!("1-block".parse::<WatchInterval>().unwrap(), WatchInterval::Blocks(1));
+        assert_eq!("30s".parse::<WatchInterval>().unwrap(), WatchInterval::Seconds(30));
+        assert_eq!("5m".parse::<WatchInterval>().unwrap(), WatchInterval::Seconds(300));
+        assert!("0-blocks".parse::<WatchInterval>().is_err());
+        assert!("10".parse::<WatchInterval>().is_err());
+        assert!("10d".parse::<WatchInterval>().is_err());
+        assert!(format!("{}h", u64::MAX / 2).parse::<WatchInterval>().is_err());
+    }
+
+    #[test]
+    fn test_tagged_requests_are_not_cached() {
+        assert!(names_block_tag(&json!(["0x1", "0x2", "latest"])));
+        assert!(names_block_tag(&json!(["finalized", false])));
+        assert!(!names_block_tag(&json!(["0x1", "0x2", "0x10"])));
+        assert!(!names_block_tag(&json!([])));
+    }
+
+    #[test]
+    fn test_diff_layouts_gate() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let entry = |label: &str, slot: &str| StorageEntry {
+            label: label.to_string(),
+            slot: slot.to_string(),
+            offset: 0,
+            type_name: "t_uint256".to_string(),
+            zero_semantics: ZeroSemantics::ValidZero,
+        };
+        let write_layout = |name: &str, storage: Vec<StorageEntry>| {
+            let path = temp_dir.path().join(name);
+            let layout = LayoutInfo {
+                contract_name: "Vault".to_string(),
+                storage,
+                types: vec![TypeInfo {
+                    label: "t_uint256".to_string(),
+                    number_of_bytes: "32".to_string(),
+                    encoding: "inplace".to_string(),
+                    base: None,
+                    key: None,
+                    value: None,
+                }],
+                code_variables: Vec::new(),
+            };
+            std::fs::write(&path, serde_json::to_string(&layout).unwrap()).unwrap();
+            path
+        };
+
+        let v1 = write_layout("v1.json", vec![entry("total", "0")]);
+        let v2 = write_layout("v2.json", vec![entry("total", "0"), entry("fee", "1")]);
+        let v3 = write_layout("v3.json", vec![entry("fee", "0"), entry("total", "1")]);
+        let report = temp_dir.path().join("diff.json");
+
+        assert!(!cmd_ethereum_diff_layouts(&v1, &v2, &OutputFormat::Traverse, Some(&report), false).unwrap());
+        assert!(cmd_ethereum_diff_layouts(&v1, &v2, &OutputFormat::Traverse, Some(&report), true).unwrap());
+        assert!(cmd_ethereum_diff_layouts(&v2, &v3, &OutputFormat::CoprocessorJson, Some(&report), false).unwrap());
+
+        let document: Value = serde_json::from_str(&std::fs::read_to_string(&report).unwrap()).unwrap();
+        assert_eq!(document["breaking"], true);
+        assert_eq!(document["changes"][0]["kind"], "moved");
+    }
+
+    #[test]
+    fn test_check_layouts_gate() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let entry = |label: &str, slot: &str, offset: u8, type_name: &str| StorageEntry {
+            label: label.to_string(),
+            slot: slot.to_string(),
+            offset,
+            type_name: type_name.to_string(),
+            zero_semantics: ZeroSemantics::ValidZero,
+        };
+        let inplace = |label: &str, bytes: &str| TypeInfo {
+            label: label.to_string(),
+            number_of_bytes: bytes.to_string(),
+            encoding: "inplace".to_string(),
+            base: None,
+            key: None,
+            value: None,
+        };
+        let layout = |storage: Vec<StorageEntry>| LayoutInfo {
+            contract_name: "Vault".to_string(),
+            storage,
+            types: vec![inplace("t_address", "20"), inplace("t_bool", "1")],
+            code_variables: Vec::new(),
+        };
+        let write_layout = |name: &str, layout: &LayoutInfo| {
+            let path = temp_dir.path().join(name);
+            std::fs::write(&path, serde_json::to_string(layout).unwrap()).unwrap();
+            path
+        };
+
+        // Packed fields share a slot without colliding
+        let v1 = layout(vec![entry("owner", "0", 0, "t_address"), entry("paused", "0", 20, "t_bool")]);
+        validate_layout(&v1).unwrap();
+        let v2 = layout(vec![entry("paused", "0", 0, "t_bool"), entry("owner", "0", 1, "t_address")]);
+        let (v1, v2) = (write_layout("v1.json", &v1), write_layout("v2.json", &v2));
+        let report = temp_dir.path().join("check.json");
+
+        assert!(!cmd_ethereum_check_layouts(&[&v1, &v1], &OutputFormat::Traverse, Some(&report)).unwrap());
+        assert!(cmd_ethereum_check_layouts(&[&v1, &v2], &OutputFormat::CoprocessorJson, Some(&report)).unwrap());
+
+        let document: Value = serde_json::from_str(&std::fs::read_to_string(&report).unwrap()).unwrap();
+        assert_eq!(document["reorders"][0]["labels"], json!(["paused", "owner"]));
+        assert_eq!(document["collisions"][0]["first"]["label"], "owner");
+        assert_eq!(document["collisions"][0]["second"]["label"], "paused");
+    }
+}
@@ -7,10 +7,22 @@
 use clap::{Parser, Subcommand};
 use serde_json::{json, Value};
 use std::process;
-use traverse_cli_core::{CommonArgs, CliResult, CliUtils, OutputFormat};
+use traverse_cli_core::{
+    artifact,
+    batch::{self, BatchInput},
+    cache::{run_cache_command, CacheCommand},
+    chain_id,
+    pool, project,
+    schema::{run_schema_command, SchemaCommand},
+    watch::{self, WatchOptions},
+    witness::{run_witness_command, WitnessCommand},
+    CommonArgs, CliResult, CliUtils, OutputFormat,
+};
 
 
 mod commands;
+#[cfg(all(feature = "ethereum", feature = "server"))]
+mod server;
 
 /// Ethereum-specific CLI arguments
 #[derive(Parser)]
@@ -32,7 +44,7 @@ enum EthereumCommand {
     AnalyzeContract {
         /// Path to the ABI file
         abi_file: String,
-        /// Contract address (optional)
+        /// Contract address or address book name (optional)
         #[arg(long)]
         address: Option<String>,
         /// Enable deep analysis
@@ -43,10 +55,40 @@ enum EthereumCommand {
     /// Compile Ethereum storage layout
     CompileLayout {
         /// Input ABI file path
-        input: String,
+        #[arg(required_unless_present = "address", conflicts_with = "address")]
+        input: Option<String>,
         /// Output layout file path
         #[arg(short, long)]
         output: Option<String>,
+        /// Fetch the ABI from Etherscan or Sourcify instead of reading a file
+        #[arg(long)]
+        address: Option<String>,
+        /// Guess a partial layout from the address's deployed bytecode, for
+        /// unverified contracts; the output is marked heuristic
+        #[arg(long, requires = "address", conflicts_with_all = ["verified", "follow_proxy"])]
+        infer: bool,
+        /// Chain name or ID the address is deployed on
+        #[arg(long, default_value = "mainnet", requires = "address")]
+        chain: String,
+        /// Fetch the ABI again even if a cached copy exists
+        #[arg(long, requires = "address")]
+        refresh: bool,
+        /// Compile the implementation's layout when the address is an EIP-1967 proxy
+        #[arg(long, requires = "address")]
+        follow_proxy: bool,
+        /// RPC endpoint for reading proxy slots or bytecode (defaults to the profile's)
+        #[arg(long, requires = "address")]
+        rpc: Option<String>,
+        /// Fetch the verified contract's exact storage layout instead of its ABI
+        #[arg(long, requires = "address", conflicts_with = "follow_proxy")]
+        verified: bool,
+        /// solc binary for recompiling Etherscan sources, matching their version
+        #[arg(long, requires = "verified")]
+        solc: Option<String>,
+        /// Contract to compile from solc output or a Hardhat build-info file,
+        /// e.g. contracts/Token.sol:Token
+        #[arg(long, requires = "input")]
+        contract: Option<String>,
     },
     
     /// Generate Ethereum storage queries
@@ -62,36 +104,432 @@ enum EthereumCommand {
         /// Query string to resolve
         query: String,
         /// Layout file path
+        #[arg(short, long, required_unless_present = "preset")]
+        layout: Option<String>,
+        /// Built-in layout to resolve against instead of a file: erc20, erc721, erc1155, chainlink-proxy or safe
+        #[arg(long, conflicts_with = "layout")]
+        preset: Option<String>,
+        /// Contract address or address book name
+        #[arg(long)]
+        address: Option<String>,
+        /// Render the result through a Tera template instead of `--format`
+        #[arg(long)]
+        template: Option<String>,
+    },
+    
+    /// Show each step of a query's storage key derivation
+    Explain {
+        /// Query string to explain, e.g. `balances[0x...]`
+        query: String,
+        /// Layout file path
+        #[arg(short, long, required_unless_present = "preset")]
+        layout: Option<String>,
+        /// Built-in layout to explain against instead of a file: erc20, erc721, erc1155, chainlink-proxy or safe
+        #[arg(long, conflicts_with = "layout")]
+        preset: Option<String>,
+        /// Write the trace as JSON in the selected format instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// Generate minimal Rust sources for a compiled layout, or TypeScript request types
+    Codegen {
+        /// Layout file path (all targets but typescript)
+        #[arg(short, long)]
+        layout: Option<String>,
+        /// Built-in layout to generate for instead of a file: erc20, erc721, erc1155, chainlink-proxy or safe
+        #[arg(long, conflicts_with = "layout")]
+        preset: Option<String>,
+        /// What to generate
+        #[arg(long, value_enum, default_value_t = commands::CodegenTarget::Combined)]
+        target: commands::CodegenTarget,
+        /// Only generate what this query needs (repeatable; query and verifier targets)
+        #[arg(long = "query")]
+        queries: Vec<String>,
+        /// Also generate zod validators (typescript target)
+        #[arg(long)]
+        zod: bool,
+    },
+    
+    /// Compare two compiled layouts and fail on breaking changes
+    ///
+    /// Exits with status 2 when a field was moved, retyped or removed.
+    DiffLayouts {
+        /// Old layout file path
+        old: String,
+        /// New layout file path
+        new: String,
+        /// Also fail on compatible changes (added fields, semantics updates)
+        #[arg(long)]
+        strict: bool,
+    },
+    
+    /// Check layouts sharing storage for overlapping fields
+    ///
+    /// Give versions of an upgradeable contract in upgrade order, or a proxy
+    /// and its implementation. Exits with status 2 when fields overlap or
+    /// shared fields changed order, as when base contracts are reordered.
+    CheckLayouts {
+        /// Layout file paths
+        #[arg(required = true)]
+        layouts: Vec<String>,
+    },
+    
+    /// Verify an eth_getProof response offline against a state root
+    ///
+    /// Exits with status 2 when any part of the proof fails to verify.
+    VerifyProof {
+        /// Proof file (eth_getProof result or JSON-RPC response)
+        #[arg(long)]
+        proof: String,
+        /// Layout file used to label the proven storage keys
         #[arg(short, long)]
         layout: String,
-        /// Contract address
+        /// State root of the block the proof was taken at
         #[arg(long)]
-        address: Option<String>,
+        state_root: String,
+        /// Queries that must be covered by the proof
+        #[arg(long = "query")]
+        queries: Vec<String>,
+    },
+    
+    /// Spot-check a layout against a contract's current storage
+    ///
+    /// Exits with status 2 when any entry's value contradicts the layout.
+    VerifyLayout {
+        /// Contract address or address book name
+        #[arg(long, alias = "contract")]
+        address: String,
+        /// Layout file to check (defaults to the address book entry's layout)
+        #[arg(short, long)]
+        layout: Option<String>,
+        /// RPC endpoint (defaults to the profile's endpoint)
+        #[arg(long)]
+        rpc: Option<String>,
+        /// Block number or tag (latest if not specified)
+        #[arg(long)]
+        block: Option<String>,
+    },
+    
+    /// Resolve many Ethereum storage queries against one layout
+    BatchResolve {
+        /// Layout file path (repeat for CSV input covering several contracts)
+        #[arg(short, long, required = true)]
+        layout: Vec<String>,
+        /// Queries to resolve
+        queries: Vec<String>,
+        /// File with one query per line, or CSV rows with `--input csv`
+        #[arg(long)]
+        queries_file: Option<String>,
+        /// Format of the queries file
+        #[arg(long, value_enum, default_value_t = BatchInput::Lines)]
+        input: BatchInput,
+        /// Render the result through a Tera template instead of `--format`
+        #[arg(long)]
+        template: Option<String>,
     },
     
     /// Generate Ethereum proof
     GenerateProof {
-        /// Contract address
+        /// Contract address or address book name
+        #[arg(long, alias = "contract")]
+        address: String,
+        /// Storage query, or a storage slot when no layout is given
+        #[arg(long, alias = "slot")]
+        query: String,
+        /// Layout file used to resolve the query (defaults to the address book entry's layout)
+        #[arg(short, long)]
+        layout: Option<String>,
+        /// Built-in layout to resolve the query against: erc20, erc721, erc1155, chainlink-proxy or safe
+        #[arg(long, conflicts_with = "layout")]
+        preset: Option<String>,
+        /// RPC endpoint (defaults to the profile's endpoint)
+        #[arg(long)]
+        rpc: Option<String>,
+        /// Block number or tag (latest if not specified)
+        #[arg(long, alias = "block-number")]
+        block: Option<String>,
+        /// Zero semantics to declare, overriding the layout
         #[arg(long)]
+        zero_means: Option<String>,
+        /// Check the declared zero semantics against the slot's history (exact on Alchemy endpoints, sampled elsewhere)
+        #[arg(long)]
+        validate_semantics: bool,
+        /// Print the RPC requests the proof needs instead of sending them
+        #[arg(long)]
+        dry_run: bool,
+        /// Render the result through a Tera template instead of `--format`
+        #[arg(long)]
+        template: Option<String>,
+    },
+    
+    /// Prove a transaction's log through the block's receipts trie
+    ProveLog {
+        /// Hash of the transaction that emitted the log
+        #[arg(long)]
+        tx_hash: String,
+        /// Position of the log among the transaction's logs
+        #[arg(long, default_value_t = 0)]
+        log_index: usize,
+        /// RPC endpoint (defaults to the profile's endpoint)
+        #[arg(long)]
+        rpc: Option<String>,
+    },
+    
+    /// Prove a transaction's inclusion through the block's transactions trie
+    ProveTransaction {
+        /// Hash of the transaction
+        #[arg(long)]
+        tx_hash: String,
+        /// RPC endpoint (defaults to the profile's endpoint)
+        #[arg(long)]
+        rpc: Option<String>,
+    },
+    
+    /// Prove Arbitrum storage at the block the chain's rollup last confirmed on L1
+    ProveArbitrum {
+        /// Contract address on the Arbitrum chain, or address book name
+        #[arg(long, alias = "contract")]
         address: String,
-        /// Storage slot query
+        /// Storage slot to prove (repeatable)
+        #[arg(long = "slot", required = true)]
+        slots: Vec<String>,
+        /// Rollup contract address on L1
         #[arg(long)]
-        query: String,
-        /// RPC endpoint
+        rollup: String,
+        /// L1 RPC endpoint the rollup is read through
+        #[arg(long)]
+        l1_rpc: String,
+        /// L1 block or tag to read the rollup at
+        #[arg(long, default_value = "finalized")]
+        l1_block: String,
+        /// The rollup confirms BoLD assertions rather than classic nodes
+        #[arg(long)]
+        bold: bool,
+        /// Arbitrum RPC endpoint (defaults to the profile's endpoint)
+        #[arg(long)]
+        rpc: Option<String>,
+    },
+    
+    /// Prove OP Stack storage chained to the block's L2 output root
+    ProveOptimism {
+        /// Contract address on the OP Stack chain, or address book name
+        #[arg(long, alias = "contract")]
+        address: String,
+        /// Storage slot to prove (repeatable)
+        #[arg(long = "slot", required = true)]
+        slots: Vec<String>,
+        /// L2 block of an output root posted on L1 (latest if not specified)
+        #[arg(long, alias = "block-number")]
+        block: Option<String>,
+        /// Output root posted on L1 to check the proof against
         #[arg(long)]
-        rpc: String,
-        /// Block number (latest if not specified)
+        output_root: Option<String>,
+        /// RPC endpoint (defaults to the profile's endpoint)
         #[arg(long)]
-        block: Option<u64>,
+        rpc: Option<String>,
+    },
+    
+    /// Prove storage at the execution block of the latest finalized beacon checkpoint
+    ProveFinalized {
+        /// Contract address, or address book name
+        #[arg(long, alias = "contract")]
+        address: String,
+        /// Storage slot to prove (repeatable)
+        #[arg(long = "slot", required = true)]
+        slots: Vec<String>,
+        /// Beacon node API endpoint finality is read from
+        #[arg(long)]
+        beacon: String,
+        /// Finalized checkpoint root to require, from a trusted source
+        #[arg(long)]
+        checkpoint_root: Option<String>,
+        /// RPC endpoint (defaults to the profile's endpoint)
+        #[arg(long)]
+        rpc: Option<String>,
+    },
+    
+    /// Prove storage slots at a range or list of blocks, as one ordered batch
+    ProveHistory {
+        /// Contract address, or address book name
+        #[arg(long, alias = "contract")]
+        address: String,
+        /// Storage slot to prove (repeatable)
+        #[arg(long = "slot", required = true)]
+        slots: Vec<String>,
+        /// Block to prove at (repeatable); alternative to --from/--to
+        #[arg(long = "block")]
+        blocks: Vec<u64>,
+        /// First block of the range
+        #[arg(long)]
+        from: Option<u64>,
+        /// Last block of the range (inclusive)
+        #[arg(long)]
+        to: Option<u64>,
+        /// Blocks between samples in the range
+        #[arg(long, default_value = "100")]
+        step: u64,
+        /// RPC endpoint (defaults to the profile's endpoint)
+        #[arg(long)]
+        rpc: Option<String>,
+    },
+    
+    /// Prove zkSync Era storage against an L1 batch's tree root
+    ProveZksync {
+        /// Contract address on Era, or address book name
+        #[arg(long, alias = "contract")]
+        address: String,
+        /// Storage slot to prove (repeatable)
+        #[arg(long = "slot", required = true)]
+        slots: Vec<String>,
+        /// L1 batch whose tree root to prove against (latest if not specified)
+        #[arg(long)]
+        l1_batch: Option<u64>,
+        /// RPC endpoint (defaults to the profile's endpoint)
+        #[arg(long)]
+        rpc: Option<String>,
+    },
+    
+    /// Prove storage from a Verkle block's execution witness (EIP-6800)
+    ///
+    /// Needs a Bandersnatch backend, which is not available yet: the witness
+    /// is fetched and checked, then the command fails saying so.
+    ProveVerkle {
+        /// Contract address, or address book name
+        #[arg(long, alias = "contract")]
+        address: String,
+        /// Storage slot to prove (repeatable)
+        #[arg(long = "slot", required = true)]
+        slots: Vec<String>,
+        /// Block whose execution witness to read
+        #[arg(long, default_value = "latest")]
+        block: String,
+        /// RPC endpoint (defaults to the profile's endpoint)
+        #[arg(long)]
+        rpc: Option<String>,
+    },
+    
+    /// Regenerate proofs periodically and deliver them to a webhook
+    WatchProof {
+        /// Watch config listing contracts, layouts and queries
+        proofs: String,
+        /// Interval between rounds, e.g. 10-blocks, 30s or 5m
+        #[arg(long, default_value = "1-block")]
+        every: String,
+        /// URL the verification requests are POSTed to (stdout if not specified)
+        #[arg(long)]
+        webhook: Option<String>,
+        /// RPC endpoint (defaults to the profile's endpoint)
+        #[arg(long)]
+        rpc: Option<String>,
+        /// Seconds between chain head polls for block intervals, or before resubscribing
+        #[arg(long, default_value_t = 12)]
+        poll_secs: u64,
+        /// Run a single round and exit
+        #[arg(long)]
+        once: bool,
+        /// WebSocket endpoint to follow new heads on instead of polling
+        #[arg(long, value_name = "WS_URL")]
+        subscribe: Option<String>,
     },
     
     /// Auto-generate for Ethereum contracts
     AutoGenerate {
-        /// Configuration file path
-        config: String,
+        /// Configuration file path (JSON with `abi`, `contract` and `queries`, or a multi-chain project)
+        #[arg(value_name = "CONFIG")]
+        config_file: String,
         /// Output directory
         #[arg(short, long, default_value = "output")]
         output_dir: String,
+        /// RPC endpoint (defaults to the profile's endpoint)
+        #[arg(long)]
+        rpc: Option<String>,
+        /// Resolve queries offline and write the RPC plan instead of proof templates
+        #[arg(long)]
+        dry_run: bool,
+        /// Run only this chain's section of a project config
+        #[arg(long, hide = true)]
+        only: Option<String>,
+    },
+    
+    /// Auto-generate many contracts on a bounded worker pool
+    ///
+    /// With `--format ndjson`, each entry\'s report is streamed to stdout as it finishes.
+    BatchGenerate {
+        /// Batch config: a JSON list of auto-generate entries, or a project config
+        #[arg(value_name = "CONFIG")]
+        config_file: String,
+        /// Output directory; each entry is generated into `<output-dir>/<name>`
+        #[arg(short, long, default_value = "output")]
+        output_dir: String,
+        /// RPC endpoint for entries without their own (defaults to the profile's endpoint)
+        #[arg(long)]
+        rpc: Option<String>,
+        /// Resolve queries offline and write each entry's RPC plan instead of proof templates
+        #[arg(long)]
+        dry_run: bool,
+        /// Number of entries to generate at once
+        #[arg(long, default_value_t = 1)]
+        parallel: usize,
+        /// Maximum RPC requests in flight across all workers
+        #[arg(long)]
+        max_concurrency: Option<usize>,
+        /// Skip entries a previous run of this batch completed, per its checkpoint
+        #[arg(long)]
+        resume: bool,
+    },
+
+    /// Regenerate batch entries whenever the config or their inputs change
+    Watch {
+        /// Batch config: a JSON list of auto-generate entries, or a project config
+        #[arg(value_name = "CONFIG")]
+        config_file: String,
+        /// Output directory; each entry is generated into `<output-dir>/<name>`
+        #[arg(short, long, default_value = "output")]
+        output_dir: String,
+        /// RPC endpoint for entries without their own (defaults to the profile's endpoint)
+        #[arg(long)]
+        rpc: Option<String>,
+        /// Resolve queries and write each entry's RPC plan instead of fetching proofs
+        #[arg(long)]
+        dry_run: bool,
+        /// URL change reports are POSTed to (stdout if not specified)
+        #[arg(long)]
+        webhook: Option<String>,
+        /// Milliseconds to let a burst of file events settle before rehashing
+        #[arg(long, default_value_t = 300)]
+        debounce_ms: u64,
+    },
+    
+    /// Decode a binary or base64 artifact back to JSON
+    Decode {
+        /// Artifact file path
+        input: String,
+    },
+    
+    /// Export JSON Schemas for the CLI output formats
+    Schema {
+        #[command(subcommand)]
+        action: SchemaCommand,
+    },
+    
+    /// Inspect or clear the RPC response cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheCommand,
+    },
+    
+    /// Inspect controller witnesses
+    Witness {
+        #[command(subcommand)]
+        action: WitnessCommand,
+    },
+    
+    /// Serve the Ethereum commands over HTTP
+    Serve {
+        /// Address to bind
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        bind: String,
     },
 }
 
@@ -122,7 +560,7 @@ async fn analyze_contract(abi_file: &str, address: Option<&str>, deep: bool) ->
 }
 
 #[cfg(feature = "ethereum")]
-fn compile_layout(input: &str, output: Option<&str>) -> CliResult<()> {
+fn compile_layout(input: &str, output: Option<&str>, contract: Option<&str>) -> CliResult<()> {
     use std::path::Path;
     
     // Call the command implementation
@@ -131,6 +569,7 @@ fn compile_layout(input: &str, output: Option<&str>) -> CliResult<()> {
         output.map(Path::new),
         &OutputFormat::Traverse,
         true, // validate
+        contract,
     );
     
     match result {
@@ -139,16 +578,73 @@ fn compile_layout(input: &str, output: Option<&str>) -> CliResult<()> {
     }
 }
 
+/// Fetch an ABI into its cache file and return the cache path
+async fn fetch_abi(
+    address: &str,
+    chain: &str,
+    api_key: Option<&str>,
+    output: Option<&str>,
+    refresh: bool,
+    proxy_rpc: Option<&str>,
+) -> CliResult<String> {
+    use std::path::Path;
+
+    let cache = commands::abi_cache_path(address, output.map(Path::new));
+    commands::cmd_ethereum_fetch_abi(address, chain, api_key, &cache, refresh, proxy_rpc)
+        .await
+        .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))?;
+    Ok(cache.to_string_lossy().into_owned())
+}
+
+/// Fetch a verified layout into its cache file and return the cache path
+async fn fetch_verified_layout(
+    address: &str,
+    chain: &str,
+    api_key: Option<&str>,
+    solc: Option<&str>,
+    output: Option<&str>,
+    refresh: bool,
+) -> CliResult<String> {
+    use std::path::Path;
+
+    // `usdt.json` caches `usdt.verified.json`, as ABIs are cached
+    let cache = match output {
+        Some(output) => Path::new(output).with_extension("verified.json"),
+        None => std::path::PathBuf::from(format!("{}.verified.json", address.to_lowercase())),
+    };
+    commands::cmd_ethereum_fetch_verified_layout(address, chain, api_key, solc.map(Path::new), &cache, refresh)
+        .await
+        .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))?;
+    Ok(cache.to_string_lossy().into_owned())
+}
+
+/// The layout named by `--layout` or `--preset`, the preset first
+fn layout_source<'a>(layout: Option<&'a str>, preset: Option<&'a str>) -> Option<commands::LayoutSource<'a>> {
+    match (preset, layout) {
+        (Some(name), _) => Some(commands::LayoutSource::Preset(name)),
+        (None, Some(path)) => Some(commands::LayoutSource::File(std::path::Path::new(path))),
+        (None, None) => None,
+    }
+}
+
 #[cfg(feature = "ethereum")]
-async fn resolve_query(query: &str, layout_file: &str, address: Option<&str>) -> CliResult<Value> {
+async fn resolve_query(
+    query: &str,
+    layout: commands::LayoutSource<'_>,
+    address: Option<&str>,
+    template: Option<&str>,
+    output: Option<&str>,
+) -> CliResult<Value> {
     use std::path::Path;
     
     // Call the command implementation
     let result = commands::cmd_ethereum_resolve_query(
         query,
-        Path::new(layout_file),
+        layout,
         &OutputFormat::CoprocessorJson,
-        None, // output handled by caller
+        template.map(Path::new),
+        // A rendered report goes to --output; otherwise the caller writes the status there
+        template.and(output).map(Path::new),
         address,
         None, // rpc
     ).await;
@@ -156,18 +652,296 @@ async fn resolve_query(query: &str, layout_file: &str, address: Option<&str>) ->
     match result {
         Ok(()) => {
             // For now, return a placeholder result since the command writes to output
-            Ok(json!({
+            let mut result = json!({
                 "status": "success",
                 "query": query,
-                "layout_file": layout_file,
                 "address": address.unwrap_or("not_specified"),
                 "resolved": true
-            }))
+            });
+            match layout {
+                commands::LayoutSource::File(path) => result["layout_file"] = json!(path.display().to_string()),
+                commands::LayoutSource::Preset(name) => result["preset"] = json!(name),
+            }
+            Ok(result)
         }
         Err(e) => Err(traverse_cli_core::CliError::Processing(e.to_string()))
     }
 }
 
+fn batch_resolve(
+    layout_file: &str,
+    queries: &[String],
+    format: &OutputFormat,
+    template: Option<&str>,
+    output: Option<&str>,
+) -> CliResult<()> {
+    use std::path::Path;
+
+    commands::cmd_ethereum_batch_resolve(
+        Path::new(layout_file),
+        queries,
+        format,
+        template.map(Path::new),
+        output.map(Path::new),
+    )
+    .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn generate_proof(
+    address: &str,
+    query: &str,
+    layout: Option<commands::LayoutSource<'_>>,
+    rpc: &str,
+    block: Option<&str>,
+    zero_means: Option<traverse_core::ZeroSemantics>,
+    validate_semantics: bool,
+    dry_run: bool,
+    format: &OutputFormat,
+    template: Option<&str>,
+    output: Option<&str>,
+) -> CliResult<()> {
+    use std::path::Path;
+
+    commands::cmd_ethereum_generate_proof(
+        address,
+        query,
+        layout,
+        rpc,
+        block,
+        zero_means,
+        validate_semantics,
+        dry_run,
+        format,
+        template.map(Path::new),
+        output.map(Path::new),
+    )
+    .await
+    .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))
+}
+
+/// Run `auto-generate` for a single-contract config or a project config
+///
+/// For a project, the Ethereum section runs here and the other sections are
+/// dispatched to their CLIs unless `only` restricts the run to this chain.
+async fn auto_generate(
+    config: &str,
+    profile: &traverse_cli_core::Profile,
+    rpc: Option<&str>,
+    output_dir: &str,
+    dry_run: bool,
+    only: Option<&str>,
+    dispatch: &project::DispatchOptions,
+) -> CliResult<()> {
+    use std::path::Path;
+
+    let config_path = Path::new(config);
+    let value = CliUtils::load_config(config_path)?;
+    let Some(project) = project::ProjectConfig::from_value(&value)? else {
+        return auto_generate_entry(value, config_path, profile, rpc, Path::new(output_dir).to_path_buf(), dry_run).await;
+    };
+    if let Some(chain) = only.filter(|chain| *chain != "ethereum") {
+        return Err(traverse_cli_core::CliError::InvalidArgument(format!(
+            "traverse-ethereum cannot run the {} section",
+            chain
+        )));
+    }
+
+    let own = project::run_section("ethereum", &project, Path::new(output_dir), |entry, entry_dir| {
+        auto_generate_entry(entry, config_path, profile, rpc, entry_dir, dry_run)
+    })
+    .await?;
+    if only.is_some() {
+        return Ok(());
+    }
+
+    let manifest = project::write_manifest(&project, config_path, Path::new(output_dir), own, dispatch)?;
+    if !manifest.is_ok() {
+        return Err(traverse_cli_core::CliError::Processing(format!(
+            "Some project entries failed; see {}",
+            Path::new(output_dir).join(project::PROJECT_MANIFEST).display()
+        )));
+    }
+    Ok(())
+}
+
+async fn auto_generate_entry(
+    entry: Value,
+    config_path: &std::path::Path,
+    profile: &traverse_cli_core::Profile,
+    rpc: Option<&str>,
+    output_dir: std::path::PathBuf,
+    dry_run: bool,
+) -> CliResult<()> {
+    use std::path::Path;
+
+    let config: commands::AutoGenerateConfig = serde_json::from_value(entry)
+        .map_err(|e| traverse_cli_core::CliError::Configuration(format!("Invalid auto-generate config: {}", e)))?;
+    let rpc = profile.require_rpc(config.rpc.as_deref().or(rpc), "ethereum")?;
+    if !dry_run {
+        chain_id::verify(&rpc, || commands::fetch_chain_id(&rpc)).await?;
+    }
+    let contract = profile.resolve_address(&config.contract, "ethereum")?.address;
+    let abi = config_path.parent().unwrap_or(Path::new("")).join(&config.abi);
+
+    commands::cmd_ethereum_auto_generate(
+        &abi,
+        &rpc,
+        &contract,
+        &config.queries.join(","),
+        &output_dir,
+        false,
+        dry_run,
+    )
+    .await
+    .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))
+}
+
+/// Run `batch-generate`, failing if any entry failed
+async fn batch_generate(
+    config: &str,
+    profile: &traverse_cli_core::Profile,
+    rpc: Option<&str>,
+    output_dir: &str,
+    dry_run: bool,
+    options: &pool::PoolOptions,
+) -> CliResult<()> {
+    use std::path::Path;
+
+    let config_path = Path::new(config);
+    let entries = pool::batch_entries(&CliUtils::load_config(config_path)?, "ethereum")?;
+    if let Some(max_concurrency) = options.max_concurrency {
+        pool::limit_rpc(max_concurrency);
+    }
+
+    let output_dir = Path::new(output_dir);
+    let settings = serde_json::json!({ "dry_run": dry_run, "rpc": rpc });
+    let summary = pool::run_batch(&entries, output_dir, options, settings, |entry, entry_dir| {
+        auto_generate_entry(entry, config_path, profile, rpc, entry_dir, dry_run)
+    })
+    .await?;
+    pool::print_summary(&summary, output_dir);
+    if !summary.is_ok() {
+        return Err(traverse_cli_core::CliError::Processing(format!(
+            "{} of {} entries failed",
+            summary.failed, summary.total
+        )));
+    }
+    Ok(())
+}
+
+/// Run `watch`, regenerating entries as the config and their inputs change
+async fn watch_entries(
+    config: &str,
+    profile: &traverse_cli_core::Profile,
+    rpc: Option<&str>,
+    output_dir: &str,
+    dry_run: bool,
+    options: &WatchOptions,
+) -> CliResult<()> {
+    use std::path::Path;
+
+    let config_path = Path::new(config);
+    watch::run_watch(config_path, Path::new(output_dir), options, |entry, entry_dir| {
+        auto_generate_entry(entry, config_path, profile, rpc, entry_dir, dry_run)
+    })
+    .await?;
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn watch_proof(
+    proofs: &str,
+    profile: &traverse_cli_core::Profile,
+    rpc: &str,
+    every: &str,
+    webhook: Option<&str>,
+    poll_secs: u64,
+    once: bool,
+    subscribe: Option<&str>,
+) -> CliResult<()> {
+    use std::path::Path;
+
+    let every: commands::WatchInterval = every
+        .parse()
+        .map_err(|e: anyhow::Error| traverse_cli_core::CliError::InvalidArgument(e.to_string()))?;
+    commands::cmd_ethereum_watch_proof(Path::new(proofs), profile, rpc, every, webhook, poll_secs, once, subscribe)
+        .await
+        .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))
+}
+
+fn batch_resolve_csv(
+    layout_files: &[String],
+    csv_file: &str,
+    format: &OutputFormat,
+    template: Option<&str>,
+    output: Option<&str>,
+) -> CliResult<()> {
+    use std::path::Path;
+
+    let layout_files: Vec<&Path> = layout_files.iter().map(Path::new).collect();
+    let template = template.map(Path::new);
+    commands::cmd_ethereum_batch_resolve_csv(&layout_files, Path::new(csv_file), format, template, output.map(Path::new))
+        .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))
+}
+
+fn diff_layouts(old: &str, new: &str, format: &OutputFormat, output: Option<&str>, strict: bool) -> CliResult<bool> {
+    use std::path::Path;
+
+    commands::cmd_ethereum_diff_layouts(
+        Path::new(old),
+        Path::new(new),
+        format,
+        output.map(Path::new),
+        strict,
+    )
+    .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))
+}
+
+fn check_layouts(layouts: &[String], format: &OutputFormat, output: Option<&str>) -> CliResult<bool> {
+    use std::path::Path;
+
+    let layouts: Vec<&Path> = layouts.iter().map(Path::new).collect();
+    commands::cmd_ethereum_check_layouts(&layouts, format, output.map(Path::new))
+        .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))
+}
+
+fn verify_proof(
+    proof: &str,
+    layout: &str,
+    state_root: &str,
+    queries: &[String],
+    format: &OutputFormat,
+    output: Option<&str>,
+) -> CliResult<bool> {
+    use std::path::Path;
+
+    commands::cmd_ethereum_verify_proof(
+        Path::new(proof),
+        Path::new(layout),
+        state_root,
+        queries,
+        format,
+        output.map(Path::new),
+    )
+    .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))
+}
+
+async fn verify_layout(
+    address: &str,
+    layout: &str,
+    rpc: &str,
+    block: Option<&str>,
+    format: &OutputFormat,
+    output: Option<&str>,
+) -> CliResult<bool> {
+    use std::path::Path;
+
+    commands::cmd_ethereum_verify_layout(Path::new(layout), address, rpc, block, format, output.map(Path::new))
+        .await
+        .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))
+}
+
 #[cfg(not(feature = "ethereum"))]
 async fn analyze_contract(_abi_file: &str, _address: Option<&str>, _deep: bool) -> CliResult<Value> {
     Err(traverse_cli_core::CliError::Configuration(
@@ -176,34 +950,111 @@ async fn analyze_contract(_abi_file: &str, _address: Option<&str>, _deep: bool)
 }
 
 #[cfg(not(feature = "ethereum"))]
-fn compile_layout(_input: &str, _output: Option<&str>) -> CliResult<()> {
+fn compile_layout(_input: &str, _output: Option<&str>, _contract: Option<&str>) -> CliResult<()> {
     Err(traverse_cli_core::CliError::Configuration(
         "Ethereum support not enabled. Build with --features ethereum".to_string()
     ))
 }
 
 #[cfg(not(feature = "ethereum"))]
-async fn resolve_query(_query: &str, _layout_file: &str, _address: Option<&str>) -> CliResult<Value> {
+async fn resolve_query(
+    _query: &str,
+    _layout: commands::LayoutSource<'_>,
+    _address: Option<&str>,
+    _template: Option<&str>,
+    _output: Option<&str>,
+) -> CliResult<Value> {
     Err(traverse_cli_core::CliError::Configuration(
         "Ethereum support not enabled. Build with --features ethereum".to_string()
     ))
 }
 
+fn explain(
+    query: &str,
+    layout: commands::LayoutSource<'_>,
+    json: bool,
+    format: &OutputFormat,
+    output: Option<&str>,
+) -> CliResult<()> {
+    use std::path::Path;
+
+    commands::cmd_ethereum_explain(query, layout, json, format, output.map(Path::new))
+        .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))
+}
+
+fn codegen(
+    layout: Option<commands::LayoutSource<'_>>,
+    target: commands::CodegenTarget,
+    queries: &[String],
+    zod: bool,
+    output: Option<&str>,
+) -> CliResult<()> {
+    use std::path::Path;
+
+    commands::cmd_ethereum_codegen(layout, target, queries, zod, output.map(Path::new))
+        .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))
+}
+
+#[cfg(all(feature = "ethereum", feature = "server"))]
+async fn serve(profile: traverse_cli_core::Profile, bind: &str) -> CliResult<()> {
+    traverse_cli_core::server::ApiServer::new(profile)
+        .with_chain("ethereum", server::EthereumHandler)
+        .serve(bind)
+        .await
+}
+
+#[cfg(not(all(feature = "ethereum", feature = "server")))]
+async fn serve(_profile: traverse_cli_core::Profile, _bind: &str) -> CliResult<()> {
+    Err(traverse_cli_core::CliError::Processing(
+        "Server mode not enabled. Build with --features ethereum,server".to_string()
+    ))
+}
+
 async fn handle_command(args: EthereumArgs) -> CliResult<()> {
     // Set verbose mode
     if args.common.verbose {
         std::env::set_var("VERBOSE", "1");
     }
+
+    let profile = args.common.load_profile()?;
+    let format = args.common.output_format(&profile);
+    args.common.install_cache(&profile);
+    args.common.install_chain_id(&profile, "ethereum");
+    commands::install_rpc_failover(&profile);
     
     match args.command {
         EthereumCommand::AnalyzeContract { abi_file, address, deep } => {
+            let address = address.map(|a| profile.resolve_address(&a, "ethereum")).transpose()?.map(|e| e.address);
             let result = analyze_contract(&abi_file, address.as_deref(), deep).await?;
-            let output = CliUtils::format_json(&result, &args.common.format)?;
-            CliUtils::write_output(&output, args.common.output.as_deref())?;
+            CliUtils::write_value(&result, &format, args.common.output.as_deref())?;
         }
         
-        EthereumCommand::CompileLayout { input, output } => {
-            compile_layout(&input, output.as_deref())?;
+        EthereumCommand::CompileLayout { input, output, address, infer, chain, refresh, follow_proxy, rpc, verified, solc, contract } => {
+            let input = match (input, address) {
+                (None, Some(address)) if infer => {
+                    let rpc = profile.require_rpc(rpc.as_deref(), "ethereum")?;
+                    chain_id::verify(&rpc, || commands::fetch_chain_id(&rpc)).await?;
+                    let address = profile.resolve_address(&address, "ethereum")?.address;
+                    commands::cmd_ethereum_infer_layout(&address, &rpc, output.as_deref().map(std::path::Path::new))
+                        .await
+                        .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))?;
+                    return Ok(());
+                }
+                (Some(input), _) => input,
+                (None, Some(address)) if verified => {
+                    let address = profile.resolve_address(&address, "ethereum")?.address;
+                    let api_key = profile.api_key("etherscan");
+                    fetch_verified_layout(&address, &chain, api_key, solc.as_deref(), output.as_deref(), refresh).await?
+                }
+                (None, Some(address)) => {
+                    let address = profile.resolve_address(&address, "ethereum")?.address;
+                    let api_key = profile.api_key("etherscan");
+                    let proxy_rpc = follow_proxy.then(|| profile.require_rpc(rpc.as_deref(), "ethereum")).transpose()?;
+                    fetch_abi(&address, &chain, api_key, output.as_deref(), refresh, proxy_rpc.as_deref()).await?
+                }
+                (None, None) => unreachable!("clap requires an input or --address"),
+            };
+            compile_layout(&input, output.as_deref(), contract.as_deref())?;
         }
         
         EthereumCommand::GenerateQueries { layout, patterns } => {
@@ -212,39 +1063,279 @@ async fn handle_command(args: EthereumArgs) -> CliResult<()> {
                 "patterns": patterns,
                 "note": "Query generation implementation would go here"
             });
-            let output = CliUtils::format_json(&result, &args.common.format)?;
-            CliUtils::write_output(&output, args.common.output.as_deref())?;
+            CliUtils::write_value(&result, &format, args.common.output.as_deref())?;
         }
         
-        EthereumCommand::ResolveQuery { query, layout, address } => {
-            let result = resolve_query(&query, &layout, address.as_deref()).await?;
-            let output = CliUtils::format_json(&result, &args.common.format)?;
-            CliUtils::write_output(&output, args.common.output.as_deref())?;
+        EthereumCommand::ResolveQuery { query, layout, preset, address, template } => {
+            let address = address.map(|a| profile.resolve_address(&a, "ethereum")).transpose()?.map(|e| e.address);
+            let output = args.common.output.as_deref();
+            let layout = layout_source(layout.as_deref(), preset.as_deref()).expect("clap requires --layout or --preset");
+            let result = resolve_query(&query, layout, address.as_deref(), template.as_deref(), output).await?;
+            if template.is_none() {
+                CliUtils::write_value(&result, &format, output)?;
+            }
         }
         
-        EthereumCommand::GenerateProof { address, query, rpc, block } => {
-            let result = json!({
-                "address": address,
-                "query": query,
-                "rpc": rpc,
-                "block": block,
-                "note": "Proof generation implementation would go here"
-            });
-            let output = CliUtils::format_json(&result, &args.common.format)?;
-            CliUtils::write_output(&output, args.common.output.as_deref())?;
+        EthereumCommand::Explain { query, layout, preset, json } => {
+            let layout = layout_source(layout.as_deref(), preset.as_deref()).expect("clap requires --layout or --preset");
+            explain(&query, layout, json, &format, args.common.output.as_deref())?;
         }
         
-        EthereumCommand::AutoGenerate { config, output_dir } => {
-            let _config_data = CliUtils::load_config(&config)?;
-            CliUtils::ensure_output_dir(&output_dir)?;
+        EthereumCommand::Codegen { layout, preset, target, queries, zod } => {
+            let layout = layout_source(layout.as_deref(), preset.as_deref());
+            codegen(layout, target, &queries, zod, args.common.output.as_deref())?;
+        }
+        
+        EthereumCommand::DiffLayouts { old, new, strict } => {
+            if diff_layouts(&old, &new, &format, args.common.output.as_deref(), strict)? {
+                process::exit(2);
+            }
+        }
+        
+        EthereumCommand::CheckLayouts { layouts } => {
+            if check_layouts(&layouts, &format, args.common.output.as_deref())? {
+                process::exit(2);
+            }
+        }
+        
+        EthereumCommand::VerifyProof { proof, layout, state_root, queries } => {
+            if !verify_proof(&proof, &layout, &state_root, &queries, &format, args.common.output.as_deref())? {
+                process::exit(2);
+            }
+        }
+        
+        EthereumCommand::VerifyLayout { address, layout, rpc, block } => {
+            let rpc = profile.require_rpc(rpc.as_deref(), "ethereum")?;
+            chain_id::verify(&rpc, || commands::fetch_chain_id(&rpc)).await?;
+            let entry = profile.resolve_address(&address, "ethereum")?;
+            let layout = layout.or(entry.layout).ok_or_else(|| {
+                traverse_cli_core::CliError::InvalidArgument(format!("No layout for {}. Pass --layout", address))
+            })?;
+            if verify_layout(&entry.address, &layout, &rpc, block.as_deref(), &format, args.common.output.as_deref()).await? {
+                process::exit(2);
+            }
+        }
+        
+        EthereumCommand::BatchResolve { layout, mut queries, queries_file, input, template } => {
+            if input == BatchInput::Csv {
+                let path = queries_file.ok_or_else(|| {
+                    traverse_cli_core::CliError::InvalidArgument("--input csv requires --queries-file".to_string())
+                })?;
+                if !queries.is_empty() {
+                    return Err(traverse_cli_core::CliError::InvalidArgument(
+                        "Positional queries cannot be combined with --input csv".to_string()
+                    ));
+                }
+                batch_resolve_csv(&layout, &path, &format, template.as_deref(), args.common.output.as_deref())?;
+                return Ok(());
+            }
             
-            let result = json!({
-                "config": config,
-                "output_dir": output_dir,
-                "note": "Auto-generation implementation would go here"
-            });
-            let output = CliUtils::format_json(&result, &args.common.format)?;
-            CliUtils::write_output(&output, args.common.output.as_deref())?;
+            let [layout] = layout.as_slice() else {
+                return Err(traverse_cli_core::CliError::InvalidArgument(
+                    "Line input resolves against a single --layout; use --input csv for several contracts".to_string()
+                ));
+            };
+            if let Some(path) = queries_file {
+                queries.extend(batch::parse_lines(&CliUtils::read_file(&path)?));
+            }
+            if queries.is_empty() {
+                return Err(traverse_cli_core::CliError::InvalidArgument(
+                    "No queries given. Pass queries or --queries-file".to_string()
+                ));
+            }
+            batch_resolve(layout, &queries, &format, template.as_deref(), args.common.output.as_deref())?;
+        }
+        
+        EthereumCommand::GenerateProof { address, query, layout, preset, rpc, block, zero_means, validate_semantics, dry_run, template } => {
+            let rpc = profile.require_rpc(rpc.as_deref(), "ethereum")?;
+            if !dry_run {
+                chain_id::verify(&rpc, || commands::fetch_chain_id(&rpc)).await?;
+            }
+            let zero_means = zero_means.as_deref().map(batch::parse_zero_semantics).transpose()?;
+            let entry = profile.resolve_address(&address, "ethereum")?;
+            let layout = match preset {
+                Some(_) => layout,
+                None => layout.or(entry.layout),
+            };
+            generate_proof(
+                &entry.address,
+                &query,
+                layout_source(layout.as_deref(), preset.as_deref()),
+                &rpc,
+                block.as_deref(),
+                zero_means,
+                validate_semantics,
+                dry_run,
+                &format,
+                template.as_deref(),
+                args.common.output.as_deref(),
+            )
+            .await?;
+        }
+        
+        EthereumCommand::ProveLog { tx_hash, log_index, rpc } => {
+            let rpc = profile.require_rpc(rpc.as_deref(), "ethereum")?;
+            chain_id::verify(&rpc, || commands::fetch_chain_id(&rpc)).await?;
+            commands::cmd_ethereum_prove_log(&tx_hash, log_index, &rpc, args.common.output.as_deref().map(std::path::Path::new))
+                .await
+                .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))?;
+        }
+        
+        EthereumCommand::ProveTransaction { tx_hash, rpc } => {
+            let rpc = profile.require_rpc(rpc.as_deref(), "ethereum")?;
+            chain_id::verify(&rpc, || commands::fetch_chain_id(&rpc)).await?;
+            commands::cmd_ethereum_prove_transaction(&tx_hash, &rpc, args.common.output.as_deref().map(std::path::Path::new))
+                .await
+                .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))?;
+        }
+        
+        EthereumCommand::ProveArbitrum { address, slots, rollup, l1_rpc, l1_block, bold, rpc } => {
+            let rpc = profile.require_rpc(rpc.as_deref(), "ethereum")?;
+            chain_id::verify(&rpc, || commands::fetch_chain_id(&rpc)).await?;
+            let entry = profile.resolve_address(&address, "ethereum")?;
+            commands::cmd_ethereum_prove_arbitrum(
+                &entry.address,
+                &slots,
+                &rollup,
+                &l1_rpc,
+                &l1_block,
+                bold,
+                &rpc,
+                args.common.output.as_deref().map(std::path::Path::new),
+            )
+            .await
+            .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))?;
+        }
+        
+        EthereumCommand::ProveOptimism { address, slots, block, output_root, rpc } => {
+            let rpc = profile.require_rpc(rpc.as_deref(), "ethereum")?;
+            chain_id::verify(&rpc, || commands::fetch_chain_id(&rpc)).await?;
+            let entry = profile.resolve_address(&address, "ethereum")?;
+            commands::cmd_ethereum_prove_optimism(
+                &entry.address,
+                &slots,
+                block.as_deref(),
+                output_root.as_deref(),
+                &rpc,
+                args.common.output.as_deref().map(std::path::Path::new),
+            )
+            .await
+            .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))?;
+        }
+        
+        EthereumCommand::ProveFinalized { address, slots, beacon, checkpoint_root, rpc } => {
+            let rpc = profile.require_rpc(rpc.as_deref(), "ethereum")?;
+            chain_id::verify(&rpc, || commands::fetch_chain_id(&rpc)).await?;
+            let entry = profile.resolve_address(&address, "ethereum")?;
+            commands::cmd_ethereum_prove_finalized(
+                &entry.address,
+                &slots,
+                &beacon,
+                checkpoint_root.as_deref(),
+                &rpc,
+                args.common.output.as_deref().map(std::path::Path::new),
+            )
+            .await
+            .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))?;
+        }
+        
+        EthereumCommand::ProveHistory { address, slots, blocks, from, to, step, rpc } => {
+            let blocks = commands::history_blocks(&blocks, from, to, step)
+                .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))?;
+            let rpc = profile.require_rpc(rpc.as_deref(), "ethereum")?;
+            chain_id::verify(&rpc, || commands::fetch_chain_id(&rpc)).await?;
+            let entry = profile.resolve_address(&address, "ethereum")?;
+            commands::cmd_ethereum_prove_history(
+                &entry.address,
+                &slots,
+                &blocks,
+                &rpc,
+                args.common.output.as_deref().map(std::path::Path::new),
+            )
+            .await
+            .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))?;
+        }
+        
+        EthereumCommand::ProveZksync { address, slots, l1_batch, rpc } => {
+            let rpc = profile.require_rpc(rpc.as_deref(), "ethereum")?;
+            chain_id::verify(&rpc, || commands::fetch_chain_id(&rpc)).await?;
+            let entry = profile.resolve_address(&address, "ethereum")?;
+            commands::cmd_ethereum_prove_zksync(
+                &entry.address,
+                &slots,
+                l1_batch,
+                &rpc,
+                args.common.output.as_deref().map(std::path::Path::new),
+            )
+            .await
+            .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))?;
+        }
+        
+        EthereumCommand::ProveVerkle { address, slots, block, rpc } => {
+            let rpc = profile.require_rpc(rpc.as_deref(), "ethereum")?;
+            chain_id::verify(&rpc, || commands::fetch_chain_id(&rpc)).await?;
+            let entry = profile.resolve_address(&address, "ethereum")?;
+            commands::cmd_ethereum_prove_verkle(
+                &entry.address,
+                &slots,
+                &block,
+                &rpc,
+                args.common.output.as_deref().map(std::path::Path::new),
+            )
+            .await
+            .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))?;
+        }
+        
+        EthereumCommand::WatchProof { proofs, every, webhook, rpc, poll_secs, once, subscribe } => {
+            let rpc = profile.require_rpc(rpc.as_deref(), "ethereum")?;
+            chain_id::verify(&rpc, || commands::fetch_chain_id(&rpc)).await?;
+            watch_proof(&proofs, &profile, &rpc, &every, webhook.as_deref(), poll_secs, once, subscribe.as_deref()).await?;
+        }
+        
+        EthereumCommand::AutoGenerate { config_file, output_dir, rpc, dry_run, only } => {
+            let dispatch = project::DispatchOptions {
+                dry_run,
+                profile: args.common.profile.clone(),
+                config: args.common.config.clone(),
+                cache: args.common.cache_flag(),
+            };
+            auto_generate(&config_file, &profile, rpc.as_deref(), &output_dir, dry_run, only.as_deref(), &dispatch).await?;
+        }
+        
+        EthereumCommand::BatchGenerate { config_file, output_dir, rpc, dry_run, parallel, max_concurrency, resume } => {
+            let options = pool::PoolOptions { parallel, max_concurrency, progress: true, resume, ndjson: matches!(format, OutputFormat::Ndjson) };
+            batch_generate(&config_file, &profile, rpc.as_deref(), &output_dir, dry_run, &options).await?;
+        }
+        
+        EthereumCommand::Decode { input } => {
+            let value: Value = artifact::read_file(&input)?;
+            CliUtils::write_value(&value, &format, args.common.output.as_deref())?;
+        }
+        
+        EthereumCommand::Watch { config_file, output_dir, rpc, dry_run, webhook, debounce_ms } => {
+            let options = WatchOptions {
+                chain: "ethereum",
+                input_key: "abi",
+                webhook,
+                debounce: std::time::Duration::from_millis(debounce_ms),
+            };
+            watch_entries(&config_file, &profile, rpc.as_deref(), &output_dir, dry_run, &options).await?;
+        }
+        
+        EthereumCommand::Schema { action } => {
+            run_schema_command(&action, args.common.output.as_deref())?;
+        }
+        
+        EthereumCommand::Cache { action } => {
+            run_cache_command(&action, &profile.cache, args.common.output.as_deref())?;
+        }
+        
+        EthereumCommand::Witness { action } => {
+            run_witness_command(&action, &format, args.common.output.as_deref())?;
+        }
+        
+        EthereumCommand::Serve { bind } => {
+            serve(profile, &bind).await?;
         }
     }
     
@@ -0,0 +1,184 @@
+//! Ethereum handler for the HTTP API server
+//!
+//! Request bodies mirror the CLI inputs: `compile-layout` takes the forge/solc
+//! storage layout, or the `vyper -f layout` output with an optional
+//! `contract_name`, under `storage_layout`, and the query endpoints take a
+//! compiled layout under `layout`. `generate-proof` takes `address` and
+//! `query`, with the optional `layout`, `block`, `zero_means`,
+//! `validate_semantics` and `dry_run` of the CLI command.
+
+use crate::commands::{batch_resolve_summary, generate_storage_proof, key_to_bytes, validate_layout};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+use traverse_cli_core::{
+    server::{field, str_field, BoxFuture, ChainHandler},
+    CliError, CliResult,
+};
+use traverse_core::{KeyResolver, LayoutCompiler, LayoutInfo, ZeroSemantics};
+use traverse_ethereum::{EthereumKeyResolver, EthereumLayoutCompiler, VyperLayoutCompiler};
+
+/// Serves Ethereum requests for `traverse-ethereum serve`
+pub struct EthereumHandler;
+
+fn parse_layout(value: &Value) -> CliResult<LayoutInfo> {
+    serde_json::from_value(value.clone())
+        .map_err(|e| CliError::InvalidArgument(format!("Invalid layout: {}", e)))
+}
+
+impl ChainHandler for EthereumHandler {
+    fn compile_layout(&self, request: &Value) -> CliResult<Value> {
+        let storage_layout = field(request, "storage_layout")?;
+        let compiled = if VyperLayoutCompiler::is_vyper_layout(storage_layout) {
+            let contract_name = request.get("contract_name").and_then(Value::as_str).unwrap_or("Contract");
+            VyperLayoutCompiler.compile_str(contract_name, &storage_layout.to_string())
+        } else {
+            // The compiler reads from a path, so stage the layout in a temp file
+            static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+            let path = std::env::temp_dir().join(format!(
+                "traverse-serve-{}-{}.json",
+                std::process::id(),
+                NEXT_ID.fetch_add(1, Ordering::Relaxed)
+            ));
+            std::fs::write(&path, storage_layout.to_string()).map_err(CliError::Io)?;
+            let compiled = EthereumLayoutCompiler.compile_layout(&path);
+            let _ = std::fs::remove_file(&path);
+            compiled
+        };
+
+        let layout = compiled.map_err(|e| CliError::Processing(e.to_string()))?;
+        if request.get("validate").and_then(Value::as_bool).unwrap_or(true) {
+            validate_layout(&layout).map_err(|e| CliError::Processing(e.to_string()))?;
+        }
+
+        Ok(json!({
+            "layout": layout,
+            "layout_commitment": hex::encode(layout.commitment())
+        }))
+    }
+
+    fn resolve_query(&self, layout: &Value, query: &str) -> CliResult<Value> {
+        let layout = parse_layout(layout)?;
+        let resolved = EthereumKeyResolver
+            .resolve(&layout, query)
+            .map_err(|e| CliError::Processing(e.to_string()))?;
+
+        Ok(json!({
+            "query": query,
+            "storage_key": hex::encode(key_to_bytes(&resolved.key)),
+            "layout_commitment": hex::encode(resolved.layout_commitment),
+            "field_size": resolved.field_size,
+            "offset": resolved.offset
+        }))
+    }
+
+    fn batch_resolve(&self, layout: &Value, queries: &[String]) -> CliResult<Value> {
+        Ok(batch_resolve_summary(&parse_layout(layout)?, queries))
+    }
+
+    fn generate_proof<'a>(&'a self, request: &'a Value, rpc: &'a str) -> BoxFuture<'a, CliResult<Value>> {
+        Box::pin(async move {
+            let address = str_field(request, "address")?;
+            let query = str_field(request, "query")?;
+            let layout = request.get("layout").map(parse_layout).transpose()?;
+            // A block number, or a tag such as `finalized`
+            let block = match request.get("block") {
+                Some(Value::Number(number)) => Some(number.to_string()),
+                Some(Value::String(block)) => Some(block.clone()),
+                Some(_) => return Err(CliError::InvalidArgument("'block' must be a number or a tag".to_string())),
+                None => None,
+            };
+            let zero_means = request
+                .get("zero_means")
+                .map(|value| serde_json::from_value::<ZeroSemantics>(value.clone()))
+                .transpose()
+                .map_err(|e| CliError::InvalidArgument(format!("Invalid zero_means: {}", e)))?;
+            let flag = |name: &str| request.get(name).and_then(Value::as_bool).unwrap_or(false);
+
+            generate_storage_proof(
+                address,
+                query,
+                layout.as_ref(),
+                rpc,
+                block.as_deref(),
+                zero_means,
+                flag("validate_semantics"),
+                flag("dry_run"),
+            )
+            .await
+            .map_err(|e| CliError::Processing(e.to_string()))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use traverse_core::{StorageEntry, TypeInfo, ZeroSemantics};
+
+    fn layout() -> Value {
+        serde_json::to_value(LayoutInfo {
+            contract_name: "Token".to_string(),
+            storage: vec![StorageEntry {
+                label: "totalSupply".to_string(),
+                slot: "2".to_string(),
+                offset: 0,
+                type_name: "t_uint256".to_string(),
+                zero_semantics: ZeroSemantics::ValidZero,
+            }],
+            types: vec![TypeInfo {
+                label: "t_uint256".to_string(),
+                number_of_bytes: "32".to_string(),
+                encoding: "inplace".to_string(),
+                base: None,
+                key: None,
+                value: None,
+            }],
+            code_variables: Vec::new(),
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_handler_resolves_and_plans_proof() {
+        let resolved = EthereumHandler.resolve_query(&layout(), "totalSupply").unwrap();
+        assert_eq!(resolved["storage_key"], format!("{:064x}", 2));
+
+        let batch = EthereumHandler
+            .batch_resolve(&layout(), &["totalSupply".to_string(), "missing".to_string()])
+            .unwrap();
+        assert_eq!(batch["resolved"], 1);
+        assert_eq!(batch["failed"], 1);
+
+        let request = json!({
+            "layout": layout(),
+            "query": "totalSupply",
+            "address": "0x0000000000000000000000000000000000000abc",
+            "block": 16,
+            "dry_run": true
+        });
+        let plan = EthereumHandler.generate_proof(&request, "https://eth.example").await.unwrap();
+        let calls = plan["calls"].as_array().unwrap();
+        assert_eq!(calls.last().unwrap()["method"], "eth_getProof");
+        assert_eq!(calls.last().unwrap()["params"][1][0], format!("0x{:064x}", 2));
+
+        let missing = json!({ "query": "totalSupply" });
+        assert!(EthereumHandler.generate_proof(&missing, "https://eth.example").await.is_err());
+    }
+
+    #[test]
+    fn test_handler_compiles_vyper_layout() {
+        let storage_layout = json!({
+            "storage_layout": {
+                "owner": { "type": "address", "slot": 0, "n_slots": 1 },
+                "balanceOf": { "type": "HashMap[address, uint256]", "slot": 1, "n_slots": 1 }
+            }
+        });
+        let compiled = EthereumHandler
+            .compile_layout(&json!({ "storage_layout": storage_layout, "contract_name": "Pool" }))
+            .unwrap();
+        assert_eq!(compiled["layout"]["contract_name"], "Pool");
+
+        let resolved = EthereumHandler.resolve_query(&compiled["layout"], "owner").unwrap();
+        assert_eq!(resolved["storage_key"], format!("{:064x}", 0));
+    }
+}
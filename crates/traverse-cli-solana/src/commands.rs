@@ -5,8 +5,12 @@
 
 use anyhow::Result;
 use std::path::Path;
-use traverse_cli_core::{formatters::write_output, OutputFormat};
-use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use traverse_cli_core::{
+    artifact, cache,
+    formatters::{write_output, write_result},
+    plan::{RpcPlan, PLAN_FILE},
+    template, OutputFormat,
+};
 
 /// Analyze Solana program from IDL
 #[cfg(feature = "solana")]
@@ -151,14 +155,9 @@ pub async fn cmd_solana_compile_layout(
             });
             toml::to_string_pretty(&simplified)?
         }
-        OutputFormat::Binary => {
-            let binary_data = bincode::serialize(&layout)?;
-            format!("Binary layout: {} bytes\nBase64: {}", binary_data.len(), BASE64.encode(&binary_data))
-        }
-        OutputFormat::Base64 => {
-            let binary_data = bincode::serialize(&layout)?;
-            BASE64.encode(&binary_data)
-        }
+        OutputFormat::Binary => return Ok(artifact::write(&layout, output)?),
+        OutputFormat::Base64 => artifact::to_base64(&layout)?,
+        OutputFormat::Ndjson => serde_json::to_string(&layout)?,
     };
     
     write_output(&output_str, output)?;
@@ -180,16 +179,21 @@ pub async fn cmd_solana_compile_layout(
     Err(anyhow::anyhow!("Solana support not enabled. Build with --features solana"))
 }
 
-/// Generate storage queries for Solana state
+/// Generate account queries from a compiled Solana layout
+///
+/// Each pattern is `Account`, `Account.field`, `Account.*` or `*` and expands
+/// to one query per matching field. Queries are written as templates for how
+/// the account is located: PDAs take their seeds in brackets, token accounts
+/// a mint and owner, and other accounts need an explicit `--account`. With
+/// `include_examples`, the placeholders are also filled with sample addresses.
 #[cfg(feature = "solana")]
 pub async fn cmd_solana_generate_queries(
     layout_file: &Path,
-    state_keys: &str,
+    patterns: &str,
     output: Option<&Path>,
+    format: &OutputFormat,
     include_examples: bool,
 ) -> Result<()> {
-    println!("Generating Solana storage queries for keys: {}", state_keys);
-    
     // Check if the layout file exists before attempting to read it
     if !layout_file.exists() {
         return Err(anyhow::anyhow!(
@@ -197,125 +201,206 @@ pub async fn cmd_solana_generate_queries(
             layout_file.display()
         ));
     }
-    
-    // Load layout
-    let layout_content = std::fs::read_to_string(layout_file)
-        .map_err(|e| anyhow::anyhow!("Failed to read layout file '{}': {}", layout_file.display(), e))?;
-    let layout: traverse_core::LayoutInfo = serde_json::from_str(&layout_content)
-        .map_err(|e| anyhow::anyhow!("Failed to parse layout file '{}': {}", layout_file.display(), e))?;
-    
-    // Parse key list
-    let key_list: Vec<&str> = state_keys.split(',').map(|k| k.trim()).collect();
-    
-    // Generate queries
+    let layout = load_solana_layout(layout_file)?;
+
+    // Sort account names so the output is stable across runs
+    let mut account_names: Vec<&String> = layout.accounts.keys().collect();
+    account_names.sort();
+
+    let pattern_list = split_queries(patterns);
     let mut queries = Vec::new();
-    for key in &key_list {
-        if let Some(entry) = layout.storage.iter().find(|e| e.label == *key) {
-            let mut query = serde_json::json!({
-                "account": key,
-                "type": entry.type_name,
-                "slot": entry.slot,
-                "offset": entry.offset
-            });
-            
-            if include_examples {
-                query["example_queries"] = match entry.type_name.as_str() {
-                    t if t.contains("pubkey") => {
-                        serde_json::json!([
-                            format!("{}[9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM]", key),
-                            format!("{}[11111111111111111111111111111112]", key)
-                        ])
-                    }
-                    t if t.contains("array") => {
-                        serde_json::json!([
-                            format!("{}[0]", key),
-                            format!("{}[1]", key)
-                        ])
-                    }
-                    _ => serde_json::json!([key])
-                };
+    let mut unmatched = Vec::new();
+    for pattern in &pattern_list {
+        let (account_pattern, field_pattern) = split_field_query(pattern);
+        let field_pattern = field_pattern.filter(|f| *f != "*");
+        let before = queries.len();
+
+        for name in account_names.iter().filter(|n| account_pattern == "*" || n.as_str() == account_pattern) {
+            let account = &layout.accounts[name.as_str()];
+            let template = account_template(name, &account.account_type);
+            for field in account.data_layout.iter().filter(|f| field_pattern.is_none_or(|p| p == f.name)) {
+                let mut query = serde_json::json!({
+                    "query": format!("{}.{}", template.query, field.name),
+                    "account": name,
+                    "field": field.name,
+                    "field_type": field.field_type,
+                    "offset": field.offset,
+                    "size": field.size,
+                    "zero_semantics": field.zero_semantics,
+                    "derivation": template.derivation,
+                });
+                if let Some(discriminator) = account.discriminator {
+                    query["discriminator"] = hex::encode(discriminator).into();
+                }
+                if include_examples {
+                    query["example_queries"] = template
+                        .examples
+                        .iter()
+                        .map(|example| format!("{}.{}", example, field.name))
+                        .collect::<Vec<_>>()
+                        .into();
+                }
+                queries.push(query);
             }
-            
-            queries.push(query);
-        } else {
-            println!("Warning: Account '{}' not found in layout", key);
+        }
+
+        if queries.len() == before {
+            eprintln!("Warning: pattern '{}' matched no account fields in the layout", pattern);
+            unmatched.push(*pattern);
         }
     }
-    
+
     let output_json = serde_json::json!({
+        "program_id": layout.program_id,
         "queries": queries,
-        "total_keys": key_list.len(),
-        "found_keys": queries.len()
+        "total_patterns": pattern_list.len(),
+        "unmatched_patterns": unmatched,
     });
-    
-    let output_str = serde_json::to_string_pretty(&output_json)?;
-    write_output(&output_str, output)?;
-    
-    println!("✓ Generated {} queries for {} keys", queries.len(), key_list.len());
-    
+    write_result(&output_json, format, None, output)?;
+
     Ok(())
 }
 
+/// Example pubkey substituted for seed and owner placeholders
+#[cfg(feature = "solana")]
+const EXAMPLE_PUBKEY: &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+
+/// USDC mint, substituted for mint placeholders
+#[cfg(feature = "solana")]
+const EXAMPLE_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+/// How queries for one account are written and its address derived
+#[cfg(feature = "solana")]
+struct AccountTemplate {
+    /// Account part of the query, with placeholders for unknown arguments
+    query: String,
+    derivation: serde_json::Value,
+    /// The account part with placeholders filled in
+    examples: Vec<String>,
+}
+
+#[cfg(feature = "solana")]
+fn account_template(name: &str, account_type: &traverse_solana::AccountType) -> AccountTemplate {
+    use traverse_solana::AccountType;
+
+    match account_type {
+        AccountType::PDA { program_id, seeds, .. } if !seeds.is_empty() => {
+            let seeds: Vec<String> = seeds.iter().map(|s| format!("0x{}", hex::encode(s))).collect();
+            let query = format!("{}[{}]", name, seeds.join(","));
+            AccountTemplate {
+                derivation: serde_json::json!({ "kind": "pda", "program_id": program_id, "seeds": seeds }),
+                examples: vec![query.clone()],
+                query,
+            }
+        }
+        AccountType::PDA { .. } | AccountType::Program { .. } => AccountTemplate {
+            query: format!("{}[{{seed}}]", name),
+            derivation: serde_json::json!({ "kind": "pda", "seeds": ["{seed}"] }),
+            examples: vec![
+                format!("{}[{}]", name, EXAMPLE_PUBKEY),
+                format!("{}[{},{}]", name, name.to_lowercase(), EXAMPLE_PUBKEY),
+            ],
+        },
+        AccountType::ATA { mint, owner } | AccountType::Token { mint, owner, .. } => {
+            // The resolver only reads a two-argument query as an ATA when the
+            // account name mentions a token
+            let prefix = if name.contains("token") { name } else { "token_account" };
+            let known = |value: &str, placeholder: &'static str| {
+                if value.is_empty() { placeholder.to_string() } else { value.to_string() }
+            };
+            let (mint, owner) = (known(mint, "{mint}"), known(owner, "{owner}"));
+            AccountTemplate {
+                query: format!("{}[{},{}]", prefix, mint, owner),
+                derivation: serde_json::json!({ "kind": "ata", "mint": mint, "owner": owner }),
+                examples: vec![format!(
+                    "{}[{},{}]",
+                    prefix,
+                    mint.replace("{mint}", EXAMPLE_MINT),
+                    owner.replace("{owner}", EXAMPLE_PUBKEY)
+                )],
+            }
+        }
+        AccountType::System { .. } => AccountTemplate {
+            query: name.to_string(),
+            derivation: serde_json::json!({ "kind": "address" }),
+            examples: vec![name.to_string()],
+        },
+    }
+}
+
 #[cfg(not(feature = "solana"))]
 pub async fn cmd_solana_generate_queries(
     _layout_file: &Path,
-    _state_keys: &str,
+    _patterns: &str,
     _output: Option<&Path>,
+    _format: &OutputFormat,
     _include_examples: bool,
 ) -> Result<()> {
     Err(anyhow::anyhow!("Solana support not enabled. Build with --features solana"))
 }
 
 /// Resolve specific Solana storage query
+///
+/// Without a layout file, `mint.<field>`, `token_account.<field>`,
+/// `associated_token_account.<field>`, `stake_account.<field>`,
+/// `vote_account.<field>` and `metadata.<field>` resolve against the built-in
+/// layouts.
 #[cfg(feature = "solana")]
 pub async fn cmd_solana_resolve_query(
     query: &str,
-    layout_file: &Path,
+    layout_file: Option<&Path>,
     format: &OutputFormat,
+    template: Option<&Path>,
     output: Option<&Path>,
 ) -> Result<()> {
     println!("Resolving Solana storage query: {}", query);
     
-    // Check if the layout file exists before attempting to read it
-    if !layout_file.exists() {
-        return Err(anyhow::anyhow!(
-            "Layout file does not exist: {}",
-            layout_file.display()
-        ));
-    }
-    
-    // Load layout
-    let layout_content = std::fs::read_to_string(layout_file)
-        .map_err(|e| anyhow::anyhow!("Failed to read layout file '{}': {}", layout_file.display(), e))?;
-    let _layout: traverse_core::LayoutInfo = serde_json::from_str(&layout_content)
-        .map_err(|e| anyhow::anyhow!("Failed to parse layout file '{}': {}", layout_file.display(), e))?;
-    
-    // Create resolver
     use traverse_solana::SolanaKeyResolver;
     
-    let resolver = SolanaKeyResolver::new();
-    let parsed_query = SolanaKeyResolver::parse_query(query)?;
-    let resolved_address = resolver.resolve_account_address(&parsed_query)?;
-    
-    // Create a resolved structure for compatibility
-    let resolved = serde_json::json!({
-        "address": resolved_address,
-        "query": query
-    });
+    let resolved = match layout_file {
+        Some(layout_file) => {
+            // Check if the layout file exists before attempting to read it
+            if !layout_file.exists() {
+                return Err(anyhow::anyhow!(
+                    "Layout file does not exist: {}",
+                    layout_file.display()
+                ));
+            }
+            
+            // Load layout
+            let layout_content = std::fs::read_to_string(layout_file)
+                .map_err(|e| anyhow::anyhow!("Failed to read layout file '{}': {}", layout_file.display(), e))?;
+            let _layout: traverse_core::LayoutInfo = serde_json::from_str(&layout_content)
+                .map_err(|e| anyhow::anyhow!("Failed to parse layout file '{}': {}", layout_file.display(), e))?;
+            
+            // Create resolver
+            let resolver = SolanaKeyResolver::new();
+            let parsed_query = SolanaKeyResolver::parse_query(query)?;
+            let resolved_address = resolver.resolve_account_address(&parsed_query)?;
+            
+            // Create a resolved structure for compatibility
+            serde_json::json!({
+                "address": resolved_address,
+                "query": query
+            })
+        }
+        None => resolve_builtin_query(query)?,
+    };
     
-    let output_str = match format {
-        OutputFormat::Traverse => serde_json::to_string_pretty(&resolved)?,
-        OutputFormat::CoprocessorJson => {
+    let output_str = match (template, format) {
+        (Some(template), _) => template::render(template, &resolved)?,
+        (None, OutputFormat::Traverse) => serde_json::to_string_pretty(&resolved)?,
+        (None, OutputFormat::CoprocessorJson) => {
             let coprocessor_format = serde_json::json!({
                 "query": query,
                 "resolved_address": resolved["address"].as_str().unwrap_or(""),
                 "layout_commitment": "not_implemented",
-                "field_size": 0,
-                "offset": 0
+                "field_size": resolved["size"].as_u64().unwrap_or(0),
+                "offset": resolved["offset"].as_u64().unwrap_or(0)
             });
             serde_json::to_string_pretty(&coprocessor_format)?
         }
-        OutputFormat::Toml => {
+        (None, OutputFormat::Toml) => {
             let simplified = serde_json::json!({
                 "query": query,
                 "account_key": resolved["address"].as_str().unwrap_or(""),
@@ -323,38 +408,90 @@ pub async fn cmd_solana_resolve_query(
             });
             toml::to_string_pretty(&simplified)?
         }
-        OutputFormat::Binary => {
-            // For binary/base64, we need to serialize the JSON value
-            let binary_data = resolved.to_string().as_bytes().to_vec();
-            format!("Binary query result: {} bytes\nBase64: {}", binary_data.len(), BASE64.encode(&binary_data))
-        }
-        OutputFormat::Base64 => {
-            // For binary/base64, we need to serialize the JSON value
-            let binary_data = resolved.to_string().as_bytes().to_vec();
-            BASE64.encode(&binary_data)
-        }
+        (None, OutputFormat::Binary) => return Ok(artifact::write(&resolved, output)?),
+        (None, OutputFormat::Base64) => artifact::to_base64(&resolved)?,
+        (None, OutputFormat::Ndjson) => resolved.to_string(),
     };
     
     write_output(&output_str, output)?;
     
     println!("✓ Query resolved");
     println!("  - Query: {}", query);
-    println!("  - Account address: {}", resolved["address"].as_str().unwrap_or(""));
+    if let Some(address) = resolved["address"].as_str() {
+        println!("  - Account address: {}", address);
+    }
+    if let Some(field) = resolved["field"].as_str() {
+        println!("  - Field: {} (offset {}, {} bytes)", field, resolved["offset"], resolved["size"]);
+    }
     
     Ok(())
 }
 
+/// Resolve a query against the built-in token, stake, vote and metadata layouts
+///
+/// Field queries give the field's offset, size and type within the account;
+/// ATA queries derive the account address.
+#[cfg(feature = "solana")]
+fn resolve_builtin_query(query: &str) -> Result<serde_json::Value> {
+    use traverse_solana::{resolver::SolanaQuery, SolanaKeyResolver};
+
+    match SolanaKeyResolver::parse_query(query)? {
+        SolanaQuery::FieldAccess { account_name, field_path } => {
+            let field = traverse_solana::builtin_field(&account_name, &field_path)?;
+            Ok(serde_json::json!({
+                "query": query,
+                "account": account_name,
+                "owner_program": traverse_solana::builtin_program(&account_name),
+                "field": field.name,
+                "type": field.field_type,
+                "offset": field.offset,
+                "size": field.size,
+                "zero_semantics": field.zero_semantics
+            }))
+        }
+        parsed @ SolanaQuery::ATA { .. } => Ok(serde_json::json!({
+            "address": SolanaKeyResolver::new().resolve_account_address(&parsed)?,
+            "query": query
+        })),
+        _ => Err(anyhow::anyhow!(
+            "No layout file given and '{}' is not a built-in account query, e.g. token_account.amount",
+            query
+        )),
+    }
+}
+
 #[cfg(not(feature = "solana"))]
 pub async fn cmd_solana_resolve_query(
     _query: &str,
-    _layout_file: &Path,
+    _layout_file: Option<&Path>,
     _format: &OutputFormat,
+    _template: Option<&Path>,
     _output: Option<&Path>,
 ) -> Result<()> {
     Err(anyhow::anyhow!("Solana support not enabled. Build with --features solana"))
 }
 
+/// Input for `auto-generate`
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct AutoGenerateConfig {
+    /// Anchor IDL or compiled layout, relative to the config file
+    pub idl: String,
+    /// Program ID or address book name
+    pub program_id: String,
+    /// Account queries, e.g. `vault[seed].balance`
+    pub queries: Vec<String>,
+    /// RPC endpoint for this entry, overriding `--rpc`
+    #[serde(default)]
+    pub rpc: Option<String>,
+}
+
 /// End-to-end automation for Solana
+///
+/// Compiles the layout, resolves each query to an account and field, fetches
+/// every account in batches and writes `layout.json`, `queries.json`,
+/// `proofs.json` and `summary.json` to `output_dir`. `idl_file` may also be a
+/// layout compiled earlier. A dry run stops before any RPC request and
+/// writes the requests it would have made to `plan.json` instead.
 #[cfg(feature = "solana")]
 pub async fn cmd_solana_auto_generate(
     idl_file: &Path,
@@ -399,56 +536,96 @@ pub async fn cmd_solana_auto_generate(
     // Step 1: Compile layout
     println!("Step 1: Compiling layout...");
     let layout_file = output_dir.join("layout.json");
-    cmd_solana_compile_layout(idl_file, Some(&layout_file), &OutputFormat::Traverse).await?;
+    let layout = match load_solana_layout(idl_file) {
+        Ok(layout) => {
+            std::fs::write(&layout_file, serde_json::to_string_pretty(&layout)?)?;
+            layout
+        }
+        Err(_) => {
+            cmd_solana_compile_layout(idl_file, Some(&layout_file), &OutputFormat::Traverse).await?;
+            load_solana_layout(&layout_file)?
+        }
+    };
     
-    // Step 2: Generate queries
-    println!("Step 2: Generating queries...");
-    let queries_file = output_dir.join("queries.json");
-    cmd_solana_generate_queries(&layout_file, queries, Some(&queries_file), true).await?;
-    
-    // Step 3: Resolve queries
-    println!("Step 3: Resolving queries...");
-    let query_list: Vec<&str> = queries.split(',').map(|q| q.trim()).collect();
-    let resolved_file = output_dir.join("resolved.json");
-    
-    let mut resolved_queries = Vec::new();
-    for query in &query_list {
-        match cmd_solana_resolve_query(query, &layout_file, &OutputFormat::CoprocessorJson, None).await {
-            Ok(()) => {
-                resolved_queries.push(serde_json::json!({
-                    "query": query,
-                    "status": "resolved"
-                }));
-            }
-            Err(_) => {
-                resolved_queries.push(serde_json::json!({
-                    "query": query,
-                    "status": "failed"
-                }));
+    // Step 2: Resolve queries to accounts and fields
+    println!("Step 2: Resolving queries...");
+    let query_list = split_queries(queries);
+    let resolved: Vec<(&str, Result<ResolvedAccountQuery>)> = query_list
+        .iter()
+        .map(|query| (*query, resolve_account_query(Some(&layout), program_address, None, query)))
+        .collect();
+    
+    let query_records: Vec<serde_json::Value> = resolved
+        .iter()
+        .map(|(query, result)| match result {
+            Ok(resolved) => {
+                let mut record = resolved.to_json();
+                record["status"] = "resolved".into();
+                record
             }
-        }
-    }
+            Err(e) => serde_json::json!({ "query": query, "status": "failed", "error": e.to_string() }),
+        })
+        .collect();
+    let resolved_count = resolved.iter().filter(|(_, r)| r.is_ok()).count();
     
-    let resolved_output = serde_json::json!({
+    let queries_file = output_dir.join("queries.json");
+    std::fs::write(&queries_file, serde_json::to_string_pretty(&serde_json::json!({
         "program": program_address,
-        "queries": resolved_queries,
-        "total_queries": query_list.len()
-    });
+        "queries": query_records,
+        "total_queries": query_list.len(),
+        "resolved": resolved_count
+    }))?)?;
     
-    std::fs::write(&resolved_file, serde_json::to_string_pretty(&resolved_output)?)?;
-    
-    // Step 4: Generate proof templates (if not dry run)
-    if !dry_run {
-        println!("Step 4: Generating proof templates...");
-        let proof_template = serde_json::json!({
-            "program": program_address,
-            "rpc": rpc,
-            "queries": query_list,
-            "note": "Use these queries with the generate-proof command"
-        });
+    // Step 3: Fetch accounts and build verification requests (if not dry run)
+    let proofs_file = output_dir.join("proofs.json");
+    let plan_file = output_dir.join(PLAN_FILE);
+    let mut proof_failures = Vec::new();
+    let mut addresses: Vec<String> = Vec::new();
+    for (_, result) in &resolved {
+        if let Ok(resolved) = result {
+            if !addresses.contains(&resolved.address) {
+                addresses.push(resolved.address.clone());
+            }
+        }
+    }
+    if dry_run {
+        let mut plan = RpcPlan::new();
+        plan_accounts(&mut plan, rpc, &addresses);
+        plan.write(&plan_file)?;
+    } else {
+        println!("Step 3: Fetching {} accounts...", addresses.len());
+        
+        let fetcher = proof_fetcher(rpc).await?;
+        let proofs = fetch_accounts(&fetcher, rpc, &addresses).await?;
+        let proofs: std::collections::HashMap<&str, _> = addresses
+            .iter()
+            .map(String::as_str)
+            .zip(proofs)
+            .collect();
+        
+        let mut account_batch = Vec::new();
+        for (query, result) in &resolved {
+            let Ok(resolved) = result else { continue };
+            let request = match &proofs[resolved.address.as_str()] {
+                Some(proof) => verification_request(&fetcher, resolved, proof),
+                None => Err(anyhow::anyhow!("Account {} not found", resolved.address)),
+            };
+            match request {
+                Ok(request) => account_batch.push(request),
+                Err(e) => proof_failures.push(serde_json::json!({ "query": query, "error": e.to_string() })),
+            }
+        }
+        
+        // Batches only carry a common slot when every account was read at it
+        let mut slots = account_batch.iter().filter_map(|r| r["slot"].as_str());
+        let first_slot = slots.next();
+        let common_slot = first_slot.filter(|first| slots.all(|slot| slot == *first));
         
-        let proof_file = output_dir.join("proof_template.json");
-        std::fs::write(&proof_file, serde_json::to_string_pretty(&proof_template)?)?;
+        std::fs::write(&proofs_file, serde_json::to_string_pretty(&serde_json::json!({
+            "account_batch": account_batch,
+            "program_address": program_address,
+            "slot": common_slot
+        }))?)?;
     }
     
     // Summary
@@ -457,15 +634,18 @@ pub async fn cmd_solana_auto_generate(
         "idl_file": idl_file.display().to_string(),
         "output_dir": output_dir.display().to_string(),
         "dry_run": dry_run,
+        "total_queries": query_list.len(),
+        "resolved": resolved_count,
+        "proof_failures": proof_failures,
         "files_generated": {
             "layout": layout_file.display().to_string(),
             "queries": queries_file.display().to_string(),
-            "resolved": resolved_file.display().to_string(),
-            "proof_template": if dry_run { "skipped" } else { "generated" }
+            "proofs": if dry_run { "skipped".to_string() } else { proofs_file.display().to_string() },
+            "plan": if dry_run { plan_file.display().to_string() } else { "skipped".to_string() }
         },
         "next_steps": [
             "Review generated files",
-            "Run proof generation with generated template",
+            "Submit proofs.json to the coprocessor as a batch verification request",
             "Integrate with your ZK application"
         ]
     });
@@ -489,6 +669,714 @@ pub async fn cmd_solana_auto_generate(
     Err(anyhow::anyhow!("Solana support not enabled. Build with --features solana"))
 }
 
+/// Split a comma-separated query list, keeping commas inside brackets
+///
+/// `token[mint,owner], vault[seed]` is two queries.
+pub(crate) fn split_queries(queries: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in queries.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(&queries[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&queries[start..]);
+    parts.into_iter().map(str::trim).filter(|q| !q.is_empty()).collect()
+}
+
+/// Split a trailing field access off an account query
+///
+/// `vault[seed].balance` and `vault.balance` both select the `balance` field
+/// of the `vault` account; queries without a field prove the whole account.
+pub(crate) fn split_field_query(query: &str) -> (&str, Option<&str>) {
+    let account_end = query.rfind(']').map_or(0, |i| i + 1);
+    match query[account_end..].find('.') {
+        Some(dot) => {
+            let dot = account_end + dot;
+            (&query[..dot], Some(&query[dot + 1..]))
+        }
+        None => (query, None),
+    }
+}
+
+/// Read a layout written by `compile-layout`
+#[cfg(feature = "solana")]
+fn load_solana_layout(path: &Path) -> Result<traverse_solana::layout::SolanaLayout> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read layout file '{}': {}", path.display(), e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("Failed to parse layout file '{}': {}", path.display(), e))
+}
+
+/// Proof fetcher for an RPC endpoint
+#[cfg(all(feature = "solana", feature = "client"))]
+async fn proof_fetcher(rpc_url: &str) -> Result<traverse_solana::SolanaProofFetcher> {
+    Ok(traverse_solana::SolanaProofFetcher::new(rpc_url.to_string()).await?)
+}
+
+/// Proof fetcher for an RPC endpoint
+#[cfg(all(feature = "solana", not(feature = "client")))]
+async fn proof_fetcher(rpc_url: &str) -> Result<traverse_solana::SolanaProofFetcher> {
+    Ok(traverse_solana::SolanaProofFetcher::new(rpc_url.to_string())?)
+}
+
+/// Genesis hash of the cluster an endpoint serves
+#[cfg(feature = "solana")]
+pub async fn fetch_chain_id(rpc_url: &str) -> Result<String> {
+    Ok(proof_fetcher(rpc_url).await?.genesis_hash().await?)
+}
+
+#[cfg(not(feature = "solana"))]
+pub async fn fetch_chain_id(_rpc_url: &str) -> Result<String> {
+    Err(anyhow::anyhow!("Solana support not enabled. Build with --features solana"))
+}
+
+/// Fetch accounts through the disk cache, batching the ones not cached
+///
+/// Missing accounts are not cached since they may be created later. Cached
+/// accounts keep the slot they were read at, so a batch mixing cached and
+/// fresh accounts may not share a slot.
+#[cfg(feature = "solana")]
+async fn fetch_accounts(
+    fetcher: &traverse_solana::SolanaProofFetcher,
+    rpc_url: &str,
+    addresses: &[String],
+) -> Result<Vec<Option<traverse_solana::SolanaAccountProof>>> {
+    let key = |address: &str| serde_json::json!({ "rpc": rpc_url, "account": address });
+    let mut accounts: Vec<_> = addresses
+        .iter()
+        .map(|address| cache::active().and_then(|c| c.get("account", &key(address))))
+        .collect();
+
+    let missing: Vec<String> = addresses
+        .iter()
+        .zip(&accounts)
+        .filter(|(_, account)| account.is_none())
+        .map(|(address, _)| address.clone())
+        .collect();
+    if missing.is_empty() {
+        return Ok(accounts);
+    }
+
+    let permit = traverse_cli_core::pool::rpc_permit().await;
+    let mut fetched = fetcher.fetch_account_proofs_over_http(&missing).await?.into_iter();
+    drop(permit);
+    for (address, account) in addresses.iter().zip(&mut accounts) {
+        if account.is_some() {
+            continue;
+        }
+        *account = fetched.next().flatten();
+        if let (Some(cache), Some(proof)) = (cache::active(), account.as_ref()) {
+            if let Err(e) = cache.put("account", &key(address), proof) {
+                eprintln!("Warning: failed to cache account {}: {}", address, e);
+            }
+        }
+    }
+    Ok(accounts)
+}
+
+/// Plan the requests [`fetch_accounts`] makes for `addresses` on a cold cache
+///
+/// Each chunk of accounts is one `getMultipleAccounts` call followed by a
+/// `getBlock` for the slot it was read at. Chunks read at a slot an earlier
+/// chunk already saw skip the `getBlock`, so the plan is an upper bound.
+#[cfg(feature = "solana")]
+fn plan_accounts(plan: &mut RpcPlan, rpc_url: &str, addresses: &[String]) {
+    use traverse_solana::{proof::MAX_ACCOUNTS_PER_REQUEST, SolanaProofFetcher};
+
+    for chunk in addresses.chunks(MAX_ACCOUNTS_PER_REQUEST) {
+        let accounts = plan.push(
+            rpc_url,
+            "getMultipleAccounts",
+            SolanaProofFetcher::accounts_request_params(chunk),
+            format!("Account data for {} accounts at a finalized slot", chunk.len()),
+        );
+        plan.push(
+            rpc_url,
+            "getBlock",
+            SolanaProofFetcher::block_request_params(RpcPlan::reference(accounts, "context.slot").into()),
+            "Block hash of the slot the accounts were read at",
+        );
+    }
+}
+
+/// An account query resolved to an address and, with a layout, a field
+#[cfg(feature = "solana")]
+struct ResolvedAccountQuery {
+    query: String,
+    address: String,
+    program_id: String,
+    account_name: String,
+    discriminator: Option<[u8; 8]>,
+    /// Offset and size of the selected field
+    field: Option<(u32, u32)>,
+    /// Zero semantics the layout declares for the selected field
+    zero_semantics: Option<traverse_solana::ZeroSemantics>,
+}
+
+#[cfg(feature = "solana")]
+impl ResolvedAccountQuery {
+    /// The query in `SolanaAccountQuery` form
+    fn to_json(&self) -> serde_json::Value {
+        let mut query = serde_json::json!({
+            "query": self.query,
+            "account_address": self.address,
+            "program_id": self.program_id,
+        });
+        if let Some(discriminator) = self.discriminator {
+            query["discriminator"] = hex::encode(discriminator).into();
+        }
+        if let Some((offset, size)) = self.field {
+            query["field_offset"] = offset.into();
+            query["field_size"] = size.into();
+        }
+        query
+    }
+}
+
+/// Resolve a query to its account, deriving PDA/ATA addresses when no
+/// account is given, and locate its field in the layout
+#[cfg(feature = "solana")]
+fn resolve_account_query(
+    layout: Option<&traverse_solana::layout::SolanaLayout>,
+    program_id: &str,
+    account: Option<&str>,
+    query: &str,
+) -> Result<ResolvedAccountQuery> {
+    use traverse_solana::{resolver::SolanaQuery, SolanaKeyResolver};
+
+    let (account_query, field) = split_field_query(query);
+    let parsed = SolanaKeyResolver::parse_query(account_query)?;
+    let address = match account {
+        Some(account) => account.to_string(),
+        None => SolanaKeyResolver::with_program_id(program_id.to_string())
+            .resolve_account_address(&parsed)
+            .map_err(|e| anyhow::anyhow!("Cannot derive the account for '{}' ({}). Pass --account", query, e))?,
+    };
+
+    let account_name = match &parsed {
+        SolanaQuery::Direct { account_name }
+        | SolanaQuery::PDA { account_name, .. }
+        | SolanaQuery::FieldAccess { account_name, .. } => Some(account_name.as_str()),
+        SolanaQuery::ATA { .. } | SolanaQuery::LookupTable { .. } => None,
+    };
+    let account_layout = layout
+        .zip(account_name)
+        .and_then(|(layout, name)| layout.accounts.get(name));
+    let field = match (field, account_layout) {
+        (None, _) => None,
+        (Some(field), Some(account_layout)) => {
+            let field_layout = account_layout
+                .data_layout
+                .iter()
+                .find(|f| f.name == field)
+                .ok_or_else(|| anyhow::anyhow!("Field '{}' not found in account '{}'", field, account_query))?;
+            Some(field_layout)
+        }
+        (Some(field), None) => {
+            return Err(anyhow::anyhow!("Selecting field '{}' needs a layout defining '{}'", field, account_query));
+        }
+    };
+
+    Ok(ResolvedAccountQuery {
+        query: query.to_string(),
+        address,
+        program_id: program_id.to_string(),
+        account_name: account_name.unwrap_or(account_query).to_string(),
+        discriminator: account_layout.and_then(|l| l.discriminator),
+        field: field.map(|f| (f.offset, f.size)),
+        zero_semantics: field.map(|f| f.zero_semantics),
+    })
+}
+
+/// Pair a resolved query with its fetched account as a
+/// `SolanaAccountVerificationRequest`
+///
+/// Fails when the account data lacks the layout's discriminator or is too
+/// short for the selected field. Lamports, rent epoch and slots are decimal
+/// strings, as the request type writes them.
+#[cfg(feature = "solana")]
+fn verification_request(
+    fetcher: &traverse_solana::SolanaProofFetcher,
+    resolved: &ResolvedAccountQuery,
+    proof: &traverse_solana::SolanaAccountProof,
+) -> Result<serde_json::Value> {
+    use base64::engine::{general_purpose::STANDARD, Engine};
+
+    if let Some(expected) = resolved.discriminator {
+        if !proof.data.starts_with(&expected) {
+            return Err(anyhow::anyhow!(
+                "Account {} is not a '{}' account: discriminator mismatch",
+                resolved.address,
+                resolved.account_name
+            ));
+        }
+    }
+    if let Some((offset, size)) = resolved.field {
+        fetcher.extract_field(proof, offset as usize, size as usize)?;
+    }
+
+    Ok(serde_json::json!({
+        "account_query": resolved.to_json(),
+        "account_proof": {
+            "address": proof.address,
+            "data": STANDARD.encode(&proof.data),
+            "owner": proof.owner,
+            "lamports": proof.lamports.to_string(),
+            "rent_epoch": proof.rent_epoch.to_string(),
+            "slot": proof.slot.to_string(),
+            "block_hash": proof.block_hash,
+        },
+        "program_address": resolved.program_id,
+        "slot": proof.slot.to_string(),
+    }))
+}
+
+/// Check the selected field's declared zero semantics against the
+/// account's history from the DAS RPC at `rpc_url`
+///
+/// DAS indexes assets, so the account must be one, such as an NFT mint or a
+/// compressed asset ID. History after the proof's slot is not evidence.
+#[cfg(feature = "solana")]
+async fn validate_field_semantics(
+    rpc_url: &str,
+    resolved: &ResolvedAccountQuery,
+    proof: &traverse_solana::SolanaAccountProof,
+) -> Result<traverse_solana::ValidationResult> {
+    use traverse_solana::{SemanticValidator, SolanaDasIndexer};
+
+    let (Some((offset, size)), Some(declared)) = (resolved.field, resolved.zero_semantics) else {
+        return Err(anyhow::anyhow!("--validate-semantics needs a field query and a --layout declaring its semantics"));
+    };
+    let declared = traverse_core::ZeroSemantics::from(declared);
+    let value = &proof.data[offset as usize..(offset + size) as usize];
+    let (_, field) = split_field_query(&resolved.query);
+    let result = SemanticValidator::new(SolanaDasIndexer::new(rpc_url))
+        .validate_semantics_at_block(
+            &resolved.address,
+            field.unwrap_or_default(),
+            declared,
+            Some(&format!("0x{}", hex::encode(value))),
+            Some(proof.slot),
+        )
+        .await?;
+    if !result.is_valid {
+        eprintln!(
+            "Warning: semantic validation failed: declared {:?}, detected {:?}",
+            declared, result.detected_semantics
+        );
+    }
+    Ok(result)
+}
+
+/// Generate a Solana account proof as a verification request
+///
+/// The account comes from `account` or is derived from a PDA/ATA query using
+/// the program ID; an `alt[<table>][<index>]` query reads the lookup table
+/// and takes the address at that index. With a layout, the query's account type and field are
+/// looked up to fill in the discriminator and field location, and the fetched
+/// data must carry that discriminator. The output has the shape of
+/// `SolanaAccountVerificationRequest` from traverse-valence. With
+/// `validate_semantics`, the field's declared zero semantics are checked
+/// against the asset's DAS history and the result added as
+/// `semantic_validation`.
+#[cfg(feature = "solana")]
+#[allow(clippy::too_many_arguments)]
+pub async fn cmd_solana_generate_proof(
+    account: Option<&str>,
+    query: &str,
+    layout_file: Option<&Path>,
+    program_id: Option<&str>,
+    rpc_url: &str,
+    dry_run: bool,
+    validate_semantics: bool,
+    format: &OutputFormat,
+    template: Option<&Path>,
+    output: Option<&Path>,
+) -> Result<()> {
+    if dry_run && template.is_some() {
+        return Err(anyhow::anyhow!("--template renders proofs, not the plan --dry-run prints"));
+    }
+    let layout = layout_file.map(load_solana_layout).transpose()?;
+    let request =
+        generate_account_proof(account, query, layout.as_ref(), program_id, rpc_url, dry_run, validate_semantics)
+            .await?;
+    write_result(&request, format, template, output)?;
+    Ok(())
+}
+
+/// The verification request `generate-proof` writes, or with `dry_run` the
+/// plan of its requests
+///
+/// Shared by the CLI command and the HTTP API server.
+#[cfg(feature = "solana")]
+pub(crate) async fn generate_account_proof(
+    account: Option<&str>,
+    query: &str,
+    layout: Option<&traverse_solana::layout::SolanaLayout>,
+    program_id: Option<&str>,
+    rpc_url: &str,
+    dry_run: bool,
+    validate_semantics: bool,
+) -> Result<serde_json::Value> {
+    if dry_run && validate_semantics {
+        return Err(anyhow::anyhow!(
+            "--dry-run cannot plan --validate-semantics, whose requests depend on the asset's history"
+        ));
+    }
+    let program_id = program_id
+        .map(str::to_string)
+        .or_else(|| layout.map(|l| l.program_id.clone()))
+        .ok_or_else(|| anyhow::anyhow!("No program ID. Pass --program-id or a layout"))?;
+
+    // A lookup table entry is only known once the table is read
+    let (account_query, _) = split_field_query(query);
+    let lookup = traverse_solana::lookup_table::parse_lookup_query(account_query).transpose()?;
+    let account = match (account, lookup) {
+        (None, Some((table, _))) if dry_run => {
+            return Err(anyhow::anyhow!(
+                "'{}' names an entry of lookup table {}, which --dry-run does not read. Pass --account",
+                query,
+                table
+            ));
+        }
+        (None, Some((table, index))) => {
+            let fetcher = proof_fetcher(rpc_url).await?;
+            let (table_proof, _) = fetcher.fetch_lookup_table_over_http(&table).await?;
+            Some(traverse_solana::SolanaKeyResolver::new().resolve_lookup_entry(&table_proof.data, index)?)
+        }
+        (account, _) => account.map(str::to_string),
+    };
+
+    let resolved = resolve_account_query(layout, &program_id, account.as_deref(), query)?;
+    if dry_run {
+        let mut plan = RpcPlan::new();
+        plan_accounts(&mut plan, rpc_url, std::slice::from_ref(&resolved.address));
+        return Ok(plan.to_value());
+    }
+    let fetcher = proof_fetcher(rpc_url).await?;
+    let proof = fetch_accounts(&fetcher, rpc_url, std::slice::from_ref(&resolved.address))
+        .await?
+        .remove(0)
+        .ok_or_else(|| anyhow::anyhow!("Account {} not found", resolved.address))?;
+    let mut request = verification_request(&fetcher, &resolved, &proof)?;
+    if validate_semantics {
+        let validation = validate_field_semantics(rpc_url, &resolved, &proof).await?;
+        request["zero_semantics"] = serde_json::to_value(resolved.zero_semantics)?;
+        request["semantic_validation"] = serde_json::to_value(validation)?;
+    }
+    Ok(request)
+}
+
+#[cfg(not(feature = "solana"))]
+#[allow(clippy::too_many_arguments)]
+pub async fn cmd_solana_generate_proof(
+    _account: Option<&str>,
+    _query: &str,
+    _layout_file: Option<&Path>,
+    _program_id: Option<&str>,
+    _rpc_url: &str,
+    _dry_run: bool,
+    _validate_semantics: bool,
+    _format: &OutputFormat,
+    _template: Option<&Path>,
+    _output: Option<&Path>,
+) -> Result<()> {
+    Err(anyhow::anyhow!("Solana support not enabled. Build with --features solana"))
+}
+
+/// Stream verification requests for a query's account from a Yellowstone
+/// gRPC endpoint
+///
+/// The query resolves as for `generate-proof`, except that lookup table
+/// entries need `account`. Each write to the account that reaches the
+/// commitment becomes one `SolanaAccountVerificationRequest`, written as a
+/// line of JSON to stdout or appended to `output`. A dropped stream is
+/// reconnected after `retry_secs`; with `once` the first request ends it.
+#[cfg(feature = "yellowstone")]
+#[allow(clippy::too_many_arguments)]
+pub async fn cmd_solana_stream_proof(
+    account: Option<&str>,
+    query: &str,
+    layout_file: Option<&Path>,
+    program_id: Option<&str>,
+    endpoint: &str,
+    x_token: Option<&str>,
+    confirmed: bool,
+    once: bool,
+    retry_secs: u64,
+    output: Option<&Path>,
+) -> Result<()> {
+    use std::io::Write;
+    use traverse_solana::{AccountStream, GeyserCommitment};
+
+    let layout = layout_file.map(load_solana_layout).transpose()?;
+    let program_id = program_id
+        .map(str::to_string)
+        .or_else(|| layout.as_ref().map(|l| l.program_id.clone()))
+        .ok_or_else(|| anyhow::anyhow!("No program ID. Pass --program-id or a layout"))?;
+    let (account_query, _) = split_field_query(query);
+    if account.is_none() && traverse_solana::lookup_table::parse_lookup_query(account_query).is_some() {
+        return Err(anyhow::anyhow!("'{}' names a lookup table entry, which streaming does not read. Pass --account", query));
+    }
+    let resolved = resolve_account_query(layout.as_ref(), &program_id, account, query)?;
+    let fetcher = proof_fetcher(endpoint).await?;
+
+    let emit = |proof: &traverse_solana::SolanaAccountProof| -> Result<()> {
+        let line = serde_json::to_string(&verification_request(&fetcher, &resolved, proof)?)?;
+        match output {
+            Some(path) => writeln!(std::fs::OpenOptions::new().create(true).append(true).open(path)?, "{}", line)?,
+            None => println!("{}", line),
+        }
+        Ok(())
+    };
+    let commitment = if confirmed { GeyserCommitment::Confirmed } else { GeyserCommitment::Finalized };
+    let mut stream = AccountStream::new().account(&resolved.address).commitment(commitment);
+
+    loop {
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(16);
+        let run = stream.run(endpoint, x_token, sender);
+        tokio::pin!(run);
+
+        let ended = loop {
+            tokio::select! {
+                ended = &mut run => break ended,
+                Some(proof) = receiver.recv() => match emit(&proof) {
+                    Ok(()) if once => return Ok(()),
+                    Err(e) if once => return Err(e),
+                    Err(e) => eprintln!("Warning: skipped update of {} at slot {}: {}", proof.address, proof.slot, e),
+                    Ok(()) => {}
+                },
+            }
+        };
+
+        match ended {
+            Err(e) if once => return Err(e.into()),
+            Err(e) => eprintln!("Warning: stream from {} ended: {}", endpoint, e),
+            Ok(()) => return Ok(()),
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(retry_secs)).await;
+    }
+}
+
+#[cfg(not(feature = "yellowstone"))]
+#[allow(clippy::too_many_arguments)]
+pub async fn cmd_solana_stream_proof(
+    _account: Option<&str>,
+    _query: &str,
+    _layout_file: Option<&Path>,
+    _program_id: Option<&str>,
+    _endpoint: &str,
+    _x_token: Option<&str>,
+    _confirmed: bool,
+    _once: bool,
+    _retry_secs: u64,
+    _output: Option<&Path>,
+) -> Result<()> {
+    Err(anyhow::anyhow!("Yellowstone streaming not enabled. Build with --features yellowstone"))
+}
+
+/// Spot-check a layout against live program accounts
+///
+/// Each account is fetched, matched to the account type whose discriminator
+/// its data starts with, and every field of that type is checked: the data
+/// must cover the field, the field must be as large as its type, booleans
+/// and option tags must be 0 or 1, and the value must not contradict the
+/// field's zero semantics. Returns whether any field mismatched.
+#[cfg(feature = "solana")]
+pub async fn cmd_solana_verify_layout(
+    layout_file: &Path,
+    accounts: &[String],
+    rpc_url: &str,
+    format: &OutputFormat,
+    output: Option<&Path>,
+) -> Result<bool> {
+    use traverse_cli_core::spot_check::{SpotCheck, SpotCheckReport};
+
+    let layout = load_solana_layout(layout_file)?;
+    let fetcher = proof_fetcher(rpc_url).await?;
+    let proofs = fetch_accounts(&fetcher, rpc_url, accounts).await?;
+
+    let mut targets = Vec::new();
+    let mut checks = Vec::new();
+    for (address, proof) in accounts.iter().zip(proofs) {
+        let Some(proof) = proof else {
+            let mut check = SpotCheck::new(address, "account", address, traverse_core::ZeroSemantics::ValidZero);
+            check.skip("the account does not exist");
+            checks.push(check);
+            continue;
+        };
+        let account_type = layout.accounts.iter().find(|(_, account)| {
+            account.discriminator.is_some_and(|discriminator| proof.data.starts_with(&discriminator))
+        });
+        let Some((name, account_layout)) = account_type else {
+            let mut check = SpotCheck::new(address, "account", address, traverse_core::ZeroSemantics::ValidZero);
+            check.mismatch(format!(
+                "no account type in the layout has the discriminator {}",
+                hex::encode(&proof.data[..proof.data.len().min(8)])
+            ));
+            checks.push(check);
+            continue;
+        };
+        targets.push(serde_json::json!({ "address": address, "type": name, "slot": proof.slot }));
+        checks.extend(inspect_account(name, account_layout, address, &proof.data));
+    }
+
+    let mut report = SpotCheckReport::new(
+        "solana",
+        serde_json::json!({ "program_id": layout.program_id, "accounts": targets }),
+    );
+    report.checks = checks;
+    let mismatched = report.mismatched();
+    if mismatched > 0 {
+        eprintln!("Warning: {} of {} fields do not match their live values", mismatched, report.checks.len());
+    }
+    write_result(&report.to_value(), format, None, output)?;
+    Ok(mismatched > 0)
+}
+
+#[cfg(not(feature = "solana"))]
+pub async fn cmd_solana_verify_layout(
+    _layout_file: &Path,
+    _accounts: &[String],
+    _rpc_url: &str,
+    _format: &OutputFormat,
+    _output: Option<&Path>,
+) -> Result<bool> {
+    Err(anyhow::anyhow!("Solana support not enabled. Build with --features solana"))
+}
+
+/// Verify a bank-hash proof offline against a trusted bank hash
+///
+/// Hashes the proven account up its delta hash proof to the accounts delta
+/// hash, computes the slot's bank hash from its components and compares it
+/// with `bank_hash`, which must come from a source the caller trusts, such
+/// as the votes of a stake supermajority. With a layout and query, the
+/// account must be owned by the layout's program and carry the discriminator
+/// of the account type the query names. Returns whether the whole proof
+/// verified.
+#[cfg(feature = "solana")]
+pub fn cmd_solana_verify_proof(
+    proof_file: &Path,
+    bank_hash: &str,
+    layout_file: Option<&Path>,
+    query: Option<&str>,
+    format: &OutputFormat,
+    output: Option<&Path>,
+) -> Result<bool> {
+    use traverse_solana::{bank_hash::parse_hash, BankHashProof};
+
+    let content = std::fs::read_to_string(proof_file)
+        .map_err(|e| anyhow::anyhow!("Failed to read proof file '{}': {}", proof_file.display(), e))?;
+    let proof: BankHashProof = serde_json::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("Failed to parse bank-hash proof '{}': {}", proof_file.display(), e))?;
+    let trusted = parse_hash(bank_hash)?;
+
+    let mut report = serde_json::json!({
+        "address": proof.account.address,
+        "slot": proof.slot,
+        "bank_hash": bank_hash,
+    });
+    let mut valid = match proof.verify() {
+        Ok(computed) if computed == trusted => true,
+        Ok(_) => {
+            report["error"] = "the account proves into a different bank hash".into();
+            false
+        }
+        Err(e) => {
+            report["error"] = e.to_string().into();
+            false
+        }
+    };
+
+    if let (Some(layout_file), Some(query)) = (layout_file, query) {
+        let layout = load_solana_layout(layout_file)?;
+        let resolved = resolve_account_query(Some(&layout), &layout.program_id, Some(&proof.account.address), query)?;
+        let data = &proof.account.data;
+        let error = if proof.account.owner != resolved.program_id {
+            Some(format!("account is owned by {}, not {}", proof.account.owner, resolved.program_id))
+        } else if resolved.discriminator.is_some_and(|expected| !data.starts_with(&expected)) {
+            Some(format!("account is not a '{}' account: discriminator mismatch", resolved.account_name))
+        } else if resolved.field.is_some_and(|(offset, size)| data.len() < (offset + size) as usize) {
+            Some(format!("account data is too short for '{}'", query))
+        } else {
+            None
+        };
+        valid &= error.is_none();
+        report["account_query"] = resolved.to_json();
+        report["layout"] = serde_json::json!({ "verified": error.is_none(), "error": error });
+    }
+
+    report["valid"] = valid.into();
+    write_result(&report, format, None, output)?;
+    Ok(valid)
+}
+
+#[cfg(not(feature = "solana"))]
+pub fn cmd_solana_verify_proof(
+    _proof_file: &Path,
+    _bank_hash: &str,
+    _layout_file: Option<&Path>,
+    _query: Option<&str>,
+    _format: &OutputFormat,
+    _output: Option<&Path>,
+) -> Result<bool> {
+    Err(anyhow::anyhow!("Solana support not enabled. Build with --features solana"))
+}
+
+/// Check every field of an account type against an account's data
+#[cfg(feature = "solana")]
+fn inspect_account(
+    name: &str,
+    account_layout: &traverse_solana::AccountLayout,
+    address: &str,
+    data: &[u8],
+) -> Vec<traverse_cli_core::spot_check::SpotCheck> {
+    use traverse_cli_core::spot_check::{Observed, SpotCheck};
+    use traverse_solana::FieldType;
+
+    account_layout
+        .data_layout
+        .iter()
+        .map(|field| {
+            let (start, end) = (field.offset as usize, field.offset as usize + field.size as usize);
+            let mut check = SpotCheck::new(
+                format!("{}.{}", name, field.name),
+                format!("{:?}", field.field_type),
+                format!("{} bytes {}..{}", address, start, end),
+                field.zero_semantics.into(),
+            );
+            if let Some(size) = field.field_type.fixed_size().filter(|size| *size != field.size) {
+                check.mismatch(format!("the type is {} bytes, but the layout gives the field {}", size, field.size));
+            }
+            let Some(value) = data.get(start..end) else {
+                check.mismatch(format!("the account data is {} bytes, but the field ends at byte {}", data.len(), end));
+                return check;
+            };
+
+            let observed = if value.iter().all(|b| *b == 0) { Observed::Zero } else { Observed::Value };
+            check.observe(Some(hex::encode(value).into()), observed);
+            match (&field.field_type, value.first()) {
+                (FieldType::Bool, Some(byte)) if *byte > 1 => {
+                    check.mismatch(format!("a bool must be 0 or 1, found {}", byte));
+                }
+                (FieldType::Option(_), Some(tag)) if *tag > 1 => {
+                    check.mismatch(format!("an option tag must be 0 or 1, found {}", tag));
+                }
+                _ => {}
+            }
+            check
+        })
+        .collect()
+}
+
 /// Validate IDL schema for correctness and completeness
 #[cfg(feature = "anchor")]
 fn validate_idl_schema(idl: &traverse_solana::anchor::SolanaIdl) -> Result<()> {
@@ -556,6 +1444,78 @@ fn validate_idl_schema(_idl: &()) -> Result<()> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_split_queries() {
+        assert_eq!(split_queries("token[mint,owner], vault[seed].balance,,config"), vec![
+            "token[mint,owner]",
+            "vault[seed].balance",
+            "config",
+        ]);
+    }
+
+    #[test]
+    fn test_split_field_query() {
+        assert_eq!(split_field_query("vault[seed].balance"), ("vault[seed]", Some("balance")));
+        assert_eq!(split_field_query("config.admin"), ("config", Some("admin")));
+        assert_eq!(split_field_query("vault[a.b]"), ("vault[a.b]", None));
+        assert_eq!(split_field_query("config"), ("config", None));
+    }
+
+    #[cfg(feature = "solana")]
+    #[test]
+    fn test_account_template() {
+        use traverse_solana::AccountType;
+
+        let pda = AccountType::PDA { program_id: "p".to_string(), seeds: vec![b"vault".to_vec()], bump: 255 };
+        assert_eq!(account_template("Vault", &pda).query, "Vault[0x7661756c74]");
+
+        let program = AccountType::Program { program_id: "p".to_string(), discriminator: None };
+        assert_eq!(account_template("Vault", &program).query, "Vault[{seed}]");
+
+        let ata = AccountType::ATA { mint: String::new(), owner: "owner".to_string() };
+        let template = account_template("Holder", &ata);
+        assert_eq!(template.query, "token_account[{mint},owner]");
+        assert_eq!(template.examples, vec![format!("token_account[{},owner]", EXAMPLE_MINT)]);
+    }
+
+    #[cfg(feature = "solana")]
+    #[test]
+    fn test_inspect_account_fields() {
+        use traverse_solana::{AccountLayout, AccountType, FieldLayout, FieldType, ZeroSemantics};
+
+        let field = |name: &str, field_type, offset, size, zero_semantics| FieldLayout {
+            name: name.to_string(),
+            field_type,
+            offset,
+            size,
+            zero_semantics,
+        };
+        let layout = AccountLayout {
+            account_type: AccountType::Program { program_id: "p".to_string(), discriminator: Some([7; 8]) },
+            address: String::new(),
+            data_layout: vec![
+                field("discriminator", FieldType::Bytes8, 0, 8, ZeroSemantics::NeverWritten),
+                field("active", FieldType::Bool, 8, 1, ZeroSemantics::ValidZero),
+                field("amount", FieldType::U64, 9, 4, ZeroSemantics::ValidZero),
+                field("authority", FieldType::Pubkey, 13, 32, ZeroSemantics::ExplicitlyZero),
+            ],
+            size: 45,
+            initialized: true,
+            discriminator: Some([7; 8]),
+        };
+
+        let mut data = vec![7u8; 8];
+        data.extend([2, 0, 0, 0, 0]);
+        let checks = inspect_account("Vault", &layout, "addr", &data);
+        let mismatches: Vec<_> = checks.iter().map(|c| c.mismatches.clone()).collect();
+        assert_eq!(checks[0].field, "Vault.discriminator");
+        assert!(mismatches[0].is_empty());
+        assert_eq!(mismatches[1], ["a bool must be 0 or 1, found 2"]);
+        assert_eq!(mismatches[2], ["the type is 8 bytes, but the layout gives the field 4"]);
+        assert_eq!(mismatches[3], ["the account data is 13 bytes, but the field ends at byte 45"]);
+        assert_eq!(checks[2].location, "addr bytes 9..13");
+    }
+
     #[cfg(feature = "solana")]
     #[test]
     fn test_validate_idl_schema_valid() {
@@ -700,6 +1660,50 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("Solana support not enabled"));
     }
 
+    #[cfg(feature = "solana")]
+    #[test]
+    fn test_verify_proof() {
+        use base58::ToBase58;
+        use traverse_solana::{bank_hash, BankHashComponents, BankHashProof, SolanaAccountProof};
+
+        let account = SolanaAccountProof {
+            address: [3u8; 32].to_base58(),
+            data: vec![7; 40],
+            data_len: 40,
+            owner: [9u8; 32].to_base58(),
+            lamports: 10,
+            rent_epoch: u64::MAX,
+            slot: 300_000_000,
+            block_hash: String::new(),
+            signature: None,
+        };
+        let leaf = bank_hash::account_proof_hash(&account, false).unwrap();
+        let hashes = vec![([1; 32], [5; 32]), ([3; 32], leaf)];
+        let components = BankHashComponents {
+            parent_bank_hash: [1; 32],
+            accounts_delta_hash: bank_hash::accounts_delta_hash(&[[5; 32], leaf]),
+            signature_count: 1_234,
+            last_blockhash: [2; 32],
+            epoch_accounts_hash: None,
+        };
+        let proof = BankHashProof::new(account, false, hashes, components).unwrap();
+        let trusted = proof.bank_hash().to_base58();
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let proof_file = dir.path().join("proof.json");
+        let report = dir.path().join("report.json");
+        std::fs::write(&proof_file, serde_json::to_string(&proof).unwrap()).unwrap();
+        let verify = |bank_hash: &str| {
+            cmd_solana_verify_proof(&proof_file, bank_hash, None, None, &OutputFormat::Traverse, Some(&report)).unwrap()
+        };
+
+        assert!(verify(&trusted));
+        assert!(!verify(&[0u8; 32].to_base58()));
+        let report: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&report).unwrap()).unwrap();
+        assert_eq!(report["valid"], false);
+        assert!(report["error"].is_string());
+    }
+
     #[cfg(feature = "solana")]
     #[tokio::test]
     async fn test_file_existence_checks() {
@@ -729,6 +1733,7 @@ mod tests {
             Path::new("nonexistent_layout.json"),
             "balance",
             None,
+            &traverse_core::OutputFormat::Json,
             false,
         ).await;
         assert!(result.is_err());
@@ -737,9 +1742,10 @@ mod tests {
         // Test missing layout file in resolve_query
         let result = cmd_solana_resolve_query(
             "balance",
-            Path::new("nonexistent_layout.json"),
+            Some(Path::new("nonexistent_layout.json")),
             &traverse_core::OutputFormat::Json,
             None,
+            None,
         ).await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("Layout file does not exist"));
@@ -790,6 +1796,7 @@ mod tests {
             temp_layout.path(),
             "balance",
             None,
+            &traverse_core::OutputFormat::Json,
             false,
         ).await;
         // Should not fail due to file existence issues
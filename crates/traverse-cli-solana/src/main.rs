@@ -7,11 +7,22 @@
 use clap::{Parser, Subcommand};
 use serde_json::{json, Value};
 use std::process;
-use traverse_cli_core::{CommonArgs, CliResult, CliUtils, OutputFormat};
+use traverse_cli_core::{
+    artifact,
+    cache::{run_cache_command, CacheCommand},
+    chain_id,
+    pool, project,
+    schema::{run_schema_command, SchemaCommand},
+    watch::{self, WatchOptions},
+    witness::{run_witness_command, WitnessCommand},
+    CommonArgs, CliResult, CliUtils, OutputFormat,
+};
 
 // Note: traverse_solana imports are used conditionally in commands.rs
 
 mod commands;
+#[cfg(all(feature = "solana", feature = "server"))]
+mod server;
 
 /// Solana-specific CLI arguments
 #[derive(Parser)]
@@ -33,7 +44,7 @@ enum SolanaCommand {
     AnalyzeProgram {
         /// Path to the IDL file
         idl_file: String,
-        /// Program ID (optional)
+        /// Program ID or address book name (optional)
         #[arg(long)]
         program_id: Option<String>,
         /// Enable deep analysis
@@ -54,45 +65,219 @@ enum SolanaCommand {
     GenerateQueries {
         /// Layout file path
         layout: String,
-        /// Query patterns
+        /// Query patterns: `Account`, `Account.field`, `Account.*` or `*` (default)
         patterns: Vec<String>,
+        /// Fill query placeholders with example addresses
+        #[arg(long)]
+        include_examples: bool,
     },
     
     /// Resolve Solana account query
     ResolveQuery {
         /// Query string to resolve
         query: String,
-        /// Layout file path
+        /// Layout file path; built-in SPL Token layouts are used if omitted
         #[arg(short, long)]
-        layout: String,
-        /// Program ID
+        layout: Option<String>,
+        /// Program ID or address book name
         #[arg(long)]
         program_id: Option<String>,
+        /// Render the result through a Tera template instead of `--format`
+        #[arg(long)]
+        template: Option<String>,
     },
     
     /// Generate Solana account proof
     GenerateProof {
-        /// Account address
+        /// Account address or address book name (derived from PDA/ATA and lookup table queries if omitted)
         #[arg(long)]
-        account: String,
-        /// Account query
+        account: Option<String>,
+        /// Account query, e.g. `vault[seed].balance`
         #[arg(long)]
         query: String,
-        /// RPC endpoint
+        /// Layout file used to locate the account type and field
+        #[arg(short, long)]
+        layout: Option<String>,
+        /// RPC endpoint (defaults to the profile's endpoint)
+        #[arg(long)]
+        rpc: Option<String>,
+        /// Program ID or address book name (defaults to the layout's program ID)
+        #[arg(long)]
+        program_id: Option<String>,
+        /// Print the RPC requests the proof needs instead of sending them
+        #[arg(long)]
+        dry_run: bool,
+        /// Check the field's declared zero semantics against the asset's history from the RPC's DAS API
+        #[arg(long)]
+        validate_semantics: bool,
+        /// Render the result through a Tera template instead of `--format`
+        #[arg(long)]
+        template: Option<String>,
+    },
+    
+    /// Stream account proofs from a Yellowstone gRPC (Geyser) endpoint
+    ///
+    /// Writes one verification request per line as the account changes.
+    StreamProof {
+        /// Account address or address book name (derived from PDA/ATA queries if omitted)
+        #[arg(long)]
+        account: Option<String>,
+        /// Account query, e.g. `vault[seed].balance`
+        #[arg(long)]
+        query: String,
+        /// Layout file used to locate the account type and field
+        #[arg(short, long)]
+        layout: Option<String>,
+        /// Program ID or address book name (defaults to the layout's program ID)
         #[arg(long)]
-        rpc: String,
-        /// Program ID
+        program_id: Option<String>,
+        /// Yellowstone gRPC endpoint
+        #[arg(long)]
+        endpoint: String,
+        /// Access token for hosted endpoints
+        #[arg(long)]
+        x_token: Option<String>,
+        /// Send updates once confirmed rather than finalized
         #[arg(long)]
-        program_id: String,
+        confirmed: bool,
+        /// Stop after the first request
+        #[arg(long)]
+        once: bool,
+        /// Seconds to wait before reconnecting a dropped stream
+        #[arg(long, default_value_t = 5)]
+        retry_secs: u64,
+    },
+    
+    /// Verify a bank-hash account proof offline against a trusted bank hash
+    ///
+    /// Exits with status 2 when the proof does not verify.
+    VerifyProof {
+        /// Bank-hash proof file
+        #[arg(long)]
+        proof: String,
+        /// Bank hash of the proof's slot, base58, from a source you trust
+        #[arg(long)]
+        bank_hash: String,
+        /// Layout file the account is checked against
+        #[arg(short, long, requires = "query")]
+        layout: Option<String>,
+        /// Query naming the account type and field the proof is for
+        #[arg(long, requires = "layout")]
+        query: Option<String>,
+    },
+    
+    /// Spot-check a layout against live program accounts
+    ///
+    /// Exits with status 2 when any field's value contradicts the layout.
+    VerifyLayout {
+        /// Account addresses or address book names to read (repeatable)
+        #[arg(long = "account", required = true)]
+        accounts: Vec<String>,
+        /// Layout file to check (defaults to the first account's address book layout)
+        #[arg(short, long)]
+        layout: Option<String>,
+        /// RPC endpoint (defaults to the profile's endpoint)
+        #[arg(long)]
+        rpc: Option<String>,
     },
     
     /// Auto-generate for Solana programs
     AutoGenerate {
-        /// Configuration file path
-        config: String,
+        /// Configuration file path (JSON with `idl`, `program_id` and `queries`, or a multi-chain project)
+        #[arg(value_name = "CONFIG")]
+        config_file: String,
         /// Output directory
         #[arg(short, long, default_value = "output")]
         output_dir: String,
+        /// RPC endpoint (defaults to the profile's endpoint)
+        #[arg(long)]
+        rpc: Option<String>,
+        /// Resolve queries and write the RPC plan instead of fetching accounts
+        #[arg(long)]
+        dry_run: bool,
+        /// Run only this chain's section of a project config
+        #[arg(long, hide = true)]
+        only: Option<String>,
+    },
+    
+    /// Auto-generate many programs on a bounded worker pool
+    ///
+    /// With `--format ndjson`, each entry\'s report is streamed to stdout as it finishes.
+    BatchGenerate {
+        /// Batch config: a JSON list of auto-generate entries, or a project config
+        #[arg(value_name = "CONFIG")]
+        config_file: String,
+        /// Output directory; each entry is generated into `<output-dir>/<name>`
+        #[arg(short, long, default_value = "output")]
+        output_dir: String,
+        /// RPC endpoint for entries without their own (defaults to the profile's endpoint)
+        #[arg(long)]
+        rpc: Option<String>,
+        /// Resolve queries and write each entry's RPC plan instead of fetching accounts
+        #[arg(long)]
+        dry_run: bool,
+        /// Number of entries to generate at once
+        #[arg(long, default_value_t = 1)]
+        parallel: usize,
+        /// Maximum RPC requests in flight across all workers
+        #[arg(long)]
+        max_concurrency: Option<usize>,
+        /// Skip entries a previous run of this batch completed, per its checkpoint
+        #[arg(long)]
+        resume: bool,
+    },
+
+    /// Regenerate batch entries whenever the config or their inputs change
+    Watch {
+        /// Batch config: a JSON list of auto-generate entries, or a project config
+        #[arg(value_name = "CONFIG")]
+        config_file: String,
+        /// Output directory; each entry is generated into `<output-dir>/<name>`
+        #[arg(short, long, default_value = "output")]
+        output_dir: String,
+        /// RPC endpoint for entries without their own (defaults to the profile's endpoint)
+        #[arg(long)]
+        rpc: Option<String>,
+        /// Resolve queries and write each entry's RPC plan instead of fetching accounts
+        #[arg(long)]
+        dry_run: bool,
+        /// URL change reports are POSTed to (stdout if not specified)
+        #[arg(long)]
+        webhook: Option<String>,
+        /// Milliseconds to let a burst of file events settle before rehashing
+        #[arg(long, default_value_t = 300)]
+        debounce_ms: u64,
+    },
+    
+    /// Decode a binary or base64 artifact back to JSON
+    Decode {
+        /// Artifact file path
+        input: String,
+    },
+    
+    /// Export JSON Schemas for the CLI output formats
+    Schema {
+        #[command(subcommand)]
+        action: SchemaCommand,
+    },
+    
+    /// Inspect or clear the RPC response cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheCommand,
+    },
+    
+    /// Inspect controller witnesses
+    Witness {
+        #[command(subcommand)]
+        action: WitnessCommand,
+    },
+    
+    /// Serve the Solana commands over HTTP
+    Serve {
+        /// Address to bind
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        bind: String,
     },
 }
 
@@ -138,16 +323,243 @@ async fn compile_layout(input: &str, output: Option<&str>) -> CliResult<()> {
     }
 }
 
+async fn generate_queries(
+    layout: &str,
+    patterns: &[String],
+    include_examples: bool,
+    format: &OutputFormat,
+    output: Option<&str>,
+) -> CliResult<()> {
+    use std::path::Path;
+
+    let patterns = if patterns.is_empty() { "*".to_string() } else { patterns.join(",") };
+    commands::cmd_solana_generate_queries(Path::new(layout), &patterns, output.map(Path::new), format, include_examples)
+        .await
+        .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn generate_proof(
+    account: Option<&str>,
+    query: &str,
+    layout: Option<&str>,
+    program_id: Option<&str>,
+    rpc: &str,
+    dry_run: bool,
+    validate_semantics: bool,
+    format: &OutputFormat,
+    template: Option<&str>,
+    output: Option<&str>,
+) -> CliResult<()> {
+    use std::path::Path;
+
+    commands::cmd_solana_generate_proof(
+        account,
+        query,
+        layout.map(Path::new),
+        program_id,
+        rpc,
+        dry_run,
+        validate_semantics,
+        format,
+        template.map(Path::new),
+        output.map(Path::new),
+    )
+    .await
+    .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn stream_proof(
+    account: Option<&str>,
+    query: &str,
+    layout: Option<&str>,
+    program_id: Option<&str>,
+    endpoint: &str,
+    x_token: Option<&str>,
+    confirmed: bool,
+    once: bool,
+    retry_secs: u64,
+    output: Option<&str>,
+) -> CliResult<()> {
+    use std::path::Path;
+
+    commands::cmd_solana_stream_proof(
+        account,
+        query,
+        layout.map(Path::new),
+        program_id,
+        endpoint,
+        x_token,
+        confirmed,
+        once,
+        retry_secs,
+        output.map(Path::new),
+    )
+    .await
+    .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))
+}
+
+async fn verify_layout(
+    layout: &str,
+    accounts: &[String],
+    rpc: &str,
+    format: &OutputFormat,
+    output: Option<&str>,
+) -> CliResult<bool> {
+    use std::path::Path;
+
+    commands::cmd_solana_verify_layout(Path::new(layout), accounts, rpc, format, output.map(Path::new))
+        .await
+        .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))
+}
+
+/// Run `auto-generate` for a single-program config or a project config
+///
+/// For a project, the Solana section runs here and the other sections are
+/// dispatched to their CLIs unless `only` restricts the run to this chain.
+async fn auto_generate(
+    config: &str,
+    profile: &traverse_cli_core::Profile,
+    rpc: Option<&str>,
+    output_dir: &str,
+    dry_run: bool,
+    only: Option<&str>,
+    dispatch: &project::DispatchOptions,
+) -> CliResult<()> {
+    use std::path::Path;
+
+    let config_path = Path::new(config);
+    let value = CliUtils::load_config(config_path)?;
+    let Some(project) = project::ProjectConfig::from_value(&value)? else {
+        return auto_generate_entry(value, config_path, profile, rpc, Path::new(output_dir).to_path_buf(), dry_run).await;
+    };
+    if let Some(chain) = only.filter(|chain| *chain != "solana") {
+        return Err(traverse_cli_core::CliError::InvalidArgument(format!(
+            "traverse-solana cannot run the {} section",
+            chain
+        )));
+    }
+
+    let own = project::run_section("solana", &project, Path::new(output_dir), |entry, entry_dir| {
+        auto_generate_entry(entry, config_path, profile, rpc, entry_dir, dry_run)
+    })
+    .await?;
+    if only.is_some() {
+        return Ok(());
+    }
+
+    let manifest = project::write_manifest(&project, config_path, Path::new(output_dir), own, dispatch)?;
+    if !manifest.is_ok() {
+        return Err(traverse_cli_core::CliError::Processing(format!(
+            "Some project entries failed; see {}",
+            Path::new(output_dir).join(project::PROJECT_MANIFEST).display()
+        )));
+    }
+    Ok(())
+}
+
+async fn auto_generate_entry(
+    entry: Value,
+    config_path: &std::path::Path,
+    profile: &traverse_cli_core::Profile,
+    rpc: Option<&str>,
+    output_dir: std::path::PathBuf,
+    dry_run: bool,
+) -> CliResult<()> {
+    use std::path::Path;
+
+    let config: commands::AutoGenerateConfig = serde_json::from_value(entry)
+        .map_err(|e| traverse_cli_core::CliError::Configuration(format!("Invalid auto-generate config: {}", e)))?;
+    let rpc = profile.require_rpc(config.rpc.as_deref().or(rpc), "solana")?;
+    if !dry_run {
+        chain_id::verify(&rpc, || commands::fetch_chain_id(&rpc)).await?;
+    }
+    let program_id = profile.resolve_address(&config.program_id, "solana")?.address;
+    let idl = config_path.parent().unwrap_or(Path::new("")).join(&config.idl);
+
+    commands::cmd_solana_auto_generate(
+        &idl,
+        &rpc,
+        &program_id,
+        &config.queries.join(","),
+        &output_dir,
+        dry_run,
+    )
+    .await
+    .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))
+}
+
+/// Run `batch-generate`, failing if any entry failed
+async fn batch_generate(
+    config: &str,
+    profile: &traverse_cli_core::Profile,
+    rpc: Option<&str>,
+    output_dir: &str,
+    dry_run: bool,
+    options: &pool::PoolOptions,
+) -> CliResult<()> {
+    use std::path::Path;
+
+    let config_path = Path::new(config);
+    let entries = pool::batch_entries(&CliUtils::load_config(config_path)?, "solana")?;
+    if let Some(max_concurrency) = options.max_concurrency {
+        pool::limit_rpc(max_concurrency);
+    }
+
+    let output_dir = Path::new(output_dir);
+    let settings = serde_json::json!({ "dry_run": dry_run, "rpc": rpc });
+    let summary = pool::run_batch(&entries, output_dir, options, settings, |entry, entry_dir| {
+        auto_generate_entry(entry, config_path, profile, rpc, entry_dir, dry_run)
+    })
+    .await?;
+    pool::print_summary(&summary, output_dir);
+    if !summary.is_ok() {
+        return Err(traverse_cli_core::CliError::Processing(format!(
+            "{} of {} entries failed",
+            summary.failed, summary.total
+        )));
+    }
+    Ok(())
+}
+
+/// Run `watch`, regenerating entries as the config and their inputs change
+async fn watch_entries(
+    config: &str,
+    profile: &traverse_cli_core::Profile,
+    rpc: Option<&str>,
+    output_dir: &str,
+    dry_run: bool,
+    options: &WatchOptions,
+) -> CliResult<()> {
+    use std::path::Path;
+
+    let config_path = Path::new(config);
+    watch::run_watch(config_path, Path::new(output_dir), options, |entry, entry_dir| {
+        auto_generate_entry(entry, config_path, profile, rpc, entry_dir, dry_run)
+    })
+    .await?;
+    Ok(())
+}
+
 #[cfg(feature = "solana")]
-async fn resolve_query(query: &str, layout_file: &str, program_id: Option<&str>) -> CliResult<Value> {
+async fn resolve_query(
+    query: &str,
+    layout_file: Option<&str>,
+    program_id: Option<&str>,
+    template: Option<&str>,
+    output: Option<&str>,
+) -> CliResult<Value> {
     use std::path::Path;
     
     // Call the command implementation
     let result = commands::cmd_solana_resolve_query(
         query,
-        Path::new(layout_file),
+        layout_file.map(Path::new),
         &OutputFormat::CoprocessorJson,
-        None, // output handled by caller
+        template.map(Path::new),
+        // A rendered report goes to --output; otherwise the caller writes the status there
+        template.and(output).map(Path::new),
     ).await;
     
     match result {
@@ -156,7 +568,7 @@ async fn resolve_query(query: &str, layout_file: &str, program_id: Option<&str>)
             Ok(json!({
                 "status": "success",
                 "query": query,
-                "layout_file": layout_file,
+                "layout_file": layout_file.unwrap_or("builtin"),
                 "program_id": program_id.unwrap_or("not_specified"),
                 "resolved": true
             }))
@@ -180,68 +592,210 @@ async fn compile_layout(_input: &str, _output: Option<&str>) -> CliResult<()> {
 }
 
 #[cfg(not(feature = "solana"))]
-async fn resolve_query(_query: &str, _layout_file: &str, _program_id: Option<&str>) -> CliResult<Value> {
+async fn resolve_query(
+    _query: &str,
+    _layout_file: Option<&str>,
+    _program_id: Option<&str>,
+    _template: Option<&str>,
+    _output: Option<&str>,
+) -> CliResult<Value> {
     Err(traverse_cli_core::CliError::Configuration(
         "Solana support not enabled. Build with --features solana".to_string()
     ))
 }
 
+#[cfg(all(feature = "solana", feature = "server"))]
+async fn serve(profile: traverse_cli_core::Profile, bind: &str) -> CliResult<()> {
+    traverse_cli_core::server::ApiServer::new(profile)
+        .with_chain("solana", server::SolanaHandler)
+        .serve(bind)
+        .await
+}
+
+#[cfg(not(all(feature = "solana", feature = "server")))]
+async fn serve(_profile: traverse_cli_core::Profile, _bind: &str) -> CliResult<()> {
+    Err(traverse_cli_core::CliError::Processing(
+        "Server mode not enabled. Build with --features solana,server".to_string()
+    ))
+}
+
 async fn handle_command(args: SolanaArgs) -> CliResult<()> {
     // Set verbose mode
     if args.common.verbose {
         std::env::set_var("VERBOSE", "1");
     }
+
+    let profile = args.common.load_profile()?;
+    let format = args.common.output_format(&profile);
+    args.common.install_cache(&profile);
+    args.common.install_chain_id(&profile, "solana");
     
     match args.command {
         SolanaCommand::AnalyzeProgram { idl_file, program_id, deep } => {
+            let program_id = program_id.map(|p| profile.resolve_address(&p, "solana")).transpose()?.map(|e| e.address);
             let result = analyze_program(&idl_file, program_id.as_deref(), deep).await?;
-            let output = CliUtils::format_json(&result, &args.common.format)?;
-            CliUtils::write_output(&output, args.common.output.as_deref())?;
+            CliUtils::write_value(&result, &format, args.common.output.as_deref())?;
         }
         
         SolanaCommand::CompileLayout { input, output } => {
             compile_layout(&input, output.as_deref()).await?;
         }
         
-        SolanaCommand::GenerateQueries { layout, patterns } => {
-            let result = json!({
-                "layout": layout,
-                "patterns": patterns,
-                "note": "Query generation implementation would go here"
-            });
-            let output = CliUtils::format_json(&result, &args.common.format)?;
-            CliUtils::write_output(&output, args.common.output.as_deref())?;
+        SolanaCommand::GenerateQueries { layout, patterns, include_examples } => {
+            generate_queries(&layout, &patterns, include_examples, &format, args.common.output.as_deref()).await?;
         }
         
-        SolanaCommand::ResolveQuery { query, layout, program_id } => {
-            let result = resolve_query(&query, &layout, program_id.as_deref()).await?;
-            let output = CliUtils::format_json(&result, &args.common.format)?;
-            CliUtils::write_output(&output, args.common.output.as_deref())?;
+        SolanaCommand::ResolveQuery { query, layout, program_id, template } => {
+            let program_id = program_id.map(|p| profile.resolve_address(&p, "solana")).transpose()?.map(|e| e.address);
+            let output = args.common.output.as_deref();
+            let result = resolve_query(&query, layout.as_deref(), program_id.as_deref(), template.as_deref(), output).await?;
+            if template.is_none() {
+                CliUtils::write_value(&result, &format, output)?;
+            }
         }
         
-        SolanaCommand::GenerateProof { account, query, rpc, program_id } => {
-            let result = json!({
-                "account": account,
-                "query": query,
-                "rpc": rpc,
-                "program_id": program_id,
-                "note": "Proof generation implementation would go here"
-            });
-            let output = CliUtils::format_json(&result, &args.common.format)?;
-            CliUtils::write_output(&output, args.common.output.as_deref())?;
+        SolanaCommand::GenerateProof {
+            account,
+            query,
+            layout,
+            rpc,
+            program_id,
+            dry_run,
+            validate_semantics,
+            template,
+        } => {
+            let rpc = profile.require_rpc(rpc.as_deref(), "solana")?;
+            if !dry_run {
+                chain_id::verify(&rpc, || commands::fetch_chain_id(&rpc)).await?;
+            }
+            let account = account.map(|a| profile.resolve_address(&a, "solana")).transpose()?;
+            let program = program_id.map(|p| profile.resolve_address(&p, "solana")).transpose()?;
+            let layout = layout
+                .or_else(|| account.as_ref().and_then(|e| e.layout.clone()))
+                .or_else(|| program.as_ref().and_then(|e| e.layout.clone()));
+            generate_proof(
+                account.as_ref().map(|e| e.address.as_str()),
+                &query,
+                layout.as_deref(),
+                program.as_ref().map(|e| e.address.as_str()),
+                &rpc,
+                dry_run,
+                validate_semantics,
+                &format,
+                template.as_deref(),
+                args.common.output.as_deref(),
+            )
+            .await?;
+        }
+        
+        SolanaCommand::StreamProof {
+            account,
+            query,
+            layout,
+            program_id,
+            endpoint,
+            x_token,
+            confirmed,
+            once,
+            retry_secs,
+        } => {
+            let account = account.map(|a| profile.resolve_address(&a, "solana")).transpose()?;
+            let program = program_id.map(|p| profile.resolve_address(&p, "solana")).transpose()?;
+            let layout = layout
+                .or_else(|| account.as_ref().and_then(|e| e.layout.clone()))
+                .or_else(|| program.as_ref().and_then(|e| e.layout.clone()));
+            stream_proof(
+                account.as_ref().map(|e| e.address.as_str()),
+                &query,
+                layout.as_deref(),
+                program.as_ref().map(|e| e.address.as_str()),
+                &endpoint,
+                x_token.as_deref(),
+                confirmed,
+                once,
+                retry_secs,
+                args.common.output.as_deref(),
+            )
+            .await?;
+        }
+        
+        SolanaCommand::VerifyProof { proof, bank_hash, layout, query } => {
+            use std::path::Path;
+
+            let valid = commands::cmd_solana_verify_proof(
+                Path::new(&proof),
+                &bank_hash,
+                layout.as_deref().map(Path::new),
+                query.as_deref(),
+                &format,
+                args.common.output.as_deref().map(Path::new),
+            )
+            .map_err(|e| traverse_cli_core::CliError::Processing(e.to_string()))?;
+            if !valid {
+                process::exit(2);
+            }
+        }
+        
+        SolanaCommand::VerifyLayout { accounts, layout, rpc } => {
+            let rpc = profile.require_rpc(rpc.as_deref(), "solana")?;
+            chain_id::verify(&rpc, || commands::fetch_chain_id(&rpc)).await?;
+            let entries = accounts
+                .iter()
+                .map(|a| profile.resolve_address(a, "solana"))
+                .collect::<CliResult<Vec<_>>>()?;
+            let layout = layout.or_else(|| entries.iter().find_map(|e| e.layout.clone())).ok_or_else(|| {
+                traverse_cli_core::CliError::InvalidArgument("No layout for these accounts. Pass --layout".to_string())
+            })?;
+            let addresses: Vec<String> = entries.into_iter().map(|e| e.address).collect();
+            if verify_layout(&layout, &addresses, &rpc, &format, args.common.output.as_deref()).await? {
+                process::exit(2);
+            }
+        }
+        
+        SolanaCommand::AutoGenerate { config_file, output_dir, rpc, dry_run, only } => {
+            let dispatch = project::DispatchOptions {
+                dry_run,
+                profile: args.common.profile.clone(),
+                config: args.common.config.clone(),
+                cache: args.common.cache_flag(),
+            };
+            auto_generate(&config_file, &profile, rpc.as_deref(), &output_dir, dry_run, only.as_deref(), &dispatch).await?;
+        }
+        
+        SolanaCommand::BatchGenerate { config_file, output_dir, rpc, dry_run, parallel, max_concurrency, resume } => {
+            let options = pool::PoolOptions { parallel, max_concurrency, progress: true, resume, ndjson: matches!(format, OutputFormat::Ndjson) };
+            batch_generate(&config_file, &profile, rpc.as_deref(), &output_dir, dry_run, &options).await?;
+        }
+        
+        SolanaCommand::Decode { input } => {
+            let value: Value = artifact::read_file(&input)?;
+            CliUtils::write_value(&value, &format, args.common.output.as_deref())?;
+        }
+        
+        SolanaCommand::Watch { config_file, output_dir, rpc, dry_run, webhook, debounce_ms } => {
+            let options = WatchOptions {
+                chain: "solana",
+                input_key: "idl",
+                webhook,
+                debounce: std::time::Duration::from_millis(debounce_ms),
+            };
+            watch_entries(&config_file, &profile, rpc.as_deref(), &output_dir, dry_run, &options).await?;
+        }
+        
+        SolanaCommand::Schema { action } => {
+            run_schema_command(&action, args.common.output.as_deref())?;
+        }
+        
+        SolanaCommand::Cache { action } => {
+            run_cache_command(&action, &profile.cache, args.common.output.as_deref())?;
+        }
+        
+        SolanaCommand::Witness { action } => {
+            run_witness_command(&action, &format, args.common.output.as_deref())?;
         }
         
-        SolanaCommand::AutoGenerate { config, output_dir } => {
-            let _config_data = CliUtils::load_config(&config)?;
-            CliUtils::ensure_output_dir(&output_dir)?;
-            
-            let result = json!({
-                "config": config,
-                "output_dir": output_dir,
-                "note": "Auto-generation implementation would go here"
-            });
-            let output = CliUtils::format_json(&result, &args.common.format)?;
-            CliUtils::write_output(&output, args.common.output.as_deref())?;
+        SolanaCommand::Serve { bind } => {
+            serve(profile, &bind).await?;
         }
     }
     
@@ -0,0 +1,114 @@
+//! Solana handler for the HTTP API server
+//!
+//! `compile-layout` takes an Anchor IDL under `idl`. Queries resolve to account
+//! addresses, so `layout` is accepted for parity with the other chains but is
+//! not needed to derive PDA or ATA addresses. `generate-proof` takes `query`,
+//! with the optional `address`, `layout`, `program_id`, `validate_semantics`
+//! and `dry_run` of the CLI command, and needs a layout or `program_id` to
+//! know the account's program.
+
+use crate::commands::generate_account_proof;
+use serde_json::{json, Value};
+use traverse_cli_core::{
+    server::{field, str_field, BoxFuture, ChainHandler},
+    CliError, CliResult,
+};
+use traverse_solana::{SolanaKeyResolver, SolanaLayoutCompiler};
+
+/// Serves Solana requests for `traverse-solana serve`
+pub struct SolanaHandler;
+
+fn resolve_address(query: &str) -> CliResult<String> {
+    let parsed = SolanaKeyResolver::parse_query(query)
+        .map_err(|e| CliError::InvalidArgument(e.to_string()))?;
+    SolanaKeyResolver::new()
+        .resolve_account_address(&parsed)
+        .map_err(|e| CliError::Processing(e.to_string()))
+}
+
+impl ChainHandler for SolanaHandler {
+    fn compile_layout(&self, request: &Value) -> CliResult<Value> {
+        let idl = field(request, "idl")?;
+        let layout = SolanaLayoutCompiler::new()
+            .compile_from_idl(&idl.to_string())
+            .map_err(|e| CliError::Processing(e.to_string()))?;
+        Ok(json!({ "layout": layout }))
+    }
+
+    fn resolve_query(&self, _layout: &Value, query: &str) -> CliResult<Value> {
+        Ok(json!({
+            "query": query,
+            "address": resolve_address(query)?
+        }))
+    }
+
+    fn batch_resolve(&self, _layout: &Value, queries: &[String]) -> CliResult<Value> {
+        let results: Vec<Value> = queries
+            .iter()
+            .map(|query| match resolve_address(query) {
+                Ok(address) => json!({ "query": query, "status": "resolved", "address": address }),
+                Err(e) => json!({ "query": query, "status": "failed", "error": e.to_string() }),
+            })
+            .collect();
+        let failed = results.iter().filter(|r| r["status"] == "failed").count();
+
+        Ok(json!({
+            "total_queries": results.len(),
+            "resolved": results.len() - failed,
+            "failed": failed,
+            "results": results
+        }))
+    }
+
+    fn generate_proof<'a>(&'a self, request: &'a Value, rpc: &'a str) -> BoxFuture<'a, CliResult<Value>> {
+        Box::pin(async move {
+            let query = str_field(request, "query")?;
+            let layout = request
+                .get("layout")
+                .map(|layout| serde_json::from_value(layout.clone()))
+                .transpose()
+                .map_err(|e| CliError::InvalidArgument(format!("Invalid layout: {}", e)))?;
+            let optional = |name: &str| request.get(name).and_then(Value::as_str);
+            let flag = |name: &str| request.get(name).and_then(Value::as_bool).unwrap_or(false);
+
+            generate_account_proof(
+                optional("address"),
+                query,
+                layout.as_ref(),
+                optional("program_id"),
+                rpc,
+                flag("dry_run"),
+                flag("validate_semantics"),
+            )
+            .await
+            .map_err(|e| CliError::Processing(e.to_string()))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_handler_plans_proof() {
+        let request = json!({
+            "query": "config",
+            "address": "SysvarC1ock11111111111111111111111111111111",
+            "program_id": "11111111111111111111111111111111",
+            "dry_run": true
+        });
+        let plan = SolanaHandler.generate_proof(&request, "https://solana.example").await.unwrap();
+        assert_eq!(plan["calls"][0]["method"], "getMultipleAccounts");
+        assert_eq!(plan["calls"][0]["endpoint"], "https://solana.example");
+
+        let without_program = json!({ "query": "config", "address": "SysvarC1ock11111111111111111111111111111111" });
+        assert!(SolanaHandler.generate_proof(&without_program, "https://solana.example").await.is_err());
+
+        // Semantic validation reads the asset's history, which a plan cannot
+        let mut validated = request.clone();
+        validated["validate_semantics"] = true.into();
+        let error = SolanaHandler.generate_proof(&validated, "https://solana.example").await.unwrap_err();
+        assert!(error.to_string().contains("--dry-run cannot plan --validate-semantics"));
+    }
+}
@@ -0,0 +1,302 @@
+//! Storage slot collision detection across layouts
+//!
+//! Layouts compiled from several sources often share storage: a proxy and
+//! its implementation, the facets of a diamond, or successive versions of
+//! an upgradeable contract. A field whose bytes overlap another field's is
+//! read as something it is not, and nothing fails. [`LayoutConflicts`]
+//! reports every such overlap, within one layout or between several, and
+//! every change in the order of fields two layouts share, which is how a
+//! reordered inheritance list shows up.
+//!
+//! A field declared the same way in two layouts — same label, slot, offset
+//! and type, as when both inherit one base — is the same variable and does
+//! not collide with itself.
+
+use crate::{LayoutInfo, StorageEntry};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/// A storage field as one layout places it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SlotOccupant {
+    /// Contract of the layout declaring the field
+    pub contract: String,
+    pub label: String,
+    pub slot: String,
+    pub offset: u8,
+    pub type_name: String,
+}
+
+impl SlotOccupant {
+    fn new(layout: &LayoutInfo, entry: &StorageEntry) -> Self {
+        Self {
+            contract: layout.contract_name.clone(),
+            label: entry.label.clone(),
+            slot: entry.slot.clone(),
+            offset: entry.offset,
+            type_name: entry.type_name.clone(),
+        }
+    }
+}
+
+impl fmt::Display for SlotOccupant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{} ({}) at slot {} offset {}",
+            self.contract, self.label, self.type_name, self.slot, self.offset
+        )
+    }
+}
+
+/// Two fields whose bytes overlap
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SlotCollision {
+    pub first: SlotOccupant,
+    pub second: SlotOccupant,
+}
+
+impl fmt::Display for SlotCollision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} overlaps {}", self.first, self.second)
+    }
+}
+
+/// Fields two layouts share, declared in a different order
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FieldReorder {
+    pub first_contract: String,
+    pub second_contract: String,
+    /// Shared fields out of place, in the second layout's order
+    pub labels: Vec<String>,
+}
+
+impl fmt::Display for FieldReorder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} declares {} in a different order than {}",
+            self.second_contract,
+            self.labels.join(", "),
+            self.first_contract
+        )
+    }
+}
+
+/// Overlapping fields and order changes among layouts sharing storage
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LayoutConflicts {
+    pub collisions: Vec<SlotCollision>,
+    /// Order changes between each layout and the next
+    pub reorders: Vec<FieldReorder>,
+}
+
+impl LayoutConflicts {
+    /// Check `layouts`, in upgrade order where they are versions of one contract
+    ///
+    /// Every pair of fields is compared, within a layout and across layouts;
+    /// shared fields are compared for order between consecutive layouts.
+    pub fn check(layouts: &[&LayoutInfo]) -> Self {
+        let fields: Vec<(&LayoutInfo, &StorageEntry, Span)> = layouts
+            .iter()
+            .flat_map(|layout| layout.storage.iter().map(move |entry| (*layout, entry, Span::of(layout, entry))))
+            .collect();
+
+        let mut collisions = Vec::new();
+        for (i, (first_layout, first, first_span)) in fields.iter().enumerate() {
+            for (second_layout, second, second_span) in &fields[i + 1..] {
+                if first_span.overlaps(second_span) && !same_declaration(first, second) {
+                    collisions.push(SlotCollision {
+                        first: SlotOccupant::new(first_layout, first),
+                        second: SlotOccupant::new(second_layout, second),
+                    });
+                }
+            }
+        }
+
+        let reorders = layouts
+            .windows(2)
+            .filter_map(|pair| reordered(pair[0], pair[1]))
+            .collect();
+
+        Self { collisions, reorders }
+    }
+
+    /// Whether no fields overlap and no shared fields moved in order
+    pub fn is_empty(&self) -> bool {
+        self.collisions.is_empty() && self.reorders.is_empty()
+    }
+}
+
+/// Bytes a field occupies
+///
+/// Numeric slots address storage as one byte range, `slot * 32 + offset`,
+/// so a struct spanning several slots overlaps the fields after it. Slots
+/// that are not numbers, or too large, only meet fields in the same slot.
+#[derive(Debug, PartialEq, Eq)]
+struct Span {
+    /// Slot for slots outside the byte range
+    slot: Option<String>,
+    start: u128,
+    end: u128,
+}
+
+impl Span {
+    fn of(layout: &LayoutInfo, entry: &StorageEntry) -> Self {
+        let size = layout
+            .types
+            .iter()
+            .find(|t| t.label == entry.type_name)
+            .and_then(|t| t.number_of_bytes.parse::<u128>().ok())
+            .filter(|size| *size > 0)
+            .unwrap_or(32);
+        // Values over a slot start their own and fill whole slots
+        let (offset, size) = if size > 32 {
+            (0, size.div_ceil(32) * 32)
+        } else {
+            (u128::from(entry.offset), size)
+        };
+        let linear = entry
+            .slot
+            .parse::<u128>()
+            .ok()
+            .and_then(|slot| slot.checked_mul(32))
+            .and_then(|base| base.checked_add(offset))
+            .and_then(|start| Some((start, start.checked_add(size)?)));
+        match linear {
+            Some((start, end)) => Self { slot: None, start, end },
+            None => Self {
+                slot: Some(entry.slot.to_string()),
+                start: offset,
+                end: offset + size,
+            },
+        }
+    }
+
+    fn overlaps(&self, other: &Span) -> bool {
+        self.slot == other.slot && self.start < other.end && other.start < self.end
+    }
+}
+
+fn same_declaration(a: &StorageEntry, b: &StorageEntry) -> bool {
+    a.label == b.label && a.slot == b.slot && a.offset == b.offset && a.type_name == b.type_name
+}
+
+/// Shared fields of `second` not in the order `first` declares them
+fn reordered(first: &LayoutInfo, second: &LayoutInfo) -> Option<FieldReorder> {
+    let shared = |layout: &LayoutInfo, other: &LayoutInfo| -> Vec<String> {
+        layout
+            .storage
+            .iter()
+            .filter(|entry| other.storage.iter().any(|e| e.label == entry.label))
+            .map(|entry| entry.label.clone())
+            .collect()
+    };
+    let before = shared(first, second);
+    let after = shared(second, first);
+    let labels: Vec<String> = after
+        .iter()
+        .zip(&before)
+        .filter(|(a, b)| a != b)
+        .map(|(a, _)| a.clone())
+        .collect();
+    (!labels.is_empty()).then(|| FieldReorder {
+        first_contract: first.contract_name.clone(),
+        second_contract: second.contract_name.clone(),
+        labels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TypeInfo, ZeroSemantics};
+    use alloc::vec;
+
+    fn entry(label: &str, slot: &str, offset: u8, type_name: &str) -> StorageEntry {
+        StorageEntry {
+            label: label.to_string(),
+            slot: slot.to_string(),
+            offset,
+            type_name: type_name.to_string(),
+            zero_semantics: ZeroSemantics::NeverWritten,
+        }
+    }
+
+    fn inplace(label: &str, bytes: &str) -> TypeInfo {
+        TypeInfo {
+            label: label.to_string(),
+            number_of_bytes: bytes.to_string(),
+            encoding: "inplace".to_string(),
+            base: None,
+            key: None,
+            value: None,
+        }
+    }
+
+    fn layout(name: &str, storage: Vec<StorageEntry>) -> LayoutInfo {
+        LayoutInfo {
+            contract_name: name.to_string(),
+            storage,
+            types: vec![
+                inplace("t_uint256", "32"),
+                inplace("t_address", "20"),
+                inplace("t_bool", "1"),
+                inplace("t_struct(Config)", "64"),
+            ],
+            code_variables: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_packed_fields_do_not_collide() {
+        let packed = layout("Vault", vec![entry("owner", "0", 0, "t_address"), entry("paused", "0", 20, "t_bool")]);
+        assert!(LayoutConflicts::check(&[&packed]).is_empty());
+
+        let overlapping = layout("Vault", vec![entry("owner", "0", 0, "t_address"), entry("paused", "0", 19, "t_bool")]);
+        let conflicts = LayoutConflicts::check(&[&overlapping]);
+        assert_eq!(conflicts.collisions.len(), 1);
+        assert_eq!(conflicts.collisions[0].second.label, "paused");
+
+        // A two-slot struct at slot 1 covers slot 2
+        let spanning = layout("Vault", vec![entry("config", "1", 0, "t_struct(Config)"), entry("total", "2", 0, "t_uint256")]);
+        assert_eq!(LayoutConflicts::check(&[&spanning]).collisions.len(), 1);
+    }
+
+    #[test]
+    fn test_upgrade_inserting_a_field() {
+        let v1 = layout("VaultV1", vec![entry("owner", "0", 0, "t_address"), entry("total", "1", 0, "t_uint256")]);
+        let v2 = layout(
+            "VaultV2",
+            vec![entry("owner", "0", 0, "t_address"), entry("fee", "1", 0, "t_uint256"), entry("total", "2", 0, "t_uint256")],
+        );
+
+        // The shared base field is one variable; the inserted one takes total's slot
+        let conflicts = LayoutConflicts::check(&[&v1, &v2]);
+        assert_eq!(conflicts.collisions.len(), 1);
+        assert_eq!(conflicts.collisions[0].first.label, "total");
+        assert_eq!(conflicts.collisions[0].second.label, "fee");
+        assert!(conflicts.reorders.is_empty());
+    }
+
+    #[test]
+    fn test_inheritance_order_change() {
+        // `contract Vault is Ownable, Pausable` becoming `is Pausable, Ownable`
+        let before = layout("Vault", vec![entry("owner", "0", 0, "t_address"), entry("paused", "1", 0, "t_bool")]);
+        let after = layout("Vault", vec![entry("paused", "0", 0, "t_bool"), entry("owner", "1", 0, "t_address")]);
+
+        let conflicts = LayoutConflicts::check(&[&before, &after]);
+        assert_eq!(conflicts.reorders.len(), 1);
+        assert_eq!(conflicts.reorders[0].labels, vec!["paused".to_string(), "owner".to_string()]);
+        assert_eq!(conflicts.collisions.len(), 2);
+
+        // Huge slots, such as namespaced storage, still meet fields in the same slot
+        let namespaced = "0x52c63247e1f47db19d5ce0460030c497f067ca4cebf71ba98eeadabe20bace00";
+        let facets = layout("Facet", vec![entry("a", namespaced, 0, "t_uint256"), entry("b", namespaced, 0, "t_uint256")]);
+        assert_eq!(LayoutConflicts::check(&[&facets]).collisions.len(), 1);
+    }
+}
@@ -149,18 +149,30 @@ impl TryFrom<&crate::LayoutInfo> for ConstrainedLayoutInfo {
         let mut constrained_storage = Vec::new();
 
         for entry in &layout.storage {
-            // Parse slot as hex and convert to bytes
-            let slot_hex = entry.slot.strip_prefix("0x").unwrap_or(&entry.slot);
+            // Canonical layouts store slots as decimal; accept 0x-prefixed hex too
             let mut slot_bytes = [0u8; 32];
-            
-            // Parse hex slot to bytes
-            if slot_hex.len() <= 64 { // Max 32 bytes * 2 hex chars
-                let slot_vec = hex::decode(slot_hex)
-                    .map_err(|e| crate::TraverseError::InvalidInput(format!("Invalid slot hex: {}", e)))?;
-                
-                if slot_vec.len() <= 32 {
-                    slot_bytes[32 - slot_vec.len()..].copy_from_slice(&slot_vec);
+            if let Ok(slot) = entry.slot.parse::<u64>() {
+                slot_bytes[24..].copy_from_slice(&slot.to_be_bytes());
+            } else if let Some(slot_hex) = entry.slot.strip_prefix("0x") {
+                if slot_hex.len() <= 64 { // Max 32 bytes * 2 hex chars
+                    let padded;
+                    let slot_hex = if slot_hex.len() % 2 == 1 {
+                        padded = format!("0{}", slot_hex);
+                        padded.as_str()
+                    } else {
+                        slot_hex
+                    };
+                    let slot_vec = hex::decode(slot_hex)
+                        .map_err(|e| crate::TraverseError::InvalidInput(format!("Invalid slot hex: {}", e)))?;
+
+                    if slot_vec.len() <= 32 {
+                        slot_bytes[32 - slot_vec.len()..].copy_from_slice(&slot_vec);
+                    }
                 }
+            } else {
+                return Err(crate::TraverseError::InvalidInput(
+                    format!("Invalid slot: {}", entry.slot)
+                ));
             }
 
             // Determine field type from type name
@@ -0,0 +1,312 @@
+//! Storage layout diffing
+//!
+//! Compares two versions of a contract's layout field by field. Contract
+//! upgrades must not move, retype or remove existing storage variables, so
+//! [`LayoutDiff::is_breaking`] flags exactly those changes while appended
+//! fields and zero-semantics updates are reported as compatible.
+
+use crate::{LayoutInfo, StorageEntry, TypeInfo, ZeroSemantics};
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/// A change to a single storage field between two layouts
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FieldChange {
+    /// Field only present in the new layout
+    Added {
+        label: String,
+        slot: String,
+        offset: u8,
+        type_name: String,
+    },
+    /// Field only present in the old layout
+    Removed {
+        label: String,
+        slot: String,
+        offset: u8,
+        type_name: String,
+    },
+    /// Field stored at a different slot or offset
+    Moved {
+        label: String,
+        old_slot: String,
+        old_offset: u8,
+        new_slot: String,
+        new_offset: u8,
+    },
+    /// Field type name or type definition changed
+    Retyped {
+        label: String,
+        old_type: String,
+        new_type: String,
+    },
+    /// Declared zero semantics changed
+    SemanticsChanged {
+        label: String,
+        old: ZeroSemantics,
+        new: ZeroSemantics,
+    },
+}
+
+impl FieldChange {
+    /// Label of the changed field
+    pub fn label(&self) -> &str {
+        match self {
+            FieldChange::Added { label, .. }
+            | FieldChange::Removed { label, .. }
+            | FieldChange::Moved { label, .. }
+            | FieldChange::Retyped { label, .. }
+            | FieldChange::SemanticsChanged { label, .. } => label,
+        }
+    }
+
+    /// Whether existing storage is read differently after this change
+    pub fn is_breaking(&self) -> bool {
+        matches!(
+            self,
+            FieldChange::Removed { .. } | FieldChange::Moved { .. } | FieldChange::Retyped { .. }
+        )
+    }
+}
+
+impl fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldChange::Added { label, slot, offset, type_name } => {
+                write!(f, "added     {} ({}) at slot {} offset {}", label, type_name, slot, offset)
+            }
+            FieldChange::Removed { label, slot, offset, type_name } => {
+                write!(f, "removed   {} ({}) from slot {} offset {}", label, type_name, slot, offset)
+            }
+            FieldChange::Moved { label, old_slot, old_offset, new_slot, new_offset } => write!(
+                f,
+                "moved     {} from slot {} offset {} to slot {} offset {}",
+                label, old_slot, old_offset, new_slot, new_offset
+            ),
+            FieldChange::Retyped { label, old_type, new_type } => {
+                write!(f, "retyped   {} from {} to {}", label, old_type, new_type)
+            }
+            FieldChange::SemanticsChanged { label, old, new } => {
+                write!(f, "semantics {} from {:?} to {:?}", label, old, new)
+            }
+        }
+    }
+}
+
+/// Field-level differences between two layouts
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LayoutDiff {
+    /// Commitment of the old layout
+    pub old_commitment: [u8; 32],
+    /// Commitment of the new layout
+    pub new_commitment: [u8; 32],
+    /// Changes in old-layout order, followed by added fields
+    pub changes: Vec<FieldChange>,
+}
+
+impl LayoutDiff {
+    /// Compare `old` against `new`
+    ///
+    /// Fields are matched by label. Slot numbers are compared numerically when
+    /// both parse as decimal, so `"1"` and `"01"` are the same slot.
+    pub fn between(old: &LayoutInfo, new: &LayoutInfo) -> Self {
+        let mut changes = Vec::new();
+
+        for old_entry in &old.storage {
+            let Some(new_entry) = new.storage.iter().find(|e| e.label == old_entry.label) else {
+                changes.push(FieldChange::Removed {
+                    label: old_entry.label.clone(),
+                    slot: old_entry.slot.clone(),
+                    offset: old_entry.offset,
+                    type_name: old_entry.type_name.clone(),
+                });
+                continue;
+            };
+
+            if !same_slot(&old_entry.slot, &new_entry.slot) || old_entry.offset != new_entry.offset {
+                changes.push(FieldChange::Moved {
+                    label: old_entry.label.clone(),
+                    old_slot: old_entry.slot.clone(),
+                    old_offset: old_entry.offset,
+                    new_slot: new_entry.slot.clone(),
+                    new_offset: new_entry.offset,
+                });
+            }
+
+            if old_entry.type_name != new_entry.type_name
+                || !same_type_definition(old, new, &old_entry.type_name)
+            {
+                changes.push(FieldChange::Retyped {
+                    label: old_entry.label.clone(),
+                    old_type: old_entry.type_name.clone(),
+                    new_type: new_entry.type_name.clone(),
+                });
+            }
+
+            if old_entry.zero_semantics != new_entry.zero_semantics {
+                changes.push(FieldChange::SemanticsChanged {
+                    label: old_entry.label.clone(),
+                    old: old_entry.zero_semantics,
+                    new: new_entry.zero_semantics,
+                });
+            }
+        }
+
+        for new_entry in &new.storage {
+            if !old.storage.iter().any(|e| e.label == new_entry.label) {
+                changes.push(added(new_entry));
+            }
+        }
+
+        Self {
+            old_commitment: old.commitment(),
+            new_commitment: new.commitment(),
+            changes,
+        }
+    }
+
+    /// Whether the layouts have no field-level differences
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Whether any change alters how existing storage is read
+    pub fn is_breaking(&self) -> bool {
+        self.changes.iter().any(FieldChange::is_breaking)
+    }
+
+    /// Whether the layout commitment changed
+    ///
+    /// This can be true even when [`is_empty`](Self::is_empty) is, e.g. when
+    /// only the contract name or field order changed.
+    pub fn commitment_changed(&self) -> bool {
+        self.old_commitment != self.new_commitment
+    }
+}
+
+fn added(entry: &StorageEntry) -> FieldChange {
+    FieldChange::Added {
+        label: entry.label.clone(),
+        slot: entry.slot.clone(),
+        offset: entry.offset,
+        type_name: entry.type_name.clone(),
+    }
+}
+
+fn same_slot(a: &str, b: &str) -> bool {
+    match (a.parse::<u128>(), b.parse::<u128>()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn find_type<'a>(layout: &'a LayoutInfo, label: &str) -> Option<&'a TypeInfo> {
+    layout.types.iter().find(|t| t.label == label)
+}
+
+/// Whether `label` has the same definition in both layouts
+fn same_type_definition(old: &LayoutInfo, new: &LayoutInfo, label: &str) -> bool {
+    match (find_type(old, label), find_type(new, label)) {
+        (Some(a), Some(b)) => {
+            a.number_of_bytes == b.number_of_bytes
+                && a.encoding == b.encoding
+                && a.base == b.base
+                && a.key == b.key
+                && a.value == b.value
+        }
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{string::ToString, vec};
+
+    fn entry(label: &str, slot: &str, offset: u8, type_name: &str) -> StorageEntry {
+        StorageEntry {
+            label: label.to_string(),
+            slot: slot.to_string(),
+            offset,
+            type_name: type_name.to_string(),
+            zero_semantics: ZeroSemantics::NeverWritten,
+        }
+    }
+
+    fn uint(label: &str, bytes: &str) -> TypeInfo {
+        TypeInfo {
+            label: label.to_string(),
+            number_of_bytes: bytes.to_string(),
+            encoding: "inplace".to_string(),
+            base: None,
+            key: None,
+            value: None,
+        }
+    }
+
+    fn layout(storage: Vec<StorageEntry>) -> LayoutInfo {
+        LayoutInfo {
+            contract_name: "Vault".to_string(),
+            storage,
+            types: vec![uint("t_uint256", "32"), uint("t_uint128", "16"), uint("t_address", "20")],
+            code_variables: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_identical_layouts() {
+        let a = layout(vec![entry("owner", "0", 0, "t_address")]);
+        let diff = LayoutDiff::between(&a, &a.clone());
+        assert!(diff.is_empty());
+        assert!(!diff.is_breaking());
+        assert!(!diff.commitment_changed());
+    }
+
+    #[test]
+    fn test_append_is_compatible() {
+        let old = layout(vec![entry("owner", "0", 0, "t_address")]);
+        let new = layout(vec![entry("owner", "0", 0, "t_address"), entry("fee", "1", 0, "t_uint256")]);
+
+        let diff = LayoutDiff::between(&old, &new);
+        assert_eq!(diff.changes.len(), 1);
+        assert!(matches!(diff.changes[0], FieldChange::Added { .. }));
+        assert!(!diff.is_breaking());
+        assert!(diff.commitment_changed());
+    }
+
+    #[test]
+    fn test_breaking_changes() {
+        let old = layout(vec![
+            entry("owner", "0", 0, "t_address"),
+            entry("total", "1", 0, "t_uint256"),
+            entry("legacy", "2", 0, "t_uint256"),
+        ]);
+        let new = layout(vec![
+            entry("owner", "00", 0, "t_address"),
+            entry("total", "3", 0, "t_uint128"),
+        ]);
+
+        let diff = LayoutDiff::between(&old, &new);
+        assert!(diff.is_breaking());
+        assert_eq!(
+            diff.changes.iter().map(FieldChange::label).collect::<Vec<_>>(),
+            vec!["total", "total", "legacy"]
+        );
+        assert!(matches!(diff.changes[0], FieldChange::Moved { .. }));
+        assert!(matches!(diff.changes[1], FieldChange::Retyped { .. }));
+        assert!(matches!(diff.changes[2], FieldChange::Removed { .. }));
+    }
+
+    #[test]
+    fn test_type_definition_change_is_retype() {
+        let old = layout(vec![entry("total", "0", 0, "t_uint256")]);
+        let mut new = old.clone();
+        new.types[0].number_of_bytes = "16".to_string();
+
+        let diff = LayoutDiff::between(&old, &new);
+        assert!(matches!(diff.changes.as_slice(), [FieldChange::Retyped { .. }]));
+    }
+}
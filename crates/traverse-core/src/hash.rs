@@ -0,0 +1,70 @@
+//! Hashing and trie path helpers shared by the chain crates
+//!
+//! Storage keys, Merkle-Patricia trie nodes and many chain-specific
+//! commitments are keccak256 hashes, and trie nodes store their paths
+//! hex-prefix encoded. Both the fetchers and the no_std verifiers need these,
+//! so they live here rather than in each module that hashes.
+
+use alloc::vec::Vec;
+use tiny_keccak::{Hasher, Keccak};
+
+/// keccak256 of `data`
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    keccak256_concat(&[data])
+}
+
+/// keccak256 of `parts` concatenated
+pub fn keccak256_concat(parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    for part in parts {
+        hasher.update(part);
+    }
+    let mut out = [0u8; 32];
+    hasher.finalize(&mut out);
+    out
+}
+
+/// Split bytes into nibbles, high nibble first
+pub fn nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect()
+}
+
+/// Decode a hex-prefix encoded trie path into nibbles and a leaf flag
+pub fn decode_compact(encoded: &[u8]) -> Result<(Vec<u8>, bool), &'static str> {
+    let first = *encoded.first().ok_or("empty node path")?;
+    let flag = first >> 4;
+    if flag > 3 {
+        return Err("bad node path prefix");
+    }
+
+    let mut path = Vec::with_capacity(encoded.len() * 2);
+    if flag & 1 == 1 {
+        path.push(first & 0x0f);
+    }
+    path.extend(nibbles(&encoded[1..]));
+    Ok((path, flag >= 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keccak256() {
+        assert_eq!(
+            hex::encode(keccak256(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        assert_eq!(keccak256(b"ab"), keccak256_concat(&[b"a", b"b"]));
+    }
+
+    #[test]
+    fn test_decode_compact() {
+        // Odd extension, even leaf, odd leaf
+        assert_eq!(decode_compact(&[0x1a, 0xbc]).unwrap(), (alloc::vec![0xa, 0xb, 0xc], false));
+        assert_eq!(decode_compact(&[0x20, 0xab]).unwrap(), (alloc::vec![0xa, 0xb], true));
+        assert_eq!(decode_compact(&[0x3f]).unwrap(), (alloc::vec![0xf], true));
+        assert!(decode_compact(&[]).is_err());
+        assert!(decode_compact(&[0x40]).is_err());
+    }
+}
@@ -123,6 +123,51 @@ pub struct StaticKeyPath {
     pub zero_semantics: ZeroSemantics,
 }
 
+/// How a field's value is encoded in its slot, for extraction
+///
+/// Solidity stores an `intN` or `uintN` narrower than 256 bits in its own
+/// `N / 8` bytes; a signed value is only sign-extended once read. Circuits
+/// use this to turn the field's bytes into a full 256-bit word.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct FieldEncoding {
+    /// Whether the value is a two's complement signed integer
+    pub signed: bool,
+    /// Width of the value in bits, at most 256
+    pub bits: u16,
+    /// Byte offset of the field within its slot, from the low-order end
+    pub offset: u8,
+}
+
+impl FieldEncoding {
+    /// A full-width unsigned word, the encoding of anything not an integer
+    pub const WORD: FieldEncoding = FieldEncoding { signed: false, bits: 256, offset: 0 };
+
+    /// Truncate a right-aligned value to its width, sign-extending signed values
+    ///
+    /// Bits above the width are cleared, or for a negative signed value set,
+    /// so the word reads as the same number at 256 bits.
+    pub fn apply(&self, value: &[u8; 32]) -> [u8; 32] {
+        let bits = self.bits.min(256) as usize;
+        if bits == 0 {
+            return [0u8; 32];
+        }
+        let negative = self.signed && (value[31 - (bits - 1) / 8] >> ((bits - 1) % 8)) & 1 == 1;
+        let fill = if negative { 0xff } else { 0x00 };
+        let mut word = *value;
+        for (i, byte) in word.iter_mut().enumerate() {
+            // Bits of this byte, counted from the low-order end of the word
+            let low = (31 - i) * 8;
+            if low >= bits {
+                *byte = fill;
+            } else if low + 8 > bits {
+                let keep = (1u8 << (bits - low)) - 1;
+                *byte = (*byte & keep) | (fill & !keep);
+            }
+        }
+        word
+    }
+}
+
 /// Semantic storage proof for ZK coprocessor verification
 ///
 /// Contains all the information needed to verify a storage proof in a ZK circuit
@@ -167,6 +212,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_field_encoding_apply() {
+        let word = |tail: &[u8]| {
+            let mut word = [0u8; 32];
+            word[32 - tail.len()..].copy_from_slice(tail);
+            word
+        };
+
+        // int24 -1 and -8388608 extend to 256 bits; a positive value is unchanged
+        let int24 = FieldEncoding { signed: true, bits: 24, offset: 0 };
+        assert_eq!(int24.apply(&word(&[0xff, 0xff, 0xff])), [0xff; 32]);
+        let mut min = [0xff; 32];
+        min[29..].copy_from_slice(&[0x80, 0x00, 0x00]);
+        assert_eq!(int24.apply(&word(&[0x80, 0x00, 0x00])), min);
+        assert_eq!(int24.apply(&word(&[0x7f, 0x00, 0x01])), word(&[0x7f, 0x00, 0x01]));
+
+        // Bits above a uintN are dropped, even within a byte
+        let uint12 = FieldEncoding { signed: false, bits: 12, offset: 0 };
+        assert_eq!(uint12.apply(&word(&[0xab, 0xff, 0xff])), word(&[0x0f, 0xff]));
+        let int12 = FieldEncoding { signed: true, bits: 12, offset: 0 };
+        let mut negative = [0xff; 32];
+        negative[30] = 0xf8;
+        negative[31] = 0x00;
+        assert_eq!(int12.apply(&word(&[0x08, 0x00])), negative);
+
+        assert_eq!(FieldEncoding::WORD.apply(&[0xaa; 32]), [0xaa; 32]);
+    }
+
     #[test]
     fn test_zero_semantics() {
         let semantics = StorageSemantics::new(ZeroSemantics::NeverWritten);
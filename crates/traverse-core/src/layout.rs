@@ -34,9 +34,20 @@ pub struct LayoutInfo {
     pub storage: Vec<StorageEntry>,
     /// ABI types information for proper field size calculation
     pub types: Vec<TypeInfo>,
+    /// State variables kept in contract code rather than storage
+    ///
+    /// They have no slot; they are listed so a query naming one can say so
+    /// instead of failing as an unknown field. Not part of the commitment.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub code_variables: Vec<CodeVariable>,
 }
 
 impl LayoutInfo {
+    /// The code variable named `label`, if the layout lists one
+    pub fn code_variable(&self, label: &str) -> Option<&CodeVariable> {
+        self.code_variables.iter().find(|variable| variable.label == label)
+    }
+
     /// Validate the storage layout for consistency and correctness
     ///
     /// This performs sanity checks on the layout to catch common errors:
@@ -215,6 +226,7 @@ impl LayoutInfo {
     ///     contract_name: "MyContract".into(),
     ///     storage: vec![],
     ///     types: vec![],
+    ///     code_variables: Vec::new(),
     /// };
     /// let commitment = layout.commitment();
     /// assert_eq!(commitment.len(), 32);
@@ -253,6 +265,63 @@ impl LayoutInfo {
         
         hasher.finalize().into()
     }
+
+    /// Size in bytes of `entry`'s type
+    ///
+    /// `None` when the type is missing from the layout or its size is not a
+    /// number of bytes that fits in a slot.
+    pub fn field_size(&self, entry: &StorageEntry) -> Option<u8> {
+        self.types
+            .iter()
+            .find(|t| t.label == entry.type_name)
+            .and_then(|t| t.number_of_bytes.parse::<u8>().ok())
+            .filter(|size| *size <= 32)
+    }
+
+    /// Whether `entry` shares its slot with other fields
+    ///
+    /// A packed field is read by masking its bytes out of the slot's word,
+    /// see [`extract_packed`]. Fields that fill their slot are never packed,
+    /// even when a struct's members are listed at the struct's own slot.
+    pub fn is_packed(&self, entry: &StorageEntry) -> bool {
+        let size = self
+            .types
+            .iter()
+            .find(|t| t.label == entry.type_name)
+            .and_then(|t| t.number_of_bytes.parse::<u64>().ok());
+        if size.is_some_and(|size| size >= 32) {
+            return false;
+        }
+        entry.offset > 0
+            || self
+                .storage
+                .iter()
+                .any(|other| other.slot == entry.slot && other.label != entry.label)
+    }
+}
+
+/// A packed field's bytes, right-aligned in an otherwise zero word
+///
+/// Solidity fills a slot from its low-order end, so a field of `size` bytes
+/// at `offset` occupies `word[32 - offset - size..32 - offset]`. Returns
+/// `None` when the field would run past the slot.
+///
+/// ```rust
+/// use traverse_core::layout::extract_packed;
+///
+/// // address owner at offset 0, bool paused at offset 20
+/// let mut word = [0u8; 32];
+/// word[11] = 1;
+/// word[31] = 0xaa;
+/// assert_eq!(extract_packed(&word, 20, 1).unwrap()[31], 1);
+/// assert_eq!(extract_packed(&word, 0, 20).unwrap()[11], 0);
+/// ```
+pub fn extract_packed(word: &[u8; 32], offset: u8, size: u8) -> Option<[u8; 32]> {
+    let end = 32usize.checked_sub(offset as usize)?;
+    let start = end.checked_sub(size as usize)?;
+    let mut field = [0u8; 32];
+    field[32 - (end - start)..].copy_from_slice(&word[start..end]);
+    Some(field)
 }
 
 /// A single entry in the storage layout
@@ -275,6 +344,27 @@ pub struct StorageEntry {
     pub zero_semantics: ZeroSemantics,
 }
 
+/// A state variable whose value lives in contract code, not storage
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CodeVariable {
+    /// Variable name as it appears in the contract source
+    pub label: String,
+    /// Type identifier, as for storage entries
+    pub type_name: String,
+    /// Whether the value is a compile-time constant or set at deployment
+    pub kind: CodeVariableKind,
+}
+
+/// How a code variable gets its value
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CodeVariableKind {
+    /// Fixed at compile time and inlined where it is used
+    Constant,
+    /// Set by the constructor and written into the deployed bytecode
+    Immutable,
+}
+
 /// Type information for ABI types
 ///
 /// Provides detailed information about the types used in storage variables,
@@ -319,6 +409,7 @@ mod tests {
                 key: None,
                 value: None,
             }],
+            code_variables: Vec::new(),
         };
 
         let commitment = layout.commitment();
@@ -368,6 +459,7 @@ mod tests {
                     value: None,
                 },
             ],
+            code_variables: Vec::new(),
         };
         
         assert!(valid_layout.validate().is_ok());
@@ -390,6 +482,7 @@ mod tests {
                 key: None,
                 value: None,
             }],
+            code_variables: Vec::new(),
         };
         
         let result = invalid_overflow.validate();
@@ -407,6 +500,7 @@ mod tests {
                 zero_semantics: ZeroSemantics::ValidZero,
             }],
             types: alloc::vec![],
+            code_variables: Vec::new(),
         };
         
         let result = invalid_missing_type.validate();
@@ -440,6 +534,7 @@ mod tests {
                 key: None,
                 value: None,
             }],
+            code_variables: Vec::new(),
         };
         
         let result = invalid_duplicate.validate();
@@ -473,6 +568,7 @@ mod tests {
                 key: None,
                 value: None,
             }],
+            code_variables: Vec::new(),
         };
         
         let result = invalid_overlap.validate();
@@ -530,6 +626,7 @@ mod tests {
                     value: None,
                 },
             ],
+            code_variables: Vec::new(),
         };
         
         assert!(valid_packed.validate().is_ok());
@@ -552,6 +649,7 @@ mod tests {
                 key: None,
                 value: None,
             }],
+            code_variables: Vec::new(),
         };
         
         let result = invalid_alignment.validate();
@@ -576,6 +674,7 @@ mod tests {
                 key: None,  // Missing key
                 value: None, // Missing value
             }],
+            code_variables: Vec::new(),
         };
         
         let result = invalid_mapping.validate();
@@ -605,6 +704,7 @@ mod tests {
                 },
             ],
             types: alloc::vec![],
+            code_variables: Vec::new(),
         };
         
         let layout2 = LayoutInfo {
@@ -626,6 +726,7 @@ mod tests {
                 },
             ],
             types: alloc::vec![],
+            code_variables: Vec::new(),
         };
         
         let commitment1 = layout1.commitment();
@@ -637,6 +738,7 @@ mod tests {
             contract_name: "DifferentContract".into(),
             storage: layout1.storage.clone(),
             types: alloc::vec![],
+            code_variables: Vec::new(),
         };
         
         let commitment3 = layout3.commitment();
@@ -647,6 +749,7 @@ mod tests {
             contract_name: "EmptyContract".into(),
             storage: alloc::vec![],
             types: alloc::vec![],
+            code_variables: Vec::new(),
         };
         
         let empty_commitment = empty_layout.commitment();
@@ -664,6 +767,7 @@ mod tests {
                 zero_semantics: ZeroSemantics::ValidZero,
             }],
             types: alloc::vec![],
+            code_variables: Vec::new(),
         };
         
         let layout5 = LayoutInfo {
@@ -676,10 +780,59 @@ mod tests {
                 zero_semantics: ZeroSemantics::ValidZero,
             }],
             types: alloc::vec![],
+            code_variables: Vec::new(),
         };
         
         let commitment4 = layout4.commitment();
         let commitment5 = layout5.commitment();
         assert_ne!(commitment4, commitment5, "Different offsets should produce different commitments");
     }
+
+    #[test]
+    fn test_packed_fields() {
+        let entry = |label: &str, slot: &str, offset: u8, type_name: &str| StorageEntry {
+            label: label.into(),
+            slot: slot.into(),
+            offset,
+            type_name: type_name.into(),
+            zero_semantics: ZeroSemantics::ValidZero,
+        };
+        let inplace = |label: &str, bytes: &str| TypeInfo {
+            label: label.into(),
+            number_of_bytes: bytes.into(),
+            encoding: "inplace".into(),
+            base: None,
+            key: None,
+            value: None,
+        };
+        let layout = LayoutInfo {
+            contract_name: "Packed".into(),
+            storage: alloc::vec![
+                entry("owner", "0", 0, "t_address"),
+                entry("paused", "0", 20, "t_bool"),
+                entry("supply", "1", 0, "t_uint256"),
+            ],
+            types: alloc::vec![inplace("t_address", "20"), inplace("t_bool", "1"), inplace("t_uint256", "32")],
+            code_variables: Vec::new(),
+        };
+
+        assert!(layout.is_packed(&layout.storage[0]));
+        assert!(layout.is_packed(&layout.storage[1]));
+        assert!(!layout.is_packed(&layout.storage[2]));
+        assert_eq!(layout.field_size(&layout.storage[1]), Some(1));
+
+        let mut word = [0u8; 32];
+        word[11] = 0x01;
+        word[12..].copy_from_slice(&[0xab; 20]);
+        let paused = extract_packed(&word, 20, 1).unwrap();
+        assert_eq!(paused[31], 1);
+        assert!(paused[..31].iter().all(|b| *b == 0));
+        let owner = extract_packed(&word, 0, 20).unwrap();
+        assert_eq!(&owner[12..], &[0xab; 20]);
+        assert!(owner[..12].iter().all(|b| *b == 0));
+
+        assert_eq!(extract_packed(&word, 0, 32), Some(word));
+        assert_eq!(extract_packed(&word, 20, 13), None);
+        assert_eq!(extract_packed(&word, 33, 0), None);
+    }
 }
@@ -40,21 +40,36 @@ extern crate alloc;
 extern crate std;
 
 // Module declarations
+pub mod collision;
+pub mod diff;
 pub mod error;
+pub mod hash;
 pub mod key;
 pub mod layout;
 pub mod semantic;
+pub mod trace;
 pub mod traits;
 
 // Constrained environment support
 #[cfg(any(feature = "no-std", feature = "constrained", feature = "embedded"))]
 pub mod constrained;
 
+// Property-based testing support
+#[cfg(feature = "test-utils")]
+pub mod testing;
+
+// Metrics for fetchers, resolvers and the CLI server
+#[cfg(feature = "telemetry")]
+pub mod telemetry;
+
 // Re-export all public types and traits for convenience
+pub use collision::{FieldReorder, LayoutConflicts, SlotCollision, SlotOccupant};
+pub use diff::{FieldChange, LayoutDiff};
 pub use error::TraverseError;
-pub use key::{Key, SemanticStorageProof, StaticKeyPath, StorageSemantics, ZeroSemantics};
-pub use layout::{LayoutInfo, StorageEntry, TypeInfo};
+pub use key::{FieldEncoding, Key, SemanticStorageProof, StaticKeyPath, StorageSemantics, ZeroSemantics};
+pub use layout::{extract_packed, CodeVariable, CodeVariableKind, LayoutInfo, StorageEntry, TypeInfo};
 pub use semantic::{ResolvedSemantics, SemanticResolver, SemanticSource, StorageSemanticsExt};
+pub use trace::{DerivationStep, DerivationTrace, TracingKeyResolver};
 pub use traits::KeyResolver;
 
 #[cfg(feature = "std")]
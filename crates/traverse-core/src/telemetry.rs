@@ -0,0 +1,106 @@
+//! Metrics shared by the chain crates and the CLI server
+//!
+//! Enabled with the `telemetry` feature. The helpers here record through the
+//! [`metrics`] facade, so nothing is collected until the application installs
+//! a recorder; `traverse-cli-core` installs a Prometheus one in serve mode and
+//! exposes it at `/metrics`. Chain crates pair these with `tracing` spans on
+//! their fetchers and resolvers.
+//!
+//! | Metric | Type | Labels |
+//! |--------|------|--------|
+//! | `traverse_rpc_requests_total` | counter | `chain`, `method`, `outcome` |
+//! | `traverse_rpc_duration_seconds` | histogram | `chain`, `method` |
+//! | `traverse_proof_size_bytes` | histogram | `chain`, `kind` |
+//! | `traverse_resolutions_total` | counter | `chain`, `outcome` |
+//! | `traverse_cache_requests_total` | counter | `kind`, `result` |
+//! | `traverse_batch_duration_seconds` | histogram | `chain` |
+//! | `traverse_batch_queries` | histogram | `chain` |
+
+use core::future::Future;
+use std::string::ToString;
+use std::time::{Duration, Instant};
+
+pub const RPC_REQUESTS_TOTAL: &str = "traverse_rpc_requests_total";
+pub const RPC_DURATION_SECONDS: &str = "traverse_rpc_duration_seconds";
+pub const PROOF_SIZE_BYTES: &str = "traverse_proof_size_bytes";
+pub const RESOLUTIONS_TOTAL: &str = "traverse_resolutions_total";
+pub const CACHE_REQUESTS_TOTAL: &str = "traverse_cache_requests_total";
+pub const BATCH_DURATION_SECONDS: &str = "traverse_batch_duration_seconds";
+pub const BATCH_QUERIES: &str = "traverse_batch_queries";
+
+/// Register units and help text with the installed recorder
+pub fn describe() {
+    use metrics::{describe_counter, describe_histogram, Unit};
+
+    describe_counter!(RPC_REQUESTS_TOTAL, "RPC requests sent, by outcome");
+    describe_histogram!(RPC_DURATION_SECONDS, Unit::Seconds, "RPC request latency");
+    describe_histogram!(PROOF_SIZE_BYTES, Unit::Bytes, "Size of fetched proofs");
+    describe_counter!(RESOLUTIONS_TOTAL, "Storage queries resolved, by outcome");
+    describe_counter!(CACHE_REQUESTS_TOTAL, "Response cache lookups, by hit or miss");
+    describe_histogram!(BATCH_DURATION_SECONDS, Unit::Seconds, "Duration of batch resolutions and fetches");
+    describe_histogram!(BATCH_QUERIES, Unit::Count, "Queries per batch");
+}
+
+fn outcome<T, E>(result: &Result<T, E>) -> &'static str {
+    if result.is_ok() {
+        "ok"
+    } else {
+        "error"
+    }
+}
+
+/// Await an RPC request, recording its latency and outcome
+///
+/// ```rust,ignore
+/// let request = async { client.post(url).json(&body).send().await?.json().await };
+/// #[cfg(feature = "telemetry")]
+/// let request = traverse_core::telemetry::observe_rpc("ethereum", "eth_getProof", request);
+/// let response = request.await?;
+/// ```
+pub async fn observe_rpc<T, E, F>(chain: &str, method: &str, request: F) -> Result<T, E>
+where
+    F: Future<Output = Result<T, E>>,
+{
+    let started = Instant::now();
+    let result = request.await;
+    record_rpc(chain, method, started.elapsed(), &result);
+    result
+}
+
+/// Record one RPC request that took `elapsed`
+pub fn record_rpc<T, E>(chain: &str, method: &str, elapsed: Duration, result: &Result<T, E>) {
+    metrics::counter!(
+        RPC_REQUESTS_TOTAL,
+        "chain" => chain.to_string(),
+        "method" => method.to_string(),
+        "outcome" => outcome(result)
+    )
+    .increment(1);
+    metrics::histogram!(RPC_DURATION_SECONDS, "chain" => chain.to_string(), "method" => method.to_string())
+        .record(elapsed.as_secs_f64());
+}
+
+/// Record the encoded size of a fetched proof
+///
+/// `kind` names what was proven, e.g. `storage` or `account`.
+pub fn record_proof_size(chain: &str, kind: &str, bytes: usize) {
+    metrics::histogram!(PROOF_SIZE_BYTES, "chain" => chain.to_string(), "kind" => kind.to_string())
+        .record(bytes as f64);
+}
+
+/// Record the outcome of resolving one query
+pub fn record_resolution<T, E>(chain: &str, result: &Result<T, E>) {
+    metrics::counter!(RESOLUTIONS_TOTAL, "chain" => chain.to_string(), "outcome" => outcome(result)).increment(1);
+}
+
+/// Record a response cache lookup
+pub fn record_cache(kind: &str, hit: bool) {
+    let result = if hit { "hit" } else { "miss" };
+    metrics::counter!(CACHE_REQUESTS_TOTAL, "kind" => kind.to_string(), "result" => result).increment(1);
+}
+
+/// Record a batch of `queries` that took `elapsed`
+pub fn record_batch(chain: &str, queries: usize, elapsed: Duration) {
+    metrics::histogram!(BATCH_DURATION_SECONDS, "chain" => chain.to_string()).record(elapsed.as_secs_f64());
+    metrics::histogram!(BATCH_QUERIES, "chain" => chain.to_string()).record(queries as f64);
+}
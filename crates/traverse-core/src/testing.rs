@@ -0,0 +1,383 @@
+//! Property-based testing strategies and round-trip checks
+//!
+//! Enabled with the `test-utils` feature. The strategies generate layouts
+//! that pass [`LayoutInfo::validate`] together with queries that name their
+//! fields, in the bracket syntax the resolvers share (`field`, `map[key]`,
+//! `map[key][key]`). The `check_*` functions return proptest's
+//! [`TestCaseError`] so they compose inside `proptest!` bodies with `?`:
+//!
+//! ```rust,ignore
+//! use proptest::prelude::*;
+//! use traverse_core::testing::{arb_layout_and_query, check_resolver_round_trip};
+//!
+//! proptest! {
+//!     #[test]
+//!     fn resolves((layout, query) in arb_layout_and_query()) {
+//!         check_resolver_round_trip(&MyResolver, &layout, &query)?;
+//!     }
+//! }
+//! ```
+//!
+//! Chain crates use these to check their resolvers; crates generated by
+//! `traverse-valence` codegen can depend on this feature to test against the
+//! same layouts.
+
+use crate::{KeyResolver, LayoutInfo, StorageEntry, StorageSemantics, TracingKeyResolver, TypeInfo, ZeroSemantics};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use proptest::prelude::*;
+use proptest::test_runner::TestCaseError;
+
+/// The kind of storage variable a generated layout entry has
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    /// `uint256` in its own slot
+    Uint256,
+    /// `bytes32` in its own slot
+    Bytes32,
+    /// `address`, `bool` and `uint64` packed into one slot
+    PackedSlot,
+    /// `mapping(address => uint256)`
+    AddressMapping,
+    /// `mapping(uint256 => uint256)`
+    UintMapping,
+    /// `mapping(address => mapping(address => uint256))`
+    NestedMapping,
+}
+
+impl FieldKind {
+    const ALL: [FieldKind; 6] = [
+        FieldKind::Uint256,
+        FieldKind::Bytes32,
+        FieldKind::PackedSlot,
+        FieldKind::AddressMapping,
+        FieldKind::UintMapping,
+        FieldKind::NestedMapping,
+    ];
+
+    /// Type labels for the entries of this kind, with their offsets
+    fn entries(self) -> &'static [(&'static str, u8)] {
+        match self {
+            FieldKind::Uint256 => &[("t_uint256", 0)],
+            FieldKind::Bytes32 => &[("t_bytes32", 0)],
+            FieldKind::PackedSlot => &[("t_address", 0), ("t_bool", 20), ("t_uint64", 24)],
+            FieldKind::AddressMapping => &[("t_mapping(t_address,t_uint256)", 0)],
+            FieldKind::UintMapping => &[("t_mapping(t_uint256,t_uint256)", 0)],
+            FieldKind::NestedMapping => &[("t_mapping(t_address,t_mapping(t_address,t_uint256))", 0)],
+        }
+    }
+}
+
+/// Every type a generated layout may reference
+fn type_table() -> Vec<TypeInfo> {
+    let inplace = |label: &str, bytes: &str| TypeInfo {
+        label: label.to_string(),
+        number_of_bytes: bytes.to_string(),
+        encoding: "inplace".to_string(),
+        base: None,
+        key: None,
+        value: None,
+    };
+    let mapping = |label: &str, key: &str, value: &str| TypeInfo {
+        label: label.to_string(),
+        number_of_bytes: "32".to_string(),
+        encoding: "mapping".to_string(),
+        base: None,
+        key: Some(key.to_string()),
+        value: Some(value.to_string()),
+    };
+
+    alloc::vec![
+        inplace("t_uint256", "32"),
+        inplace("t_bytes32", "32"),
+        inplace("t_address", "20"),
+        inplace("t_bool", "1"),
+        inplace("t_uint64", "8"),
+        mapping("t_mapping(t_address,t_uint256)", "t_address", "t_uint256"),
+        mapping("t_mapping(t_uint256,t_uint256)", "t_uint256", "t_uint256"),
+        mapping(
+            "t_mapping(t_address,t_mapping(t_address,t_uint256))",
+            "t_address",
+            "t_mapping(t_address,t_uint256)",
+        ),
+    ]
+}
+
+/// Any [`ZeroSemantics`]
+pub fn arb_zero_semantics() -> impl Strategy<Value = ZeroSemantics> {
+    prop_oneof![
+        Just(ZeroSemantics::NeverWritten),
+        Just(ZeroSemantics::ExplicitlyZero),
+        Just(ZeroSemantics::Cleared),
+        Just(ZeroSemantics::ValidZero),
+    ]
+}
+
+/// Consistent [`StorageSemantics`], with or without a validated override
+pub fn arb_storage_semantics() -> impl Strategy<Value = StorageSemantics> {
+    (arb_zero_semantics(), proptest::option::of(arb_zero_semantics())).prop_map(|(declared, validated)| {
+        match validated {
+            Some(validated) => StorageSemantics::with_validation(declared, validated),
+            None => StorageSemantics::new(declared),
+        }
+    })
+}
+
+/// A Solidity-style identifier
+pub fn arb_identifier() -> impl Strategy<Value = String> {
+    "[a-zA-Z_][a-zA-Z0-9_]{0,15}"
+}
+
+/// Any [`FieldKind`]
+pub fn arb_field_kind() -> impl Strategy<Value = FieldKind> {
+    proptest::sample::select(&FieldKind::ALL[..])
+}
+
+/// A valid layout with 1 to 8 storage variables
+///
+/// Variables take consecutive slots from a random starting slot; packed
+/// slots contribute three entries sharing one slot. Labels are made unique
+/// by an index suffix and only the types the layout uses are listed.
+pub fn arb_layout() -> impl Strategy<Value = LayoutInfo> {
+    (
+        arb_identifier(),
+        proptest::collection::vec((arb_identifier(), arb_field_kind(), arb_zero_semantics()), 1..=8),
+        0u64..1_000_000,
+    )
+        .prop_map(|(contract_name, fields, first_slot)| build_layout(contract_name, &fields, first_slot))
+}
+
+fn build_layout(contract_name: String, fields: &[(String, FieldKind, ZeroSemantics)], first_slot: u64) -> LayoutInfo {
+    let mut storage = Vec::new();
+    for (index, (name, kind, zero_semantics)) in fields.iter().enumerate() {
+        let slot = (first_slot + index as u64).to_string();
+        for (part, (type_name, offset)) in kind.entries().iter().enumerate() {
+            storage.push(StorageEntry {
+                label: format!("{}_{}_{}", name, index, part),
+                slot: slot.clone(),
+                offset: *offset,
+                type_name: type_name.to_string(),
+                zero_semantics: *zero_semantics,
+            });
+        }
+    }
+
+    // Referenced types, then the value types of mappings among them
+    let table = type_table();
+    let mut wanted: Vec<String> = storage.iter().map(|entry| entry.type_name.clone()).collect();
+    let mut types: Vec<TypeInfo> = Vec::new();
+    let mut next = 0;
+    while next < wanted.len() {
+        if let Some(info) = table.iter().find(|t| t.label == wanted[next]) {
+            if !types.iter().any(|t| t.label == info.label) {
+                wanted.extend(info.value.clone());
+                types.push(info.clone());
+            }
+        }
+        next += 1;
+    }
+
+    LayoutInfo { contract_name, storage, types, code_variables: Vec::new() }
+}
+
+/// A `0x`-prefixed 20-byte address
+pub fn arb_address() -> impl Strategy<Value = String> {
+    any::<[u8; 20]>().prop_map(|bytes| format!("0x{}", hex::encode(bytes)))
+}
+
+/// A `0x`-prefixed 32-byte word
+pub fn arb_word() -> impl Strategy<Value = String> {
+    any::<[u8; 32]>().prop_map(|bytes| format!("0x{}", hex::encode(bytes)))
+}
+
+/// A query for one of `layout`'s storage variables
+///
+/// Plain variables are queried by label; mappings get keys of their key
+/// type, as `0x`-prefixed hex so no resolver mistakes them for array
+/// indices.
+pub fn arb_query(layout: &LayoutInfo) -> BoxedStrategy<String> {
+    let queries: Vec<BoxedStrategy<String>> = layout
+        .storage
+        .iter()
+        .map(|entry| {
+            let label = entry.label.clone();
+            match mapping_keys(layout, &entry.type_name).as_slice() {
+                [] => Just(label).boxed(),
+                [key] => arb_key(key).prop_map(move |key| format!("{}[{}]", label, key)).boxed(),
+                [outer, inner, ..] => (arb_key(outer), arb_key(inner))
+                    .prop_map(move |(outer, inner)| format!("{}[{}][{}]", label, outer, inner))
+                    .boxed(),
+            }
+        })
+        .collect();
+    proptest::strategy::Union::new(queries).boxed()
+}
+
+/// A layout together with a query for one of its variables
+pub fn arb_layout_and_query() -> impl Strategy<Value = (LayoutInfo, String)> {
+    arb_layout().prop_flat_map(|layout| {
+        let query = arb_query(&layout);
+        (Just(layout), query)
+    })
+}
+
+/// Key types of a mapping, outermost first
+fn mapping_keys(layout: &LayoutInfo, type_name: &str) -> Vec<String> {
+    let mut keys = Vec::new();
+    let mut current = type_name.to_string();
+    while let Some(info) = layout.types.iter().find(|t| t.label == current && t.encoding == "mapping") {
+        keys.push(info.key.clone().unwrap_or_default());
+        current = info.value.clone().unwrap_or_default();
+    }
+    keys
+}
+
+fn arb_key(key_type: &str) -> BoxedStrategy<String> {
+    match key_type {
+        "t_address" => arb_address().boxed(),
+        _ => arb_word().boxed(),
+    }
+}
+
+/// A layout differing from `layout` in exactly one committed property
+///
+/// Renames the contract or changes one entry's label, slot, offset or type,
+/// so its commitment must differ.
+pub fn arb_layout_mutation(layout: &LayoutInfo) -> BoxedStrategy<LayoutInfo> {
+    let layout = layout.clone();
+    let entries = layout.storage.len();
+    (0..5u8, 0..entries)
+        .prop_map(move |(property, index)| {
+            let mut mutated = layout.clone();
+            let entry = &mut mutated.storage[index];
+            match property {
+                0 => mutated.contract_name.push('X'),
+                1 => entry.label.push('X'),
+                2 => entry.slot = format!("{}", entry.slot.parse::<u64>().unwrap_or(0) + 1_000_000),
+                3 => entry.offset = entry.offset.wrapping_add(1),
+                _ => entry.type_name.push('X'),
+            }
+            mutated
+        })
+        .boxed()
+}
+
+/// Check that `layout` survives a JSON round trip unchanged
+pub fn check_layout_json_round_trip(layout: &LayoutInfo) -> Result<(), TestCaseError> {
+    let json = serde_json::to_string(layout).map_err(|e| TestCaseError::fail(e.to_string()))?;
+    let decoded: LayoutInfo = serde_json::from_str(&json).map_err(|e| TestCaseError::fail(e.to_string()))?;
+    prop_assert_eq!(&decoded, layout);
+    Ok(())
+}
+
+/// Check that `layout`'s commitment is stable and binds what it commits to
+///
+/// The commitment must be the same when recomputed, after a clone and after
+/// a JSON round trip, must ignore zero semantics, and must differ from
+/// `mutated`'s, which should come from [`arb_layout_mutation`].
+pub fn check_commitment_stability(layout: &LayoutInfo, mutated: &LayoutInfo) -> Result<(), TestCaseError> {
+    let commitment = layout.commitment();
+    prop_assert_eq!(commitment, layout.commitment());
+    prop_assert_eq!(commitment, layout.clone().commitment());
+
+    let json = serde_json::to_string(layout).map_err(|e| TestCaseError::fail(e.to_string()))?;
+    let decoded: LayoutInfo = serde_json::from_str(&json).map_err(|e| TestCaseError::fail(e.to_string()))?;
+    prop_assert_eq!(commitment, decoded.commitment());
+
+    let mut resemantic = layout.clone();
+    for entry in &mut resemantic.storage {
+        entry.zero_semantics = ZeroSemantics::NeverWritten;
+    }
+    prop_assert_eq!(commitment, resemantic.commitment());
+
+    prop_assert_ne!(commitment, mutated.commitment(), "mutation kept the commitment");
+    Ok(())
+}
+
+/// Check that `resolver` resolves `query` deterministically and consistently
+///
+/// Resolving twice, and against a JSON round trip of the layout, must give
+/// the same path; the path must carry the layout's commitment and the
+/// queried entry's zero semantics; and a plain variable's path must be among
+/// those `resolve_all` returns.
+pub fn check_resolver_round_trip<R: KeyResolver>(
+    resolver: &R,
+    layout: &LayoutInfo,
+    query: &str,
+) -> Result<(), TestCaseError> {
+    let path = resolver
+        .resolve(layout, query)
+        .map_err(|e| TestCaseError::fail(format!("{} did not resolve: {}", query, e)))?;
+    let again = resolver.resolve(layout, query).map_err(|e| TestCaseError::fail(e.to_string()))?;
+    prop_assert_eq!(&path, &again);
+
+    let json = serde_json::to_string(layout).map_err(|e| TestCaseError::fail(e.to_string()))?;
+    let decoded: LayoutInfo = serde_json::from_str(&json).map_err(|e| TestCaseError::fail(e.to_string()))?;
+    let from_decoded = resolver.resolve(&decoded, query).map_err(|e| TestCaseError::fail(e.to_string()))?;
+    prop_assert_eq!(&path, &from_decoded);
+
+    prop_assert_eq!(path.layout_commitment, layout.commitment());
+    let label = query.split('[').next().unwrap_or(query);
+    let entry = layout
+        .storage
+        .iter()
+        .find(|entry| entry.label == label)
+        .ok_or_else(|| TestCaseError::fail(format!("{} names no entry", query)))?;
+    prop_assert_eq!(path.zero_semantics, entry.zero_semantics);
+
+    if !query.contains('[') {
+        let all = resolver.resolve_all(layout).map_err(|e| TestCaseError::fail(e.to_string()))?;
+        prop_assert!(all.iter().any(|p| p.key == path.key && p.offset == path.offset), "resolve_all lacks {}", query);
+    }
+    Ok(())
+}
+
+/// Check that a traced resolution agrees with the plain one
+pub fn check_trace_matches<R: TracingKeyResolver>(
+    resolver: &R,
+    layout: &LayoutInfo,
+    query: &str,
+) -> Result<(), TestCaseError> {
+    let trace = resolver.resolve_traced(layout, query).map_err(|e| TestCaseError::fail(e.to_string()))?;
+    let path = resolver.resolve(layout, query).map_err(|e| TestCaseError::fail(e.to_string()))?;
+    prop_assert_eq!(&trace.path, &path);
+    prop_assert_eq!(trace.query.as_str(), query);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn test_generated_layouts_validate(layout in arb_layout()) {
+            prop_assert_eq!(layout.validate(), Ok(()));
+            check_layout_json_round_trip(&layout)?;
+        }
+
+        #[test]
+        fn test_commitment_stability((layout, mutated) in arb_layout().prop_flat_map(|layout| {
+            let mutated = arb_layout_mutation(&layout);
+            (Just(layout), mutated)
+        })) {
+            check_commitment_stability(&layout, &mutated)?;
+        }
+
+        #[test]
+        fn test_queries_name_layout_entries((layout, query) in arb_layout_and_query()) {
+            let label = query.split('[').next().unwrap();
+            let entry = layout.storage.iter().find(|e| e.label == label).unwrap();
+            let brackets = query.matches('[').count();
+            prop_assert_eq!(brackets, mapping_keys(&layout, &entry.type_name).len());
+        }
+
+        #[test]
+        fn test_storage_semantics_are_consistent(semantics in arb_storage_semantics()) {
+            prop_assert!(semantics.validate().is_ok());
+        }
+    }
+}
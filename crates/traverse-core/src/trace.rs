@@ -0,0 +1,50 @@
+//! Step-by-step traces of storage key derivations
+//!
+//! [`TracingKeyResolver`] resolves a query like [`KeyResolver`] does but also
+//! records how the key was reached: the storage entry's base slot, every key
+//! padded to a word, each keccak preimage with its digest, and index
+//! additions. Rendering a trace lets a key be checked by hand or against
+//! another implementation before a proof depends on it.
+
+use crate::{KeyResolver, LayoutInfo, StaticKeyPath, TraverseError};
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// One step of a storage key derivation
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DerivationStep {
+    /// The storage entry the query names and its declared slot as a word
+    BaseSlot { field: String, slot: [u8; 32] },
+    /// A query key left-padded to a 32-byte word
+    PadKey { key: Vec<u8>, padded: [u8; 32] },
+    /// A hash of the concatenated words before it
+    Keccak { preimage: Vec<u8>, digest: [u8; 32] },
+    /// An array element's slot offset added to a base word, modulo 2^256
+    ///
+    /// `index` counts slots: the element index times the element's stride,
+    /// or divided by the elements per slot for packed elements.
+    AddIndex { base: [u8; 32], index: u64, result: [u8; 32] },
+}
+
+/// A resolved query together with the steps that produced its key
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct DerivationTrace {
+    pub query: String,
+    /// Storage entry the query starts from
+    pub field: String,
+    /// Declared type of that entry
+    pub type_name: String,
+    pub steps: Vec<DerivationStep>,
+    /// The same path [`KeyResolver::resolve`] returns
+    pub path: StaticKeyPath,
+}
+
+/// A [`KeyResolver`] that can explain its derivations
+pub trait TracingKeyResolver: KeyResolver {
+    /// Resolve a query, recording each derivation step
+    ///
+    /// The trace's `path` must equal what `resolve` returns for the same
+    /// layout and query.
+    fn resolve_traced(&self, layout: &LayoutInfo, query: &str) -> Result<DerivationTrace, TraverseError>;
+}
@@ -341,6 +341,7 @@ impl LayoutCompiler for CosmosLayoutCompiler {
             contract_name: contract.name,
             storage,
             types,
+            code_variables: Vec::new(),
         };
 
         Ok(layout)
@@ -45,8 +45,11 @@ pub use resolver::CosmosKeyResolver;
 
 #[cfg(feature = "client")]
 pub use proof::{
-    cosmos_iavl_spec, verify_iavl_proof, CosmosChainConfig, CosmosProofFetcher, IavlProof,
+    contract_store_key, cosmos_iavl_spec, verify_iavl_proof, verify_store_proof,
+    CosmosChainConfig, CosmosProofFetcher, HeaderBinding, IavlProof, CONTRACT_STORE_PREFIX,
 };
+#[cfg(feature = "client")]
+pub use traverse_indexer::{GraphIndexer, IndexerService, SemanticValidator, SubQueryIndexer, ValidationResult};
 
 /// Error types specific to CosmWasm contract analysis
 #[cfg_attr(feature = "std", derive(thiserror::Error))]
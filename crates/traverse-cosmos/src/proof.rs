@@ -21,13 +21,21 @@ use crate::CosmosError;
 use anyhow::Result;
 use base64::{engine::general_purpose::STANDARD, Engine};
 use ics23::{
-    iavl_spec, verify_membership, verify_non_membership, CommitmentProof, HostFunctionsManager,
-    ProofSpec,
+    calculate_existence_root, commitment_proof, iavl_spec, tendermint_spec, verify_membership,
+    verify_non_membership, CommitmentProof, HostFunctionsManager, ProofSpec,
 };
+use prost::Message;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Prefix of contract state in the wasm module store (`ContractStorePrefix`)
+pub const CONTRACT_STORE_PREFIX: u8 = 0x03;
+
 /// IAVL proof data from Cosmos RPC
+///
+/// Verification has two layers: `proof` shows the key in the module store
+/// with root `root`, and `store_proof` shows that root under `store_key` in
+/// the multistore whose root is the header's app hash.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IavlProof {
     /// Storage key that was queried
@@ -38,8 +46,47 @@ pub struct IavlProof {
     pub proof: CommitmentProof,
     /// Block height for the proof
     pub height: u64,
-    /// State root hash
+    /// Root of the module store, computed from `proof`
     pub root: Vec<u8>,
+    /// Module store name, e.g. `wasm`
+    pub store_key: String,
+    /// ICS23 proof of the module store root in the multistore
+    pub store_proof: CommitmentProof,
+    /// Header committing to the state at `height`
+    pub header: HeaderBinding,
+}
+
+impl IavlProof {
+    /// Protobuf encoding of the key proof, as carried in `proofOps`
+    pub fn proof_bytes(&self) -> Vec<u8> {
+        self.proof.encode_to_vec()
+    }
+
+    /// Protobuf encoding of the store proof, as carried in `proofOps`
+    pub fn store_proof_bytes(&self) -> Vec<u8> {
+        self.store_proof.encode_to_vec()
+    }
+
+    /// Decode a key or store proof from its protobuf encoding
+    pub fn decode_proof(bytes: &[u8]) -> Result<CommitmentProof, CosmosError> {
+        CommitmentProof::decode(bytes).map_err(|e| CosmosError::InvalidSchema(format!("Invalid commitment proof: {}", e)))
+    }
+}
+
+/// Block header that commits to the app hash a proof verifies against
+///
+/// The app hash after block `h` is only recorded in the header of block
+/// `h + 1`, so the header is one above the proof height.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HeaderBinding {
+    /// Height of the header
+    pub height: u64,
+    /// Chain ID recorded in the header
+    pub chain_id: String,
+    /// Block hash (header hash)
+    pub block_hash: Vec<u8>,
+    /// Multistore root committed by the header
+    pub app_hash: Vec<u8>,
 }
 
 /// IAVL proof fetcher for Cosmos chains
@@ -91,66 +138,70 @@ impl CosmosProofFetcher {
     ///
     /// * `store_key` - The storage key to query (e.g., "wasm" for CosmWasm)
     /// * `key` - The specific key within the store
-    /// * `height` - Block height to query (None for latest)
+    /// * `height` - Block height to query (None for one below the latest,
+    ///   the newest state whose header is already available)
     ///
     /// # Returns
     ///
-    /// IAVL proof that can be verified against the state root
+    /// IAVL proof that can be verified against the header's app hash
+    #[cfg_attr(
+        feature = "telemetry",
+        tracing::instrument(name = "cosmos_fetch_proof", skip(self, key), fields(key = %hex::encode(key)))
+    )]
     pub async fn fetch_proof(
         &self,
         store_key: &str,
         key: &[u8],
         height: Option<u64>,
     ) -> Result<IavlProof, CosmosError> {
-        let client = reqwest::Client::new();
-
-        // Construct the query path
-        let query_path = if let Some(h) = height {
-            format!(
-                "/store/{}/key?key={}&height={}",
-                store_key,
-                hex::encode(key),
-                h
-            )
-        } else {
-            format!("/store/{}/key?key={}", store_key, hex::encode(key))
+        let height = match height {
+            Some(height) => height,
+            None => self.latest_height().await?.saturating_sub(1),
         };
 
-        let url = format!("{}/abci_query?path=\"{}\"", self.rpc_url, query_path);
-
-        // Fetch the proof from RPC
-        let response = client.get(&url).send().await?;
-        let rpc_response: serde_json::Value = response.json().await?;
-
-        // Parse the response (this is a simplified version - real implementation would
-        // need to handle the full Cosmos RPC response format)
-        let result = rpc_response
-            .get("result")
-            .and_then(|r| r.get("response"))
-            .ok_or_else(|| {
-                CosmosError::InvalidSchema("No response data in RPC result".to_string())
-            })?;
+        let result = self
+            .rpc_call("abci_query", self.abci_query_params(store_key, key, height.to_string().into()))
+            .await?;
+        let response = result.get("response").ok_or_else(|| {
+            CosmosError::InvalidSchema("No response data in RPC result".to_string())
+        })?;
+
+        let code = response.get("code").and_then(|c| c.as_u64()).unwrap_or(0);
+        if code != 0 {
+            let log = response.get("log").and_then(|l| l.as_str()).unwrap_or_default();
+            return Err(CosmosError::InvalidSchema(format!(
+                "abci_query failed with code {}: {}",
+                code, log
+            )));
+        }
 
-        let proof_data = result
+        let ops = response
             .get("proofOps")
+            .and_then(|p| p.get("ops"))
+            .and_then(|o| o.as_array())
             .ok_or_else(|| CosmosError::InvalidSchema("No proof data in response".to_string()))?;
+        let proof = self.parse_cosmos_proof(ops, "ics23:iavl")?;
+        let store_proof = self.parse_cosmos_proof(ops, "ics23:simple")?;
 
-        // Convert to ICS23 proof format
-        let proof = self.parse_cosmos_proof(proof_data)?;
-
-        let value = result
+        let value = response
             .get("value")
             .and_then(|v| v.as_str())
-            .map(|s| STANDARD.decode(s).unwrap_or_default());
+            .filter(|s| !s.is_empty())
+            .map(|s| STANDARD.decode(s))
+            .transpose()
+            .map_err(|e| CosmosError::InvalidSchema(format!("Invalid value encoding: {}", e)))?;
 
-        let height = result
+        let height = response
             .get("height")
             .and_then(|h| h.as_str())
             .and_then(|s| s.parse().ok())
-            .unwrap_or(0);
+            .unwrap_or(height);
 
-        // Get the state root (this would come from the block header)
-        let root = self.fetch_state_root(height).await?;
+        #[cfg(feature = "telemetry")]
+        traverse_core::telemetry::record_proof_size("cosmos", "iavl", proof.encoded_len() + store_proof.encoded_len());
+
+        let root = commitment_root(&proof)?;
+        let header = self.fetch_header(height + 1).await?;
 
         Ok(IavlProof {
             key: key.to_vec(),
@@ -158,10 +209,31 @@ impl CosmosProofFetcher {
             proof,
             height,
             root,
+            store_key: store_key.to_string(),
+            store_proof,
+            header,
+        })
+    }
+
+    /// `abci_query` params requesting a proven read of `key` at `height`
+    ///
+    /// Heights are decimal strings; they are taken as JSON values so a dry-run
+    /// plan can pass a reference to a height an earlier call will report.
+    pub fn abci_query_params(&self, store_key: &str, key: &[u8], height: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "path": format!("/{}/{}/key", self.config.store_prefix, store_key),
+            "data": hex::encode(key),
+            "height": height,
+            "prove": true,
         })
     }
 
-    /// Verify an IAVL proof against a known state root
+    /// `block` params for the header at `height`
+    pub fn block_params(height: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({ "height": height })
+    }
+
+    /// Verify an IAVL proof against its header's app hash
     ///
     /// # Arguments
     ///
@@ -170,36 +242,14 @@ impl CosmosProofFetcher {
     ///
     /// # Returns
     ///
-    /// True if the proof is valid, false otherwise
+    /// True if both the key proof and the store proof are valid
     pub fn verify_proof(
         &self,
         proof: &IavlProof,
         expected_value: Option<&[u8]>,
     ) -> Result<bool, CosmosError> {
-        let default_spec = iavl_spec();
-        let spec = self.config.proof_spec.as_ref().unwrap_or(&default_spec);
-
-        match expected_value {
-            Some(value) => {
-                // Verify existence proof
-                Ok(verify_membership::<HostFunctionsManager>(
-                    &proof.proof,
-                    spec,
-                    &proof.root,
-                    &proof.key,
-                    value,
-                ))
-            }
-            None => {
-                // Verify non-existence proof
-                Ok(verify_non_membership::<HostFunctionsManager>(
-                    &proof.proof,
-                    spec,
-                    &proof.root,
-                    &proof.key,
-                ))
-            }
-        }
+        Ok(verify_iavl_proof(proof, expected_value, self.config.proof_spec.as_ref())?
+            && verify_store_proof(proof))
     }
 
     /// Fetch storage proofs for multiple keys in batch
@@ -213,6 +263,10 @@ impl CosmosProofFetcher {
     /// # Returns
     ///
     /// Map of keys to their corresponding proofs
+    #[cfg_attr(
+        feature = "telemetry",
+        tracing::instrument(name = "cosmos_fetch_batch_proofs", skip(self, keys), fields(keys = keys.len()))
+    )]
     pub async fn fetch_batch_proofs(
         &self,
         store_key: &str,
@@ -220,55 +274,171 @@ impl CosmosProofFetcher {
         height: Option<u64>,
     ) -> Result<HashMap<Vec<u8>, IavlProof>, CosmosError> {
         let mut proofs = HashMap::new();
+        #[cfg(feature = "telemetry")]
+        let started = std::time::Instant::now();
+
+        // Pin the height so every proof binds to the same header
+        let height = match height {
+            Some(height) => height,
+            None => self.latest_height().await?.saturating_sub(1),
+        };
 
         // For now, fetch proofs sequentially
         // A more efficient implementation could use batch RPC calls
         for key in keys {
-            let proof = self.fetch_proof(store_key, key, height).await?;
+            let proof = self.fetch_proof(store_key, key, Some(height)).await?;
             proofs.insert(key.clone(), proof);
         }
 
+        #[cfg(feature = "telemetry")]
+        traverse_core::telemetry::record_batch("cosmos", keys.len(), started.elapsed());
         Ok(proofs)
     }
 
-    /// Parse Cosmos RPC proof response into ICS23 format
+    /// Decode the ICS23 proof of one operation type from `proofOps`
     fn parse_cosmos_proof(
         &self,
-        _proof_data: &serde_json::Value,
+        ops: &[serde_json::Value],
+        op_type: &str,
     ) -> Result<CommitmentProof, CosmosError> {
-        // This is a simplified parser - real implementation would need to handle
-        // the full Cosmos proof operation format and convert to ICS23
+        let data = ops
+            .iter()
+            .find(|op| op.get("type").and_then(|t| t.as_str()) == Some(op_type))
+            .and_then(|op| op.get("data"))
+            .and_then(|d| d.as_str())
+            .ok_or_else(|| {
+                CosmosError::InvalidSchema(format!("No {} operation in proof", op_type))
+            })?;
+        let bytes = STANDARD
+            .decode(data)
+            .map_err(|e| CosmosError::InvalidSchema(format!("Invalid {} proof encoding: {}", op_type, e)))?;
+        CommitmentProof::decode(bytes.as_slice())
+            .map_err(|e| CosmosError::InvalidSchema(format!("Invalid {} proof: {}", op_type, e)))
+    }
+
+    /// Fetch the header at a given block height
+    async fn fetch_header(&self, height: u64) -> Result<HeaderBinding, CosmosError> {
+        let result = self
+            .rpc_call("block", Self::block_params(height.to_string().into()))
+            .await?;
+        let header = result
+            .get("block")
+            .and_then(|b| b.get("header"))
+            .ok_or_else(|| {
+                CosmosError::InvalidSchema(format!("No header for height {} in block response", height))
+            })?;
+
+        let hex_field = |value: Option<&serde_json::Value>, name: &str| {
+            let value = value.and_then(|v| v.as_str()).ok_or_else(|| {
+                CosmosError::InvalidSchema(format!("No {} in block response", name))
+            })?;
+            hex::decode(value)
+                .map_err(|e| CosmosError::InvalidSchema(format!("Invalid {} format: {}", name, e)))
+        };
 
-        // For now, return a placeholder proof structure
-        // In a full implementation, this would parse the proof operations
-        // and construct a proper ICS23 CommitmentProof
+        Ok(HeaderBinding {
+            height,
+            chain_id: header
+                .get("chain_id")
+                .and_then(|c| c.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            block_hash: hex_field(result.get("block_id").and_then(|id| id.get("hash")), "block_id.hash")?,
+            app_hash: hex_field(header.get("app_hash"), "app_hash")?,
+        })
+    }
 
-        Err(CosmosError::UnsupportedPattern(
-            "Full IAVL proof parsing not yet implemented".to_string(),
-        ))
+    /// Height of the latest block
+    ///
+    /// Its state is not provable until the next header is produced, so pin
+    /// proofs to one below this.
+    pub async fn latest_height(&self) -> Result<u64, CosmosError> {
+        let result = self.rpc_call("status", serde_json::json!({})).await?;
+        result
+            .get("sync_info")
+            .and_then(|s| s.get("latest_block_height"))
+            .and_then(|h| h.as_str())
+            .and_then(|h| h.parse().ok())
+            .ok_or_else(|| CosmosError::InvalidSchema("No latest height in status response".to_string()))
     }
 
-    /// Fetch the state root for a given block height
-    async fn fetch_state_root(&self, height: u64) -> Result<Vec<u8>, CosmosError> {
-        let client = reqwest::Client::new();
-        let url = format!("{}/block?height={}", self.rpc_url, height);
+    /// Chain ID the node serves, as reported by `status`
+    pub async fn chain_id(&self) -> Result<String, CosmosError> {
+        let result = self.rpc_call("status", serde_json::json!({})).await?;
+        result
+            .get("node_info")
+            .and_then(|n| n.get("network"))
+            .and_then(|n| n.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| CosmosError::InvalidSchema("No network in status response".to_string()))
+    }
 
-        let response = client.get(&url).send().await?;
-        let block_response: serde_json::Value = response.json().await?;
+    /// Issue a Tendermint JSON-RPC call and return its result
+    async fn rpc_call(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, CosmosError> {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let call = async {
+            reqwest::Client::new()
+                .post(&self.rpc_url)
+                .json(&request)
+                .send()
+                .await?
+                .json::<serde_json::Value>()
+                .await
+        };
+        #[cfg(feature = "telemetry")]
+        let call = traverse_core::telemetry::observe_rpc("cosmos", method, call);
+        let response = call.await?;
 
-        let app_hash = block_response
+        if let Some(error) = response.get("error") {
+            return Err(CosmosError::InvalidSchema(format!("RPC error in {}: {}", method, error)));
+        }
+        response
             .get("result")
-            .and_then(|r| r.get("block"))
-            .and_then(|b| b.get("header"))
-            .and_then(|h| h.get("app_hash"))
-            .and_then(|ah| ah.as_str())
-            .ok_or_else(|| {
-                CosmosError::InvalidSchema("No app_hash in block response".to_string())
-            })?;
+            .cloned()
+            .ok_or_else(|| CosmosError::InvalidSchema(format!("No result in {} response", method)))
+    }
+}
+
+/// Key of a contract's storage entry in the wasm module store
+///
+/// wasmd stores contract state under `0x03 || contract address bytes`
+/// followed by the contract's own storage key.
+pub fn contract_store_key(contract_address: &str, storage_key: &[u8]) -> Result<Vec<u8>, CosmosError> {
+    let (_, address) = bech32::decode(contract_address).map_err(|e| {
+        CosmosError::KeyGenerationFailed(format!("Invalid contract address '{}': {}", contract_address, e))
+    })?;
+
+    let mut key = Vec::with_capacity(1 + address.len() + storage_key.len());
+    key.push(CONTRACT_STORE_PREFIX);
+    key.extend_from_slice(&address);
+    key.extend_from_slice(storage_key);
+    Ok(key)
+}
 
-        hex::decode(app_hash)
-            .map_err(|e| CosmosError::InvalidSchema(format!("Invalid app_hash format: {}", e)))
+/// Root a single-key commitment proof commits to
+fn commitment_root(proof: &CommitmentProof) -> Result<Vec<u8>, CosmosError> {
+    let existence = match &proof.proof {
+        Some(commitment_proof::Proof::Exist(exist)) => Some(exist),
+        Some(commitment_proof::Proof::Nonexist(nonexist)) => {
+            nonexist.left.as_ref().or(nonexist.right.as_ref())
+        }
+        _ => None,
     }
+    .ok_or_else(|| {
+        CosmosError::UnsupportedPattern("Expected an existence or non-existence proof".to_string())
+    })?;
+
+    calculate_existence_root::<HostFunctionsManager>(existence)
+        .map_err(|e| CosmosError::InvalidSchema(format!("Invalid existence proof: {}", e)))
 }
 
 /// Create IAVL-specific proof spec
@@ -276,7 +446,7 @@ pub fn cosmos_iavl_spec() -> ProofSpec {
     iavl_spec()
 }
 
-/// Verify a single IAVL proof
+/// Verify the key layer of an IAVL proof against the module store root
 pub fn verify_iavl_proof(
     proof: &IavlProof,
     expected_value: Option<&[u8]>,
@@ -302,6 +472,17 @@ pub fn verify_iavl_proof(
     }
 }
 
+/// Verify that the module store root is committed to by the header's app hash
+pub fn verify_store_proof(proof: &IavlProof) -> bool {
+    verify_membership::<HostFunctionsManager>(
+        &proof.store_proof,
+        &tendermint_spec(),
+        &proof.header.app_hash,
+        proof.store_key.as_bytes(),
+        &proof.root,
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -322,6 +503,17 @@ mod tests {
         assert!(spec.inner_spec.is_some());
     }
 
+    #[test]
+    fn test_contract_store_key() {
+        let address = "neutron1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5z5tpwxqergd3c8g7rusqpsa9eu";
+        let key = contract_store_key(address, b"config").unwrap();
+        assert_eq!(key[0], CONTRACT_STORE_PREFIX);
+        assert_eq!(&key[1..33], (1..=32).collect::<Vec<u8>>().as_slice());
+        assert_eq!(&key[33..], b"config");
+
+        assert!(contract_store_key("not-an-address", b"config").is_err());
+    }
+
     #[tokio::test]
     async fn test_proof_fetcher_creation() {
         let fetcher = CosmosProofFetcher::with_defaults("http://localhost:26657".to_string());
@@ -49,6 +49,45 @@ impl CosmosKeyResolver {
         hasher.finalize().into()
     }
 
+    /// Raw storage key of a query as written by cw-storage-plus
+    ///
+    /// Unlike [`KeyResolver::resolve`], which commits to a fixed-size hash,
+    /// this is the key the contract actually stores under and what
+    /// `abci_query` looks up. Items use their namespace as the key. Map
+    /// entries prefix the namespace and every key component but the last
+    /// with its length as a big-endian `u16`. Components are taken as UTF-8,
+    /// or as raw bytes when written as `0x`-prefixed hex. Field access reads
+    /// the whole item, so `config.owner` has the key of `config`.
+    pub fn raw_storage_key(query: &str) -> Result<Vec<u8>, TraverseError> {
+        match Self::parse_cosmwasm_query(query)? {
+            CosmWasmQuery::Item { name } | CosmWasmQuery::NestedField { base: name, .. } => {
+                Ok(name.into_bytes())
+            }
+            CosmWasmQuery::Map { name, key } => Self::map_storage_key(&name, &[key]),
+            CosmWasmQuery::NestedMap { name, keys } => Self::map_storage_key(&name, &keys),
+        }
+    }
+
+    fn map_storage_key(namespace: &str, keys: &[String]) -> Result<Vec<u8>, TraverseError> {
+        let mut raw = Vec::new();
+        let mut prefix = namespace.as_bytes().to_vec();
+        for key in keys {
+            let length = u16::try_from(prefix.len()).map_err(|_| {
+                TraverseError::InvalidInput(format!("Key component longer than {} bytes", u16::MAX))
+            })?;
+            raw.extend_from_slice(&length.to_be_bytes());
+            raw.extend_from_slice(&prefix);
+            prefix = match key.strip_prefix("0x") {
+                Some(hex_key) => hex::decode(hex_key).map_err(|e| {
+                    TraverseError::InvalidInput(format!("Invalid hex key '{}': {}", key, e))
+                })?,
+                None => key.as_bytes().to_vec(),
+            };
+        }
+        raw.extend_from_slice(&prefix);
+        Ok(raw)
+    }
+
     /// Parse CosmWasm query syntax
     ///
     /// Supports patterns like:
@@ -176,10 +215,9 @@ impl KeyResolver for CosmosKeyResolver {
     /// // Nested field access
     /// let path = resolver.resolve(&layout, "config.owner")?;
     /// ```
+    #[cfg_attr(feature = "telemetry", tracing::instrument(name = "cosmos_resolve", skip(self, layout)))]
     fn resolve(&self, layout: &LayoutInfo, query: &str) -> Result<StaticKeyPath, TraverseError> {
-        let parsed_query = Self::parse_cosmwasm_query(query)?;
-
-        match parsed_query {
+        let result = Self::parse_cosmwasm_query(query).and_then(|parsed_query| match parsed_query {
             CosmWasmQuery::Item { name } => {
                 let entry = Self::find_storage_entry(layout, &name)?;
                 let type_info = Self::get_type_info(layout, &entry.type_name)?;
@@ -279,7 +317,11 @@ impl KeyResolver for CosmosKeyResolver {
                     zero_semantics: entry.zero_semantics,
                 })
             }
-        }
+        });
+
+        #[cfg(feature = "telemetry")]
+        traverse_core::telemetry::record_resolution("cosmos", &result);
+        result
     }
 
     /// Resolve all possible storage paths from a CosmWasm layout
@@ -333,3 +375,26 @@ impl KeyResolver for CosmosKeyResolver {
         Ok(paths)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raw_storage_key() {
+        assert_eq!(CosmosKeyResolver::raw_storage_key("config").unwrap(), b"config");
+        assert_eq!(CosmosKeyResolver::raw_storage_key("config.owner").unwrap(), b"config");
+
+        let mut expected = vec![0, 8];
+        expected.extend_from_slice(b"balancesneutron1abc");
+        assert_eq!(CosmosKeyResolver::raw_storage_key("balances[neutron1abc]").unwrap(), expected);
+
+        let mut expected = vec![0, 10];
+        expected.extend_from_slice(b"allowances");
+        expected.extend_from_slice(&[0, 5]);
+        expected.extend_from_slice(b"owner\x01\x02");
+        assert_eq!(CosmosKeyResolver::raw_storage_key("allowances[owner][0x0102]").unwrap(), expected);
+
+        assert!(CosmosKeyResolver::raw_storage_key("balances[0xzz]").is_err());
+    }
+}
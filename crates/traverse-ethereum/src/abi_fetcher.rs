@@ -2,9 +2,20 @@
 //!
 //! This module provides functionality to fetch contract Application Binary Interfaces (ABIs)
 //! from various sources including Etherscan API, and convert them to storage layouts.
+//! Sourcify is used as a fallback for verified contracts when Etherscan is unavailable
+//! or no API key is configured.
+//!
+//! [`AbiFetcher::fetch_verified_layout`] gets a verified contract's exact
+//! storage layout instead of inferring one from its ABI: Sourcify serves the
+//! layout of contracts it verified, and for contracts only Etherscan has, the
+//! verified sources are recompiled with a local `solc` of the same version.
+//! Layouts are cached under [`AbiFetcher::cache_dir`] by chain and address.
 
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use traverse_core::{LayoutInfo, StorageEntry, TraverseError, TypeInfo, ZeroSemantics};
+use crate::layout::EthereumLayoutCompiler;
+use crate::proxy::{ProxyDetector, ProxyInfo};
 
 /// Etherscan API response for contract ABI
 #[derive(Debug, Deserialize)]
@@ -44,12 +55,91 @@ struct AbiOutput {
     internal_type: Option<String>,
 }
 
+/// Sourcify API response with only the ABI field selected
+#[derive(Debug, Deserialize)]
+struct SourcifyContractResponse {
+    abi: Option<serde_json::Value>,
+}
+
+/// Sourcify API response with the layout and compilation fields selected
+#[derive(Debug, Deserialize)]
+struct SourcifyLayoutResponse {
+    #[serde(rename = "storageLayout")]
+    storage_layout: Option<serde_json::Value>,
+    compilation: Option<SourcifyCompilation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SourcifyCompilation {
+    name: String,
+}
+
+/// Etherscan `getsourcecode` response; `result` is a message on errors
+#[derive(Debug, Deserialize)]
+struct EtherscanSourceResponse {
+    status: String,
+    message: String,
+    result: serde_json::Value,
+}
+
+/// One contract from Etherscan's `getsourcecode`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct EtherscanSource {
+    source_code: String,
+    contract_name: String,
+    compiler_version: String,
+    optimization_used: String,
+    runs: String,
+    #[serde(rename = "EVMVersion")]
+    evm_version: String,
+}
+
+/// Verified sources ready to recompile with solc
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifiedSource {
+    pub contract_name: String,
+    /// solc version without the `v` prefix, e.g. `0.8.24+commit.e11b9ed9`
+    pub compiler_version: String,
+    /// solc standard JSON input selecting `storageLayout` output
+    pub input: serde_json::Value,
+}
+
+/// A fetched layout as cached on disk
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedLayout {
+    source: AbiSource,
+    layout: LayoutInfo,
+}
+
+/// Default Sourcify server
+const SOURCIFY_BASE_URL: &str = "https://sourcify.dev/server";
+
+/// Etherscan multichain API, selecting the chain with `chainid`
+const ETHERSCAN_V2_BASE_URL: &str = "https://api.etherscan.io/v2";
+
+/// Service an ABI was fetched from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AbiSource {
+    Etherscan,
+    Sourcify,
+}
+
 /// ABI fetcher for retrieving contract ABIs from various sources
 pub struct AbiFetcher {
     /// Etherscan API key
     pub etherscan_api_key: Option<String>,
     /// Base URL for Etherscan API (default: mainnet)
     pub etherscan_base_url: String,
+    /// Base URL for the Sourcify server
+    pub sourcify_base_url: String,
+    /// EIP-155 chain ID the contract is deployed on
+    pub chain_id: u64,
+    /// Where fetched layouts are cached, `None` to always fetch
+    pub cache_dir: Option<PathBuf>,
+    /// solc binary used to recompile Etherscan sources
+    pub solc_path: PathBuf,
     /// HTTP client for making requests
     client: reqwest::Client,
 }
@@ -71,10 +161,173 @@ impl AbiFetcher {
         Self {
             etherscan_api_key,
             etherscan_base_url: base_url,
+            sourcify_base_url: SOURCIFY_BASE_URL.to_string(),
+            chain_id: 1,
+            cache_dir: None,
+            solc_path: PathBuf::from("solc"),
             client: reqwest::Client::new(),
         }
     }
 
+    /// Cache fetched layouts under `dir`
+    pub fn with_cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Recompile Etherscan sources with the solc at `path`
+    ///
+    /// The binary must be the version the contract was verified with, e.g.
+    /// one installed by `solc-select` or `svm`.
+    pub fn with_solc(mut self, path: impl Into<PathBuf>) -> Self {
+        self.solc_path = path.into();
+        self
+    }
+
+    /// Create a fetcher for a named chain or a numeric chain ID
+    ///
+    /// Uses the Etherscan multichain API so one API key covers every chain.
+    ///
+    /// # Arguments
+    ///
+    /// * `chain` - Chain name (`mainnet`, `sepolia`, `base`, ...) or chain ID
+    /// * `etherscan_api_key` - Optional Etherscan API key
+    pub fn for_chain(chain: &str, etherscan_api_key: Option<String>) -> Result<Self, TraverseError> {
+        let chain_id = Self::chain_id(chain).ok_or_else(|| {
+            TraverseError::InvalidInput(format!(
+                "Unknown chain '{}'. Use a chain name such as mainnet or sepolia, or a chain ID",
+                chain
+            ))
+        })?;
+
+        let mut fetcher = Self::new(etherscan_api_key, Some(ETHERSCAN_V2_BASE_URL.to_string()));
+        fetcher.chain_id = chain_id;
+        Ok(fetcher)
+    }
+
+    /// Chain ID for a chain name or numeric ID
+    pub fn chain_id(chain: &str) -> Option<u64> {
+        if let Ok(id) = chain.parse() {
+            return Some(id);
+        }
+        match chain.to_lowercase().as_str() {
+            "mainnet" | "ethereum" => Some(1),
+            "sepolia" => Some(11_155_111),
+            "holesky" => Some(17_000),
+            "optimism" => Some(10),
+            "arbitrum" => Some(42_161),
+            "base" => Some(8_453),
+            "polygon" => Some(137),
+            _ => None,
+        }
+    }
+
+    /// Fetch a contract ABI, trying Etherscan first and Sourcify second
+    ///
+    /// Etherscan is skipped when no API key is set, since it rejects anonymous
+    /// requests.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((String, AbiSource))` - ABI JSON string and where it came from
+    /// * `Err(TraverseError)` - Neither source had a verified ABI
+    pub async fn fetch_abi(&self, contract_address: &str) -> Result<(String, AbiSource), TraverseError> {
+        let etherscan_error = if self.etherscan_api_key.is_some() {
+            match self.fetch_abi_from_etherscan(contract_address).await {
+                Ok(abi) => return Ok((abi, AbiSource::Etherscan)),
+                Err(e) => e.to_string(),
+            }
+        } else {
+            "no API key configured".to_string()
+        };
+
+        self.fetch_abi_from_sourcify(contract_address)
+            .await
+            .map(|abi| (abi, AbiSource::Sourcify))
+            .map_err(|e| {
+                TraverseError::ExternalService(format!(
+                    "No ABI found for {} (Etherscan: {}; Sourcify: {})",
+                    contract_address, etherscan_error, e
+                ))
+            })
+    }
+
+    /// Fetch the ABI that lays out a contract's storage
+    ///
+    /// For an EIP-1967 proxy this is the implementation's ABI, found through
+    /// the proxy's slots at `block` on `rpc_url`; for any other contract it is
+    /// the contract's own. Queries compiled from it are read from the proxy's
+    /// address.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((String, AbiSource, Option<ProxyInfo>))` - ABI JSON, where it came
+    ///   from, and the proxy's slots when `contract_address` is a proxy
+    /// * `Err(TraverseError)` - Slot reads failed or no verified ABI was found
+    pub async fn fetch_storage_abi(
+        &self,
+        contract_address: &str,
+        rpc_url: &str,
+        block: &str,
+    ) -> Result<(String, AbiSource, Option<ProxyInfo>), TraverseError> {
+        let proxy = ProxyDetector::new(rpc_url).detect(contract_address, block).await?;
+        let target = proxy
+            .as_ref()
+            .and_then(|info| info.implementation.as_deref())
+            .unwrap_or(contract_address);
+        let (abi, source) = self.fetch_abi(target).await?;
+        Ok((abi, source, proxy))
+    }
+
+    /// Fetch contract ABI from Sourcify
+    ///
+    /// # Arguments
+    ///
+    /// * `contract_address` - Ethereum contract address (with or without 0x prefix)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - ABI JSON string
+    /// * `Err(TraverseError)` - Contract not verified or request failed
+    pub async fn fetch_abi_from_sourcify(&self, contract_address: &str) -> Result<String, TraverseError> {
+        let clean_address = contract_address
+            .strip_prefix("0x")
+            .unwrap_or(contract_address);
+        let url = format!(
+            "{}/v2/contract/{}/0x{}?fields=abi",
+            self.sourcify_base_url, self.chain_id, clean_address
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| TraverseError::ExternalService(format!("Failed to fetch ABI: {}", e)))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(TraverseError::ExternalService(format!(
+                "Contract not verified on chain {}",
+                self.chain_id
+            )));
+        }
+        if !response.status().is_success() {
+            return Err(TraverseError::ExternalService(format!(
+                "Sourcify returned HTTP {}",
+                response.status()
+            )));
+        }
+
+        let sourcify_response: SourcifyContractResponse = response.json().await.map_err(|e| {
+            TraverseError::Serialization(format!("Failed to parse Sourcify response: {}", e))
+        })?;
+
+        sourcify_response
+            .abi
+            .map(|abi| abi.to_string())
+            .ok_or_else(|| TraverseError::ExternalService("Sourcify response has no ABI".to_string()))
+    }
+
     /// Fetch contract ABI from Etherscan
     ///
     /// # Arguments
@@ -94,8 +347,8 @@ impl AbiFetcher {
             .unwrap_or(contract_address);
 
         let mut url = format!(
-            "{}/api?module=contract&action=getabi&address=0x{}",
-            self.etherscan_base_url, clean_address
+            "{}/api?chainid={}&module=contract&action=getabi&address=0x{}",
+            self.etherscan_base_url, self.chain_id, clean_address
         );
 
         if let Some(ref api_key) = self.etherscan_api_key {
@@ -129,6 +382,248 @@ impl AbiFetcher {
         Ok(etherscan_response.result)
     }
 
+    /// Fetch a verified contract's storage layout, trying Sourcify first
+    ///
+    /// Sourcify serves the layout solc produced when it verified the
+    /// contract. Otherwise the sources verified on Etherscan are fetched and
+    /// recompiled with [`solc_path`](Self::solc_path). A cached layout is
+    /// returned without fetching when [`cache_dir`](Self::cache_dir) has one.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((LayoutInfo, AbiSource))` - Exact layout and where it came from
+    /// * `Err(TraverseError)` - Neither source had the layout or sources
+    pub async fn fetch_verified_layout(&self, contract_address: &str) -> Result<(LayoutInfo, AbiSource), TraverseError> {
+        let cache = self.layout_cache_path(contract_address);
+        if let Some(cached) = cache.as_deref().and_then(Self::read_cached_layout) {
+            return Ok((cached.layout, cached.source));
+        }
+
+        let sourcify_error = match self.fetch_layout_from_sourcify(contract_address).await {
+            Ok(layout) => return self.cache_layout(cache.as_deref(), layout, AbiSource::Sourcify),
+            Err(e) => e.to_string(),
+        };
+        let etherscan = async {
+            let source = self.fetch_source_from_etherscan(contract_address).await?;
+            self.recompile_layout(&source).await
+        };
+        match etherscan.await {
+            Ok(layout) => self.cache_layout(cache.as_deref(), layout, AbiSource::Etherscan),
+            Err(e) => Err(TraverseError::ExternalService(format!(
+                "No storage layout for {} (Sourcify: {}; Etherscan: {})",
+                contract_address, sourcify_error, e
+            ))),
+        }
+    }
+
+    /// Fetch the storage layout Sourcify compiled when verifying a contract
+    pub async fn fetch_layout_from_sourcify(&self, contract_address: &str) -> Result<LayoutInfo, TraverseError> {
+        let clean_address = contract_address
+            .strip_prefix("0x")
+            .unwrap_or(contract_address);
+        let url = format!(
+            "{}/v2/contract/{}/0x{}?fields=storageLayout,compilation",
+            self.sourcify_base_url, self.chain_id, clean_address
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| TraverseError::ExternalService(format!("Failed to fetch layout: {}", e)))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(TraverseError::ExternalService(format!(
+                "Contract not verified on chain {}",
+                self.chain_id
+            )));
+        }
+        if !response.status().is_success() {
+            return Err(TraverseError::ExternalService(format!(
+                "Sourcify returned HTTP {}",
+                response.status()
+            )));
+        }
+
+        let sourcify_response: SourcifyLayoutResponse = response.json().await.map_err(|e| {
+            TraverseError::Serialization(format!("Failed to parse Sourcify response: {}", e))
+        })?;
+        Self::layout_from_sourcify(sourcify_response)
+    }
+
+    fn layout_from_sourcify(response: SourcifyLayoutResponse) -> Result<LayoutInfo, TraverseError> {
+        let storage_layout = response
+            .storage_layout
+            .filter(|layout| !layout.is_null())
+            .ok_or_else(|| TraverseError::ExternalService("Sourcify response has no storage layout".to_string()))?;
+        let name = response.compilation.map(|c| c.name);
+        EthereumLayoutCompiler.compile_solc_output(
+            &serde_json::json!({ "storageLayout": storage_layout }),
+            name.as_deref(),
+        )
+    }
+
+    /// Fetch the sources a contract was verified with on Etherscan
+    pub async fn fetch_source_from_etherscan(&self, contract_address: &str) -> Result<VerifiedSource, TraverseError> {
+        let clean_address = contract_address
+            .strip_prefix("0x")
+            .unwrap_or(contract_address);
+        let mut url = format!(
+            "{}/api?chainid={}&module=contract&action=getsourcecode&address=0x{}",
+            self.etherscan_base_url, self.chain_id, clean_address
+        );
+        if let Some(ref api_key) = self.etherscan_api_key {
+            url.push_str(&format!("&apikey={}", api_key));
+        }
+
+        let response: EtherscanSourceResponse = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| TraverseError::ExternalService(format!("Failed to fetch source: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| TraverseError::Serialization(format!("Failed to parse Etherscan response: {}", e)))?;
+        Self::verified_source(response)
+    }
+
+    fn verified_source(response: EtherscanSourceResponse) -> Result<VerifiedSource, TraverseError> {
+        if response.status != "1" {
+            return Err(TraverseError::ExternalService(format!(
+                "Etherscan API error: {} ({})",
+                response.message, response.result
+            )));
+        }
+        let mut sources: Vec<EtherscanSource> = serde_json::from_value(response.result)
+            .map_err(|e| TraverseError::Serialization(format!("Failed to parse Etherscan source: {}", e)))?;
+        let source = sources
+            .pop()
+            .filter(|source| !source.source_code.is_empty())
+            .ok_or_else(|| TraverseError::ExternalService("Contract source not verified".to_string()))?;
+        if source.compiler_version.starts_with("vyper") {
+            return Err(TraverseError::FeatureNotSupported(
+                "Recompiling Vyper sources; compile the layout with vyper -f layout".to_string(),
+            ));
+        }
+
+        // Sources come as one file, a JSON map of files, or a whole
+        // standard JSON input wrapped in an extra pair of braces
+        let code = source.source_code.trim();
+        let files = |sources: serde_json::Value| serde_json::json!({ "language": "Solidity", "sources": sources });
+        let (mut input, standard) = if let Some(inner) = code.strip_prefix("{{").and_then(|c| c.strip_suffix("}}")) {
+            (serde_json::from_str(&format!("{{{}}}", inner))?, true)
+        } else if let Ok(sources @ serde_json::Value::Object(_)) = serde_json::from_str(code) {
+            (files(sources), false)
+        } else {
+            let file = format!("{}.sol", source.contract_name);
+            (files(serde_json::json!({ file: { "content": code } })), false)
+        };
+
+        let settings = input
+            .as_object_mut()
+            .ok_or_else(|| TraverseError::Serialization("Standard JSON input is not an object".to_string()))?
+            .entry("settings")
+            .or_insert_with(|| serde_json::json!({}));
+        if !standard {
+            settings["optimizer"] = serde_json::json!({
+                "enabled": source.optimization_used == "1",
+                "runs": source.runs.parse::<u64>().unwrap_or(200),
+            });
+            if !source.evm_version.is_empty() && !source.evm_version.eq_ignore_ascii_case("default") {
+                settings["evmVersion"] = serde_json::Value::String(source.evm_version.to_lowercase());
+            }
+        }
+        settings["outputSelection"] = serde_json::json!({ "*": { "*": ["storageLayout"] } });
+
+        Ok(VerifiedSource {
+            contract_name: source.contract_name,
+            compiler_version: source.compiler_version.trim_start_matches('v').to_string(),
+            input,
+        })
+    }
+
+    /// Recompile verified sources with solc and compile their storage layout
+    ///
+    /// Fails when [`solc_path`](Self::solc_path) is a different solc version
+    /// from the one the contract was verified with, since layouts are only
+    /// guaranteed to match for the same compiler.
+    pub async fn recompile_layout(&self, source: &VerifiedSource) -> Result<LayoutInfo, TraverseError> {
+        use tokio::io::AsyncWriteExt;
+        use tokio::process::Command;
+
+        let solc = self.solc_path.display();
+        let version = Command::new(&self.solc_path)
+            .arg("--version")
+            .output()
+            .await
+            .map_err(|e| TraverseError::Configuration(format!("Cannot run {}: {}", solc, e)))?;
+        let wanted = source.compiler_version.split('+').next().unwrap_or(&source.compiler_version);
+        if !String::from_utf8_lossy(&version.stdout).contains(&format!("Version: {}", wanted)) {
+            return Err(TraverseError::Configuration(format!(
+                "{} is not solc {}, which {} was verified with",
+                solc, wanted, source.contract_name
+            )));
+        }
+
+        let mut child = Command::new(&self.solc_path)
+            .arg("--standard-json")
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .spawn()
+            .map_err(|e| TraverseError::Configuration(format!("Cannot run {}: {}", solc, e)))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(source.input.to_string().as_bytes()).await?;
+        }
+        let output = child.wait_with_output().await?;
+        let output: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+
+        let errors: Vec<&str> = output["errors"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|error| error["severity"] == "error")
+            .filter_map(|error| error["formattedMessage"].as_str().or(error["message"].as_str()))
+            .collect();
+        if !errors.is_empty() {
+            return Err(TraverseError::LayoutCompilation(format!(
+                "solc failed to compile {}: {}",
+                source.contract_name,
+                errors.join("\n")
+            )));
+        }
+        EthereumLayoutCompiler.compile_solc_output(&output, Some(&source.contract_name))
+    }
+
+    /// Where the layout of `contract_address` is cached, if caching is on
+    pub fn layout_cache_path(&self, contract_address: &str) -> Option<PathBuf> {
+        let address = contract_address.strip_prefix("0x").unwrap_or(contract_address).to_lowercase();
+        self.cache_dir
+            .as_ref()
+            .map(|dir| dir.join(self.chain_id.to_string()).join(format!("0x{}.layout.json", address)))
+    }
+
+    fn read_cached_layout(path: &Path) -> Option<CachedLayout> {
+        serde_json::from_slice(&std::fs::read(path).ok()?).ok()
+    }
+
+    fn cache_layout(
+        &self,
+        path: Option<&Path>,
+        layout: LayoutInfo,
+        source: AbiSource,
+    ) -> Result<(LayoutInfo, AbiSource), TraverseError> {
+        if let Some(path) = path {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let cached = CachedLayout { source, layout };
+            std::fs::write(path, serde_json::to_vec_pretty(&cached)?)?;
+            return Ok((cached.layout, cached.source));
+        }
+        Ok((layout, source))
+    }
+
     /// Generate a realistic storage layout from contract ABI
     ///
     /// This creates an estimated storage layout based on common ERC20/ERC721 patterns
@@ -286,6 +781,7 @@ impl AbiFetcher {
             contract_name,
             storage,
             types,
+            code_variables: Vec::new(),
         })
     }
 
@@ -378,3 +874,103 @@ impl AbiFetcher {
         Ok(layout)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_selection() {
+        assert_eq!(AbiFetcher::chain_id("mainnet"), Some(1));
+        assert_eq!(AbiFetcher::chain_id("Sepolia"), Some(11_155_111));
+        assert_eq!(AbiFetcher::chain_id("8453"), Some(8_453));
+        assert_eq!(AbiFetcher::chain_id("atlantis"), None);
+
+        let fetcher = AbiFetcher::for_chain("base", Some("key".to_string())).unwrap();
+        assert_eq!(fetcher.chain_id, 8_453);
+        assert_eq!(fetcher.etherscan_base_url, ETHERSCAN_V2_BASE_URL);
+        assert!(AbiFetcher::for_chain("atlantis", None).is_err());
+    }
+
+    #[test]
+    fn test_verified_source_forms() {
+        let response = |source_code: &str| EtherscanSourceResponse {
+            status: "1".to_string(),
+            message: "OK".to_string(),
+            result: serde_json::json!([{
+                "SourceCode": source_code,
+                "ABI": "[]",
+                "ContractName": "Token",
+                "CompilerVersion": "v0.8.24+commit.e11b9ed9",
+                "OptimizationUsed": "1",
+                "Runs": "10000",
+                "EVMVersion": "Default",
+                "Library": ""
+            }]),
+        };
+
+        // A single flattened file
+        let source = AbiFetcher::verified_source(response("contract Token { uint256 x; }")).unwrap();
+        assert_eq!(source.compiler_version, "0.8.24+commit.e11b9ed9");
+        assert_eq!(source.input["sources"]["Token.sol"]["content"], "contract Token { uint256 x; }");
+        assert_eq!(source.input["settings"]["optimizer"]["runs"], 10000);
+        assert!(source.input["settings"].get("evmVersion").is_none());
+        assert_eq!(source.input["settings"]["outputSelection"]["*"]["*"][0], "storageLayout");
+
+        // A map of files
+        let files = r#"{"src/Token.sol": {"content": "contract Token {}"}}"#;
+        let source = AbiFetcher::verified_source(response(files)).unwrap();
+        assert_eq!(source.input["sources"]["src/Token.sol"]["content"], "contract Token {}");
+
+        // A standard JSON input keeps its own settings
+        let standard = r#"{{"language": "Solidity", "sources": {}, "settings": {"viaIR": true, "optimizer": {"enabled": false}}}}"#;
+        let source = AbiFetcher::verified_source(response(standard)).unwrap();
+        assert_eq!(source.input["settings"]["viaIR"], true);
+        assert_eq!(source.input["settings"]["optimizer"]["enabled"], false);
+        assert!(source.input["settings"]["outputSelection"].is_object());
+
+        // Unverified contracts come back with empty source
+        assert!(AbiFetcher::verified_source(response("")).is_err());
+        let error = EtherscanSourceResponse {
+            status: "0".to_string(),
+            message: "NOTOK".to_string(),
+            result: serde_json::json!("Invalid API Key"),
+        };
+        assert!(AbiFetcher::verified_source(error).unwrap_err().to_string().contains("Invalid API Key"));
+    }
+
+    #[test]
+    fn test_layout_from_sourcify() {
+        let response: SourcifyLayoutResponse = serde_json::from_value(serde_json::json!({
+            "storageLayout": {
+                "storage": [{"astId": 1, "contract": "src/Token.sol:Token", "label": "owner", "offset": 0, "slot": "0", "type": "t_address"}],
+                "types": {"t_address": {"encoding": "inplace", "label": "address", "numberOfBytes": "20"}}
+            },
+            "compilation": {"name": "Token", "fullyQualifiedName": "src/Token.sol:Token"}
+        }))
+        .unwrap();
+        let layout = AbiFetcher::layout_from_sourcify(response).unwrap();
+        assert_eq!(layout.contract_name, "Token");
+        assert_eq!(layout.storage[0].label, "owner");
+
+        let missing: SourcifyLayoutResponse =
+            serde_json::from_value(serde_json::json!({"storageLayout": null, "compilation": null})).unwrap();
+        assert!(AbiFetcher::layout_from_sourcify(missing).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cached_layout_is_reused() {
+        let dir = tempfile::tempdir().unwrap();
+        let fetcher = AbiFetcher::for_chain("base", None).unwrap().with_cache_dir(dir.path());
+        let path = fetcher.layout_cache_path("0xAbC").unwrap();
+        assert_eq!(path, dir.path().join("8453").join("0xabc.layout.json"));
+
+        let layout = LayoutInfo { contract_name: "Token".to_string(), storage: Vec::new(), types: Vec::new(), code_variables: Vec::new() };
+        fetcher.cache_layout(Some(&path), layout.clone(), AbiSource::Sourcify).unwrap();
+
+        // Served from the cache, so no request reaches the network
+        let (cached, source) = fetcher.fetch_verified_layout("0xabc").await.unwrap();
+        assert_eq!(cached, layout);
+        assert_eq!(source, AbiSource::Sourcify);
+    }
+}
@@ -139,6 +139,57 @@ impl StorageProofResponse {
     }
 }
 
+// =============================================================================
+// Typed EIP-1186 Proof Responses
+// =============================================================================
+
+/// Parse the `result` of an `eth_getProof` call into alloy's typed response
+///
+/// Every field is typed, down to each storage proof's key, value and trie
+/// nodes, so no full alloy provider is needed to read a proof.
+#[cfg(feature = "lightweight-alloy")]
+pub fn parse_account_proof(result: &serde_json::Value) -> Result<EIP1186AccountProofResponse, LightweightAlloyError> {
+    Ok(serde_json::from_value(result.clone())?)
+}
+
+#[cfg(feature = "lightweight-alloy")]
+impl From<&EIP1186StorageProof> for StorageProofResponse {
+    fn from(proof: &EIP1186StorageProof) -> Self {
+        Self {
+            key: proof.key.as_b256().0,
+            value: proof.value.to_be_bytes::<32>(),
+            proof_nodes: proof.proof.iter().map(|node| format!("0x{}", hex::encode(node))).collect(),
+        }
+    }
+}
+
+#[cfg(feature = "lightweight-alloy")]
+impl From<&EIP1186StorageProof> for crate::Eip1186StorageProof {
+    fn from(proof: &EIP1186StorageProof) -> Self {
+        Self {
+            key: proof.key.as_b256().0,
+            value: proof.value.to_be_bytes::<32>(),
+            proof: proof.proof.iter().map(|node| node.to_vec()).collect(),
+        }
+    }
+}
+
+/// The typed response as traverse's own proof, ready to verify against a state root
+#[cfg(feature = "lightweight-alloy")]
+impl From<&EIP1186AccountProofResponse> for crate::Eip1186Proof {
+    fn from(response: &EIP1186AccountProofResponse) -> Self {
+        Self {
+            address: response.address.0 .0,
+            nonce: response.nonce,
+            balance: response.balance.to_be_bytes::<32>(),
+            storage_hash: response.storage_hash.0,
+            code_hash: response.code_hash.0,
+            account_proof: response.account_proof.iter().map(|node| node.to_vec()).collect(),
+            storage_proofs: response.storage_proof.iter().map(crate::Eip1186StorageProof::from).collect(),
+        }
+    }
+}
+
 // =============================================================================
 // ABI Encoding Utilities
 // =============================================================================
@@ -335,6 +386,31 @@ mod tests {
         assert!(!features.is_empty() || !alloy_features_available());
     }
 
+    #[cfg(feature = "lightweight-alloy")]
+    #[test]
+    fn test_typed_account_proof() {
+        use crate::proof::tests::lone_account_proof;
+
+        let (state_root, mut proof) = lone_account_proof([0xab; 20]);
+        proof.storage_proofs.push(crate::Eip1186StorageProof {
+            key: [0x01; 32],
+            value: [0x02; 32],
+            proof: vec![vec![0xc0], vec![0x80, 0x01]],
+        });
+        let typed = parse_account_proof(&proof.to_response()).unwrap();
+        assert_eq!(typed.storage_proof.len(), 1);
+        assert_eq!(typed.account_proof.len(), proof.account_proof.len());
+        assert_eq!(crate::Eip1186Proof::from(&typed), proof);
+
+        let storage = StorageProofResponse::from(&typed.storage_proof[0]);
+        assert_eq!(storage.key, [0x01; 32]);
+        assert_eq!(storage.proof_nodes, ["0xc0", "0x8001"]);
+
+        proof.storage_proofs.clear();
+        let typed = parse_account_proof(&proof.to_response()).unwrap();
+        crate::Eip1186Proof::from(&typed).verify(&state_root).unwrap();
+    }
+
     #[test]
     fn test_abi_encoding() {
         let selector = LightweightAbi::function_selector("transfer(address,uint256)");
@@ -0,0 +1,197 @@
+//! Anchoring L2 storage proofs to L1 commitments
+//!
+//! Every rollup proves storage the same way once it has picked an L2 block:
+//! an `eth_getProof` response checked against the block's state root. What
+//! differs is how that block's state root is tied to L1, by a confirmed
+//! assertion, a posted output root or something else. An [`L2Anchor`]
+//! supplies just that part: it picks the block, gathers the rollup's
+//! commitment to it and checks the commitment covers the block.
+//! [`EthereumProofFetcher::fetch_anchored_proof`] runs the shared part around
+//! any anchor, so a new rollup only implements the trait. The same shape
+//! anchors L1 blocks to beacon finality, in [`crate::beacon`].
+
+use std::future::Future;
+
+use traverse_core::TraverseError;
+
+use crate::{Eip1186Proof, EthereumProofFetcher, PinnedBlock};
+
+/// A rollup's tie between an L2 block's state root and its commitment on L1
+pub trait L2Anchor: Sync {
+    /// Evidence tying a block to L1, such as a confirmation or an output root preimage
+    type Commitment: Send;
+
+    /// Short name of the rollup family, used in output and errors
+    fn name(&self) -> &str;
+
+    /// Pick the L2 block to prove at and gather the commitment to it
+    ///
+    /// `l2` is the fetcher of the proof, so its endpoint and pinned block
+    /// are available to the anchor.
+    fn resolve(
+        &self,
+        l2: &EthereumProofFetcher,
+        client: &reqwest::Client,
+    ) -> impl Future<Output = Result<(PinnedBlock, Self::Commitment), TraverseError>> + Send;
+
+    /// Check that `commitment` attests `block`'s hash and state root
+    fn verify_commitment(&self, block: &PinnedBlock, commitment: &Self::Commitment) -> Result<(), TraverseError>;
+
+    fn commitment_json(&self, commitment: &Self::Commitment) -> serde_json::Value;
+}
+
+/// An [`Eip1186Proof`] at an L2 block with the commitment anchoring the block to L1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnchoredProof<C> {
+    pub block: PinnedBlock,
+    pub commitment: C,
+    pub proof: Eip1186Proof,
+}
+
+impl<C> AnchoredProof<C> {
+    /// Check the commitment with `anchor`, then the proof against the block's state root
+    pub fn verify<A: L2Anchor<Commitment = C>>(&self, anchor: &A) -> Result<(), TraverseError> {
+        anchor.verify_commitment(&self.block, &self.commitment)?;
+        self.proof.verify(&self.block.state_root)
+    }
+
+    /// Combined verification request: the L2 block, the rollup's commitment and the `eth_getProof` response
+    pub fn to_json<A: L2Anchor<Commitment = C>>(&self, anchor: &A) -> serde_json::Value {
+        serde_json::json!({
+            "anchor": anchor.name(),
+            "block": {
+                "number": self.block.number,
+                "hash": format!("0x{}", hex::encode(self.block.hash)),
+                "state_root": format!("0x{}", hex::encode(self.block.state_root)),
+            },
+            "commitment": anchor.commitment_json(&self.commitment),
+            "proof": self.proof.to_response(),
+        })
+    }
+}
+
+impl EthereumProofFetcher {
+    /// Fetch the proof of `keys` at the block `anchor` resolves, checked end to end
+    ///
+    /// The proof is requested by block hash (EIP-1898), so it cannot come
+    /// from another block than the one committed to. Anchored blocks are
+    /// often old, in which case the endpoint has to be an archive node.
+    #[cfg_attr(feature = "telemetry", tracing::instrument(name = "ethereum_fetch_anchored_proof", skip(self, anchor, keys)))]
+    pub async fn fetch_anchored_proof<A: L2Anchor>(
+        &self,
+        anchor: &A,
+        keys: &[[u8; 32]],
+    ) -> Result<AnchoredProof<A::Commitment>, TraverseError> {
+        let client = reqwest::Client::new();
+        let (block, commitment) = anchor.resolve(self, &client).await?;
+        anchor.verify_commitment(&block, &commitment)?;
+
+        let keys: Vec<String> = keys.iter().map(|key| format!("0x{}", hex::encode(key))).collect();
+        let block_ref = serde_json::json!({ "blockHash": format!("0x{}", hex::encode(block.hash)) });
+        let result = self
+            .rpc(&client, "eth_getProof", serde_json::json!([self.contract_address, keys, block_ref]))
+            .await
+            .map_err(|e| {
+                TraverseError::external_service(format!(
+                    "Node did not serve {} block {} ({}); older state needs an archive node",
+                    anchor.name(),
+                    block.number,
+                    e
+                ))
+            })?;
+        let proof = Eip1186Proof::from_response(&result)?;
+        proof.verify(&block.state_root)?;
+
+        #[cfg(feature = "telemetry")]
+        traverse_core::telemetry::record_proof_size("ethereum", anchor.name(), proof.proof_size());
+        Ok(AnchoredProof { block, commitment, proof })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proof::tests::lone_account_proof;
+
+    /// Anchors a fixed block, committing to its hash
+    struct FixedAnchor(PinnedBlock);
+
+    impl L2Anchor for FixedAnchor {
+        type Commitment = [u8; 32];
+
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        async fn resolve(
+            &self,
+            _l2: &EthereumProofFetcher,
+            _client: &reqwest::Client,
+        ) -> Result<(PinnedBlock, [u8; 32]), TraverseError> {
+            Ok((self.0.clone(), self.0.hash))
+        }
+
+        fn verify_commitment(&self, block: &PinnedBlock, commitment: &[u8; 32]) -> Result<(), TraverseError> {
+            match block.hash == *commitment {
+                true => Ok(()),
+                false => Err(TraverseError::Validation("committed to another block".to_string())),
+            }
+        }
+
+        fn commitment_json(&self, commitment: &[u8; 32]) -> serde_json::Value {
+            format!("0x{}", hex::encode(commitment)).into()
+        }
+    }
+
+    #[test]
+    fn test_anchored_proof_verify_and_json() {
+        let (state_root, proof) = lone_account_proof([0xab; 20]);
+        let block = PinnedBlock { number: 7, hash: [0x11; 32], state_root };
+        let anchor = FixedAnchor(block.clone());
+        let anchored = AnchoredProof { block, commitment: [0x11; 32], proof };
+        anchored.verify(&anchor).unwrap();
+
+        let json = anchored.to_json(&anchor);
+        assert_eq!(json["anchor"], "fixed");
+        assert_eq!(json["commitment"], format!("0x{}", "11".repeat(32)));
+        assert_eq!(json["block"]["number"], 7);
+
+        let mut other_commitment = anchored.clone();
+        other_commitment.commitment = [0x12; 32];
+        assert!(other_commitment.verify(&anchor).is_err());
+
+        let mut other_state = anchored;
+        other_state.block.state_root = [0x22; 32];
+        assert!(other_state.verify(&anchor).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_failures_stop_the_pipeline() {
+        struct Unreachable;
+
+        impl L2Anchor for Unreachable {
+            type Commitment = ();
+
+            fn name(&self) -> &str {
+                "unreachable"
+            }
+
+            async fn resolve(&self, _l2: &EthereumProofFetcher, _client: &reqwest::Client) -> Result<(PinnedBlock, ()), TraverseError> {
+                Err(TraverseError::external_service("L1 unavailable"))
+            }
+
+            fn verify_commitment(&self, _block: &PinnedBlock, _commitment: &()) -> Result<(), TraverseError> {
+                Ok(())
+            }
+
+            fn commitment_json(&self, _commitment: &()) -> serde_json::Value {
+                serde_json::Value::Null
+            }
+        }
+
+        // The fetcher's endpoint is never contacted
+        let fetcher = EthereumProofFetcher::new("http://127.0.0.1:1", "0xab");
+        let error = fetcher.fetch_anchored_proof(&Unreachable, &[[0u8; 32]]).await.unwrap_err();
+        assert!(error.to_string().contains("L1 unavailable"));
+    }
+}
@@ -0,0 +1,402 @@
+//! Arbitrum storage proofs anchored to L1 rollup confirmations
+//!
+//! An Arbitrum chain's state is only final on L1 once its rollup contract
+//! has confirmed an assertion about it. A confirmation commits to the L2
+//! block hash and the outbox send root: the classic rollup stores
+//! `keccak256(blockHash ‖ sendRoot)` as the node's `confirmData` and emits
+//! `NodeConfirmed`, BoLD emits `AssertionConfirmed` with both values.
+//!
+//! As an [`L2Anchor`], an [`ArbitrumRollup`] reads its latest confirmation
+//! at an L1 block and fetches the confirmed L2 header, then checks the chain
+//! between them: the header hashes to the confirmed block hash and carries
+//! the send root in its `extraData`, as Nitro headers do. The resulting
+//! [`ArbitrumProof`] records where on L1 the confirmation happened, so a
+//! verifier can anchor it with a receipt proof of the confirmation log or a
+//! storage proof of the rollup.
+//!
+//! Confirmed blocks trail the L2 head by at least the challenge period, so
+//! the L2 endpoint has to be an archive node.
+
+use traverse_core::hash::keccak256;
+use traverse_core::TraverseError;
+
+use crate::header::{encode_header, header_field, EXTRA_DATA_INDEX, STATE_ROOT_INDEX};
+use crate::receipts::{fixed, hex_field, hex_value, quantity};
+use crate::{AnchoredProof, EthereumProofFetcher, L2Anchor, PinnedBlock, RpcTransport};
+
+/// `latestConfirmed()`, returning the node number or assertion hash
+const LATEST_CONFIRMED: [u8; 4] = [0x65, 0xf7, 0xf8, 0x0d];
+/// `getNode(uint64)` on the classic rollup
+const GET_NODE: [u8; 4] = [0x92, 0xc8, 0x13, 0x4c];
+/// `getAssertion(bytes32)` on the BoLD rollup
+const GET_ASSERTION: [u8; 4] = [0x88, 0x30, 0x28, 0x84];
+
+/// `keccak256("NodeConfirmed(uint64,bytes32,bytes32)")`
+const NODE_CONFIRMED_TOPIC: [u8; 32] = [
+    0x22, 0xef, 0x04, 0x79, 0xa7, 0xff, 0x66, 0x06, 0x60, 0xd1, 0xc2, 0xfe, 0x35, 0xf1, 0xb6, 0x32,
+    0xcf, 0x31, 0x67, 0x5c, 0x2d, 0x93, 0x78, 0xdb, 0x8c, 0xec, 0x95, 0xb0, 0x0d, 0x8f, 0xfa, 0x3c,
+];
+/// `keccak256("AssertionConfirmed(bytes32,bytes32,bytes32)")`
+const ASSERTION_CONFIRMED_TOPIC: [u8; 32] = [
+    0xfc, 0x42, 0x82, 0x9b, 0x29, 0xc2, 0x59, 0xa7, 0x37, 0x0a, 0xb5, 0x6c, 0x8f, 0x69, 0xfc, 0xe2,
+    0x3b, 0x5f, 0x35, 0x1a, 0x9c, 0xe1, 0x51, 0xda, 0x45, 0x32, 0x81, 0x99, 0x3e, 0xc0, 0x09, 0x0c,
+];
+
+/// L1 blocks per `eth_getLogs` call when searching for a confirmation
+const LOG_WINDOW: u64 = 10_000;
+
+/// The classic rollup's `confirmData` for an L2 block hash and send root
+pub fn confirm_data(block_hash: &[u8; 32], send_root: &[u8; 32]) -> [u8; 32] {
+    let mut preimage = [0u8; 64];
+    preimage[..32].copy_from_slice(block_hash);
+    preimage[32..].copy_from_slice(send_root);
+    keccak256(&preimage)
+}
+
+/// Rollup contract generation an Arbitrum chain settles through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RollupProtocol {
+    /// Numbered nodes with `confirmData`, before BoLD
+    #[default]
+    Classic,
+    /// Assertions identified by hash
+    Bold,
+}
+
+impl RollupProtocol {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RollupProtocol::Classic => "classic",
+            RollupProtocol::Bold => "bold",
+        }
+    }
+
+    fn confirmed_topic(&self) -> [u8; 32] {
+        match self {
+            RollupProtocol::Classic => NODE_CONFIRMED_TOPIC,
+            RollupProtocol::Bold => ASSERTION_CONFIRMED_TOPIC,
+        }
+    }
+}
+
+/// A confirmed assertion and the L1 log that confirmed it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArbitrumAssertion {
+    pub protocol: RollupProtocol,
+    /// Node number (classic, as a word) or assertion hash (BoLD)
+    pub id: [u8; 32],
+    /// Confirmed L2 block hash
+    pub block_hash: [u8; 32],
+    /// Root of the outbox's L2-to-L1 messages at the block
+    pub send_root: [u8; 32],
+    /// `confirmData` stored in the classic rollup's node
+    pub confirm_data: Option<[u8; 32]>,
+    /// L1 block, transaction and log index of the confirmation event
+    pub confirmed_in_block: u64,
+    pub confirmed_in_tx: [u8; 32],
+    pub log_index: u64,
+}
+
+/// An Arbitrum rollup contract on L1
+#[derive(Debug, Clone)]
+pub struct ArbitrumRollup {
+    address: String,
+    transport: RpcTransport,
+    protocol: RollupProtocol,
+    l1_block: String,
+}
+
+impl ArbitrumRollup {
+    /// Classic rollup at `address`, read through `l1_rpc` at the finalized L1 block
+    pub fn new(l1_rpc: &str, address: impl Into<String>) -> Self {
+        Self {
+            address: address.into(),
+            transport: RpcTransport::new(l1_rpc),
+            protocol: RollupProtocol::Classic,
+            l1_block: "finalized".to_string(),
+        }
+    }
+
+    pub fn with_protocol(mut self, protocol: RollupProtocol) -> Self {
+        self.protocol = protocol;
+        self
+    }
+
+    pub fn with_transport(mut self, transport: RpcTransport) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Read the rollup at `block`, a tag or `0x` number, instead of `finalized`
+    pub fn at_l1_block(mut self, block: impl Into<String>) -> Self {
+        self.l1_block = block.into();
+        self
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    async fn call(&self, client: &reqwest::Client, block: u64, selector: [u8; 4], arg: Option<&[u8; 32]>) -> Result<Vec<u8>, TraverseError> {
+        let mut data = selector.to_vec();
+        data.extend_from_slice(arg.map_or(&[][..], |arg| &arg[..]));
+        let call = serde_json::json!({ "to": self.address, "data": format!("0x{}", hex::encode(data)) });
+        let result = self.transport.call(client, "eth_call", serde_json::json!([call, format!("0x{:x}", block)])).await?;
+        hex_value(&result, "eth_call result")
+    }
+
+    /// The latest confirmed assertion at the L1 block, and that block
+    ///
+    /// For the classic rollup the confirmation's block hash and send root
+    /// must hash to the node's `confirmData`.
+    pub async fn latest_confirmed(&self, client: &reqwest::Client) -> Result<(PinnedBlock, ArbitrumAssertion), TraverseError> {
+        let header = self.transport.call(client, "eth_getBlockByNumber", serde_json::json!([self.l1_block, false])).await?;
+        let l1_block = PinnedBlock {
+            number: quantity(&hex_field(&header, "number")?, "number")?,
+            hash: fixed(&hex_field(&header, "hash")?, "hash")?,
+            state_root: fixed(&hex_field(&header, "stateRoot")?, "stateRoot")?,
+        };
+
+        let id: [u8; 32] = fixed(&self.call(client, l1_block.number, LATEST_CONFIRMED, None).await?, "latestConfirmed")?;
+        let (getter, created_at_word) = match self.protocol {
+            RollupProtocol::Classic => (GET_NODE, 10),
+            RollupProtocol::Bold => (GET_ASSERTION, 2),
+        };
+        let node = self.call(client, l1_block.number, getter, Some(&id)).await?;
+        let word = |index: usize| {
+            node.get(index * 32..(index + 1) * 32)
+                .ok_or_else(|| TraverseError::Validation(format!("Rollup returned a {}-byte assertion", node.len())))
+        };
+        let created_at = quantity(word(created_at_word)?, "createdAtBlock")?;
+        let stored_confirm_data = match self.protocol {
+            RollupProtocol::Classic => Some(fixed(word(2)?, "confirmData")?),
+            RollupProtocol::Bold => None,
+        };
+
+        let log = self.find_confirmation(client, &id, created_at, l1_block.number).await?;
+        let data = hex_field(&log, "data")?;
+        if data.len() != 64 {
+            return Err(TraverseError::Validation(format!("Confirmation log has {} data bytes, expected 64", data.len())));
+        }
+        let assertion = ArbitrumAssertion {
+            protocol: self.protocol,
+            id,
+            block_hash: fixed(&data[..32], "blockHash")?,
+            send_root: fixed(&data[32..], "sendRoot")?,
+            confirm_data: stored_confirm_data,
+            confirmed_in_block: quantity(&hex_field(&log, "blockNumber")?, "blockNumber")?,
+            confirmed_in_tx: fixed(&hex_field(&log, "transactionHash")?, "transactionHash")?,
+            log_index: quantity(&hex_field(&log, "logIndex")?, "logIndex")?,
+        };
+        if let Some(stored) = assertion.confirm_data {
+            if confirm_data(&assertion.block_hash, &assertion.send_root) != stored {
+                return Err(TraverseError::Validation(
+                    "Confirmed block hash and send root do not match the node's confirmData".to_string(),
+                ));
+            }
+        }
+        Ok((l1_block, assertion))
+    }
+
+    /// The confirmation log of `id`, searching back from `to` to the assertion's creation
+    async fn find_confirmation(&self, client: &reqwest::Client, id: &[u8; 32], from: u64, to: u64) -> Result<serde_json::Value, TraverseError> {
+        let topics = [
+            format!("0x{}", hex::encode(self.protocol.confirmed_topic())),
+            format!("0x{}", hex::encode(id)),
+        ];
+        let mut end = to;
+        loop {
+            let start = end.saturating_sub(LOG_WINDOW - 1).max(from);
+            let filter = serde_json::json!({
+                "address": self.address,
+                "topics": topics,
+                "fromBlock": format!("0x{:x}", start),
+                "toBlock": format!("0x{:x}", end),
+            });
+            let logs = self.transport.call(client, "eth_getLogs", serde_json::json!([filter])).await?;
+            if let Some(log) = logs.as_array().and_then(|logs| logs.last()) {
+                return Ok(log.clone());
+            }
+            if start <= from {
+                return Err(TraverseError::Validation(format!(
+                    "No {} confirmation of 0x{} between L1 blocks {} and {}",
+                    self.protocol.as_str(),
+                    hex::encode(id),
+                    from,
+                    to
+                )));
+            }
+            end = start - 1;
+        }
+    }
+}
+
+/// Evidence anchoring an Arbitrum block: the rollup's confirmation and the block's header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArbitrumCommitment {
+    /// Rollup contract address on L1
+    pub rollup: String,
+    /// L1 block the rollup was read at
+    pub l1_block: PinnedBlock,
+    pub assertion: ArbitrumAssertion,
+    /// RLP of the confirmed L2 header
+    pub l2_header: Vec<u8>,
+}
+
+/// Storage proof of an Arbitrum contract at a block confirmed on L1
+pub type ArbitrumProof = AnchoredProof<ArbitrumCommitment>;
+
+impl L2Anchor for ArbitrumRollup {
+    type Commitment = ArbitrumCommitment;
+
+    fn name(&self) -> &str {
+        "arbitrum"
+    }
+
+    /// The latest confirmed block, with its header from the Arbitrum endpoint
+    async fn resolve(
+        &self,
+        l2: &EthereumProofFetcher,
+        client: &reqwest::Client,
+    ) -> Result<(PinnedBlock, ArbitrumCommitment), TraverseError> {
+        let (l1_block, assertion) = self.latest_confirmed(client).await?;
+        let block_hash = format!("0x{}", hex::encode(assertion.block_hash));
+        let header = l2.rpc(client, "eth_getBlockByHash", serde_json::json!([block_hash, false])).await?;
+        let l2_header = encode_header(&header)?;
+        let block = PinnedBlock {
+            number: quantity(&hex_field(&header, "number")?, "number")?,
+            hash: keccak256(&l2_header),
+            state_root: fixed(&header_field(&l2_header, STATE_ROOT_INDEX)?, "stateRoot")?,
+        };
+        Ok((block, ArbitrumCommitment { rollup: self.address.clone(), l1_block, assertion, l2_header }))
+    }
+
+    /// Check every link from the confirmation to the block
+    ///
+    /// The confirmation's values must match the classic `confirmData`, and
+    /// the header must hash to the confirmed block hash and carry the send
+    /// root and the block's state root.
+    fn verify_commitment(&self, block: &PinnedBlock, commitment: &ArbitrumCommitment) -> Result<(), TraverseError> {
+        let assertion = &commitment.assertion;
+        if let Some(stored) = assertion.confirm_data {
+            if confirm_data(&assertion.block_hash, &assertion.send_root) != stored {
+                return Err(TraverseError::Validation("Confirmation does not match the node's confirmData".to_string()));
+            }
+        }
+        if keccak256(&commitment.l2_header) != assertion.block_hash || block.hash != assertion.block_hash {
+            return Err(TraverseError::Validation("L2 header is not the confirmed block".to_string()));
+        }
+        if header_field(&commitment.l2_header, EXTRA_DATA_INDEX)? != assertion.send_root {
+            return Err(TraverseError::Validation("L2 header does not carry the confirmed send root".to_string()));
+        }
+        if header_field(&commitment.l2_header, STATE_ROOT_INDEX)? != block.state_root {
+            return Err(TraverseError::Validation("L2 state root is not the header's".to_string()));
+        }
+        Ok(())
+    }
+
+    /// The L1 confirmation, with its transaction and log index for a receipt proof, and the L2 header
+    fn commitment_json(&self, commitment: &ArbitrumCommitment) -> serde_json::Value {
+        let word = |bytes: &[u8]| format!("0x{}", hex::encode(bytes));
+        let assertion = &commitment.assertion;
+        serde_json::json!({
+            "rollup": commitment.rollup,
+            "l1_block": {
+                "number": commitment.l1_block.number,
+                "hash": word(&commitment.l1_block.hash),
+                "state_root": word(&commitment.l1_block.state_root),
+            },
+            "protocol": assertion.protocol.as_str(),
+            "assertion": word(&assertion.id),
+            "confirm_data": assertion.confirm_data.map(|data| word(&data)),
+            "confirmation": {
+                "block_number": assertion.confirmed_in_block,
+                "tx_hash": word(&assertion.confirmed_in_tx),
+                "log_index": assertion.log_index,
+            },
+            "header": word(&commitment.l2_header),
+            "send_root": word(&assertion.send_root),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::tests::header_json;
+    use crate::proof::tests::lone_account_proof;
+
+    fn rollup() -> ArbitrumRollup {
+        ArbitrumRollup::new("http://localhost:8545", format!("0x{}", "5e".repeat(20)))
+    }
+
+    fn arbitrum_proof(protocol: RollupProtocol) -> ArbitrumProof {
+        let send_root = [0x07; 32];
+        let (state_root, proof) = lone_account_proof([0xab; 20]);
+        let mut header = header_json();
+        header["stateRoot"] = format!("0x{}", hex::encode(state_root)).into();
+        let l2_header = encode_header(&header).unwrap();
+        let block_hash = keccak256(&l2_header);
+
+        let assertion = ArbitrumAssertion {
+            protocol,
+            id: [0x33; 32],
+            block_hash,
+            send_root,
+            confirm_data: (protocol == RollupProtocol::Classic).then(|| confirm_data(&block_hash, &send_root)),
+            confirmed_in_block: 20_999_000,
+            confirmed_in_tx: [0x44; 32],
+            log_index: 3,
+        };
+        AnchoredProof {
+            block: PinnedBlock { number: 436, hash: block_hash, state_root },
+            commitment: ArbitrumCommitment {
+                rollup: rollup().address().to_string(),
+                l1_block: PinnedBlock { number: 21_000_000, hash: [0x11; 32], state_root: [0x22; 32] },
+                assertion,
+                l2_header,
+            },
+            proof,
+        }
+    }
+
+    #[test]
+    fn test_verify_arbitrum_proof() {
+        let rollup = rollup();
+        arbitrum_proof(RollupProtocol::Classic).verify(&rollup).unwrap();
+        arbitrum_proof(RollupProtocol::Bold).verify(&rollup).unwrap();
+
+        let mut wrong_confirm = arbitrum_proof(RollupProtocol::Classic);
+        wrong_confirm.commitment.assertion.confirm_data = Some([0u8; 32]);
+        assert!(wrong_confirm.verify(&rollup).is_err());
+
+        // A send root the header does not carry, even with consistent confirmData
+        let mut wrong_send_root = arbitrum_proof(RollupProtocol::Classic);
+        let assertion = &mut wrong_send_root.commitment.assertion;
+        assertion.send_root = [0x08; 32];
+        assertion.confirm_data = Some(confirm_data(&assertion.block_hash, &[0x08; 32]));
+        assert!(wrong_send_root.verify(&rollup).is_err());
+
+        let mut other_header = arbitrum_proof(RollupProtocol::Bold);
+        other_header.commitment.l2_header = encode_header(&header_json()).unwrap();
+        assert!(other_header.verify(&rollup).is_err());
+
+        let mut other_state = arbitrum_proof(RollupProtocol::Bold);
+        other_state.block.state_root = [0x04; 32];
+        assert!(other_state.verify(&rollup).is_err());
+    }
+
+    #[test]
+    fn test_arbitrum_proof_json() {
+        let rollup = rollup();
+        let json = arbitrum_proof(RollupProtocol::Classic).to_json(&rollup);
+        assert_eq!(json["anchor"], "arbitrum");
+        assert_eq!(json["commitment"]["protocol"], "classic");
+        assert_eq!(json["commitment"]["confirmation"]["log_index"], 3);
+        assert_eq!(json["commitment"]["send_root"], format!("0x{}", "07".repeat(32)));
+        assert_eq!(json["block"]["number"], 436);
+        assert!(json["proof"]["accountProof"].is_array());
+
+        assert!(arbitrum_proof(RollupProtocol::Bold).to_json(&rollup)["commitment"]["confirm_data"].is_null());
+    }
+}
@@ -0,0 +1,268 @@
+//! Ethereum storage proofs anchored to the finalized beacon checkpoint
+//!
+//! `latest` is whatever block the node happens to be on and can be
+//! reorganized away; a finalized block cannot without slashing a third of
+//! the stake. A [`BeaconAnchor`] asks a beacon node for the latest finalized
+//! checkpoint, reads the execution payload of the checkpoint block, and pins
+//! the proof to that payload's block. The [`FinalizedProof`] records the
+//! checkpoint epoch and root, the beacon slot and state root, so a verifier
+//! that follows finality — a light client, or a contract reading beacon
+//! roots through EIP-4788 — can check the proof is for a finalized block.
+//!
+//! The link from the beacon block to its execution payload is taken from
+//! the beacon node as reported; pin the checkpoint root with
+//! [`BeaconAnchor::with_checkpoint_root`] to reject a node on another fork.
+
+use traverse_core::TraverseError;
+
+use crate::receipts::{fixed, hex_field, hex_value, quantity};
+use crate::{AnchoredProof, EthereumProofFetcher, L2Anchor, PinnedBlock};
+
+/// Slots per epoch on mainnet and its testnets
+pub const SLOTS_PER_EPOCH: u64 = 32;
+
+/// Evidence that a block is the execution payload of the finalized beacon checkpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BeaconFinality {
+    /// Epoch of the finalized checkpoint
+    pub finalized_epoch: u64,
+    /// Root of the checkpoint's beacon block
+    pub checkpoint_root: [u8; 32],
+    /// Slot of the checkpoint block, at or before the epoch's first slot
+    pub slot: u64,
+    pub beacon_state_root: [u8; 32],
+    pub execution_block_number: u64,
+    pub execution_block_hash: [u8; 32],
+    pub execution_state_root: [u8; 32],
+    /// Whether the beacon node had not yet validated the payload with its execution client
+    pub execution_optimistic: bool,
+}
+
+/// Storage proof at the execution block of the finalized beacon checkpoint
+pub type FinalizedProof = AnchoredProof<BeaconFinality>;
+
+/// Anchor at the execution block of the latest finalized beacon checkpoint
+#[derive(Debug, Clone)]
+pub struct BeaconAnchor {
+    beacon_url: String,
+    checkpoint_root: Option<[u8; 32]>,
+}
+
+impl BeaconAnchor {
+    /// Read finality from the beacon node API at `beacon_url`
+    pub fn new(beacon_url: impl Into<String>) -> Self {
+        Self {
+            beacon_url: beacon_url.into(),
+            checkpoint_root: None,
+        }
+    }
+
+    /// Require the finalized checkpoint to be `root`, as known from a trusted source
+    pub fn with_checkpoint_root(mut self, root: [u8; 32]) -> Self {
+        self.checkpoint_root = Some(root);
+        self
+    }
+
+    async fn get(&self, client: &reqwest::Client, path: &str) -> Result<serde_json::Value, TraverseError> {
+        let url = format!("{}/{}", self.beacon_url.trim_end_matches('/'), path);
+        let response = client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| TraverseError::external_service(format!("Beacon request failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(TraverseError::external_service(format!("Beacon node answered HTTP {} for {}", response.status(), path)));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| TraverseError::external_service(format!("Invalid beacon response: {}", e)))
+    }
+}
+
+/// Parse a beacon API number, written as a decimal string
+fn decimal(object: &serde_json::Value, field: &str) -> Result<u64, TraverseError> {
+    object
+        .get(field)
+        .and_then(serde_json::Value::as_str)
+        .and_then(|text| text.parse().ok())
+        .ok_or_else(|| TraverseError::external_service(format!("Invalid {} in beacon response", field)))
+}
+
+/// Finality metadata from a `finality_checkpoints` response and the checkpoint's block
+fn parse_finality(checkpoints: &serde_json::Value, block: &serde_json::Value) -> Result<BeaconFinality, TraverseError> {
+    let finalized = &checkpoints["data"]["finalized"];
+    let message = &block["data"]["message"];
+    let payload = message["body"].get("execution_payload").ok_or_else(|| {
+        TraverseError::external_service("Finalized beacon block has no execution payload; the chain is pre-merge")
+    })?;
+    Ok(BeaconFinality {
+        finalized_epoch: decimal(finalized, "epoch")?,
+        checkpoint_root: fixed(&hex_field(finalized, "root")?, "root")?,
+        slot: decimal(message, "slot")?,
+        beacon_state_root: fixed(&hex_field(message, "state_root")?, "state_root")?,
+        execution_block_number: decimal(payload, "block_number")?,
+        execution_block_hash: fixed(&hex_field(payload, "block_hash")?, "block_hash")?,
+        execution_state_root: fixed(&hex_field(payload, "state_root")?, "state_root")?,
+        execution_optimistic: block["execution_optimistic"].as_bool().unwrap_or(false),
+    })
+}
+
+impl L2Anchor for BeaconAnchor {
+    type Commitment = BeaconFinality;
+
+    fn name(&self) -> &str {
+        "beacon-finalized"
+    }
+
+    /// The execution block of the finalized checkpoint, as the fetcher's node has it
+    ///
+    /// The fetcher's pinned block is ignored; finality picks the block.
+    async fn resolve(
+        &self,
+        l1: &EthereumProofFetcher,
+        client: &reqwest::Client,
+    ) -> Result<(PinnedBlock, BeaconFinality), TraverseError> {
+        let checkpoints = self.get(client, "eth/v1/beacon/states/head/finality_checkpoints").await?;
+        let root = hex_value(&checkpoints["data"]["finalized"]["root"], "root")?;
+        let block = self.get(client, &format!("eth/v2/beacon/blocks/0x{}", hex::encode(root))).await?;
+        let finality = parse_finality(&checkpoints, &block)?;
+
+        let hash = format!("0x{}", hex::encode(finality.execution_block_hash));
+        let header = l1.rpc(client, "eth_getBlockByHash", serde_json::json!([hash, false])).await?;
+        if header.is_null() {
+            return Err(TraverseError::external_service(format!(
+                "Execution node does not have finalized block {}",
+                hash
+            )));
+        }
+        let block = PinnedBlock {
+            number: quantity(&hex_field(&header, "number")?, "number")?,
+            hash: fixed(&hex_field(&header, "hash")?, "hash")?,
+            state_root: fixed(&hex_field(&header, "stateRoot")?, "stateRoot")?,
+        };
+        Ok((block, finality))
+    }
+
+    /// Check the block is the checkpoint's validated execution payload
+    ///
+    /// With a checkpoint root set, the checkpoint must also be that root.
+    fn verify_commitment(&self, block: &PinnedBlock, finality: &BeaconFinality) -> Result<(), TraverseError> {
+        if finality.execution_optimistic {
+            return Err(TraverseError::Validation(
+                "Beacon node has not validated the finalized payload (optimistic sync)".to_string(),
+            ));
+        }
+        if let Some(root) = self.checkpoint_root {
+            if finality.checkpoint_root != root {
+                return Err(TraverseError::Validation(format!(
+                    "Finalized checkpoint 0x{} is not the trusted 0x{}",
+                    hex::encode(finality.checkpoint_root),
+                    hex::encode(root)
+                )));
+            }
+        }
+        if finality.slot > finality.finalized_epoch * SLOTS_PER_EPOCH {
+            return Err(TraverseError::Validation(format!(
+                "Slot {} is after the start of finalized epoch {}",
+                finality.slot, finality.finalized_epoch
+            )));
+        }
+        if finality.execution_block_hash != block.hash
+            || finality.execution_state_root != block.state_root
+            || finality.execution_block_number != block.number
+        {
+            return Err(TraverseError::Validation(
+                "Block is not the finalized checkpoint's execution payload".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The finalized checkpoint and the beacon block it names
+    fn commitment_json(&self, finality: &BeaconFinality) -> serde_json::Value {
+        let word = |bytes: &[u8; 32]| format!("0x{}", hex::encode(bytes));
+        serde_json::json!({
+            "finalized_checkpoint": {
+                "epoch": finality.finalized_epoch,
+                "root": word(&finality.checkpoint_root),
+            },
+            "beacon_block": {
+                "slot": finality.slot,
+                "state_root": word(&finality.beacon_state_root),
+            },
+            "execution_payload": {
+                "block_number": finality.execution_block_number,
+                "block_hash": word(&finality.execution_block_hash),
+                "state_root": word(&finality.execution_state_root),
+            },
+            "execution_optimistic": finality.execution_optimistic,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proof::tests::lone_account_proof;
+
+    fn finalized_proof() -> (serde_json::Value, serde_json::Value, FinalizedProof) {
+        let (state_root, proof) = lone_account_proof([0xab; 20]);
+        let word = |bytes: &[u8]| format!("0x{}", hex::encode(bytes));
+        let checkpoints = serde_json::json!({
+            "execution_optimistic": false,
+            "finalized": true,
+            "data": { "finalized": { "epoch": "300000", "root": word(&[0x77; 32]) } }
+        });
+        let block = serde_json::json!({
+            "version": "deneb",
+            "execution_optimistic": false,
+            "finalized": true,
+            "data": { "message": {
+                "slot": "9600000",
+                "proposer_index": "1",
+                "parent_root": word(&[0x66; 32]),
+                "state_root": word(&[0x55; 32]),
+                "body": { "execution_payload": {
+                    "block_number": "21000000",
+                    "block_hash": word(&[0x11; 32]),
+                    "state_root": word(&state_root),
+                } }
+            } }
+        });
+        let finality = parse_finality(&checkpoints, &block).unwrap();
+        let pinned = PinnedBlock { number: 21_000_000, hash: [0x11; 32], state_root };
+        (checkpoints, block, AnchoredProof { block: pinned, commitment: finality, proof })
+    }
+
+    #[test]
+    fn test_verify_finalized_proof() {
+        let (checkpoints, block, proof) = finalized_proof();
+        assert_eq!(proof.commitment.slot, 300_000 * SLOTS_PER_EPOCH);
+        let anchor = BeaconAnchor::new("http://beacon").with_checkpoint_root([0x77; 32]);
+        proof.verify(&anchor).unwrap();
+        assert!(proof.verify(&BeaconAnchor::new("http://beacon").with_checkpoint_root([0x78; 32])).is_err());
+
+        let mut optimistic = proof.clone();
+        optimistic.commitment.execution_optimistic = true;
+        assert!(optimistic.verify(&anchor).is_err());
+
+        let mut other_block = proof.clone();
+        other_block.block.hash = [0x12; 32];
+        assert!(other_block.verify(&anchor).is_err());
+
+        let mut late_slot = proof.clone();
+        late_slot.commitment.slot += 1;
+        assert!(late_slot.verify(&anchor).is_err());
+
+        let mut pre_merge = block.clone();
+        pre_merge["data"]["message"]["body"] = serde_json::json!({});
+        assert!(parse_finality(&checkpoints, &pre_merge).is_err());
+
+        let json = proof.to_json(&anchor);
+        assert_eq!(json["anchor"], "beacon-finalized");
+        assert_eq!(json["commitment"]["finalized_checkpoint"]["epoch"], 300_000);
+        assert_eq!(json["commitment"]["beacon_block"]["state_root"], format!("0x{}", "55".repeat(32)));
+        assert_eq!(json["block"]["number"], 21_000_000);
+    }
+}
@@ -0,0 +1,136 @@
+//! On-disk cache of `eth_getProof` responses
+//!
+//! A proof at a given block never changes, so a [`ProofCache`] lets repeated
+//! proof generation for the same block reuse earlier responses instead of
+//! going back to the node. Entries are keyed by chain, contract, block and
+//! slot: a contract's account proof at a block is stored once as
+//! `<dir>/<chain id>/<address>/<block>/account.json` and each storage proof
+//! next to it as `<key>.json`, so asking for more slots of a cached contract
+//! only fetches the slots that are missing.
+//!
+//! Block numbers only name one block once it is final. Cache blocks past the
+//! chain's finality; after a reorg the cache would keep serving proofs of
+//! the replaced block.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use traverse_core::TraverseError;
+
+use crate::Eip1186Proof;
+
+/// Directory of cached `eth_getProof` responses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofCache {
+    dir: PathBuf,
+}
+
+impl ProofCache {
+    /// Cache in `dir`, which is created on the first write
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn block_dir(&self, chain_id: u64, address: &str, block: u64) -> PathBuf {
+        let address = address.trim_start_matches("0x").to_lowercase();
+        self.dir.join(chain_id.to_string()).join(address).join(block.to_string())
+    }
+
+    /// The cached proof of `address` at `block` with whichever of `keys` are cached
+    ///
+    /// Storage proofs follow the order of `keys`, skipping keys not in the
+    /// cache. Returns `None` when the account proof is not cached; unreadable
+    /// entries count as missing.
+    pub fn get(&self, chain_id: u64, address: &str, block: u64, keys: &[[u8; 32]]) -> Option<Eip1186Proof> {
+        let dir = self.block_dir(chain_id, address, block);
+        let mut response = read_json(&dir.join("account.json"))?;
+        let slots: Vec<serde_json::Value> = keys
+            .iter()
+            .filter_map(|key| read_json(&dir.join(format!("{}.json", hex::encode(key)))))
+            .collect();
+        response["storageProof"] = serde_json::Value::Array(slots);
+        Eip1186Proof::from_response(&response).ok()
+    }
+
+    /// Store `proof` as the response at `block`, adding its slots to any already cached
+    pub fn put(&self, chain_id: u64, block: u64, proof: &Eip1186Proof) -> Result<(), TraverseError> {
+        let dir = self.block_dir(chain_id, &hex::encode(proof.address), block);
+        fs::create_dir_all(&dir)?;
+
+        let mut response = proof.to_response();
+        let slots = response["storageProof"].take();
+        response["storageProof"] = serde_json::Value::Array(Vec::new());
+        write_json(&dir.join("account.json"), &response)?;
+        for (slot, entry) in proof.storage_proofs.iter().zip(slots.as_array().into_iter().flatten()) {
+            write_json(&dir.join(format!("{}.json", hex::encode(slot.key))), entry)?;
+        }
+        Ok(())
+    }
+
+    /// Remove every cached proof
+    pub fn clear(&self) -> Result<(), TraverseError> {
+        match fs::remove_dir_all(&self.dir) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn read_json(path: &Path) -> Option<serde_json::Value> {
+    serde_json::from_slice(&fs::read(path).ok()?).ok()
+}
+
+/// Write through a temporary file so concurrent readers never see a partial entry
+fn write_json(path: &Path, value: &serde_json::Value) -> Result<(), TraverseError> {
+    let bytes = serde_json::to_vec(value).map_err(|e| TraverseError::Serialization(e.to_string()))?;
+    let temporary = path.with_extension(format!("{}.tmp", std::process::id()));
+    fs::write(&temporary, bytes)?;
+    fs::rename(&temporary, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Eip1186StorageProof;
+
+    fn proof(keys: &[u8]) -> Eip1186Proof {
+        Eip1186Proof {
+            address: [0xab; 20],
+            nonce: 1,
+            balance: [0u8; 32],
+            storage_hash: [0x11; 32],
+            code_hash: [0x22; 32],
+            account_proof: vec![vec![0xf8, 0x01], vec![0xf8, 0x02]],
+            storage_proofs: keys
+                .iter()
+                .map(|key| Eip1186StorageProof { key: [*key; 32], value: [*key; 32], proof: vec![vec![*key, 0x80]] })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_cache_round_trip_and_partial_hits() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ProofCache::new(dir.path().join("proofs"));
+        let address = format!("0x{}", "AB".repeat(20));
+        assert_eq!(cache.get(1, &address, 100, &[[1u8; 32]]), None);
+
+        cache.put(1, 100, &proof(&[1, 2])).unwrap();
+        cache.put(1, 100, &proof(&[3])).unwrap();
+        assert_eq!(cache.get(1, &address, 100, &[[1u8; 32], [2u8; 32], [3u8; 32]]), Some(proof(&[1, 2, 3])));
+        assert_eq!(cache.get(1, &address, 100, &[[3u8; 32], [4u8; 32], [1u8; 32]]), Some(proof(&[3, 1])));
+
+        // Other blocks and chains are separate entries
+        assert_eq!(cache.get(1, &address, 101, &[[1u8; 32]]), None);
+        assert_eq!(cache.get(10, &address, 100, &[[1u8; 32]]), None);
+
+        cache.clear().unwrap();
+        assert_eq!(cache.get(1, &address, 100, &[[1u8; 32]]), None);
+        cache.clear().unwrap();
+    }
+}
@@ -0,0 +1,303 @@
+//! Chainlink price feed storage
+//!
+//! Consumers read a feed through its `EACAggregatorProxy`, whose layout is
+//! the [`chainlink_proxy`](crate::presets::chainlink_proxy) preset: the
+//! packed current phase names the aggregator behind the proxy. The rounds
+//! themselves live in that aggregator. An OCR aggregator keeps the latest
+//! round id in its packed `s_hotVars` and each round's answer and timestamps
+//! in `s_transmissions`, a `mapping(uint32 => Transmission)`:
+//!
+//! ```rust,ignore
+//! use traverse_ethereum::chainlink::{AggregatorVersion, ChainlinkAggregator};
+//!
+//! // Slots from the aggregator's compiled layout
+//! let aggregator = ChainlinkAggregator::from_layout(AggregatorVersion::Ocr2, &layout)?;
+//! let round = EthereumProofFetcher::new(rpc, aggregator_address).fetch_latest_round(&aggregator).await?;
+//! println!("{} at {}", round.transmission.answer, round.transmission.updated_at());
+//! ```
+//!
+//! The two aggregator generations differ in their packing:
+//!
+//! | | `s_hotVars` round id | `Transmission` |
+//! |---|---|---|
+//! | OCR1 | `uint32` at offset 22, after a `bytes16` digest, `uint40` epoch and round and `uint8` threshold | `int192 answer`, `uint64 timestamp` |
+//! | OCR2 | `uint32` at offset 5, after a `uint32` epoch and round and `uint8 f` | `int192 answer`, `uint32 observationsTimestamp`, `uint32 transmissionTimestamp` |
+
+use traverse_core::hash::keccak256;
+use traverse_core::{LayoutInfo, TraverseError};
+
+#[cfg(feature = "std")]
+use crate::{Eip1186Proof, EthereumProofFetcher};
+
+/// Slot of the proxy's packed `currentPhase`
+pub const PROXY_CURRENT_PHASE: u64 = 2;
+/// Slot of the proxy's `phaseAggregators`, a `mapping(uint16 => address)`
+pub const PROXY_PHASE_AGGREGATORS: u64 = 4;
+
+fn slot_word(slot: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&slot.to_be_bytes());
+    word
+}
+
+/// Field of `size` bytes at byte `offset` of a packed word, counted from the low end
+fn field(word: &[u8; 32], offset: usize, size: usize) -> &[u8] {
+    &word[32 - offset - size..32 - offset]
+}
+
+fn unsigned(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0, |value, byte| (value << 8) | u64::from(*byte))
+}
+
+/// The proxy's current phase: its id and the aggregator serving it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Phase {
+    pub id: u16,
+    pub aggregator: [u8; 20],
+}
+
+impl Phase {
+    /// Decode the proxy's `currentPhase` word
+    pub fn decode(word: &[u8; 32]) -> Self {
+        let mut aggregator = [0u8; 20];
+        aggregator.copy_from_slice(field(word, 2, 20));
+        Self { id: unsigned(field(word, 0, 2)) as u16, aggregator }
+    }
+
+    /// Round id the proxy reports for `aggregator_round`, `phase << 64 | round`
+    pub fn proxy_round_id(&self, aggregator_round: u32) -> u128 {
+        (u128::from(self.id) << 64) | u128::from(aggregator_round)
+    }
+}
+
+/// Generation of an OCR aggregator, which sets its storage packing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AggregatorVersion {
+    /// `OffchainAggregator`
+    Ocr1,
+    /// `OCR2Aggregator`, which current feeds run on
+    #[default]
+    Ocr2,
+}
+
+impl AggregatorVersion {
+    /// Latest round id in the aggregator's `s_hotVars` word
+    pub fn latest_round(self, hot_vars: &[u8; 32]) -> u32 {
+        let offset = match self {
+            AggregatorVersion::Ocr1 => 22,
+            AggregatorVersion::Ocr2 => 5,
+        };
+        unsigned(field(hot_vars, offset, 4)) as u32
+    }
+}
+
+/// One round of an aggregator, as stored in `s_transmissions`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transmission {
+    /// The answer, scaled by the feed's decimals
+    pub answer: i128,
+    /// When the observations were made; for OCR1 the transmission time
+    pub started_at: u64,
+    /// When the round was transmitted on chain
+    pub transmitted_at: u64,
+}
+
+impl Transmission {
+    /// Decode a `Transmission` word
+    ///
+    /// Answers are `int192`; one that does not fit an `i128` is an error,
+    /// as no feed reports such values.
+    pub fn decode(version: AggregatorVersion, word: &[u8; 32]) -> Result<Self, TraverseError> {
+        let answer = field(word, 0, 24);
+        let fill = if answer[0] & 0x80 != 0 { 0xff } else { 0 };
+        if answer[..8].iter().any(|byte| *byte != fill) || (answer[8] ^ fill) & 0x80 != 0 {
+            return Err(TraverseError::Validation(format!("Answer 0x{} does not fit 128 bits", hex::encode(answer))));
+        }
+        let answer = i128::from_be_bytes(answer[8..].try_into().expect("16 bytes"));
+
+        let (started_at, transmitted_at) = match version {
+            AggregatorVersion::Ocr1 => {
+                let timestamp = unsigned(field(word, 24, 8));
+                (timestamp, timestamp)
+            }
+            AggregatorVersion::Ocr2 => (unsigned(field(word, 24, 4)), unsigned(field(word, 28, 4))),
+        };
+        Ok(Self { answer, started_at, transmitted_at })
+    }
+
+    /// `updatedAt` of `latestRoundData`
+    pub fn updated_at(&self) -> u64 {
+        self.transmitted_at
+    }
+}
+
+/// Where an aggregator keeps its rounds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainlinkAggregator {
+    pub version: AggregatorVersion,
+    /// Slot of `s_hotVars`
+    pub hot_vars_slot: u64,
+    /// Slot of `s_transmissions`
+    pub transmissions_slot: u64,
+}
+
+impl ChainlinkAggregator {
+    pub fn new(version: AggregatorVersion, hot_vars_slot: u64, transmissions_slot: u64) -> Self {
+        Self { version, hot_vars_slot, transmissions_slot }
+    }
+
+    /// Take the slots of `s_hotVars` and `s_transmissions` from the aggregator's compiled layout
+    pub fn from_layout(version: AggregatorVersion, layout: &LayoutInfo) -> Result<Self, TraverseError> {
+        let slot = |label: &str| {
+            layout
+                .storage
+                .iter()
+                .find(|entry| entry.label == label)
+                .and_then(|entry| entry.slot.parse::<u64>().ok())
+                .ok_or_else(|| {
+                    TraverseError::LayoutCompilation(format!("{} has no {}; is it an OCR aggregator?", layout.contract_name, label))
+                })
+        };
+        Ok(Self::new(version, slot("s_hotVars")?, slot("s_transmissions")?))
+    }
+
+    /// Storage key of `s_hotVars`
+    pub fn hot_vars_key(&self) -> [u8; 32] {
+        slot_word(self.hot_vars_slot)
+    }
+
+    /// Storage key of `s_transmissions[round]`
+    pub fn transmission_key(&self, round: u32) -> [u8; 32] {
+        let mut preimage = [0u8; 64];
+        preimage[28..32].copy_from_slice(&round.to_be_bytes());
+        preimage[32..].copy_from_slice(&slot_word(self.transmissions_slot));
+        keccak256(&preimage)
+    }
+}
+
+/// The latest round of an aggregator with the proof of both slots it was read from
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainlinkRound {
+    pub block: u64,
+    /// Aggregator round id; see [`Phase::proxy_round_id`] for the proxy's
+    pub round: u32,
+    pub transmission: Transmission,
+    /// Storage proofs of `s_hotVars` and of the round's transmission, in that order
+    pub proof: Eip1186Proof,
+}
+
+#[cfg(feature = "std")]
+impl ChainlinkRound {
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "block": self.block,
+            "round": self.round,
+            "answer": self.transmission.answer.to_string(),
+            "started_at": self.transmission.started_at,
+            "updated_at": self.transmission.updated_at(),
+            "proof": self.proof.to_response(),
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl EthereumProofFetcher {
+    /// Prove the latest round of the aggregator at the fetcher's address
+    ///
+    /// The round id comes from `s_hotVars`, then its transmission is proven
+    /// at the same block: the archive block, or the latest one as of the
+    /// first call.
+    pub async fn fetch_latest_round(&self, aggregator: &ChainlinkAggregator) -> Result<ChainlinkRound, TraverseError> {
+        let block = match self.archive_block {
+            Some(number) => number,
+            None => {
+                let client = reqwest::Client::new();
+                let latest = self.rpc(&client, "eth_blockNumber", serde_json::json!([])).await?;
+                crate::receipts::quantity(&crate::receipts::hex_value(&latest, "eth_blockNumber")?, "eth_blockNumber")?
+            }
+        };
+        let block_param = format!("0x{:x}", block);
+
+        let mut proof = self.fetch_proof(&[aggregator.hot_vars_key()], &block_param).await?;
+        let hot_vars = proof.storage_proofs.first().map(|slot| slot.value).unwrap_or_default();
+        let round = aggregator.version.latest_round(&hot_vars);
+        if round == 0 {
+            return Err(TraverseError::Validation(format!("{} has no rounds yet", self.contract_address)));
+        }
+
+        let transmission = self.fetch_proof(&[aggregator.transmission_key(round)], &block_param).await?;
+        if transmission.storage_hash != proof.storage_hash {
+            return Err(TraverseError::Validation("Aggregator storage changed between reads".to_string()));
+        }
+        proof.storage_proofs.extend(transmission.storage_proofs);
+        let word = proof.storage_proofs[1].value;
+        Ok(ChainlinkRound { block, round, transmission: Transmission::decode(aggregator.version, &word)?, proof })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_transmissions() {
+        // OCR2: answer -2500, observed at 1000, transmitted at 1012
+        let mut word = [0xffu8; 32];
+        word[30..].copy_from_slice(&[0xf6, 0x3c]);
+        word[..8].copy_from_slice(&[0, 0, 0x03, 0xf4, 0, 0, 0x03, 0xe8]);
+        let ocr2 = Transmission::decode(AggregatorVersion::Ocr2, &word).unwrap();
+        assert_eq!(ocr2, Transmission { answer: -2500, started_at: 1000, transmitted_at: 1012 });
+        assert_eq!(ocr2.updated_at(), 1012);
+
+        // OCR1 reads the same low eight bytes as one timestamp
+        let ocr1 = Transmission::decode(AggregatorVersion::Ocr1, &word).unwrap();
+        assert_eq!(ocr1.started_at, ocr1.transmitted_at);
+
+        let mut huge = [0u8; 32];
+        huge[8] = 1;
+        assert!(Transmission::decode(AggregatorVersion::Ocr2, &huge).is_err());
+    }
+
+    #[test]
+    fn test_phase_and_round_keys() {
+        let mut word = [0u8; 32];
+        word[10..30].copy_from_slice(&[0xab; 20]);
+        word[30..].copy_from_slice(&6u16.to_be_bytes());
+        let phase = Phase::decode(&word);
+        assert_eq!(phase, Phase { id: 6, aggregator: [0xab; 20] });
+        assert_eq!(phase.proxy_round_id(42), (6u128 << 64) + 42);
+
+        let mut hot_vars = [0u8; 32];
+        hot_vars[23..27].copy_from_slice(&77u32.to_be_bytes());
+        assert_eq!(AggregatorVersion::Ocr2.latest_round(&hot_vars), 77);
+        hot_vars = [0u8; 32];
+        hot_vars[6..10].copy_from_slice(&78u32.to_be_bytes());
+        assert_eq!(AggregatorVersion::Ocr1.latest_round(&hot_vars), 78);
+
+        let layout = LayoutInfo {
+            contract_name: "OCR2Aggregator".to_string(),
+            storage: ["s_hotVars", "s_transmissions"]
+                .iter()
+                .zip([43, 44])
+                .map(|(label, slot)| traverse_core::StorageEntry {
+                    label: label.to_string(),
+                    slot: slot.to_string(),
+                    offset: 0,
+                    type_name: "t_uint256".to_string(),
+                    zero_semantics: traverse_core::ZeroSemantics::NeverWritten,
+                })
+                .collect(),
+            types: Vec::new(),
+            code_variables: Vec::new(),
+        };
+        let aggregator = ChainlinkAggregator::from_layout(AggregatorVersion::Ocr2, &layout).unwrap();
+        assert_eq!(aggregator, ChainlinkAggregator::new(AggregatorVersion::Ocr2, 43, 44));
+
+        let mut preimage = [0u8; 64];
+        preimage[31] = 77;
+        preimage[63] = 44;
+        assert_eq!(aggregator.transmission_key(77), keccak256(&preimage));
+        assert!(ChainlinkAggregator::from_layout(AggregatorVersion::Ocr2, &crate::presets::safe()).is_err());
+    }
+}
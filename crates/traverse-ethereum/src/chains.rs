@@ -0,0 +1,344 @@
+//! Quirks of EVM-compatible chains
+//!
+//! Chains that run the EVM do not all behave like Ethereum mainnet at the
+//! RPC level. Some nodes reject the `safe` tag, Avalanche finalizes every
+//! accepted block, full nodes keep state for a limited window of recent
+//! blocks, and each chain has its own set of precompiles. A
+//! [`QuirkProfile`] records these differences for one chain, and an
+//! [`EvmChainAdapter`] picks the profile by chain ID and applies it when
+//! choosing blocks and batching `eth_getProof` requests.
+
+use traverse_core::TraverseError;
+
+/// A named block parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockTag {
+    Latest,
+    Safe,
+    Finalized,
+    Pending,
+    Earliest,
+}
+
+impl BlockTag {
+    pub const ALL: &'static [BlockTag] =
+        &[BlockTag::Latest, BlockTag::Safe, BlockTag::Finalized, BlockTag::Pending, BlockTag::Earliest];
+
+    pub fn parse(tag: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|t| t.as_str() == tag)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BlockTag::Latest => "latest",
+            BlockTag::Safe => "safe",
+            BlockTag::Finalized => "finalized",
+            BlockTag::Pending => "pending",
+            BlockTag::Earliest => "earliest",
+        }
+    }
+}
+
+/// How a chain settles blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvmFinality {
+    /// Nodes report settled blocks through the `safe` and `finalized` tags
+    Tagged,
+    /// Every accepted block is final
+    Instant,
+    /// Blocks are treated as final once this many blocks, themselves included, are on the chain
+    Confirmations(u64),
+}
+
+/// A block to read at, as an adapter selects it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockSelection {
+    /// Pass the tag to the node
+    Tag(BlockTag),
+    /// The block this many blocks below the head
+    BehindHead(u64),
+}
+
+/// RPC and execution differences of one EVM chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuirkProfile {
+    pub name: &'static str,
+    pub chain_id: u64,
+    /// Block tags the chain's nodes accept
+    pub block_tags: &'static [BlockTag],
+    pub finality: EvmFinality,
+    /// Most storage keys one `eth_getProof` call may ask for, if nodes limit it
+    pub max_proof_keys: Option<usize>,
+    /// Blocks behind the head full nodes keep state for; older proofs need an archive node
+    pub proof_window: Option<u64>,
+    /// Highest of Ethereum's precompiles, counting up from `0x01`, the chain has
+    pub last_standard_precompile: u8,
+    /// Precompiles at other addresses
+    pub extra_precompiles: &'static [[u8; 20]],
+}
+
+const fn low_address(n: u16) -> [u8; 20] {
+    let mut address = [0u8; 20];
+    address[18] = (n >> 8) as u8;
+    address[19] = n as u8;
+    address
+}
+
+const fn prefixed_address(prefix: u8, n: u8) -> [u8; 20] {
+    let mut address = [0u8; 20];
+    address[0] = prefix;
+    address[19] = n;
+    address
+}
+
+/// `P256VERIFY`, as RIP-7212 places it
+const P256_VERIFY: [u8; 20] = low_address(0x100);
+
+pub const ETHEREUM: QuirkProfile = QuirkProfile {
+    name: "Ethereum",
+    chain_id: 1,
+    block_tags: BlockTag::ALL,
+    finality: EvmFinality::Tagged,
+    max_proof_keys: None,
+    proof_window: Some(128),
+    last_standard_precompile: 0x11,
+    extra_precompiles: &[],
+};
+
+pub const BSC: QuirkProfile = QuirkProfile {
+    name: "BNB Smart Chain",
+    chain_id: 56,
+    block_tags: BlockTag::ALL,
+    // Fast finality
+    finality: EvmFinality::Tagged,
+    max_proof_keys: None,
+    proof_window: Some(128),
+    last_standard_precompile: 0x0a,
+    // Light client, BLS and double-sign evidence precompiles for the Beacon Chain bridge
+    extra_precompiles: &[
+        low_address(0x64),
+        low_address(0x65),
+        low_address(0x66),
+        low_address(0x67),
+        low_address(0x68),
+        low_address(0x69),
+        P256_VERIFY,
+    ],
+};
+
+pub const POLYGON_POS: QuirkProfile = QuirkProfile {
+    name: "Polygon PoS",
+    chain_id: 137,
+    // Bor reports milestones through `finalized` and has no `safe`
+    block_tags: &[BlockTag::Latest, BlockTag::Finalized, BlockTag::Pending, BlockTag::Earliest],
+    finality: EvmFinality::Tagged,
+    max_proof_keys: None,
+    proof_window: Some(128),
+    last_standard_precompile: 0x0a,
+    extra_precompiles: &[P256_VERIFY],
+};
+
+pub const AVALANCHE_C_CHAIN: QuirkProfile = QuirkProfile {
+    name: "Avalanche C-Chain",
+    chain_id: 43114,
+    block_tags: BlockTag::ALL,
+    // Snowman accepts blocks irreversibly
+    finality: EvmFinality::Instant,
+    max_proof_keys: None,
+    // Pruning nodes keep the tries of the last 32 accepted blocks
+    proof_window: Some(32),
+    last_standard_precompile: 0x09,
+    // Native asset balance and call, and Warp messaging
+    extra_precompiles: &[prefixed_address(0x01, 0x01), prefixed_address(0x01, 0x02), prefixed_address(0x02, 0x05)],
+};
+
+pub const GNOSIS: QuirkProfile = QuirkProfile {
+    name: "Gnosis",
+    chain_id: 100,
+    block_tags: BlockTag::ALL,
+    finality: EvmFinality::Tagged,
+    max_proof_keys: None,
+    proof_window: Some(128),
+    last_standard_precompile: 0x11,
+    extra_precompiles: &[],
+};
+
+/// Profiles selected by chain ID
+pub const PROFILES: &[QuirkProfile] = &[ETHEREUM, BSC, POLYGON_POS, AVALANCHE_C_CHAIN, GNOSIS];
+
+impl QuirkProfile {
+    /// The profile assumed for chains without one: Ethereum's behaviour, without its newest precompiles
+    pub fn generic(chain_id: u64) -> Self {
+        QuirkProfile { name: "EVM chain", chain_id, last_standard_precompile: 0x0a, ..ETHEREUM }
+    }
+
+    pub fn for_chain_id(chain_id: u64) -> Option<&'static QuirkProfile> {
+        PROFILES.iter().find(|profile| profile.chain_id == chain_id)
+    }
+}
+
+/// Applies a chain's quirks to the requests traverse makes of it
+///
+/// # Usage
+///
+/// ```rust,ignore
+/// use traverse_ethereum::{BlockSelection, BlockTag, EvmChainAdapter};
+///
+/// // Polygon has no `safe` tag, so `finalized` is read instead
+/// let adapter = EvmChainAdapter::for_chain_id(137);
+/// assert_eq!(adapter.select_block(BlockTag::Safe)?, BlockSelection::Tag(BlockTag::Finalized));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvmChainAdapter {
+    pub profile: QuirkProfile,
+}
+
+impl EvmChainAdapter {
+    pub fn new(profile: QuirkProfile) -> Self {
+        Self { profile }
+    }
+
+    /// The adapter for a chain, with the generic profile if the chain has none
+    pub fn for_chain_id(chain_id: u64) -> Self {
+        Self::new(QuirkProfile::for_chain_id(chain_id).cloned().unwrap_or_else(|| QuirkProfile::generic(chain_id)))
+    }
+
+    /// Limit the keys per `eth_getProof` call, as some providers do
+    pub fn with_max_proof_keys(mut self, max_proof_keys: usize) -> Self {
+        self.profile.max_proof_keys = Some(max_proof_keys.max(1));
+        self
+    }
+
+    /// Set how far back the node serves proofs, `None` for an archive node
+    pub fn with_proof_window(mut self, proof_window: Option<u64>) -> Self {
+        self.profile.proof_window = proof_window;
+        self
+    }
+
+    /// Choose the block to read for a tag, substituting for tags the chain lacks
+    ///
+    /// `safe` and `finalized` fall back on the chain's finality: the latest
+    /// block where blocks are final when accepted, a depth below the head
+    /// where finality is counted in confirmations, and `finalized` for `safe`
+    /// where only that tag is served.
+    pub fn select_block(&self, tag: BlockTag) -> Result<BlockSelection, TraverseError> {
+        let profile = &self.profile;
+        if profile.block_tags.contains(&tag) {
+            return Ok(BlockSelection::Tag(tag));
+        }
+        match (tag, profile.finality) {
+            (BlockTag::Safe | BlockTag::Finalized, EvmFinality::Instant) => Ok(BlockSelection::Tag(BlockTag::Latest)),
+            (BlockTag::Safe | BlockTag::Finalized, EvmFinality::Confirmations(confirmations)) => {
+                Ok(BlockSelection::BehindHead(confirmations.saturating_sub(1)))
+            }
+            (BlockTag::Safe, EvmFinality::Tagged) if profile.block_tags.contains(&BlockTag::Finalized) => {
+                Ok(BlockSelection::Tag(BlockTag::Finalized))
+            }
+            _ => Err(TraverseError::InvalidInput(format!(
+                "{} does not support the '{}' block tag",
+                profile.name,
+                tag.as_str()
+            ))),
+        }
+    }
+
+    /// Split storage keys into the batches one `eth_getProof` call each may ask for
+    pub fn proof_key_batches<'a, T>(&self, keys: &'a [T]) -> core::slice::Chunks<'a, T> {
+        keys.chunks(self.profile.max_proof_keys.unwrap_or(keys.len()).max(1))
+    }
+
+    /// Fail early when `block` is older than full nodes keep state for
+    pub fn check_proof_depth(&self, head: u64, block: u64) -> Result<(), TraverseError> {
+        match self.profile.proof_window {
+            Some(window) if head.saturating_sub(block) >= window => Err(TraverseError::InvalidInput(format!(
+                "Block {} is {} blocks behind the head; {} full nodes serve proofs for the last {} blocks only, so use an archive node",
+                block,
+                head - block,
+                self.profile.name,
+                window
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether an address is a precompile, which has code but no storage to prove
+    pub fn is_precompile(&self, address: &[u8; 20]) -> bool {
+        let standard = address[..19].iter().all(|b| *b == 0)
+            && address[19] != 0
+            && address[19] <= self.profile.last_standard_precompile;
+        standard || self.profile.extra_precompiles.contains(address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(hex_address: &str) -> [u8; 20] {
+        let mut bytes = [0u8; 20];
+        hex::decode_to_slice(hex_address.trim_start_matches("0x"), &mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_block_selection() {
+        let ethereum = EvmChainAdapter::for_chain_id(1);
+        assert_eq!(ethereum.select_block(BlockTag::Safe).unwrap(), BlockSelection::Tag(BlockTag::Safe));
+
+        let polygon = EvmChainAdapter::for_chain_id(137);
+        assert_eq!(polygon.profile.name, "Polygon PoS");
+        assert_eq!(polygon.select_block(BlockTag::Safe).unwrap(), BlockSelection::Tag(BlockTag::Finalized));
+
+        let instant = EvmChainAdapter::new(QuirkProfile { block_tags: &[BlockTag::Latest], ..AVALANCHE_C_CHAIN });
+        assert_eq!(instant.select_block(BlockTag::Finalized).unwrap(), BlockSelection::Tag(BlockTag::Latest));
+        assert!(instant.select_block(BlockTag::Pending).is_err());
+
+        let counted = EvmChainAdapter::new(QuirkProfile {
+            block_tags: &[BlockTag::Latest],
+            finality: EvmFinality::Confirmations(64),
+            ..QuirkProfile::generic(8453)
+        });
+        assert_eq!(counted.select_block(BlockTag::Finalized).unwrap(), BlockSelection::BehindHead(63));
+
+        assert_eq!(BlockTag::parse("finalized"), Some(BlockTag::Finalized));
+        assert_eq!(BlockTag::parse("0x10"), None);
+    }
+
+    #[test]
+    fn test_proof_limits() {
+        let keys: Vec<u32> = (0..10).collect();
+        let gnosis = EvmChainAdapter::for_chain_id(100);
+        assert_eq!(gnosis.proof_key_batches(&keys).count(), 1);
+        let limited = gnosis.with_max_proof_keys(4);
+        let sizes: Vec<usize> = limited.proof_key_batches(&keys).map(|batch| batch.len()).collect();
+        assert_eq!(sizes, [4, 4, 2]);
+        assert_eq!(limited.proof_key_batches::<u32>(&[]).count(), 0);
+
+        let avalanche = EvmChainAdapter::for_chain_id(43114);
+        assert!(avalanche.check_proof_depth(1_000, 969).is_ok());
+        assert!(avalanche.check_proof_depth(1_000, 968).is_err());
+        assert!(avalanche.with_proof_window(None).check_proof_depth(1_000, 0).is_ok());
+    }
+
+    #[test]
+    fn test_precompiles() {
+        let ethereum = EvmChainAdapter::for_chain_id(1);
+        assert!(ethereum.is_precompile(&address("0x0000000000000000000000000000000000000001")));
+        assert!(ethereum.is_precompile(&address("0x0000000000000000000000000000000000000011")));
+        assert!(!ethereum.is_precompile(&address("0x0000000000000000000000000000000000000000")));
+        assert!(!ethereum.is_precompile(&address("0x0000000000000000000000000000000000000100")));
+
+        let bsc = EvmChainAdapter::for_chain_id(56);
+        assert!(bsc.is_precompile(&address("0x0000000000000000000000000000000000000066")));
+        assert!(!bsc.is_precompile(&address("0x0000000000000000000000000000000000000011")));
+
+        let avalanche = EvmChainAdapter::for_chain_id(43114);
+        assert!(avalanche.is_precompile(&address("0x0200000000000000000000000000000000000005")));
+
+        // Chains without a profile get Ethereum's behaviour
+        let unknown = EvmChainAdapter::for_chain_id(999_999);
+        assert_eq!(unknown.profile.chain_id, 999_999);
+        assert_eq!(unknown.select_block(BlockTag::Safe).unwrap(), BlockSelection::Tag(BlockTag::Safe));
+        assert!(!unknown.is_precompile(&address("0x000000000000000000000000000000000000000b")));
+    }
+}
@@ -0,0 +1,129 @@
+//! Block header encoding
+//!
+//! A block hash is `keccak256(rlp(header))`, so tying a state root to a
+//! block hash takes the header's RLP. Nodes serve headers as JSON objects;
+//! [`encode_header`] rebuilds the consensus encoding from their fields,
+//! including the fields later forks appended whenever the object has them.
+
+use rlp::{Rlp, RlpStream};
+use traverse_core::TraverseError;
+
+use crate::receipts::hex_field;
+
+/// Header fields up to Paris, in consensus order, and whether each is a quantity
+const BASE_FIELDS: [(&str, bool); 15] = [
+    ("parentHash", false),
+    ("sha3Uncles", false),
+    ("miner", false),
+    ("stateRoot", false),
+    ("transactionsRoot", false),
+    ("receiptsRoot", false),
+    ("logsBloom", false),
+    ("difficulty", true),
+    ("number", true),
+    ("gasLimit", true),
+    ("gasUsed", true),
+    ("timestamp", true),
+    ("extraData", false),
+    ("mixHash", false),
+    ("nonce", false),
+];
+
+/// Fields appended by London, Shanghai, Cancun and Prague, in order
+const FORK_FIELDS: [(&str, bool); 6] = [
+    ("baseFeePerGas", true),
+    ("withdrawalsRoot", false),
+    ("blobGasUsed", true),
+    ("excessBlobGas", true),
+    ("parentBeaconBlockRoot", false),
+    ("requestsHash", false),
+];
+
+/// Position of `stateRoot` in the encoded header
+pub(crate) const STATE_ROOT_INDEX: usize = 3;
+/// Position of `extraData` in the encoded header
+pub(crate) const EXTRA_DATA_INDEX: usize = 12;
+
+/// RLP encoding of a header object as returned by `eth_getBlockByHash`
+///
+/// Fork fields are appended while present and stop at the first missing
+/// one, since a header cannot skip a field and keep a later one.
+pub fn encode_header(header: &serde_json::Value) -> Result<Vec<u8>, TraverseError> {
+    let forks = FORK_FIELDS.iter().take_while(|(field, _)| header.get(field).is_some_and(|value| !value.is_null()));
+    let fields: Vec<&(&str, bool)> = BASE_FIELDS.iter().chain(forks).collect();
+
+    let mut stream = RlpStream::new_list(fields.len());
+    for (field, is_quantity) in fields {
+        let bytes = hex_field(header, field)?;
+        if *is_quantity {
+            let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+            stream.append(&&bytes[start..]);
+        } else {
+            stream.append(&bytes.as_slice());
+        }
+    }
+    Ok(stream.out().to_vec())
+}
+
+/// Byte string at `index` of an encoded header
+pub(crate) fn header_field(header: &[u8], index: usize) -> Result<Vec<u8>, TraverseError> {
+    Rlp::new(header)
+        .at(index)
+        .and_then(|item| item.data().map(<[u8]>::to_vec))
+        .map_err(|e| TraverseError::Validation(format!("Invalid block header: field {}: {}", index, e)))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A post-Cancun header object with distinct, recognizable fields
+    pub(crate) fn header_json() -> serde_json::Value {
+        let word = |byte: &str| format!("0x{}", byte.repeat(32));
+        serde_json::json!({
+            "parentHash": word("01"),
+            "sha3Uncles": word("02"),
+            "miner": format!("0x{}", "03".repeat(20)),
+            "stateRoot": word("04"),
+            "transactionsRoot": word("05"),
+            "receiptsRoot": word("06"),
+            "logsBloom": format!("0x{}", "00".repeat(256)),
+            "difficulty": "0x0",
+            "number": "0x1b4",
+            "gasLimit": "0x1c9c380",
+            "gasUsed": "0x0",
+            "timestamp": "0x65f0a000",
+            "extraData": word("07"),
+            "mixHash": word("08"),
+            "nonce": "0x0000000000000000",
+            "baseFeePerGas": "0x7",
+            "withdrawalsRoot": word("09"),
+            "blobGasUsed": "0x0",
+            "excessBlobGas": "0x20000",
+            "parentBeaconBlockRoot": word("0a"),
+            "hash": word("ff")
+        })
+    }
+
+    #[test]
+    fn test_encode_header() {
+        let encoded = encode_header(&header_json()).unwrap();
+        let rlp = Rlp::new(&encoded);
+        assert_eq!(rlp.item_count().unwrap(), 20);
+        assert_eq!(header_field(&encoded, STATE_ROOT_INDEX).unwrap(), vec![0x04; 32]);
+        assert_eq!(header_field(&encoded, EXTRA_DATA_INDEX).unwrap(), vec![0x07; 32]);
+        // Quantities are minimal, zero being the empty string
+        assert_eq!(header_field(&encoded, 7).unwrap(), Vec::<u8>::new());
+        assert_eq!(header_field(&encoded, 8).unwrap(), vec![0x01, 0xb4]);
+        // The fixed 8-byte nonce is kept whole
+        assert_eq!(header_field(&encoded, 14).unwrap(), vec![0u8; 8]);
+
+        // Without London's base fee no later fork field is encoded
+        let mut legacy = header_json();
+        legacy["baseFeePerGas"] = serde_json::Value::Null;
+        assert_eq!(Rlp::new(&encode_header(&legacy).unwrap()).item_count().unwrap(), 15);
+
+        legacy.as_object_mut().unwrap().remove("stateRoot");
+        assert!(encode_header(&legacy).is_err());
+    }
+}
@@ -0,0 +1,587 @@
+//! Best-effort storage layout inference from deployed bytecode
+//!
+//! An unverified contract has no compiler layout to read, but its runtime
+//! code still names its storage: a state variable is read with a constant
+//! `SLOAD` key, a mapping entry with a key hashed from memory holding the
+//! mapping key and a constant slot, and a dynamic array element with a key
+//! hashed from the array's slot. [`infer_layout`] walks the code with an
+//! abstract stack, collects those patterns, and proposes a partial layout.
+//!
+//! The result is a guess. Slot numbers are reliable, but names are made up
+//! (`slot_3`, `mapping_5`), value types are unknown beyond the width of a
+//! packed field, and slots only reached through internal function calls or
+//! computed offsets are missed. [`InferredLayout::to_json`] marks the layout
+//! `heuristic` in its provenance so it is never mistaken for a compiler's.
+
+use std::collections::BTreeMap;
+
+use traverse_core::hash::keccak256;
+use traverse_core::{LayoutInfo, StorageEntry, TypeInfo, ZeroSemantics};
+
+use crate::Eip1967Slot;
+
+/// Slots below this are numbered state variables; above it, hashed namespaces
+const NAMED_SLOT_THRESHOLD: u64 = 1 << 32;
+
+/// Type assumed for a whole-word value of unknown type
+const WORD_TYPE: &str = "t_uint256";
+
+/// How a slot is used by the code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SlotKind {
+    /// Read or written directly
+    Value,
+    /// Hashed with its length to find the elements of a dynamic array
+    DynamicArray,
+    /// Hashed after a key to find the entries of a mapping
+    Mapping,
+}
+
+/// A storage slot the code touches, with what was seen of its use
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InferredSlot {
+    pub slot: [u8; 32],
+    pub kind: SlotKind,
+    /// Number of `SLOAD`s reaching the slot or its entries
+    pub reads: usize,
+    /// Number of `SSTORE`s reaching the slot or its entries
+    pub writes: usize,
+    /// Packed fields seen masked out of the word, as `(offset, size)` in bytes
+    pub fields: Vec<(u8, u8)>,
+}
+
+impl InferredSlot {
+    /// The slot as a number, if it is a numbered state variable
+    pub fn index(&self) -> Option<u64> {
+        if self.slot[..24].iter().any(|&byte| byte != 0) {
+            return None;
+        }
+        let index = u64::from_be_bytes(self.slot[24..].try_into().expect("eight bytes"));
+        (index < NAMED_SLOT_THRESHOLD).then_some(index)
+    }
+
+    /// The EIP-1967 query naming a hashed slot, if it is one
+    pub fn eip1967_name(&self) -> Option<&'static str> {
+        Eip1967Slot::ALL.into_iter().find(|slot| slot.slot() == self.slot).map(Eip1967Slot::query)
+    }
+}
+
+/// Where a layout came from and how far to trust it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutProvenance {
+    /// `bytecode` for an inferred layout
+    pub source: String,
+    /// Whether the layout is a guess rather than compiler output
+    pub heuristic: bool,
+    /// Keccak-256 of the analyzed runtime code
+    pub code_hash: [u8; 32],
+}
+
+/// A partial layout proposed from bytecode
+#[derive(Debug, Clone)]
+pub struct InferredLayout {
+    /// Entries for the numbered slots, loadable like a compiled layout
+    pub layout: LayoutInfo,
+    /// Every slot seen, including hashed ones left out of the layout
+    pub slots: Vec<InferredSlot>,
+    pub provenance: LayoutProvenance,
+}
+
+impl InferredLayout {
+    /// The layout as JSON, with `provenance` and `slots` next to the compiled fields
+    ///
+    /// Layout loaders ignore the extra keys, so the output can be fed to
+    /// anything that takes a compiled layout.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut json = serde_json::to_value(&self.layout).expect("layout serializes");
+        json["provenance"] = serde_json::json!({
+            "source": self.provenance.source,
+            "heuristic": self.provenance.heuristic,
+            "code_hash": format!("0x{}", hex::encode(self.provenance.code_hash)),
+        });
+        json["slots"] = self
+            .slots
+            .iter()
+            .map(|slot| {
+                serde_json::json!({
+                    "slot": format!("0x{}", hex::encode(slot.slot)),
+                    "kind": match slot.kind {
+                        SlotKind::Value => "value",
+                        SlotKind::DynamicArray => "dynamic_array",
+                        SlotKind::Mapping => "mapping",
+                    },
+                    "reads": slot.reads,
+                    "writes": slot.writes,
+                    "fields": slot.fields.iter().map(|(offset, size)| serde_json::json!({ "offset": offset, "size": size })).collect::<Vec<_>>(),
+                    "name": slot.eip1967_name(),
+                })
+            })
+            .collect();
+        json
+    }
+}
+
+/// A stack word as far as the analysis can tell
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Word {
+    Const([u8; 32]),
+    /// A key derived by hashing `slot`: a mapping entry or array element, or an offset from one
+    Derived { slot: [u8; 32], kind: SlotKind },
+    /// The word stored at a constant slot, shifted right by `shift` bits
+    Loaded { slot: [u8; 32], shift: u32 },
+    Unknown,
+}
+
+fn small(word: &[u8; 32]) -> Option<u64> {
+    if word[..24].iter().any(|&byte| byte != 0) {
+        return None;
+    }
+    Some(u64::from_be_bytes(word[24..].try_into().expect("eight bytes")))
+}
+
+fn add_words(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    let mut carry = 0u16;
+    for i in (0..32).rev() {
+        let sum = a[i] as u16 + b[i] as u16 + carry;
+        out[i] = sum as u8;
+        carry = sum >> 8;
+    }
+    out
+}
+
+/// Width in bytes of a low-bits mask `2^(8n) - 1`, for `n` below 32
+fn low_mask_bytes(mask: &[u8; 32]) -> Option<u8> {
+    let ones = mask.iter().rev().take_while(|&&byte| byte == 0xff).count();
+    let rest_clear = mask[..32 - ones].iter().all(|&byte| byte == 0);
+    (rest_clear && ones > 0 && ones < 32).then_some(ones as u8)
+}
+
+/// The field a clearing mask keeps out of a word, as `(offset, size)`
+///
+/// Writing a packed field first clears it with `and(word, not(mask))`: all
+/// bytes set except a contiguous run at the field.
+fn cleared_field(mask: &[u8; 32]) -> Option<(u8, u8)> {
+    let first = mask.iter().position(|&byte| byte == 0)?;
+    let size = mask[first..].iter().take_while(|&&byte| byte == 0).count();
+    let rest_set = mask[..first].iter().chain(&mask[first + size..]).all(|&byte| byte == 0xff);
+    (rest_set && size < 32).then_some(((32 - first - size) as u8, size as u8))
+}
+
+/// Stack inputs and outputs of an opcode, or `None` if it ends the block
+fn stack_effect(op: u8) -> Option<(usize, usize)> {
+    Some(match op {
+        0x01..=0x07 | 0x0a | 0x0b | 0x10..=0x14 | 0x16..=0x18 | 0x1a..=0x1d | 0x20 => (2, 1),
+        0x08 | 0x09 => (3, 1),
+        0x15 | 0x19 | 0x31 | 0x35 | 0x3b | 0x3f | 0x40 | 0x49 | 0x51 | 0x54 | 0x5c => (1, 1),
+        0x30 | 0x32..=0x34 | 0x36 | 0x38 | 0x3a | 0x3d | 0x41..=0x48 | 0x4a | 0x58..=0x5a | 0x5f => (0, 1),
+        0x37 | 0x39 | 0x3e | 0x5e => (3, 0),
+        0x3c => (4, 0),
+        0x50 => (1, 0),
+        0x52 | 0x53 | 0x55 | 0x57 | 0x5d => (2, 0),
+        0x5b => (0, 0),
+        0xa0..=0xa4 => (2 + (op - 0xa0) as usize, 0),
+        0xf0 => (3, 1),
+        0xf1 | 0xf2 => (7, 1),
+        0xf4 | 0xfa => (6, 1),
+        0xf5 => (4, 1),
+        _ => return None,
+    })
+}
+
+/// Runtime code without the compiler's trailing CBOR metadata
+fn strip_metadata(code: &[u8]) -> &[u8] {
+    if code.len() < 2 {
+        return code;
+    }
+    let length = u16::from_be_bytes([code[code.len() - 2], code[code.len() - 1]]) as usize;
+    match code.len().checked_sub(length + 2) {
+        // A CBOR map of one to five entries opens the metadata
+        Some(start) if length > 0 && (0xa1..=0xa5).contains(&code[start]) => &code[..start],
+        _ => code,
+    }
+}
+
+#[derive(Default)]
+struct Usage {
+    kind: Option<SlotKind>,
+    reads: usize,
+    writes: usize,
+    fields: Vec<(u8, u8)>,
+}
+
+#[derive(Default)]
+struct Analysis {
+    slots: BTreeMap<[u8; 32], Usage>,
+}
+
+impl Analysis {
+    fn usage(&mut self, slot: [u8; 32], kind: SlotKind) -> &mut Usage {
+        let usage = self.slots.entry(slot).or_default();
+        usage.kind = usage.kind.max(Some(kind));
+        usage
+    }
+
+    /// Record an access through `key`, returning what a load from it yields
+    fn access(&mut self, key: &Word, write: bool) -> Word {
+        let (slot, kind) = match key {
+            Word::Const(slot) => (*slot, SlotKind::Value),
+            Word::Derived { slot, kind } => (*slot, *kind),
+            _ => return Word::Unknown,
+        };
+        let usage = self.usage(slot, kind);
+        if write {
+            usage.writes += 1;
+        } else {
+            usage.reads += 1;
+        }
+        match key {
+            Word::Const(slot) => Word::Loaded { slot: *slot, shift: 0 },
+            _ => Word::Unknown,
+        }
+    }
+
+    fn field(&mut self, slot: [u8; 32], field: (u8, u8)) {
+        let usage = self.usage(slot, SlotKind::Value);
+        if !usage.fields.contains(&field) {
+            usage.fields.push(field);
+        }
+    }
+
+    /// The result of `AND`, recording a packed field if one word masks a loaded one
+    fn and(&mut self, a: Word, b: Word) -> Word {
+        let (slot, shift, mask) = match (a, b) {
+            (Word::Loaded { slot, shift }, Word::Const(mask)) | (Word::Const(mask), Word::Loaded { slot, shift }) => {
+                (slot, shift, mask)
+            }
+            _ => return Word::Unknown,
+        };
+        if shift % 8 != 0 {
+            return Word::Unknown;
+        }
+        let offset = (shift / 8) as u8;
+        if let Some(size) = low_mask_bytes(&mask) {
+            if offset as u32 + size as u32 <= 32 {
+                self.field(slot, (offset, size));
+            }
+        } else if let Some(field) = cleared_field(&mask).filter(|_| offset == 0) {
+            self.field(slot, field);
+        }
+        Word::Unknown
+    }
+
+    /// Run one pass over the code
+    fn run(&mut self, code: &[u8]) {
+        let mut stack: Vec<Word> = Vec::new();
+        let mut memory: BTreeMap<u64, Word> = BTreeMap::new();
+        let mut pc = 0;
+        while pc < code.len() {
+            let op = code[pc];
+            pc += 1;
+
+            if (0x60..=0x7f).contains(&op) {
+                let size = (op - 0x5f) as usize;
+                let mut word = [0u8; 32];
+                let immediate = &code[pc..code.len().min(pc + size)];
+                word[32 - size..32 - size + immediate.len()].copy_from_slice(immediate);
+                stack.push(Word::Const(word));
+                pc += size;
+                continue;
+            }
+            if (0x80..=0x8f).contains(&op) {
+                let depth = (op - 0x7f) as usize;
+                let word = stack.len().checked_sub(depth).map_or(Word::Unknown, |i| stack[i].clone());
+                stack.push(word);
+                continue;
+            }
+            if (0x90..=0x9f).contains(&op) {
+                let depth = (op - 0x8f) as usize;
+                if let Some(i) = stack.len().checked_sub(depth + 1) {
+                    let top = stack.len() - 1;
+                    stack.swap(i, top);
+                }
+                continue;
+            }
+
+            let Some((inputs, outputs)) = stack_effect(op) else {
+                // Terminators and invalid opcodes: whatever follows is reached by a jump
+                stack.clear();
+                memory.clear();
+                continue;
+            };
+            let mut args: Vec<Word> = (0..inputs).map(|_| stack.pop().unwrap_or(Word::Unknown)).collect();
+            let mut arg = |i: usize| std::mem::replace(&mut args[i], Word::Unknown);
+
+            let result = match op {
+                // ADD
+                0x01 => match (arg(0), arg(1)) {
+                    (Word::Const(a), Word::Const(b)) => Word::Const(add_words(&a, &b)),
+                    (derived @ Word::Derived { .. }, _) | (_, derived @ Word::Derived { .. }) => derived,
+                    _ => Word::Unknown,
+                },
+                // DIV by a power of two shifts a loaded word
+                0x04 => match (arg(0), arg(1)) {
+                    (Word::Loaded { slot, shift }, Word::Const(divisor)) => match small(&divisor) {
+                        Some(divisor) if divisor.is_power_of_two() => {
+                            Word::Loaded { slot, shift: shift + divisor.trailing_zeros() }
+                        }
+                        _ => Word::Unknown,
+                    },
+                    _ => Word::Unknown,
+                },
+                0x16 => {
+                    let (a, b) = (arg(0), arg(1));
+                    self.and(a, b)
+                }
+                // SHR
+                0x1c => match (arg(0), arg(1)) {
+                    (Word::Const(bits), Word::Loaded { slot, shift }) => match small(&bits) {
+                        Some(bits) if bits < 256 => Word::Loaded { slot, shift: shift + bits as u32 },
+                        _ => Word::Unknown,
+                    },
+                    _ => Word::Unknown,
+                },
+                // KECCAK256 over a mapping key and slot, or over an array's slot
+                0x20 => match (arg(0), arg(1)) {
+                    (Word::Const(offset), Word::Const(length)) => match (small(&offset), small(&length)) {
+                        (Some(offset), Some(0x40)) => match memory.get(&(offset + 0x20)) {
+                            Some(Word::Const(slot)) => Word::Derived { slot: *slot, kind: SlotKind::Mapping },
+                            // A nested mapping hashes after the outer entry's key
+                            Some(Word::Derived { slot, kind: SlotKind::Mapping }) => {
+                                Word::Derived { slot: *slot, kind: SlotKind::Mapping }
+                            }
+                            _ => Word::Unknown,
+                        },
+                        (Some(offset), Some(0x20)) => match memory.get(&offset) {
+                            Some(Word::Const(slot)) => Word::Derived { slot: *slot, kind: SlotKind::DynamicArray },
+                            _ => Word::Unknown,
+                        },
+                        _ => Word::Unknown,
+                    },
+                    _ => Word::Unknown,
+                },
+                // MSTORE
+                0x52 => {
+                    match (arg(0), arg(1)) {
+                        (Word::Const(offset), value) => match small(&offset) {
+                            Some(offset) => {
+                                memory.insert(offset, value);
+                            }
+                            None => memory.clear(),
+                        },
+                        _ => memory.clear(),
+                    }
+                    Word::Unknown
+                }
+                // SLOAD
+                0x54 => {
+                    let key = arg(0);
+                    self.access(&key, false)
+                }
+                // SSTORE
+                0x55 => {
+                    let key = arg(0);
+                    self.access(&key, true);
+                    Word::Unknown
+                }
+                // Writes to memory the analysis does not follow
+                0x37 | 0x39 | 0x3c | 0x3e | 0x53 | 0x5e => {
+                    memory.clear();
+                    Word::Unknown
+                }
+                // JUMPDEST: entered from unknown jumps
+                0x5b => {
+                    stack.clear();
+                    memory.clear();
+                    Word::Unknown
+                }
+                // JUMPI falls through with the rest of the stack
+                _ => Word::Unknown,
+            };
+            if outputs == 1 {
+                stack.push(result);
+            }
+        }
+    }
+}
+
+/// Type label for a packed field of `size` bytes
+fn field_type(size: u8) -> String {
+    format!("t_uint{}", size as u16 * 8)
+}
+
+fn inplace(label: &str, bytes: u8) -> TypeInfo {
+    TypeInfo {
+        label: label.to_string(),
+        number_of_bytes: bytes.to_string(),
+        encoding: "inplace".to_string(),
+        base: None,
+        key: None,
+        value: None,
+    }
+}
+
+/// Propose a layout for runtime `code`, as read with `eth_getCode`
+pub fn infer_layout(contract_name: &str, code: &[u8]) -> InferredLayout {
+    let mut analysis = Analysis::default();
+    analysis.run(strip_metadata(code));
+
+    let slots: Vec<InferredSlot> = analysis
+        .slots
+        .into_iter()
+        .map(|(slot, mut usage)| {
+            usage.fields.sort_unstable();
+            InferredSlot {
+                slot,
+                kind: usage.kind.unwrap_or(SlotKind::Value),
+                reads: usage.reads,
+                writes: usage.writes,
+                fields: usage.fields,
+            }
+        })
+        .collect();
+
+    let mut storage = Vec::new();
+    let mut types: BTreeMap<String, TypeInfo> = BTreeMap::new();
+    for slot in &slots {
+        let Some(index) = slot.index() else { continue };
+        let entries = match slot.kind {
+            SlotKind::Mapping => {
+                types.insert("t_bytes32".to_string(), inplace("t_bytes32", 32));
+                types.insert(WORD_TYPE.to_string(), inplace(WORD_TYPE, 32));
+                let label = format!("t_mapping(t_bytes32,{})", WORD_TYPE);
+                let mapping = TypeInfo {
+                    encoding: "mapping".to_string(),
+                    key: Some("t_bytes32".to_string()),
+                    value: Some(WORD_TYPE.to_string()),
+                    ..inplace(&label, 32)
+                };
+                vec![(format!("mapping_{}", index), 0, mapping)]
+            }
+            SlotKind::DynamicArray => {
+                types.insert(WORD_TYPE.to_string(), inplace(WORD_TYPE, 32));
+                let label = format!("t_array({})dyn_storage", WORD_TYPE);
+                let array = TypeInfo {
+                    encoding: "dynamic_array".to_string(),
+                    base: Some(WORD_TYPE.to_string()),
+                    ..inplace(&label, 32)
+                };
+                vec![(format!("array_{}", index), 0, array)]
+            }
+            SlotKind::Value if slot.fields.is_empty() => vec![(format!("slot_{}", index), 0, inplace(WORD_TYPE, 32))],
+            SlotKind::Value => slot
+                .fields
+                .iter()
+                .map(|&(offset, size)| (format!("slot_{}_{}", index, offset), offset, inplace(&field_type(size), size)))
+                .collect(),
+        };
+        for (label, offset, type_info) in entries {
+            storage.push(StorageEntry {
+                label,
+                slot: index.to_string(),
+                offset,
+                type_name: type_info.label.clone(),
+                // Nothing in the code says whether zero is a valid value
+                zero_semantics: ZeroSemantics::ValidZero,
+            });
+            types.insert(type_info.label.clone(), type_info);
+        }
+    }
+
+    InferredLayout {
+        layout: LayoutInfo {
+            contract_name: contract_name.to_string(),
+            storage,
+            types: types.into_values().collect(),
+            code_variables: Vec::new(),
+        },
+        slots,
+        provenance: LayoutProvenance {
+            source: "bytecode".to_string(),
+            heuristic: true,
+            code_hash: keccak256(code),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EthereumKeyResolver;
+    use traverse_core::KeyResolver;
+
+    fn word(value: u64) -> [u8; 32] {
+        let mut word = [0u8; 32];
+        word[24..].copy_from_slice(&value.to_be_bytes());
+        word
+    }
+
+    #[test]
+    fn test_infer_layout_from_bytecode() {
+        let mut code = Vec::new();
+        // slot 5: a whole word
+        code.extend([0x60, 0x05, 0x54, 0x50]);
+        // slot 2: an address at offset 0 and a uint8 at offset 20
+        code.extend([0x60, 0x02, 0x54, 0x73]);
+        code.extend([0xff; 20]);
+        code.extend([0x16, 0x50]);
+        code.extend([0x60, 0x02, 0x54, 0x60, 0xa0, 0x1c, 0x60, 0xff, 0x16, 0x50]);
+        // the uint8 written back: and(sload(2), not(0xff << 160))
+        let mut clear = [0xff; 32];
+        clear[11] = 0;
+        code.extend([0x60, 0x07, 0x60, 0x02, 0x54, 0x7f]);
+        code.extend(clear);
+        code.extend([0x16, 0x17, 0x60, 0x02, 0x55]);
+        // mapping at slot 1: sload(keccak256(caller . 1))
+        code.extend([0x33, 0x60, 0x00, 0x52, 0x60, 0x01, 0x60, 0x20, 0x52, 0x60, 0x40, 0x60, 0x00, 0x20, 0x54, 0x50]);
+        // nested mapping at slot 3, written
+        code.extend([0x60, 0x01]);
+        code.extend([0x33, 0x60, 0x00, 0x52, 0x60, 0x03, 0x60, 0x20, 0x52, 0x60, 0x40, 0x60, 0x00, 0x20]);
+        code.extend([0x60, 0x20, 0x52, 0x34, 0x60, 0x00, 0x52, 0x60, 0x40, 0x60, 0x00, 0x20, 0x55]);
+        // dynamic array at slot 4: element 3
+        code.extend([0x60, 0x04, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0x20, 0x60, 0x03, 0x01, 0x54, 0x50]);
+        // the EIP-1967 implementation slot
+        code.push(0x7f);
+        code.extend(Eip1967Slot::Implementation.slot());
+        code.extend([0x54, 0x50, 0x00]);
+        // metadata whose bytes would read slot 9 if analyzed
+        code.extend([0xa1, 0x60, 0x09, 0x54, 0x00, 0x04]);
+
+        let inferred = infer_layout("Unverified", &code);
+        let kinds: Vec<_> = inferred.slots.iter().map(|slot| (slot.index(), slot.kind)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (Some(1), SlotKind::Mapping),
+                (Some(2), SlotKind::Value),
+                (Some(3), SlotKind::Mapping),
+                (Some(4), SlotKind::DynamicArray),
+                (Some(5), SlotKind::Value),
+                (None, SlotKind::Value),
+            ]
+        );
+        assert_eq!(inferred.slots[1].fields, vec![(0, 20), (20, 1)]);
+        assert_eq!((inferred.slots[1].reads, inferred.slots[1].writes), (3, 1));
+        assert_eq!(inferred.slots[2].writes, 1);
+        assert_eq!(inferred.slots[5].eip1967_name(), Some("eip1967.implementation"));
+        assert_eq!(inferred.slots[5].slot, Eip1967Slot::Implementation.slot());
+        assert!(inferred.slots.iter().all(|slot| slot.slot != word(9)));
+
+        let layout = &inferred.layout;
+        layout.validate().unwrap();
+        let labels: Vec<_> = layout.storage.iter().map(|entry| entry.label.as_str()).collect();
+        assert_eq!(labels, ["mapping_1", "slot_2_0", "slot_2_20", "mapping_3", "array_4", "slot_5"]);
+        let key = EthereumKeyResolver.resolve(layout, &format!("mapping_1[0x{}]", "11".repeat(32))).unwrap();
+        let mut preimage = [0x11u8; 32].to_vec();
+        preimage.extend(word(1));
+        assert_eq!(key.key, traverse_core::Key::Fixed(keccak256(&preimage)));
+        assert_eq!(EthereumKeyResolver.resolve(layout, "slot_2_20").unwrap().offset, Some(20));
+
+        let json = inferred.to_json();
+        assert_eq!(json["provenance"]["source"], "bytecode");
+        assert_eq!(json["provenance"]["heuristic"], true);
+        assert_eq!(json["slots"][5]["name"], "eip1967.implementation");
+        let reloaded: LayoutInfo = serde_json::from_value(json).unwrap();
+        assert_eq!(reloaded.storage.len(), 6);
+    }
+}
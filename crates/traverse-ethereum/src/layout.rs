@@ -7,17 +7,27 @@
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::path::Path;
-use traverse_core::{LayoutCompiler, LayoutInfo, StorageEntry, TraverseError, TypeInfo};
+use traverse_core::{
+    CodeVariable, CodeVariableKind, LayoutCompiler, LayoutInfo, StorageEntry, TraverseError, TypeInfo,
+};
 
 /// Forge storage layout output structure
 #[derive(Debug, Deserialize)]
 struct ForgeStorageLayout {
     /// Storage entries from forge inspect
     storage: Vec<ForgeStorageEntry>,
-    /// Type definitions from forge
+    /// Type definitions from forge, `null` when there is no storage
+    #[serde(default, deserialize_with = "null_as_empty")]
     types: HashMap<String, ForgeTypeInfo>,
 }
 
+fn null_as_empty<'de, D>(deserializer: D) -> Result<HashMap<String, ForgeTypeInfo>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(Option::deserialize(deserializer)?.unwrap_or_default())
+}
+
 /// Storage entry from forge inspect output
 #[derive(Debug, Deserialize)]
 struct ForgeStorageEntry {
@@ -67,6 +77,16 @@ struct ForgeStructMember {
     offset: u32,
 }
 
+/// A contract's layout and ABI from a Hardhat build-info file
+#[derive(Debug, Clone)]
+pub struct BuildInfoContract {
+    /// Fully qualified name, `contracts/Token.sol:Token`
+    pub qualified_name: String,
+    pub layout: LayoutInfo,
+    /// The contract's ABI as solc emitted it
+    pub abi: serde_json::Value,
+}
+
 /// Ethereum layout compiler that uses forge inspect output
 ///
 /// This implementation reads Solidity storage layout information
@@ -493,10 +513,298 @@ impl EthereumLayoutCompiler {
             contract_name,
             storage,
             types,
+            code_variables: Vec::new(),
+        };
+
+        Ok(layout)
+    }
+
+    /// Convert a solc storage layout into the canonical format
+    fn compile_forge_layout(
+        forge_layout: &ForgeStorageLayout,
+        contract_name: String,
+    ) -> Result<LayoutInfo, TraverseError> {
+        // Convert forge format to canonical format
+        let mut storage = Vec::new();
+        let mut types = Vec::new();
+
+        // Convert storage entries
+        for forge_entry in &forge_layout.storage {
+            storage.push(StorageEntry {
+                label: forge_entry.label.clone(),
+                slot: forge_entry.slot.clone(),
+                offset: if forge_entry.offset <= 255 {
+                    forge_entry.offset as u8
+                } else {
+                    return Err(TraverseError::InvalidInput(format!(
+                        "Offset too large for u8: {}",
+                        forge_entry.offset
+                    )));
+                },
+                type_name: forge_entry.type_ref.clone(),
+                zero_semantics: traverse_core::ZeroSemantics::NeverWritten,
+            });
+
+            // Generate additional entries for complex types
+            if let Some(forge_type) = forge_layout.types.get(&forge_entry.type_ref) {
+                match forge_type.encoding.as_str() {
+                    "bytes" => {
+                        // Dynamic array/string - add length and data access
+                        let slot = forge_entry.slot.parse::<u64>().map_err(|e| {
+                            TraverseError::InvalidInput(format!("Invalid slot: {}", e))
+                        })?;
+                        let dynamic_entries =
+                            Self::generate_dynamic_array_entries(&forge_entry.label, slot);
+                        storage.extend(dynamic_entries);
+                    }
+                    "inplace" => {
+                        // Check if this is a struct with members
+                        if let Some(members) = &forge_type.members {
+                            let slot = forge_entry.slot.parse::<u64>().map_err(|e| {
+                                TraverseError::InvalidInput(format!("Invalid slot: {}", e))
+                            })?;
+                            let struct_entries = Self::generate_struct_storage_entries(
+                                &forge_entry.label,
+                                slot,
+                                members,
+                                &forge_layout.types,
+                            )?;
+                            storage.extend(struct_entries);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        // Convert type information
+        for (type_ref, forge_type) in &forge_layout.types {
+            types.push(Self::convert_type_info(type_ref, forge_type));
+        }
+
+        // Add standard types that might be missing
+        let standard_types = Self::generate_standard_types();
+        for std_type in standard_types {
+            if !types.iter().any(|t| t.label == std_type.label) {
+                types.push(std_type);
+            }
+        }
+
+        // Validate storage layout for conflicts
+        Self::validate_storage_layout(&storage, &types)?;
+
+        let layout = LayoutInfo {
+            contract_name,
+            storage,
+            types,
+            code_variables: Vec::new(),
         };
 
         Ok(layout)
     }
+
+    /// Compile the storage layout a Foundry artifact or solc output carries
+    ///
+    /// Accepts a Foundry artifact (`out/Token.sol/Token.json`, with
+    /// `extra_output = ["storageLayout"]`), solc standard JSON output
+    /// (`contracts.<file>.<name>.storageLayout`), a Hardhat build-info file,
+    /// which wraps that output under `output`, and `solc --combined-json
+    /// storage-layout` output (`contracts["<file>:<name>"]["storage-layout"]`).
+    /// These give the compiler's exact slots, offsets and types, unlike
+    /// layouts inferred from an ABI.
+    ///
+    /// When the output holds several contracts, `contract` picks one by name
+    /// or fully qualified name (`contracts/Token.sol:Token`); without it the
+    /// output must hold exactly one.
+    pub fn compile_solc_output(
+        &self,
+        output: &serde_json::Value,
+        contract: Option<&str>,
+    ) -> Result<LayoutInfo, TraverseError> {
+        if let Some(storage_layout) = output.get("storageLayout") {
+            let name = contract.unwrap_or("UnknownContract").to_string();
+            let mut layout = Self::compile_forge_layout(&Self::parse_embedded_json(storage_layout)?, name)?;
+            // A Foundry artifact carries the AST of its own source file
+            if let Some(ast) = output.get("ast") {
+                layout.code_variables = Self::code_variables_of(&[ast], &layout.contract_name);
+            }
+            return Ok(layout);
+        }
+
+        let (name, artifact) = Self::select_contract(output, contract)?;
+        let layout = Self::storage_layout_of(artifact).ok_or_else(|| {
+            TraverseError::LayoutCompilation(format!("No storage layout for {}; compile with --storage-layout", name))
+        })?;
+        let mut layout = Self::compile_forge_layout(&Self::parse_embedded_json(layout)?, name)?;
+        let file = contract.and_then(|contract| contract.rsplit_once(':')).map(|(file, _)| file);
+        layout.code_variables = Self::code_variables_of(&Self::source_asts(output, file), &layout.contract_name);
+        Ok(layout)
+    }
+
+    /// Compile a contract's layout and read its ABI from a Hardhat build-info file
+    ///
+    /// `contract` is the fully qualified name, `contracts/Token.sol:Token`,
+    /// or a bare name when only one contract has it. The build must select
+    /// `storageLayout` output, e.g. through `outputSelection` in
+    /// `hardhat.config`.
+    ///
+    /// ```rust,ignore
+    /// let build_info = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    /// let token = EthereumLayoutCompiler.compile_build_info(&build_info, "contracts/Token.sol:Token")?;
+    /// ```
+    pub fn compile_build_info(
+        &self,
+        build_info: &serde_json::Value,
+        contract: &str,
+    ) -> Result<BuildInfoContract, TraverseError> {
+        let (name, artifact) = Self::select_contract(build_info, Some(contract))?;
+        let layout = Self::storage_layout_of(artifact).ok_or_else(|| {
+            TraverseError::LayoutCompilation(format!(
+                "No storage layout for {}; add storageLayout to the outputSelection",
+                contract
+            ))
+        })?;
+        let abi = match artifact.get("abi") {
+            Some(abi) => Self::parse_embedded_json(abi)?,
+            None => serde_json::Value::Array(Vec::new()),
+        };
+        let mut layout = Self::compile_forge_layout(&Self::parse_embedded_json(layout)?, name)?;
+        let file = contract.rsplit_once(':').map(|(file, _)| file);
+        layout.code_variables = Self::code_variables_of(&Self::source_asts(build_info, file), &layout.contract_name);
+        Ok(BuildInfoContract { qualified_name: contract.to_string(), layout, abi })
+    }
+
+    /// Find one contract in solc output or a build-info file
+    ///
+    /// Returns the contract's name and its entry, which holds `abi` and the
+    /// storage layout. Without `contract`, only contracts with a storage
+    /// layout are considered and there must be one.
+    fn select_contract<'a>(
+        output: &'a serde_json::Value,
+        contract: Option<&str>,
+    ) -> Result<(String, &'a serde_json::Value), TraverseError> {
+        // Hardhat build-info wraps the solc output
+        let output = output.get("output").filter(|o| o.get("contracts").is_some()).unwrap_or(output);
+        let contracts = output
+            .get("contracts")
+            .and_then(|c| c.as_object())
+            .ok_or_else(|| TraverseError::LayoutCompilation("No storageLayout or contracts in solc output".to_string()))?;
+
+        // (file, name, entry) for every contract, from either output shape
+        let mut candidates = Vec::new();
+        for (key, value) in contracts {
+            if let Some((file, name)) = key.rsplit_once(':') {
+                candidates.push((file, name, value));
+            } else if let Some(file) = value.as_object() {
+                for (name, artifact) in file {
+                    candidates.push((key.as_str(), name.as_str(), artifact));
+                }
+            }
+        }
+
+        let matching: Vec<_> = match contract {
+            Some(contract) => candidates
+                .iter()
+                .filter(|(file, name, _)| *name == contract || format!("{}:{}", file, name) == contract)
+                .collect(),
+            None => candidates.iter().filter(|(_, _, entry)| Self::storage_layout_of(entry).is_some()).collect(),
+        };
+        match matching.as_slice() {
+            [(_, name, entry)] => Ok((name.to_string(), entry)),
+            [] => Err(TraverseError::LayoutCompilation(match contract {
+                Some(contract) => format!("Contract {} not found in solc output", contract),
+                None => "solc output has no storage layouts; compile with --storage-layout".to_string(),
+            })),
+            several => Err(TraverseError::LayoutCompilation(format!(
+                "solc output has layouts for several contracts ({}); choose one",
+                several.iter().map(|(file, name, _)| format!("{}:{}", file, name)).collect::<Vec<_>>().join(", ")
+            ))),
+        }
+    }
+
+    /// The source unit ASTs of solc output or a build-info file
+    ///
+    /// `file` puts that source's AST first, so a contract named in several
+    /// files is found in the one asked for.
+    fn source_asts<'a>(output: &'a serde_json::Value, file: Option<&str>) -> Vec<&'a serde_json::Value> {
+        let output = output.get("output").filter(|o| o.get("contracts").is_some()).unwrap_or(output);
+        let Some(sources) = output.get("sources").and_then(|s| s.as_object()) else {
+            return Vec::new();
+        };
+        let mut asts: Vec<_> = sources
+            .iter()
+            .filter_map(|(path, source)| Some((path, source.get("ast").or_else(|| source.get("AST"))?)))
+            .collect();
+        asts.sort_by_key(|(path, _)| Some(path.as_str()) != file);
+        asts.into_iter().map(|(_, ast)| ast).collect()
+    }
+
+    /// The `constant` and `immutable` state variables of a contract and its bases
+    ///
+    /// Read from solc's AST, as the storage layout leaves them out. Bases
+    /// declared in sources missing from `asts` are not seen.
+    fn code_variables_of(asts: &[&serde_json::Value], contract_name: &str) -> Vec<CodeVariable> {
+        let definitions: Vec<&serde_json::Value> = asts
+            .iter()
+            .filter_map(|ast| ast.get("nodes").and_then(|n| n.as_array()))
+            .flatten()
+            .filter(|node| node.get("nodeType").and_then(|t| t.as_str()) == Some("ContractDefinition"))
+            .collect();
+        let Some(contract) = definitions.iter().find(|d| d.get("name").and_then(|n| n.as_str()) == Some(contract_name))
+        else {
+            return Vec::new();
+        };
+
+        // Most base-like first, the order variables are declared in
+        let lineage: Vec<&serde_json::Value> = match contract.get("linearizedBaseContracts").and_then(|b| b.as_array()) {
+            Some(bases) => bases
+                .iter()
+                .rev()
+                .filter_map(|id| definitions.iter().find(|d| d.get("id") == Some(id)).copied())
+                .collect(),
+            None => vec![*contract],
+        };
+
+        let mut variables = Vec::new();
+        for definition in lineage {
+            let nodes = definition.get("nodes").and_then(|n| n.as_array()).into_iter().flatten();
+            for node in nodes {
+                if node.get("nodeType").and_then(|t| t.as_str()) != Some("VariableDeclaration")
+                    || node.get("stateVariable").and_then(|s| s.as_bool()) != Some(true)
+                {
+                    continue;
+                }
+                // Before 0.6.5 only `constant` existed, as a flag
+                let kind = match node.get("mutability").and_then(|m| m.as_str()) {
+                    Some("constant") => CodeVariableKind::Constant,
+                    Some("immutable") => CodeVariableKind::Immutable,
+                    None if node.get("constant").and_then(|c| c.as_bool()) == Some(true) => CodeVariableKind::Constant,
+                    _ => continue,
+                };
+                let (Some(label), Some(type_name)) = (
+                    node.get("name").and_then(|n| n.as_str()),
+                    node["typeDescriptions"]["typeIdentifier"].as_str(),
+                ) else {
+                    continue;
+                };
+                variables.push(CodeVariable { label: label.to_string(), type_name: type_name.to_string(), kind });
+            }
+        }
+        variables
+    }
+
+    /// A contract entry's storage layout, under either output's key
+    fn storage_layout_of(entry: &serde_json::Value) -> Option<&serde_json::Value> {
+        entry.get("storageLayout").or_else(|| entry.get("storage-layout"))
+    }
+
+    /// A value older solc emits as a JSON string, such as `storageLayout`
+    fn parse_embedded_json<T: serde::de::DeserializeOwned>(value: &serde_json::Value) -> Result<T, TraverseError> {
+        match value {
+            serde_json::Value::String(text) => Ok(serde_json::from_str(text)?),
+            value => Ok(serde_json::from_value(value.clone())?),
+        }
+    }
 }
 
 impl LayoutCompiler for EthereumLayoutCompiler {
@@ -505,7 +813,10 @@ impl LayoutCompiler for EthereumLayoutCompiler {
     /// Enhanced to support complex types and proper validation
     ///
     /// Expects a JSON file containing the output of:
-    /// `forge inspect <Contract> storageLayout`
+    /// `forge inspect <Contract> storageLayout`, or a Foundry artifact or
+    /// solc output or Hardhat build-info with storage layouts (see
+    /// [`compile_solc_output`](Self::compile_solc_output)), whose contract
+    /// is the one named by the file stem, if any, or else the only one.
     ///
     /// # Arguments
     ///
@@ -524,91 +835,33 @@ impl LayoutCompiler for EthereumLayoutCompiler {
     fn compile_layout(&self, abi_path: &Path) -> Result<LayoutInfo, TraverseError> {
         let content = std::fs::read_to_string(abi_path)?;
 
+        let contract_name = abi_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("UnknownContract")
+            .to_string();
+
         // Try to parse as forge storage layout first
         if let Ok(forge_layout) = serde_json::from_str::<ForgeStorageLayout>(&content) {
-            // Convert forge format to canonical format
-            let mut storage = Vec::new();
-            let mut types = Vec::new();
-
-            // Convert storage entries
-            for forge_entry in &forge_layout.storage {
-                storage.push(StorageEntry {
-                    label: forge_entry.label.clone(),
-                    slot: forge_entry.slot.clone(),
-                    offset: if forge_entry.offset <= 255 {
-                        forge_entry.offset as u8
-                    } else {
-                        return Err(TraverseError::InvalidInput(format!(
-                            "Offset too large for u8: {}",
-                            forge_entry.offset
-                        )));
-                    },
-                    type_name: forge_entry.type_ref.clone(),
-                    zero_semantics: traverse_core::ZeroSemantics::NeverWritten,
-                });
-
-                // Generate additional entries for complex types
-                if let Some(forge_type) = forge_layout.types.get(&forge_entry.type_ref) {
-                    match forge_type.encoding.as_str() {
-                        "bytes" => {
-                            // Dynamic array/string - add length and data access
-                            let slot = forge_entry.slot.parse::<u64>().map_err(|e| {
-                                TraverseError::InvalidInput(format!("Invalid slot: {}", e))
-                            })?;
-                            let dynamic_entries =
-                                Self::generate_dynamic_array_entries(&forge_entry.label, slot);
-                            storage.extend(dynamic_entries);
-                        }
-                        "inplace" => {
-                            // Check if this is a struct with members
-                            if let Some(members) = &forge_type.members {
-                                let slot = forge_entry.slot.parse::<u64>().map_err(|e| {
-                                    TraverseError::InvalidInput(format!("Invalid slot: {}", e))
-                                })?;
-                                let struct_entries = Self::generate_struct_storage_entries(
-                                    &forge_entry.label,
-                                    slot,
-                                    members,
-                                    &forge_layout.types,
-                                )?;
-                                storage.extend(struct_entries);
-                            }
-                        }
-                        _ => {}
-                    }
-                }
-            }
+            return Self::compile_forge_layout(&forge_layout, contract_name);
+        }
 
-            // Convert type information
-            for (type_ref, forge_type) in &forge_layout.types {
-                types.push(Self::convert_type_info(type_ref, forge_type));
+        // Then as a Foundry artifact, solc output or Hardhat build-info
+        // carrying a storage layout
+        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
+            let solc_output = json.get("output").unwrap_or(&json);
+            if json.get("storageLayout").is_some() || solc_output.get("contracts").is_some() {
+                // Build-info files are named by hash, so the stem is only a hint
+                let named = json.get("storageLayout").is_some()
+                    || Self::select_contract(&json, Some(&contract_name)).is_ok();
+                return self.compile_solc_output(&json, named.then_some(contract_name.as_str()));
             }
-
-            // Add standard types that might be missing
-            let standard_types = Self::generate_standard_types();
-            for std_type in standard_types {
-                if !types.iter().any(|t| t.label == std_type.label) {
-                    types.push(std_type);
-                }
+            if json.get("abi").is_some() && json.get("bytecode").is_some() {
+                return Err(TraverseError::LayoutCompilation(format!(
+                    "{} is a Foundry artifact without a storage layout; build with extra_output = [\"storageLayout\"]",
+                    abi_path.display()
+                )));
             }
-
-            // Validate storage layout for conflicts
-            Self::validate_storage_layout(&storage, &types)?;
-
-            // Extract contract name from file path
-            let contract_name = abi_path
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("UnknownContract")
-                .to_string();
-
-            let layout = LayoutInfo {
-                contract_name,
-                storage,
-                types,
-            };
-
-            return Ok(layout);
         }
 
         // Try to parse as standard Ethereum ABI array
@@ -992,4 +1245,145 @@ mod tests {
         let result = EthereumLayoutCompiler::validate_storage_layout(&storage, &types);
         assert!(result.is_ok());
     }
+
+    const TOKEN_STORAGE_LAYOUT: &str = r#"{
+        "storage": [
+            {"astId": 3, "contract": "src/Token.sol:Token", "label": "owner", "offset": 0, "slot": "0", "type": "t_address"},
+            {"astId": 5, "contract": "src/Token.sol:Token", "label": "paused", "offset": 20, "slot": "0", "type": "t_bool"},
+            {"astId": 9, "contract": "src/Token.sol:Token", "label": "balances", "offset": 0, "slot": "1", "type": "t_mapping(t_address,t_uint256)"}
+        ],
+        "types": {
+            "t_address": {"encoding": "inplace", "label": "address", "numberOfBytes": "20"},
+            "t_bool": {"encoding": "inplace", "label": "bool", "numberOfBytes": "1"},
+            "t_mapping(t_address,t_uint256)": {"encoding": "mapping", "key": "t_address", "label": "mapping(address => uint256)", "numberOfBytes": "32", "value": "t_uint256"},
+            "t_uint256": {"encoding": "inplace", "label": "uint256", "numberOfBytes": "32"}
+        }
+    }"#;
+
+    fn token_layout() -> serde_json::Value {
+        serde_json::from_str(TOKEN_STORAGE_LAYOUT).unwrap()
+    }
+
+    #[test]
+    fn test_compile_foundry_artifact() {
+        let artifact = serde_json::json!({
+            "abi": [],
+            "bytecode": {"object": "0x"},
+            "storageLayout": token_layout(),
+            "ast": {"nodeType": "SourceUnit", "nodes": [
+                {"nodeType": "ContractDefinition", "id": 1, "name": "Token", "nodes": [
+                    variable("MAX_SUPPLY", "constant", "t_uint256")
+                ]}
+            ]},
+        });
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Token.json");
+        std::fs::write(&path, artifact.to_string()).unwrap();
+
+        let layout = EthereumLayoutCompiler.compile_layout(&path).unwrap();
+        assert_eq!(layout.contract_name, "Token");
+        let paused = layout.storage.iter().find(|s| s.label == "paused").unwrap();
+        assert_eq!((paused.slot.as_str(), paused.offset), ("0", 20));
+        assert!(layout.is_packed(paused));
+        assert_eq!(layout.code_variable("MAX_SUPPLY").unwrap().kind, CodeVariableKind::Constant);
+
+        // Without extra_output the artifact carries no layout
+        std::fs::write(&path, serde_json::json!({"abi": [], "bytecode": {"object": "0x"}}).to_string()).unwrap();
+        let err = EthereumLayoutCompiler.compile_layout(&path).unwrap_err();
+        assert!(err.to_string().contains("storageLayout"), "{}", err);
+    }
+
+    #[test]
+    fn test_compile_solc_outputs() {
+        let empty = serde_json::json!({"storage": [], "types": null});
+        let standard = serde_json::json!({
+            "contracts": {
+                "src/Token.sol": {
+                    "Token": {"storageLayout": token_layout()},
+                    "Empty": {"storageLayout": empty}
+                }
+            }
+        });
+        let layout = EthereumLayoutCompiler.compile_solc_output(&standard, Some("Token")).unwrap();
+        assert_eq!(layout.contract_name, "Token");
+        assert_eq!(layout.storage.len(), 3);
+        let empty = EthereumLayoutCompiler.compile_solc_output(&standard, Some("Empty")).unwrap();
+        assert!(empty.storage.is_empty());
+        let err = EthereumLayoutCompiler.compile_solc_output(&standard, None).unwrap_err();
+        assert!(err.to_string().contains("src/Token.sol:Empty, src/Token.sol:Token"), "{}", err);
+
+        // Older solc emits the combined-json layout as a string
+        let combined = serde_json::json!({
+            "contracts": {"src/Token.sol:Token": {"storage-layout": TOKEN_STORAGE_LAYOUT}},
+            "version": "0.8.24"
+        });
+        let layout = EthereumLayoutCompiler.compile_solc_output(&combined, None).unwrap();
+        assert_eq!(layout.contract_name, "Token");
+        let balances = layout.storage.iter().find(|s| s.label == "balances").unwrap();
+        assert_eq!(balances.type_name, "t_mapping(t_address,t_uint256)");
+    }
+
+    fn variable(name: &str, mutability: &str, type_identifier: &str) -> serde_json::Value {
+        serde_json::json!({
+            "nodeType": "VariableDeclaration",
+            "name": name,
+            "stateVariable": true,
+            "mutability": mutability,
+            "constant": mutability == "constant",
+            "typeDescriptions": {"typeIdentifier": type_identifier}
+        })
+    }
+
+    #[test]
+    fn test_compile_hardhat_build_info() {
+        let abi = serde_json::json!([{"type": "function", "name": "owner", "inputs": [], "outputs": [{"type": "address"}]}]);
+        let build_info = serde_json::json!({
+            "_format": "hh-sol-build-info-1",
+            "solcVersion": "0.8.24",
+            "input": {"language": "Solidity", "sources": {}},
+            "output": {
+                "contracts": {
+                    "contracts/Token.sol": {"Token": {"abi": abi, "storageLayout": token_layout()}},
+                    "contracts/legacy/Token.sol": {"Token": {"abi": [], "storageLayout": {"storage": [], "types": null}}},
+                    "contracts/IToken.sol": {"IToken": {"abi": []}}
+                },
+                "sources": {
+                    "contracts/Base.sol": {"id": 0, "ast": {"nodeType": "SourceUnit", "nodes": [
+                        {"nodeType": "ContractDefinition", "id": 10, "name": "Base", "linearizedBaseContracts": [10], "nodes": [
+                            variable("VERSION", "constant", "t_string_memory_ptr"),
+                            variable("owner", "mutable", "t_address")
+                        ]}
+                    ]}},
+                    "contracts/Token.sol": {"id": 1, "ast": {"nodeType": "SourceUnit", "nodes": [
+                        {"nodeType": "ContractDefinition", "id": 20, "name": "Token", "linearizedBaseContracts": [20, 10], "nodes": [
+                            variable("decimals", "immutable", "t_uint8")
+                        ]}
+                    ]}},
+                    "contracts/legacy/Token.sol": {"id": 2, "ast": {"nodeType": "SourceUnit", "nodes": [
+                        {"nodeType": "ContractDefinition", "id": 30, "name": "Token", "linearizedBaseContracts": [30], "nodes": []}
+                    ]}}
+                }
+            }
+        });
+
+        let token = EthereumLayoutCompiler.compile_build_info(&build_info, "contracts/Token.sol:Token").unwrap();
+        assert_eq!(token.qualified_name, "contracts/Token.sol:Token");
+        assert_eq!(token.layout.contract_name, "Token");
+        assert_eq!(token.layout.storage.len(), 3);
+        assert_eq!(token.abi, abi);
+        let labels: Vec<_> = token.layout.code_variables.iter().map(|v| (v.label.as_str(), v.kind)).collect();
+        assert_eq!(labels, [("VERSION", CodeVariableKind::Constant), ("decimals", CodeVariableKind::Immutable)]);
+        assert_eq!(token.layout.code_variable("decimals").unwrap().type_name, "t_uint8");
+
+        // The bare name is ambiguous; interfaces have no layout
+        assert!(EthereumLayoutCompiler.compile_build_info(&build_info, "Token").is_err());
+        let err = EthereumLayoutCompiler.compile_build_info(&build_info, "IToken").unwrap_err();
+        assert!(err.to_string().contains("outputSelection"), "{}", err);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Token.json");
+        std::fs::write(&path, build_info.to_string()).unwrap();
+        let err = EthereumLayoutCompiler.compile_layout(&path).unwrap_err();
+        assert!(err.to_string().contains("several contracts"), "{}", err);
+    }
 }
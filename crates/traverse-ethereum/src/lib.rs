@@ -15,22 +15,102 @@
 //! - `alloy-rpc-types-eth`: Essential RPC types for storage proofs
 //! - `alloy-provider`: Basic provider functionality
 //! - `alloy-transport-http`: HTTP transport layer
+//!
+//! Without `std`, only layout compilation and key resolution are built, for
+//! targets such as `wasm32-unknown-unknown` where the network clients do not.
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
 
+#[cfg(feature = "std")]
 mod abi_fetcher;
+#[cfg(all(feature = "std", feature = "ethereum"))]
+mod anchor;
+#[cfg(all(feature = "std", feature = "ethereum"))]
+pub mod arbitrum;
+#[cfg(all(feature = "std", feature = "ethereum"))]
+pub mod beacon;
+#[cfg(feature = "std")]
+mod cache;
+#[cfg(feature = "ethereum")]
+pub mod chainlink;
+pub mod chains;
 mod indexer;
+#[cfg(feature = "ethereum")]
+mod header;
+#[cfg(feature = "ethereum")]
+pub mod inference;
 mod layout;
+#[cfg(feature = "ethereum")]
+pub mod mpt;
+#[cfg(all(feature = "std", feature = "ethereum"))]
+pub mod optimism;
+pub mod presets;
+#[cfg(feature = "std")]
 mod proof;
+mod proxy;
+#[cfg(feature = "ethereum")]
+mod receipts;
 mod resolver;
+#[cfg(all(feature = "std", feature = "ethereum"))]
+mod subscription;
+#[cfg(feature = "ethereum")]
+mod transactions;
+#[cfg(feature = "std")]
+mod transport;
+#[cfg(feature = "ethereum")]
+pub mod uniswap_v3;
+#[cfg(feature = "verkle")]
+pub mod verkle;
+mod vyper;
+#[cfg(feature = "zksync")]
+pub mod zksync;
 
 // Lightweight alloy with selective imports
 pub mod alloy;
 
 // Re-export the main types for backward compatibility
-pub use abi_fetcher::AbiFetcher;
-pub use indexer::{IndexerService, MockIndexerService, SemanticValidator, ValidationResult};
-pub use layout::EthereumLayoutCompiler;
-pub use proof::EthereumProofFetcher;
+#[cfg(feature = "std")]
+pub use abi_fetcher::{AbiFetcher, AbiSource, VerifiedSource};
+#[cfg(all(feature = "std", feature = "ethereum"))]
+pub use anchor::{AnchoredProof, L2Anchor};
+#[cfg(all(feature = "std", feature = "ethereum"))]
+pub use arbitrum::{ArbitrumAssertion, ArbitrumCommitment, ArbitrumProof, ArbitrumRollup, RollupProtocol};
+#[cfg(all(feature = "std", feature = "ethereum"))]
+pub use beacon::{BeaconAnchor, BeaconFinality, FinalizedProof};
+#[cfg(feature = "std")]
+pub use cache::ProofCache;
+pub use chains::{BlockSelection, BlockTag, EvmChainAdapter, EvmFinality, QuirkProfile};
+#[cfg(feature = "ethereum")]
+pub use header::encode_header;
+#[cfg(feature = "std")]
+pub use indexer::RpcIndexerService;
+#[cfg(feature = "ethereum")]
+pub use inference::{infer_layout, InferredLayout, InferredSlot, LayoutProvenance, SlotKind};
+pub use layout::{BuildInfoContract, EthereumLayoutCompiler};
+#[cfg(all(feature = "std", feature = "ethereum"))]
+pub use optimism::{OpStackAnchor, OptimismProof, OutputRootCommitment, OutputRootProof};
+pub use presets::SafeSlot;
+#[cfg(feature = "std")]
+pub use proof::{ArchiveProof, Eip1186Proof, Eip1186StorageProof, EthereumProofFetcher, PinnedBlock, TimeSeriesProof};
+pub use proxy::{address_from_word, Eip1967Slot, ProxyInfo, BEACON_IMPLEMENTATION_SELECTOR};
+#[cfg(feature = "std")]
+pub use proxy::ProxyDetector;
+#[cfg(feature = "ethereum")]
+pub use receipts::{Log, Receipt, ReceiptOutcome, ReceiptProof, TRANSFER_TOPIC};
 pub use resolver::EthereumKeyResolver;
+#[cfg(all(feature = "std", feature = "ethereum"))]
+pub use subscription::{parse_new_head, subscribe_request, subscription_id, ProofRefresh, ProofSubscription};
+#[cfg(feature = "ethereum")]
+pub use transactions::TransactionProof;
+#[cfg(feature = "std")]
+pub use transport::{RetryPolicy, RpcTransport};
+pub use vyper::VyperLayoutCompiler;
+#[cfg(feature = "zksync")]
+pub use zksync::{EraProof, EraStorageProof};
+pub use traverse_indexer::{IndexerService, MockIndexerService, SemanticValidator, ValidationResult};
+#[cfg(feature = "std")]
+pub use traverse_indexer::AlchemyIndexer;
 
 // Re-export lightweight alloy types
 pub use alloy::{
@@ -40,7 +120,7 @@ pub use alloy::{
 
 // Re-export conditional types
 #[cfg(feature = "lightweight-alloy")]
-pub use alloy::{LightweightAbi};
+pub use alloy::{parse_account_proof, LightweightAbi};
 
 #[cfg(feature = "lightweight-alloy")]
 pub use alloy::{Address, B256, U256, Bytes, FixedBytes, Uint};
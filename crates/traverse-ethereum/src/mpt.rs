@@ -0,0 +1,461 @@
+//! Merkle-Patricia trie proof verification
+//!
+//! Verifies `eth_getProof` responses locally: the account proof against a
+//! block's state root, and each storage proof against the account's storage
+//! root. No RPC access is needed, so third-party proof artifacts can be audited
+//! offline.
+//!
+//! Trie paths are `keccak256(address)` for accounts and `keccak256(slot)` for
+//! storage, as in the Ethereum state and storage tries. The per-block receipt
+//! and transaction tries key their entries by `rlp(index)` without hashing;
+//! [`verify_trie_proof`] walks those, and [`ordered_trie_proof`] rebuilds such a
+//! trie from a block's entries to produce a proof nodes do not serve.
+
+use rlp::{Rlp, RlpStream};
+use traverse_core::hash::{decode_compact, keccak256, nibbles};
+use traverse_core::TraverseError;
+
+/// Root of an empty trie, `keccak256(rlp(""))`
+pub const EMPTY_TRIE_ROOT: [u8; 32] = [
+    0x56, 0xe8, 0x1f, 0x17, 0x1b, 0xcc, 0x55, 0xa6, 0xff, 0x83, 0x45, 0xe6, 0x92, 0xc0, 0xf8, 0x6e,
+    0x5b, 0x48, 0xe0, 0x1b, 0x99, 0x6c, 0xad, 0xc0, 0x01, 0x62, 0x2f, 0xb5, 0xe3, 0x63, 0xb4, 0x21,
+];
+
+/// Account fields proven by an account proof
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountState {
+    /// Transaction count
+    pub nonce: u64,
+    /// Balance in wei (big-endian, 32 bytes)
+    pub balance: [u8; 32],
+    /// Root of the account's storage trie
+    pub storage_root: [u8; 32],
+    /// Hash of the account's code
+    pub code_hash: [u8; 32],
+}
+
+fn invalid(message: &str) -> TraverseError {
+    TraverseError::Validation(format!("Invalid proof: {}", message))
+}
+
+/// Reference from a parent node to a child
+enum NodeRef {
+    Hash([u8; 32]),
+    Inline(Vec<u8>),
+    Empty,
+}
+
+fn child_ref(item: &Rlp) -> Result<NodeRef, TraverseError> {
+    if item.is_list() {
+        // Nodes shorter than 32 bytes are embedded in their parent
+        return Ok(NodeRef::Inline(item.as_raw().to_vec()));
+    }
+    let data = item.data().map_err(|_| invalid("bad child reference"))?;
+    match data.len() {
+        0 => Ok(NodeRef::Empty),
+        32 => {
+            let mut hash = [0u8; 32];
+            hash.copy_from_slice(data);
+            Ok(NodeRef::Hash(hash))
+        }
+        _ => Err(invalid("child reference is neither a hash nor an inline node")),
+    }
+}
+
+/// Look up `key` in the trie rooted at `root` using `proof`
+///
+/// The trie path is `keccak256(key)`. Returns the raw leaf value, or `None`
+/// when the proof shows the key is absent. Errors if any node does not hash
+/// to the reference in its parent or the proof ends early.
+pub fn verify_proof(root: &[u8; 32], key: &[u8], proof: &[Vec<u8>]) -> Result<Option<Vec<u8>>, TraverseError> {
+    walk_proof(root, &nibbles(&keccak256(key)), proof)
+}
+
+/// Look up `key` in a trie whose paths are the keys themselves
+///
+/// Like [`verify_proof`], for the receipt and transaction tries where the
+/// key is `rlp(index)` rather than a hash.
+pub fn verify_trie_proof(root: &[u8; 32], key: &[u8], proof: &[Vec<u8>]) -> Result<Option<Vec<u8>>, TraverseError> {
+    walk_proof(root, &nibbles(key), proof)
+}
+
+fn walk_proof(root: &[u8; 32], path: &[u8], proof: &[Vec<u8>]) -> Result<Option<Vec<u8>>, TraverseError> {
+    if proof.is_empty() && *root == EMPTY_TRIE_ROOT {
+        return Ok(None);
+    }
+
+    let mut position = 0;
+    let mut nodes = proof.iter();
+    let mut next = NodeRef::Hash(*root);
+
+    loop {
+        let node = match next {
+            NodeRef::Hash(hash) => {
+                let node = nodes.next().ok_or_else(|| invalid("proof ends before the key's leaf"))?;
+                if keccak256(node) != hash {
+                    return Err(invalid("node hash does not match its parent reference"));
+                }
+                node.clone()
+            }
+            NodeRef::Inline(node) => node,
+            NodeRef::Empty => return Ok(None),
+        };
+
+        let rlp = Rlp::new(&node);
+        match rlp.item_count().map_err(|_| invalid("node is not an RLP list"))? {
+            17 => {
+                if position == path.len() {
+                    let value = rlp.at(16).and_then(|v| v.data().map(<[u8]>::to_vec));
+                    let value = value.map_err(|_| invalid("bad branch value"))?;
+                    return Ok((!value.is_empty()).then_some(value));
+                }
+                let child = rlp.at(path[position] as usize).map_err(|_| invalid("bad branch child"))?;
+                next = child_ref(&child)?;
+                position += 1;
+            }
+            2 => {
+                let encoded = rlp.at(0).and_then(|p| p.data().map(<[u8]>::to_vec));
+                let (segment, is_leaf) = decode_compact(&encoded.map_err(|_| invalid("bad node path"))?).map_err(invalid)?;
+                let remaining = &path[position..];
+
+                if is_leaf {
+                    if remaining != segment.as_slice() {
+                        return Ok(None);
+                    }
+                    let value = rlp.at(1).and_then(|v| v.data().map(<[u8]>::to_vec));
+                    return Ok(Some(value.map_err(|_| invalid("bad leaf value"))?));
+                }
+
+                if !remaining.starts_with(&segment) {
+                    return Ok(None);
+                }
+                position += segment.len();
+                let child = rlp.at(1).map_err(|_| invalid("bad extension child"))?;
+                next = child_ref(&child)?;
+            }
+            _ => return Err(invalid("node is neither a branch, extension nor leaf")),
+        }
+    }
+}
+
+/// Key of entry `index` in a receipt or transaction trie
+pub fn index_key(index: usize) -> Vec<u8> {
+    rlp::encode(&(index as u64)).to_vec()
+}
+
+/// Build the trie of `values` keyed by [`index_key`] and prove entry `index`
+///
+/// Returns the trie root and the proof nodes, root first, in the form
+/// [`verify_trie_proof`] takes. With `values` being a block's encoded
+/// receipts or transactions the root is the header's `receiptsRoot` or
+/// `transactionsRoot`.
+pub fn ordered_trie_proof(values: &[Vec<u8>], index: usize) -> ([u8; 32], Vec<Vec<u8>>) {
+    let keys: Vec<Vec<u8>> = (0..values.len()).map(index_key).collect();
+    trie_proof(keys.iter().map(Vec::as_slice).zip(values.iter().map(Vec::as_slice)), &index_key(index))
+}
+
+pub(crate) fn trie_proof<'a>(entries: impl Iterator<Item = (&'a [u8], &'a [u8])>, key: &[u8]) -> ([u8; 32], Vec<Vec<u8>>) {
+    let mut entries: Vec<(Vec<u8>, &[u8])> = entries.map(|(key, value)| (nibbles(key), value)).collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    if entries.is_empty() {
+        return (EMPTY_TRIE_ROOT, Vec::new());
+    }
+
+    let target = nibbles(key);
+    let mut proof = Vec::new();
+    let root = encode_node(&entries, 0, Some(&target), &mut proof);
+    // Every node is hashed into its parent except the root, which may be short
+    if root.len() < 32 {
+        proof.push(root.clone());
+    }
+    proof.reverse();
+    (keccak256(&root), proof)
+}
+
+fn compact(path: &[u8], leaf: bool) -> Vec<u8> {
+    let flag = if leaf { 2 } else { 0 } + (path.len() % 2) as u8;
+    let (mut out, rest) = if path.len() % 2 == 1 {
+        (vec![(flag << 4) | path[0]], &path[1..])
+    } else {
+        (vec![flag << 4], path)
+    };
+    out.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    out
+}
+
+fn append_child(stream: &mut RlpStream, node: &[u8]) {
+    if node.len() < 32 {
+        stream.append_raw(node, 1);
+    } else {
+        stream.append(&keccak256(node).to_vec());
+    }
+}
+
+/// Encode the node holding `entries`, which share their first `depth` nibbles
+///
+/// Nodes on the way to `target` that are referenced by hash are pushed to
+/// `proof` leaf first; inline nodes travel inside their parent.
+fn encode_node(entries: &[(Vec<u8>, &[u8])], depth: usize, target: Option<&[u8]>, proof: &mut Vec<Vec<u8>>) -> Vec<u8> {
+    let node = if let [(path, value)] = entries {
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&compact(&path[depth..], true));
+        stream.append(&value.to_vec());
+        stream.out().to_vec()
+    } else {
+        let first = &entries[0].0;
+        let shared = entries[1..]
+            .iter()
+            .map(|(path, _)| path[depth..].iter().zip(&first[depth..]).take_while(|(a, b)| a == b).count())
+            .min()
+            .unwrap_or(0);
+
+        if shared > 0 {
+            let prefix = &first[depth..depth + shared];
+            let target = target.filter(|t| t.len() >= depth + shared && &t[depth..depth + shared] == prefix);
+            let child = encode_node(entries, depth + shared, target, proof);
+            let mut stream = RlpStream::new_list(2);
+            stream.append(&compact(prefix, false));
+            append_child(&mut stream, &child);
+            stream.out().to_vec()
+        } else {
+            let mut stream = RlpStream::new_list(17);
+            for nibble in 0..16u8 {
+                let start = entries.partition_point(|(path, _)| path.len() <= depth || path[depth] < nibble);
+                let end = entries.partition_point(|(path, _)| path.len() <= depth || path[depth] <= nibble);
+                if start == end {
+                    stream.append_empty_data();
+                    continue;
+                }
+                let target = target.filter(|t| t.get(depth) == Some(&nibble));
+                let child = encode_node(&entries[start..end], depth + 1, target, proof);
+                append_child(&mut stream, &child);
+            }
+            match entries.iter().find(|(path, _)| path.len() == depth) {
+                Some((_, value)) => stream.append(&value.to_vec()),
+                None => stream.append_empty_data(),
+            };
+            stream.out().to_vec()
+        }
+    };
+
+    if target.is_some() && node.len() >= 32 {
+        proof.push(node.clone());
+    }
+    node
+}
+
+fn fixed_32(data: &[u8], field: &str) -> Result<[u8; 32], TraverseError> {
+    if data.len() > 32 {
+        return Err(invalid(&format!("{} longer than 32 bytes", field)));
+    }
+    let mut out = [0u8; 32];
+    out[32 - data.len()..].copy_from_slice(data);
+    Ok(out)
+}
+
+/// Verify an account proof against a state root
+///
+/// Returns `None` when the proof shows the account does not exist.
+pub fn verify_account_proof(
+    state_root: &[u8; 32],
+    address: &[u8; 20],
+    proof: &[Vec<u8>],
+) -> Result<Option<AccountState>, TraverseError> {
+    let Some(encoded) = verify_proof(state_root, address, proof)? else {
+        return Ok(None);
+    };
+
+    let account = Rlp::new(&encoded);
+    if account.item_count().ok() != Some(4) {
+        return Err(invalid("account leaf is not [nonce, balance, storageRoot, codeHash]"));
+    }
+    let field = |index: usize, name: &str| {
+        account
+            .at(index)
+            .and_then(|item| item.data().map(<[u8]>::to_vec))
+            .map_err(|_| invalid(&format!("bad account {}", name)))
+    };
+
+    let nonce_bytes = field(0, "nonce")?;
+    if nonce_bytes.len() > 8 {
+        return Err(invalid("account nonce longer than 8 bytes"));
+    }
+    let nonce = nonce_bytes.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64);
+
+    Ok(Some(AccountState {
+        nonce,
+        balance: fixed_32(&field(1, "balance")?, "balance")?,
+        storage_root: fixed_32(&field(2, "storage root")?, "storage root")?,
+        code_hash: fixed_32(&field(3, "code hash")?, "code hash")?,
+    }))
+}
+
+/// Verify a storage proof against an account's storage root
+///
+/// Returns the proven 32-byte slot value; absent slots are proven as zero.
+pub fn verify_storage_proof(
+    storage_root: &[u8; 32],
+    slot: &[u8; 32],
+    proof: &[Vec<u8>],
+) -> Result<[u8; 32], TraverseError> {
+    match verify_proof(storage_root, slot, proof)? {
+        Some(encoded) => {
+            let value = Rlp::new(&encoded).data().map_err(|_| invalid("bad storage value"))?.to_vec();
+            fixed_32(&value, "storage value")
+        }
+        None => Ok([0u8; 32]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(path: &[u8], value: &[u8]) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&compact(path, true));
+        stream.append(&value.to_vec());
+        stream.out().to_vec()
+    }
+
+    fn rlp_u256(value: u64) -> Vec<u8> {
+        let bytes = value.to_be_bytes();
+        let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+        rlp::encode(&bytes[start..].to_vec()).to_vec()
+    }
+
+    struct TwoSlotTrie {
+        root: [u8; 32],
+        slot_a: [u8; 32],
+        slot_b: [u8; 32],
+        proof_a: Vec<Vec<u8>>,
+        proof_b: Vec<Vec<u8>>,
+    }
+
+    /// Two-leaf storage trie: a branch at the root with both leaves hashed
+    fn two_slot_trie() -> TwoSlotTrie {
+        let slot_a = [0u8; 32];
+        let slot_b = (1u8..=255)
+            .map(|i| {
+                let mut slot = [0u8; 32];
+                slot[31] = i;
+                slot
+            })
+            .find(|slot| keccak256(slot)[0] >> 4 != keccak256(&slot_a)[0] >> 4)
+            .unwrap();
+
+        let path_a = nibbles(&keccak256(&slot_a));
+        let path_b = nibbles(&keccak256(&slot_b));
+        let leaf_a = leaf(&path_a[1..], &rlp_u256(1000));
+        let leaf_b = leaf(&path_b[1..], &rlp_u256(7));
+
+        let mut branch = RlpStream::new_list(17);
+        for nibble in 0..16u8 {
+            if nibble == path_a[0] {
+                branch.append(&keccak256(&leaf_a).to_vec());
+            } else if nibble == path_b[0] {
+                branch.append(&keccak256(&leaf_b).to_vec());
+            } else {
+                branch.append_empty_data();
+            }
+        }
+        branch.append_empty_data();
+        let branch = branch.out().to_vec();
+        let root = keccak256(&branch);
+
+        TwoSlotTrie {
+            root,
+            slot_a,
+            slot_b,
+            proof_a: vec![branch.clone(), leaf_a],
+            proof_b: vec![branch, leaf_b],
+        }
+    }
+
+    #[test]
+    fn test_storage_proof_round_trip() {
+        let TwoSlotTrie { root, slot_a, slot_b, proof_a, proof_b } = two_slot_trie();
+
+        let value_a = verify_storage_proof(&root, &slot_a, &proof_a).unwrap();
+        assert_eq!(u64::from_be_bytes(value_a[24..].try_into().unwrap()), 1000);
+
+        let value_b = verify_storage_proof(&root, &slot_b, &proof_b).unwrap();
+        assert_eq!(value_b[31], 7);
+    }
+
+    #[test]
+    fn test_tampered_proofs_are_rejected() {
+        let TwoSlotTrie { root, slot_a, proof_a, proof_b, .. } = two_slot_trie();
+
+        let mut tampered = proof_a.clone();
+        let last = tampered[1].len() - 1;
+        tampered[1][last] ^= 1;
+        assert!(verify_storage_proof(&root, &slot_a, &tampered).is_err());
+
+        let mut wrong_root = root;
+        wrong_root[0] ^= 1;
+        assert!(verify_storage_proof(&wrong_root, &slot_a, &proof_a).is_err());
+
+        // A valid path to a different leaf does not prove slot_a's value
+        assert!(verify_storage_proof(&root, &slot_a, &proof_b).is_err());
+        assert!(verify_storage_proof(&root, &slot_a, &proof_a[..1]).is_err());
+    }
+
+    #[test]
+    fn test_account_proof() {
+        let address = [0x11u8; 20];
+        let mut account = RlpStream::new_list(4);
+        account.append(&5u64);
+        account.append(&vec![0x0du8, 0xe0, 0xb6, 0xb3]);
+        account.append(&EMPTY_TRIE_ROOT.to_vec());
+        account.append(&keccak256(&[]).to_vec());
+        let account = account.out().to_vec();
+
+        let node = leaf(&nibbles(&keccak256(&address)), &account);
+        let root = keccak256(&node);
+
+        let state = verify_account_proof(&root, &address, std::slice::from_ref(&node)).unwrap().unwrap();
+        assert_eq!(state.nonce, 5);
+        assert_eq!(state.storage_root, EMPTY_TRIE_ROOT);
+        assert_eq!(&state.balance[28..], &[0x0d, 0xe0, 0xb6, 0xb3]);
+
+        // Same trie, different address: the leaf proves absence
+        assert_eq!(verify_account_proof(&root, &[0x22u8; 20], &[node]).unwrap(), None);
+        assert_eq!(verify_storage_proof(&EMPTY_TRIE_ROOT, &[0u8; 32], &[]).unwrap(), [0u8; 32]);
+    }
+
+    #[test]
+    fn test_ordered_trie_proofs() {
+        // Enough entries for extensions, hashed and inline children, and keys
+        // of both one and two bytes past index 127
+        let values: Vec<Vec<u8>> = (0..300u32).map(|i| i.to_be_bytes().repeat(1 + i as usize % 12)).collect();
+
+        let mut roots = Vec::new();
+        for index in [0, 1, 15, 127, 128, 255, 299] {
+            let (root, proof) = ordered_trie_proof(&values, index);
+            let proven = verify_trie_proof(&root, &index_key(index), &proof).unwrap();
+            assert_eq!(proven.as_deref(), Some(values[index].as_slice()));
+            roots.push(root);
+        }
+        assert!(roots.windows(2).all(|pair| pair[0] == pair[1]));
+
+        let (root, proof) = ordered_trie_proof(&values, 300);
+        assert_eq!(verify_trie_proof(&root, &index_key(300), &proof).unwrap(), None);
+
+        // A single short entry is an inline root
+        let (root, proof) = ordered_trie_proof(&[vec![1, 2, 3]], 0);
+        assert_eq!(verify_trie_proof(&root, &index_key(0), &proof).unwrap(), Some(vec![1, 2, 3]));
+        assert_eq!(ordered_trie_proof(&[], 0), (EMPTY_TRIE_ROOT, Vec::new()));
+    }
+
+    #[test]
+    fn test_known_trie_root() {
+        // The unhashed-key example trie from the Ethereum wiki
+        let entries: [(&[u8], &[u8]); 3] =
+            [(b"doe", b"reindeer"), (b"dog", b"puppy"), (b"dogglesworth", b"cat")];
+        let (root, proof) = trie_proof(entries.into_iter(), b"dog");
+        assert_eq!(hex::encode(root), "8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3");
+        assert_eq!(verify_trie_proof(&root, b"dog", &proof).unwrap(), Some(b"puppy".to_vec()));
+        assert_eq!(verify_trie_proof(&root, b"do", &proof).unwrap(), None);
+    }
+}
@@ -0,0 +1,268 @@
+//! OP Stack storage proofs chained to an L2 output root
+//!
+//! OP Stack chains such as Optimism and Base post output roots to L1 rather
+//! than state roots. A version 0 output root commits to an L2 block as
+//! `keccak256(version ‖ stateRoot ‖ messagePasserStorageRoot ‖ blockHash)`,
+//! the message passer being the `L2ToL1MessagePasser` predeploy that holds
+//! withdrawals. As an [`L2Anchor`], an [`OpStackAnchor`] gathers the preimage
+//! and the message passer's account proof, so the [`OptimismProof`] lets a
+//! verifier that trusts an output root read from L1 reach the state root,
+//! and from it the storage, without trusting the L2 node.
+//!
+//! Output roots are only posted for some blocks; pin the fetcher with
+//! [`EthereumProofFetcher::at_block`] to the L2 block of the posted root.
+
+use traverse_core::hash::keccak256;
+use traverse_core::TraverseError;
+
+use crate::receipts::{fixed, hex_field, quantity};
+use crate::{AnchoredProof, Eip1186Proof, EthereumProofFetcher, L2Anchor, PinnedBlock};
+
+/// Address of the `L2ToL1MessagePasser` predeploy
+pub const MESSAGE_PASSER: [u8; 20] = [
+    0x42, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x16,
+];
+
+/// The only output root version in use
+pub const OUTPUT_VERSION_V0: [u8; 32] = [0u8; 32];
+
+/// Preimage of an output root, as the L1 contracts' `OutputRootProof`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputRootProof {
+    pub version: [u8; 32],
+    pub state_root: [u8; 32],
+    /// Storage root of the `L2ToL1MessagePasser`
+    pub message_passer_storage_root: [u8; 32],
+    pub latest_block_hash: [u8; 32],
+}
+
+impl OutputRootProof {
+    /// The output root this preimage hashes to
+    pub fn output_root(&self) -> [u8; 32] {
+        let mut preimage = [0u8; 128];
+        preimage[..32].copy_from_slice(&self.version);
+        preimage[32..64].copy_from_slice(&self.state_root);
+        preimage[64..96].copy_from_slice(&self.message_passer_storage_root);
+        preimage[96..].copy_from_slice(&self.latest_block_hash);
+        keccak256(&preimage)
+    }
+}
+
+/// Evidence anchoring an OP Stack block: its output root preimage and the message passer's account proof
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputRootCommitment {
+    pub output: OutputRootProof,
+    /// Account proof of the `L2ToL1MessagePasser`, proving its storage root
+    pub message_passer: Eip1186Proof,
+}
+
+/// Storage proof of an OP Stack contract chained to the block's output root
+pub type OptimismProof = AnchoredProof<OutputRootCommitment>;
+
+impl OptimismProof {
+    pub fn output_root(&self) -> [u8; 32] {
+        self.commitment.output.output_root()
+    }
+}
+
+/// Anchor at the fetcher's block through its output root
+#[derive(Debug, Clone, Default)]
+pub struct OpStackAnchor {
+    output_root: Option<[u8; 32]>,
+}
+
+impl OpStackAnchor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require the block's output root to be `output_root`, as posted on L1
+    pub fn with_output_root(mut self, output_root: [u8; 32]) -> Self {
+        self.output_root = Some(output_root);
+        self
+    }
+}
+
+impl L2Anchor for OpStackAnchor {
+    type Commitment = OutputRootCommitment;
+
+    fn name(&self) -> &str {
+        "op-stack"
+    }
+
+    /// The fetcher's archive block, or `latest`, with its message passer proof
+    async fn resolve(
+        &self,
+        l2: &EthereumProofFetcher,
+        client: &reqwest::Client,
+    ) -> Result<(PinnedBlock, OutputRootCommitment), TraverseError> {
+        let header = l2.rpc(client, "eth_getBlockByNumber", serde_json::json!([l2.block_param(), false])).await?;
+        let block = PinnedBlock {
+            number: quantity(&hex_field(&header, "number")?, "number")?,
+            hash: fixed(&hex_field(&header, "hash")?, "hash")?,
+            state_root: fixed(&hex_field(&header, "stateRoot")?, "stateRoot")?,
+        };
+
+        let block_ref = serde_json::json!({ "blockHash": format!("0x{}", hex::encode(block.hash)) });
+        let passer = format!("0x{}", hex::encode(MESSAGE_PASSER));
+        let message_passer = l2.rpc(client, "eth_getProof", serde_json::json!([passer, [], block_ref])).await?;
+        let message_passer = Eip1186Proof::from_response(&message_passer)?;
+
+        let output = OutputRootProof {
+            version: OUTPUT_VERSION_V0,
+            state_root: block.state_root,
+            message_passer_storage_root: message_passer.storage_hash,
+            latest_block_hash: block.hash,
+        };
+        Ok((block, OutputRootCommitment { output, message_passer }))
+    }
+
+    /// Check the output root preimage names the block and its message passer storage root
+    ///
+    /// With an output root set, the preimage must also hash to it.
+    fn verify_commitment(&self, block: &PinnedBlock, commitment: &OutputRootCommitment) -> Result<(), TraverseError> {
+        let output = &commitment.output;
+        if output.version != OUTPUT_VERSION_V0 {
+            return Err(TraverseError::Validation(format!(
+                "Unsupported output root version 0x{}",
+                hex::encode(output.version)
+            )));
+        }
+        if let Some(output_root) = self.output_root {
+            if output.output_root() != output_root {
+                return Err(TraverseError::Validation(format!(
+                    "Output root 0x{} does not match the block's 0x{}",
+                    hex::encode(output_root),
+                    hex::encode(output.output_root())
+                )));
+            }
+        }
+        if output.state_root != block.state_root || output.latest_block_hash != block.hash {
+            return Err(TraverseError::Validation("Output root is for a different L2 block".to_string()));
+        }
+        if commitment.message_passer.address != MESSAGE_PASSER {
+            return Err(TraverseError::Validation("Message passer proof is for another account".to_string()));
+        }
+        commitment.message_passer.verify(&output.state_root)?;
+        if commitment.message_passer.storage_hash != output.message_passer_storage_root {
+            return Err(TraverseError::Validation(
+                "Message passer storage root does not match its account proof".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The output root and its preimage, and the message passer's account proof
+    fn commitment_json(&self, commitment: &OutputRootCommitment) -> serde_json::Value {
+        let word = |bytes: &[u8]| format!("0x{}", hex::encode(bytes));
+        let output = &commitment.output;
+        serde_json::json!({
+            "output_root": word(&output.output_root()),
+            "output_root_proof": {
+                "version": word(&output.version),
+                "state_root": word(&output.state_root),
+                "message_passer_storage_root": word(&output.message_passer_storage_root),
+                "latest_block_hash": word(&output.latest_block_hash),
+            },
+            "message_passer_proof": commitment.message_passer.to_response(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpt::trie_proof;
+    use crate::Eip1186StorageProof;
+
+    fn account(storage_root: &[u8; 32]) -> Vec<u8> {
+        let mut account = rlp::RlpStream::new_list(4);
+        account.append(&1u64);
+        account.append(&vec![0x2au8]);
+        account.append(&storage_root.to_vec());
+        account.append(&vec![0xccu8; 32]);
+        account.out().to_vec()
+    }
+
+    /// A contract with one slot and the message passer, both proven from one state root
+    fn optimism_proof() -> OptimismProof {
+        let contract = [0xab; 20];
+        let slot_path = keccak256(&[0u8; 32]);
+        let slot_value = rlp::encode(&vec![0x03u8, 0xe8]).to_vec();
+        let (storage_root, slot_proof) = trie_proof([(&slot_path[..], &slot_value[..])].into_iter(), &slot_path);
+
+        let passer_storage_root = [0x5a; 32];
+        let accounts = [
+            (keccak256(&contract), account(&storage_root)),
+            (keccak256(&MESSAGE_PASSER), account(&passer_storage_root)),
+        ];
+        let entries = || accounts.iter().map(|(path, value)| (&path[..], &value[..]));
+        let (state_root, contract_proof) = trie_proof(entries(), &accounts[0].0);
+        let (_, passer_proof) = trie_proof(entries(), &accounts[1].0);
+
+        let account_proof = |address: [u8; 20], storage_hash: [u8; 32], account_proof: Vec<Vec<u8>>| Eip1186Proof {
+            address,
+            nonce: 1,
+            balance: fixed(&[0x2a], "balance").unwrap(),
+            storage_hash,
+            code_hash: [0xcc; 32],
+            account_proof,
+            storage_proofs: Vec::new(),
+        };
+        let mut proof = account_proof(contract, storage_root, contract_proof);
+        proof.storage_proofs.push(Eip1186StorageProof {
+            key: [0u8; 32],
+            value: fixed(&[0x03, 0xe8], "value").unwrap(),
+            proof: slot_proof,
+        });
+
+        let block = PinnedBlock { number: 120_000_000, hash: [0x11; 32], state_root };
+        AnchoredProof {
+            commitment: OutputRootCommitment {
+                output: OutputRootProof {
+                    version: OUTPUT_VERSION_V0,
+                    state_root,
+                    message_passer_storage_root: passer_storage_root,
+                    latest_block_hash: block.hash,
+                },
+                message_passer: account_proof(MESSAGE_PASSER, passer_storage_root, passer_proof),
+            },
+            block,
+            proof,
+        }
+    }
+
+    #[test]
+    fn test_verify_optimism_proof() {
+        let proof = optimism_proof();
+        let anchor = OpStackAnchor::new().with_output_root(proof.output_root());
+        proof.verify(&anchor).unwrap();
+        assert!(proof.verify(&OpStackAnchor::new().with_output_root([0u8; 32])).is_err());
+
+        // A storage root the message passer's account proof does not prove
+        let mut forged_passer = optimism_proof();
+        forged_passer.commitment.output.message_passer_storage_root = [0x5b; 32];
+        forged_passer.commitment.message_passer.storage_hash = [0x5b; 32];
+        assert!(forged_passer.verify(&OpStackAnchor::new()).is_err());
+
+        let mut other_block = optimism_proof();
+        other_block.block.hash = [0x12; 32];
+        assert!(other_block.verify(&anchor).is_err());
+
+        let mut forged_value = optimism_proof();
+        forged_value.proof.storage_proofs[0].value[31] = 0;
+        assert!(forged_value.verify(&anchor).is_err());
+    }
+
+    #[test]
+    fn test_output_root_json() {
+        let proof = optimism_proof();
+        let json = proof.to_json(&OpStackAnchor::new());
+        assert_eq!(json["anchor"], "op-stack");
+        assert_eq!(json["commitment"]["output_root"], format!("0x{}", hex::encode(proof.output_root())));
+        assert_eq!(json["commitment"]["output_root_proof"]["version"], format!("0x{}", "00".repeat(32)));
+        assert_eq!(json["commitment"]["message_passer_proof"]["address"], "0x4200000000000000000000000000000000000016");
+        assert_eq!(json["proof"]["storageProof"].as_array().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,353 @@
+//! Built-in layouts of widely deployed contracts
+//!
+//! Some contracts are deployed so often, and verified so rarely under their
+//! own address, that compiling their layout is more work than it is worth.
+//! Their layouts are fixed by the audited sources and ship here, ready to
+//! resolve queries against:
+//!
+//! ```rust,ignore
+//! use traverse_ethereum::{presets, EthereumKeyResolver};
+//! use traverse_core::KeyResolver;
+//!
+//! let safe = presets::safe();
+//! let threshold = EthereumKeyResolver.resolve(&safe, "threshold")?;
+//! ```
+//!
+//! ## Tokens
+//!
+//! [`erc20`], [`erc721`] and [`erc1155`] are the layouts of OpenZeppelin's
+//! token contracts, v4 and v5, deployed without upgradeability and as the
+//! first contract in the inheritance order. A token that inherits storage
+//! from another base first, or OpenZeppelin's upgradeable variants with
+//! ERC-7201 namespaces, needs its compiled layout instead.
+//!
+//! ## Chainlink
+//!
+//! [`chainlink_proxy`] is the layout of `EACAggregatorProxy`, the address
+//! consumers read a feed from. Its `currentPhase.aggregator` names the
+//! aggregator holding the rounds, which [`crate::chainlink`] decodes.
+//!
+//! ## Gnosis Safe
+//!
+//! [`safe`] is the layout of the Safe singleton from v1.3.0 on, which every
+//! Safe proxy delegates to. Owners and modules are linked lists threaded
+//! through `owners` and `modules`, each starting at [`SENTINEL`]:
+//! `owners[0x…01]` is the first owner, `owners[owner]` the next, and the
+//! last points back to the sentinel. The fallback handler and the guard sit
+//! at fixed slots outside the layout, see [`SafeSlot`].
+
+use traverse_core::{DerivationStep, DerivationTrace, Key, LayoutInfo, StaticKeyPath, StorageEntry, TypeInfo, ZeroSemantics};
+
+/// Head and tail of the Safe's owner and module lists
+pub const SENTINEL: [u8; 20] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+
+/// Names of the built-in layouts, as [`preset`] accepts them
+pub const PRESETS: [&str; 5] = ["erc20", "erc721", "erc1155", "chainlink-proxy", "safe"];
+
+/// The built-in layout called `name`
+pub fn preset(name: &str) -> Option<LayoutInfo> {
+    match name.trim().to_ascii_lowercase().replace('-', "").as_str() {
+        "erc20" => Some(erc20()),
+        "erc721" => Some(erc721()),
+        "erc1155" => Some(erc1155()),
+        "chainlinkproxy" => Some(chainlink_proxy()),
+        "safe" | "gnosissafe" => Some(safe()),
+        _ => None,
+    }
+}
+
+fn entry(label: &str, slot: u64, type_name: &str, zero_semantics: ZeroSemantics) -> StorageEntry {
+    StorageEntry {
+        label: label.to_string(),
+        slot: slot.to_string(),
+        offset: 0,
+        type_name: type_name.to_string(),
+        zero_semantics,
+    }
+}
+
+fn inplace(label: &str, bytes: u8) -> TypeInfo {
+    TypeInfo {
+        label: label.to_string(),
+        number_of_bytes: bytes.to_string(),
+        encoding: "inplace".to_string(),
+        base: None,
+        key: None,
+        value: None,
+    }
+}
+
+/// A `string` in storage, its length or short value at the slot
+fn string() -> TypeInfo {
+    TypeInfo { encoding: "bytes".to_string(), ..inplace("t_string_storage", 32) }
+}
+
+fn mapping(key: &str, value: &str) -> TypeInfo {
+    TypeInfo {
+        label: format!("t_mapping({},{})", key, value),
+        number_of_bytes: "32".to_string(),
+        encoding: "mapping".to_string(),
+        base: None,
+        key: Some(key.to_string()),
+        value: Some(value.to_string()),
+    }
+}
+
+fn layout(contract_name: &str, storage: Vec<StorageEntry>, types: Vec<TypeInfo>) -> LayoutInfo {
+    LayoutInfo { contract_name: contract_name.to_string(), storage, types, code_variables: Vec::new() }
+}
+
+/// Storage layout of OpenZeppelin's `ERC20`
+pub fn erc20() -> LayoutInfo {
+    layout(
+        "ERC20",
+        vec![
+            entry("_balances", 0, "t_mapping(t_address,t_uint256)", ZeroSemantics::ValidZero),
+            entry("_allowances", 1, "t_mapping(t_address,t_mapping(t_address,t_uint256))", ZeroSemantics::ValidZero),
+            entry("_totalSupply", 2, "t_uint256", ZeroSemantics::ValidZero),
+            entry("_name", 3, "t_string_storage", ZeroSemantics::NeverWritten),
+            entry("_symbol", 4, "t_string_storage", ZeroSemantics::NeverWritten),
+        ],
+        vec![
+            inplace("t_address", 20),
+            inplace("t_uint256", 32),
+            string(),
+            mapping("t_address", "t_uint256"),
+            mapping("t_address", "t_mapping(t_address,t_uint256)"),
+        ],
+    )
+}
+
+/// Storage layout of OpenZeppelin's `ERC721`
+pub fn erc721() -> LayoutInfo {
+    layout(
+        "ERC721",
+        vec![
+            entry("_name", 0, "t_string_storage", ZeroSemantics::NeverWritten),
+            entry("_symbol", 1, "t_string_storage", ZeroSemantics::NeverWritten),
+            entry("_owners", 2, "t_mapping(t_uint256,t_address)", ZeroSemantics::NeverWritten),
+            entry("_balances", 3, "t_mapping(t_address,t_uint256)", ZeroSemantics::ValidZero),
+            entry("_tokenApprovals", 4, "t_mapping(t_uint256,t_address)", ZeroSemantics::Cleared),
+            entry("_operatorApprovals", 5, "t_mapping(t_address,t_mapping(t_address,t_bool))", ZeroSemantics::ValidZero),
+        ],
+        vec![
+            inplace("t_address", 20),
+            inplace("t_bool", 1),
+            inplace("t_uint256", 32),
+            string(),
+            mapping("t_uint256", "t_address"),
+            mapping("t_address", "t_uint256"),
+            mapping("t_address", "t_bool"),
+            mapping("t_address", "t_mapping(t_address,t_bool)"),
+        ],
+    )
+}
+
+/// Storage layout of OpenZeppelin's `ERC1155`
+///
+/// Balances are keyed by token id first: `_balances[id][account]`.
+pub fn erc1155() -> LayoutInfo {
+    layout(
+        "ERC1155",
+        vec![
+            entry("_balances", 0, "t_mapping(t_uint256,t_mapping(t_address,t_uint256))", ZeroSemantics::ValidZero),
+            entry("_operatorApprovals", 1, "t_mapping(t_address,t_mapping(t_address,t_bool))", ZeroSemantics::ValidZero),
+            entry("_uri", 2, "t_string_storage", ZeroSemantics::NeverWritten),
+        ],
+        vec![
+            inplace("t_address", 20),
+            inplace("t_bool", 1),
+            inplace("t_uint256", 32),
+            string(),
+            mapping("t_address", "t_uint256"),
+            mapping("t_uint256", "t_mapping(t_address,t_uint256)"),
+            mapping("t_address", "t_bool"),
+            mapping("t_address", "t_mapping(t_address,t_bool)"),
+        ],
+    )
+}
+
+/// Storage layout of Chainlink's `EACAggregatorProxy`
+///
+/// The packed current phase is two entries, `currentPhase.id` and
+/// `currentPhase.aggregator`.
+pub fn chainlink_proxy() -> LayoutInfo {
+    let packed = |label: &str, offset: u8, type_name: &str| StorageEntry {
+        offset,
+        ..entry(label, 2, type_name, ZeroSemantics::NeverWritten)
+    };
+    layout(
+        "EACAggregatorProxy",
+        vec![
+            entry("owner", 0, "t_address", ZeroSemantics::NeverWritten),
+            entry("pendingOwner", 1, "t_address", ZeroSemantics::Cleared),
+            packed("currentPhase.id", 0, "t_uint16"),
+            packed("currentPhase.aggregator", 2, "t_address"),
+            entry("proposedAggregator", 3, "t_address", ZeroSemantics::Cleared),
+            entry("phaseAggregators", 4, "t_mapping(t_uint16,t_address)", ZeroSemantics::NeverWritten),
+            entry("accessController", 5, "t_address", ZeroSemantics::ValidZero),
+        ],
+        vec![inplace("t_address", 20), inplace("t_uint16", 2), mapping("t_uint16", "t_address")],
+    )
+}
+
+/// Storage layout of the Safe singleton, v1.3.0 to v1.4.1
+pub fn safe() -> LayoutInfo {
+    layout(
+        "Safe",
+        vec![
+            entry("singleton", 0, "t_address", ZeroSemantics::NeverWritten),
+            entry("modules", 1, "t_mapping(t_address,t_address)", ZeroSemantics::NeverWritten),
+            entry("owners", 2, "t_mapping(t_address,t_address)", ZeroSemantics::NeverWritten),
+            entry("ownerCount", 3, "t_uint256", ZeroSemantics::NeverWritten),
+            entry("threshold", 4, "t_uint256", ZeroSemantics::NeverWritten),
+            entry("nonce", 5, "t_uint256", ZeroSemantics::ValidZero),
+            entry("_deprecatedDomainSeparator", 6, "t_bytes32", ZeroSemantics::NeverWritten),
+            entry("signedMessages", 7, "t_mapping(t_bytes32,t_uint256)", ZeroSemantics::NeverWritten),
+            entry(
+                "approvedHashes",
+                8,
+                "t_mapping(t_address,t_mapping(t_bytes32,t_uint256))",
+                ZeroSemantics::NeverWritten,
+            ),
+        ],
+        vec![
+            inplace("t_address", 20),
+            inplace("t_uint256", 32),
+            inplace("t_bytes32", 32),
+            mapping("t_address", "t_address"),
+            mapping("t_bytes32", "t_uint256"),
+            mapping("t_address", "t_mapping(t_bytes32,t_uint256)"),
+        ],
+    )
+}
+
+/// A Safe address kept at a slot derived from a name, outside the layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SafeSlot {
+    /// Fallback handler, `keccak256("fallback_manager.handler.address")`
+    FallbackHandler,
+    /// Transaction guard, `keccak256("guard_manager.guard.address")`
+    Guard,
+}
+
+impl SafeSlot {
+    pub const ALL: [SafeSlot; 2] = [SafeSlot::FallbackHandler, SafeSlot::Guard];
+
+    /// The storage slot
+    pub const fn slot(self) -> [u8; 32] {
+        match self {
+            SafeSlot::FallbackHandler => [
+                0x6c, 0x9a, 0x6c, 0x4a, 0x39, 0x28, 0x4e, 0x37, 0xed, 0x1c, 0xf5, 0x3d, 0x33, 0x75, 0x77, 0xd1,
+                0x42, 0x12, 0xa4, 0x87, 0x0f, 0xb9, 0x76, 0xa4, 0x36, 0x6c, 0x69, 0x3b, 0x93, 0x99, 0x18, 0xd5,
+            ],
+            SafeSlot::Guard => [
+                0x4a, 0x20, 0x4f, 0x62, 0x0c, 0x8c, 0x5c, 0xcd, 0xca, 0x3f, 0xd5, 0x4d, 0x00, 0x3b, 0xad, 0xd8,
+                0x5b, 0xa5, 0x00, 0x43, 0x6a, 0x43, 0x1f, 0x0c, 0xbd, 0xa4, 0xf5, 0x58, 0xc9, 0x3c, 0x34, 0xc8,
+            ],
+        }
+    }
+
+    /// Query name the resolver accepts for this slot
+    pub const fn query(self) -> &'static str {
+        match self {
+            SafeSlot::FallbackHandler => "safe.fallbackHandler",
+            SafeSlot::Guard => "safe.guard",
+        }
+    }
+
+    /// The slot a query names, if it names one
+    pub fn from_query(query: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|slot| slot.query() == query.trim())
+    }
+
+    /// Derivation of the slot's path, committed to `layout`
+    ///
+    /// The slot holds an address in its low 20 bytes; an empty slot means no
+    /// handler or guard was ever set.
+    pub fn trace(self, layout: &LayoutInfo) -> DerivationTrace {
+        DerivationTrace {
+            query: self.query().to_string(),
+            field: self.query().to_string(),
+            type_name: "t_address".to_string(),
+            steps: vec![DerivationStep::BaseSlot { field: self.query().to_string(), slot: self.slot() }],
+            path: StaticKeyPath {
+                name: self.query(),
+                key: Key::Fixed(self.slot()),
+                offset: None,
+                field_size: Some(20),
+                layout_commitment: layout.commitment(),
+                zero_semantics: ZeroSemantics::NeverWritten,
+            },
+        }
+    }
+}
+
+#[cfg(all(test, feature = "ethereum"))]
+mod tests {
+    use super::*;
+    use crate::EthereumKeyResolver;
+    use traverse_core::hash::keccak256;
+    use traverse_core::KeyResolver;
+
+    #[test]
+    fn test_safe_layout_resolves() {
+        let layout = safe();
+        layout.validate().unwrap();
+        assert_eq!(preset("Safe"), Some(layout.clone()));
+        assert!(preset("erc4626").is_none());
+
+        let threshold = EthereumKeyResolver.resolve(&layout, "threshold").unwrap();
+        let mut slot = [0u8; 32];
+        slot[31] = 4;
+        assert_eq!(threshold.key, Key::Fixed(slot));
+
+        // The first owner hangs off the sentinel
+        let first_owner = EthereumKeyResolver.resolve(&layout, "owners[0x0000000000000000000000000000000000000001]").unwrap();
+        let mut preimage = [0u8; 64];
+        preimage[12..32].copy_from_slice(&SENTINEL);
+        preimage[63] = 2;
+        assert_eq!(first_owner.key, Key::Fixed(keccak256(&preimage)));
+    }
+
+    #[test]
+    fn test_token_layouts_resolve() {
+        for name in PRESETS {
+            preset(name).unwrap().validate().unwrap();
+        }
+        assert_eq!(preset("ERC-20"), Some(erc20()));
+
+        let holder = "0x742d35cc6634c0532925a3b844bc454e4438f44e";
+        let mut preimage = [0u8; 64];
+        preimage[12..32].copy_from_slice(&hex::decode(&holder[2..]).unwrap());
+        let balance = EthereumKeyResolver.resolve(&erc20(), &format!("_balances[{}]", holder)).unwrap();
+        assert_eq!(balance.key, Key::Fixed(keccak256(&preimage)));
+
+        // Token 1's owner sits at keccak256(1 ‖ 2)
+        let mut preimage = [0u8; 64];
+        preimage[31] = 1;
+        preimage[63] = 2;
+        let owner = EthereumKeyResolver.resolve(&erc721(), "_owners[1]").unwrap();
+        assert_eq!(owner.key, Key::Fixed(keccak256(&preimage)));
+        assert_eq!(owner.field_size, Some(20));
+
+        let approval = format!("_operatorApprovals[{}][{}]", holder, holder);
+        assert!(EthereumKeyResolver.resolve(&erc1155(), &approval).is_ok());
+
+        // The proxy's aggregator is packed after the phase id
+        let aggregator = EthereumKeyResolver.resolve(&chainlink_proxy(), "currentPhase.aggregator").unwrap();
+        assert_eq!((aggregator.offset, aggregator.field_size), (Some(2), Some(20)));
+    }
+
+    #[test]
+    fn test_safe_slots() {
+        assert_eq!(SafeSlot::FallbackHandler.slot(), keccak256(b"fallback_manager.handler.address"));
+        assert_eq!(SafeSlot::Guard.slot(), keccak256(b"guard_manager.guard.address"));
+
+        let layout = safe();
+        let guard = EthereumKeyResolver.resolve(&layout, "safe.guard").unwrap();
+        assert_eq!(guard.key, Key::Fixed(SafeSlot::Guard.slot()));
+        assert_eq!(guard.field_size, Some(20));
+        assert_eq!(SafeSlot::from_query(" safe.fallbackHandler "), Some(SafeSlot::FallbackHandler));
+    }
+}
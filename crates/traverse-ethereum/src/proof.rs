@@ -2,11 +2,24 @@
 //!
 //! This module provides functionality to fetch storage proofs from Ethereum nodes
 //! using the standard `eth_getProof` RPC method with selective alloy imports.
+//!
+//! [`EthereumProofFetcher::fetch_proof`] returns the whole EIP-1186 response:
+//! the account proof, nonce, balance, storage and code hashes as well as the
+//! storage proofs, so consumers can check the account against a block's state
+//! root before trusting the storage root the storage proofs hang from.
+//!
+//! In archive mode ([`EthereumProofFetcher::at_block`]) every fetch is pinned to
+//! a historical block, and [`EthereumProofFetcher::fetch_archive_proof`] checks
+//! the node really served that block's state before returning the proof with
+//! the block hash to anchor it to.
 
 use traverse_core::{
     ProofFetcher, SemanticStorageProof, StorageSemantics, TraverseError, ZeroSemantics,
 };
 
+use crate::cache::ProofCache;
+use crate::transport::{RetryPolicy, RpcTransport};
+
 #[cfg(feature = "ethereum")]
 use {
     reqwest,
@@ -24,6 +37,10 @@ use {
 ///
 /// - `rpc_url`: The HTTP(S) endpoint of an Ethereum node (e.g., Infura, Alchemy)
 /// - `contract_address`: The Ethereum address of the contract to query
+/// - `archive_block`: A historical block to pin fetches to, which needs an archive node
+/// - `fallback_urls`, `retry`: Endpoints to fail over to and how transient
+///   failures are retried, see [`RpcTransport`]
+/// - `cache`: An on-disk [`ProofCache`] of earlier responses
 ///
 /// # Usage
 ///
@@ -31,10 +48,10 @@ use {
 /// use traverse_ethereum::EthereumProofFetcher;
 /// use traverse_core::ProofFetcher;
 ///
-/// let fetcher = EthereumProofFetcher {
-///     rpc_url: "https://mainnet.infura.io/v3/YOUR_PROJECT_ID".to_string(),
-///     contract_address: "0x742d35Cc6634C0532925a3b8D97C2e0D8b2D9C".to_string(),
-/// };
+/// let fetcher = EthereumProofFetcher::new(
+///     "https://mainnet.infura.io/v3/YOUR_PROJECT_ID",
+///     "0x742d35Cc6634C0532925a3b8D97C2e0D8b2D9C",
+/// );
 ///
 /// let key = [0u8; 32]; // Your storage key
 /// let payload = fetcher.fetch(&key)?;
@@ -44,11 +61,239 @@ use {
 ///
 /// Requires an Ethereum node that supports the `eth_getProof` RPC method.
 /// Most modern Ethereum clients (geth, erigon, etc.) support this method.
+#[derive(Debug, Clone)]
 pub struct EthereumProofFetcher {
     /// RPC endpoint URL for the Ethereum node
     pub rpc_url: String,
     /// Contract address to query (with or without 0x prefix)
     pub contract_address: String,
+    /// Historical block every fetch is pinned to; `None` reads `latest`
+    pub archive_block: Option<u64>,
+    /// Endpoints tried in order after `rpc_url` fails
+    pub fallback_urls: Vec<String>,
+    pub retry: RetryPolicy,
+    /// Where [`EthereumProofFetcher::fetch_proofs`] keeps responses for reuse
+    pub cache: Option<ProofCache>,
+}
+
+/// An `eth_getProof` response: an account and some of its storage, with proofs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Eip1186Proof {
+    pub address: [u8; 20],
+    pub nonce: u64,
+    /// Balance in wei (big-endian)
+    pub balance: [u8; 32],
+    /// Root of the account's storage trie, which the storage proofs prove against
+    pub storage_hash: [u8; 32],
+    pub code_hash: [u8; 32],
+    /// RLP trie nodes from the state root to the account leaf
+    pub account_proof: Vec<Vec<u8>>,
+    pub storage_proofs: Vec<Eip1186StorageProof>,
+}
+
+/// Header fields of the block an archive proof was taken at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinnedBlock {
+    pub number: u64,
+    /// Block hash to anchor the proof to, e.g. against a light client or `BLOCKHASH`
+    pub hash: [u8; 32],
+    pub state_root: [u8; 32],
+}
+
+/// An [`Eip1186Proof`] checked against the state root of the block it was taken at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveProof {
+    pub block: PinnedBlock,
+    pub proof: Eip1186Proof,
+}
+
+impl ArchiveProof {
+    /// JSON with the block's number, hash and state root next to the `eth_getProof` response
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "block": {
+                "number": self.block.number,
+                "hash": format!("0x{}", hex::encode(self.block.hash)),
+                "state_root": format!("0x{}", hex::encode(self.block.state_root)),
+            },
+            "proof": self.proof.to_response(),
+        })
+    }
+}
+
+/// [`ArchiveProof`]s of the same keys at several blocks, oldest first
+///
+/// Circuits proving a value's history or a time-weighted average take one
+/// point per sampled block, each verified against its own state root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeSeriesProof {
+    pub points: Vec<ArchiveProof>,
+}
+
+impl TimeSeriesProof {
+    /// Value of `key` at each block, oldest first, skipping blocks that do not prove it
+    pub fn values(&self, key: &[u8; 32]) -> Vec<(u64, [u8; 32])> {
+        self.points
+            .iter()
+            .filter_map(|point| {
+                let slot = point.proof.storage_proofs.iter().find(|slot| slot.key == *key)?;
+                Some((point.block.number, slot.value))
+            })
+            .collect()
+    }
+
+    /// JSON with each block's [`ArchiveProof::to_json`], oldest first
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "points": self.points.iter().map(ArchiveProof::to_json).collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// One storage slot of an [`Eip1186Proof`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Eip1186StorageProof {
+    pub key: [u8; 32],
+    pub value: [u8; 32],
+    /// RLP trie nodes from the storage root to the slot's leaf
+    pub proof: Vec<Vec<u8>>,
+}
+
+/// Bytes of a `0x` hex string
+fn hex_bytes(value: &serde_json::Value, field: &str) -> Result<Vec<u8>, TraverseError> {
+    let text = value
+        .as_str()
+        .ok_or_else(|| TraverseError::Serialization(format!("Missing {} in eth_getProof response", field)))?;
+    let digits = text.strip_prefix("0x").unwrap_or(text);
+    // Quantities drop leading zeros, so may have an odd number of digits
+    let padded = if digits.len() % 2 == 1 { format!("0{}", digits) } else { digits.to_string() };
+    hex::decode(padded).map_err(|e| TraverseError::Serialization(format!("Invalid hex in {}: {}", field, e)))
+}
+
+/// A hex quantity or word, left-padded to `N` bytes
+fn hex_fixed<const N: usize>(value: &serde_json::Value, field: &str) -> Result<[u8; N], TraverseError> {
+    let bytes = hex_bytes(value, field)?;
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    let bytes = &bytes[start.min(bytes.len().saturating_sub(N))..];
+    if bytes.len() > N {
+        return Err(TraverseError::Serialization(format!("{} is longer than {} bytes", field, N)));
+    }
+    let mut out = [0u8; N];
+    out[N - bytes.len()..].copy_from_slice(bytes);
+    Ok(out)
+}
+
+fn hex_nodes(value: &serde_json::Value, field: &str) -> Result<Vec<Vec<u8>>, TraverseError> {
+    value
+        .as_array()
+        .ok_or_else(|| TraverseError::Serialization(format!("Missing {} in eth_getProof response", field)))?
+        .iter()
+        .map(|node| hex_bytes(node, field))
+        .collect()
+}
+
+impl Eip1186Proof {
+    /// Decode the `result` of an `eth_getProof` call
+    pub fn from_response(result: &serde_json::Value) -> Result<Self, TraverseError> {
+        let nonce = u64::from_be_bytes(hex_fixed(&result["nonce"], "nonce")?);
+        let storage_proofs = result["storageProof"]
+            .as_array()
+            .ok_or_else(|| TraverseError::Serialization("Missing storageProof in eth_getProof response".to_string()))?
+            .iter()
+            .map(|item| {
+                Ok(Eip1186StorageProof {
+                    key: hex_fixed(&item["key"], "storage key")?,
+                    value: hex_fixed(&item["value"], "storage value")?,
+                    proof: hex_nodes(&item["proof"], "storage proof")?,
+                })
+            })
+            .collect::<Result<_, TraverseError>>()?;
+
+        Ok(Self {
+            address: hex_fixed(&result["address"], "address")?,
+            nonce,
+            balance: hex_fixed(&result["balance"], "balance")?,
+            storage_hash: hex_fixed(&result["storageHash"], "storageHash")?,
+            code_hash: hex_fixed(&result["codeHash"], "codeHash")?,
+            account_proof: hex_nodes(&result["accountProof"], "accountProof")?,
+            storage_proofs,
+        })
+    }
+
+    /// Encode in the shape of an `eth_getProof` result, the inverse of [`Eip1186Proof::from_response`]
+    pub fn to_response(&self) -> serde_json::Value {
+        let word = |bytes: &[u8]| format!("0x{}", hex::encode(bytes));
+        let nodes = |nodes: &[Vec<u8>]| nodes.iter().map(|node| word(node)).collect::<Vec<_>>();
+        serde_json::json!({
+            "address": word(&self.address),
+            "nonce": format!("0x{:x}", self.nonce),
+            "balance": word(&self.balance),
+            "storageHash": word(&self.storage_hash),
+            "codeHash": word(&self.code_hash),
+            "accountProof": nodes(&self.account_proof),
+            "storageProof": self.storage_proofs.iter().map(|slot| serde_json::json!({
+                "key": word(&slot.key),
+                "value": word(&slot.value),
+                "proof": nodes(&slot.proof),
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    /// Verify the account against `state_root` and every slot against its storage root
+    ///
+    /// The account's proven nonce, balance, storage and code hashes must be
+    /// the ones the response claims, and each storage value must be the one
+    /// proven under `storage_hash`.
+    #[cfg(feature = "ethereum")]
+    pub fn verify(&self, state_root: &[u8; 32]) -> Result<(), TraverseError> {
+        use crate::mpt::{verify_account_proof, verify_storage_proof, AccountState};
+
+        let proven = verify_account_proof(state_root, &self.address, &self.account_proof)?.ok_or_else(|| {
+            TraverseError::Validation(format!("Account 0x{} is not in the state trie", hex::encode(self.address)))
+        })?;
+        let claimed = AccountState {
+            nonce: self.nonce,
+            balance: self.balance,
+            storage_root: self.storage_hash,
+            code_hash: self.code_hash,
+        };
+        if proven != claimed {
+            return Err(TraverseError::Validation(format!(
+                "Account 0x{} does not match its proof",
+                hex::encode(self.address)
+            )));
+        }
+
+        for slot in &self.storage_proofs {
+            if verify_storage_proof(&self.storage_hash, &slot.key, &slot.proof)? != slot.value {
+                return Err(TraverseError::Validation(format!(
+                    "Storage value at 0x{} does not match its proof",
+                    hex::encode(slot.key)
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Encoded size of the account and storage proofs
+    pub fn proof_size(&self) -> usize {
+        self.account_proof.iter().chain(self.storage_proofs.iter().flat_map(|s| &s.proof)).map(Vec::len).sum()
+    }
+}
+
+/// Group slots per contract, in order of first appearance, dropping repeated keys
+#[cfg(feature = "ethereum")]
+fn group_slots(slots: &[(&str, [u8; 32])]) -> Vec<(String, Vec<[u8; 32]>)> {
+    let mut groups: Vec<(String, Vec<[u8; 32]>)> = Vec::new();
+    for (address, key) in slots {
+        let address = address.to_lowercase();
+        match groups.iter_mut().find(|(existing, _)| *existing == address) {
+            Some((_, keys)) if keys.contains(key) => {}
+            Some((_, keys)) => keys.push(*key),
+            None => groups.push((address, vec![*key])),
+        }
+    }
+    groups
 }
 
 impl ProofFetcher for EthereumProofFetcher {
@@ -89,14 +334,9 @@ impl ProofFetcher for EthereumProofFetcher {
             Ok(_handle) => {
                 // We're already in a tokio runtime, spawn the async work in a separate thread
                 let key_copy = *key;
-                let rpc_url = self.rpc_url.clone();
-                let contract_address = self.contract_address.clone();
+                let fetcher = self.clone();
 
                 let result = std::thread::spawn(move || {
-                    let fetcher = EthereumProofFetcher {
-                        rpc_url,
-                        contract_address,
-                    };
                     let rt = tokio::runtime::Runtime::new().unwrap();
                     rt.block_on(fetcher.fetch_async(key_copy, zero_semantics))
                 })
@@ -116,7 +356,331 @@ impl ProofFetcher for EthereumProofFetcher {
 }
 
 impl EthereumProofFetcher {
+    /// Fetcher for `contract_address` reading the latest block
+    pub fn new(rpc_url: impl Into<String>, contract_address: impl Into<String>) -> Self {
+        Self {
+            rpc_url: rpc_url.into(),
+            contract_address: contract_address.into(),
+            archive_block: None,
+            fallback_urls: Vec::new(),
+            retry: RetryPolicy::default(),
+            cache: None,
+        }
+    }
+
+    /// Reuse and store `fetch_proofs` responses in `cache`
+    pub fn with_cache(mut self, cache: ProofCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Fail over to `fallback_urls`, in order, when `rpc_url` fails
+    pub fn with_fallbacks(mut self, fallback_urls: Vec<String>) -> Self {
+        self.fallback_urls = fallback_urls;
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Transport over `rpc_url` and the fallbacks
+    pub fn transport(&self) -> RpcTransport {
+        RpcTransport::new(self.rpc_url.as_str()).with_fallbacks(&self.fallback_urls).with_retry(self.retry)
+    }
+
+    pub(crate) async fn rpc(&self, client: &reqwest::Client, method: &str, params: serde_json::Value) -> Result<serde_json::Value, TraverseError> {
+        self.transport().call(client, method, params).await
+    }
+
+    /// Send `method` once per entry of `params` as JSON-RPC batches, returning results in order
+    #[cfg(feature = "ethereum")]
+    pub(crate) async fn rpc_batch(
+        &self,
+        client: &reqwest::Client,
+        method: &str,
+        params: &[serde_json::Value],
+    ) -> Result<Vec<serde_json::Value>, TraverseError> {
+        // Providers cap batch sizes; 100 calls is within every common limit
+        const BATCH_SIZE: usize = 100;
+
+        let transport = self.transport();
+        let mut results = Vec::with_capacity(params.len());
+        for chunk in params.chunks(BATCH_SIZE) {
+            let batch: Vec<serde_json::Value> = chunk
+                .iter()
+                .enumerate()
+                .map(|(id, params)| serde_json::json!({ "jsonrpc": "2.0", "method": method, "params": params, "id": id }))
+                .collect();
+            let response = transport.send(client, method, &serde_json::Value::Array(batch)).await?;
+
+            let mut responses = match response {
+                serde_json::Value::Array(responses) if responses.len() == chunk.len() => responses,
+                other => {
+                    return Err(TraverseError::external_service(format!(
+                        "{} batch of {} calls got {}",
+                        method,
+                        chunk.len(),
+                        other.get("error").map_or_else(|| "a short response".to_string(), ToString::to_string)
+                    )))
+                }
+            };
+            // Batch responses may come back in any order
+            responses.sort_by_key(|response| response["id"].as_u64());
+            for response in responses {
+                if let Some(error) = response.get("error") {
+                    return Err(TraverseError::external_service(format!("{} failed: {}", method, error)));
+                }
+                results.push(response["result"].clone());
+            }
+        }
+        Ok(results)
+    }
+
+    /// Pin every fetch to historical block `number`
+    ///
+    /// Full nodes only keep recent state, so this needs an archive node for
+    /// blocks more than a few minutes old.
+    pub fn at_block(mut self, number: u64) -> Self {
+        self.archive_block = Some(number);
+        self
+    }
+
+    /// Block parameter for RPC calls: the archive block, or `latest`
+    pub(crate) fn block_param(&self) -> String {
+        match self.archive_block {
+            Some(number) => format!("0x{:x}", number),
+            None => "latest".to_string(),
+        }
+    }
+
+    /// Fetch and check the EIP-1186 proof of `keys` at the archive block
+    ///
+    /// The header must be the requested block, the proof is requested by
+    /// that block's hash (EIP-1898) so a reorg between the calls cannot mix
+    /// blocks, and the account proof must verify against the header's state
+    /// root. A node without the block's state fails with an error naming
+    /// the block rather than a bare RPC error.
+    #[cfg(feature = "ethereum")]
+    #[cfg_attr(feature = "telemetry", tracing::instrument(name = "ethereum_fetch_archive_proof", skip(self, keys)))]
+    pub async fn fetch_archive_proof(&self, keys: &[[u8; 32]]) -> Result<ArchiveProof, TraverseError> {
+        let number = self.archive_block.ok_or_else(|| {
+            TraverseError::Configuration("Archive proofs need a block; set one with at_block".to_string())
+        })?;
+        let unavailable = |e: TraverseError| {
+            TraverseError::external_service(format!(
+                "Node did not serve block {} ({}); historical state needs an archive node",
+                number, e
+            ))
+        };
+
+        let client = reqwest::Client::new();
+        let header = self
+            .rpc(&client, "eth_getBlockByNumber", serde_json::json!([self.block_param(), false]))
+            .await
+            .map_err(unavailable)?;
+        let served = u64::from_be_bytes(hex_fixed(&header["number"], "block number")?);
+        if served != number {
+            return Err(TraverseError::Validation(format!("Asked for block {}, node served block {}", number, served)));
+        }
+        let block = PinnedBlock {
+            number,
+            hash: hex_fixed(&header["hash"], "block hash")?,
+            state_root: hex_fixed(&header["stateRoot"], "stateRoot")?,
+        };
+
+        let keys: Vec<String> = keys.iter().map(|key| format!("0x{}", hex::encode(key))).collect();
+        let block_ref = serde_json::json!({ "blockHash": format!("0x{}", hex::encode(block.hash)) });
+        let result = self
+            .rpc(&client, "eth_getProof", serde_json::json!([self.contract_address, keys, block_ref]))
+            .await
+            .map_err(unavailable)?;
+        let proof = Eip1186Proof::from_response(&result)?;
+        proof.verify(&block.state_root)?;
+
+        #[cfg(feature = "telemetry")]
+        traverse_core::telemetry::record_proof_size("ethereum", "archive", proof.proof_size());
+        Ok(ArchiveProof { block, proof })
+    }
+
+    /// Fetch and check the EIP-1186 proof of `keys` at each of `blocks`
+    ///
+    /// Blocks are sorted and deduplicated. Headers are fetched in one batch
+    /// and proofs in another, each proof requested by its block's hash and
+    /// verified against that block's state root as in
+    /// [`EthereumProofFetcher::fetch_archive_proof`]. Blocks older than a
+    /// full node keeps need an archive node.
+    #[cfg(feature = "ethereum")]
+    #[cfg_attr(feature = "telemetry", tracing::instrument(name = "ethereum_fetch_time_series", skip(self, keys, blocks)))]
+    pub async fn fetch_time_series(&self, keys: &[[u8; 32]], blocks: &[u64]) -> Result<TimeSeriesProof, TraverseError> {
+        let mut blocks = blocks.to_vec();
+        blocks.sort_unstable();
+        blocks.dedup();
+        if blocks.is_empty() {
+            return Err(TraverseError::Configuration("Time series proofs need at least one block".to_string()));
+        }
+
+        let client = reqwest::Client::new();
+        let header_calls: Vec<serde_json::Value> =
+            blocks.iter().map(|number| serde_json::json!([format!("0x{:x}", number), false])).collect();
+        let headers = self.rpc_batch(&client, "eth_getBlockByNumber", &header_calls).await?;
+        let mut pinned = Vec::with_capacity(blocks.len());
+        for (number, header) in blocks.iter().zip(&headers) {
+            if header.is_null() {
+                return Err(TraverseError::external_service(format!("Node did not serve block {}", number)));
+            }
+            let served = u64::from_be_bytes(hex_fixed(&header["number"], "block number")?);
+            if served != *number {
+                return Err(TraverseError::Validation(format!("Asked for block {}, node served block {}", number, served)));
+            }
+            pinned.push(PinnedBlock {
+                number: *number,
+                hash: hex_fixed(&header["hash"], "block hash")?,
+                state_root: hex_fixed(&header["stateRoot"], "stateRoot")?,
+            });
+        }
+
+        let keys: Vec<String> = keys.iter().map(|key| format!("0x{}", hex::encode(key))).collect();
+        let proof_calls: Vec<serde_json::Value> = pinned
+            .iter()
+            .map(|block| {
+                let block_ref = serde_json::json!({ "blockHash": format!("0x{}", hex::encode(block.hash)) });
+                serde_json::json!([self.contract_address, keys, block_ref])
+            })
+            .collect();
+        let results = self.rpc_batch(&client, "eth_getProof", &proof_calls).await.map_err(|e| {
+            TraverseError::external_service(format!(
+                "Node did not serve blocks {} to {} ({}); historical state needs an archive node",
+                blocks[0],
+                blocks[blocks.len() - 1],
+                e
+            ))
+        })?;
+
+        let mut points = Vec::with_capacity(pinned.len());
+        for (block, result) in pinned.into_iter().zip(&results) {
+            let proof = Eip1186Proof::from_response(result)?;
+            proof
+                .verify(&block.state_root)
+                .map_err(|e| TraverseError::Validation(format!("Proof at block {}: {}", block.number, e)))?;
+            points.push(ArchiveProof { block, proof });
+        }
+
+        #[cfg(feature = "telemetry")]
+        traverse_core::telemetry::record_proof_size(
+            "ethereum",
+            "time_series",
+            points.iter().map(|point| point.proof.proof_size()).sum(),
+        );
+        Ok(TimeSeriesProof { points })
+    }
+
+    /// Fetch proofs of storage slots across contracts in as few round trips as possible
+    ///
+    /// `slots` pairs contract addresses with keys. Each contract's keys go in
+    /// one `eth_getProof` call, or as many as `adapter` allows keys per call,
+    /// and all calls are sent as JSON-RPC batches. A tag `block` is resolved
+    /// to a number first so every call reads the same block. Returns one proof
+    /// per contract, in order of first appearance, with its storage proofs in
+    /// request order and repeated keys proven once. With a [`ProofCache`],
+    /// cached slots are not fetched again and fetched ones are cached.
+    #[cfg(feature = "ethereum")]
+    #[cfg_attr(feature = "telemetry", tracing::instrument(name = "ethereum_fetch_proofs", skip(self, slots, adapter)))]
+    pub async fn fetch_proofs(
+        &self,
+        slots: &[(&str, [u8; 32])],
+        block: &str,
+        adapter: &crate::EvmChainAdapter,
+    ) -> Result<Vec<Eip1186Proof>, TraverseError> {
+        let client = reqwest::Client::new();
+        let block = match block.strip_prefix("0x") {
+            Some(_) => block.to_string(),
+            None => {
+                let header = self.rpc(&client, "eth_getBlockByNumber", serde_json::json!([block, false])).await?;
+                format!("0x{:x}", u64::from_be_bytes(hex_fixed(&header["number"], "block number")?))
+            }
+        };
+
+        let chain_id = adapter.profile.chain_id;
+        let number = u64::from_str_radix(&block[2..], 16).ok();
+        let cache = self.cache.as_ref().zip(number);
+
+        let groups = group_slots(slots);
+        let mut proofs: Vec<Option<Eip1186Proof>> = vec![None; groups.len()];
+        let mut calls = Vec::new();
+        let mut owners = Vec::new();
+        for (index, (address, keys)) in groups.iter().enumerate() {
+            proofs[index] = cache.and_then(|(cache, number)| cache.get(chain_id, address, number, keys));
+            let missing: Vec<[u8; 32]> = match &proofs[index] {
+                Some(cached) => keys.iter().filter(|key| !cached.storage_proofs.iter().any(|slot| slot.key == **key)).copied().collect(),
+                None => keys.clone(),
+            };
+            for batch in adapter.proof_key_batches(&missing) {
+                let batch_keys: Vec<String> = batch.iter().map(|key| format!("0x{}", hex::encode(key))).collect();
+                calls.push(serde_json::json!([address, batch_keys, block]));
+                owners.push((index, batch.len()));
+            }
+        }
+
+        for ((index, expected), result) in owners.into_iter().zip(self.rpc_batch(&client, "eth_getProof", &calls).await?) {
+            let part = Eip1186Proof::from_response(&result)?;
+            if part.storage_proofs.len() != expected {
+                return Err(TraverseError::external_service(format!(
+                    "eth_getProof for {} returned {} storage proofs for {} keys",
+                    groups[index].0,
+                    part.storage_proofs.len(),
+                    expected
+                )));
+            }
+            if let Some((cache, number)) = cache {
+                cache.put(chain_id, number, &part)?;
+            }
+            match &mut proofs[index] {
+                Some(proof) => proof.storage_proofs.extend(part.storage_proofs),
+                empty => *empty = Some(part),
+            }
+        }
+        // Cached and fetched storage proofs back in request order
+        for (proof, (_, keys)) in proofs.iter_mut().zip(&groups) {
+            if let Some(proof) = proof {
+                proof.storage_proofs.sort_by_key(|slot| keys.iter().position(|key| *key == slot.key));
+            }
+        }
+
+        #[cfg(feature = "telemetry")]
+        traverse_core::telemetry::record_proof_size(
+            "ethereum",
+            "account",
+            proofs.iter().flatten().map(Eip1186Proof::proof_size).sum(),
+        );
+        Ok(proofs.into_iter().flatten().collect())
+    }
+
+    /// Fetch the EIP-1186 proof of the contract and `keys` at `block`
+    ///
+    /// `block` is a tag such as `latest` or a hex block number. The result
+    /// is not verified; check it with [`Eip1186Proof::verify`] against the
+    /// block's state root.
+    #[cfg_attr(feature = "telemetry", tracing::instrument(name = "ethereum_fetch_eip1186_proof", skip(self, keys)))]
+    pub async fn fetch_proof(&self, keys: &[[u8; 32]], block: &str) -> Result<Eip1186Proof, TraverseError> {
+        let client = reqwest::Client::new();
+        let keys: Vec<String> = keys.iter().map(|key| format!("0x{}", hex::encode(key))).collect();
+        let result = self
+            .rpc(&client, "eth_getProof", serde_json::json!([self.contract_address, keys, block]))
+            .await?;
+        let proof = Eip1186Proof::from_response(&result)?;
+
+        #[cfg(feature = "telemetry")]
+        traverse_core::telemetry::record_proof_size("ethereum", "account", proof.proof_size());
+        Ok(proof)
+    }
+
     /// Async implementation of storage proof fetching
+    #[cfg_attr(
+        feature = "telemetry",
+        tracing::instrument(name = "ethereum_fetch_proof", skip_all, fields(key = %hex::encode(key)))
+    )]
     async fn fetch_async(
         &self,
         key: [u8; 32],
@@ -128,30 +692,11 @@ impl EthereumProofFetcher {
             .build()
             .map_err(|e| TraverseError::external_service(format!("Failed to create HTTP client: {}", e)))?;
         let key_hex = format!("0x{}", hex::encode(key));
-        
-        let rpc_request = serde_json::json!({
-            "jsonrpc": "2.0",
-            "method": "eth_getStorageAt",
-            "params": [self.contract_address, key_hex, "latest"],
-            "id": 1
-        });
-        
-        let response = client
-            .post(&self.rpc_url)
-            .json(&rpc_request)
-            .send()
-            .await
-            .map_err(|e| TraverseError::external_service(format!("RPC request failed: {}", e)))?;
-        
-        let rpc_response: serde_json::Value = response
-            .json()
-            .await
-            .map_err(|e| TraverseError::external_service(format!("Failed to parse RPC response: {}", e)))?;
-        
-        // Extract storage value from response
-        let value_str = rpc_response
-            .get("result")
-            .and_then(|v| v.as_str())
+        let result = self
+            .rpc(&client, "eth_getStorageAt", serde_json::json!([self.contract_address, key_hex, self.block_param()]))
+            .await?;
+        let value_str = result
+            .as_str()
             .ok_or_else(|| TraverseError::external_service("No result in RPC response".to_string()))?;
         
         // Parse hex value to bytes
@@ -172,18 +717,23 @@ impl EthereumProofFetcher {
         
         // TODO: In a full implementation, we would also fetch merkle proofs
         // For now, return proof without merkle path
-        Ok(SemanticStorageProof {
+        let proof = SemanticStorageProof {
             key,
             value,
             proof: Vec::new(), // Would contain merkle proof in full implementation
             semantics,
-        })
+        };
+        #[cfg(feature = "telemetry")]
+        traverse_core::telemetry::record_proof_size("ethereum", "storage", proof.proof.len());
+        Ok(proof)
     }
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
+    #[cfg(feature = "ethereum")]
+    use traverse_core::hash::keccak256;
     use tokio;
 
     #[test]
@@ -191,6 +741,10 @@ mod tests {
         let fetcher = EthereumProofFetcher {
             rpc_url: "http://localhost:8545".to_string(),
             contract_address: "0x1234567890123456789012345678901234567890".to_string(),
+            archive_block: None,
+            fallback_urls: Vec::new(),
+            retry: RetryPolicy::NONE,
+            cache: None,
         };
 
         assert_eq!(fetcher.rpc_url, "http://localhost:8545");
@@ -203,6 +757,10 @@ mod tests {
         let fetcher = EthereumProofFetcher {
             rpc_url: "http://localhost:8545".to_string(),
             contract_address: "0x1234567890123456789012345678901234567890".to_string(),
+            archive_block: None,
+            fallback_urls: Vec::new(),
+            retry: RetryPolicy::NONE,
+            cache: None,
         };
 
         let key = [1u8; 32];
@@ -235,6 +793,10 @@ mod tests {
         let fetcher = EthereumProofFetcher {
             rpc_url: "http://invalid-rpc-url.test".to_string(),
             contract_address: "0x1234567890123456789012345678901234567890".to_string(),
+            archive_block: None,
+            fallback_urls: Vec::new(),
+            retry: RetryPolicy::NONE,
+            cache: None,
         };
 
         let key = [1u8; 32];
@@ -253,6 +815,10 @@ mod tests {
         let fetcher = EthereumProofFetcher {
             rpc_url: "http://localhost:8545".to_string(),
             contract_address: "0x1234567890123456789012345678901234567890".to_string(),
+            archive_block: None,
+            fallback_urls: Vec::new(),
+            retry: RetryPolicy::NONE,
+            cache: None,
         };
 
         let key = [1u8; 32];
@@ -262,4 +828,189 @@ mod tests {
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("Ethereum support not enabled"));
     }
+
+    /// A trie holding one value, as its root and the proof of that value
+    #[cfg(feature = "ethereum")]
+    fn single_leaf_trie(key: &[u8], value: Vec<u8>) -> ([u8; 32], Vec<u8>) {
+        // The leaf holds the whole even-length path: flag nibble 2, padding 0
+        let mut path = vec![0x20];
+        path.extend_from_slice(&keccak256(key));
+        let mut leaf = rlp::RlpStream::new_list(2);
+        leaf.append(&path);
+        leaf.append(&value);
+        let leaf = leaf.out().to_vec();
+        (keccak256(&leaf), leaf)
+    }
+
+    /// A state trie holding only `address`, as its root and the account's proof without slots
+    #[cfg(feature = "ethereum")]
+    pub(crate) fn lone_account_proof(address: [u8; 20]) -> ([u8; 32], Eip1186Proof) {
+        let mut account = rlp::RlpStream::new_list(4);
+        account.append(&1u64);
+        account.append(&vec![0x2au8]);
+        account.append(&crate::mpt::EMPTY_TRIE_ROOT.to_vec());
+        account.append(&vec![0xccu8; 32]);
+        let (state_root, account_node) = single_leaf_trie(&address, account.out().to_vec());
+
+        let mut response = response(address, crate::mpt::EMPTY_TRIE_ROOT, &account_node, &[]);
+        response["storageProof"] = serde_json::json!([]);
+        (state_root, Eip1186Proof::from_response(&response).unwrap())
+    }
+
+    fn response(address: [u8; 20], storage_hash: [u8; 32], account_node: &[u8], slot_node: &[u8]) -> serde_json::Value {
+        serde_json::json!({
+            "address": format!("0x{}", hex::encode(address)),
+            "nonce": "0x1",
+            "balance": "0x2a",
+            "storageHash": format!("0x{}", hex::encode(storage_hash)),
+            "codeHash": format!("0x{}", hex::encode([0xcc; 32])),
+            "accountProof": [format!("0x{}", hex::encode(account_node))],
+            "storageProof": [{
+                "key": "0x0",
+                "value": "0x3e8",
+                "proof": [format!("0x{}", hex::encode(slot_node))]
+            }]
+        })
+    }
+
+    #[test]
+    fn test_decode_eip1186_response() {
+        let proof = Eip1186Proof::from_response(&response([0xab; 20], [0x11; 32], &[0xc0], &[0xc1])).unwrap();
+        assert_eq!(proof.address, [0xab; 20]);
+        assert_eq!(proof.nonce, 1);
+        assert_eq!(proof.balance[31], 0x2a);
+        assert_eq!(proof.storage_hash, [0x11; 32]);
+        assert_eq!(proof.account_proof, vec![vec![0xc0]]);
+        // Quantity-encoded keys and values are padded to words
+        assert_eq!(proof.storage_proofs[0].key, [0u8; 32]);
+        assert_eq!(proof.storage_proofs[0].value[30..], [0x03, 0xe8]);
+        assert_eq!(proof.proof_size(), 2);
+
+        let mut missing = response([0xab; 20], [0x11; 32], &[0xc0], &[0xc1]);
+        missing.as_object_mut().unwrap().remove("accountProof");
+        assert!(Eip1186Proof::from_response(&missing).is_err());
+    }
+
+    #[cfg(feature = "ethereum")]
+    #[test]
+    fn test_verify_eip1186_proof() {
+        let address = [0xab; 20];
+        let (storage_hash, slot_node) = single_leaf_trie(&[0u8; 32], rlp::encode(&vec![0x03u8, 0xe8]).to_vec());
+
+        let mut account = rlp::RlpStream::new_list(4);
+        account.append(&1u64);
+        account.append(&vec![0x2au8]);
+        account.append(&storage_hash.to_vec());
+        account.append(&vec![0xccu8; 32]);
+        let (state_root, account_node) = single_leaf_trie(&address, account.out().to_vec());
+
+        let proof = Eip1186Proof::from_response(&response(address, storage_hash, &account_node, &slot_node)).unwrap();
+        proof.verify(&state_root).unwrap();
+        assert!(proof.verify(&[0u8; 32]).is_err());
+
+        // A balance the account proof does not prove
+        let mut inflated = proof.clone();
+        inflated.balance[31] = 0xff;
+        assert!(inflated.verify(&state_root).is_err());
+
+        // A storage value the storage proof does not prove
+        let mut forged = proof;
+        forged.storage_proofs[0].value[31] = 0;
+        assert!(forged.verify(&state_root).is_err());
+    }
+
+    #[cfg(feature = "ethereum")]
+    #[test]
+    fn test_archive_proof_json() {
+        let fetcher = EthereumProofFetcher::new("http://localhost:8545", "0xab").at_block(17_000_000);
+        assert_eq!(fetcher.block_param(), "0x1036640");
+        assert_eq!(EthereumProofFetcher::new("http://localhost:8545", "0xab").block_param(), "latest");
+
+        let (storage_hash, slot_node) = single_leaf_trie(&[0u8; 32], rlp::encode(&vec![0x03u8, 0xe8]).to_vec());
+        let proof = Eip1186Proof::from_response(&response([0xab; 20], storage_hash, &[0xc0], &slot_node)).unwrap();
+        assert_eq!(Eip1186Proof::from_response(&proof.to_response()).unwrap(), proof);
+
+        let archive = ArchiveProof {
+            block: PinnedBlock { number: 17_000_000, hash: [0x11; 32], state_root: [0x22; 32] },
+            proof,
+        };
+        let json = archive.to_json();
+        assert_eq!(json["block"]["hash"], format!("0x{}", "11".repeat(32)));
+        assert_eq!(json["proof"]["storageHash"], format!("0x{}", hex::encode(storage_hash)));
+    }
+
+    #[cfg(feature = "ethereum")]
+    #[test]
+    fn test_time_series_values() {
+        let point = |number: u64, value: u8| {
+            let mut proof = Eip1186Proof::from_response(&response([0xab; 20], [0x11; 32], &[0xc0], &[0xc1])).unwrap();
+            proof.storage_proofs[0].value = [value; 32];
+            ArchiveProof { block: PinnedBlock { number, hash: [value; 32], state_root: [0x22; 32] }, proof }
+        };
+        let series = TimeSeriesProof { points: vec![point(100, 1), point(200, 2)] };
+        assert_eq!(series.values(&[0u8; 32]), vec![(100, [1u8; 32]), (200, [2u8; 32])]);
+        assert!(series.values(&[9u8; 32]).is_empty());
+
+        let json = series.to_json();
+        assert_eq!(json["points"][1]["block"]["number"], 200);
+    }
+
+    #[cfg(feature = "ethereum")]
+    #[tokio::test]
+    async fn test_time_series_needs_blocks() {
+        let fetcher = EthereumProofFetcher::new("http://localhost:8545", "0xab");
+        let error = fetcher.fetch_time_series(&[[0u8; 32]], &[]).await.unwrap_err();
+        assert!(matches!(error, TraverseError::Configuration(_)));
+    }
+
+    #[cfg(feature = "ethereum")]
+    #[tokio::test]
+    async fn test_archive_proof_needs_a_block() {
+        let fetcher = EthereumProofFetcher::new("http://localhost:8545", "0xab");
+        let error = fetcher.fetch_archive_proof(&[[0u8; 32]]).await.unwrap_err();
+        assert!(matches!(error, TraverseError::Configuration(_)));
+    }
+
+    #[cfg(feature = "ethereum")]
+    #[test]
+    fn test_group_slots() {
+        let groups = group_slots(&[
+            ("0xAB", [1u8; 32]),
+            ("0xcd", [1u8; 32]),
+            ("0xab", [2u8; 32]),
+            ("0xab", [1u8; 32]),
+        ]);
+        assert_eq!(
+            groups,
+            vec![("0xab".to_string(), vec![[1u8; 32], [2u8; 32]]), ("0xcd".to_string(), vec![[1u8; 32]])]
+        );
+    }
+
+    #[cfg(feature = "ethereum")]
+    #[tokio::test]
+    async fn test_fetch_proofs_from_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ProofCache::new(dir.path());
+        let slot = |key: u8| Eip1186StorageProof { key: [key; 32], value: [key; 32], proof: vec![vec![key]] };
+        let cached = Eip1186Proof {
+            address: [0xab; 20],
+            nonce: 0,
+            balance: [0u8; 32],
+            storage_hash: [0x11; 32],
+            code_hash: [0x22; 32],
+            account_proof: vec![vec![0xf8]],
+            storage_proofs: vec![slot(1), slot(2)],
+        };
+        cache.put(1, 100, &cached).unwrap();
+
+        // Nothing listens here, so every slot must come from the cache
+        let fetcher = EthereumProofFetcher::new("http://127.0.0.1:9", "").with_retry(RetryPolicy::NONE).with_cache(cache);
+        let address = format!("0x{}", "ab".repeat(20));
+        let adapter = crate::EvmChainAdapter::for_chain_id(1);
+        let proofs = fetcher.fetch_proofs(&[(&address, [2u8; 32]), (&address, [1u8; 32])], "0x64", &adapter).await.unwrap();
+        assert_eq!(proofs[0].storage_proofs, vec![slot(2), slot(1)]);
+
+        assert!(fetcher.fetch_proofs(&[(&address, [3u8; 32])], "0x64", &adapter).await.is_err());
+        assert!(fetcher.fetch_proofs(&[(&address, [1u8; 32])], "0x65", &adapter).await.is_err());
+    }
 }
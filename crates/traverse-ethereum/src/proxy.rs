@@ -0,0 +1,243 @@
+//! EIP-1967 proxy slots and implementation lookup
+//!
+//! EIP-1967 proxies keep their own state at fixed slots derived from a name,
+//! `keccak256(name) - 1`, so they cannot collide with the implementation's
+//! layout. Everything else in a proxy's storage is laid out by the
+//! implementation contract: queries are compiled against the implementation's
+//! layout and read from the proxy's address.
+//!
+//! [`EthereumKeyResolver`](crate::EthereumKeyResolver) resolves the slots by
+//! name against any layout (`eip1967.implementation`, `eip1967.admin`,
+//! `eip1967.beacon`). [`ProxyDetector`] reads them from a node to find the
+//! implementation, following a beacon when the proxy has one.
+
+use traverse_core::{DerivationStep, DerivationTrace, Key, LayoutInfo, StaticKeyPath, ZeroSemantics};
+
+#[cfg(feature = "std")]
+use traverse_core::TraverseError;
+
+/// `bytes4(keccak256("implementation()"))`, the beacon's getter
+pub const BEACON_IMPLEMENTATION_SELECTOR: [u8; 4] = [0x5c, 0x60, 0xda, 0x1b];
+
+/// One of the slots EIP-1967 reserves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Eip1967Slot {
+    /// Logic contract address, `keccak256("eip1967.proxy.implementation") - 1`
+    Implementation,
+    /// Admin address, `keccak256("eip1967.proxy.admin") - 1`
+    Admin,
+    /// Beacon contract address, `keccak256("eip1967.proxy.beacon") - 1`
+    Beacon,
+}
+
+impl Eip1967Slot {
+    pub const ALL: [Eip1967Slot; 3] = [Eip1967Slot::Implementation, Eip1967Slot::Admin, Eip1967Slot::Beacon];
+
+    /// The storage slot
+    pub const fn slot(self) -> [u8; 32] {
+        match self {
+            Eip1967Slot::Implementation => [
+                0x36, 0x08, 0x94, 0xa1, 0x3b, 0xa1, 0xa3, 0x21, 0x06, 0x67, 0xc8, 0x28, 0x49, 0x2d, 0xb9, 0x8d,
+                0xca, 0x3e, 0x20, 0x76, 0xcc, 0x37, 0x35, 0xa9, 0x20, 0xa3, 0xca, 0x50, 0x5d, 0x38, 0x2b, 0xbc,
+            ],
+            Eip1967Slot::Admin => [
+                0xb5, 0x31, 0x27, 0x68, 0x4a, 0x56, 0x8b, 0x31, 0x73, 0xae, 0x13, 0xb9, 0xf8, 0xa6, 0x01, 0x6e,
+                0x24, 0x3e, 0x63, 0xb6, 0xe8, 0xee, 0x11, 0x78, 0xd6, 0xa7, 0x17, 0x85, 0x0b, 0x5d, 0x61, 0x03,
+            ],
+            Eip1967Slot::Beacon => [
+                0xa3, 0xf0, 0xad, 0x74, 0xe5, 0x42, 0x3a, 0xeb, 0xfd, 0x80, 0xd3, 0xef, 0x43, 0x46, 0x57, 0x83,
+                0x35, 0xa9, 0xa7, 0x2a, 0xea, 0xee, 0x59, 0xff, 0x6c, 0xb3, 0x58, 0x2b, 0x35, 0x13, 0x3d, 0x50,
+            ],
+        }
+    }
+
+    /// Query name the resolver accepts for this slot
+    pub const fn query(self) -> &'static str {
+        match self {
+            Eip1967Slot::Implementation => "eip1967.implementation",
+            Eip1967Slot::Admin => "eip1967.admin",
+            Eip1967Slot::Beacon => "eip1967.beacon",
+        }
+    }
+
+    /// The slot a query names, if it names one
+    pub fn from_query(query: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|slot| slot.query() == query.trim())
+    }
+
+    /// Derivation of the slot's path, committed to `layout`
+    ///
+    /// The slot holds an address in its low 20 bytes. An empty slot means the
+    /// contract is not that kind of proxy, so zero reads as never written.
+    pub fn trace(self, layout: &LayoutInfo) -> DerivationTrace {
+        DerivationTrace {
+            query: self.query().to_string(),
+            field: self.query().to_string(),
+            type_name: "t_address".to_string(),
+            steps: vec![DerivationStep::BaseSlot { field: self.query().to_string(), slot: self.slot() }],
+            path: StaticKeyPath {
+                name: self.query(),
+                key: Key::Fixed(self.slot()),
+                offset: None,
+                field_size: Some(20),
+                layout_commitment: layout.commitment(),
+                zero_semantics: ZeroSemantics::NeverWritten,
+            },
+        }
+    }
+}
+
+/// Address held in the low 20 bytes of a storage word, `None` when zero
+pub fn address_from_word(word: &[u8; 32]) -> Option<String> {
+    let address = &word[12..];
+    address.iter().any(|b| *b != 0).then(|| format!("0x{}", hex::encode(address)))
+}
+
+/// The EIP-1967 slots of a proxy
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ProxyInfo {
+    /// Proxy address, whose storage holds the state
+    pub proxy: String,
+    /// Implementation slot, or the beacon's implementation for beacon proxies
+    pub implementation: Option<String>,
+    pub admin: Option<String>,
+    pub beacon: Option<String>,
+}
+
+impl ProxyInfo {
+    /// Whether any EIP-1967 slot is set
+    pub fn is_proxy(&self) -> bool {
+        self.implementation.is_some() || self.admin.is_some() || self.beacon.is_some()
+    }
+}
+
+/// Reads EIP-1967 slots from a node to find a proxy's implementation
+///
+/// ```rust,ignore
+/// let detector = ProxyDetector::new("https://eth.llamarpc.com");
+/// if let Some(info) = detector.detect("0x...", "latest").await? {
+///     let (abi, _) = fetcher.fetch_abi(info.implementation.as_deref().unwrap()).await?;
+/// }
+/// ```
+#[cfg(feature = "std")]
+pub struct ProxyDetector {
+    transport: crate::RpcTransport,
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "std")]
+impl ProxyDetector {
+    pub fn new(rpc_url: impl Into<String>) -> Self {
+        Self { transport: crate::RpcTransport::new(rpc_url), client: reqwest::Client::new() }
+    }
+
+    /// Retry and fail over as `transport` does instead of the defaults
+    pub fn with_transport(mut self, transport: crate::RpcTransport) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Read `address`'s EIP-1967 slots at `block`, `None` when none is set
+    pub async fn detect(&self, address: &str, block: &str) -> Result<Option<ProxyInfo>, TraverseError> {
+        let mut info = ProxyInfo { proxy: address.to_lowercase(), ..ProxyInfo::default() };
+        for slot in Eip1967Slot::ALL {
+            let word = self.read_word("eth_getStorageAt", serde_json::json!([
+                address,
+                format!("0x{}", hex::encode(slot.slot())),
+                block
+            ])).await?;
+            let value = address_from_word(&word);
+            match slot {
+                Eip1967Slot::Implementation => info.implementation = value,
+                Eip1967Slot::Admin => info.admin = value,
+                Eip1967Slot::Beacon => info.beacon = value,
+            }
+        }
+
+        // Beacon proxies leave the implementation slot empty and ask the beacon
+        if let (None, Some(beacon)) = (&info.implementation, &info.beacon) {
+            let call = serde_json::json!({ "to": beacon, "data": format!("0x{}", hex::encode(BEACON_IMPLEMENTATION_SELECTOR)) });
+            let word = self.read_word("eth_call", serde_json::json!([call, block])).await?;
+            info.implementation = address_from_word(&word);
+        }
+
+        Ok(info.is_proxy().then_some(info))
+    }
+
+    /// Storage address and layout address for `address`
+    ///
+    /// Queries against a proxy read the proxy's storage with the
+    /// implementation's layout; any other contract is its own layout.
+    pub async fn layout_address(&self, address: &str, block: &str) -> Result<String, TraverseError> {
+        Ok(self
+            .detect(address, block)
+            .await?
+            .and_then(|info| info.implementation)
+            .unwrap_or_else(|| address.to_string()))
+    }
+
+    async fn read_word(&self, method: &str, params: serde_json::Value) -> Result<[u8; 32], TraverseError> {
+        let result = self.transport.call(&self.client, method, params).await?;
+        let result = result
+            .as_str()
+            .ok_or_else(|| TraverseError::external_service(format!("No result in {} response", method)))?;
+        let bytes = hex::decode(result.trim_start_matches("0x"))
+            .map_err(|e| TraverseError::external_service(format!("Invalid hex in {} result: {}", method, e)))?;
+
+        // Words are returned left-padded; take the last 32 bytes of longer returns
+        let mut word = [0u8; 32];
+        let len = bytes.len().min(32);
+        word[32 - len..].copy_from_slice(&bytes[bytes.len() - len..]);
+        Ok(word)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EthereumKeyResolver;
+    use traverse_core::{KeyResolver, TracingKeyResolver};
+
+    #[cfg(feature = "ethereum")]
+    #[test]
+    fn test_slots_are_hashed_names_minus_one() {
+        use tiny_keccak::{Hasher, Keccak};
+
+        for (slot, name) in [
+            (Eip1967Slot::Implementation, "eip1967.proxy.implementation"),
+            (Eip1967Slot::Admin, "eip1967.proxy.admin"),
+            (Eip1967Slot::Beacon, "eip1967.proxy.beacon"),
+        ] {
+            let mut digest = [0u8; 32];
+            let mut keccak = Keccak::v256();
+            keccak.update(name.as_bytes());
+            keccak.finalize(&mut digest);
+            // None of the digests end in a zero byte, so no borrow
+            digest[31] -= 1;
+            assert_eq!(slot.slot(), digest, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_resolves_slots_against_any_layout() {
+        let layout = LayoutInfo { contract_name: "Token".to_string(), storage: Vec::new(), types: Vec::new(), code_variables: Vec::new() };
+
+        let path = EthereumKeyResolver.resolve(&layout, "eip1967.implementation").unwrap();
+        assert_eq!(path.key, Key::Fixed(Eip1967Slot::Implementation.slot()));
+        assert_eq!(path.field_size, Some(20));
+        assert_eq!(path.layout_commitment, layout.commitment());
+
+        let trace = EthereumKeyResolver.resolve_traced(&layout, "eip1967.beacon").unwrap();
+        assert_eq!(trace.path.key, Key::Fixed(Eip1967Slot::Beacon.slot()));
+        assert!(EthereumKeyResolver.resolve(&layout, "eip1967.owner").is_err());
+    }
+
+    #[test]
+    fn test_address_from_word() {
+        let mut word = [0u8; 32];
+        assert_eq!(address_from_word(&word), None);
+        word[12] = 0xab;
+        word[31] = 0x01;
+        assert_eq!(address_from_word(&word).unwrap(), "0xab00000000000000000000000000000000000001");
+    }
+}
@@ -0,0 +1,399 @@
+//! Receipt trie proofs for log inclusion
+//!
+//! Nodes do not serve proofs into a block's receipts trie, so the trie is
+//! rebuilt from `eth_getBlockReceipts` and checked against the header's
+//! `receiptsRoot` before a proof for one receipt is taken from it. A
+//! [`ReceiptProof`] then shows that a given log, such as an ERC-20 `Transfer`,
+//! was emitted in that block, given a trusted receipts root.
+
+use rlp::{Rlp, RlpStream};
+use traverse_core::TraverseError;
+
+use crate::mpt::{index_key, ordered_trie_proof, verify_trie_proof};
+
+/// `keccak256("Transfer(address,address,uint256)")`, topic 0 of ERC-20 and ERC-721 transfers
+pub const TRANSFER_TOPIC: [u8; 32] = [
+    0xdd, 0xf2, 0x52, 0xad, 0x1b, 0xe2, 0xc8, 0x9b, 0x69, 0xc2, 0xb0, 0x68, 0xfc, 0x37, 0x8d, 0xaa,
+    0x95, 0x2b, 0xa7, 0xf1, 0x63, 0xc4, 0xa1, 0x16, 0x28, 0xf5, 0x5a, 0x4d, 0xf5, 0x23, 0xb3, 0xef,
+];
+
+/// An event log as committed in a receipt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Log {
+    pub address: [u8; 20],
+    pub topics: Vec<[u8; 32]>,
+    pub data: Vec<u8>,
+}
+
+impl Log {
+    /// Whether the log was emitted by `address` with `topic0` as its event signature
+    pub fn is_event(&self, address: &[u8; 20], topic0: &[u8; 32]) -> bool {
+        self.address == *address && self.topics.first() == Some(topic0)
+    }
+}
+
+/// Post-transaction outcome recorded in a receipt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReceiptOutcome {
+    /// EIP-658 status code, from Byzantium on
+    Status(bool),
+    /// Intermediate state root, before Byzantium
+    StateRoot([u8; 32]),
+}
+
+/// A transaction receipt in its consensus form
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Receipt {
+    /// EIP-2718 transaction type, 0 for legacy receipts
+    pub tx_type: u8,
+    pub outcome: ReceiptOutcome,
+    pub cumulative_gas_used: u64,
+    pub logs_bloom: [u8; 256],
+    pub logs: Vec<Log>,
+}
+
+fn invalid(message: &str) -> TraverseError {
+    TraverseError::Validation(format!("Invalid receipt: {}", message))
+}
+
+/// Bytes of the `0x` hex string at `object[field]`
+pub(crate) fn hex_field(object: &serde_json::Value, field: &str) -> Result<Vec<u8>, TraverseError> {
+    hex_value(&object[field], field)
+}
+
+pub(crate) fn hex_value(value: &serde_json::Value, field: &str) -> Result<Vec<u8>, TraverseError> {
+    let text = value
+        .as_str()
+        .ok_or_else(|| TraverseError::Serialization(format!("Missing {} in RPC response", field)))?;
+    let digits = text.strip_prefix("0x").unwrap_or(text);
+    let padded = if digits.len() % 2 == 1 { format!("0{}", digits) } else { digits.to_string() };
+    hex::decode(padded).map_err(|e| TraverseError::Serialization(format!("Invalid hex in {}: {}", field, e)))
+}
+
+pub(crate) fn fixed<const N: usize>(bytes: &[u8], field: &str) -> Result<[u8; N], TraverseError> {
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len()).min(bytes.len().saturating_sub(N));
+    let bytes = &bytes[start..];
+    if bytes.len() > N {
+        return Err(TraverseError::Validation(format!("{} longer than {} bytes", field, N)));
+    }
+    let mut out = [0u8; N];
+    out[N - bytes.len()..].copy_from_slice(bytes);
+    Ok(out)
+}
+
+pub(crate) fn quantity(bytes: &[u8], field: &str) -> Result<u64, TraverseError> {
+    Ok(u64::from_be_bytes(fixed(bytes, field)?))
+}
+
+impl Receipt {
+    /// Decode a receipt object as returned by `eth_getTransactionReceipt`
+    pub fn from_rpc(receipt: &serde_json::Value) -> Result<Self, TraverseError> {
+        let tx_type = match receipt.get("type") {
+            Some(_) => quantity(&hex_field(receipt, "type")?, "type")? as u8,
+            None => 0,
+        };
+        let outcome = match receipt.get("status") {
+            Some(_) => ReceiptOutcome::Status(quantity(&hex_field(receipt, "status")?, "status")? == 1),
+            None => ReceiptOutcome::StateRoot(fixed(&hex_field(receipt, "root")?, "root")?),
+        };
+        let logs = receipt
+            .get("logs")
+            .and_then(serde_json::Value::as_array)
+            .ok_or_else(|| TraverseError::Serialization("Missing logs in receipt".to_string()))?
+            .iter()
+            .map(|log| {
+                let topics = log
+                    .get("topics")
+                    .and_then(serde_json::Value::as_array)
+                    .ok_or_else(|| TraverseError::Serialization("Missing topics in log".to_string()))?
+                    .iter()
+                    .map(|topic| fixed(&hex_value(topic, "topic")?, "topic"))
+                    .collect::<Result<_, _>>()?;
+                Ok(Log {
+                    address: fixed(&hex_field(log, "address")?, "address")?,
+                    topics,
+                    data: hex_field(log, "data")?,
+                })
+            })
+            .collect::<Result<_, TraverseError>>()?;
+
+        Ok(Self {
+            tx_type,
+            outcome,
+            cumulative_gas_used: quantity(&hex_field(receipt, "cumulativeGasUsed")?, "cumulativeGasUsed")?,
+            logs_bloom: fixed(&hex_field(receipt, "logsBloom")?, "logsBloom")?,
+            logs,
+        })
+    }
+
+    /// Encode as a receipts trie value: `rlp(receipt)`, prefixed by the type for typed receipts
+    pub fn encode(&self) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(4);
+        match &self.outcome {
+            ReceiptOutcome::Status(true) => stream.append(&1u8),
+            ReceiptOutcome::Status(false) => stream.append_empty_data(),
+            ReceiptOutcome::StateRoot(root) => stream.append(&root.to_vec()),
+        };
+        stream.append(&self.cumulative_gas_used);
+        stream.append(&self.logs_bloom.to_vec());
+        stream.begin_list(self.logs.len());
+        for log in &self.logs {
+            stream.begin_list(3);
+            stream.append(&log.address.to_vec());
+            stream.begin_list(log.topics.len());
+            for topic in &log.topics {
+                stream.append(&topic.to_vec());
+            }
+            stream.append(&log.data);
+        }
+
+        let body = stream.out().to_vec();
+        if self.tx_type == 0 {
+            return body;
+        }
+        let mut encoded = Vec::with_capacity(body.len() + 1);
+        encoded.push(self.tx_type);
+        encoded.extend(body);
+        encoded
+    }
+
+    /// Decode a receipts trie value
+    pub fn decode(encoded: &[u8]) -> Result<Self, TraverseError> {
+        // Legacy receipts are bare RLP lists; typed ones start with a type byte below 0x80
+        let (tx_type, body) = match encoded.first() {
+            Some(first) if *first < 0x80 => (*first, &encoded[1..]),
+            Some(_) => (0, encoded),
+            None => return Err(invalid("empty")),
+        };
+
+        let rlp = Rlp::new(body);
+        if rlp.item_count().ok() != Some(4) {
+            return Err(invalid("not [status, cumulativeGasUsed, logsBloom, logs]"));
+        }
+        let data = |item: Rlp, field: &str| item.data().map(<[u8]>::to_vec).map_err(|_| invalid(field));
+        let at = |index: usize, field: &str| rlp.at(index).map_err(|_| invalid(field));
+
+        let outcome = data(at(0, "status")?, "status")?;
+        let outcome = match outcome.len() {
+            32 => ReceiptOutcome::StateRoot(fixed(&outcome, "root")?),
+            _ => ReceiptOutcome::Status(quantity(&outcome, "status")? == 1),
+        };
+        let logs = at(3, "logs")?
+            .iter()
+            .map(|log| {
+                let topics = log
+                    .at(1)
+                    .map_err(|_| invalid("log topics"))?
+                    .iter()
+                    .map(|topic| fixed(&data(topic, "log topic")?, "log topic"))
+                    .collect::<Result<_, _>>()?;
+                Ok(Log {
+                    address: fixed(&data(log.at(0).map_err(|_| invalid("log address"))?, "log address")?, "log address")?,
+                    topics,
+                    data: data(log.at(2).map_err(|_| invalid("log data"))?, "log data")?,
+                })
+            })
+            .collect::<Result<_, TraverseError>>()?;
+
+        Ok(Self {
+            tx_type,
+            outcome,
+            cumulative_gas_used: quantity(&data(at(1, "cumulativeGasUsed")?, "cumulativeGasUsed")?, "cumulativeGasUsed")?,
+            logs_bloom: fixed(&data(at(2, "logsBloom")?, "logsBloom")?, "logsBloom")?,
+            logs,
+        })
+    }
+}
+
+/// Inclusion proof of one log, through its receipt, in a block's receipts trie
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceiptProof {
+    pub block_hash: [u8; 32],
+    pub block_number: u64,
+    /// Header `receiptsRoot` the proof was built against
+    pub receipts_root: [u8; 32],
+    /// Position of the transaction in the block, the trie key being `rlp(tx_index)`
+    pub tx_index: usize,
+    /// Position of the log within its receipt, not the block-wide `logIndex`
+    pub log_index: usize,
+    /// Encoded receipt, the trie value
+    pub receipt: Vec<u8>,
+    /// RLP trie nodes from the receipts root to the receipt's leaf
+    pub proof: Vec<Vec<u8>>,
+}
+
+impl ReceiptProof {
+    /// Verify the receipt against `receipts_root` and return the proven log
+    ///
+    /// `receipts_root` must come from a trusted header; the root stored in the
+    /// proof is only what the fetching node claimed.
+    pub fn verify(&self, receipts_root: &[u8; 32]) -> Result<Log, TraverseError> {
+        let proven = verify_trie_proof(receipts_root, &index_key(self.tx_index), &self.proof)?
+            .ok_or_else(|| TraverseError::Validation(format!("No receipt at index {} in the trie", self.tx_index)))?;
+        if proven != self.receipt {
+            return Err(TraverseError::Validation(format!(
+                "Receipt {} does not match its proof",
+                self.tx_index
+            )));
+        }
+
+        let mut receipt = Receipt::decode(&proven)?;
+        if self.log_index >= receipt.logs.len() {
+            return Err(TraverseError::Validation(format!(
+                "Receipt {} has {} logs, no log {}",
+                self.tx_index,
+                receipt.logs.len(),
+                self.log_index
+            )));
+        }
+        Ok(receipt.logs.swap_remove(self.log_index))
+    }
+
+    /// Encoded size of the proof nodes
+    pub fn proof_size(&self) -> usize {
+        self.proof.iter().map(Vec::len).sum()
+    }
+}
+
+#[cfg(feature = "std")]
+impl crate::EthereumProofFetcher {
+    /// Prove log `log_index` of transaction `tx_hash` through the receipts trie
+    ///
+    /// Fetches every receipt of the transaction's block, rebuilds the receipts
+    /// trie and checks its root against the header before extracting the
+    /// proof. `log_index` counts the logs of this transaction only. The
+    /// configured contract address is not used; compare the proven log's
+    /// address instead.
+    #[cfg_attr(feature = "telemetry", tracing::instrument(name = "ethereum_fetch_log_proof", skip(self)))]
+    pub async fn fetch_log_proof(&self, tx_hash: &str, log_index: usize) -> Result<ReceiptProof, TraverseError> {
+        let client = reqwest::Client::new();
+        let receipt = self.rpc(&client, "eth_getTransactionReceipt", serde_json::json!([tx_hash])).await?;
+        let block_hash: [u8; 32] = fixed(&hex_field(&receipt, "blockHash")?, "blockHash")?;
+        let block_hash_hex = format!("0x{}", hex::encode(block_hash));
+        let tx_index = quantity(&hex_field(&receipt, "transactionIndex")?, "transactionIndex")? as usize;
+
+        let header = self.rpc(&client, "eth_getBlockByHash", serde_json::json!([block_hash_hex, false])).await?;
+        let receipts_root: [u8; 32] = fixed(&hex_field(&header, "receiptsRoot")?, "receiptsRoot")?;
+        let block_number = quantity(&hex_field(&header, "number")?, "number")?;
+
+        let receipts = self.rpc(&client, "eth_getBlockReceipts", serde_json::json!([block_hash_hex])).await?;
+        let encoded = receipts
+            .as_array()
+            .ok_or_else(|| TraverseError::external_service("eth_getBlockReceipts did not return a list".to_string()))?
+            .iter()
+            .map(|receipt| Receipt::from_rpc(receipt).map(|receipt| receipt.encode()))
+            .collect::<Result<Vec<_>, _>>()?;
+        if tx_index >= encoded.len() {
+            return Err(TraverseError::external_service(format!(
+                "Block {} has {} receipts, no receipt {}",
+                block_number,
+                encoded.len(),
+                tx_index
+            )));
+        }
+
+        let (root, proof) = ordered_trie_proof(&encoded, tx_index);
+        if root != receipts_root {
+            return Err(TraverseError::ProofGeneration(format!(
+                "Rebuilt receipts root 0x{} does not match block {}'s receiptsRoot 0x{}",
+                hex::encode(root),
+                block_number,
+                hex::encode(receipts_root)
+            )));
+        }
+
+        let receipt_proof = ReceiptProof {
+            block_hash,
+            block_number,
+            receipts_root,
+            tx_index,
+            log_index,
+            receipt: encoded[tx_index].clone(),
+            proof,
+        };
+        // Catches a log index past the receipt's logs before handing the proof out
+        receipt_proof.verify(&receipts_root)?;
+
+        #[cfg(feature = "telemetry")]
+        traverse_core::telemetry::record_proof_size("ethereum", "receipt", receipt_proof.proof_size());
+        Ok(receipt_proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer_log(amount: u8) -> Log {
+        let mut to = [0u8; 32];
+        to[31] = amount;
+        Log {
+            address: [0xa0; 20],
+            topics: vec![TRANSFER_TOPIC, [0u8; 32], to],
+            data: vec![amount; 32],
+        }
+    }
+
+    fn receipts(count: usize) -> Vec<Receipt> {
+        (0..count)
+            .map(|i| Receipt {
+                tx_type: (i % 3) as u8,
+                outcome: ReceiptOutcome::Status(i % 7 != 0),
+                cumulative_gas_used: 21_000 * (i as u64 + 1),
+                logs_bloom: [0u8; 256],
+                logs: (0..i % 4).map(|j| transfer_log((i + j) as u8)).collect(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_receipt_rpc_round_trip() {
+        let json = serde_json::json!({
+            "type": "0x2",
+            "status": "0x1",
+            "cumulativeGasUsed": "0x5208",
+            "logsBloom": format!("0x{}", "00".repeat(256)),
+            "logs": [{
+                "address": format!("0x{}", "a0".repeat(20)),
+                "topics": [format!("0x{}", hex::encode(TRANSFER_TOPIC))],
+                "data": "0x2a"
+            }]
+        });
+        let receipt = Receipt::from_rpc(&json).unwrap();
+        assert_eq!(receipt.tx_type, 2);
+        assert_eq!(receipt.cumulative_gas_used, 21_000);
+        assert!(receipt.logs[0].is_event(&[0xa0; 20], &TRANSFER_TOPIC));
+
+        let encoded = receipt.encode();
+        assert_eq!(encoded[0], 2);
+        assert_eq!(Receipt::decode(&encoded).unwrap(), receipt);
+
+        let legacy = Receipt { tx_type: 0, outcome: ReceiptOutcome::StateRoot([7u8; 32]), ..receipt };
+        assert_eq!(Receipt::decode(&legacy.encode()).unwrap(), legacy);
+    }
+
+    #[test]
+    fn test_log_proof_round_trip() {
+        let encoded: Vec<Vec<u8>> = receipts(150).iter().map(Receipt::encode).collect();
+
+        for tx_index in [1, 3, 129, 147] {
+            let (root, proof) = ordered_trie_proof(&encoded, tx_index);
+            let receipt_proof = ReceiptProof {
+                block_hash: [0u8; 32],
+                block_number: 1,
+                receipts_root: root,
+                tx_index,
+                log_index: 0,
+                receipt: encoded[tx_index].clone(),
+                proof,
+            };
+            assert_eq!(receipt_proof.verify(&root).unwrap(), transfer_log(tx_index as u8));
+
+            // The proof does not carry another transaction's receipt
+            let swapped = ReceiptProof { tx_index: tx_index + 1, ..receipt_proof.clone() };
+            assert!(swapped.verify(&root).is_err());
+            let past_logs = ReceiptProof { log_index: 3, ..receipt_proof };
+            assert!(past_logs.verify(&root).is_err());
+        }
+    }
+}
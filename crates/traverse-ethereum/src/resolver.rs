@@ -4,9 +4,11 @@
 //! storage queries (like `balances[0x123...]`) into deterministic Ethereum
 //! storage keys using Solidity's storage layout rules.
 
-#[cfg(feature = "ethereum")]
-use tiny_keccak::{Hasher, Keccak};
-use traverse_core::{Key, KeyResolver, LayoutInfo, StaticKeyPath, TraverseError};
+use traverse_core::hash::keccak256;
+use traverse_core::{
+    CodeVariableKind, DerivationStep, DerivationTrace, FieldEncoding, Key, KeyResolver, LayoutInfo, StaticKeyPath, StorageEntry,
+    TracingKeyResolver, TraverseError,
+};
 
 /// Ethereum key resolver that implements Solidity storage key derivation
 ///
@@ -25,9 +27,28 @@ use traverse_core::{Key, KeyResolver, LayoutInfo, StaticKeyPath, TraverseError};
 /// - Key is left-padded to 32 bytes
 /// - Slot is right-padded to 32 bytes (big-endian)
 ///
+/// Mappings keyed by `string` or `bytes` hash the key's bytes unpadded,
+/// `keccak256(key ++ slot)`. Such keys are written quoted, `names["alice"]`,
+/// or as hex, `data[0xdeadbeef]`.
+///
+/// ## Arrays
+/// `items[i]` resolves element `i` from `keccak256(slot)` for dynamic arrays
+/// and from `slot` for fixed-size ones. Elements of up to 16 bytes are
+/// packed several to a slot and carry their byte offset; larger elements,
+/// such as structs, step `ceil(size / 32)` slots per index and resolve to
+/// their first slot.
+///
+/// ## Vyper
+/// Layouts from [`VyperLayoutCompiler`](crate::VyperLayoutCompiler) mark
+/// `HashMap`s with the `hashmap` encoding, hashed as
+/// `keccak256(slot ++ pad(key))`, and `DynArray`s with `dynarray`, whose
+/// elements follow the length at `slot + 1`. Vyper never packs.
+///
 /// ## Packed Variables  
 /// Uses the same slot key but includes byte offset information
-/// for proper field extraction.
+/// for proper field extraction. Fields sharing a slot always carry their
+/// offset and size, and the field's value is
+/// [`extract_packed`](traverse_core::extract_packed) of the slot's word.
 ///
 /// # Examples
 ///
@@ -42,6 +63,9 @@ use traverse_core::{Key, KeyResolver, LayoutInfo, StaticKeyPath, TraverseError};
 ///
 /// // Mapping access
 /// let path = resolver.resolve(&layout, "balances[0x742d35Cc6634C0532925a3b8D97C2e0D8b2D9C]")?;
+///
+/// // EIP-1967 proxy slot, resolved against any layout
+/// let path = resolver.resolve(&layout, "eip1967.implementation")?;
 /// ```
 pub struct EthereumKeyResolver;
 
@@ -71,35 +95,29 @@ enum QueryParts {
 }
 
 impl EthereumKeyResolver {
-    /// Compute keccak256 hash
+    /// The storage entry labelled `label`, or an error naming what it is instead
     ///
-    /// # Arguments
-    ///
-    /// * `data` - Input data to hash
-    ///
-    /// # Returns
-    ///
-    /// 32-byte Keccak256 hash of the input
-    fn keccak256(data: &[u8]) -> [u8; 32] {
-        #[cfg(feature = "ethereum")]
-        {
-            let mut hasher = Keccak::v256();
-            let mut output = [0u8; 32];
-            hasher.update(data);
-            hasher.finalize(&mut output);
-            output
-        }
-        #[cfg(not(feature = "ethereum"))]
-        {
-            // Without ethereum feature, use a simple hash
-            let mut output = [0u8; 32];
-            for (i, &byte) in data.iter().enumerate() {
-                if i < 32 {
-                    output[i] = byte;
-                }
-            }
-            output
+    /// A `constant` or `immutable` has no slot, and any slot computed for it
+    /// would prove some other variable, so the error points at the code.
+    fn storage_entry<'a>(layout: &'a LayoutInfo, label: &str, what: &str) -> Result<&'a StorageEntry, TraverseError> {
+        if let Some(entry) = layout.storage.iter().find(|s| s.label == label) {
+            return Ok(entry);
         }
+        Err(TraverseError::KeyResolution(match layout.code_variable(label) {
+            Some(variable) => match variable.kind {
+                CodeVariableKind::Constant => format!(
+                    "{} is a constant {}, inlined into the contract's code and not in storage; \
+                     take its value from the source or call its getter",
+                    label, variable.type_name
+                ),
+                CodeVariableKind::Immutable => format!(
+                    "{} is an immutable {}, written into the deployed bytecode and not in storage; \
+                     extract it from the code at its immutableReferences offsets or call its getter",
+                    label, variable.type_name
+                ),
+            },
+            None => format!("{} not found: {}", what, label),
+        }))
     }
 
     /// Derive storage key for array element: keccak256(slot) + index
@@ -116,13 +134,13 @@ impl EthereumKeyResolver {
     /// # Returns
     ///
     /// 32-byte storage key for the array element
+    #[cfg(test)]
     fn derive_array_key(slot: u64, index: u64) -> [u8; 32] {
-        // First, get the base location: keccak256(slot)
-        let mut slot_bytes = [0u8; 32];
-        slot_bytes[24..].copy_from_slice(&slot.to_be_bytes());
-        let base_key = Self::keccak256(&slot_bytes);
+        Self::traced_element_key(slot, true, index, &mut Vec::new())
+    }
 
-        // Convert base key to u256 and add index
+    /// Add an array index to a 32-byte base key, wrapping modulo 2^256
+    fn add_index(base_key: [u8; 32], index: u64) -> [u8; 32] {
         let mut result = base_key;
 
         // SECURITY: Add index to the base key with overflow protection
@@ -162,23 +180,9 @@ impl EthereumKeyResolver {
     /// # Returns
     ///
     /// 32-byte storage key for the nested mapping entry
+    #[cfg(test)]
     fn derive_nested_mapping_key(map_key: &[u8], previous_key: &[u8; 32]) -> [u8; 32] {
-        let mut data = Vec::new();
-
-        // Pad key to 32 bytes (left-padded for addresses, right-padded for others)
-        if map_key.len() <= 32 {
-            let mut padded_key = [0u8; 32];
-            let start = 32 - map_key.len();
-            padded_key[start..].copy_from_slice(map_key);
-            data.extend_from_slice(&padded_key);
-        } else {
-            data.extend_from_slice(map_key);
-        }
-
-        // Use the full 32-byte previous key directly
-        data.extend_from_slice(previous_key);
-
-        Self::keccak256(&data)
+        Self::traced_mapping_key(map_key, previous_key, false, &mut Vec::new())
     }
 
     /// Derives storage key for mapping entries using Ethereum's standard keccak256(key ++ slot)
@@ -191,24 +195,167 @@ impl EthereumKeyResolver {
     ///
     /// 32-byte storage key for the mapping entry
     pub fn derive_mapping_key(key: &[u8], slot: u64) -> [u8; 32] {
-        let mut data = Vec::new();
+        Self::traced_mapping_key(key, &Self::slot_word(slot), false, &mut Vec::new())
+    }
+
+    /// A slot number as a big-endian 32-byte word
+    fn slot_word(slot: u64) -> [u8; 32] {
+        let mut word = [0u8; 32];
+        word[24..].copy_from_slice(&slot.to_be_bytes());
+        word
+    }
+
+    /// keccak256 of `preimage`, recorded in `steps`
+    fn traced_keccak(preimage: Vec<u8>, steps: &mut Vec<DerivationStep>) -> [u8; 32] {
+        let digest = keccak256(&preimage);
+        steps.push(DerivationStep::Keccak { preimage, digest });
+        digest
+    }
 
-        // Pad key to 32 bytes (left-padded for addresses, right-padded for others)
+    /// One mapping level: `keccak256(pad(key) ++ slot)`
+    ///
+    /// `slot` is the mapping's declared slot for the first level and the
+    /// previous level's key for nested ones. Keys of up to 32 bytes are
+    /// left-padded; longer keys are hashed as they are. Vyper hashes the
+    /// other way round, `keccak256(slot ++ pad(key))`, which `slot_first`
+    /// selects.
+    fn traced_mapping_key(
+        key: &[u8],
+        slot: &[u8; 32],
+        slot_first: bool,
+        steps: &mut Vec<DerivationStep>,
+    ) -> [u8; 32] {
+        let mut preimage = Vec::with_capacity(64);
+        if slot_first {
+            preimage.extend_from_slice(slot);
+        }
         if key.len() <= 32 {
-            let mut padded_key = [0u8; 32];
-            let start = 32 - key.len();
-            padded_key[start..].copy_from_slice(key);
-            data.extend_from_slice(&padded_key);
+            let mut padded = [0u8; 32];
+            padded[32 - key.len()..].copy_from_slice(key);
+            steps.push(DerivationStep::PadKey { key: key.to_vec(), padded });
+            preimage.extend_from_slice(&padded);
         } else {
-            data.extend_from_slice(key);
+            preimage.extend_from_slice(key);
+        }
+        if !slot_first {
+            preimage.extend_from_slice(slot);
         }
+        Self::traced_keccak(preimage, steps)
+    }
 
-        // Pad slot to 32 bytes (big-endian)
-        let mut padded_slot = [0u8; 32];
-        padded_slot[24..].copy_from_slice(&slot.to_be_bytes());
-        data.extend_from_slice(&padded_slot);
+    /// One mapping level keyed by `string` or `bytes`
+    ///
+    /// Solidity hashes the key's bytes unpadded, `keccak256(key ++ slot)`;
+    /// Vyper hashes the key first, `keccak256(slot ++ keccak256(key))`.
+    fn traced_bytes_mapping_key(
+        key: &[u8],
+        slot: &[u8; 32],
+        slot_first: bool,
+        steps: &mut Vec<DerivationStep>,
+    ) -> [u8; 32] {
+        if slot_first {
+            let digest = Self::traced_keccak(key.to_vec(), steps);
+            return Self::traced_mapping_key(&digest, slot, true, steps);
+        }
+        let mut preimage = key.to_vec();
+        preimage.extend_from_slice(slot);
+        Self::traced_keccak(preimage, steps)
+    }
+
+    /// Whether each level of the mapping `type_name` is keyed by `string` or `bytes`, outermost first
+    ///
+    /// Levels whose key type the layout does not name count as fixed-size.
+    fn dynamic_key_levels(layout: &LayoutInfo, type_name: &str, depth: usize) -> Vec<bool> {
+        let mut levels = Vec::with_capacity(depth);
+        let mut current = layout.types.iter().find(|t| t.label == type_name);
+        while let Some(type_info) = current.filter(|_| levels.len() < depth) {
+            levels.push(type_info.key.as_deref().is_some_and(|key| Self::is_dynamic_key(layout, key)));
+            current = type_info.value.as_ref().and_then(|value| layout.types.iter().find(|t| &t.label == value));
+        }
+        levels.resize(depth, false);
+        levels
+    }
+
+    /// Whether a mapping key type is `string` or `bytes`
+    ///
+    /// solc does not always list key types such as `t_string_memory_ptr`, so
+    /// unlisted ones are judged by name.
+    fn is_dynamic_key(layout: &LayoutInfo, key_type: &str) -> bool {
+        match layout.types.iter().find(|t| t.label == key_type) {
+            Some(type_info) => matches!(type_info.encoding.as_str(), "bytes" | "bytestring"),
+            None => key_type.starts_with("t_string") || key_type.starts_with("t_bytes_"),
+        }
+    }
+
+    /// `query` with the contents of quoted keys blanked out
+    ///
+    /// Brackets and dots inside `"..."` or `'...'` are part of a key, not
+    /// syntax. The result has the same byte length, so positions found in it
+    /// index `query`.
+    fn mask_quoted(query: &str) -> String {
+        let mut masked = String::with_capacity(query.len());
+        let mut quote = None;
+        for c in query.chars() {
+            match quote {
+                Some(q) if c == q => {
+                    quote = None;
+                    masked.push(c);
+                }
+                Some(_) => masked.extend(std::iter::repeat_n('_', c.len_utf8())),
+                None => {
+                    if c == '"' || c == '\'' {
+                        quote = Some(c);
+                    }
+                    masked.push(c);
+                }
+            }
+        }
+        masked
+    }
+
+    /// Whether `entry` is a Vyper `HashMap`, keyed slot first
+    fn is_hashmap(layout: &LayoutInfo, entry: &traverse_core::StorageEntry) -> bool {
+        layout.types.iter().any(|t| t.label == entry.type_name && t.encoding == "hashmap")
+    }
+
+    /// Whether the field labelled `field_name` is a Solidity mapping or a Vyper `HashMap`
+    fn is_mapping_field(layout: &LayoutInfo, field_name: &str) -> bool {
+        layout
+            .storage
+            .iter()
+            .find(|entry| entry.label == field_name)
+            .and_then(|entry| layout.types.iter().find(|t| t.label == entry.type_name))
+            .is_some_and(|t| t.encoding == "mapping" || t.encoding == "hashmap")
+    }
 
-        Self::keccak256(&data)
+    /// The slot `delta` slots into an array's storage
+    ///
+    /// Dynamic arrays store their elements from `keccak256(slot)`, fixed-size
+    /// arrays from their declared slot.
+    fn traced_element_key(slot: u64, dynamic: bool, delta: u64, steps: &mut Vec<DerivationStep>) -> [u8; 32] {
+        let base = if dynamic {
+            Self::traced_keccak(Self::slot_word(slot).to_vec(), steps)
+        } else {
+            Self::slot_word(slot)
+        };
+        let result = Self::add_index(base, delta);
+        steps.push(DerivationStep::AddIndex { base, index: delta, result });
+        result
+    }
+
+    /// Where element `index` of an array of `element_size`-byte elements sits
+    ///
+    /// Elements of up to 16 bytes share slots, filled from the low-order end;
+    /// larger ones, structs included, take `ceil(size / 32)` whole slots each.
+    /// Returns the element's slot relative to the array's first slot and its
+    /// byte offset in that slot, `None` when the slot does not fit a `u64`.
+    fn element_position(element_size: u64, index: u64) -> Option<(u64, u8)> {
+        if element_size <= 16 {
+            let per_slot = 32 / element_size;
+            Some((index / per_slot, ((index % per_slot) * element_size) as u8))
+        } else {
+            Some((index.checked_mul(element_size.div_ceil(32))?, 0))
+        }
     }
 
     /// Parse a query that may include nested mappings, arrays, and struct access
@@ -227,6 +374,7 @@ impl EthereumKeyResolver {
     /// - Simple fields: `"owner"`, `"totalSupply"`
     /// - Single mappings: `"balances[0x742d35...]"`
     /// - Nested mappings: `"allowances[0x123...][0x456...]"`
+    /// - String keys: `"balances[\"alice\"]"`
     /// - Array indexing: `"items[5]"`
     /// - Struct fields: `"user.balance"` (future support)
     ///
@@ -236,8 +384,10 @@ impl EthereumKeyResolver {
     /// - Invalid hex encoding in mapping keys
     /// - Unsupported query patterns
     fn parse_query(&self, query: &str) -> Result<QueryParts, TraverseError> {
+        let masked = Self::mask_quoted(query);
+
         // Handle struct field access (dot notation)
-        if query.contains('.') {
+        if masked.contains('.') {
             let parts: Vec<&str> = query.split('.').collect();
             if parts.len() != 2 {
                 return Err(TraverseError::InvalidInput(format!(
@@ -278,10 +428,10 @@ impl EthereumKeyResolver {
         }
 
         // Check for array/mapping access patterns
-        if query.contains('[') && query.contains(']') {
+        if masked.contains('[') && masked.contains(']') {
             // Count the number of bracket pairs to determine nesting level
-            let open_brackets = query.matches('[').count();
-            let close_brackets = query.matches(']').count();
+            let open_brackets = masked.matches('[').count();
+            let close_brackets = masked.matches(']').count();
 
             if open_brackets != close_brackets {
                 return Err(TraverseError::InvalidInput(format!(
@@ -291,26 +441,22 @@ impl EthereumKeyResolver {
             }
 
             // Extract field name (everything before first '[')
-            let field_name = query
-                .split('[')
-                .next()
-                .ok_or_else(|| {
-                    TraverseError::InvalidInput(format!("Invalid query format: {}", query))
-                })?
-                .trim()
-                .to_string();
+            let first_bracket = masked.find('[').ok_or_else(|| {
+                TraverseError::InvalidInput(format!("Invalid query format: {}", query))
+            })?;
+            let field_name = query[..first_bracket].trim().to_string();
 
             // Extract all keys from bracket pairs
             let mut keys = Vec::new();
             let mut is_array_access = true;
-            let mut current = query;
+            let mut current = 0;
 
-            while let Some(start) = current.find('[') {
-                let end = current.find(']').ok_or_else(|| {
+            while let Some(start) = masked[current..].find('[').map(|i| current + i) {
+                let end = masked[start..].find(']').map(|i| start + i).ok_or_else(|| {
                     TraverseError::InvalidInput(format!("Unclosed bracket in query: {}", query))
                 })?;
 
-                let key_str = &current[start + 1..end];
+                let key_str = &query[start + 1..end];
 
                 // Check if this looks like a numeric index (array) or hex key (mapping)
                 if key_str.trim().parse::<u64>().is_ok() {
@@ -325,7 +471,7 @@ impl EthereumKeyResolver {
                 }
 
                 // Move past this bracket pair
-                current = &current[end + 1..];
+                current = end + 1;
             }
 
             if keys.is_empty() {
@@ -338,7 +484,7 @@ impl EthereumKeyResolver {
             // Determine the query type based on the analysis
             if keys.len() == 1 && is_array_access {
                 // Single numeric key - could be array access
-                let key_str = &query[query.find('[').unwrap() + 1..query.find(']').unwrap()];
+                let key_str = &query[first_bracket + 1..masked.find(']').unwrap()];
                 if let Ok(index) = key_str.trim().parse::<u64>() {
                     Ok(QueryParts::Array { field_name, index })
                 } else {
@@ -382,9 +528,15 @@ impl EthereumKeyResolver {
     /// - Hex addresses: `0x742d35...` or `742d35...`
     /// - Decimal numbers: `123`, `0`
     /// - Hex numbers: `0x1a2b`
+    /// - Quoted strings: `"alice"` or `'alice'`, as their UTF-8 bytes
     fn parse_key(&self, key_str: &str) -> Result<Vec<u8>, TraverseError> {
         let key_str = key_str.trim();
 
+        let quoted = ['"', '\''].iter().find_map(|q| key_str.strip_prefix(*q).and_then(|k| k.strip_suffix(*q)));
+        if let Some(text) = quoted.filter(|_| key_str.len() >= 2) {
+            return Ok(text.as_bytes().to_vec());
+        }
+
         // Try parsing as hex (with or without 0x prefix)
         if key_str.starts_with("0x") || key_str.len() == 40 || key_str.len() == 64 {
             let hex_str = key_str.strip_prefix("0x").unwrap_or(key_str);
@@ -408,6 +560,136 @@ impl EthereumKeyResolver {
         }
     }
 
+    /// Length of a `string` or `bytes` value from the word in its slot
+    ///
+    /// Values under 32 bytes are stored in the slot itself with twice their
+    /// length in the lowest byte; longer ones store `length * 2 + 1`. `None`
+    /// when the word is neither.
+    pub fn bytes_length(head: &[u8; 32]) -> Option<u64> {
+        if head[31] & 1 == 0 {
+            let length = u64::from(head[31] / 2);
+            return (length < 32).then_some(length);
+        }
+        if head[..24].iter().any(|b| *b != 0) {
+            return None;
+        }
+        let encoded = u64::from_be_bytes(head[24..].try_into().ok()?);
+        let length = encoded / 2;
+        (length >= 32).then_some(length)
+    }
+
+    /// Every slot holding `field`, a `string` or `bytes` of `length` bytes
+    ///
+    /// The first path is the field's own slot, which holds the whole value
+    /// when it is under 32 bytes. Longer values continue in `ceil(length / 32)`
+    /// data slots from `keccak256(slot)`, named `field.data[n]`. Read the
+    /// field's slot first and take the length from [`Self::bytes_length`].
+    pub fn resolve_bytes_slots(
+        &self,
+        layout: &LayoutInfo,
+        field: &str,
+        length: u64,
+    ) -> Result<Vec<StaticKeyPath>, TraverseError> {
+        let entry = Self::storage_entry(layout, field, "Field")?;
+        let encoding = layout
+            .types
+            .iter()
+            .find(|t| t.label == entry.type_name)
+            .map(|t| t.encoding.as_str());
+        if encoding != Some("bytes") {
+            return Err(TraverseError::KeyResolution(format!(
+                "{} is not a string or bytes field",
+                field
+            )));
+        }
+        let slot = entry
+            .slot
+            .parse::<u64>()
+            .map_err(|e| TraverseError::KeyResolution(format!("Invalid slot: {}", e)))?;
+
+        let path = |name: String, key: [u8; 32]| StaticKeyPath {
+            name: Box::leak(name.into_boxed_str()),
+            key: Key::Fixed(key),
+            offset: None,
+            field_size: Some(32),
+            layout_commitment: layout.commitment(),
+            zero_semantics: entry.zero_semantics,
+        };
+        let mut paths = vec![path(field.to_string(), Self::slot_word(slot))];
+        if length >= 32 {
+            let data = keccak256(&Self::slot_word(slot));
+            paths.extend(
+                (0..length.div_ceil(32))
+                    .map(|chunk| path(format!("{}.data[{}]", field, chunk), Self::add_index(data, chunk))),
+            );
+        }
+        Ok(paths)
+    }
+
+    /// How the value `query` reads is encoded, for sign extension and truncation
+    ///
+    /// The leaf type is the field's own, a mapping's value type after each
+    /// key, or an array's element type; lengths, data slots and the proxy
+    /// slots read as a full word. The offset is the one [`KeyResolver::resolve`]
+    /// gives the path.
+    pub fn resolve_encoding(&self, layout: &LayoutInfo, query: &str) -> Result<FieldEncoding, TraverseError> {
+        let trace = self.resolve_traced(layout, query)?;
+        let offset = trace.path.offset.unwrap_or(0);
+        if layout.storage.iter().all(|s| s.label != trace.field) {
+            return Ok(FieldEncoding { offset, ..FieldEncoding::WORD });
+        }
+        let type_of = |label: &str| layout.types.iter().find(|t| t.label == label);
+        let query_parts = if layout.storage.iter().any(|s| s.label == query) {
+            QueryParts::Field { field_name: query.to_string() }
+        } else {
+            self.parse_query(query)?
+        };
+        let leaf = match query_parts {
+            QueryParts::Field { .. } => Some(trace.type_name.clone()),
+            QueryParts::Mapping { .. } => type_of(&trace.type_name).and_then(|t| t.value.clone().or(t.base.clone())),
+            QueryParts::NestedMapping { keys, .. } => keys.iter().try_fold(trace.type_name.clone(), |label, _| {
+                type_of(&label).and_then(|t| t.value.clone())
+            }),
+            QueryParts::Array { .. } => type_of(&trace.type_name).and_then(|t| t.base.clone().or(t.value.clone())),
+            QueryParts::StructField { field_name, .. } => Some(field_name),
+            QueryParts::DynamicLength { .. } | QueryParts::DynamicData { .. } => None,
+        };
+        Ok(FieldEncoding {
+            offset,
+            ..leaf.map_or(FieldEncoding::WORD, |label| Self::type_encoding(layout, &label))
+        })
+    }
+
+    /// Encoding of a value of type `label`, at offset zero
+    ///
+    /// `intN` is signed and `uintN`, `bytesN`, `bool` and `address` are
+    /// their own width, whether labelled by solc (`t_int24`) or Vyper
+    /// (`int128`); enums take their storage size. Anything else is a word.
+    pub fn type_encoding(layout: &LayoutInfo, label: &str) -> FieldEncoding {
+        let name = label.strip_prefix("t_").unwrap_or(label);
+        let width = |digits: &str, scale: u16| digits.parse::<u16>().ok().map(|n| n * scale).filter(|bits| (1..=256).contains(bits));
+        let (signed, bits) = if let Some(digits) = name.strip_prefix("uint") {
+            (false, width(digits, 1))
+        } else if let Some(digits) = name.strip_prefix("int") {
+            (true, width(digits, 1))
+        } else if let Some(digits) = name.strip_prefix("bytes") {
+            (false, width(digits, 8))
+        } else if name == "bool" {
+            (false, Some(8))
+        } else if name.starts_with("address") || name.starts_with("contract") {
+            (false, Some(160))
+        } else if name.starts_with("enum") {
+            let size = layout.types.iter().find(|t| t.label == label).and_then(|t| t.number_of_bytes.parse::<u16>().ok());
+            (false, size.map(|bytes| bytes * 8).filter(|bits| (1..=256).contains(bits)))
+        } else {
+            (false, None)
+        };
+        match bits {
+            Some(bits) => FieldEncoding { signed, bits, offset: 0 },
+            None => FieldEncoding::WORD,
+        }
+    }
+
     /// Generate example keys for different mapping key types
     fn generate_example_keys(&self, key_type: &str) -> Vec<String> {
         match key_type {
@@ -427,33 +709,95 @@ impl EthereumKeyResolver {
                 "0x48656c6c6f".to_string(), // "Hello" in hex
                 "0x576f726c64".to_string(), // "World" in hex
             ],
+            "t_string_memory_ptr" => vec!["\"alice\"".to_string(), "\"bob\"".to_string()],
+            "t_bytes_memory_ptr" => vec!["0x".to_string(), "0xdeadbeef".to_string()],
             _ => vec!["0".to_string(), "1".to_string()],
         }
     }
 }
 
 impl KeyResolver for EthereumKeyResolver {
+    #[cfg_attr(feature = "telemetry", tracing::instrument(name = "ethereum_resolve", skip(self, layout)))]
     fn resolve(&self, layout: &LayoutInfo, query: &str) -> Result<StaticKeyPath, TraverseError> {
-        let query_parts = self.parse_query(query)?;
+        let result = self.resolve_traced(layout, query).map(|trace| trace.path);
+        #[cfg(feature = "telemetry")]
+        traverse_core::telemetry::record_resolution("ethereum", &result);
+        result
+    }
+
+    fn resolve_all(&self, layout: &LayoutInfo) -> Result<Vec<StaticKeyPath>, TraverseError> {
+        let mut paths = Vec::new();
+
+        for entry in &layout.storage {
+            // Get type information to determine if this is a mapping
+            if let Some(type_info) = layout.types.iter().find(|t| t.label == entry.type_name) {
+                if matches!(type_info.encoding.as_str(), "mapping" | "hashmap") {
+                    // Generate example mapping paths with common example keys
+                    let example_keys = self.generate_example_keys(
+                        type_info.key.as_ref().unwrap_or(&"t_address".to_string()),
+                    );
+
+                    for example_key in example_keys {
+                        let query = format!("{}[{}]", entry.label, example_key);
+                        if let Ok(path) = self.resolve(layout, &query) {
+                            paths.push(path);
+                        }
+                    }
+                } else {
+                    // Simple field - resolve directly
+                    if let Ok(path) = self.resolve(layout, &entry.label) {
+                        paths.push(path);
+                    }
+                }
+            } else {
+                // Fallback for entries without type info
+                if let Ok(path) = self.resolve(layout, &entry.label) {
+                    paths.push(path);
+                }
+            }
+        }
+
+        Ok(paths)
+    }
+}
+
+impl TracingKeyResolver for EthereumKeyResolver {
+    fn resolve_traced(&self, layout: &LayoutInfo, query: &str) -> Result<DerivationTrace, TraverseError> {
+        // Proxy slots sit outside every implementation layout
+        if let Some(slot) = crate::proxy::Eip1967Slot::from_query(query) {
+            return Ok(slot.trace(layout));
+        }
+        if let Some(slot) = crate::presets::SafeSlot::from_query(query) {
+            return Ok(slot.trace(layout));
+        }
 
-        match query_parts {
+        // Vyper module variables are labelled `module.name`
+        let query_parts = if layout.storage.iter().any(|s| s.label == query) {
+            QueryParts::Field { field_name: query.to_string() }
+        } else {
+            self.parse_query(query)?
+        };
+        // A number in brackets is a key, not an index, when the field is a mapping
+        let query_parts = match query_parts {
+            QueryParts::Array { field_name, index } if Self::is_mapping_field(layout, &field_name) => {
+                QueryParts::Mapping { field_name, key: index.to_be_bytes().to_vec() }
+            }
+            other => other,
+        };
+        let mut steps = Vec::new();
+
+        let (entry, path) = match query_parts {
             QueryParts::Field { field_name } => {
                 // Find the storage entry
-                let entry = layout
-                    .storage
-                    .iter()
-                    .find(|s| s.label == field_name)
-                    .ok_or_else(|| {
-                        TraverseError::KeyResolution(format!("Field not found: {}", field_name))
-                    })?;
+                let entry = Self::storage_entry(layout, &field_name, "Field")?;
 
                 let slot = entry
                     .slot
                     .parse::<u64>()
                     .map_err(|e| TraverseError::KeyResolution(format!("Invalid slot: {}", e)))?;
+                steps.push(DerivationStep::BaseSlot { field: entry.label.clone(), slot: Self::slot_word(slot) });
 
-                let mut key_bytes = [0u8; 32];
-                key_bytes[24..].copy_from_slice(&slot.to_be_bytes());
+                let key_bytes = Self::slot_word(slot);
 
                 // Get type info for field size
                 let type_info = layout
@@ -469,14 +813,12 @@ impl KeyResolver for EthereumKeyResolver {
 
                 let field_size = type_info.number_of_bytes.parse::<u8>().ok();
 
-                Ok(StaticKeyPath {
+                // Packed fields carry their offset even at 0, so consumers mask
+                // the field out of the slot instead of reading the whole word
+                (entry, StaticKeyPath {
                     name: Box::leak(query.to_string().into_boxed_str()),
                     key: Key::Fixed(key_bytes),
-                    offset: if entry.offset > 0 {
-                        Some(entry.offset)
-                    } else {
-                        None
-                    },
+                    offset: layout.is_packed(entry).then_some(entry.offset),
                     field_size,
                     layout_commitment: layout.commitment(),
                     zero_semantics: entry.zero_semantics,
@@ -484,20 +826,20 @@ impl KeyResolver for EthereumKeyResolver {
             }
             QueryParts::Mapping { field_name, key } => {
                 // Find the mapping storage entry
-                let entry = layout
-                    .storage
-                    .iter()
-                    .find(|s| s.label == field_name)
-                    .ok_or_else(|| {
-                        TraverseError::KeyResolution(format!("Mapping not found: {}", field_name))
-                    })?;
+                let entry = Self::storage_entry(layout, &field_name, "Mapping")?;
 
                 let slot = entry
                     .slot
                     .parse::<u64>()
                     .map_err(|e| TraverseError::KeyResolution(format!("Invalid slot: {}", e)))?;
+                steps.push(DerivationStep::BaseSlot { field: entry.label.clone(), slot: Self::slot_word(slot) });
 
-                let storage_key = Self::derive_mapping_key(&key, slot);
+                let slot_first = Self::is_hashmap(layout, entry);
+                let storage_key = if Self::dynamic_key_levels(layout, &entry.type_name, 1)[0] {
+                    Self::traced_bytes_mapping_key(&key, &Self::slot_word(slot), slot_first, &mut steps)
+                } else {
+                    Self::traced_mapping_key(&key, &Self::slot_word(slot), slot_first, &mut steps)
+                };
 
                 // Get type info for value size
                 let type_info = layout
@@ -521,7 +863,7 @@ impl KeyResolver for EthereumKeyResolver {
                     type_info.number_of_bytes.parse::<u8>().ok()
                 };
 
-                Ok(StaticKeyPath {
+                (entry, StaticKeyPath {
                     name: Box::leak(query.to_string().into_boxed_str()),
                     key: Key::Fixed(storage_key),
                     offset: None, // Mappings typically don't have offsets
@@ -532,27 +874,28 @@ impl KeyResolver for EthereumKeyResolver {
             }
             QueryParts::NestedMapping { field_name, keys } => {
                 // Find the mapping storage entry
-                let entry = layout
-                    .storage
-                    .iter()
-                    .find(|s| s.label == field_name)
-                    .ok_or_else(|| {
-                        TraverseError::KeyResolution(format!("Mapping not found: {}", field_name))
-                    })?;
+                let entry = Self::storage_entry(layout, &field_name, "Mapping")?;
 
                 let slot = entry
                     .slot
                     .parse::<u64>()
                     .map_err(|e| TraverseError::KeyResolution(format!("Invalid slot: {}", e)))?;
+                steps.push(DerivationStep::BaseSlot { field: entry.label.clone(), slot: Self::slot_word(slot) });
 
                 // For nested mappings like allowances[owner][spender]:
                 // 1. Derive key1 = keccak256(owner ++ slot)
                 // 2. Derive key2 = keccak256(spender ++ key1)
-                let mut current_key = Self::derive_mapping_key(&keys[0], slot);
-
-                // Chain each subsequent key derivation
-                for key in &keys[1..] {
-                    current_key = Self::derive_nested_mapping_key(key, &current_key);
+                let slot_first = Self::is_hashmap(layout, entry);
+                let dynamic = Self::dynamic_key_levels(layout, &entry.type_name, keys.len());
+                let mut current_key = Self::slot_word(slot);
+
+                // Chain each key derivation, the first from the declared slot
+                for (key, dynamic) in keys.iter().zip(dynamic) {
+                    current_key = if dynamic {
+                        Self::traced_bytes_mapping_key(key, &current_key, slot_first, &mut steps)
+                    } else {
+                        Self::traced_mapping_key(key, &current_key, slot_first, &mut steps)
+                    };
                 }
 
                 // Get type info for value size
@@ -577,7 +920,7 @@ impl KeyResolver for EthereumKeyResolver {
                     type_info.number_of_bytes.parse::<u8>().ok()
                 };
 
-                Ok(StaticKeyPath {
+                (entry, StaticKeyPath {
                     name: Box::leak(query.to_string().into_boxed_str()),
                     key: Key::Fixed(current_key),
                     offset: None, // Mappings typically don't have offsets
@@ -588,20 +931,13 @@ impl KeyResolver for EthereumKeyResolver {
             }
             QueryParts::Array { field_name, index } => {
                 // Find the array storage entry
-                let entry = layout
-                    .storage
-                    .iter()
-                    .find(|s| s.label == field_name)
-                    .ok_or_else(|| {
-                        TraverseError::KeyResolution(format!("Array not found: {}", field_name))
-                    })?;
+                let entry = Self::storage_entry(layout, &field_name, "Array")?;
 
                 let slot = entry
                     .slot
                     .parse::<u64>()
                     .map_err(|e| TraverseError::KeyResolution(format!("Invalid slot: {}", e)))?;
-
-                let array_key = Self::derive_array_key(slot, index);
+                steps.push(DerivationStep::BaseSlot { field: entry.label.clone(), slot: Self::slot_word(slot) });
 
                 // Get type info for value size
                 let type_info = layout
@@ -615,12 +951,50 @@ impl KeyResolver for EthereumKeyResolver {
                         ))
                     })?;
 
-                let field_size = type_info.number_of_bytes.parse::<u8>().ok();
+                // The element type sets the stride; layouts that do not name
+                // it get one slot per element
+                let element = type_info
+                    .base
+                    .as_ref()
+                    .and_then(|base| layout.types.iter().find(|t| &t.label == base));
+                let (delta, offset, field_size) = match element {
+                    Some(element) => {
+                        let element_size = element
+                            .number_of_bytes
+                            .parse::<u64>()
+                            .ok()
+                            .filter(|size| *size > 0)
+                            .ok_or_else(|| {
+                                TraverseError::KeyResolution(format!(
+                                    "Invalid size '{}' for array element type {}",
+                                    element.number_of_bytes, element.label
+                                ))
+                            })?;
+                        let (delta, offset) = Self::element_position(element_size, index).ok_or_else(|| {
+                            TraverseError::KeyResolution(format!("Array index {} out of range for {}", index, field_name))
+                        })?;
+                        // Multi-slot elements resolve to their first slot
+                        let field_size = element_size.min(32) as u8;
+                        (delta, (element_size <= 16).then_some(offset), Some(field_size))
+                    }
+                    None => (index, None, type_info.number_of_bytes.parse::<u8>().ok()),
+                };
 
-                Ok(StaticKeyPath {
+                // Vyper's DynArray keeps its length at the slot and its
+                // elements in place after it
+                let (dynamic, delta) = match type_info.encoding.as_str() {
+                    "inplace" => (false, delta),
+                    "dynarray" => (false, delta.checked_add(1).ok_or_else(|| {
+                        TraverseError::KeyResolution(format!("Array index {} out of range for {}", index, field_name))
+                    })?),
+                    _ => (true, delta),
+                };
+                let array_key = Self::traced_element_key(slot, dynamic, delta, &mut steps);
+
+                (entry, StaticKeyPath {
                     name: Box::leak(query.to_string().into_boxed_str()),
                     key: Key::Fixed(array_key),
-                    offset: None, // Arrays typically don't have offsets
+                    offset,
                     field_size,
                     layout_commitment: layout.commitment(),
                     zero_semantics: entry.zero_semantics,
@@ -631,21 +1005,15 @@ impl KeyResolver for EthereumKeyResolver {
                 field_name,
             } => {
                 // Find the storage entry
-                let entry = layout
-                    .storage
-                    .iter()
-                    .find(|s| s.label == struct_name)
-                    .ok_or_else(|| {
-                        TraverseError::KeyResolution(format!("Struct not found: {}", struct_name))
-                    })?;
+                let entry = Self::storage_entry(layout, &struct_name, "Struct")?;
 
                 let slot = entry
                     .slot
                     .parse::<u64>()
                     .map_err(|e| TraverseError::KeyResolution(format!("Invalid slot: {}", e)))?;
+                steps.push(DerivationStep::BaseSlot { field: entry.label.clone(), slot: Self::slot_word(slot) });
 
-                let mut key_bytes = [0u8; 32];
-                key_bytes[24..].copy_from_slice(&slot.to_be_bytes());
+                let key_bytes = Self::slot_word(slot);
 
                 // Get type info for field size
                 let type_info = layout
@@ -658,7 +1026,7 @@ impl KeyResolver for EthereumKeyResolver {
 
                 let field_size = type_info.number_of_bytes.parse::<u8>().ok();
 
-                Ok(StaticKeyPath {
+                (entry, StaticKeyPath {
                     name: Box::leak(query.to_string().into_boxed_str()),
                     key: Key::Fixed(key_bytes),
                     offset: None,
@@ -669,26 +1037,17 @@ impl KeyResolver for EthereumKeyResolver {
             }
             QueryParts::DynamicLength { field_name } => {
                 // For dynamic arrays and strings, the length is stored at the slot itself
-                let entry = layout
-                    .storage
-                    .iter()
-                    .find(|s| s.label == field_name)
-                    .ok_or_else(|| {
-                        TraverseError::KeyResolution(format!(
-                            "Dynamic field not found: {}",
-                            field_name
-                        ))
-                    })?;
+                let entry = Self::storage_entry(layout, &field_name, "Dynamic field")?;
 
                 let slot = entry
                     .slot
                     .parse::<u64>()
                     .map_err(|e| TraverseError::KeyResolution(format!("Invalid slot: {}", e)))?;
+                steps.push(DerivationStep::BaseSlot { field: entry.label.clone(), slot: Self::slot_word(slot) });
 
-                let mut key_bytes = [0u8; 32];
-                key_bytes[24..].copy_from_slice(&slot.to_be_bytes());
+                let key_bytes = Self::slot_word(slot);
 
-                Ok(StaticKeyPath {
+                (entry, StaticKeyPath {
                     name: Box::leak(query.to_string().into_boxed_str()),
                     key: Key::Fixed(key_bytes),
                     offset: None,
@@ -699,28 +1058,27 @@ impl KeyResolver for EthereumKeyResolver {
             }
             QueryParts::DynamicData { field_name } => {
                 // For dynamic arrays and strings, the data starts at keccak256(slot)
-                let entry = layout
-                    .storage
-                    .iter()
-                    .find(|s| s.label == field_name)
-                    .ok_or_else(|| {
-                        TraverseError::KeyResolution(format!(
-                            "Dynamic field not found: {}",
-                            field_name
-                        ))
-                    })?;
+                let entry = Self::storage_entry(layout, &field_name, "Dynamic field")?;
 
                 let slot = entry
                     .slot
                     .parse::<u64>()
                     .map_err(|e| TraverseError::KeyResolution(format!("Invalid slot: {}", e)))?;
+                steps.push(DerivationStep::BaseSlot { field: entry.label.clone(), slot: Self::slot_word(slot) });
 
-                // Data starts at keccak256(slot)
-                let mut slot_bytes = [0u8; 32];
-                slot_bytes[24..].copy_from_slice(&slot.to_be_bytes());
-                let data_key = Self::keccak256(&slot_bytes);
+                // Data starts at keccak256(slot), or right after the length
+                // for Vyper's in-place strings and arrays
+                let in_place = layout
+                    .types
+                    .iter()
+                    .any(|t| t.label == entry.type_name && matches!(t.encoding.as_str(), "dynarray" | "bytestring"));
+                let data_key = if in_place {
+                    Self::traced_element_key(slot, false, 1, &mut steps)
+                } else {
+                    Self::traced_keccak(Self::slot_word(slot).to_vec(), &mut steps)
+                };
 
-                Ok(StaticKeyPath {
+                (entry, StaticKeyPath {
                     name: Box::leak(query.to_string().into_boxed_str()),
                     key: Key::Fixed(data_key),
                     offset: None,
@@ -729,42 +1087,15 @@ impl KeyResolver for EthereumKeyResolver {
                     zero_semantics: entry.zero_semantics,
                 })
             }
-        }
-    }
-
-    fn resolve_all(&self, layout: &LayoutInfo) -> Result<Vec<StaticKeyPath>, TraverseError> {
-        let mut paths = Vec::new();
-
-        for entry in &layout.storage {
-            // Get type information to determine if this is a mapping
-            if let Some(type_info) = layout.types.iter().find(|t| t.label == entry.type_name) {
-                if type_info.encoding == "mapping" {
-                    // Generate example mapping paths with common example keys
-                    let example_keys = self.generate_example_keys(
-                        type_info.key.as_ref().unwrap_or(&"t_address".to_string()),
-                    );
-
-                    for example_key in example_keys {
-                        let query = format!("{}[{}]", entry.label, example_key);
-                        if let Ok(path) = self.resolve(layout, &query) {
-                            paths.push(path);
-                        }
-                    }
-                } else {
-                    // Simple field - resolve directly
-                    if let Ok(path) = self.resolve(layout, &entry.label) {
-                        paths.push(path);
-                    }
-                }
-            } else {
-                // Fallback for entries without type info
-                if let Ok(path) = self.resolve(layout, &entry.label) {
-                    paths.push(path);
-                }
-            }
-        }
-
-        Ok(paths)
+        };
+
+        Ok(DerivationTrace {
+            query: query.to_string(),
+            field: entry.label.clone(),
+            type_name: entry.type_name.clone(),
+            steps,
+            path,
+        })
     }
 }
 
@@ -774,7 +1105,7 @@ mod tests {
 
     #[test]
     fn test_keccak256() {
-        let result = EthereumKeyResolver::keccak256(b"hello");
+        let result = keccak256(b"hello");
         // Known keccak256("hello") = 0x1c8aff950685c2ed4bc3174f3472287b56d9517b9c948127319a09a7a36deac8
         let expected =
             hex::decode("1c8aff950685c2ed4bc3174f3472287b56d9517b9c948127319a09a7a36deac8")
@@ -846,7 +1177,7 @@ mod tests {
         expected_input.extend_from_slice(&padded_key2);
         expected_input.extend_from_slice(&key1);
 
-        let expected = EthereumKeyResolver::keccak256(&expected_input);
+        let expected = keccak256(&expected_input);
         assert_eq!(result, expected);
     }
 
@@ -872,7 +1203,7 @@ mod tests {
         // Should be keccak256(slot) + index
         let mut slot_bytes = [0u8; 32];
         slot_bytes[24..].copy_from_slice(&slot.to_be_bytes());
-        let base_key = EthereumKeyResolver::keccak256(&slot_bytes);
+        let base_key = keccak256(&slot_bytes);
 
         // Manually add index to base_key
         let mut expected = base_key;
@@ -975,7 +1306,7 @@ mod tests {
         // Get base key for slot 5
         let mut slot_bytes = [0u8; 32];
         slot_bytes[24..].copy_from_slice(&5u64.to_be_bytes());
-        let base_key = EthereumKeyResolver::keccak256(&slot_bytes);
+        let base_key = keccak256(&slot_bytes);
         
         // Add u64::MAX to base key
         let key_with_max = EthereumKeyResolver::derive_array_key(5, u64::MAX);
@@ -1002,4 +1333,318 @@ mod tests {
         expected_key_1[31] = expected_key_1[31].wrapping_add(1);
         assert_eq!(expected_key_1, key_1);
     }
+
+    #[test]
+    fn test_resolve_traced_nested_mapping() {
+        use traverse_core::{StorageEntry, TypeInfo, ZeroSemantics};
+
+        let type_info = |label: &str, bytes: &str, encoding: &str| TypeInfo {
+            label: label.to_string(),
+            number_of_bytes: bytes.to_string(),
+            encoding: encoding.to_string(),
+            base: None,
+            key: None,
+            value: None,
+        };
+        let layout = LayoutInfo {
+            contract_name: "Token".to_string(),
+            storage: vec![StorageEntry {
+                label: "allowances".to_string(),
+                slot: "1".to_string(),
+                offset: 0,
+                type_name: "t_mapping_nested".to_string(),
+                zero_semantics: ZeroSemantics::ValidZero,
+            }],
+            types: vec![
+                TypeInfo { value: Some("t_uint256".to_string()), ..type_info("t_mapping_nested", "32", "mapping") },
+                type_info("t_uint256", "32", "inplace"),
+            ],
+            code_variables: Vec::new(),
+        };
+        let query = "allowances[0x1234][0x5678]";
+        let trace = EthereumKeyResolver.resolve_traced(&layout, query).unwrap();
+
+        assert_eq!(trace.path, EthereumKeyResolver.resolve(&layout, query).unwrap());
+        assert_eq!(trace.field, "allowances");
+        // Base slot, then a padded key and a hash per level
+        assert_eq!(trace.steps.len(), 5);
+        let DerivationStep::Keccak { preimage, digest } = &trace.steps[2] else {
+            panic!("Expected a keccak step");
+        };
+        assert_eq!(preimage[30..32], [0x12, 0x34]);
+        assert_eq!(preimage[32..], EthereumKeyResolver::slot_word(1));
+        let DerivationStep::Keccak { preimage, .. } = &trace.steps[4] else {
+            panic!("Expected a keccak step");
+        };
+        assert_eq!(preimage[32..], digest[..]);
+    }
+
+    #[test]
+    fn test_code_variables_have_no_slot() {
+        let mut layout = crate::presets::erc20();
+        layout.code_variables = vec![
+            traverse_core::CodeVariable {
+                label: "_decimals".to_string(),
+                type_name: "t_uint8".to_string(),
+                kind: CodeVariableKind::Immutable,
+            },
+            traverse_core::CodeVariable {
+                label: "FEE_BPS".to_string(),
+                type_name: "t_uint256".to_string(),
+                kind: CodeVariableKind::Constant,
+            },
+        ];
+
+        let error = EthereumKeyResolver.resolve(&layout, "_decimals").unwrap_err().to_string();
+        assert!(error.contains("immutable t_uint8") && error.contains("bytecode"), "{}", error);
+        let error = EthereumKeyResolver.resolve(&layout, "FEE_BPS[1]").unwrap_err().to_string();
+        assert!(error.contains("constant t_uint256"), "{}", error);
+        let error = EthereumKeyResolver.resolve(&layout, "_missing").unwrap_err().to_string();
+        assert!(error.contains("Field not found"), "{}", error);
+
+        // Code variables stay out of the commitment and of serialized layouts without them
+        let commitment = layout.commitment();
+        layout.code_variables.clear();
+        assert_eq!(layout.commitment(), commitment);
+        assert!(serde_json::to_value(&layout).unwrap().get("code_variables").is_none());
+    }
+
+    #[test]
+    fn test_resolve_string_and_bytes_keys() {
+        use traverse_core::{StorageEntry, TypeInfo, ZeroSemantics};
+
+        let type_info = |label: &str, encoding: &str, key: Option<&str>, value: Option<&str>| TypeInfo {
+            label: label.to_string(),
+            number_of_bytes: "32".to_string(),
+            encoding: encoding.to_string(),
+            base: None,
+            key: key.map(str::to_string),
+            value: value.map(str::to_string),
+        };
+        let entry = |label: &str, slot: &str, type_name: &str| StorageEntry {
+            label: label.to_string(),
+            slot: slot.to_string(),
+            offset: 0,
+            type_name: type_name.to_string(),
+            zero_semantics: ZeroSemantics::ValidZero,
+        };
+        let string_map = "t_mapping(t_string_memory_ptr,t_uint256)";
+        let bytes_map = "t_mapping(t_bytes_memory_ptr,t_mapping(t_address,t_uint256))";
+        let address_map = "t_mapping(t_address,t_uint256)";
+        let layout = LayoutInfo {
+            contract_name: "Registry".to_string(),
+            storage: vec![entry("balances", "0", string_map), entry("data", "1", bytes_map)],
+            types: vec![
+                type_info(string_map, "mapping", Some("t_string_memory_ptr"), Some("t_uint256")),
+                type_info(bytes_map, "mapping", Some("t_bytes_memory_ptr"), Some(address_map)),
+                type_info(address_map, "mapping", Some("t_address"), Some("t_uint256")),
+                type_info("t_bytes_memory_ptr", "bytes", None, None),
+                type_info("t_uint256", "inplace", None, None),
+            ],
+            code_variables: Vec::new(),
+        };
+        let key_of = |query: &str| match EthereumKeyResolver.resolve(&layout, query).unwrap().key {
+            Key::Fixed(key) => key,
+            other => panic!("Expected a fixed key, got {:?}", other),
+        };
+        let raw = |key: &[u8], slot: &[u8; 32]| keccak256(&[key, &slot[..]].concat());
+
+        // The key's bytes, unpadded and in any length
+        let alice = raw(b"alice", &EthereumKeyResolver::slot_word(0));
+        assert_eq!(key_of("balances[\"alice\"]"), alice);
+        assert_eq!(key_of("balances['alice']"), alice);
+        assert_eq!(key_of("balances[0x616c696365]"), alice);
+        assert_eq!(key_of("balances[\"\"]"), raw(b"", &EthereumKeyResolver::slot_word(0)));
+        let long = "a key longer than thirty-two bytes";
+        assert_eq!(key_of(&format!("balances[\"{}\"]", long)), raw(long.as_bytes(), &EthereumKeyResolver::slot_word(0)));
+        // Dots and brackets inside quotes are part of the key
+        assert_eq!(key_of("balances[\"a.b[c]\"]"), raw(b"a.b[c]", &EthereumKeyResolver::slot_word(0)));
+
+        // bytes keys the outer level, an address the inner one
+        let owner = "0x742d35cc6634c0532925a3b8d97c2e0d8b2d9c00";
+        let outer = raw(&[0xde, 0xad, 0xbe, 0xef], &EthereumKeyResolver::slot_word(1));
+        let inner = EthereumKeyResolver::derive_nested_mapping_key(&hex::decode(&owner[2..]).unwrap(), &outer);
+        assert_eq!(key_of(&format!("data[0xdeadbeef][{}]", owner)), inner);
+
+        let trace = EthereumKeyResolver.resolve_traced(&layout, "balances[\"alice\"]").unwrap();
+        assert!(!trace.steps.iter().any(|step| matches!(step, DerivationStep::PadKey { .. })));
+    }
+
+    #[test]
+    fn test_resolve_array_elements() {
+        use traverse_core::{StorageEntry, TypeInfo, ZeroSemantics};
+
+        let type_info = |label: &str, bytes: &str, encoding: &str, base: Option<&str>| TypeInfo {
+            label: label.to_string(),
+            number_of_bytes: bytes.to_string(),
+            encoding: encoding.to_string(),
+            base: base.map(str::to_string),
+            key: None,
+            value: None,
+        };
+        let entry = |label: &str, slot: &str, type_name: &str| StorageEntry {
+            label: label.to_string(),
+            slot: slot.to_string(),
+            offset: 0,
+            type_name: type_name.to_string(),
+            zero_semantics: ZeroSemantics::NeverWritten,
+        };
+        let layout = LayoutInfo {
+            contract_name: "Arrays".to_string(),
+            storage: vec![
+                entry("holders", "2", "t_array(t_address)dyn_storage"),
+                entry("positions", "3", "t_array(t_struct(Position))dyn_storage"),
+                entry("flags", "4", "t_array(t_uint64)dyn_storage"),
+                entry("fees", "5", "t_array(t_uint256)3_storage"),
+            ],
+            types: vec![
+                type_info("t_array(t_address)dyn_storage", "32", "dynamic_array", Some("t_address")),
+                type_info("t_array(t_struct(Position))dyn_storage", "32", "dynamic_array", Some("t_struct(Position)")),
+                type_info("t_array(t_uint64)dyn_storage", "32", "dynamic_array", Some("t_uint64")),
+                type_info("t_array(t_uint256)3_storage", "96", "inplace", Some("t_uint256")),
+                type_info("t_address", "20", "inplace", None),
+                type_info("t_struct(Position)", "96", "inplace", None),
+                type_info("t_uint64", "8", "inplace", None),
+                type_info("t_uint256", "32", "inplace", None),
+            ],
+            code_variables: Vec::new(),
+        };
+        let resolve = |query: &str| EthereumKeyResolver.resolve(&layout, query).unwrap();
+        let data = |slot: u64| keccak256(&EthereumKeyResolver::slot_word(slot));
+
+        // One slot per address
+        let path = resolve("holders[7]");
+        assert_eq!(path.key, Key::Fixed(EthereumKeyResolver::add_index(data(2), 7)));
+        assert_eq!((path.offset, path.field_size), (None, Some(20)));
+
+        // Three slots per struct, resolving to the element's first slot
+        let path = resolve("positions[2]");
+        assert_eq!(path.key, Key::Fixed(EthereumKeyResolver::add_index(data(3), 6)));
+        assert_eq!((path.offset, path.field_size), (None, Some(32)));
+
+        // Four uint64 per slot, packed from the low-order end
+        let path = resolve("flags[5]");
+        assert_eq!(path.key, Key::Fixed(EthereumKeyResolver::add_index(data(4), 1)));
+        assert_eq!((path.offset, path.field_size), (Some(8), Some(8)));
+
+        // Fixed-size arrays start at their declared slot
+        let path = resolve("fees[2]");
+        assert_eq!(path.key, Key::Fixed(EthereumKeyResolver::slot_word(7)));
+
+        let trace = EthereumKeyResolver.resolve_traced(&layout, "positions[2]").unwrap();
+        assert!(matches!(trace.steps.last(), Some(DerivationStep::AddIndex { index: 6, .. })));
+        assert!(EthereumKeyResolver.resolve(&layout, &format!("positions[{}]", u64::MAX)).is_err());
+    }
+
+    #[test]
+    fn test_resolve_encoding() {
+        use traverse_core::{StorageEntry, TypeInfo, ZeroSemantics};
+
+        let type_info = |label: &str, bytes: &str, encoding: &str, base: Option<&str>, value: Option<&str>| TypeInfo {
+            label: label.to_string(),
+            number_of_bytes: bytes.to_string(),
+            encoding: encoding.to_string(),
+            base: base.map(str::to_string),
+            key: value.map(|_| "t_address".to_string()),
+            value: value.map(str::to_string),
+        };
+        let entry = |label: &str, slot: &str, offset: u8, type_name: &str| StorageEntry {
+            label: label.to_string(),
+            slot: slot.to_string(),
+            offset,
+            type_name: type_name.to_string(),
+            zero_semantics: ZeroSemantics::NeverWritten,
+        };
+        let layout = LayoutInfo {
+            contract_name: "Pool".to_string(),
+            storage: vec![
+                entry("owner", "0", 0, "t_address"),
+                entry("tick", "0", 20, "t_int24"),
+                entry("liquidity", "1", 0, "t_mapping(t_address,t_int128)"),
+                entry("fees", "2", 0, "t_array(t_uint64)dyn_storage"),
+                entry("name", "3", 0, "t_string_storage"),
+            ],
+            types: vec![
+                type_info("t_address", "20", "inplace", None, None),
+                type_info("t_int24", "3", "inplace", None, None),
+                type_info("t_int128", "16", "inplace", None, None),
+                type_info("t_uint64", "8", "inplace", None, None),
+                type_info("t_mapping(t_address,t_int128)", "32", "mapping", None, Some("t_int128")),
+                type_info("t_array(t_uint64)dyn_storage", "32", "dynamic_array", Some("t_uint64"), None),
+                type_info("t_string_storage", "32", "bytes", None, None),
+            ],
+            code_variables: Vec::new(),
+        };
+        let encoding = |query: &str| EthereumKeyResolver.resolve_encoding(&layout, query).unwrap();
+
+        assert_eq!(encoding("tick"), FieldEncoding { signed: true, bits: 24, offset: 20 });
+        assert_eq!(encoding("owner"), FieldEncoding { signed: false, bits: 160, offset: 0 });
+        let holder = "0x742d35Cc6634C0532925a3b8D97C2e0D8b2D9C00";
+        assert_eq!(encoding(&format!("liquidity[{}]", holder)), FieldEncoding { signed: true, bits: 128, offset: 0 });
+        assert_eq!(encoding("fees[5]"), FieldEncoding { signed: false, bits: 64, offset: 8 });
+        assert_eq!(encoding("fees.length"), FieldEncoding::WORD);
+        assert_eq!(encoding("name"), FieldEncoding::WORD);
+        assert_eq!(encoding("eip1967.implementation"), FieldEncoding::WORD);
+
+        // Vyper labels have no t_ prefix
+        assert_eq!(EthereumKeyResolver::type_encoding(&layout, "int128").bits, 128);
+        assert!(EthereumKeyResolver.resolve_encoding(&layout, "missing").is_err());
+    }
+
+    #[test]
+    fn test_resolve_long_string_slots() {
+        use traverse_core::{StorageEntry, TypeInfo, ZeroSemantics};
+
+        let layout = LayoutInfo {
+            contract_name: "Token".to_string(),
+            storage: vec![StorageEntry {
+                label: "name".to_string(),
+                slot: "3".to_string(),
+                offset: 0,
+                type_name: "t_string_storage".to_string(),
+                zero_semantics: ZeroSemantics::NeverWritten,
+            }],
+            types: vec![TypeInfo {
+                label: "t_string_storage".to_string(),
+                number_of_bytes: "32".to_string(),
+                encoding: "bytes".to_string(),
+                base: None,
+                key: None,
+                value: None,
+            }],
+            code_variables: Vec::new(),
+        };
+
+        // "Wrapped Ether" in place: 13 bytes, 26 in the lowest byte
+        let mut head = [0u8; 32];
+        head[..13].copy_from_slice(b"Wrapped Ether");
+        head[31] = 26;
+        assert_eq!(EthereumKeyResolver::bytes_length(&head), Some(13));
+        let paths = EthereumKeyResolver.resolve_bytes_slots(&layout, "name", 13).unwrap();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].key, Key::Fixed(EthereumKeyResolver::slot_word(3)));
+
+        // 70 bytes spill into three data slots
+        let mut head = [0u8; 32];
+        head[31] = 141;
+        assert_eq!(EthereumKeyResolver::bytes_length(&head), Some(70));
+        let paths = EthereumKeyResolver.resolve_bytes_slots(&layout, "name", 70).unwrap();
+        assert_eq!(paths.len(), 4);
+        let data = keccak256(&EthereumKeyResolver::slot_word(3));
+        assert_eq!(paths[1].key, Key::Fixed(data));
+        assert_eq!(paths[3].key, Key::Fixed(EthereumKeyResolver::add_index(data, 2)));
+        assert_eq!(paths[3].name, "name.data[2]");
+
+        // A long marker with a short length is not a valid encoding
+        head[31] = 21;
+        assert_eq!(EthereumKeyResolver::bytes_length(&head), None);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_resolve_round_trips((layout, query) in traverse_core::testing::arb_layout_and_query()) {
+            traverse_core::testing::check_resolver_round_trip(&EthereumKeyResolver, &layout, &query)?;
+            traverse_core::testing::check_trace_matches(&EthereumKeyResolver, &layout, &query)?;
+        }
+    }
 }
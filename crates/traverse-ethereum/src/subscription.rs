@@ -0,0 +1,210 @@
+//! Live proof refresh from a `newHeads` subscription
+//!
+//! A [`ProofSubscription`] holds storage slots across contracts and re-fetches
+//! their proofs as the chain advances. Heads arrive as `eth_subscription`
+//! notifications over a WebSocket; proofs are fetched from the HTTP endpoint
+//! with [`EthereumProofFetcher::fetch_proofs`] at the head's number and
+//! checked against the head's state root, so each [`ProofRefresh`] is
+//! consistent within one block.
+//!
+//! Only the transport needs the `websocket` feature. [`parse_new_head`] and
+//! [`ProofSubscription::refresh`] let a caller with its own head source, such
+//! as a consensus client, drive refreshes directly.
+
+use traverse_core::TraverseError;
+
+use crate::receipts::{fixed, hex_field, quantity};
+use crate::{Eip1186Proof, EthereumProofFetcher, EvmChainAdapter, PinnedBlock};
+
+/// Request opening a `newHeads` subscription
+pub fn subscribe_request() -> serde_json::Value {
+    serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "eth_subscribe", "params": ["newHeads"] })
+}
+
+/// Subscription id from the reply to [`subscribe_request`]
+pub fn subscription_id(reply: &serde_json::Value) -> Result<String, TraverseError> {
+    if let Some(error) = reply.get("error") {
+        return Err(TraverseError::external_service(format!("eth_subscribe failed: {}", error)));
+    }
+    reply["result"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| TraverseError::external_service(format!("eth_subscribe returned {}", reply)))
+}
+
+/// The head in a `newHeads` notification for `subscription`
+///
+/// Returns `None` for any other message, such as notifications of another
+/// subscription on the same connection.
+pub fn parse_new_head(message: &serde_json::Value, subscription: &str) -> Result<Option<PinnedBlock>, TraverseError> {
+    if message["method"] != "eth_subscription" || message["params"]["subscription"] != subscription {
+        return Ok(None);
+    }
+    let header = &message["params"]["result"];
+    Ok(Some(PinnedBlock {
+        number: quantity(&hex_field(header, "number")?, "number")?,
+        hash: fixed(&hex_field(header, "hash")?, "hash")?,
+        state_root: fixed(&hex_field(header, "stateRoot")?, "stateRoot")?,
+    }))
+}
+
+/// Proofs of every subscribed slot at one head
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofRefresh {
+    pub block: PinnedBlock,
+    /// One proof per contract, in order of first subscription
+    pub proofs: Vec<Eip1186Proof>,
+}
+
+/// Storage slots whose proofs are refreshed on new heads
+#[derive(Debug, Clone)]
+pub struct ProofSubscription {
+    fetcher: EthereumProofFetcher,
+    adapter: EvmChainAdapter,
+    slots: Vec<(String, [u8; 32])>,
+    every_blocks: u64,
+}
+
+impl ProofSubscription {
+    /// A subscription fetching proofs from `rpc_url` within `adapter`'s limits
+    pub fn new(rpc_url: &str, adapter: EvmChainAdapter) -> Self {
+        Self { fetcher: EthereumProofFetcher::new(rpc_url, ""), adapter, slots: Vec::new(), every_blocks: 1 }
+    }
+
+    /// Refresh the proof of `key` in `address`'s storage
+    pub fn watch(mut self, address: &str, key: [u8; 32]) -> Self {
+        self.slots.push((address.to_string(), key));
+        self
+    }
+
+    /// Refresh only once `blocks` blocks have passed since the last refresh
+    pub fn every_blocks(mut self, blocks: u64) -> Self {
+        self.every_blocks = blocks.max(1);
+        self
+    }
+
+    pub fn slots(&self) -> &[(String, [u8; 32])] {
+        &self.slots
+    }
+
+    /// Whether a head at `number` is due after a refresh at `last`
+    pub fn is_due(&self, number: u64, last: Option<u64>) -> bool {
+        last.is_none_or(|last| number >= last.saturating_add(self.every_blocks))
+    }
+
+    /// Fetch every slot's proof at `head` and check it against the head's state root
+    ///
+    /// After a reorg the node may already serve a different block at the
+    /// head's number, which fails the state root check; the replacing head
+    /// is notified and refreshed in turn.
+    pub async fn refresh(&self, head: &PinnedBlock) -> Result<ProofRefresh, TraverseError> {
+        let slots: Vec<(&str, [u8; 32])> = self.slots.iter().map(|(address, key)| (address.as_str(), *key)).collect();
+        let proofs = self.fetcher.fetch_proofs(&slots, &format!("0x{:x}", head.number), &self.adapter).await?;
+        for proof in &proofs {
+            proof.verify(&head.state_root)?;
+        }
+        Ok(ProofRefresh { block: head.clone(), proofs })
+    }
+
+    /// Subscribe to new heads at `ws_url` and send a refresh for each due head
+    ///
+    /// A failed refresh is sent as an error and the subscription continues
+    /// with the next head. Returns once `refreshes` is closed, or with an
+    /// error when the connection fails or the node closes it; reconnecting
+    /// is up to the caller.
+    #[cfg(feature = "websocket")]
+    pub async fn run(
+        &self,
+        ws_url: &str,
+        refreshes: tokio::sync::mpsc::Sender<Result<ProofRefresh, TraverseError>>,
+    ) -> Result<(), TraverseError> {
+        use futures_util::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite::Message;
+
+        let ws_error = |e: tokio_tungstenite::tungstenite::Error| {
+            TraverseError::external_service(format!("WebSocket error on {}: {}", ws_url, e))
+        };
+        let (mut socket, _) = tokio_tungstenite::connect_async(ws_url).await.map_err(ws_error)?;
+        socket.send(Message::Text(subscribe_request().to_string())).await.map_err(ws_error)?;
+
+        let mut subscription = None;
+        let mut last = None;
+        while let Some(message) = socket.next().await {
+            let text = match message.map_err(ws_error)? {
+                Message::Text(text) => text,
+                Message::Close(_) => break,
+                _ => continue,
+            };
+            let message: serde_json::Value = serde_json::from_str(&text)
+                .map_err(|e| TraverseError::Serialization(format!("Invalid subscription message: {}", e)))?;
+
+            let Some(id) = &subscription else {
+                subscription = Some(subscription_id(&message)?);
+                continue;
+            };
+            let Some(head) = parse_new_head(&message, id)? else {
+                continue;
+            };
+            if !self.is_due(head.number, last) {
+                continue;
+            }
+            let refresh = self.refresh(&head).await;
+            if refresh.is_ok() {
+                last = Some(head.number);
+            }
+            if refreshes.send(refresh).await.is_err() {
+                return Ok(());
+            }
+        }
+        Err(TraverseError::external_service(format!("{} closed the newHeads subscription", ws_url)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(subscription: &str, number: &str) -> serde_json::Value {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "eth_subscription",
+            "params": {
+                "subscription": subscription,
+                "result": {
+                    "number": number,
+                    "hash": format!("0x{}", "11".repeat(32)),
+                    "stateRoot": format!("0x{}", "22".repeat(32)),
+                    "parentHash": format!("0x{}", "33".repeat(32))
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_parse_new_head() {
+        let head = parse_new_head(&notification("0xabc", "0x1b4"), "0xabc").unwrap().unwrap();
+        assert_eq!(head, PinnedBlock { number: 436, hash: [0x11; 32], state_root: [0x22; 32] });
+
+        assert_eq!(parse_new_head(&notification("0xdef", "0x1b4"), "0xabc").unwrap(), None);
+        assert_eq!(parse_new_head(&serde_json::json!({ "jsonrpc": "2.0", "id": 2, "result": true }), "0xabc").unwrap(), None);
+
+        let mut missing_root = notification("0xabc", "0x1b4");
+        missing_root["params"]["result"]["stateRoot"] = serde_json::Value::Null;
+        assert!(parse_new_head(&missing_root, "0xabc").is_err());
+    }
+
+    #[test]
+    fn test_subscription_id_and_interval() {
+        assert_eq!(subscription_id(&serde_json::json!({ "id": 1, "result": "0xabc" })).unwrap(), "0xabc");
+        let refused = serde_json::json!({ "id": 1, "error": { "code": -32601, "message": "notifications not supported" } });
+        assert!(subscription_id(&refused).is_err());
+
+        let subscription = ProofSubscription::new("http://localhost:8545", EvmChainAdapter::for_chain_id(1))
+            .watch("0xab", [1u8; 32])
+            .every_blocks(10);
+        assert_eq!(subscription.slots().len(), 1);
+        assert!(subscription.is_due(100, None));
+        assert!(!subscription.is_due(109, Some(100)));
+        assert!(subscription.is_due(110, Some(100)));
+    }
+}
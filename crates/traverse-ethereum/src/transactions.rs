@@ -0,0 +1,170 @@
+//! Transaction trie proofs for transaction inclusion
+//!
+//! Like receipts, a block's transactions trie is not served by nodes, so it
+//! is rebuilt from the block's raw transactions and checked against the
+//! header's `transactionsRoot`. Raw transactions are EIP-2718 envelopes,
+//! which are both the trie values and the preimages of the transaction
+//! hashes, so a [`TransactionProof`] ties a hash to its position in a block.
+
+use traverse_core::hash::keccak256;
+use traverse_core::TraverseError;
+
+use crate::mpt::{index_key, verify_trie_proof};
+
+/// Inclusion proof of a transaction in a block's transactions trie
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionProof {
+    pub block_hash: [u8; 32],
+    pub block_number: u64,
+    /// Header `transactionsRoot` the proof was built against
+    pub transactions_root: [u8; 32],
+    pub tx_hash: [u8; 32],
+    /// Position of the transaction in the block, the trie key being `rlp(tx_index)`
+    pub tx_index: usize,
+    /// Raw transaction, the trie value
+    pub transaction: Vec<u8>,
+    /// RLP trie nodes from the transactions root to the transaction's leaf
+    pub proof: Vec<Vec<u8>>,
+}
+
+impl TransactionProof {
+    /// Verify that `tx_hash` is the transaction at `tx_index` under `transactions_root`
+    ///
+    /// `transactions_root` must come from a trusted header; the root stored in
+    /// the proof is only what the fetching node claimed.
+    pub fn verify(&self, transactions_root: &[u8; 32]) -> Result<(), TraverseError> {
+        if keccak256(&self.transaction) != self.tx_hash {
+            return Err(TraverseError::Validation(format!(
+                "Transaction does not hash to 0x{}",
+                hex::encode(self.tx_hash)
+            )));
+        }
+        let proven = verify_trie_proof(transactions_root, &index_key(self.tx_index), &self.proof)?;
+        if proven.as_deref() != Some(self.transaction.as_slice()) {
+            return Err(TraverseError::Validation(format!(
+                "Transaction 0x{} is not at index {} of the transactions trie",
+                hex::encode(self.tx_hash),
+                self.tx_index
+            )));
+        }
+        Ok(())
+    }
+
+    /// Encoded size of the proof nodes
+    pub fn proof_size(&self) -> usize {
+        self.proof.iter().map(Vec::len).sum()
+    }
+}
+
+#[cfg(feature = "std")]
+impl crate::EthereumProofFetcher {
+    /// Prove that `tx_hash` is included in its block through the transactions trie
+    ///
+    /// Fetches every raw transaction of the block in JSON-RPC batches,
+    /// rebuilds the transactions trie and checks its root against the header
+    /// before extracting the proof. The node must serve
+    /// `eth_getRawTransactionByBlockHashAndIndex`, as geth, erigon, nethermind
+    /// and reth do.
+    #[cfg_attr(feature = "telemetry", tracing::instrument(name = "ethereum_fetch_transaction_proof", skip(self)))]
+    pub async fn fetch_transaction_proof(&self, tx_hash: &str) -> Result<TransactionProof, TraverseError> {
+        use crate::mpt::ordered_trie_proof;
+        use crate::receipts::{fixed, hex_field, hex_value, quantity};
+
+        let client = reqwest::Client::new();
+        let transaction = self.rpc(&client, "eth_getTransactionByHash", serde_json::json!([tx_hash])).await?;
+        if transaction["blockHash"].is_null() {
+            return Err(TraverseError::external_service(format!("Transaction {} is still pending", tx_hash)));
+        }
+        let block_hash: [u8; 32] = fixed(&hex_field(&transaction, "blockHash")?, "blockHash")?;
+        let block_hash_hex = format!("0x{}", hex::encode(block_hash));
+        let tx_index = quantity(&hex_field(&transaction, "transactionIndex")?, "transactionIndex")? as usize;
+
+        let header = self.rpc(&client, "eth_getBlockByHash", serde_json::json!([block_hash_hex, false])).await?;
+        let transactions_root: [u8; 32] = fixed(&hex_field(&header, "transactionsRoot")?, "transactionsRoot")?;
+        let block_number = quantity(&hex_field(&header, "number")?, "number")?;
+        let count = header["transactions"].as_array().map(Vec::len).ok_or_else(|| {
+            TraverseError::external_service("eth_getBlockByHash did not list the block's transactions".to_string())
+        })?;
+        if tx_index >= count {
+            return Err(TraverseError::external_service(format!(
+                "Block {} has {} transactions, no transaction {}",
+                block_number, count, tx_index
+            )));
+        }
+
+        let params: Vec<serde_json::Value> =
+            (0..count).map(|index| serde_json::json!([block_hash_hex, format!("0x{:x}", index)])).collect();
+        let raw_transactions = self
+            .rpc_batch(&client, "eth_getRawTransactionByBlockHashAndIndex", &params)
+            .await?
+            .iter()
+            .map(|raw| hex_value(raw, "raw transaction"))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let (root, proof) = ordered_trie_proof(&raw_transactions, tx_index);
+        if root != transactions_root {
+            return Err(TraverseError::ProofGeneration(format!(
+                "Rebuilt transactions root 0x{} does not match block {}'s transactionsRoot 0x{}",
+                hex::encode(root),
+                block_number,
+                hex::encode(transactions_root)
+            )));
+        }
+
+        let transaction_proof = TransactionProof {
+            block_hash,
+            block_number,
+            transactions_root,
+            tx_hash: fixed(&hex_field(&transaction, "hash")?, "hash")?,
+            tx_index,
+            transaction: raw_transactions[tx_index].clone(),
+            proof,
+        };
+        // Catches a node reporting another transaction at this index
+        transaction_proof.verify(&transactions_root)?;
+
+        #[cfg(feature = "telemetry")]
+        traverse_core::telemetry::record_proof_size("ethereum", "transaction", transaction_proof.proof_size());
+        Ok(transaction_proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpt::ordered_trie_proof;
+
+    #[test]
+    fn test_transaction_proof_round_trip() {
+        // Legacy list payloads and typed envelopes
+        let transactions: Vec<Vec<u8>> = (0..40u8)
+            .map(|i| {
+                let payload = rlp::encode_list::<Vec<u8>, _>(&[vec![i; 20], vec![i; i as usize % 50]]).to_vec();
+                match i % 3 {
+                    0 => payload,
+                    tx_type => [vec![tx_type], payload].concat(),
+                }
+            })
+            .collect();
+
+        let (root, proof) = ordered_trie_proof(&transactions, 17);
+        let transaction_proof = TransactionProof {
+            block_hash: [0u8; 32],
+            block_number: 1,
+            transactions_root: root,
+            tx_hash: keccak256(&transactions[17]),
+            tx_index: 17,
+            transaction: transactions[17].clone(),
+            proof,
+        };
+        transaction_proof.verify(&root).unwrap();
+
+        let wrong_hash = TransactionProof { tx_hash: keccak256(&transactions[18]), ..transaction_proof.clone() };
+        assert!(wrong_hash.verify(&root).is_err());
+        let wrong_index = TransactionProof { tx_index: 18, ..transaction_proof.clone() };
+        assert!(wrong_index.verify(&root).is_err());
+        let mut wrong_root = root;
+        wrong_root[31] ^= 1;
+        assert!(transaction_proof.verify(&wrong_root).is_err());
+    }
+}
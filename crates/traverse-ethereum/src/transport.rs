@@ -0,0 +1,238 @@
+//! JSON-RPC transport with retries and endpoint failover
+//!
+//! Public endpoints rate-limit, time out and go down. An [`RpcTransport`]
+//! sends each request to an ordered list of endpoints: a transient failure
+//! (a connection error, a timeout, HTTP 429 or 5xx, or a JSON-RPC rate-limit
+//! error) moves on to the next endpoint, and once every endpoint has failed
+//! the round starts over from the first after an exponential backoff. Any
+//! other failure, such as a JSON-RPC error for a bad request, is returned at
+//! once since every endpoint would answer it the same way.
+
+use std::time::Duration;
+
+use traverse_core::TraverseError;
+
+/// JSON-RPC error codes providers use for rate limiting
+const RATE_LIMIT_CODES: [i64; 2] = [-32005, 429];
+
+/// How often and how patiently an [`RpcTransport`] retries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Rounds over all endpoints after the first one fails
+    pub max_retries: u32,
+    /// Pause before the first retry, doubled for every later one
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// A single attempt per endpoint
+    pub const NONE: RetryPolicy = RetryPolicy { max_retries: 0, initial_backoff: Duration::ZERO, max_backoff: Duration::ZERO };
+
+    /// Pause before retry `retry`, counting from zero
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff.saturating_mul(1u32.checked_shl(retry).unwrap_or(u32::MAX)).min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { max_retries: 3, initial_backoff: Duration::from_millis(250), max_backoff: Duration::from_secs(4) }
+    }
+}
+
+/// Why one endpoint failed a request
+enum Failure {
+    /// Worth trying another endpoint or a later retry
+    Transient(String),
+    Fatal(TraverseError),
+}
+
+/// JSON-RPC over HTTP to a primary endpoint and its fallbacks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RpcTransport {
+    endpoints: Vec<String>,
+    retry: RetryPolicy,
+}
+
+impl RpcTransport {
+    /// Transport to `primary` with the default retry policy and no fallbacks
+    pub fn new(primary: impl Into<String>) -> Self {
+        Self { endpoints: vec![primary.into()], retry: RetryPolicy::default() }
+    }
+
+    /// Fail over to `fallbacks`, in order, when the endpoints before them fail
+    pub fn with_fallbacks<I, S>(mut self, fallbacks: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        for fallback in fallbacks {
+            let fallback = fallback.into();
+            if !self.endpoints.contains(&fallback) {
+                self.endpoints.push(fallback);
+            }
+        }
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Endpoints in the order they are tried
+    pub fn endpoints(&self) -> &[String] {
+        &self.endpoints
+    }
+
+    pub fn retry(&self) -> RetryPolicy {
+        self.retry
+    }
+
+    /// POST a JSON-RPC request or batch, returning the first usable response
+    ///
+    /// `method` only labels errors and metrics. Errors inside the response
+    /// are left to the caller, except rate-limit errors, which are retried.
+    pub async fn send(
+        &self,
+        client: &reqwest::Client,
+        method: &str,
+        body: &serde_json::Value,
+    ) -> Result<serde_json::Value, TraverseError> {
+        let mut failures = Vec::new();
+        for round in 0..=self.retry.max_retries {
+            if round > 0 {
+                tokio::time::sleep(self.retry.backoff(round - 1)).await;
+            }
+            for endpoint in &self.endpoints {
+                let attempt = post(client, endpoint, body);
+                #[cfg(feature = "telemetry")]
+                let attempt = traverse_core::telemetry::observe_rpc("ethereum", method, attempt);
+                match attempt.await {
+                    Ok(response) => return Ok(response),
+                    Err(Failure::Transient(reason)) => failures.push(format!("{}: {}", endpoint, reason)),
+                    Err(Failure::Fatal(error)) => return Err(error),
+                }
+            }
+        }
+        Err(TraverseError::external_service(format!(
+            "RPC request failed for {} after {} attempts; last error from {}",
+            method,
+            failures.len(),
+            failures.last().map(String::as_str).unwrap_or("no endpoint")
+        )))
+    }
+
+    /// Call `method` and return its result, failing on a JSON-RPC error or null result
+    pub async fn call(
+        &self,
+        client: &reqwest::Client,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, TraverseError> {
+        let request = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response = self.send(client, method, &request).await?;
+
+        if let Some(error) = response.get("error") {
+            return Err(TraverseError::external_service(format!("{} failed: {}", method, error)));
+        }
+        match response.get("result") {
+            Some(result) if !result.is_null() => Ok(result.clone()),
+            _ => Err(TraverseError::external_service(format!("No result for {}", method))),
+        }
+    }
+}
+
+async fn post(client: &reqwest::Client, endpoint: &str, body: &serde_json::Value) -> Result<serde_json::Value, Failure> {
+    let response = client.post(endpoint).json(body).send().await.map_err(|e| {
+        if e.is_builder() {
+            Failure::Fatal(TraverseError::Configuration(format!("Invalid RPC endpoint {}: {}", endpoint, e)))
+        } else {
+            Failure::Transient(e.to_string())
+        }
+    })?;
+
+    let status = response.status();
+    if status.as_u16() == 429 || status.is_server_error() {
+        return Err(Failure::Transient(format!("HTTP {}", status)));
+    }
+    let response: serde_json::Value = response.json().await.map_err(|e| {
+        if status.is_success() {
+            Failure::Transient(format!("unreadable response: {}", e))
+        } else {
+            Failure::Fatal(TraverseError::external_service(format!("RPC endpoint {} returned HTTP {}", endpoint, status)))
+        }
+    })?;
+    if is_rate_limited(&response) {
+        return Err(Failure::Transient(format!("rate limited: {}", response["error"])));
+    }
+    Ok(response)
+}
+
+/// Whether a single JSON-RPC response is a rate-limit error
+fn is_rate_limited(response: &serde_json::Value) -> bool {
+    response["error"]["code"].as_i64().is_some_and(|code| RATE_LIMIT_CODES.contains(&code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        let retry = RetryPolicy::default();
+        assert_eq!(retry.backoff(0), Duration::from_millis(250));
+        assert_eq!(retry.backoff(2), Duration::from_secs(1));
+        assert_eq!(retry.backoff(5), Duration::from_secs(4));
+        assert_eq!(retry.backoff(40), Duration::from_secs(4));
+        assert_eq!(RetryPolicy::NONE.backoff(3), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_fallbacks_keep_order_without_duplicates() {
+        let transport = RpcTransport::new("http://a").with_fallbacks(["http://b", "http://a", "http://c"]);
+        assert_eq!(transport.endpoints(), ["http://a", "http://b", "http://c"]);
+    }
+
+    #[test]
+    fn test_rate_limit_errors() {
+        assert!(is_rate_limited(&serde_json::json!({ "error": { "code": -32005, "message": "limit exceeded" } })));
+        assert!(!is_rate_limited(&serde_json::json!({ "error": { "code": -32602, "message": "invalid params" } })));
+        assert!(!is_rate_limited(&serde_json::json!([{ "error": { "code": -32005 } }])));
+        assert!(!is_rate_limited(&serde_json::json!({ "result": "0x1" })));
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_to_the_next_endpoint() {
+        // A closed port on the primary, and a fallback answering once
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let fallback = format!("http://{}", listener.local_addr().unwrap());
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let primary = format!("http://{}", closed.local_addr().unwrap());
+        drop(closed);
+
+        let server = tokio::spawn(async move {
+            use tokio::io::{AsyncReadExt, AsyncWriteExt};
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = vec![0u8; 4096];
+            let _ = socket.read(&mut request).await.unwrap();
+            let body = r#"{"jsonrpc":"2.0","id":1,"result":"0x1"}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+
+        let transport = RpcTransport::new(primary).with_fallbacks([fallback]).with_retry(RetryPolicy::NONE);
+        let result = transport.call(&reqwest::Client::new(), "eth_chainId", serde_json::json!([])).await.unwrap();
+        assert_eq!(result, "0x1");
+        server.await.unwrap();
+
+        let unreachable = RpcTransport::new(transport.endpoints()[0].clone()).with_retry(RetryPolicy::NONE);
+        let error = unreachable.call(&reqwest::Client::new(), "eth_chainId", serde_json::json!([])).await.unwrap_err();
+        assert!(error.to_string().contains("after 1 attempts"));
+    }
+}
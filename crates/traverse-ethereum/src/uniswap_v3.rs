@@ -0,0 +1,331 @@
+//! Typed decoding of Uniswap V3 pool storage
+//!
+//! A pool keeps its price in the packed `slot0` word and its liquidity
+//! bookkeeping in the `ticks` and `positions` mappings of four-slot structs.
+//! The helpers here derive those slots, and decode proven words into the
+//! pool's own fields, sign-extending the packed `int24`, `int56` and
+//! `int128` values:
+//!
+//! ```rust,ignore
+//! use traverse_ethereum::uniswap_v3::{self, Slot0, TickInfo};
+//!
+//! let keys = [uniswap_v3::slot(uniswap_v3::SLOT0)];
+//! let slot0 = Slot0::decode(&proof.storage_proofs[0].value);
+//! println!("tick {} price {}", slot0.tick, slot0.price());
+//!
+//! let tick_keys = uniswap_v3::struct_slots(uniswap_v3::tick_slot(-887_220));
+//! ```
+//!
+//! Values wider than 128 bits, such as `sqrtPriceX96` and the fee growth
+//! accumulators, are kept as big-endian bytes.
+
+use traverse_core::hash::keccak256;
+
+/// Packed `slot0`: price, tick, oracle indices, protocol fee and lock
+pub const SLOT0: u64 = 0;
+pub const FEE_GROWTH_GLOBAL0_X128: u64 = 1;
+pub const FEE_GROWTH_GLOBAL1_X128: u64 = 2;
+/// Protocol fees owed in token0 and token1, packed as two `uint128`
+pub const PROTOCOL_FEES: u64 = 3;
+/// In-range liquidity
+pub const LIQUIDITY: u64 = 4;
+/// `mapping(int24 => Tick.Info)`
+pub const TICKS: u64 = 5;
+/// `mapping(int16 => uint256)`
+pub const TICK_BITMAP: u64 = 6;
+/// `mapping(bytes32 => Position.Info)`, keyed by [`position_key`]
+pub const POSITIONS: u64 = 7;
+/// `Oracle.Observation[65535]`, one slot each
+pub const OBSERVATIONS: u64 = 8;
+
+/// Storage key of a pool slot number
+pub fn slot(slot: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&slot.to_be_bytes());
+    word
+}
+
+/// A signed value as its 32-byte two's complement, as `abi.encode` pads it
+fn signed_word(value: i64) -> [u8; 32] {
+    let mut word = if value < 0 { [0xff; 32] } else { [0u8; 32] };
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// First of the four slots of `ticks[tick]`
+pub fn tick_slot(tick: i32) -> [u8; 32] {
+    let mut preimage = [0u8; 64];
+    preimage[..32].copy_from_slice(&signed_word(tick.into()));
+    preimage[32..].copy_from_slice(&slot(TICKS));
+    keccak256(&preimage)
+}
+
+/// Slot of `tickBitmap[word_position]`, the word holding ticks `word_position * 256 * spacing` on
+pub fn tick_bitmap_slot(word_position: i16) -> [u8; 32] {
+    let mut preimage = [0u8; 64];
+    preimage[..32].copy_from_slice(&signed_word(word_position.into()));
+    preimage[32..].copy_from_slice(&slot(TICK_BITMAP));
+    keccak256(&preimage)
+}
+
+/// Key of a position, `keccak256(abi.encodePacked(owner, tickLower, tickUpper))`
+///
+/// The owner of a position minted through the periphery is the
+/// `NonfungiblePositionManager`, not the NFT holder.
+pub fn position_key(owner: &[u8; 20], tick_lower: i32, tick_upper: i32) -> [u8; 32] {
+    let mut preimage = [0u8; 26];
+    preimage[..20].copy_from_slice(owner);
+    preimage[20..23].copy_from_slice(&tick_lower.to_be_bytes()[1..]);
+    preimage[23..].copy_from_slice(&tick_upper.to_be_bytes()[1..]);
+    keccak256(&preimage)
+}
+
+/// First of the four slots of `positions[key]`
+pub fn position_slot(key: &[u8; 32]) -> [u8; 32] {
+    let mut preimage = [0u8; 64];
+    preimage[..32].copy_from_slice(key);
+    preimage[32..].copy_from_slice(&slot(POSITIONS));
+    keccak256(&preimage)
+}
+
+/// The four consecutive slots of a struct starting at `base`
+pub fn struct_slots(base: [u8; 32]) -> [[u8; 32]; 4] {
+    let mut slots = [base; 4];
+    for (offset, slot) in slots.iter_mut().enumerate().skip(1) {
+        let mut carry = offset as u16;
+        for byte in slot.iter_mut().rev() {
+            let sum = *byte as u16 + carry;
+            *byte = sum as u8;
+            carry = sum >> 8;
+        }
+    }
+    slots
+}
+
+/// Field of `size` bytes at byte `offset` of a packed word, counted from the low end
+fn field(word: &[u8; 32], offset: usize, size: usize) -> &[u8] {
+    &word[32 - offset - size..32 - offset]
+}
+
+fn unsigned(bytes: &[u8]) -> u128 {
+    bytes.iter().fold(0, |value, byte| (value << 8) | u128::from(*byte))
+}
+
+/// A two's complement value of up to 16 bytes, sign-extended
+fn signed(bytes: &[u8]) -> i128 {
+    let fill = if bytes.first().is_some_and(|b| b & 0x80 != 0) { 0xff } else { 0 };
+    let mut extended = [fill; 16];
+    extended[16 - bytes.len()..].copy_from_slice(bytes);
+    i128::from_be_bytes(extended)
+}
+
+fn hex_word(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// The pool's packed `slot0`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slot0 {
+    /// `sqrt(token1 / token0)` as a Q64.96, big-endian
+    pub sqrt_price_x96: [u8; 20],
+    pub tick: i32,
+    pub observation_index: u16,
+    pub observation_cardinality: u16,
+    pub observation_cardinality_next: u16,
+    /// Protocol fee denominators, token0 in the low four bits
+    pub fee_protocol: u8,
+    /// False while a swap or mint holds the reentrancy lock
+    pub unlocked: bool,
+}
+
+impl Slot0 {
+    pub fn decode(word: &[u8; 32]) -> Self {
+        let mut sqrt_price_x96 = [0u8; 20];
+        sqrt_price_x96.copy_from_slice(field(word, 0, 20));
+        Self {
+            sqrt_price_x96,
+            tick: signed(field(word, 20, 3)) as i32,
+            observation_index: unsigned(field(word, 23, 2)) as u16,
+            observation_cardinality: unsigned(field(word, 25, 2)) as u16,
+            observation_cardinality_next: unsigned(field(word, 27, 2)) as u16,
+            fee_protocol: word[2],
+            unlocked: word[1] != 0,
+        }
+    }
+
+    /// Price of token0 in token1, in base units and not adjusted for decimals
+    ///
+    /// As an `f64` this is approximate; circuits should work from
+    /// `sqrt_price_x96` itself.
+    pub fn price(&self) -> f64 {
+        let sqrt_price = self.sqrt_price_x96.iter().fold(0f64, |value, byte| value * 256.0 + *byte as f64);
+        let ratio = sqrt_price / 2f64.powi(96);
+        ratio * ratio
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "sqrt_price_x96": hex_word(&self.sqrt_price_x96),
+            "price": self.price(),
+            "tick": self.tick,
+            "observation_index": self.observation_index,
+            "observation_cardinality": self.observation_cardinality,
+            "observation_cardinality_next": self.observation_cardinality_next,
+            "fee_protocol": self.fee_protocol,
+            "unlocked": self.unlocked,
+        })
+    }
+}
+
+/// A pool's `ticks[tick]` entry, `Tick.Info`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TickInfo {
+    /// Liquidity of all positions with this tick as a bound
+    pub liquidity_gross: u128,
+    /// Liquidity added when the price crosses the tick upwards, negative for upper bounds
+    pub liquidity_net: i128,
+    pub fee_growth_outside0_x128: [u8; 32],
+    pub fee_growth_outside1_x128: [u8; 32],
+    pub tick_cumulative_outside: i64,
+    pub seconds_per_liquidity_outside_x128: [u8; 20],
+    pub seconds_outside: u32,
+    pub initialized: bool,
+}
+
+impl TickInfo {
+    /// Decode the words of the four slots from [`tick_slot`], in order
+    pub fn decode(words: &[[u8; 32]; 4]) -> Self {
+        let mut seconds_per_liquidity_outside_x128 = [0u8; 20];
+        seconds_per_liquidity_outside_x128.copy_from_slice(field(&words[3], 7, 20));
+        Self {
+            liquidity_gross: unsigned(field(&words[0], 0, 16)),
+            liquidity_net: signed(field(&words[0], 16, 16)),
+            fee_growth_outside0_x128: words[1],
+            fee_growth_outside1_x128: words[2],
+            tick_cumulative_outside: signed(field(&words[3], 0, 7)) as i64,
+            seconds_per_liquidity_outside_x128,
+            seconds_outside: unsigned(field(&words[3], 27, 4)) as u32,
+            initialized: words[3][0] != 0,
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "liquidity_gross": self.liquidity_gross.to_string(),
+            "liquidity_net": self.liquidity_net.to_string(),
+            "fee_growth_outside0_x128": hex_word(&self.fee_growth_outside0_x128),
+            "fee_growth_outside1_x128": hex_word(&self.fee_growth_outside1_x128),
+            "tick_cumulative_outside": self.tick_cumulative_outside,
+            "seconds_per_liquidity_outside_x128": hex_word(&self.seconds_per_liquidity_outside_x128),
+            "seconds_outside": self.seconds_outside,
+            "initialized": self.initialized,
+        })
+    }
+}
+
+/// A pool's `positions[key]` entry, `Position.Info`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PositionInfo {
+    pub liquidity: u128,
+    pub fee_growth_inside0_last_x128: [u8; 32],
+    pub fee_growth_inside1_last_x128: [u8; 32],
+    pub tokens_owed0: u128,
+    pub tokens_owed1: u128,
+}
+
+impl PositionInfo {
+    /// Decode the words of the four slots from [`position_slot`], in order
+    pub fn decode(words: &[[u8; 32]; 4]) -> Self {
+        Self {
+            liquidity: unsigned(field(&words[0], 0, 16)),
+            fee_growth_inside0_last_x128: words[1],
+            fee_growth_inside1_last_x128: words[2],
+            tokens_owed0: unsigned(field(&words[3], 0, 16)),
+            tokens_owed1: unsigned(field(&words[3], 16, 16)),
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "liquidity": self.liquidity.to_string(),
+            "fee_growth_inside0_last_x128": hex_word(&self.fee_growth_inside0_last_x128),
+            "fee_growth_inside1_last_x128": hex_word(&self.fee_growth_inside1_last_x128),
+            "tokens_owed0": self.tokens_owed0.to_string(),
+            "tokens_owed1": self.tokens_owed1.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_slot0() {
+        // sqrtPriceX96 = 2^96, tick -1, observations 7/100/120, fee protocol 0x44, unlocked
+        let mut word = [0u8; 32];
+        word[19] = 1;
+        word[9..12].copy_from_slice(&[0xff, 0xff, 0xff]);
+        word[7..9].copy_from_slice(&7u16.to_be_bytes());
+        word[5..7].copy_from_slice(&100u16.to_be_bytes());
+        word[3..5].copy_from_slice(&120u16.to_be_bytes());
+        word[2] = 0x44;
+        word[1] = 1;
+
+        let slot0 = Slot0::decode(&word);
+        assert_eq!(slot0.tick, -1);
+        assert_eq!(slot0.price(), 1.0);
+        assert_eq!(
+            (slot0.observation_index, slot0.observation_cardinality, slot0.observation_cardinality_next),
+            (7, 100, 120)
+        );
+        assert_eq!(slot0.fee_protocol, 0x44);
+        assert!(slot0.unlocked);
+        assert_eq!(slot0.to_json()["sqrt_price_x96"], format!("0x{}{}", "00".repeat(7), "01000000000000000000000000"));
+    }
+
+    #[test]
+    fn test_decode_tick_and_position() {
+        let mut words = [[0u8; 32]; 4];
+        words[0][16..].copy_from_slice(&500u128.to_be_bytes());
+        words[0][..16].copy_from_slice(&(-500i128).to_be_bytes());
+        words[1][31] = 9;
+        // tickCumulativeOutside -2, secondsOutside 3600, initialized
+        words[3][25..].copy_from_slice(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe]);
+        words[3][1..5].copy_from_slice(&3600u32.to_be_bytes());
+        words[3][0] = 1;
+
+        let tick = TickInfo::decode(&words);
+        assert_eq!((tick.liquidity_gross, tick.liquidity_net), (500, -500));
+        assert_eq!(tick.fee_growth_outside0_x128[31], 9);
+        assert_eq!(tick.tick_cumulative_outside, -2);
+        assert_eq!(tick.seconds_outside, 3600);
+        assert!(tick.initialized);
+        assert_eq!(tick.to_json()["liquidity_net"], "-500");
+
+        // The same words read as a position: tokens owed sit in the last slot
+        let position = PositionInfo::decode(&words);
+        assert_eq!(position.liquidity, 500);
+        assert_eq!(position.tokens_owed0, u128::from_be_bytes(words[3][16..].try_into().unwrap()));
+    }
+
+    #[test]
+    fn test_slot_derivation() {
+        // Negative ticks are sign-extended into the mapping key
+        let mut preimage = [0xffu8; 64];
+        preimage[29..32].copy_from_slice(&[0xf2, 0x76, 0x4c]);
+        preimage[32..].copy_from_slice(&slot(TICKS));
+        assert_eq!(tick_slot(-887_220), keccak256(&preimage));
+
+        let owner = [0xc3; 20];
+        let mut packed = owner.to_vec();
+        packed.extend_from_slice(&[0xf2, 0x76, 0x4c, 0x0d, 0x89, 0xb4]);
+        assert_eq!(position_key(&owner, -887_220, 887_220), keccak256(&packed));
+
+        let mut base = [0u8; 32];
+        base[30..].copy_from_slice(&[0x01, 0xfe]);
+        let slots = struct_slots(base);
+        assert_eq!(slots[2][30..], [0x02, 0x00]);
+        assert_eq!(slots[3][30..], [0x02, 0x01]);
+    }
+}
@@ -0,0 +1,442 @@
+//! Verkle tree proofs (EIP-6800)
+//!
+//! After the Verkle transition, a block carries an execution witness: every
+//! tree leaf the block touched, with its value before and after, and one IPA
+//! multiproof of the pre-state values against the parent block's state root.
+//! `eth_getProof` has no Verkle equivalent, so storage is proven by fetching
+//! a block's witness and looking up the slot's leaf in it.
+//!
+//! Leaves are addressed by a 31-byte stem and a one-byte suffix. The stem is
+//! a Pedersen hash over the Bandersnatch curve, and the multiproof is checked
+//! with the same commitments, so both are left to a [`VerkleCrypto`] backend.
+//! This module handles the tree embedding, witness decoding and lookup, and
+//! the structural checks that need no curve arithmetic.
+//!
+//! No Bandersnatch backend ships with traverse yet: [`UnavailableBandersnatch`]
+//! is the default, and fails every curve operation with a clear error, so
+//! witnesses can be fetched and checked for shape but not proven.
+
+use traverse_core::TraverseError;
+
+/// Children per inner node and leaves per stem
+pub const VERKLE_NODE_WIDTH: usize = 256;
+/// Suffix of the leaf packing an account's version, code size, nonce and balance
+pub const BASIC_DATA_LEAF_KEY: u8 = 0;
+/// Suffix of the account's code hash leaf
+pub const CODE_HASH_LEAF_KEY: u8 = 1;
+/// First suffix of the header storage slots in the account's own stem
+pub const HEADER_STORAGE_OFFSET: u64 = 64;
+/// First suffix of the first code chunks in the account's own stem
+pub const CODE_OFFSET: u64 = 128;
+/// IPA rounds for a width-256 vector, the length of `cl` and `cr`
+pub const IPA_ROUNDS: usize = 8;
+
+/// Curve operations the Verkle tree needs, supplied by a Bandersnatch backend
+pub trait VerkleCrypto {
+    /// EIP-6800 `get_tree_key` without its last byte: the stem of `tree_index` for `address`
+    fn tree_key_stem(&self, address: &[u8; 20], tree_index: &[u8; 32]) -> Result<[u8; 31], TraverseError>;
+
+    /// Check the witness's multiproof of its pre-state values against `state_root`
+    fn verify_multiproof(&self, state_root: &[u8; 32], witness: &ExecutionWitness) -> Result<(), TraverseError>;
+}
+
+/// Backend used when no Bandersnatch implementation is available
+///
+/// Every operation fails with [`TraverseError::FeatureNotSupported`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnavailableBandersnatch;
+
+impl UnavailableBandersnatch {
+    fn error(operation: &str) -> TraverseError {
+        TraverseError::FeatureNotSupported(format!(
+            "Bandersnatch backend unavailable: cannot {} without a VerkleCrypto implementation",
+            operation
+        ))
+    }
+}
+
+impl VerkleCrypto for UnavailableBandersnatch {
+    fn tree_key_stem(&self, _address: &[u8; 20], _tree_index: &[u8; 32]) -> Result<[u8; 31], TraverseError> {
+        Err(Self::error("derive Verkle tree keys"))
+    }
+
+    fn verify_multiproof(&self, _state_root: &[u8; 32], _witness: &ExecutionWitness) -> Result<(), TraverseError> {
+        Err(Self::error("verify the Verkle multiproof"))
+    }
+}
+
+/// Tree index and suffix of storage slot `slot`
+///
+/// The first 64 slots share the account's stem with its header fields and
+/// code; the rest are offset by 256^31 into the main storage range, wrapping
+/// as the EIP's 256-bit arithmetic does.
+pub fn storage_slot_position(slot: &[u8; 32]) -> ([u8; 32], u8) {
+    let header_slots = CODE_OFFSET - HEADER_STORAGE_OFFSET;
+    let small = slot[..31].iter().all(|b| *b == 0) && u64::from(slot[31]) < header_slots;
+
+    let mut position = *slot;
+    if small {
+        position[31] += HEADER_STORAGE_OFFSET as u8;
+    } else {
+        // MAIN_STORAGE_OFFSET = 256^31
+        position[0] = position[0].wrapping_add(1);
+    }
+
+    let mut tree_index = [0u8; 32];
+    tree_index[1..].copy_from_slice(&position[..31]);
+    (tree_index, position[31])
+}
+
+/// Value of one leaf before and after the block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuffixDiff {
+    pub suffix: u8,
+    /// Pre-state value, `None` if the leaf was absent
+    pub current_value: Option<[u8; 32]>,
+    /// Post-state value, `None` if the block did not write the leaf
+    pub new_value: Option<[u8; 32]>,
+}
+
+/// Leaves of one stem touched by the block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StemStateDiff {
+    pub stem: [u8; 31],
+    pub suffix_diffs: Vec<SuffixDiff>,
+}
+
+/// Inner-product argument opening the multiproof's aggregated polynomial
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IpaProof {
+    pub cl: Vec<[u8; 32]>,
+    pub cr: Vec<[u8; 32]>,
+    pub final_evaluation: [u8; 32],
+}
+
+/// Multiproof of every stem in a witness
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerkleProof {
+    /// Stems found instead of absent ones, proving their absence
+    pub other_stems: Vec<[u8; 31]>,
+    /// Per stem, the extension status in the low 3 bits and the depth above them
+    pub depth_extension_present: Vec<u8>,
+    /// Commitments of the inner nodes on the stems' paths, root excluded
+    pub commitments_by_path: Vec<[u8; 32]>,
+    pub d: [u8; 32],
+    pub ipa_proof: IpaProof,
+}
+
+/// A block's execution witness
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionWitness {
+    /// Stems in ascending order
+    pub state_diff: Vec<StemStateDiff>,
+    pub verkle_proof: VerkleProof,
+}
+
+fn hex_array<const N: usize>(value: &serde_json::Value, field: &str) -> Result<[u8; N], TraverseError> {
+    let text = value
+        .as_str()
+        .ok_or_else(|| TraverseError::Serialization(format!("Missing {} in execution witness", field)))?;
+    let bytes = hex::decode(text.strip_prefix("0x").unwrap_or(text))
+        .map_err(|e| TraverseError::Serialization(format!("Invalid hex in {}: {}", field, e)))?;
+    bytes
+        .try_into()
+        .map_err(|_| TraverseError::Serialization(format!("{} is not {} bytes", field, N)))
+}
+
+fn optional_value(value: &serde_json::Value, field: &str) -> Result<Option<[u8; 32]>, TraverseError> {
+    if value.is_null() {
+        return Ok(None);
+    }
+    hex_array(value, field).map(Some)
+}
+
+fn json_list<'a>(value: &'a serde_json::Value, field: &str) -> Result<&'a Vec<serde_json::Value>, TraverseError> {
+    value
+        .as_array()
+        .ok_or_else(|| TraverseError::Serialization(format!("Missing {} in execution witness", field)))
+}
+
+impl ExecutionWitness {
+    /// Decode the `executionWitness` object of a Verkle block
+    pub fn from_response(witness: &serde_json::Value) -> Result<Self, TraverseError> {
+        let state_diff = json_list(&witness["stateDiff"], "stateDiff")?
+            .iter()
+            .map(|stem| {
+                let suffix_diffs = json_list(&stem["suffixDiffs"], "suffixDiffs")?
+                    .iter()
+                    .map(|diff| {
+                        let suffix = diff["suffix"]
+                            .as_u64()
+                            .filter(|suffix| *suffix < VERKLE_NODE_WIDTH as u64)
+                            .ok_or_else(|| TraverseError::Serialization("Invalid suffix in stateDiff".to_string()))?;
+                        Ok(SuffixDiff {
+                            suffix: suffix as u8,
+                            current_value: optional_value(&diff["currentValue"], "currentValue")?,
+                            new_value: optional_value(&diff["newValue"], "newValue")?,
+                        })
+                    })
+                    .collect::<Result<_, TraverseError>>()?;
+                Ok(StemStateDiff { stem: hex_array(&stem["stem"], "stem")?, suffix_diffs })
+            })
+            .collect::<Result<_, TraverseError>>()?;
+
+        let proof = &witness["verkleProof"];
+        let words = |field: &str| -> Result<Vec<[u8; 32]>, TraverseError> {
+            json_list(&proof[field], field)?.iter().map(|word| hex_array(word, field)).collect()
+        };
+        let depth_extension_present = match &proof["depthExtensionPresent"] {
+            serde_json::Value::String(text) => hex::decode(text.strip_prefix("0x").unwrap_or(text))
+                .map_err(|e| TraverseError::Serialization(format!("Invalid hex in depthExtensionPresent: {}", e)))?,
+            _ => return Err(TraverseError::Serialization("Missing depthExtensionPresent in execution witness".to_string())),
+        };
+        let ipa = &proof["ipaProof"];
+        let ipa_words = |field: &str| -> Result<Vec<[u8; 32]>, TraverseError> {
+            json_list(&ipa[field], field)?.iter().map(|word| hex_array(word, field)).collect()
+        };
+
+        Ok(Self {
+            state_diff,
+            verkle_proof: VerkleProof {
+                other_stems: json_list(&proof["otherStems"], "otherStems")?
+                    .iter()
+                    .map(|stem| hex_array(stem, "otherStems"))
+                    .collect::<Result<_, _>>()?,
+                depth_extension_present,
+                commitments_by_path: words("commitmentsByPath")?,
+                d: hex_array(&proof["d"], "d")?,
+                ipa_proof: IpaProof {
+                    cl: ipa_words("cl")?,
+                    cr: ipa_words("cr")?,
+                    final_evaluation: hex_array(&ipa["finalEvaluation"], "finalEvaluation")?,
+                },
+            },
+        })
+    }
+
+    /// Check the witness's shape: sorted unique stems and suffixes, one
+    /// depth/extension byte per stem, and a full-length IPA proof
+    pub fn check_structure(&self) -> Result<(), TraverseError> {
+        let invalid = |message: String| Err(TraverseError::Validation(format!("Invalid execution witness: {}", message)));
+
+        if !self.state_diff.windows(2).all(|pair| pair[0].stem < pair[1].stem) {
+            return invalid("stems are not in strictly ascending order".to_string());
+        }
+        for stem in &self.state_diff {
+            if !stem.suffix_diffs.windows(2).all(|pair| pair[0].suffix < pair[1].suffix) {
+                return invalid(format!("suffixes of stem 0x{} are not in ascending order", hex::encode(stem.stem)));
+            }
+        }
+        let proof = &self.verkle_proof;
+        if proof.depth_extension_present.len() != self.state_diff.len() {
+            return invalid(format!(
+                "{} depth/extension entries for {} stems",
+                proof.depth_extension_present.len(),
+                self.state_diff.len()
+            ));
+        }
+        if proof.ipa_proof.cl.len() != IPA_ROUNDS || proof.ipa_proof.cr.len() != IPA_ROUNDS {
+            return invalid(format!(
+                "IPA proof has {}/{} rounds, expected {}",
+                proof.ipa_proof.cl.len(),
+                proof.ipa_proof.cr.len(),
+                IPA_ROUNDS
+            ));
+        }
+        Ok(())
+    }
+
+    /// Pre-state value of leaf (`stem`, `suffix`)
+    ///
+    /// Errors if the witness does not cover the leaf, since it then proves
+    /// nothing about it; `Ok(None)` means the leaf is proven absent.
+    pub fn pre_state_value(&self, stem: &[u8; 31], suffix: u8) -> Result<Option<[u8; 32]>, TraverseError> {
+        self.state_diff
+            .binary_search_by(|diff| diff.stem.cmp(stem))
+            .ok()
+            .and_then(|index| self.state_diff[index].suffix_diffs.iter().find(|diff| diff.suffix == suffix))
+            .map(|diff| diff.current_value)
+            .ok_or_else(|| {
+                TraverseError::Validation(format!(
+                    "Execution witness does not cover leaf 0x{}{:02x}",
+                    hex::encode(stem),
+                    suffix
+                ))
+            })
+    }
+
+    /// Pre-state value of storage slot `slot` of `address`, absent slots being zero
+    pub fn storage_value(
+        &self,
+        crypto: &impl VerkleCrypto,
+        address: &[u8; 20],
+        slot: &[u8; 32],
+    ) -> Result<[u8; 32], TraverseError> {
+        let (tree_index, suffix) = storage_slot_position(slot);
+        let stem = crypto.tree_key_stem(address, &tree_index)?;
+        Ok(self.pre_state_value(&stem, suffix)?.unwrap_or([0u8; 32]))
+    }
+
+    /// Verify the witness's pre-state against the parent block's state root
+    pub fn verify(&self, crypto: &impl VerkleCrypto, parent_state_root: &[u8; 32]) -> Result<(), TraverseError> {
+        self.check_structure()?;
+        crypto.verify_multiproof(parent_state_root, self)
+    }
+
+    /// Serialize for a circuit witness
+    ///
+    /// Layout, integers little-endian: stem count (u32), then per stem the
+    /// stem (31), suffix count (u16) and per suffix the suffix (1), a presence
+    /// byte (bit 0 current, bit 1 new) and the present values (32 each); then
+    /// the proof: other stem count (u32) and stems (31 each), the
+    /// depth/extension bytes (one per stem), commitment count (u32) and
+    /// commitments (32 each), `d` (32), IPA rounds (u8), `cl` and `cr`
+    /// (32 each per round) and the final evaluation (32).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend((self.state_diff.len() as u32).to_le_bytes());
+        for stem in &self.state_diff {
+            out.extend(stem.stem);
+            out.extend((stem.suffix_diffs.len() as u16).to_le_bytes());
+            for diff in &stem.suffix_diffs {
+                out.push(diff.suffix);
+                out.push(diff.current_value.is_some() as u8 | (diff.new_value.is_some() as u8) << 1);
+                out.extend(diff.current_value.iter().chain(&diff.new_value).flatten());
+            }
+        }
+
+        let proof = &self.verkle_proof;
+        out.extend((proof.other_stems.len() as u32).to_le_bytes());
+        out.extend(proof.other_stems.iter().flatten());
+        out.extend(&proof.depth_extension_present);
+        out.extend((proof.commitments_by_path.len() as u32).to_le_bytes());
+        out.extend(proof.commitments_by_path.iter().flatten());
+        out.extend(proof.d);
+        out.push(proof.ipa_proof.cl.len() as u8);
+        out.extend(proof.ipa_proof.cl.iter().chain(&proof.ipa_proof.cr).flatten());
+        out.extend(proof.ipa_proof.final_evaluation);
+        out
+    }
+}
+
+#[cfg(feature = "std")]
+impl crate::EthereumProofFetcher {
+    /// Fetch the execution witness of `block`, a tag or hex block number
+    ///
+    /// The witness proves state as of the start of the block, against the
+    /// parent block's state root. Errors on chains that have not moved to
+    /// Verkle, whose blocks carry no witness.
+    #[cfg_attr(feature = "telemetry", tracing::instrument(name = "ethereum_fetch_verkle_witness", skip(self)))]
+    pub async fn fetch_verkle_witness(&self, block: &str) -> Result<ExecutionWitness, TraverseError> {
+        let client = reqwest::Client::new();
+        let header = self.rpc(&client, "eth_getBlockByNumber", serde_json::json!([block, false])).await?;
+        let witness = header.get("executionWitness").filter(|witness| !witness.is_null()).ok_or_else(|| {
+            TraverseError::FeatureNotSupported(format!(
+                "Block {} has no execution witness; the chain has not moved to Verkle",
+                block
+            ))
+        })?;
+        let witness = ExecutionWitness::from_response(witness)?;
+        witness.check_structure()?;
+
+        #[cfg(feature = "telemetry")]
+        traverse_core::telemetry::record_proof_size("ethereum", "verkle", witness.to_bytes().len());
+        Ok(witness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn witness_json() -> serde_json::Value {
+        let word = |byte: u8| format!("0x{}", hex::encode([byte; 32]));
+        serde_json::json!({
+            "stateDiff": [
+                {
+                    "stem": format!("0x{}", hex::encode([0x11u8; 31])),
+                    "suffixDiffs": [
+                        { "suffix": 0, "currentValue": word(1), "newValue": word(2) },
+                        { "suffix": 64, "currentValue": null, "newValue": null }
+                    ]
+                },
+                {
+                    "stem": format!("0x{}", hex::encode([0x22u8; 31])),
+                    "suffixDiffs": [{ "suffix": 3, "currentValue": word(9), "newValue": null }]
+                }
+            ],
+            "verkleProof": {
+                "otherStems": [],
+                "depthExtensionPresent": "0x0a0a",
+                "commitmentsByPath": [word(5)],
+                "d": word(6),
+                "ipaProof": {
+                    "cl": vec![word(7); IPA_ROUNDS],
+                    "cr": vec![word(8); IPA_ROUNDS],
+                    "finalEvaluation": word(4)
+                }
+            }
+        })
+    }
+
+    struct FixedStem;
+
+    impl VerkleCrypto for FixedStem {
+        fn tree_key_stem(&self, _address: &[u8; 20], tree_index: &[u8; 32]) -> Result<[u8; 31], TraverseError> {
+            Ok(if tree_index.iter().all(|b| *b == 0) { [0x11; 31] } else { [0x22; 31] })
+        }
+
+        fn verify_multiproof(&self, state_root: &[u8; 32], _witness: &ExecutionWitness) -> Result<(), TraverseError> {
+            match state_root {
+                [0xaa, ..] => Ok(()),
+                _ => Err(TraverseError::Validation("multiproof rejected".to_string())),
+            }
+        }
+    }
+
+    #[test]
+    fn test_storage_slot_position() {
+        assert_eq!(storage_slot_position(&[0u8; 32]), ([0u8; 32], 64));
+
+        let mut slot = [0u8; 32];
+        slot[31] = 63;
+        assert_eq!(storage_slot_position(&slot), ([0u8; 32], 127));
+
+        // Slot 64 moves to main storage: position 256^31 + 64
+        slot[31] = 64;
+        let mut tree_index = [0u8; 32];
+        tree_index[1] = 1;
+        assert_eq!(storage_slot_position(&slot), (tree_index, 64));
+    }
+
+    #[test]
+    fn test_decode_and_look_up_witness() {
+        let witness = ExecutionWitness::from_response(&witness_json()).unwrap();
+        witness.check_structure().unwrap();
+
+        assert_eq!(witness.pre_state_value(&[0x11; 31], BASIC_DATA_LEAF_KEY).unwrap(), Some([1u8; 32]));
+        assert_eq!(witness.pre_state_value(&[0x11; 31], 64).unwrap(), None);
+        assert!(witness.pre_state_value(&[0x11; 31], 65).is_err());
+        assert!(witness.pre_state_value(&[0x33; 31], 0).is_err());
+
+        // Slot 0 is header storage in the account stem, proven absent
+        assert_eq!(witness.storage_value(&FixedStem, &[0u8; 20], &[0u8; 32]).unwrap(), [0u8; 32]);
+
+        witness.verify(&FixedStem, &[0xaa; 32]).unwrap();
+        assert!(witness.verify(&FixedStem, &[0xbb; 32]).is_err());
+
+        let mut unsorted = witness.clone();
+        unsorted.state_diff.swap(0, 1);
+        assert!(unsorted.verify(&FixedStem, &[0xaa; 32]).is_err());
+
+        // Without a backend the shape is still checked, but nothing is proven
+        let error = witness.verify(&UnavailableBandersnatch, &[0xaa; 32]).unwrap_err();
+        assert!(error.to_string().contains("Bandersnatch backend unavailable"));
+        assert!(witness.storage_value(&UnavailableBandersnatch, &[0u8; 20], &[0u8; 32]).is_err());
+        assert!(unsorted.verify(&UnavailableBandersnatch, &[0xaa; 32]).unwrap_err().to_string().contains("ascending"));
+
+        let bytes = witness.to_bytes();
+        assert_eq!(&bytes[..4], &2u32.to_le_bytes());
+        assert_eq!(&bytes[bytes.len() - 32..], &[4u8; 32]);
+    }
+}
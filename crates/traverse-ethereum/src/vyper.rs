@@ -0,0 +1,298 @@
+//! Vyper storage layout compiler
+//!
+//! Converts the output of `vyper -f layout` into the canonical `LayoutInfo`,
+//! so Vyper contracts are queried like Solidity ones. Vyper reports each
+//! variable's type as source text and its first slot:
+//!
+//! ```json
+//! {"storage_layout": {"balanceOf": {"type": "HashMap[address, uint256]", "slot": 1}}}
+//! ```
+//!
+//! Vyper 0.4 nests the variables of imported modules under the module's name;
+//! they become `module.name` entries. Reentrancy locks are skipped. The
+//! `code_layout` immutables are kept as code variables, which have no slot.
+//!
+//! Vyper never packs: every value starts a slot, so each type's size is its
+//! slot count times 32 and every offset is zero. The Vyper-specific
+//! encodings are `hashmap`, `dynarray` and `bytestring`; see
+//! [`EthereumKeyResolver`](crate::EthereumKeyResolver) for how they resolve.
+
+use serde_json::{Map, Value};
+use std::path::Path;
+use traverse_core::{
+    CodeVariable, CodeVariableKind, LayoutCompiler, LayoutInfo, StorageEntry, TraverseError, TypeInfo, ZeroSemantics,
+};
+
+/// Layout compiler for `vyper -f layout` output
+///
+/// ```rust,ignore
+/// use traverse_ethereum::VyperLayoutCompiler;
+/// use traverse_core::LayoutCompiler;
+/// use std::path::Path;
+///
+/// let layout = VyperLayoutCompiler.compile_layout(Path::new("Pool.layout.json"))?;
+/// let key = EthereumKeyResolver.resolve(&layout, "balanceOf[0x...]")?;
+/// ```
+pub struct VyperLayoutCompiler;
+
+impl VyperLayoutCompiler {
+    /// Whether `json` looks like `vyper -f layout` output
+    pub fn is_vyper_layout(json: &Value) -> bool {
+        json.get("storage_layout").is_some_and(Value::is_object)
+    }
+
+    /// Compile a layout from the JSON text of `vyper -f layout`
+    pub fn compile_str(&self, contract_name: &str, content: &str) -> Result<LayoutInfo, TraverseError> {
+        let json: Value = serde_json::from_str(content)?;
+        let variables = json
+            .get("storage_layout")
+            .and_then(Value::as_object)
+            .ok_or_else(|| TraverseError::LayoutCompilation("Missing storage_layout object".to_string()))?;
+
+        let mut storage = Vec::new();
+        let mut types = Vec::new();
+        Self::collect(variables, "", &mut storage, &mut types)?;
+        storage.sort_by_key(|entry: &StorageEntry| entry.slot.parse::<u64>().unwrap_or(u64::MAX));
+
+        let mut code_variables = Vec::new();
+        if let Some(immutables) = json.get("code_layout").and_then(Value::as_object) {
+            Self::collect_immutables(immutables, "", &mut code_variables);
+        }
+
+        Ok(LayoutInfo { contract_name: contract_name.to_string(), storage, types, code_variables })
+    }
+
+    /// Add the immutables of one module's `code_layout`, recursing into imported modules
+    fn collect_immutables(variables: &Map<String, Value>, prefix: &str, code_variables: &mut Vec<CodeVariable>) {
+        for (name, variable) in variables {
+            let label = format!("{}{}", prefix, name);
+            match variable.get("type").and_then(Value::as_str) {
+                Some(type_name) => code_variables.push(CodeVariable {
+                    label,
+                    type_name: type_name.trim().to_string(),
+                    kind: CodeVariableKind::Immutable,
+                }),
+                None => {
+                    if let Some(fields) = variable.as_object() {
+                        Self::collect_immutables(fields, &format!("{}.", label), code_variables);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Add the variables of one module, recursing into imported modules
+    fn collect(
+        variables: &Map<String, Value>,
+        prefix: &str,
+        storage: &mut Vec<StorageEntry>,
+        types: &mut Vec<TypeInfo>,
+    ) -> Result<(), TraverseError> {
+        for (name, variable) in variables {
+            let label = format!("{}{}", prefix, name);
+            let Some(fields) = variable.as_object() else {
+                return Err(TraverseError::LayoutCompilation(format!("Invalid layout entry for {}", label)));
+            };
+            let Some(slot) = fields.get("slot") else {
+                Self::collect(fields, &format!("{}.", label), storage, types)?;
+                continue;
+            };
+
+            let type_name = fields
+                .get("type")
+                .and_then(Value::as_str)
+                .ok_or_else(|| TraverseError::LayoutCompilation(format!("Missing type for {}", label)))?
+                .trim();
+            if type_name.starts_with("nonreentrant") {
+                continue;
+            }
+            let slot = slot
+                .as_u64()
+                .or_else(|| slot.as_str().and_then(|s| s.parse().ok()))
+                .ok_or_else(|| TraverseError::LayoutCompilation(format!("Invalid slot for {}", label)))?;
+
+            // Structs are only known by name; trust the compiler's slot count
+            let n_slots = fields.get("n_slots").and_then(Value::as_u64);
+            Self::register_type(type_name, n_slots, types)?;
+
+            storage.push(StorageEntry {
+                label,
+                slot: slot.to_string(),
+                offset: 0,
+                type_name: type_name.to_string(),
+                zero_semantics: ZeroSemantics::NeverWritten,
+            });
+        }
+        Ok(())
+    }
+
+    /// Add `type_name` and the types it is built from, returning its slot count
+    fn register_type(type_name: &str, n_slots: Option<u64>, types: &mut Vec<TypeInfo>) -> Result<u64, TraverseError> {
+        let invalid = || TraverseError::LayoutCompilation(format!("Invalid Vyper type: {}", type_name));
+        let type_info = |encoding: &str, slots: u64, base: Option<&str>, key: Option<&str>, value: Option<&str>| {
+            TypeInfo {
+                label: type_name.to_string(),
+                number_of_bytes: slots.saturating_mul(32).to_string(),
+                encoding: encoding.to_string(),
+                base: base.map(str::to_string),
+                key: key.map(str::to_string),
+                value: value.map(str::to_string),
+            }
+        };
+
+        let (info, slots) = if let Some(inner) = generic_args(type_name, "HashMap") {
+            let (key, value) = split_top_level(inner).ok_or_else(invalid)?;
+            Self::register_type(key, None, types)?;
+            Self::register_type(value, None, types)?;
+            (type_info("hashmap", 1, None, Some(key), Some(value)), 1)
+        } else if let Some(inner) = generic_args(type_name, "DynArray") {
+            let (base, length) = split_top_level(inner).ok_or_else(invalid)?;
+            let length: u64 = length.parse().map_err(|_| invalid())?;
+            let stride = Self::register_type(base, None, types)?;
+            let slots = length.checked_mul(stride).and_then(|s| s.checked_add(1)).ok_or_else(invalid)?;
+            (type_info("dynarray", slots, Some(base), None, None), slots)
+        } else if let Some(length) =
+            generic_args(type_name, "String").or_else(|| generic_args(type_name, "Bytes"))
+        {
+            let length: u64 = length.trim().parse().map_err(|_| invalid())?;
+            let slots = length.div_ceil(32) + 1;
+            (type_info("bytestring", slots, None, None, None), slots)
+        } else if let Some((base, length)) = fixed_array(type_name) {
+            let length: u64 = length.parse().map_err(|_| invalid())?;
+            let stride = Self::register_type(base, None, types)?;
+            let slots = length.checked_mul(stride).ok_or_else(invalid)?;
+            (type_info("inplace", slots, Some(base), None, None), slots)
+        } else {
+            // Scalars take a slot each, right-aligned
+            let slots = n_slots.unwrap_or(1).max(1);
+            (type_info("inplace", slots, None, None, None), slots)
+        };
+
+        if !types.iter().any(|t| t.label == info.label) {
+            types.push(info);
+        }
+        Ok(n_slots.unwrap_or(slots))
+    }
+}
+
+/// The arguments of `Name[...]`, if `type_name` is one
+fn generic_args<'a>(type_name: &'a str, name: &str) -> Option<&'a str> {
+    type_name.strip_prefix(name)?.trim_start().strip_prefix('[')?.strip_suffix(']')
+}
+
+/// `T[N]` as `(T, N)`
+fn fixed_array(type_name: &str) -> Option<(&str, &str)> {
+    let inner = type_name.strip_suffix(']')?;
+    let open = inner.rfind('[')?;
+    Some((inner[..open].trim(), inner[open + 1..].trim()))
+}
+
+/// Split `A, B` at its one comma outside brackets
+fn split_top_level(args: &str) -> Option<(&str, &str)> {
+    let mut depth = 0usize;
+    for (i, c) in args.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth = depth.checked_sub(1)?,
+            ',' if depth == 0 => return Some((args[..i].trim(), args[i + 1..].trim())),
+            _ => {}
+        }
+    }
+    None
+}
+
+impl LayoutCompiler for VyperLayoutCompiler {
+    /// Compile `vyper -f layout` output, named after the file stem
+    fn compile_layout(&self, abi_path: &Path) -> Result<LayoutInfo, TraverseError> {
+        let content = std::fs::read_to_string(abi_path)?;
+        let contract_name = abi_path.file_stem().and_then(|s| s.to_str()).unwrap_or("UnknownContract");
+        self.compile_str(contract_name, &content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EthereumKeyResolver;
+    #[cfg(feature = "ethereum")]
+    use traverse_core::hash::keccak256;
+    use traverse_core::{Key, KeyResolver};
+
+    const POOL_LAYOUT: &str = r#"{
+        "storage_layout": {
+            "$.nonreentrant_key": {"type": "nonreentrant lock", "slot": 0, "n_slots": 1},
+            "owner": {"type": "address", "slot": 1, "n_slots": 1},
+            "coins": {"type": "address[2]", "slot": 2, "n_slots": 2},
+            "balanceOf": {"type": "HashMap[address, uint256]", "slot": 4, "n_slots": 1},
+            "allowance": {"type": "HashMap[address, HashMap[address, uint256]]", "slot": 5, "n_slots": 1},
+            "history": {"type": "DynArray[uint8, 4]", "slot": 6, "n_slots": 5},
+            "name": {"type": "String[64]", "slot": 11, "n_slots": 3},
+            "ownable": {
+                "pending_owner": {"type": "address", "slot": 14, "n_slots": 1}
+            }
+        },
+        "code_layout": {
+            "DECIMALS": {"type": "uint8", "offset": 0, "length": 32}
+        }
+    }"#;
+
+    #[cfg(feature = "ethereum")]
+    fn slot_word(slot: u64) -> [u8; 32] {
+        let mut word = [0u8; 32];
+        word[24..].copy_from_slice(&slot.to_be_bytes());
+        word
+    }
+
+    #[test]
+    fn test_compile_vyper_layout() {
+        let layout = VyperLayoutCompiler.compile_str("Pool", POOL_LAYOUT).unwrap();
+        let labels: Vec<&str> = layout.storage.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(labels, ["owner", "coins", "balanceOf", "allowance", "history", "name", "ownable.pending_owner"]);
+        assert!(layout.storage.iter().all(|s| s.offset == 0));
+
+        let type_of = |label: &str| layout.types.iter().find(|t| t.label == label).unwrap();
+        assert_eq!(type_of("HashMap[address, uint256]").encoding, "hashmap");
+        assert_eq!(type_of("HashMap[address, HashMap[address, uint256]]").value.as_deref(), Some("HashMap[address, uint256]"));
+        assert_eq!(type_of("DynArray[uint8, 4]").number_of_bytes, "160");
+        assert_eq!(type_of("String[64]").encoding, "bytestring");
+        assert_eq!(type_of("uint8").number_of_bytes, "32");
+        assert_eq!(layout.code_variable("DECIMALS").unwrap().kind, CodeVariableKind::Immutable);
+    }
+
+    #[cfg(feature = "ethereum")]
+    #[test]
+    fn test_resolve_vyper_keys() {
+        let layout = VyperLayoutCompiler.compile_str("Pool", POOL_LAYOUT).unwrap();
+        let resolver = EthereumKeyResolver;
+        let holder = hex::decode("742d35cc6634c0532925a3b8d97c2e0d8b2d9c00").unwrap();
+        let mut padded = [0u8; 32];
+        padded[12..].copy_from_slice(&holder);
+
+        // HashMap keys hash the slot first
+        let path = resolver.resolve(&layout, "balanceOf[0x742d35cc6634c0532925a3b8d97c2e0d8b2d9c00]").unwrap();
+        let expected = keccak256(&[slot_word(4), padded].concat());
+        assert_eq!(path.key, Key::Fixed(expected));
+
+        let path = resolver
+            .resolve(&layout, "allowance[0x742d35cc6634c0532925a3b8d97c2e0d8b2d9c00][0x742d35cc6634c0532925a3b8d97c2e0d8b2d9c00]")
+            .unwrap();
+        let outer = keccak256(&[slot_word(5), padded].concat());
+        assert_eq!(path.key, Key::Fixed(keccak256(&[outer, padded].concat())));
+
+        // DynArray elements follow the length, one slot each
+        assert_eq!(resolver.resolve(&layout, "history[2]").unwrap().key, Key::Fixed(slot_word(9)));
+        assert_eq!(resolver.resolve(&layout, "history[2]").unwrap().offset, None);
+        assert_eq!(resolver.resolve(&layout, "coins[1]").unwrap().key, Key::Fixed(slot_word(3)));
+        assert_eq!(resolver.resolve(&layout, "name.data").unwrap().key, Key::Fixed(slot_word(12)));
+        assert_eq!(resolver.resolve(&layout, "ownable.pending_owner").unwrap().key, Key::Fixed(slot_word(14)));
+
+        let error = resolver.resolve(&layout, "DECIMALS").unwrap_err().to_string();
+        assert!(error.contains("immutable"), "{}", error);
+    }
+
+    #[test]
+    fn test_rejects_non_vyper_layout() {
+        assert!(VyperLayoutCompiler.compile_str("Token", r#"{"storage": [], "types": {}}"#).is_err());
+        assert!(!VyperLayoutCompiler::is_vyper_layout(&serde_json::json!({"storage": []})));
+    }
+}
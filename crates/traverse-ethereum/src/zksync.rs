@@ -0,0 +1,259 @@
+//! zkSync Era storage proofs
+//!
+//! Era keeps the storage of every contract in one sparse Merkle tree of
+//! depth 256 hashed with Blake2s-256, rather than a Merkle-Patricia trie per
+//! account. A slot's tree key is `blake2s(address as a word ‖ slot)`, read as
+//! a little-endian integer whose bit `d` picks the branch at depth `d`
+//! counted from the leaves. Leaves hash as `blake2s(leaf index ‖ value)`,
+//! where the leaf index numbers entries by insertion from 1; an absent slot
+//! is the empty leaf with index 0 and a zero value.
+//!
+//! `zks_getProof` serves proofs against the tree root of an L1 batch, which
+//! the batch's commitment carries to L1. Proof paths list sibling hashes
+//! from the root down and omit the empty-subtree hashes at the bottom.
+
+use core::ops::Range;
+
+use blake2::{Blake2s256, Digest};
+use traverse_core::TraverseError;
+
+use crate::receipts::{fixed, hex_field, hex_value, quantity};
+use crate::EthereumProofFetcher;
+
+pub const TREE_DEPTH: usize = 256;
+
+fn blake2s(parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Blake2s256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+/// Tree key of `slot` in `address`'s storage
+pub fn hashed_key(address: &[u8; 20], slot: &[u8; 32]) -> [u8; 32] {
+    let mut padded = [0u8; 32];
+    padded[12..].copy_from_slice(address);
+    blake2s(&[&padded, slot])
+}
+
+fn leaf_hash(value: &[u8; 32], leaf_index: u64) -> [u8; 32] {
+    blake2s(&[&leaf_index.to_be_bytes(), value])
+}
+
+/// Hashes of empty subtrees by height, the empty leaf first
+fn empty_subtree_hashes() -> Vec<[u8; 32]> {
+    let mut hashes = Vec::with_capacity(TREE_DEPTH);
+    hashes.push(leaf_hash(&[0u8; 32], 0));
+    for depth in 1..TREE_DEPTH {
+        let below = hashes[depth - 1];
+        hashes.push(blake2s(&[&below, &below]));
+    }
+    hashes
+}
+
+/// Branch of `key` at `depth`, counted from the leaves
+fn key_bit(key: &[u8; 32], depth: usize) -> bool {
+    key[depth / 8] >> (depth % 8) & 1 == 1
+}
+
+/// Hash `node` at height `levels.start` up to height `levels.end` along `key`
+fn fold(mut node: [u8; 32], key: &[u8; 32], levels: Range<usize>, sibling: impl Fn(usize) -> [u8; 32]) -> [u8; 32] {
+    for depth in levels {
+        let sibling = sibling(depth);
+        node = if key_bit(key, depth) { blake2s(&[&sibling, &node]) } else { blake2s(&[&node, &sibling]) };
+    }
+    node
+}
+
+/// One slot of an [`EraProof`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EraStorageProof {
+    pub key: [u8; 32],
+    pub value: [u8; 32],
+    /// Leaf index in the tree, 0 for an absent slot
+    pub index: u64,
+    /// Sibling hashes from the root down, without the empty ones at the bottom
+    pub proof: Vec<[u8; 32]>,
+}
+
+impl EraStorageProof {
+    /// Tree root the proof leads to for the slot of `address`
+    pub fn root_hash(&self, address: &[u8; 20]) -> Result<[u8; 32], TraverseError> {
+        if self.proof.len() > TREE_DEPTH {
+            return Err(TraverseError::Validation(format!("Merkle path of {} hashes is deeper than the tree", self.proof.len())));
+        }
+        if self.index == 0 && self.value != [0u8; 32] {
+            return Err(TraverseError::Validation("Absent slot with a non-zero value".to_string()));
+        }
+        let empty = empty_subtree_hashes();
+        let trimmed = TREE_DEPTH - self.proof.len();
+        let sibling = |depth: usize| if depth < trimmed { empty[depth] } else { self.proof[TREE_DEPTH - 1 - depth] };
+        Ok(fold(leaf_hash(&self.value, self.index), &hashed_key(address, &self.key), 0..TREE_DEPTH, sibling))
+    }
+}
+
+/// A `zks_getProof` response with the batch root it proves against
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EraProof {
+    pub address: [u8; 20],
+    pub l1_batch: u64,
+    /// Tree root hash of the batch, as committed to L1
+    pub root_hash: [u8; 32],
+    pub storage_proofs: Vec<EraStorageProof>,
+}
+
+impl EraProof {
+    /// Decode the `result` of `zks_getProof` for batch `l1_batch` with root `root_hash`
+    pub fn from_response(result: &serde_json::Value, l1_batch: u64, root_hash: [u8; 32]) -> Result<Self, TraverseError> {
+        let storage_proofs = result["storageProof"]
+            .as_array()
+            .ok_or_else(|| TraverseError::Serialization("Missing storageProof in zks_getProof response".to_string()))?
+            .iter()
+            .map(|slot| {
+                let index = match &slot["index"] {
+                    serde_json::Value::Number(index) => index.as_u64(),
+                    other => hex_value(other, "index").ok().and_then(|bytes| quantity(&bytes, "index").ok()),
+                }
+                .ok_or_else(|| TraverseError::Serialization("Missing index in zks_getProof response".to_string()))?;
+                let proof = slot["proof"]
+                    .as_array()
+                    .ok_or_else(|| TraverseError::Serialization("Missing proof in zks_getProof response".to_string()))?
+                    .iter()
+                    .map(|hash| fixed(&hex_value(hash, "proof")?, "proof"))
+                    .collect::<Result<_, _>>()?;
+                Ok(EraStorageProof {
+                    key: fixed(&hex_field(slot, "key")?, "key")?,
+                    value: fixed(&hex_field(slot, "value")?, "value")?,
+                    index,
+                    proof,
+                })
+            })
+            .collect::<Result<_, TraverseError>>()?;
+
+        Ok(Self { address: fixed(&hex_field(result, "address")?, "address")?, l1_batch, root_hash, storage_proofs })
+    }
+
+    /// Verify every slot against `root_hash`, a batch root the caller trusts
+    pub fn verify(&self, root_hash: &[u8; 32]) -> Result<(), TraverseError> {
+        for slot in &self.storage_proofs {
+            if slot.root_hash(&self.address)? != *root_hash {
+                return Err(TraverseError::Validation(format!(
+                    "Storage proof of 0x{} does not lead to the batch root",
+                    hex::encode(slot.key)
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let word = |bytes: &[u8]| format!("0x{}", hex::encode(bytes));
+        serde_json::json!({
+            "address": word(&self.address),
+            "l1_batch": self.l1_batch,
+            "root_hash": word(&self.root_hash),
+            "storageProof": self.storage_proofs.iter().map(|slot| serde_json::json!({
+                "key": word(&slot.key),
+                "value": word(&slot.value),
+                "index": slot.index,
+                "proof": slot.proof.iter().map(|hash| word(hash)).collect::<Vec<_>>(),
+            })).collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl EthereumProofFetcher {
+    /// Fetch Era proofs of `keys` against the tree root of `l1_batch`, or the latest batch
+    ///
+    /// The fetcher's endpoint must be an Era node. The latest sealed batch
+    /// may not have a tree root yet, which is an error naming the batch.
+    #[cfg_attr(feature = "telemetry", tracing::instrument(name = "ethereum_fetch_era_proof", skip(self, keys)))]
+    pub async fn fetch_era_proof(&self, keys: &[[u8; 32]], l1_batch: Option<u64>) -> Result<EraProof, TraverseError> {
+        let client = reqwest::Client::new();
+        let l1_batch = match l1_batch {
+            Some(batch) => batch,
+            None => {
+                let latest = self.rpc(&client, "zks_L1BatchNumber", serde_json::json!([])).await?;
+                quantity(&hex_value(&latest, "zks_L1BatchNumber")?, "zks_L1BatchNumber")?
+            }
+        };
+        let details = self.rpc(&client, "zks_getL1BatchDetails", serde_json::json!([l1_batch])).await?;
+        let root_hash = hex_field(&details, "rootHash")
+            .map_err(|_| TraverseError::external_service(format!("L1 batch {} has no tree root yet", l1_batch)))?;
+        let root_hash = fixed(&root_hash, "rootHash")?;
+
+        let keys: Vec<String> = keys.iter().map(|key| format!("0x{}", hex::encode(key))).collect();
+        let result = self
+            .rpc(&client, "zks_getProof", serde_json::json!([self.contract_address, keys, l1_batch]))
+            .await?;
+        let proof = EraProof::from_response(&result, l1_batch, root_hash)?;
+        proof.verify(&root_hash)?;
+        Ok(proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: [u8; 20] = [0xab; 20];
+
+    /// Two slots of `ADDRESS` in an otherwise empty tree, with the root and both proofs
+    fn two_slot_tree() -> ([u8; 32], EraStorageProof, EraStorageProof) {
+        let (a, b) = ([1u8; 32], [2u8; 32]);
+        let (key_a, key_b) = (hashed_key(&ADDRESS, &a), hashed_key(&ADDRESS, &b));
+        let (leaf_a, leaf_b) = (leaf_hash(&[7u8; 32], 1), leaf_hash(&[8u8; 32], 2));
+
+        // The keys share their branches above `split` and part there
+        let split = (0..TREE_DEPTH).rev().find(|depth| key_bit(&key_a, *depth) != key_bit(&key_b, *depth)).unwrap();
+        let empty = empty_subtree_hashes();
+        let node_a = fold(leaf_a, &key_a, 0..split, |depth| empty[depth]);
+        let node_b = fold(leaf_b, &key_b, 0..split, |depth| empty[depth]);
+        let root = fold(node_a, &key_a, split..TREE_DEPTH, |depth| if depth == split { node_b } else { empty[depth] });
+
+        let path = |sibling: [u8; 32]| {
+            let mut path: Vec<[u8; 32]> = (split + 1..TREE_DEPTH).rev().map(|depth| empty[depth]).collect();
+            path.push(sibling);
+            path
+        };
+        (
+            root,
+            EraStorageProof { key: a, value: [7u8; 32], index: 1, proof: path(node_b) },
+            EraStorageProof { key: b, value: [8u8; 32], index: 2, proof: path(node_a) },
+        )
+    }
+
+    #[test]
+    fn test_verify_era_proof() {
+        let (root, a, b) = two_slot_tree();
+        let proof = EraProof { address: ADDRESS, l1_batch: 500_000, root_hash: root, storage_proofs: vec![a.clone(), b] };
+        proof.verify(&root).unwrap();
+        assert!(proof.verify(&[0u8; 32]).is_err());
+
+        let mut forged = proof.clone();
+        forged.storage_proofs[0].value = [9u8; 32];
+        assert!(forged.verify(&root).is_err());
+
+        // The same leaf under another contract's key
+        let other = EraProof { address: [0xcd; 20], ..proof.clone() };
+        assert!(other.verify(&root).is_err());
+
+        let mut absent = a;
+        absent.index = 0;
+        assert!(absent.root_hash(&ADDRESS).is_err());
+    }
+
+    #[test]
+    fn test_empty_tree_and_response_round_trip() {
+        // An absent slot in an empty tree needs no siblings at all
+        let absent = EraStorageProof { key: [3u8; 32], value: [0u8; 32], index: 0, proof: Vec::new() };
+        let top = empty_subtree_hashes()[TREE_DEPTH - 1];
+        let empty_root = blake2s(&[&top, &top]);
+        assert_eq!(absent.root_hash(&ADDRESS).unwrap(), empty_root);
+
+        let (root, a, _) = two_slot_tree();
+        let proof = EraProof { address: ADDRESS, l1_batch: 7, root_hash: root, storage_proofs: vec![a] };
+        assert_eq!(EraProof::from_response(&proof.to_json(), 7, root).unwrap(), proof);
+    }
+}
@@ -285,6 +285,7 @@ fn create_uniswap_v3_pool_layout() -> LayoutInfo {
                 value: None,
             },
         ],
+        code_variables: Vec::new(),
     }
 }
 
@@ -496,6 +497,7 @@ fn create_compound_v3_layout() -> LayoutInfo {
                 value: None,
             },
         ],
+        code_variables: Vec::new(),
     }
 }
 
@@ -610,11 +612,11 @@ mod tests {
         let layout = create_compound_v3_layout();
         let resolver = EthereumKeyResolver;
 
-        // Test basic struct access (note: offset is only set when > 0 in the layout)
+        // Test basic struct access (note: packed fields carry their offset, even 0)
         let test_cases = vec![
-            ("governor", 0, None), // First field has no offset (offset 0 → None)
+            ("governor", 0, Some(0)), // First field of a packed slot
             ("pauseGuardian", 0, Some(20)),
-            ("baseToken", 1, None), // First field has no offset
+            ("baseToken", 1, Some(0)), // First field of a packed slot
             ("baseTokenPriceFeed", 1, Some(20)),
             ("extensionDelegate", 2, None), // First field has no offset
             ("userBasic", 3, None),         // Mapping, no offset
@@ -71,7 +71,7 @@ fn test_resolve_packed_fields() {
     assert!(owner_result.is_ok(), "Should resolve owner");
 
     let owner_path = owner_result.unwrap();
-    assert_eq!(owner_path.offset, None); // First field, no offset
+    assert_eq!(owner_path.offset, Some(0)); // First field of a packed slot
     assert_eq!(owner_path.field_size, Some(20)); // 20 bytes for address
 
     // Test paused (slot 6, offset 20) - packed with owner
@@ -214,6 +214,7 @@ fn create_usdc_layout() -> LayoutInfo {
                 value: Some("t_mapping_address_uint256".to_string()),
             },
         ],
+        code_variables: Vec::new(),
     }
 }
 
@@ -306,6 +307,7 @@ fn create_uniswap_v2_layout() -> LayoutInfo {
                 value: None,
             },
         ],
+        code_variables: Vec::new(),
     }
 }
 
@@ -513,6 +515,7 @@ fn create_conflict_test_layout() -> LayoutInfo {
             key: None,
             value: None,
         }],
+        code_variables: Vec::new(),
     }
 }
 
@@ -587,6 +590,7 @@ fn create_negative_test_layout() -> LayoutInfo {
             key: None,
             value: None,
         }],
+        code_variables: Vec::new(),
     }
 }
 
@@ -1011,6 +1015,7 @@ mod validated_ethereum_tests {
                 key: None,
                 value: None,
             }],
+            code_variables: Vec::new(),
         };
 
         let resolver = EthereumKeyResolver;
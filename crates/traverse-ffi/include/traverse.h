@@ -0,0 +1,282 @@
+/* traverse C ABI: storage query resolution, witness creation and circuit validation */
+
+#ifndef TRAVERSE_H
+#define TRAVERSE_H
+
+/* Generated by cbindgen from crates/traverse-ffi. Do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Result of every call that can fail
+ */
+typedef enum TraverseStatus {
+  TRAVERSE_STATUS_OK = 0,
+  /**
+   * A required pointer argument was null
+   */
+  TRAVERSE_STATUS_NULL_ARGUMENT = 1,
+  /**
+   * A string argument was not valid UTF-8
+   */
+  TRAVERSE_STATUS_INVALID_UTF8 = 2,
+  /**
+   * An argument could not be parsed or did not fit the call
+   */
+  TRAVERSE_STATUS_INVALID_INPUT = 3,
+  /**
+   * The controller could not create a witness
+   */
+  TRAVERSE_STATUS_WITNESS = 4,
+  /**
+   * The library panicked; the call had no effect
+   */
+  TRAVERSE_STATUS_PANIC = 5,
+} TraverseStatus;
+
+/**
+ * A parsed storage layout
+ */
+typedef struct TraverseLayout TraverseLayout;
+
+/**
+ * A circuit processor for one layout
+ */
+typedef struct TraverseProcessor TraverseProcessor;
+
+/**
+ * Bytes owned by the library; free with `traverse_buffer_free`
+ */
+typedef struct TraverseBuffer {
+  uint8_t *data;
+  size_t len;
+} TraverseBuffer;
+
+/**
+ * A list of buffers owned by the library; free with `traverse_buffer_list_free`
+ */
+typedef struct TraverseBufferList {
+  struct TraverseBuffer *buffers;
+  size_t len;
+} TraverseBufferList;
+
+/**
+ * Outcome of validating one witness
+ */
+typedef struct TraverseFieldValue {
+  /**
+   * Whether the witness passed every circuit check; the other fields are zero if not
+   */
+  bool valid;
+  /**
+   * Index of the field in the layout's storage entries
+   */
+  uint16_t field_index;
+  /**
+   * Number of meaningful bytes at the start of `value`
+   */
+  size_t value_len;
+  /**
+   * The extracted value, big-endian
+   */
+  uint8_t value[32];
+} TraverseFieldValue;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * The library version, as a static NUL-terminated string
+ */
+const char *traverse_version(void);
+
+/**
+ * Free a buffer returned by the library
+ *
+ * # Safety
+ *
+ * `buffer` must have been returned by this library and not freed before.
+ */
+void traverse_buffer_free(struct TraverseBuffer buffer);
+
+/**
+ * Free a buffer list and every buffer in it
+ *
+ * # Safety
+ *
+ * `list` must have been returned by this library and not freed before.
+ */
+void traverse_buffer_list_free(struct TraverseBufferList list);
+
+/**
+ * Free a string returned by the library
+ *
+ * # Safety
+ *
+ * `value` must be null or have been returned by this library and not freed before.
+ */
+void traverse_string_free(char *value);
+
+/**
+ * The message of the last failed call on this thread, or null after a successful one
+ *
+ * The string is owned by the library and valid until the next call on this thread.
+ */
+const char *traverse_last_error(void);
+
+/**
+ * Parse a layout from its JSON, as `traverse-ethereum compile-layout` writes it
+ *
+ * On success `*out` holds a handle the caller frees with `traverse_layout_free`.
+ *
+ * # Safety
+ *
+ * `layout_json` must be a NUL-terminated string and `out` valid for writes.
+ */
+enum TraverseStatus traverse_layout_from_json(const char *layout_json, struct TraverseLayout **out);
+
+/**
+ * Free a layout handle; null is ignored
+ *
+ * # Safety
+ *
+ * `layout` must be null or a handle from `traverse_layout_from_json` that was not freed before.
+ */
+void traverse_layout_free(struct TraverseLayout *layout);
+
+/**
+ * Copy the layout commitment into the 32 bytes at `out`
+ *
+ * # Safety
+ *
+ * `layout` must be a live handle and `out` valid for writes of 32 bytes.
+ */
+enum TraverseStatus traverse_layout_commitment(const struct TraverseLayout *layout, uint8_t *out);
+
+/**
+ * Resolve a query such as `balances[0x742d...]` against a layout
+ *
+ * On success `*out` holds the coprocessor storage query as JSON, freed with
+ * `traverse_string_free`.
+ *
+ * # Safety
+ *
+ * `layout` must be a live handle, `query` a NUL-terminated string and `out` valid for writes.
+ */
+enum TraverseStatus traverse_layout_resolve(const struct TraverseLayout *layout,
+                                            const char *query,
+                                            char **out);
+
+/**
+ * Create a processor that checks witnesses against a layout, without block checks
+ *
+ * On success `*out` holds a handle the caller frees with `traverse_processor_free`.
+ *
+ * # Safety
+ *
+ * `layout` must be a live handle and `out` valid for writes.
+ */
+enum TraverseStatus traverse_processor_new(const struct TraverseLayout *layout,
+                                           struct TraverseProcessor **out);
+
+/**
+ * Create a processor that also checks witnesses against a light client's block
+ *
+ * Witnesses at `block_height` must carry `block_hash`; older ones are
+ * accepted up to `max_proof_age_blocks` behind it.
+ *
+ * # Safety
+ *
+ * `layout` must be a live handle, `block_hash` valid for reads of 32 bytes and `out` valid for writes.
+ */
+enum TraverseStatus traverse_processor_new_with_light_client(const struct TraverseLayout *layout,
+                                                             uint64_t block_height,
+                                                             const uint8_t *block_hash,
+                                                             uint64_t max_proof_age_blocks,
+                                                             struct TraverseProcessor **out);
+
+/**
+ * Free a processor handle; null is ignored
+ *
+ * # Safety
+ *
+ * `processor` must be null or a handle from a `traverse_processor_new*` call that was not freed before.
+ */
+void traverse_processor_free(struct TraverseProcessor *processor);
+
+/**
+ * Validate a witness and extract its value
+ *
+ * Returns `TRAVERSE_STATUS_INVALID_INPUT` if the bytes are not a witness. A
+ * witness that fails the circuit's checks is not an error: the call
+ * succeeds with `out->valid` false.
+ *
+ * # Safety
+ *
+ * `processor` must be a live handle, `data` valid for reads of `len` bytes and `out` valid for writes.
+ */
+enum TraverseStatus traverse_processor_process_witness(const struct TraverseProcessor *processor,
+                                                       const uint8_t *data,
+                                                       size_t len,
+                                                       struct TraverseFieldValue *out);
+
+/**
+ * Combine a storage query with its `eth_getProof` storage proof entry into a verification request
+ *
+ * `contract_address` may be null; a `block_number` of 0 leaves the block unset.
+ * On success `*out` holds the request as JSON, freed with `traverse_string_free`.
+ *
+ * # Safety
+ *
+ * String arguments must be null (where allowed) or NUL-terminated, and `out` valid for writes.
+ */
+enum TraverseStatus traverse_build_request(const char *storage_query_json,
+                                           const char *storage_proof_json,
+                                           const char *contract_address,
+                                           uint64_t block_number,
+                                           char **out);
+
+/**
+ * Create the witness for a verification request
+ *
+ * On success `*out` holds the witness bytes, freed with `traverse_buffer_free`.
+ *
+ * # Safety
+ *
+ * `request_json` must be a NUL-terminated string and `out` valid for writes.
+ */
+enum TraverseStatus traverse_create_witness(const char *request_json, struct TraverseBuffer *out);
+
+/**
+ * Create a witness per request in a batch
+ *
+ * On success `*out` holds the witnesses in request order, freed with `traverse_buffer_list_free`.
+ *
+ * # Safety
+ *
+ * `batch_json` must be a NUL-terminated string and `out` valid for writes.
+ */
+enum TraverseStatus traverse_create_witnesses(const char *batch_json,
+                                              struct TraverseBufferList *out);
+
+/**
+ * Decode a witness as the circuit reads it
+ *
+ * On success `*out` holds the witness fields as JSON, freed with `traverse_string_free`.
+ *
+ * # Safety
+ *
+ * `data` must be valid for reads of `len` bytes and `out` valid for writes.
+ */
+enum TraverseStatus traverse_inspect_witness(const uint8_t *data, size_t len, char **out);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* TRAVERSE_H */
@@ -0,0 +1,91 @@
+//! Memory the library hands to callers
+
+use std::ffi::{c_char, CString};
+
+/// Bytes owned by the library; free with `traverse_buffer_free`
+#[repr(C)]
+#[derive(Debug)]
+pub struct TraverseBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl TraverseBuffer {
+    pub(crate) fn from_vec(bytes: Vec<u8>) -> Self {
+        let len = bytes.len();
+        let data = Box::into_raw(bytes.into_boxed_slice()) as *mut u8;
+        Self { data, len }
+    }
+
+    /// Reclaim the bytes of a buffer this library returned
+    ///
+    /// # Safety
+    ///
+    /// The buffer must come from [`TraverseBuffer::from_vec`] and not have been freed.
+    unsafe fn into_vec(self) -> Vec<u8> {
+        if self.data.is_null() {
+            return Vec::new();
+        }
+        Box::from_raw(std::ptr::slice_from_raw_parts_mut(self.data, self.len)).into_vec()
+    }
+}
+
+/// A list of buffers owned by the library; free with `traverse_buffer_list_free`
+#[repr(C)]
+#[derive(Debug)]
+pub struct TraverseBufferList {
+    pub buffers: *mut TraverseBuffer,
+    pub len: usize,
+}
+
+impl TraverseBufferList {
+    pub(crate) fn from_vecs(items: Vec<Vec<u8>>) -> Self {
+        let buffers: Box<[TraverseBuffer]> = items.into_iter().map(TraverseBuffer::from_vec).collect();
+        let len = buffers.len();
+        Self { buffers: Box::into_raw(buffers) as *mut TraverseBuffer, len }
+    }
+}
+
+/// Move a string into a NUL-terminated C string the caller frees with `traverse_string_free`
+pub(crate) fn into_c_string(value: String) -> *mut c_char {
+    // JSON output never contains NUL, so this cannot fail in practice
+    CString::new(value).map_or(std::ptr::null_mut(), CString::into_raw)
+}
+
+/// Free a buffer returned by the library
+///
+/// # Safety
+///
+/// `buffer` must have been returned by this library and not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn traverse_buffer_free(buffer: TraverseBuffer) {
+    drop(buffer.into_vec());
+}
+
+/// Free a buffer list and every buffer in it
+///
+/// # Safety
+///
+/// `list` must have been returned by this library and not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn traverse_buffer_list_free(list: TraverseBufferList) {
+    if list.buffers.is_null() {
+        return;
+    }
+    let buffers = Box::from_raw(std::ptr::slice_from_raw_parts_mut(list.buffers, list.len));
+    for buffer in buffers.into_vec() {
+        drop(buffer.into_vec());
+    }
+}
+
+/// Free a string returned by the library
+///
+/// # Safety
+///
+/// `value` must be null or have been returned by this library and not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn traverse_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(value));
+    }
+}
@@ -0,0 +1,151 @@
+//! Status codes, the per-thread last error, and argument checks
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use traverse_wasm::WasmError;
+
+/// Result of every call that can fail
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraverseStatus {
+    Ok = 0,
+    /// A required pointer argument was null
+    NullArgument = 1,
+    /// A string argument was not valid UTF-8
+    InvalidUtf8 = 2,
+    /// An argument could not be parsed or did not fit the call
+    InvalidInput = 3,
+    /// The controller could not create a witness
+    Witness = 4,
+    /// The library panicked; the call had no effect
+    Panic = 5,
+}
+
+/// A failed call: its status and the message `traverse_last_error` returns
+#[derive(Debug)]
+pub(crate) struct FfiError {
+    status: TraverseStatus,
+    message: String,
+}
+
+impl FfiError {
+    pub(crate) fn new(status: TraverseStatus, message: impl Into<String>) -> Self {
+        Self { status, message: message.into() }
+    }
+
+    pub(crate) fn invalid_input(message: impl Into<String>) -> Self {
+        Self::new(TraverseStatus::InvalidInput, message)
+    }
+}
+
+impl From<WasmError> for FfiError {
+    fn from(err: WasmError) -> Self {
+        let status = match err {
+            WasmError::Witness(_) => TraverseStatus::Witness,
+            _ => TraverseStatus::InvalidInput,
+        };
+        Self::new(status, err.to_string())
+    }
+}
+
+impl From<serde_json::Error> for FfiError {
+    fn from(err: serde_json::Error) -> Self {
+        WasmError::from(err).into()
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    // Interior NULs would truncate the message in C; drop them instead
+    let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Run a call body, recording its error and containing panics
+pub(crate) fn run(body: impl FnOnce() -> Result<(), FfiError>) -> TraverseStatus {
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => {
+            LAST_ERROR.with(|last| *last.borrow_mut() = None);
+            TraverseStatus::Ok
+        }
+        Ok(Err(err)) => {
+            set_last_error(err.message);
+            err.status
+        }
+        Err(_) => {
+            set_last_error("traverse panicked".to_string());
+            TraverseStatus::Panic
+        }
+    }
+}
+
+/// A NUL-terminated UTF-8 argument
+///
+/// # Safety
+///
+/// `ptr` must be null or point to a NUL-terminated string that outlives the call.
+pub(crate) unsafe fn str_arg<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, FfiError> {
+    if ptr.is_null() {
+        return Err(FfiError::new(TraverseStatus::NullArgument, format!("{} is null", name)));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| FfiError::new(TraverseStatus::InvalidUtf8, format!("{} is not valid UTF-8", name)))
+}
+
+/// An optional string argument; null means absent
+///
+/// # Safety
+///
+/// As for [`str_arg`].
+pub(crate) unsafe fn optional_str_arg<'a>(ptr: *const c_char, name: &str) -> Result<Option<&'a str>, FfiError> {
+    if ptr.is_null() {
+        Ok(None)
+    } else {
+        str_arg(ptr, name).map(Some)
+    }
+}
+
+/// A byte-slice argument; null is allowed when `len` is 0
+///
+/// # Safety
+///
+/// `ptr` must be null or valid for reads of `len` bytes for the duration of the call.
+pub(crate) unsafe fn bytes_arg<'a>(ptr: *const u8, len: usize, name: &str) -> Result<&'a [u8], FfiError> {
+    match (ptr.is_null(), len) {
+        (true, 0) => Ok(&[]),
+        (true, _) => Err(FfiError::new(TraverseStatus::NullArgument, format!("{} is null", name))),
+        (false, _) => Ok(std::slice::from_raw_parts(ptr, len)),
+    }
+}
+
+/// A reference to a handle or out-parameter
+///
+/// # Safety
+///
+/// `ptr` must be null or valid and properly aligned for the duration of the call.
+pub(crate) unsafe fn ref_arg<'a, T>(ptr: *const T, name: &str) -> Result<&'a T, FfiError> {
+    ptr.as_ref()
+        .ok_or_else(|| FfiError::new(TraverseStatus::NullArgument, format!("{} is null", name)))
+}
+
+/// Check an out-parameter before any work is done
+pub(crate) fn out_arg<T>(ptr: *mut T, name: &str) -> Result<*mut T, FfiError> {
+    if ptr.is_null() {
+        Err(FfiError::new(TraverseStatus::NullArgument, format!("{} is null", name)))
+    } else {
+        Ok(ptr)
+    }
+}
+
+/// The message of the last failed call on this thread, or null after a successful one
+///
+/// The string is owned by the library and valid until the next call on this thread.
+#[no_mangle]
+pub extern "C" fn traverse_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(std::ptr::null(), |message| message.as_ptr()))
+}
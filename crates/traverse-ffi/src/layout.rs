@@ -0,0 +1,85 @@
+//! Layout handles and query resolution
+
+use crate::buffer::into_c_string;
+use crate::error::{out_arg, ref_arg, run, str_arg, FfiError, TraverseStatus};
+use std::ffi::c_char;
+use traverse_core::LayoutInfo;
+use traverse_wasm::request;
+
+/// A parsed storage layout
+pub struct TraverseLayout {
+    pub(crate) layout: LayoutInfo,
+    pub(crate) commitment: [u8; 32],
+}
+
+/// Parse a layout from its JSON, as `traverse-ethereum compile-layout` writes it
+///
+/// On success `*out` holds a handle the caller frees with `traverse_layout_free`.
+///
+/// # Safety
+///
+/// `layout_json` must be a NUL-terminated string and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn traverse_layout_from_json(
+    layout_json: *const c_char,
+    out: *mut *mut TraverseLayout,
+) -> TraverseStatus {
+    run(|| {
+        let out = out_arg(out, "out")?;
+        let layout: LayoutInfo = serde_json::from_str(str_arg(layout_json, "layout_json")?)?;
+        layout.validate().map_err(FfiError::invalid_input)?;
+        let commitment = layout.commitment();
+        *out = Box::into_raw(Box::new(TraverseLayout { layout, commitment }));
+        Ok(())
+    })
+}
+
+/// Free a layout handle; null is ignored
+///
+/// # Safety
+///
+/// `layout` must be null or a handle from `traverse_layout_from_json` that was not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn traverse_layout_free(layout: *mut TraverseLayout) {
+    if !layout.is_null() {
+        drop(Box::from_raw(layout));
+    }
+}
+
+/// Copy the layout commitment into the 32 bytes at `out`
+///
+/// # Safety
+///
+/// `layout` must be a live handle and `out` valid for writes of 32 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn traverse_layout_commitment(layout: *const TraverseLayout, out: *mut u8) -> TraverseStatus {
+    run(|| {
+        let out = out_arg(out, "out")?;
+        let layout = ref_arg(layout, "layout")?;
+        std::ptr::copy_nonoverlapping(layout.commitment.as_ptr(), out, layout.commitment.len());
+        Ok(())
+    })
+}
+
+/// Resolve a query such as `balances[0x742d...]` against a layout
+///
+/// On success `*out` holds the coprocessor storage query as JSON, freed with
+/// `traverse_string_free`.
+///
+/// # Safety
+///
+/// `layout` must be a live handle, `query` a NUL-terminated string and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn traverse_layout_resolve(
+    layout: *const TraverseLayout,
+    query: *const c_char,
+    out: *mut *mut c_char,
+) -> TraverseStatus {
+    run(|| {
+        let out = out_arg(out, "out")?;
+        let layout = ref_arg(layout, "layout")?;
+        let storage_query = request::resolve_in_layout(&layout.layout, str_arg(query, "query")?)?;
+        *out = into_c_string(serde_json::to_string(&storage_query)?);
+        Ok(())
+    })
+}
@@ -0,0 +1,152 @@
+//! C ABI for embedding traverse in other runtimes
+//!
+//! Go and C++ coprocessor hosts call the controller and circuit helpers
+//! through this library instead of re-implementing the witness format:
+//! parse a layout, resolve queries against it, turn `eth_getProof` entries
+//! into verification requests, create witnesses, and validate witnesses
+//! the way the circuit does.
+//!
+//! # Conventions
+//!
+//! - Every fallible call returns a [`TraverseStatus`]; after a failure,
+//!   [`traverse_last_error`] describes it (per thread)
+//! - Results are written to out-parameters, and only on success
+//! - Strings are NUL-terminated UTF-8; requests and queries are JSON in the
+//!   formats the CLIs and `traverse-valence` use
+//! - Layouts and processors are opaque handles freed with their `_free`
+//!   call; strings, buffers and buffer lists returned by the library are
+//!   freed with [`traverse_string_free`], [`traverse_buffer_free`] and
+//!   [`traverse_buffer_list_free`]
+//! - Panics are caught at the boundary and reported as
+//!   [`TraverseStatus::Panic`]
+//!
+//! The build regenerates `include/traverse.h` with cbindgen; the header is
+//! checked in so hosts can build against it without a Rust toolchain.
+//!
+//! # Usage
+//!
+//! ```c
+//! #include "traverse.h"
+//!
+//! TraverseLayout *layout = NULL;
+//! char *query = NULL, *request = NULL;
+//! TraverseBuffer witness = {0};
+//!
+//! if (traverse_layout_from_json(layout_json, &layout) != TRAVERSE_STATUS_OK ||
+//!     traverse_layout_resolve(layout, "balances[0x742d35Cc6634C0532925a3b8D97C2e0D8b2D9C53]", &query) != TRAVERSE_STATUS_OK ||
+//!     traverse_build_request(query, storage_proof_json, token, block_number, &request) != TRAVERSE_STATUS_OK ||
+//!     traverse_create_witness(request, &witness) != TRAVERSE_STATUS_OK) {
+//!     fprintf(stderr, "traverse: %s\n", traverse_last_error());
+//! }
+//!
+//! traverse_buffer_free(witness);
+//! traverse_string_free(request);
+//! traverse_string_free(query);
+//! traverse_layout_free(layout);
+//! ```
+
+pub mod buffer;
+pub mod error;
+pub mod layout;
+pub mod processor;
+pub mod witness;
+
+pub use buffer::{traverse_buffer_free, traverse_buffer_list_free, traverse_string_free, TraverseBuffer, TraverseBufferList};
+pub use error::{traverse_last_error, TraverseStatus};
+pub use layout::{traverse_layout_commitment, traverse_layout_free, traverse_layout_from_json, traverse_layout_resolve, TraverseLayout};
+pub use processor::{
+    traverse_processor_free, traverse_processor_new, traverse_processor_new_with_light_client,
+    traverse_processor_process_witness, TraverseFieldValue, TraverseProcessor,
+};
+pub use witness::{traverse_build_request, traverse_create_witness, traverse_create_witnesses, traverse_inspect_witness};
+
+use std::ffi::c_char;
+
+/// The library version, as a static NUL-terminated string
+#[no_mangle]
+pub extern "C" fn traverse_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::{CStr, CString};
+    use std::ptr;
+
+    const LAYOUT: &str = r#"{
+        "contract_name": "Vault",
+        "storage": [{ "label": "owner", "slot": "0", "offset": 0, "type_name": "t_address", "zero_semantics": "NeverWritten" }],
+        "types": [{ "label": "t_address", "number_of_bytes": "20", "encoding": "inplace", "base": null, "key": null, "value": null }]
+    }"#;
+    const PROOF: &str = r#"{ "key": "0x0", "value": "0x742d35cc6634c0532925a3b8d97c2e0d8b2d9c53", "proof": ["0xf851", "0x80"] }"#;
+
+    fn last_error() -> String {
+        unsafe { CStr::from_ptr(traverse_last_error()) }.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_witness_through_c_abi() {
+        let layout_json = CString::new(LAYOUT).unwrap();
+        let query = CString::new("owner").unwrap();
+        let proof = CString::new(PROOF).unwrap();
+
+        unsafe {
+            let mut layout = ptr::null_mut();
+            assert_eq!(traverse_layout_from_json(layout_json.as_ptr(), &mut layout), TraverseStatus::Ok);
+            assert!(traverse_last_error().is_null());
+
+            let mut storage_query = ptr::null_mut();
+            assert_eq!(traverse_layout_resolve(layout, query.as_ptr(), &mut storage_query), TraverseStatus::Ok);
+            let mut request = ptr::null_mut();
+            assert_eq!(
+                traverse_build_request(storage_query, proof.as_ptr(), ptr::null(), 100, &mut request),
+                TraverseStatus::Ok
+            );
+
+            let mut witness = TraverseBuffer { data: ptr::null_mut(), len: 0 };
+            assert_eq!(traverse_create_witness(request, &mut witness), TraverseStatus::Ok);
+            let mut summary = ptr::null_mut();
+            assert_eq!(traverse_inspect_witness(witness.data, witness.len, &mut summary), TraverseStatus::Ok);
+            let summary_json: serde_json::Value = serde_json::from_str(CStr::from_ptr(summary).to_str().unwrap()).unwrap();
+            assert_eq!(summary_json["storage_key"], "0".repeat(64));
+
+            let mut commitment = [0u8; 32];
+            assert_eq!(traverse_layout_commitment(layout, commitment.as_mut_ptr()), TraverseStatus::Ok);
+            assert_eq!(summary_json["layout_commitment"], hex_string(&commitment));
+
+            // A processor for another layout rejects the witness without failing the call
+            let other_json = CString::new(LAYOUT.replace("Vault", "Other").replace("\"0\"", "\"1\"")).unwrap();
+            let mut other = ptr::null_mut();
+            assert_eq!(traverse_layout_from_json(other_json.as_ptr(), &mut other), TraverseStatus::Ok);
+            let mut processor = ptr::null_mut();
+            assert_eq!(traverse_processor_new(other, &mut processor), TraverseStatus::Ok);
+            let mut field = TraverseFieldValue { valid: true, ..Default::default() };
+            assert_eq!(
+                traverse_processor_process_witness(processor, witness.data, witness.len, &mut field),
+                TraverseStatus::Ok
+            );
+            assert!(!field.valid);
+            assert_eq!(
+                traverse_processor_process_witness(processor, witness.data, 10, &mut field),
+                TraverseStatus::InvalidInput
+            );
+            assert!(last_error().contains("too small"));
+
+            traverse_processor_free(processor);
+            traverse_layout_free(other);
+            traverse_string_free(summary);
+            traverse_buffer_free(witness);
+            traverse_string_free(request);
+            traverse_string_free(storage_query);
+            traverse_layout_free(layout);
+        }
+
+        assert_eq!(unsafe { traverse_layout_from_json(ptr::null(), ptr::null_mut()) }, TraverseStatus::NullArgument);
+        assert!(last_error().contains("out is null"));
+    }
+
+    fn hex_string(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
@@ -0,0 +1,183 @@
+//! Circuit helpers: processor handles and witness validation
+
+use crate::error::{bytes_arg, out_arg, ref_arg, run, FfiError, TraverseStatus};
+use crate::layout::TraverseLayout;
+use traverse_core::{LayoutInfo, ZeroSemantics};
+use traverse_valence::circuit::{CircuitProcessor, CircuitResult, FieldType, ZeroSemantics as CircuitSemantics};
+
+/// A circuit processor for one layout
+pub struct TraverseProcessor {
+    processor: CircuitProcessor,
+}
+
+/// Outcome of validating one witness
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TraverseFieldValue {
+    /// Whether the witness passed every circuit check; the other fields are zero if not
+    pub valid: bool,
+    /// Index of the field in the layout's storage entries
+    pub field_index: u16,
+    /// Number of meaningful bytes at the start of `value`
+    pub value_len: usize,
+    /// The extracted value, big-endian
+    pub value: [u8; 32],
+}
+
+/// The circuit's field type for a layout type, following mappings to their values
+fn field_type(layout: &LayoutInfo, type_name: &str) -> FieldType {
+    match type_name {
+        "t_bool" => FieldType::Bool,
+        "t_uint8" => FieldType::Uint8,
+        "t_uint16" => FieldType::Uint16,
+        "t_uint32" => FieldType::Uint32,
+        "t_uint64" => FieldType::Uint64,
+        "t_address" => FieldType::Address,
+        "t_bytes32" => FieldType::Bytes32,
+        "t_string_storage" | "t_string" => FieldType::String,
+        "t_bytes_storage" | "t_bytes" => FieldType::Bytes,
+        other => {
+            let mapped_value = layout
+                .types
+                .iter()
+                .find(|ty| ty.label == other && ty.encoding == "mapping")
+                .and_then(|ty| ty.value.as_deref());
+            match mapped_value {
+                Some(value) if value != other => field_type(layout, value),
+                _ => FieldType::Uint256,
+            }
+        }
+    }
+}
+
+fn circuit_semantics(semantics: ZeroSemantics) -> CircuitSemantics {
+    match semantics {
+        ZeroSemantics::NeverWritten => CircuitSemantics::NeverWritten,
+        ZeroSemantics::ExplicitlyZero => CircuitSemantics::ExplicitlyZero,
+        ZeroSemantics::Cleared => CircuitSemantics::Cleared,
+        ZeroSemantics::ValidZero => CircuitSemantics::ValidZero,
+    }
+}
+
+fn processor_fields(layout: &LayoutInfo) -> (Vec<FieldType>, Vec<CircuitSemantics>) {
+    layout
+        .storage
+        .iter()
+        .map(|entry| (field_type(layout, &entry.type_name), circuit_semantics(entry.zero_semantics)))
+        .unzip()
+}
+
+/// Packed fields are masked out of their slot at the layout's offsets
+fn field_offsets(layout: &LayoutInfo) -> Vec<u8> {
+    layout.storage.iter().map(|entry| entry.offset).collect()
+}
+
+fn into_handle(processor: CircuitProcessor) -> *mut TraverseProcessor {
+    Box::into_raw(Box::new(TraverseProcessor { processor }))
+}
+
+/// Create a processor that checks witnesses against a layout, without block checks
+///
+/// On success `*out` holds a handle the caller frees with `traverse_processor_free`.
+///
+/// # Safety
+///
+/// `layout` must be a live handle and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn traverse_processor_new(
+    layout: *const TraverseLayout,
+    out: *mut *mut TraverseProcessor,
+) -> TraverseStatus {
+    run(|| {
+        let out = out_arg(out, "out")?;
+        let layout = ref_arg(layout, "layout")?;
+        let (field_types, field_semantics) = processor_fields(&layout.layout);
+        *out = into_handle(
+            CircuitProcessor::new(layout.commitment, field_types, field_semantics)
+                .with_field_offsets(field_offsets(&layout.layout)),
+        );
+        Ok(())
+    })
+}
+
+/// Create a processor that also checks witnesses against a light client's block
+///
+/// Witnesses at `block_height` must carry `block_hash`; older ones are
+/// accepted up to `max_proof_age_blocks` behind it.
+///
+/// # Safety
+///
+/// `layout` must be a live handle, `block_hash` valid for reads of 32 bytes and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn traverse_processor_new_with_light_client(
+    layout: *const TraverseLayout,
+    block_height: u64,
+    block_hash: *const u8,
+    max_proof_age_blocks: u64,
+    out: *mut *mut TraverseProcessor,
+) -> TraverseStatus {
+    run(|| {
+        let out = out_arg(out, "out")?;
+        let layout = ref_arg(layout, "layout")?;
+        let block_hash: [u8; 32] = bytes_arg(block_hash, 32, "block_hash")?
+            .try_into()
+            .map_err(|_| FfiError::invalid_input("block_hash must be 32 bytes"))?;
+        let (field_types, field_semantics) = processor_fields(&layout.layout);
+        *out = into_handle(CircuitProcessor::new_with_expiration(
+            layout.commitment,
+            field_types,
+            field_semantics,
+            block_height,
+            block_hash,
+            max_proof_age_blocks,
+        )
+        .with_field_offsets(field_offsets(&layout.layout)));
+        Ok(())
+    })
+}
+
+/// Free a processor handle; null is ignored
+///
+/// # Safety
+///
+/// `processor` must be null or a handle from a `traverse_processor_new*` call that was not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn traverse_processor_free(processor: *mut TraverseProcessor) {
+    if !processor.is_null() {
+        drop(Box::from_raw(processor));
+    }
+}
+
+/// Validate a witness and extract its value
+///
+/// Returns `TRAVERSE_STATUS_INVALID_INPUT` if the bytes are not a witness. A
+/// witness that fails the circuit's checks is not an error: the call
+/// succeeds with `out->valid` false.
+///
+/// # Safety
+///
+/// `processor` must be a live handle, `data` valid for reads of `len` bytes and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn traverse_processor_process_witness(
+    processor: *const TraverseProcessor,
+    data: *const u8,
+    len: usize,
+    out: *mut TraverseFieldValue,
+) -> TraverseStatus {
+    run(|| {
+        let out = out_arg(out, "out")?;
+        let processor = ref_arg(processor, "processor")?;
+        let witness = CircuitProcessor::parse_witness_from_bytes(bytes_arg(data, len, "data")?)
+            .map_err(FfiError::invalid_input)?;
+        let mut field = TraverseFieldValue::default();
+        if let CircuitResult::Valid { field_index, extracted_value } = processor.processor.process_witness(&witness) {
+            let bytes = extracted_value.to_bytes();
+            field.valid = true;
+            field.field_index = field_index;
+            field.value_len = bytes.len();
+            field.value[..bytes.len()].copy_from_slice(&bytes);
+        }
+        out.write(field);
+        Ok(())
+    })
+}
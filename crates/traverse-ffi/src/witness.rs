@@ -0,0 +1,87 @@
+//! Controller helpers: verification requests and witnesses
+
+use crate::buffer::{into_c_string, TraverseBuffer, TraverseBufferList};
+use crate::error::{bytes_arg, optional_str_arg, out_arg, run, str_arg, TraverseStatus};
+use std::ffi::c_char;
+use traverse_valence::{BatchStorageVerificationRequest, CoprocessorStorageQuery, StorageVerificationRequest};
+use traverse_wasm::request::{self, ProofEntry};
+use traverse_wasm::witness;
+
+/// Combine a storage query with its `eth_getProof` storage proof entry into a verification request
+///
+/// `contract_address` may be null; a `block_number` of 0 leaves the block unset.
+/// On success `*out` holds the request as JSON, freed with `traverse_string_free`.
+///
+/// # Safety
+///
+/// String arguments must be null (where allowed) or NUL-terminated, and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn traverse_build_request(
+    storage_query_json: *const c_char,
+    storage_proof_json: *const c_char,
+    contract_address: *const c_char,
+    block_number: u64,
+    out: *mut *mut c_char,
+) -> TraverseStatus {
+    run(|| {
+        let out = out_arg(out, "out")?;
+        let storage_query: CoprocessorStorageQuery = serde_json::from_str(str_arg(storage_query_json, "storage_query_json")?)?;
+        let proof: ProofEntry = serde_json::from_str(str_arg(storage_proof_json, "storage_proof_json")?)?;
+        let contract_address = optional_str_arg(contract_address, "contract_address")?.map(str::to_string);
+        let block_number = (block_number != 0).then_some(block_number);
+        let request = request::build_request(storage_query, proof, contract_address, block_number)?;
+        *out = into_c_string(serde_json::to_string(&request)?);
+        Ok(())
+    })
+}
+
+/// Create the witness for a verification request
+///
+/// On success `*out` holds the witness bytes, freed with `traverse_buffer_free`.
+///
+/// # Safety
+///
+/// `request_json` must be a NUL-terminated string and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn traverse_create_witness(request_json: *const c_char, out: *mut TraverseBuffer) -> TraverseStatus {
+    run(|| {
+        let out = out_arg(out, "out")?;
+        let request: StorageVerificationRequest = serde_json::from_str(str_arg(request_json, "request_json")?)?;
+        out.write(TraverseBuffer::from_vec(witness::create_witness(&request)?));
+        Ok(())
+    })
+}
+
+/// Create a witness per request in a batch
+///
+/// On success `*out` holds the witnesses in request order, freed with `traverse_buffer_list_free`.
+///
+/// # Safety
+///
+/// `batch_json` must be a NUL-terminated string and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn traverse_create_witnesses(batch_json: *const c_char, out: *mut TraverseBufferList) -> TraverseStatus {
+    run(|| {
+        let out = out_arg(out, "out")?;
+        let batch: BatchStorageVerificationRequest = serde_json::from_str(str_arg(batch_json, "batch_json")?)?;
+        out.write(TraverseBufferList::from_vecs(witness::create_witnesses(&batch)?));
+        Ok(())
+    })
+}
+
+/// Decode a witness as the circuit reads it
+///
+/// On success `*out` holds the witness fields as JSON, freed with `traverse_string_free`.
+///
+/// # Safety
+///
+/// `data` must be valid for reads of `len` bytes and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn traverse_inspect_witness(data: *const u8, len: usize, out: *mut *mut c_char) -> TraverseStatus {
+    run(|| {
+        let out = out_arg(out, "out")?;
+        let summary = witness::inspect_witness(bytes_arg(data, len, "data")?)?;
+        *out = into_c_string(serde_json::to_string(&summary)?);
+        Ok(())
+    })
+}
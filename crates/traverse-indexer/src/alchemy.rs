@@ -0,0 +1,311 @@
+//! Storage histories and event logs from Alchemy's enhanced JSON-RPC
+//!
+//! Alchemy's EVM endpoints serve the trace and debug namespaces, which is
+//! enough to recover a slot's writes exactly rather than by sampling:
+//! `trace_filter` lists the transactions that called the contract, and a
+//! `prestateTracer` diff of each shows whether it changed the slot and what
+//! it left there. Event scans use `eth_getLogs`, splitting the block range
+//! when Alchemy refuses a response as too large.
+//!
+//! Writes that store the value a slot already holds do not appear in state
+//! diffs, so an explicit zero over an unwritten slot is not seen.
+
+use crate::etherscan::parse_log;
+use crate::event::{events_from_writes, EventFilter, IndexedEvent, ObservedWrite, StorageEvent};
+use crate::rate::RateLimiter;
+use crate::service::IndexerService;
+use serde_json::{json, Value};
+use std::future::Future;
+use traverse_core::TraverseError;
+
+/// Traces fetched per `trace_filter` request
+const TRACE_PAGE_SIZE: u64 = 500;
+
+/// Requests per second that fit Alchemy's free tier compute budget
+const DEFAULT_RATE: u32 = 10;
+
+/// Indexer backed by an Alchemy endpoint
+pub struct AlchemyIndexer {
+    url: String,
+    client: reqwest::Client,
+    limiter: RateLimiter,
+}
+
+impl AlchemyIndexer {
+    /// Create an indexer for an Alchemy network (e.g. `eth-mainnet`, `base-mainnet`)
+    pub fn new(network: &str, api_key: &str) -> Self {
+        Self::from_url(format!("https://{}.g.alchemy.com/v2/{}", network, api_key))
+    }
+
+    /// Create an indexer for an endpoint URL that carries its key
+    pub fn from_url(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            client: reqwest::Client::new(),
+            limiter: RateLimiter::per_second(DEFAULT_RATE),
+        }
+    }
+
+    /// Whether `url` is an Alchemy endpoint
+    pub fn is_alchemy_url(url: &str) -> bool {
+        url.split("://").nth(1).and_then(|rest| rest.split(['/', ':']).next()).is_some_and(|host| host.ends_with(".alchemy.com"))
+    }
+
+    /// Pace requests with `limiter` instead of ten per second
+    pub fn with_rate_limit(mut self, limiter: RateLimiter) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    /// The raw JSON-RPC response, error or not
+    async fn response(&self, method: &str, params: Value) -> Result<Value, TraverseError> {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        self.limiter.send(|| self.client.post(&self.url).json(&body), "Alchemy").await
+    }
+
+    async fn rpc(&self, method: &str, params: Value) -> Result<Value, TraverseError> {
+        let response = self.response(method, params).await?;
+        if let Some(error) = response.get("error") {
+            return Err(TraverseError::external_service(format!("Alchemy {} failed: {}", method, error)));
+        }
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    async fn latest_block(&self) -> Result<u64, TraverseError> {
+        let latest = self.rpc("eth_blockNumber", json!([])).await?;
+        quantity(&latest, "block number")
+    }
+
+    /// Hashes of the transactions that called `contract_address` in the range, in execution order
+    async fn transactions(&self, contract_address: &str, from_block: u64, to_block: u64) -> Result<Vec<(u64, String)>, TraverseError> {
+        let mut transactions: Vec<(u64, u64, String)> = Vec::new();
+        for page in 0.. {
+            let filter = json!({
+                "fromBlock": format!("0x{:x}", from_block),
+                "toBlock": format!("0x{:x}", to_block),
+                "toAddress": [contract_address],
+                "after": page * TRACE_PAGE_SIZE,
+                "count": TRACE_PAGE_SIZE,
+            });
+            let traces = self.rpc("trace_filter", json!([filter])).await?;
+            let traces = traces
+                .as_array()
+                .ok_or_else(|| TraverseError::external_service("Alchemy trace_filter result is not a list"))?;
+            for trace in traces {
+                let Some(hash) = trace.get("transactionHash").and_then(Value::as_str) else {
+                    continue;
+                };
+                let block = trace.get("blockNumber").and_then(Value::as_u64).unwrap_or_default();
+                let position = trace.get("transactionPosition").and_then(Value::as_u64).unwrap_or_default();
+                transactions.push((block, position, hash.to_string()));
+            }
+            if (traces.len() as u64) < TRACE_PAGE_SIZE {
+                break;
+            }
+        }
+        transactions.sort();
+        transactions.dedup();
+        Ok(transactions.into_iter().map(|(block, _, hash)| (block, hash)).collect())
+    }
+}
+
+/// Parse a JSON-RPC hex quantity
+fn quantity(value: &Value, what: &str) -> Result<u64, TraverseError> {
+    value
+        .as_str()
+        .and_then(|s| s.strip_prefix("0x"))
+        .and_then(|digits| u64::from_str_radix(digits, 16).ok())
+        .ok_or_else(|| TraverseError::external_service(format!("Invalid {}: {}", what, value)))
+}
+
+/// Normalize a slot to `0x` followed by 64 lowercase hex digits, as tracers key storage
+fn storage_word(slot: &str) -> String {
+    let digits = slot.trim_start_matches("0x").trim_start_matches('0');
+    format!("0x{:0>64}", digits.to_lowercase())
+}
+
+/// The storage map of `address` in one side of a `prestateTracer` diff
+fn account_storage<'a>(side: Option<&'a Value>, address: &str) -> Option<&'a serde_json::Map<String, Value>> {
+    side?
+        .as_object()?
+        .iter()
+        .find(|(account, _)| account.eq_ignore_ascii_case(address))?
+        .1
+        .get("storage")?
+        .as_object()
+}
+
+/// The value a transaction left in a slot, from its `prestateTracer` diff
+///
+/// Diffs list changed slots only, and omit from the post state a slot the
+/// transaction zeroed.
+fn written_value(diff: &Value, contract_address: &str, storage_slot: &str) -> Option<String> {
+    let slot = storage_word(storage_slot);
+    let find = |side: &str| {
+        account_storage(diff.get(side), contract_address)?
+            .iter()
+            .find(|(key, _)| storage_word(key) == slot)?
+            .1
+            .as_str()
+            .map(str::to_string)
+    };
+    find("post").or_else(|| find("pre").map(|_| storage_word("0")))
+}
+
+/// Whether a log query failed because the range holds too many logs
+fn is_range_error(response: &Value) -> bool {
+    let message = response
+        .get("error")
+        .and_then(|e| e.get("message"))
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_lowercase();
+    message.contains("block range") || message.contains("response size") || message.contains("query returned more than")
+}
+
+impl IndexerService for AlchemyIndexer {
+    fn get_storage_events(
+        &self,
+        contract_address: &str,
+        storage_slot: &str,
+        from_block: Option<u64>,
+        to_block: Option<u64>,
+    ) -> impl Future<Output = Result<Vec<StorageEvent>, TraverseError>> + Send {
+        let contract_address = contract_address.to_string();
+        let storage_slot = storage_slot.to_string();
+
+        async move {
+            let to_block = match to_block {
+                Some(block) => block,
+                None => self.latest_block().await?,
+            };
+            let from_block = from_block.unwrap_or(0).min(to_block);
+
+            let tracer = json!({ "tracer": "prestateTracer", "tracerConfig": { "diffMode": true } });
+            let mut writes = Vec::new();
+            for (block_number, hash) in self.transactions(&contract_address, from_block, to_block).await? {
+                let diff = self.rpc("debug_traceTransaction", json!([hash, tracer])).await?;
+                if let Some(value) = written_value(&diff, &contract_address, &storage_slot) {
+                    writes.push(ObservedWrite { block_number, transaction_hash: hash, value });
+                }
+            }
+            Ok(events_from_writes(&contract_address, &storage_slot, &writes))
+        }
+    }
+
+    fn get_current_value(
+        &self,
+        contract_address: &str,
+        storage_slot: &str,
+    ) -> impl Future<Output = Result<String, TraverseError>> + Send {
+        let params = json!([contract_address, storage_word(storage_slot), "latest"]);
+
+        async move {
+            let value = self.rpc("eth_getStorageAt", params).await?;
+            value
+                .as_str()
+                .map(storage_word)
+                .ok_or_else(|| TraverseError::external_service(format!("Invalid Alchemy storage value: {}", value)))
+        }
+    }
+
+    fn service_name(&self) -> &str {
+        "alchemy"
+    }
+
+    fn scan_events(
+        &self,
+        filter: &EventFilter,
+    ) -> impl Future<Output = Result<Vec<IndexedEvent>, TraverseError>> + Send {
+        let filter = filter.clone();
+
+        async move {
+            let to_block = match filter.to_block {
+                Some(block) => block,
+                None => self.latest_block().await?,
+            };
+            let mut from_block = filter.from_block.unwrap_or(0);
+            let mut window = to_block.saturating_sub(from_block) + 1;
+            let topics = filter.event.as_ref().map_or(json!([]), |event| json!([event]));
+
+            let mut events = Vec::new();
+            while from_block <= to_block {
+                let last = from_block.saturating_add(window - 1).min(to_block);
+                let query = json!({
+                    "address": filter.contract_address,
+                    "fromBlock": format!("0x{:x}", from_block),
+                    "toBlock": format!("0x{:x}", last),
+                    "topics": topics,
+                });
+                let response = self.response("eth_getLogs", json!([query])).await?;
+                if is_range_error(&response) && window > 1 {
+                    window /= 2;
+                    continue;
+                }
+                if let Some(error) = response.get("error") {
+                    return Err(TraverseError::external_service(format!("Alchemy eth_getLogs failed: {}", error)));
+                }
+                let logs = response
+                    .get("result")
+                    .and_then(Value::as_array)
+                    .ok_or_else(|| TraverseError::external_service("Alchemy eth_getLogs result is not a list"))?;
+                for log in logs {
+                    events.push(parse_log(log)?);
+                }
+                from_block = last + 1;
+            }
+            events.sort_by_key(|event| (event.block_number, event.log_index));
+            Ok(events)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::StorageEventType;
+
+    #[test]
+    fn test_written_value_from_state_diff() {
+        let contract = "0xA0b86991c6218b36c1d19d4a2e9eb0ce3606eB48";
+        let slot = "0x0000000000000000000000000000000000000000000000000000000000000002";
+        let diff = json!({
+            "pre": {
+                "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48": {
+                    "balance": "0x0",
+                    "storage": {
+                        "0x0000000000000000000000000000000000000000000000000000000000000002": "0x00000000000000000000000000000000000000000000000000000000000003e8",
+                        "0x0000000000000000000000000000000000000000000000000000000000000005": "0x0000000000000000000000000000000000000000000000000000000000000001"
+                    }
+                }
+            },
+            "post": {
+                "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48": {
+                    "storage": {
+                        "0x0000000000000000000000000000000000000000000000000000000000000002": "0x00000000000000000000000000000000000000000000000000000000000007d0"
+                    }
+                }
+            }
+        });
+        assert!(written_value(&diff, contract, "0x2").unwrap().ends_with("07d0"));
+        // Slot 5 was zeroed, so it leaves the post state
+        assert_eq!(written_value(&diff, contract, "0x5"), Some(storage_word("0")));
+        assert_eq!(written_value(&diff, contract, "0x9"), None);
+        assert_eq!(written_value(&diff, "0x0000000000000000000000000000000000000001", slot), None);
+
+        let writes = [
+            ObservedWrite { block_number: 10, transaction_hash: "0x01".into(), value: written_value(&diff, contract, slot).unwrap() },
+            ObservedWrite { block_number: 12, transaction_hash: "0x02".into(), value: written_value(&diff, contract, "0x5").unwrap() },
+        ];
+        let events = events_from_writes(contract, slot, &writes);
+        assert_eq!(events[0].event_type, StorageEventType::FirstWrite);
+        assert_eq!(events[1].event_type, StorageEventType::Cleared);
+
+        let too_many = json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32602, "message": "Log response size exceeded. You can make eth_getLogs requests with up to a 2K block range" } });
+        assert!(is_range_error(&too_many));
+        assert!(!is_range_error(&json!({ "jsonrpc": "2.0", "id": 1, "result": [] })));
+
+        assert!(AlchemyIndexer::is_alchemy_url("https://eth-mainnet.g.alchemy.com/v2/key"));
+        assert!(!AlchemyIndexer::is_alchemy_url("https://alchemy.com.example.org/v2/key"));
+    }
+}
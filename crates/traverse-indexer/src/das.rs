@@ -0,0 +1,254 @@
+//! Asset history from a Solana Digital Asset Standard (DAS) RPC
+//!
+//! DAS providers index the transactions that touched an asset, including
+//! compressed NFTs whose state never lives in an account. The key is the
+//! asset ID, passed as the contract address; the slot names the field
+//! being validated and is only echoed back. DAS reports what each
+//! transaction did but not the state it left, so writes carry no value
+//! except burns, which clear the asset. Blocks are Solana slots.
+
+use crate::event::{events_from_writes, EventFilter, IndexedEvent, ObservedWrite, StorageEvent};
+use crate::service::IndexerService;
+use serde_json::{json, Value};
+use std::future::Future;
+use traverse_core::TraverseError;
+
+/// Signatures fetched per `getSignaturesForAsset` page
+const PAGE_SIZE: usize = 1000;
+/// Most signatures `getSignatureStatuses` accepts at once
+const STATUS_BATCH: usize = 256;
+
+/// A transaction that touched an asset, with the slot it landed in
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AssetTransaction {
+    signature: String,
+    kind: String,
+    slot: u64,
+}
+
+/// Indexer backed by a DAS-capable Solana RPC
+pub struct SolanaDasIndexer {
+    rpc_url: String,
+    client: reqwest::Client,
+}
+
+impl SolanaDasIndexer {
+    /// Create an indexer for a DAS RPC endpoint
+    pub fn new(rpc_url: impl Into<String>) -> Self {
+        Self {
+            rpc_url: rpc_url.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value, TraverseError> {
+        let mut response: Value = self
+            .client
+            .post(&self.rpc_url)
+            .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+            .send()
+            .await
+            .map_err(|e| TraverseError::external_service(format!("{} request failed: {}", method, e)))?
+            .json()
+            .await
+            .map_err(|e| TraverseError::external_service(format!("Invalid {} response: {}", method, e)))?;
+
+        if let Some(error) = response.get("error") {
+            return Err(TraverseError::external_service(format!("{} failed: {}", method, error)));
+        }
+        match response.get_mut("result").map(Value::take) {
+            Some(result) if !result.is_null() => Ok(result),
+            _ => Err(TraverseError::external_service(format!("No result in {} response", method))),
+        }
+    }
+
+    /// Every transaction that touched the asset, oldest first
+    async fn asset_transactions(&self, asset_id: &str) -> Result<Vec<AssetTransaction>, TraverseError> {
+        let mut signatures = Vec::new();
+        for page in 1.. {
+            let result = self
+                .call("getSignaturesForAsset", json!({ "id": asset_id, "page": page, "limit": PAGE_SIZE }))
+                .await?;
+            let items = parse_signature_page(&result)?;
+            let done = items.len() < PAGE_SIZE;
+            signatures.extend(items);
+            if done {
+                break;
+            }
+        }
+
+        let mut transactions = Vec::with_capacity(signatures.len());
+        for batch in signatures.chunks(STATUS_BATCH) {
+            let ids: Vec<&str> = batch.iter().map(|(signature, _)| signature.as_str()).collect();
+            let result = self
+                .call("getSignatureStatuses", json!([ids, { "searchTransactionHistory": true }]))
+                .await?;
+            transactions.extend(attach_slots(batch, &result)?);
+        }
+        transactions.sort_by_key(|transaction| transaction.slot);
+        Ok(transactions)
+    }
+}
+
+/// Parse a `getSignaturesForAsset` page into `(signature, kind)` pairs
+fn parse_signature_page(result: &Value) -> Result<Vec<(String, String)>, TraverseError> {
+    let items = result
+        .get("items")
+        .and_then(Value::as_array)
+        .ok_or_else(|| TraverseError::external_service("No items in getSignaturesForAsset result"))?;
+    items
+        .iter()
+        .map(|item| match item.as_array().map(Vec::as_slice) {
+            Some([signature, kind, ..]) => match (signature.as_str(), kind.as_str()) {
+                (Some(signature), Some(kind)) => Ok((signature.to_string(), kind.to_string())),
+                _ => Err(TraverseError::external_service(format!("Invalid asset signature: {}", item))),
+            },
+            _ => Err(TraverseError::external_service(format!("Invalid asset signature: {}", item))),
+        })
+        .collect()
+}
+
+/// Pair signatures with the slots `getSignatureStatuses` reports
+///
+/// Failed and unknown transactions changed nothing and are dropped.
+fn attach_slots(batch: &[(String, String)], result: &Value) -> Result<Vec<AssetTransaction>, TraverseError> {
+    let statuses = result
+        .get("value")
+        .and_then(Value::as_array)
+        .filter(|statuses| statuses.len() == batch.len())
+        .ok_or_else(|| TraverseError::external_service("getSignatureStatuses returned the wrong number of statuses"))?;
+
+    Ok(batch
+        .iter()
+        .zip(statuses)
+        .filter(|(_, status)| status.get("err").is_some_and(Value::is_null))
+        .filter_map(|((signature, kind), status)| {
+            Some(AssetTransaction {
+                signature: signature.clone(),
+                kind: kind.clone(),
+                slot: status.get("slot")?.as_u64()?,
+            })
+        })
+        .collect())
+}
+
+/// The value a transaction left: zero for burns, unknown otherwise
+fn write_of(transaction: &AssetTransaction) -> ObservedWrite {
+    let burned = transaction.kind.to_ascii_lowercase().starts_with("burn");
+    ObservedWrite {
+        block_number: transaction.slot,
+        transaction_hash: transaction.signature.clone(),
+        value: if burned { "0".to_string() } else { String::new() },
+    }
+}
+
+/// The asset's current value: zero once burnt, otherwise its owner
+fn asset_value(asset: &Value) -> Result<String, TraverseError> {
+    if asset.get("burnt").and_then(Value::as_bool).unwrap_or(false) {
+        return Ok("0".to_string());
+    }
+    asset
+        .pointer("/ownership/owner")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| TraverseError::external_service("No owner in getAsset result"))
+}
+
+fn in_range(slot: u64, from: Option<u64>, to: Option<u64>) -> bool {
+    from.is_none_or(|from| slot >= from) && to.is_none_or(|to| slot <= to)
+}
+
+impl IndexerService for SolanaDasIndexer {
+    fn get_storage_events(
+        &self,
+        contract_address: &str,
+        storage_slot: &str,
+        from_block: Option<u64>,
+        to_block: Option<u64>,
+    ) -> impl Future<Output = Result<Vec<StorageEvent>, TraverseError>> + Send {
+        let asset_id = contract_address.to_string();
+        let storage_slot = storage_slot.to_string();
+
+        async move {
+            // Classify the whole history so a range starting mid-way keeps its context
+            let writes: Vec<ObservedWrite> = self.asset_transactions(&asset_id).await?.iter().map(write_of).collect();
+            let mut events = events_from_writes(&asset_id, &storage_slot, &writes);
+            events.retain(|event| in_range(event.block_number, from_block, to_block));
+            Ok(events)
+        }
+    }
+
+    fn get_current_value(
+        &self,
+        contract_address: &str,
+        _storage_slot: &str,
+    ) -> impl Future<Output = Result<String, TraverseError>> + Send {
+        let params = json!({ "id": contract_address });
+        async move { asset_value(&self.call("getAsset", params).await?) }
+    }
+
+    fn service_name(&self) -> &str {
+        "solana-das"
+    }
+
+    fn scan_events(
+        &self,
+        filter: &EventFilter,
+    ) -> impl Future<Output = Result<Vec<IndexedEvent>, TraverseError>> + Send {
+        let filter = filter.clone();
+
+        async move {
+            let transactions = self.asset_transactions(&filter.contract_address).await?;
+            Ok(transactions
+                .into_iter()
+                .filter(|t| in_range(t.slot, filter.from_block, filter.to_block))
+                .filter(|t| filter.event.as_ref().is_none_or(|event| t.kind.eq_ignore_ascii_case(event)))
+                .map(|t| IndexedEvent {
+                    block_number: t.slot,
+                    transaction_hash: t.signature,
+                    log_index: 0,
+                    contract_address: filter.contract_address.clone(),
+                    topics: vec![t.kind],
+                    data: String::new(),
+                })
+                .collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::StorageEventType;
+
+    #[test]
+    fn test_asset_history() {
+        let page = json!({
+            "total": 3, "limit": 1000, "page": 1,
+            "items": [["5sig3", "Burn"], ["4sig2", "Transfer"], ["3sig1", "MintToCollectionV1"]]
+        });
+        let signatures = parse_signature_page(&page).unwrap();
+        let statuses = json!({ "value": [
+            { "slot": 300, "err": null, "confirmationStatus": "finalized" },
+            { "slot": 200, "err": null, "confirmationStatus": "finalized" },
+            { "slot": 100, "err": null, "confirmationStatus": "finalized" }
+        ] });
+        let mut transactions = attach_slots(&signatures, &statuses).unwrap();
+        transactions.sort_by_key(|t| t.slot);
+
+        let writes: Vec<_> = transactions.iter().map(write_of).collect();
+        let events = events_from_writes("asset", "owner", &writes);
+        let kinds: Vec<_> = events.iter().map(|e| e.event_type.clone()).collect();
+        assert_eq!(kinds, [StorageEventType::FirstWrite, StorageEventType::Update, StorageEventType::Cleared]);
+        assert_eq!(events[0].transaction_hash, "3sig1");
+
+        // Failed or unknown transactions are dropped; a short status list is an error
+        let partial = json!({ "value": [null, { "slot": 200, "err": { "InstructionError": [0, "Custom"] } }, { "slot": 100, "err": null }] });
+        assert_eq!(attach_slots(&signatures, &partial).unwrap().len(), 1);
+        assert!(attach_slots(&signatures, &json!({ "value": [] })).is_err());
+
+        let owned = json!({ "burnt": false, "ownership": { "owner": "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM" } });
+        assert_eq!(asset_value(&owned).unwrap(), "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM");
+        assert_eq!(asset_value(&json!({ "burnt": true })).unwrap(), "0");
+    }
+}
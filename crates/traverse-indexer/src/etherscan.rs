@@ -0,0 +1,231 @@
+//! Event logs and current values from Etherscan-family explorers
+//!
+//! Etherscan and the explorers that copy its API (BscScan, Polygonscan,
+//! Blockscout's compatibility endpoint) index logs but not storage writes,
+//! so this backend answers event scans and current values; storage
+//! histories need a backend that records writes.
+
+use crate::event::{EventFilter, IndexedEvent, StorageEvent};
+use crate::rate::RateLimiter;
+use crate::service::IndexerService;
+use serde_json::Value;
+use std::future::Future;
+use traverse_core::TraverseError;
+
+/// Logs fetched per request
+const PAGE_SIZE: usize = 1000;
+
+/// Requests per second on Etherscan's free tier
+const DEFAULT_RATE: u32 = 5;
+
+/// Indexer backed by an Etherscan-compatible explorer API
+pub struct EtherscanIndexer {
+    api_url: String,
+    api_key: String,
+    chain_id: Option<u64>,
+    client: reqwest::Client,
+    limiter: RateLimiter,
+}
+
+impl EtherscanIndexer {
+    /// Create an indexer for an explorer's API URL (e.g. `https://api.etherscan.io/api`)
+    pub fn new(api_url: impl Into<String>, api_key: impl Into<String>) -> Self {
+        Self {
+            api_url: api_url.into(),
+            api_key: api_key.into(),
+            chain_id: None,
+            client: reqwest::Client::new(),
+            limiter: RateLimiter::per_second(DEFAULT_RATE),
+        }
+    }
+
+    /// Select the chain on multichain APIs such as Etherscan's `v2/api`
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    /// Pace requests with `limiter` instead of the free tier's five per second
+    pub fn with_rate_limit(mut self, limiter: RateLimiter) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    async fn get(&self, params: &[(&str, String)]) -> Result<Value, TraverseError> {
+        let mut query: Vec<(&str, String)> = params.to_vec();
+        if let Some(chain_id) = self.chain_id {
+            query.push(("chainid", chain_id.to_string()));
+        }
+        query.push(("apikey", self.api_key.clone()));
+
+        self.limiter.send(|| self.client.get(&self.api_url).query(&query), "Explorer").await
+    }
+}
+
+/// Parse a hex quantity; explorers write zero as `0x`
+fn parse_quantity(value: &Value, field: &str) -> Result<u64, TraverseError> {
+    let invalid = || TraverseError::external_service(format!("Invalid {} in log: {}", field, value));
+    let digits = value.as_str().and_then(|s| s.strip_prefix("0x")).ok_or_else(invalid)?;
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(digits, 16).map_err(|_| invalid())
+}
+
+/// Parse a `getLogs` response; "No records found" is an empty page
+fn parse_logs(response: &Value) -> Result<Vec<IndexedEvent>, TraverseError> {
+    let result = response.get("result").unwrap_or(&Value::Null);
+    if response.get("status").and_then(Value::as_str) != Some("1") {
+        let message = response.get("message").and_then(Value::as_str).unwrap_or_default();
+        if message.starts_with("No records found") || result.as_array().is_some_and(|logs| logs.is_empty()) {
+            return Ok(Vec::new());
+        }
+        return Err(TraverseError::external_service(format!("Explorer getLogs failed: {} ({})", message, result)));
+    }
+
+    let logs = result
+        .as_array()
+        .ok_or_else(|| TraverseError::external_service("Explorer getLogs result is not a list"))?;
+    logs.iter().map(parse_log).collect()
+}
+
+/// Parse a log in the JSON-RPC `eth_getLogs` shape, which explorers reuse
+pub(crate) fn parse_log(log: &Value) -> Result<IndexedEvent, TraverseError> {
+    let text = |field: &str| {
+        log.get(field)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| TraverseError::external_service(format!("Missing {} in log", field)))
+    };
+    let topics = log
+        .get("topics")
+        .and_then(Value::as_array)
+        .map(|topics| topics.iter().filter_map(|t| t.as_str().map(str::to_string)).collect())
+        .unwrap_or_default();
+    Ok(IndexedEvent {
+        block_number: parse_quantity(log.get("blockNumber").unwrap_or(&Value::Null), "blockNumber")?,
+        transaction_hash: text("transactionHash")?,
+        log_index: parse_quantity(log.get("logIndex").unwrap_or(&Value::Null), "logIndex")?,
+        contract_address: text("address")?,
+        topics,
+        data: text("data")?,
+    })
+}
+
+/// Parse a proxied JSON-RPC response
+fn parse_proxy_result(response: &Value) -> Result<String, TraverseError> {
+    if let Some(error) = response.get("error") {
+        return Err(TraverseError::external_service(format!("Explorer proxy call failed: {}", error)));
+    }
+    match (response.get("status").and_then(Value::as_str), response.get("result").and_then(Value::as_str)) {
+        (Some("0"), result) => Err(TraverseError::external_service(format!(
+            "Explorer proxy call failed: {}",
+            result.unwrap_or_default()
+        ))),
+        (_, Some(result)) if result.starts_with("0x") => Ok(result.to_string()),
+        _ => Err(TraverseError::external_service(format!("Unexpected explorer proxy response: {}", response))),
+    }
+}
+
+impl IndexerService for EtherscanIndexer {
+    fn get_storage_events(
+        &self,
+        contract_address: &str,
+        _storage_slot: &str,
+        _from_block: Option<u64>,
+        _to_block: Option<u64>,
+    ) -> impl Future<Output = Result<Vec<StorageEvent>, TraverseError>> + Send {
+        let error = TraverseError::feature_not_supported(format!(
+            "Explorers do not index storage writes of {}; use a subgraph or RPC sampling",
+            contract_address
+        ));
+        async move { Err(error) }
+    }
+
+    fn get_current_value(
+        &self,
+        contract_address: &str,
+        storage_slot: &str,
+    ) -> impl Future<Output = Result<String, TraverseError>> + Send {
+        let params = [
+            ("module", "proxy".to_string()),
+            ("action", "eth_getStorageAt".to_string()),
+            ("address", contract_address.to_string()),
+            ("position", storage_slot.to_string()),
+            ("tag", "latest".to_string()),
+        ];
+
+        async move { parse_proxy_result(&self.get(&params).await?) }
+    }
+
+    fn service_name(&self) -> &str {
+        "etherscan"
+    }
+
+    fn scan_events(
+        &self,
+        filter: &EventFilter,
+    ) -> impl Future<Output = Result<Vec<IndexedEvent>, TraverseError>> + Send {
+        let mut params = vec![
+            ("module", "logs".to_string()),
+            ("action", "getLogs".to_string()),
+            ("address", filter.contract_address.clone()),
+            ("fromBlock", filter.from_block.unwrap_or(0).to_string()),
+            ("toBlock", filter.to_block.map_or_else(|| "latest".to_string(), |block| block.to_string())),
+            ("offset", PAGE_SIZE.to_string()),
+        ];
+        if let Some(event) = &filter.event {
+            params.push(("topic0", event.clone()));
+        }
+
+        async move {
+            let mut events = Vec::new();
+            for page in 1.. {
+                let mut query = params.clone();
+                query.push(("page", page.to_string()));
+                let logs = parse_logs(&self.get(&query).await?)?;
+                let done = logs.len() < PAGE_SIZE;
+                events.extend(logs);
+                if done {
+                    break;
+                }
+            }
+            events.sort_by_key(|event| (event.block_number, event.log_index));
+            Ok(events)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_explorer_responses() {
+        let response = json!({
+            "status": "1",
+            "message": "OK",
+            "result": [{
+                "address": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                "topics": ["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"],
+                "data": "0x00000000000000000000000000000000000000000000000000000000000003e8",
+                "blockNumber": "0x1234",
+                "transactionHash": "0xfeed",
+                "logIndex": "0x"
+            }]
+        });
+        let logs = parse_logs(&response).unwrap();
+        assert_eq!(logs[0].block_number, 0x1234);
+        assert_eq!(logs[0].log_index, 0);
+
+        let empty = json!({ "status": "0", "message": "No records found", "result": [] });
+        assert!(parse_logs(&empty).unwrap().is_empty());
+        let rejected = json!({ "status": "0", "message": "NOTOK", "result": "Invalid API Key" });
+        assert!(parse_logs(&rejected).is_err());
+
+        let word = "0x0000000000000000000000000000000000000000000000000000000000000001";
+        assert_eq!(parse_proxy_result(&json!({ "jsonrpc": "2.0", "id": 1, "result": word })).unwrap(), word);
+        assert!(parse_proxy_result(&json!({ "status": "0", "message": "NOTOK", "result": "Max rate limit reached" })).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+//! What indexers report: writes to a storage key, and emitted events
+
+use serde::{Deserialize, Serialize};
+
+/// Event data returned by indexer services
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageEvent {
+    /// Block number where the event occurred
+    pub block_number: u64,
+    /// Transaction hash
+    pub transaction_hash: String,
+    /// Contract address
+    pub contract_address: String,
+    /// Storage slot affected
+    pub storage_slot: String,
+    /// Previous value (if available)
+    pub previous_value: Option<String>,
+    /// New value; empty when the backend knows a write happened but not what it wrote
+    pub new_value: String,
+    /// Event type (write, clear, etc.)
+    pub event_type: StorageEventType,
+}
+
+/// Types of storage events
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum StorageEventType {
+    /// First write to a slot
+    FirstWrite,
+    /// Update to existing value
+    Update,
+    /// Value cleared to zero
+    Cleared,
+    /// Value set to zero (but not cleared)
+    SetToZero,
+}
+
+/// A write an indexer recorded, before it is classified
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObservedWrite {
+    pub block_number: u64,
+    pub transaction_hash: String,
+    pub value: String,
+}
+
+/// Which events to scan for
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventFilter {
+    /// Contract, program or asset that emitted the events
+    pub contract_address: String,
+    /// Event to match: the signature hash (`topic0`) on EVM chains, otherwise as the backend names events
+    pub event: Option<String>,
+    /// First block to scan (None = from genesis)
+    pub from_block: Option<u64>,
+    /// Last block to scan (None = latest)
+    pub to_block: Option<u64>,
+}
+
+/// An event an indexer returned for a scan
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedEvent {
+    pub block_number: u64,
+    pub transaction_hash: String,
+    /// Position of the event in its block
+    pub log_index: u64,
+    pub contract_address: String,
+    /// Indexed fields, the event's identifier first
+    pub topics: Vec<String>,
+    /// Unindexed data (hex encoded, empty if the backend has none)
+    pub data: String,
+}
+
+/// Whether a value reads as zero: `0`, `0x`, or only zero digits
+///
+/// An empty value is unknown, not zero.
+pub fn is_zero_value(value: &str) -> bool {
+    if value.is_empty() {
+        return false;
+    }
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    digits.bytes().all(|b| b == b'0')
+}
+
+/// Classify writes, in block order, into storage events
+///
+/// A zero written over a non-zero value clears the key; a zero written
+/// over nothing or over zero is an explicit zero.
+pub fn events_from_writes(contract_address: &str, storage_slot: &str, writes: &[ObservedWrite]) -> Vec<StorageEvent> {
+    let mut events: Vec<StorageEvent> = Vec::with_capacity(writes.len());
+    for write in writes {
+        let previous = events.last().map(|event| event.new_value.clone());
+        let previous_zero = previous.as_deref().is_none_or(is_zero_value);
+        let event_type = match (is_zero_value(&write.value), previous_zero) {
+            (true, false) => StorageEventType::Cleared,
+            (true, true) => StorageEventType::SetToZero,
+            (false, _) if events.is_empty() => StorageEventType::FirstWrite,
+            (false, _) => StorageEventType::Update,
+        };
+        events.push(StorageEvent {
+            block_number: write.block_number,
+            transaction_hash: write.transaction_hash.clone(),
+            contract_address: contract_address.to_string(),
+            storage_slot: storage_slot.to_string(),
+            previous_value: previous,
+            new_value: write.value.clone(),
+            event_type,
+        });
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(block_number: u64, value: &str) -> ObservedWrite {
+        ObservedWrite { block_number, transaction_hash: format!("0x{:02x}", block_number), value: value.to_string() }
+    }
+
+    #[test]
+    fn test_events_from_writes() {
+        let writes = [write(1, "0x0"), write(2, "0x05"), write(3, "0x07"), write(4, "0x00"), write(5, "0x")];
+        let events = events_from_writes("0xaa", "0x1", &writes);
+        let kinds: Vec<_> = events.iter().map(|e| e.event_type.clone()).collect();
+        assert_eq!(
+            kinds,
+            [
+                StorageEventType::SetToZero,
+                StorageEventType::Update,
+                StorageEventType::Update,
+                StorageEventType::Cleared,
+                StorageEventType::SetToZero,
+            ]
+        );
+        assert_eq!(events[1].previous_value.as_deref(), Some("0x0"));
+
+        let first = events_from_writes("0xaa", "0x1", &[write(9, "0x2a")]);
+        assert_eq!(first[0].event_type, StorageEventType::FirstWrite);
+        assert_eq!(first[0].previous_value, None);
+
+        assert!(is_zero_value("0"));
+        assert!(is_zero_value("0x"));
+        assert!(!is_zero_value(""));
+        assert!(!is_zero_value("0x10"));
+    }
+}
@@ -0,0 +1,145 @@
+//! Storage writes and events from a subgraph on The Graph
+//!
+//! The subgraph must expose the `StorageWrite` and `ContractEvent` entities
+//! described in the crate's GraphQL conventions; a subgraph only sees what
+//! its handlers record, so history starts at the subgraph's start block.
+
+use crate::event::{events_from_writes, EventFilter, IndexedEvent, StorageEvent};
+use crate::graphql;
+use crate::service::IndexerService;
+use serde_json::json;
+use std::future::Future;
+use traverse_core::TraverseError;
+
+const WRITES_QUERY: &str = "query Writes($contract: String!, $slot: String!, $from: BigInt!, $to: BigInt!, $skip: Int!) {
+  storageWrites(first: 1000, skip: $skip, orderBy: blockNumber, orderDirection: asc,
+    where: { contract: $contract, slot: $slot, blockNumber_gte: $from, blockNumber_lte: $to }) {
+    blockNumber transactionHash value
+  }
+}";
+
+const LATEST_WRITE_QUERY: &str = "query LatestWrite($contract: String!, $slot: String!) {
+  storageWrites(first: 1, orderBy: blockNumber, orderDirection: desc, where: { contract: $contract, slot: $slot }) {
+    blockNumber transactionHash value
+  }
+}";
+
+const EVENTS_QUERY: &str = "query Events($contract: String!, $topics: [String!]!, $from: BigInt!, $to: BigInt!, $skip: Int!) {
+  contractEvents(first: 1000, skip: $skip, orderBy: blockNumber, orderDirection: asc,
+    where: { contract: $contract, topics_contains: $topics, blockNumber_gte: $from, blockNumber_lte: $to }) {
+    contract blockNumber transactionHash logIndex topics data
+  }
+}";
+
+/// Indexer backed by a subgraph's GraphQL endpoint
+pub struct GraphIndexer {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl GraphIndexer {
+    /// Create an indexer for a subgraph's query URL
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+impl IndexerService for GraphIndexer {
+    fn get_storage_events(
+        &self,
+        contract_address: &str,
+        storage_slot: &str,
+        from_block: Option<u64>,
+        to_block: Option<u64>,
+    ) -> impl Future<Output = Result<Vec<StorageEvent>, TraverseError>> + Send {
+        let contract_address = contract_address.to_lowercase();
+        let storage_slot = storage_slot.to_lowercase();
+        let variables = json!({
+            "contract": contract_address,
+            "slot": storage_slot,
+            "from": from_block.unwrap_or(0).to_string(),
+            "to": to_block.unwrap_or(u64::MAX).to_string(),
+        });
+
+        async move {
+            let rows = graphql::query_all(&self.client, &self.url, WRITES_QUERY, variables, "/storageWrites").await?;
+            let writes = graphql::parse_writes(&rows)?;
+            Ok(events_from_writes(&contract_address, &storage_slot, &writes))
+        }
+    }
+
+    fn get_current_value(
+        &self,
+        contract_address: &str,
+        storage_slot: &str,
+    ) -> impl Future<Output = Result<String, TraverseError>> + Send {
+        let variables = json!({ "contract": contract_address.to_lowercase(), "slot": storage_slot.to_lowercase() });
+
+        async move {
+            let data = graphql::query(&self.client, &self.url, LATEST_WRITE_QUERY, variables).await?;
+            let rows = data.get("storageWrites").and_then(|rows| rows.as_array()).cloned().unwrap_or_default();
+            let latest = graphql::parse_writes(&rows)?.pop();
+            Ok(latest.map_or_else(|| "0x0".to_string(), |write| write.value))
+        }
+    }
+
+    fn service_name(&self) -> &str {
+        "the-graph"
+    }
+
+    fn scan_events(
+        &self,
+        filter: &EventFilter,
+    ) -> impl Future<Output = Result<Vec<IndexedEvent>, TraverseError>> + Send {
+        let wanted = filter.event.clone();
+        let variables = json!({
+            "contract": filter.contract_address.to_lowercase(),
+            "topics": wanted.iter().collect::<Vec<_>>(),
+            "from": filter.from_block.unwrap_or(0).to_string(),
+            "to": filter.to_block.unwrap_or(u64::MAX).to_string(),
+        });
+
+        async move {
+            let rows = graphql::query_all(&self.client, &self.url, EVENTS_QUERY, variables, "/contractEvents").await?;
+            let mut events = graphql::parse_events(&rows)?;
+            // `topics_contains` matches the topic anywhere; keep events it identifies
+            events.retain(|event| wanted.is_none() || event.topics.first() == wanted.as_ref());
+            events.sort_by_key(|event| (event.block_number, event.log_index));
+            Ok(events)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::StorageEventType;
+
+    #[test]
+    fn test_parse_subgraph_rows() {
+        let data = json!({
+            "storageWrites": [
+                { "blockNumber": "17000000", "transactionHash": "0xa1", "value": "0x01" },
+                { "blockNumber": "17000100", "transactionHash": "0xa2", "value": "0x00" }
+            ],
+            "contractEvents": [
+                { "contract": "0xaa", "blockNumber": "17000000", "transactionHash": "0xa1", "logIndex": "3",
+                  "topics": ["0xddf2", "0x01"], "data": "0x" }
+            ]
+        });
+        let writes = graphql::parse_writes(data["storageWrites"].as_array().unwrap()).unwrap();
+        let events = events_from_writes("0xaa", "0x0", &writes);
+        assert_eq!(events[0].event_type, StorageEventType::FirstWrite);
+        assert_eq!(events[1].event_type, StorageEventType::Cleared);
+
+        let logs = graphql::parse_events(data["contractEvents"].as_array().unwrap()).unwrap();
+        assert_eq!(logs[0].log_index, 3);
+        assert_eq!(logs[0].topics[0], "0xddf2");
+
+        let malformed = json!([{ "blockNumber": "not a number", "transactionHash": "0xa1", "value": "0x01" }]);
+        assert!(graphql::parse_writes(malformed.as_array().unwrap()).is_err());
+    }
+}
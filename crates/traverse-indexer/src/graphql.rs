@@ -0,0 +1,142 @@
+//! GraphQL transport and the entities GraphQL indexers are expected to expose
+//!
+//! The Graph and SubQuery projects define their own schemas, so the
+//! backends assume a conventional one:
+//!
+//! ```graphql
+//! type StorageWrite @entity {
+//!   contract: String!          # lowercase address
+//!   slot: String!              # 0x-prefixed slot
+//!   blockNumber: BigInt!
+//!   transactionHash: String!
+//!   value: String!             # the word written
+//! }
+//!
+//! type ContractEvent @entity {
+//!   contract: String!
+//!   blockNumber: BigInt!
+//!   transactionHash: String!
+//!   logIndex: BigInt!
+//!   topics: [String!]!
+//!   data: String!
+//! }
+//! ```
+
+use crate::event::{IndexedEvent, ObservedWrite};
+use serde_json::Value;
+use traverse_core::TraverseError;
+
+/// Rows fetched per request
+pub(crate) const PAGE_SIZE: usize = 1000;
+
+/// Post a query and return its `data`
+pub(crate) async fn query(
+    client: &reqwest::Client,
+    url: &str,
+    graphql: &str,
+    variables: Value,
+) -> Result<Value, TraverseError> {
+    let mut response: Value = client
+        .post(url)
+        .json(&serde_json::json!({ "query": graphql, "variables": variables }))
+        .send()
+        .await
+        .map_err(|e| TraverseError::external_service(format!("GraphQL request to {} failed: {}", url, e)))?
+        .json()
+        .await
+        .map_err(|e| TraverseError::external_service(format!("Invalid GraphQL response from {}: {}", url, e)))?;
+
+    if let Some(errors) = response.get("errors").filter(|errors| !errors.is_null()) {
+        return Err(TraverseError::external_service(format!("GraphQL query failed: {}", errors)));
+    }
+    match response.get_mut("data").map(Value::take) {
+        Some(data) if !data.is_null() => Ok(data),
+        _ => Err(TraverseError::external_service("No data in GraphQL response")),
+    }
+}
+
+/// Page through a query, collecting the rows at `rows` (a JSON pointer into `data`)
+///
+/// The query takes the offset of the page as `$skip`.
+pub(crate) async fn query_all(
+    client: &reqwest::Client,
+    url: &str,
+    graphql: &str,
+    mut variables: Value,
+    rows: &str,
+) -> Result<Vec<Value>, TraverseError> {
+    let mut collected = Vec::new();
+    loop {
+        variables["skip"] = Value::from(collected.len());
+        let data = query(client, url, graphql, variables.clone()).await?;
+        let page = data
+            .pointer(rows)
+            .and_then(Value::as_array)
+            .ok_or_else(|| TraverseError::external_service(format!("No {} in GraphQL response", rows)))?;
+        collected.extend(page.iter().cloned());
+        if page.len() < PAGE_SIZE {
+            return Ok(collected);
+        }
+    }
+}
+
+/// A number that may be encoded as a JSON number or a (decimal or hex) string
+pub(crate) fn parse_number(value: &Value, field: &str) -> Result<u64, TraverseError> {
+    let parsed = match value {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => match s.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16).ok(),
+            None => s.parse().ok(),
+        },
+        _ => None,
+    };
+    parsed.ok_or_else(|| TraverseError::external_service(format!("Invalid {}: {}", field, value)))
+}
+
+fn string_field(row: &Value, field: &str) -> Result<String, TraverseError> {
+    row.get(field)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| TraverseError::external_service(format!("Missing {} in indexer row", field)))
+}
+
+fn number_field(row: &Value, field: &str) -> Result<u64, TraverseError> {
+    parse_number(row.get(field).unwrap_or(&Value::Null), field)
+}
+
+/// Parse `StorageWrite` rows
+pub(crate) fn parse_writes(rows: &[Value]) -> Result<Vec<ObservedWrite>, TraverseError> {
+    rows.iter()
+        .map(|row| {
+            Ok(ObservedWrite {
+                block_number: number_field(row, "blockNumber")?,
+                transaction_hash: string_field(row, "transactionHash")?,
+                value: string_field(row, "value")?,
+            })
+        })
+        .collect()
+}
+
+/// Parse `ContractEvent` rows
+pub(crate) fn parse_events(rows: &[Value]) -> Result<Vec<IndexedEvent>, TraverseError> {
+    rows.iter()
+        .map(|row| {
+            let topics = row
+                .get("topics")
+                .and_then(Value::as_array)
+                .ok_or_else(|| TraverseError::external_service("Missing topics in indexer row"))?
+                .iter()
+                .map(|topic| topic.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| TraverseError::external_service("Invalid topics in indexer row"))?;
+            Ok(IndexedEvent {
+                block_number: number_field(row, "blockNumber")?,
+                transaction_hash: string_field(row, "transactionHash")?,
+                log_index: number_field(row, "logIndex")?,
+                contract_address: string_field(row, "contract")?,
+                topics,
+                data: string_field(row, "data")?,
+            })
+        })
+        .collect()
+}
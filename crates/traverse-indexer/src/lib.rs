@@ -0,0 +1,80 @@
+//! Chain-agnostic indexer API for validating zero semantics
+//!
+//! A storage proof shows what a key holds at one block, but a declared zero
+//! semantics is a claim about the key's history: whether it was ever
+//! written, explicitly zeroed or cleared. Checking the claim needs an
+//! indexer. This crate defines what traverse asks of one — the writes to a
+//! key, the events a contract emitted, and the first write — and a
+//! [`SemanticValidator`] that checks declarations against any backend.
+//!
+//! # Backends
+//!
+//! - [`MockIndexerService`]: In-memory events and values for tests
+//! - **Etherscan family**: Event logs and current values from Etherscan-compatible explorers (`client`)
+//! - **Alchemy**: Exact storage writes from transaction state diffs, plus event logs (`client`)
+//! - **Moralis**: Event logs from the Moralis Web3 Data API (`client`)
+//! - **The Graph** and **SubQuery**: Storage writes and events from a subgraph or SubQuery project (`client`)
+//! - **Solana DAS**: Asset history from a Digital Asset Standard RPC (`client`)
+//!
+//! Chain crates add backends of their own; `traverse-ethereum` samples
+//! `eth_getStorageAt` from a plain node.
+//!
+//! `traverse-ethereum`, `traverse-solana` (DAS) and `traverse-cosmos` (The
+//! Graph and SubQuery) re-export the backends they validate with, behind
+//! their CLIs' `generate-proof --validate-semantics`.
+//!
+//! Hosted backends pace their requests with a [`RateLimiter`] set to the
+//! provider's free tier and retry throttled ones; pass one built for your
+//! plan to `with_rate_limit`.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! use traverse_indexer::{EtherscanIndexer, EventFilter, IndexerService};
+//!
+//! let indexer = EtherscanIndexer::new("https://api.etherscan.io/api", api_key);
+//! let filter = EventFilter { contract_address: token.into(), from_block: Some(19_000_000), ..Default::default() };
+//! let logs = indexer.scan_events(&filter).await?;
+//! ```
+
+pub mod event;
+pub mod mock;
+pub mod service;
+pub mod validator;
+
+#[cfg(feature = "client")]
+pub mod alchemy;
+#[cfg(feature = "client")]
+pub mod das;
+#[cfg(feature = "client")]
+pub mod etherscan;
+#[cfg(feature = "client")]
+pub mod graph;
+#[cfg(feature = "client")]
+mod graphql;
+#[cfg(feature = "client")]
+pub mod moralis;
+#[cfg(feature = "client")]
+pub mod rate;
+#[cfg(feature = "client")]
+pub mod subquery;
+
+pub use event::{events_from_writes, is_zero_value, EventFilter, IndexedEvent, ObservedWrite, StorageEvent, StorageEventType};
+pub use mock::MockIndexerService;
+pub use service::IndexerService;
+pub use validator::{SemanticConflict, SemanticValidator, ValidationResult};
+
+#[cfg(feature = "client")]
+pub use alchemy::AlchemyIndexer;
+#[cfg(feature = "client")]
+pub use das::SolanaDasIndexer;
+#[cfg(feature = "client")]
+pub use etherscan::EtherscanIndexer;
+#[cfg(feature = "client")]
+pub use graph::GraphIndexer;
+#[cfg(feature = "client")]
+pub use moralis::MoralisIndexer;
+#[cfg(feature = "client")]
+pub use rate::RateLimiter;
+#[cfg(feature = "client")]
+pub use subquery::SubQueryIndexer;
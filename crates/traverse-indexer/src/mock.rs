@@ -0,0 +1,134 @@
+//! An in-memory indexer for tests
+
+use crate::event::{EventFilter, IndexedEvent, StorageEvent};
+use crate::service::IndexerService;
+use std::collections::HashMap;
+use std::future::Future;
+use traverse_core::TraverseError;
+
+/// Mock indexer service for testing
+pub struct MockIndexerService {
+    name: String,
+    mock_events: HashMap<String, Vec<StorageEvent>>,
+    mock_values: HashMap<String, String>,
+    mock_logs: Vec<IndexedEvent>,
+}
+
+impl MockIndexerService {
+    /// Create a new mock indexer
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            mock_events: HashMap::new(),
+            mock_values: HashMap::new(),
+            mock_logs: Vec::new(),
+        }
+    }
+
+    /// Add mock events for testing
+    pub fn add_mock_events(&mut self, key: String, events: Vec<StorageEvent>) {
+        self.mock_events.insert(key, events);
+    }
+
+    /// Add mock current value
+    pub fn add_mock_value(&mut self, key: String, value: String) {
+        self.mock_values.insert(key, value);
+    }
+
+    /// Add a mock event for scans to find
+    pub fn add_mock_log(&mut self, event: IndexedEvent) {
+        self.mock_logs.push(event);
+    }
+
+    /// Create key for lookups
+    fn make_key(&self, contract_address: &str, storage_slot: &str) -> String {
+        format!("{}:{}", contract_address, storage_slot)
+    }
+}
+
+impl IndexerService for MockIndexerService {
+    fn get_storage_events(
+        &self,
+        contract_address: &str,
+        storage_slot: &str,
+        _from_block: Option<u64>,
+        _to_block: Option<u64>,
+    ) -> impl Future<Output = Result<Vec<StorageEvent>, TraverseError>> + Send {
+        let key = self.make_key(contract_address, storage_slot);
+        let result = self.mock_events.get(&key).cloned().unwrap_or_default();
+        async move { Ok(result) }
+    }
+
+    fn get_current_value(
+        &self,
+        contract_address: &str,
+        storage_slot: &str,
+    ) -> impl Future<Output = Result<String, TraverseError>> + Send {
+        let key = self.make_key(contract_address, storage_slot);
+        let result = self.mock_values.get(&key).cloned().unwrap_or_else(|| {
+            "0x0000000000000000000000000000000000000000000000000000000000000000".to_string()
+        });
+        async move { Ok(result) }
+    }
+
+    fn service_name(&self) -> &str {
+        &self.name
+    }
+
+    fn scan_events(
+        &self,
+        filter: &EventFilter,
+    ) -> impl Future<Output = Result<Vec<IndexedEvent>, TraverseError>> + Send {
+        let mut result: Vec<IndexedEvent> = self
+            .mock_logs
+            .iter()
+            .filter(|event| event.contract_address == filter.contract_address)
+            .filter(|event| filter.event.as_ref().is_none_or(|wanted| event.topics.first() == Some(wanted)))
+            .filter(|event| filter.from_block.is_none_or(|from| event.block_number >= from))
+            .filter(|event| filter.to_block.is_none_or(|to| event.block_number <= to))
+            .cloned()
+            .collect();
+        result.sort_by_key(|event| (event.block_number, event.log_index));
+        async move { Ok(result) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{events_from_writes, ObservedWrite};
+
+    fn log(block_number: u64, log_index: u64, topic: &str) -> IndexedEvent {
+        IndexedEvent {
+            block_number,
+            transaction_hash: format!("0x{:02x}", block_number),
+            log_index,
+            contract_address: "0xaa".to_string(),
+            topics: vec![topic.to_string()],
+            data: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scan_and_first_write() {
+        let mut indexer = MockIndexerService::new("mock".to_string());
+        indexer.add_mock_log(log(7, 1, "0xddf2"));
+        indexer.add_mock_log(log(3, 0, "0xddf2"));
+        indexer.add_mock_log(log(7, 0, "0x8c5b"));
+
+        let filter = EventFilter { contract_address: "0xaa".to_string(), event: Some("0xddf2".to_string()), ..Default::default() };
+        let found = indexer.scan_events(&filter).await.unwrap();
+        assert_eq!(found.iter().map(|e| e.block_number).collect::<Vec<_>>(), [3, 7]);
+        let bounded = EventFilter { from_block: Some(5), event: None, ..filter };
+        assert_eq!(indexer.scan_events(&bounded).await.unwrap().len(), 2);
+
+        let writes = [
+            ObservedWrite { block_number: 12, transaction_hash: "0x0c".into(), value: "0x1".into() },
+            ObservedWrite { block_number: 20, transaction_hash: "0x14".into(), value: "0x2".into() },
+        ];
+        indexer.add_mock_events("0xaa:0x0".to_string(), events_from_writes("0xaa", "0x0", &writes));
+        let first = indexer.first_write("0xaa", "0x0", None).await.unwrap().unwrap();
+        assert_eq!(first.block_number, 12);
+        assert!(indexer.first_write("0xaa", "0x1", None).await.unwrap().is_none());
+    }
+}
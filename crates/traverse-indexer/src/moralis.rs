@@ -0,0 +1,223 @@
+//! Event logs from the Moralis Web3 Data API
+//!
+//! Moralis indexes contract logs across EVM chains behind one REST API and
+//! pages results with an opaque cursor. It serves neither storage writes nor
+//! storage reads, so this backend answers event scans only.
+
+use crate::event::{EventFilter, IndexedEvent, StorageEvent};
+use crate::rate::RateLimiter;
+use crate::service::IndexerService;
+use serde_json::Value;
+use std::future::Future;
+use traverse_core::TraverseError;
+
+/// Moralis' EVM API
+pub const DEFAULT_API_URL: &str = "https://deep-index.moralis.io/api/v2.2";
+
+/// Logs fetched per request, the API's maximum
+const PAGE_SIZE: usize = 100;
+
+/// Requests per second on Moralis' free plan
+const DEFAULT_RATE: u32 = 25;
+
+/// Indexer backed by the Moralis Web3 Data API
+pub struct MoralisIndexer {
+    api_url: String,
+    api_key: String,
+    chain: String,
+    client: reqwest::Client,
+    limiter: RateLimiter,
+}
+
+impl MoralisIndexer {
+    /// Create an indexer for Ethereum mainnet
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            api_url: DEFAULT_API_URL.to_string(),
+            api_key: api_key.into(),
+            chain: "eth".to_string(),
+            client: reqwest::Client::new(),
+            limiter: RateLimiter::per_second(DEFAULT_RATE),
+        }
+    }
+
+    /// Select the chain by Moralis name (`polygon`, `base`) or hex chain ID (`0x1`)
+    pub fn with_chain(mut self, chain: impl Into<String>) -> Self {
+        self.chain = chain.into();
+        self
+    }
+
+    /// Use another deployment of the API
+    pub fn with_api_url(mut self, api_url: impl Into<String>) -> Self {
+        self.api_url = api_url.into();
+        self
+    }
+
+    /// Pace requests with `limiter` instead of the free plan's 25 per second
+    pub fn with_rate_limit(mut self, limiter: RateLimiter) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    async fn get(&self, path: &str, query: &[(&str, String)]) -> Result<Value, TraverseError> {
+        let url = format!("{}/{}", self.api_url.trim_end_matches('/'), path);
+        self.limiter
+            .send(|| self.client.get(&url).header("X-API-Key", &self.api_key).query(query), "Moralis")
+            .await
+    }
+
+    fn unsupported(&self, what: &str, contract_address: &str) -> TraverseError {
+        TraverseError::feature_not_supported(format!(
+            "Moralis does not serve {} of {}; use a subgraph, Alchemy or RPC sampling",
+            what, contract_address
+        ))
+    }
+}
+
+/// Parse a number Moralis writes as a decimal string or a JSON number
+fn parse_number(value: Option<&Value>, field: &str) -> Result<u64, TraverseError> {
+    match value {
+        Some(Value::Number(number)) => number.as_u64(),
+        Some(Value::String(text)) => text.parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| TraverseError::external_service(format!("Invalid {} in Moralis log: {:?}", field, value)))
+}
+
+/// Parse a page of logs and the cursor of the next page, if any
+fn parse_page(response: &Value) -> Result<(Vec<IndexedEvent>, Option<String>), TraverseError> {
+    let logs = response.get("result").and_then(Value::as_array).ok_or_else(|| {
+        let message = response.get("message").and_then(Value::as_str).unwrap_or_default();
+        TraverseError::external_service(format!("Moralis logs request failed: {}", message))
+    })?;
+
+    let events = logs
+        .iter()
+        .map(|log| {
+            let text = |field: &str| {
+                log.get(field)
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+                    .ok_or_else(|| TraverseError::external_service(format!("Missing {} in Moralis log", field)))
+            };
+            let topics = ["topic0", "topic1", "topic2", "topic3"]
+                .iter()
+                .map_while(|field| log.get(*field).and_then(Value::as_str).map(str::to_string))
+                .collect();
+            Ok(IndexedEvent {
+                block_number: parse_number(log.get("block_number"), "block_number")?,
+                transaction_hash: text("transaction_hash")?,
+                log_index: parse_number(log.get("log_index"), "log_index")?,
+                contract_address: text("address")?,
+                topics,
+                data: log.get("data").and_then(Value::as_str).unwrap_or_default().to_string(),
+            })
+        })
+        .collect::<Result<Vec<_>, TraverseError>>()?;
+
+    let cursor = response
+        .get("cursor")
+        .and_then(Value::as_str)
+        .filter(|cursor| !cursor.is_empty())
+        .map(str::to_string);
+    Ok((events, cursor))
+}
+
+impl IndexerService for MoralisIndexer {
+    fn get_storage_events(
+        &self,
+        contract_address: &str,
+        _storage_slot: &str,
+        _from_block: Option<u64>,
+        _to_block: Option<u64>,
+    ) -> impl Future<Output = Result<Vec<StorageEvent>, TraverseError>> + Send {
+        let error = self.unsupported("storage writes", contract_address);
+        async move { Err(error) }
+    }
+
+    fn get_current_value(
+        &self,
+        contract_address: &str,
+        _storage_slot: &str,
+    ) -> impl Future<Output = Result<String, TraverseError>> + Send {
+        let error = self.unsupported("storage reads", contract_address);
+        async move { Err(error) }
+    }
+
+    fn service_name(&self) -> &str {
+        "moralis"
+    }
+
+    fn scan_events(
+        &self,
+        filter: &EventFilter,
+    ) -> impl Future<Output = Result<Vec<IndexedEvent>, TraverseError>> + Send {
+        let path = format!("{}/logs", filter.contract_address);
+        let mut params = vec![("chain", self.chain.clone()), ("limit", PAGE_SIZE.to_string())];
+        if let Some(from_block) = filter.from_block {
+            params.push(("from_block", from_block.to_string()));
+        }
+        if let Some(to_block) = filter.to_block {
+            params.push(("to_block", to_block.to_string()));
+        }
+        if let Some(event) = &filter.event {
+            params.push(("topic0", event.clone()));
+        }
+
+        async move {
+            let mut events = Vec::new();
+            let mut cursor: Option<String> = None;
+            loop {
+                let mut query = params.clone();
+                if let Some(cursor) = &cursor {
+                    query.push(("cursor", cursor.clone()));
+                }
+                let (logs, next) = parse_page(&self.get(&path, &query).await?)?;
+                events.extend(logs);
+                match next {
+                    Some(next) => cursor = Some(next),
+                    None => break,
+                }
+            }
+            events.sort_by_key(|event| (event.block_number, event.log_index));
+            Ok(events)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_moralis_page() {
+        let response = json!({
+            "page": 0,
+            "page_size": 100,
+            "cursor": "eyJhbGciOi",
+            "result": [{
+                "transaction_hash": "0xfeed",
+                "address": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                "block_timestamp": "2024-01-01T00:00:00.000Z",
+                "block_number": "18908895",
+                "block_hash": "0xbeef",
+                "data": "0x00000000000000000000000000000000000000000000000000000000000003e8",
+                "topic0": "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+                "topic1": "0x000000000000000000000000000000000000000000000000000000000000dead",
+                "topic2": null,
+                "topic3": null,
+                "log_index": 7
+            }]
+        });
+        let (logs, cursor) = parse_page(&response).unwrap();
+        assert_eq!(logs[0].block_number, 18_908_895);
+        assert_eq!(logs[0].log_index, 7);
+        assert_eq!(logs[0].topics.len(), 2);
+        assert_eq!(cursor.as_deref(), Some("eyJhbGciOi"));
+
+        let last = json!({ "page": 1, "cursor": null, "result": [] });
+        assert_eq!(parse_page(&last).unwrap(), (Vec::new(), None));
+        assert!(parse_page(&json!({ "message": "Invalid key" })).is_err());
+    }
+}
@@ -0,0 +1,132 @@
+//! Client-side rate limiting for hosted indexer APIs
+//!
+//! Hosted APIs cap requests per second per key and answer bursts with HTTP
+//! 429 or, on Etherscan-family explorers, a `Max rate limit reached` result.
+//! A [`RateLimiter`] spaces a backend's requests and retries throttled ones
+//! with exponential backoff.
+
+use serde_json::Value;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+use traverse_core::TraverseError;
+
+/// Spaces requests to one API and retries those it throttles
+#[derive(Debug)]
+pub struct RateLimiter {
+    interval: Duration,
+    retries: u32,
+    next: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    /// Retries of a throttled request when none are configured
+    pub const DEFAULT_RETRIES: u32 = 3;
+
+    /// Allow at most `requests` requests per second
+    pub fn per_second(requests: u32) -> Self {
+        Self::with_interval(Duration::from_secs(1) / requests.max(1))
+    }
+
+    /// Leave at least `interval` between requests
+    pub fn with_interval(interval: Duration) -> Self {
+        Self { interval, retries: Self::DEFAULT_RETRIES, next: Mutex::new(None) }
+    }
+
+    /// Do not space requests; throttled requests are still retried
+    pub fn unlimited() -> Self {
+        Self::with_interval(Duration::ZERO)
+    }
+
+    /// Set how often a throttled request is retried
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Wait for the next request slot
+    pub async fn acquire(&self) {
+        let mut next = self.next.lock().await;
+        let now = Instant::now();
+        let start = next.map_or(now, |at| at.max(now));
+        *next = Some(start + self.interval);
+        drop(next);
+        tokio::time::sleep_until(start).await;
+    }
+
+    /// Send a request built by `request` and decode its JSON body, retrying while throttled
+    pub(crate) async fn send(
+        &self,
+        request: impl Fn() -> reqwest::RequestBuilder,
+        service: &str,
+    ) -> Result<Value, TraverseError> {
+        let mut backoff = self.interval.max(Duration::from_millis(500));
+        for attempt in 0..=self.retries {
+            self.acquire().await;
+            let response = request()
+                .send()
+                .await
+                .map_err(|e| TraverseError::external_service(format!("{} request failed: {}", service, e)))?;
+
+            let status = response.status();
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse().ok())
+                .map(Duration::from_secs);
+            let throttled = status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+            let body: Option<Value> = if throttled { None } else { response.json().await.ok() };
+
+            if throttled || body.as_ref().is_some_and(is_throttled) {
+                if attempt < self.retries {
+                    tokio::time::sleep(retry_after.unwrap_or(backoff)).await;
+                    backoff *= 2;
+                    continue;
+                }
+                return Err(TraverseError::external_service(format!(
+                    "{} is rate limiting requests; retried {} times",
+                    service, self.retries
+                )));
+            }
+            if !status.is_success() {
+                return Err(TraverseError::external_service(format!("{} answered HTTP {}", service, status)));
+            }
+            return body.ok_or_else(|| TraverseError::external_service(format!("Invalid {} response", service)));
+        }
+        unreachable!("the last attempt returns")
+    }
+}
+
+/// Whether a response body reports throttling rather than a result
+fn is_throttled(body: &Value) -> bool {
+    let rate_limited = |text: &str| text.to_lowercase().contains("rate limit");
+    let result_limited = body.get("status").and_then(Value::as_str) == Some("0")
+        && body.get("result").and_then(Value::as_str).is_some_and(rate_limited);
+    let error = body.get("error");
+    let error_limited = error.and_then(|e| e.get("code")).and_then(Value::as_i64) == Some(429)
+        || error.and_then(|e| e.get("message")).and_then(Value::as_str).is_some_and(rate_limited);
+    result_limited || error_limited
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_rate_limiter_spaces_requests() {
+        let limiter = RateLimiter::per_second(20);
+        let start = std::time::Instant::now();
+        for _ in 0..3 {
+            limiter.acquire().await;
+        }
+        // The first request goes at once, the next two 50ms apart
+        assert!(start.elapsed() >= Duration::from_millis(100));
+
+        assert!(is_throttled(&json!({ "status": "0", "message": "NOTOK", "result": "Max rate limit reached" })));
+        assert!(is_throttled(&json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": 429, "message": "Too many" } })));
+        assert!(!is_throttled(&json!({ "status": "0", "message": "NOTOK", "result": "Invalid API Key" })));
+        assert!(!is_throttled(&json!({ "jsonrpc": "2.0", "id": 1, "result": "0x1" })));
+    }
+}
@@ -0,0 +1,66 @@
+//! The interface every indexer backend implements
+
+use crate::event::{EventFilter, IndexedEvent, StorageEvent};
+use std::future::Future;
+use traverse_core::TraverseError;
+
+/// Trait for blockchain indexer services
+///
+/// Keys are chain-specific: storage slots on EVM chains, accounts or asset
+/// IDs on Solana. Backends implement what they index; the event scan is
+/// optional and fails by default.
+pub trait IndexerService: Sync {
+    /// Get storage events for a specific slot
+    ///
+    /// # Arguments
+    ///
+    /// * `contract_address` - Contract address to query
+    /// * `storage_slot` - Storage slot to get events for
+    /// * `from_block` - Starting block number (None = from genesis)
+    /// * `to_block` - Ending block number (None = latest)
+    ///
+    /// # Returns
+    ///
+    /// Vector of storage events affecting the slot
+    fn get_storage_events(
+        &self,
+        contract_address: &str,
+        storage_slot: &str,
+        from_block: Option<u64>,
+        to_block: Option<u64>,
+    ) -> impl Future<Output = Result<Vec<StorageEvent>, TraverseError>> + Send;
+
+    /// Get the current value of a storage slot
+    fn get_current_value(
+        &self,
+        contract_address: &str,
+        storage_slot: &str,
+    ) -> impl Future<Output = Result<String, TraverseError>> + Send;
+
+    /// Get service name for identification
+    fn service_name(&self) -> &str;
+
+    /// Scan for events a contract emitted, in block order
+    fn scan_events(
+        &self,
+        filter: &EventFilter,
+    ) -> impl Future<Output = Result<Vec<IndexedEvent>, TraverseError>> + Send {
+        let error = TraverseError::external_service(format!(
+            "{} does not index events of {}",
+            self.service_name(),
+            filter.contract_address
+        ));
+        async move { Err(error) }
+    }
+
+    /// The first write to a slot up to `to_block`, if it was ever written
+    fn first_write(
+        &self,
+        contract_address: &str,
+        storage_slot: &str,
+        to_block: Option<u64>,
+    ) -> impl Future<Output = Result<Option<StorageEvent>, TraverseError>> + Send {
+        let events = self.get_storage_events(contract_address, storage_slot, None, to_block);
+        async move { Ok(events.await?.into_iter().min_by_key(|event| event.block_number)) }
+    }
+}
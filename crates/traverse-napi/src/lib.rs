@@ -0,0 +1,209 @@
+//! Node.js bindings for query resolution and witness creation
+//!
+//! TypeScript services that build witnesses on every request pay for a
+//! process spawn when they shell out to the CLI. These napi-rs bindings
+//! expose the calls such services make — resolving queries, turning an
+//! `eth_getProof` response into verification requests, and creating
+//! witnesses — in-process. They share their logic with `traverse-wasm`, so
+//! both produce the witnesses the controller would.
+//!
+//! Queries, layouts and requests are JSON strings in the formats the CLIs
+//! and `traverse-valence` use; witnesses are `Buffer`s.
+//!
+//! # Usage
+//!
+//! ```ts
+//! import { resolveQueries, buildVerificationRequests, createWitnesses } from "traverse-napi";
+//!
+//! const queries = resolveQueries(layoutJson, ["totalSupply", `balances[${holder}]`]);
+//! const keys = queries.map((q) => "0x" + JSON.parse(q).storage_key);
+//! const proof = await provider.send("eth_getProof", [token, keys, "latest"]);
+//! const batch = buildVerificationRequests(queries, JSON.stringify(proof), blockNumber);
+//! const witnesses: Buffer[] = createWitnesses(batch);
+//! ```
+//!
+//! Build with `npm run build` in `crates/traverse-napi`, which also writes the TypeScript declarations.
+
+use napi::bindgen_prelude::Buffer;
+use napi_derive::napi;
+use serde::Deserialize;
+use traverse_core::LayoutInfo;
+use traverse_valence::{BatchStorageVerificationRequest, CoprocessorStorageQuery, StorageVerificationRequest};
+use traverse_wasm::request::{self, ProofEntry};
+use traverse_wasm::{witness, WasmError};
+
+/// The parts of an `eth_getProof` result requests are built from
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetProofResult {
+    address: String,
+    storage_proof: Vec<ProofEntry>,
+}
+
+fn to_napi(err: impl std::fmt::Display) -> napi::Error {
+    napi::Error::from_reason(err.to_string())
+}
+
+fn block_number(block_number: Option<i64>) -> napi::Result<Option<u64>> {
+    block_number
+        .map(|n| u64::try_from(n).map_err(|_| to_napi(format!("Invalid block number: {}", n))))
+        .transpose()
+}
+
+/// Parse an `eth_getProof` result, with or without its JSON-RPC envelope
+fn parse_get_proof(response_json: &str) -> Result<GetProofResult, WasmError> {
+    let mut response: serde_json::Value = serde_json::from_str(response_json)?;
+    let result = match response.get_mut("result") {
+        Some(result) => result.take(),
+        None => response,
+    };
+    Ok(serde_json::from_value(result)?)
+}
+
+/// Pair each query with the `eth_getProof` entry for its storage key
+fn requests_from_get_proof(
+    queries: Vec<CoprocessorStorageQuery>,
+    response_json: &str,
+    block_number: Option<u64>,
+) -> Result<BatchStorageVerificationRequest, WasmError> {
+    let response = parse_get_proof(response_json)?;
+    let mut storage_batch = Vec::with_capacity(queries.len());
+    for query in queries {
+        let mut entry = None;
+        for candidate in &response.storage_proof {
+            if candidate.is_for(&query.storage_key)? {
+                entry = Some(candidate.clone());
+                break;
+            }
+        }
+        let entry = entry.ok_or_else(|| {
+            WasmError::InvalidInput(format!("eth_getProof response has no proof for {} ({})", query.query, query.storage_key))
+        })?;
+        storage_batch.push(request::build_request(query, entry, Some(response.address.clone()), block_number)?);
+    }
+
+    Ok(BatchStorageVerificationRequest {
+        storage_batch,
+        contract_address: Some(response.address),
+        block_number,
+    })
+}
+
+/// Resolve a query against a layout; returns the coprocessor storage query as JSON
+#[napi]
+pub fn resolve_query(layout_json: String, query: String) -> napi::Result<String> {
+    let resolved = request::resolve_query(&layout_json, &query).map_err(to_napi)?;
+    serde_json::to_string(&resolved).map_err(to_napi)
+}
+
+/// Resolve several queries against one layout
+#[napi]
+pub fn resolve_queries(layout_json: String, queries: Vec<String>) -> napi::Result<Vec<String>> {
+    let layout: LayoutInfo = serde_json::from_str(&layout_json).map_err(to_napi)?;
+    queries
+        .iter()
+        .map(|query| {
+            let resolved = request::resolve_in_layout(&layout, query).map_err(to_napi)?;
+            serde_json::to_string(&resolved).map_err(to_napi)
+        })
+        .collect()
+}
+
+/// Build a verification request from a storage query and the `eth_getProof` response for its key
+#[napi]
+pub fn build_verification_request(
+    storage_query_json: String,
+    get_proof_json: String,
+    block_number: Option<i64>,
+) -> napi::Result<String> {
+    let query: CoprocessorStorageQuery = serde_json::from_str(&storage_query_json).map_err(to_napi)?;
+    let batch = requests_from_get_proof(vec![query], &get_proof_json, self::block_number(block_number)?).map_err(to_napi)?;
+    serde_json::to_string(&batch.storage_batch[0]).map_err(to_napi)
+}
+
+/// Build a batch request from storage queries and one `eth_getProof` response covering their keys
+#[napi]
+pub fn build_verification_requests(
+    storage_queries_json: Vec<String>,
+    get_proof_json: String,
+    block_number: Option<i64>,
+) -> napi::Result<String> {
+    let queries = storage_queries_json
+        .iter()
+        .map(|query| serde_json::from_str(query).map_err(to_napi))
+        .collect::<napi::Result<Vec<CoprocessorStorageQuery>>>()?;
+    let batch = requests_from_get_proof(queries, &get_proof_json, self::block_number(block_number)?).map_err(to_napi)?;
+    serde_json::to_string(&batch).map_err(to_napi)
+}
+
+/// Create the witness for a verification request
+#[napi]
+pub fn create_witness(request_json: String) -> napi::Result<Buffer> {
+    let request: StorageVerificationRequest = serde_json::from_str(&request_json).map_err(to_napi)?;
+    Ok(witness::create_witness(&request).map_err(to_napi)?.into())
+}
+
+/// Create the witnesses for a batch request
+#[napi]
+pub fn create_witnesses(batch_json: String) -> napi::Result<Vec<Buffer>> {
+    let request: BatchStorageVerificationRequest = serde_json::from_str(&batch_json).map_err(to_napi)?;
+    let witnesses = witness::create_witnesses(&request).map_err(to_napi)?;
+    Ok(witnesses.into_iter().map(Buffer::from).collect())
+}
+
+/// Decode a witness; returns its fields as JSON
+#[napi]
+pub fn inspect_witness(witness: Buffer) -> napi::Result<String> {
+    let summary = witness::inspect_witness(&witness).map_err(to_napi)?;
+    serde_json::to_string(&summary).map_err(to_napi)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAYOUT: &str = r#"{
+        "contract_name": "Token",
+        "storage": [
+            { "label": "owner", "slot": "0", "offset": 0, "type_name": "t_address", "zero_semantics": "NeverWritten" },
+            { "label": "totalSupply", "slot": "2", "offset": 0, "type_name": "t_uint256", "zero_semantics": "ValidZero" }
+        ],
+        "types": [
+            { "label": "t_address", "number_of_bytes": "20", "encoding": "inplace", "base": null, "key": null, "value": null },
+            { "label": "t_uint256", "number_of_bytes": "32", "encoding": "inplace", "base": null, "key": null, "value": null }
+        ]
+    }"#;
+
+    #[test]
+    fn test_requests_from_get_proof() {
+        let queries = vec![
+            request::resolve_query(LAYOUT, "totalSupply").unwrap(),
+            request::resolve_query(LAYOUT, "owner").unwrap(),
+        ];
+        // Entries need not be in query order, and nodes trim storage keys and values
+        let response = r#"{ "jsonrpc": "2.0", "id": 1, "result": {
+            "address": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+            "accountProof": [], "balance": "0x0", "codeHash": "0x00", "nonce": "0x1", "storageHash": "0x00",
+            "storageProof": [
+                { "key": "0x0", "value": "0x742d35cc6634c0532925a3b8d97c2e0d8b2d9c53", "proof": ["0xf8"] },
+                { "key": "0x2", "value": "0x3e8", "proof": ["0xf9"] }
+            ]
+        } }"#;
+
+        let batch = requests_from_get_proof(queries.clone(), response, Some(21_000_000)).unwrap();
+        assert_eq!(batch.storage_batch.len(), 2);
+        assert_eq!(batch.storage_batch[0].storage_proof.value, format!("0x{:0>64}", "3e8"));
+        assert_eq!(batch.storage_batch[1].storage_proof.proof, ["0xf8"]);
+        assert_eq!(batch.contract_address.as_deref(), Some("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"));
+        assert_eq!(witness::create_witnesses(&batch).unwrap().len(), 2);
+
+        // The bare result works too; a key the response does not cover is an error
+        let bare = serde_json::to_string(&serde_json::from_str::<serde_json::Value>(response).unwrap()["result"]).unwrap();
+        assert!(requests_from_get_proof(queries[..1].to_vec(), &bare, None).is_ok());
+        let missing = request::resolve_query(LAYOUT, "owner").map(|mut q| {
+            q.storage_key = format!("{:0>64}", "5");
+            q
+        });
+        assert!(requests_from_get_proof(vec![missing.unwrap()], &bare, None).is_err());
+    }
+}
@@ -0,0 +1,70 @@
+//! Account IDs and contract state keys
+//!
+//! Contract storage lives in the state trie under the contract's account
+//! ID, so the trie key of a storage key is the `ContractData` column byte,
+//! the account ID, a `,` (which account IDs cannot contain) and the storage
+//! key itself.
+
+use crate::NearError;
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, vec::Vec};
+
+/// Storage key near-sdk writes the contract struct under
+pub const STATE_KEY: &[u8] = b"STATE";
+
+/// Trie column of contract storage
+const CONTRACT_DATA: u8 = 9;
+
+/// Separates the account ID from the storage key in a trie key
+const ACCOUNT_DATA_SEPARATOR: u8 = b',';
+
+/// Check an account ID: 2 to 64 characters, lowercase alphanumeric parts
+/// joined by single `-`, `_` or `.` separators
+pub fn validate_account_id(account_id: &str) -> Result<(), NearError> {
+    let invalid = |reason: &str| NearError::InvalidAccountId(format!("'{}' {}", account_id, reason));
+    if !(2..=64).contains(&account_id.len()) {
+        return Err(invalid("is not 2 to 64 characters long"));
+    }
+    let mut after_separator = true;
+    for c in account_id.chars() {
+        match c {
+            'a'..='z' | '0'..='9' => after_separator = false,
+            '-' | '_' | '.' if !after_separator => after_separator = true,
+            '-' | '_' | '.' => return Err(invalid("has a leading or repeated separator")),
+            _ => return Err(invalid("may only hold lowercase letters, digits, '-', '_' and '.'")),
+        }
+    }
+    if after_separator {
+        return Err(invalid("ends with a separator"));
+    }
+    Ok(())
+}
+
+/// Trie key of `storage_key` in the storage of `account_id`
+pub fn contract_state_key(account_id: &str, storage_key: &[u8]) -> Result<Vec<u8>, NearError> {
+    validate_account_id(account_id)?;
+    let mut key = Vec::with_capacity(1 + account_id.len() + 1 + storage_key.len());
+    key.push(CONTRACT_DATA);
+    key.extend_from_slice(account_id.as_bytes());
+    key.push(ACCOUNT_DATA_SEPARATOR);
+    key.extend_from_slice(storage_key);
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contract_state_key() {
+        assert_eq!(contract_state_key("wrap.near", STATE_KEY).unwrap(), b"\x09wrap.near,STATE");
+
+        for valid in ["aa", "wrap.near", "a-b_c.d0", &"f".repeat(64)] {
+            assert!(validate_account_id(valid).is_ok(), "{}", valid);
+        }
+        for invalid in ["a", "Wrap.near", "wrap..near", ".near", "wrap.", "a,b", &"f".repeat(65)] {
+            assert!(validate_account_id(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
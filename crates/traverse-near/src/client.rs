@@ -0,0 +1,176 @@
+//! State proofs over JSON-RPC
+//!
+//! `view_state` with `include_proof` returns the values under a storage
+//! key prefix with the trie nodes read to find them. The state root those
+//! nodes hang from is taken from the first chunk of the shard produced
+//! after the block the state was read at, found by scanning the following
+//! blocks for a chunk whose `prev_state_root` is the hash of one of the
+//! nodes.
+//!
+//! Nodes refuse `view_state` for contracts holding more state than their
+//! `trie_viewer_state_size_limit` (50 kB by default), so proofs of larger
+//! contracts need a node configured with a higher limit.
+
+use crate::proof::{ChunkAnchor, NearStateProof};
+use crate::trie::node_hash;
+use crate::NearError;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Chain-specific configuration for proof fetching
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NearChainConfig {
+    /// Finality of the block state is read at when no height is given
+    pub finality: String,
+    /// How many blocks after the state's to search for its chunk
+    pub anchor_search_depth: u64,
+}
+
+impl Default for NearChainConfig {
+    fn default() -> Self {
+        Self { finality: "final".to_string(), anchor_search_depth: 10 }
+    }
+}
+
+/// Contract state proof fetcher for a NEAR RPC node
+#[derive(Debug, Clone)]
+pub struct NearProofFetcher {
+    pub rpc_url: String,
+    pub config: NearChainConfig,
+}
+
+impl NearProofFetcher {
+    pub fn new(rpc_url: String, config: NearChainConfig) -> Self {
+        Self { rpc_url, config }
+    }
+
+    pub fn with_defaults(rpc_url: String) -> Self {
+        Self::new(rpc_url, NearChainConfig::default())
+    }
+
+    /// Fetch a storage value of a contract, at `block_height` or the configured finality, with its proof
+    pub async fn fetch_proof(
+        &self,
+        account_id: &str,
+        storage_key: &[u8],
+        block_height: Option<u64>,
+    ) -> Result<NearStateProof, NearError> {
+        let base64 = base64::engine::general_purpose::STANDARD;
+        let mut params = json!({
+            "request_type": "view_state",
+            "account_id": account_id,
+            "prefix_base64": base64.encode(storage_key),
+            "include_proof": true,
+        });
+        match block_height {
+            Some(height) => params["block_id"] = json!(height),
+            None => params["finality"] = json!(self.config.finality),
+        }
+        let state = self.call("query", params).await?;
+
+        let mut value = None;
+        for entry in state["values"].as_array().into_iter().flatten() {
+            if parse_base64(&entry["key"])? == storage_key {
+                value = Some(parse_base64(&entry["value"])?);
+            }
+        }
+        // Older nodes attach the nodes to each value rather than to the result
+        let mut nodes = Vec::new();
+        let lists = state["values"].as_array().into_iter().flatten().map(|entry| &entry["proof"]);
+        for node in core::iter::once(&state["proof"]).chain(lists).filter_map(Value::as_array).flatten() {
+            let node = parse_base64(node)?;
+            if !nodes.contains(&node) {
+                nodes.push(node);
+            }
+        }
+        let block_height = state["block_height"]
+            .as_u64()
+            .ok_or_else(|| NearError::Rpc("The state has no block height".into()))?;
+        let block_hash = parse_base58_hash(&state["block_hash"])?;
+
+        let (state_root, anchor) = self.find_anchor(block_height, &nodes).await?;
+        let proof = NearStateProof {
+            account_id: account_id.to_string(),
+            storage_key: storage_key.to_vec(),
+            value,
+            nodes,
+            block_height,
+            block_hash,
+            state_root,
+            anchor,
+        };
+        proof.verify()?;
+        Ok(proof)
+    }
+
+    /// The first chunk after `block_height` whose state root is the hash of one of `nodes`
+    async fn find_anchor(&self, block_height: u64, nodes: &[Vec<u8>]) -> Result<([u8; 32], ChunkAnchor), NearError> {
+        let hashes: Vec<_> = nodes.iter().map(|node| node_hash(node)).collect();
+        for height in block_height + 1..=block_height + self.config.anchor_search_depth {
+            let block = match self.call("block", json!({ "block_id": height })).await {
+                Ok(block) => block,
+                // Heights without a block are skipped
+                Err(NearError::Rpc(message)) if message.contains("UNKNOWN_BLOCK") => continue,
+                Err(err) => return Err(err),
+            };
+            for chunk in block["chunks"].as_array().into_iter().flatten() {
+                if chunk["height_included"].as_u64() != Some(height) {
+                    continue;
+                }
+                let state_root = parse_base58_hash(&chunk["prev_state_root"])?;
+                if hashes.contains(&state_root) {
+                    let anchor = ChunkAnchor {
+                        shard_id: chunk["shard_id"].as_u64().unwrap_or_default(),
+                        chunk_hash: parse_base58_hash(&chunk["chunk_hash"])?,
+                        block_height: height,
+                        block_hash: parse_base58_hash(&block["header"]["hash"])?,
+                    };
+                    return Ok((state_root, anchor));
+                }
+            }
+        }
+        Err(NearError::Rpc(format!(
+            "No chunk in the {} blocks after {} has the state root of the proof",
+            self.config.anchor_search_depth, block_height
+        )))
+    }
+
+    /// Chain ID the node reports, e.g. `mainnet`
+    pub async fn chain_id(&self) -> Result<String, NearError> {
+        let status = self.call("status", json!([])).await?;
+        status["chain_id"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| NearError::Rpc("Status has no chain_id".into()))
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value, NearError> {
+        let request = json!({ "jsonrpc": "2.0", "id": "traverse", "method": method, "params": params });
+        let response: Value = reqwest::Client::new().post(&self.rpc_url).json(&request).send().await?.json().await?;
+        if let Some(error) = response.get("error") {
+            return Err(NearError::Rpc(format!("{}: {}", method, error)));
+        }
+        // Query errors come back as results
+        if let Some(error) = response["result"].get("error") {
+            return Err(NearError::Rpc(format!("{}: {}", method, error)));
+        }
+        Ok(response["result"].clone())
+    }
+}
+
+fn parse_base58_hash(value: &Value) -> Result<[u8; 32], NearError> {
+    let encoded = value.as_str().ok_or_else(|| NearError::Rpc(format!("Expected a base58 hash, found {}", value)))?;
+    bs58::decode(encoded)
+        .into_vec()
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| NearError::Rpc(format!("'{}' is not a 32-byte base58 hash", encoded)))
+}
+
+fn parse_base64(value: &Value) -> Result<Vec<u8>, NearError> {
+    let encoded = value.as_str().ok_or_else(|| NearError::Rpc(format!("Expected base64, found {}", value)))?;
+    base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| NearError::Rpc(format!("Invalid base64: {}", e)))
+}
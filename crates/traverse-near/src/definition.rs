@@ -0,0 +1,314 @@
+//! Storage definitions for near-sdk contracts
+//!
+//! Wasm contracts carry no description of their storage, so layouts come
+//! from a definition that mirrors the contract's source: the fields of the
+//! contract struct, Borsh-encoded under `STATE`, and the collections it
+//! owns with their prefixes. A prefix is written as text (`"a"`) or as hex
+//! (`"0x00"`, the Borsh encoding of the first variant of a `StorageKey`
+//! enum). Iterable collections keep their entries in an inner `LookupMap`,
+//! which can be declared with its own prefix.
+//!
+//! A contract ABI, as `cargo near abi` writes it, names the contract but
+//! not its storage, so it only yields the `STATE` record.
+
+use crate::account::validate_account_id;
+use crate::NearError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// A contract's storage, as written by hand or derived from its ABI
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NearStorageDefinition {
+    /// Account the contract is deployed to; may be left out of definitions shared by several deployments
+    #[serde(default)]
+    pub contract: Option<String>,
+    /// Name of the contract struct
+    #[serde(default = "default_state_type")]
+    pub state_type: String,
+    /// Fields of the contract struct, in declaration order
+    #[serde(default)]
+    pub state: Vec<FieldDefinition>,
+    #[serde(default)]
+    pub collections: Vec<CollectionDefinition>,
+}
+
+/// A field of the contract struct
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldDefinition {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: String,
+}
+
+/// A near-sdk collection and the prefix its entries are stored under
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollectionDefinition {
+    pub name: String,
+    pub kind: CollectionKind,
+    pub prefix: String,
+    /// Key type of a map or set
+    #[serde(default)]
+    pub key: Option<String>,
+    /// Value type of a map, vector or lazy value
+    #[serde(default)]
+    pub value: Option<String>,
+}
+
+/// How a collection lays its entries out under its prefix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CollectionKind {
+    /// `prefix ‖ borsh(key)` holds the value
+    LookupMap,
+    /// `prefix ‖ borsh(key)` holds an empty value when the key is present
+    LookupSet,
+    /// `prefix ‖ u32 index` holds the element (`near_sdk::store::Vector`)
+    Vector,
+    /// `prefix` holds the value
+    LazyOption,
+}
+
+/// A Borsh type as far as keys and offsets need it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BorshType {
+    Bool,
+    Unsigned(usize),
+    Signed(usize),
+    /// `String` or `AccountId`: a u32 length, then UTF-8
+    String,
+    /// `Vec<u8>`: a u32 length, then the bytes
+    Bytes,
+    /// `[u8; N]`
+    FixedBytes(usize),
+    /// Any other type, whose size is not known
+    Other(String),
+}
+
+fn default_state_type() -> String {
+    "Contract".to_string()
+}
+
+impl NearStorageDefinition {
+    /// Parse a storage definition, or derive one from a contract ABI
+    pub fn from_json(content: &str) -> Result<Self, NearError> {
+        let value: Value = serde_json::from_str(content).map_err(NearError::Json)?;
+        let definition = if value.get("schema_version").is_some() && value.get("body").is_some() {
+            Self::from_abi(&value)?
+        } else {
+            serde_json::from_value(value).map_err(NearError::Json)?
+        };
+        definition.validate()?;
+        Ok(definition)
+    }
+
+    /// The `STATE` record of the contract an ABI describes
+    pub fn from_abi(abi: &Value) -> Result<Self, NearError> {
+        let name = abi["metadata"]["name"]
+            .as_str()
+            .ok_or_else(|| NearError::InvalidDefinition("The ABI has no metadata.name".into()))?;
+        // The crate name, e.g. `fungible-token`, as a type name
+        let state_type = name
+            .split(['-', '_'])
+            .map(|part| {
+                let mut chars = part.chars();
+                chars.next().map(|c| c.to_ascii_uppercase().to_string() + chars.as_str()).unwrap_or_default()
+            })
+            .collect();
+        Ok(Self { contract: None, state_type, state: Vec::new(), collections: Vec::new() })
+    }
+
+    fn validate(&self) -> Result<(), NearError> {
+        if let Some(contract) = &self.contract {
+            validate_account_id(contract)?;
+        }
+        let mut names: Vec<&str> = self.collections.iter().map(|c| c.name.as_str()).collect();
+        names.sort_unstable();
+        if let Some(pair) = names.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(NearError::InvalidDefinition(format!("Collection {} is declared twice", pair[0])));
+        }
+        for collection in &self.collections {
+            if collection.name == "STATE" || collection.name.contains(['.', '[', ']']) {
+                return Err(NearError::InvalidDefinition(format!("'{}' cannot name a collection", collection.name)));
+            }
+            if collection.prefix_bytes()?.is_empty() {
+                return Err(NearError::InvalidDefinition(format!("Collection {} has an empty prefix", collection.name)));
+            }
+            let keyed = matches!(collection.kind, CollectionKind::LookupMap | CollectionKind::LookupSet);
+            if keyed != collection.key.is_some() {
+                return Err(NearError::InvalidDefinition(format!(
+                    "Collection {} is a {:?}, which {} a key type",
+                    collection.name,
+                    collection.kind,
+                    if keyed { "needs" } else { "has no" }
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CollectionDefinition {
+    /// The prefix as bytes: `0x` hex, or the text itself
+    pub fn prefix_bytes(&self) -> Result<Vec<u8>, NearError> {
+        match self.prefix.strip_prefix("0x") {
+            Some(digits) => hex::decode(digits)
+                .map_err(|e| NearError::InvalidDefinition(format!("Prefix of {}: {}", self.name, e))),
+            None => Ok(self.prefix.as_bytes().to_vec()),
+        }
+    }
+
+    /// Type name of the collection, e.g. `LookupMap<AccountId, u128>`
+    pub fn type_name(&self) -> String {
+        let value = self.value.as_deref().unwrap_or("()");
+        match (self.kind, &self.key) {
+            (CollectionKind::LookupMap, Some(key)) => format!("LookupMap<{}, {}>", key, value),
+            (CollectionKind::LookupSet, Some(key)) => format!("LookupSet<{}>", key),
+            (CollectionKind::Vector, _) => format!("Vector<{}>", value),
+            _ => format!("LazyOption<{}>", value),
+        }
+    }
+}
+
+impl BorshType {
+    /// Parse a Rust type name, resolving the near-sdk aliases with known encodings
+    pub fn parse(type_name: &str) -> Self {
+        let type_name = type_name.trim();
+        match type_name {
+            "bool" => Self::Bool,
+            "u8" => Self::Unsigned(1),
+            "u16" => Self::Unsigned(2),
+            "u32" => Self::Unsigned(4),
+            "u64" | "U64" | "Timestamp" | "BlockHeight" | "Gas" | "EpochHeight" => Self::Unsigned(8),
+            "u128" | "U128" | "Balance" | "NearToken" => Self::Unsigned(16),
+            "i8" => Self::Signed(1),
+            "i16" => Self::Signed(2),
+            "i32" => Self::Signed(4),
+            "i64" | "I64" => Self::Signed(8),
+            "i128" | "I128" => Self::Signed(16),
+            "String" | "AccountId" => Self::String,
+            "Vec<u8>" => Self::Bytes,
+            "CryptoHash" => Self::FixedBytes(32),
+            _ => type_name
+                .strip_prefix("[u8;")
+                .and_then(|n| n.strip_suffix(']'))
+                .and_then(|n| n.trim().parse().ok())
+                .map(Self::FixedBytes)
+                .unwrap_or_else(|| Self::Other(type_name.to_string())),
+        }
+    }
+
+    /// Encoded size, if every value has the same
+    pub fn fixed_size(&self) -> Option<usize> {
+        match self {
+            Self::Bool => Some(1),
+            Self::Unsigned(size) | Self::Signed(size) | Self::FixedBytes(size) => Some(*size),
+            Self::String | Self::Bytes | Self::Other(_) => None,
+        }
+    }
+
+    /// Borsh encoding of a key written in a query
+    ///
+    /// Numbers are decimal, byte strings `0x` hex, and strings are taken as
+    /// written; account IDs are not told apart from strings, so they are not
+    /// validated.
+    pub fn encode_key(&self, key: &str) -> Result<Vec<u8>, NearError> {
+        let invalid = || NearError::InvalidQuery(format!("'{}' is not a {:?} key", key, self));
+        let hex_bytes = || hex::decode(key.strip_prefix("0x").ok_or_else(invalid)?).map_err(|_| invalid());
+        match self {
+            Self::Bool => match key {
+                "true" => Ok([1].to_vec()),
+                "false" => Ok([0].to_vec()),
+                _ => Err(invalid()),
+            },
+            Self::Unsigned(size) => {
+                let value: u128 = key.parse().map_err(|_| invalid())?;
+                let bytes = value.to_le_bytes();
+                if bytes[*size..].iter().any(|b| *b != 0) {
+                    return Err(invalid());
+                }
+                Ok(bytes[..*size].to_vec())
+            }
+            Self::Signed(size) => {
+                let value: i128 = key.parse().map_err(|_| invalid())?;
+                let bits = *size as u32 * 8;
+                if bits < 128 && (value >= 1 << (bits - 1) || value < -(1 << (bits - 1))) {
+                    return Err(invalid());
+                }
+                Ok(value.to_le_bytes()[..*size].to_vec())
+            }
+            Self::String => Ok(length_prefixed(key.trim_matches('"').as_bytes())),
+            Self::Bytes => Ok(length_prefixed(&hex_bytes()?)),
+            Self::FixedBytes(size) => {
+                let bytes = hex_bytes()?;
+                if bytes.len() != *size {
+                    return Err(invalid());
+                }
+                Ok(bytes)
+            }
+            Self::Other(name) => Err(NearError::InvalidQuery(format!("Keys of type {} cannot be encoded", name))),
+        }
+    }
+}
+
+/// Borsh `Vec<u8>` or `String`: a u32 length, then the bytes
+fn length_prefixed(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(4 + bytes.len());
+    out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    out.extend_from_slice(bytes);
+    out
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) const TOKEN_DEFINITION: &str = r#"{
+        "contract": "token.near",
+        "state_type": "FungibleToken",
+        "state": [
+            { "name": "total_supply", "type": "u128" },
+            { "name": "owner_id", "type": "AccountId" },
+            { "name": "paused", "type": "bool" }
+        ],
+        "collections": [
+            { "name": "accounts", "kind": "LookupMap", "prefix": "a", "key": "AccountId", "value": "Balance" },
+            { "name": "holders", "kind": "Vector", "prefix": "0x01", "value": "AccountId" },
+            { "name": "metadata", "kind": "LazyOption", "prefix": "m", "value": "FungibleTokenMetadata" }
+        ]
+    }"#;
+
+    #[test]
+    fn test_definitions_and_keys() {
+        let definition = NearStorageDefinition::from_json(TOKEN_DEFINITION).unwrap();
+        assert_eq!(definition.collections[0].type_name(), "LookupMap<AccountId, Balance>");
+        assert_eq!(definition.collections[1].prefix_bytes().unwrap(), [1]);
+
+        assert_eq!(BorshType::parse("AccountId").encode_key("alice.near").unwrap(), b"\x0a\0\0\0alice.near");
+        assert_eq!(BorshType::parse("u16").encode_key("258").unwrap(), [2, 1]);
+        assert!(BorshType::parse("u8").encode_key("256").is_err());
+        assert_eq!(BorshType::parse("i8").encode_key("-1").unwrap(), [0xff]);
+        assert_eq!(BorshType::parse("[u8; 2]").encode_key("0xbeef").unwrap(), [0xbe, 0xef]);
+        assert!(BorshType::parse("[u8; 2]").encode_key("0xbe").is_err());
+        assert_eq!(BorshType::parse("Balance").fixed_size(), Some(16));
+
+        let abi = r#"{ "schema_version": "0.4.0", "metadata": { "name": "fungible-token" }, "body": { "functions": [] } }"#;
+        assert_eq!(NearStorageDefinition::from_json(abi).unwrap().state_type, "FungibleToken");
+
+        let keyless_map = r#"{ "collections": [{ "name": "m", "kind": "LookupMap", "prefix": "m" }] }"#;
+        assert!(NearStorageDefinition::from_json(keyless_map).is_err());
+        assert!(NearStorageDefinition::from_json(r#"{ "contract": "Token" }"#).is_err());
+
+        // Collection names become query paths, and prefixes must tell collections from `STATE`
+        for (name, prefix) in [("STATE", "s"), ("a.b", "a"), ("accounts", "0x"), ("accounts", "0xz1")] {
+            let vector = format!(r#"{{ "name": "{}", "kind": "Vector", "prefix": "{}" }}"#, name, prefix);
+            let definition = format!(r#"{{ "collections": [{}] }}"#, vector);
+            assert!(NearStorageDefinition::from_json(&definition).is_err(), "{} {}", name, prefix);
+        }
+    }
+}
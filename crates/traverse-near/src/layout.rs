@@ -0,0 +1,153 @@
+//! Layout compilation from storage definitions
+//!
+//! The contract struct becomes an entry labelled `STATE` whose slot is the
+//! `STATE` storage key, and each of its fields an entry `STATE.field` whose
+//! "slot" is its byte offset in the Borsh-encoded struct. Offsets are only
+//! static up to the first variable-size field, so fields after it are left
+//! out. Each collection becomes an entry whose slot is its prefix.
+
+use crate::account::STATE_KEY;
+use crate::definition::{BorshType, CollectionKind, NearStorageDefinition};
+use crate::NearError;
+use traverse_core::{LayoutInfo, StorageEntry, TypeInfo, ZeroSemantics};
+
+#[cfg(feature = "std")]
+use std::path::Path;
+
+#[cfg(feature = "std")]
+use traverse_core::{LayoutCompiler, TraverseError};
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::ToString, vec::Vec};
+
+/// Encoding recorded for contract state and its entries
+pub const STATE_ENCODING: &str = "borsh";
+
+/// Layout compiler for near-sdk contracts
+///
+/// # Usage
+///
+/// ```rust,ignore
+/// use traverse_near::NearLayoutCompiler;
+/// use traverse_core::LayoutCompiler;
+///
+/// let layout = NearLayoutCompiler.compile_layout(Path::new("storage.json"))?;
+/// assert!(layout.storage.iter().any(|e| e.label == "accounts"));
+/// ```
+pub struct NearLayoutCompiler;
+
+impl NearLayoutCompiler {
+    /// Compile a parsed storage definition
+    ///
+    /// The layout is named after the contract's account, or after its
+    /// struct when the definition names no account.
+    pub fn compile_definition(definition: &NearStorageDefinition) -> Result<LayoutInfo, NearError> {
+        let mut storage = Vec::new();
+        let mut types: Vec<TypeInfo> = Vec::new();
+        let mut add_type = |label: &str, size: Option<usize>, key: Option<&str>, value: Option<&str>| {
+            if !types.iter().any(|t| t.label == label) {
+                types.push(TypeInfo {
+                    label: label.to_string(),
+                    // 0 for variable-size types
+                    number_of_bytes: size.unwrap_or(0).to_string(),
+                    encoding: STATE_ENCODING.to_string(),
+                    base: None,
+                    key: key.map(str::to_string),
+                    value: value.map(str::to_string),
+                });
+            }
+        };
+
+        let sizes: Vec<_> = definition.state.iter().map(|f| BorshType::parse(&f.field_type).fixed_size()).collect();
+        storage.push(StorageEntry {
+            label: "STATE".to_string(),
+            slot: format!("0x{}", hex::encode(STATE_KEY)),
+            offset: 0,
+            type_name: definition.state_type.clone(),
+            // The contract struct is absent until the contract is initialized
+            zero_semantics: ZeroSemantics::NeverWritten,
+        });
+        let state_size = if definition.state.is_empty() { None } else { sizes.iter().copied().sum() };
+        add_type(&definition.state_type, state_size, None, None);
+
+        let mut offset = 0usize;
+        for (field, size) in definition.state.iter().zip(&sizes) {
+            storage.push(StorageEntry {
+                label: format!("STATE.{}", field.name),
+                slot: offset.to_string(),
+                offset: 0,
+                type_name: field.field_type.clone(),
+                // Fields of an initialized contract hold real values, zero included
+                zero_semantics: ZeroSemantics::ValidZero,
+            });
+            add_type(&field.field_type, *size, None, None);
+            match size {
+                Some(size) => offset += size,
+                None => break,
+            }
+        }
+
+        for collection in &definition.collections {
+            let key = match collection.kind {
+                CollectionKind::Vector => Some("u32"),
+                _ => collection.key.as_deref(),
+            };
+            let type_name = collection.type_name();
+            storage.push(StorageEntry {
+                label: collection.name.clone(),
+                slot: format!("0x{}", hex::encode(collection.prefix_bytes()?)),
+                offset: 0,
+                type_name: type_name.clone(),
+                // A missing entry was never inserted, or was removed
+                zero_semantics: ZeroSemantics::NeverWritten,
+            });
+            add_type(&type_name, None, key, collection.value.as_deref());
+            for element in key.iter().chain(&collection.value.as_deref()) {
+                add_type(element, BorshType::parse(element).fixed_size(), None, None);
+            }
+        }
+
+        let contract_name = definition.contract.clone().unwrap_or_else(|| definition.state_type.clone());
+        Ok(LayoutInfo { contract_name, storage, types, code_variables: Vec::new() })
+    }
+}
+
+#[cfg(feature = "std")]
+impl LayoutCompiler for NearLayoutCompiler {
+    /// Compile a layout from a storage definition or a contract ABI
+    fn compile_layout(&self, definition_path: &Path) -> Result<LayoutInfo, TraverseError> {
+        let content = std::fs::read_to_string(definition_path)?;
+        let definition = NearStorageDefinition::from_json(&content)?;
+        Ok(Self::compile_definition(&definition)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definition::tests::TOKEN_DEFINITION;
+
+    #[test]
+    fn test_compile_definition() {
+        let definition = NearStorageDefinition::from_json(TOKEN_DEFINITION).unwrap();
+        let layout = NearLayoutCompiler::compile_definition(&definition).unwrap();
+        assert_eq!(layout.contract_name, "token.near");
+
+        let entries: Vec<_> = layout.storage.iter().map(|e| (e.label.as_str(), e.slot.as_str())).collect();
+        assert_eq!(entries, [
+            ("STATE", "0x5354415445"),
+            ("STATE.total_supply", "0"),
+            ("STATE.owner_id", "16"),
+            ("accounts", "0x61"),
+            ("holders", "0x01"),
+            ("metadata", "0x6d"),
+        ]);
+
+        let type_info = |label: &str| layout.types.iter().find(|t| t.label == label).unwrap();
+        assert_eq!(type_info("FungibleToken").number_of_bytes, "0");
+        assert_eq!(type_info("Balance").number_of_bytes, "16");
+        let accounts = type_info("LookupMap<AccountId, Balance>");
+        assert_eq!((accounts.key.as_deref(), accounts.value.as_deref()), (Some("AccountId"), Some("Balance")));
+        assert_eq!(type_info("Vector<AccountId>").key.as_deref(), Some("u32"));
+    }
+}
@@ -0,0 +1,102 @@
+//! Contract state layouts and trie proofs for NEAR
+//!
+//! A NEAR contract's storage is a key-value map kept in its shard's state
+//! trie under `ContractData` keys: a column byte, the contract's account ID,
+//! a `,` separator and the contract's own storage key. near-sdk writes the
+//! contract struct under `STATE` and each collection entry under the
+//! collection's prefix followed by the Borsh-encoded key. Each chunk header
+//! records the shard's state root, so a value is proven by the trie nodes on
+//! the path from that root to its key. This crate maps that model onto the
+//! traverse core types.
+//!
+//! # Features
+//!
+//! - **Layout Compilation**: Turn a storage definition, or a contract's ABI, into a layout
+//! - **Query Resolution**: Resolve `STATE.field` and `collection[key]` queries to state keys
+//! - **Proof Verification**: Check trie proofs against a chunk's state root
+//! - **Proof Fetching**: Read state and its proof over JSON-RPC (`client`)
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! use traverse_near::{NearKeyResolver, NearLayoutCompiler};
+//! use traverse_core::{KeyResolver, LayoutCompiler};
+//!
+//! let layout = NearLayoutCompiler.compile_layout(Path::new("storage.json"))?;
+//! let path = NearKeyResolver.resolve(&layout, "accounts[alice.near]")?;
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(not(feature = "std"))]
+use alloc::string::{String, ToString};
+
+pub mod account;
+pub mod definition;
+pub mod layout;
+pub mod proof;
+pub mod resolver;
+pub mod trie;
+
+#[cfg(feature = "client")]
+pub mod client;
+
+pub use account::{contract_state_key, validate_account_id, STATE_KEY};
+pub use definition::{BorshType, CollectionDefinition, CollectionKind, FieldDefinition, NearStorageDefinition};
+pub use layout::NearLayoutCompiler;
+pub use proof::{ChunkAnchor, NearStateProof};
+pub use resolver::{NearKeyResolver, StateKey};
+pub use trie::{verify_trie_proof, TrieNode, ValueRef};
+
+#[cfg(feature = "client")]
+pub use client::{NearChainConfig, NearProofFetcher};
+
+/// Error types specific to NEAR
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+#[derive(Debug)]
+pub enum NearError {
+    #[cfg_attr(feature = "std", error("Invalid storage definition: {0}"))]
+    InvalidDefinition(String),
+
+    #[cfg_attr(feature = "std", error("Invalid query: {0}"))]
+    InvalidQuery(String),
+
+    #[cfg_attr(feature = "std", error("Invalid account ID: {0}"))]
+    InvalidAccountId(String),
+
+    #[cfg_attr(feature = "std", error("Proof verification failed: {0}"))]
+    ProofVerification(String),
+
+    #[cfg_attr(feature = "std", error("RPC error: {0}"))]
+    Rpc(String),
+
+    #[cfg(feature = "client")]
+    #[cfg_attr(feature = "std", error("Network error: {0}"))]
+    Network(#[from] reqwest::Error),
+
+    #[cfg_attr(feature = "std", error("JSON error: {0}"))]
+    Json(#[cfg_attr(feature = "std", from)] serde_json::Error),
+
+    #[cfg_attr(feature = "std", error("Traverse core error: {0}"))]
+    TraverseCore(#[cfg_attr(feature = "std", from)] traverse_core::TraverseError),
+}
+
+impl From<NearError> for traverse_core::TraverseError {
+    fn from(err: NearError) -> Self {
+        use traverse_core::TraverseError;
+
+        match err {
+            NearError::InvalidDefinition(msg) => TraverseError::LayoutCompilation(msg),
+            NearError::InvalidQuery(msg) | NearError::InvalidAccountId(msg) => TraverseError::InvalidInput(msg),
+            NearError::ProofVerification(msg) => TraverseError::Validation(msg),
+            NearError::Rpc(msg) => TraverseError::ExternalService(msg),
+            #[cfg(feature = "client")]
+            NearError::Network(err) => TraverseError::ExternalService(err.to_string()),
+            NearError::Json(err) => TraverseError::Serialization(err.to_string()),
+            NearError::TraverseCore(err) => err,
+        }
+    }
+}
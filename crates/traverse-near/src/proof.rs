@@ -0,0 +1,177 @@
+//! Contract state proofs anchored to chunk state roots
+//!
+//! A chunk header's `prev_state_root` is the shard's state root before the
+//! chunk was applied, which is the state after the last block that applied
+//! a chunk of the shard. So state read at block `h` is anchored to the
+//! first chunk of its shard produced after `h`, whose header is included in
+//! a later block.
+
+use crate::account::contract_state_key;
+use crate::trie::{verify_trie_proof, ValueRef};
+use crate::NearError;
+use serde_json::{json, Value};
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// The chunk whose header records the state root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkAnchor {
+    pub shard_id: u64,
+    pub chunk_hash: [u8; 32],
+    /// Height of the block that includes the chunk
+    pub block_height: u64,
+    pub block_hash: [u8; 32],
+}
+
+/// A storage value of a contract, or its absence, with the trie nodes proving it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NearStateProof {
+    pub account_id: String,
+    /// Storage key within the contract's storage
+    pub storage_key: Vec<u8>,
+    /// Stored value; `None` when nothing is stored under the key
+    pub value: Option<Vec<u8>>,
+    /// Trie nodes from the state root to the key, in any order
+    pub nodes: Vec<Vec<u8>>,
+    /// Block the state was read at
+    pub block_height: u64,
+    pub block_hash: [u8; 32],
+    pub state_root: [u8; 32],
+    pub anchor: ChunkAnchor,
+}
+
+impl NearStateProof {
+    /// Check the value, or its absence, against the state root
+    pub fn verify(&self) -> Result<(), NearError> {
+        let key = contract_state_key(&self.account_id, &self.storage_key)?;
+        let proven = verify_trie_proof(&self.state_root, &key, &self.nodes)?;
+        match (proven, &self.value) {
+            (Some(proven), Some(value)) if proven == ValueRef::of(value) => Ok(()),
+            (Some(_), Some(_)) => Err(NearError::ProofVerification("the value does not match the trie".into())),
+            (None, None) => Ok(()),
+            (Some(_), None) => {
+                Err(NearError::ProofVerification("the trie holds a value the proof says is absent".into()))
+            }
+            (None, Some(_)) => Err(NearError::ProofVerification("the trie holds no value under the key".into())),
+        }
+    }
+
+    /// JSON form with bytes and hashes in `0x` hex
+    pub fn to_json(&self) -> Value {
+        json!({
+            "account_id": self.account_id,
+            "storage_key": format!("0x{}", hex::encode(&self.storage_key)),
+            "value": self.value.as_deref().map(|value| format!("0x{}", hex::encode(value))),
+            "nodes": self.nodes.iter().map(|node| format!("0x{}", hex::encode(node))).collect::<Vec<_>>(),
+            "block_height": self.block_height,
+            "block_hash": format!("0x{}", hex::encode(self.block_hash)),
+            "state_root": format!("0x{}", hex::encode(self.state_root)),
+            "anchor": {
+                "shard_id": self.anchor.shard_id,
+                "chunk_hash": format!("0x{}", hex::encode(self.anchor.chunk_hash)),
+                "block_height": self.anchor.block_height,
+                "block_hash": format!("0x{}", hex::encode(self.anchor.block_hash)),
+            },
+        })
+    }
+
+    /// Parse the JSON written by [`Self::to_json`]
+    pub fn from_json(value: &Value) -> Result<Self, NearError> {
+        let field = |value: &'_ Value, name: &str| -> Result<Value, NearError> {
+            value.get(name).cloned().ok_or_else(|| NearError::Rpc(format!("Proof is missing '{}'", name)))
+        };
+        let number = |value: &Value, name: &str| {
+            field(value, name)?
+                .as_u64()
+                .ok_or_else(|| NearError::Rpc(format!("Proof field '{}' is not a number", name)))
+        };
+        let anchor = field(value, "anchor")?;
+        let nodes = field(value, "nodes")?;
+        Ok(Self {
+            account_id: field(value, "account_id")?.as_str().unwrap_or_default().to_string(),
+            storage_key: parse_bytes(&field(value, "storage_key")?)?,
+            value: match field(value, "value")? {
+                Value::Null => None,
+                stored => Some(parse_bytes(&stored)?),
+            },
+            nodes: nodes
+                .as_array()
+                .ok_or_else(|| NearError::Rpc("Proof field 'nodes' is not a list".into()))?
+                .iter()
+                .map(parse_bytes)
+                .collect::<Result<_, _>>()?,
+            block_height: number(value, "block_height")?,
+            block_hash: parse_hash(&field(value, "block_hash")?)?,
+            state_root: parse_hash(&field(value, "state_root")?)?,
+            anchor: ChunkAnchor {
+                shard_id: number(&anchor, "shard_id")?,
+                chunk_hash: parse_hash(&field(&anchor, "chunk_hash")?)?,
+                block_height: number(&anchor, "block_height")?,
+                block_hash: parse_hash(&field(&anchor, "block_hash")?)?,
+            },
+        })
+    }
+}
+
+fn parse_bytes(value: &Value) -> Result<Vec<u8>, NearError> {
+    let hex_str = value.as_str().ok_or_else(|| NearError::Rpc(format!("Expected hex, found {}", value)))?;
+    hex::decode(hex_str.trim_start_matches("0x")).map_err(|e| NearError::Rpc(format!("Invalid hex '{}': {}", hex_str, e)))
+}
+
+fn parse_hash(value: &Value) -> Result<[u8; 32], NearError> {
+    parse_bytes(value)?
+        .try_into()
+        .map_err(|_| NearError::Rpc(format!("Expected a 32-byte hash, found {}", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::STATE_KEY;
+    use crate::trie::tests::small_trie;
+
+    #[test]
+    fn test_proof_verification_and_json() {
+        // The `STATE` of token.near, in a trie that also holds one balance
+        let state = [&1_000u128.to_le_bytes()[..], b"\x0a\0\0\0owner.near", &[0]].concat();
+        let balance_key = contract_state_key("token.near", b"a\x0a\0\0\0alice.near").unwrap();
+        let state_key = contract_state_key("token.near", STATE_KEY).unwrap();
+        let (state_root, nodes) = small_trie(&[(&state_key, &state), (&balance_key, &7u128.to_le_bytes())]);
+        let proof = NearStateProof {
+            account_id: "token.near".to_string(),
+            storage_key: STATE_KEY.to_vec(),
+            value: Some(state),
+            nodes,
+            block_height: 100,
+            block_hash: [1; 32],
+            state_root,
+            anchor: ChunkAnchor { shard_id: 2, chunk_hash: [3; 32], block_height: 101, block_hash: [4; 32] },
+        };
+        proof.verify().unwrap();
+        assert_eq!(NearStateProof::from_json(&proof.to_json()).unwrap(), proof);
+
+        let mut other_value = proof.clone();
+        other_value.value.as_mut().unwrap()[0] ^= 1;
+        assert!(other_value.verify().is_err());
+
+        let mut absent = proof.clone();
+        absent.value = None;
+        assert!(absent.verify().is_err());
+
+        // An absent key is proven by the same nodes
+        let mut missing = proof.clone();
+        missing.storage_key = b"b".to_vec();
+        missing.value = None;
+        missing.verify().unwrap();
+        assert!(NearStateProof::from_json(&missing.to_json()).unwrap().value.is_none());
+
+        let mut other_account = proof;
+        other_account.account_id = "token2.near".to_string();
+        assert!(other_account.verify().is_err());
+    }
+}
@@ -0,0 +1,218 @@
+//! State key resolution
+//!
+//! Queries name the contract struct, one of its fields, or a collection
+//! entry: `STATE`, `STATE.owner_id`, `accounts[alice.near]`, `holders[3]`,
+//! or `metadata` for a lazy value. The storage key is the collection's
+//! prefix followed by the Borsh-encoded key, and the resolved key is its
+//! trie key under the layout's contract account.
+
+use crate::account::{contract_state_key, validate_account_id, STATE_KEY};
+use crate::definition::BorshType;
+use crate::NearError;
+use traverse_core::{Key, KeyResolver, LayoutInfo, StaticKeyPath, StorageEntry, TraverseError, ZeroSemantics};
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Key resolver for near-sdk contract storage
+///
+/// # Usage
+///
+/// ```rust,ignore
+/// use traverse_near::NearKeyResolver;
+/// use traverse_core::KeyResolver;
+///
+/// let path = NearKeyResolver.resolve(&layout, "accounts[alice.near]")?;
+/// ```
+pub struct NearKeyResolver;
+
+/// A storage value, or a field of the contract struct, resolved against a layout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateKey {
+    /// Key within the contract's storage
+    pub storage_key: Vec<u8>,
+    /// Type of the value, or of the field
+    pub type_name: String,
+    /// Field name; `None` for the whole value
+    pub field: Option<String>,
+    /// Byte offset of the field in the value
+    pub offset: usize,
+    /// Size of the value or field, if fixed
+    pub size: Option<usize>,
+    pub zero_semantics: ZeroSemantics,
+}
+
+impl StateKey {
+    /// The field's bytes in a stored value
+    pub fn extract<'a>(&self, value: &'a [u8]) -> Option<&'a [u8]> {
+        let size = match self.field {
+            Some(_) => self.size?,
+            None => value.len(),
+        };
+        value.get(self.offset..self.offset.checked_add(size)?)
+    }
+}
+
+impl NearKeyResolver {
+    /// Resolve a query to the storage key, and field, it names
+    pub fn state_key(layout: &LayoutInfo, query: &str) -> Result<StateKey, NearError> {
+        let query = query.trim();
+        if query == "STATE" || query.starts_with("STATE.") {
+            return Self::state_field(layout, query);
+        }
+
+        let (name, key) = match query.split_once('[') {
+            Some((name, rest)) => {
+                let key = rest.strip_suffix(']').filter(|key| !key.is_empty()).ok_or_else(|| {
+                    NearError::InvalidQuery(format!("'{}' is not collection[key]", query))
+                })?;
+                (name.trim(), Some(key.trim()))
+            }
+            None => (query, None),
+        };
+        let entry = layout
+            .storage
+            .iter()
+            .find(|e| e.label == name)
+            .ok_or_else(|| NearError::InvalidQuery(format!("{} declares no collection {}", layout.contract_name, name)))?;
+        let collection = layout.types.iter().find(|t| t.label == entry.type_name);
+        let mut storage_key = parse_prefix(entry)?;
+        let value_type = collection.and_then(|t| t.value.clone()).unwrap_or_else(|| "()".to_string());
+
+        match (key, collection.and_then(|t| t.key.as_deref())) {
+            (Some(key), Some(key_type)) => storage_key.extend(BorshType::parse(key_type).encode_key(key)?),
+            (None, None) => {}
+            (Some(_), None) => return Err(NearError::InvalidQuery(format!("{} is a single value; write {}", name, name))),
+            (None, Some(_)) => return Err(NearError::InvalidQuery(format!("{} needs a key: {}[key]", name, name))),
+        }
+
+        Ok(StateKey {
+            storage_key,
+            size: type_size(layout, &value_type),
+            type_name: value_type,
+            field: None,
+            offset: 0,
+            zero_semantics: entry.zero_semantics,
+        })
+    }
+
+    /// `STATE`, or a field of it at a static offset
+    fn state_field(layout: &LayoutInfo, query: &str) -> Result<StateKey, NearError> {
+        let entry = layout.storage.iter().find(|e| e.label == query).ok_or_else(|| {
+            NearError::InvalidQuery(format!(
+                "{} has no {}; fields after a variable-size field cannot be resolved",
+                layout.contract_name, query
+            ))
+        })?;
+        let field = query.strip_prefix("STATE.");
+        let offset = match field {
+            Some(_) => entry
+                .slot
+                .parse()
+                .map_err(|_| NearError::InvalidQuery(format!("Layout entry {} has no byte offset", entry.label)))?,
+            None => 0,
+        };
+        Ok(StateKey {
+            storage_key: STATE_KEY.to_vec(),
+            type_name: entry.type_name.clone(),
+            field: field.map(str::to_string),
+            offset,
+            size: type_size(layout, &entry.type_name),
+            zero_semantics: entry.zero_semantics,
+        })
+    }
+}
+
+impl KeyResolver for NearKeyResolver {
+    /// Resolve a query to its trie key under the layout's contract account
+    fn resolve(&self, layout: &LayoutInfo, query: &str) -> Result<StaticKeyPath, TraverseError> {
+        let state_key = Self::state_key(layout, query)?;
+        validate_account_id(&layout.contract_name).map_err(|_| {
+            TraverseError::InvalidInput(format!(
+                "Layout {} names no account; set the definition's contract to resolve trie keys",
+                layout.contract_name
+            ))
+        })?;
+        Ok(StaticKeyPath {
+            name: "near_state",
+            key: Key::Variable(contract_state_key(&layout.contract_name, &state_key.storage_key)?),
+            offset: state_key.field.as_ref().and_then(|_| u8::try_from(state_key.offset).ok()),
+            field_size: state_key.size.and_then(|size| u8::try_from(size).ok()),
+            layout_commitment: layout.commitment(),
+            zero_semantics: state_key.zero_semantics,
+        })
+    }
+
+    /// Resolve the contract struct, its fields and the lazy values, which need no key
+    fn resolve_all(&self, layout: &LayoutInfo) -> Result<Vec<StaticKeyPath>, TraverseError> {
+        let unkeyed = |entry: &&StorageEntry| {
+            entry.label == "STATE"
+                || entry.label.starts_with("STATE.")
+                || layout.types.iter().any(|t| t.label == entry.type_name && t.key.is_none() && t.value.is_some())
+        };
+        layout.storage.iter().filter(unkeyed).map(|entry| self.resolve(layout, &entry.label)).collect()
+    }
+}
+
+/// A collection's prefix, which its slot holds in hex
+fn parse_prefix(entry: &StorageEntry) -> Result<Vec<u8>, NearError> {
+    entry
+        .slot
+        .strip_prefix("0x")
+        .and_then(|digits| hex::decode(digits).ok())
+        .ok_or_else(|| NearError::InvalidQuery(format!("Layout entry {} has no prefix", entry.label)))
+}
+
+fn type_size(layout: &LayoutInfo, type_name: &str) -> Option<usize> {
+    layout
+        .types
+        .iter()
+        .find(|t| t.label == type_name)
+        .and_then(|t| t.number_of_bytes.parse::<usize>().ok())
+        .filter(|size| *size > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definition::{tests::TOKEN_DEFINITION, NearStorageDefinition};
+    use crate::layout::NearLayoutCompiler;
+
+    #[test]
+    fn test_resolve_state_keys() {
+        let definition = NearStorageDefinition::from_json(TOKEN_DEFINITION).unwrap();
+        let layout = NearLayoutCompiler::compile_definition(&definition).unwrap();
+
+        let balance = NearKeyResolver::state_key(&layout, "accounts[alice.near]").unwrap();
+        assert_eq!(balance.storage_key, b"a\x0a\0\0\0alice.near");
+        assert_eq!((balance.size, balance.zero_semantics), (Some(16), ZeroSemantics::NeverWritten));
+        let path = NearKeyResolver.resolve(&layout, "accounts[alice.near]").unwrap();
+        assert_eq!(path.key, Key::Variable(b"\x09token.near,a\x0a\0\0\0alice.near".to_vec()));
+
+        let holder = NearKeyResolver::state_key(&layout, "holders[3]").unwrap();
+        assert_eq!(holder.storage_key, [1, 3, 0, 0, 0]);
+        assert_eq!(NearKeyResolver::state_key(&layout, "metadata").unwrap().storage_key, b"m");
+
+        let supply = NearKeyResolver::state_key(&layout, "STATE.total_supply").unwrap();
+        assert_eq!((supply.offset, supply.size), (0, Some(16)));
+        let mut state = 1_000u128.to_le_bytes().to_vec();
+        state.extend(b"\x0a\0\0\0owner.near");
+        assert_eq!(supply.extract(&state), Some(&1_000u128.to_le_bytes()[..]));
+        let owner = NearKeyResolver.resolve(&layout, "STATE.owner_id").unwrap();
+        assert_eq!((owner.offset, owner.field_size, owner.zero_semantics), (Some(16), None, ZeroSemantics::ValidZero));
+
+        // `paused` follows the variable-size `owner_id`
+        assert!(NearKeyResolver.resolve(&layout, "STATE.paused").is_err());
+        assert!(NearKeyResolver.resolve(&layout, "accounts").is_err());
+        assert!(NearKeyResolver.resolve(&layout, "metadata[1]").is_err());
+        assert!(NearKeyResolver.resolve(&layout, "holders[-1]").is_err());
+        assert!(NearKeyResolver.resolve(&layout, "balances[alice.near]").is_err());
+
+        let labels: Vec<_> = NearKeyResolver.resolve_all(&layout).unwrap().into_iter().map(|p| p.offset).collect();
+        assert_eq!(labels, [None, Some(0), Some(16), None]);
+    }
+}
@@ -0,0 +1,265 @@
+//! State trie nodes and proofs
+//!
+//! Each shard keeps its state in a hex-radix trie whose nodes are Borsh
+//! encoded and hashed with SHA-256. Leaves and branches refer to values by
+//! length and hash, so a proof is the set of nodes on the path from the
+//! state root to a key: the key is present when the path ends at a value,
+//! and absent when it leaves the trie.
+
+use crate::NearError;
+use sha2::{Digest, Sha256};
+use traverse_core::hash::nibbles;
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, vec::Vec};
+
+/// A stored value, by length and hash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValueRef {
+    pub length: u32,
+    pub hash: [u8; 32],
+}
+
+/// A trie node; keys are in nibbles
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrieNode {
+    Leaf { key: Vec<u8>, value: ValueRef },
+    Branch { children: [Option<[u8; 32]>; 16], value: Option<ValueRef> },
+    Extension { key: Vec<u8>, child: [u8; 32] },
+}
+
+impl ValueRef {
+    /// Reference to `value`
+    pub fn of(value: &[u8]) -> Self {
+        Self { length: value.len() as u32, hash: Sha256::digest(value).into() }
+    }
+}
+
+impl TrieNode {
+    /// Decode a node with its trailing memory usage
+    pub fn decode(bytes: &[u8]) -> Result<Self, NearError> {
+        let mut reader = Reader { bytes, position: 0 };
+        let node = match reader.take(1)?[0] {
+            0 => {
+                let key = reader.nibbles(true)?;
+                Self::Leaf { key, value: reader.value_ref()? }
+            }
+            1 => Self::Branch { children: reader.children()?, value: None },
+            2 => {
+                let value = reader.value_ref()?;
+                Self::Branch { children: reader.children()?, value: Some(value) }
+            }
+            3 => {
+                let key = reader.nibbles(false)?;
+                Self::Extension { key, child: reader.hash()? }
+            }
+            tag => return Err(NearError::ProofVerification(format!("unknown trie node kind {}", tag))),
+        };
+        reader.take(8)?;
+        if reader.position != bytes.len() {
+            return Err(NearError::ProofVerification("trailing bytes after a trie node".into()));
+        }
+        Ok(node)
+    }
+
+    /// Encode the node with its memory usage
+    pub fn encode(&self, memory_usage: u64) -> Vec<u8> {
+        let mut out = Vec::new();
+        match self {
+            Self::Leaf { key, value } => {
+                out.push(0);
+                write_nibbles(&mut out, key, true);
+                write_value_ref(&mut out, value);
+            }
+            Self::Branch { children, value: None } => {
+                out.push(1);
+                write_children(&mut out, children);
+            }
+            Self::Branch { children, value: Some(value) } => {
+                out.push(2);
+                write_value_ref(&mut out, value);
+                write_children(&mut out, children);
+            }
+            Self::Extension { key, child } => {
+                out.push(3);
+                write_nibbles(&mut out, key, false);
+                out.extend_from_slice(child);
+            }
+        }
+        out.extend_from_slice(&memory_usage.to_le_bytes());
+        out
+    }
+}
+
+/// Hash of an encoded node
+pub fn node_hash(node: &[u8]) -> [u8; 32] {
+    Sha256::digest(node).into()
+}
+
+/// Follow `key` from `state_root` through `nodes`
+///
+/// Returns the reference of the value stored under the key, or `None` when
+/// the nodes prove there is none. Nodes the path does not reach are
+/// ignored, so the nodes of a prefix proof can be passed as they are.
+pub fn verify_trie_proof(state_root: &[u8; 32], key: &[u8], nodes: &[Vec<u8>]) -> Result<Option<ValueRef>, NearError> {
+    // The empty trie has the default hash
+    if *state_root == [0; 32] {
+        return Ok(None);
+    }
+    let hashed: Vec<_> = nodes.iter().map(|node| (node_hash(node), node.as_slice())).collect();
+    let path = nibbles(key);
+    let (mut hash, mut position) = (*state_root, 0);
+
+    loop {
+        let node = hashed.iter().find(|(h, _)| *h == hash).map(|(_, node)| *node).ok_or_else(|| {
+            NearError::ProofVerification(format!("the proof is missing trie node {}", hex::encode(hash)))
+        })?;
+        let rest = &path[position..];
+        match TrieNode::decode(node)? {
+            TrieNode::Leaf { key, value } => return Ok((rest == key.as_slice()).then_some(value)),
+            TrieNode::Extension { key, child } if rest.starts_with(&key) => {
+                position += key.len();
+                hash = child;
+            }
+            TrieNode::Extension { .. } => return Ok(None),
+            TrieNode::Branch { value, .. } if rest.is_empty() => return Ok(value),
+            TrieNode::Branch { children, .. } => match children[rest[0] as usize] {
+                Some(child) => {
+                    position += 1;
+                    hash = child;
+                }
+                None => return Ok(None),
+            },
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], NearError> {
+        let end = self.position.checked_add(len).filter(|end| *end <= self.bytes.len());
+        let end = end.ok_or_else(|| NearError::ProofVerification("truncated trie node".into()))?;
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, NearError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap_or_default()))
+    }
+
+    fn hash(&mut self) -> Result<[u8; 32], NearError> {
+        Ok(self.take(32)?.try_into().unwrap_or_default())
+    }
+
+    fn value_ref(&mut self) -> Result<ValueRef, NearError> {
+        Ok(ValueRef { length: self.u32()?, hash: self.hash()? })
+    }
+
+    /// A bitmap of the children present, then their hashes
+    fn children(&mut self) -> Result<[Option<[u8; 32]>; 16], NearError> {
+        let bitmap = u16::from_le_bytes(self.take(2)?.try_into().unwrap_or_default());
+        let mut children = [None; 16];
+        for (i, child) in children.iter_mut().enumerate() {
+            if bitmap & (1 << i) != 0 {
+                *child = Some(self.hash()?);
+            }
+        }
+        Ok(children)
+    }
+
+    /// A key path: its length, a flag byte holding the first nibble of odd paths, then nibble pairs
+    fn nibbles(&mut self, leaf: bool) -> Result<Vec<u8>, NearError> {
+        let len = self.u32()? as usize;
+        let encoded = self.take(len)?;
+        let (flag, pairs) = encoded
+            .split_first()
+            .ok_or_else(|| NearError::ProofVerification("empty trie key path".into()))?;
+        if (flag & 0x20 != 0) != leaf {
+            return Err(NearError::ProofVerification("trie key path flag does not match its node".into()));
+        }
+        let mut nibbles = Vec::with_capacity(pairs.len() * 2 + 1);
+        if flag & 0x10 != 0 {
+            nibbles.push(flag & 0x0f);
+        }
+        nibbles.extend(pairs.iter().flat_map(|b| [b >> 4, b & 0x0f]));
+        Ok(nibbles)
+    }
+}
+
+fn write_value_ref(out: &mut Vec<u8>, value: &ValueRef) {
+    out.extend_from_slice(&value.length.to_le_bytes());
+    out.extend_from_slice(&value.hash);
+}
+
+fn write_children(out: &mut Vec<u8>, children: &[Option<[u8; 32]>; 16]) {
+    let bitmap = children.iter().enumerate().filter(|(_, c)| c.is_some()).fold(0u16, |bits, (i, _)| bits | 1 << i);
+    out.extend_from_slice(&bitmap.to_le_bytes());
+    children.iter().flatten().for_each(|child| out.extend_from_slice(child));
+}
+
+fn write_nibbles(out: &mut Vec<u8>, nibbles: &[u8], leaf: bool) {
+    let mut encoded = Vec::with_capacity(nibbles.len() / 2 + 1);
+    let flag = if leaf { 0x20 } else { 0 };
+    let pairs = if nibbles.len() % 2 == 1 {
+        encoded.push(flag | 0x10 | nibbles[0]);
+        &nibbles[1..]
+    } else {
+        encoded.push(flag);
+        nibbles
+    };
+    encoded.extend(pairs.chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+    out.extend_from_slice(&(encoded.len() as u32).to_le_bytes());
+    out.extend_from_slice(&encoded);
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A trie holding `entries`: an extension over the keys' common prefix,
+    /// then a branch on the next nibble, where they must differ, to a leaf per key
+    pub(crate) fn small_trie(entries: &[(&[u8], &[u8])]) -> ([u8; 32], Vec<Vec<u8>>) {
+        let keys: Vec<_> = entries.iter().map(|(key, _)| nibbles(key)).collect();
+        let common = (0..).find(|i| keys.iter().any(|k| k.get(*i) != keys[0].get(*i))).unwrap();
+
+        let mut nodes = Vec::new();
+        let mut children = [None; 16];
+        for (key, (_, value)) in keys.iter().zip(entries) {
+            let leaf = TrieNode::Leaf { key: key[common + 1..].to_vec(), value: ValueRef::of(value) }.encode(100);
+            children[key[common] as usize] = Some(node_hash(&leaf));
+            nodes.push(leaf);
+        }
+        let branch = TrieNode::Branch { children, value: None }.encode(300);
+        let extension = TrieNode::Extension { key: keys[0][..common].to_vec(), child: node_hash(&branch) }.encode(400);
+        let root = node_hash(&extension);
+        nodes.extend([branch, extension]);
+        (root, nodes)
+    }
+
+    #[test]
+    fn test_trie_proofs() {
+        let (root, nodes) = small_trie(&[(b"\x09a", b"one"), (b"\x09\x12\x01", b"two")]);
+        for node in &nodes {
+            assert_eq!(&TrieNode::decode(node).unwrap().encode(u64::from_le_bytes(node[node.len() - 8..].try_into().unwrap())), node);
+        }
+
+        assert_eq!(verify_trie_proof(&root, b"\x09a", &nodes).unwrap(), Some(ValueRef::of(b"one")));
+        assert_eq!(verify_trie_proof(&root, b"\x09\x12\x01", &nodes).unwrap(), Some(ValueRef::of(b"two")));
+        // Absent: a missing child, a diverging extension and a diverging leaf
+        assert_eq!(verify_trie_proof(&root, b"\x09\x00", &nodes).unwrap(), None);
+        assert_eq!(verify_trie_proof(&root, b"\x0aa", &nodes).unwrap(), None);
+        assert_eq!(verify_trie_proof(&root, b"\x09\x12\x02", &nodes).unwrap(), None);
+        assert_eq!(verify_trie_proof(&[0; 32], b"\x09a", &[]).unwrap(), None);
+
+        // Every node on the path is needed
+        assert!(verify_trie_proof(&root, b"\x09a", &nodes[1..]).is_err());
+        assert!(verify_trie_proof(&[1; 32], b"\x09a", &nodes).is_err());
+        assert!(TrieNode::decode(&nodes[0][..nodes[0].len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,101 @@
+//! What each backend can prove
+//!
+//! Backends differ in what a proof says: most prove a value under a key of
+//! a contract's storage, some prove a whole account or object, and Bitcoin
+//! proves transactions rather than state. Callers routing a request pick a
+//! chain whose backend supports the proof the request needs, instead of
+//! hard-coding which chains can serve it.
+
+use crate::chain::ChainKind;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/// A kind of statement a backend can prove
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofType {
+    /// A value under a storage key of a contract, module or pallet
+    Storage,
+    /// The state of a whole account or object
+    Account,
+    /// A transaction's inclusion in a block
+    Transaction,
+    /// A chain of headers and the work or signatures behind them
+    HeaderChain,
+}
+
+impl ProofType {
+    pub fn name(self) -> &'static str {
+        match self {
+            ProofType::Storage => "storage",
+            ProofType::Account => "account",
+            ProofType::Transaction => "transaction",
+            ProofType::HeaderChain => "header_chain",
+        }
+    }
+}
+
+impl fmt::Display for ProofType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// What a backend supports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Capabilities {
+    /// Proofs the backend can fetch and verify
+    pub proofs: &'static [ProofType],
+    /// Whether it compiles layouts from contract definitions
+    pub layouts: bool,
+    /// Whether it resolves queries to storage keys; otherwise values are decoded from proven state
+    pub key_resolution: bool,
+}
+
+impl Capabilities {
+    pub fn supports(&self, proof: ProofType) -> bool {
+        self.proofs.contains(&proof)
+    }
+}
+
+impl ChainKind {
+    /// What the kind's backend supports
+    pub fn capabilities(self) -> Capabilities {
+        use ProofType::*;
+
+        let (proofs, layouts, key_resolution): (&'static [ProofType], bool, bool) = match self {
+            ChainKind::Ethereum => (&[Storage, Account], true, true),
+            ChainKind::Solana | ChainKind::Sui => (&[Account], true, true),
+            ChainKind::Cosmos | ChainKind::Aptos | ChainKind::Near | ChainKind::Substrate | ChainKind::Starknet => {
+                (&[Storage], true, true)
+            }
+            ChainKind::Bitcoin => (&[Transaction, HeaderChain], false, false),
+            // Where a TL-B field starts depends on the values before it
+            ChainKind::Ton => (&[Account], true, false),
+        };
+        Capabilities { proofs, layouts, key_resolution }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities() {
+        assert!(ChainKind::Ethereum.capabilities().supports(ProofType::Storage));
+        assert!(!ChainKind::Solana.capabilities().supports(ProofType::Storage));
+        assert!(ChainKind::Bitcoin.capabilities().supports(ProofType::HeaderChain));
+        assert!(!ChainKind::Bitcoin.capabilities().layouts);
+
+        // Every backend proves something, and only Bitcoin proves transactions
+        for kind in ChainKind::ALL {
+            let capabilities = kind.capabilities();
+            assert!(!capabilities.proofs.is_empty());
+            assert_eq!(capabilities.supports(ProofType::Transaction), kind == ChainKind::Bitcoin);
+        }
+
+        let json = serde_json::to_value(ChainKind::Ton.capabilities()).unwrap();
+        assert_eq!(json, serde_json::json!({ "proofs": ["account"], "layouts": true, "key_resolution": false }));
+    }
+}
@@ -0,0 +1,208 @@
+//! Chain kinds and their consensus-level defaults
+//!
+//! A kind names the backend crate that understands a chain, not a network:
+//! mainnet, testnets and app-chains built on the same stack share a kind
+//! and differ by chain ID. Each kind has a default finality policy and the
+//! hash function its state commitments use.
+
+use crate::RegistryError;
+use core::fmt;
+use core::str::FromStr;
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(feature = "std"))]
+use alloc::format;
+
+/// The family of chains one backend crate handles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChainKind {
+    Ethereum,
+    Solana,
+    Cosmos,
+    Aptos,
+    Sui,
+    Near,
+    Substrate,
+    Starknet,
+    Bitcoin,
+    Ton,
+}
+
+impl ChainKind {
+    /// Every kind, in the order backends were added
+    pub const ALL: [ChainKind; 10] = [
+        ChainKind::Ethereum,
+        ChainKind::Solana,
+        ChainKind::Cosmos,
+        ChainKind::Aptos,
+        ChainKind::Sui,
+        ChainKind::Near,
+        ChainKind::Substrate,
+        ChainKind::Starknet,
+        ChainKind::Bitcoin,
+        ChainKind::Ton,
+    ];
+
+    /// The kind's name, as configs and CLI arguments write it
+    pub fn name(self) -> &'static str {
+        match self {
+            ChainKind::Ethereum => "ethereum",
+            ChainKind::Solana => "solana",
+            ChainKind::Cosmos => "cosmos",
+            ChainKind::Aptos => "aptos",
+            ChainKind::Sui => "sui",
+            ChainKind::Near => "near",
+            ChainKind::Substrate => "substrate",
+            ChainKind::Starknet => "starknet",
+            ChainKind::Bitcoin => "bitcoin",
+            ChainKind::Ton => "ton",
+        }
+    }
+
+    /// The backend crate for the kind
+    pub fn crate_name(self) -> &'static str {
+        match self {
+            ChainKind::Ethereum => "traverse-ethereum",
+            ChainKind::Solana => "traverse-solana",
+            ChainKind::Cosmos => "traverse-cosmos",
+            ChainKind::Aptos => "traverse-aptos",
+            ChainKind::Sui => "traverse-sui",
+            ChainKind::Near => "traverse-near",
+            ChainKind::Substrate => "traverse-substrate",
+            ChainKind::Starknet => "traverse-starknet",
+            ChainKind::Bitcoin => "traverse-bitcoin",
+            ChainKind::Ton => "traverse-ton",
+        }
+    }
+
+    /// When a block is considered settled, unless a config says otherwise
+    ///
+    /// BFT chains finalize each block as it is committed, so the latest
+    /// block is final; Bitcoin only ever has probabilistic finality.
+    pub fn default_finality(self) -> FinalityPolicy {
+        match self {
+            ChainKind::Ethereum | ChainKind::Solana | ChainKind::Near | ChainKind::Substrate | ChainKind::Starknet => {
+                FinalityPolicy::Finalized
+            }
+            ChainKind::Cosmos | ChainKind::Aptos | ChainKind::Sui | ChainKind::Ton => FinalityPolicy::Latest,
+            ChainKind::Bitcoin => FinalityPolicy::Confirmations(6),
+        }
+    }
+
+    /// Hash functions chains of the kind commit to their state with, the default first
+    ///
+    /// Substrate runtimes choose their hasher; the rest are fixed by the protocol.
+    pub fn hash_backends(self) -> &'static [HashBackend] {
+        match self {
+            ChainKind::Ethereum => &[HashBackend::Keccak256],
+            ChainKind::Solana | ChainKind::Cosmos | ChainKind::Near | ChainKind::Ton => &[HashBackend::Sha256],
+            ChainKind::Aptos => &[HashBackend::Sha3_256],
+            ChainKind::Sui => &[HashBackend::Blake2b256],
+            ChainKind::Substrate => &[HashBackend::Blake2b256, HashBackend::Keccak256],
+            ChainKind::Starknet => &[HashBackend::Pedersen],
+            ChainKind::Bitcoin => &[HashBackend::DoubleSha256],
+        }
+    }
+
+    pub fn default_hash_backend(self) -> HashBackend {
+        self.hash_backends()[0]
+    }
+}
+
+impl fmt::Display for ChainKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ChainKind {
+    type Err = RegistryError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        ChainKind::ALL
+            .into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| RegistryError::InvalidConfig(format!("Unknown chain kind '{}'", name)))
+    }
+}
+
+/// Which blocks proofs may be anchored to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FinalityPolicy {
+    /// The latest block
+    Latest,
+    /// Blocks with at least this many blocks on top, the block itself counting as one
+    Confirmations(u64),
+    /// Blocks the chain's finality gadget has finalized
+    Finalized,
+}
+
+impl FinalityPolicy {
+    /// Whether a block at `height` is settled when the chain's tip is at `tip`
+    ///
+    /// `finalized` is the height of the latest finalized block, for chains
+    /// that report one.
+    pub fn is_settled(self, height: u64, tip: u64, finalized: Option<u64>) -> bool {
+        if height > tip {
+            return false;
+        }
+        match self {
+            FinalityPolicy::Latest => true,
+            FinalityPolicy::Confirmations(confirmations) => tip - height + 1 >= confirmations,
+            FinalityPolicy::Finalized => finalized.is_some_and(|finalized| height <= finalized),
+        }
+    }
+}
+
+/// Hash function a chain's state commitments are built with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashBackend {
+    Keccak256,
+    Sha256,
+    #[serde(rename = "sha3_256")]
+    Sha3_256,
+    Blake2b256,
+    /// SHA-256 applied twice
+    DoubleSha256,
+    /// The Stark curve Pedersen hash, over field elements
+    Pedersen,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_kinds() {
+        for kind in ChainKind::ALL {
+            assert_eq!(kind.name().parse::<ChainKind>().unwrap(), kind);
+            assert_eq!(serde_json::to_value(kind).unwrap(), kind.name());
+            assert!(kind.crate_name().ends_with(kind.name()));
+            assert_eq!(kind.default_hash_backend(), kind.hash_backends()[0]);
+        }
+        assert_eq!("NEAR".parse::<ChainKind>().unwrap(), ChainKind::Near);
+        assert!("tron".parse::<ChainKind>().is_err());
+        assert_eq!(serde_json::to_value(HashBackend::Sha3_256).unwrap(), "sha3_256");
+    }
+
+    #[test]
+    fn test_finality_policies() {
+        assert!(FinalityPolicy::Latest.is_settled(100, 100, None));
+        assert!(!FinalityPolicy::Latest.is_settled(101, 100, None));
+
+        let six = ChainKind::Bitcoin.default_finality();
+        assert!(six.is_settled(95, 100, None));
+        assert!(!six.is_settled(96, 100, None));
+
+        assert!(FinalityPolicy::Finalized.is_settled(90, 100, Some(90)));
+        assert!(!FinalityPolicy::Finalized.is_settled(91, 100, Some(90)));
+        assert!(!FinalityPolicy::Finalized.is_settled(90, 100, None));
+
+        let json = serde_json::to_value(FinalityPolicy::Confirmations(12)).unwrap();
+        assert_eq!(json, serde_json::json!({ "confirmations": 12 }));
+        assert_eq!(serde_json::from_value::<FinalityPolicy>("finalized".into()).unwrap(), FinalityPolicy::Finalized);
+    }
+}
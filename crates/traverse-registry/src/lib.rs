@@ -0,0 +1,74 @@
+//! Chain configurations and capability discovery across traverse backends
+//!
+//! Each backend crate handles one family of chains, and the families differ
+//! in what can be proven about them. This crate describes both sides
+//! without depending on any backend: a [`ChainConfig`] says which chain a
+//! name refers to, where to reach it, when its blocks are settled and how
+//! its state is hashed, and [`ChainKind::capabilities`] says which proofs
+//! the kind's backend supports. Tools that handle several chains route a
+//! request through a [`ChainRegistry`] instead of matching on chain names.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! use traverse_registry::{ChainRegistry, ProofType};
+//!
+//! let registry = ChainRegistry::from_json(&std::fs::read_to_string("chains.json")?)?;
+//! let chain = registry.route("neutron", ProofType::Storage)?;
+//! println!("{} via {}", chain.kind.crate_name(), chain.endpoint()?);
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+
+pub mod capability;
+pub mod chain;
+pub mod registry;
+
+pub use capability::{Capabilities, ProofType};
+pub use chain::{ChainKind, FinalityPolicy, HashBackend};
+pub use registry::{ChainConfig, ChainRegistry};
+
+/// Error types for chain configuration and routing
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+#[derive(Debug)]
+pub enum RegistryError {
+    #[cfg_attr(feature = "std", error("Invalid chain config: {0}"))]
+    InvalidConfig(String),
+
+    #[cfg_attr(feature = "std", error("Chain '{0}' is already registered"))]
+    DuplicateChain(String),
+
+    #[cfg_attr(feature = "std", error("Unknown chain '{0}'"))]
+    UnknownChain(String),
+
+    #[cfg_attr(feature = "std", error("Unsupported request: {0}"))]
+    Unsupported(String),
+
+    #[cfg_attr(feature = "std", error("JSON error: {0}"))]
+    Json(#[cfg_attr(feature = "std", from)] serde_json::Error),
+}
+
+impl From<RegistryError> for traverse_core::TraverseError {
+    fn from(err: RegistryError) -> Self {
+        use traverse_core::TraverseError;
+
+        match err {
+            RegistryError::InvalidConfig(msg) => TraverseError::InvalidInput(msg),
+            RegistryError::DuplicateChain(name) => {
+                TraverseError::InvalidInput(format!("Chain '{}' is already registered", name))
+            }
+            RegistryError::UnknownChain(name) => TraverseError::InvalidInput(format!("Unknown chain '{}'", name)),
+            RegistryError::Unsupported(msg) => TraverseError::InvalidInput(msg),
+            RegistryError::Json(err) => TraverseError::Serialization(err.to_string()),
+        }
+    }
+}
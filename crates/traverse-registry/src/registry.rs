@@ -0,0 +1,254 @@
+//! Configured chains, looked up by name or by capability
+//!
+//! A registry is read from JSON:
+//!
+//! ```json
+//! {
+//!   "chains": [
+//!     { "name": "ethereum-mainnet", "kind": "ethereum", "chain_id": "1",
+//!       "endpoints": ["https://eth.example.com"] },
+//!     { "name": "bitcoin", "kind": "bitcoin", "chain_id": "main",
+//!       "finality": { "confirmations": 3 } }
+//!   ]
+//! }
+//! ```
+//!
+//! `finality` and `hash` default to the kind's.
+
+use crate::capability::{Capabilities, ProofType};
+use crate::chain::{ChainKind, FinalityPolicy, HashBackend};
+use crate::RegistryError;
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// A chain requests can be routed to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainConfig {
+    /// Name requests refer to the chain by, unique in a registry
+    pub name: String,
+    pub kind: ChainKind,
+    /// The ID the chain's endpoints report, as the CLIs' `--expect-chain-id` takes it
+    pub chain_id: String,
+    /// RPC endpoints, in order of preference
+    #[serde(default)]
+    pub endpoints: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finality: Option<FinalityPolicy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<HashBackend>,
+}
+
+impl ChainConfig {
+    pub fn new(name: impl Into<String>, kind: ChainKind, chain_id: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            chain_id: chain_id.into(),
+            endpoints: Vec::new(),
+            finality: None,
+            hash: None,
+        }
+    }
+
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoints.push(endpoint.into());
+        self
+    }
+
+    pub fn with_finality(mut self, finality: FinalityPolicy) -> Self {
+        self.finality = Some(finality);
+        self
+    }
+
+    pub fn with_hash_backend(mut self, hash: HashBackend) -> Self {
+        self.hash = Some(hash);
+        self
+    }
+
+    /// The finality policy, the kind's unless configured
+    pub fn finality(&self) -> FinalityPolicy {
+        self.finality.unwrap_or_else(|| self.kind.default_finality())
+    }
+
+    /// The hash backend, the kind's unless configured
+    pub fn hash_backend(&self) -> HashBackend {
+        self.hash.unwrap_or_else(|| self.kind.default_hash_backend())
+    }
+
+    pub fn capabilities(&self) -> Capabilities {
+        self.kind.capabilities()
+    }
+
+    /// The preferred endpoint
+    pub fn endpoint(&self) -> Result<&str, RegistryError> {
+        self.endpoints
+            .first()
+            .map(String::as_str)
+            .ok_or_else(|| RegistryError::InvalidConfig(format!("Chain '{}' has no endpoints", self.name)))
+    }
+
+    /// Check the config is usable
+    pub fn validate(&self) -> Result<(), RegistryError> {
+        let invalid = |why: &str| RegistryError::InvalidConfig(format!("Chain '{}': {}", self.name, why));
+        if self.name.trim().is_empty() {
+            return Err(RegistryError::InvalidConfig("Chain name cannot be empty".into()));
+        }
+        if self.chain_id.trim().is_empty() {
+            return Err(invalid("chain_id cannot be empty"));
+        }
+        if self.endpoints.iter().any(|endpoint| endpoint.trim().is_empty()) {
+            return Err(invalid("endpoints cannot be empty"));
+        }
+        if self.finality == Some(FinalityPolicy::Confirmations(0)) {
+            return Err(invalid("a block needs at least one confirmation"));
+        }
+        if !self.kind.hash_backends().contains(&self.hash_backend()) {
+            return Err(invalid(&format!("{} chains do not use {:?}", self.kind, self.hash_backend())));
+        }
+        Ok(())
+    }
+}
+
+/// The chains requests can be routed to
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainRegistry {
+    chains: Vec<ChainConfig>,
+}
+
+impl ChainRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read a registry, checking each chain
+    pub fn from_json(content: &str) -> Result<Self, RegistryError> {
+        let parsed: Self = serde_json::from_str(content).map_err(RegistryError::Json)?;
+        let mut registry = Self::new();
+        for chain in parsed.chains {
+            registry.register(chain)?;
+        }
+        Ok(registry)
+    }
+
+    pub fn to_json(&self) -> Result<String, RegistryError> {
+        serde_json::to_string_pretty(self).map_err(RegistryError::Json)
+    }
+
+    /// Add a chain, refusing invalid configs and names already taken
+    pub fn register(&mut self, chain: ChainConfig) -> Result<(), RegistryError> {
+        chain.validate()?;
+        if self.get(&chain.name).is_some() {
+            return Err(RegistryError::DuplicateChain(chain.name));
+        }
+        self.chains.push(chain);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ChainConfig> {
+        self.chains.iter().find(|chain| chain.name == name)
+    }
+
+    /// All chains, in registration order
+    pub fn chains(&self) -> &[ChainConfig] {
+        &self.chains
+    }
+
+    pub fn of_kind(&self, kind: ChainKind) -> impl Iterator<Item = &ChainConfig> {
+        self.chains.iter().filter(move |chain| chain.kind == kind)
+    }
+
+    /// Chains whose backend can produce `proof`
+    pub fn supporting(&self, proof: ProofType) -> impl Iterator<Item = &ChainConfig> {
+        self.chains.iter().filter(move |chain| chain.capabilities().supports(proof))
+    }
+
+    /// The chain a request for `proof` on the chain named `name` goes to
+    pub fn route(&self, name: &str, proof: ProofType) -> Result<&ChainConfig, RegistryError> {
+        let chain = self.get(name).ok_or_else(|| RegistryError::UnknownChain(name.to_string()))?;
+        if !chain.capabilities().supports(proof) {
+            return Err(RegistryError::Unsupported(format!(
+                "{} chains cannot prove {}; '{}' supports {:?}",
+                chain.kind,
+                proof,
+                name,
+                chain.capabilities().proofs
+            )));
+        }
+        Ok(chain)
+    }
+
+    /// The chain of a kind that serves `chain_id`, IDs compared as written
+    pub fn find_chain_id(&self, kind: ChainKind, chain_id: &str) -> Option<&ChainConfig> {
+        self.of_kind(kind).find(|chain| chain.chain_id == chain_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REGISTRY: &str = r#"{
+        "chains": [
+            { "name": "ethereum-mainnet", "kind": "ethereum", "chain_id": "1",
+              "endpoints": ["https://eth.example.com", "https://eth-backup.example.com"] },
+            { "name": "neutron", "kind": "cosmos", "chain_id": "neutron-1" },
+            { "name": "bitcoin", "kind": "bitcoin", "chain_id": "main", "finality": { "confirmations": 3 } },
+            { "name": "moonbeam", "kind": "substrate", "chain_id": "moonbeam", "hash": "keccak256" }
+        ]
+    }"#;
+
+    #[test]
+    fn test_registry_from_json() {
+        let registry = ChainRegistry::from_json(REGISTRY).unwrap();
+        assert_eq!(registry.chains().len(), 4);
+
+        let ethereum = registry.get("ethereum-mainnet").unwrap();
+        assert_eq!(ethereum.endpoint().unwrap(), "https://eth.example.com");
+        assert_eq!(ethereum.finality(), FinalityPolicy::Finalized);
+        assert_eq!(ethereum.hash_backend(), HashBackend::Keccak256);
+        assert_eq!(registry.get("bitcoin").unwrap().finality(), FinalityPolicy::Confirmations(3));
+        assert_eq!(registry.get("moonbeam").unwrap().hash_backend(), HashBackend::Keccak256);
+        assert!(registry.get("neutron").unwrap().endpoint().is_err());
+        assert_eq!(registry.find_chain_id(ChainKind::Cosmos, "neutron-1").unwrap().name, "neutron");
+
+        // Defaults are not written back
+        let round_trip = ChainRegistry::from_json(&registry.to_json().unwrap()).unwrap();
+        assert_eq!(round_trip, registry);
+        assert!(!registry.to_json().unwrap().contains("\"hash\": null"));
+    }
+
+    #[test]
+    fn test_routing() {
+        let registry = ChainRegistry::from_json(REGISTRY).unwrap();
+        let storage: Vec<_> = registry.supporting(ProofType::Storage).map(|c| c.name.as_str()).collect();
+        assert_eq!(storage, ["ethereum-mainnet", "neutron", "moonbeam"]);
+
+        assert_eq!(registry.route("bitcoin", ProofType::Transaction).unwrap().kind, ChainKind::Bitcoin);
+        assert!(matches!(registry.route("bitcoin", ProofType::Storage), Err(RegistryError::Unsupported(_))));
+        assert!(matches!(registry.route("osmosis", ProofType::Storage), Err(RegistryError::UnknownChain(_))));
+    }
+
+    #[test]
+    fn test_invalid_configs() {
+        let mut registry = ChainRegistry::new();
+        registry.register(ChainConfig::new("sui", ChainKind::Sui, "35834a8a").with_endpoint("https://sui.example.com")).unwrap();
+        assert!(matches!(
+            registry.register(ChainConfig::new("sui", ChainKind::Sui, "4c78adac")),
+            Err(RegistryError::DuplicateChain(_))
+        ));
+
+        let wrong_hash = ChainConfig::new("eth", ChainKind::Ethereum, "1").with_hash_backend(HashBackend::Sha256);
+        assert!(registry.register(wrong_hash).is_err());
+        let no_confirmations = ChainConfig::new("btc", ChainKind::Bitcoin, "main").with_finality(FinalityPolicy::Confirmations(0));
+        assert!(registry.register(no_confirmations).is_err());
+        assert!(registry.register(ChainConfig::new("", ChainKind::Near, "mainnet")).is_err());
+        assert!(registry.register(ChainConfig::new("near", ChainKind::Near, " ")).is_err());
+        assert!(ChainRegistry::from_json(r#"{ "chains": [{ "name": "x", "kind": "tron", "chain_id": "1" }] }"#).is_err());
+    }
+}
@@ -0,0 +1,21 @@
+//! Anchor IDL parsing and layout extraction
+//!
+//! IDLs are fetched from chain or supplied by users, so parsing and the
+//! layout and PDA extraction that follow must fail cleanly on any input.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use traverse_solana::IdlParser;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(json) = std::str::from_utf8(data) else {
+        return;
+    };
+    let Ok(idl) = IdlParser::parse_idl(json) else {
+        return;
+    };
+
+    let _ = IdlParser::extract_account_layouts(&idl);
+    let _ = IdlParser::extract_pdas(&idl);
+});
@@ -269,6 +269,19 @@ pub enum ZeroSemantics {
     Closed,
 }
 
+impl From<ZeroSemantics> for traverse_core::ZeroSemantics {
+    /// The chain-independent meaning, folding never-initialized fields into
+    /// never-written ones and closed accounts into cleared ones
+    fn from(semantics: ZeroSemantics) -> Self {
+        match semantics {
+            ZeroSemantics::NeverInitialized | ZeroSemantics::NeverWritten => Self::NeverWritten,
+            ZeroSemantics::ExplicitlyZero => Self::ExplicitlyZero,
+            ZeroSemantics::Cleared | ZeroSemantics::Closed => Self::Cleared,
+            ZeroSemantics::ValidZero => Self::ValidZero,
+        }
+    }
+}
+
 /// Wrapper for Solana program account with traverse analysis
 #[derive(Debug, Clone)]
 pub struct ProgramAccount {
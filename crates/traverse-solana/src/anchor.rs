@@ -21,7 +21,7 @@ pub struct SolanaIdl {
     /// Account definitions
     pub accounts: Vec<IdlAccount>,
     /// Type definitions
-    pub types: Vec<IdlType>,
+    pub types: Vec<IdlTypeDef>,
     /// Event definitions
     #[serde(default)]
     pub events: Vec<IdlEvent>,
@@ -177,8 +177,26 @@ pub enum IdlEnumFields {
     Tuple(Vec<IdlType>),
 }
 
-/// Type definition
+/// Named type definition from the IDL's `types` section
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdlTypeDef {
+    /// Type name, as `defined` references it
+    pub name: String,
+    /// Struct or enum definition
+    #[serde(rename = "type")]
+    pub type_def: IdlAccountType,
+    /// Type documentation
+    #[serde(default)]
+    pub docs: Vec<String>,
+}
+
+/// Type definition
+///
+/// Deserializes Anchor's shorthand (`{"vec": "u64"}`, `{"option": T}`,
+/// `{"array": [T, N]}`, `{"defined": "Name"}` or, since 0.30,
+/// `{"defined": {"name": "Name"}}`) as well as the `kind`-tagged form this
+/// type serializes to.
+#[derive(Debug, Clone, Serialize)]
 #[serde(untagged)]
 pub enum IdlType {
     /// Primitive type (string)
@@ -196,6 +214,50 @@ pub enum IdlType {
     },
 }
 
+impl<'de> Deserialize<'de> for IdlType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let object = match value {
+            serde_json::Value::String(name) => return Ok(IdlType::Primitive(name)),
+            serde_json::Value::Object(object) => object,
+            other => return Err(D::Error::custom(format!("invalid IDL type: {}", other))),
+        };
+        let nested = |value: &serde_json::Value| -> Result<Box<IdlType>, D::Error> {
+            IdlType::deserialize(value).map(Box::new).map_err(D::Error::custom)
+        };
+
+        if object.contains_key("kind") {
+            let kind = IdlTypeKind::deserialize(serde_json::Value::Object(object)).map_err(D::Error::custom)?;
+            return Ok(IdlType::Complex { kind });
+        }
+        // Anchor 0.30 writes `{"defined": {"name": "Config", "generics": []}}`
+        let defined = object.get("defined").and_then(|d| d.as_str().or_else(|| d.get("name")?.as_str()));
+        let kind = if let Some(defined) = defined {
+            return Ok(IdlType::Defined { defined: defined.to_string() });
+        } else if let Some(inner) = object.get("option").or_else(|| object.get("coption")) {
+            IdlTypeKind::Option { inner: nested(inner)? }
+        } else if let Some(element) = object.get("vec") {
+            IdlTypeKind::Vec { element: nested(element)? }
+        } else if let Some(array) = object.get("array") {
+            match array.as_array().map(Vec::as_slice) {
+                Some([element, size]) => IdlTypeKind::Array {
+                    element: nested(element)?,
+                    size: size
+                        .as_u64()
+                        .and_then(|size| u32::try_from(size).ok())
+                        .ok_or_else(|| D::Error::custom("IDL array size must be a number"))?,
+                },
+                _ => return Err(D::Error::custom("IDL array must be [type, size]")),
+            }
+        } else {
+            return Err(D::Error::custom(format!("invalid IDL type: {}", serde_json::Value::Object(object))));
+        };
+        Ok(IdlType::Complex { kind })
+    }
+}
+
 /// Complex type kinds
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "kind", rename_all = "camelCase")]
@@ -282,11 +344,140 @@ pub struct IdlMetadata {
 /// IDL parser for Anchor programs
 pub struct IdlParser;
 
+/// IDL schemas [`IdlParser`] reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdlFormat {
+    /// Anchor before 0.30: `programId` and `name` at the top, `isMut`/`isSigner`
+    /// accounts, account layouts inline
+    Legacy,
+    /// Anchor 0.30 and later: `address` and `metadata.spec` at the top,
+    /// discriminators in the IDL, account layouts in `types`
+    Spec,
+}
+
 impl IdlParser {
-    /// Parse IDL from JSON string
+    /// Parse IDL from JSON string, in either [`IdlFormat`]
+    ///
+    /// Anchor 0.30 IDLs are converted to the legacy shape [`SolanaIdl`]
+    /// models: each account takes its layout from the type of the same
+    /// name and keeps the discriminator the IDL lists for it.
     pub fn parse_idl(json_content: &str) -> SolanaResult<SolanaIdl> {
-        serde_json::from_str(json_content)
-            .map_err(|e| SolanaError::InvalidIdl(format!("Failed to parse IDL JSON: {}", e)))
+        let value: serde_json::Value = serde_json::from_str(json_content)
+            .map_err(|e| SolanaError::InvalidIdl(format!("Failed to parse IDL JSON: {}", e)))?;
+        let value = match Self::detect_format(&value) {
+            IdlFormat::Legacy => value,
+            IdlFormat::Spec => Self::convert_spec_idl(&value)?,
+        };
+        serde_json::from_value(value).map_err(|e| SolanaError::InvalidIdl(format!("Failed to parse IDL JSON: {}", e)))
+    }
+
+    /// Which schema an IDL document follows
+    pub fn detect_format(idl: &serde_json::Value) -> IdlFormat {
+        let has_spec = idl.get("metadata").and_then(|m| m.get("spec")).is_some();
+        let address_only = idl.get("address").is_some() && idl.get("programId").is_none();
+        if has_spec || address_only {
+            IdlFormat::Spec
+        } else {
+            IdlFormat::Legacy
+        }
+    }
+
+    /// Rewrite an Anchor 0.30 IDL in the legacy schema
+    fn convert_spec_idl(idl: &serde_json::Value) -> SolanaResult<serde_json::Value> {
+        use serde_json::{json, Value};
+
+        let metadata = idl.get("metadata").cloned().unwrap_or(Value::Null);
+        let address = idl.get("address").cloned().unwrap_or(Value::Null);
+        let list = |key: &str| idl.get(key).and_then(Value::as_array).cloned().unwrap_or_default();
+        let types = list("types");
+        let type_of = |name: &str| types.iter().find(|def| def["name"] == name).map(|def| def["type"].clone());
+
+        let instructions: Vec<Value> = list("instructions")
+            .iter()
+            .map(|instruction| {
+                json!({
+                    "name": instruction["name"],
+                    "accounts": Self::convert_spec_accounts(&instruction["accounts"]),
+                    "args": instruction.get("args").cloned().unwrap_or_else(|| json!([])),
+                    "returns": instruction.get("returns").cloned().unwrap_or(Value::Null),
+                })
+            })
+            .collect();
+
+        let accounts = list("accounts")
+            .iter()
+            .map(|account| {
+                let name = account["name"].as_str().unwrap_or_default();
+                let layout = type_of(name).ok_or_else(|| {
+                    SolanaError::InvalidIdl(format!("Account '{}' has no type of the same name", name))
+                })?;
+                Ok(json!({ "name": name, "discriminator": account["discriminator"], "type": layout }))
+            })
+            .collect::<SolanaResult<Vec<Value>>>()?;
+
+        // Events, like accounts, take their fields from the type of the same name
+        let events: Vec<Value> = list("events")
+            .iter()
+            .map(|event| {
+                let name = event["name"].as_str().unwrap_or_default();
+                let fields = type_of(name).and_then(|layout| layout.get("fields").cloned()).unwrap_or_else(|| json!([]));
+                json!({ "name": name, "fields": fields })
+            })
+            .collect();
+
+        Ok(json!({
+            "version": metadata.get("version").cloned().unwrap_or_else(|| json!("0.0.0")),
+            "name": metadata.get("name").cloned().unwrap_or_else(|| json!("")),
+            "programId": address,
+            "instructions": instructions,
+            "accounts": accounts,
+            "types": types,
+            "events": events,
+            "errors": list("errors"),
+            "constants": list("constants"),
+            "metadata": { "address": address },
+        }))
+    }
+
+    /// Instruction accounts in the legacy schema, with composite groups flattened
+    fn convert_spec_accounts(accounts: &serde_json::Value) -> Vec<serde_json::Value> {
+        use serde_json::{json, Value};
+
+        let flag = |account: &Value, key: &str| account.get(key).and_then(Value::as_bool).unwrap_or(false);
+        accounts
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|account| match account.get("accounts") {
+                Some(group) => Self::convert_spec_accounts(group),
+                None => vec![json!({
+                    "name": account["name"],
+                    "isMut": flag(account, "writable"),
+                    "isSigner": flag(account, "signer"),
+                    "is_optional": flag(account, "optional"),
+                    "docs": account.get("docs").cloned().unwrap_or_else(|| json!([])),
+                    "pda": account.get("pda").map(Self::convert_spec_pda).unwrap_or(Value::Null),
+                })],
+            })
+            .collect()
+    }
+
+    /// PDA seeds in the legacy schema: paths become the account or argument seeded from
+    fn convert_spec_pda(pda: &serde_json::Value) -> serde_json::Value {
+        use serde_json::{json, Value};
+
+        let seeds: Vec<Value> = pda["seeds"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|seed| match seed["kind"].as_str() {
+                Some("account") => json!({ "kind": "account", "account": seed["path"], "path": seed["path"] }),
+                Some("arg") => json!({ "kind": "arg", "arg": seed["path"], "path": seed["path"] }),
+                _ => json!({ "kind": "const", "type": "bytes", "value": seed["value"] }),
+            })
+            .collect();
+        let program_id = pda.get("program").and_then(|program| program.get("value")).cloned().unwrap_or(Value::Null);
+        json!({ "seeds": seeds, "programId": program_id })
     }
 
     /// Extract account layouts from IDL
@@ -304,7 +495,7 @@ impl IdlParser {
     /// Convert IDL account to layout info
     fn convert_account_to_layout(
         account: &IdlAccount,
-        types: &[IdlType],
+        types: &[IdlTypeDef],
     ) -> SolanaResult<AccountLayoutInfo> {
         let mut fields = Vec::new();
 
@@ -368,7 +559,7 @@ impl IdlParser {
     /// Convert IDL field to layout field
     fn convert_field_to_layout(
         field: &IdlField,
-        types: &[IdlType],
+        types: &[IdlTypeDef],
     ) -> SolanaResult<FieldLayoutInfo> {
         let type_info = Self::get_type_info(&field.field_type, types)?;
         
@@ -382,7 +573,7 @@ impl IdlParser {
     }
 
     /// Get type information from IDL type
-    fn get_type_info(idl_type: &IdlType, _types: &[IdlType]) -> SolanaResult<TypeInfo> {
+    fn get_type_info(idl_type: &IdlType, _types: &[IdlTypeDef]) -> SolanaResult<TypeInfo> {
         match idl_type {
             IdlType::Primitive(name) => {
                 let (size, type_name) = match name.as_str() {
@@ -594,6 +785,87 @@ mod tests {
         assert_eq!(pubkey_info.type_name, "pubkey");
     }
 
+    #[test]
+    fn test_parse_idl_type_shorthand() {
+        let parse = |json: &str| serde_json::from_str::<IdlType>(json).unwrap();
+        assert!(matches!(parse(r#"{"defined": "Config"}"#), IdlType::Defined { defined } if defined == "Config"));
+        assert!(matches!(
+            parse(r#"{"array": ["u8", 32]}"#),
+            IdlType::Complex { kind: IdlTypeKind::Array { size: 32, .. } }
+        ));
+        assert!(matches!(parse(r#"{"option": {"vec": "u64"}}"#), IdlType::Complex { kind: IdlTypeKind::Option { .. } }));
+
+        // The serialized form reads back
+        let tagged = serde_json::to_string(&parse(r#"{"vec": "publicKey"}"#)).unwrap();
+        assert!(matches!(parse(&tagged), IdlType::Complex { kind: IdlTypeKind::Vec { .. } }));
+        assert!(serde_json::from_str::<IdlType>(r#"{"array": ["u8"]}"#).is_err());
+    }
+
+    /// An Anchor 0.30 IDL with a PDA, a nested type and an event
+    const SPEC_IDL: &str = r#"{
+        "address": "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM",
+        "metadata": {"name": "vault", "version": "0.1.0", "spec": "0.1.0"},
+        "instructions": [{
+            "name": "deposit",
+            "discriminator": [242, 35, 198, 137, 82, 225, 242, 182],
+            "accounts": [
+                {"name": "vault", "writable": true, "pda": {"seeds": [
+                    {"kind": "const", "value": [118, 97, 117, 108, 116]},
+                    {"kind": "account", "path": "authority"}
+                ]}},
+                {"name": "authority", "signer": true},
+                {"name": "system", "accounts": [{"name": "system_program", "address": "11111111111111111111111111111111"}]}
+            ],
+            "args": [{"name": "amount", "type": "u64"}]
+        }],
+        "accounts": [{"name": "Vault", "discriminator": [211, 8, 232, 43, 2, 152, 117, 119]}],
+        "events": [{"name": "Deposited", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8]}],
+        "types": [
+            {"name": "Vault", "type": {"kind": "struct", "fields": [
+                {"name": "authority", "type": "pubkey"},
+                {"name": "config", "type": {"defined": {"name": "Config"}}}
+            ]}},
+            {"name": "Config", "type": {"kind": "struct", "fields": [{"name": "fee_bps", "type": "u16"}]}},
+            {"name": "Deposited", "type": {"kind": "struct", "fields": [{"name": "amount", "type": "u64"}]}}
+        ]
+    }"#;
+
+    #[test]
+    fn test_parse_spec_idl() {
+        let value: serde_json::Value = serde_json::from_str(SPEC_IDL).unwrap();
+        assert_eq!(IdlParser::detect_format(&value), IdlFormat::Spec);
+        let legacy = serde_json::json!({"name": "vault", "programId": "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM"});
+        assert_eq!(IdlParser::detect_format(&legacy), IdlFormat::Legacy);
+
+        let idl = IdlParser::parse_idl(SPEC_IDL).unwrap();
+        assert_eq!(idl.name, "vault");
+        assert_eq!(idl.version, "0.1.0");
+        assert_eq!(idl.program_id, "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM");
+        assert_eq!(idl.metadata.unwrap().address.as_deref(), Some(idl.program_id.as_str()));
+
+        // The account keeps its discriminator and takes its layout from its type
+        assert_eq!(idl.accounts[0].discriminator, Some(vec![211, 8, 232, 43, 2, 152, 117, 119]));
+        match &idl.accounts[0].account_type {
+            IdlAccountType::Struct { fields } => {
+                assert!(matches!(&fields[1].field_type, IdlType::Defined { defined } if defined == "Config"));
+            }
+            other => panic!("expected a struct, got {:?}", other),
+        }
+
+        let accounts = &idl.instructions[0].accounts;
+        assert_eq!(accounts.len(), 3);
+        assert!(accounts[0].is_mut && !accounts[0].is_signer);
+        assert!(accounts[1].is_signer);
+        assert_eq!(accounts[2].name, "system_program");
+        assert!(matches!(&accounts[0].pda.as_ref().unwrap().seeds[1], IdlSeed::Account { account, .. } if account == "authority"));
+        assert_eq!(idl.events[0].fields[0].name, "amount");
+
+        // Accounts need a type to take their layout from
+        let mut missing = value;
+        missing["types"] = serde_json::json!([]);
+        assert!(IdlParser::parse_idl(&missing.to_string()).is_err());
+    }
+
     #[test]
     fn test_invalid_idl_parsing() {
         let invalid_json = "{ invalid json }";
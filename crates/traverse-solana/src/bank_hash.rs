@@ -0,0 +1,325 @@
+//! Bank-hash anchored account proofs
+//!
+//! Solana has no state trie. Instead each slot's bank hash commits to the
+//! accounts that slot wrote, through its accounts delta hash:
+//!
+//! - an account hashes to `blake3(lamports, rent_epoch, data, executable,
+//!   owner, pubkey)`, integers little-endian, or to zeros once it has no
+//!   lamports
+//! - the delta hash is a Merkle root over the hashes of the accounts written
+//!   in the slot, sorted by pubkey, where each node is the `sha256` of up to
+//!   [`MERKLE_FANOUT`] children
+//! - the bank hash is `sha256(parent_bank_hash, accounts_delta_hash,
+//!   signature_count, last_blockhash)`, hashed again with the epoch accounts
+//!   hash in the slot that includes it
+//!
+//! Validators vote on bank hashes, so an account proven into one is anchored
+//! to consensus rather than to whatever an RPC node returned. RPC does not
+//! serve delta hash proofs: the slot's account hashes must come from a
+//! Geyser plugin or ledger replay. Slots after the accounts lattice hash
+//! activation (SIMD-0215) no longer commit to a delta hash and cannot be
+//! proven this way.
+
+use crate::{SolanaAccountProof, SolanaError, SolanaResult};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{blake3, hash, pubkey::Pubkey};
+
+/// Children per node of the accounts delta Merkle tree
+pub const MERKLE_FANOUT: usize = 16;
+
+/// Hash of an account as it is committed to in the delta hash
+pub fn account_hash(
+    pubkey: &[u8; 32],
+    lamports: u64,
+    rent_epoch: u64,
+    data: &[u8],
+    executable: bool,
+    owner: &[u8; 32],
+) -> [u8; 32] {
+    if lamports == 0 {
+        return [0u8; 32];
+    }
+    blake3::hashv(&[
+        &lamports.to_le_bytes(),
+        &rent_epoch.to_le_bytes(),
+        data,
+        &[executable as u8],
+        owner,
+        pubkey,
+    ])
+    .to_bytes()
+}
+
+fn pubkey_bytes(address: &str) -> SolanaResult<[u8; 32]> {
+    address
+        .parse::<Pubkey>()
+        .map(|pubkey| pubkey.to_bytes())
+        .map_err(|e| SolanaError::AddressParsingError(format!("Invalid address {}: {}", address, e)))
+}
+
+/// Decode a base58 hash, as RPC and explorers print bank hashes
+pub fn parse_hash(hash: &str) -> SolanaResult<[u8; 32]> {
+    hash.parse::<hash::Hash>().map(|hash| hash.to_bytes()).map_err(SolanaError::base58)
+}
+
+/// Hash of a fetched account
+pub fn account_proof_hash(proof: &SolanaAccountProof, executable: bool) -> SolanaResult<[u8; 32]> {
+    Ok(account_hash(
+        &pubkey_bytes(&proof.address)?,
+        proof.lamports,
+        proof.rent_epoch,
+        &proof.data,
+        executable,
+        &pubkey_bytes(&proof.owner)?,
+    ))
+}
+
+fn merkle_node(children: &[[u8; 32]]) -> [u8; 32] {
+    let parts: Vec<&[u8]> = children.iter().map(|child| child.as_slice()).collect();
+    hash::hashv(&parts).to_bytes()
+}
+
+/// One level of a delta hash proof: the node's siblings and itself, and its position among them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleLevel {
+    pub children: Vec<[u8; 32]>,
+    pub position: usize,
+}
+
+/// Accounts delta hash of a slot from the hashes of the accounts it wrote, in pubkey order
+pub fn accounts_delta_hash(account_hashes: &[[u8; 32]]) -> [u8; 32] {
+    if account_hashes.is_empty() {
+        return hash::hashv(&[]).to_bytes();
+    }
+    let mut level: Vec<[u8; 32]> = account_hashes.chunks(MERKLE_FANOUT).map(merkle_node).collect();
+    while level.len() > 1 {
+        level = level.chunks(MERKLE_FANOUT).map(merkle_node).collect();
+    }
+    level[0]
+}
+
+/// Proof that the account at `index` of a slot's account hashes is in its delta hash
+pub fn delta_hash_proof(account_hashes: &[[u8; 32]], index: usize) -> SolanaResult<Vec<MerkleLevel>> {
+    if index >= account_hashes.len() {
+        return Err(SolanaError::ProofVerificationFailed(format!(
+            "Account {} is not among the slot's {} written accounts",
+            index,
+            account_hashes.len()
+        )));
+    }
+    let mut levels = Vec::new();
+    let mut level = account_hashes.to_vec();
+    let mut position = index;
+    // The leaves are always hashed at least once, even when there is one chunk
+    loop {
+        let chunk = position / MERKLE_FANOUT;
+        let start = chunk * MERKLE_FANOUT;
+        let end = (start + MERKLE_FANOUT).min(level.len());
+        levels.push(MerkleLevel { children: level[start..end].to_vec(), position: position - start });
+        level = level.chunks(MERKLE_FANOUT).map(merkle_node).collect();
+        position = chunk;
+        if level.len() == 1 {
+            return Ok(levels);
+        }
+    }
+}
+
+/// Root a leaf and its delta hash proof hash to
+pub fn delta_hash_root(leaf: &[u8; 32], levels: &[MerkleLevel]) -> SolanaResult<[u8; 32]> {
+    levels.iter().try_fold(*leaf, |node, level| {
+        if level.children.len() > MERKLE_FANOUT || level.children.get(level.position) != Some(&node) {
+            return Err(SolanaError::ProofVerificationFailed(
+                "Delta hash proof level does not contain the node below it".to_string(),
+            ));
+        }
+        Ok(merkle_node(&level.children))
+    })
+}
+
+/// Everything a slot's bank hash is computed from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BankHashComponents {
+    pub parent_bank_hash: [u8; 32],
+    pub accounts_delta_hash: [u8; 32],
+    pub signature_count: u64,
+    pub last_blockhash: [u8; 32],
+    /// Present only in the slot that folds the epoch accounts hash in
+    pub epoch_accounts_hash: Option<[u8; 32]>,
+}
+
+impl BankHashComponents {
+    /// The bank hash
+    pub fn hash(&self) -> [u8; 32] {
+        let bank_hash = hash::hashv(&[
+            &self.parent_bank_hash,
+            &self.accounts_delta_hash,
+            &self.signature_count.to_le_bytes(),
+            &self.last_blockhash,
+        ])
+        .to_bytes();
+        match &self.epoch_accounts_hash {
+            Some(epoch_accounts_hash) => hash::hashv(&[&bank_hash, epoch_accounts_hash]).to_bytes(),
+            None => bank_hash,
+        }
+    }
+}
+
+/// An account proven into a slot's bank hash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BankHashProof {
+    pub slot: u64,
+    pub account: SolanaAccountProof,
+    pub executable: bool,
+    /// From the account's hash up to the accounts delta hash
+    pub delta_proof: Vec<MerkleLevel>,
+    pub components: BankHashComponents,
+}
+
+impl BankHashProof {
+    /// Prove `account` from the hashes of every account written in its slot
+    ///
+    /// `account_hashes` pairs each written account's pubkey with its hash;
+    /// they are sorted here, as the delta hash requires.
+    pub fn new(
+        account: SolanaAccountProof,
+        executable: bool,
+        mut account_hashes: Vec<([u8; 32], [u8; 32])>,
+        components: BankHashComponents,
+    ) -> SolanaResult<Self> {
+        let leaf = account_proof_hash(&account, executable)?;
+        let pubkey = pubkey_bytes(&account.address)?;
+        account_hashes.sort_by_key(|(pubkey, _)| *pubkey);
+        // Zero-lamport accounts all hash to zeros, so find the account by its key
+        let index = account_hashes
+            .iter()
+            .position(|(key, hash)| *key == pubkey && *hash == leaf)
+            .ok_or_else(|| {
+                SolanaError::ProofVerificationFailed(format!(
+                    "Account {} as fetched was not written in slot {}",
+                    account.address, account.slot
+                ))
+            })?;
+        let hashes: Vec<[u8; 32]> = account_hashes.into_iter().map(|(_, hash)| hash).collect();
+        if accounts_delta_hash(&hashes) != components.accounts_delta_hash {
+            return Err(SolanaError::ProofVerificationFailed(format!(
+                "Account hashes do not produce the accounts delta hash of slot {}",
+                account.slot
+            )));
+        }
+        Ok(Self {
+            slot: account.slot,
+            delta_proof: delta_hash_proof(&hashes, index)?,
+            account,
+            executable,
+            components,
+        })
+    }
+
+    /// The bank hash the account is proven into
+    pub fn bank_hash(&self) -> [u8; 32] {
+        self.components.hash()
+    }
+
+    /// Check the account hashes up to the delta hash, and return the bank hash it is committed to
+    pub fn verify(&self) -> SolanaResult<[u8; 32]> {
+        let leaf = account_proof_hash(&self.account, self.executable)?;
+        if delta_hash_root(&leaf, &self.delta_proof)? != self.components.accounts_delta_hash {
+            return Err(SolanaError::ProofVerificationFailed(format!(
+                "Account {} does not hash to the accounts delta hash of slot {}",
+                self.account.address, self.slot
+            )));
+        }
+        Ok(self.bank_hash())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(address: [u8; 32], lamports: u64) -> SolanaAccountProof {
+        SolanaAccountProof {
+            address: Pubkey::new_from_array(address).to_string(),
+            data: vec![7; 40],
+            data_len: 40,
+            owner: Pubkey::new_from_array([9; 32]).to_string(),
+            lamports,
+            rent_epoch: u64::MAX,
+            slot: 300_000_000,
+            block_hash: String::new(),
+            signature: None,
+        }
+    }
+
+    fn components(accounts_delta_hash: [u8; 32]) -> BankHashComponents {
+        BankHashComponents {
+            parent_bank_hash: [1; 32],
+            accounts_delta_hash,
+            signature_count: 1_234,
+            last_blockhash: [2; 32],
+            epoch_accounts_hash: None,
+        }
+    }
+
+    #[test]
+    fn test_account_hash() {
+        assert_eq!(account_hash(&[1; 32], 0, 0, &[5], false, &[2; 32]), [0; 32]);
+        let hashed = account_hash(&[1; 32], 10, 0, &[5], false, &[2; 32]);
+        assert_ne!(hashed, account_hash(&[1; 32], 10, 0, &[5], true, &[2; 32]));
+        assert_eq!(account_proof_hash(&account([3; 32], 10), false).unwrap(), account_hash(
+            &[3; 32],
+            10,
+            u64::MAX,
+            &[7; 40],
+            false,
+            &[9; 32],
+        ));
+    }
+
+    #[test]
+    fn test_delta_hash_proofs() {
+        // A single chunk is still hashed once
+        assert_eq!(accounts_delta_hash(&[[5; 32]]), merkle_node(&[[5; 32]]));
+
+        // 300 leaves make a three-level tree: 19 chunks, then 2, then the root
+        let hashes: Vec<[u8; 32]> = (0..300u32).map(|i| hash::hashv(&[&i.to_le_bytes()]).to_bytes()).collect();
+        let root = accounts_delta_hash(&hashes);
+        for index in [0, 17, 299] {
+            let proof = delta_hash_proof(&hashes, index).unwrap();
+            assert_eq!(proof.len(), 3);
+            assert_eq!(delta_hash_root(&hashes[index], &proof).unwrap(), root);
+        }
+        let proof = delta_hash_proof(&hashes, 17).unwrap();
+        assert!(delta_hash_root(&hashes[16], &proof).is_err());
+        assert!(delta_hash_proof(&hashes, 300).is_err());
+    }
+
+    #[test]
+    fn test_bank_hash_proof() {
+        let written = account([3; 32], 10);
+        let mut account_hashes: Vec<([u8; 32], [u8; 32])> =
+            (0..40u8).map(|i| ([i.wrapping_mul(7); 32], [i.wrapping_add(100); 32])).collect();
+        account_hashes.retain(|(pubkey, _)| *pubkey != [3; 32]);
+        account_hashes.push(([3; 32], account_proof_hash(&written, false).unwrap()));
+        let mut sorted = account_hashes.clone();
+        sorted.sort_by_key(|(pubkey, _)| *pubkey);
+        let delta = accounts_delta_hash(&sorted.iter().map(|(_, hash)| *hash).collect::<Vec<_>>());
+
+        let proof = BankHashProof::new(written.clone(), false, account_hashes.clone(), components(delta)).unwrap();
+        assert_eq!(proof.verify().unwrap(), components(delta).hash());
+        let printed = hash::Hash::new_from_array(proof.bank_hash()).to_string();
+        assert_eq!(parse_hash(&printed).unwrap(), proof.bank_hash());
+        assert!(parse_hash("not a hash").is_err());
+
+        let mut with_epoch_hash = components(delta);
+        with_epoch_hash.epoch_accounts_hash = Some([4; 32]);
+        assert_ne!(with_epoch_hash.hash(), components(delta).hash());
+
+        // Data the slot did not write, or a delta hash the accounts do not produce
+        let mut tampered = proof.clone();
+        tampered.account.data[0] = 8;
+        assert!(tampered.verify().is_err());
+        assert!(BankHashProof::new(account([3; 32], 11), false, account_hashes.clone(), components(delta)).is_err());
+        assert!(BankHashProof::new(written, false, account_hashes, components([0; 32])).is_err());
+    }
+}
@@ -0,0 +1,364 @@
+//! Borsh decoding of account data driven by IDL types
+//!
+//! Anchor serializes accounts with Borsh after an 8-byte discriminator.
+//! [`BorshDecoder`] walks account data with the IDL's type definitions, so a
+//! `u64` comes back as a number, a `Pubkey` as a key and a `String` as text
+//! instead of a window of raw bytes. Variable-length fields are why fixed
+//! offsets stop working after the first `String` or `Vec`; decoding reads
+//! each length prefix in turn.
+
+use crate::anchor::{IdlAccountType, IdlEnumFields, IdlField, IdlType, IdlTypeDef, IdlTypeKind, SolanaIdl};
+use crate::{SolanaError, SolanaResult};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// Nesting of defined types decoding gives up at, against self-referential IDLs
+const MAX_DEPTH: usize = 32;
+
+/// A value decoded from Borsh, typed by its IDL type
+#[derive(Debug, Clone, PartialEq)]
+pub enum BorshValue {
+    Bool(bool),
+    U8(u8),
+    I8(i8),
+    U16(u16),
+    I16(i16),
+    U32(u32),
+    I32(i32),
+    U64(u64),
+    I64(i64),
+    U128(u128),
+    I128(i128),
+    F32(f32),
+    F64(f64),
+    Pubkey([u8; 32]),
+    String(String),
+    Bytes(Vec<u8>),
+    Option(Option<Box<BorshValue>>),
+    /// `Vec` and fixed-size arrays
+    Seq(Vec<BorshValue>),
+    /// Fields in declaration order
+    Struct(Vec<(String, BorshValue)>),
+    /// Tuple variant fields are named by position
+    Enum { variant: String, fields: Vec<(String, BorshValue)> },
+}
+
+impl BorshValue {
+    /// Field `name` of a struct or enum variant
+    pub fn field(&self, name: &str) -> Option<&BorshValue> {
+        match self {
+            BorshValue::Struct(fields) | BorshValue::Enum { fields, .. } => {
+                fields.iter().find(|(field, _)| field == name).map(|(_, value)| value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Value at a dotted field path such as `config.fee`
+    pub fn path(&self, path: &str) -> Option<&BorshValue> {
+        path.split('.').try_fold(self, |value, name| value.field(name))
+    }
+
+    /// JSON rendering: 128-bit integers as decimal strings, keys in base58, bytes in hex
+    pub fn to_json(&self) -> Value {
+        match self {
+            BorshValue::Bool(v) => Value::from(*v),
+            BorshValue::U8(v) => Value::from(*v),
+            BorshValue::I8(v) => Value::from(*v),
+            BorshValue::U16(v) => Value::from(*v),
+            BorshValue::I16(v) => Value::from(*v),
+            BorshValue::U32(v) => Value::from(*v),
+            BorshValue::I32(v) => Value::from(*v),
+            BorshValue::U64(v) => Value::from(*v),
+            BorshValue::I64(v) => Value::from(*v),
+            BorshValue::U128(v) => Value::from(v.to_string()),
+            BorshValue::I128(v) => Value::from(v.to_string()),
+            BorshValue::F32(v) => Value::from(*v),
+            BorshValue::F64(v) => Value::from(*v),
+            BorshValue::Pubkey(key) => Value::from(solana_sdk::pubkey::Pubkey::new_from_array(*key).to_string()),
+            BorshValue::String(v) => Value::from(v.as_str()),
+            BorshValue::Bytes(v) => Value::from(format!("0x{}", hex::encode(v))),
+            BorshValue::Option(v) => v.as_ref().map_or(Value::Null, |inner| inner.to_json()),
+            BorshValue::Seq(items) => Value::Array(items.iter().map(BorshValue::to_json).collect()),
+            BorshValue::Struct(fields) => fields_json(fields),
+            BorshValue::Enum { variant, fields } if fields.is_empty() => Value::from(variant.as_str()),
+            BorshValue::Enum { variant, fields } => serde_json::json!({ variant.as_str(): fields_json(fields) }),
+        }
+    }
+}
+
+fn fields_json(fields: &[(String, BorshValue)]) -> Value {
+    Value::Object(fields.iter().map(|(name, value)| (name.clone(), value.to_json())).collect())
+}
+
+/// Decodes account data with an IDL's account and type definitions
+pub struct BorshDecoder<'a> {
+    idl: &'a SolanaIdl,
+}
+
+impl<'a> BorshDecoder<'a> {
+    pub fn new(idl: &'a SolanaIdl) -> Self {
+        Self { idl }
+    }
+
+    /// Anchor discriminator of `account`: the IDL's, or `sha256("account:<name>")[..8]`
+    pub fn discriminator(account: &str, declared: Option<&[u8]>) -> Vec<u8> {
+        match declared {
+            Some(declared) => declared.to_vec(),
+            None => Sha256::digest(format!("account:{}", account).as_bytes())[..8].to_vec(),
+        }
+    }
+
+    /// Decode the data of an `account_name` account, discriminator included
+    ///
+    /// The data must start with the account's discriminator. Bytes after the
+    /// decoded fields are allowed, since accounts are often allocated larger
+    /// than their contents.
+    pub fn decode_account(&self, account_name: &str, data: &[u8]) -> SolanaResult<BorshValue> {
+        let account = self
+            .idl
+            .accounts
+            .iter()
+            .find(|account| account.name == account_name)
+            .ok_or_else(|| SolanaError::InvalidQuery(format!("Account '{}' is not in the IDL", account_name)))?;
+        let discriminator = Self::discriminator(&account.name, account.discriminator.as_deref());
+        let body = data.strip_prefix(discriminator.as_slice()).ok_or_else(|| {
+            SolanaError::InvalidAccountData(format!("Data does not start with the {} discriminator", account_name))
+        })?;
+        let mut input = body;
+        self.decode_definition(&account.account_type, &mut input, 0)
+    }
+
+    /// Decode one value of `ty` from the front of `input`, advancing it
+    pub fn decode(&self, ty: &IdlType, input: &mut &[u8]) -> SolanaResult<BorshValue> {
+        self.decode_type(ty, input, 0)
+    }
+
+    fn type_def(&self, name: &str) -> SolanaResult<&IdlTypeDef> {
+        self.idl
+            .types
+            .iter()
+            .find(|def| def.name == name)
+            .ok_or_else(|| SolanaError::InvalidIdl(format!("Type '{}' is not defined in the IDL", name)))
+    }
+
+    fn decode_definition(&self, def: &IdlAccountType, input: &mut &[u8], depth: usize) -> SolanaResult<BorshValue> {
+        match def {
+            IdlAccountType::Struct { fields } => Ok(BorshValue::Struct(self.decode_fields(fields, input, depth)?)),
+            IdlAccountType::Enum { variants } => {
+                let index = take::<1>(input)?[0] as usize;
+                let variant = variants.get(index).ok_or_else(|| {
+                    SolanaError::InvalidAccountData(format!("Enum variant {} out of {}", index, variants.len()))
+                })?;
+                let fields = match &variant.fields {
+                    None => Vec::new(),
+                    Some(IdlEnumFields::Named(fields)) => self.decode_fields(fields, input, depth)?,
+                    Some(IdlEnumFields::Tuple(types)) => types
+                        .iter()
+                        .enumerate()
+                        .map(|(i, ty)| Ok((i.to_string(), self.decode_type(ty, input, depth)?)))
+                        .collect::<SolanaResult<_>>()?,
+                };
+                Ok(BorshValue::Enum { variant: variant.name.clone(), fields })
+            }
+        }
+    }
+
+    fn decode_fields(&self, fields: &[IdlField], input: &mut &[u8], depth: usize) -> SolanaResult<Vec<(String, BorshValue)>> {
+        fields
+            .iter()
+            .map(|field| Ok((field.name.clone(), self.decode_type(&field.field_type, input, depth)?)))
+            .collect()
+    }
+
+    fn decode_type(&self, ty: &IdlType, input: &mut &[u8], depth: usize) -> SolanaResult<BorshValue> {
+        if depth > MAX_DEPTH {
+            return Err(SolanaError::InvalidIdl("Types nest too deeply to decode".into()));
+        }
+        match ty {
+            IdlType::Primitive(name) => decode_primitive(name, input),
+            IdlType::Defined { defined } => {
+                let def = self.type_def(defined)?;
+                self.decode_definition(&def.type_def, input, depth + 1)
+            }
+            IdlType::Complex { kind } => match kind {
+                IdlTypeKind::Option { inner } => match take::<1>(input)?[0] {
+                    0 => Ok(BorshValue::Option(None)),
+                    1 => Ok(BorshValue::Option(Some(Box::new(self.decode_type(inner, input, depth + 1)?)))),
+                    tag => Err(SolanaError::InvalidAccountData(format!("Option tag {}", tag))),
+                },
+                IdlTypeKind::Vec { element } => {
+                    let len = u32::from_le_bytes(take(input)?) as usize;
+                    // Every element takes a byte or more, so the length is bounded by the input
+                    let mut items = Vec::with_capacity(len.min(input.len()));
+                    for _ in 0..len {
+                        items.push(self.decode_type(element, input, depth + 1)?);
+                    }
+                    Ok(BorshValue::Seq(items))
+                }
+                IdlTypeKind::Array { element, size } => (0..*size)
+                    .map(|_| self.decode_type(element, input, depth + 1))
+                    .collect::<SolanaResult<_>>()
+                    .map(BorshValue::Seq),
+                IdlTypeKind::Struct { fields } => {
+                    Ok(BorshValue::Struct(self.decode_fields(fields, input, depth + 1)?))
+                }
+                IdlTypeKind::Enum { variants } => {
+                    self.decode_definition(&IdlAccountType::Enum { variants: variants.clone() }, input, depth + 1)
+                }
+            },
+        }
+    }
+}
+
+fn decode_primitive(name: &str, input: &mut &[u8]) -> SolanaResult<BorshValue> {
+    Ok(match name {
+        "bool" => match take::<1>(input)?[0] {
+            0 => BorshValue::Bool(false),
+            1 => BorshValue::Bool(true),
+            other => return Err(SolanaError::InvalidAccountData(format!("bool byte {}", other))),
+        },
+        "u8" => BorshValue::U8(take::<1>(input)?[0]),
+        "i8" => BorshValue::I8(i8::from_le_bytes(take(input)?)),
+        "u16" => BorshValue::U16(u16::from_le_bytes(take(input)?)),
+        "i16" => BorshValue::I16(i16::from_le_bytes(take(input)?)),
+        "u32" => BorshValue::U32(u32::from_le_bytes(take(input)?)),
+        "i32" => BorshValue::I32(i32::from_le_bytes(take(input)?)),
+        "u64" => BorshValue::U64(u64::from_le_bytes(take(input)?)),
+        "i64" => BorshValue::I64(i64::from_le_bytes(take(input)?)),
+        "u128" => BorshValue::U128(u128::from_le_bytes(take(input)?)),
+        "i128" => BorshValue::I128(i128::from_le_bytes(take(input)?)),
+        "f32" => BorshValue::F32(f32::from_le_bytes(take(input)?)),
+        "f64" => BorshValue::F64(f64::from_le_bytes(take(input)?)),
+        "publicKey" | "pubkey" => BorshValue::Pubkey(take(input)?),
+        "string" => {
+            let bytes = take_prefixed(input)?;
+            BorshValue::String(
+                String::from_utf8(bytes).map_err(|_| SolanaError::InvalidAccountData("String is not UTF-8".into()))?,
+            )
+        }
+        "bytes" => BorshValue::Bytes(take_prefixed(input)?),
+        _ => return Err(SolanaError::InvalidIdl(format!("Unknown primitive type: {}", name))),
+    })
+}
+
+fn take<const N: usize>(input: &mut &[u8]) -> SolanaResult<[u8; N]> {
+    if input.len() < N {
+        return Err(SolanaError::InvalidAccountData(format!("Account data ends {} bytes short", N - input.len())));
+    }
+    let (head, rest) = input.split_at(N);
+    *input = rest;
+    Ok(head.try_into().expect("split at N"))
+}
+
+/// A `u32`-length-prefixed byte string
+fn take_prefixed(input: &mut &[u8]) -> SolanaResult<Vec<u8>> {
+    let len = u32::from_le_bytes(take(input)?) as usize;
+    if input.len() < len {
+        return Err(SolanaError::InvalidAccountData(format!(
+            "Length prefix {} exceeds the {} bytes left",
+            len,
+            input.len()
+        )));
+    }
+    let (head, rest) = input.split_at(len);
+    *input = rest;
+    Ok(head.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anchor::IdlParser;
+
+    fn idl() -> SolanaIdl {
+        IdlParser::parse_idl(
+            r#"{
+            "version": "0.1.0",
+            "name": "vault",
+            "programId": "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM",
+            "instructions": [],
+            "accounts": [{
+                "name": "Vault",
+                "type": {"kind": "struct", "fields": [
+                    {"name": "authority", "type": "publicKey"},
+                    {"name": "label", "type": "string"},
+                    {"name": "config", "type": {"defined": "Config"}},
+                    {"name": "balances", "type": {"vec": "u64"}},
+                    {"name": "delegate", "type": {"option": "publicKey"}},
+                    {"name": "status", "type": {"defined": "Status"}}
+                ]}
+            }],
+            "types": [
+                {"name": "Config", "type": {"kind": "struct", "fields": [
+                    {"name": "fee_bps", "type": "u16"},
+                    {"name": "offset", "type": "i64"}
+                ]}},
+                {"name": "Status", "type": {"kind": "enum", "variants": [
+                    {"name": "Active"},
+                    {"name": "Frozen", "fields": [{"name": "until", "type": "i64"}]}
+                ]}}
+            ]
+        }"#,
+        )
+        .unwrap()
+    }
+
+    fn vault_data() -> Vec<u8> {
+        let mut data = BorshDecoder::discriminator("Vault", None);
+        data.extend_from_slice(&[7u8; 32]);
+        data.extend_from_slice(&5u32.to_le_bytes());
+        data.extend_from_slice(b"alpha");
+        data.extend_from_slice(&30u16.to_le_bytes());
+        data.extend_from_slice(&(-5i64).to_le_bytes());
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&100u64.to_le_bytes());
+        data.extend_from_slice(&200u64.to_le_bytes());
+        data.push(0);
+        data.push(1);
+        data.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        // Unused allocation after the fields
+        data.extend_from_slice(&[0u8; 16]);
+        data
+    }
+
+    #[test]
+    fn test_decode_account() {
+        let idl = idl();
+        let vault = BorshDecoder::new(&idl).decode_account("Vault", &vault_data()).unwrap();
+
+        assert_eq!(vault.field("authority"), Some(&BorshValue::Pubkey([7u8; 32])));
+        assert_eq!(vault.field("label"), Some(&BorshValue::String("alpha".to_string())));
+        // Fields after a string and a vector still land where they are
+        assert_eq!(vault.path("config.fee_bps"), Some(&BorshValue::U16(30)));
+        assert_eq!(vault.path("config.offset"), Some(&BorshValue::I64(-5)));
+        assert_eq!(vault.field("balances"), Some(&BorshValue::Seq(vec![BorshValue::U64(100), BorshValue::U64(200)])));
+        assert_eq!(vault.field("delegate"), Some(&BorshValue::Option(None)));
+        assert_eq!(vault.path("status.until"), Some(&BorshValue::I64(1_700_000_000)));
+
+        let json = vault.to_json();
+        assert_eq!(json["label"], "alpha");
+        assert_eq!(json["balances"][1], 200);
+        assert_eq!(json["status"]["Frozen"]["until"], 1_700_000_000);
+    }
+
+    #[test]
+    fn test_decode_rejects_bad_data() {
+        let idl = idl();
+        let decoder = BorshDecoder::new(&idl);
+        let data = vault_data();
+
+        let mut wrong_account = data.clone();
+        wrong_account[0] ^= 1;
+        assert!(decoder.decode_account("Vault", &wrong_account).is_err());
+        assert!(decoder.decode_account("Missing", &data).is_err());
+
+        // Truncated inside the balances vector
+        assert!(decoder.decode_account("Vault", &data[..8 + 32 + 9 + 10 + 4 + 8]).is_err());
+
+        // A string length past the end of the data
+        let mut long_label = data;
+        long_label[40..44].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(decoder.decode_account("Vault", &long_label).is_err());
+    }
+}
@@ -0,0 +1,352 @@
+//! Concurrent Merkle tree (SPL account compression) proofs
+//!
+//! Compressed state such as Bubblegum NFTs lives in the leaves of a
+//! concurrent Merkle tree; only the tree account is on chain. A tree account
+//! holds a 56-byte header, a buffer of recent change logs whose roots a proof
+//! may be checked against, the rightmost path, and a canopy: the tree's top
+//! levels, cached so instructions can take proofs without them. Nodes hash
+//! with keccak256 of the left child then the right, and untouched subtrees
+//! are the empty nodes built up from a zero leaf.
+//!
+//! Leaves and proofs come from an indexer's `getAssetProof`.
+//! [`ConcurrentMerkleTree::complete_proof`] restores the levels a
+//! canopy-truncated proof leaves out, and
+//! [`SolanaProofFetcher::fetch_asset_proof_over_http`](crate::SolanaProofFetcher::fetch_asset_proof_over_http)
+//! checks an indexer's proof against the tree account it fetches alongside.
+
+use crate::{SolanaAccountProof, SolanaError, SolanaResult};
+use serde::{Deserialize, Serialize};
+use solana_sdk::keccak;
+
+/// SPL account compression program
+pub const ACCOUNT_COMPRESSION_PROGRAM_ID: &str = "cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK";
+
+/// Size of a tree account's header
+pub const TREE_HEADER_SIZE: usize = 56;
+/// Deepest tree the program supports
+pub const MAX_TREE_DEPTH: u32 = 30;
+
+/// keccak256 of `parts` concatenated
+fn hashv(parts: &[&[u8]]) -> [u8; 32] {
+    keccak::hashv(parts).to_bytes()
+}
+
+/// Root of an empty subtree `level` levels tall
+pub fn empty_node(level: u32) -> [u8; 32] {
+    (0..level).fold([0u8; 32], |node, _| hashv(&[&node, &node]))
+}
+
+/// Hash of a Bubblegum `LeafSchema::V1` leaf
+pub fn hash_leaf_v1(
+    asset_id: &[u8; 32],
+    owner: &[u8; 32],
+    delegate: &[u8; 32],
+    nonce: u64,
+    data_hash: &[u8; 32],
+    creator_hash: &[u8; 32],
+) -> [u8; 32] {
+    hashv(&[&[1], asset_id, owner, delegate, &nonce.to_le_bytes(), data_hash, creator_hash])
+}
+
+/// Root a leaf and its proof hash to, from the leaf's sibling upwards
+pub fn compute_root(leaf: &[u8; 32], leaf_index: u32, proof: &[[u8; 32]]) -> [u8; 32] {
+    proof.iter().enumerate().fold(*leaf, |node, (level, sibling)| {
+        if (leaf_index >> level) & 1 == 0 {
+            hashv(&[&node, sibling])
+        } else {
+            hashv(&[sibling, &node])
+        }
+    })
+}
+
+/// A decoded concurrent Merkle tree account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConcurrentMerkleTree {
+    pub max_depth: u32,
+    pub max_buffer_size: u32,
+    pub authority: [u8; 32],
+    pub creation_slot: u64,
+    pub sequence_number: u64,
+    /// Roots in the change log buffer, newest first
+    pub roots: Vec<[u8; 32]>,
+    /// Cached top levels below the root, breadth first
+    pub canopy: Vec<[u8; 32]>,
+}
+
+impl ConcurrentMerkleTree {
+    /// Decode a tree account's data
+    pub fn parse(data: &[u8]) -> SolanaResult<Self> {
+        let invalid = |message: String| SolanaError::InvalidAccountData(message);
+        if data.len() < TREE_HEADER_SIZE {
+            return Err(invalid(format!("{} bytes is too short for a Merkle tree account", data.len())));
+        }
+        // `CompressionAccountType::ConcurrentMerkleTree`, then header version V1
+        if data[0] != 1 || data[1] != 0 {
+            return Err(invalid(format!("Not a V1 concurrent Merkle tree: type {}, version {}", data[0], data[1])));
+        }
+        let u32_at = |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().expect("4 bytes"));
+        let u64_at = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().expect("8 bytes"));
+        let max_buffer_size = u32_at(2);
+        let max_depth = u32_at(6);
+        if max_depth == 0 || max_depth > MAX_TREE_DEPTH || max_buffer_size == 0 {
+            return Err(invalid(format!("Unsupported tree of depth {} and buffer {}", max_depth, max_buffer_size)));
+        }
+
+        // Sequence number, active index and buffer size, then the change logs
+        // (root, path, index and padding) and the rightmost path
+        let depth = max_depth as usize;
+        let change_log_size = 32 + 32 * depth + 8;
+        let logs_start = TREE_HEADER_SIZE + 24;
+        let canopy_start = logs_start + max_buffer_size as usize * change_log_size + 32 * depth + 40;
+        if data.len() < canopy_start || !(data.len() - canopy_start).is_multiple_of(32) {
+            return Err(invalid(format!(
+                "{} bytes does not fit a tree of depth {} and buffer {} with a canopy",
+                data.len(),
+                max_depth,
+                max_buffer_size
+            )));
+        }
+
+        let active_index = u64_at(TREE_HEADER_SIZE + 8);
+        let buffer_size = u64_at(TREE_HEADER_SIZE + 16);
+        if active_index >= u64::from(max_buffer_size) || buffer_size > u64::from(max_buffer_size) {
+            return Err(invalid(format!("Change log index {} or size {} exceeds the buffer", active_index, buffer_size)));
+        }
+        let roots = (0..buffer_size)
+            .map(|age| {
+                let index = (active_index + u64::from(max_buffer_size) - age) % u64::from(max_buffer_size);
+                let offset = logs_start + index as usize * change_log_size;
+                data[offset..offset + 32].try_into().expect("32 bytes")
+            })
+            .collect();
+
+        let tree = Self {
+            max_depth,
+            max_buffer_size,
+            authority: data[10..42].try_into().expect("32 bytes"),
+            creation_slot: u64_at(42),
+            sequence_number: u64_at(TREE_HEADER_SIZE),
+            roots,
+            canopy: data[canopy_start..].chunks_exact(32).map(|node| node.try_into().expect("32 bytes")).collect(),
+        };
+        tree.canopy_depth()?;
+        Ok(tree)
+    }
+
+    /// Current root
+    pub fn root(&self) -> Option<[u8; 32]> {
+        self.roots.first().copied()
+    }
+
+    /// Whether `root` is in the change log buffer, so proofs against it are still accepted
+    pub fn is_known_root(&self, root: &[u8; 32]) -> bool {
+        self.roots.contains(root)
+    }
+
+    /// Levels the canopy caches; a canopy of depth `d` holds `2^(d+1) - 2` nodes
+    pub fn canopy_depth(&self) -> SolanaResult<u32> {
+        let nodes = self.canopy.len() + 2;
+        if !nodes.is_power_of_two() {
+            return Err(SolanaError::InvalidAccountData(format!(
+                "{} canopy nodes is not a whole number of levels",
+                self.canopy.len()
+            )));
+        }
+        let depth = nodes.trailing_zeros() - 1;
+        if depth > self.max_depth {
+            return Err(SolanaError::InvalidAccountData(format!("Canopy of depth {} is deeper than its tree", depth)));
+        }
+        Ok(depth)
+    }
+
+    /// Extend a canopy-truncated proof to the tree's full depth
+    ///
+    /// The missing siblings are read from the canopy, with empty nodes where
+    /// the canopy holds zeros. A proof that is already complete comes back
+    /// unchanged.
+    pub fn complete_proof(&self, proof: &[[u8; 32]], leaf_index: u32) -> SolanaResult<Vec<[u8; 32]>> {
+        let canopy_depth = self.canopy_depth()?;
+        if proof.len() + (canopy_depth as usize) < self.max_depth as usize || proof.len() > self.max_depth as usize {
+            return Err(SolanaError::ProofVerificationFailed(format!(
+                "A proof of {} nodes does not reach the top of a depth-{} tree with a depth-{} canopy",
+                proof.len(),
+                self.max_depth,
+                canopy_depth
+            )));
+        }
+        if u64::from(leaf_index) >= 1u64 << self.max_depth {
+            return Err(SolanaError::ProofVerificationFailed(format!("Leaf {} is outside the tree", leaf_index)));
+        }
+
+        // Heap index (root 1) of the leaf's ancestor at the canopy's bottom level
+        let mut node = ((1u64 << self.max_depth) + u64::from(leaf_index)) >> (self.max_depth - canopy_depth);
+        let mut inferred = Vec::new();
+        while node > 1 {
+            // The canopy stores heap index `i` at `i - 2`
+            let sibling = (node ^ 1) as usize - 2;
+            let level = self.max_depth - (63 - node.leading_zeros());
+            inferred.push(match self.canopy[sibling] {
+                cached if cached != [0u8; 32] => cached,
+                _ => empty_node(level),
+            });
+            node >>= 1;
+        }
+        let overlap = (proof.len() + inferred.len()).saturating_sub(self.max_depth as usize);
+        Ok(proof.iter().copied().chain(inferred.into_iter().skip(overlap)).collect())
+    }
+
+    /// Verify a leaf against a root in the change log buffer
+    pub fn verify_leaf(&self, leaf: &[u8; 32], leaf_index: u32, proof: &[[u8; 32]], root: &[u8; 32]) -> SolanaResult<()> {
+        if !self.is_known_root(root) {
+            return Err(SolanaError::ProofVerificationFailed(
+                "Root is not in the tree's change log buffer".to_string(),
+            ));
+        }
+        let proof = self.complete_proof(proof, leaf_index)?;
+        if compute_root(leaf, leaf_index, &proof) != *root {
+            return Err(SolanaError::ProofVerificationFailed(format!(
+                "Leaf {} does not hash to the tree root",
+                leaf_index
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// A compressed leaf with its proof and the tree account it was checked against
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompressedLeafProof {
+    /// Tree account address
+    pub tree: String,
+    pub leaf: [u8; 32],
+    pub leaf_index: u32,
+    /// Siblings from the leaf up, to the tree's full depth
+    pub proof: Vec<[u8; 32]>,
+    pub root: [u8; 32],
+    /// Levels an instruction's proof may leave to the canopy
+    pub canopy_depth: u32,
+    /// The tree account, so the root can be tied to on-chain state
+    pub tree_proof: SolanaAccountProof,
+}
+
+impl CompressedLeafProof {
+    /// The proof as an instruction takes it, without the levels the canopy caches
+    pub fn truncated_proof(&self) -> &[[u8; 32]] {
+        let keep = self.proof.len().saturating_sub(self.canopy_depth as usize);
+        &self.proof[..keep]
+    }
+
+    /// JSON with hashes in hex, for the valence verifier
+    pub fn to_json(&self) -> serde_json::Value {
+        use base64::engine::{general_purpose::STANDARD, Engine};
+
+        serde_json::json!({
+            "tree": self.tree,
+            "leaf": hex::encode(self.leaf),
+            "leaf_index": self.leaf_index,
+            "proof": self.proof.iter().map(hex::encode).collect::<Vec<_>>(),
+            "root": hex::encode(self.root),
+            "canopy_depth": self.canopy_depth,
+            "tree_account": {
+                "data": STANDARD.encode(&self.tree_proof.data),
+                "slot": self.tree_proof.slot,
+                "block_hash": self.tree_proof.block_hash,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tree account data for `leaves` in a tree of `depth` with a `canopy_depth` canopy
+    fn tree_account(depth: u32, canopy_depth: u32, leaves: &[[u8; 32]]) -> (Vec<u8>, Vec<Vec<[u8; 32]>>) {
+        // levels[0] are the leaves, levels[depth] the root
+        let mut levels = vec![(0..1usize << depth).map(|i| leaves.get(i).copied().unwrap_or([0; 32])).collect::<Vec<_>>()];
+        for level in 0..depth as usize {
+            let next = levels[level].chunks(2).map(|pair| hashv(&[&pair[0], &pair[1]])).collect();
+            levels.push(next);
+        }
+
+        let buffer = 2u32;
+        let mut data = vec![1, 0];
+        data.extend_from_slice(&buffer.to_le_bytes());
+        data.extend_from_slice(&depth.to_le_bytes());
+        data.extend_from_slice(&[0x77; 32]);
+        data.extend_from_slice(&123u64.to_le_bytes());
+        data.extend_from_slice(&[0u8; 6]);
+        // Sequence 5 with change log 1 active and both logs filled
+        data.extend_from_slice(&5u64.to_le_bytes());
+        data.extend_from_slice(&1u64.to_le_bytes());
+        data.extend_from_slice(&2u64.to_le_bytes());
+        for root in [[0x99; 32], levels[depth as usize][0]] {
+            data.extend_from_slice(&root);
+            data.extend(std::iter::repeat(0u8).take(32 * depth as usize + 8));
+        }
+        data.extend(std::iter::repeat(0u8).take(32 * depth as usize + 40));
+        for level in (depth - canopy_depth..depth).rev() {
+            for node in &levels[level as usize] {
+                data.extend_from_slice(node);
+            }
+        }
+        (data, levels)
+    }
+
+    fn full_proof(levels: &[Vec<[u8; 32]>], index: usize) -> Vec<[u8; 32]> {
+        (0..levels.len() - 1).map(|level| levels[level][(index >> level) ^ 1]).collect()
+    }
+
+    #[test]
+    fn test_empty_nodes() {
+        assert_eq!(empty_node(0), [0; 32]);
+        assert_eq!(empty_node(2), hashv(&[&empty_node(1), &empty_node(1)]));
+        // An empty tree's root is the empty node of its depth
+        assert_eq!(compute_root(&[0; 32], 5, &(0..3).map(empty_node).collect::<Vec<_>>()), empty_node(3));
+    }
+
+    #[test]
+    fn test_verify_leaf_with_canopy() {
+        let leaves: Vec<[u8; 32]> = (1..=5u8).map(|i| hash_leaf_v1(&[i; 32], &[2; 32], &[2; 32], i as u64, &[3; 32], &[4; 32])).collect();
+        let (data, levels) = tree_account(4, 2, &leaves);
+        let tree = ConcurrentMerkleTree::parse(&data).unwrap();
+        assert_eq!(tree.canopy_depth().unwrap(), 2);
+        assert_eq!(tree.canopy.len(), 6);
+        assert_eq!(tree.roots[1], [0x99; 32]);
+        let root = tree.root().unwrap();
+        assert_eq!(root, levels[4][0]);
+
+        let proof = full_proof(&levels, 3);
+        assert_eq!(compute_root(&leaves[3], 3, &proof), root);
+        tree.verify_leaf(&leaves[3], 3, &proof, &root).unwrap();
+
+        // The canopy supplies the top two levels of a truncated proof
+        assert_eq!(tree.complete_proof(&proof[..2], 3).unwrap(), proof);
+        tree.verify_leaf(&leaves[3], 3, &proof[..2], &root).unwrap();
+
+        // The right half is empty, so its canopy nodes are empty-node hashes
+        let empty_leaf_proof = full_proof(&levels, 12);
+        assert_eq!(tree.complete_proof(&empty_leaf_proof[..2], 12).unwrap(), empty_leaf_proof);
+
+        assert!(tree.verify_leaf(&leaves[2], 3, &proof, &root).is_err());
+        assert!(tree.verify_leaf(&leaves[3], 3, &proof, &[0x98; 32]).is_err());
+        assert!(tree.complete_proof(&proof[..1], 3).is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_tree() {
+        let (data, _) = tree_account(3, 1, &[[1; 32]]);
+        assert!(ConcurrentMerkleTree::parse(&data).is_ok());
+
+        let mut wrong_type = data.clone();
+        wrong_type[0] = 2;
+        assert!(ConcurrentMerkleTree::parse(&wrong_type).is_err());
+
+        // A canopy of three nodes is not a whole number of levels
+        let mut ragged = data.clone();
+        ragged.extend_from_slice(&[0u8; 32]);
+        assert!(ConcurrentMerkleTree::parse(&ragged).is_err());
+
+        assert!(ConcurrentMerkleTree::parse(&data[..data.len() - 1]).is_err());
+    }
+}
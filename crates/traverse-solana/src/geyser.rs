@@ -0,0 +1,301 @@
+//! Streaming account updates from a Yellowstone gRPC (Geyser) endpoint
+//!
+//! An [`AccountStream`] watches accounts by address or owning program and
+//! turns the updates a Yellowstone endpoint pushes into
+//! [`SolanaAccountProof`]s as soon as their slot reaches the stream's
+//! commitment, without polling RPC. Account updates do not carry a block
+//! hash, so the stream also subscribes to block metadata and holds each
+//! slot's updates until that slot's block hash arrives. Within a slot only
+//! an account's last write is emitted, and writes older than one already
+//! seen are dropped.
+//!
+//! Only the transport needs the `yellowstone` feature. [`AccountStream::push_account`]
+//! and [`AccountStream::push_block_meta`] let a caller with its own Geyser
+//! source, such as a plugin in the same process, drive the stream directly.
+
+use std::collections::{BTreeMap, HashMap};
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::SolanaAccountProof;
+#[cfg(feature = "yellowstone")]
+use crate::{SolanaError, SolanaResult};
+
+/// Slots of updates held while waiting for their block hash
+const MAX_PENDING_SLOTS: u64 = 150;
+
+/// Commitment a stream's updates must reach before they are sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GeyserCommitment {
+    Confirmed,
+    #[default]
+    Finalized,
+}
+
+/// One account write, as a Geyser source reports it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountUpdate {
+    pub pubkey: [u8; 32],
+    pub lamports: u64,
+    pub owner: [u8; 32],
+    pub executable: bool,
+    pub rent_epoch: u64,
+    pub data: Vec<u8>,
+    /// Orders writes to the same account within a slot
+    pub write_version: u64,
+    /// Transaction that made the write, if any
+    pub txn_signature: Option<Vec<u8>>,
+}
+
+/// Accounts whose updates are streamed as proofs
+#[derive(Debug, Clone, Default)]
+pub struct AccountStream {
+    accounts: Vec<String>,
+    owners: Vec<String>,
+    commitment: GeyserCommitment,
+    /// Updates by slot, until the slot's block hash arrives
+    pending: BTreeMap<u64, Vec<AccountUpdate>>,
+    /// Slot and write version of the newest write seen per account
+    latest: HashMap<[u8; 32], (u64, u64)>,
+}
+
+impl AccountStream {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stream writes to the account at `address`
+    pub fn account(mut self, address: &str) -> Self {
+        self.accounts.push(address.to_string());
+        self
+    }
+
+    /// Stream writes to every account owned by `program`
+    pub fn owner(mut self, program: &str) -> Self {
+        self.owners.push(program.to_string());
+        self
+    }
+
+    pub fn commitment(mut self, commitment: GeyserCommitment) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    pub fn accounts(&self) -> &[String] {
+        &self.accounts
+    }
+
+    pub fn owners(&self) -> &[String] {
+        &self.owners
+    }
+
+    fn is_watched(&self, update: &AccountUpdate) -> bool {
+        let matches = |addresses: &[String], key: &[u8; 32]| {
+            addresses.iter().any(|address| address.parse::<Pubkey>().is_ok_and(|pubkey| pubkey.to_bytes() == *key))
+        };
+        matches(&self.accounts, &update.pubkey) || matches(&self.owners, &update.owner)
+    }
+
+    /// Hold a write made in `slot` until the slot's block hash arrives
+    ///
+    /// Returns whether the write was kept: writes to unwatched accounts and
+    /// writes no newer than one already seen are dropped.
+    pub fn push_account(&mut self, slot: u64, update: AccountUpdate) -> bool {
+        if !self.is_watched(&update) {
+            return false;
+        }
+        let version = (slot, update.write_version);
+        if self.latest.get(&update.pubkey).is_some_and(|latest| *latest >= version) {
+            return false;
+        }
+        self.latest.insert(update.pubkey, version);
+        self.pending.entry(slot).or_default().push(update);
+        true
+    }
+
+    /// Release `slot`'s writes with its block hash, one proof per account
+    ///
+    /// Writes still held for slots too far behind are discarded; their block
+    /// metadata is not coming.
+    pub fn push_block_meta(&mut self, slot: u64, block_hash: &str) -> Vec<SolanaAccountProof> {
+        self.pending.retain(|pending, _| *pending + MAX_PENDING_SLOTS >= slot);
+        let Some(updates) = self.pending.remove(&slot) else {
+            return Vec::new();
+        };
+
+        // Each account's last write in the slot, in the order accounts were first written
+        let mut last: Vec<AccountUpdate> = Vec::new();
+        for update in updates {
+            match last.iter_mut().find(|kept| kept.pubkey == update.pubkey) {
+                Some(kept) if kept.write_version < update.write_version => *kept = update,
+                Some(_) => {}
+                None => last.push(update),
+            }
+        }
+        last.into_iter()
+            .map(|update| SolanaAccountProof {
+                address: Pubkey::new_from_array(update.pubkey).to_string(),
+                data_len: update.data.len(),
+                data: update.data,
+                owner: Pubkey::new_from_array(update.owner).to_string(),
+                lamports: update.lamports,
+                rent_epoch: update.rent_epoch,
+                slot,
+                block_hash: block_hash.to_string(),
+                signature: update.txn_signature.map(|signature| base58::ToBase58::to_base58(signature.as_slice())),
+            })
+            .collect()
+    }
+
+    /// Yellowstone request for the watched accounts and block metadata
+    #[cfg(feature = "yellowstone")]
+    pub fn subscribe_request(&self) -> yellowstone_grpc_proto::prelude::SubscribeRequest {
+        use yellowstone_grpc_proto::prelude::{
+            CommitmentLevel, SubscribeRequest, SubscribeRequestFilterAccounts, SubscribeRequestFilterBlocksMeta,
+        };
+
+        let commitment = match self.commitment {
+            GeyserCommitment::Confirmed => CommitmentLevel::Confirmed,
+            GeyserCommitment::Finalized => CommitmentLevel::Finalized,
+        };
+        SubscribeRequest {
+            accounts: HashMap::from([(
+                "traverse".to_string(),
+                SubscribeRequestFilterAccounts {
+                    account: self.accounts.clone(),
+                    owner: self.owners.clone(),
+                    ..Default::default()
+                },
+            )]),
+            blocks_meta: HashMap::from([("traverse".to_string(), SubscribeRequestFilterBlocksMeta::default())]),
+            commitment: Some(commitment as i32),
+            ..Default::default()
+        }
+    }
+
+    /// Subscribe at `endpoint` and send a proof for each watched account write
+    ///
+    /// `x_token` authenticates with hosted endpoints. Returns once `proofs`
+    /// is closed, or with an error when the connection fails or the endpoint
+    /// ends the stream; reconnecting is up to the caller.
+    #[cfg(feature = "yellowstone")]
+    pub async fn run(
+        &mut self,
+        endpoint: &str,
+        x_token: Option<&str>,
+        proofs: tokio::sync::mpsc::Sender<SolanaAccountProof>,
+    ) -> SolanaResult<()> {
+        use futures_util::{SinkExt, StreamExt};
+        use yellowstone_grpc_client::{ClientTlsConfig, GeyserGrpcClient};
+        use yellowstone_grpc_proto::prelude::{subscribe_update::UpdateOneof, SubscribeRequest, SubscribeRequestPing};
+
+        let grpc_error = |e: &dyn std::fmt::Display| SolanaError::NetworkError(format!("Yellowstone error on {}: {}", endpoint, e));
+        let mut client = GeyserGrpcClient::build_from_shared(endpoint.to_string())
+            .map_err(|e| grpc_error(&e))?
+            .x_token(x_token.map(str::to_string))
+            .map_err(|e| grpc_error(&e))?
+            .tls_config(ClientTlsConfig::new().with_native_roots())
+            .map_err(|e| grpc_error(&e))?
+            .connect()
+            .await
+            .map_err(|e| grpc_error(&e))?;
+        let (mut requests, mut updates) =
+            client.subscribe_with_request(Some(self.subscribe_request())).await.map_err(|e| grpc_error(&e))?;
+
+        while let Some(update) = updates.next().await {
+            match update.map_err(|e| grpc_error(&e))?.update_oneof {
+                Some(UpdateOneof::Account(account)) => {
+                    let Some(info) = account.account else {
+                        continue;
+                    };
+                    let key = |bytes: Vec<u8>, what: &str| {
+                        <[u8; 32]>::try_from(bytes).map_err(|_| grpc_error(&format!("{} is not 32 bytes", what)))
+                    };
+                    self.push_account(
+                        account.slot,
+                        AccountUpdate {
+                            pubkey: key(info.pubkey, "Account pubkey")?,
+                            lamports: info.lamports,
+                            owner: key(info.owner, "Account owner")?,
+                            executable: info.executable,
+                            rent_epoch: info.rent_epoch,
+                            data: info.data,
+                            write_version: info.write_version,
+                            txn_signature: info.txn_signature,
+                        },
+                    );
+                }
+                Some(UpdateOneof::BlockMeta(meta)) => {
+                    for proof in self.push_block_meta(meta.slot, &meta.blockhash) {
+                        if proofs.send(proof).await.is_err() {
+                            return Ok(());
+                        }
+                    }
+                }
+                // Endpoints drop connections that do not answer pings
+                Some(UpdateOneof::Ping(_)) => {
+                    let pong = SubscribeRequest { ping: Some(SubscribeRequestPing { id: 1 }), ..Default::default() };
+                    requests.send(pong).await.map_err(|e| grpc_error(&e))?;
+                }
+                _ => {}
+            }
+        }
+        Err(SolanaError::NetworkError(format!("{} closed the account stream", endpoint)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OWNER: [u8; 32] = [9; 32];
+
+    fn update(pubkey: u8, write_version: u64, lamports: u64) -> AccountUpdate {
+        AccountUpdate {
+            pubkey: [pubkey; 32],
+            lamports,
+            owner: OWNER,
+            executable: false,
+            rent_epoch: u64::MAX,
+            data: vec![pubkey; 8],
+            write_version,
+            txn_signature: Some(vec![1; 64]),
+        }
+    }
+
+    #[test]
+    fn test_stream_releases_slots_on_block_meta() {
+        let watched = Pubkey::new_from_array([1; 32]).to_string();
+        let mut stream = AccountStream::new().account(&watched).commitment(GeyserCommitment::Confirmed);
+        assert_eq!(stream.accounts(), [watched.clone()]);
+
+        assert!(stream.push_account(100, update(1, 5, 10)));
+        assert!(stream.push_account(100, update(1, 7, 20)));
+        // Not watched, then older than a write already seen
+        assert!(!stream.push_account(100, update(2, 8, 30)));
+        assert!(!stream.push_account(100, update(1, 6, 40)));
+        assert!(stream.push_block_meta(99, "hash99").is_empty());
+
+        let proofs = stream.push_block_meta(100, "hash100");
+        assert_eq!(proofs.len(), 1);
+        assert_eq!(proofs[0].address, watched);
+        assert_eq!((proofs[0].lamports, proofs[0].slot), (20, 100));
+        assert_eq!(proofs[0].block_hash, "hash100");
+        assert_eq!(proofs[0].data_len, 8);
+        assert!(proofs[0].signature.is_some());
+        assert!(stream.push_block_meta(100, "hash100").is_empty());
+    }
+
+    #[test]
+    fn test_stream_by_owner_and_stale_slots() {
+        let mut stream = AccountStream::new().owner(&Pubkey::new_from_array(OWNER).to_string());
+        assert!(stream.push_account(10, update(2, 1, 1)));
+        assert!(stream.push_account(11, update(3, 1, 1)));
+        assert!(!stream.push_account(9, update(2, 9, 1)));
+
+        // Neither slot's block metadata arrived, and both are now too far behind
+        let proofs = stream.push_block_meta(12 + MAX_PENDING_SLOTS, "later");
+        assert!(proofs.is_empty());
+        assert!(stream.pending.is_empty());
+    }
+}
@@ -42,8 +42,7 @@ impl SolanaLayoutCompiler {
     /// Compile layout from IDL string (requires anchor feature)
     #[cfg(feature = "anchor")]
     pub fn compile_from_idl(&self, idl_data: &str) -> SolanaResult<SolanaLayout> {
-        let idl: SolanaIdl = serde_json::from_str(idl_data)
-            .map_err(|e| SolanaError::IdlParsingError(format!("Failed to parse IDL: {}", e)))?;
+        let idl: SolanaIdl = crate::anchor::IdlParser::parse_idl(idl_data)?;
 
         let mut accounts = HashMap::new();
         let mut instructions = HashMap::new();
@@ -8,7 +8,7 @@
 //! - `solana`: Enables Solana SDK integration (may conflict with Alloy ecosystem)
 //! - `anchor`: Enables Anchor framework support for IDL parsing
 //! - `client`: Enables HTTP client for live RPC queries
-//! - `std`: Standard library support
+//! - `std`: Standard library support, with semantic validation through `SolanaDasIndexer`
 //! - `no-std`: No standard library (embedded/wasm compatible)
 //!
 //! ## Dependency Conflicts
@@ -36,6 +36,18 @@ pub mod error;
 // Account types (always available)
 pub mod account;
 
+// SPL Token and Token-2022 layouts (always available)
+pub mod token;
+
+// Stake and vote account layouts (always available)
+pub mod native;
+
+// Metaplex Token Metadata layout (always available)
+pub mod metaplex;
+
+// Address lookup tables (always available)
+pub mod lookup_table;
+
 // Layout compiler (conditional on solana feature)
 #[cfg(feature = "solana")]
 pub mod layout;
@@ -48,10 +60,26 @@ pub mod resolver;
 #[cfg(feature = "solana")]
 pub mod proof;
 
+// Account proofs anchored to a slot's bank hash (conditional on solana feature)
+#[cfg(feature = "solana")]
+pub mod bank_hash;
+
+// Streamed account updates from Geyser (conditional on solana feature)
+#[cfg(feature = "solana")]
+pub mod geyser;
+
+// Concurrent Merkle tree proofs for compressed state (conditional on solana feature)
+#[cfg(feature = "solana")]
+pub mod compression;
+
 // Anchor IDL support (conditional on anchor feature)
 #[cfg(feature = "anchor")]
 pub mod anchor;
 
+// Borsh decoding of account data by IDL type
+#[cfg(feature = "anchor")]
+pub mod borsh;
+
 // Always export error types
 pub use error::{SolanaError, SolanaResult, Result};
 
@@ -61,26 +89,51 @@ pub use account::{
     ProgramAccount, SolanaAccount,
 };
 
+// Always export token layouts
+pub use token::{
+    builtin_field, builtin_layout, builtin_program, ExtensionEntry, ExtensionType, Token2022Layout,
+    TokenAccountKind, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID,
+};
+pub use lookup_table::{AddressLookupTable, ADDRESS_LOOKUP_TABLE_PROGRAM_ID};
+pub use metaplex::{metadata_layout, MetadataLayout, METADATA_PROGRAM_ID};
+pub use native::{stake_layout, vote_layout, VoteLayout, STAKE_PROGRAM_ID, VOTE_PROGRAM_ID};
+
 // Conditionally export Solana SDK-dependent functionality
 #[cfg(feature = "solana")]
 pub use layout::SolanaLayoutCompiler;
 
 #[cfg(feature = "solana")]
-pub use resolver::SolanaKeyResolver;
+pub use resolver::{DerivedPda, SolanaKeyResolver};
 
 #[cfg(feature = "solana")]
 pub use proof::{SolanaProofFetcher, SolanaAccountProof};
 
+#[cfg(feature = "solana")]
+pub use bank_hash::{BankHashComponents, BankHashProof};
+
+#[cfg(feature = "solana")]
+pub use geyser::{AccountStream, AccountUpdate, GeyserCommitment};
+
+#[cfg(feature = "solana")]
+pub use compression::{CompressedLeafProof, ConcurrentMerkleTree, ACCOUNT_COMPRESSION_PROGRAM_ID};
+
+// Semantic validation against asset history from a DAS RPC
+#[cfg(feature = "std")]
+pub use traverse_indexer::{IndexerService, SemanticValidator, SolanaDasIndexer, ValidationResult};
+
 // Conditionally export Anchor functionality
 #[cfg(feature = "anchor")]
 pub use anchor::{
-    SolanaIdl, IdlParser, IdlAccount, IdlInstruction, IdlType,
+    SolanaIdl, IdlParser, IdlFormat, IdlAccount, IdlInstruction, IdlType, IdlTypeDef,
     IdlField, IdlEvent, IdlError, IdlConstant, IdlAccountItem,
     IdlPda, IdlSeed, IdlAccountType, IdlEnumVariant, IdlEnumFields,
     IdlTypeKind, IdlMetadata, AccountLayoutInfo, FieldLayoutInfo,
     TypeInfo, PdaInfo,
 };
 
+#[cfg(feature = "anchor")]
+pub use borsh::{BorshDecoder, BorshValue};
+
 /// Check if Solana SDK features are available
 pub fn solana_sdk_available() -> bool {
     cfg!(feature = "solana")
@@ -261,9 +314,9 @@ mod security_tests {
                 
                 // Should either succeed safely or fail gracefully
                 match result {
-                    Ok(address) => {
+                    Ok(derived) => {
                         // If successful, result should be valid
-                        assert!(SolanaKeyResolver::validate_address(&address), 
+                        assert!(SolanaKeyResolver::validate_address(&derived.address), 
                             "PDA derivation {} should produce valid address", i);
                     }
                     Err(_) => {
@@ -580,8 +633,8 @@ mod security_tests {
                     
                     // Should handle concurrent access safely
                     match result {
-                        Ok(address) => {
-                            assert!(SolanaKeyResolver::validate_address(&address),
+                        Ok(derived) => {
+                            assert!(SolanaKeyResolver::validate_address(&derived.address),
                                 "Concurrent PDA derivation {} should produce valid address", i);
                         }
                         Err(_) => {
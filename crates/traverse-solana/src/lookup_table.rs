@@ -0,0 +1,168 @@
+//! Address lookup table accounts
+//!
+//! Versioned transactions name accounts by index into an address lookup
+//! table. A table is a 56-byte header followed by the addresses it holds,
+//! 32 bytes each, so expanding an index means reading the table's account
+//! and taking the address at that position. Queries reference an entry as
+//! `alt[<table>][<index>]`.
+
+use crate::{SolanaError, SolanaResult};
+
+/// Address lookup table program
+pub const ADDRESS_LOOKUP_TABLE_PROGRAM_ID: &str = "AddressLookupTab1e1111111111111111111111111";
+
+/// Size of a table's header; addresses start here
+pub const LOOKUP_TABLE_META_SIZE: usize = 56;
+/// Most addresses a table can hold, since indexes are a byte
+pub const LOOKUP_TABLE_MAX_ADDRESSES: usize = 256;
+
+/// A decoded address lookup table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressLookupTable {
+    /// `u64::MAX` while the table is active
+    pub deactivation_slot: u64,
+    /// Slot of the last extension; addresses it added are usable from the next slot
+    pub last_extended_slot: u64,
+    /// Number of addresses before the last extension
+    pub last_extended_slot_start_index: u8,
+    /// `None` once the table is frozen
+    pub authority: Option<[u8; 32]>,
+    pub addresses: Vec<[u8; 32]>,
+}
+
+impl AddressLookupTable {
+    /// Decode a lookup table account's data
+    pub fn parse(data: &[u8]) -> SolanaResult<Self> {
+        if data.len() < LOOKUP_TABLE_META_SIZE {
+            return Err(SolanaError::InvalidAccountData(format!(
+                "{} bytes is too short for a lookup table",
+                data.len()
+            )));
+        }
+        let u64_at = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().expect("8 bytes"));
+        // `ProgramState::LookupTable`; 0 is an uninitialized table
+        let state = u32::from_le_bytes(data[0..4].try_into().expect("4 bytes"));
+        if state != 1 {
+            return Err(SolanaError::InvalidAccountData(format!("Not an initialized lookup table: state {}", state)));
+        }
+        let authority = match data[21] {
+            0 => None,
+            1 => Some(data[22..54].try_into().expect("32 bytes")),
+            tag => return Err(SolanaError::InvalidAccountData(format!("Bad lookup table authority tag {}", tag))),
+        };
+
+        let entries = &data[LOOKUP_TABLE_META_SIZE..];
+        if !entries.len().is_multiple_of(32) || entries.len() / 32 > LOOKUP_TABLE_MAX_ADDRESSES {
+            return Err(SolanaError::InvalidAccountData(format!(
+                "{} bytes of lookup table addresses is not a whole number of at most {} addresses",
+                entries.len(),
+                LOOKUP_TABLE_MAX_ADDRESSES
+            )));
+        }
+        Ok(Self {
+            deactivation_slot: u64_at(4),
+            last_extended_slot: u64_at(12),
+            last_extended_slot_start_index: data[20],
+            authority,
+            addresses: entries.chunks_exact(32).map(|entry| entry.try_into().expect("32 bytes")).collect(),
+        })
+    }
+
+    /// Whether the table has been deactivated and may be closed
+    pub fn is_deactivated(&self) -> bool {
+        self.deactivation_slot != u64::MAX
+    }
+
+    /// Address at `index`
+    pub fn address(&self, index: u8) -> SolanaResult<[u8; 32]> {
+        self.addresses.get(index as usize).copied().ok_or_else(|| {
+            SolanaError::InvalidQuery(format!(
+                "Lookup table index {} is out of range; the table holds {} addresses",
+                index,
+                self.addresses.len()
+            ))
+        })
+    }
+
+    /// Addresses at `indexes`, in order, as a transaction's lookups expand them
+    pub fn expand(&self, indexes: &[u8]) -> SolanaResult<Vec<[u8; 32]>> {
+        indexes.iter().map(|index| self.address(*index)).collect()
+    }
+
+    /// Offset of the entry at `index` in the table's account data
+    pub fn entry_offset(index: u8) -> usize {
+        LOOKUP_TABLE_META_SIZE + index as usize * 32
+    }
+}
+
+/// Parse the table and index of an `alt[<table>][<index>]` query
+///
+/// Returns `None` for queries of any other form.
+pub fn parse_lookup_query(query: &str) -> Option<SolanaResult<(String, u8)>> {
+    let rest = query.strip_prefix("alt[")?;
+    let parsed = rest
+        .split_once("][")
+        .and_then(|(table, index)| Some((table, index.strip_suffix(']')?)))
+        .ok_or_else(|| SolanaError::InvalidQuery(format!("Expected alt[<table>][<index>], got '{}'", query)))
+        .and_then(|(table, index)| {
+            let index = index.trim().parse::<u8>().map_err(|_| {
+                SolanaError::InvalidQuery(format!("Lookup table index '{}' is not a number from 0 to 255", index))
+            })?;
+            Ok((table.trim().to_string(), index))
+        });
+    Some(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(addresses: u8, authority: bool) -> Vec<u8> {
+        let mut data = 1u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&u64::MAX.to_le_bytes());
+        data.extend_from_slice(&250_000_000u64.to_le_bytes());
+        data.push(2);
+        if authority {
+            data.push(1);
+            data.extend_from_slice(&[0xaa; 32]);
+        } else {
+            data.extend_from_slice(&[0u8; 33]);
+        }
+        data.extend_from_slice(&[0u8; 2]);
+        for index in 0..addresses {
+            data.extend_from_slice(&[index + 1; 32]);
+        }
+        data
+    }
+
+    #[test]
+    fn test_parse_lookup_table() {
+        let parsed = AddressLookupTable::parse(&table(3, true)).unwrap();
+        assert!(!parsed.is_deactivated());
+        assert_eq!(parsed.authority, Some([0xaa; 32]));
+        assert_eq!(parsed.last_extended_slot_start_index, 2);
+        assert_eq!(parsed.expand(&[2, 0]).unwrap(), vec![[3; 32], [1; 32]]);
+        assert!(parsed.address(3).is_err());
+        assert_eq!(AddressLookupTable::entry_offset(2), 120);
+
+        assert_eq!(AddressLookupTable::parse(&table(0, false)).unwrap().authority, None);
+
+        let mut truncated = table(2, true);
+        truncated.pop();
+        assert!(AddressLookupTable::parse(&truncated).is_err());
+        let mut uninitialized = table(2, true);
+        uninitialized[0] = 0;
+        assert!(AddressLookupTable::parse(&uninitialized).is_err());
+    }
+
+    #[test]
+    fn test_parse_lookup_query() {
+        let table = "AddressLookupTab1e1111111111111111111111111";
+        let (parsed, index) = parse_lookup_query(&format!("alt[{}][7]", table)).unwrap().unwrap();
+        assert_eq!((parsed.as_str(), index), (table, 7));
+
+        assert!(parse_lookup_query("vault[seed]").is_none());
+        assert!(parse_lookup_query("alt[table][256]").unwrap().is_err());
+        assert!(parse_lookup_query("alt[table]").unwrap().is_err());
+    }
+}
@@ -0,0 +1,279 @@
+//! Metaplex Token Metadata account layout
+//!
+//! Metadata accounts are Borsh without an Anchor discriminator or published
+//! IDL. Name, symbol and URI are length-prefixed strings, and creators an
+//! optional list, so every field after `name` moves with the lengths before
+//! it: [`MetadataLayout::parse`] walks an account's data to place them.
+//! Accounts written by older program versions end after `is_mutable` or one
+//! of the optional fields added since; the fields they lack are left out.
+
+use crate::native::Cursor;
+use crate::{FieldLayout, FieldType, SolanaError, SolanaResult, ZeroSemantics};
+
+/// Metaplex Token Metadata program
+pub const METADATA_PROGRAM_ID: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
+
+/// `Key::MetadataV1`, the first byte of every metadata account
+const METADATA_KEY: u8 = 4;
+/// Size of a creator: address, verified flag and share
+const CREATOR_LEN: usize = 34;
+
+fn field(name: &str, field_type: FieldType, offset: u32, size: u32, zero_semantics: ZeroSemantics) -> FieldLayout {
+    FieldLayout { name: name.to_string(), field_type, offset, size, zero_semantics }
+}
+
+/// Fields every metadata account has at fixed offsets
+pub fn metadata_layout() -> Vec<FieldLayout> {
+    vec![
+        field("key", FieldType::U8, 0, 1, ZeroSemantics::NeverInitialized),
+        field("update_authority", FieldType::Pubkey, 1, 32, ZeroSemantics::NeverWritten),
+        field("mint", FieldType::Pubkey, 33, 32, ZeroSemantics::NeverInitialized),
+    ]
+}
+
+/// The layout of one metadata account, with its variable-length fields placed
+#[derive(Debug, Clone)]
+pub struct MetadataLayout {
+    pub fields: Vec<FieldLayout>,
+}
+
+impl MetadataLayout {
+    /// Walk a metadata account's data to place each field
+    ///
+    /// A string field spans its `u32` length prefix and bytes; an option
+    /// spans its tag and value, and a present option's members follow it as
+    /// `<option>.<member>`. Creators are numbered: `creators.0.address`.
+    pub fn parse(data: &[u8]) -> SolanaResult<Self> {
+        let mut parser = Parser { cursor: Cursor::new(data), fields: Vec::new() };
+        let key = parser.cursor.u8()?;
+        if key != METADATA_KEY {
+            return Err(SolanaError::InvalidAccountData(format!("Not a metadata account: key {}", key)));
+        }
+        parser.cursor.take(64)?;
+        parser.fields = metadata_layout();
+
+        parser.string("name")?;
+        parser.string("symbol")?;
+        parser.string("uri")?;
+        parser.fixed("seller_fee_basis_points", FieldType::U16, 2)?;
+        parser.option("creators", FieldType::Vec(Box::new(FieldType::Defined("Creator".into()))), |parser| {
+            let count = parser.cursor.u32()?;
+            for index in 0..count {
+                let offset = parser.cursor.offset as u32;
+                parser.cursor.take(CREATOR_LEN)?;
+                let name = |member: &str| format!("creators.{}.{}", index, member);
+                parser.fields.push(field(&name("address"), FieldType::Pubkey, offset, 32, ZeroSemantics::NeverWritten));
+                parser.fields.push(field(&name("verified"), FieldType::Bool, offset + 32, 1, ZeroSemantics::ValidZero));
+                parser.fields.push(field(&name("share"), FieldType::U8, offset + 33, 1, ZeroSemantics::ValidZero));
+            }
+            Ok(())
+        })?;
+        parser.fixed("primary_sale_happened", FieldType::Bool, 1)?;
+        parser.fixed("is_mutable", FieldType::Bool, 1)?;
+
+        // Fields added by later program versions, in the order they were added
+        if parser.ended() {
+            return Ok(Self { fields: parser.fields });
+        }
+        parser.option("edition_nonce", FieldType::U8, |parser| parser.cursor.take(1).map(drop))?;
+        if parser.ended() {
+            return Ok(Self { fields: parser.fields });
+        }
+        parser.option("token_standard", FieldType::U8, |parser| parser.cursor.take(1).map(drop))?;
+        if parser.ended() {
+            return Ok(Self { fields: parser.fields });
+        }
+        parser.option("collection", FieldType::Defined("Collection".into()), |parser| {
+            parser.fixed("collection.verified", FieldType::Bool, 1)?;
+            parser.fixed("collection.key", FieldType::Pubkey, 32)
+        })?;
+        if parser.ended() {
+            return Ok(Self { fields: parser.fields });
+        }
+        parser.option("uses", FieldType::Defined("Uses".into()), |parser| {
+            parser.fixed("uses.use_method", FieldType::U8, 1)?;
+            parser.fixed("uses.remaining", FieldType::U64, 8)?;
+            parser.fixed("uses.total", FieldType::U64, 8)
+        })?;
+        if parser.ended() {
+            return Ok(Self { fields: parser.fields });
+        }
+        // `V1 { size: u64 }` or `V2 { padding: [u8; 8] }`
+        parser.option("collection_details", FieldType::Defined("CollectionDetails".into()), |parser| {
+            parser.fixed("collection_details.version", FieldType::U8, 1)?;
+            parser.fixed("collection_details.size", FieldType::U64, 8)
+        })?;
+        if parser.ended() {
+            return Ok(Self { fields: parser.fields });
+        }
+        // `V1 { rule_set: Option<Pubkey> }`
+        parser.option("programmable_config", FieldType::Defined("ProgrammableConfig".into()), |parser| {
+            parser.fixed("programmable_config.version", FieldType::U8, 1)?;
+            parser.option("programmable_config.rule_set", FieldType::Pubkey, |parser| parser.cursor.take(32).map(drop))
+        })?;
+        Ok(Self { fields: parser.fields })
+    }
+
+    /// Field at a path such as `uri` or `collection.key`
+    pub fn field(&self, path: &str) -> SolanaResult<FieldLayout> {
+        self.fields
+            .iter()
+            .find(|field| field.name == path)
+            .cloned()
+            .ok_or_else(|| SolanaError::InvalidQuery(format!("No field '{}' in this metadata account", path)))
+    }
+
+    /// Value of a string field, without the NUL padding Metaplex pads names and URIs with
+    pub fn string(&self, data: &[u8], path: &str) -> SolanaResult<String> {
+        let field = self.field(path)?;
+        if field.field_type != FieldType::String {
+            return Err(SolanaError::InvalidQuery(format!("'{}' is not a string field", path)));
+        }
+        let start = field.offset as usize + 4;
+        let bytes = data
+            .get(start..(field.offset + field.size) as usize)
+            .ok_or_else(|| SolanaError::InvalidAccountData(format!("'{}' runs past the end of the account", path)))?;
+        let value = std::str::from_utf8(bytes)
+            .map_err(|e| SolanaError::InvalidAccountData(format!("'{}' is not UTF-8: {}", path, e)))?;
+        Ok(value.trim_end_matches('\0').to_string())
+    }
+}
+
+struct Parser<'a> {
+    cursor: Cursor<'a>,
+    fields: Vec<FieldLayout>,
+}
+
+impl Parser<'_> {
+    fn ended(&self) -> bool {
+        self.cursor.offset == self.cursor.data.len()
+    }
+
+    fn fixed(&mut self, name: &str, field_type: FieldType, size: usize) -> SolanaResult<()> {
+        let offset = self.cursor.offset as u32;
+        self.cursor.take(size)?;
+        self.fields.push(field(name, field_type, offset, size as u32, ZeroSemantics::ValidZero));
+        Ok(())
+    }
+
+    fn string(&mut self, name: &str) -> SolanaResult<()> {
+        let offset = self.cursor.offset as u32;
+        let len = self.cursor.u32()? as usize;
+        self.cursor.take(len)?;
+        self.fields.push(field(name, FieldType::String, offset, 4 + len as u32, ZeroSemantics::NeverWritten));
+        Ok(())
+    }
+
+    /// An option's tag, then its value's fields if present
+    fn option(
+        &mut self,
+        name: &str,
+        inner: FieldType,
+        value: impl FnOnce(&mut Self) -> SolanaResult<()>,
+    ) -> SolanaResult<()> {
+        let offset = self.cursor.offset;
+        let index = self.fields.len();
+        match self.cursor.u8()? {
+            0 => {}
+            1 => value(self)?,
+            tag => {
+                return Err(SolanaError::InvalidAccountData(format!("Bad option tag {} for '{}'", tag, name)));
+            }
+        }
+        let size = (self.cursor.offset - offset) as u32;
+        let option = field(name, FieldType::Option(Box::new(inner)), offset as u32, size, ZeroSemantics::NeverWritten);
+        self.fields.insert(index, option);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(data: &mut Vec<u8>, value: &str, padded: usize) {
+        let mut bytes = value.as_bytes().to_vec();
+        bytes.resize(padded, 0);
+        data.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+        data.extend_from_slice(&bytes);
+    }
+
+    /// A metadata account with two creators, written by a current program
+    fn metadata(tail: bool) -> Vec<u8> {
+        let mut data = vec![METADATA_KEY];
+        data.extend_from_slice(&[0x11; 32]);
+        data.extend_from_slice(&[0x22; 32]);
+        string(&mut data, "Token", 32);
+        string(&mut data, "TKN", 10);
+        string(&mut data, "https://example.com/token.json", 200);
+        data.extend_from_slice(&500u16.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(&2u32.to_le_bytes());
+        for (creator, share) in [(0x33u8, 60u8), (0x44, 40)] {
+            data.extend_from_slice(&[creator; 32]);
+            data.push(1);
+            data.push(share);
+        }
+        data.extend_from_slice(&[0, 1]);
+        if tail {
+            data.extend_from_slice(&[1, 254]);
+            data.extend_from_slice(&[1, 0]);
+            data.extend_from_slice(&[1, 1]);
+            data.extend_from_slice(&[0x55; 32]);
+            data.push(0);
+            data.push(0);
+            data.push(0);
+            // Accounts are allocated at their maximum size
+            data.extend_from_slice(&[0u8; 32]);
+        }
+        data
+    }
+
+    #[test]
+    fn test_parse_metadata() {
+        let data = metadata(true);
+        let layout = MetadataLayout::parse(&data).unwrap();
+        assert_eq!(layout.string(&data, "name").unwrap(), "Token");
+        assert_eq!(layout.string(&data, "symbol").unwrap(), "TKN");
+        assert_eq!(layout.string(&data, "uri").unwrap(), "https://example.com/token.json");
+
+        // name and symbol are padded to 32 and 10 bytes, the URI to 200
+        let uri = layout.field("uri").unwrap();
+        assert_eq!((uri.offset, uri.size), (65 + 36 + 14, 204));
+        let fee = layout.field("seller_fee_basis_points").unwrap();
+        assert_eq!(&data[fee.offset as usize..fee.offset as usize + 2], 500u16.to_le_bytes());
+
+        let share = layout.field("creators.1.share").unwrap();
+        assert_eq!(data[share.offset as usize], 40);
+        assert_eq!(layout.field("creators").unwrap().size, 1 + 4 + 2 * 34);
+
+        let collection = layout.field("collection.key").unwrap();
+        assert_eq!(data[collection.offset as usize..collection.offset as usize + 32], [0x55; 32]);
+        assert_eq!(layout.field("collection.verified").unwrap().offset + 1, collection.offset);
+        assert_eq!(layout.field("uses").unwrap().size, 1);
+        assert!(layout.field("uses.total").is_err());
+        assert_eq!(layout.field("programmable_config").unwrap().size, 1);
+    }
+
+    #[test]
+    fn test_parse_legacy_and_malformed_metadata() {
+        // Accounts from before edition nonces end at is_mutable
+        let data = metadata(false);
+        let layout = MetadataLayout::parse(&data).unwrap();
+        assert_eq!(layout.field("is_mutable").unwrap().offset as usize, data.len() - 1);
+        assert!(layout.field("collection").is_err());
+
+        let mut wrong_key = data.clone();
+        wrong_key[0] = 6;
+        assert!(MetadataLayout::parse(&wrong_key).is_err());
+
+        // A name longer than the account
+        let mut long_name = data.clone();
+        long_name[65..69].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(MetadataLayout::parse(&long_name).is_err());
+
+        let mut bad_tag = metadata(true);
+        bad_tag[data.len()] = 2;
+        assert!(MetadataLayout::parse(&bad_tag).is_err());
+    }
+}
@@ -0,0 +1,294 @@
+//! Stake and vote account layouts
+//!
+//! The stake and vote programs are native programs without an IDL, and their
+//! accounts are bincode: integers little-endian, enum variants as a `u32`
+//! tag, and collections behind a `u64` length. A stake account's fields sit at
+//! fixed offsets. A vote account's fields after `commission` follow its
+//! variable-length vote tower, so [`VoteLayout::parse`] walks the account's
+//! data to place them.
+
+use crate::{FieldLayout, FieldType, SolanaError, SolanaResult, ZeroSemantics};
+
+/// Stake program
+pub const STAKE_PROGRAM_ID: &str = "Stake11111111111111111111111111111111111111";
+/// Vote program
+pub const VOTE_PROGRAM_ID: &str = "Vote111111111111111111111111111111111111111";
+
+/// Size of a stake account
+pub const STAKE_ACCOUNT_LEN: usize = 200;
+
+fn field(name: &str, field_type: FieldType, offset: u32, size: u32, zero_semantics: ZeroSemantics) -> FieldLayout {
+    FieldLayout { name: name.to_string(), field_type, offset, size, zero_semantics }
+}
+
+fn key(name: &str, offset: u32) -> FieldLayout {
+    field(name, FieldType::Pubkey, offset, 32, ZeroSemantics::NeverWritten)
+}
+
+/// Fields of a stake account
+///
+/// `state` is 0 uninitialized, 1 initialized, 2 delegated or 3 the retired
+/// rewards pool. `authorized` and `lockup` are set once initialized,
+/// `delegation` and `credits_observed` only once delegated. An active
+/// delegation's `deactivation_epoch` is `u64::MAX`.
+pub fn stake_layout() -> Vec<FieldLayout> {
+    vec![
+        field("state", FieldType::U32, 0, 4, ZeroSemantics::NeverInitialized),
+        field("rent_exempt_reserve", FieldType::U64, 4, 8, ZeroSemantics::NeverInitialized),
+        key("authorized.staker", 12),
+        key("authorized.withdrawer", 44),
+        field("lockup.unix_timestamp", FieldType::I64, 76, 8, ZeroSemantics::ValidZero),
+        field("lockup.epoch", FieldType::U64, 84, 8, ZeroSemantics::ValidZero),
+        key("lockup.custodian", 92),
+        key("delegation.voter_pubkey", 124),
+        field("delegation.stake", FieldType::U64, 156, 8, ZeroSemantics::ValidZero),
+        field("delegation.activation_epoch", FieldType::U64, 164, 8, ZeroSemantics::ValidZero),
+        field("delegation.deactivation_epoch", FieldType::U64, 172, 8, ZeroSemantics::ValidZero),
+        // 180..188 is the deprecated `warmup_cooldown_rate`, an `f64`
+        field("credits_observed", FieldType::U64, 188, 8, ZeroSemantics::ValidZero),
+        field("flags", FieldType::U8, 196, 1, ZeroSemantics::ValidZero),
+    ]
+}
+
+/// Fields every vote account has at fixed offsets, after its version tag
+pub fn vote_layout() -> Vec<FieldLayout> {
+    vec![
+        field("version", FieldType::U32, 0, 4, ZeroSemantics::NeverInitialized),
+        key("node_pubkey", 4),
+        key("authorized_withdrawer", 36),
+        field("commission", FieldType::U8, 68, 1, ZeroSemantics::ValidZero),
+    ]
+}
+
+/// Offset of the vote tower's length
+const VOTES_OFFSET: usize = 69;
+/// `prior_voters`: 32 `(Pubkey, Epoch, Epoch)` entries, an index and an empty flag
+const PRIOR_VOTERS_LEN: usize = 32 * 48 + 8 + 1;
+
+/// The layout of one vote account, with its variable-length sections located
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoteLayout {
+    /// 1 for v1.14.11 state, 2 for the current state with vote latencies
+    pub version: u32,
+    /// Offset of `root_slot`'s option tag
+    pub root_slot: u32,
+    pub has_root_slot: bool,
+    /// Offset of the newest `(epoch, pubkey)` entry of `authorized_voters`
+    pub authorized_voter: Option<u32>,
+    /// Offset of the newest `(epoch, credits, prev_credits)` entry of `epoch_credits`
+    pub epoch_credits: Option<u32>,
+    /// Offset of `last_timestamp`
+    pub last_timestamp: u32,
+}
+
+impl VoteLayout {
+    /// Walk a vote account's data to locate the fields after its vote tower
+    pub fn parse(data: &[u8]) -> SolanaResult<Self> {
+        let mut cursor = Cursor::new(data);
+        let version = cursor.u32()?;
+        let vote_len = match version {
+            // `Lockout { slot, confirmation_count }`
+            1 => 12,
+            // `LandedVote { latency, lockout }`
+            2 => 13,
+            other => {
+                return Err(SolanaError::InvalidAccountData(format!("Unsupported vote state version {}", other)))
+            }
+        };
+        cursor.offset = VOTES_OFFSET;
+        let votes = cursor.u64()?;
+        cursor.skip_entries(votes, vote_len)?;
+
+        let root_slot = cursor.offset as u32;
+        let has_root_slot = match cursor.u8()? {
+            0 => false,
+            1 => {
+                cursor.take(8)?;
+                true
+            }
+            other => return Err(SolanaError::InvalidAccountData(format!("Bad root slot option tag {}", other))),
+        };
+
+        let voters = cursor.u64()?;
+        let authorized_voter = cursor.skip_entries(voters, 40)?;
+        cursor.take(PRIOR_VOTERS_LEN)?;
+        let credits = cursor.u64()?;
+        let epoch_credits = cursor.skip_entries(credits, 24)?;
+
+        let last_timestamp = cursor.offset as u32;
+        cursor.take(16)?;
+        Ok(Self { version, root_slot, has_root_slot, authorized_voter, epoch_credits, last_timestamp })
+    }
+
+    /// Fixed fields, then the located ones
+    ///
+    /// `authorized_voter` and `epoch_credits` are the newest entries of their
+    /// collections, and are absent when the collection is empty.
+    pub fn fields(&self) -> Vec<FieldLayout> {
+        let mut fields = vote_layout();
+        let root_size = if self.has_root_slot { 9 } else { 1 };
+        fields.push(field(
+            "root_slot",
+            FieldType::Option(Box::new(FieldType::U64)),
+            self.root_slot,
+            root_size,
+            ZeroSemantics::NeverWritten,
+        ));
+        if let Some(offset) = self.authorized_voter {
+            fields.push(field("authorized_voter.epoch", FieldType::U64, offset, 8, ZeroSemantics::ValidZero));
+            fields.push(key("authorized_voter.pubkey", offset + 8));
+        }
+        if let Some(offset) = self.epoch_credits {
+            fields.push(field("epoch_credits.epoch", FieldType::U64, offset, 8, ZeroSemantics::ValidZero));
+            fields.push(field("epoch_credits.credits", FieldType::U64, offset + 8, 8, ZeroSemantics::ValidZero));
+            fields.push(field("epoch_credits.prev_credits", FieldType::U64, offset + 16, 8, ZeroSemantics::ValidZero));
+        }
+        fields.push(field("last_timestamp.slot", FieldType::U64, self.last_timestamp, 8, ZeroSemantics::NeverWritten));
+        fields.push(field(
+            "last_timestamp.timestamp",
+            FieldType::I64,
+            self.last_timestamp + 8,
+            8,
+            ZeroSemantics::NeverWritten,
+        ));
+        fields
+    }
+
+    /// Field at a path such as `commission` or `epoch_credits.credits`
+    pub fn field(&self, path: &str) -> SolanaResult<FieldLayout> {
+        self.fields()
+            .into_iter()
+            .find(|field| field.name == path)
+            .ok_or_else(|| SolanaError::InvalidQuery(format!("No field '{}' in this vote account", path)))
+    }
+}
+
+/// Reads bincode and Borsh fields in order, tracking their offsets
+pub(crate) struct Cursor<'a> {
+    pub(crate) data: &'a [u8],
+    pub(crate) offset: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    pub(crate) fn take(&mut self, len: usize) -> SolanaResult<&'a [u8]> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| SolanaError::InvalidAccountData(format!("Account data ends before offset {}", self.offset)))?;
+        let bytes = &self.data[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    pub(crate) fn u8(&mut self) -> SolanaResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn u32(&mut self) -> SolanaResult<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().expect("4 bytes")))
+    }
+
+    pub(crate) fn u64(&mut self) -> SolanaResult<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().expect("8 bytes")))
+    }
+
+    /// Skip `count` entries of `len` bytes, returning the last entry's offset
+    pub(crate) fn skip_entries(&mut self, count: u64, len: usize) -> SolanaResult<Option<u32>> {
+        // A count the account cannot hold fails here rather than overflowing
+        let total = usize::try_from(count)
+            .ok()
+            .and_then(|count| count.checked_mul(len))
+            .ok_or_else(|| SolanaError::InvalidAccountData(format!("Bad collection length {}", count)))?;
+        let start = self.offset;
+        self.take(total)?;
+        Ok((count > 0).then(|| (start + total - len) as u32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vote_account(version: u32, votes: u64, root: Option<u64>) -> Vec<u8> {
+        let mut data = version.to_le_bytes().to_vec();
+        data.extend_from_slice(&[0x11; 32]);
+        data.extend_from_slice(&[0x22; 32]);
+        data.push(5);
+        data.extend_from_slice(&votes.to_le_bytes());
+        data.extend(std::iter::repeat(0u8).take(votes as usize * if version == 1 { 12 } else { 13 }));
+        match root {
+            Some(slot) => {
+                data.push(1);
+                data.extend_from_slice(&slot.to_le_bytes());
+            }
+            None => data.push(0),
+        }
+        data.extend_from_slice(&2u64.to_le_bytes());
+        for epoch in [600u64, 601] {
+            data.extend_from_slice(&epoch.to_le_bytes());
+            data.extend_from_slice(&[epoch as u8; 32]);
+        }
+        data.extend(std::iter::repeat(0u8).take(PRIOR_VOTERS_LEN));
+        data.extend_from_slice(&1u64.to_le_bytes());
+        for value in [601u64, 9_000, 8_000] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&250_000_000u64.to_le_bytes());
+        data.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        // Vote accounts are allocated larger than their state
+        data.extend_from_slice(&[0u8; 64]);
+        data
+    }
+
+    fn read_u64(data: &[u8], field: &FieldLayout) -> u64 {
+        let start = field.offset as usize;
+        u64::from_le_bytes(data[start..start + 8].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_stake_layout() {
+        let layout = stake_layout();
+        let last = layout.last().unwrap();
+        assert!((last.offset + last.size) as usize <= STAKE_ACCOUNT_LEN);
+        let stake = layout.iter().find(|field| field.name == "delegation.stake").unwrap();
+        assert_eq!((stake.offset, stake.size), (156, 8));
+        assert_eq!(layout.iter().find(|field| field.name == "lockup.custodian").unwrap().offset, 92);
+    }
+
+    #[test]
+    fn test_vote_layout() {
+        let data = vote_account(2, 31, Some(123));
+        let layout = VoteLayout::parse(&data).unwrap();
+        assert!(layout.has_root_slot);
+        assert_eq!(layout.field("commission").unwrap().offset, 68);
+        assert_eq!(layout.root_slot as usize, 69 + 8 + 31 * 13);
+
+        let voter = layout.field("authorized_voter.epoch").unwrap();
+        assert_eq!(read_u64(&data, &voter), 601);
+        assert_eq!(read_u64(&data, &layout.field("epoch_credits.credits").unwrap()), 9_000);
+        assert_eq!(read_u64(&data, &layout.field("last_timestamp.slot").unwrap()), 250_000_000);
+
+        // v1.14.11 votes are a byte shorter, and a missing root is just its tag
+        let data = vote_account(1, 31, None);
+        let layout = VoteLayout::parse(&data).unwrap();
+        assert_eq!(layout.field("root_slot").unwrap().size, 1);
+        assert_eq!(read_u64(&data, &layout.field("epoch_credits.prev_credits").unwrap()), 8_000);
+    }
+
+    #[test]
+    fn test_vote_layout_rejects_malformed() {
+        assert!(VoteLayout::parse(&vote_account(0, 0, None)).is_err());
+
+        let mut oversized = vote_account(2, 0, None);
+        oversized[VOTES_OFFSET..VOTES_OFFSET + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(VoteLayout::parse(&oversized).is_err());
+
+        let data = vote_account(2, 4, None);
+        assert!(VoteLayout::parse(&data[..data.len() - 80]).is_err());
+    }
+}
@@ -39,6 +39,9 @@ pub struct SolanaAccountProof {
     pub signature: Option<String>,
 }
 
+/// Maximum accounts per `getMultipleAccounts` request
+pub const MAX_ACCOUNTS_PER_REQUEST: usize = 100;
+
 /// Solana proof fetcher for account data verification using valence-domain-clients
 pub struct SolanaProofFetcher {
     /// RPC endpoint URL
@@ -115,6 +118,324 @@ impl SolanaProofFetcher {
         Err(SolanaError::FeatureNotEnabled("Client feature required for account proof fetching".into()))
     }
 
+    /// Fetch account proof with plain JSON-RPC calls
+    ///
+    /// Unlike [`fetch_account_proof`](Self::fetch_account_proof) this does not
+    /// need the `client` feature. The proof is taken at the finalized slot the
+    /// account was read at, and the block hash is that slot's rather than the
+    /// latest one.
+    #[cfg(feature = "std")]
+    pub async fn fetch_account_proof_over_http(&self, address: &str) -> SolanaResult<SolanaAccountProof> {
+        self.fetch_account_proofs_over_http(&[address.to_string()])
+            .await?
+            .pop()
+            .flatten()
+            .ok_or_else(|| SolanaError::AccountNotFound(format!("Account {} not found", address)))
+    }
+
+    /// Fetch proofs for many accounts with `getMultipleAccounts`
+    ///
+    /// Returns one entry per address, `None` for accounts that do not exist.
+    /// Accounts requested together share a slot, so batches of up to
+    /// [`MAX_ACCOUNTS_PER_REQUEST`] are consistent with each other. Fails on
+    /// the first invalid address or account that could not be fetched; use
+    /// [`fetch_account_batch_over_http`](Self::fetch_account_batch_over_http)
+    /// to keep the accounts that could.
+    #[cfg(feature = "std")]
+    pub async fn fetch_account_proofs_over_http(
+        &self,
+        addresses: &[String],
+    ) -> SolanaResult<Vec<Option<SolanaAccountProof>>> {
+        for address in addresses {
+            self.validate_address(address)?;
+        }
+        self.fetch_account_batch_over_http(addresses)
+            .await
+            .into_iter()
+            .map(|result| match result {
+                Ok(proof) => Ok(Some(proof)),
+                Err(SolanaError::AccountNotFound(_)) => Ok(None),
+                Err(e) => Err(e),
+            })
+            .collect()
+    }
+
+    /// Fetch proofs for many accounts, with a result for each
+    ///
+    /// Addresses are requested with `getMultipleAccounts` in chunks of
+    /// [`MAX_ACCOUNTS_PER_REQUEST`]. Each entry is the account's proof or why
+    /// it has none: an invalid address, `AccountNotFound`, data that could not
+    /// be decoded, or the failure of the request its chunk was sent in. Other
+    /// chunks are still fetched when one fails.
+    #[cfg(feature = "std")]
+    pub async fn fetch_account_batch_over_http(&self, addresses: &[String]) -> Vec<SolanaResult<SolanaAccountProof>> {
+        use std::collections::HashMap;
+
+        let client = reqwest::Client::new();
+        let mut block_hashes = HashMap::new();
+        let mut results: Vec<Option<SolanaResult<SolanaAccountProof>>> =
+            addresses.iter().map(|address| self.validate_address(address).err().map(Err)).collect();
+        let valid: Vec<usize> = (0..addresses.len()).filter(|index| results[*index].is_none()).collect();
+
+        for chunk in valid.chunks(MAX_ACCOUNTS_PER_REQUEST) {
+            let chunk_addresses: Vec<String> = chunk.iter().map(|index| addresses[*index].clone()).collect();
+            match self.fetch_accounts_chunk(&client, &chunk_addresses, &mut block_hashes).await {
+                Ok(proofs) => {
+                    for (index, proof) in chunk.iter().zip(proofs) {
+                        results[*index] = Some(proof);
+                    }
+                }
+                Err(e) => {
+                    for index in chunk {
+                        results[*index] = Some(Err(match &e {
+                            SolanaError::NetworkError(message) => SolanaError::NetworkError(message.clone()),
+                            other => SolanaError::NetworkError(other.to_string()),
+                        }));
+                    }
+                }
+            }
+        }
+
+        results.into_iter().map(|result| result.expect("every address has a result")).collect()
+    }
+
+    /// One `getMultipleAccounts` request, with each account's proof or why it has none
+    #[cfg(feature = "std")]
+    async fn fetch_accounts_chunk(
+        &self,
+        client: &reqwest::Client,
+        chunk: &[String],
+        block_hashes: &mut std::collections::HashMap<u64, String>,
+    ) -> SolanaResult<Vec<SolanaResult<SolanaAccountProof>>> {
+        let response = self
+            .rpc_call(client, "getMultipleAccounts", Self::accounts_request_params(chunk))
+            .await?;
+        let slot = response["context"]["slot"]
+            .as_u64()
+            .ok_or_else(|| SolanaError::NetworkError("getMultipleAccounts response has no slot".into()))?;
+        let accounts = response["value"]
+            .as_array()
+            .filter(|accounts| accounts.len() == chunk.len())
+            .ok_or_else(|| SolanaError::NetworkError("getMultipleAccounts returned the wrong number of accounts".into()))?;
+
+        let block_hash = match block_hashes.get(&slot) {
+            Some(block_hash) => block_hash.clone(),
+            None => {
+                let block_hash = self.block_hash_at(client, slot).await?;
+                block_hashes.insert(slot, block_hash.clone());
+                block_hash
+            }
+        };
+        Ok(chunk
+            .iter()
+            .zip(accounts)
+            .map(|(address, account)| Self::account_result(address, account, slot, &block_hash))
+            .collect())
+    }
+
+    /// An account from a `getMultipleAccounts` response, `AccountNotFound` when it is null
+    #[cfg(feature = "std")]
+    fn account_result(
+        address: &str,
+        account: &serde_json::Value,
+        slot: u64,
+        block_hash: &str,
+    ) -> SolanaResult<SolanaAccountProof> {
+        if account.is_null() {
+            return Err(SolanaError::AccountNotFound(format!("Account {} not found at slot {}", address, slot)));
+        }
+        Self::proof_from_rpc_account(address, account, slot, block_hash)
+            .map_err(|e| SolanaError::InvalidAccountData(format!("Account {}: {}", address, e)))
+    }
+
+    /// Fetch and decode an address lookup table
+    ///
+    /// The table's proof is returned with it, so the addresses its entries
+    /// expand to can be proven along with the accounts they name.
+    #[cfg(feature = "std")]
+    pub async fn fetch_lookup_table_over_http(
+        &self,
+        table: &str,
+    ) -> SolanaResult<(SolanaAccountProof, crate::AddressLookupTable)> {
+        let proof = self.fetch_account_proof_over_http(table).await?;
+        if proof.owner != crate::ADDRESS_LOOKUP_TABLE_PROGRAM_ID {
+            return Err(SolanaError::InvalidAccountData(format!(
+                "Account {} is owned by {}, not the address lookup table program",
+                table, proof.owner
+            )));
+        }
+        let decoded = crate::AddressLookupTable::parse(&proof.data)?;
+        Ok((proof, decoded))
+    }
+
+    /// Fetch a compressed asset's leaf proof and check it against its tree
+    ///
+    /// The leaf and proof come from the RPC's DAS `getAssetProof`, so the
+    /// endpoint must index compressed assets. The tree account is fetched
+    /// too, and the proof is only returned if it hashes to a root still in
+    /// the tree's change log buffer.
+    #[cfg(feature = "std")]
+    pub async fn fetch_asset_proof_over_http(
+        &self,
+        asset_id: &str,
+    ) -> SolanaResult<crate::compression::CompressedLeafProof> {
+        let client = reqwest::Client::new();
+        let result = self
+            .rpc_call(&client, "getAssetProof", serde_json::json!({ "id": asset_id }))
+            .await?;
+        let node = |value: &serde_json::Value| -> SolanaResult<[u8; 32]> {
+            let pubkey = value
+                .as_str()
+                .and_then(|encoded| encoded.parse::<Pubkey>().ok())
+                .ok_or_else(|| SolanaError::NetworkError(format!("getAssetProof returned a bad node: {}", value)))?;
+            Ok(pubkey.to_bytes())
+        };
+        let tree = result["tree_id"]
+            .as_str()
+            .ok_or_else(|| SolanaError::NetworkError(format!("getAssetProof returned no tree for {}", asset_id)))?
+            .to_string();
+        let leaf = node(&result["leaf"])?;
+        let root = node(&result["root"])?;
+        let proof = result["proof"]
+            .as_array()
+            .ok_or_else(|| SolanaError::NetworkError(format!("getAssetProof returned no proof for {}", asset_id)))?
+            .iter()
+            .map(node)
+            .collect::<SolanaResult<Vec<_>>>()?;
+
+        let tree_proof = self.fetch_account_proof_over_http(&tree).await?;
+        if tree_proof.owner != crate::compression::ACCOUNT_COMPRESSION_PROGRAM_ID {
+            return Err(SolanaError::InvalidAccountData(format!(
+                "Account {} is owned by {}, not the account compression program",
+                tree, tree_proof.owner
+            )));
+        }
+        let decoded = crate::compression::ConcurrentMerkleTree::parse(&tree_proof.data)?;
+
+        // `node_index` counts from the root at 1, so leaves start at 2^depth
+        let leaf_index = result["node_index"]
+            .as_u64()
+            .and_then(|index| index.checked_sub(1 << decoded.max_depth))
+            .and_then(|index| u32::try_from(index).ok())
+            .ok_or_else(|| {
+                SolanaError::NetworkError(format!("getAssetProof returned a bad node index: {}", result["node_index"]))
+            })?;
+        decoded.verify_leaf(&leaf, leaf_index, &proof, &root)?;
+
+        Ok(crate::compression::CompressedLeafProof {
+            tree,
+            leaf,
+            leaf_index,
+            proof: decoded.complete_proof(&proof, leaf_index)?,
+            root,
+            canopy_depth: decoded.canopy_depth()?,
+            tree_proof,
+        })
+    }
+
+    /// `getMultipleAccounts` params for one chunk of at most
+    /// [`MAX_ACCOUNTS_PER_REQUEST`] addresses
+    pub fn accounts_request_params(chunk: &[String]) -> serde_json::Value {
+        serde_json::json!([chunk, { "encoding": "base64", "commitment": "finalized" }])
+    }
+
+    /// `getBlock` params for reading a slot's block hash
+    ///
+    /// The slot is a JSON value so a dry-run plan can pass a reference to the
+    /// slot an earlier `getMultipleAccounts` call will report.
+    pub fn block_request_params(slot: serde_json::Value) -> serde_json::Value {
+        serde_json::json!([slot, {
+            "commitment": "finalized",
+            "transactionDetails": "none",
+            "rewards": false,
+            "maxSupportedTransactionVersion": 0
+        }])
+    }
+
+    /// Block hash of a finalized slot
+    #[cfg(feature = "std")]
+    async fn block_hash_at(&self, client: &reqwest::Client, slot: u64) -> SolanaResult<String> {
+        let block = self
+            .rpc_call(
+                client,
+                "getBlock",
+                Self::block_request_params(slot.into()),
+            )
+            .await?;
+        block["blockhash"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| SolanaError::NetworkError(format!("No block hash for slot {}", slot)))
+    }
+
+    /// Build a proof from a base64-encoded RPC account object
+    #[cfg(feature = "std")]
+    fn proof_from_rpc_account(
+        address: &str,
+        account: &serde_json::Value,
+        slot: u64,
+        block_hash: &str,
+    ) -> SolanaResult<SolanaAccountProof> {
+        use base64::engine::{general_purpose::STANDARD, Engine};
+
+        let data = account["data"][0]
+            .as_str()
+            .ok_or_else(|| SolanaError::InvalidAccountData("Account data is not base64 encoded".into()))
+            .and_then(|encoded| {
+                STANDARD
+                    .decode(encoded)
+                    .map_err(|e| SolanaError::InvalidAccountData(format!("Invalid base64 account data: {}", e)))
+            })?;
+        let owner = account["owner"]
+            .as_str()
+            .ok_or_else(|| SolanaError::InvalidAccountData("Account has no owner".into()))?;
+
+        Ok(Self::create_proof_from_account_data(
+            address.to_string(),
+            data,
+            owner.to_string(),
+            account["lamports"].as_u64().unwrap_or_default(),
+            account["rentEpoch"].as_u64().unwrap_or_default(),
+            slot,
+            block_hash.to_string(),
+        ))
+    }
+
+    /// Genesis hash of the cluster, which identifies it the way a chain ID would
+    #[cfg(feature = "std")]
+    pub async fn genesis_hash(&self) -> SolanaResult<String> {
+        let result = self.rpc_call(&reqwest::Client::new(), "getGenesisHash", serde_json::json!([])).await?;
+        result
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| SolanaError::NetworkError(format!("getGenesisHash returned {}", result)))
+    }
+
+    /// Send a JSON-RPC request and return its result
+    #[cfg(feature = "std")]
+    async fn rpc_call(
+        &self,
+        client: &reqwest::Client,
+        method: &str,
+        params: serde_json::Value,
+    ) -> SolanaResult<serde_json::Value> {
+        let body = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response: serde_json::Value = client
+            .post(&self.rpc_url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| SolanaError::NetworkError(format!("{} request failed: {}", method, e)))?
+            .json()
+            .await
+            .map_err(|e| SolanaError::NetworkError(format!("Invalid {} response: {}", method, e)))?;
+
+        if let Some(error) = response.get("error") {
+            return Err(SolanaError::NetworkError(format!("{} failed: {}", method, error)));
+        }
+        Ok(response["result"].clone())
+    }
+
     /// Get current slot using valence-domain-clients
     #[cfg(feature = "client")]
     async fn get_current_slot(&self) -> SolanaResult<u64> {
@@ -323,6 +644,29 @@ mod tests {
         assert!(!is_valid);
     }
 
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_account_result() {
+        let account = serde_json::json!({
+            "data": ["AQIDBA==", "base64"],
+            "owner": "11111111111111111111111111111111",
+            "lamports": 1_000_000,
+            "rentEpoch": 250,
+        });
+        let address = "11111111111111111111111111111112";
+        let proof = SolanaProofFetcher::account_result(address, &account, 12345, "AbCdEf123456").unwrap();
+        assert_eq!((proof.data, proof.slot), (vec![1, 2, 3, 4], 12345));
+
+        let missing = SolanaProofFetcher::account_result(address, &serde_json::Value::Null, 12345, "AbCdEf123456");
+        assert!(matches!(missing, Err(SolanaError::AccountNotFound(_))));
+
+        let mut undecodable = account.clone();
+        undecodable["data"][0] = "not base64!".into();
+        let error = SolanaProofFetcher::account_result(address, &undecodable, 12345, "AbCdEf123456").unwrap_err();
+        assert!(matches!(error, SolanaError::InvalidAccountData(_)));
+        assert!(error.to_string().contains(address));
+    }
+
     #[cfg(not(feature = "client"))]
     #[tokio::test]
     async fn test_fetch_account_proof_without_client_feature() {
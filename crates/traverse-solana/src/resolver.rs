@@ -38,6 +38,13 @@ pub enum SolanaQuery {
         /// Token owner address
         owner: String,
     },
+    /// Address lookup table entry: "alt[{table}][3]"
+    LookupTable {
+        /// Lookup table address
+        table: String,
+        /// Index of the entry
+        index: u8,
+    },
     /// Field access: "user_account.balance"
     FieldAccess {
         /// Account name
@@ -47,6 +54,20 @@ pub enum SolanaQuery {
     },
 }
 
+/// A program derived address and its canonical bump
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivedPda {
+    /// Base58 address
+    pub address: String,
+    /// Highest bump for which the seeds hash off the curve
+    pub bump: u8,
+}
+
+/// Maximum seeds in a derivation, including the bump
+const MAX_SEEDS: usize = 16;
+/// Maximum length of one seed
+const MAX_SEED_LEN: usize = 32;
+
 /// Solana-specific key resolver for PDA and ATA derivation
 pub struct SolanaKeyResolver {
     /// Default program ID for PDA derivation
@@ -70,6 +91,12 @@ impl SolanaKeyResolver {
 
     /// Parse query string into SolanaQuery
     pub fn parse_query(query: &str) -> SolanaResult<SolanaQuery> {
+        // Handle lookup table entries: "alt[table][index]"
+        if let Some(parsed) = crate::lookup_table::parse_lookup_query(query) {
+            let (table, index) = parsed?;
+            return Ok(SolanaQuery::LookupTable { table, index });
+        }
+
         // Handle field access: "account.field"
         if query.contains('.') && !query.contains('[') {
             let parts: Vec<&str> = query.splitn(2, '.').collect();
@@ -125,11 +152,18 @@ impl SolanaKeyResolver {
                 ))
             }
             SolanaQuery::PDA { account_name: _, seeds } => {
-                self.derive_pda_address(seeds)
+                self.derive_pda_address(seeds).map(|derived| derived.address)
             }
             SolanaQuery::ATA { mint, owner } => {
                 self.derive_ata_address(mint, owner)
             }
+            SolanaQuery::LookupTable { table, index } => {
+                // The entry is only known from the table's account data
+                Err(SolanaError::InvalidQuery(format!(
+                    "Entry {} of lookup table {} needs the table's data; fetch it first",
+                    index, table
+                )))
+            }
             SolanaQuery::FieldAccess { account_name: _, field_path: _ } => {
                 // Field access resolves to the account address, not a specific field
                 Err(SolanaError::InvalidQuery(
@@ -140,8 +174,13 @@ impl SolanaKeyResolver {
     }
 
     /// Derive Program Derived Account (PDA) address
+    ///
+    /// Tries bumps from 255 down and returns the first whose address is off
+    /// the curve, as `find_program_address` does, so the bump is the
+    /// canonical one Anchor's `bump` constraint checks. Seeds beyond the
+    /// runtime's limits are rejected rather than searched.
     #[cfg(feature = "solana")]
-    pub fn derive_pda_address(&self, seeds: &[String]) -> SolanaResult<String> {
+    pub fn derive_pda_address(&self, seeds: &[String]) -> SolanaResult<DerivedPda> {
         let program_id_str = self.default_program_id.as_ref()
             .ok_or_else(|| SolanaError::InvalidProgramId("No program ID configured".to_string()))?;
 
@@ -165,23 +204,72 @@ impl SolanaKeyResolver {
             }
         }
 
-        // Convert to byte slices
-        let seed_slices: Vec<&[u8]> = seed_bytes.iter().map(|v| v.as_slice()).collect();
+        if seed_bytes.len() >= MAX_SEEDS {
+            return Err(SolanaError::PdaDerivationFailed(format!(
+                "{} seeds leave no room for the bump; at most {} are allowed",
+                seed_bytes.len(),
+                MAX_SEEDS - 1
+            )));
+        }
+        if let Some(seed) = seed_bytes.iter().find(|seed| seed.len() > MAX_SEED_LEN) {
+            return Err(SolanaError::PdaDerivationFailed(format!(
+                "Seed of {} bytes exceeds the {}-byte limit",
+                seed.len(),
+                MAX_SEED_LEN
+            )));
+        }
 
         // Derive PDA
-        let (pda, _bump) = Pubkey::find_program_address(&seed_slices, &program_id);
-        
-        Ok(pda.to_string())
+        let seed_slices: Vec<&[u8]> = seed_bytes.iter().map(|v| v.as_slice()).collect();
+        for bump in (0..=u8::MAX).rev() {
+            let bump_seed = [bump];
+            let mut with_bump = seed_slices.clone();
+            with_bump.push(&bump_seed);
+            if let Ok(pda) = Pubkey::create_program_address(&with_bump, &program_id) {
+                return Ok(DerivedPda { address: pda.to_string(), bump });
+            }
+        }
+        Err(SolanaError::PdaDerivationFailed("No bump puts the address off the curve".to_string()))
     }
 
     /// Derive Program Derived Account (PDA) address (fallback without solana feature)
     #[cfg(not(feature = "solana"))]
-    pub fn derive_pda_address(&self, _seeds: &[String]) -> SolanaResult<String> {
+    pub fn derive_pda_address(&self, _seeds: &[String]) -> SolanaResult<DerivedPda> {
         Err(SolanaError::NetworkError(
             "PDA derivation requires 'solana' feature".to_string()
         ))
     }
 
+    /// Derive a PDA and check the bump byte its account stores at `bump_offset`
+    ///
+    /// Programs that store their bump and re-derive with it trust that byte;
+    /// an account holding a non-canonical bump would pass such a check while
+    /// being a different address from the one a query derives.
+    pub fn verify_stored_bump(&self, seeds: &[String], data: &[u8], bump_offset: usize) -> SolanaResult<DerivedPda> {
+        let derived = self.derive_pda_address(seeds)?;
+        let stored = *data.get(bump_offset).ok_or_else(|| {
+            SolanaError::InvalidAccountData(format!(
+                "Bump offset {} is past the end of {} bytes of account data",
+                bump_offset,
+                data.len()
+            ))
+        })?;
+        if stored != derived.bump {
+            return Err(SolanaError::PdaDerivationFailed(format!(
+                "Account stores bump {} but the canonical bump for {} is {}",
+                stored, derived.address, derived.bump
+            )));
+        }
+        Ok(derived)
+    }
+
+    /// Address at `index` of a lookup table, given the table's account data
+    #[cfg(feature = "solana")]
+    pub fn resolve_lookup_entry(&self, table_data: &[u8], index: u8) -> SolanaResult<String> {
+        let table = crate::AddressLookupTable::parse(table_data)?;
+        Ok(Pubkey::new_from_array(table.address(index)?).to_string())
+    }
+
     /// Derive Associated Token Account (ATA) address
     #[cfg(all(feature = "solana", feature = "spl-token"))]
     pub fn derive_ata_address(&self, mint: &str, owner: &str) -> SolanaResult<String> {
@@ -411,7 +499,42 @@ mod tests {
         let result = resolver.derive_pda_address(&seeds);
         
         assert!(result.is_ok());
-        let address = result.unwrap();
-        assert!(SolanaKeyResolver::validate_address(&address));
+        let derived = result.unwrap();
+        assert!(SolanaKeyResolver::validate_address(&derived.address));
+
+        // The loop finds the same bump as the SDK's search
+        let program_id = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM".parse::<Pubkey>().unwrap();
+        let (pda, bump) = Pubkey::find_program_address(&[b"user", b"123"], &program_id);
+        assert_eq!(derived, DerivedPda { address: pda.to_string(), bump });
+
+        let too_many: Vec<String> = (0..16).map(|i| i.to_string()).collect();
+        assert!(resolver.derive_pda_address(&too_many).is_err());
+        assert!(resolver.derive_pda_address(&["x".repeat(33)]).is_err());
+    }
+
+    #[test]
+    fn test_parse_lookup_table_query() {
+        let query = SolanaKeyResolver::parse_query("alt[AddressLookupTab1e1111111111111111111111111][4]").unwrap();
+        assert!(matches!(query, SolanaQuery::LookupTable { index: 4, .. }));
+        assert!(SolanaKeyResolver::new().resolve_account_address(&query).is_err());
+        assert!(SolanaKeyResolver::parse_query("alt[table][x]").is_err());
+    }
+
+    #[cfg(feature = "solana")]
+    #[test]
+    fn test_verify_stored_bump() {
+        let resolver = SolanaKeyResolver::with_program_id(
+            "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM".to_string()
+        );
+        let seeds = vec!["vault".to_string()];
+        let bump = resolver.derive_pda_address(&seeds).unwrap().bump;
+
+        let mut data = vec![0u8; 16];
+        data[8] = bump;
+        assert_eq!(resolver.verify_stored_bump(&seeds, &data, 8).unwrap().bump, bump);
+
+        data[8] = bump.wrapping_sub(1);
+        assert!(resolver.verify_stored_bump(&seeds, &data, 8).is_err());
+        assert!(resolver.verify_stored_bump(&seeds, &data, 16).is_err());
     }
 } 
\ No newline at end of file
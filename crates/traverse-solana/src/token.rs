@@ -0,0 +1,416 @@
+//! SPL Token and Token-2022 account layouts
+//!
+//! Token accounts are packed by hand rather than Borsh, and the token
+//! programs publish no IDL, so their layouts are written out here. Token-2022
+//! accounts start with the same base layout, then an account type byte at
+//! offset 165 and a list of TLV-encoded extensions (`u16` type, `u16`
+//! length, value). Extensions appear in the order they were initialized, so
+//! their offsets differ between accounts: [`Token2022Layout::parse`] reads
+//! them from an account's data and places each extension's fields, named
+//! `<extension>.<field>`, at their offsets in that account.
+
+use crate::{FieldLayout, FieldType, SolanaError, SolanaResult, ZeroSemantics};
+
+/// SPL Token program
+pub const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+/// SPL Token-2022 program
+pub const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PQnBqCXEpPxuEb";
+
+/// Size of a mint without extensions
+pub const MINT_LEN: usize = 82;
+/// Size of a token account without extensions; also where Token-2022's account type byte sits
+pub const TOKEN_ACCOUNT_LEN: usize = 165;
+
+fn field(name: &str, field_type: FieldType, offset: u32, size: u32, zero_semantics: ZeroSemantics) -> FieldLayout {
+    FieldLayout { name: name.to_string(), field_type, offset, size, zero_semantics }
+}
+
+/// `COption<Pubkey>`: a 4-byte tag, then the key
+fn coption_pubkey(name: &str, offset: u32) -> FieldLayout {
+    field(name, FieldType::Option(Box::new(FieldType::Pubkey)), offset, 36, ZeroSemantics::NeverWritten)
+}
+
+/// Fields of a mint
+pub fn mint_layout() -> Vec<FieldLayout> {
+    vec![
+        coption_pubkey("mint_authority", 0),
+        field("supply", FieldType::U64, 36, 8, ZeroSemantics::ValidZero),
+        field("decimals", FieldType::U8, 44, 1, ZeroSemantics::ValidZero),
+        field("is_initialized", FieldType::Bool, 45, 1, ZeroSemantics::NeverInitialized),
+        coption_pubkey("freeze_authority", 46),
+    ]
+}
+
+/// Fields of a token account
+pub fn token_account_layout() -> Vec<FieldLayout> {
+    vec![
+        field("mint", FieldType::Pubkey, 0, 32, ZeroSemantics::NeverInitialized),
+        field("owner", FieldType::Pubkey, 32, 32, ZeroSemantics::NeverInitialized),
+        field("amount", FieldType::U64, 64, 8, ZeroSemantics::ValidZero),
+        coption_pubkey("delegate", 72),
+        // 0 uninitialized, 1 initialized, 2 frozen
+        field("state", FieldType::U8, 108, 1, ZeroSemantics::NeverInitialized),
+        // `COption<u64>`: rent-exempt reserve of wrapped SOL accounts
+        field("is_native", FieldType::Option(Box::new(FieldType::U64)), 109, 12, ZeroSemantics::NeverWritten),
+        field("delegated_amount", FieldType::U64, 121, 8, ZeroSemantics::ValidZero),
+        coption_pubkey("close_authority", 129),
+    ]
+}
+
+/// Layout of an account named in a query without an IDL
+///
+/// `mint` and `token_account` are the base SPL Token layouts, which Token-2022
+/// accounts share; an associated token account is a token account at a
+/// derived address. `vote_account` and `metadata` have only their fixed
+/// leading fields; [`crate::VoteLayout`] and [`crate::MetadataLayout`] place
+/// the rest from an account's data.
+pub fn builtin_layout(account_name: &str) -> Option<Vec<FieldLayout>> {
+    match account_name {
+        "mint" => Some(mint_layout()),
+        "token_account" | "associated_token_account" => Some(token_account_layout()),
+        "stake_account" => Some(crate::native::stake_layout()),
+        "vote_account" => Some(crate::native::vote_layout()),
+        "metadata" => Some(crate::metaplex::metadata_layout()),
+        _ => None,
+    }
+}
+
+/// Program that owns accounts with a built-in layout
+pub fn builtin_program(account_name: &str) -> Option<&'static str> {
+    match account_name {
+        "mint" | "token_account" | "associated_token_account" => Some(TOKEN_PROGRAM_ID),
+        "stake_account" => Some(crate::native::STAKE_PROGRAM_ID),
+        "vote_account" => Some(crate::native::VOTE_PROGRAM_ID),
+        "metadata" => Some(crate::metaplex::METADATA_PROGRAM_ID),
+        _ => None,
+    }
+}
+
+/// Field of a built-in account, e.g. `token_account` and `amount`
+pub fn builtin_field(account_name: &str, field_path: &str) -> SolanaResult<FieldLayout> {
+    let layout = builtin_layout(account_name).ok_or_else(|| {
+        SolanaError::InvalidQuery(format!(
+            "'{}' has no built-in layout; expected mint, token_account, stake_account, vote_account or metadata",
+            account_name
+        ))
+    })?;
+    layout
+        .into_iter()
+        .find(|field| field.name == field_path)
+        .ok_or_else(|| SolanaError::InvalidQuery(format!("No field '{}' in {}", field_path, account_name)))
+}
+
+/// Whether Token-2022 data holds a mint or a token account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenAccountKind {
+    Mint,
+    Account,
+}
+
+/// Token-2022 extension types, by their TLV type number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtensionType {
+    TransferFeeConfig,
+    TransferFeeAmount,
+    MintCloseAuthority,
+    DefaultAccountState,
+    ImmutableOwner,
+    MemoTransfer,
+    NonTransferable,
+    InterestBearingConfig,
+    CpiGuard,
+    PermanentDelegate,
+    TransferHook,
+    MetadataPointer,
+    TokenMetadata,
+    /// An extension without a layout here; its bytes are still skipped correctly
+    Other(u16),
+}
+
+impl ExtensionType {
+    pub fn from_u16(value: u16) -> Self {
+        match value {
+            1 => Self::TransferFeeConfig,
+            2 => Self::TransferFeeAmount,
+            3 => Self::MintCloseAuthority,
+            6 => Self::DefaultAccountState,
+            7 => Self::ImmutableOwner,
+            8 => Self::MemoTransfer,
+            9 => Self::NonTransferable,
+            10 => Self::InterestBearingConfig,
+            11 => Self::CpiGuard,
+            12 => Self::PermanentDelegate,
+            14 => Self::TransferHook,
+            18 => Self::MetadataPointer,
+            19 => Self::TokenMetadata,
+            other => Self::Other(other),
+        }
+    }
+
+    /// Name extension fields are queried under
+    pub fn name(&self) -> String {
+        match self {
+            Self::TransferFeeConfig => "transfer_fee_config".to_string(),
+            Self::TransferFeeAmount => "transfer_fee_amount".to_string(),
+            Self::MintCloseAuthority => "mint_close_authority".to_string(),
+            Self::DefaultAccountState => "default_account_state".to_string(),
+            Self::ImmutableOwner => "immutable_owner".to_string(),
+            Self::MemoTransfer => "memo_transfer".to_string(),
+            Self::NonTransferable => "non_transferable".to_string(),
+            Self::InterestBearingConfig => "interest_bearing_config".to_string(),
+            Self::CpiGuard => "cpi_guard".to_string(),
+            Self::PermanentDelegate => "permanent_delegate".to_string(),
+            Self::TransferHook => "transfer_hook".to_string(),
+            Self::MetadataPointer => "metadata_pointer".to_string(),
+            Self::TokenMetadata => "token_metadata".to_string(),
+            Self::Other(value) => format!("extension_{}", value),
+        }
+    }
+
+    /// Fields of the extension's value, at offsets from its start
+    ///
+    /// Optional authorities are `OptionalNonZeroPubkey`s: 32 bytes, all zero
+    /// when unset. Token metadata is Borsh after its two keys, so only the
+    /// keys have fixed offsets.
+    pub fn fields(&self) -> Vec<FieldLayout> {
+        let key = |name: &str, offset: u32| field(name, FieldType::Pubkey, offset, 32, ZeroSemantics::NeverWritten);
+        let transfer_fee = |prefix: &str, offset: u32| {
+            vec![
+                field(&format!("{}.epoch", prefix), FieldType::U64, offset, 8, ZeroSemantics::ValidZero),
+                field(&format!("{}.maximum_fee", prefix), FieldType::U64, offset + 8, 8, ZeroSemantics::ValidZero),
+                field(
+                    &format!("{}.transfer_fee_basis_points", prefix),
+                    FieldType::U16,
+                    offset + 16,
+                    2,
+                    ZeroSemantics::ValidZero,
+                ),
+            ]
+        };
+        match self {
+            Self::TransferFeeConfig => {
+                let mut fields = vec![
+                    key("transfer_fee_config_authority", 0),
+                    key("withdraw_withheld_authority", 32),
+                    field("withheld_amount", FieldType::U64, 64, 8, ZeroSemantics::ValidZero),
+                ];
+                fields.extend(transfer_fee("older_transfer_fee", 72));
+                fields.extend(transfer_fee("newer_transfer_fee", 90));
+                fields
+            }
+            Self::TransferFeeAmount => vec![field("withheld_amount", FieldType::U64, 0, 8, ZeroSemantics::ValidZero)],
+            Self::MintCloseAuthority => vec![key("close_authority", 0)],
+            Self::DefaultAccountState => vec![field("state", FieldType::U8, 0, 1, ZeroSemantics::ValidZero)],
+            Self::InterestBearingConfig => vec![
+                key("rate_authority", 0),
+                field("initialization_timestamp", FieldType::I64, 32, 8, ZeroSemantics::NeverWritten),
+                field("pre_update_average_rate", FieldType::I16, 40, 2, ZeroSemantics::ValidZero),
+                field("last_update_timestamp", FieldType::I64, 42, 8, ZeroSemantics::NeverWritten),
+                field("current_rate", FieldType::I16, 50, 2, ZeroSemantics::ValidZero),
+            ],
+            Self::MemoTransfer => vec![field("require_incoming_transfer_memos", FieldType::Bool, 0, 1, ZeroSemantics::ValidZero)],
+            Self::CpiGuard => vec![field("lock_cpi", FieldType::Bool, 0, 1, ZeroSemantics::ValidZero)],
+            Self::PermanentDelegate => vec![key("delegate", 0)],
+            Self::TransferHook => vec![key("authority", 0), key("program_id", 32)],
+            Self::MetadataPointer => vec![key("authority", 0), key("metadata_address", 32)],
+            Self::TokenMetadata => vec![key("update_authority", 0), key("mint", 32)],
+            Self::ImmutableOwner | Self::NonTransferable | Self::Other(_) => Vec::new(),
+        }
+    }
+}
+
+/// One extension in an account's TLV data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionEntry {
+    pub extension: ExtensionType,
+    /// Offset of the value, past its type and length
+    pub offset: u32,
+    pub length: u32,
+}
+
+/// The layout of one Token-2022 account, extensions included
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token2022Layout {
+    pub kind: TokenAccountKind,
+    pub extensions: Vec<ExtensionEntry>,
+}
+
+impl Token2022Layout {
+    /// Read the account type and extension list from account data
+    ///
+    /// Data of exactly 82 or 165 bytes is a mint or token account without
+    /// extensions. Anything longer must carry the account type byte and a
+    /// TLV list that ends at, or in zero padding before, the end of the data.
+    pub fn parse(data: &[u8]) -> SolanaResult<Self> {
+        let kind = match data.len() {
+            MINT_LEN => return Ok(Self { kind: TokenAccountKind::Mint, extensions: Vec::new() }),
+            TOKEN_ACCOUNT_LEN => return Ok(Self { kind: TokenAccountKind::Account, extensions: Vec::new() }),
+            len if len > TOKEN_ACCOUNT_LEN => match data[TOKEN_ACCOUNT_LEN] {
+                1 => TokenAccountKind::Mint,
+                2 => TokenAccountKind::Account,
+                other => {
+                    return Err(SolanaError::InvalidAccountData(format!("Unknown Token-2022 account type {}", other)))
+                }
+            },
+            len => return Err(SolanaError::InvalidAccountData(format!("{} bytes is not a token account or mint", len))),
+        };
+        // Mints are padded to the token account size so the type byte lines up
+        if kind == TokenAccountKind::Mint && data[MINT_LEN..TOKEN_ACCOUNT_LEN].iter().any(|b| *b != 0) {
+            return Err(SolanaError::InvalidAccountData("Mint padding is not zero".into()));
+        }
+
+        let mut extensions = Vec::new();
+        let mut cursor = TOKEN_ACCOUNT_LEN + 1;
+        while cursor + 4 <= data.len() {
+            let extension_type = u16::from_le_bytes([data[cursor], data[cursor + 1]]);
+            let length = u16::from_le_bytes([data[cursor + 2], data[cursor + 3]]) as usize;
+            // Type 0 is uninitialized space after the last extension
+            if extension_type == 0 {
+                break;
+            }
+            let offset = cursor + 4;
+            if offset + length > data.len() {
+                return Err(SolanaError::InvalidAccountData(format!(
+                    "Extension {} runs past the end of the account",
+                    extension_type
+                )));
+            }
+            extensions.push(ExtensionEntry {
+                extension: ExtensionType::from_u16(extension_type),
+                offset: offset as u32,
+                length: length as u32,
+            });
+            cursor = offset + length;
+        }
+        Ok(Self { kind, extensions })
+    }
+
+    /// Base fields, then each extension's fields named `<extension>.<field>`
+    pub fn fields(&self) -> Vec<FieldLayout> {
+        let mut fields = match self.kind {
+            TokenAccountKind::Mint => mint_layout(),
+            TokenAccountKind::Account => token_account_layout(),
+        };
+        for entry in &self.extensions {
+            let prefix = entry.extension.name();
+            fields.extend(
+                entry
+                    .extension
+                    .fields()
+                    .into_iter()
+                    // A field the extension's length does not cover is not in this account
+                    .filter(|field| field.offset + field.size <= entry.length)
+                    .map(|field| FieldLayout {
+                        name: format!("{}.{}", prefix, field.name),
+                        offset: entry.offset + field.offset,
+                        ..field
+                    }),
+            );
+        }
+        fields
+    }
+
+    /// Field at a path such as `amount` or `transfer_fee_config.newer_transfer_fee.epoch`
+    pub fn field(&self, path: &str) -> SolanaResult<FieldLayout> {
+        self.fields().into_iter().find(|field| field.name == path).ok_or_else(|| {
+            SolanaError::InvalidQuery(format!("No field '{}' in this token account or its extensions", path))
+        })
+    }
+
+    /// The entry of `extension`, if the account has it
+    pub fn extension(&self, extension: ExtensionType) -> Option<&ExtensionEntry> {
+        self.extensions.iter().find(|entry| entry.extension == extension)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tlv(data: &mut Vec<u8>, extension_type: u16, value: &[u8]) {
+        data.extend_from_slice(&extension_type.to_le_bytes());
+        data.extend_from_slice(&(value.len() as u16).to_le_bytes());
+        data.extend_from_slice(value);
+    }
+
+    #[test]
+    fn test_base_layouts() {
+        let mint = mint_layout();
+        let last = mint.last().unwrap();
+        assert_eq!((last.offset + last.size) as usize, MINT_LEN);
+
+        let account = token_account_layout();
+        let last = account.last().unwrap();
+        assert_eq!((last.offset + last.size) as usize, TOKEN_ACCOUNT_LEN);
+
+        let layout = Token2022Layout::parse(&[0u8; TOKEN_ACCOUNT_LEN]).unwrap();
+        assert_eq!(layout.field("amount").unwrap().offset, 64);
+        assert!(Token2022Layout::parse(&[0u8; 100]).is_err());
+    }
+
+    #[test]
+    fn test_builtin_fields() {
+        let amount = builtin_field("token_account", "amount").unwrap();
+        assert_eq!((amount.offset, amount.size), (64, 8));
+        assert_eq!(builtin_field("associated_token_account", "owner").unwrap().offset, 32);
+        assert_eq!(builtin_field("mint", "decimals").unwrap().offset, 44);
+        assert!(builtin_field("mint", "amount").is_err());
+        assert_eq!(builtin_field("stake_account", "delegation.stake").unwrap().offset, 156);
+        assert!(builtin_field("vault", "amount").is_err());
+    }
+
+    #[test]
+    fn test_mint_extensions() {
+        let mut data = vec![0u8; TOKEN_ACCOUNT_LEN];
+        data.push(1);
+        let mut fee_config = vec![0u8; 108];
+        fee_config[90..98].copy_from_slice(&600u64.to_le_bytes());
+        fee_config[106..108].copy_from_slice(&50u16.to_le_bytes());
+        tlv(&mut data, 1, &fee_config);
+        tlv(&mut data, 18, &[0x11; 64]);
+        tlv(&mut data, 10, &[0u8; 52]);
+
+        let layout = Token2022Layout::parse(&data).unwrap();
+        assert_eq!(layout.kind, TokenAccountKind::Mint);
+        assert_eq!(layout.extensions.len(), 3);
+
+        let epoch = layout.field("transfer_fee_config.newer_transfer_fee.epoch").unwrap();
+        assert_eq!(epoch.offset, 166 + 4 + 90);
+        let at = |field: &FieldLayout| &data[field.offset as usize..(field.offset + field.size) as usize];
+        assert_eq!(at(&epoch), 600u64.to_le_bytes());
+        let bps = layout.field("transfer_fee_config.newer_transfer_fee.transfer_fee_basis_points").unwrap();
+        assert_eq!(at(&bps), 50u16.to_le_bytes());
+
+        // The pointer follows the fee config's 4-byte header and 108-byte value
+        let pointer = layout.field("metadata_pointer.metadata_address").unwrap();
+        assert_eq!(pointer.offset, 166 + 112 + 4 + 32);
+        assert_eq!(layout.field("interest_bearing_config.current_rate").unwrap().field_type, FieldType::I16);
+        assert!(layout.field("transfer_fee_amount.withheld_amount").is_err());
+    }
+
+    #[test]
+    fn test_account_extensions_and_malformed_tlv() {
+        let mut data = vec![0u8; TOKEN_ACCOUNT_LEN];
+        data.push(2);
+        tlv(&mut data, 2, &7u64.to_le_bytes());
+        tlv(&mut data, 7, &[]);
+        tlv(&mut data, 99, &[0xaa; 3]);
+        // Uninitialized space after the last extension
+        data.extend_from_slice(&[0u8; 8]);
+
+        let layout = Token2022Layout::parse(&data).unwrap();
+        assert_eq!(layout.kind, TokenAccountKind::Account);
+        assert_eq!(layout.extension(ExtensionType::Other(99)).unwrap().length, 3);
+        assert_eq!(layout.field("transfer_fee_amount.withheld_amount").unwrap().offset, 170);
+        assert_eq!(layout.field("owner").unwrap().offset, 32);
+
+        let mut truncated = data[..TOKEN_ACCOUNT_LEN + 1].to_vec();
+        tlv(&mut truncated, 2, &7u64.to_le_bytes());
+        truncated.truncate(truncated.len() - 2);
+        assert!(Token2022Layout::parse(&truncated).is_err());
+
+        let mut unknown_kind = data;
+        unknown_kind[TOKEN_ACCOUNT_LEN] = 9;
+        assert!(Token2022Layout::parse(&unknown_kind).is_err());
+    }
+}
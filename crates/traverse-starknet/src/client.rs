@@ -0,0 +1,274 @@
+//! Storage proofs over JSON-RPC
+//!
+//! Proofs come from one of two methods. `starknet_getStorageProof`, part
+//! of the JSON-RPC spec since 0.8 and served by Juno and pathfinder,
+//! returns the trie nodes keyed by hash with the trie roots. pathfinder's
+//! own `pathfinder_getProof`, served on its `/rpc/pathfinder/v0.1` path,
+//! returns them from the root down with the state commitment. Either way
+//! the values are read from the storage trie itself, and the state
+//! commitment is checked against the header of the block.
+
+use crate::proof::{felt_field, field, list, node_from_json, ContractLeaf, StarknetStorageProof, StorageValueProof};
+use crate::storage::parse_felt;
+use crate::trie::{verify_trie_proof, TrieHash, TrieNode};
+use crate::StarknetError;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use starknet_types_core::felt::Felt;
+
+/// Method storage proofs are fetched with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StarknetProofApi {
+    /// `starknet_getStorageProof` (JSON-RPC 0.8)
+    StorageProof,
+    /// `pathfinder_getProof`
+    Pathfinder,
+}
+
+/// Chain-specific configuration for proof fetching
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StarknetChainConfig {
+    pub api: StarknetProofApi,
+    /// Endpoint serving `pathfinder_getProof`, when it is not the RPC URL
+    pub pathfinder_url: Option<String>,
+    /// Block read when no number is given
+    pub block_tag: String,
+}
+
+impl Default for StarknetChainConfig {
+    fn default() -> Self {
+        Self { api: StarknetProofApi::StorageProof, pathfinder_url: None, block_tag: "latest".to_string() }
+    }
+}
+
+/// Storage proof fetcher for a Starknet full node
+#[derive(Debug, Clone)]
+pub struct StarknetProofFetcher {
+    pub rpc_url: String,
+    pub config: StarknetChainConfig,
+}
+
+/// The header fields a proof is anchored to
+struct BlockAnchor {
+    number: u64,
+    hash: Felt,
+    state_commitment: Felt,
+}
+
+impl StarknetProofFetcher {
+    pub fn new(rpc_url: String, config: StarknetChainConfig) -> Self {
+        Self { rpc_url, config }
+    }
+
+    pub fn with_defaults(rpc_url: String) -> Self {
+        Self::new(rpc_url, StarknetChainConfig::default())
+    }
+
+    /// Fetch storage values of a contract, at `block_number` or the configured tag, with their proofs
+    pub async fn fetch_proof(
+        &self,
+        contract_address: &Felt,
+        addresses: &[Felt],
+        block_number: Option<u64>,
+    ) -> Result<StarknetStorageProof, StarknetError> {
+        // Pin the block first, so a tag cannot move between the two calls
+        let block = self.block(block_number).await?;
+        let proof = match self.config.api {
+            StarknetProofApi::StorageProof => self.storage_proof(contract_address, addresses, &block).await?,
+            StarknetProofApi::Pathfinder => self.pathfinder_proof(contract_address, addresses, &block).await?,
+        };
+        proof.verify()?;
+        Ok(proof)
+    }
+
+    async fn storage_proof(
+        &self,
+        contract_address: &Felt,
+        addresses: &[Felt],
+        block: &BlockAnchor,
+    ) -> Result<StarknetStorageProof, StarknetError> {
+        let address_hex: Vec<_> = addresses.iter().map(|a| format!("{:#x}", a)).collect();
+        let params = json!({
+            "block_id": { "block_number": block.number },
+            "contract_addresses": [format!("{:#x}", contract_address)],
+            "contracts_storage_keys": [{
+                "contract_address": format!("{:#x}", contract_address),
+                "storage_keys": address_hex,
+            }],
+        });
+        let result = self.call(&self.rpc_url, "starknet_getStorageProof", params).await?;
+
+        let roots = field(&result, "global_roots")?;
+        let contracts_root = felt_field(roots, "contracts_tree_root")?;
+        let contract_nodes = hashed_nodes(field(field(&result, "contracts_proof")?, "nodes")?)?;
+        let leaf = result["contracts_proof"]["contract_leaves_data"]
+            .get(0)
+            .ok_or_else(|| StarknetError::Rpc("The proof has no contract leaf".into()))?;
+        let storage_nodes = hashed_nodes(
+            result["contracts_storage_proofs"]
+                .get(0)
+                .ok_or_else(|| StarknetError::Rpc("The proof has no storage proof".into()))?,
+        )?;
+        let storage_root = match leaf.get("storage_root") {
+            Some(_) => felt_field(leaf, "storage_root")?,
+            // Nodes before 0.8.1 leave the root out; it is the node nothing refers to
+            None => root_of(&storage_nodes),
+        };
+        let contract = ContractLeaf {
+            class_hash: felt_field(leaf, "class_hash")?,
+            nonce: felt_field(leaf, "nonce")?,
+            storage_root,
+        };
+
+        Ok(StarknetStorageProof {
+            contract_address: *contract_address,
+            storage: storage_values(&contract.storage_root, addresses, &storage_nodes)?,
+            contract,
+            contract_nodes,
+            contracts_root,
+            classes_root: felt_field(roots, "classes_tree_root")?,
+            state_commitment: block.state_commitment,
+            block_number: block.number,
+            block_hash: block.hash,
+        })
+    }
+
+    async fn pathfinder_proof(
+        &self,
+        contract_address: &Felt,
+        addresses: &[Felt],
+        block: &BlockAnchor,
+    ) -> Result<StarknetStorageProof, StarknetError> {
+        let params = json!({
+            "block_id": { "block_number": block.number },
+            "contract_address": format!("{:#x}", contract_address),
+            "keys": addresses.iter().map(|a| format!("{:#x}", a)).collect::<Vec<_>>(),
+        });
+        let url = self.config.pathfinder_url.as_deref().unwrap_or(&self.rpc_url);
+        let result = self.call(url, "pathfinder_getProof", params).await?;
+
+        let contract_nodes: Vec<TrieNode> = list(&result, "contract_proof")?
+            .iter()
+            .map(node_from_json)
+            .collect::<Result<_, _>>()?;
+        // The nodes come from the root down
+        let contracts_root = contract_nodes
+            .first()
+            .map(|node| node.hash(TrieHash::Pedersen))
+            .ok_or_else(|| StarknetError::Rpc("The contract proof is empty".into()))?;
+        let data = result
+            .get("contract_data")
+            .filter(|data| !data.is_null())
+            .ok_or_else(|| StarknetError::Rpc(format!("No contract is deployed at {:#x}", contract_address)))?;
+        let contract = ContractLeaf {
+            class_hash: felt_field(data, "class_hash")?,
+            nonce: felt_field(data, "nonce")?,
+            storage_root: felt_field(data, "root")?,
+        };
+        let mut storage_nodes = Vec::new();
+        for proof in list(data, "storage_proofs")? {
+            let nodes = proof.as_array().ok_or_else(|| StarknetError::Rpc("A storage proof is not a list".into()))?;
+            for node in nodes {
+                let node = node_from_json(node)?;
+                if !storage_nodes.contains(&node) {
+                    storage_nodes.push(node);
+                }
+            }
+        }
+
+        Ok(StarknetStorageProof {
+            contract_address: *contract_address,
+            storage: storage_values(&contract.storage_root, addresses, &storage_nodes)?,
+            contract,
+            contract_nodes,
+            contracts_root,
+            classes_root: felt_field(&result, "class_commitment")?,
+            state_commitment: felt_field(&result, "state_commitment")?,
+            block_number: block.number,
+            block_hash: block.hash,
+        })
+    }
+
+    async fn block(&self, block_number: Option<u64>) -> Result<BlockAnchor, StarknetError> {
+        let block_id = match block_number {
+            Some(number) => json!({ "block_number": number }),
+            None => json!(self.config.block_tag),
+        };
+        let block = self.call(&self.rpc_url, "starknet_getBlockWithTxHashes", json!({ "block_id": block_id })).await?;
+        Ok(BlockAnchor {
+            number: field(&block, "block_number")?
+                .as_u64()
+                .ok_or_else(|| StarknetError::Rpc("The block has no number; pending blocks cannot be proven".into()))?,
+            hash: felt_field(&block, "block_hash")?,
+            state_commitment: felt_field(&block, "new_root")?,
+        })
+    }
+
+    /// Chain ID the node reports, as a short string such as `SN_MAIN`
+    pub async fn chain_id(&self) -> Result<String, StarknetError> {
+        let chain_id = self.call(&self.rpc_url, "starknet_chainId", json!([])).await?;
+        let felt = parse_felt(chain_id.as_str().unwrap_or_default())?;
+        let bytes = felt.to_bytes_be();
+        let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+        String::from_utf8(bytes[start..].to_vec()).map_err(|_| StarknetError::Rpc("Chain ID is not a short string".into()))
+    }
+
+    async fn call(&self, url: &str, method: &str, params: Value) -> Result<Value, StarknetError> {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response: Value = reqwest::Client::new().post(url).json(&request).send().await?.json().await?;
+        if let Some(error) = response.get("error") {
+            return Err(StarknetError::Rpc(format!("{}: {}", method, error)));
+        }
+        Ok(response["result"].clone())
+    }
+}
+
+/// Read each address's value from the storage trie
+fn storage_values(
+    storage_root: &Felt,
+    addresses: &[Felt],
+    nodes: &[TrieNode],
+) -> Result<Vec<StorageValueProof>, StarknetError> {
+    addresses
+        .iter()
+        .map(|address| {
+            let value = verify_trie_proof(storage_root, address, nodes, TrieHash::Pedersen)?;
+            Ok(StorageValueProof { address: *address, value: value.unwrap_or(Felt::ZERO), nodes: nodes.to_vec() })
+        })
+        .collect()
+}
+
+/// Nodes in the JSON-RPC 0.8 form, `{node_hash, node}` with the node `{left, right}` or `{path, length, child}`
+fn hashed_nodes(value: &Value) -> Result<Vec<TrieNode>, StarknetError> {
+    let items = value.as_array().ok_or_else(|| StarknetError::Rpc("Proof nodes are not a list".into()))?;
+    items
+        .iter()
+        .map(|item| {
+            let node = field(item, "node")?;
+            if node.get("left").is_some() {
+                return Ok(TrieNode::Binary { left: felt_field(node, "left")?, right: felt_field(node, "right")? });
+            }
+            let length = field(node, "length")?
+                .as_u64()
+                .and_then(|length| u8::try_from(length).ok())
+                .ok_or_else(|| StarknetError::Rpc(format!("Invalid edge length in {}", node)))?;
+            Ok(TrieNode::Edge { child: felt_field(node, "child")?, path: felt_field(node, "path")?, length })
+        })
+        .collect()
+}
+
+/// The node no other node refers to; zero for an empty trie
+fn root_of(nodes: &[TrieNode]) -> Felt {
+    let children: Vec<Felt> = nodes
+        .iter()
+        .flat_map(|node| match node {
+            TrieNode::Binary { left, right } => [*left, *right].to_vec(),
+            TrieNode::Edge { child, .. } => [*child].to_vec(),
+        })
+        .collect();
+    nodes
+        .iter()
+        .map(|node| node.hash(TrieHash::Pedersen))
+        .find(|hash| !children.contains(hash))
+        .unwrap_or(Felt::ZERO)
+}
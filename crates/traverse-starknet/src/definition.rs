@@ -0,0 +1,392 @@
+//! Storage definitions for Cairo contracts
+//!
+//! A contract class carries no description of its storage, so layouts come
+//! from a definition that mirrors the contract's `Storage` struct: each
+//! variable with its Cairo type, and the structs stored in it. Component
+//! storage is flattened, so variables are named as they are stored, e.g.
+//! `ERC20_balances`. Structs are taken to derive `starknet::Store`, which
+//! writes their members at consecutive offsets.
+
+use crate::storage::parse_felt;
+use crate::StarknetError;
+use serde::{Deserialize, Serialize};
+use starknet_types_core::felt::Felt;
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// A contract's storage, as written by hand
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StarknetStorageDefinition {
+    /// Address the contract is deployed at; may be left out of definitions shared by several deployments
+    #[serde(default)]
+    pub contract: Option<String>,
+    /// Name of the contract, used when no address is given
+    #[serde(default = "default_name")]
+    pub name: String,
+    /// Members of the `Storage` struct
+    pub storage: Vec<VariableDefinition>,
+    #[serde(default)]
+    pub structs: Vec<StructDefinition>,
+}
+
+/// A storage variable, or a member of a struct
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VariableDefinition {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub var_type: String,
+}
+
+/// A struct stored in a variable or a map
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StructDefinition {
+    pub name: String,
+    /// Members in declaration order
+    pub members: Vec<VariableDefinition>,
+}
+
+/// A Cairo type as far as addresses and sizes need it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CairoType {
+    /// One felt: `felt252`, `ContractAddress`, `bool`, integers up to 128 bits, ...
+    Felt(String),
+    /// `u256`: its low and high halves, in that order
+    U256,
+    /// `Map<K, V>` or `LegacyMap<K, V>`; a tuple key contributes one key per element
+    Map { keys: Vec<CairoType>, value: String },
+    /// Any other type: a struct from the definition, or one whose size is not known
+    Named(String),
+}
+
+/// Nesting limit for structs, which also catches recursive definitions
+const MAX_STRUCT_DEPTH: usize = 16;
+
+fn default_name() -> String {
+    "Contract".to_string()
+}
+
+impl StarknetStorageDefinition {
+    /// Parse and check a storage definition
+    pub fn from_json(content: &str) -> Result<Self, StarknetError> {
+        let definition: Self = serde_json::from_str(content).map_err(StarknetError::Json)?;
+        definition.validate()?;
+        Ok(definition)
+    }
+
+    /// The contract's address, if the definition gives one
+    pub fn contract_address(&self) -> Result<Option<Felt>, StarknetError> {
+        self.contract.as_deref().map(parse_felt).transpose()
+    }
+
+    /// A struct of the definition
+    pub fn find_struct(&self, name: &str) -> Option<&StructDefinition> {
+        self.structs.iter().find(|s| s.name == name)
+    }
+
+    /// Size of a type in felts, if known
+    pub fn size_of(&self, type_name: &str) -> Option<usize> {
+        self.size_at_depth(type_name, 0)
+    }
+
+    fn size_at_depth(&self, type_name: &str, depth: usize) -> Option<usize> {
+        match CairoType::parse(type_name) {
+            CairoType::Felt(_) => Some(1),
+            CairoType::U256 => Some(2),
+            // A map occupies no storage of its own
+            CairoType::Map { .. } => Some(0),
+            CairoType::Named(name) if depth < MAX_STRUCT_DEPTH => self
+                .find_struct(&name)?
+                .members
+                .iter()
+                .map(|member| self.size_at_depth(&member.var_type, depth + 1))
+                .sum(),
+            CairoType::Named(_) => None,
+        }
+    }
+
+    /// Members of a struct type, flattened, as `(path, type, felt offset)`
+    pub fn flatten_members(&self, type_name: &str) -> Result<Vec<(String, String, usize)>, StarknetError> {
+        let mut members = Vec::new();
+        self.flatten_into(type_name, "", 0, 0, &mut members)?;
+        Ok(members)
+    }
+
+    fn flatten_into(
+        &self,
+        type_name: &str,
+        prefix: &str,
+        offset: usize,
+        depth: usize,
+        members: &mut Vec<(String, String, usize)>,
+    ) -> Result<(), StarknetError> {
+        let Some(definition) = self.find_struct(type_name) else {
+            return Ok(());
+        };
+        if depth >= MAX_STRUCT_DEPTH {
+            return Err(StarknetError::InvalidDefinition(format!("Struct {} nests too deeply", type_name)));
+        }
+        let mut member_offset = offset;
+        for member in &definition.members {
+            let path = format!("{}{}", prefix, member.name);
+            members.push((path.clone(), member.var_type.clone(), member_offset));
+            self.flatten_into(&member.var_type, &format!("{}.", path), member_offset, depth + 1, members)?;
+            match self.size_of(&member.var_type) {
+                Some(size) => member_offset += size,
+                // Members after one of unknown size have no known offset
+                None => break,
+            }
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), StarknetError> {
+        self.contract_address()?;
+        let mut names: Vec<&str> = self.storage.iter().map(|v| v.name.as_str()).collect();
+        names.sort_unstable();
+        if let Some(pair) = names.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(StarknetError::InvalidDefinition(format!("Variable {} is declared twice", pair[0])));
+        }
+        for variable in &self.storage {
+            if variable.name.is_empty()
+                || !variable.name.is_ascii()
+                || variable.name.contains(['.', '[', ']', ' '])
+            {
+                return Err(StarknetError::InvalidDefinition(format!("'{}' cannot name a variable", variable.name)));
+            }
+            if let CairoType::Map { keys, value } = CairoType::parse(&variable.var_type) {
+                if let Some(key) = keys.iter().find(|key| matches!(key, CairoType::Map { .. })) {
+                    return Err(StarknetError::InvalidDefinition(format!(
+                        "{} is keyed by {:?}, which is not hashable",
+                        variable.name, key
+                    )));
+                }
+                if value.is_empty() {
+                    return Err(StarknetError::InvalidDefinition(format!("{} has no value type", variable.name)));
+                }
+            }
+        }
+        for definition in &self.structs {
+            // Surfaces recursive structs
+            self.flatten_members(&definition.name)?;
+        }
+        Ok(())
+    }
+}
+
+impl CairoType {
+    /// Parse a Cairo type name, ignoring module paths such as `core::integer::`
+    pub fn parse(type_name: &str) -> Self {
+        let type_name = type_name.trim();
+        let (head, args) = match type_name.split_once('<') {
+            Some((head, rest)) => (head.trim(), rest.strip_suffix('>').map(str::trim)),
+            None => (type_name, None),
+        };
+        let short = head.rsplit("::").next().unwrap_or(head);
+        match (short, args) {
+            ("Map" | "LegacyMap", Some(args)) => {
+                let parts = split_top_level(args);
+                let (key, value) = match parts.as_slice() {
+                    [key, value] => (*key, *value),
+                    _ => return Self::Named(type_name.to_string()),
+                };
+                let keys = match key.strip_prefix('(').and_then(|k| k.strip_suffix(')')) {
+                    Some(elements) => split_top_level(elements).into_iter().map(Self::parse).collect(),
+                    None => [Self::parse(key)].to_vec(),
+                };
+                Self::Map { keys, value: value.to_string() }
+            }
+            (
+                "felt252" | "felt" | "ContractAddress" | "ClassHash" | "StorageAddress" | "EthAddress" | "bool"
+                | "bytes31" | "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64"
+                | "i128",
+                None,
+            ) => Self::Felt(short.to_string()),
+            ("u256", None) => Self::U256,
+            _ => Self::Named(type_name.to_string()),
+        }
+    }
+
+    /// Felts a key written in a query hashes as
+    ///
+    /// Numbers are decimal or `0x` hex, `'abc'` is a short string, and
+    /// negative integers wrap around the field as Cairo stores them.
+    pub fn encode_key(&self, key: &str) -> Result<Vec<Felt>, StarknetError> {
+        let key = key.trim();
+        let invalid = || StarknetError::InvalidQuery(format!("'{}' is not a {:?} key", key, self));
+        match self {
+            Self::Felt(name) => {
+                let felt = match (name.as_str(), key) {
+                    ("bool", "true") => Felt::ONE,
+                    ("bool", "false") => Felt::ZERO,
+                    ("bool", _) => return Err(invalid()),
+                    (_, quoted) if quoted.len() >= 2 && quoted.starts_with('\'') && quoted.ends_with('\'') => {
+                        short_string(&quoted[1..quoted.len() - 1]).ok_or_else(invalid)?
+                    }
+                    (_, negative) if negative.starts_with('-') => {
+                        if !name.starts_with('i') {
+                            return Err(invalid());
+                        }
+                        -parse_felt(&negative[1..]).map_err(|_| invalid())?
+                    }
+                    _ => parse_felt(key).map_err(|_| invalid())?,
+                };
+                if !fits(name, &felt) {
+                    return Err(invalid());
+                }
+                Ok([felt].to_vec())
+            }
+            Self::U256 => {
+                let bytes = parse_u256(key).ok_or_else(invalid)?;
+                let mut high = [0u8; 32];
+                let mut low = [0u8; 32];
+                high[16..].copy_from_slice(&bytes[..16]);
+                low[16..].copy_from_slice(&bytes[16..]);
+                Ok([Felt::from_bytes_be(&low), Felt::from_bytes_be(&high)].to_vec())
+            }
+            Self::Map { .. } | Self::Named(_) => {
+                Err(StarknetError::InvalidQuery(format!("Keys of type {:?} cannot be encoded", self)))
+            }
+        }
+    }
+}
+
+/// Whether `felt` is in range for the integer type `name`; other types take any felt
+fn fits(name: &str, felt: &Felt) -> bool {
+    let bits: usize = match name {
+        "usize" => 32,
+        _ => match name.strip_prefix(['u', 'i']).and_then(|bits| bits.parse().ok()) {
+            Some(bits) => bits,
+            None => return true,
+        },
+    };
+    if name.starts_with('u') {
+        return felt.bits() <= bits;
+    }
+    // Negative values sit just below the field's order
+    let limit = Felt::from(1u128 << (bits - 1)).to_bytes_be();
+    felt.to_bytes_be() < limit || (-*felt).to_bytes_be() <= limit
+}
+
+/// A Cairo short string: up to 31 ASCII characters, big-endian
+fn short_string(text: &str) -> Option<Felt> {
+    if text.len() > 31 || !text.is_ascii() {
+        return None;
+    }
+    let mut bytes = [0u8; 32];
+    bytes[32 - text.len()..].copy_from_slice(text.as_bytes());
+    Some(Felt::from_bytes_be(&bytes))
+}
+
+/// A 256-bit unsigned integer, in decimal or `0x` hex, as 32 big-endian bytes
+fn parse_u256(text: &str) -> Option<[u8; 32]> {
+    let mut bytes = [0u8; 32];
+    if let Some(digits) = text.strip_prefix("0x") {
+        if digits.is_empty() || digits.len() > 64 {
+            return None;
+        }
+        hex::decode_to_slice(format!("{:0>64}", digits), &mut bytes).ok()?;
+        return Some(bytes);
+    }
+    if text.is_empty() {
+        return None;
+    }
+    for digit in text.bytes() {
+        let mut carry = (digit as char).to_digit(10)?;
+        for byte in bytes.iter_mut().rev() {
+            let value = *byte as u32 * 10 + carry;
+            *byte = value as u8;
+            carry = value >> 8;
+        }
+        if carry != 0 {
+            return None;
+        }
+    }
+    Some(bytes)
+}
+
+/// Split generic arguments at the commas outside any brackets
+pub(crate) fn split_top_level(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0i32, 0);
+    for (i, c) in text.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(text[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    let last = text[start..].trim();
+    if !last.is_empty() {
+        parts.push(last);
+    }
+    parts
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) const TOKEN_DEFINITION: &str = r#"{
+        "contract": "0x49d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7",
+        "name": "ERC20",
+        "storage": [
+            { "name": "ERC20_total_supply", "type": "u256" },
+            { "name": "ERC20_balances", "type": "Map<ContractAddress, u256>" },
+            { "name": "ERC20_allowances", "type": "Map<(ContractAddress, ContractAddress), u256>" },
+            { "name": "positions", "type": "Map<ContractAddress, Map<u64, Position>>" },
+            { "name": "config", "type": "Config" }
+        ],
+        "structs": [
+            { "name": "Position", "members": [{ "name": "amount", "type": "u256" }, { "name": "owner", "type": "ContractAddress" }] },
+            { "name": "Config", "members": [{ "name": "admin", "type": "ContractAddress" }, { "name": "fee", "type": "Fee" }, { "name": "paused", "type": "bool" }] },
+            { "name": "Fee", "members": [{ "name": "bps", "type": "u16" }, { "name": "recipient", "type": "ContractAddress" }] }
+        ]
+    }"#;
+
+    #[test]
+    fn test_definitions_and_keys() {
+        let definition = StarknetStorageDefinition::from_json(TOKEN_DEFINITION).unwrap();
+        assert_eq!(definition.size_of("Position"), Some(3));
+        assert_eq!(definition.size_of("Config"), Some(4));
+        let members = definition.flatten_members("Config").unwrap();
+        let offsets: Vec<_> = members.iter().map(|(path, _, offset)| (path.as_str(), *offset)).collect();
+        assert_eq!(offsets, [("admin", 0), ("fee", 1), ("fee.bps", 1), ("fee.recipient", 2), ("paused", 3)]);
+
+        let CairoType::Map { keys, value } = CairoType::parse("LegacyMap<(ContractAddress, u256), Map<u8, felt252>>") else {
+            panic!("Expected a map")
+        };
+        assert_eq!(keys, [CairoType::Felt("ContractAddress".into()), CairoType::U256]);
+        assert_eq!(value, "Map<u8, felt252>");
+        assert_eq!(CairoType::parse("core::integer::u128"), CairoType::Felt("u128".into()));
+
+        let u8_key = CairoType::parse("u8");
+        assert_eq!(u8_key.encode_key("255").unwrap(), [Felt::from(255u64)]);
+        assert!(u8_key.encode_key("256").is_err());
+        assert!(u8_key.encode_key("-1").is_err());
+        assert_eq!(CairoType::parse("i8").encode_key("-128").unwrap(), [-Felt::from(128u64)]);
+        assert!(CairoType::parse("i8").encode_key("-129").is_err());
+        assert!(CairoType::parse("i8").encode_key("128").is_err());
+        assert_eq!(CairoType::parse("felt252").encode_key("'ETH'").unwrap(), [Felt::from(0x455448u64)]);
+        assert_eq!(
+            CairoType::U256.encode_key("340282366920938463463374607431768211457").unwrap(),
+            [Felt::ONE, Felt::ONE]
+        );
+        assert_eq!(CairoType::U256.encode_key("0x1").unwrap(), [Felt::ONE, Felt::ZERO]);
+        assert!(CairoType::parse("bool").encode_key("1").is_err());
+
+        let recursive = r#"{ "storage": [], "structs": [{ "name": "A", "members": [{ "name": "a", "type": "A" }] }] }"#;
+        assert!(StarknetStorageDefinition::from_json(recursive).is_err());
+        let twice = r#"{ "storage": [{ "name": "a", "type": "u8" }, { "name": "a", "type": "u8" }] }"#;
+        assert!(StarknetStorageDefinition::from_json(twice).is_err());
+        assert!(StarknetStorageDefinition::from_json(r#"{ "contract": "token", "storage": [] }"#).is_err());
+    }
+}
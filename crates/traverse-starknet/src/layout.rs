@@ -0,0 +1,172 @@
+//! Layout compilation from storage definitions
+//!
+//! Each storage variable becomes an entry whose slot is its base address in
+//! hex. The members of a struct stored in a variable, or in the values of a
+//! map, become entries `var.member` with the variable's slot and the
+//! member's offset in felts from the address of the struct; Cairo offsets
+//! fit in a byte, as [`StorageEntry::offset`] does.
+
+use crate::definition::{CairoType, StarknetStorageDefinition};
+use crate::storage::storage_address;
+use crate::StarknetError;
+use traverse_core::{LayoutInfo, StorageEntry, TypeInfo, ZeroSemantics};
+
+#[cfg(feature = "std")]
+use std::path::Path;
+
+#[cfg(feature = "std")]
+use traverse_core::{LayoutCompiler, TraverseError};
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Encoding recorded for values stored as felts
+pub const FELT_ENCODING: &str = "felt";
+
+/// Encoding recorded for maps, which store nothing at their own address
+pub const MAP_ENCODING: &str = "map";
+
+/// Layout compiler for Cairo contracts
+///
+/// # Usage
+///
+/// ```rust,ignore
+/// use traverse_starknet::StarknetLayoutCompiler;
+/// use traverse_core::LayoutCompiler;
+///
+/// let layout = StarknetLayoutCompiler.compile_layout(Path::new("storage.json"))?;
+/// assert!(layout.storage.iter().any(|e| e.label == "ERC20_balances"));
+/// ```
+pub struct StarknetLayoutCompiler;
+
+impl StarknetLayoutCompiler {
+    /// Compile a parsed storage definition
+    ///
+    /// The layout is named after the contract's address, or after the
+    /// definition's name when it gives no address.
+    pub fn compile_definition(definition: &StarknetStorageDefinition) -> Result<LayoutInfo, StarknetError> {
+        let mut storage = Vec::new();
+        let mut types: Vec<TypeInfo> = Vec::new();
+        let mut add_type = |label: &str, key: Option<String>, value: Option<String>| {
+            if !types.iter().any(|t| t.label == label) {
+                let encoding = if key.is_some() { MAP_ENCODING } else { FELT_ENCODING };
+                types.push(TypeInfo {
+                    label: label.to_string(),
+                    // 0 for maps and types of unknown size
+                    number_of_bytes: (definition.size_of(label).unwrap_or(0) * 32).to_string(),
+                    encoding: encoding.to_string(),
+                    base: None,
+                    key,
+                    value,
+                });
+            }
+        };
+
+        for variable in &definition.storage {
+            let slot = format!("{:#x}", storage_address(&variable.name, &[])?);
+            storage.push(StorageEntry {
+                label: variable.name.clone(),
+                slot: slot.clone(),
+                offset: 0,
+                type_name: variable.var_type.clone(),
+                // Unwritten storage reads as zero
+                zero_semantics: ZeroSemantics::NeverWritten,
+            });
+
+            // The value type at the end of any chain of maps
+            let mut value_type = variable.var_type.clone();
+            while let CairoType::Map { keys, value } = CairoType::parse(&value_type) {
+                let key: Vec<_> = keys.iter().map(type_label).collect();
+                add_type(&value_type, Some(key.join(", ")), Some(value.clone()));
+                value_type = value;
+            }
+            add_type(&value_type, None, None);
+
+            for (path, member_type, offset) in definition.flatten_members(&value_type)? {
+                let offset = u8::try_from(offset).map_err(|_| {
+                    StarknetError::InvalidDefinition(format!("{}.{} is more than 255 felts in", variable.name, path))
+                })?;
+                storage.push(StorageEntry {
+                    label: format!("{}.{}", variable.name, path),
+                    slot: slot.clone(),
+                    offset,
+                    type_name: member_type.clone(),
+                    zero_semantics: ZeroSemantics::NeverWritten,
+                });
+                add_type(&member_type, None, None);
+            }
+        }
+
+        let contract_name = match definition.contract_address()? {
+            Some(address) => format!("{:#x}", address),
+            None => definition.name.clone(),
+        };
+        Ok(LayoutInfo { contract_name, storage, types, code_variables: Vec::new() })
+    }
+}
+
+fn type_label(key: &CairoType) -> String {
+    match key {
+        CairoType::Felt(name) | CairoType::Named(name) => name.clone(),
+        CairoType::U256 => "u256".to_string(),
+        CairoType::Map { .. } => "Map".to_string(),
+    }
+}
+
+#[cfg(feature = "std")]
+impl LayoutCompiler for StarknetLayoutCompiler {
+    /// Compile a layout from a storage definition
+    fn compile_layout(&self, definition_path: &Path) -> Result<LayoutInfo, TraverseError> {
+        let content = std::fs::read_to_string(definition_path)?;
+        let definition = StarknetStorageDefinition::from_json(&content)?;
+        Ok(Self::compile_definition(&definition)?)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::definition::tests::TOKEN_DEFINITION;
+
+    pub(crate) fn token_layout() -> LayoutInfo {
+        let definition = StarknetStorageDefinition::from_json(TOKEN_DEFINITION).unwrap();
+        StarknetLayoutCompiler::compile_definition(&definition).unwrap()
+    }
+
+    #[test]
+    fn test_compile_definition() {
+        let layout = token_layout();
+        assert_eq!(layout.contract_name, "0x49d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7");
+
+        let entries: Vec<_> = layout.storage.iter().map(|e| (e.label.as_str(), e.offset)).collect();
+        assert_eq!(entries, [
+            ("ERC20_total_supply", 0),
+            ("ERC20_balances", 0),
+            ("ERC20_allowances", 0),
+            ("positions", 0),
+            ("positions.amount", 0),
+            ("positions.owner", 2),
+            ("config", 0),
+            ("config.admin", 0),
+            ("config.fee", 1),
+            ("config.fee.bps", 1),
+            ("config.fee.recipient", 2),
+            ("config.paused", 3),
+        ]);
+        assert_eq!(layout.storage[3].slot, layout.storage[5].slot);
+        assert_eq!(layout.storage[0].slot, format!("{:#x}", storage_address("ERC20_total_supply", &[]).unwrap()));
+
+        let type_info = |label: &str| layout.types.iter().find(|t| t.label == label).unwrap();
+        assert_eq!(type_info("u256").number_of_bytes, "64");
+        assert_eq!(type_info("Position").number_of_bytes, "96");
+        let allowances = type_info("Map<(ContractAddress, ContractAddress), u256>");
+        assert_eq!(allowances.key.as_deref(), Some("ContractAddress, ContractAddress"));
+        assert_eq!((allowances.encoding.as_str(), allowances.number_of_bytes.as_str()), ("map", "0"));
+        assert_eq!(type_info("Map<ContractAddress, Map<u64, Position>>").value.as_deref(), Some("Map<u64, Position>"));
+        assert_eq!(type_info("Map<u64, Position>").key.as_deref(), Some("u64"));
+    }
+}
@@ -0,0 +1,104 @@
+//! Contract storage layouts and storage proofs for Starknet
+//!
+//! A Starknet contract's storage is a map from 251-bit addresses to felts.
+//! A storage variable lives at the `sn_keccak` of its name, a map entry at
+//! the Pedersen hash chain of that address and the entry's keys, and the
+//! members of a struct at consecutive offsets from its address. Each
+//! contract's storage is committed to by a binary Merkle-Patricia trie
+//! hashed with Pedersen, whose root is part of the contract's leaf in the
+//! contracts trie; the block's state commitment is the Poseidon hash of
+//! the contracts trie root and the classes trie root. This crate maps that
+//! model onto the traverse core types.
+//!
+//! # Features
+//!
+//! - **Layout Compilation**: Turn a storage definition into a layout of storage addresses
+//! - **Query Resolution**: Resolve `var`, `map[key]` and `map[key].member` queries to addresses
+//! - **Proof Verification**: Check storage and contract proofs against a block's state commitment
+//! - **Proof Fetching**: Read storage proofs from pathfinder or Juno (`client`)
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! use traverse_starknet::{StarknetKeyResolver, StarknetLayoutCompiler};
+//! use traverse_core::{KeyResolver, LayoutCompiler};
+//!
+//! let layout = StarknetLayoutCompiler.compile_layout(Path::new("storage.json"))?;
+//! let path = StarknetKeyResolver.resolve(&layout, "ERC20_balances[0x1234]")?;
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(not(feature = "std"))]
+use alloc::string::{String, ToString};
+
+pub mod definition;
+pub mod layout;
+pub mod proof;
+pub mod resolver;
+pub mod storage;
+pub mod trie;
+
+#[cfg(feature = "client")]
+pub mod client;
+
+pub use definition::{CairoType, StarknetStorageDefinition, StructDefinition, VariableDefinition};
+pub use layout::StarknetLayoutCompiler;
+pub use proof::{ContractLeaf, StarknetStorageProof, StorageValueProof};
+pub use resolver::{StarknetKeyResolver, StorageSlot};
+pub use starknet_types_core::felt::Felt;
+pub use storage::{normalize_address, parse_felt, sn_keccak, storage_address, ADDRESS_BOUND};
+pub use trie::{verify_trie_proof, TrieHash, TrieNode, TRIE_HEIGHT};
+
+#[cfg(feature = "client")]
+pub use client::{StarknetChainConfig, StarknetProofApi, StarknetProofFetcher};
+
+/// Error types specific to Starknet
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+#[derive(Debug)]
+pub enum StarknetError {
+    #[cfg_attr(feature = "std", error("Invalid storage definition: {0}"))]
+    InvalidDefinition(String),
+
+    #[cfg_attr(feature = "std", error("Invalid query: {0}"))]
+    InvalidQuery(String),
+
+    #[cfg_attr(feature = "std", error("Invalid felt: {0}"))]
+    InvalidFelt(String),
+
+    #[cfg_attr(feature = "std", error("Proof verification failed: {0}"))]
+    ProofVerification(String),
+
+    #[cfg_attr(feature = "std", error("RPC error: {0}"))]
+    Rpc(String),
+
+    #[cfg(feature = "client")]
+    #[cfg_attr(feature = "std", error("Network error: {0}"))]
+    Network(#[from] reqwest::Error),
+
+    #[cfg_attr(feature = "std", error("JSON error: {0}"))]
+    Json(#[cfg_attr(feature = "std", from)] serde_json::Error),
+
+    #[cfg_attr(feature = "std", error("Traverse core error: {0}"))]
+    TraverseCore(#[cfg_attr(feature = "std", from)] traverse_core::TraverseError),
+}
+
+impl From<StarknetError> for traverse_core::TraverseError {
+    fn from(err: StarknetError) -> Self {
+        use traverse_core::TraverseError;
+
+        match err {
+            StarknetError::InvalidDefinition(msg) => TraverseError::LayoutCompilation(msg),
+            StarknetError::InvalidQuery(msg) | StarknetError::InvalidFelt(msg) => TraverseError::InvalidInput(msg),
+            StarknetError::ProofVerification(msg) => TraverseError::Validation(msg),
+            StarknetError::Rpc(msg) => TraverseError::ExternalService(msg),
+            #[cfg(feature = "client")]
+            StarknetError::Network(err) => TraverseError::ExternalService(err.to_string()),
+            StarknetError::Json(err) => TraverseError::Serialization(err.to_string()),
+            StarknetError::TraverseCore(err) => err,
+        }
+    }
+}
@@ -0,0 +1,278 @@
+//! Storage proofs anchored to a block's state commitment
+//!
+//! A storage value is proven in two steps: the contract's storage trie
+//! leads from its root to the value, and the contracts trie leads from its
+//! root to the contract's leaf, `H(H(H(class_hash, storage_root), nonce), 0)`
+//! with Pedersen. The block header commits to
+//! `Poseidon("STARKNET_STATE_V0", contracts_root, classes_root)`, or to the
+//! contracts root alone while the classes trie is empty.
+
+use crate::trie::{verify_trie_proof, TrieHash, TrieNode};
+use crate::StarknetError;
+use serde_json::{json, Value};
+use starknet_types_core::felt::Felt;
+use starknet_types_core::hash::{Pedersen, Poseidon, StarkHash};
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::String, vec::Vec};
+
+/// `'STARKNET_STATE_V0'` as a short string
+pub const STATE_COMMITMENT_VERSION: Felt = Felt::from_hex_unchecked("0x535441524b4e45545f53544154455f5630");
+
+/// A contract's leaf in the contracts trie
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractLeaf {
+    pub class_hash: Felt,
+    pub nonce: Felt,
+    pub storage_root: Felt,
+}
+
+/// A storage value, zero when the trie holds none, with the nodes proving it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageValueProof {
+    pub address: Felt,
+    pub value: Felt,
+    /// Storage trie nodes from the contract's storage root to the address
+    pub nodes: Vec<TrieNode>,
+}
+
+/// Storage values of a contract with the trie nodes proving them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StarknetStorageProof {
+    pub contract_address: Felt,
+    pub contract: ContractLeaf,
+    /// Contracts trie nodes from the contracts root to the contract
+    pub contract_nodes: Vec<TrieNode>,
+    pub storage: Vec<StorageValueProof>,
+    pub contracts_root: Felt,
+    pub classes_root: Felt,
+    /// State commitment in the block's header
+    pub state_commitment: Felt,
+    pub block_number: u64,
+    pub block_hash: Felt,
+}
+
+impl ContractLeaf {
+    /// The leaf's value in the contracts trie
+    pub fn hash(&self) -> Felt {
+        let hash = Pedersen::hash(&Pedersen::hash(&self.class_hash, &self.storage_root), &self.nonce);
+        Pedersen::hash(&hash, &Felt::ZERO)
+    }
+}
+
+/// State commitment of a block with these trie roots
+pub fn state_commitment(contracts_root: &Felt, classes_root: &Felt) -> Felt {
+    if *classes_root == Felt::ZERO {
+        *contracts_root
+    } else {
+        Poseidon::hash_array(&[STATE_COMMITMENT_VERSION, *contracts_root, *classes_root])
+    }
+}
+
+impl StarknetStorageProof {
+    /// Check every value against the state commitment
+    pub fn verify(&self) -> Result<(), StarknetError> {
+        if state_commitment(&self.contracts_root, &self.classes_root) != self.state_commitment {
+            return Err(StarknetError::ProofVerification("the trie roots do not make up the state commitment".into()));
+        }
+        let leaf = verify_trie_proof(&self.contracts_root, &self.contract_address, &self.contract_nodes, TrieHash::Pedersen)?;
+        match leaf {
+            Some(leaf) if leaf == self.contract.hash() => {}
+            Some(_) => return Err(StarknetError::ProofVerification("the contract leaf does not match the trie".into())),
+            None => {
+                return Err(StarknetError::ProofVerification(format!(
+                    "no contract is deployed at {:#x}",
+                    self.contract_address
+                )))
+            }
+        }
+        for value in &self.storage {
+            let proven = verify_trie_proof(&self.contract.storage_root, &value.address, &value.nodes, TrieHash::Pedersen)?;
+            if proven.unwrap_or(Felt::ZERO) != value.value {
+                return Err(StarknetError::ProofVerification(format!(
+                    "the value at {:#x} does not match the storage trie",
+                    value.address
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The proven value at `address`
+    pub fn value_at(&self, address: &Felt) -> Option<Felt> {
+        self.storage.iter().find(|value| value.address == *address).map(|value| value.value)
+    }
+
+    /// JSON form with felts in `0x` hex and nodes as pathfinder writes them
+    pub fn to_json(&self) -> Value {
+        json!({
+            "contract_address": felt_hex(&self.contract_address),
+            "contract": {
+                "class_hash": felt_hex(&self.contract.class_hash),
+                "nonce": felt_hex(&self.contract.nonce),
+                "storage_root": felt_hex(&self.contract.storage_root),
+            },
+            "contract_nodes": self.contract_nodes.iter().map(node_to_json).collect::<Vec<_>>(),
+            "storage": self.storage.iter().map(|value| json!({
+                "address": felt_hex(&value.address),
+                "value": felt_hex(&value.value),
+                "nodes": value.nodes.iter().map(node_to_json).collect::<Vec<_>>(),
+            })).collect::<Vec<_>>(),
+            "contracts_root": felt_hex(&self.contracts_root),
+            "classes_root": felt_hex(&self.classes_root),
+            "state_commitment": felt_hex(&self.state_commitment),
+            "block_number": self.block_number,
+            "block_hash": felt_hex(&self.block_hash),
+        })
+    }
+
+    /// Parse the JSON written by [`Self::to_json`]
+    pub fn from_json(value: &Value) -> Result<Self, StarknetError> {
+        let contract = field(value, "contract")?;
+        let nodes = |value: &Value| -> Result<Vec<TrieNode>, StarknetError> {
+            list(value, "nodes")?.iter().map(node_from_json).collect()
+        };
+        Ok(Self {
+            contract_address: felt_field(value, "contract_address")?,
+            contract: ContractLeaf {
+                class_hash: felt_field(contract, "class_hash")?,
+                nonce: felt_field(contract, "nonce")?,
+                storage_root: felt_field(contract, "storage_root")?,
+            },
+            contract_nodes: list(value, "contract_nodes")?.iter().map(node_from_json).collect::<Result<_, _>>()?,
+            storage: list(value, "storage")?
+                .iter()
+                .map(|value| {
+                    Ok(StorageValueProof {
+                        address: felt_field(value, "address")?,
+                        value: felt_field(value, "value")?,
+                        nodes: nodes(value)?,
+                    })
+                })
+                .collect::<Result<_, StarknetError>>()?,
+            contracts_root: felt_field(value, "contracts_root")?,
+            classes_root: felt_field(value, "classes_root")?,
+            state_commitment: felt_field(value, "state_commitment")?,
+            block_number: field(value, "block_number")?
+                .as_u64()
+                .ok_or_else(|| StarknetError::Rpc("Proof field 'block_number' is not a number".into()))?,
+            block_hash: felt_field(value, "block_hash")?,
+        })
+    }
+}
+
+/// `{"binary": {left, right}}` or `{"edge": {child, path: {value, len}}}`
+pub fn node_to_json(node: &TrieNode) -> Value {
+    match node {
+        TrieNode::Binary { left, right } => json!({ "binary": { "left": felt_hex(left), "right": felt_hex(right) } }),
+        TrieNode::Edge { child, path, length } => json!({
+            "edge": { "child": felt_hex(child), "path": { "value": felt_hex(path), "len": length } }
+        }),
+    }
+}
+
+/// Parse a node in the form [`node_to_json`] writes
+pub fn node_from_json(value: &Value) -> Result<TrieNode, StarknetError> {
+    if let Some(binary) = value.get("binary") {
+        return Ok(TrieNode::Binary { left: felt_field(binary, "left")?, right: felt_field(binary, "right")? });
+    }
+    let edge = field(value, "edge")?;
+    let path = field(edge, "path")?;
+    let length = field(path, "len")?
+        .as_u64()
+        .and_then(|len| u8::try_from(len).ok())
+        .ok_or_else(|| StarknetError::Rpc(format!("Invalid edge length in {}", value)))?;
+    Ok(TrieNode::Edge { child: felt_field(edge, "child")?, path: felt_field(path, "value")?, length })
+}
+
+pub(crate) fn felt_hex(felt: &Felt) -> String {
+    format!("{:#x}", felt)
+}
+
+pub(crate) fn field<'a>(value: &'a Value, name: &str) -> Result<&'a Value, StarknetError> {
+    value.get(name).ok_or_else(|| StarknetError::Rpc(format!("Missing '{}' in {}", name, value)))
+}
+
+pub(crate) fn felt_field(value: &Value, name: &str) -> Result<Felt, StarknetError> {
+    let text = field(value, name)?
+        .as_str()
+        .ok_or_else(|| StarknetError::Rpc(format!("Field '{}' is not a hex string", name)))?;
+    crate::storage::parse_felt(text)
+}
+
+pub(crate) fn list<'a>(value: &'a Value, name: &str) -> Result<&'a Vec<Value>, StarknetError> {
+    field(value, name)?.as_array().ok_or_else(|| StarknetError::Rpc(format!("Field '{}' is not a list", name)))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::storage::storage_address;
+    use crate::trie::tests::small_trie;
+
+    /// A proof of the two felts of a balance, in a contract whose storage also holds its total supply
+    pub(crate) fn token_proof() -> StarknetStorageProof {
+        let balance = storage_address("ERC20_balances", &[Felt::from_hex_unchecked("0x1234")]).unwrap();
+        let supply = storage_address("ERC20_total_supply", &[]).unwrap();
+        let storage = [(balance, Felt::from(500u64)), (supply, Felt::from(1_000u64))];
+        let (storage_root, storage_nodes) = small_trie(&storage, TrieHash::Pedersen);
+
+        let contract_address = Felt::from_hex_unchecked("0x49d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7");
+        let contract = ContractLeaf { class_hash: Felt::from(0xc1a55u64), nonce: Felt::ZERO, storage_root };
+        let other = ContractLeaf { class_hash: Felt::from(0xc1a55u64), nonce: Felt::ONE, storage_root: Felt::ONE };
+        let (contracts_root, contract_nodes) =
+            small_trie(&[(contract_address, contract.hash()), (Felt::from(0x1001u64), other.hash())], TrieHash::Pedersen);
+        let classes_root = Felt::from(0xc1a55e5u64);
+
+        StarknetStorageProof {
+            contract_address,
+            contract,
+            contract_nodes,
+            storage: [balance, balance + Felt::ONE]
+                .into_iter()
+                .map(|address| StorageValueProof {
+                    address,
+                    value: storage.iter().find(|(a, _)| *a == address).map(|(_, v)| *v).unwrap_or(Felt::ZERO),
+                    nodes: storage_nodes.clone(),
+                })
+                .collect(),
+            contracts_root,
+            classes_root,
+            state_commitment: state_commitment(&contracts_root, &classes_root),
+            block_number: 900_000,
+            block_hash: Felt::from(0xb10cu64),
+        }
+    }
+
+    #[test]
+    fn test_proof_verification_and_json() {
+        let proof = token_proof();
+        proof.verify().unwrap();
+        assert_eq!(proof.value_at(&proof.storage[1].address), Some(Felt::ZERO));
+        assert_eq!(StarknetStorageProof::from_json(&proof.to_json()).unwrap(), proof);
+        assert_ne!(proof.state_commitment, proof.contracts_root);
+
+        let mut other_value = proof.clone();
+        other_value.storage[0].value = Felt::from(501u64);
+        assert!(other_value.verify().is_err());
+
+        // The high half is absent, so it cannot be claimed as nonzero
+        let mut absent = proof.clone();
+        absent.storage[1].value = Felt::ONE;
+        assert!(absent.verify().is_err());
+
+        let mut other_nonce = proof.clone();
+        other_nonce.contract.nonce = Felt::ONE;
+        assert!(other_nonce.verify().is_err());
+
+        let mut other_contract = proof.clone();
+        other_contract.contract_address = Felt::from(0x1002u64);
+        assert!(other_contract.verify().is_err());
+
+        let mut other_commitment = proof;
+        other_commitment.classes_root = Felt::ZERO;
+        assert!(other_commitment.verify().is_err());
+        other_commitment.state_commitment = other_commitment.contracts_root;
+        other_commitment.verify().unwrap();
+    }
+}
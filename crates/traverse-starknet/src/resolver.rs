@@ -0,0 +1,203 @@
+//! Storage address resolution
+//!
+//! Queries name a variable, an entry of a map, or a member of a struct:
+//! `ERC20_total_supply`, `ERC20_balances[0x1234]`, `ERC20_allowances[0x1, 0x2]`
+//! for a tuple key, `positions[0x1][7].owner` through nested maps, or
+//! `config.fee.bps`. The resolved key is the address of the value's first
+//! felt; a `u256` or a struct continues at the addresses after it.
+
+use crate::definition::{split_top_level, CairoType};
+use crate::storage::{entry_address, parse_felt};
+use crate::StarknetError;
+use starknet_types_core::felt::Felt;
+use traverse_core::{Key, KeyResolver, LayoutInfo, StaticKeyPath, StorageEntry, TraverseError, ZeroSemantics};
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::String, vec::Vec};
+
+/// Key resolver for Cairo contract storage
+///
+/// # Usage
+///
+/// ```rust,ignore
+/// use traverse_starknet::StarknetKeyResolver;
+/// use traverse_core::KeyResolver;
+///
+/// let path = StarknetKeyResolver.resolve(&layout, "ERC20_balances[0x1234]")?;
+/// ```
+pub struct StarknetKeyResolver;
+
+/// A value resolved against a layout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageSlot {
+    /// Address of the value's first felt
+    pub address: Felt,
+    pub type_name: String,
+    /// Number of felts the value spans, if known
+    pub felts: Option<usize>,
+    pub zero_semantics: ZeroSemantics,
+}
+
+impl StorageSlot {
+    /// Addresses of each felt of the value; empty when its size is not known
+    pub fn addresses(&self) -> Vec<Felt> {
+        (0..self.felts.unwrap_or(0) as u64).map(|i| self.address + Felt::from(i)).collect()
+    }
+}
+
+impl StarknetKeyResolver {
+    /// Resolve a query to the address of the value it names
+    pub fn storage_slot(layout: &LayoutInfo, query: &str) -> Result<StorageSlot, StarknetError> {
+        let query = query.trim();
+        let name_end = query.find(['[', '.']).unwrap_or(query.len());
+        let name = &query[..name_end];
+        let entry = find_entry(layout, name)?;
+        let base = parse_felt(&entry.slot)
+            .map_err(|_| StarknetError::InvalidQuery(format!("Layout entry {} has no address", entry.label)))?;
+
+        let mut rest = &query[name_end..];
+        let mut keys = Vec::new();
+        let mut value_type = entry.type_name.clone();
+        while let Some(group) = rest.strip_prefix('[') {
+            let close = group
+                .find(']')
+                .ok_or_else(|| StarknetError::InvalidQuery(format!("Unclosed '[' in '{}'", query)))?;
+            let CairoType::Map { keys: key_types, value } = CairoType::parse(&value_type) else {
+                return Err(StarknetError::InvalidQuery(format!("{} is a {}, not a map", name, value_type)));
+            };
+            let written = split_top_level(&group[..close]);
+            if written.len() != key_types.len() {
+                return Err(StarknetError::InvalidQuery(format!(
+                    "{} is keyed by {} values, not {}",
+                    value_type,
+                    key_types.len(),
+                    written.len()
+                )));
+            }
+            for (key_type, key) in key_types.iter().zip(written) {
+                keys.extend(key_type.encode_key(key)?);
+            }
+            value_type = value;
+            rest = &group[close + 1..];
+        }
+        if matches!(CairoType::parse(&value_type), CairoType::Map { .. }) {
+            return Err(StarknetError::InvalidQuery(format!("{} needs a key: {}[key]", query, query)));
+        }
+        let mut address = entry_address(base, &keys);
+
+        if !rest.is_empty() {
+            let path = rest
+                .strip_prefix('.')
+                .filter(|path| !path.is_empty())
+                .ok_or_else(|| StarknetError::InvalidQuery(format!("Unexpected '{}' in '{}'", rest, query)))?;
+            let member = find_entry(layout, &format!("{}.{}", name, path))?;
+            address += Felt::from(member.offset as u64);
+            value_type = member.type_name.clone();
+        }
+
+        Ok(StorageSlot {
+            address,
+            felts: type_size(layout, &value_type),
+            type_name: value_type,
+            zero_semantics: entry.zero_semantics,
+        })
+    }
+}
+
+impl KeyResolver for StarknetKeyResolver {
+    /// Resolve a query to the address of its value's first felt
+    fn resolve(&self, layout: &LayoutInfo, query: &str) -> Result<StaticKeyPath, TraverseError> {
+        let slot = Self::storage_slot(layout, query)?;
+        Ok(StaticKeyPath {
+            name: "starknet_storage",
+            key: Key::Fixed(slot.address.to_bytes_be()),
+            offset: None,
+            field_size: slot.felts.and_then(|felts| u8::try_from(felts * 32).ok()),
+            layout_commitment: layout.commitment(),
+            zero_semantics: slot.zero_semantics,
+        })
+    }
+
+    /// Resolve the variables and struct members that are not behind a map
+    fn resolve_all(&self, layout: &LayoutInfo) -> Result<Vec<StaticKeyPath>, TraverseError> {
+        let is_map = |label: &str| {
+            layout
+                .storage
+                .iter()
+                .find(|e| e.label == label)
+                .is_some_and(|e| matches!(CairoType::parse(&e.type_name), CairoType::Map { .. }))
+        };
+        layout
+            .storage
+            .iter()
+            .filter(|entry| !is_map(entry.label.split('.').next().unwrap_or_default()))
+            .map(|entry| self.resolve(layout, &entry.label))
+            .collect()
+    }
+}
+
+fn find_entry<'a>(layout: &'a LayoutInfo, label: &str) -> Result<&'a StorageEntry, StarknetError> {
+    layout
+        .storage
+        .iter()
+        .find(|e| e.label == label)
+        .ok_or_else(|| StarknetError::InvalidQuery(format!("{} has no {}", layout.contract_name, label)))
+}
+
+fn type_size(layout: &LayoutInfo, type_name: &str) -> Option<usize> {
+    layout
+        .types
+        .iter()
+        .find(|t| t.label == type_name)
+        .and_then(|t| t.number_of_bytes.parse::<usize>().ok())
+        .filter(|size| *size > 0)
+        .map(|size| size / 32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::tests::token_layout;
+    use crate::storage::storage_address;
+
+    #[test]
+    fn test_resolve_storage_addresses() {
+        let layout = token_layout();
+        let owner = Felt::from_hex_unchecked("0x1234");
+        let spender = Felt::from_hex_unchecked("0x5678");
+
+        let supply = StarknetKeyResolver::storage_slot(&layout, "ERC20_total_supply").unwrap();
+        assert_eq!(supply.address, storage_address("ERC20_total_supply", &[]).unwrap());
+        assert_eq!(supply.addresses(), [supply.address, supply.address + Felt::ONE]);
+
+        let balance = StarknetKeyResolver::storage_slot(&layout, "ERC20_balances[0x1234]").unwrap();
+        assert_eq!(balance.address, storage_address("ERC20_balances", &[owner]).unwrap());
+        assert_eq!((balance.type_name.as_str(), balance.felts), ("u256", Some(2)));
+        let path = StarknetKeyResolver.resolve(&layout, "ERC20_balances[4660]").unwrap();
+        assert_eq!(path.key, Key::Fixed(balance.address.to_bytes_be()));
+        assert_eq!(path.field_size, Some(64));
+
+        let allowance = StarknetKeyResolver::storage_slot(&layout, "ERC20_allowances[0x1234, 0x5678]").unwrap();
+        assert_eq!(allowance.address, storage_address("ERC20_allowances", &[owner, spender]).unwrap());
+
+        let position_owner = StarknetKeyResolver::storage_slot(&layout, "positions[0x1234][7].owner").unwrap();
+        let position = storage_address("positions", &[owner, Felt::from(7u64)]).unwrap();
+        assert_eq!(position_owner.address, position + Felt::TWO);
+        assert_eq!(position_owner.felts, Some(1));
+        assert_eq!(StarknetKeyResolver::storage_slot(&layout, "positions[0x1234][7]").unwrap().felts, Some(3));
+
+        let bps = StarknetKeyResolver::storage_slot(&layout, "config.fee.bps").unwrap();
+        assert_eq!(bps.address, storage_address("config", &[]).unwrap() + Felt::ONE);
+
+        assert!(StarknetKeyResolver::storage_slot(&layout, "ERC20_balances").is_err());
+        assert!(StarknetKeyResolver::storage_slot(&layout, "positions[0x1234]").is_err());
+        assert!(StarknetKeyResolver::storage_slot(&layout, "ERC20_allowances[0x1234]").is_err());
+        assert!(StarknetKeyResolver::storage_slot(&layout, "ERC20_total_supply[1]").is_err());
+        assert!(StarknetKeyResolver::storage_slot(&layout, "positions[0x1234][-1]").is_err());
+        assert!(StarknetKeyResolver::storage_slot(&layout, "config.fee.amount").is_err());
+        assert!(StarknetKeyResolver::storage_slot(&layout, "ERC20_balances[0x1234").is_err());
+
+        let resolved = StarknetKeyResolver.resolve_all(&layout).unwrap();
+        assert_eq!(resolved.len(), 7);
+    }
+}
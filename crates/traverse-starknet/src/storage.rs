@@ -0,0 +1,116 @@
+//! Storage address derivation
+//!
+//! A storage variable's address is the `sn_keccak` of its name: Keccak-256
+//! truncated to 250 bits. Map entries chain the Pedersen hash over the
+//! entry's keys, as Cairo's `LegacyHash` and storage paths do, and the
+//! result is reduced below [`ADDRESS_BOUND`] so that a value of several
+//! felts, or a struct, fits above it.
+
+use crate::StarknetError;
+use starknet_types_core::felt::Felt;
+use starknet_types_core::hash::{Pedersen, StarkHash};
+use tiny_keccak::{Hasher, Keccak};
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::ToString};
+
+/// Storage addresses lie below 2^251 - 256
+pub const ADDRESS_BOUND: Felt =
+    Felt::from_hex_unchecked("0x7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff00");
+
+/// Keccak-256 of `data`, keeping its low 250 bits
+pub fn sn_keccak(data: &[u8]) -> Felt {
+    let mut hash = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    keccak.update(data);
+    keccak.finalize(&mut hash);
+    hash[0] &= 0x03;
+    Felt::from_bytes_be(&hash)
+}
+
+/// Reduce an address below [`ADDRESS_BOUND`]
+pub fn normalize_address(address: Felt) -> Felt {
+    // Felts are below 2^252, so one subtraction suffices
+    if address.to_bytes_be() >= ADDRESS_BOUND.to_bytes_be() {
+        address - ADDRESS_BOUND
+    } else {
+        address
+    }
+}
+
+/// Address of a storage variable, or of its entry under `keys`
+pub fn storage_address(name: &str, keys: &[Felt]) -> Result<Felt, StarknetError> {
+    if name.is_empty() || !name.is_ascii() {
+        return Err(StarknetError::InvalidQuery(format!("'{}' cannot name a storage variable", name)));
+    }
+    Ok(entry_address(sn_keccak(name.as_bytes()), keys))
+}
+
+/// Address of the entry under `keys` of the map at `base`
+pub fn entry_address(base: Felt, keys: &[Felt]) -> Felt {
+    normalize_address(keys.iter().fold(base, |address, key| Pedersen::hash(&address, key)))
+}
+
+/// Parse a felt written as `0x` hex or in decimal, rejecting values of the field's size or more
+pub fn parse_felt(text: &str) -> Result<Felt, StarknetError> {
+    let text = text.trim();
+    let invalid = || StarknetError::InvalidFelt(format!("'{}' is not a felt", text));
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(digits) => {
+            if digits.is_empty() || digits.len() > 64 {
+                return Err(invalid());
+            }
+            let padded = format!("{:0>64}", digits);
+            let mut bytes = [0u8; 32];
+            hex::decode_to_slice(&padded, &mut bytes).map_err(|_| invalid())?;
+            if bytes > Felt::MAX.to_bytes_be() {
+                return Err(invalid());
+            }
+            Ok(Felt::from_bytes_be(&bytes))
+        }
+        None => {
+            if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(invalid());
+            }
+            let felt = Felt::from_dec_str(text).map_err(|_| invalid())?;
+            // Larger numbers are reduced rather than refused
+            let canonical = text.trim_start_matches('0');
+            if felt.to_string() != if canonical.is_empty() { "0" } else { canonical } {
+                return Err(invalid());
+            }
+            Ok(felt)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storage_addresses() {
+        // Vectors from cairo-lang
+        assert_eq!(
+            sn_keccak(b"execute"),
+            Felt::from_hex_unchecked("0x240060cdb34fcc260f41eac7474ee1d7c80b7e3607daff9ac67c7ea2ebb1c44")
+        );
+        assert_eq!(
+            storage_address("balance", &[]).unwrap(),
+            Felt::from_hex_unchecked("0x206f38f7e4f15e87567361213c28f235cccdaa1d7fd34c9db1dfe9489c6a091")
+        );
+        assert_eq!(
+            storage_address("balanceOf", &[Felt::from(1234u64)]).unwrap(),
+            Felt::from_hex_unchecked("0x7de334d65aa93d9185729b424025918b18892418c85b802775d1f0d2be30a1d")
+        );
+        assert_eq!(normalize_address(ADDRESS_BOUND + Felt::ONE), Felt::ONE);
+        assert!(storage_address("", &[]).is_err());
+
+        assert_eq!(parse_felt("0x04d2").unwrap(), Felt::from(1234u64));
+        assert_eq!(parse_felt("1234").unwrap(), Felt::from(1234u64));
+        assert_eq!(parse_felt(&Felt::MAX.to_string()).unwrap(), Felt::MAX);
+        assert!(parse_felt(&format!("0x{}", "f".repeat(64))).is_err());
+        assert!(parse_felt("3618502788666131213697322783095070105623107215331596699973092056135872020481").is_err());
+        assert!(parse_felt("-1").is_err());
+        assert!(parse_felt("0x").is_err());
+    }
+}
@@ -0,0 +1,168 @@
+//! Binary Merkle-Patricia tries
+//!
+//! Starknet commits to contract storage, to contracts and to classes with
+//! binary tries of height 251 keyed by the big-endian bits of a felt. A
+//! binary node hashes as `H(left, right)`; an edge node, which skips a run
+//! of bits shared by everything below it, as `H(child, path) + length`.
+//! Storage and contracts tries hash with Pedersen, the classes trie with
+//! Poseidon. Zero values are not stored, so a key the trie does not hold
+//! reads as zero.
+
+use crate::StarknetError;
+use starknet_types_core::felt::Felt;
+use starknet_types_core::hash::{Pedersen, Poseidon, StarkHash};
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, vec::Vec};
+
+/// Number of key bits a trie branches on
+pub const TRIE_HEIGHT: usize = 251;
+
+/// Hash function a trie is built with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrieHash {
+    Pedersen,
+    Poseidon,
+}
+
+/// An inner node of a trie
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrieNode {
+    Binary { left: Felt, right: Felt },
+    /// Leads to `child` along the `length` bits of `path`
+    Edge { child: Felt, path: Felt, length: u8 },
+}
+
+impl TrieHash {
+    pub fn hash(&self, a: &Felt, b: &Felt) -> Felt {
+        match self {
+            Self::Pedersen => Pedersen::hash(a, b),
+            Self::Poseidon => Poseidon::hash(a, b),
+        }
+    }
+}
+
+impl TrieNode {
+    /// Hash of the node, which its parent refers to it by
+    pub fn hash(&self, hash: TrieHash) -> Felt {
+        match self {
+            Self::Binary { left, right } => hash.hash(left, right),
+            Self::Edge { child, path, length } => hash.hash(child, path) + Felt::from(*length),
+        }
+    }
+}
+
+/// Bit `index` of a trie key, counting from the most significant of its 251
+fn key_bit(bits: &[bool; 256], index: usize) -> bool {
+    bits[TRIE_HEIGHT - 1 - index]
+}
+
+/// Check that `nodes` lead from `root` to `key`, returning the value there
+///
+/// Nodes may come in any order, and nodes off the key's path are ignored.
+/// `None` means the trie holds no value under the key, which the nodes
+/// show by an edge leaving the key's path, or by an empty trie.
+pub fn verify_trie_proof(
+    root: &Felt,
+    key: &Felt,
+    nodes: &[TrieNode],
+    hash: TrieHash,
+) -> Result<Option<Felt>, StarknetError> {
+    if key.bits() > TRIE_HEIGHT {
+        return Err(StarknetError::ProofVerification(format!("Key {:#x} is longer than 251 bits", key)));
+    }
+    if *root == Felt::ZERO {
+        return Ok(None);
+    }
+    let hashes: Vec<_> = nodes.iter().map(|node| node.hash(hash)).collect();
+    let key_bits = key.to_bits_le();
+    let mut expected = *root;
+    let mut depth = 0;
+
+    while depth < TRIE_HEIGHT {
+        let node = hashes
+            .iter()
+            .position(|h| *h == expected)
+            .map(|index| &nodes[index])
+            .ok_or_else(|| StarknetError::ProofVerification(format!("Missing node {:#x} at depth {}", expected, depth)))?;
+        match node {
+            TrieNode::Binary { left, right } => {
+                expected = if key_bit(&key_bits, depth) { *right } else { *left };
+                depth += 1;
+            }
+            TrieNode::Edge { child, path, length } => {
+                let length = *length as usize;
+                if length == 0 || depth + length > TRIE_HEIGHT || path.bits() > length {
+                    return Err(StarknetError::ProofVerification(format!("Malformed edge at depth {}", depth)));
+                }
+                let path_bits = path.to_bits_le();
+                if (0..length).any(|i| path_bits[length - 1 - i] != key_bit(&key_bits, depth + i)) {
+                    return Ok(None);
+                }
+                expected = *child;
+                depth += length;
+            }
+        }
+    }
+    Ok(Some(expected))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Build a trie over `entries`, returning its root and every inner node
+    pub(crate) fn small_trie(entries: &[(Felt, Felt)], hash: TrieHash) -> (Felt, Vec<TrieNode>) {
+        let mut nodes = Vec::new();
+        let mut sorted: Vec<_> = entries.iter().map(|(key, value)| (key.to_bits_le(), *value)).collect();
+        sorted.sort_by_key(|(bits, _)| (0..TRIE_HEIGHT).map(|i| key_bit(bits, i)).collect::<Vec<_>>());
+        let root = build(&sorted, 0, hash, &mut nodes);
+        (root, nodes)
+    }
+
+    fn build(entries: &[([bool; 256], Felt)], depth: usize, hash: TrieHash, nodes: &mut Vec<TrieNode>) -> Felt {
+        if depth == TRIE_HEIGHT {
+            return entries[0].1;
+        }
+        let (first, last) = (&entries[0].0, &entries[entries.len() - 1].0);
+        let shared = (depth..TRIE_HEIGHT).take_while(|i| key_bit(first, *i) == key_bit(last, *i)).count();
+        let node = if shared > 0 {
+            let path = (depth..depth + shared).fold(Felt::ZERO, |path, i| path.double() + Felt::from(key_bit(first, i) as u64));
+            let child = build(entries, depth + shared, hash, nodes);
+            TrieNode::Edge { child, path, length: shared as u8 }
+        } else {
+            let split = entries.iter().position(|(bits, _)| key_bit(bits, depth)).unwrap();
+            let left = build(&entries[..split], depth + 1, hash, nodes);
+            let right = build(&entries[split..], depth + 1, hash, nodes);
+            TrieNode::Binary { left, right }
+        };
+        nodes.push(node.clone());
+        node.hash(hash)
+    }
+
+    #[test]
+    fn test_trie_proofs() {
+        let entries = [
+            (Felt::from(5u64), Felt::from(50u64)),
+            (Felt::from(7u64), Felt::from(70u64)),
+            (Felt::from_hex_unchecked("0x400000000000000000000000000000000000000000000000000000000000000"), Felt::ONE),
+        ];
+        for hash in [TrieHash::Pedersen, TrieHash::Poseidon] {
+            let (root, nodes) = small_trie(&entries, hash);
+            for (key, value) in &entries {
+                assert_eq!(verify_trie_proof(&root, key, &nodes, hash).unwrap(), Some(*value));
+            }
+            // 6 shares the edge to 5 and 7 but leaves it at the last bit
+            assert_eq!(verify_trie_proof(&root, &Felt::from(6u64), &nodes, hash).unwrap(), None);
+            assert_eq!(verify_trie_proof(&root, &Felt::from(1u64 << 40), &nodes, hash).unwrap(), None);
+        }
+
+        let (root, nodes) = small_trie(&entries, TrieHash::Pedersen);
+        assert!(verify_trie_proof(&root, &Felt::from(5u64), &nodes, TrieHash::Poseidon).is_err());
+        assert!(verify_trie_proof(&(root + Felt::ONE), &Felt::from(5u64), &nodes, TrieHash::Pedersen).is_err());
+        assert!(verify_trie_proof(&root, &Felt::MAX, &nodes, TrieHash::Pedersen).is_err());
+        // The leaf's sibling alone cannot prove the leaf
+        assert!(verify_trie_proof(&root, &Felt::from(5u64), &nodes[1..], TrieHash::Pedersen).is_err());
+        assert_eq!(verify_trie_proof(&Felt::ZERO, &Felt::ONE, &[], TrieHash::Pedersen).unwrap(), None);
+    }
+}
@@ -0,0 +1,196 @@
+//! Read proofs over JSON-RPC
+//!
+//! `state_getReadProof` returns the trie nodes, and hashed values, read to
+//! find a set of keys at a block, and `state_getChildReadProof` does the
+//! same within a child trie. The value is taken from the proof itself rather
+//! than asked for separately, and the header from `chain_getHeader` is
+//! re-encoded so its hash can be checked against the block hash.
+
+use crate::hashing::blake2_256;
+use crate::proof::{decode_header, ChildTrieProof, SubstrateStorageProof};
+use crate::resolver::StorageKey;
+use crate::scale::encode_compact;
+use crate::trie::verify_trie_proof;
+use crate::SubstrateError;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Chain-specific configuration for proof fetching
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubstrateChainConfig {
+    /// Read state at the finalized head, rather than the best block, when no block is given
+    pub finalized: bool,
+}
+
+impl Default for SubstrateChainConfig {
+    fn default() -> Self {
+        Self { finalized: true }
+    }
+}
+
+/// Read proof fetcher for a Substrate RPC node
+#[derive(Debug, Clone)]
+pub struct SubstrateProofFetcher {
+    pub rpc_url: String,
+    pub config: SubstrateChainConfig,
+}
+
+impl SubstrateProofFetcher {
+    pub fn new(rpc_url: String, config: SubstrateChainConfig) -> Self {
+        Self { rpc_url, config }
+    }
+
+    pub fn with_defaults(rpc_url: String) -> Self {
+        Self::new(rpc_url, SubstrateChainConfig::default())
+    }
+
+    /// Fetch the value a resolved key names, in the main trie or its child trie
+    pub async fn fetch_storage(
+        &self,
+        storage_key: &StorageKey,
+        block_hash: Option<[u8; 32]>,
+    ) -> Result<SubstrateStorageProof, SubstrateError> {
+        match &storage_key.child_storage_key {
+            Some(child) => self.fetch_child_proof(child, &storage_key.key, block_hash).await,
+            None => self.fetch_proof(&storage_key.key, block_hash).await,
+        }
+    }
+
+    /// Fetch a main trie value, at `block_hash` or the configured head, with its proof
+    pub async fn fetch_proof(
+        &self,
+        key: &[u8],
+        block_hash: Option<[u8; 32]>,
+    ) -> Result<SubstrateStorageProof, SubstrateError> {
+        let (block_hash, header) = self.header(block_hash).await?;
+        let (block_number, state_root) = decode_header(&header)
+            .ok_or_else(|| SubstrateError::Rpc("The node returned a header that cannot be decoded".into()))?;
+        let params = json!([[format!("0x{}", hex::encode(key))], format!("0x{}", hex::encode(block_hash))]);
+        let proof = self.read_proof("state_getReadProof", params).await?;
+
+        let value = verify_trie_proof(&state_root, key, &proof)?;
+        let proof = SubstrateStorageProof {
+            key: key.to_vec(),
+            value,
+            proof,
+            child: None,
+            block_number,
+            block_hash,
+            header,
+            state_root,
+        };
+        proof.verify()?;
+        Ok(proof)
+    }
+
+    /// Fetch a value of the child trie whose root the main trie holds under `child_storage_key`
+    pub async fn fetch_child_proof(
+        &self,
+        child_storage_key: &[u8],
+        key: &[u8],
+        block_hash: Option<[u8; 32]>,
+    ) -> Result<SubstrateStorageProof, SubstrateError> {
+        let mut proof = self.fetch_proof(child_storage_key, block_hash).await?;
+        let root: [u8; 32] = proof
+            .value
+            .take()
+            .and_then(|root| root.try_into().ok())
+            .ok_or_else(|| SubstrateError::Rpc(format!("No child trie is stored under 0x{}", hex::encode(child_storage_key))))?;
+
+        let params = json!([
+            format!("0x{}", hex::encode(child_storage_key)),
+            [format!("0x{}", hex::encode(key))],
+            format!("0x{}", hex::encode(proof.block_hash)),
+        ]);
+        let child_proof = self.read_proof("state_getChildReadProof", params).await?;
+        proof.value = verify_trie_proof(&root, key, &child_proof)?;
+        proof.key = key.to_vec();
+        proof.child = Some(ChildTrieProof { storage_key: child_storage_key.to_vec(), root, proof: child_proof });
+        proof.verify()?;
+        Ok(proof)
+    }
+
+    /// Name of the chain the node follows, e.g. `Polkadot`
+    pub async fn chain_name(&self) -> Result<String, SubstrateError> {
+        let chain = self.call("system_chain", json!([])).await?;
+        chain.as_str().map(str::to_string).ok_or_else(|| SubstrateError::Rpc("system_chain returned no name".into()))
+    }
+
+    /// Hash of the chain's genesis block, which tells chains of the same name apart
+    pub async fn genesis_hash(&self) -> Result<[u8; 32], SubstrateError> {
+        parse_hash(&self.call("chain_getBlockHash", json!([0])).await?)
+    }
+
+    /// The hash of the block state is read at, and its SCALE-encoded header
+    async fn header(&self, block_hash: Option<[u8; 32]>) -> Result<([u8; 32], Vec<u8>), SubstrateError> {
+        let block_hash = match block_hash {
+            Some(hash) => hash,
+            None if self.config.finalized => parse_hash(&self.call("chain_getFinalizedHead", json!([])).await?)?,
+            None => parse_hash(&self.call("chain_getBlockHash", json!([])).await?)?,
+        };
+        let header = self.call("chain_getHeader", json!([format!("0x{}", hex::encode(block_hash))])).await?;
+        let encoded = encode_header(&header)?;
+        if blake2_256(&encoded) != block_hash {
+            return Err(SubstrateError::Rpc(format!(
+                "The header of 0x{} does not hash to it; the chain may not hash headers with Blake2-256",
+                hex::encode(block_hash)
+            )));
+        }
+        Ok((block_hash, encoded))
+    }
+
+    async fn read_proof(&self, method: &str, params: Value) -> Result<Vec<Vec<u8>>, SubstrateError> {
+        let result = self.call(method, params).await?;
+        result["proof"]
+            .as_array()
+            .ok_or_else(|| SubstrateError::Rpc(format!("{} returned no proof", method)))?
+            .iter()
+            .map(parse_bytes)
+            .collect()
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value, SubstrateError> {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response: Value = reqwest::Client::new().post(&self.rpc_url).json(&request).send().await?.json().await?;
+        if let Some(error) = response.get("error") {
+            return Err(SubstrateError::Rpc(format!("{}: {}", method, error)));
+        }
+        Ok(response["result"].clone())
+    }
+}
+
+/// SCALE encoding of a header as `chain_getHeader` returns it
+///
+/// Digest items come already encoded, so the header is the parent hash, the
+/// compact block number, the state and extrinsics roots, and the digest.
+fn encode_header(header: &Value) -> Result<Vec<u8>, SubstrateError> {
+    let number = header["number"]
+        .as_str()
+        .and_then(|number| u64::from_str_radix(number.trim_start_matches("0x"), 16).ok())
+        .ok_or_else(|| SubstrateError::Rpc(format!("Header has no block number: {}", header)))?;
+    let logs = header["digest"]["logs"]
+        .as_array()
+        .ok_or_else(|| SubstrateError::Rpc("Header has no digest".into()))?;
+
+    let mut encoded = parse_hash(&header["parentHash"])?.to_vec();
+    encoded.extend(encode_compact(number as u128));
+    encoded.extend(parse_hash(&header["stateRoot"])?);
+    encoded.extend(parse_hash(&header["extrinsicsRoot"])?);
+    encoded.extend(encode_compact(logs.len() as u128));
+    for log in logs {
+        encoded.extend(parse_bytes(log)?);
+    }
+    Ok(encoded)
+}
+
+fn parse_bytes(value: &Value) -> Result<Vec<u8>, SubstrateError> {
+    let hex_str = value.as_str().ok_or_else(|| SubstrateError::Rpc(format!("Expected hex, found {}", value)))?;
+    hex::decode(hex_str.trim_start_matches("0x"))
+        .map_err(|e| SubstrateError::Rpc(format!("Invalid hex '{}': {}", hex_str, e)))
+}
+
+fn parse_hash(value: &Value) -> Result<[u8; 32], SubstrateError> {
+    parse_bytes(value)?
+        .try_into()
+        .map_err(|_| SubstrateError::Rpc(format!("Expected a 32-byte hash, found {}", value)))
+}
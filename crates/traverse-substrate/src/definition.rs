@@ -0,0 +1,423 @@
+//! Storage definitions for FRAME runtimes
+//!
+//! A runtime describes its storage in its metadata: each pallet's storage
+//! prefix, and for every item its hashers, key and value types, and whether
+//! a missing entry reads as `None` or as a default. Metadata is read in the
+//! JSON form polkadot.js writes (`api.runtimeMetadata.toJSON()`), whose
+//! type registry gives the items' types their names. A definition can also
+//! be written by hand, which is how child tries are declared: the metadata
+//! does not say which child tries a pallet creates or how it keys them.
+
+use crate::hashing::StorageHasher;
+use crate::scale::ScaleType;
+use crate::SubstrateError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Nesting past which metadata types are named by ID
+const MAX_TYPE_DEPTH: usize = 8;
+
+/// A runtime's storage, from its metadata or written by hand
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubstrateStorageDefinition {
+    /// Chain the runtime belongs to, e.g. `polkadot` or a parachain's name
+    #[serde(default)]
+    pub chain: Option<String>,
+    #[serde(default)]
+    pub items: Vec<StorageItemDefinition>,
+    #[serde(default)]
+    pub child_tries: Vec<ChildTrieDefinition>,
+}
+
+/// A storage value or map of a pallet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageItemDefinition {
+    /// Storage prefix of the pallet, usually its name
+    pub pallet: String,
+    pub name: String,
+    /// One hasher per key; none for a plain value
+    #[serde(default)]
+    pub hashers: Vec<StorageHasher>,
+    #[serde(default)]
+    pub keys: Vec<String>,
+    pub value: String,
+    /// Encoded size of the value, when its name does not give it
+    #[serde(default)]
+    pub value_size: Option<usize>,
+    #[serde(default)]
+    pub modifier: StorageModifier,
+}
+
+/// What reading a missing entry returns
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StorageModifier {
+    /// `None` (`OptionQuery`)
+    #[default]
+    Optional,
+    /// The item's default value (`ValueQuery`)
+    Default,
+}
+
+/// A default child trie and how its keys are formed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChildTrieDefinition {
+    pub name: String,
+    /// Key of the child trie, without the `:child_storage:default:` prefix; text or `0x` hex
+    pub storage_key: String,
+    /// Hasher applied to keys within the child trie
+    #[serde(default = "default_child_hasher")]
+    pub hasher: StorageHasher,
+    pub key: String,
+    pub value: String,
+}
+
+fn default_child_hasher() -> StorageHasher {
+    StorageHasher::Identity
+}
+
+impl SubstrateStorageDefinition {
+    /// Parse a storage definition, or derive one from runtime metadata
+    pub fn from_json(content: &str) -> Result<Self, SubstrateError> {
+        let value: Value = serde_json::from_str(content).map_err(SubstrateError::Json)?;
+        let definition = if value.get("magicNumber").is_some() && value.get("metadata").is_some() {
+            Self::from_metadata(&value)?
+        } else {
+            serde_json::from_value(value).map_err(SubstrateError::Json)?
+        };
+        definition.validate()?;
+        Ok(definition)
+    }
+
+    /// The storage items of runtime metadata, version 14 or later
+    pub fn from_metadata(metadata: &Value) -> Result<Self, SubstrateError> {
+        let versions = metadata["metadata"]
+            .as_object()
+            .ok_or_else(|| SubstrateError::InvalidDefinition("The metadata has no versioned body".into()))?;
+        let (version, body) = versions
+            .iter()
+            .next()
+            .ok_or_else(|| SubstrateError::InvalidDefinition("The metadata has no versioned body".into()))?;
+        if version.trim_start_matches(['v', 'V']).parse::<u32>().map_or(true, |v| v < 14) {
+            return Err(SubstrateError::InvalidDefinition(format!(
+                "Metadata {} has no type registry; version 14 or later is needed",
+                version
+            )));
+        }
+        let registry = TypeRegistry { types: body["lookup"]["types"].as_array().map(Vec::as_slice).unwrap_or_default() };
+
+        let mut items = Vec::new();
+        for pallet in body["pallets"].as_array().into_iter().flatten() {
+            let storage = &pallet["storage"];
+            let Some(prefix) = storage["prefix"].as_str() else { continue };
+            for item in storage["items"].as_array().into_iter().flatten() {
+                items.push(registry.storage_item(prefix, item)?);
+            }
+        }
+        Ok(Self { chain: None, items, child_tries: Vec::new() })
+    }
+
+    fn validate(&self) -> Result<(), SubstrateError> {
+        let mut labels: Vec<String> = self.items.iter().map(StorageItemDefinition::label).collect();
+        labels.extend(self.child_tries.iter().map(|child| child.name.clone()));
+        labels.sort_unstable();
+        if let Some(pair) = labels.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(SubstrateError::InvalidDefinition(format!("{} is declared twice", pair[0])));
+        }
+        for item in &self.items {
+            if item.hashers.len() != item.keys.len() {
+                return Err(SubstrateError::InvalidDefinition(format!(
+                    "{} has {} hashers for {} keys",
+                    item.label(),
+                    item.hashers.len(),
+                    item.keys.len()
+                )));
+            }
+        }
+        for child in &self.child_tries {
+            if child.name.contains([':', '[', ']']) {
+                return Err(SubstrateError::InvalidDefinition(format!("'{}' cannot name a child trie", child.name)));
+            }
+            if child.storage_key_bytes()?.is_empty() {
+                return Err(SubstrateError::InvalidDefinition(format!("Child trie {} has an empty key", child.name)));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl StorageItemDefinition {
+    /// `Pallet::Item`, as queries name the item
+    pub fn label(&self) -> String {
+        format!("{}::{}", self.pallet, self.name)
+    }
+
+    /// Type name of the item, e.g. `StorageMap<Blake2_128Concat<AccountId32>, AccountInfo>`
+    pub fn type_name(&self) -> String {
+        match self.keys.len() {
+            0 => format!("StorageValue<{}>", self.value),
+            1 => format!("StorageMap<{}, {}>", self.key_name(), self.value),
+            2 => format!("StorageDoubleMap<{}, {}>", self.key_name(), self.value),
+            _ => format!("StorageNMap<{}, {}>", self.key_name(), self.value),
+        }
+    }
+
+    /// The keys with their hashers, e.g. `Twox64Concat<u32>, Blake2_128Concat<AccountId32>`
+    pub fn key_name(&self) -> String {
+        let keys: Vec<String> =
+            self.hashers.iter().zip(&self.keys).map(|(hasher, key)| format!("{}<{}>", hasher.name(), key)).collect();
+        keys.join(", ")
+    }
+}
+
+impl ChildTrieDefinition {
+    /// The child trie's key as bytes: `0x` hex, or the text itself
+    pub fn storage_key_bytes(&self) -> Result<Vec<u8>, SubstrateError> {
+        match self.storage_key.strip_prefix("0x") {
+            Some(digits) => hex::decode(digits)
+                .map_err(|e| SubstrateError::InvalidDefinition(format!("Key of child trie {}: {}", self.name, e))),
+            None => Ok(self.storage_key.as_bytes().to_vec()),
+        }
+    }
+
+    /// Type name of the child trie, e.g. `ChildTrie<Blake2_256<Vec<u8>>, Vec<u8>>`
+    pub fn type_name(&self) -> String {
+        format!("ChildTrie<{}<{}>, {}>", self.hasher.name(), self.key, self.value)
+    }
+}
+
+/// The `lookup` type registry of metadata
+struct TypeRegistry<'a> {
+    types: &'a [Value],
+}
+
+impl TypeRegistry<'_> {
+    fn storage_item(&self, pallet: &str, item: &Value) -> Result<StorageItemDefinition, SubstrateError> {
+        let name = item["name"].as_str().unwrap_or_default().to_string();
+        let invalid = || SubstrateError::InvalidDefinition(format!("Storage item {}::{} has no type", pallet, name));
+        let storage_type = field(&item["type"], "plain").map(|plain| (Vec::new(), Vec::new(), plain));
+        let (hashers, keys, value) = match storage_type {
+            Some((hashers, keys, plain)) => (hashers, keys, plain.as_u64().ok_or_else(invalid)?),
+            None => {
+                let map = field(&item["type"], "map").ok_or_else(invalid)?;
+                let hashers = map["hashers"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|hasher| StorageHasher::parse(hasher.as_str().unwrap_or_default()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let key = map["key"].as_u64().ok_or_else(invalid)?;
+                // Several hashers hash the fields of a tuple key one by one
+                let keys = match (hashers.len(), field(&self.def(key), "tuple")) {
+                    (1, _) => [key].to_vec(),
+                    (_, Some(fields)) => fields.as_array().into_iter().flatten().filter_map(Value::as_u64).collect(),
+                    _ => return Err(invalid()),
+                };
+                (hashers, keys, map["value"].as_u64().ok_or_else(invalid)?)
+            }
+        };
+        let modifier = match item["modifier"].as_str() {
+            Some("Default") => StorageModifier::Default,
+            _ => StorageModifier::Optional,
+        };
+        Ok(StorageItemDefinition {
+            pallet: pallet.to_string(),
+            name,
+            hashers,
+            keys: keys.iter().map(|key| self.name(*key, 0)).collect(),
+            value: self.name(value, 0),
+            value_size: self.size(value, 0),
+            modifier,
+        })
+    }
+
+    fn entry(&self, id: u64) -> &Value {
+        let entry = self.types.iter().find(|entry| entry["id"].as_u64() == Some(id));
+        entry.map(|entry| &entry["type"]).unwrap_or(&Value::Null)
+    }
+
+    fn def(&self, id: u64) -> Value {
+        self.entry(id)["def"].clone()
+    }
+
+    /// A Rust-like name: primitives and containers spelled out, other types by
+    /// the last segment of their path, and wrappers of one field by what they wrap
+    fn name(&self, id: u64, depth: usize) -> String {
+        if depth > MAX_TYPE_DEPTH {
+            return format!("#{}", id);
+        }
+        let entry = self.entry(id);
+        let def = &entry["def"];
+        let inner = |value: &Value| value.as_u64().map(|id| self.name(id, depth + 1)).unwrap_or_default();
+        if let Some(primitive) = field(def, "primitive").and_then(|p| p.as_str()) {
+            return match primitive.to_ascii_lowercase().as_str() {
+                "str" => "String".to_string(),
+                other => other.to_string(),
+            };
+        }
+        if let Some(array) = field(def, "array") {
+            return format!("[{}; {}]", inner(&array["type"]), array["len"]);
+        }
+        if let Some(sequence) = field(def, "sequence") {
+            return format!("Vec<{}>", inner(&sequence["type"]));
+        }
+        if let Some(compact) = field(def, "compact") {
+            return format!("Compact<{}>", inner(&compact["type"]));
+        }
+        if let Some(fields) = field(def, "tuple") {
+            let names: Vec<String> = fields.as_array().into_iter().flatten().map(inner).collect();
+            return format!("({})", names.join(", "));
+        }
+        let path = entry["path"].as_array().and_then(|path| path.last()).and_then(Value::as_str).unwrap_or("?");
+        let fields = field(def, "composite").and_then(|c| c["fields"].as_array());
+        if let Some([only]) = fields.map(Vec::as_slice) {
+            if matches!(ScaleType::parse(path), ScaleType::Other(_)) {
+                return inner(&only["type"]);
+            }
+        }
+        let params: Vec<String> = entry["params"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|param| param["type"].is_u64())
+            .map(|param| inner(&param["type"]))
+            .collect();
+        match params.is_empty() {
+            true => path.to_string(),
+            false => format!("{}<{}>", path, params.join(", ")),
+        }
+    }
+
+    /// Encoded size, when every value of the type has the same
+    fn size(&self, id: u64, depth: usize) -> Option<usize> {
+        if depth > MAX_TYPE_DEPTH {
+            return None;
+        }
+        let def = self.def(id);
+        let size_of = |value: &Value| value.as_u64().and_then(|id| self.size(id, depth + 1));
+        if let Some(primitive) = field(&def, "primitive").and_then(|p| p.as_str()) {
+            return ScaleType::parse(&primitive.to_ascii_lowercase()).fixed_size();
+        }
+        if let Some(array) = field(&def, "array") {
+            return size_of(&array["type"])?.checked_mul(array["len"].as_u64()? as usize);
+        }
+        let fields: Vec<Value> = match (field(&def, "tuple"), field(&def, "composite")) {
+            (Some(fields), _) => fields.as_array()?.clone(),
+            (_, Some(composite)) => composite["fields"].as_array()?.iter().map(|f| f["type"].clone()).collect(),
+            _ => return None,
+        };
+        fields.iter().map(size_of).sum()
+    }
+}
+
+/// A field named in lower camel case, as polkadot.js writes it, or capitalized
+fn field<'a>(value: &'a Value, name: &str) -> Option<&'a Value> {
+    let object = value.as_object()?;
+    object.get(name).or_else(|| {
+        let mut chars = name.chars();
+        let capitalized = chars.next().map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())?;
+        object.get(&capitalized)
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Runtime metadata with `System::Account`, `Timestamp::Now` and `Staking::ErasStakers`
+    pub(crate) const METADATA: &str = r#"{
+        "magicNumber": 1635018093,
+        "metadata": { "v14": {
+            "lookup": { "types": [
+                { "id": 0, "type": { "path": ["sp_core", "crypto", "AccountId32"], "params": [],
+                    "def": { "composite": { "fields": [{ "name": null, "type": 1, "typeName": "[u8; 32]" }] } } } },
+                { "id": 1, "type": { "path": [], "params": [], "def": { "array": { "len": 32, "type": 2 } } } },
+                { "id": 2, "type": { "path": [], "params": [], "def": { "primitive": "U8" } } },
+                { "id": 3, "type": { "path": ["frame_system", "AccountInfo"], "params": [{ "name": "Nonce", "type": 4 }],
+                    "def": { "composite": { "fields": [
+                        { "name": "nonce", "type": 4 }, { "name": "consumers", "type": 4 },
+                        { "name": "providers", "type": 4 }, { "name": "sufficients", "type": 4 },
+                        { "name": "data", "type": 5 }
+                    ] } } } },
+                { "id": 4, "type": { "path": [], "params": [], "def": { "primitive": "U32" } } },
+                { "id": 5, "type": { "path": ["pallet_balances", "types", "AccountData"], "params": [],
+                    "def": { "composite": { "fields": [
+                        { "name": "free", "type": 6 }, { "name": "reserved", "type": 6 },
+                        { "name": "frozen", "type": 6 }, { "name": "flags", "type": 7 }
+                    ] } } } },
+                { "id": 6, "type": { "path": [], "params": [], "def": { "primitive": "U128" } } },
+                { "id": 7, "type": { "path": ["pallet_balances", "types", "ExtraFlags"], "params": [],
+                    "def": { "composite": { "fields": [{ "name": null, "type": 6 }] } } } },
+                { "id": 8, "type": { "path": [], "params": [], "def": { "primitive": "U64" } } },
+                { "id": 9, "type": { "path": [], "params": [], "def": { "tuple": [4, 0] } } },
+                { "id": 10, "type": { "path": ["sp_staking", "Exposure"], "params": [],
+                    "def": { "composite": { "fields": [{ "name": "total", "type": 11 }, { "name": "others", "type": 12 }] } } } },
+                { "id": 11, "type": { "path": [], "params": [], "def": { "compact": { "type": 6 } } } },
+                { "id": 12, "type": { "path": [], "params": [], "def": { "sequence": { "type": 0 } } } }
+            ] },
+            "pallets": [
+                { "name": "System", "storage": { "prefix": "System", "items": [
+                    { "name": "Account", "modifier": "Default",
+                      "type": { "map": { "hashers": ["Blake2_128Concat"], "key": 0, "value": 3 } }, "fallback": "0x00" }
+                ] }, "index": 0 },
+                { "name": "Timestamp", "storage": { "prefix": "Timestamp", "items": [
+                    { "name": "Now", "modifier": "Default", "type": { "plain": 8 }, "fallback": "0x00" }
+                ] }, "index": 2 },
+                { "name": "Staking", "storage": { "prefix": "Staking", "items": [
+                    { "name": "ErasStakers", "modifier": "Optional",
+                      "type": { "map": { "hashers": ["Twox64Concat", "Twox64Concat"], "key": 9, "value": 10 } } }
+                ] }, "index": 7 },
+                { "name": "Utility", "storage": null, "index": 26 }
+            ]
+        } }
+    }"#;
+
+    /// A hand-written definition with a crowdloan-style child trie
+    pub(crate) const CHILD_DEFINITION: &str = r#"{
+        "chain": "rococo",
+        "items": [
+            { "pallet": "Paras", "name": "Heads", "hashers": ["Twox64Concat"], "keys": ["ParaId"], "value": "Vec<u8>" }
+        ],
+        "child_tries": [
+            { "name": "fund_2000", "storage_key": "0xd0070000", "hasher": "Identity", "key": "AccountId32", "value": "(Balance, Vec<u8>)" }
+        ]
+    }"#;
+
+    #[test]
+    fn test_definitions_from_metadata() {
+        let definition = SubstrateStorageDefinition::from_json(METADATA).unwrap();
+        let labels: Vec<_> = definition.items.iter().map(|item| item.label()).collect();
+        assert_eq!(labels, ["System::Account", "Timestamp::Now", "Staking::ErasStakers"]);
+
+        let account = &definition.items[0];
+        assert_eq!(account.type_name(), "StorageMap<Blake2_128Concat<AccountId32>, AccountInfo<u32>>");
+        assert_eq!((account.value_size, account.modifier), (Some(80), StorageModifier::Default));
+        let now = &definition.items[1];
+        assert_eq!((now.value.as_str(), now.value_size, now.hashers.len()), ("u64", Some(8), 0));
+        let stakers = &definition.items[2];
+        assert_eq!(stakers.keys, ["u32", "AccountId32"]);
+        assert_eq!((stakers.value.as_str(), stakers.value_size), ("Exposure", None));
+
+        let old = r#"{ "magicNumber": 1635018093, "metadata": { "v13": { "modules": [] } } }"#;
+        assert!(SubstrateStorageDefinition::from_json(old).is_err());
+    }
+
+    #[test]
+    fn test_hand_written_definitions() {
+        let definition = SubstrateStorageDefinition::from_json(CHILD_DEFINITION).unwrap();
+        assert_eq!(definition.items[0].type_name(), "StorageMap<Twox64Concat<ParaId>, Vec<u8>>");
+        assert_eq!(definition.child_tries[0].storage_key_bytes().unwrap(), [0xd0, 0x07, 0, 0]);
+        assert_eq!(definition.child_tries[0].type_name(), "ChildTrie<Identity<AccountId32>, (Balance, Vec<u8>)>");
+
+        let missing_hasher = r#"{ "items": [{ "pallet": "P", "name": "M", "keys": ["u32"], "value": "u32" }] }"#;
+        assert!(SubstrateStorageDefinition::from_json(missing_hasher).is_err());
+        let twice = r#"{ "items": [{ "pallet": "P", "name": "V", "value": "u32" }, { "pallet": "P", "name": "V", "value": "u64" }] }"#;
+        assert!(SubstrateStorageDefinition::from_json(twice).is_err());
+    }
+}
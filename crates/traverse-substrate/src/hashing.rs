@@ -0,0 +1,149 @@
+//! Storage key hashing
+//!
+//! A storage item's key starts with the 128-bit xxHash of its pallet's
+//! prefix and of its own name. Map keys follow, each hashed with the hasher
+//! the item declares; the `Concat` hashers and `Identity` keep the key
+//! itself after the hash, so entries can be iterated and their keys read
+//! back.
+
+use crate::SubstrateError;
+use blake2::{
+    digest::consts::{U16, U32},
+    Blake2b, Digest,
+};
+use core::hash::Hasher;
+use serde::{Deserialize, Serialize};
+use twox_hash::XxHash64;
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, vec::Vec};
+
+/// Prefix of the main trie keys holding the roots of default child tries
+pub const CHILD_STORAGE_PREFIX: &[u8] = b":child_storage:default:";
+
+/// Hasher applied to a map key, as runtime metadata names it
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StorageHasher {
+    Blake2_128,
+    Blake2_256,
+    Blake2_128Concat,
+    Twox128,
+    Twox256,
+    Twox64Concat,
+    Identity,
+}
+
+impl StorageHasher {
+    const ALL: [Self; 7] = [
+        Self::Blake2_128,
+        Self::Blake2_256,
+        Self::Blake2_128Concat,
+        Self::Twox128,
+        Self::Twox256,
+        Self::Twox64Concat,
+        Self::Identity,
+    ];
+
+    /// Parse a hasher name, e.g. `Blake2_128Concat`
+    pub fn parse(name: &str) -> Result<Self, SubstrateError> {
+        Self::ALL
+            .into_iter()
+            .find(|hasher| hasher.name() == name.trim())
+            .ok_or_else(|| SubstrateError::InvalidDefinition(format!("Unknown storage hasher {}", name)))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Blake2_128 => "Blake2_128",
+            Self::Blake2_256 => "Blake2_256",
+            Self::Blake2_128Concat => "Blake2_128Concat",
+            Self::Twox128 => "Twox128",
+            Self::Twox256 => "Twox256",
+            Self::Twox64Concat => "Twox64Concat",
+            Self::Identity => "Identity",
+        }
+    }
+
+    /// Hash an encoded key
+    pub fn hash(&self, key: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Blake2_128 => blake2_128(key).to_vec(),
+            Self::Blake2_256 => blake2_256(key).to_vec(),
+            Self::Blake2_128Concat => [&blake2_128(key)[..], key].concat(),
+            Self::Twox128 => twox_128(key).to_vec(),
+            Self::Twox256 => twox_256(key).to_vec(),
+            Self::Twox64Concat => [&twox_64(key)[..], key].concat(),
+            Self::Identity => key.to_vec(),
+        }
+    }
+}
+
+/// `twox128(pallet) ‖ twox128(item)`, the key of a plain value and the prefix of a map's entries
+pub fn storage_prefix(pallet: &str, item: &str) -> [u8; 32] {
+    let mut prefix = [0u8; 32];
+    prefix[..16].copy_from_slice(&twox_128(pallet.as_bytes()));
+    prefix[16..].copy_from_slice(&twox_128(item.as_bytes()));
+    prefix
+}
+
+/// Main trie key holding the root of the default child trie `storage_key`
+pub fn child_storage_key(storage_key: &[u8]) -> Vec<u8> {
+    [CHILD_STORAGE_PREFIX, storage_key].concat()
+}
+
+pub fn blake2_128(data: &[u8]) -> [u8; 16] {
+    Blake2b::<U16>::digest(data).into()
+}
+
+pub fn blake2_256(data: &[u8]) -> [u8; 32] {
+    Blake2b::<U32>::digest(data).into()
+}
+
+pub fn twox_64(data: &[u8]) -> [u8; 8] {
+    twox::<8>(data)
+}
+
+pub fn twox_128(data: &[u8]) -> [u8; 16] {
+    twox::<16>(data)
+}
+
+pub fn twox_256(data: &[u8]) -> [u8; 32] {
+    twox::<32>(data)
+}
+
+/// xxHash64 with seeds 0, 1, ..., little-endian and concatenated
+fn twox<const N: usize>(data: &[u8]) -> [u8; N] {
+    let mut out = [0u8; N];
+    for (seed, chunk) in out.chunks_mut(8).enumerate() {
+        let mut hasher = XxHash64::with_seed(seed as u64);
+        hasher.write(data);
+        chunk.copy_from_slice(&hasher.finish().to_le_bytes());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storage_keys() {
+        let prefix = storage_prefix("System", "Account");
+        assert_eq!(hex::encode(prefix), "26aa394eea5630e07c48ae0c9558cef7b99d880ec681799c0cf30e8886371da9");
+        assert_eq!(hex::encode(storage_prefix("Timestamp", "Now")), "f0c365c3cf59d671eb72da0e7a4113c49f1f0515f462cdcf84e0f1d6045dfcbb");
+
+        // Alice's account
+        let alice = hex::decode("d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d").unwrap();
+        let hashed = StorageHasher::Blake2_128Concat.hash(&alice);
+        assert_eq!(hex::encode(&hashed[..16]), "de1e86a9a8c739864cf3cc5ec2bea59f");
+        assert_eq!(&hashed[16..], alice);
+
+        assert_eq!(StorageHasher::Twox64Concat.hash(&[1, 0, 0, 0]).len(), 12);
+        assert_eq!(StorageHasher::Identity.hash(&alice), alice);
+        assert_eq!(&twox_256(b"System")[..16], twox_128(b"System"));
+        assert_eq!(StorageHasher::parse("Twox64Concat").unwrap(), StorageHasher::Twox64Concat);
+        assert!(StorageHasher::parse("Keccak256").is_err());
+        assert!(child_storage_key(b"crowdloan").starts_with(b":child_storage:default:"));
+    }
+}
@@ -0,0 +1,148 @@
+//! Layout compilation from storage definitions
+//!
+//! Each storage item becomes an entry labelled `Pallet::Item` whose slot is
+//! `twox128(pallet) ‖ twox128(item)`, the key of a plain value and the prefix
+//! of a map's entries. A map's type records its hashers with its key types,
+//! e.g. `Blake2_128Concat<AccountId32>`, so queries can be hashed from the
+//! layout alone. Each child trie becomes an entry whose slot is the main
+//! trie key holding its root.
+
+use crate::definition::{StorageModifier, SubstrateStorageDefinition};
+use crate::hashing::{child_storage_key, storage_prefix};
+use crate::scale::ScaleType;
+use crate::SubstrateError;
+use traverse_core::{LayoutInfo, StorageEntry, TypeInfo, ZeroSemantics};
+
+#[cfg(feature = "std")]
+use std::path::Path;
+
+#[cfg(feature = "std")]
+use traverse_core::{LayoutCompiler, TraverseError};
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::ToString, vec::Vec};
+
+/// Encoding recorded for runtime storage and its entries
+pub const STORAGE_ENCODING: &str = "scale";
+
+/// Layout name of definitions that name no chain
+const DEFAULT_CONTRACT_NAME: &str = "runtime";
+
+/// Layout compiler for FRAME runtime storage
+///
+/// # Usage
+///
+/// ```rust,ignore
+/// use traverse_substrate::SubstrateLayoutCompiler;
+/// use traverse_core::LayoutCompiler;
+///
+/// let layout = SubstrateLayoutCompiler.compile_layout(Path::new("metadata.json"))?;
+/// assert!(layout.storage.iter().any(|e| e.label == "System::Account"));
+/// ```
+pub struct SubstrateLayoutCompiler;
+
+impl SubstrateLayoutCompiler {
+    /// Compile a parsed storage definition
+    pub fn compile_definition(definition: &SubstrateStorageDefinition) -> Result<LayoutInfo, SubstrateError> {
+        let mut storage = Vec::new();
+        let mut types: Vec<TypeInfo> = Vec::new();
+        let mut add_type = |label: &str, size: Option<usize>, key: Option<&str>, value: Option<&str>| {
+            if !types.iter().any(|t| t.label == label) {
+                types.push(TypeInfo {
+                    label: label.to_string(),
+                    // 0 for variable-size types
+                    number_of_bytes: size.unwrap_or(0).to_string(),
+                    encoding: STORAGE_ENCODING.to_string(),
+                    base: None,
+                    key: key.map(str::to_string),
+                    value: value.map(str::to_string),
+                });
+            }
+        };
+
+        for item in &definition.items {
+            let type_name = item.type_name();
+            let key_name = item.key_name();
+            storage.push(StorageEntry {
+                label: item.label(),
+                slot: format!("0x{}", hex::encode(storage_prefix(&item.pallet, &item.name))),
+                offset: 0,
+                type_name: type_name.clone(),
+                zero_semantics: match item.modifier {
+                    // A missing entry reads as the item's default
+                    StorageModifier::Default => ZeroSemantics::ValidZero,
+                    StorageModifier::Optional => ZeroSemantics::NeverWritten,
+                },
+            });
+            let key = (!item.keys.is_empty()).then_some(key_name.as_str());
+            add_type(&type_name, None, key, Some(&item.value));
+            add_type(&item.value, item.value_size.or_else(|| ScaleType::parse(&item.value).fixed_size()), None, None);
+            for key in &item.keys {
+                add_type(key, ScaleType::parse(key).fixed_size(), None, None);
+            }
+        }
+
+        for child in &definition.child_tries {
+            let type_name = child.type_name();
+            let key_name = format!("{}<{}>", child.hasher.name(), child.key);
+            storage.push(StorageEntry {
+                label: child.name.clone(),
+                slot: format!("0x{}", hex::encode(child_storage_key(&child.storage_key_bytes()?))),
+                offset: 0,
+                type_name: type_name.clone(),
+                // A missing entry was never inserted, or was removed
+                zero_semantics: ZeroSemantics::NeverWritten,
+            });
+            add_type(&type_name, None, Some(&key_name), Some(&child.value));
+            for element in [&child.key, &child.value] {
+                add_type(element, ScaleType::parse(element).fixed_size(), None, None);
+            }
+        }
+
+        let contract_name = definition.chain.clone().unwrap_or_else(|| DEFAULT_CONTRACT_NAME.to_string());
+        Ok(LayoutInfo { contract_name, storage, types, code_variables: Vec::new() })
+    }
+}
+
+#[cfg(feature = "std")]
+impl LayoutCompiler for SubstrateLayoutCompiler {
+    /// Compile a layout from runtime metadata or a storage definition
+    fn compile_layout(&self, definition_path: &Path) -> Result<LayoutInfo, TraverseError> {
+        let content = std::fs::read_to_string(definition_path)?;
+        let definition = SubstrateStorageDefinition::from_json(&content)?;
+        Ok(Self::compile_definition(&definition)?)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::definition::tests::{CHILD_DEFINITION, METADATA};
+
+    pub(crate) fn compile(definition: &str) -> LayoutInfo {
+        SubstrateLayoutCompiler::compile_definition(&SubstrateStorageDefinition::from_json(definition).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_compile_definition() {
+        let layout = compile(METADATA);
+        assert_eq!(layout.contract_name, "runtime");
+        let account = &layout.storage[0];
+        assert_eq!(account.slot, "0x26aa394eea5630e07c48ae0c9558cef7b99d880ec681799c0cf30e8886371da9");
+        assert_eq!(account.zero_semantics, ZeroSemantics::ValidZero);
+        assert_eq!(layout.storage[2].zero_semantics, ZeroSemantics::NeverWritten);
+
+        let type_info = |layout: &LayoutInfo, label: &str| layout.types.iter().find(|t| t.label == label).cloned().unwrap();
+        let map = type_info(&layout, &account.type_name);
+        assert_eq!((map.key.as_deref(), map.value.as_deref()), (Some("Blake2_128Concat<AccountId32>"), Some("AccountInfo<u32>")));
+        assert_eq!(type_info(&layout, "AccountInfo<u32>").number_of_bytes, "80");
+        assert_eq!(type_info(&layout, "StorageValue<u64>").key, None);
+        assert_eq!(type_info(&layout, "Exposure").number_of_bytes, "0");
+
+        let layout = compile(CHILD_DEFINITION);
+        assert_eq!(layout.contract_name, "rococo");
+        let fund = layout.storage.iter().find(|e| e.label == "fund_2000").unwrap();
+        assert_eq!(fund.slot, format!("0x{}d0070000", hex::encode(":child_storage:default:")));
+        assert_eq!(type_info(&layout, &fund.type_name).key.as_deref(), Some("Identity<AccountId32>"));
+    }
+}
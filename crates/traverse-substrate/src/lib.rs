@@ -0,0 +1,104 @@
+//! Pallet storage layouts and trie read proofs for Substrate chains
+//!
+//! FRAME pallets keep their storage in the runtime's state trie: a storage
+//! item lives under `twox128(pallet) ‖ twox128(item)`, and each key of a map
+//! is appended after being hashed with the item's hasher (`Blake2_128Concat`,
+//! `Twox64Concat`, `Identity`, ...). Child tries hang off the main trie, which
+//! stores their roots under `:child_storage:default:` keys. Every block
+//! header commits to the state root, so a value is proven by the trie nodes
+//! `state_getReadProof` returns for its key. This crate maps that model onto
+//! the traverse core types, for relay chains and parachains alike.
+//!
+//! # Features
+//!
+//! - **Layout Compilation**: Turn runtime metadata, or a storage definition, into a layout
+//! - **Query Resolution**: Resolve `Pallet::Item[key]` queries to hashed storage keys
+//! - **Proof Verification**: Check read proofs, and child trie proofs, against a header's state root
+//! - **Proof Fetching**: Read storage and its proof over JSON-RPC (`client`)
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! use traverse_substrate::{SubstrateKeyResolver, SubstrateLayoutCompiler};
+//! use traverse_core::{KeyResolver, LayoutCompiler};
+//!
+//! let layout = SubstrateLayoutCompiler.compile_layout(Path::new("metadata.json"))?;
+//! let path = SubstrateKeyResolver.resolve(&layout, "System::Account[5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY]")?;
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(not(feature = "std"))]
+use alloc::string::{String, ToString};
+
+pub mod definition;
+pub mod hashing;
+pub mod layout;
+pub mod proof;
+pub mod resolver;
+pub mod scale;
+pub mod trie;
+
+#[cfg(feature = "client")]
+pub mod client;
+
+pub use definition::{ChildTrieDefinition, StorageItemDefinition, StorageModifier, SubstrateStorageDefinition};
+pub use hashing::{blake2_128, blake2_256, child_storage_key, storage_prefix, twox_128, twox_256, twox_64, StorageHasher};
+pub use layout::SubstrateLayoutCompiler;
+pub use proof::{ChildTrieProof, SubstrateStorageProof};
+pub use resolver::{StorageKey, SubstrateKeyResolver};
+pub use scale::{decode_ss58, ScaleType};
+pub use trie::{empty_trie_root, verify_trie_proof, NodeHandle, NodeValue, TrieNode};
+
+#[cfg(feature = "client")]
+pub use client::{SubstrateChainConfig, SubstrateProofFetcher};
+
+/// Error types specific to Substrate
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+#[derive(Debug)]
+pub enum SubstrateError {
+    #[cfg_attr(feature = "std", error("Invalid storage definition: {0}"))]
+    InvalidDefinition(String),
+
+    #[cfg_attr(feature = "std", error("Invalid query: {0}"))]
+    InvalidQuery(String),
+
+    #[cfg_attr(feature = "std", error("Invalid address: {0}"))]
+    InvalidAddress(String),
+
+    #[cfg_attr(feature = "std", error("Proof verification failed: {0}"))]
+    ProofVerification(String),
+
+    #[cfg_attr(feature = "std", error("RPC error: {0}"))]
+    Rpc(String),
+
+    #[cfg(feature = "client")]
+    #[cfg_attr(feature = "std", error("Network error: {0}"))]
+    Network(#[from] reqwest::Error),
+
+    #[cfg_attr(feature = "std", error("JSON error: {0}"))]
+    Json(#[cfg_attr(feature = "std", from)] serde_json::Error),
+
+    #[cfg_attr(feature = "std", error("Traverse core error: {0}"))]
+    TraverseCore(#[cfg_attr(feature = "std", from)] traverse_core::TraverseError),
+}
+
+impl From<SubstrateError> for traverse_core::TraverseError {
+    fn from(err: SubstrateError) -> Self {
+        use traverse_core::TraverseError;
+
+        match err {
+            SubstrateError::InvalidDefinition(msg) => TraverseError::LayoutCompilation(msg),
+            SubstrateError::InvalidQuery(msg) | SubstrateError::InvalidAddress(msg) => TraverseError::InvalidInput(msg),
+            SubstrateError::ProofVerification(msg) => TraverseError::Validation(msg),
+            SubstrateError::Rpc(msg) => TraverseError::ExternalService(msg),
+            #[cfg(feature = "client")]
+            SubstrateError::Network(err) => TraverseError::ExternalService(err.to_string()),
+            SubstrateError::Json(err) => TraverseError::Serialization(err.to_string()),
+            SubstrateError::TraverseCore(err) => err,
+        }
+    }
+}
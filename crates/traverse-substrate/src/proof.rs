@@ -0,0 +1,242 @@
+//! Read proofs anchored to block headers
+//!
+//! A block's hash is the Blake2-256 hash of its SCALE-encoded header, and
+//! the header holds the state root after the block. A proof carries the
+//! header so the state root it is checked against is bound to the block
+//! hash, which finality proofs and bridges refer to. A value in a child trie
+//! is proven in two steps: the main trie proves the child trie's root, and
+//! the child trie's own nodes prove the value under that root.
+
+use crate::hashing::blake2_256;
+use crate::scale::decode_compact;
+use crate::trie::verify_trie_proof;
+use crate::SubstrateError;
+use serde_json::{json, Value};
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::String, vec::Vec};
+
+/// The root of a default child trie, with the nodes proving a value under it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChildTrieProof {
+    /// Main trie key holding the root, with its `:child_storage:default:` prefix
+    pub storage_key: Vec<u8>,
+    pub root: [u8; 32],
+    /// Nodes and values of the child trie, in any order
+    pub proof: Vec<Vec<u8>>,
+}
+
+/// A storage value, or its absence, with the trie nodes proving it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubstrateStorageProof {
+    /// Storage key, within the child trie if there is one
+    pub key: Vec<u8>,
+    /// Stored value; `None` when nothing is stored under the key
+    pub value: Option<Vec<u8>>,
+    /// Nodes and values of the main trie, on the path to the key or to the child trie's root
+    pub proof: Vec<Vec<u8>>,
+    pub child: Option<ChildTrieProof>,
+    pub block_number: u64,
+    pub block_hash: [u8; 32],
+    /// SCALE-encoded header of the block
+    pub header: Vec<u8>,
+    pub state_root: [u8; 32],
+}
+
+impl SubstrateStorageProof {
+    /// Check the header against the block hash, then the value, or its absence, against the header's state root
+    pub fn verify(&self) -> Result<(), SubstrateError> {
+        if blake2_256(&self.header) != self.block_hash {
+            return Err(SubstrateError::ProofVerification("the header does not hash to the block hash".into()));
+        }
+        let (number, state_root) = decode_header(&self.header)
+            .ok_or_else(|| SubstrateError::ProofVerification("the header cannot be decoded".into()))?;
+        if number != self.block_number || state_root != self.state_root {
+            return Err(SubstrateError::ProofVerification(
+                "the block number or state root does not match the header".into(),
+            ));
+        }
+
+        let proven = match &self.child {
+            Some(child) => {
+                let root = verify_trie_proof(&self.state_root, &child.storage_key, &self.proof)?;
+                if root.as_deref() != Some(&child.root[..]) {
+                    return Err(SubstrateError::ProofVerification(
+                        "the state does not hold the child trie's root".into(),
+                    ));
+                }
+                verify_trie_proof(&child.root, &self.key, &child.proof)?
+            }
+            None => verify_trie_proof(&self.state_root, &self.key, &self.proof)?,
+        };
+        match (proven, &self.value) {
+            (Some(proven), Some(value)) if proven == *value => Ok(()),
+            (Some(_), Some(_)) => Err(SubstrateError::ProofVerification("the value does not match the trie".into())),
+            (None, None) => Ok(()),
+            (Some(_), None) => {
+                Err(SubstrateError::ProofVerification("the trie holds a value the proof says is absent".into()))
+            }
+            (None, Some(_)) => Err(SubstrateError::ProofVerification("the trie holds no value under the key".into())),
+        }
+    }
+
+    /// JSON form with bytes and hashes in `0x` hex
+    pub fn to_json(&self) -> Value {
+        json!({
+            "key": format!("0x{}", hex::encode(&self.key)),
+            "value": self.value.as_deref().map(|value| format!("0x{}", hex::encode(value))),
+            "proof": hex_list(&self.proof),
+            "child": self.child.as_ref().map(|child| json!({
+                "storage_key": format!("0x{}", hex::encode(&child.storage_key)),
+                "root": format!("0x{}", hex::encode(child.root)),
+                "proof": hex_list(&child.proof),
+            })),
+            "block_number": self.block_number,
+            "block_hash": format!("0x{}", hex::encode(self.block_hash)),
+            "header": format!("0x{}", hex::encode(&self.header)),
+            "state_root": format!("0x{}", hex::encode(self.state_root)),
+        })
+    }
+
+    /// Parse the JSON written by [`Self::to_json`]
+    pub fn from_json(value: &Value) -> Result<Self, SubstrateError> {
+        let field = |value: &'_ Value, name: &str| -> Result<Value, SubstrateError> {
+            value.get(name).cloned().ok_or_else(|| SubstrateError::Rpc(format!("Proof is missing '{}'", name)))
+        };
+        let proof_items = |value: &Value| -> Result<Vec<Vec<u8>>, SubstrateError> {
+            field(value, "proof")?
+                .as_array()
+                .ok_or_else(|| SubstrateError::Rpc("Proof field 'proof' is not a list".into()))?
+                .iter()
+                .map(parse_bytes)
+                .collect()
+        };
+        Ok(Self {
+            key: parse_bytes(&field(value, "key")?)?,
+            value: match field(value, "value")? {
+                Value::Null => None,
+                stored => Some(parse_bytes(&stored)?),
+            },
+            proof: proof_items(value)?,
+            child: match field(value, "child")? {
+                Value::Null => None,
+                child => Some(ChildTrieProof {
+                    storage_key: parse_bytes(&field(&child, "storage_key")?)?,
+                    root: parse_hash(&field(&child, "root")?)?,
+                    proof: proof_items(&child)?,
+                }),
+            },
+            block_number: field(value, "block_number")?
+                .as_u64()
+                .ok_or_else(|| SubstrateError::Rpc("Proof field 'block_number' is not a number".into()))?,
+            block_hash: parse_hash(&field(value, "block_hash")?)?,
+            header: parse_bytes(&field(value, "header")?)?,
+            state_root: parse_hash(&field(value, "state_root")?)?,
+        })
+    }
+}
+
+/// Number and state root of a SCALE-encoded header: parent hash, compact number, state root, ...
+pub fn decode_header(header: &[u8]) -> Option<(u64, [u8; 32])> {
+    let (number, read) = decode_compact(header.get(32..)?)?;
+    let state_root = header.get(32 + read..64 + read)?.try_into().ok()?;
+    Some((u64::try_from(number).ok()?, state_root))
+}
+
+fn hex_list(items: &[Vec<u8>]) -> Vec<String> {
+    items.iter().map(|item| format!("0x{}", hex::encode(item))).collect()
+}
+
+fn parse_bytes(value: &Value) -> Result<Vec<u8>, SubstrateError> {
+    let hex_str = value.as_str().ok_or_else(|| SubstrateError::Rpc(format!("Expected hex, found {}", value)))?;
+    hex::decode(hex_str.trim_start_matches("0x"))
+        .map_err(|e| SubstrateError::Rpc(format!("Invalid hex '{}': {}", hex_str, e)))
+}
+
+fn parse_hash(value: &Value) -> Result<[u8; 32], SubstrateError> {
+    parse_bytes(value)?
+        .try_into()
+        .map_err(|_| SubstrateError::Rpc(format!("Expected a 32-byte hash, found {}", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hashing::{child_storage_key, storage_prefix};
+    use crate::scale::encode_compact;
+    use crate::trie::tests::build_trie;
+
+    /// A header of block `number` with `state_root`, empty roots elsewhere and no digest
+    fn header(number: u64, state_root: &[u8; 32]) -> Vec<u8> {
+        [&[5u8; 32][..], &encode_compact(number as u128), state_root, &[6; 32], &[0]].concat()
+    }
+
+    /// Timestamp::Now, with a child trie holding one contribution
+    fn proofs() -> (SubstrateStorageProof, SubstrateStorageProof) {
+        let now_key = storage_prefix("Timestamp", "Now").to_vec();
+        let now = 1_700_000_000_000u64.to_le_bytes();
+        let contribution = [&[0x42; 16][..], &[0xaa; 20]].concat();
+        let (child_root, child_proof) = build_trie(&[(&[1; 32], &contribution), (&[2; 32], &[1])]);
+        let child_key = child_storage_key(&[0xd0, 0x07, 0, 0]);
+        let (state_root, proof) = build_trie(&[(&now_key, &now), (&child_key, &child_root)]);
+        let header = header(1_000_000, &state_root);
+
+        let main = SubstrateStorageProof {
+            key: now_key,
+            value: Some(now.to_vec()),
+            proof: proof.clone(),
+            child: None,
+            block_number: 1_000_000,
+            block_hash: blake2_256(&header),
+            header,
+            state_root,
+        };
+        let child = SubstrateStorageProof {
+            key: [1; 32].to_vec(),
+            value: Some(contribution),
+            child: Some(ChildTrieProof { storage_key: child_key, root: child_root, proof: child_proof }),
+            ..main.clone()
+        };
+        (main, child)
+    }
+
+    #[test]
+    fn test_proof_verification_and_json() {
+        let (main, child) = proofs();
+        main.verify().unwrap();
+        child.verify().unwrap();
+        assert_eq!(SubstrateStorageProof::from_json(&main.to_json()).unwrap(), main);
+        assert_eq!(SubstrateStorageProof::from_json(&child.to_json()).unwrap(), child);
+
+        let mut other_value = main.clone();
+        other_value.value.as_mut().unwrap()[0] ^= 1;
+        assert!(other_value.verify().is_err());
+
+        // Absent keys, in the main trie and in the child trie
+        let mut missing = main.clone();
+        missing.key = storage_prefix("Timestamp", "DidUpdate").to_vec();
+        missing.value = None;
+        missing.verify().unwrap();
+        let mut missing_child = child.clone();
+        missing_child.key = [3; 32].to_vec();
+        missing_child.value = None;
+        missing_child.verify().unwrap();
+
+        // The header binds the state root to the block
+        let mut other_root = main.clone();
+        other_root.state_root = child.child.as_ref().unwrap().root;
+        assert!(other_root.verify().is_err());
+        let mut other_block = main.clone();
+        other_block.block_number += 1;
+        assert!(other_block.verify().is_err());
+        let mut other_hash = main;
+        other_hash.block_hash[0] ^= 1;
+        assert!(other_hash.verify().is_err());
+
+        // The child trie's root must be the one the main trie holds
+        let mut other_child = child;
+        let (root, proof) = build_trie(&[(&[1; 32], &other_child.value.clone().unwrap())]);
+        other_child.child = Some(ChildTrieProof { root, proof, ..other_child.child.unwrap() });
+        assert!(other_child.verify().is_err());
+    }
+}
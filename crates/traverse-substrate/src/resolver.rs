@@ -0,0 +1,217 @@
+//! Storage key resolution
+//!
+//! Queries name a storage item as `Pallet::Item`, followed by one `[key]` per
+//! key of a map: `Timestamp::Now`, `System::Account[5Grw...]`,
+//! `Staking::ErasStakers[1200][0xd435...]`. Child trie entries are named by
+//! the child trie's label, e.g. `fund_2000[5Grw...]`. Each key is SCALE
+//! encoded and hashed with the hasher the layout records for it.
+
+use crate::hashing::StorageHasher;
+use crate::scale::{encode_compact, ScaleType};
+use crate::SubstrateError;
+use traverse_core::{Key, KeyResolver, LayoutInfo, StaticKeyPath, StorageEntry, TraverseError, ZeroSemantics};
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::String, vec::Vec};
+
+/// Key resolver for FRAME runtime storage
+///
+/// # Usage
+///
+/// ```rust,ignore
+/// use traverse_substrate::SubstrateKeyResolver;
+/// use traverse_core::KeyResolver;
+///
+/// let path = SubstrateKeyResolver.resolve(&layout, "System::Account[5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY]")?;
+/// ```
+pub struct SubstrateKeyResolver;
+
+/// A storage value resolved against a layout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageKey {
+    /// Main trie key holding the root of the child trie the value is in, if it is in one
+    pub child_storage_key: Option<Vec<u8>>,
+    /// Key of the value, within the child trie if there is one
+    pub key: Vec<u8>,
+    /// Type of the value
+    pub type_name: String,
+    /// Size of the value, if fixed
+    pub size: Option<usize>,
+    pub zero_semantics: ZeroSemantics,
+}
+
+impl SubstrateKeyResolver {
+    /// Resolve a query to the storage key it names
+    pub fn storage_key(layout: &LayoutInfo, query: &str) -> Result<StorageKey, SubstrateError> {
+        let query = query.trim();
+        let (name, keys) = match query.split_once('[') {
+            Some((name, rest)) => {
+                let keys = rest
+                    .strip_suffix(']')
+                    .filter(|keys| !keys.is_empty())
+                    .ok_or_else(|| SubstrateError::InvalidQuery(format!("'{}' is not Item[key]...", query)))?;
+                (name.trim(), keys.split("][").map(str::trim).collect())
+            }
+            None => (query, Vec::new()),
+        };
+        let entry = layout
+            .storage
+            .iter()
+            .find(|e| e.label == name)
+            .ok_or_else(|| SubstrateError::InvalidQuery(format!("{} declares no storage item {}", layout.contract_name, name)))?;
+        let item_type = layout.types.iter().find(|t| t.label == entry.type_name);
+        let hashed_keys = match item_type.and_then(|t| t.key.as_deref()) {
+            Some(key_name) => parse_hashed_keys(key_name)?,
+            None => Vec::new(),
+        };
+        if keys.len() != hashed_keys.len() {
+            return Err(SubstrateError::InvalidQuery(format!(
+                "{} takes {} key(s), the query gives {}",
+                name,
+                hashed_keys.len(),
+                keys.len()
+            )));
+        }
+
+        let slot = parse_slot(entry)?;
+        let child = entry.type_name.starts_with("ChildTrie<");
+        let (child_storage_key, mut key) = if child { (Some(slot), Vec::new()) } else { (None, slot) };
+        for (query_key, (hasher, key_type)) in keys.iter().zip(&hashed_keys) {
+            key.extend(hasher.hash(&ScaleType::parse(key_type).encode_key(query_key)?));
+        }
+
+        let value_type = item_type.and_then(|t| t.value.clone()).unwrap_or_default();
+        Ok(StorageKey {
+            child_storage_key,
+            key,
+            size: type_size(layout, &value_type),
+            type_name: value_type,
+            zero_semantics: entry.zero_semantics,
+        })
+    }
+}
+
+impl KeyResolver for SubstrateKeyResolver {
+    /// Resolve a query to its trie key
+    ///
+    /// A child trie entry's key is the child trie's main trie key as a SCALE
+    /// byte vector, followed by the key within the child trie.
+    fn resolve(&self, layout: &LayoutInfo, query: &str) -> Result<StaticKeyPath, TraverseError> {
+        let storage_key = Self::storage_key(layout, query)?;
+        let (name, key) = match &storage_key.child_storage_key {
+            Some(child) => {
+                let prefix = [encode_compact(child.len() as u128).as_slice(), child].concat();
+                ("substrate_child_storage", [prefix, storage_key.key].concat())
+            }
+            None => ("substrate_storage", storage_key.key),
+        };
+        Ok(StaticKeyPath {
+            name,
+            key: Key::Variable(key),
+            offset: None,
+            field_size: storage_key.size.and_then(|size| u8::try_from(size).ok()),
+            layout_commitment: layout.commitment(),
+            zero_semantics: storage_key.zero_semantics,
+        })
+    }
+
+    /// Resolve the plain storage values, which need no key
+    fn resolve_all(&self, layout: &LayoutInfo) -> Result<Vec<StaticKeyPath>, TraverseError> {
+        let plain = |entry: &&StorageEntry| entry.type_name.starts_with("StorageValue<");
+        layout.storage.iter().filter(plain).map(|entry| self.resolve(layout, &entry.label)).collect()
+    }
+}
+
+/// `Hasher<Type>, ...` as the layout records a map's keys
+fn parse_hashed_keys(key_name: &str) -> Result<Vec<(StorageHasher, String)>, SubstrateError> {
+    let mut keys = Vec::new();
+    let (mut depth, mut start) = (0usize, 0);
+    for (i, c) in key_name.char_indices().chain([(key_name.len(), ',')]) {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                let part = key_name[start..i].trim();
+                let (hasher, key_type) = part
+                    .split_once('<')
+                    .and_then(|(hasher, rest)| Some((hasher, rest.strip_suffix('>')?)))
+                    .ok_or_else(|| SubstrateError::InvalidQuery(format!("Layout key '{}' is not Hasher<Type>", part)))?;
+                keys.push((StorageHasher::parse(hasher)?, key_type.into()));
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    Ok(keys)
+}
+
+/// A storage prefix or child trie key, which its slot holds in hex
+fn parse_slot(entry: &StorageEntry) -> Result<Vec<u8>, SubstrateError> {
+    entry
+        .slot
+        .strip_prefix("0x")
+        .and_then(|digits| hex::decode(digits).ok())
+        .ok_or_else(|| SubstrateError::InvalidQuery(format!("Layout entry {} has no storage key", entry.label)))
+}
+
+fn type_size(layout: &LayoutInfo, type_name: &str) -> Option<usize> {
+    layout
+        .types
+        .iter()
+        .find(|t| t.label == type_name)
+        .and_then(|t| t.number_of_bytes.parse::<usize>().ok())
+        .filter(|size| *size > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hashing::{child_storage_key, storage_prefix, twox_64};
+    use crate::definition::tests::{CHILD_DEFINITION, METADATA};
+    use crate::layout::tests::compile;
+    use crate::scale::tests::ALICE;
+
+    #[test]
+    fn test_resolve_storage_keys() {
+        let layout = compile(METADATA);
+        let alice = hex::decode(ALICE).unwrap();
+
+        let account = SubstrateKeyResolver::storage_key(&layout, "System::Account[5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY]").unwrap();
+        assert_eq!(
+            hex::encode(&account.key),
+            format!("26aa394eea5630e07c48ae0c9558cef7b99d880ec681799c0cf30e8886371da9de1e86a9a8c739864cf3cc5ec2bea59f{}", ALICE)
+        );
+        assert_eq!((account.size, account.child_storage_key.as_ref()), (Some(80), None));
+
+        let stakers = SubstrateKeyResolver::storage_key(&layout, &format!("Staking::ErasStakers[1200][0x{}]", ALICE)).unwrap();
+        let era = 1200u32.to_le_bytes();
+        let expected = [&storage_prefix("Staking", "ErasStakers")[..], &twox_64(&era), &era, &twox_64(&alice), &alice].concat();
+        assert_eq!(stakers.key, expected);
+
+        let now = SubstrateKeyResolver.resolve(&layout, "Timestamp::Now").unwrap();
+        assert_eq!(now.key, Key::Variable(storage_prefix("Timestamp", "Now").to_vec()));
+        assert_eq!((now.field_size, now.zero_semantics), (Some(8), ZeroSemantics::ValidZero));
+        assert_eq!(SubstrateKeyResolver.resolve_all(&layout).unwrap(), [now]);
+
+        assert!(SubstrateKeyResolver.resolve(&layout, "System::Account").is_err());
+        assert!(SubstrateKeyResolver.resolve(&layout, "Timestamp::Now[1]").is_err());
+        assert!(SubstrateKeyResolver.resolve(&layout, "Staking::ErasStakers[1200]").is_err());
+        assert!(SubstrateKeyResolver.resolve(&layout, "System::Account[alice]").is_err());
+        assert!(SubstrateKeyResolver.resolve(&layout, "Balances::Account[0x00]").is_err());
+        // Empty and unclosed key brackets
+        assert!(SubstrateKeyResolver.resolve(&layout, "System::Account[]").is_err());
+        assert!(SubstrateKeyResolver.resolve(&layout, &format!("System::Account[0x{}", ALICE)).is_err());
+
+        // Child trie entries are keyed within the child trie
+        let layout = compile(CHILD_DEFINITION);
+        let contribution = SubstrateKeyResolver::storage_key(&layout, &format!("fund_2000[0x{}]", ALICE)).unwrap();
+        assert_eq!(contribution.child_storage_key, Some(child_storage_key(&[0xd0, 0x07, 0, 0])));
+        assert_eq!(contribution.key, alice);
+        let path = SubstrateKeyResolver.resolve(&layout, &format!("fund_2000[0x{}]", ALICE)).unwrap();
+        assert_eq!(path.name, "substrate_child_storage");
+        let Key::Variable(key) = path.key else { panic!("Expected a variable key") };
+        assert_eq!(key[0] as usize, 27 << 2);
+        assert_eq!(&key[28..], alice);
+        assert!(SubstrateKeyResolver.resolve_all(&layout).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,215 @@
+//! SCALE encoding of map keys
+//!
+//! Map keys are SCALE encoded before they are hashed: integers little-endian
+//! at their full width, byte vectors after a compact length, and fixed
+//! arrays as they are. Queries write numbers in decimal, byte strings in
+//! `0x` hex, and accounts in hex or as SS58 addresses.
+
+use crate::SubstrateError;
+use blake2::{Blake2b512, Digest};
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// A SCALE type as far as keys and offsets need it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScaleType {
+    Bool,
+    Unsigned(usize),
+    Signed(usize),
+    /// `AccountId32`: 32 bytes, written in hex or SS58
+    AccountId,
+    /// `[u8; N]`, `H160` or `H256`
+    FixedBytes(usize),
+    /// `Vec<u8>`: a compact length, then the bytes
+    Bytes,
+    /// `Compact<uN>`
+    Compact,
+    /// Any other type; keys of it are written as their SCALE encoding in hex
+    Other(String),
+}
+
+impl ScaleType {
+    /// Parse a type name, resolving the FRAME aliases with known encodings
+    pub fn parse(type_name: &str) -> Self {
+        let type_name = type_name.trim();
+        let name = type_name.strip_prefix("T::").unwrap_or(type_name);
+        match name {
+            "bool" => Self::Bool,
+            "u8" => Self::Unsigned(1),
+            "u16" => Self::Unsigned(2),
+            "u32" | "BlockNumber" | "BlockNumberFor<T>" | "ParaId" | "SessionIndex" | "EraIndex" => Self::Unsigned(4),
+            "u64" | "Moment" => Self::Unsigned(8),
+            "u128" | "Balance" | "BalanceOf<T>" => Self::Unsigned(16),
+            "i8" => Self::Signed(1),
+            "i16" => Self::Signed(2),
+            "i32" => Self::Signed(4),
+            "i64" => Self::Signed(8),
+            "i128" => Self::Signed(16),
+            "AccountId32" | "AccountId" => Self::AccountId,
+            "H160" => Self::FixedBytes(20),
+            "H256" | "Hash" => Self::FixedBytes(32),
+            "Vec<u8>" | "String" => Self::Bytes,
+            _ if name.starts_with("Compact<") => Self::Compact,
+            _ if name.starts_with("BoundedVec<u8,") => Self::Bytes,
+            _ => name
+                .strip_prefix("[u8;")
+                .and_then(|n| n.strip_suffix(']'))
+                .and_then(|n| n.trim().parse().ok())
+                .map(Self::FixedBytes)
+                .unwrap_or_else(|| Self::Other(type_name.to_string())),
+        }
+    }
+
+    /// Encoded size, if every value has the same
+    pub fn fixed_size(&self) -> Option<usize> {
+        match self {
+            Self::Bool => Some(1),
+            Self::Unsigned(size) | Self::Signed(size) | Self::FixedBytes(size) => Some(*size),
+            Self::AccountId => Some(32),
+            Self::Bytes | Self::Compact | Self::Other(_) => None,
+        }
+    }
+
+    /// SCALE encoding of a key written in a query
+    pub fn encode_key(&self, key: &str) -> Result<Vec<u8>, SubstrateError> {
+        let key = key.trim();
+        let invalid = || SubstrateError::InvalidQuery(format!("'{}' is not a {:?} key", key, self));
+        let hex_bytes = || hex::decode(key.strip_prefix("0x").ok_or_else(invalid)?).map_err(|_| invalid());
+        match self {
+            Self::Bool => match key {
+                "true" => Ok([1].to_vec()),
+                "false" => Ok([0].to_vec()),
+                _ => Err(invalid()),
+            },
+            Self::Unsigned(size) => {
+                let value: u128 = key.parse().map_err(|_| invalid())?;
+                let bytes = value.to_le_bytes();
+                if bytes[*size..].iter().any(|b| *b != 0) {
+                    return Err(invalid());
+                }
+                Ok(bytes[..*size].to_vec())
+            }
+            Self::Signed(size) => {
+                let value: i128 = key.parse().map_err(|_| invalid())?;
+                let bits = *size as u32 * 8;
+                if bits < 128 && (value >= 1 << (bits - 1) || value < -(1 << (bits - 1))) {
+                    return Err(invalid());
+                }
+                Ok(value.to_le_bytes()[..*size].to_vec())
+            }
+            Self::AccountId if key.starts_with("0x") => Self::FixedBytes(32).encode_key(key),
+            Self::AccountId => Ok(decode_ss58(key)?.to_vec()),
+            Self::FixedBytes(size) => {
+                let bytes = hex_bytes()?;
+                if bytes.len() != *size {
+                    return Err(invalid());
+                }
+                Ok(bytes)
+            }
+            Self::Bytes => {
+                let bytes = if key.starts_with("0x") { hex_bytes()? } else { key.trim_matches('"').as_bytes().to_vec() };
+                Ok([encode_compact(bytes.len() as u128), bytes].concat())
+            }
+            Self::Compact => Ok(encode_compact(key.parse().map_err(|_| invalid())?)),
+            Self::Other(_) => hex_bytes(),
+        }
+    }
+}
+
+/// SCALE compact encoding of an unsigned integer
+pub fn encode_compact(value: u128) -> Vec<u8> {
+    match value {
+        0..=0x3f => [(value as u8) << 2].to_vec(),
+        0x40..=0x3fff => (((value as u16) << 2) | 1).to_le_bytes().to_vec(),
+        0x4000..=0x3fff_ffff => (((value as u32) << 2) | 2).to_le_bytes().to_vec(),
+        _ => {
+            let bytes = value.to_le_bytes();
+            let len = 16 - bytes.iter().rev().take_while(|b| **b == 0).count();
+            [&[((len as u8 - 4) << 2) | 3][..], &bytes[..len]].concat()
+        }
+    }
+}
+
+/// Decode a compact integer at the start of `bytes`, returning it with its encoded length
+pub fn decode_compact(bytes: &[u8]) -> Option<(u128, usize)> {
+    let first = *bytes.first()?;
+    let le = |len: usize| -> Option<u128> {
+        let encoded = bytes.get(..len)?;
+        Some(encoded.iter().rev().fold(0u128, |value, b| value << 8 | *b as u128))
+    };
+    match first & 0b11 {
+        0 => Some(((first >> 2) as u128, 1)),
+        1 => Some((le(2)? >> 2, 2)),
+        2 => Some((le(4)? >> 2, 4)),
+        _ => {
+            let len = (first >> 2) as usize + 4;
+            if len > 16 {
+                return None;
+            }
+            let value = bytes.get(1..1 + len)?.iter().rev().fold(0u128, |value, b| value << 8 | *b as u128);
+            Some((value, 1 + len))
+        }
+    }
+}
+
+/// The account an SS58 address encodes, whatever its network prefix
+pub fn decode_ss58(address: &str) -> Result<[u8; 32], SubstrateError> {
+    let invalid = |reason: &str| SubstrateError::InvalidAddress(format!("'{}' {}", address, reason));
+    let data = bs58::decode(address).into_vec().map_err(|_| invalid("is not base58"))?;
+    // One prefix byte for network IDs below 64, two above
+    let prefix_len = match data.first() {
+        Some(first) if *first < 64 => 1,
+        Some(first) if *first < 128 => 2,
+        _ => return Err(invalid("has no network prefix")),
+    };
+    if data.len() != prefix_len + 32 + 2 {
+        return Err(invalid("is not a 32-byte account address"));
+    }
+    let (body, checksum) = data.split_at(prefix_len + 32);
+    let hash = Blake2b512::new().chain_update(b"SS58PRE").chain_update(body).finalize();
+    if hash[..2] != *checksum {
+        return Err(invalid("has a wrong checksum"));
+    }
+    let mut account = [0u8; 32];
+    account.copy_from_slice(&body[prefix_len..]);
+    Ok(account)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) const ALICE: &str = "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d";
+
+    #[test]
+    fn test_keys_and_compact() {
+        // Alice on the generic and the Polkadot networks
+        let alice = hex::decode(ALICE).unwrap();
+        assert_eq!(decode_ss58("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY").unwrap().to_vec(), alice);
+        assert_eq!(decode_ss58("15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5").unwrap().to_vec(), alice);
+        assert!(decode_ss58("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQZ").is_err());
+        assert_eq!(ScaleType::parse("T::AccountId").encode_key(&format!("0x{}", ALICE)).unwrap(), alice);
+
+        assert_eq!(ScaleType::parse("u32").encode_key("2000").unwrap(), [0xd0, 0x07, 0, 0]);
+        assert!(ScaleType::parse("u8").encode_key("256").is_err());
+        assert_eq!(ScaleType::parse("i16").encode_key("-2").unwrap(), [0xfe, 0xff]);
+        assert_eq!(ScaleType::parse("Vec<u8>").encode_key("0xbeef").unwrap(), [8, 0xbe, 0xef]);
+        assert_eq!(ScaleType::parse("Vec<u8>").encode_key("ab").unwrap(), [8, b'a', b'b']);
+        assert_eq!(ScaleType::parse("(u32, u32)").encode_key("0x0100000002000000").unwrap().len(), 8);
+        assert_eq!(ScaleType::parse("BalanceOf<T>").fixed_size(), Some(16));
+
+        for value in [0, 63, 64, 16_383, 16_384, 1 << 30, u64::MAX as u128, u128::MAX] {
+            let encoded = encode_compact(value);
+            assert_eq!(decode_compact(&encoded), Some((value, encoded.len())));
+        }
+        assert_eq!(encode_compact(1), [4]);
+        assert_eq!(encode_compact(64), [1, 1]);
+        assert_eq!(encode_compact(1 << 30), [3, 0, 0, 0, 0x40]);
+        assert_eq!(decode_compact(&[1]), None);
+    }
+}
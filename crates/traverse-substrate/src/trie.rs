@@ -0,0 +1,374 @@
+//! State trie nodes and read proofs
+//!
+//! Substrate keeps state in a base-16 Patricia trie without extension nodes:
+//! leaves and branches carry the partial key they consume, and nodes are
+//! hashed with Blake2-256. A child whose encoding is shorter than a hash is
+//! inlined in its parent. Since state version 1, values of 33 bytes or more
+//! are stored apart and referenced by hash, so a read proof holds those
+//! values next to the nodes. A proof is therefore an unordered set of
+//! encoded nodes and values: the key is present when its path ends at a
+//! value, and absent when the path leaves the trie.
+
+use crate::hashing::blake2_256;
+use crate::scale::{decode_compact, encode_compact};
+use crate::SubstrateError;
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, vec::Vec};
+
+/// Largest partial key a node header may declare, in nibbles
+const NIBBLE_SIZE_BOUND: usize = u16::MAX as usize;
+
+const LEAF_PREFIX: u8 = 0b01 << 6;
+const BRANCH_WITHOUT_VALUE_PREFIX: u8 = 0b10 << 6;
+const BRANCH_WITH_VALUE_PREFIX: u8 = 0b11 << 6;
+const HASHED_VALUE_LEAF_PREFIX: u8 = 0b001 << 5;
+const HASHED_VALUE_BRANCH_PREFIX: u8 = 0b0001 << 4;
+
+/// A value, stored in its node or referenced by hash
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeValue {
+    Inline(Vec<u8>),
+    Hashed([u8; 32]),
+}
+
+/// A branch's reference to a child: its hash, or its encoding when shorter than a hash
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeHandle {
+    Hash([u8; 32]),
+    Inline(Vec<u8>),
+}
+
+/// A trie node; partial keys are in nibbles
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrieNode {
+    Empty,
+    Leaf { partial: Vec<u8>, value: NodeValue },
+    Branch { partial: Vec<u8>, children: [Option<NodeHandle>; 16], value: Option<NodeValue> },
+}
+
+impl TrieNode {
+    pub fn decode(bytes: &[u8]) -> Result<Self, SubstrateError> {
+        let mut reader = Reader { bytes, position: 0 };
+        let first = reader.take(1)?[0];
+        if first == 0 {
+            return reader.finish(Self::Empty);
+        }
+        // (is a branch, has a value, value is hashed, prefix bits)
+        let (branch, has_value, hashed, prefix_bits) = match first >> 6 {
+            0b01 => (false, true, false, 2),
+            0b10 => (true, false, false, 2),
+            0b11 => (true, true, false, 2),
+            _ if first >> 5 == 0b001 => (false, true, true, 3),
+            _ if first >> 4 == 0b0001 => (true, true, true, 4),
+            _ => return Err(SubstrateError::ProofVerification(format!("unknown trie node header {:#04x}", first))),
+        };
+        let partial = reader.partial(first, prefix_bits)?;
+        if !branch {
+            let value = reader.value(hashed)?;
+            return reader.finish(Self::Leaf { partial, value });
+        }
+
+        let bitmap = u16::from_le_bytes(reader.take(2)?.try_into().unwrap_or_default());
+        let value = if has_value { Some(reader.value(hashed)?) } else { None };
+        let mut children: [Option<NodeHandle>; 16] = Default::default();
+        for (i, child) in children.iter_mut().enumerate() {
+            if bitmap & (1 << i) != 0 {
+                let encoded = reader.length_prefixed()?;
+                *child = Some(match <[u8; 32]>::try_from(encoded) {
+                    Ok(hash) => NodeHandle::Hash(hash),
+                    Err(_) => NodeHandle::Inline(encoded.to_vec()),
+                });
+            }
+        }
+        reader.finish(Self::Branch { partial, children, value })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        match self {
+            Self::Empty => out.push(0),
+            Self::Leaf { partial, value } => {
+                match value {
+                    NodeValue::Inline(_) => write_header(&mut out, LEAF_PREFIX, 2, partial.len()),
+                    NodeValue::Hashed(_) => write_header(&mut out, HASHED_VALUE_LEAF_PREFIX, 3, partial.len()),
+                }
+                write_partial(&mut out, partial);
+                write_value(&mut out, value);
+            }
+            Self::Branch { partial, children, value } => {
+                match value {
+                    None => write_header(&mut out, BRANCH_WITHOUT_VALUE_PREFIX, 2, partial.len()),
+                    Some(NodeValue::Inline(_)) => write_header(&mut out, BRANCH_WITH_VALUE_PREFIX, 2, partial.len()),
+                    Some(NodeValue::Hashed(_)) => write_header(&mut out, HASHED_VALUE_BRANCH_PREFIX, 4, partial.len()),
+                }
+                write_partial(&mut out, partial);
+                let bitmap = children.iter().enumerate().filter(|(_, c)| c.is_some()).fold(0u16, |bits, (i, _)| bits | 1 << i);
+                out.extend_from_slice(&bitmap.to_le_bytes());
+                if let Some(value) = value {
+                    write_value(&mut out, value);
+                }
+                for child in children.iter().flatten() {
+                    let encoded = match child {
+                        NodeHandle::Hash(hash) => &hash[..],
+                        NodeHandle::Inline(node) => node,
+                    };
+                    out.extend(encode_compact(encoded.len() as u128));
+                    out.extend_from_slice(encoded);
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Root of a trie holding nothing
+pub fn empty_trie_root() -> [u8; 32] {
+    blake2_256(&[0])
+}
+
+/// Follow `key` from `root` through the nodes and values of a read proof
+///
+/// Returns the value stored under the key, or `None` when the proof shows
+/// there is none. Items the path does not reach are ignored, so a proof of
+/// several keys can be passed as it is.
+pub fn verify_trie_proof(root: &[u8; 32], key: &[u8], proof: &[Vec<u8>]) -> Result<Option<Vec<u8>>, SubstrateError> {
+    if *root == empty_trie_root() {
+        return Ok(None);
+    }
+    let hashed: Vec<_> = proof.iter().map(|item| (blake2_256(item), item.as_slice())).collect();
+    let lookup = |hash: &[u8; 32]| {
+        hashed.iter().find(|(h, _)| h == hash).map(|(_, item)| *item).ok_or_else(|| {
+            SubstrateError::ProofVerification(format!("the proof is missing trie item {}", hex::encode(hash)))
+        })
+    };
+    let resolve = |value: NodeValue| match value {
+        NodeValue::Inline(value) => Ok(value),
+        NodeValue::Hashed(hash) => lookup(&hash).map(<[u8]>::to_vec),
+    };
+    let nibbles: Vec<u8> = key.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect();
+    let (mut node, mut position) = (lookup(root)?.to_vec(), 0);
+
+    loop {
+        let rest = &nibbles[position..];
+        let (partial, children, value) = match TrieNode::decode(&node)? {
+            TrieNode::Empty => return Ok(None),
+            TrieNode::Leaf { partial, value } if rest == partial.as_slice() => return resolve(value).map(Some),
+            TrieNode::Leaf { .. } => return Ok(None),
+            TrieNode::Branch { partial, children, value } => (partial, children, value),
+        };
+        let Some(rest) = rest.strip_prefix(partial.as_slice()) else {
+            return Ok(None);
+        };
+        let Some(next) = rest.first() else {
+            return value.map(resolve).transpose();
+        };
+        node = match &children[*next as usize] {
+            Some(NodeHandle::Hash(hash)) => lookup(hash)?.to_vec(),
+            Some(NodeHandle::Inline(encoded)) => encoded.clone(),
+            None => return Ok(None),
+        };
+        position += partial.len() + 1;
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], SubstrateError> {
+        let end = self.position.checked_add(len).filter(|end| *end <= self.bytes.len());
+        let end = end.ok_or_else(|| SubstrateError::ProofVerification("truncated trie node".into()))?;
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn length_prefixed(&mut self) -> Result<&'a [u8], SubstrateError> {
+        let (len, read) = decode_compact(&self.bytes[self.position..])
+            .ok_or_else(|| SubstrateError::ProofVerification("invalid length in trie node".into()))?;
+        self.position += read;
+        self.take(usize::try_from(len).unwrap_or(usize::MAX))
+    }
+
+    fn value(&mut self, hashed: bool) -> Result<NodeValue, SubstrateError> {
+        Ok(match hashed {
+            true => NodeValue::Hashed(self.take(32)?.try_into().unwrap_or_default()),
+            false => NodeValue::Inline(self.length_prefixed()?.to_vec()),
+        })
+    }
+
+    /// The partial key: its nibble count, from the header on, then the nibbles
+    /// packed in pairs, with an odd first nibble alone in the low half of its byte
+    fn partial(&mut self, first: u8, prefix_bits: u32) -> Result<Vec<u8>, SubstrateError> {
+        let max = 255u8 >> prefix_bits;
+        let mut count = (first & max) as usize;
+        if count == max as usize {
+            count -= 1;
+            loop {
+                let next = self.take(1)?[0] as usize;
+                count += next;
+                if next < 255 {
+                    count += 1;
+                    break;
+                }
+                if count > NIBBLE_SIZE_BOUND {
+                    return Err(SubstrateError::ProofVerification("trie node partial key is too long".into()));
+                }
+            }
+        }
+        let packed = self.take(count.div_ceil(2))?;
+        let mut nibbles: Vec<u8> = packed.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect();
+        if count % 2 == 1 {
+            if nibbles[0] != 0 {
+                return Err(SubstrateError::ProofVerification("trie node partial key has bad padding".into()));
+            }
+            nibbles.remove(0);
+        }
+        Ok(nibbles)
+    }
+
+    fn finish(self, node: TrieNode) -> Result<TrieNode, SubstrateError> {
+        if self.position != self.bytes.len() {
+            return Err(SubstrateError::ProofVerification("trailing bytes after a trie node".into()));
+        }
+        Ok(node)
+    }
+}
+
+/// A node header: the prefix, and the partial key's nibble count spilling into following bytes
+fn write_header(out: &mut Vec<u8>, prefix: u8, prefix_bits: u32, nibble_count: usize) {
+    let max = 255usize >> prefix_bits;
+    if nibble_count < max {
+        out.push(prefix | nibble_count as u8);
+        return;
+    }
+    out.push(prefix | max as u8);
+    let mut rest = nibble_count - (max - 1);
+    while rest >= 256 {
+        out.push(255);
+        rest -= 255;
+    }
+    out.push((rest - 1) as u8);
+}
+
+fn write_partial(out: &mut Vec<u8>, nibbles: &[u8]) {
+    let pairs = if nibbles.len() % 2 == 1 {
+        out.push(nibbles[0]);
+        &nibbles[1..]
+    } else {
+        nibbles
+    };
+    out.extend(pairs.chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+}
+
+fn write_value(out: &mut Vec<u8>, value: &NodeValue) {
+    match value {
+        NodeValue::Inline(value) => {
+            out.extend(encode_compact(value.len() as u128));
+            out.extend_from_slice(value);
+        }
+        NodeValue::Hashed(hash) => out.extend_from_slice(hash),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A state version 1 trie holding `entries`, as its root and every node and stored value
+    pub(crate) fn build_trie(entries: &[(&[u8], &[u8])]) -> ([u8; 32], Vec<Vec<u8>>) {
+        let mut entries: Vec<(Vec<u8>, &[u8])> =
+            entries.iter().map(|(key, value)| (key.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect(), *value)).collect();
+        entries.sort();
+        let mut items = Vec::new();
+        let root = build_node(&entries, &mut items);
+        let root_hash = blake2_256(&root);
+        items.push(root);
+        (root_hash, items)
+    }
+
+    fn build_node(entries: &[(Vec<u8>, &[u8])], items: &mut Vec<Vec<u8>>) -> Vec<u8> {
+        let mut node_value = |value: &[u8]| {
+            if value.len() >= 33 {
+                items.push(value.to_vec());
+                NodeValue::Hashed(blake2_256(value))
+            } else {
+                NodeValue::Inline(value.to_vec())
+            }
+        };
+        if let [(key, value)] = entries {
+            return TrieNode::Leaf { partial: key.clone(), value: node_value(value) }.encode();
+        }
+        let first = &entries[0].0;
+        let common = (0..first.len()).find(|i| entries.iter().any(|(key, _)| key.get(*i) != first.get(*i)));
+        let common = common.unwrap_or(first.len());
+        let value = entries.iter().find(|(key, _)| key.len() == common).map(|(_, value)| node_value(value));
+
+        let mut children: [Option<NodeHandle>; 16] = Default::default();
+        for (nibble, child) in children.iter_mut().enumerate() {
+            let below: Vec<_> = entries
+                .iter()
+                .filter(|(key, _)| key.get(common) == Some(&(nibble as u8)))
+                .map(|(key, value)| (key[common + 1..].to_vec(), *value))
+                .collect();
+            if below.is_empty() {
+                continue;
+            }
+            let encoded = build_node(&below, items);
+            *child = Some(if encoded.len() < 32 {
+                NodeHandle::Inline(encoded)
+            } else {
+                let hash = blake2_256(&encoded);
+                items.push(encoded);
+                NodeHandle::Hash(hash)
+            });
+        }
+        TrieNode::Branch { partial: first[..common].to_vec(), children, value }.encode()
+    }
+
+    #[test]
+    fn test_trie_proofs() {
+        assert_eq!(hex::encode(empty_trie_root()), "03170a2e7597b7b7e3d84c05391d139a62b157e78786d8c082f29dcf4c111314");
+
+        let long = [7u8; 40];
+        let entries: [(&[u8], &[u8]); 5] =
+            [(b"\x12\x34", b"one"), (b"\x12\x35", &long), (b"\x12", b"branch value"), (b"\x13\x00", &[]), (b"\xff", &long)];
+        let (root, items) = build_trie(&entries);
+        for (key, value) in entries {
+            assert_eq!(verify_trie_proof(&root, key, &items).unwrap().as_deref(), Some(value));
+        }
+        // Absent: a missing child, a diverging leaf, a key ending inside a partial key
+        assert_eq!(verify_trie_proof(&root, b"\x20", &items).unwrap(), None);
+        assert_eq!(verify_trie_proof(&root, b"\x12\x36", &items).unwrap(), None);
+        assert_eq!(verify_trie_proof(&root, b"\x13", &items).unwrap(), None);
+        assert_eq!(verify_trie_proof(&empty_trie_root(), b"\x12", &[]).unwrap(), None);
+
+        // Hashed values and hashed nodes must be in the proof
+        let without_values: Vec<_> = items.iter().filter(|item| item.as_slice() != long).cloned().collect();
+        assert!(verify_trie_proof(&root, b"\xff", &without_values).is_err());
+        assert!(verify_trie_proof(&root, b"\x12\x34", &items[..items.len() - 1]).is_err());
+        assert!(verify_trie_proof(&[1; 32], b"\x12\x34", &items).is_err());
+
+        for item in items.iter().filter(|item| item.as_slice() != long) {
+            assert_eq!(&TrieNode::decode(item).unwrap().encode(), item);
+        }
+        assert!(TrieNode::decode(&items[items.len() - 1][..5]).is_err());
+    }
+
+    #[test]
+    fn test_long_partial_keys() {
+        for count in [62, 63, 64, 318, 319, 320] {
+            let partial = (0..count).map(|i| (i % 16) as u8).collect::<Vec<_>>();
+            let leaf = TrieNode::Leaf { partial: partial.clone(), value: NodeValue::Hashed([9; 32]) };
+            assert_eq!(TrieNode::decode(&leaf.encode()).unwrap(), leaf);
+            let branch = TrieNode::Branch { partial, children: Default::default(), value: None };
+            assert_eq!(TrieNode::decode(&branch.encode()).unwrap(), branch);
+        }
+        // An odd partial key must leave the high half of its first byte empty
+        assert!(TrieNode::decode(&[0x41, 0x1f, 0]).is_err());
+    }
+}
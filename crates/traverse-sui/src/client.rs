@@ -0,0 +1,207 @@
+//! Object proofs over JSON-RPC
+//!
+//! The object is read with its BCS contents, owner and previous
+//! transaction, re-encoded, and checked against the digest the node
+//! reports. The transaction's raw effects and checkpoint come from
+//! `sui_getTransactionBlock`, and the checkpoint's digest from
+//! `sui_getCheckpoint`.
+//!
+//! JSON-RPC does not return checkpoint contents or summaries as BCS, so
+//! that endpoint is configurable: [`SuiChainConfig::checkpoint_path`] is
+//! requested as `GET {rpc_url}/{checkpoint_path}/{sequence number}` and must
+//! answer with `{ "summary": ..., "contents": ... }`, both base64 BCS.
+
+use crate::object::{Owner, SuiObject, SuiObjectProof};
+use crate::types::{display_address, StructTag};
+use crate::SuiError;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use traverse_core::{ProofFetcher, SemanticStorageProof, StorageSemantics, TraverseError, ZeroSemantics};
+
+/// Chain-specific configuration for proof fetching
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuiChainConfig {
+    /// Path of the checkpoint BCS endpoint, relative to the RPC URL
+    pub checkpoint_path: String,
+}
+
+impl Default for SuiChainConfig {
+    fn default() -> Self {
+        Self { checkpoint_path: "checkpoint_bcs".to_string() }
+    }
+}
+
+/// Object proof fetcher for a Sui fullnode
+#[derive(Debug, Clone)]
+pub struct SuiProofFetcher {
+    pub rpc_url: String,
+    pub config: SuiChainConfig,
+}
+
+impl SuiProofFetcher {
+    pub fn new(rpc_url: String, config: SuiChainConfig) -> Self {
+        Self { rpc_url: rpc_url.trim_end_matches('/').to_string(), config }
+    }
+
+    pub fn with_defaults(rpc_url: String) -> Self {
+        Self::new(rpc_url, SuiChainConfig::default())
+    }
+
+    /// Fetch an object, at `version` or its latest, with its proof
+    pub async fn fetch_proof(&self, object_id: &[u8; 32], version: Option<u64>) -> Result<SuiObjectProof, SuiError> {
+        let object = self.fetch_object(object_id, version).await?;
+        let transaction = self
+            .call(
+                "sui_getTransactionBlock",
+                json!([bs58::encode(object.previous_transaction).into_string(), { "showRawEffects": true }]),
+            )
+            .await?;
+        let effects = transaction["rawEffects"]
+            .as_array()
+            .ok_or_else(|| SuiError::Rpc("The transaction has no raw effects".into()))?
+            .iter()
+            .map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(|| SuiError::Rpc("Raw effects are not bytes".into()))?;
+        let checkpoint = number(&transaction["checkpoint"])
+            .ok_or_else(|| SuiError::Rpc("The object's transaction is not in a checkpoint yet".into()))?;
+
+        let summary = self.call("sui_getCheckpoint", json!([checkpoint.to_string()])).await?;
+        let checkpoint_digest = parse_base58_digest(&summary["digest"])?;
+        let bcs = self.get_json(&format!("{}/{}/{}", self.rpc_url, self.config.checkpoint_path.trim_matches('/'), checkpoint)).await?;
+
+        Ok(SuiObjectProof {
+            object,
+            effects,
+            checkpoint_contents: parse_base64(&bcs["contents"])?,
+            checkpoint_summary: parse_base64(&bcs["summary"])?,
+            checkpoint,
+            checkpoint_digest,
+        })
+    }
+
+    /// Read an object, checking that its re-encoding has the digest the node reports
+    pub async fn fetch_object(&self, object_id: &[u8; 32], version: Option<u64>) -> Result<SuiObject, SuiError> {
+        let options = json!({ "showType": true, "showOwner": true, "showPreviousTransaction": true, "showBcs": true, "showStorageRebate": true });
+        let id = display_address(object_id);
+        let data = match version {
+            Some(version) => {
+                let past = self.call("sui_tryGetPastObject", json!([id, version, options])).await?;
+                if past["status"] != "VersionFound" {
+                    return Err(SuiError::Rpc(format!("Version {} of {} is {}", version, id, past["status"])));
+                }
+                past["details"].clone()
+            }
+            None => {
+                let result = self.call("sui_getObject", json!([id, options])).await?;
+                if let Some(error) = result.get("error") {
+                    return Err(SuiError::Rpc(format!("Object {}: {}", id, error)));
+                }
+                result["data"].clone()
+            }
+        };
+
+        let bcs = &data["bcs"];
+        if bcs["dataType"] != "moveObject" {
+            return Err(SuiError::Rpc(format!("{} is not a Move object", id)));
+        }
+        let object = SuiObject {
+            object_type: StructTag::parse(bcs["type"].as_str().unwrap_or_default())?,
+            has_public_transfer: bcs["hasPublicTransfer"].as_bool().unwrap_or_default(),
+            version: number(&data["version"]).ok_or_else(|| SuiError::Rpc("Object has no version".into()))?,
+            contents: parse_base64(&bcs["bcsBytes"])?,
+            owner: Owner::from_json(&data["owner"])?,
+            previous_transaction: parse_base58_digest(&data["previousTransaction"])?,
+            storage_rebate: number(&data["storageRebate"]).unwrap_or_default(),
+        };
+        if object.digest() != parse_base58_digest(&data["digest"])? {
+            return Err(SuiError::Rpc(format!(
+                "Re-encoding {} does not reproduce its digest; the node may use a newer object format",
+                id
+            )));
+        }
+        Ok(object)
+    }
+
+    /// Chain identifier: the first bytes of the genesis checkpoint digest
+    pub async fn chain_id(&self) -> Result<String, SuiError> {
+        let id = self.call("sui_getChainIdentifier", json!([])).await?;
+        id.as_str().map(str::to_string).ok_or_else(|| SuiError::Rpc("Chain identifier is not a string".into()))
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value, SuiError> {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response: Value = reqwest::Client::new().post(&self.rpc_url).json(&request).send().await?.json().await?;
+        if let Some(error) = response.get("error") {
+            return Err(SuiError::Rpc(format!("{}: {}", method, error)));
+        }
+        Ok(response["result"].clone())
+    }
+
+    async fn get_json(&self, url: &str) -> Result<Value, SuiError> {
+        let response = reqwest::Client::new().get(url).send().await?;
+        if !response.status().is_success() {
+            return Err(SuiError::Rpc(format!("{} returned {}: {}", url, response.status(), response.text().await?)));
+        }
+        Ok(response.json().await?)
+    }
+}
+
+impl ProofFetcher for SuiProofFetcher {
+    /// Fetch the proof of the latest version of the object with ID `key`
+    ///
+    /// The value is the object's digest, and the proof the digests linking
+    /// it to the checkpoint: transaction, effects, contents, then the
+    /// checkpoint's own.
+    fn fetch(&self, key: &[u8; 32], zero_semantics: ZeroSemantics) -> Result<SemanticStorageProof, TraverseError> {
+        let fetcher = self.clone();
+        let key = *key;
+        let run = move || -> Result<SuiObjectProof, SuiError> {
+            let runtime = tokio::runtime::Runtime::new()
+                .map_err(|e| SuiError::Rpc(format!("Failed to create runtime: {}", e)))?;
+            runtime.block_on(fetcher.fetch_proof(&key, None))
+        };
+        // Inside a runtime, block on a thread with its own
+        let proof = match tokio::runtime::Handle::try_current() {
+            Ok(_) => std::thread::spawn(run)
+                .join()
+                .map_err(|_| TraverseError::ProofGeneration("Thread panicked".to_string()))?,
+            Err(_) => run(),
+        }?;
+        proof.verify()?;
+
+        Ok(SemanticStorageProof {
+            key,
+            value: proof.object.digest(),
+            proof: vec![
+                proof.object.previous_transaction,
+                crate::digest("TransactionEffects", &proof.effects),
+                crate::digest("CheckpointContents", &proof.checkpoint_contents),
+                proof.checkpoint_digest,
+            ],
+            semantics: StorageSemantics::new(zero_semantics),
+        })
+    }
+}
+
+/// A number JSON-RPC may write as a string
+fn number(value: &Value) -> Option<u64> {
+    value.as_u64().or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+}
+
+fn parse_base58_digest(value: &Value) -> Result<[u8; 32], SuiError> {
+    let encoded = value.as_str().ok_or_else(|| SuiError::Rpc(format!("Expected a base58 digest, found {}", value)))?;
+    bs58::decode(encoded)
+        .into_vec()
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| SuiError::Rpc(format!("'{}' is not a 32-byte base58 digest", encoded)))
+}
+
+fn parse_base64(value: &Value) -> Result<Vec<u8>, SuiError> {
+    let encoded = value.as_str().ok_or_else(|| SuiError::Rpc(format!("Expected base64, found {}", value)))?;
+    base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| SuiError::Rpc(format!("Invalid base64: {}", e)))
+}
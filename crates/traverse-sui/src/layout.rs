@@ -0,0 +1,142 @@
+//! Layout compilation from normalized Move packages
+//!
+//! An object's contents are the BCS encoding of its struct, so its fields
+//! sit at byte offsets the way packed variables sit in a slot. Each object
+//! type becomes an entry labelled `module::Type` at offset 0, and each field
+//! an entry `module::Type.field` whose "slot" is its byte offset in the
+//! contents. Offsets are only static up to the first variable-size field,
+//! so fields after it are left out.
+
+use crate::package::MovePackage;
+use crate::types::{display_type_address, parse_address};
+use crate::SuiError;
+use traverse_core::{LayoutInfo, StorageEntry, TypeInfo, ZeroSemantics};
+
+#[cfg(feature = "std")]
+use std::path::Path;
+
+#[cfg(feature = "std")]
+use traverse_core::{LayoutCompiler, TraverseError};
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::ToString, vec::Vec};
+
+/// Encoding recorded for object contents and their fields
+pub const OBJECT_ENCODING: &str = "bcs";
+
+/// Layout compiler for Move packages
+///
+/// # Usage
+///
+/// ```rust,ignore
+/// use traverse_sui::SuiLayoutCompiler;
+/// use traverse_core::LayoutCompiler;
+///
+/// let layout = SuiLayoutCompiler.compile_layout(Path::new("package.json"))?;
+/// assert!(layout.storage.iter().any(|e| e.label == "pool::Pool.reserve"));
+/// ```
+pub struct SuiLayoutCompiler;
+
+impl SuiLayoutCompiler {
+    /// Compile the object types of a parsed package
+    pub fn compile_package(package: &MovePackage) -> Result<LayoutInfo, SuiError> {
+        let package_id = package.id()?;
+        let type_address = display_type_address(&parse_address(&package_id)?);
+        let mut storage = Vec::new();
+        let mut types: Vec<TypeInfo> = Vec::new();
+        let mut add_type = |label: &str, size: Option<usize>| {
+            if !types.iter().any(|t| t.label == label) {
+                types.push(TypeInfo {
+                    label: label.to_string(),
+                    // 0 for variable-size types
+                    number_of_bytes: size.unwrap_or(0).to_string(),
+                    encoding: OBJECT_ENCODING.to_string(),
+                    base: None,
+                    key: None,
+                    value: None,
+                });
+            }
+        };
+
+        for (module, name, object) in package.objects() {
+            let label = format!("{}::{}", module, name);
+            let params: Vec<_> = (0..object.type_parameters.len()).map(|i| format!("T{}", i)).collect();
+            let type_name = if params.is_empty() {
+                format!("{}::{}", type_address, label)
+            } else {
+                format!("{}::{}<{}>", type_address, label, params.join(", "))
+            };
+            let sizes: Vec<_> = object.fields.iter().map(|f| package.fixed_size(&f.field_type)).collect();
+            storage.push(StorageEntry {
+                label: label.clone(),
+                slot: "0".to_string(),
+                offset: 0,
+                type_name: type_name.clone(),
+                // A deleted or never created object is absent, not zero
+                zero_semantics: ZeroSemantics::NeverWritten,
+            });
+            add_type(&type_name, sizes.iter().copied().sum());
+
+            let mut offset = 0usize;
+            for (field, size) in object.fields.iter().zip(&sizes) {
+                let field_type = field.field_type.to_type_string();
+                storage.push(StorageEntry {
+                    label: format!("{}.{}", label, field.name),
+                    slot: offset.to_string(),
+                    offset: 0,
+                    type_name: field_type.clone(),
+                    // Fields of an existing object hold real values, zero included
+                    zero_semantics: ZeroSemantics::ValidZero,
+                });
+                add_type(&field_type, *size);
+                match size {
+                    Some(size) => offset += size,
+                    None => break,
+                }
+            }
+        }
+
+        Ok(LayoutInfo { contract_name: package_id, storage, types, code_variables: Vec::new() })
+    }
+}
+
+#[cfg(feature = "std")]
+impl LayoutCompiler for SuiLayoutCompiler {
+    /// Compile a layout from a package's normalized modules
+    ///
+    /// Accepts the result of `sui_getNormalizedMoveModulesByPackage`, the
+    /// full JSON-RPC response, or a single normalized module.
+    fn compile_layout(&self, package_path: &Path) -> Result<LayoutInfo, TraverseError> {
+        let content = std::fs::read_to_string(package_path)?;
+        let package = MovePackage::from_json(&content)?;
+        Ok(Self::compile_package(&package)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::tests::pool_package;
+
+    #[test]
+    fn test_compile_package_objects() {
+        let layout = SuiLayoutCompiler::compile_package(&pool_package()).unwrap();
+        assert_eq!(layout.contract_name, format!("0x{:0>64}", "5a1e"));
+
+        let entries: Vec<_> = layout.storage.iter().map(|e| (e.label.as_str(), e.slot.as_str())).collect();
+        assert_eq!(entries, [
+            ("pool::Pool", "0"),
+            ("pool::Pool.id", "0"),
+            ("pool::Pool.reserve", "32"),
+            ("pool::Pool.fees", "40"),
+            ("pool::Pool.name", "43"),
+        ]);
+        assert!(layout.storage[0].type_name.ends_with("::pool::Pool<T0>"));
+        assert_eq!(layout.storage[2].type_name, "0x2::balance::Balance<T0>");
+
+        let size = |label: &str| layout.types.iter().find(|t| t.label == label).unwrap().number_of_bytes.as_str();
+        assert_eq!(size(&layout.storage[0].type_name), "0");
+        assert_eq!(size("0x2::balance::Balance<T0>"), "8");
+        assert_eq!(size("vector<u8>"), "0");
+    }
+}
@@ -0,0 +1,99 @@
+//! Move object analysis and checkpoint-anchored proofs for Sui
+//!
+//! Sui has no global state tree. Each object version is committed to by
+//! its digest, which the effects of the transaction that wrote it record,
+//! and each checkpoint commits to the effects of the transactions it
+//! executed. An object proof follows that chain from the object's BCS bytes
+//! up to a checkpoint digest, which a light client can check against the
+//! committee's certificate. This crate maps that model onto the traverse
+//! core types.
+//!
+//! # Features
+//!
+//! - **Layout Compilation**: Turn a package's normalized Move modules into a layout of its object types
+//! - **Query Resolution**: Resolve `module::Type[object id].field` queries to object fields
+//! - **Proof Verification**: Check an object's digest chain up to a checkpoint
+//! - **Proof Fetching**: Read objects and their proofs over JSON-RPC (`client`)
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! use traverse_sui::{SuiKeyResolver, SuiLayoutCompiler};
+//! use traverse_core::{KeyResolver, LayoutCompiler};
+//!
+//! let layout = SuiLayoutCompiler.compile_layout(Path::new("package.json"))?;
+//! let path = SuiKeyResolver.resolve(&layout, "pool::Pool[0x5a1e].reserve")?;
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(not(feature = "std"))]
+use alloc::string::{String, ToString};
+
+pub mod layout;
+pub mod object;
+pub mod package;
+pub mod resolver;
+pub mod types;
+
+#[cfg(feature = "client")]
+pub mod client;
+
+pub use layout::SuiLayoutCompiler;
+pub use object::{digest, Owner, SuiObject, SuiObjectProof};
+pub use package::{MovePackage, NormalizedModule, NormalizedStruct, NormalizedType};
+pub use resolver::{ObjectField, SuiKeyResolver};
+pub use types::{display_address, parse_address, StructTag, TypeTag};
+
+#[cfg(feature = "client")]
+pub use client::{SuiChainConfig, SuiProofFetcher};
+
+/// Error types specific to Sui
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+#[derive(Debug)]
+pub enum SuiError {
+    #[cfg_attr(feature = "std", error("Invalid Move package: {0}"))]
+    InvalidPackage(String),
+
+    #[cfg_attr(feature = "std", error("Invalid type tag: {0}"))]
+    InvalidTypeTag(String),
+
+    #[cfg_attr(feature = "std", error("Invalid address or object ID: {0}"))]
+    InvalidAddress(String),
+
+    #[cfg_attr(feature = "std", error("Proof verification failed: {0}"))]
+    ProofVerification(String),
+
+    #[cfg_attr(feature = "std", error("RPC error: {0}"))]
+    Rpc(String),
+
+    #[cfg(feature = "client")]
+    #[cfg_attr(feature = "std", error("Network error: {0}"))]
+    Network(#[from] reqwest::Error),
+
+    #[cfg_attr(feature = "std", error("JSON error: {0}"))]
+    Json(#[cfg_attr(feature = "std", from)] serde_json::Error),
+
+    #[cfg_attr(feature = "std", error("Traverse core error: {0}"))]
+    TraverseCore(#[cfg_attr(feature = "std", from)] traverse_core::TraverseError),
+}
+
+impl From<SuiError> for traverse_core::TraverseError {
+    fn from(err: SuiError) -> Self {
+        use traverse_core::TraverseError;
+
+        match err {
+            SuiError::InvalidPackage(msg) => TraverseError::LayoutCompilation(msg),
+            SuiError::InvalidTypeTag(msg) | SuiError::InvalidAddress(msg) => TraverseError::InvalidInput(msg),
+            SuiError::ProofVerification(msg) => TraverseError::Validation(msg),
+            SuiError::Rpc(msg) => TraverseError::ExternalService(msg),
+            #[cfg(feature = "client")]
+            SuiError::Network(err) => TraverseError::ExternalService(err.to_string()),
+            SuiError::Json(err) => TraverseError::Serialization(err.to_string()),
+            SuiError::TraverseCore(err) => err,
+        }
+    }
+}
@@ -0,0 +1,395 @@
+//! Objects, digests and checkpoint-anchored object proofs
+//!
+//! Every Sui digest is BLAKE2b-256 over the value's type name, `::`, and
+//! its BCS bytes. An object version is identified by its digest; the
+//! effects of the transaction that wrote it list that digest; the
+//! checkpoint's contents list the digest of those effects next to the
+//! transaction digest; and the checkpoint summary, whose digest validators
+//! certify, commits to the contents' digest.
+//!
+//! The effects, contents and summary are checked by finding the digest they
+//! must commit to among their BCS bytes rather than by decoding them, since
+//! their formats change between protocol versions. Digests are written as
+//! BCS byte strings there, `0x20` followed by the 32 bytes, and a chance
+//! match is as unlikely as a hash collision. Note that a version appears in
+//! effects both when it is written and when a later transaction consumes
+//! it, so a proof shows the version existed as of the checkpoint, not that
+//! it is still the latest.
+
+use crate::types::{display_address, parse_address, write_bytes, StructTag, TypeTag};
+use crate::SuiError;
+use blake2::{digest::consts::U32, Blake2b, Digest};
+use serde_json::{json, Value};
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+type Blake2b256 = Blake2b<U32>;
+
+/// Digest of a value of the Sui type `type_name` with BCS encoding `bcs`
+pub fn digest(type_name: &str, bcs: &[u8]) -> [u8; 32] {
+    Blake2b256::new()
+        .chain_update(type_name.as_bytes())
+        .chain_update(b"::")
+        .chain_update(bcs)
+        .finalize()
+        .into()
+}
+
+/// Owner of an object
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Owner {
+    Address([u8; 32]),
+    Object([u8; 32]),
+    Shared { initial_shared_version: u64 },
+    Immutable,
+    ConsensusAddress { start_version: u64, owner: [u8; 32] },
+}
+
+impl Owner {
+    /// Parse the owner as JSON-RPC shows it, e.g. `{ "AddressOwner": "0x..." }` or `"Immutable"`
+    pub fn from_json(value: &Value) -> Result<Self, SuiError> {
+        let invalid = || SuiError::Rpc(format!("Unknown owner {}", value));
+        let address = |v: &Value| parse_address(v.as_str().unwrap_or_default());
+        let number = |v: &Value| {
+            v.as_u64()
+                .or_else(|| v.as_str().and_then(|s| s.parse().ok()))
+                .ok_or_else(invalid)
+        };
+        if value.as_str() == Some("Immutable") {
+            return Ok(Self::Immutable);
+        }
+        let (kind, inner) = value.as_object().and_then(|o| o.iter().next()).ok_or_else(invalid)?;
+        Ok(match kind.as_str() {
+            "AddressOwner" => Self::Address(address(inner)?),
+            "ObjectOwner" => Self::Object(address(inner)?),
+            "Shared" => Self::Shared { initial_shared_version: number(&inner["initial_shared_version"])? },
+            "ConsensusAddressOwner" => Self::ConsensusAddress {
+                start_version: number(&inner["start_version"])?,
+                owner: address(&inner["owner"])?,
+            },
+            _ => return Err(invalid()),
+        })
+    }
+
+    pub fn to_json(&self) -> Value {
+        match self {
+            Self::Address(address) => json!({ "AddressOwner": display_address(address) }),
+            Self::Object(address) => json!({ "ObjectOwner": display_address(address) }),
+            Self::Shared { initial_shared_version } => {
+                json!({ "Shared": { "initial_shared_version": initial_shared_version } })
+            }
+            Self::Immutable => json!("Immutable"),
+            Self::ConsensusAddress { start_version, owner } => json!({
+                "ConsensusAddressOwner": { "start_version": start_version, "owner": display_address(owner) }
+            }),
+        }
+    }
+
+    fn bcs(&self, out: &mut Vec<u8>) {
+        match self {
+            Self::Address(address) => {
+                out.push(0);
+                out.extend_from_slice(address);
+            }
+            Self::Object(address) => {
+                out.push(1);
+                out.extend_from_slice(address);
+            }
+            Self::Shared { initial_shared_version } => {
+                out.push(2);
+                out.extend_from_slice(&initial_shared_version.to_le_bytes());
+            }
+            Self::Immutable => out.push(3),
+            Self::ConsensusAddress { start_version, owner } => {
+                out.push(4);
+                out.extend_from_slice(&start_version.to_le_bytes());
+                out.extend_from_slice(owner);
+            }
+        }
+    }
+}
+
+/// A Move object at one version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuiObject {
+    pub object_type: StructTag,
+    pub has_public_transfer: bool,
+    pub version: u64,
+    /// BCS encoding of the object's struct, starting with its ID
+    pub contents: Vec<u8>,
+    pub owner: Owner,
+    /// Transaction that wrote this version
+    pub previous_transaction: [u8; 32],
+    pub storage_rebate: u64,
+}
+
+impl SuiObject {
+    /// Object ID, the first field of every object
+    pub fn id(&self) -> Result<[u8; 32], SuiError> {
+        self.contents
+            .get(..32)
+            .and_then(|id| id.try_into().ok())
+            .ok_or_else(|| SuiError::ProofVerification("object contents are shorter than an ID".into()))
+    }
+
+    /// BCS encoding of the object
+    pub fn bcs(&self) -> Vec<u8> {
+        let mut out = vec![0]; // Data::Move
+        self.object_type_bcs(&mut out);
+        out.push(self.has_public_transfer as u8);
+        out.extend_from_slice(&self.version.to_le_bytes());
+        write_bytes(&mut out, &self.contents);
+        self.owner.bcs(&mut out);
+        write_bytes(&mut out, &self.previous_transaction);
+        out.extend_from_slice(&self.storage_rebate.to_le_bytes());
+        out
+    }
+
+    /// Digest identifying this version of the object
+    pub fn digest(&self) -> [u8; 32] {
+        digest("Object", &self.bcs())
+    }
+
+    /// Object types have compact encodings for SUI coins, stakes and coins
+    fn object_type_bcs(&self, out: &mut Vec<u8>) {
+        let tag = &self.object_type;
+        let sui = |t: &TypeTag| matches!(t, TypeTag::Struct(s) if s.is(2, "sui", "SUI") && s.type_args.is_empty());
+        match tag.type_args.as_slice() {
+            [coin] if tag.is(2, "coin", "Coin") && sui(coin) => out.push(1),
+            [coin] if tag.is(2, "coin", "Coin") => {
+                out.push(3);
+                coin.bcs(out);
+            }
+            [] if tag.is(3, "staking_pool", "StakedSui") => out.push(2),
+            _ => {
+                out.push(0);
+                tag.bcs(out);
+            }
+        }
+    }
+}
+
+/// An object version with the digest chain from it to a checkpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuiObjectProof {
+    pub object: SuiObject,
+    /// BCS of the effects of the transaction that wrote the object
+    pub effects: Vec<u8>,
+    /// BCS of the contents of the checkpoint that executed the transaction
+    pub checkpoint_contents: Vec<u8>,
+    /// BCS of the checkpoint's summary
+    pub checkpoint_summary: Vec<u8>,
+    /// Checkpoint sequence number
+    pub checkpoint: u64,
+    /// Digest of the summary, as certified by validators
+    pub checkpoint_digest: [u8; 32],
+}
+
+impl SuiObjectProof {
+    /// Check the chain from the object to the checkpoint digest
+    pub fn verify(&self) -> Result<(), SuiError> {
+        let object_id = self.object.id()?;
+        let object_digest = self.object.digest();
+        if !contains(&self.effects, &object_id) || !contains(&self.effects, &digest_bcs(&object_digest)) {
+            return Err(SuiError::ProofVerification(format!(
+                "the effects do not record version {} of {}",
+                self.object.version,
+                display_address(&object_id)
+            )));
+        }
+        if !contains(&self.effects, &digest_bcs(&self.object.previous_transaction)) {
+            return Err(SuiError::ProofVerification("the effects are not those of the object's transaction".into()));
+        }
+
+        let effects_digest = digest("TransactionEffects", &self.effects);
+        let execution = [digest_bcs(&self.object.previous_transaction), digest_bcs(&effects_digest)].concat();
+        if !contains(&self.checkpoint_contents, &execution) {
+            return Err(SuiError::ProofVerification("the checkpoint did not execute the object's transaction".into()));
+        }
+
+        // The summary starts with the epoch, then the sequence number
+        let contents_digest = digest("CheckpointContents", &self.checkpoint_contents);
+        let sequence = self.checkpoint_summary.get(8..16).map(|s| u64::from_le_bytes(s.try_into().unwrap_or_default()));
+        if sequence != Some(self.checkpoint) {
+            return Err(SuiError::ProofVerification(format!("the summary is not that of checkpoint {}", self.checkpoint)));
+        }
+        if !contains(&self.checkpoint_summary, &digest_bcs(&contents_digest)) {
+            return Err(SuiError::ProofVerification("the summary does not commit to the checkpoint contents".into()));
+        }
+
+        let summary_digest = digest("CheckpointSummary", &self.checkpoint_summary);
+        if summary_digest != self.checkpoint_digest {
+            return Err(SuiError::ProofVerification(format!(
+                "the summary's digest is {}, not {}",
+                hex::encode(summary_digest),
+                hex::encode(self.checkpoint_digest)
+            )));
+        }
+        Ok(())
+    }
+
+    /// JSON form with bytes and digests in `0x` hex
+    ///
+    /// `object_bcs` is the object's full BCS, derived from the other object
+    /// fields; traverse-valence hashes it, with the effects and checkpoint
+    /// data, back to `checkpoint_digest`.
+    pub fn to_json(&self) -> Value {
+        let object = &self.object;
+        json!({
+            "object_id": self.object.id().map(|id| display_address(&id)).unwrap_or_default(),
+            "object_type": object.object_type.to_string(),
+            "version": object.version,
+            "digest": format!("0x{}", hex::encode(object.digest())),
+            "object_bcs": format!("0x{}", hex::encode(object.bcs())),
+            "contents": format!("0x{}", hex::encode(&object.contents)),
+            "has_public_transfer": object.has_public_transfer,
+            "owner": object.owner.to_json(),
+            "previous_transaction": format!("0x{}", hex::encode(object.previous_transaction)),
+            "storage_rebate": object.storage_rebate,
+            "effects": format!("0x{}", hex::encode(&self.effects)),
+            "checkpoint_contents": format!("0x{}", hex::encode(&self.checkpoint_contents)),
+            "checkpoint_summary": format!("0x{}", hex::encode(&self.checkpoint_summary)),
+            "checkpoint": self.checkpoint,
+            "checkpoint_digest": format!("0x{}", hex::encode(self.checkpoint_digest)),
+        })
+    }
+
+    /// Parse the JSON written by [`Self::to_json`]
+    pub fn from_json(value: &Value) -> Result<Self, SuiError> {
+        let field = |name: &str| {
+            value
+                .get(name)
+                .ok_or_else(|| SuiError::Rpc(format!("Proof is missing '{}'", name)))
+        };
+        let number = |name: &str| {
+            field(name)?
+                .as_u64()
+                .ok_or_else(|| SuiError::Rpc(format!("Proof field '{}' is not a number", name)))
+        };
+        Ok(Self {
+            object: SuiObject {
+                object_type: StructTag::parse(field("object_type")?.as_str().unwrap_or_default())?,
+                has_public_transfer: field("has_public_transfer")?.as_bool().unwrap_or_default(),
+                version: number("version")?,
+                contents: parse_bytes(field("contents")?)?,
+                owner: Owner::from_json(field("owner")?)?,
+                previous_transaction: parse_digest(field("previous_transaction")?)?,
+                storage_rebate: number("storage_rebate")?,
+            },
+            effects: parse_bytes(field("effects")?)?,
+            checkpoint_contents: parse_bytes(field("checkpoint_contents")?)?,
+            checkpoint_summary: parse_bytes(field("checkpoint_summary")?)?,
+            checkpoint: number("checkpoint")?,
+            checkpoint_digest: parse_digest(field("checkpoint_digest")?)?,
+        })
+    }
+}
+
+/// A digest as BCS writes it: a 32-byte string
+fn digest_bcs(digest: &[u8; 32]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(33);
+    write_bytes(&mut out, digest);
+    out
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|window| window == needle)
+}
+
+pub(crate) fn parse_bytes(value: &Value) -> Result<Vec<u8>, SuiError> {
+    let hex_str = value.as_str().ok_or_else(|| SuiError::Rpc(format!("Expected hex, found {}", value)))?;
+    hex::decode(hex_str.trim_start_matches("0x")).map_err(|e| SuiError::Rpc(format!("Invalid hex '{}': {}", hex_str, e)))
+}
+
+fn parse_digest(value: &Value) -> Result<[u8; 32], SuiError> {
+    parse_bytes(value)?
+        .try_into()
+        .map_err(|_| SuiError::Rpc(format!("Expected a 32-byte digest, found {}", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A proof whose effects, contents and summary hold just the digests they commit to
+    fn pool_proof() -> SuiObjectProof {
+        let mut contents = parse_address("0xbeef").unwrap().to_vec();
+        contents.extend(1_000u64.to_le_bytes());
+        let object = SuiObject {
+            object_type: StructTag::parse(&format!("0x{:0>64}::pool::Pool<0x2::sui::SUI>", "5a1e")).unwrap(),
+            has_public_transfer: false,
+            version: 12,
+            contents,
+            owner: Owner::Shared { initial_shared_version: 3 },
+            previous_transaction: [7; 32],
+            storage_rebate: 100,
+        };
+
+        let effects = [&[1u8][..], &object.id().unwrap(), &digest_bcs(&object.digest()), &digest_bcs(&[7; 32])].concat();
+        let execution = [digest_bcs(&[7; 32]), digest_bcs(&digest("TransactionEffects", &effects))].concat();
+        let checkpoint_contents = [&[0u8, 1][..], &execution, &[1, 0]].concat();
+        let checkpoint_summary = [
+            &5u64.to_le_bytes()[..],
+            &42u64.to_le_bytes(),
+            &9u64.to_le_bytes(),
+            &digest_bcs(&digest("CheckpointContents", &checkpoint_contents)),
+        ]
+        .concat();
+        SuiObjectProof {
+            object,
+            effects,
+            checkpoint_contents,
+            checkpoint_digest: digest("CheckpointSummary", &checkpoint_summary),
+            checkpoint_summary,
+            checkpoint: 42,
+        }
+    }
+
+    #[test]
+    fn test_object_bcs_and_digest() {
+        let coin = SuiObject {
+            object_type: StructTag::parse("0x2::coin::Coin<0x2::sui::SUI>").unwrap(),
+            has_public_transfer: true,
+            version: 1,
+            contents: vec![0xaa; 40],
+            owner: Owner::Immutable,
+            previous_transaction: [0; 32],
+            storage_rebate: 0,
+        };
+        let bcs = coin.bcs();
+        // Data::Move, then the compact GasCoin type and the public transfer flag
+        assert_eq!(&bcs[..3], &[0, 1, 1]);
+        assert_eq!(bcs.len(), 3 + 8 + 1 + 40 + 1 + 1 + 32 + 8);
+        assert_eq!(coin.digest(), digest("Object", &bcs));
+
+        let other = SuiObject { object_type: StructTag::parse("0x2::coin::Coin<0xdead::usd::USD>").unwrap(), ..coin };
+        assert_eq!(&other.bcs()[..3], &[0, 3, 7]);
+    }
+
+    #[test]
+    fn test_proof_verification_and_json() {
+        let proof = pool_proof();
+        proof.verify().unwrap();
+        let json = proof.to_json();
+        assert_eq!(SuiObjectProof::from_json(&json).unwrap(), proof);
+        assert_eq!(json["object_bcs"], format!("0x{}", hex::encode(proof.object.bcs())));
+
+        let mut other_version = proof.clone();
+        other_version.object.version += 1;
+        assert!(other_version.verify().is_err());
+
+        let mut other_checkpoint = proof.clone();
+        other_checkpoint.checkpoint = 43;
+        assert!(other_checkpoint.verify().is_err());
+
+        let mut other_digest = proof;
+        other_digest.checkpoint_digest[0] ^= 1;
+        assert!(other_digest.verify().is_err());
+    }
+}
@@ -0,0 +1,257 @@
+//! Normalized Move packages
+//!
+//! `sui_getNormalizedMoveModulesByPackage` describes a published package as
+//! a map of module names to normalized modules, each listing its structs
+//! with their abilities and field types. Structs with the `key` ability are
+//! object types; their first field is always the object's `UID`.
+
+use crate::types::{display_address, display_type_address, parse_address};
+use crate::SuiError;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// A published package: its modules by name
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MovePackage {
+    pub modules: BTreeMap<String, NormalizedModule>,
+}
+
+/// A normalized Move module
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NormalizedModule {
+    /// Package ID the module is published at
+    pub address: String,
+    pub name: String,
+    #[serde(default)]
+    pub structs: BTreeMap<String, NormalizedStruct>,
+}
+
+/// A struct declared by a module
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalizedStruct {
+    pub abilities: AbilitySet,
+    /// Type parameters; only their number matters here
+    #[serde(default)]
+    pub type_parameters: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub fields: Vec<NormalizedField>,
+}
+
+/// Abilities of a struct, e.g. `Key` and `Store`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AbilitySet {
+    pub abilities: Vec<String>,
+}
+
+/// A field of a struct
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NormalizedField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: NormalizedType,
+}
+
+/// A field type as JSON-RPC normalizes it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum NormalizedType {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    Address,
+    Signer,
+    Struct {
+        address: String,
+        module: String,
+        name: String,
+        #[serde(rename = "typeArguments", default)]
+        type_arguments: Vec<NormalizedType>,
+    },
+    Vector(Box<NormalizedType>),
+    TypeParameter(u16),
+    Reference(Box<NormalizedType>),
+    MutableReference(Box<NormalizedType>),
+}
+
+impl MovePackage {
+    /// Parse the modules of a package, or a single normalized module
+    pub fn from_json(content: &str) -> Result<Self, SuiError> {
+        let value: serde_json::Value = serde_json::from_str(content).map_err(SuiError::Json)?;
+        // Accept the full JSON-RPC response as well as its result
+        let value = value.get("result").cloned().unwrap_or(value);
+        let modules = if value.get("structs").is_some() {
+            let module: NormalizedModule = serde_json::from_value(value)
+                .map_err(|e| SuiError::InvalidPackage(format!("Expected a normalized module: {}", e)))?;
+            BTreeMap::from([(module.name.clone(), module)])
+        } else {
+            serde_json::from_value(value)
+                .map_err(|e| SuiError::InvalidPackage(format!("Expected normalized modules by name: {}", e)))?
+        };
+
+        let package = Self { modules };
+        let mut addresses = package.modules.values().map(|m| parse_address(&m.address));
+        let Some(first) = addresses.next().transpose()? else {
+            return Err(SuiError::InvalidPackage("The package has no modules".into()));
+        };
+        for address in addresses {
+            if address? != first {
+                return Err(SuiError::InvalidPackage("Modules are published at different addresses".into()));
+            }
+        }
+        Ok(package)
+    }
+
+    /// Package ID, in full
+    pub fn id(&self) -> Result<String, SuiError> {
+        let module = self.modules.values().next().ok_or_else(|| SuiError::InvalidPackage("No modules".into()))?;
+        Ok(display_address(&parse_address(&module.address)?))
+    }
+
+    /// Object types of every module, by module name
+    pub fn objects(&self) -> impl Iterator<Item = (&str, &str, &NormalizedStruct)> {
+        self.modules.iter().flat_map(|(module, m)| {
+            m.structs
+                .iter()
+                .filter(|(_, s)| s.is_object())
+                .map(move |(name, s)| (module.as_str(), name.as_str(), s))
+        })
+    }
+
+    /// BCS size of a type, if it is fixed
+    ///
+    /// Structs of this package are sized from their fields; of the framework
+    /// only `UID`, `ID` and `Balance` are known. Type parameters are not.
+    pub fn fixed_size(&self, field_type: &NormalizedType) -> Option<usize> {
+        match field_type {
+            NormalizedType::Bool | NormalizedType::U8 => Some(1),
+            NormalizedType::U16 => Some(2),
+            NormalizedType::U32 => Some(4),
+            NormalizedType::U64 => Some(8),
+            NormalizedType::U128 => Some(16),
+            NormalizedType::U256 | NormalizedType::Address => Some(32),
+            NormalizedType::Struct { address, module, name, .. } => {
+                let address = parse_address(address).ok()?;
+                let framework = address[..31].iter().all(|b| *b == 0) && address[31] == 2;
+                match (framework, module.as_str(), name.as_str()) {
+                    (true, "object", "UID" | "ID") => Some(32),
+                    (true, "balance", "Balance") => Some(8),
+                    _ => {
+                        let declared = self.modules.get(module).filter(|m| parse_address(&m.address).ok() == Some(address))?;
+                        let fields = &declared.structs.get(name)?.fields;
+                        fields.iter().map(|f| self.fixed_size(&f.field_type)).sum()
+                    }
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+impl NormalizedStruct {
+    /// Whether the struct has the `key` ability
+    pub fn is_object(&self) -> bool {
+        self.abilities.abilities.iter().any(|a| a == "Key")
+    }
+}
+
+impl NormalizedType {
+    /// Move syntax for the type, with type parameters written `T0`, `T1`, ...
+    pub fn to_type_string(&self) -> String {
+        match self {
+            Self::Bool => "bool".to_string(),
+            Self::U8 => "u8".to_string(),
+            Self::U16 => "u16".to_string(),
+            Self::U32 => "u32".to_string(),
+            Self::U64 => "u64".to_string(),
+            Self::U128 => "u128".to_string(),
+            Self::U256 => "u256".to_string(),
+            Self::Address => "address".to_string(),
+            Self::Signer => "signer".to_string(),
+            Self::Struct { address, module, name, type_arguments } => {
+                let address = parse_address(address).map(|a| display_type_address(&a)).unwrap_or_else(|_| address.clone());
+                let base = format!("{}::{}::{}", address, module, name);
+                if type_arguments.is_empty() {
+                    base
+                } else {
+                    let args: Vec<_> = type_arguments.iter().map(Self::to_type_string).collect();
+                    format!("{}<{}>", base, args.join(", "))
+                }
+            }
+            Self::Vector(element) => format!("vector<{}>", element.to_type_string()),
+            Self::TypeParameter(index) => format!("T{}", index),
+            Self::Reference(inner) => format!("&{}", inner.to_type_string()),
+            Self::MutableReference(inner) => format!("&mut {}", inner.to_type_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A pool module whose `Pool` object holds a nested `Fees` struct and a
+    /// field after its variable-size `name`, next to a struct that is no object
+    pub(crate) fn pool_package() -> MovePackage {
+        MovePackage::from_json(
+            r#"{
+                "pool": {
+                    "fileFormatVersion": 6, "address": "0x5a1e", "name": "pool", "friends": [],
+                    "structs": {
+                        "Pool": {
+                            "abilities": { "abilities": ["Key"] },
+                            "typeParameters": [{ "constraints": { "abilities": [] }, "isPhantom": true }],
+                            "fields": [
+                                { "name": "id", "type": { "Struct": { "address": "0x2", "module": "object", "name": "UID", "typeArguments": [] } } },
+                                { "name": "reserve", "type": { "Struct": { "address": "0x2", "module": "balance", "name": "Balance", "typeArguments": [{ "TypeParameter": 0 }] } } },
+                                { "name": "fees", "type": { "Struct": { "address": "0x5a1e", "module": "pool", "name": "Fees", "typeArguments": [] } } },
+                                { "name": "name", "type": { "Vector": "U8" } },
+                                { "name": "paused", "type": "Bool" }
+                            ]
+                        },
+                        "Fees": {
+                            "abilities": { "abilities": ["Store"] },
+                            "typeParameters": [],
+                            "fields": [{ "name": "bps", "type": "U16" }, { "name": "paused", "type": "Bool" }]
+                        },
+                        "Receipt": {
+                            "abilities": { "abilities": ["Drop"] },
+                            "typeParameters": [],
+                            "fields": [{ "name": "amount", "type": "U64" }]
+                        }
+                    },
+                    "exposedFunctions": {}
+                }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_package_objects_and_sizes() {
+        let package = pool_package();
+        assert_eq!(package.id().unwrap(), format!("0x{:0>64}", "5a1e"));
+        let objects: Vec<_> = package.objects().map(|(module, name, _)| (module, name)).collect();
+        assert_eq!(objects, [("pool", "Pool")]);
+
+        let fields = &package.modules["pool"].structs["Pool"].fields;
+        let sizes: Vec<_> = fields.iter().map(|f| package.fixed_size(&f.field_type)).collect();
+        assert_eq!(sizes, [Some(32), Some(8), Some(3), None, Some(1)]);
+        assert_eq!(fields[1].field_type.to_type_string(), "0x2::balance::Balance<T0>");
+        assert_eq!(fields[3].field_type.to_type_string(), "vector<u8>");
+    }
+}
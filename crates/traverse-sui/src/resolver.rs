@@ -0,0 +1,195 @@
+//! Object field resolution
+//!
+//! Objects are addressed by ID rather than by a key derived from the
+//! layout, so a query names the object type and the ID of the object:
+//! `pool::Pool[0x5a1e...]`, optionally followed by a field, `.reserve`. The
+//! module may be left out when the type name is unique in the package. The
+//! resolved key is the object ID, and a field resolves to its byte offset
+//! and size within the object's contents.
+
+use crate::types::{parse_address, split_type_args, StructTag};
+use crate::SuiError;
+use traverse_core::{Key, KeyResolver, LayoutInfo, StaticKeyPath, StorageEntry, TraverseError, ZeroSemantics};
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Key resolver for Move objects
+///
+/// # Usage
+///
+/// ```rust,ignore
+/// use traverse_sui::SuiKeyResolver;
+/// use traverse_core::KeyResolver;
+///
+/// let path = SuiKeyResolver.resolve(&layout, "pool::Pool[0x5a1e].reserve")?;
+/// ```
+pub struct SuiKeyResolver;
+
+/// An object, or one of its fields, resolved against a layout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectField {
+    pub object_id: [u8; 32],
+    /// Object type as the layout declares it, e.g. `0x5a1e::pool::Pool<T0>`
+    pub object_type: String,
+    /// Field name; `None` for the whole object
+    pub field: Option<String>,
+    /// Byte offset of the field in the object's contents
+    pub offset: usize,
+    /// Size of the field, if fixed
+    pub size: Option<usize>,
+    pub zero_semantics: ZeroSemantics,
+}
+
+impl ObjectField {
+    /// Whether an object of type `object_type` is of the resolved type, whatever its type arguments
+    pub fn matches_type(&self, object_type: &str) -> bool {
+        let base = |t: &str| {
+            let (base, _) = split_type_args(t).ok()?;
+            let tag = StructTag::parse(base).ok()?;
+            Some((tag.address, tag.module, tag.name))
+        };
+        base(object_type).is_some_and(|t| Some(t) == base(&self.object_type))
+    }
+
+    /// The field's bytes in an object's contents
+    pub fn extract<'a>(&self, contents: &'a [u8]) -> Option<&'a [u8]> {
+        let size = match self.field {
+            Some(_) => self.size?,
+            None => contents.len(),
+        };
+        contents.get(self.offset..self.offset.checked_add(size)?)
+    }
+}
+
+impl SuiKeyResolver {
+    /// Resolve a query to the object, and field, it names
+    pub fn object_field(layout: &LayoutInfo, query: &str) -> Result<ObjectField, SuiError> {
+        let (object_label, object_id, field) = Self::split_query(query)?;
+        // Type arguments may be written, but every instantiation shares the layout
+        let object = Self::find_object(layout, split_type_args(object_label)?.0)?;
+        let entry = match field {
+            Some(field) => {
+                let label = format!("{}.{}", object.label, field);
+                layout.storage.iter().find(|e| e.label == label).ok_or_else(|| {
+                    SuiError::InvalidTypeTag(format!(
+                        "{} has no field {} at a static offset; fields after a variable-size field cannot be resolved",
+                        object.label, field
+                    ))
+                })?
+            }
+            None => object,
+        };
+        let offset = entry
+            .slot
+            .parse()
+            .map_err(|_| SuiError::InvalidTypeTag(format!("Layout entry {} has no byte offset", entry.label)))?;
+        let size = layout
+            .types
+            .iter()
+            .find(|t| t.label == entry.type_name)
+            .and_then(|t| t.number_of_bytes.parse::<usize>().ok())
+            .filter(|size| *size > 0);
+
+        Ok(ObjectField {
+            object_id: parse_address(object_id)?,
+            object_type: object.type_name.clone(),
+            field: field.map(str::to_string),
+            offset,
+            size,
+            zero_semantics: entry.zero_semantics,
+        })
+    }
+
+    /// The object entry labelled `module::Type`, or `Type` if only one module declares it
+    fn find_object<'a>(layout: &'a LayoutInfo, label: &str) -> Result<&'a StorageEntry, SuiError> {
+        let objects = layout.storage.iter().filter(|e| !e.label.contains('.'));
+        let matches: Vec<_> = if label.contains("::") {
+            objects.filter(|e| e.label == label).collect()
+        } else {
+            objects.filter(|e| e.label.rsplit("::").next() == Some(label)).collect()
+        };
+        match matches.as_slice() {
+            [entry] => Ok(entry),
+            [] => Err(SuiError::InvalidTypeTag(format!("{} declares no object type {}", layout.contract_name, label))),
+            _ => Err(SuiError::InvalidTypeTag(format!("{} is declared by several modules; write module::{}", label, label))),
+        }
+    }
+
+    /// Split `module::Type[id].field` into the type, the object ID and the field
+    fn split_query(query: &str) -> Result<(&str, &str, Option<&str>), SuiError> {
+        let invalid = || SuiError::InvalidTypeTag(format!("'{}' is not module::Type[object id] or module::Type[object id].field", query));
+        let (head, rest) = query.split_once('[').ok_or_else(invalid)?;
+        let (object_id, rest) = rest.split_once(']').ok_or_else(invalid)?;
+        let field = match rest {
+            "" => None,
+            rest => Some(rest.strip_prefix('.').filter(|f| !f.is_empty()).ok_or_else(invalid)?),
+        };
+        Ok((head.trim(), object_id.trim(), field))
+    }
+}
+
+impl KeyResolver for SuiKeyResolver {
+    /// Resolve an object query to the object ID, with the field's offset and size
+    fn resolve(&self, layout: &LayoutInfo, query: &str) -> Result<StaticKeyPath, TraverseError> {
+        let field = Self::object_field(layout, query)?;
+        Ok(StaticKeyPath {
+            name: "sui_object",
+            key: Key::Fixed(field.object_id),
+            offset: field.field.as_ref().and_then(|_| u8::try_from(field.offset).ok()),
+            field_size: field.size.and_then(|size| u8::try_from(size).ok()),
+            layout_commitment: layout.commitment(),
+            zero_semantics: field.zero_semantics,
+        })
+    }
+
+    /// Objects are addressed by ID, which no layout holds
+    fn resolve_all(&self, _layout: &LayoutInfo) -> Result<Vec<StaticKeyPath>, TraverseError> {
+        Err(TraverseError::InvalidInput(
+            "Sui objects are addressed by ID; resolve module::Type[object id] queries instead".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::tests::pool_package;
+    use crate::SuiLayoutCompiler;
+
+    #[test]
+    fn test_resolve_object_fields() {
+        let layout = SuiLayoutCompiler::compile_package(&pool_package()).unwrap();
+
+        let reserve = SuiKeyResolver::object_field(&layout, "pool::Pool[0xbeef].reserve").unwrap();
+        assert_eq!(reserve.object_id, parse_address("0xbeef").unwrap());
+        assert_eq!((reserve.offset, reserve.size), (32, Some(8)));
+        assert_eq!(reserve.zero_semantics, ZeroSemantics::ValidZero);
+        assert!(reserve.matches_type(&format!("0x{:0>64}::pool::Pool<0x2::sui::SUI>", "5a1e")));
+        assert!(!reserve.matches_type("0x2::coin::Coin<0x2::sui::SUI>"));
+
+        let mut contents = vec![0u8; 40];
+        contents[32] = 7;
+        assert_eq!(reserve.extract(&contents), Some(&[7, 0, 0, 0, 0, 0, 0, 0][..]));
+
+        // The type alone is enough when it is unique
+        let path = SuiKeyResolver.resolve(&layout, "Pool[0xbeef].reserve").unwrap();
+        assert_eq!((path.offset, path.field_size), (Some(32), Some(8)));
+        let object = SuiKeyResolver.resolve(&layout, "Pool[0xbeef]").unwrap();
+        assert_eq!((object.offset, object.zero_semantics), (None, ZeroSemantics::NeverWritten));
+
+        // A nested struct is read whole
+        let fees = SuiKeyResolver.resolve(&layout, "Pool[0xbeef].fees").unwrap();
+        assert_eq!((fees.offset, fees.field_size), (Some(40), Some(3)));
+
+        // `paused` follows the variable-size `name`
+        assert!(SuiKeyResolver.resolve(&layout, "Pool[0xbeef].paused").is_err());
+        assert!(SuiKeyResolver.resolve(&layout, "Pool.reserve").is_err());
+        assert!(SuiKeyResolver.resolve(&layout, "Receipt[0xbeef]").is_err());
+        assert!(SuiKeyResolver.resolve_all(&layout).is_err());
+    }
+}
@@ -0,0 +1,266 @@
+//! Move type tags and addresses
+//!
+//! Object types are written `0x2::coin::Coin<0x2::sui::SUI>`, and their BCS
+//! encoding is part of an object's bytes and hence of its digest. Addresses
+//! and object IDs are both 32 bytes.
+
+use crate::SuiError;
+use core::fmt;
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Parse an address or object ID, left-padding short forms such as `0x2`
+pub fn parse_address(address: &str) -> Result<[u8; 32], SuiError> {
+    let digits = address
+        .strip_prefix("0x")
+        .ok_or_else(|| SuiError::InvalidAddress(format!("'{}' does not start with 0x", address)))?;
+    if digits.is_empty() || digits.len() > 64 {
+        return Err(SuiError::InvalidAddress(format!("'{}' is not 1 to 64 hex digits", address)));
+    }
+    let padded = format!("{:0>64}", digits);
+    let mut bytes = [0u8; 32];
+    hex::decode_to_slice(&padded, &mut bytes)
+        .map_err(|e| SuiError::InvalidAddress(format!("'{}': {}", address, e)))?;
+    Ok(bytes)
+}
+
+/// Display an address in full, the way JSON-RPC returns object IDs and owners
+pub fn display_address(address: &[u8; 32]) -> String {
+    format!("0x{}", hex::encode(address))
+}
+
+/// Display an address inside a type, where the framework's are short: `0x2::coin::Coin`
+pub(crate) fn display_type_address(address: &[u8; 32]) -> String {
+    if address[..31].iter().all(|b| *b == 0) && address[31] < 0x10 {
+        format!("0x{:x}", address[31])
+    } else {
+        display_address(address)
+    }
+}
+
+/// A Move type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeTag {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    Address,
+    Signer,
+    Vector(Box<TypeTag>),
+    Struct(Box<StructTag>),
+}
+
+/// A struct type with its type arguments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructTag {
+    pub address: [u8; 32],
+    pub module: String,
+    pub name: String,
+    pub type_args: Vec<TypeTag>,
+}
+
+impl TypeTag {
+    /// Parse a type such as `u64`, `vector<u8>` or `0x1::string::String`
+    pub fn parse(type_tag: &str) -> Result<Self, SuiError> {
+        let type_tag = type_tag.trim();
+        Ok(match type_tag {
+            "bool" => Self::Bool,
+            "u8" => Self::U8,
+            "u16" => Self::U16,
+            "u32" => Self::U32,
+            "u64" => Self::U64,
+            "u128" => Self::U128,
+            "u256" => Self::U256,
+            "address" => Self::Address,
+            "signer" => Self::Signer,
+            _ => match type_tag.strip_prefix("vector<").and_then(|t| t.strip_suffix('>')) {
+                Some(element) => Self::Vector(Box::new(Self::parse(element)?)),
+                None => Self::Struct(Box::new(StructTag::parse(type_tag)?)),
+            },
+        })
+    }
+
+    /// BCS encoding, with variant indices in the order Move declares them
+    pub fn bcs(&self, out: &mut Vec<u8>) {
+        match self {
+            Self::Bool => out.push(0),
+            Self::U8 => out.push(1),
+            Self::U64 => out.push(2),
+            Self::U128 => out.push(3),
+            Self::Address => out.push(4),
+            Self::Signer => out.push(5),
+            Self::Vector(element) => {
+                out.push(6);
+                element.bcs(out);
+            }
+            Self::Struct(tag) => {
+                out.push(7);
+                tag.bcs(out);
+            }
+            Self::U16 => out.push(8),
+            Self::U32 => out.push(9),
+            Self::U256 => out.push(10),
+        }
+    }
+}
+
+impl StructTag {
+    /// Parse a struct type such as `0x2::coin::Coin<0x2::sui::SUI>`
+    pub fn parse(type_tag: &str) -> Result<Self, SuiError> {
+        let invalid = || SuiError::InvalidTypeTag(format!("'{}' is not address::module::Name", type_tag));
+        let (base, type_args) = split_type_args(type_tag.trim())?;
+        let mut parts = base.split("::");
+        let (Some(address), Some(module), Some(name), None) = (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        if !is_identifier(module) || !is_identifier(name) {
+            return Err(invalid());
+        }
+        Ok(Self {
+            address: parse_address(address)?,
+            module: module.to_string(),
+            name: name.to_string(),
+            type_args: type_args.into_iter().map(TypeTag::parse).collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Whether this is `address::module::name`, whatever its type arguments
+    pub fn is(&self, address: u8, module: &str, name: &str) -> bool {
+        self.address[..31].iter().all(|b| *b == 0)
+            && self.address[31] == address
+            && self.module == module
+            && self.name == name
+    }
+
+    /// BCS encoding: address, module and name, then the type arguments
+    pub fn bcs(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.address);
+        write_bytes(out, self.module.as_bytes());
+        write_bytes(out, self.name.as_bytes());
+        write_uleb128(out, self.type_args.len() as u64);
+        for arg in &self.type_args {
+            arg.bcs(out);
+        }
+    }
+}
+
+impl fmt::Display for TypeTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool => write!(f, "bool"),
+            Self::U8 => write!(f, "u8"),
+            Self::U16 => write!(f, "u16"),
+            Self::U32 => write!(f, "u32"),
+            Self::U64 => write!(f, "u64"),
+            Self::U128 => write!(f, "u128"),
+            Self::U256 => write!(f, "u256"),
+            Self::Address => write!(f, "address"),
+            Self::Signer => write!(f, "signer"),
+            Self::Vector(element) => write!(f, "vector<{}>", element),
+            Self::Struct(tag) => write!(f, "{}", tag),
+        }
+    }
+}
+
+impl fmt::Display for StructTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}::{}::{}", display_type_address(&self.address), self.module, self.name)?;
+        if !self.type_args.is_empty() {
+            write!(f, "<")?;
+            for (i, arg) in self.type_args.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}", arg)?;
+            }
+            write!(f, ">")?;
+        }
+        Ok(())
+    }
+}
+
+/// Split `Name<A, B<C>>` into `Name` and its top-level type arguments
+pub fn split_type_args(type_tag: &str) -> Result<(&str, Vec<&str>), SuiError> {
+    let Some(open) = type_tag.find('<') else {
+        return Ok((type_tag, Vec::new()));
+    };
+    let unbalanced = || SuiError::InvalidTypeTag(format!("'{}' has unbalanced brackets", type_tag));
+    let inner = type_tag[open + 1..].strip_suffix('>').ok_or_else(unbalanced)?;
+
+    let mut args = Vec::new();
+    let (mut depth, mut start) = (0usize, 0);
+    for (i, c) in inner.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth = depth.checked_sub(1).ok_or_else(unbalanced)?,
+            ',' if depth == 0 => {
+                args.push(inner[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return Err(unbalanced());
+    }
+    args.push(inner[start..].trim());
+    if args.iter().any(|arg| arg.is_empty()) {
+        return Err(SuiError::InvalidTypeTag(format!("'{}' has an empty type argument", type_tag)));
+    }
+    Ok((&type_tag[..open], args))
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Append a BCS byte string: ULEB128 length, then the bytes
+pub(crate) fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    write_uleb128(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+pub(crate) fn write_uleb128(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_type_tags() {
+        let coin = StructTag::parse("0x2::coin::Coin<0x2::sui::SUI>").unwrap();
+        assert!(coin.is(2, "coin", "Coin"));
+        assert_eq!(coin.to_string(), "0x2::coin::Coin<0x2::sui::SUI>");
+
+        let mut bcs = Vec::new();
+        coin.bcs(&mut bcs);
+        let mut expected = [[0u8; 31].as_slice(), &[2], &[4], b"coin", &[4], b"Coin", &[1, 7]].concat();
+        expected.extend([[0u8; 31].as_slice(), &[2], &[3], b"sui", &[3], b"SUI", &[0]].concat());
+        assert_eq!(bcs, expected);
+
+        let pool = TypeTag::parse("0x5a1e::pool::Pool<vector<u8>, 0x2::balance::Balance<T0>>");
+        assert!(pool.is_err(), "T0 is a type parameter, not a type");
+        assert_eq!(display_address(&parse_address("0x5a1e").unwrap()), format!("0x{:0>64}", "5a1e"));
+        assert!(StructTag::parse("0x2::coin").is_err());
+        assert!(parse_address("5a1e").is_err());
+    }
+}
@@ -0,0 +1,158 @@
+//! Accounts
+//!
+//! An account cell holds the address, storage statistics, the last
+//! transaction's logical time, the balance and the state: uninitialized,
+//! frozen, or active with a `StateInit` whose `code` and `data` are cell
+//! references. The storage statistics changed shape in 2024, dropping
+//! `public_cells` and gaining `storage_extra`; both shapes are read, and a
+//! cell must parse to its end under one of them.
+
+use crate::address::load_address;
+use crate::cell::{BagOfCells, CellSlice};
+use crate::{TonAddress, TonError};
+
+#[cfg(not(feature = "std"))]
+use alloc::format;
+
+/// An account that exists
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TonAccount {
+    pub address: TonAddress,
+    /// Balance in nanotons; extra currencies are not read
+    pub balance: u128,
+    /// Logical time of the account's last transaction
+    pub last_trans_lt: u64,
+    pub status: AccountStatus,
+}
+
+/// What an account's state holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountStatus {
+    Uninit,
+    /// Frozen for unpaid storage; only the state's hash is kept
+    Frozen { state_hash: [u8; 32] },
+    /// Deployed; `code` and `data` are cell indexes in the account's bag
+    Active { code: Option<usize>, data: Option<usize> },
+}
+
+impl TonAccount {
+    /// Parse the account cell `cell`; `None` is `account_none`
+    pub fn parse(boc: &BagOfCells, cell: usize) -> Result<Option<Self>, TonError> {
+        let mut slice = boc.slice(cell)?;
+        if !slice.load_bit()? {
+            slice.expect_end()?;
+            return Ok(None);
+        }
+        let current = parse_account(slice, true);
+        current.or_else(|_| parse_account(slice, false)).map(Some)
+    }
+
+    /// Index of the data cell, for an active account that has one
+    pub fn data_cell(&self) -> Option<usize> {
+        match self.status {
+            AccountStatus::Active { data, .. } => data,
+            _ => None,
+        }
+    }
+}
+
+/// The account after its leading bit, in the current storage statistics shape or the one before
+fn parse_account(mut slice: CellSlice<'_>, current: bool) -> Result<TonAccount, TonError> {
+    let address = load_address(&mut slice)?
+        .ok_or_else(|| TonError::InvalidCell("An account has no address".into()))?;
+
+    // storage_stat:StorageInfo
+    let used = if current { 2 } else { 3 };
+    for _ in 0..used {
+        slice.load_var_uint(7)?;
+    }
+    if current {
+        match slice.load_uint(3)? {
+            0b000 => {}
+            0b001 => slice.skip_bits(256)?,
+            other => return Err(TonError::InvalidCell(format!("Unknown storage_extra tag {:03b}", other))),
+        }
+    }
+    slice.skip_bits(32)?;
+    if slice.load_bit()? {
+        slice.load_var_uint(16)?;
+    }
+
+    // storage:AccountStorage
+    let last_trans_lt = slice.load_uint(64)?;
+    let balance = slice.load_var_uint(16)?;
+    if slice.load_bit()? {
+        // Extra currencies
+        slice.load_ref()?;
+    }
+    let status = if slice.load_bit()? {
+        // StateInit: fixed_prefix_length, special, code, data, library
+        if slice.load_bit()? {
+            slice.skip_bits(5)?;
+        }
+        if slice.load_bit()? {
+            slice.skip_bits(2)?;
+        }
+        let code = if slice.load_bit()? { Some(slice.load_ref()?) } else { None };
+        let data = if slice.load_bit()? { Some(slice.load_ref()?) } else { None };
+        if slice.load_bit()? {
+            slice.load_ref()?;
+        }
+        AccountStatus::Active { code, data }
+    } else if slice.load_bit()? {
+        AccountStatus::Frozen { state_hash: slice.load_hash()? }
+    } else {
+        AccountStatus::Uninit
+    };
+    slice.expect_end()?;
+    Ok(TonAccount { address, balance, last_trans_lt, status })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::cell::tests::TestCell;
+    use crate::resolver::tests::{std_address, wallet_data};
+
+    /// An active account holding `data`, its storage statistics in the current shape or the old one
+    pub(crate) fn active_account(address: &TonAddress, data: TestCell, current: bool) -> TestCell {
+        let mut account = std_address(TestCell::new().uint(1, 1), address);
+        // cells and bits used, as VarUInteger 7
+        account = account.uint(1, 3).uint(12, 8).uint(2, 3).uint(4000, 16);
+        account = if current { account.uint(0b000, 3) } else { account.uint(1, 3).uint(0, 8) };
+        // last_paid, no due payment, last_trans_lt, balance, no extra currencies
+        account = account.uint(1_700_000_000, 32).uint(0, 1).uint(41_000_000_000_001, 64);
+        account = account.uint(4, 4).uint(2_500_000_000, 32).uint(0, 1);
+        // active, no prefix length, not special, code, data, no library
+        account = account.uint(0b1001, 4).child(TestCell::new().bytes(b"wallet code"));
+        account.uint(0b10, 2).child(data)
+    }
+
+    #[test]
+    fn test_parse_accounts() {
+        let address = TonAddress::new(0, [0x44; 32]);
+        for current in [true, false] {
+            let boc = BagOfCells::parse(&active_account(&address, wallet_data(true), current).to_boc()).unwrap();
+            let account = TonAccount::parse(&boc, 0).unwrap().unwrap();
+            assert_eq!(account.address, address);
+            assert_eq!((account.balance, account.last_trans_lt), (2_500_000_000, 41_000_000_000_001));
+            let data = account.data_cell().unwrap();
+            assert_eq!(boc.cell(data).hash(0), BagOfCells::parse(&wallet_data(true).to_boc()).unwrap().cell(0).hash(0));
+        }
+
+        let none = BagOfCells::parse(&TestCell::new().uint(0, 1).to_boc()).unwrap();
+        assert_eq!(TonAccount::parse(&none, 0).unwrap(), None);
+
+        let mut frozen = std_address(TestCell::new().uint(1, 1), &address);
+        frozen = frozen.uint(0, 3).uint(0, 3).uint(0, 3).uint(0, 32).uint(0, 1).uint(5, 64).uint(0, 4).uint(0, 1);
+        frozen = frozen.uint(0b01, 2).bytes(&[9; 32]);
+        let boc = BagOfCells::parse(&frozen.to_boc()).unwrap();
+        let account = TonAccount::parse(&boc, 0).unwrap().unwrap();
+        assert_eq!(account.status, AccountStatus::Frozen { state_hash: [9; 32] });
+        assert_eq!(account.data_cell(), None);
+
+        // Trailing data fits neither shape
+        let trailing = active_account(&address, wallet_data(true), true).uint(1, 1);
+        assert!(TonAccount::parse(&BagOfCells::parse(&trailing.to_boc()).unwrap(), 0).is_err());
+    }
+}
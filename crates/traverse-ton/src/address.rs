@@ -0,0 +1,159 @@
+//! Account addresses
+//!
+//! An account is named by its workchain and the 256-bit hash of its
+//! initial state. The raw form writes them as `workchain:hex`; wallets
+//! show a user-friendly form, 36 bytes in base64 holding flags, the
+//! workchain, the hash and a CRC16 checksum.
+
+use crate::cell::CellSlice;
+use crate::TonError;
+use core::fmt;
+
+#[cfg(not(feature = "std"))]
+use alloc::format;
+
+/// An internal address, as `addr_std` holds it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TonAddress {
+    pub workchain: i32,
+    pub hash: [u8; 32],
+}
+
+impl TonAddress {
+    pub const MASTERCHAIN: i32 = -1;
+    pub const BASECHAIN: i32 = 0;
+
+    pub fn new(workchain: i32, hash: [u8; 32]) -> Self {
+        Self { workchain, hash }
+    }
+
+    /// Parse a raw `workchain:hex` address, or under `std` a user-friendly one
+    pub fn parse(address: &str) -> Result<Self, TonError> {
+        let address = address.trim();
+        let invalid = || TonError::InvalidAddress(format!("Invalid address '{}'", address));
+        if let Some((workchain, hash)) = address.split_once(':') {
+            let workchain = workchain.parse::<i32>().map_err(|_| invalid())?;
+            let mut bytes = [0u8; 32];
+            hex::decode_to_slice(hash, &mut bytes).map_err(|_| invalid())?;
+            return Ok(Self::new(workchain, bytes));
+        }
+        #[cfg(feature = "std")]
+        {
+            Self::from_user_friendly(address)
+        }
+        #[cfg(not(feature = "std"))]
+        Err(invalid())
+    }
+
+    /// Parse the base64 form wallets show, checking its checksum
+    #[cfg(feature = "std")]
+    pub fn from_user_friendly(address: &str) -> Result<Self, TonError> {
+        use base64::Engine;
+
+        let invalid = |why: &str| TonError::InvalidAddress(format!("Invalid address '{}': {}", address, why));
+        let engine = if address.contains(['-', '_']) {
+            base64::engine::general_purpose::URL_SAFE
+        } else {
+            base64::engine::general_purpose::STANDARD
+        };
+        let bytes = engine.decode(address).map_err(|_| invalid("not base64"))?;
+        if bytes.len() != 36 {
+            return Err(invalid("not 36 bytes"));
+        }
+        if crc16(&bytes[..34]).to_be_bytes() != bytes[34..] {
+            return Err(invalid("checksum mismatch"));
+        }
+        if bytes[0] & 0x7f != 0x11 && bytes[0] & 0x7f != 0x51 {
+            return Err(invalid("unknown flags"));
+        }
+        Ok(Self::new(bytes[1] as i8 as i32, bytes[2..34].try_into().expect("32 bytes")))
+    }
+
+    /// The user-friendly form, URL-safe base64
+    #[cfg(feature = "std")]
+    pub fn to_user_friendly(&self, bounceable: bool, testnet: bool) -> String {
+        use base64::Engine;
+
+        let mut bytes = [0u8; 36];
+        bytes[0] = if bounceable { 0x11 } else { 0x51 } | if testnet { 0x80 } else { 0 };
+        bytes[1] = self.workchain as i8 as u8;
+        bytes[2..34].copy_from_slice(&self.hash);
+        let crc = crc16(&bytes[..34]);
+        bytes[34..].copy_from_slice(&crc.to_be_bytes());
+        base64::engine::general_purpose::URL_SAFE.encode(bytes)
+    }
+}
+
+impl fmt::Display for TonAddress {
+    /// The raw form
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.workchain, hex::encode(self.hash))
+    }
+}
+
+/// Read a `MsgAddress`, `None` being `addr_none`
+///
+/// External addresses, and `addr_var` ones of other than 256 bits, name no
+/// account and are refused.
+pub fn load_address(slice: &mut CellSlice<'_>) -> Result<Option<TonAddress>, TonError> {
+    let unsupported = |what: &str| TonError::InvalidCell(format!("{} addresses are not supported", what));
+    let kind = slice.load_uint(2)?;
+    if kind == 0b00 {
+        return Ok(None);
+    }
+    if kind == 0b01 {
+        return Err(unsupported("External"));
+    }
+    if slice.load_bit()? {
+        // Anycast: a rewrite prefix of up to 30 bits
+        let depth = slice.load_uint_leq(30)? as usize;
+        slice.skip_bits(depth)?;
+    }
+    let workchain = if kind == 0b10 {
+        slice.load_uint(8)? as u8 as i8 as i32
+    } else {
+        if slice.load_uint(9)? != 256 {
+            return Err(unsupported("Variable-length"));
+        }
+        slice.load_uint(32)? as u32 as i32
+    };
+    Ok(Some(TonAddress::new(workchain, slice.load_hash()?)))
+}
+
+/// CRC16-XMODEM, as user-friendly addresses are checksummed
+#[cfg(feature = "std")]
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for byte in data {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_forms() {
+        // The elector contract
+        let elector = TonAddress::parse("-1:3333333333333333333333333333333333333333333333333333333333333333").unwrap();
+        assert_eq!(elector.workchain, TonAddress::MASTERCHAIN);
+        assert_eq!(elector.to_user_friendly(true, false), "Ef8zMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzM0vF");
+        assert_eq!(TonAddress::parse("Ef8zMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzM0vF").unwrap(), elector);
+        assert_eq!(crc16(b"123456789"), 0x31c3);
+
+        let wallet = TonAddress::new(0, [0xab; 32]);
+        assert_eq!(TonAddress::parse(&wallet.to_string()).unwrap(), wallet);
+        for (bounceable, testnet) in [(true, false), (false, false), (true, true)] {
+            assert_eq!(TonAddress::parse(&wallet.to_user_friendly(bounceable, testnet)).unwrap(), wallet);
+        }
+
+        assert!(TonAddress::parse("Ef8zMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzMzM0vG").is_err());
+        assert!(TonAddress::parse("0:1234").is_err());
+        assert!(TonAddress::parse("x:3333333333333333333333333333333333333333333333333333333333333333").is_err());
+    }
+}
@@ -0,0 +1,605 @@
+//! Cells and bags of cells
+//!
+//! All TON state is a DAG of cells, each holding up to 1023 bits and four
+//! references. A cell's hash covers its descriptor bytes, its data, and
+//! the depths and hashes of its children. Exotic cells change that: a
+//! pruned branch stands in for a subtree by storing the subtree's hashes,
+//! and a Merkle proof cell wraps a tree in which some subtrees are pruned,
+//! committing to the hash the full tree would have. Hashes are kept per
+//! level so a pruned tree still yields the full tree's level-0 hash.
+//!
+//! A bag of cells (BoC) is the serialization: a header, the cells in an
+//! order where each refers only to later ones, and an optional CRC32-C.
+
+use crate::TonError;
+use sha2::{Digest, Sha256};
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, vec, vec::Vec};
+
+const BOC_MAGIC: [u8; 4] = [0xb5, 0xee, 0x9c, 0x72];
+
+/// Kind of a cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellKind {
+    Ordinary,
+    PrunedBranch,
+    LibraryReference,
+    MerkleProof,
+    MerkleUpdate,
+}
+
+/// A cell in a bag, its references given as indexes into the bag
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cell {
+    pub kind: CellKind,
+    /// Data, with any bits past `bit_len` zero
+    pub data: Vec<u8>,
+    pub bit_len: usize,
+    pub refs: Vec<usize>,
+    pub level_mask: u8,
+    hashes: Vec<[u8; 32]>,
+    depths: Vec<u16>,
+}
+
+/// A parsed bag of cells
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BagOfCells {
+    pub cells: Vec<Cell>,
+    pub roots: Vec<usize>,
+}
+
+/// Index of the hash that serves `level` under `mask`
+fn hash_index(mask: u8, level: u8) -> usize {
+    (mask & ((1u8 << level) - 1)).count_ones() as usize
+}
+
+impl Cell {
+    /// Hash of the cell at `level`; level 0 is the hash of the full, unpruned tree
+    pub fn hash(&self, level: u8) -> [u8; 32] {
+        self.hashes[hash_index(self.level_mask, level.min(3)).min(self.hashes.len() - 1)]
+    }
+
+    /// Depth of the cell at `level`
+    pub fn depth(&self, level: u8) -> u16 {
+        self.depths[hash_index(self.level_mask, level.min(3)).min(self.depths.len() - 1)]
+    }
+
+    /// The hash a bag's root is known by
+    pub fn repr_hash(&self) -> [u8; 32] {
+        self.hash(3)
+    }
+
+    /// Hash a Merkle proof or update commits to, read from its data
+    pub fn merkle_hash(&self, which: usize) -> Option<[u8; 32]> {
+        let start = 1 + 32 * which;
+        self.data.get(start..start + 32).map(|hash| hash.try_into().expect("32 bytes"))
+    }
+
+    fn descriptors(&self, level_mask: u8) -> [u8; 2] {
+        let exotic = self.kind != CellKind::Ordinary;
+        let d1 = self.refs.len() as u8 + 8 * exotic as u8 + 32 * level_mask;
+        let d2 = (self.bit_len / 8 + self.bit_len.div_ceil(8)) as u8;
+        [d1, d2]
+    }
+
+    /// Data as it is hashed: whole bytes, an incomplete last byte ending in a 1 bit
+    fn padded_data(&self) -> Vec<u8> {
+        let mut data = self.data[..self.bit_len.div_ceil(8)].to_vec();
+        if !self.bit_len.is_multiple_of(8) {
+            let last = data.len() - 1;
+            data[last] |= 0x80 >> (self.bit_len % 8);
+        }
+        data
+    }
+}
+
+impl BagOfCells {
+    /// Parse a serialized bag of cells, computing every cell's hashes
+    pub fn parse(bytes: &[u8]) -> Result<Self, TonError> {
+        let invalid = |what: &str| TonError::InvalidCell(format!("Invalid bag of cells: {}", what));
+        if bytes.get(..4) != Some(&BOC_MAGIC[..]) {
+            return Err(invalid("unknown magic"));
+        }
+        let flags = *bytes.get(4).ok_or_else(|| invalid("no header"))?;
+        let has_index = flags & 0x80 != 0;
+        let has_crc = flags & 0x40 != 0;
+        let ref_size = (flags & 0x07) as usize;
+        let offset_size = *bytes.get(5).ok_or_else(|| invalid("no header"))? as usize;
+        if !(1..=4).contains(&ref_size) || !(1..=8).contains(&offset_size) {
+            return Err(invalid("bad sizes"));
+        }
+        if has_crc {
+            let (body, crc) = bytes.split_at(bytes.len().checked_sub(4).ok_or_else(|| invalid("too short"))?);
+            if crc32c(body).to_le_bytes() != crc {
+                return Err(invalid("checksum mismatch"));
+            }
+        }
+
+        let mut position = 6;
+        let mut read = |size: usize| -> Result<usize, TonError> {
+            let field = bytes.get(position..position + size).ok_or_else(|| invalid("truncated"))?;
+            position += size;
+            Ok(field.iter().fold(0usize, |acc, b| (acc << 8) | *b as usize))
+        };
+        let cell_count = read(ref_size)?;
+        let root_count = read(ref_size)?;
+        let _absent = read(ref_size)?;
+        let data_size = read(offset_size)?;
+        let roots = (0..root_count).map(|_| read(ref_size)).collect::<Result<Vec<_>, _>>()?;
+        if has_index {
+            read(cell_count.checked_mul(offset_size).ok_or_else(|| invalid("too many cells"))?)?;
+        }
+        let data = bytes
+            .get(position..position.checked_add(data_size).ok_or_else(|| invalid("truncated"))?)
+            .ok_or_else(|| invalid("truncated"))?;
+
+        let mut cells = Vec::new();
+        let mut at = 0;
+        while cells.len() < cell_count {
+            let (cell, used) = parse_cell(&data[at..], ref_size, cells.len(), cell_count)?;
+            cells.push(cell);
+            at += used;
+        }
+        if at != data.len() {
+            return Err(invalid("cell data has trailing bytes"));
+        }
+        if roots.is_empty() || roots.iter().any(|root| *root >= cell_count) {
+            return Err(invalid("bad roots"));
+        }
+
+        // Children come after their parents, so hash from the end
+        for index in (0..cells.len()).rev() {
+            let (hashes, depths, level_mask) = compute_hashes(&cells, index)?;
+            let cell = &mut cells[index];
+            cell.hashes = hashes;
+            cell.depths = depths;
+            cell.level_mask = level_mask;
+        }
+        Ok(Self { cells, roots })
+    }
+
+    /// The bag's `index`-th root
+    pub fn root(&self, index: usize) -> Result<usize, TonError> {
+        self.roots
+            .get(index)
+            .copied()
+            .ok_or_else(|| TonError::InvalidCell(format!("The bag of cells has no root {}", index)))
+    }
+
+    pub fn cell(&self, index: usize) -> &Cell {
+        &self.cells[index]
+    }
+
+    /// A reader over a cell's data and references
+    pub fn slice(&self, index: usize) -> Result<CellSlice<'_>, TonError> {
+        let cell = &self.cells[index];
+        if cell.kind == CellKind::PrunedBranch {
+            return Err(TonError::ProofVerification("The proof prunes a cell it is needed for".into()));
+        }
+        Ok(CellSlice { boc: self, cell: index, bit: 0, next_ref: 0 })
+    }
+
+    /// The tree a Merkle proof root wraps, after checking it has the hash `expected`
+    pub fn open_merkle_proof(&self, index: usize, expected: &[u8; 32]) -> Result<usize, TonError> {
+        let cell = &self.cells[index];
+        if cell.kind != CellKind::MerkleProof {
+            return Err(TonError::ProofVerification("Expected a Merkle proof cell".into()));
+        }
+        let child = cell.refs[0];
+        if cell.merkle_hash(0) != Some(*expected) || self.cells[child].hash(0) != *expected {
+            return Err(TonError::ProofVerification("The Merkle proof is for another tree".into()));
+        }
+        Ok(child)
+    }
+}
+
+fn parse_cell(data: &[u8], ref_size: usize, index: usize, cell_count: usize) -> Result<(Cell, usize), TonError> {
+    let invalid = |what: &str| TonError::InvalidCell(format!("Invalid cell {}: {}", index, what));
+    let [d1, d2] = *data.first_chunk::<2>().ok_or_else(|| invalid("truncated"))?;
+    let ref_count = (d1 & 7) as usize;
+    let exotic = d1 & 8 != 0;
+    let level_mask = d1 >> 5;
+    if ref_count > 4 {
+        return Err(invalid("more than four references"));
+    }
+    let mut at = 2;
+    if d1 & 16 != 0 {
+        // Stored hashes and depths, recomputed below
+        at += (level_mask.count_ones() as usize + 1) * (32 + 2);
+    }
+    let byte_len = (d2 as usize).div_ceil(2);
+    let mut cell_data = data.get(at..at + byte_len).ok_or_else(|| invalid("truncated"))?.to_vec();
+    at += byte_len;
+    let bit_len = if d2.is_multiple_of(2) {
+        byte_len * 8
+    } else {
+        // The last byte ends in a 1 bit marking where the data stops
+        let last = *cell_data.last().ok_or_else(|| invalid("no data"))?;
+        if last == 0 {
+            return Err(invalid("bad padding"));
+        }
+        let trailing = last.trailing_zeros() as usize;
+        let len = cell_data.len();
+        cell_data[len - 1] &= !(1u8 << trailing);
+        byte_len * 8 - trailing - 1
+    };
+    let mut refs = Vec::with_capacity(ref_count);
+    for _ in 0..ref_count {
+        let field = data.get(at..at + ref_size).ok_or_else(|| invalid("truncated"))?;
+        let target = field.iter().fold(0usize, |acc, b| (acc << 8) | *b as usize);
+        if target <= index || target >= cell_count {
+            return Err(invalid("references a cell before it"));
+        }
+        refs.push(target);
+        at += ref_size;
+    }
+
+    let kind = if !exotic {
+        CellKind::Ordinary
+    } else {
+        match cell_data.first() {
+            Some(1) => CellKind::PrunedBranch,
+            Some(2) => CellKind::LibraryReference,
+            Some(3) if bit_len == 8 + 256 + 16 && ref_count == 1 => CellKind::MerkleProof,
+            Some(4) if bit_len == 8 + 512 + 32 && ref_count == 2 => CellKind::MerkleUpdate,
+            _ => return Err(invalid("unknown exotic cell")),
+        }
+    };
+    let cell = Cell { kind, data: cell_data, bit_len, refs, level_mask, hashes: Vec::new(), depths: Vec::new() };
+    Ok((cell, at))
+}
+
+/// A cell's hashes and depths, one per level it has, and its level mask
+type LevelHashes = (Vec<[u8; 32]>, Vec<u16>, u8);
+
+/// Hashes and depths of `cells[index]` at each of its levels, children already hashed
+fn compute_hashes(cells: &[Cell], index: usize) -> Result<LevelHashes, TonError> {
+    let cell = &cells[index];
+    let invalid = |what: &str| TonError::InvalidCell(format!("Invalid cell {}: {}", index, what));
+    let children: Vec<&Cell> = cell.refs.iter().map(|r| &cells[*r]).collect();
+    let merkle = matches!(cell.kind, CellKind::MerkleProof | CellKind::MerkleUpdate);
+
+    let level_mask = match cell.kind {
+        CellKind::PrunedBranch => {
+            let mask = *cell.data.get(1).ok_or_else(|| invalid("pruned branch has no level mask"))?;
+            let stored = mask.count_ones() as usize;
+            if mask == 0 || mask > 7 || !cell.refs.is_empty() || cell.bit_len != 16 + stored * (256 + 16) {
+                return Err(invalid("malformed pruned branch"));
+            }
+            mask
+        }
+        CellKind::MerkleProof | CellKind::MerkleUpdate => children.iter().fold(0, |m, c| m | c.level_mask) >> 1,
+        _ => children.iter().fold(0, |m, c| m | c.level_mask),
+    };
+    if cell.level_mask != 0 && cell.level_mask != level_mask && cell.kind != CellKind::PrunedBranch {
+        return Err(invalid("stated level mask does not match its children"));
+    }
+
+    let mut hashes = Vec::new();
+    let mut depths = Vec::new();
+    if cell.kind == CellKind::PrunedBranch {
+        // The lower levels' hashes and depths are stored in the cell
+        let stored = level_mask.count_ones() as usize;
+        for i in 0..stored {
+            hashes.push(cell.data[2 + 32 * i..2 + 32 * (i + 1)].try_into().expect("32 bytes"));
+        }
+        for i in 0..stored {
+            let at = 2 + 32 * stored + 2 * i;
+            depths.push(u16::from_be_bytes([cell.data[at], cell.data[at + 1]]));
+        }
+    }
+
+    let total = level_mask.count_ones() as usize + 1;
+    for level in 0..=3u8 {
+        // Only the levels the mask marks, and level 0, get their own hash
+        if level != 0 && level_mask & (1 << (level - 1)) == 0 {
+            continue;
+        }
+        let hash_i = hash_index(level_mask, level);
+        if cell.kind == CellKind::PrunedBranch && hash_i != total - 1 {
+            continue;
+        }
+        let mut hasher = Sha256::new();
+        hasher.update(cell.descriptors(level_mask & ((1u8 << level) - 1)));
+        if hash_i == 0 || cell.kind == CellKind::PrunedBranch {
+            hasher.update(cell.padded_data());
+        } else {
+            hasher.update(hashes[hash_i - 1]);
+        }
+        let child_level = if merkle { level + 1 } else { level };
+        let mut depth = 0u16;
+        for child in &children {
+            let child_depth = child.depth(child_level);
+            hasher.update(child_depth.to_be_bytes());
+            depth = depth.max(child_depth.checked_add(1).ok_or_else(|| invalid("too deep"))?);
+        }
+        for child in &children {
+            hasher.update(child.hash(child_level));
+        }
+        hashes.push(hasher.finalize().into());
+        depths.push(depth);
+    }
+    Ok((hashes, depths, level_mask))
+}
+
+/// A reader over a cell
+#[derive(Debug, Clone, Copy)]
+pub struct CellSlice<'a> {
+    pub boc: &'a BagOfCells,
+    pub cell: usize,
+    bit: usize,
+    next_ref: usize,
+}
+
+impl<'a> CellSlice<'a> {
+    fn cell_ref(&self) -> &'a Cell {
+        &self.boc.cells[self.cell]
+    }
+
+    pub fn remaining_bits(&self) -> usize {
+        self.cell_ref().bit_len - self.bit
+    }
+
+    pub fn remaining_refs(&self) -> usize {
+        self.cell_ref().refs.len() - self.next_ref
+    }
+
+    fn underflow(&self) -> TonError {
+        TonError::InvalidCell(format!("Read past the end of cell {}", self.cell))
+    }
+
+    pub fn load_bit(&mut self) -> Result<bool, TonError> {
+        if self.remaining_bits() == 0 {
+            return Err(self.underflow());
+        }
+        let byte = self.cell_ref().data[self.bit / 8];
+        let bit = byte >> (7 - self.bit % 8) & 1 == 1;
+        self.bit += 1;
+        Ok(bit)
+    }
+
+    /// An unsigned integer of up to 64 bits
+    pub fn load_uint(&mut self, bits: usize) -> Result<u64, TonError> {
+        if bits > 64 || bits > self.remaining_bits() {
+            return Err(self.underflow());
+        }
+        (0..bits).try_fold(0u64, |acc, _| Ok((acc << 1) | self.load_bit()? as u64))
+    }
+
+    /// `bits` bits, big-endian and left-aligned in whole bytes
+    pub fn load_bits(&mut self, bits: usize) -> Result<Vec<u8>, TonError> {
+        if bits > self.remaining_bits() {
+            return Err(self.underflow());
+        }
+        let mut bytes = vec![0u8; bits.div_ceil(8)];
+        for i in 0..bits {
+            if self.load_bit()? {
+                bytes[i / 8] |= 0x80 >> (i % 8);
+            }
+        }
+        Ok(bytes)
+    }
+
+    pub fn load_hash(&mut self) -> Result<[u8; 32], TonError> {
+        Ok(self.load_bits(256)?.try_into().expect("32 bytes"))
+    }
+
+    pub fn skip_bits(&mut self, bits: usize) -> Result<(), TonError> {
+        if bits > self.remaining_bits() {
+            return Err(self.underflow());
+        }
+        self.bit += bits;
+        Ok(())
+    }
+
+    /// Number of bits read so far
+    pub fn bits_read(&self) -> usize {
+        self.bit
+    }
+
+    /// Number of references read so far
+    pub fn refs_read(&self) -> usize {
+        self.next_ref
+    }
+
+    /// Index of the next referenced cell
+    pub fn load_ref(&mut self) -> Result<usize, TonError> {
+        let target = *self.cell_ref().refs.get(self.next_ref).ok_or_else(|| self.underflow())?;
+        self.next_ref += 1;
+        Ok(target)
+    }
+
+    /// A `VarUInteger n`: a length in bytes, then the value
+    pub fn load_var_uint(&mut self, n: usize) -> Result<u128, TonError> {
+        let len_bits = usize::BITS as usize - (n - 1).leading_zeros() as usize;
+        let len = self.load_uint(len_bits)? as usize;
+        if len > 16 {
+            return Err(TonError::InvalidCell(format!("VarUInteger of {} bytes does not fit", len)));
+        }
+        (0..len).try_fold(0u128, |acc, _| Ok((acc << 8) | self.load_uint(8)? as u128))
+    }
+
+    /// A number of at most `max`, `#<= max` in TL-B
+    pub fn load_uint_leq(&mut self, max: u64) -> Result<u64, TonError> {
+        let bits = u64::BITS as usize - max.leading_zeros() as usize;
+        let value = self.load_uint(bits)?;
+        if value > max {
+            return Err(TonError::InvalidCell(format!("{} is above {}", value, max)));
+        }
+        Ok(value)
+    }
+
+    /// Fail unless every bit and reference has been read
+    pub fn expect_end(&self) -> Result<(), TonError> {
+        if self.remaining_bits() != 0 || self.remaining_refs() != 0 {
+            return Err(TonError::InvalidCell(format!("Cell {} has data left over", self.cell)));
+        }
+        Ok(())
+    }
+}
+
+/// CRC32-C, as bags of cells are checksummed
+pub fn crc32c(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0x82f6_3b78 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A cell to serialize into a bag
+    #[derive(Clone)]
+    pub(crate) struct TestCell {
+        pub exotic: bool,
+        pub bits: Vec<bool>,
+        pub refs: Vec<TestCell>,
+    }
+
+    impl TestCell {
+        pub(crate) fn new() -> Self {
+            Self { exotic: false, bits: Vec::new(), refs: Vec::new() }
+        }
+
+        pub(crate) fn uint(mut self, value: u64, bits: usize) -> Self {
+            self.bits.extend((0..bits).rev().map(|i| value >> i & 1 == 1));
+            self
+        }
+
+        pub(crate) fn bytes(mut self, bytes: &[u8]) -> Self {
+            for byte in bytes {
+                self = self.uint(*byte as u64, 8);
+            }
+            self
+        }
+
+        pub(crate) fn child(mut self, child: TestCell) -> Self {
+            self.refs.push(child);
+            self
+        }
+
+        /// A pruned branch standing in for `cell`
+        pub(crate) fn pruned(cell: &TestCell) -> Self {
+            Self::pruned_at(cell, 1)
+        }
+
+        /// A pruned branch of level `level`, as one under a Merkle update in a proof is of level 2
+        pub(crate) fn pruned_at(cell: &TestCell, level: u8) -> Self {
+            let boc = BagOfCells::parse(&cell.to_boc()).unwrap();
+            let root = boc.cell(boc.roots[0]);
+            let mut pruned = TestCell::new().uint(1, 8).uint(1 << (level - 1), 8).bytes(&root.hash(0));
+            pruned = pruned.uint(root.depth(0) as u64, 16);
+            pruned.exotic = true;
+            pruned
+        }
+
+        /// A Merkle proof wrapping `cell`, which has `hash` at level 0 once unpruned
+        pub(crate) fn merkle_proof(cell: TestCell, hash: [u8; 32], depth: u16) -> Self {
+            let mut proof = TestCell::new().uint(3, 8).bytes(&hash).uint(depth as u64, 16).child(cell);
+            proof.exotic = true;
+            proof
+        }
+
+        fn flatten(&self, out: &mut Vec<(bool, Vec<bool>, Vec<usize>)>) -> usize {
+            let index = out.len();
+            out.push((self.exotic, self.bits.clone(), Vec::new()));
+            let refs = self.refs.iter().map(|child| child.flatten(out)).collect();
+            out[index].2 = refs;
+            index
+        }
+
+        /// Serialize as the only root of a bag, with a checksum
+        pub(crate) fn to_boc(&self) -> Vec<u8> {
+            to_boc(core::slice::from_ref(self))
+        }
+    }
+
+    /// Serialize trees as the roots of one bag
+    pub(crate) fn to_boc(roots: &[TestCell]) -> Vec<u8> {
+        let mut cells = Vec::new();
+        let root_indexes: Vec<usize> = roots.iter().map(|root| root.flatten(&mut cells)).collect();
+        let mut data = Vec::new();
+        for (exotic, bits, refs) in &cells {
+            let d1 = refs.len() as u8 + 8 * *exotic as u8;
+            let d2 = (bits.len() / 8 + bits.len().div_ceil(8)) as u8;
+            data.extend_from_slice(&[d1, d2]);
+            let mut bytes = vec![0u8; bits.len().div_ceil(8)];
+            for (i, bit) in bits.iter().enumerate() {
+                bytes[i / 8] |= (*bit as u8) << (7 - i % 8);
+            }
+            if !bits.len().is_multiple_of(8) {
+                bytes[bits.len() / 8] |= 0x80 >> (bits.len() % 8);
+            }
+            data.extend_from_slice(&bytes);
+            data.extend(refs.iter().map(|r| *r as u8));
+        }
+        let mut boc = BOC_MAGIC.to_vec();
+        boc.extend_from_slice(&[0x41, 2]);
+        boc.extend_from_slice(&[cells.len() as u8, roots.len() as u8, 0]);
+        boc.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        boc.extend(root_indexes.iter().map(|r| *r as u8));
+        boc.extend_from_slice(&data);
+        let crc = crc32c(&boc);
+        boc.extend_from_slice(&crc.to_le_bytes());
+        boc
+    }
+
+    #[test]
+    fn test_cell_hashes() {
+        let empty = BagOfCells::parse(&TestCell::new().to_boc()).unwrap();
+        assert_eq!(
+            hex::encode(empty.cell(0).repr_hash()),
+            "96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7"
+        );
+        assert_eq!(crc32c(b"123456789"), 0xe306_9283);
+
+        let tree = TestCell::new()
+            .uint(0x1234, 16)
+            .child(TestCell::new().uint(5, 3).child(TestCell::new().bytes(&[7; 40])))
+            .child(TestCell::new().uint(1, 1));
+        let boc = BagOfCells::parse(&tree.to_boc()).unwrap();
+        let root = boc.cell(boc.roots[0]);
+        assert_eq!((root.level_mask, root.depth(0)), (0, 2));
+        let mut slice = boc.slice(boc.roots[0]).unwrap();
+        assert_eq!(slice.load_uint(16).unwrap(), 0x1234);
+        let child = slice.load_ref().unwrap();
+        assert_eq!(boc.slice(child).unwrap().load_uint(3).unwrap(), 5);
+
+        let mut corrupted = tree.to_boc();
+        corrupted[20] ^= 1;
+        assert!(BagOfCells::parse(&corrupted).is_err());
+    }
+
+    #[test]
+    fn test_merkle_proof_hashes() {
+        let subtree = TestCell::new().uint(5, 3).child(TestCell::new().bytes(&[7; 40]));
+        let full = TestCell::new().uint(0x1234, 16).child(subtree.clone()).child(TestCell::new().uint(1, 1));
+        let full_boc = BagOfCells::parse(&full.to_boc()).unwrap();
+        let full_root = full_boc.cell(full_boc.roots[0]);
+        let (hash, depth) = (full_root.hash(0), full_root.depth(0));
+
+        // The same tree with the subtree pruned still has the full tree's level-0 hash
+        let pruned = TestCell::new().uint(0x1234, 16).child(TestCell::pruned(&subtree)).child(TestCell::new().uint(1, 1));
+        let proof = BagOfCells::parse(&TestCell::merkle_proof(pruned.clone(), hash, depth).to_boc()).unwrap();
+        let inner = proof.open_merkle_proof(proof.roots[0], &hash).unwrap();
+        assert_eq!(proof.cell(inner).level_mask, 1);
+        assert_ne!(proof.cell(inner).hash(1), hash);
+        assert_eq!(proof.cell(proof.roots[0]).level_mask, 0);
+
+        // Reading into the pruned subtree fails
+        let mut slice = proof.slice(inner).unwrap();
+        slice.skip_bits(16).unwrap();
+        assert!(proof.slice(slice.load_ref().unwrap()).is_err());
+
+        assert!(proof.open_merkle_proof(proof.roots[0], &[0; 32]).is_err());
+        let lying = BagOfCells::parse(&TestCell::merkle_proof(pruned, [1; 32], depth).to_boc()).unwrap();
+        assert!(lying.open_merkle_proof(lying.roots[0], &[1; 32]).is_err());
+    }
+}
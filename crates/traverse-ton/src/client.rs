@@ -0,0 +1,147 @@
+//! Account proofs through a liteserver gateway
+//!
+//! Liteservers speak ADNL, an encrypted UDP/TCP protocol this crate does
+//! not implement. The fetcher instead talks to a gateway that relays
+//! liteserver queries as JSON-RPC: the method is the TL function's name,
+//! `liteServer.getMasterchainInfo` or `liteServer.getAccountState`, the
+//! params are the function's fields, and `bytes` and `int256` fields are
+//! base64 both ways. Whatever the gateway returns is verified here, so it
+//! need not be trusted; which masterchain block to read at does.
+
+use crate::address::TonAddress;
+use crate::proof::{field, str_field, BlockIdExt, TonAccountProof};
+use crate::TonError;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Chain-specific configuration for proof fetching
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TonChainConfig {
+    /// API key sent as `X-API-Key`, for gateways that want one
+    pub api_key: Option<String>,
+}
+
+/// Account proof fetcher for a liteserver gateway
+#[derive(Debug, Clone)]
+pub struct TonProofFetcher {
+    pub rpc_url: String,
+    pub config: TonChainConfig,
+}
+
+impl TonProofFetcher {
+    pub fn new(rpc_url: String, config: TonChainConfig) -> Self {
+        Self { rpc_url, config }
+    }
+
+    pub fn with_defaults(rpc_url: String) -> Self {
+        Self::new(rpc_url, TonChainConfig::default())
+    }
+
+    /// The latest masterchain block the liteserver knows
+    pub async fn masterchain_block(&self) -> Result<BlockIdExt, TonError> {
+        let info = self.call("liteServer.getMasterchainInfo", json!({})).await?;
+        block_from_tl(field(&info, "last")?)
+    }
+
+    /// Fetch the proof of an account's state at a masterchain block, the latest if none is given
+    pub async fn fetch_proof(
+        &self,
+        address: &TonAddress,
+        mc_block: Option<BlockIdExt>,
+    ) -> Result<TonAccountProof, TonError> {
+        let mc_block = match mc_block {
+            Some(block) => block,
+            None => self.masterchain_block().await?,
+        };
+        let params = json!({
+            "id": block_to_tl(&mc_block),
+            "account": { "workchain": address.workchain, "id": BASE64.encode(address.hash) },
+        });
+        let state = self.call("liteServer.getAccountState", params).await?;
+        if block_from_tl(field(&state, "id")?)? != mc_block {
+            return Err(TonError::Rpc("getAccountState answered at another block".into()));
+        }
+        let bytes = |name: &str| BASE64.decode(str_field(&state, name)?).map_err(|e| TonError::Rpc(e.to_string()));
+
+        let proof = TonAccountProof {
+            address: *address,
+            mc_block,
+            shard_block: block_from_tl(field(&state, "shardblk")?)?,
+            shard_proof: bytes("shard_proof")?,
+            proof: bytes("proof")?,
+            state: bytes("state")?,
+        };
+        proof.verify()?;
+        Ok(proof)
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value, TonError> {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let mut builder = reqwest::Client::new().post(&self.rpc_url).json(&request);
+        if let Some(key) = &self.config.api_key {
+            builder = builder.header("X-API-Key", key);
+        }
+        let response: Value = builder.send().await?.json().await?;
+        if let Some(error) = response.get("error").filter(|error| !error.is_null()) {
+            return Err(TonError::Rpc(format!("{}: {}", method, error)));
+        }
+        Ok(response["result"].clone())
+    }
+}
+
+/// A `tonNode.blockIdExt`, its shard a signed 64-bit number as TL has it
+fn block_to_tl(block: &BlockIdExt) -> Value {
+    json!({
+        "workchain": block.workchain,
+        "shard": (block.shard as i64).to_string(),
+        "seqno": block.seqno,
+        "root_hash": BASE64.encode(block.root_hash),
+        "file_hash": BASE64.encode(block.file_hash),
+    })
+}
+
+fn block_from_tl(value: &Value) -> Result<BlockIdExt, TonError> {
+    let number = |name: &str| -> Result<i64, TonError> {
+        let value = field(value, name)?;
+        value
+            .as_i64()
+            .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+            .ok_or_else(|| TonError::Rpc(format!("Field '{}' is not a number", name)))
+    };
+    let hash = |name: &str| -> Result<[u8; 32], TonError> {
+        BASE64
+            .decode(str_field(value, name)?)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| TonError::Rpc(format!("Field '{}' is not a hash", name)))
+    };
+    Ok(BlockIdExt {
+        workchain: i32::try_from(number("workchain")?).map_err(|_| TonError::Rpc("Invalid workchain".into()))?,
+        shard: number("shard")? as u64,
+        seqno: u32::try_from(number("seqno")?).map_err(|_| TonError::Rpc("Invalid seqno".into()))?,
+        root_hash: hash("root_hash")?,
+        file_hash: hash("file_hash")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proof::FULL_SHARD;
+
+    #[test]
+    fn test_block_ids() {
+        let block = BlockIdExt { workchain: -1, shard: FULL_SHARD, seqno: 100, root_hash: [1; 32], file_hash: [2; 32] };
+        let tl = block_to_tl(&block);
+        assert_eq!(tl["shard"], "-9223372036854775808");
+        assert_eq!(block_from_tl(&tl).unwrap(), block);
+
+        let mut numeric = tl.clone();
+        numeric["shard"] = json!(i64::MIN);
+        assert_eq!(block_from_tl(&numeric).unwrap(), block);
+        numeric["root_hash"] = json!(BASE64.encode([1; 31]));
+        assert!(block_from_tl(&numeric).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+//! Dictionary lookups
+//!
+//! TL-B dictionaries (`Hashmap`, `HashmapAug`) are binary Patricia trees
+//! over fixed-length keys. Each edge starts with a label holding the next
+//! bits of the key in one of three encodings; a fork then refers to two
+//! subtrees for the next bit being 0 or 1, and a leaf holds the value. An
+//! augmented dictionary adds an extra value to every node, after the label
+//! in a leaf and after the references in a fork, which lookups skip past.
+
+use crate::cell::{BagOfCells, CellSlice};
+use crate::TonError;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+/// Look up `key`, a `key_bits`-bit key left-aligned in its bytes, in the
+/// dictionary whose root edge is the cell `root`
+///
+/// Returns the leaf positioned after its label, where an augmented
+/// dictionary's extra and then the value start, or `None` when the
+/// dictionary shows the key is absent. Running into a pruned branch is an
+/// error, since the proof then shows neither.
+pub fn hashmap_get<'a>(
+    boc: &'a BagOfCells,
+    root: usize,
+    key: &[u8],
+    key_bits: usize,
+) -> Result<Option<CellSlice<'a>>, TonError> {
+    let key: Vec<bool> = (0..key_bits).map(|i| key[i / 8] >> (7 - i % 8) & 1 == 1).collect();
+    let mut slice = boc.slice(root)?;
+    let mut at = 0;
+    loop {
+        let remaining = key_bits - at;
+        let label = load_label(&mut slice, remaining)?;
+        if key[at..at + label.len()] != label[..] {
+            return Ok(None);
+        }
+        at += label.len();
+        if at == key_bits {
+            return Ok(Some(slice));
+        }
+        let left = slice.load_ref()?;
+        let right = slice.load_ref()?;
+        slice = boc.slice(if key[at] { right } else { left })?;
+        at += 1;
+    }
+}
+
+/// An edge label, for an edge with at most `max` key bits left
+fn load_label(slice: &mut CellSlice<'_>, max: usize) -> Result<Vec<bool>, TonError> {
+    let length_bits = |slice: &mut CellSlice<'_>| slice.load_uint_leq(max as u64).map(|n| n as usize);
+    let label = if !slice.load_bit()? {
+        // hml_short$0: the length in unary, then the bits
+        let mut len = 0;
+        while slice.load_bit()? {
+            len += 1;
+        }
+        (0..len).map(|_| slice.load_bit()).collect::<Result<Vec<_>, _>>()?
+    } else if !slice.load_bit()? {
+        // hml_long$10: the length in binary, then the bits
+        let len = length_bits(slice)?;
+        (0..len).map(|_| slice.load_bit()).collect::<Result<Vec<_>, _>>()?
+    } else {
+        // hml_same$11: one bit, repeated
+        let bit = slice.load_bit()?;
+        let mut label = Vec::new();
+        label.resize(length_bits(slice)?, bit);
+        label
+    };
+    if label.len() > max {
+        return Err(TonError::InvalidCell("A dictionary label is longer than its key".into()));
+    }
+    Ok(label)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::cell::tests::TestCell;
+
+    /// Append the label `bits`, for an edge with at most `max` key bits left, in the long form
+    pub(crate) fn long_label(cell: TestCell, bits: &[bool], max: usize) -> TestCell {
+        let width = usize::BITS as usize - max.leading_zeros() as usize;
+        let mut cell = cell.uint(0b10, 2).uint(bits.len() as u64, width);
+        cell.bits.extend_from_slice(bits);
+        cell
+    }
+
+    pub(crate) fn key_bits(key: &[u8], bits: usize) -> Vec<bool> {
+        (0..bits).map(|i| key[i / 8] >> (7 - i % 8) & 1 == 1).collect()
+    }
+
+    #[test]
+    fn test_hashmap_lookup() {
+        // Keys 0x12 and 0x1f differ first at their fifth bit
+        let a = long_label(TestCell::new(), &key_bits(&[0x12], 8)[5..], 3).uint(0xaa, 8);
+        let b = long_label(TestCell::new(), &key_bits(&[0x1f], 8)[5..], 3).uint(0xbb, 8);
+        // The root's label is the shared prefix 0001, in the short form
+        let root = TestCell::new().uint(0, 1).uint(0b11110, 5).uint(0b0001, 4).child(a).child(b);
+        let boc = BagOfCells::parse(&root.to_boc()).unwrap();
+
+        let mut value = hashmap_get(&boc, 0, &[0x12], 8).unwrap().unwrap();
+        assert_eq!(value.load_uint(8).unwrap(), 0xaa);
+        let mut value = hashmap_get(&boc, 0, &[0x1f], 8).unwrap().unwrap();
+        assert_eq!(value.load_uint(8).unwrap(), 0xbb);
+        assert!(hashmap_get(&boc, 0, &[0x13], 8).unwrap().is_none());
+        assert!(hashmap_get(&boc, 0, &[0x22], 8).unwrap().is_none());
+
+        // A label of repeated bits
+        let same = TestCell::new().uint(0b11, 2).uint(1, 1).uint(8, 4).uint(7, 3);
+        let boc = BagOfCells::parse(&same.to_boc()).unwrap();
+        assert_eq!(hashmap_get(&boc, 0, &[0xff], 8).unwrap().unwrap().load_uint(3).unwrap(), 7);
+        assert!(hashmap_get(&boc, 0, &[0xfe], 8).unwrap().is_none());
+    }
+}
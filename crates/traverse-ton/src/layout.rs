@@ -0,0 +1,201 @@
+//! Layout compilation from TL-B schemas
+//!
+//! Each field reachable from the data cell's type becomes an entry labelled
+//! by its path, `balance` or `config.fee.bps`. Its slot names the record
+//! the field belongs to: the data cell's type for top-level fields, the
+//! path of the enclosing field otherwise. Its offset is the field's index
+//! in its constructor; TL-B packs fields bit by bit, and where a field
+//! starts depends on the values before it, so entries record order, not
+//! position. Each declared type gets a type entry whose `base` is its
+//! constructor's tag, which decoding reads past.
+
+use crate::tlb::{named_type, Constructor, TlbSchema, TlbType, TonStorageDefinition};
+use crate::TonError;
+use traverse_core::{LayoutInfo, StorageEntry, TypeInfo, ZeroSemantics};
+
+#[cfg(feature = "std")]
+use std::path::Path;
+
+#[cfg(feature = "std")]
+use traverse_core::{LayoutCompiler, TraverseError};
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Encoding recorded for values packed as TL-B
+pub const TLB_ENCODING: &str = "tlb";
+
+/// Layout compiler for TON contracts
+///
+/// # Usage
+///
+/// ```rust,ignore
+/// use traverse_ton::TonLayoutCompiler;
+/// use traverse_core::LayoutCompiler;
+///
+/// // A JSON storage definition, or a plain .tlb file whose last type is the data cell's
+/// let layout = TonLayoutCompiler.compile_layout(Path::new("jetton_wallet.tlb"))?;
+/// assert!(layout.storage.iter().any(|e| e.label == "balance"));
+/// ```
+pub struct TonLayoutCompiler;
+
+impl TonLayoutCompiler {
+    /// Compile a parsed storage definition
+    ///
+    /// The layout is named after the contract's address, or after the
+    /// definition's name when it gives no address.
+    pub fn compile_definition(definition: &TonStorageDefinition) -> Result<LayoutInfo, TonError> {
+        let (schema, root) = definition.parse_schema()?;
+        let mut layout = LayoutInfo {
+            contract_name: match &definition.contract {
+                Some(address) => crate::TonAddress::parse(address)?.to_string(),
+                None => definition.name.clone(),
+            },
+            storage: Vec::new(),
+            types: Vec::new(),
+            code_variables: Vec::new(),
+        };
+        let constructor = schema.constructor(&root).expect("checked by parse_schema");
+        add_record(&schema, constructor, &root, "", &mut layout)?;
+        Ok(layout)
+    }
+}
+
+/// Add the fields of `constructor` as members of the record at `path`, its entries' slot being `slot`
+fn add_record(
+    schema: &TlbSchema,
+    constructor: &Constructor,
+    slot: &str,
+    path: &str,
+    layout: &mut LayoutInfo,
+) -> Result<(), TonError> {
+    add_type(layout, &constructor.type_name, 0, Some(constructor.tag.clone()));
+    for (index, field) in constructor.fields.iter().enumerate() {
+        let offset = u8::try_from(index)
+            .map_err(|_| TonError::InvalidSchema(format!("{} has more than 256 fields", constructor.type_name)))?;
+        // Anonymous fields are named by their position
+        let name = if field.name == "_" { format!("_{}", index) } else { field.name.clone() };
+        let label = if path.is_empty() { name } else { format!("{}.{}", path, name) };
+        layout.storage.push(StorageEntry {
+            label: label.clone(),
+            slot: slot.to_string(),
+            offset,
+            type_name: field.type_expr.clone(),
+            // A field always holds a value, and zero is one
+            zero_semantics: ZeroSemantics::ValidZero,
+        });
+        if let Some(named) = named_type(&field.ty) {
+            let inner = schema.constructor(named).expect("checked by TlbSchema::parse");
+            add_record(schema, inner, &label, &label, layout)?;
+        }
+        let bytes = field.ty.fixed_bits().map_or(0, |bits| bits.div_ceil(8));
+        add_type(layout, &field.type_expr, bytes, None);
+    }
+    Ok(())
+}
+
+fn add_type(layout: &mut LayoutInfo, label: &str, bytes: usize, base: Option<String>) {
+    if !layout.types.iter().any(|t| t.label == label) {
+        layout.types.push(TypeInfo {
+            label: label.to_string(),
+            // 0 for types whose size depends on their value
+            number_of_bytes: bytes.to_string(),
+            encoding: TLB_ENCODING.to_string(),
+            base,
+            key: None,
+            value: None,
+        });
+    }
+}
+
+/// The record type a layout's top-level fields belong to
+pub(crate) fn root_type(layout: &LayoutInfo) -> Result<&str, TonError> {
+    layout
+        .storage
+        .iter()
+        .find(|e| !e.label.contains('.'))
+        .map(|e| e.slot.as_str())
+        .ok_or_else(|| TonError::InvalidQuery(format!("{} has no fields", layout.contract_name)))
+}
+
+/// The tag of a record type
+pub(crate) fn type_tag<'a>(layout: &'a LayoutInfo, type_name: &str) -> Result<&'a str, TonError> {
+    layout
+        .types
+        .iter()
+        .find(|t| t.label == type_name)
+        .and_then(|t| t.base.as_deref())
+        .ok_or_else(|| TonError::InvalidQuery(format!("The layout does not declare {}", type_name)))
+}
+
+/// The type of a field's record, when it holds one
+pub(crate) fn record_type(entry: &StorageEntry) -> Result<Option<String>, TonError> {
+    Ok(named_type(&TlbType::parse(&entry.type_name)?).map(|name| name.to_string()))
+}
+
+#[cfg(feature = "std")]
+impl LayoutCompiler for TonLayoutCompiler {
+    /// Compile a layout from a JSON storage definition, or from a `.tlb` file
+    fn compile_layout(&self, definition_path: &Path) -> Result<LayoutInfo, TraverseError> {
+        let content = std::fs::read_to_string(definition_path)?;
+        let definition = if definition_path.extension().is_some_and(|ext| ext == "tlb") {
+            let name = definition_path.file_stem().and_then(|s| s.to_str()).unwrap_or("Contract");
+            TonStorageDefinition::from_tlb(name, &content)?
+        } else {
+            TonStorageDefinition::from_json(&content)?
+        };
+        Ok(Self::compile_definition(&definition)?)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::tlb::tests::WALLET_SCHEMA;
+
+    pub(crate) fn wallet_layout() -> LayoutInfo {
+        let definition = TonStorageDefinition::from_tlb("JettonWallet", WALLET_SCHEMA).unwrap();
+        TonLayoutCompiler::compile_definition(&definition).unwrap()
+    }
+
+    #[test]
+    fn test_compile_definition() {
+        let layout = wallet_layout();
+        assert_eq!(layout.contract_name, "JettonWallet");
+
+        let entries: Vec<_> = layout.storage.iter().map(|e| (e.label.as_str(), e.slot.as_str(), e.offset)).collect();
+        assert_eq!(entries, [
+            ("balance", "Storage", 0),
+            ("owner", "Storage", 1),
+            ("master", "Storage", 2),
+            ("wallet_code", "Storage", 3),
+            ("config", "Storage", 4),
+            ("config.admin", "config", 0),
+            ("config.fee", "config", 1),
+            ("config.fee.bps", "config.fee", 0),
+            ("config.fee.recipient", "config.fee", 1),
+            ("config.paused", "config", 2),
+            ("nonce", "Storage", 5),
+        ]);
+        assert_eq!(root_type(&layout).unwrap(), "Storage");
+        assert_eq!(type_tag(&layout, "Config").unwrap(), "#3f5476ca");
+        assert_eq!(type_tag(&layout, "Fee").unwrap(), "$01");
+        assert_eq!(record_type(&layout.storage[4]).unwrap().as_deref(), Some("Config"));
+
+        let type_info = |label: &str| layout.types.iter().find(|t| t.label == label).unwrap();
+        assert_eq!(type_info("uint16").number_of_bytes, "2");
+        assert_eq!(type_info("Coins").number_of_bytes, "0");
+        assert_eq!(type_info("int32").encoding, TLB_ENCODING);
+
+        let definition = TonStorageDefinition {
+            contract: Some("0:".to_string() + &"ab".repeat(32)),
+            ..TonStorageDefinition::from_tlb("JettonWallet", WALLET_SCHEMA).unwrap()
+        };
+        let layout = TonLayoutCompiler::compile_definition(&definition).unwrap();
+        assert_eq!(layout.contract_name, format!("0:{}", "ab".repeat(32)));
+    }
+}
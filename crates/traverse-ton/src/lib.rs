@@ -0,0 +1,115 @@
+//! Account state layouts and bag-of-cells proofs for TON
+//!
+//! A TON contract keeps its persistent data in a tree of cells hung off its
+//! account, and the tree's shape is whatever the contract's TL-B schema
+//! says: fields are packed bit by bit, and larger values sit behind
+//! references. An account is a leaf of its shard state's accounts
+//! dictionary, the shard state is committed to by its block, and a
+//! basechain shard block is in turn recorded in the masterchain state. A
+//! liteserver proves an account with Merkle proof cells along that path,
+//! every other branch pruned, so a value is proven against a masterchain
+//! block's root hash. This crate maps that model onto the traverse core
+//! types.
+//!
+//! # Features
+//!
+//! - **Cells**: Parse bags of cells and compute cell hashes, pruned branches included
+//! - **Layout Compilation**: Turn a contract's TL-B storage schema into a layout of field paths
+//! - **Query Resolution**: Resolve `field` and `ref_field.member` queries and decode their values
+//! - **Proof Verification**: Check account proofs from a masterchain block down to the account's data
+//! - **Proof Fetching**: Read account proofs through a liteserver gateway (`client`)
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! use traverse_ton::{TonAccountProof, TonKeyResolver, TonLayoutCompiler};
+//! use traverse_core::LayoutCompiler;
+//!
+//! let layout = TonLayoutCompiler.compile_layout(Path::new("jetton_wallet.tlb"))?;
+//! let account = proof.verify()?;
+//! let balance = TonKeyResolver::decode_field(&layout, &account, "balance")?;
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(not(feature = "std"))]
+use alloc::string::{String, ToString};
+
+pub mod account;
+pub mod address;
+pub mod cell;
+pub mod hashmap;
+pub mod layout;
+pub mod proof;
+pub mod resolver;
+pub mod tlb;
+
+#[cfg(feature = "client")]
+pub mod client;
+
+pub use account::{AccountStatus, TonAccount};
+pub use address::TonAddress;
+pub use cell::{crc32c, BagOfCells, Cell, CellKind, CellSlice};
+pub use hashmap::hashmap_get;
+pub use layout::{TonLayoutCompiler, TLB_ENCODING};
+pub use proof::{BlockIdExt, TonAccountProof, VerifiedAccount};
+pub use resolver::{FieldLocation, TlbValue, TonKeyResolver};
+pub use tlb::{Constructor, TlbField, TlbSchema, TlbType, TonStorageDefinition};
+
+#[cfg(feature = "client")]
+pub use client::{TonChainConfig, TonProofFetcher};
+
+/// Error types specific to TON
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+#[derive(Debug)]
+pub enum TonError {
+    #[cfg_attr(feature = "std", error("Invalid cell: {0}"))]
+    InvalidCell(String),
+
+    #[cfg_attr(feature = "std", error("Invalid TL-B schema: {0}"))]
+    InvalidSchema(String),
+
+    #[cfg_attr(feature = "std", error("Invalid query: {0}"))]
+    InvalidQuery(String),
+
+    #[cfg_attr(feature = "std", error("Invalid address: {0}"))]
+    InvalidAddress(String),
+
+    #[cfg_attr(feature = "std", error("Proof verification failed: {0}"))]
+    ProofVerification(String),
+
+    #[cfg_attr(feature = "std", error("RPC error: {0}"))]
+    Rpc(String),
+
+    #[cfg(feature = "client")]
+    #[cfg_attr(feature = "std", error("Network error: {0}"))]
+    Network(#[from] reqwest::Error),
+
+    #[cfg_attr(feature = "std", error("JSON error: {0}"))]
+    Json(#[cfg_attr(feature = "std", from)] serde_json::Error),
+
+    #[cfg_attr(feature = "std", error("Traverse core error: {0}"))]
+    TraverseCore(#[cfg_attr(feature = "std", from)] traverse_core::TraverseError),
+}
+
+impl From<TonError> for traverse_core::TraverseError {
+    fn from(err: TonError) -> Self {
+        use traverse_core::TraverseError;
+
+        match err {
+            TonError::InvalidSchema(msg) => TraverseError::LayoutCompilation(msg),
+            TonError::InvalidCell(msg) | TonError::InvalidQuery(msg) | TonError::InvalidAddress(msg) => {
+                TraverseError::InvalidInput(msg)
+            }
+            TonError::ProofVerification(msg) => TraverseError::Validation(msg),
+            TonError::Rpc(msg) => TraverseError::ExternalService(msg),
+            #[cfg(feature = "client")]
+            TonError::Network(err) => TraverseError::ExternalService(err.to_string()),
+            TonError::Json(err) => TraverseError::Serialization(err.to_string()),
+            TonError::TraverseCore(err) => err,
+        }
+    }
+}
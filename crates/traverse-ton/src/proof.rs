@@ -0,0 +1,581 @@
+//! Account state proofs
+//!
+//! A liteserver answers `getAccountState` with three bags of cells. `proof`
+//! has two roots: a Merkle proof of the account's shard block, open down to
+//! the block's state update, and a Merkle proof of the shard state the
+//! update leads to, open down to the account's leaf in the accounts
+//! dictionary. `state` is the account cell itself, which the leaf commits
+//! to by hash. For a basechain account, `shard_proof` links the shard block
+//! to the masterchain block the query was made against: a Merkle proof of
+//! that block and one of its state, open down to the shard's description,
+//! which records the shard block's hashes. A masterchain account's shard
+//! block is the masterchain block itself.
+//!
+//! The proof checked here ends at a masterchain block's root hash; that
+//! the block is final, and on the chain the caller follows, has to come
+//! from elsewhere (a key block chain, or a trusted liteserver).
+
+use crate::account::TonAccount;
+use crate::address::TonAddress;
+use crate::cell::{BagOfCells, CellKind, CellSlice};
+use crate::hashmap::hashmap_get;
+use crate::resolver::TonKeyResolver;
+use crate::TonError;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use traverse_core::LayoutInfo;
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::ToString, vec::Vec};
+
+const BLOCK_TAG: u64 = 0x11ef_55aa;
+const BLOCK_INFO_TAG: u64 = 0x9bc7_a987;
+const SHARD_STATE_TAG: u64 = 0x9023_afe2;
+const MC_STATE_EXTRA_TAG: u64 = 0xcc26;
+
+/// The shard that covers a whole workchain
+pub const FULL_SHARD: u64 = 0x8000_0000_0000_0000;
+
+/// A block's full identity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockIdExt {
+    pub workchain: i32,
+    /// The shard's prefix, followed by a 1 bit and zeros
+    pub shard: u64,
+    pub seqno: u32,
+    pub root_hash: [u8; 32],
+    pub file_hash: [u8; 32],
+}
+
+/// An account's state with the proof of it, as a liteserver returns them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TonAccountProof {
+    pub address: TonAddress,
+    /// The masterchain block the state was read at
+    pub mc_block: BlockIdExt,
+    /// The block of the account's shard; the masterchain block for masterchain accounts
+    pub shard_block: BlockIdExt,
+    /// Bag of cells linking the shard block to the masterchain block; empty for masterchain accounts
+    pub shard_proof: Vec<u8>,
+    /// Bag of cells proving the account's leaf in the shard block's state
+    pub proof: Vec<u8>,
+    /// Bag of cells holding the account; may be empty when the account does not exist
+    pub state: Vec<u8>,
+}
+
+/// What a valid proof establishes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedAccount {
+    pub address: TonAddress,
+    pub mc_block: BlockIdExt,
+    pub shard_block: BlockIdExt,
+    /// Hash of the account cell; that of `account_none` when the account does not exist
+    pub account_hash: [u8; 32],
+    pub last_trans_hash: [u8; 32],
+    pub last_trans_lt: u64,
+    /// The account, when it exists
+    pub account: Option<TonAccount>,
+    /// The account's cells, all of them present
+    pub state: BagOfCells,
+}
+
+impl VerifiedAccount {
+    /// Index of the account's data cell in [`Self::state`], when it is active and has one
+    pub fn data_cell(&self) -> Option<usize> {
+        self.account.as_ref().and_then(TonAccount::data_cell)
+    }
+}
+
+impl BlockIdExt {
+    fn to_json(self) -> Value {
+        json!({
+            "workchain": self.workchain,
+            "shard": format!("{:016x}", self.shard),
+            "seqno": self.seqno,
+            "root_hash": hex::encode(self.root_hash),
+            "file_hash": hex::encode(self.file_hash),
+        })
+    }
+
+    fn from_json(value: &Value) -> Result<Self, TonError> {
+        let int = |name: &str| field(value, name)?.as_i64().ok_or_else(|| not_a(name, "number"));
+        let hash = |name: &str| -> Result<[u8; 32], TonError> {
+            let mut hash = [0u8; 32];
+            hex::decode_to_slice(str_field(value, name)?, &mut hash).map_err(|_| not_a(name, "hash"))?;
+            Ok(hash)
+        };
+        Ok(Self {
+            workchain: i32::try_from(int("workchain")?).map_err(|_| not_a("workchain", "32-bit number"))?,
+            shard: u64::from_str_radix(str_field(value, "shard")?, 16).map_err(|_| not_a("shard", "shard ID"))?,
+            seqno: u32::try_from(int("seqno")?).map_err(|_| not_a("seqno", "32-bit number"))?,
+            root_hash: hash("root_hash")?,
+            file_hash: hash("file_hash")?,
+        })
+    }
+}
+
+impl TonAccountProof {
+    /// Check the proof from the masterchain block down to the account
+    pub fn verify(&self) -> Result<VerifiedAccount, TonError> {
+        if self.address.workchain == TonAddress::MASTERCHAIN || self.shard_block == self.mc_block {
+            if self.shard_block != self.mc_block || self.mc_block.workchain != TonAddress::MASTERCHAIN {
+                return Err(TonError::ProofVerification(
+                    "A masterchain account must be read at a masterchain block".into(),
+                ));
+            }
+        } else {
+            self.verify_shard_proof()?;
+        }
+        if self.shard_block.workchain != self.address.workchain || !shard_contains(self.shard_block.shard, &self.address.hash) {
+            return Err(TonError::ProofVerification(format!("Block is not of account {}'s shard", self.address)));
+        }
+
+        let proof = BagOfCells::parse(&self.proof)?;
+        let state_hash = verify_block_proof(&proof, proof.root(0)?, &self.shard_block)?;
+        let state = proof.open_merkle_proof(proof.root(1)?, &state_hash)?;
+        let (accounts, _) = parse_shard_state(&proof, state, &self.shard_block)?;
+
+        let mut accounts = proof.slice(accounts)?;
+        let leaf = if accounts.load_bit()? {
+            hashmap_get(&proof, accounts.load_ref()?, &self.address.hash, 256)?
+        } else {
+            None
+        };
+
+        let Some(mut leaf) = leaf else {
+            // The dictionary shows the account does not exist
+            let state = if self.state.is_empty() { account_none_boc() } else { BagOfCells::parse(&self.state)? };
+            let account_hash = state.cell(state.root(0)?).hash(0);
+            if account_hash != account_none_hash() {
+                return Err(TonError::ProofVerification(format!("Account {} does not exist", self.address)));
+            }
+            return Ok(VerifiedAccount {
+                address: self.address,
+                mc_block: self.mc_block,
+                shard_block: self.shard_block,
+                account_hash,
+                last_trans_hash: [0; 32],
+                last_trans_lt: 0,
+                account: None,
+                state,
+            });
+        };
+
+        // DepthBalanceInfo, then ShardAccount
+        leaf.load_uint_leq(30)?;
+        leaf.load_var_uint(16)?;
+        if leaf.load_bit()? {
+            leaf.load_ref()?;
+        }
+        let account_hash = proof.cell(leaf.load_ref()?).hash(0);
+        let last_trans_hash = leaf.load_hash()?;
+        let last_trans_lt = leaf.load_uint(64)?;
+
+        let state = BagOfCells::parse(&self.state)?;
+        let root = state.root(0)?;
+        if state.cell(root).hash(0) != account_hash {
+            return Err(TonError::ProofVerification(format!("The state is not account {}'s", self.address)));
+        }
+        if state.cells.iter().any(|cell| cell.kind == CellKind::PrunedBranch) {
+            return Err(TonError::ProofVerification("The account's state is pruned".into()));
+        }
+        let account = TonAccount::parse(&state, root)?;
+        if let Some(account) = &account {
+            if account.address != self.address {
+                return Err(TonError::ProofVerification(format!("The state is account {}'s", account.address)));
+            }
+        }
+        Ok(VerifiedAccount {
+            address: self.address,
+            mc_block: self.mc_block,
+            shard_block: self.shard_block,
+            account_hash,
+            last_trans_hash,
+            last_trans_lt,
+            account,
+            state,
+        })
+    }
+
+    /// Check that the masterchain block records the shard block
+    fn verify_shard_proof(&self) -> Result<(), TonError> {
+        if self.mc_block.workchain != TonAddress::MASTERCHAIN {
+            return Err(TonError::ProofVerification("Proofs must start at a masterchain block".into()));
+        }
+        let boc = BagOfCells::parse(&self.shard_proof)?;
+        let state_hash = verify_block_proof(&boc, boc.root(0)?, &self.mc_block)?;
+        let state = boc.open_merkle_proof(boc.root(1)?, &state_hash)?;
+        let (_, extra) = parse_shard_state(&boc, state, &self.mc_block)?;
+        let extra = extra.ok_or_else(|| TonError::ProofVerification("The masterchain state has no shard list".into()))?;
+
+        let mut extra = boc.slice(extra)?;
+        if extra.load_uint(16)? != MC_STATE_EXTRA_TAG || !extra.load_bit()? {
+            return Err(TonError::ProofVerification("The masterchain state has no shard list".into()));
+        }
+        let workchain = self.shard_block.workchain.to_be_bytes();
+        let mut shards = hashmap_get(&boc, extra.load_ref()?, &workchain, 32)?
+            .ok_or_else(|| TonError::ProofVerification(format!("Workchain {} has no shards", self.shard_block.workchain)))?;
+
+        // Walk the workchain's binary tree of shards along the account's address
+        let mut node = boc.slice(shards.load_ref()?)?;
+        let mut depth = 0;
+        while node.load_bit()? {
+            if depth >= 60 {
+                return Err(TonError::ProofVerification("The shard tree is too deep".into()));
+            }
+            let bit = self.address.hash[depth / 8] >> (7 - depth % 8) & 1;
+            let left = node.load_ref()?;
+            let right = node.load_ref()?;
+            node = boc.slice(if bit == 1 { right } else { left })?;
+            depth += 1;
+        }
+        let prefix = u64::from_be_bytes(self.address.hash[..8].try_into().expect("8 bytes"));
+        let shard = (prefix & !(u64::MAX >> depth)) | (FULL_SHARD >> depth);
+
+        let tag = node.load_uint(4)?;
+        if tag != 0xa && tag != 0xb {
+            return Err(TonError::InvalidCell(format!("Unknown shard description tag {:x}", tag)));
+        }
+        let seqno = node.load_uint(32)? as u32;
+        node.skip_bits(32 + 64 + 64)?;
+        let root_hash = node.load_hash()?;
+        let file_hash = node.load_hash()?;
+        let recorded = BlockIdExt { workchain: self.shard_block.workchain, shard, seqno, root_hash, file_hash };
+        if recorded != self.shard_block {
+            return Err(TonError::ProofVerification(format!(
+                "Masterchain block {} records shard block {} of the account's shard, not {}",
+                self.mc_block.seqno, recorded.seqno, self.shard_block.seqno
+            )));
+        }
+        Ok(())
+    }
+
+    /// JSON form with hashes and bags of cells in hex
+    ///
+    /// Alongside the raw data it writes what a reader without cell hashing
+    /// needs: the account's hash, last transaction and balance, and the
+    /// hash of its data. They are taken from the verified proof, and left
+    /// out when it does not verify.
+    pub fn to_json(&self) -> Value {
+        let mut json = json!({
+            "address": self.address.to_string(),
+            "mc_block": self.mc_block.to_json(),
+            "shard_block": self.shard_block.to_json(),
+            "shard_proof": hex::encode(&self.shard_proof),
+            "proof": hex::encode(&self.proof),
+            "state": hex::encode(&self.state),
+        });
+        if let Ok(verified) = self.verify() {
+            json["account_hash"] = json!(hex::encode(verified.account_hash));
+            json["last_trans_hash"] = json!(hex::encode(verified.last_trans_hash));
+            json["last_trans_lt"] = json!(verified.last_trans_lt);
+            json["balance"] = json!(verified.account.as_ref().map_or(0, |a| a.balance).to_string());
+            json["data_hash"] = json!(verified.data_cell().map(|cell| hex::encode(verified.state.cell(cell).hash(0))));
+        }
+        json
+    }
+
+    /// JSON form with the values of `queries`, decoded from the account's data
+    ///
+    /// Each field also gets its location in the data, which a reader that
+    /// checks the proof itself reads the value at.
+    pub fn to_json_with_fields(&self, layout: &LayoutInfo, queries: &[&str]) -> Result<Value, TonError> {
+        let verified = self.verify()?;
+        let mut fields = Vec::new();
+        for query in queries {
+            let value = TonKeyResolver::decode_field(layout, &verified, query)?;
+            fields.push(json!({
+                "query": query,
+                "type": TonKeyResolver::field(layout, query)?.type_name,
+                "present": value.is_some(),
+                "value": value.as_ref().map(|v| v.to_json()),
+                "word": value.as_ref().and_then(|v| v.to_word()).map(hex::encode),
+                "location": TonKeyResolver::locate_field(layout, &verified, query)?.to_json(),
+            }));
+        }
+        let mut json = self.to_json();
+        json["fields"] = json!(fields);
+        Ok(json)
+    }
+
+    /// Parse the JSON written by [`Self::to_json`]
+    pub fn from_json(value: &Value) -> Result<Self, TonError> {
+        let bytes = |name: &str| hex::decode(str_field(value, name)?).map_err(|_| not_a(name, "hex string"));
+        Ok(Self {
+            address: TonAddress::parse(str_field(value, "address")?)?,
+            mc_block: BlockIdExt::from_json(field(value, "mc_block")?)?,
+            shard_block: BlockIdExt::from_json(field(value, "shard_block")?)?,
+            shard_proof: bytes("shard_proof")?,
+            proof: bytes("proof")?,
+            state: bytes("state")?,
+        })
+    }
+}
+
+/// Check a Merkle proof of `block` and return the hash of the state it leads to
+fn verify_block_proof(boc: &BagOfCells, root: usize, block: &BlockIdExt) -> Result<[u8; 32], TonError> {
+    let cell = boc.open_merkle_proof(root, &block.root_hash)?;
+    let mut slice = boc.slice(cell)?;
+    if slice.load_uint(32)? != BLOCK_TAG {
+        return Err(TonError::InvalidCell("Expected a block".into()));
+    }
+    let mut info = boc.slice(slice.load_ref()?)?;
+    if info.load_uint(32)? != BLOCK_INFO_TAG {
+        return Err(TonError::InvalidCell("Expected a block's info".into()));
+    }
+    // version, then eight flag bits and the flags byte
+    info.skip_bits(32 + 8 + 8)?;
+    let seqno = info.load_uint(32)? as u32;
+    info.skip_bits(32)?;
+    let (workchain, shard) = load_shard_ident(&mut info)?;
+    if (seqno, workchain, shard) != (block.seqno, block.workchain, block.shard) {
+        return Err(TonError::ProofVerification(format!("The block proven is not block {}", block.seqno)));
+    }
+
+    let _value_flow = slice.load_ref()?;
+    let update = boc.cell(slice.load_ref()?);
+    if update.kind != CellKind::MerkleUpdate {
+        return Err(TonError::InvalidCell("A block's state update must be a Merkle update".into()));
+    }
+    let new_state = boc.cell(update.refs[1]).hash(0);
+    if update.merkle_hash(1) != Some(new_state) {
+        return Err(TonError::ProofVerification("The state update's hashes disagree".into()));
+    }
+    Ok(new_state)
+}
+
+/// The accounts dictionary of a shard state, and for the masterchain its extra
+fn parse_shard_state(
+    boc: &BagOfCells,
+    cell: usize,
+    block: &BlockIdExt,
+) -> Result<(usize, Option<usize>), TonError> {
+    let mut slice = boc.slice(cell)?;
+    if slice.load_uint(32)? != SHARD_STATE_TAG {
+        return Err(TonError::InvalidCell("Expected an unsplit shard state".into()));
+    }
+    slice.skip_bits(32)?;
+    let (workchain, shard) = load_shard_ident(&mut slice)?;
+    let seqno = slice.load_uint(32)? as u32;
+    if (seqno, workchain, shard) != (block.seqno, block.workchain, block.shard) {
+        return Err(TonError::ProofVerification(format!("The state proven is not block {}'s", block.seqno)));
+    }
+    // vert_seq_no, gen_utime, gen_lt, min_ref_mc_seqno
+    slice.skip_bits(32 + 32 + 64 + 32)?;
+    let _out_msg_queue_info = slice.load_ref()?;
+    slice.skip_bits(1)?;
+    let accounts = slice.load_ref()?;
+    let _overload_history = slice.load_ref()?;
+    let custom = if slice.load_bit()? { Some(slice.load_ref()?) } else { None };
+    slice.expect_end()?;
+    Ok((accounts, custom))
+}
+
+fn load_shard_ident(slice: &mut CellSlice<'_>) -> Result<(i32, u64), TonError> {
+    if slice.load_uint(2)? != 0 {
+        return Err(TonError::InvalidCell("Expected a shard identifier".into()));
+    }
+    let prefix_bits = slice.load_uint_leq(60)? as u32;
+    let workchain = slice.load_uint(32)? as u32 as i32;
+    let prefix = slice.load_uint(64)?;
+    Ok((workchain, prefix | (FULL_SHARD >> prefix_bits)))
+}
+
+/// Whether the shard `shard` holds the account with hash `hash`
+fn shard_contains(shard: u64, hash: &[u8; 32]) -> bool {
+    let prefix_bits = 63 - shard.trailing_zeros();
+    let mask = !(u64::MAX >> prefix_bits);
+    let account = u64::from_be_bytes(hash[..8].try_into().expect("8 bytes"));
+    shard != 0 && (account & mask) == (shard & mask)
+}
+
+/// The hash of `account_none$0`
+fn account_none_hash() -> [u8; 32] {
+    // No references, not exotic, one bit: 0 and the padding bit
+    Sha256::digest([0x00, 0x01, 0x40]).into()
+}
+
+fn account_none_boc() -> BagOfCells {
+    // One cell, one root, no index or checksum
+    BagOfCells::parse(&[0xb5, 0xee, 0x9c, 0x72, 0x01, 0x01, 1, 1, 0, 3, 0, 0x00, 0x01, 0x40]).expect("valid")
+}
+
+pub(crate) fn field<'a>(value: &'a Value, name: &str) -> Result<&'a Value, TonError> {
+    value.get(name).ok_or_else(|| TonError::Rpc(format!("Missing '{}' in {}", name, value)))
+}
+
+pub(crate) fn str_field<'a>(value: &'a Value, name: &str) -> Result<&'a str, TonError> {
+    field(value, name)?.as_str().ok_or_else(|| not_a(name, "string"))
+}
+
+fn not_a(name: &str, what: &str) -> TonError {
+    TonError::Rpc(format!("Field '{}' is not a {}", name, what))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::account::tests::active_account;
+    use crate::cell::tests::{to_boc, TestCell};
+    use crate::hashmap::tests::{key_bits, long_label};
+    use crate::layout::tests::wallet_layout;
+    use crate::resolver::tests::wallet_data;
+    use crate::resolver::TlbValue;
+
+    fn hash_and_depth(cell: &TestCell) -> ([u8; 32], u16) {
+        let boc = BagOfCells::parse(&cell.to_boc()).unwrap();
+        (boc.cell(0).hash(0), boc.cell(0).depth(0))
+    }
+
+    /// A Merkle proof of `cell`, with the hash it proves
+    fn wrap(cell: TestCell) -> (TestCell, [u8; 32]) {
+        let (hash, depth) = hash_and_depth(&cell);
+        (TestCell::merkle_proof(cell, hash, depth), hash)
+    }
+
+    fn shard_ident(cell: TestCell, workchain: i32, shard: u64) -> TestCell {
+        let prefix_bits = 63 - shard.trailing_zeros();
+        cell.uint(0, 2).uint(prefix_bits as u64, 6).uint(workchain as u32 as u64, 32).uint(shard & !(FULL_SHARD >> prefix_bits), 64)
+    }
+
+    /// A shard state holding one account, or a masterchain state recording a shard block
+    fn shard_state(block: &BlockIdExt, account: Option<(&TonAddress, &TestCell)>, shard: Option<&BlockIdExt>) -> TestCell {
+        let mut state = shard_ident(TestCell::new().uint(SHARD_STATE_TAG, 32).uint(239, 32), block.workchain, block.shard);
+        state = state.uint(block.seqno as u64, 32).uint(0, 32).uint(1_700_000_000, 32).uint(42, 64).uint(1, 32);
+        state = state.child(TestCell::pruned(&TestCell::new().bytes(b"out messages"))).uint(0, 1);
+        let accounts = match account {
+            Some((address, account)) => {
+                let mut leaf = long_label(TestCell::new(), &key_bits(&address.hash, 256), 256);
+                leaf = leaf.uint(0, 5).uint(0, 4).uint(0, 1).child(account.clone());
+                leaf = leaf.bytes(&[0x77; 32]).uint(41_000_000_000_001, 64);
+                TestCell::new().uint(1, 1).child(leaf).uint(0, 10)
+            }
+            None => TestCell::new().uint(0, 1).uint(0, 10),
+        };
+        state = state.child(accounts).child(TestCell::pruned(&TestCell::new().bytes(b"overload history")));
+        match shard {
+            Some(shard) => {
+                let mut descr = TestCell::new().uint(0, 1).uint(0xb, 4).uint(shard.seqno as u64, 32).uint(7, 32);
+                descr = descr.uint(0, 64).uint(0, 64).bytes(&shard.root_hash).bytes(&shard.file_hash);
+                let leaf = long_label(TestCell::new(), &key_bits(&shard.workchain.to_be_bytes(), 32), 32).child(descr);
+                let extra = TestCell::new().uint(MC_STATE_EXTRA_TAG, 16).uint(1, 1).child(leaf);
+                state.uint(1, 1).child(extra)
+            }
+            None => state.uint(0, 1),
+        }
+    }
+
+    /// A block whose state update leads to `state`, with a made-up root hash filled in
+    fn block(block: &mut BlockIdExt, state: &TestCell) -> TestCell {
+        let mut info = TestCell::new().uint(BLOCK_INFO_TAG, 32).uint(0, 32).uint(0, 16).uint(block.seqno as u64, 32);
+        info = shard_ident(info.uint(0, 32), block.workchain, block.shard);
+        let old = TestCell::new().bytes(b"previous state");
+        let ((old_hash, old_depth), (new_hash, new_depth)) = (hash_and_depth(&old), hash_and_depth(state));
+        let mut update = TestCell::new().uint(4, 8).bytes(&old_hash).bytes(&new_hash);
+        update = update.uint(old_depth as u64, 16).uint(new_depth as u64, 16);
+        update = update.child(TestCell::pruned_at(&old, 2)).child(TestCell::pruned_at(state, 2));
+        update.exotic = true;
+        let value_flow = TestCell::pruned(&TestCell::new().bytes(b"value flow"));
+        let block_cell = TestCell::new().uint(BLOCK_TAG, 32).uint(239, 32).child(info).child(value_flow).child(update);
+        block.root_hash = hash_and_depth(&block_cell).0;
+        block_cell
+    }
+
+    fn block_id(workchain: i32, shard: u64, seqno: u32) -> BlockIdExt {
+        BlockIdExt { workchain, shard, seqno, root_hash: [0; 32], file_hash: [seqno as u8; 32] }
+    }
+
+    /// The proof of a basechain jetton wallet at masterchain block 100
+    pub(crate) fn wallet_proof() -> TonAccountProof {
+        let address = TonAddress::new(0, [0x44; 32]);
+        let account = active_account(&address, wallet_data(true), true);
+
+        let mut shard_block = block_id(0, FULL_SHARD, 5000);
+        let state = shard_state(&shard_block, Some((&address, &account)), None);
+        let block_cell = block(&mut shard_block, &state);
+        let proof = to_boc(&[wrap(block_cell).0, wrap(state).0]);
+
+        let mut mc_block = block_id(-1, FULL_SHARD, 100);
+        let mc_state = shard_state(&mc_block, None, Some(&shard_block));
+        let mc_block_cell = block(&mut mc_block, &mc_state);
+        let shard_proof = to_boc(&[wrap(mc_block_cell).0, wrap(mc_state).0]);
+
+        TonAccountProof { address, mc_block, shard_block, shard_proof, proof, state: account.to_boc() }
+    }
+
+    #[test]
+    fn test_basechain_account_proof() {
+        let proof = wallet_proof();
+        let verified = proof.verify().unwrap();
+        assert_eq!(verified.last_trans_lt, 41_000_000_000_001);
+        assert_eq!(verified.last_trans_hash, [0x77; 32]);
+        assert_eq!(verified.account.as_ref().unwrap().balance, 2_500_000_000);
+        let balance = TonKeyResolver::decode_field(&wallet_layout(), &verified, "balance").unwrap();
+        assert_eq!(balance, Some(TlbValue::Coins(1_000_000_000)));
+
+        let json = proof.to_json_with_fields(&wallet_layout(), &["balance", "config.fee.bps"]).unwrap();
+        assert_eq!(TonAccountProof::from_json(&json).unwrap(), proof);
+        assert_eq!(json["account_hash"], hex::encode(verified.account_hash));
+        assert_eq!(json["fields"][1]["value"], 250);
+        assert_eq!(json["fields"][1]["location"]["refs"], json!([1]));
+        assert_eq!(json["fields"][0]["word"], hex::encode(TlbValue::Coins(1_000_000_000).to_word().unwrap()));
+
+        // Another account's state
+        let mut other_state = proof.clone();
+        other_state.state = active_account(&proof.address, wallet_data(false), true).to_boc();
+        assert!(other_state.verify().is_err());
+
+        // A shard block the masterchain does not record
+        let mut other_shard = proof.clone();
+        other_shard.shard_block.file_hash[0] ^= 1;
+        assert!(other_shard.verify().is_err());
+
+        let mut other_mc = proof.clone();
+        other_mc.mc_block.root_hash[0] ^= 1;
+        assert!(other_mc.verify().is_err());
+
+        // An account the accounts dictionary does not hold at that key
+        let mut other_address = proof.clone();
+        other_address.address.hash[31] ^= 1;
+        assert!(other_address.verify().is_err());
+
+        let mut no_link = proof;
+        no_link.shard_proof.clear();
+        assert!(no_link.verify().is_err());
+    }
+
+    #[test]
+    fn test_masterchain_and_missing_accounts() {
+        let address = TonAddress::new(-1, [0x55; 32]);
+        let account = active_account(&address, wallet_data(false), true);
+        let mut mc_block = block_id(-1, FULL_SHARD, 100);
+        let state = shard_state(&mc_block, Some((&address, &account)), None);
+        let block_cell = block(&mut mc_block, &state);
+        let proof = TonAccountProof {
+            address,
+            mc_block,
+            shard_block: mc_block,
+            shard_proof: Vec::new(),
+            proof: to_boc(&[wrap(block_cell).0, wrap(state).0]),
+            state: account.to_boc(),
+        };
+        let verified = proof.verify().unwrap();
+        assert!(verified.data_cell().is_some());
+
+        // A block of another seqno than claimed
+        let mut other_seqno = proof.clone();
+        other_seqno.mc_block.seqno = 101;
+        other_seqno.shard_block.seqno = 101;
+        assert!(other_seqno.verify().is_err());
+
+        // The same state shows that a neighbouring account does not exist
+        let mut missing = proof;
+        missing.address.hash[0] = 0x56;
+        missing.state.clear();
+        let verified = missing.verify().unwrap();
+        assert_eq!((verified.account, verified.account_hash), (None, account_none_hash()));
+        assert_eq!(account_none_boc().cell(0).hash(0), account_none_hash());
+        missing.state = account.to_boc();
+        assert!(missing.verify().is_err());
+    }
+}
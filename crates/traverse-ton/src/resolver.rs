@@ -0,0 +1,480 @@
+//! Field resolution and decoding
+//!
+//! Queries name a field by its path: `balance`, `config.admin`,
+//! `config.fee.bps`. A path may go through records stored inline, behind
+//! references, or behind `Maybe`. There is no key to compute; a field is
+//! found by reading the data cell from its start, since each field's
+//! position depends on the values before it. The resolved key is therefore
+//! the path itself, and [`TonKeyResolver::decode_field`] reads the value
+//! out of a proven account.
+
+use crate::address::load_address;
+use crate::cell::{BagOfCells, CellSlice};
+use crate::layout::{record_type, root_type, type_tag};
+use crate::proof::VerifiedAccount;
+use crate::tlb::{named_type, tag_bits, TlbType};
+use crate::{TonAddress, TonError};
+use serde_json::{json, Value};
+use traverse_core::{Key, KeyResolver, LayoutInfo, StaticKeyPath, StorageEntry, TraverseError, ZeroSemantics};
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    format,
+    string::ToString,
+    vec,
+    vec::Vec,
+};
+
+/// Key resolver for TON contract data
+///
+/// # Usage
+///
+/// ```rust,ignore
+/// use traverse_ton::TonKeyResolver;
+/// use traverse_core::KeyResolver;
+///
+/// let path = TonKeyResolver.resolve(&layout, "config.fee.bps")?;
+/// let bps = TonKeyResolver::decode_field(&layout, &account, "config.fee.bps")?;
+/// ```
+pub struct TonKeyResolver;
+
+/// A decoded field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TlbValue {
+    /// `uintN` and `## N`: big-endian, right-aligned in whole bytes
+    Unsigned(Vec<u8>),
+    /// `intN`: big-endian two's complement, sign-extended to whole bytes
+    Signed(Vec<u8>),
+    /// `bitsN`: left-aligned in whole bytes
+    Bits(Vec<u8>),
+    Bool(bool),
+    /// `VarUInteger n` and `Coins`
+    Coins(u128),
+    /// `MsgAddress`; `None` is `addr_none`
+    Address(Option<TonAddress>),
+    /// A reference: the hash of the referenced cell
+    Cell([u8; 32]),
+    /// `HashmapE`: the hash of the dictionary's root, `None` when it is empty
+    Dict(Option<[u8; 32]>),
+}
+
+impl TlbValue {
+    /// The value as a 32-byte word, numbers right-aligned; `None` when it does not fit
+    ///
+    /// An address contributes its hash only, so its workchain must be
+    /// known from elsewhere.
+    pub fn to_word(&self) -> Option<[u8; 32]> {
+        let right_aligned = |bytes: &[u8], fill: u8| {
+            let skip = bytes.len().saturating_sub(32);
+            if bytes[..skip].iter().any(|b| *b != fill) {
+                return None;
+            }
+            let mut word = [fill; 32];
+            word[32 - (bytes.len() - skip)..].copy_from_slice(&bytes[skip..]);
+            Some(word)
+        };
+        match self {
+            Self::Unsigned(bytes) | Self::Bits(bytes) => right_aligned(bytes, 0),
+            Self::Signed(bytes) => {
+                let negative = bytes.first().is_some_and(|b| b & 0x80 != 0);
+                right_aligned(bytes, if negative { 0xff } else { 0 })
+            }
+            Self::Bool(value) => right_aligned(&[*value as u8], 0),
+            Self::Coins(value) => right_aligned(&value.to_be_bytes(), 0),
+            Self::Address(address) => Some(address.map_or([0; 32], |a| a.hash)),
+            Self::Cell(hash) => Some(*hash),
+            Self::Dict(root) => Some(root.unwrap_or([0; 32])),
+        }
+    }
+
+    /// JSON form: numbers up to 64 bits as numbers, wider ones and hashes in hex
+    pub fn to_json(&self) -> Value {
+        match self {
+            Self::Unsigned(bytes) if bytes.len() <= 8 => json!(bytes.iter().fold(0u64, |a, b| (a << 8) | *b as u64)),
+            Self::Signed(bytes) if bytes.len() <= 8 => {
+                let fill = if bytes.first().is_some_and(|b| b & 0x80 != 0) { u64::MAX } else { 0 };
+                json!(bytes.iter().fold(fill, |a, b| (a << 8) | *b as u64) as i64)
+            }
+            Self::Unsigned(bytes) | Self::Signed(bytes) | Self::Bits(bytes) => json!(format!("0x{}", hex::encode(bytes))),
+            Self::Bool(value) => json!(value),
+            Self::Coins(value) => json!(value.to_string()),
+            Self::Address(address) => json!(address.map(|a| a.to_string())),
+            Self::Cell(hash) => json!(format!("0x{}", hex::encode(hash))),
+            Self::Dict(root) => json!(root.map(|hash| format!("0x{}", hex::encode(hash)))),
+        }
+    }
+}
+
+impl TonKeyResolver {
+    /// Read the field `query` names out of a proven account's data
+    ///
+    /// Returns `None` when the field is behind a `Maybe` that is not set.
+    pub fn decode_field(
+        layout: &LayoutInfo,
+        account: &VerifiedAccount,
+        query: &str,
+    ) -> Result<Option<TlbValue>, TonError> {
+        let data = account
+            .data_cell()
+            .ok_or_else(|| TonError::InvalidQuery(format!("Account {} has no data", account.address)))?;
+        Self::decode_cell(layout, &account.state, data, query)
+    }
+
+    /// Read the field `query` names out of the data cell `cell` of `boc`
+    pub fn decode_cell(
+        layout: &LayoutInfo,
+        boc: &BagOfCells,
+        cell: usize,
+        query: &str,
+    ) -> Result<Option<TlbValue>, TonError> {
+        let (mut slice, type_name) = seek_field(layout, boc, cell, query)?;
+        read_value(boc, &mut slice, &TlbType::parse(type_name)?)
+    }
+
+    /// Find where the field `query` names starts in a proven account's data
+    pub fn locate_field(layout: &LayoutInfo, account: &VerifiedAccount, query: &str) -> Result<FieldLocation, TonError> {
+        let data = account
+            .data_cell()
+            .ok_or_else(|| TonError::InvalidQuery(format!("Account {} has no data", account.address)))?;
+        Self::locate_cell(layout, &account.state, data, query)
+    }
+
+    /// Find where the field `query` names starts in the data cell `cell` of `boc`
+    pub fn locate_cell(layout: &LayoutInfo, boc: &BagOfCells, cell: usize, query: &str) -> Result<FieldLocation, TonError> {
+        let (slice, type_name) = seek_field(layout, boc, cell, query)?;
+        let refs = ref_path(boc, cell, slice.cell).expect("the reader only follows references");
+        Ok(FieldLocation { refs, bit: slice.bits_read(), next_ref: slice.refs_read(), type_name: type_name.to_string() })
+    }
+
+    /// Check a query against a layout, returning the field it names
+    pub fn field<'a>(layout: &'a LayoutInfo, query: &str) -> Result<&'a StorageEntry, TonError> {
+        let path = parse_query(query)?;
+        let mut entry = None;
+        for depth in 0..path.len() {
+            let label = path[..=depth].join(".");
+            let found = layout
+                .storage
+                .iter()
+                .find(|e| e.label == label)
+                .ok_or_else(|| TonError::InvalidQuery(format!("{} has no {}", layout.contract_name, label)))?;
+            let is_record = record_type(found)?.is_some();
+            if depth < path.len() - 1 && !is_record {
+                return Err(TonError::InvalidQuery(format!("{} is a {}, which has no fields", label, found.type_name)));
+            }
+            entry = Some(found);
+        }
+        let entry = entry.expect("queries have at least one component");
+        if matches!(TlbType::parse(&entry.type_name)?, TlbType::Named(_)) {
+            return Err(TonError::InvalidQuery(format!("{} is stored inline; query one of its fields", query)));
+        }
+        Ok(entry)
+    }
+}
+
+/// Where a field starts: a reader that follows `refs` from the data cell,
+/// then reads `bit` bits and `next_ref` references, is at the field
+///
+/// `type_name` is the type to read there. It is the field's own, or, for a
+/// field in a record behind an unset `Maybe`, the record's, which reads as
+/// absent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldLocation {
+    pub refs: Vec<u8>,
+    pub bit: usize,
+    pub next_ref: usize,
+    pub type_name: String,
+}
+
+impl FieldLocation {
+    pub fn to_json(&self) -> Value {
+        json!({ "refs": self.refs, "bit": self.bit, "ref": self.next_ref, "type": self.type_name })
+    }
+}
+
+impl KeyResolver for TonKeyResolver {
+    /// Resolve a query to its field path
+    fn resolve(&self, layout: &LayoutInfo, query: &str) -> Result<StaticKeyPath, TraverseError> {
+        let entry = Self::field(layout, query)?;
+        let bits = TlbType::parse(&entry.type_name)?.fixed_bits().filter(|bits| *bits > 0);
+        Ok(StaticKeyPath {
+            name: "ton_field",
+            key: Key::Variable(entry.label.as_bytes().to_vec()),
+            offset: None,
+            field_size: bits.and_then(|bits| u8::try_from(bits.div_ceil(8)).ok()),
+            layout_commitment: layout.commitment(),
+            zero_semantics: ZeroSemantics::ValidZero,
+        })
+    }
+
+    /// Resolve every field but the records stored inline, whose fields are resolved instead
+    fn resolve_all(&self, layout: &LayoutInfo) -> Result<Vec<StaticKeyPath>, TraverseError> {
+        let mut paths = Vec::new();
+        for entry in &layout.storage {
+            if !matches!(TlbType::parse(&entry.type_name)?, TlbType::Named(_)) {
+                paths.push(self.resolve(layout, &entry.label)?);
+            }
+        }
+        Ok(paths)
+    }
+}
+
+fn parse_query(query: &str) -> Result<Vec<&str>, TonError> {
+    let path: Vec<&str> = query.trim().split('.').collect();
+    if path.iter().any(|part| part.is_empty()) {
+        return Err(TonError::InvalidQuery(format!("Invalid field path '{}'", query)));
+    }
+    Ok(path)
+}
+
+/// The fields of the record at `slot`, in order
+fn record_members<'a>(layout: &'a LayoutInfo, slot: &str) -> Vec<&'a StorageEntry> {
+    let mut members: Vec<_> = layout.storage.iter().filter(|e| e.slot == slot).collect();
+    members.sort_by_key(|e| e.offset);
+    members
+}
+
+/// Position a reader where the field `query` names starts, with the type to read there
+///
+/// A reader stops early, at the record's type, when the field is in a
+/// record behind an unset `Maybe`.
+fn seek_field<'a>(
+    layout: &'a LayoutInfo,
+    boc: &'a BagOfCells,
+    cell: usize,
+    query: &str,
+) -> Result<(CellSlice<'a>, &'a str), TonError> {
+    let path = parse_query(query)?;
+    let root = root_type(layout)?;
+    let mut slice = boc.slice(cell)?;
+    read_tag(&mut slice, type_tag(layout, root)?)?;
+    let mut slot = root.to_string();
+    for depth in 0..path.len() {
+        let label = path[..=depth].join(".");
+        let members = record_members(layout, &slot);
+        let target = members
+            .iter()
+            .position(|e| e.label == label)
+            .ok_or_else(|| TonError::InvalidQuery(format!("{} has no {}", layout.contract_name, label)))?;
+        for entry in &members[..target] {
+            skip_field(layout, boc, &mut slice, entry)?;
+        }
+        let type_name = &members[target].type_name;
+        if depth == path.len() - 1 {
+            return Ok((slice, type_name));
+        }
+        match open_record(layout, boc, slice, &TlbType::parse(type_name)?)? {
+            Some(record) => slice = record,
+            None => return Ok((slice, type_name)),
+        }
+        slot = label;
+    }
+    unreachable!("queries have at least one component")
+}
+
+/// Reference indexes leading from the cell `from` to the cell `to`
+fn ref_path(boc: &BagOfCells, from: usize, to: usize) -> Option<Vec<u8>> {
+    if from == to {
+        return Some(Vec::new());
+    }
+    boc.cell(from).refs.iter().enumerate().find_map(|(index, child)| {
+        let mut path = ref_path(boc, *child, to)?;
+        path.insert(0, index as u8);
+        Some(path)
+    })
+}
+
+fn read_tag(slice: &mut CellSlice<'_>, tag: &str) -> Result<(), TonError> {
+    for expected in tag_bits(tag) {
+        if slice.load_bit()? != expected {
+            return Err(TonError::InvalidCell(format!("Cell {} does not start with tag {}", slice.cell, tag)));
+        }
+    }
+    Ok(())
+}
+
+/// Position a reader at the first field of the record a field of type `ty` holds
+fn open_record<'a>(
+    layout: &LayoutInfo,
+    boc: &'a BagOfCells,
+    mut slice: CellSlice<'a>,
+    ty: &TlbType,
+) -> Result<Option<CellSlice<'a>>, TonError> {
+    match ty {
+        TlbType::Named(name) => {
+            read_tag(&mut slice, type_tag(layout, name)?)?;
+            Ok(Some(slice))
+        }
+        TlbType::Ref(inner) => open_record(layout, boc, boc.slice(slice.load_ref()?)?, inner),
+        TlbType::Maybe(inner) if slice.load_bit()? => open_record(layout, boc, slice, inner),
+        TlbType::Maybe(_) => Ok(None),
+        _ => Err(TonError::InvalidQuery(format!("A {:?} has no fields", ty))),
+    }
+}
+
+/// Read past a field, and past the fields of a record stored inline
+fn skip_field(
+    layout: &LayoutInfo,
+    boc: &BagOfCells,
+    slice: &mut CellSlice<'_>,
+    entry: &StorageEntry,
+) -> Result<(), TonError> {
+    let mut ty = TlbType::parse(&entry.type_name)?;
+    if let TlbType::Maybe(inner) = ty {
+        if !slice.load_bit()? {
+            return Ok(());
+        }
+        ty = *inner;
+    }
+    match &ty {
+        TlbType::Named(name) => {
+            read_tag(slice, type_tag(layout, name)?)?;
+            for member in record_members(layout, &entry.label) {
+                skip_field(layout, boc, slice, member)?;
+            }
+        }
+        TlbType::Ref(_) => {
+            slice.load_ref()?;
+        }
+        ty => {
+            read_value(boc, slice, ty)?;
+        }
+    }
+    Ok(())
+}
+
+/// Read a value that is not a record stored inline
+fn read_value(boc: &BagOfCells, slice: &mut CellSlice<'_>, ty: &TlbType) -> Result<Option<TlbValue>, TonError> {
+    let value = match ty {
+        TlbType::Uint(bits) => TlbValue::Unsigned(load_right_aligned(slice, *bits, false)?),
+        TlbType::Int(bits) => TlbValue::Signed(load_right_aligned(slice, *bits, true)?),
+        TlbType::Bits(bits) => TlbValue::Bits(slice.load_bits(*bits)?),
+        TlbType::Bool => TlbValue::Bool(slice.load_bit()?),
+        TlbType::VarUint(n) => TlbValue::Coins(slice.load_var_uint(*n)?),
+        TlbType::Address => TlbValue::Address(load_address(slice)?),
+        TlbType::CellRef => TlbValue::Cell(boc.cell(slice.load_ref()?).hash(0)),
+        TlbType::Ref(inner) if named_type(inner).is_some() => TlbValue::Cell(boc.cell(slice.load_ref()?).hash(0)),
+        TlbType::Ref(inner) => return read_value(boc, &mut boc.slice(slice.load_ref()?)?, inner),
+        TlbType::Maybe(inner) if slice.load_bit()? => return read_value(boc, slice, inner),
+        TlbType::Maybe(_) => return Ok(None),
+        TlbType::Dict(_) if slice.load_bit()? => TlbValue::Dict(Some(boc.cell(slice.load_ref()?).hash(0))),
+        TlbType::Dict(_) => TlbValue::Dict(None),
+        TlbType::Named(name) => {
+            return Err(TonError::InvalidQuery(format!("{} is stored inline; query one of its fields", name)))
+        }
+    };
+    Ok(Some(value))
+}
+
+/// `bits` bits as a big-endian number in whole bytes, sign-extended if `signed`
+fn load_right_aligned(slice: &mut CellSlice<'_>, bits: usize, signed: bool) -> Result<Vec<u8>, TonError> {
+    let len = bits.div_ceil(8);
+    let pad = len * 8 - bits;
+    let mut bytes = vec![0u8; len];
+    for i in 0..bits {
+        if slice.load_bit()? {
+            let at = pad + i;
+            bytes[at / 8] |= 0x80 >> (at % 8);
+        }
+    }
+    if signed && pad > 0 && bytes[0] & (0x80 >> pad) != 0 {
+        bytes[0] |= !(0xffu8 >> pad);
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::cell::tests::TestCell;
+    use crate::layout::tests::wallet_layout;
+
+    /// Append a `addr_std` address
+    pub(crate) fn std_address(cell: TestCell, address: &TonAddress) -> TestCell {
+        cell.uint(0b100, 3).uint(address.workchain as u8 as u64, 8).bytes(&address.hash)
+    }
+
+    /// Data for the wallet schema, with or without its config
+    pub(crate) fn wallet_data(with_config: bool) -> TestCell {
+        let owner = TonAddress::new(0, [0x11; 32]);
+        let master = TonAddress::new(0, [0x22; 32]);
+        let admin = TonAddress::new(-1, [0x33; 32]);
+        let mut data = TestCell::new().uint(4, 4).uint(1_000_000_000, 32);
+        data = std_address(std_address(data, &owner), &master);
+        data = data.child(TestCell::new().bytes(b"wallet code"));
+        if with_config {
+            let mut config = std_address(TestCell::new().uint(0x3f5476ca, 32), &admin);
+            config = config.uint(0b01, 2).uint(250, 16).uint(0b00, 2).uint(1, 1);
+            data = data.uint(1, 1).child(config);
+        } else {
+            data = data.uint(0, 1);
+        }
+        data.uint((-7i32) as u32 as u64, 32)
+    }
+
+    fn decode(data: &TestCell, query: &str) -> Result<Option<TlbValue>, TonError> {
+        let boc = BagOfCells::parse(&data.to_boc()).unwrap();
+        TonKeyResolver::decode_cell(&wallet_layout(), &boc, 0, query)
+    }
+
+    #[test]
+    fn test_decode_fields() {
+        let data = wallet_data(true);
+        assert_eq!(decode(&data, "balance").unwrap(), Some(TlbValue::Coins(1_000_000_000)));
+        assert_eq!(decode(&data, "owner").unwrap(), Some(TlbValue::Address(Some(TonAddress::new(0, [0x11; 32])))));
+        assert_eq!(decode(&data, "nonce").unwrap().unwrap().to_json(), json!(-7));
+        assert_eq!(decode(&data, "nonce").unwrap().unwrap().to_word().unwrap()[0], 0xff);
+        assert_eq!(decode(&data, "config.admin").unwrap().unwrap().to_word(), Some([0x33; 32]));
+        assert_eq!(decode(&data, "config.fee.bps").unwrap(), Some(TlbValue::Unsigned(vec![0, 250])));
+        assert_eq!(decode(&data, "config.fee.recipient").unwrap(), Some(TlbValue::Address(None)));
+        assert_eq!(decode(&data, "config.paused").unwrap(), Some(TlbValue::Bool(true)));
+
+        let code = BagOfCells::parse(&TestCell::new().bytes(b"wallet code").to_boc()).unwrap();
+        assert_eq!(decode(&data, "wallet_code").unwrap(), Some(TlbValue::Cell(code.cell(0).hash(0))));
+
+        // Without the config, its fields are absent and the fields after it move up
+        let data = wallet_data(false);
+        assert_eq!(decode(&data, "config.fee.bps").unwrap(), None);
+        assert_eq!(decode(&data, "config").unwrap(), None);
+        assert_eq!(decode(&data, "nonce").unwrap().unwrap().to_json(), json!(-7));
+
+        assert!(decode(&wallet_data(true), "config.fee").is_err());
+        assert!(decode(&data, "supply").is_err());
+        assert!(decode(&data, "balance.x").is_err());
+        // Data that does not follow the schema
+        assert!(decode(&TestCell::new().uint(0, 8), "nonce").is_err());
+    }
+
+    #[test]
+    fn test_locate_fields() {
+        let boc = BagOfCells::parse(&wallet_data(true).to_boc()).unwrap();
+        let locate = |query: &str| TonKeyResolver::locate_cell(&wallet_layout(), &boc, 0, query).unwrap();
+        let fee = locate("config.fee.bps");
+        // Inside the config cell, after its tag, the admin and the fee's tag
+        assert_eq!((fee.refs, fee.bit, fee.type_name.as_str()), (vec![1], 32 + 267 + 2, "uint16"));
+        assert_eq!((locate("wallet_code").bit, locate("wallet_code").next_ref), (4 + 32 + 267 * 2, 0));
+
+        // A field behind an unset Maybe is located at the Maybe
+        let boc = BagOfCells::parse(&wallet_data(false).to_boc()).unwrap();
+        let fee = TonKeyResolver::locate_cell(&wallet_layout(), &boc, 0, "config.fee.bps").unwrap();
+        assert_eq!((fee.refs.len(), fee.next_ref, fee.type_name.as_str()), (0, 1, "(Maybe ^Config)"));
+    }
+
+    #[test]
+    fn test_resolve_fields() {
+        let layout = wallet_layout();
+        let path = TonKeyResolver.resolve(&layout, "config.fee.bps").unwrap();
+        assert_eq!(path.key, Key::Variable(b"config.fee.bps".to_vec()));
+        assert_eq!(path.field_size, Some(2));
+        assert_eq!(TonKeyResolver.resolve(&layout, "balance").unwrap().field_size, None);
+        assert!(TonKeyResolver.resolve(&layout, "config.fee").is_err());
+        assert!(TonKeyResolver.resolve(&layout, "balance.x").is_err());
+        assert!(TonKeyResolver.resolve(&layout, "config..fee").is_err());
+
+        let resolved = TonKeyResolver.resolve_all(&layout).unwrap();
+        assert_eq!(resolved.len(), 10);
+
+        assert_eq!(TlbValue::Signed(vec![0xfe]).to_json(), json!(-2));
+        assert_eq!(TlbValue::Unsigned(vec![1; 33]).to_word(), None);
+        assert_eq!(TlbValue::Coins(5).to_json(), json!("5"));
+    }
+}
@@ -0,0 +1,412 @@
+//! TL-B storage schemas
+//!
+//! A contract's data cell has no self-description; its layout is given by
+//! the TL-B schema the contract's code reads and writes it with, e.g.
+//!
+//! ```text
+//! storage#_ balance:Coins owner:MsgAddress master:MsgAddress wallet_code:^Cell = Storage;
+//! ```
+//!
+//! Each constructor has a tag (`#hex`, `$bits`, or `#_`/`$_` for none) and
+//! fields of the types below. Schemas are read as records: every type has
+//! one constructor, and the parameters, implicit fields and constraints of
+//! the full language are refused. Types without a tag in the source would
+//! get one derived from a CRC32 of the declaration, so tags must be
+//! written out.
+
+use crate::TonError;
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// The type of a field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TlbType {
+    /// `uintN`, `## N`
+    Uint(usize),
+    /// `intN`
+    Int(usize),
+    /// `bitsN`
+    Bits(usize),
+    Bool,
+    /// `VarUInteger n`; `Coins` and `Grams` are `VarUInteger 16`
+    VarUint(usize),
+    /// `MsgAddress`, `MsgAddressInt`
+    Address,
+    /// `^Cell`: a reference to a cell of any content
+    CellRef,
+    /// `^X`: a reference to a cell holding an `X`
+    Ref(Box<TlbType>),
+    /// `Maybe X`: a bit, then `X` when it is set
+    Maybe(Box<TlbType>),
+    /// `HashmapE n X`: a bit, then a reference to the dictionary's root when it is set
+    Dict(usize),
+    /// A type the schema declares, stored inline
+    Named(String),
+}
+
+/// A field of a constructor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlbField {
+    pub name: String,
+    pub ty: TlbType,
+    /// The type as written
+    pub type_expr: String,
+}
+
+/// A constructor, the one way its type is built
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constructor {
+    pub name: String,
+    /// The tag as written: `#_`, `$_`, `#3f5476ca`, `$01`
+    pub tag: String,
+    pub fields: Vec<TlbField>,
+    pub type_name: String,
+}
+
+/// A parsed schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlbSchema {
+    pub constructors: Vec<Constructor>,
+}
+
+/// A contract's storage, as written by hand
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TonStorageDefinition {
+    /// Address the contract is deployed at; may be left out of definitions shared by several deployments
+    #[serde(default)]
+    pub contract: Option<String>,
+    /// Name of the contract, used when no address is given
+    #[serde(default = "default_name")]
+    pub name: String,
+    /// Type of the data cell; the last type the schema declares if left out
+    #[serde(default)]
+    pub root: Option<String>,
+    /// TL-B declarations of the data cell's types
+    pub schema: String,
+}
+
+/// Nesting limit for types, which also catches recursive schemas
+const MAX_TYPE_DEPTH: usize = 16;
+
+fn default_name() -> String {
+    "Contract".to_string()
+}
+
+impl TlbType {
+    /// Parse a type expression such as `uint64`, `(Maybe ^Config)` or `(VarUInteger 16)`
+    pub fn parse(expr: &str) -> Result<Self, TonError> {
+        let expr = expr.trim();
+        let invalid = || TonError::InvalidSchema(format!("Unsupported type '{}'", expr));
+        if let Some(inner) = expr.strip_prefix('(').and_then(|e| e.strip_suffix(')')) {
+            return Self::parse(inner);
+        }
+        if let Some(inner) = expr.strip_prefix('^') {
+            return Ok(match inner.trim() {
+                "Cell" | "Any" => Self::CellRef,
+                inner => Self::Ref(Box::new(Self::parse(inner)?)),
+            });
+        }
+        let words: Vec<&str> = expr.split_whitespace().collect();
+        let number = |s: &str| s.parse::<usize>().map_err(|_| invalid());
+        let sized = |prefix: &str, max: usize| {
+            expr.strip_prefix(prefix)
+                .and_then(|n| n.parse::<usize>().ok())
+                .filter(|n| (1..=max).contains(n))
+        };
+        let ty = match words.as_slice() {
+            ["Bool"] => Self::Bool,
+            ["Coins"] | ["Grams"] => Self::VarUint(16),
+            ["MsgAddress"] | ["MsgAddressInt"] => Self::Address,
+            ["Cell"] | ["Any"] => return Err(TonError::InvalidSchema(format!("'{}' must be behind a reference", expr))),
+            ["##", n] => Self::Uint(number(n)?),
+            ["VarUInteger", n] => Self::VarUint(number(n)?),
+            ["HashmapE", n, _] => Self::Dict(number(n)?),
+            ["Maybe", rest @ ..] if !rest.is_empty() => Self::Maybe(Box::new(Self::parse(&rest.join(" "))?)),
+            [_] => {
+                if let Some(bits) = sized("uint", 256) {
+                    Self::Uint(bits)
+                } else if let Some(bits) = sized("int", 257) {
+                    Self::Int(bits)
+                } else if let Some(bits) = sized("bits", 1023) {
+                    Self::Bits(bits)
+                } else if expr.starts_with(|c: char| c.is_ascii_uppercase())
+                    && expr.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                {
+                    Self::Named(expr.to_string())
+                } else {
+                    return Err(invalid());
+                }
+            }
+            _ => return Err(invalid()),
+        };
+        match ty {
+            Self::Uint(bits) if bits == 0 || bits > 256 => Err(invalid()),
+            Self::VarUint(n) if !(2..=32).contains(&n) => Err(invalid()),
+            _ => Ok(ty),
+        }
+    }
+
+    /// Number of bits the type takes in its cell, when that is fixed
+    pub fn fixed_bits(&self) -> Option<usize> {
+        match self {
+            Self::Uint(bits) | Self::Int(bits) | Self::Bits(bits) => Some(*bits),
+            Self::Bool => Some(1),
+            Self::CellRef | Self::Ref(_) => Some(0),
+            _ => None,
+        }
+    }
+}
+
+impl TlbSchema {
+    /// Parse TL-B declarations, `//` and `/* */` comments allowed
+    pub fn parse(source: &str) -> Result<Self, TonError> {
+        let mut constructors = Vec::new();
+        for declaration in strip_comments(source).split(';') {
+            let declaration = declaration.trim();
+            if declaration.is_empty() {
+                continue;
+            }
+            constructors.push(parse_constructor(declaration)?);
+        }
+        for (i, constructor) in constructors.iter().enumerate() {
+            if constructors[..i].iter().any(|c| c.type_name == constructor.type_name) {
+                return Err(TonError::InvalidSchema(format!(
+                    "{} has more than one constructor, which is not supported",
+                    constructor.type_name
+                )));
+            }
+        }
+        let schema = Self { constructors };
+        for constructor in &schema.constructors {
+            schema.check_type(&constructor.type_name, 0)?;
+        }
+        Ok(schema)
+    }
+
+    /// The constructor of `type_name`
+    pub fn constructor(&self, type_name: &str) -> Option<&Constructor> {
+        self.constructors.iter().find(|c| c.type_name == type_name)
+    }
+
+    /// Check that every type `type_name` uses is declared and that none contains itself
+    fn check_type(&self, type_name: &str, depth: usize) -> Result<(), TonError> {
+        if depth > MAX_TYPE_DEPTH {
+            return Err(TonError::InvalidSchema(format!("{} nests too deeply, or contains itself", type_name)));
+        }
+        let constructor = self
+            .constructor(type_name)
+            .ok_or_else(|| TonError::InvalidSchema(format!("{} is not declared", type_name)))?;
+        for field in &constructor.fields {
+            if let Some(named) = named_type(&field.ty) {
+                self.check_type(named, depth + 1)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The declared type a field holds, through references and `Maybe`
+pub(crate) fn named_type(ty: &TlbType) -> Option<&str> {
+    match ty {
+        TlbType::Named(name) => Some(name),
+        TlbType::Ref(inner) | TlbType::Maybe(inner) => named_type(inner),
+        _ => None,
+    }
+}
+
+impl TonStorageDefinition {
+    /// Parse and check a storage definition
+    pub fn from_json(content: &str) -> Result<Self, TonError> {
+        let definition: Self = serde_json::from_str(content).map_err(TonError::Json)?;
+        definition.parse_schema()?;
+        Ok(definition)
+    }
+
+    /// A definition of a plain `.tlb` file, its last type being the data cell's
+    pub fn from_tlb(name: &str, schema: &str) -> Result<Self, TonError> {
+        let definition = Self { contract: None, name: name.to_string(), root: None, schema: schema.to_string() };
+        definition.parse_schema()?;
+        Ok(definition)
+    }
+
+    /// The schema, with the data cell's type
+    pub fn parse_schema(&self) -> Result<(TlbSchema, String), TonError> {
+        let schema = TlbSchema::parse(&self.schema)?;
+        let root = match &self.root {
+            Some(root) => root.clone(),
+            None => schema
+                .constructors
+                .last()
+                .map(|c| c.type_name.clone())
+                .ok_or_else(|| TonError::InvalidSchema("The schema declares no types".into()))?,
+        };
+        if schema.constructor(&root).is_none() {
+            return Err(TonError::InvalidSchema(format!("{} is not declared", root)));
+        }
+        Ok((schema, root))
+    }
+}
+
+fn strip_comments(source: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut rest = source;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("//") {
+            rest = after.find('\n').map_or("", |end| &after[end..]);
+        } else if let Some(after) = rest.strip_prefix("/*") {
+            rest = after.find("*/").map_or("", |end| &after[end + 2..]);
+            out.push(' ');
+        } else {
+            let c = rest.chars().next().expect("not empty");
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    out
+}
+
+fn parse_constructor(declaration: &str) -> Result<Constructor, TonError> {
+    let invalid = |why: &str| TonError::InvalidSchema(format!("{} in '{}'", why, declaration));
+    let (left, type_name) = declaration.rsplit_once('=').ok_or_else(|| invalid("No '='"))?;
+    let type_name = type_name.trim();
+    if type_name.contains(char::is_whitespace) || !type_name.starts_with(|c: char| c.is_ascii_uppercase()) {
+        return Err(invalid("Parameterized or unnamed result types are not supported"));
+    }
+
+    let tokens = split_fields(left).map_err(&invalid)?;
+    let (head, fields) = tokens.split_first().ok_or_else(|| invalid("No constructor"))?;
+    let tag_at = head.find(['#', '$']).ok_or_else(|| invalid("Tags must be written out, as #_ or $_ if empty"))?;
+    let (name, tag) = head.split_at(tag_at);
+    check_tag(tag).map_err(&invalid)?;
+
+    let fields = fields
+        .iter()
+        .map(|token| {
+            if token.starts_with('{') {
+                return Err(invalid("Implicit fields and constraints are not supported"));
+            }
+            let (name, type_expr) = token.split_once(':').ok_or_else(|| invalid("Fields are written name:Type"))?;
+            Ok(TlbField { name: name.to_string(), ty: TlbType::parse(type_expr)?, type_expr: type_expr.to_string() })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    for (i, field) in fields.iter().enumerate() {
+        if field.name != "_" && fields[..i].iter().any(|f| f.name == field.name) {
+            return Err(invalid("Field names must be unique"));
+        }
+    }
+    Ok(Constructor { name: name.to_string(), tag: tag.to_string(), fields, type_name: type_name.to_string() })
+}
+
+/// Split on whitespace outside parentheses
+fn split_fields(source: &str) -> Result<Vec<&str>, &'static str> {
+    let mut tokens = Vec::new();
+    let (mut depth, mut start) = (0usize, None);
+    for (i, c) in source.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.checked_sub(1).ok_or("Unbalanced parentheses")?,
+            c if c.is_whitespace() && depth == 0 => {
+                if let Some(s) = start.take() {
+                    tokens.push(&source[s..i]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(i);
+    }
+    if depth != 0 {
+        return Err("Unbalanced parentheses");
+    }
+    if let Some(s) = start {
+        tokens.push(&source[s..]);
+    }
+    Ok(tokens)
+}
+
+fn check_tag(tag: &str) -> Result<(), &'static str> {
+    let valid = match tag.split_at(1) {
+        (_, "_") => true,
+        ("#", hex) => hex.len() <= 8 && hex.chars().all(|c| c.is_ascii_hexdigit()) && !hex.is_empty(),
+        (_, bits) => bits.len() <= 32 && bits.chars().all(|c| c == '0' || c == '1') && !bits.is_empty(),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err("Invalid tag")
+    }
+}
+
+/// The bits of a tag, most significant first
+pub(crate) fn tag_bits(tag: &str) -> Vec<bool> {
+    match tag.split_at(1) {
+        (_, "_") => Vec::new(),
+        ("#", hex) => {
+            let value = u32::from_str_radix(hex, 16).unwrap_or(0);
+            (0..hex.len() * 4).rev().map(|i| value >> i & 1 == 1).collect()
+        }
+        (_, bits) => bits.chars().map(|c| c == '1').collect(),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A jetton wallet's data with a made-up config behind a reference
+    pub(crate) const WALLET_SCHEMA: &str = r#"
+        // Fee settings, stored inline
+        fee$01 bps:uint16 recipient:MsgAddress = Fee;
+        config#3f5476ca admin:MsgAddress fee:Fee paused:Bool /* spare */ = Config;
+        storage#_ balance:Coins owner:MsgAddress master:MsgAddress wallet_code:^Cell
+            config:(Maybe ^Config) nonce:int32 = Storage;
+    "#;
+
+    #[test]
+    fn test_parse_schema() {
+        let schema = TlbSchema::parse(WALLET_SCHEMA).unwrap();
+        assert_eq!(schema.constructors.len(), 3);
+        let storage = schema.constructor("Storage").unwrap();
+        assert_eq!((storage.name.as_str(), storage.tag.as_str()), ("storage", "#_"));
+        let types: Vec<_> = storage.fields.iter().map(|f| f.ty.clone()).collect();
+        assert_eq!(types, [
+            TlbType::VarUint(16),
+            TlbType::Address,
+            TlbType::Address,
+            TlbType::CellRef,
+            TlbType::Maybe(Box::new(TlbType::Ref(Box::new(TlbType::Named("Config".into()))))),
+            TlbType::Int(32),
+        ]);
+        assert_eq!(tag_bits("#3f5476ca").len(), 32);
+        assert_eq!(tag_bits("$01"), [false, true]);
+        assert!(tag_bits("#_").is_empty());
+
+        assert_eq!(TlbType::parse("(## 5)").unwrap(), TlbType::Uint(5));
+        assert_eq!(TlbType::parse("(HashmapE 267 Bool)").unwrap(), TlbType::Dict(267));
+        assert_eq!(TlbType::parse("bits256").unwrap().fixed_bits(), Some(256));
+        assert!(TlbType::parse("uint300").is_err());
+        assert!(TlbType::parse("Cell").is_err());
+        assert!(TlbType::parse("(Either A B)").is_err());
+
+        assert!(TlbSchema::parse("a$0 x:uint8 = A; b$1 y:uint8 = A;").is_err());
+        assert!(TlbSchema::parse("a x:uint8 = A;").is_err());
+        assert!(TlbSchema::parse("a$_ x:Missing = A;").is_err());
+        assert!(TlbSchema::parse("a$_ x:^A = A;").is_err());
+        assert!(TlbSchema::parse("a$_ {n:#} x:(## n) = A;").is_err());
+        assert!(TlbSchema::parse("a$_ x:uint8 x:uint8 = A;").is_err());
+        assert!(TlbSchema::parse("a$_ x:uint8 = A X;").is_err());
+
+        let definition = TonStorageDefinition::from_tlb("Wallet", WALLET_SCHEMA).unwrap();
+        assert_eq!(definition.parse_schema().unwrap().1, "Storage");
+        let json = r#"{"name": "Wallet", "root": "Config", "schema": "fee$01 bps:uint16 = Fee; config$_ fee:Fee = Config;"}"#;
+        assert_eq!(TonStorageDefinition::from_json(json).unwrap().parse_schema().unwrap().1, "Config");
+        assert!(TonStorageDefinition::from_json(r#"{"root": "Other", "schema": "a$_ x:uint8 = A;"}"#).is_err());
+    }
+}
@@ -0,0 +1,19 @@
+//! Base58 and base64 parsing of Solana RPC fields in the controller
+//!
+//! Addresses, block hashes and account data arrive as text from untrusted
+//! RPC responses and are decoded before any witness is built.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use traverse_valence::fuzz::{parse_base58_address, parse_base58_hash, parse_base64_data};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let _ = parse_base58_address(text);
+    let _ = parse_base58_hash(text);
+    let _ = parse_base64_data(text);
+});
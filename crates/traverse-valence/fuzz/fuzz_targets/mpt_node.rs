@@ -0,0 +1,41 @@
+//! Merkle-Patricia trie node decoding in the domain verifier
+//!
+//! Input layout:
+//! - `[32 bytes]` storage key
+//! - `[1 byte]` expected value length `n`, then `n` bytes of expected value
+//! - any number of proof nodes, each a 2-byte big-endian length and the node
+//!
+//! The proof is rooted at the hash of its first node so the fuzzer reaches
+//! the RLP decoding below the root check. The whole input is also fed to the
+//! hex-prefix path decoder.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use traverse_valence::fuzz::{decode_mpt_path, verify_mpt_proof_rooted};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = decode_mpt_path(data);
+
+    if data.len() < 33 {
+        return;
+    }
+    let (key, rest) = data.split_at(32);
+    let value_len = rest[0] as usize;
+    let rest = &rest[1..];
+    if rest.len() < value_len {
+        return;
+    }
+    let (expected_value, mut rest) = rest.split_at(value_len);
+
+    let mut nodes = Vec::new();
+    while rest.len() >= 2 {
+        let len = u16::from_be_bytes([rest[0], rest[1]]) as usize;
+        let body = &rest[2..];
+        let node = &body[..len.min(body.len())];
+        nodes.push(node.to_vec());
+        rest = &body[node.len()..];
+    }
+
+    let _ = verify_mpt_proof_rooted(key, expected_value, &nodes);
+});
@@ -0,0 +1,23 @@
+//! Circuit-side parsing of controller witnesses
+//!
+//! Witness bytes cross the controller/circuit boundary unauthenticated, so the
+//! parser must reject anything malformed without panicking. Witnesses it
+//! accepts are run through a processor to cover value extraction as well.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use traverse_valence::{CircuitProcessor, FieldType, ZeroSemantics};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(witness) = CircuitProcessor::parse_witness_from_bytes(data) else {
+        return;
+    };
+
+    let processor = CircuitProcessor::new(
+        witness.layout_commitment,
+        vec![FieldType::Uint256; witness.field_index as usize + 1],
+        vec![ZeroSemantics::ValidZero; witness.field_index as usize + 1],
+    );
+    let _ = processor.process_witness(&witness);
+});
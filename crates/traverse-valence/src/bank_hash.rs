@@ -0,0 +1,192 @@
+//! Bank-hash anchored Solana account verification (no_std compatible)
+//!
+//! Checks the proofs `traverse_solana::bank_hash` builds: an account's hash
+//! up a fanout-16 Merkle tree to its slot's accounts delta hash, then the
+//! bank hash computed from it. Circuits compare that bank hash with one they
+//! already trust, such as one a supermajority of stake voted for, instead of
+//! trusting account data an RPC node returned.
+//!
+//! Accounts hash with blake3 and the tree and bank hash with sha256, which
+//! the circuit environment supplies through [`BankHasher`] so it can use its
+//! precompiles.
+
+use alloc::vec::Vec;
+
+/// Children per node of the accounts delta Merkle tree
+pub const MERKLE_FANOUT: usize = 16;
+
+/// Hash functions supplied by the circuit environment
+pub trait BankHasher {
+    /// sha256 of `parts` concatenated
+    fn sha256(&self, parts: &[&[u8]]) -> [u8; 32];
+    /// blake3 of `parts` concatenated
+    fn blake3(&self, parts: &[&[u8]]) -> [u8; 32];
+}
+
+/// An account as it is committed to in the delta hash
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolanaAccountState {
+    pub pubkey: [u8; 32],
+    pub lamports: u64,
+    pub rent_epoch: u64,
+    pub data: Vec<u8>,
+    pub executable: bool,
+    pub owner: [u8; 32],
+}
+
+impl SolanaAccountState {
+    /// `blake3(lamports, rent_epoch, data, executable, owner, pubkey)`, or zeros without lamports
+    pub fn hash(&self, hasher: &impl BankHasher) -> [u8; 32] {
+        if self.lamports == 0 {
+            return [0u8; 32];
+        }
+        hasher.blake3(&[
+            &self.lamports.to_le_bytes(),
+            &self.rent_epoch.to_le_bytes(),
+            &self.data,
+            &[self.executable as u8],
+            &self.owner,
+            &self.pubkey,
+        ])
+    }
+}
+
+/// One level of a delta hash proof: the node's siblings and itself, and its position among them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleLevel {
+    pub children: Vec<[u8; 32]>,
+    pub position: usize,
+}
+
+/// Everything a slot's bank hash is computed from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BankHashComponents {
+    pub parent_bank_hash: [u8; 32],
+    pub accounts_delta_hash: [u8; 32],
+    pub signature_count: u64,
+    pub last_blockhash: [u8; 32],
+    /// Present only in the slot that folds the epoch accounts hash in
+    pub epoch_accounts_hash: Option<[u8; 32]>,
+}
+
+impl BankHashComponents {
+    /// The bank hash
+    pub fn hash(&self, hasher: &impl BankHasher) -> [u8; 32] {
+        let bank_hash = hasher.sha256(&[
+            &self.parent_bank_hash,
+            &self.accounts_delta_hash,
+            &self.signature_count.to_le_bytes(),
+            &self.last_blockhash,
+        ]);
+        match &self.epoch_accounts_hash {
+            Some(epoch_accounts_hash) => hasher.sha256(&[&bank_hash, epoch_accounts_hash]),
+            None => bank_hash,
+        }
+    }
+}
+
+/// Root a leaf and its delta hash proof hash to
+pub fn delta_hash_root(
+    hasher: &impl BankHasher,
+    leaf: &[u8; 32],
+    levels: &[MerkleLevel],
+) -> Result<[u8; 32], &'static str> {
+    if levels.is_empty() {
+        return Err("Delta hash proof has no levels");
+    }
+    levels.iter().try_fold(*leaf, |node, level| {
+        if level.children.len() > MERKLE_FANOUT || level.children.get(level.position) != Some(&node) {
+            return Err("Delta hash proof level does not contain the node below it");
+        }
+        let parts: Vec<&[u8]> = level.children.iter().map(|child| child.as_slice()).collect();
+        Ok(hasher.sha256(&parts))
+    })
+}
+
+/// Verify an account against a trusted bank hash
+pub fn verify_bank_hash_proof(
+    hasher: &impl BankHasher,
+    account: &SolanaAccountState,
+    delta_proof: &[MerkleLevel],
+    components: &BankHashComponents,
+    trusted_bank_hash: &[u8; 32],
+) -> Result<(), &'static str> {
+    if components.hash(hasher) != *trusted_bank_hash {
+        return Err("Bank hash components do not hash to the trusted bank hash");
+    }
+    if delta_hash_root(hasher, &account.hash(hasher), delta_proof)? != components.accounts_delta_hash {
+        return Err("Account does not hash to the accounts delta hash");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stands in for sha256 and blake3; the checks only need a collision-free-enough function
+    struct TestHasher;
+
+    impl BankHasher for TestHasher {
+        fn sha256(&self, parts: &[&[u8]]) -> [u8; 32] {
+            mix(1, parts)
+        }
+
+        fn blake3(&self, parts: &[&[u8]]) -> [u8; 32] {
+            mix(2, parts)
+        }
+    }
+
+    fn mix(seed: u8, parts: &[&[u8]]) -> [u8; 32] {
+        let mut out = [seed; 32];
+        for (i, byte) in parts.iter().flat_map(|part| part.iter()).enumerate() {
+            out[i % 32] = out[i % 32].wrapping_mul(31).wrapping_add(*byte).rotate_left(3);
+        }
+        out
+    }
+
+    fn account() -> SolanaAccountState {
+        SolanaAccountState {
+            pubkey: [3; 32],
+            lamports: 10,
+            rent_epoch: u64::MAX,
+            data: alloc::vec![7; 40],
+            executable: false,
+            owner: [9; 32],
+        }
+    }
+
+    #[test]
+    fn test_verify_bank_hash_proof() {
+        let hasher = TestHasher;
+        let leaf = account().hash(&hasher);
+        let first = MerkleLevel { children: alloc::vec![[1; 32], leaf, [2; 32]], position: 1 };
+        let chunk = hasher.sha256(&[&[1; 32], &leaf, &[2; 32]]);
+        let second = MerkleLevel { children: alloc::vec![[4; 32], chunk], position: 1 };
+        let delta = hasher.sha256(&[&[4; 32], &chunk]);
+        let components = BankHashComponents {
+            parent_bank_hash: [5; 32],
+            accounts_delta_hash: delta,
+            signature_count: 12,
+            last_blockhash: [6; 32],
+            epoch_accounts_hash: None,
+        };
+        let bank_hash = components.hash(&hasher);
+        let proof = [first.clone(), second];
+        verify_bank_hash_proof(&hasher, &account(), &proof, &components, &bank_hash).unwrap();
+
+        let mut changed = account();
+        changed.data[0] = 8;
+        assert!(verify_bank_hash_proof(&hasher, &changed, &proof, &components, &bank_hash).is_err());
+        assert!(verify_bank_hash_proof(&hasher, &account(), &proof, &components, &[0; 32]).is_err());
+        assert!(verify_bank_hash_proof(&hasher, &account(), &proof[..1], &components, &bank_hash).is_err());
+        assert!(verify_bank_hash_proof(&hasher, &account(), &[], &components, &bank_hash).is_err());
+
+        let mut with_epoch_hash = components.clone();
+        with_epoch_hash.epoch_accounts_hash = Some([7; 32]);
+        assert_ne!(with_epoch_hash.hash(&hasher), bank_hash);
+
+        changed.lamports = 0;
+        assert_eq!(changed.hash(&hasher), [0; 32]);
+    }
+}
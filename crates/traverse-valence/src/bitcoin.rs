@@ -0,0 +1,555 @@
+//! Bitcoin SPV inclusion checks (no_std compatible)
+//!
+//! Redoes what `traverse_bitcoin` checks when it builds a transaction
+//! proof, on the witness the controller writes: the raw transaction hashes
+//! to its txid, the txid up its Merkle branch to the root in its block's
+//! header, and each header after that one names the one before it and
+//! meets its own target. The work of the headers is summed for the circuit
+//! to weigh. Whether each target is the one consensus required at its
+//! height depends on the network and on headers outside the witness, and is
+//! checked by `traverse_bitcoin` instead.
+//!
+//! A 64-byte transaction serializes to the same length as an inner Merkle
+//! node, so such transactions are refused, as `traverse_bitcoin` does.
+//!
+//! ## Witness Format
+//! ```text
+//! [8 bytes block_height] +
+//! [4 bytes header_count] +
+//! [header_count * 80 bytes headers, the transaction's block first] +
+//! [4 bytes merkle_index] +
+//! [4 bytes tx_count] +
+//! [1 byte branch_length] +
+//! [branch_length * 32 bytes branch, from the txid up] +
+//! [4 bytes transaction_length] +
+//! [transaction_length bytes transaction] +
+//! [1 byte output_present] +
+//! [4 bytes vout]
+//! ```
+//!
+//! Integers are little-endian and hashes in internal byte order, the
+//! reverse of what RPCs show.
+
+use alloc::vec::Vec;
+use sha2::{Digest, Sha256};
+
+/// Size of a serialized header
+pub const HEADER_SIZE: usize = 80;
+
+/// Length of a witness with no headers, branch or transaction
+pub const WITNESS_FIXED_LEN: usize = 8 + 4 + 4 + 4 + 1 + 4 + 1 + 4;
+
+/// A 256-bit number, least significant limb first
+type Limbs = [u64; 4];
+
+/// SHA-256 applied twice, as Bitcoin hashes headers, transactions and Merkle nodes
+pub fn double_sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(Sha256::digest(data)).into()
+}
+
+/// A transaction output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxOutput {
+    /// Value in satoshis
+    pub value: u64,
+    pub script_pubkey: Vec<u8>,
+}
+
+/// What an inclusion proof needs from a raw transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedTransaction {
+    /// Txid, in internal byte order
+    pub txid: [u8; 32],
+    /// Size of the serialization the txid hashes
+    pub stripped_size: usize,
+    pub is_coinbase: bool,
+    pub outputs: Vec<TxOutput>,
+}
+
+/// A cursor over serialized data
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], &'static str> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or("Data ends early")?;
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], &'static str> {
+        Ok(self.take(N)?.try_into().expect("N bytes"))
+    }
+
+    fn u8(&mut self) -> Result<u8, &'static str> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, &'static str> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, &'static str> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    /// A CompactSize length, which must use its shortest form
+    fn compact_size(&mut self) -> Result<u64, &'static str> {
+        let (value, minimum) = match self.u8()? {
+            0xfd => (u16::from_le_bytes(self.array()?) as u64, 0xfd),
+            0xfe => (u32::from_le_bytes(self.array()?) as u64, 0x1_0000),
+            0xff => (self.u64()?, 0x1_0000_0000),
+            small => return Ok(small as u64),
+        };
+        if value < minimum {
+            return Err("Non-canonical length");
+        }
+        Ok(value)
+    }
+
+    fn var_bytes(&mut self) -> Result<&'a [u8], &'static str> {
+        let length = self.compact_size()?;
+        self.take(usize::try_from(length).unwrap_or(usize::MAX))
+    }
+}
+
+/// Parse a raw transaction, with witnesses if it has any
+pub fn parse_transaction(bytes: &[u8]) -> Result<ParsedTransaction, &'static str> {
+    let mut reader = Reader { bytes, position: 0 };
+    reader.u32()?;
+    let mut body_start = reader.position;
+
+    let mut input_count = reader.compact_size()?;
+    let segwit = input_count == 0 && bytes.get(reader.position) == Some(&1);
+    if segwit {
+        reader.u8()?;
+        body_start = reader.position;
+        input_count = reader.compact_size()?;
+    }
+
+    let mut is_coinbase = input_count == 1;
+    for _ in 0..input_count {
+        let prev_txid = reader.array::<32>()?;
+        let prev_vout = reader.u32()?;
+        is_coinbase &= prev_txid == [0; 32] && prev_vout == u32::MAX;
+        reader.var_bytes()?;
+        reader.u32()?;
+    }
+    let mut outputs = Vec::new();
+    for _ in 0..reader.compact_size()? {
+        let value = reader.u64()?;
+        outputs.push(TxOutput { value, script_pubkey: reader.var_bytes()?.to_vec() });
+    }
+    let body_end = reader.position;
+
+    if segwit {
+        for _ in 0..input_count {
+            for _ in 0..reader.compact_size()? {
+                reader.var_bytes()?;
+            }
+        }
+    }
+    let lock_time = reader.array::<4>()?;
+    if reader.position != bytes.len() {
+        return Err("Data continues after the lock time");
+    }
+    if input_count == 0 || outputs.is_empty() {
+        return Err("A transaction needs inputs and outputs");
+    }
+
+    let mut stripped = Vec::with_capacity(4 + body_end - body_start + 4);
+    stripped.extend_from_slice(&bytes[..4]);
+    stripped.extend_from_slice(&bytes[body_start..body_end]);
+    stripped.extend_from_slice(&lock_time);
+    Ok(ParsedTransaction { txid: double_sha256(&stripped), stripped_size: stripped.len(), is_coinbase, outputs })
+}
+
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut data = [0u8; 64];
+    data[..32].copy_from_slice(left);
+    data[32..].copy_from_slice(right);
+    double_sha256(&data)
+}
+
+/// Levels above the txids in a tree of `tx_count` transactions
+fn tree_depth(tx_count: u32) -> usize {
+    let mut width = tx_count;
+    let mut depth = 0;
+    while width > 1 {
+        width = width.div_ceil(2);
+        depth += 1;
+    }
+    depth
+}
+
+/// The Merkle root `txid` at `index` among `tx_count` transactions leads to through `branch`
+///
+/// The branch must be exactly as deep as the tree, so an inner node cannot
+/// be passed off as a txid by cutting it short, and the last node of an odd
+/// level must be paired with itself.
+pub fn merkle_root(txid: &[u8; 32], index: u32, tx_count: u32, branch: &[[u8; 32]]) -> Result<[u8; 32], &'static str> {
+    if index >= tx_count {
+        return Err("Merkle index is outside the block");
+    }
+    if branch.len() != tree_depth(tx_count) {
+        return Err("Merkle branch is not as deep as the block's tree");
+    }
+    let mut hash = *txid;
+    let mut position = index;
+    for (height, sibling) in branch.iter().enumerate() {
+        if position.is_multiple_of(2) {
+            let width = ((tx_count as u64 + (1u64 << height) - 1) >> height) as u32;
+            if position + 1 == width && *sibling != hash {
+                return Err("Merkle branch pairs a lone node with another");
+            }
+            hash = hash_pair(&hash, sibling);
+        } else {
+            hash = hash_pair(sibling, &hash);
+        }
+        position /= 2;
+    }
+    Ok(hash)
+}
+
+fn limbs_from_le(bytes: &[u8; 32]) -> Limbs {
+    core::array::from_fn(|i| u64::from_le_bytes(bytes[i * 8..i * 8 + 8].try_into().expect("8 bytes")))
+}
+
+fn limbs_to_be(limbs: &Limbs) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    for (i, limb) in limbs.iter().enumerate() {
+        bytes[24 - i * 8..32 - i * 8].copy_from_slice(&limb.to_be_bytes());
+    }
+    bytes
+}
+
+fn limbs_gt(a: &Limbs, b: &Limbs) -> bool {
+    a.iter().rev().cmp(b.iter().rev()).is_gt()
+}
+
+fn limbs_add(a: &Limbs, b: &Limbs) -> (Limbs, bool) {
+    let mut sum = [0u64; 4];
+    let mut carry = false;
+    for i in 0..4 {
+        let (s, c1) = a[i].overflowing_add(b[i]);
+        let (s, c2) = s.overflowing_add(carry as u64);
+        sum[i] = s;
+        carry = c1 || c2;
+    }
+    (sum, carry)
+}
+
+fn limbs_sub(a: &Limbs, b: &Limbs) -> Limbs {
+    let mut difference = [0u64; 4];
+    let mut borrow = false;
+    for i in 0..4 {
+        let (d, b1) = a[i].overflowing_sub(b[i]);
+        let (d, b2) = d.overflowing_sub(borrow as u64);
+        difference[i] = d;
+        borrow = b1 || b2;
+    }
+    difference
+}
+
+/// `numerator / divisor` by shifting and subtracting
+fn limbs_div(numerator: &Limbs, divisor: &Limbs) -> Limbs {
+    let mut quotient = [0u64; 4];
+    let mut remainder = [0u64; 4];
+    for bit in (0..256).rev() {
+        // The remainder is below the divisor, so it overflows only if the divisor has its top bit set
+        let overflow = remainder[3] >> 63 == 1;
+        for i in (1..4).rev() {
+            remainder[i] = remainder[i] << 1 | remainder[i - 1] >> 63;
+        }
+        remainder[0] = remainder[0] << 1 | (numerator[bit / 64] >> (bit % 64)) & 1;
+        if overflow || !limbs_gt(divisor, &remainder) {
+            remainder = limbs_sub(&remainder, divisor);
+            quotient[bit / 64] |= 1 << (bit % 64);
+        }
+    }
+    quotient
+}
+
+/// Expand a compact target; negative, zero and overflowing targets are rejected
+fn bits_to_target(bits: u32) -> Result<Limbs, &'static str> {
+    let invalid = "Invalid target bits";
+    let exponent = bits >> 24;
+    let mantissa = (bits & 0x007f_ffff) as u64;
+    if bits & 0x0080_0000 != 0 || mantissa == 0 {
+        return Err(invalid);
+    }
+    let mut target = [0u64; 4];
+    if exponent <= 3 {
+        target[0] = mantissa >> (8 * (3 - exponent));
+    } else {
+        let shift = 8 * (exponent as usize - 3);
+        let (limb, offset) = (shift / 64, shift % 64);
+        let high = if offset == 0 { 0 } else { mantissa >> (64 - offset) };
+        if limb >= 4 || (limb == 3 && high != 0) {
+            return Err(invalid);
+        }
+        target[limb] = mantissa << offset;
+        if limb < 3 {
+            target[limb + 1] = high;
+        }
+    }
+    if target == [0; 4] {
+        return Err(invalid);
+    }
+    Ok(target)
+}
+
+/// Hash of a header, in internal byte order
+pub fn header_hash(header: &[u8; HEADER_SIZE]) -> [u8; 32] {
+    double_sha256(header)
+}
+
+/// Check a header meets its own target and return its work, `2^256 / (target + 1)`
+fn header_work(header: &[u8; HEADER_SIZE]) -> Result<Limbs, &'static str> {
+    let target = bits_to_target(u32::from_le_bytes(header[72..76].try_into().expect("4 bytes")))?;
+    if limbs_gt(&limbs_from_le(&header_hash(header)), &target) {
+        return Err("Header does not meet its target");
+    }
+    // 2^256 / (target + 1), without leaving 256 bits
+    let (divisor, _) = limbs_add(&target, &[1, 0, 0, 0]);
+    let not_target = target.map(|limb| !limb);
+    let (work, _) = limbs_add(&limbs_div(&not_target, &divisor), &[1, 0, 0, 0]);
+    Ok(work)
+}
+
+/// Check the headers form a chain, each meeting its target, and return the tip's hash and their work
+///
+/// Work is big-endian.
+pub fn verify_header_chain(headers: &[[u8; HEADER_SIZE]]) -> Result<([u8; 32], [u8; 32]), &'static str> {
+    let mut chain_work = [0u64; 4];
+    let mut prev_hash = None;
+    for header in headers {
+        if prev_hash.is_some_and(|prev_hash: [u8; 32]| header[4..36] != prev_hash) {
+            return Err("Header does not follow the one before it");
+        }
+        let (sum, overflow) = limbs_add(&chain_work, &header_work(header)?);
+        if overflow {
+            return Err("Chain work overflows");
+        }
+        chain_work = sum;
+        prev_hash = Some(header_hash(header));
+    }
+    let tip_hash = prev_hash.ok_or("Header chain is empty")?;
+    Ok((tip_hash, limbs_to_be(&chain_work)))
+}
+
+/// A Bitcoin transaction inclusion witness
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitcoinWitness {
+    /// Height of the transaction's block
+    pub block_height: u64,
+    /// The transaction's block first, then each block built on it
+    pub headers: Vec<[u8; HEADER_SIZE]>,
+    /// Position of the transaction in its block
+    pub merkle_index: u32,
+    /// Number of transactions in the block
+    pub tx_count: u32,
+    /// Sibling at each level, from the txid up
+    pub branch: Vec<[u8; 32]>,
+    /// The raw transaction, with witnesses if it has any
+    pub transaction: Vec<u8>,
+    /// Output the witness proves, if any
+    pub vout: Option<u32>,
+}
+
+/// What a valid witness proves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitcoinTxConfirmation {
+    pub txid: [u8; 32],
+    pub block_hash: [u8; 32],
+    pub block_height: u64,
+    /// Headers from the transaction's block to the tip, both included
+    pub confirmations: u32,
+    pub tip_hash: [u8; 32],
+    /// Work of the headers, big-endian
+    pub chain_work: [u8; 32],
+    /// Output at the witness's `vout`, if it names one
+    pub output: Option<TxOutput>,
+}
+
+impl BitcoinWitness {
+    /// Serialize in the witness format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            WITNESS_FIXED_LEN + self.headers.len() * HEADER_SIZE + self.branch.len() * 32 + self.transaction.len(),
+        );
+        bytes.extend_from_slice(&self.block_height.to_le_bytes());
+        bytes.extend_from_slice(&(self.headers.len() as u32).to_le_bytes());
+        self.headers.iter().for_each(|header| bytes.extend_from_slice(header));
+        bytes.extend_from_slice(&self.merkle_index.to_le_bytes());
+        bytes.extend_from_slice(&self.tx_count.to_le_bytes());
+        bytes.push(self.branch.len() as u8);
+        self.branch.iter().for_each(|sibling| bytes.extend_from_slice(sibling));
+        bytes.extend_from_slice(&(self.transaction.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.transaction);
+        bytes.push(self.vout.is_some() as u8);
+        bytes.extend_from_slice(&self.vout.unwrap_or(0).to_le_bytes());
+        bytes
+    }
+
+    /// Parse a witness; every byte must be accounted for
+    pub fn parse(bytes: &[u8]) -> Result<Self, &'static str> {
+        let mut reader = Reader { bytes, position: 0 };
+        let block_height = reader.u64()?;
+        let header_count = reader.u32()? as usize;
+        let headers = (0..header_count).map(|_| reader.array()).collect::<Result<_, _>>()?;
+        let merkle_index = reader.u32()?;
+        let tx_count = reader.u32()?;
+        let branch_length = reader.u8()? as usize;
+        let branch = (0..branch_length).map(|_| reader.array()).collect::<Result<_, _>>()?;
+        let transaction_length = reader.u32()? as usize;
+        let transaction = reader.take(transaction_length)?.to_vec();
+        let output_present = reader.u8()?;
+        let vout = reader.u32()?;
+        if reader.position != bytes.len() {
+            return Err("Data continues after the witness");
+        }
+        let vout = match output_present {
+            0 if vout == 0 => None,
+            1 => Some(vout),
+            _ => return Err("Invalid output flag"),
+        };
+        Ok(Self { block_height, headers, merkle_index, tx_count, branch, transaction, vout })
+    }
+
+    /// Check the transaction against its block and the headers against each other
+    pub fn verify(&self) -> Result<BitcoinTxConfirmation, &'static str> {
+        let transaction = parse_transaction(&self.transaction)?;
+        if transaction.stripped_size == 64 {
+            return Err("A 64-byte transaction cannot be told from a Merkle node");
+        }
+        let block = self.headers.first().ok_or("Witness has no headers")?;
+        if merkle_root(&transaction.txid, self.merkle_index, self.tx_count, &self.branch)? != block[36..68] {
+            return Err("Transaction is not in the block");
+        }
+        if (self.merkle_index == 0) != transaction.is_coinbase {
+            return Err("Only the first transaction of a block is a coinbase");
+        }
+        let (tip_hash, chain_work) = verify_header_chain(&self.headers)?;
+        let confirmations = u32::try_from(self.headers.len()).map_err(|_| "Witness has too many headers")?;
+        self.block_height.checked_add(confirmations as u64 - 1).ok_or("Tip height overflows")?;
+        let output = match self.vout {
+            Some(vout) => Some(transaction.outputs.get(vout as usize).ok_or("Transaction has no such output")?.clone()),
+            None => None,
+        };
+        Ok(BitcoinTxConfirmation {
+            txid: transaction.txid,
+            block_hash: header_hash(block),
+            block_height: self.block_height,
+            confirmations,
+            tip_hash,
+            chain_work,
+            output,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GENESIS_HEADER: &str = "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c";
+
+    /// Txids of mainnet block 100000, in the byte order RPCs show
+    const BLOCK_100000_TXIDS: [&str; 4] = [
+        "8c14f0db3df150123e6f3dbbf30f8b955a8249b62ac1d1ff16284aefa3d06d87",
+        "fff2525b8931402dd09222c50775608f75787bd2b87e56995a7bdd30f79702c4",
+        "6359f0868171b1d194cbee1af2f16ea598ae8fad666d9b012c8ed2b79a236ec4",
+        "e9a66845e05d5abc0ad04ec80f774a7e585c6e8db975962d069a522137b80c1d",
+    ];
+
+    fn display_hash(hex_str: &str) -> [u8; 32] {
+        let mut hash: [u8; 32] = hex::decode(hex_str).unwrap().try_into().unwrap();
+        hash.reverse();
+        hash
+    }
+
+    #[test]
+    fn test_header_work_and_chain() {
+        let genesis: [u8; HEADER_SIZE] = hex::decode(GENESIS_HEADER).unwrap().try_into().unwrap();
+        let (tip_hash, chain_work) = verify_header_chain(&[genesis]).unwrap();
+        assert_eq!(tip_hash, display_hash("000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"));
+        assert_eq!(chain_work[24..], 0x1_0001_0001u64.to_be_bytes());
+        assert_eq!(chain_work[..24], [0; 24]);
+
+        // The genesis block does not follow itself, and a changed nonce misses the target
+        assert!(verify_header_chain(&[genesis, genesis]).is_err());
+        let mut unmined = genesis;
+        unmined[76] ^= 1;
+        assert!(verify_header_chain(&[unmined]).is_err());
+        assert!(verify_header_chain(&[]).is_err());
+
+        // A divisor with its top bit set still divides
+        assert!(header_work(&[0; HEADER_SIZE]).is_err());
+        let target = bits_to_target(0x2100ffff).unwrap();
+        let (divisor, _) = limbs_add(&target, &[1, 0, 0, 0]);
+        assert_eq!(limbs_div(&[u64::MAX; 4], &divisor), [1, 0, 0, 0]);
+        assert_eq!(limbs_div(&target.map(|limb| !limb), &divisor), [0; 4]);
+        assert!(bits_to_target(0x2201ffff).is_err());
+        assert!(bits_to_target(0x04800000).is_err());
+    }
+
+    #[test]
+    fn test_merkle_root_of_block_100000() {
+        let txids: Vec<_> = BLOCK_100000_TXIDS.iter().map(|txid| display_hash(txid)).collect();
+        let root = display_hash("f3e94742aca4b5ef85488dc37c06c3282295ffec960994b2c0d5ac2a25a95766");
+        let branch = [txids[3], hash_pair(&txids[0], &txids[1])];
+        assert_eq!(merkle_root(&txids[2], 2, 4, &branch).unwrap(), root);
+
+        assert_ne!(merkle_root(&txids[2], 3, 4, &branch).ok(), Some(root));
+        assert!(merkle_root(&txids[2], 4, 4, &branch).is_err());
+        // An inner node cannot stand in for a txid on a branch cut short
+        assert!(merkle_root(&hash_pair(&txids[2], &txids[3]), 1, 4, &branch[1..]).is_err());
+        // A lone last node must be paired with itself
+        assert!(merkle_root(&txids[2], 2, 3, &branch).is_err());
+    }
+
+    #[test]
+    fn test_witness_round_trip_and_segwit_txid() {
+        // A segwit spend: the txid leaves out the marker, flag and witness
+        let mut legacy = hex::decode("0100000001").unwrap();
+        legacy.extend_from_slice(&[0x11; 32]);
+        legacy.extend_from_slice(&[0, 0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 1]);
+        legacy.extend_from_slice(&50_000u64.to_le_bytes());
+        legacy.extend_from_slice(&[2, 0x51, 0x87]);
+        let mut segwit = legacy[..4].to_vec();
+        segwit.extend_from_slice(&[0, 1]);
+        segwit.extend_from_slice(&legacy[4..]);
+        segwit.extend_from_slice(&[1, 2, 0xaa, 0xbb]);
+        legacy.extend_from_slice(&[0; 4]);
+        segwit.extend_from_slice(&[0; 4]);
+        let parsed = parse_transaction(&segwit).unwrap();
+        assert_eq!(parsed.txid, double_sha256(&legacy));
+        assert_eq!(parsed.outputs, [TxOutput { value: 50_000, script_pubkey: alloc::vec![0x51, 0x87] }]);
+        assert!(!parsed.is_coinbase);
+        assert!(parse_transaction(&segwit[..segwit.len() - 1]).is_err());
+
+        let witness = BitcoinWitness {
+            block_height: 7,
+            headers: alloc::vec![[0x22; HEADER_SIZE]],
+            merkle_index: 1,
+            tx_count: 2,
+            branch: alloc::vec![[0x33; 32]],
+            transaction: segwit,
+            vout: Some(0),
+        };
+        let bytes = witness.to_bytes();
+        assert_eq!(bytes.len(), WITNESS_FIXED_LEN + HEADER_SIZE + 32 + witness.transaction.len());
+        assert_eq!(BitcoinWitness::parse(&bytes).unwrap(), witness);
+        assert!(BitcoinWitness::parse(&bytes[..bytes.len() - 1]).is_err());
+        // The header's Merkle root is not the transaction's
+        assert!(witness.verify().is_err());
+    }
+}
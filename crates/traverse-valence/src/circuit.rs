@@ -18,8 +18,27 @@
 //! - Zero semantics consistency checking  
 //! - Type-specific value validation
 //! - Storage location semantic verification
+//!
+//! ## Other Chains
+//!
+//! Witnesses of chains whose proofs are not storage slot proofs are checked
+//! by free functions against an anchor the circuit trusts:
+//! [`verify_sui_witness`] against a checkpoint digest,
+//! [`verify_substrate_witness`] against a block hash,
+//! [`verify_ton_witness`] against a masterchain block's root hash and, with
+//! the `starknet` feature, `verify_starknet_witness` against a state
+//! commitment.
 
 use alloc::{vec, vec::Vec};
+use traverse_core::{extract_packed, FieldEncoding};
+
+#[cfg(feature = "starknet")]
+use crate::starknet::{StarknetFieldValue, StarknetWitness};
+#[cfg(feature = "starknet")]
+use starknet_types_core::felt::Felt;
+use crate::substrate::{SubstrateStorageValue, SubstrateWitness};
+use crate::sui::{SuiObjectValue, SuiWitness};
+use crate::ton::{TonAccountField, TonWitness};
 
 /// Zero semantics for circuit operations (must match storage layout semantics)
 /// 
@@ -83,6 +102,22 @@ impl FieldType {
         }
     }
 
+    /// Size in bytes of the field within its slot
+    ///
+    /// `None` for strings and bytes, whose slot layout depends on their length.
+    #[inline]
+    pub const fn byte_size(&self) -> Option<u8> {
+        match self {
+            FieldType::Bool | FieldType::Uint8 => Some(1),
+            FieldType::Uint16 => Some(2),
+            FieldType::Uint32 => Some(4),
+            FieldType::Uint64 => Some(8),
+            FieldType::Address => Some(20),
+            FieldType::Uint256 | FieldType::Bytes32 => Some(32),
+            FieldType::String | FieldType::Bytes => None,
+        }
+    }
+
     /// Check if extracted value is semantically valid for this field type
     /// 
     /// This prevents type confusion attacks where extracted values
@@ -97,6 +132,11 @@ impl FieldType {
             (FieldType::Uint32, ExtractedValue::Uint32(_)) => true,
             (FieldType::Uint64, ExtractedValue::Uint64(_)) => true,
             (FieldType::Uint256, ExtractedValue::Uint256(_)) => true,
+            // Signed integers read under any integer type, sign-extended
+            (
+                FieldType::Uint8 | FieldType::Uint16 | FieldType::Uint32 | FieldType::Uint64 | FieldType::Uint256,
+                ExtractedValue::Int256(_),
+            ) => true,
             (FieldType::Address, ExtractedValue::Address(addr)) => {
                 // Zero address validation prevents common attack patterns
                 // where uninitialized or malicious addresses are used. Zero addresses
@@ -173,6 +213,12 @@ pub struct CircuitProcessor {
     /// Maximum allowed age for proofs in blocks
     /// Prevents replay attacks with old proofs
     max_proof_age_blocks: u64,
+    /// Byte offset of each field within its slot
+    /// Empty unless configured; packed fields are masked out of the slot's word
+    field_offsets: Vec<u8>,
+    /// Signedness, width and offset of each field, from the resolver
+    /// Empty unless configured; values are truncated or sign-extended to 256 bits
+    field_encodings: Vec<FieldEncoding>,
 }
 
 impl CircuitProcessor {
@@ -192,6 +238,8 @@ impl CircuitProcessor {
             expected_block_height: 0,
             expected_block_hash: [0u8; 32],
             max_proof_age_blocks: 256, // Default: ~1 hour on Ethereum
+            field_offsets: Vec::new(),
+            field_encodings: Vec::new(),
         }
     }
     
@@ -212,6 +260,8 @@ impl CircuitProcessor {
             expected_block_height: block_height,
             expected_block_hash: block_hash,
             max_proof_age_blocks: 256, // Default: ~1 hour on Ethereum
+            field_offsets: Vec::new(),
+            field_encodings: Vec::new(),
         }
     }
     
@@ -233,9 +283,35 @@ impl CircuitProcessor {
             expected_block_height: block_height,
             expected_block_hash: block_hash,
             max_proof_age_blocks,
+            field_offsets: Vec::new(),
+            field_encodings: Vec::new(),
         }
     }
 
+    /// Set the byte offset of each field within its slot, in field order
+    ///
+    /// With offsets set, each fixed-size field is masked out of the 32-byte
+    /// slot value before its zero semantics are checked and its value is
+    /// extracted, so fields packed into one slot are read independently.
+    /// Offsets follow the layout's, counted from the low-order end of the
+    /// slot. Fields without an offset are read from the whole word.
+    pub fn with_field_offsets(mut self, field_offsets: Vec<u8>) -> Self {
+        self.field_offsets = field_offsets;
+        self
+    }
+
+    /// Set the encoding of each field, in field order
+    ///
+    /// Each field's value is cut to its width at the encoding's offset, which
+    /// stands in for a missing [`with_field_offsets`](Self::with_field_offsets)
+    /// entry, then truncated: a `uint12` keeps only its low 12 bits. Signed
+    /// fields, such as an `int24` tick read as [`FieldType::Uint32`], are
+    /// sign-extended and extract to [`ExtractedValue::Int256`].
+    pub fn with_field_encodings(mut self, field_encodings: Vec<FieldEncoding>) -> Self {
+        self.field_encodings = field_encodings;
+        self
+    }
+
     /// Parse witness data from raw bytes (extended format only)
     /// 
     /// This function parses the extended witness format created by the controller.
@@ -255,7 +331,7 @@ impl CircuitProcessor {
     /// - [32 bytes] expected_slot
     pub fn parse_witness_from_bytes(witness_data: &[u8]) -> Result<CircuitWitness, &'static str> {
         // Minimum size check for extended format (without proof data)
-        if witness_data.len() < 176 {
+        if witness_data.len() < crate::witness::STORAGE_WITNESS_FIXED_LEN {
             return Err("Witness data too small (extended format required)");
         }
         
@@ -400,11 +476,19 @@ impl CircuitProcessor {
         let field_type = self.field_types[witness.field_index as usize];
         let expected_semantics = self.field_semantics[witness.field_index as usize];
 
+        // Packed fields share their slot with neighbours, so only the field's
+        // own bytes take part in the checks below. An offset that puts the
+        // field past the end of the slot cannot come from a valid layout.
+        let value = match self.field_value(witness, field_type) {
+            Some(value) => value,
+            None => return CircuitResult::Invalid,
+        };
+
         // CRITICAL: Semantic consistency validation prevents semantic confusion
         // This ensures that claimed zero semantics match the actual field type and value.
         // Without this check, an attacker could claim a non-zero value has "never written"
         // semantics, or claim a zero address has "valid zero" semantics.
-        if !self.validate_semantic_consistency(witness, field_type, expected_semantics) {
+        if !self.validate_semantic_consistency(witness, &value, field_type, expected_semantics) {
             return CircuitResult::Invalid;
         }
 
@@ -418,7 +502,18 @@ impl CircuitProcessor {
 
         // Value extraction with type validation prevents type confusion
         // This ensures extracted values match their claimed field type semantics.
-        let extracted_value = self.extract_value(witness, field_type);
+        let signed = self
+            .field_encodings
+            .get(witness.field_index as usize)
+            .is_some_and(|encoding| encoding.signed);
+        let extracted_value = match field_type {
+            FieldType::Uint8 | FieldType::Uint16 | FieldType::Uint32 | FieldType::Uint64 | FieldType::Uint256
+                if signed =>
+            {
+                ExtractedValue::Int256(value)
+            }
+            _ => self.extract_value(&value, field_type),
+        };
 
         // CRITICAL: Final value validation catches field-specific attacks
         // This applies field-specific security rules (e.g., zero address detection)
@@ -453,10 +548,11 @@ impl CircuitProcessor {
     fn validate_semantic_consistency(
         &self,
         witness: &CircuitWitness,
+        value: &[u8; 32],
         field_type: FieldType,
         expected_semantics: ZeroSemantics,
     ) -> bool {
-        let is_zero = witness.value == [0u8; 32];
+        let is_zero = *value == [0u8; 32];
 
         // Zero value semantic validation prevents zero-value attacks
         if is_zero {
@@ -509,6 +605,36 @@ impl CircuitProcessor {
         witness.key == witness.expected_slot
     }
 
+    /// The witness value narrowed to the field's own bytes
+    ///
+    /// Without a configured offset the whole word is the field. With one,
+    /// fixed-size fields are right-aligned and the rest of the word cleared;
+    /// strings and bytes only ever start a slot. A field with an encoding
+    /// reads no wider than its encoding and is then truncated or
+    /// sign-extended to a full word.
+    #[inline]
+    fn field_value(&self, witness: &CircuitWitness, field_type: FieldType) -> Option<[u8; 32]> {
+        let index = witness.field_index as usize;
+        let encoding = self.field_encodings.get(index);
+        let offset = match self.field_offsets.get(index).or(encoding.map(|encoding| &encoding.offset)) {
+            Some(offset) => *offset,
+            None => return Some(witness.value),
+        };
+        let value = match (field_type.byte_size(), encoding) {
+            (Some(size), Some(encoding)) => {
+                let width = encoding.bits.div_ceil(8).min(32) as u8;
+                extract_packed(&witness.value, offset, size.min(width))?
+            }
+            (Some(size), None) => extract_packed(&witness.value, offset, size)?,
+            (None, _) if offset == 0 => witness.value,
+            (None, _) => return None,
+        };
+        Some(match encoding {
+            Some(encoding) if field_type.byte_size().is_some() => encoding.apply(&value),
+            _ => value,
+        })
+    }
+
     /// Extract value from witness with field type validation
     /// 
     /// This function performs type-safe value extraction from raw storage.
@@ -517,52 +643,52 @@ impl CircuitProcessor {
     /// The extraction follows Ethereum's storage encoding rules to prevent
     /// value interpretation attacks.
     #[inline]
-    fn extract_value(&self, witness: &CircuitWitness, field_type: FieldType) -> ExtractedValue {
+    fn extract_value(&self, value: &[u8; 32], field_type: FieldType) -> ExtractedValue {
         match field_type {
             // Bool extraction checks only the least significant bit
             // This prevents bool value manipulation attacks where non-zero/one values
             // are used to represent boolean state.
-            FieldType::Bool => ExtractedValue::Bool(witness.value[31] != 0),
+            FieldType::Bool => ExtractedValue::Bool(value[31] != 0),
             // Uint8 extraction uses only the least significant byte
             // This prevents integer overflow attacks and ensures proper value bounds.
-            FieldType::Uint8 => ExtractedValue::Uint8(witness.value[31]),
+            FieldType::Uint8 => ExtractedValue::Uint8(value[31]),
             // Uint16 extraction uses big-endian byte order (Ethereum standard)
             // This prevents byte order attacks and ensures consistent value interpretation.
             FieldType::Uint16 => {
-                ExtractedValue::Uint16(u16::from_be_bytes([witness.value[30], witness.value[31]]))
+                ExtractedValue::Uint16(u16::from_be_bytes([value[30], value[31]]))
             }
             // Uint32 extraction uses big-endian byte order
             // Bounds-checked array access prevents buffer overflow attacks.
             FieldType::Uint32 => {
                 ExtractedValue::Uint32(u32::from_be_bytes([
-                    witness.value[28], witness.value[29], witness.value[30], witness.value[31]
+                    value[28], value[29], value[30], value[31]
                 ]))
             }
             // Uint64 extraction uses big-endian byte order
             // Bounds-checked array access prevents buffer overflow attacks.
             FieldType::Uint64 => {
                 ExtractedValue::Uint64(u64::from_be_bytes([
-                    witness.value[24], witness.value[25], witness.value[26], witness.value[27],
-                    witness.value[28], witness.value[29], witness.value[30], witness.value[31]
+                    value[24], value[25], value[26], value[27],
+                    value[28], value[29], value[30], value[31]
                 ]))
             }
             // Uint256 uses the full 32-byte value
             // Direct copy prevents any value manipulation during extraction.
-            FieldType::Uint256 => ExtractedValue::Uint256(witness.value),
+            FieldType::Uint256 => ExtractedValue::Uint256(*value),
             // Address extraction uses bytes 12-31 (20 bytes)
             // This follows Ethereum's address encoding and prevents address manipulation.
             // The extracted address will be validated separately for zero-address attacks.
             FieldType::Address => {
                 let mut addr = [0u8; 20];
-                addr.copy_from_slice(&witness.value[12..32]); // Bounds-checked slice
+                addr.copy_from_slice(&value[12..32]); // Bounds-checked slice
                 ExtractedValue::Address(addr)
             }
             // Bytes32 uses the full 32-byte value
             // Direct copy prevents any value manipulation during extraction.
-            FieldType::Bytes32 => ExtractedValue::Bytes32(witness.value),
+            FieldType::Bytes32 => ExtractedValue::Bytes32(*value),
             // Fallback to raw bytes for unknown types
             // This prevents crashes while maintaining security through type validation.
-            _ => ExtractedValue::Raw(witness.value),
+            _ => ExtractedValue::Raw(*value),
         }
     }
 }
@@ -595,6 +721,7 @@ pub enum ExtractedValue {
     Uint32(u32),
     Uint64(u64),
     Uint256([u8; 32]),
+    Int256([u8; 32]),     // Two's complement, sign-extended from the field's width
     Address([u8; 20]),    // Guaranteed to be non-zero if validation passed
     Bytes32([u8; 32]),
     Raw([u8; 32]),        // Fallback for unknown types
@@ -618,6 +745,7 @@ impl ExtractedValue {
             ExtractedValue::Uint32(n) => n.to_be_bytes().to_vec(),
             ExtractedValue::Uint64(n) => n.to_be_bytes().to_vec(),
             ExtractedValue::Uint256(bytes) => bytes.to_vec(),
+            ExtractedValue::Int256(bytes) => bytes.to_vec(),
             ExtractedValue::Address(addr) => addr.to_vec(),
             ExtractedValue::Bytes32(bytes) => bytes.to_vec(),
             ExtractedValue::Raw(bytes) => bytes.to_vec(),
@@ -637,6 +765,7 @@ impl ExtractedValue {
             ExtractedValue::Uint32(_) => 4,
             ExtractedValue::Uint64(_) => 8,
             ExtractedValue::Uint256(_) => 32,
+            ExtractedValue::Int256(_) => 32,
             ExtractedValue::Address(_) => 20,
             ExtractedValue::Bytes32(_) => 32,
             ExtractedValue::Raw(_) => 32,
@@ -657,6 +786,7 @@ impl ExtractedValue {
             ExtractedValue::Uint32(n) => *n == 0,
             ExtractedValue::Uint64(n) => *n == 0,
             ExtractedValue::Uint256(bytes) => *bytes == [0u8; 32],
+            ExtractedValue::Int256(bytes) => *bytes == [0u8; 32],
             ExtractedValue::Address(addr) => *addr == [0u8; 20], // Zero address detection
             ExtractedValue::Bytes32(bytes) => *bytes == [0u8; 32],
             ExtractedValue::Raw(bytes) => *bytes == [0u8; 32],
@@ -664,11 +794,257 @@ impl ExtractedValue {
     }
 }
 
+/// Reassemble a Solidity `string` or `bytes` value from its storage slots
+///
+/// `head` is the field's own slot. Values under 32 bytes live in its
+/// high-order bytes with twice their length in the lowest byte; longer ones
+/// store `length * 2 + 1` there and their bytes in `data`, the
+/// `ceil(length / 32)` slots from `keccak256(slot)`.
+///
+/// Returns `None` for a malformed head, for `data` that is not exactly the
+/// slots the head calls for, and for nonzero bytes past the value's end.
+/// Solidity always clears them, so nonzero padding means the slots were
+/// not read from one value.
+pub fn reassemble_bytes(head: &[u8; 32], data: &[[u8; 32]]) -> Option<Vec<u8>> {
+    if head[31] & 1 == 0 {
+        // Short form: the value and its length share the slot
+        let length = (head[31] / 2) as usize;
+        if length > 31 || !data.is_empty() || head[length..31].iter().any(|b| *b != 0) {
+            return None;
+        }
+        return Some(head[..length].to_vec());
+    }
+
+    // Long form: only the length lives in the slot
+    if head[..24].iter().any(|b| *b != 0) {
+        return None;
+    }
+    let length = u64::from_be_bytes([head[24], head[25], head[26], head[27], head[28], head[29], head[30], head[31]]) / 2;
+    if length < 32 || length.div_ceil(32) != data.len() as u64 {
+        return None;
+    }
+    let length = length as usize;
+    let mut value: Vec<u8> = data.iter().flatten().copied().collect();
+    if value[length..].iter().any(|b| *b != 0) {
+        return None;
+    }
+    value.truncate(length);
+    Some(value)
+}
+
+impl CircuitProcessor {
+    /// Validate the witnesses of a `string` or `bytes` field and reassemble it
+    ///
+    /// `head` is the witness for the field's own slot and goes through
+    /// [`Self::process_witness`]. The `data` witnesses, in slot order, must
+    /// carry this processor's layout commitment and their expected slots;
+    /// their keys are derived off-circuit from the head's slot.
+    pub fn process_bytes(&self, head: &CircuitWitness, data: &[CircuitWitness]) -> Option<Vec<u8>> {
+        if let CircuitResult::Invalid = self.process_witness(head) {
+            return None;
+        }
+        // CRITICAL: Data slots must belong to the same layout and location
+        // Without these checks, chunks from another field or layout could be
+        // spliced into the value.
+        if data.iter().any(|chunk| {
+            chunk.layout_commitment != self.layout_commitment || !self.validate_storage_location(chunk)
+        }) {
+            return None;
+        }
+        let chunks: Vec<[u8; 32]> = data.iter().map(|chunk| chunk.value).collect();
+        reassemble_bytes(&head.value, &chunks)
+    }
+}
+
+/// The shared proof data of a multi-slot witness bundle
+///
+/// The controller's `create_witnesses_from_multi_slot_request` emits this
+/// as the first witness: the contract, its storage root, the account proof
+/// and the storage trie nodes the slot witnesses that follow refer to by
+/// index. Each node is sent once however many slots share it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiSlotBundle {
+    pub contract_address: [u8; 20],
+    /// Storage root the account proof commits to, and every slot proves against
+    pub storage_hash: [u8; 32],
+    /// Account proof nodes from the state root to the contract
+    pub account_proof: Vec<Vec<u8>>,
+    /// Storage trie nodes, indexed by the slot witnesses' proof data
+    pub storage_nodes: Vec<Vec<u8>>,
+}
+
+impl MultiSlotBundle {
+    /// Parse the bundle witness, rejecting truncated data or trailing bytes
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        fn take<'a>(data: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+            if data.len() < len {
+                return None;
+            }
+            let (head, rest) = data.split_at(len);
+            *data = rest;
+            Some(head)
+        }
+        fn nodes(data: &mut &[u8]) -> Option<Vec<Vec<u8>>> {
+            let count = u16::from_le_bytes(take(data, 2)?.try_into().ok()?);
+            (0..count)
+                .map(|_| {
+                    let len = u32::from_le_bytes(take(data, 4)?.try_into().ok()?) as usize;
+                    take(data, len).map(<[u8]>::to_vec)
+                })
+                .collect()
+        }
+
+        let mut data = data;
+        let contract_address = take(&mut data, 20)?.try_into().ok()?;
+        let storage_hash = take(&mut data, 32)?.try_into().ok()?;
+        let account_proof = nodes(&mut data)?;
+        let storage_nodes = nodes(&mut data)?;
+        if !data.is_empty() {
+            return None;
+        }
+        Some(Self { contract_address, storage_hash, account_proof, storage_nodes })
+    }
+
+    /// A slot witness's storage proof, its node indices resolved
+    ///
+    /// `None` when the proof data is not whole indices or an index is past
+    /// the bundle's nodes.
+    pub fn storage_proof(&self, witness: &CircuitWitness) -> Option<Vec<&[u8]>> {
+        if witness.proof.len() % 2 != 0 {
+            return None;
+        }
+        witness
+            .proof
+            .chunks(2)
+            .map(|index| {
+                let index = u16::from_le_bytes([index[0], index[1]]) as usize;
+                self.storage_nodes.get(index).map(Vec::as_slice)
+            })
+            .collect()
+    }
+}
+
+impl CircuitProcessor {
+    /// Process the slot witnesses of a multi-slot bundle
+    ///
+    /// A witness whose proof does not resolve to nodes of the bundle is
+    /// invalid; the rest go through [`Self::process_witness`]. Checking the
+    /// account proof against the state root, and the resolved storage proofs
+    /// against [`MultiSlotBundle::storage_hash`], is left to the caller as
+    /// for any proof data.
+    pub fn process_multi_slot(&self, bundle: &MultiSlotBundle, witnesses: &[CircuitWitness]) -> Vec<CircuitResult> {
+        witnesses
+            .iter()
+            .map(|witness| match bundle.storage_proof(witness) {
+                Some(_) => self.process_witness(witness),
+                None => CircuitResult::Invalid,
+            })
+            .collect()
+    }
+}
+
+/// Check a Sui object witness against a checkpoint digest the circuit trusts
+///
+/// `None` when the witness does not parse, a link from the object's BCS to
+/// the checkpoint digest is broken, or the digest is not the trusted one.
+/// The value is read from the contents of the object the digests commit to.
+pub fn verify_sui_witness(witness_data: &[u8], checkpoint_digest: &[u8; 32]) -> Option<SuiObjectValue> {
+    let witness = SuiWitness::parse(witness_data).ok()?;
+    if witness.checkpoint_digest != *checkpoint_digest {
+        return None;
+    }
+    witness.verify().ok()
+}
+
+/// Check a Substrate storage witness against a block hash the circuit trusts
+///
+/// `None` when the witness does not parse, its header or trie nodes do not
+/// lead from the block hash to the key, or the hash is not the trusted one.
+/// An absent value is proven too, with [`SubstrateStorageValue::value`] empty.
+pub fn verify_substrate_witness(witness_data: &[u8], block_hash: &[u8; 32]) -> Option<SubstrateStorageValue> {
+    let witness = SubstrateWitness::parse(witness_data).ok()?;
+    if witness.block_hash != *block_hash {
+        return None;
+    }
+    witness.verify().ok()
+}
+
+/// Check a TON account field witness against a masterchain block root hash the circuit trusts
+///
+/// `None` when the witness does not parse, its proofs do not lead from the
+/// masterchain block to the account's data, the field cannot be read at
+/// its location, or the root hash is not the trusted one. The location is
+/// the prover's; a circuit that knows the contract's layout compares it
+/// with the field's.
+pub fn verify_ton_witness(witness_data: &[u8], mc_root_hash: &[u8; 32]) -> Option<TonAccountField> {
+    let witness = TonWitness::parse(witness_data).ok()?;
+    if witness.mc_block.root_hash != *mc_root_hash {
+        return None;
+    }
+    witness.verify().ok()
+}
+
+/// Check a Starknet storage witness against a state commitment the circuit trusts
+///
+/// `None` when the witness does not parse, its trie roots or nodes do not
+/// lead from the state commitment to each felt of the value, or the
+/// commitment is not the trusted one. The block number is the witness's
+/// claim; a circuit trusting commitments per block should compare it too.
+#[cfg(feature = "starknet")]
+pub fn verify_starknet_witness(witness_data: &[u8], state_commitment: &Felt) -> Option<StarknetFieldValue> {
+    let witness = StarknetWitness::parse(witness_data).ok()?;
+    if witness.state_commitment != *state_commitment {
+        return None;
+    }
+    witness.verify().ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use alloc::vec;
 
+    #[test]
+    fn test_substrate_witness_against_trusted_block() {
+        let (witness, _, _, _) = crate::substrate::tests::timestamp_witness();
+        let bytes = witness.to_bytes();
+        let value = verify_substrate_witness(&bytes, &witness.block_hash).unwrap();
+        assert_eq!(value.block_number, 9);
+        assert!(verify_substrate_witness(&bytes, &[0; 32]).is_none());
+        assert!(verify_substrate_witness(&bytes[1..], &witness.block_hash).is_none());
+    }
+
+    #[test]
+    fn test_ton_witness_against_trusted_masterchain_block() {
+        let witness = crate::ton::tests::wallet_witness(true, crate::ton::tests::fee_bps());
+        let bytes = witness.to_bytes();
+        let field = verify_ton_witness(&bytes, &witness.mc_block.root_hash).unwrap();
+        assert_eq!(field.value.unwrap()[30..], [0, 250]);
+        assert!(verify_ton_witness(&bytes, &witness.shard_block.root_hash).is_none());
+        assert!(verify_ton_witness(&bytes[..bytes.len() - 1], &witness.mc_block.root_hash).is_none());
+    }
+
+    #[test]
+    fn test_sui_witness_against_trusted_checkpoint() {
+        let witness = crate::sui::tests::pool_witness(32, 8);
+        let bytes = witness.to_bytes();
+        let object = verify_sui_witness(&bytes, &witness.checkpoint_digest).unwrap();
+        assert_eq!(object.value[..8], 1_000u64.to_le_bytes());
+        assert!(verify_sui_witness(&bytes, &[0; 32]).is_none());
+        assert!(verify_sui_witness(&bytes[..bytes.len() - 1], &witness.checkpoint_digest).is_none());
+    }
+
+    #[cfg(feature = "starknet")]
+    #[test]
+    fn test_starknet_witness_against_trusted_commitment() {
+        let witness = crate::starknet::tests::token_witness(0x5ff, 2);
+        let bytes = witness.to_bytes();
+        let value = verify_starknet_witness(&bytes, &witness.state_commitment).unwrap();
+        assert_eq!(&value.value[30..], &[0x01, 0xf4]);
+        assert!(verify_starknet_witness(&bytes, &witness.contracts_root).is_none());
+        assert!(verify_starknet_witness(&bytes[..bytes.len() - 1], &witness.state_commitment).is_none());
+    }
+
     #[test]
     fn test_semantic_validation_valid_zero_address() {
         let layout_commitment = [1u8; 32];
@@ -722,6 +1098,144 @@ mod tests {
         assert!(matches!(result, CircuitResult::Valid { .. }));
     }
 
+    #[test]
+    fn test_packed_fields_in_one_slot() {
+        let layout_commitment = [1u8; 32];
+        // address owner, bool paused, uint16 nonce packed into one slot
+        let processor = CircuitProcessor::new(
+            layout_commitment,
+            vec![FieldType::Address, FieldType::Bool, FieldType::Uint16],
+            vec![ZeroSemantics::ValidZero, ZeroSemantics::ValidZero, ZeroSemantics::NeverWritten],
+        )
+        .with_field_offsets(vec![0, 20, 21]);
+
+        let mut value = [0u8; 32];
+        value[12..].copy_from_slice(&[0xab; 20]);
+        value[11] = 1;
+        let witness = |field_index: u16, semantics: ZeroSemantics| CircuitWitness {
+            key: [2u8; 32],
+            value,
+            proof: vec![1, 2, 3],
+            layout_commitment,
+            field_index,
+            semantics,
+            expected_slot: [2u8; 32],
+            block_height: 0,
+            block_hash: [0u8; 32],
+        };
+
+        match processor.process_witness(&witness(0, ZeroSemantics::ValidZero)) {
+            CircuitResult::Valid { extracted_value: ExtractedValue::Address(addr), .. } => assert_eq!(addr, [0xab; 20]),
+            other => panic!("owner: {:?}", other),
+        }
+        match processor.process_witness(&witness(1, ZeroSemantics::ValidZero)) {
+            CircuitResult::Valid { extracted_value: ExtractedValue::Bool(paused), .. } => assert!(paused),
+            other => panic!("paused: {:?}", other),
+        }
+        // The nonce's bytes are zero even though its neighbours are not
+        match processor.process_witness(&witness(2, ZeroSemantics::NeverWritten)) {
+            CircuitResult::Valid { extracted_value: ExtractedValue::Uint16(nonce), .. } => assert_eq!(nonce, 0),
+            other => panic!("nonce: {:?}", other),
+        }
+
+        // Without offsets the whole word is the field, and it is not zero
+        let unpacked = CircuitProcessor::new(layout_commitment, vec![FieldType::Uint16], vec![ZeroSemantics::NeverWritten]);
+        assert!(matches!(unpacked.process_witness(&witness(0, ZeroSemantics::NeverWritten)), CircuitResult::Invalid));
+
+        // An address at offset 13 would run past the slot
+        let overflowing = CircuitProcessor::new(layout_commitment, vec![FieldType::Address], vec![ZeroSemantics::ValidZero])
+            .with_field_offsets(vec![13]);
+        assert!(matches!(overflowing.process_witness(&witness(0, ZeroSemantics::ValidZero)), CircuitResult::Invalid));
+    }
+
+    #[test]
+    fn test_field_encodings_sign_extend_and_truncate() {
+        let layout_commitment = [1u8; 32];
+        // int24 tick at offset 20 and uint12 flags at offset 23, read as wider types
+        let processor = CircuitProcessor::new(
+            layout_commitment,
+            vec![FieldType::Uint32, FieldType::Uint16],
+            vec![ZeroSemantics::ValidZero, ZeroSemantics::ValidZero],
+        )
+        .with_field_encodings(vec![
+            FieldEncoding { signed: true, bits: 24, offset: 20 },
+            FieldEncoding { signed: false, bits: 12, offset: 23 },
+        ]);
+
+        let mut value = [0u8; 32];
+        value[12..].copy_from_slice(&[0xab; 20]);
+        value[9..12].copy_from_slice(&[0xff, 0xff, 0xfe]);
+        value[7..9].copy_from_slice(&[0xf1, 0x23]);
+        let witness = |field_index: u16| CircuitWitness {
+            key: [2u8; 32],
+            value,
+            proof: vec![1, 2, 3],
+            layout_commitment,
+            field_index,
+            semantics: ZeroSemantics::ValidZero,
+            expected_slot: [2u8; 32],
+            block_height: 0,
+            block_hash: [0u8; 32],
+        };
+
+        let mut minus_two = [0xff; 32];
+        minus_two[31] = 0xfe;
+        match processor.process_witness(&witness(0)) {
+            CircuitResult::Valid { extracted_value: ExtractedValue::Int256(tick), .. } => assert_eq!(tick, minus_two),
+            other => panic!("tick: {:?}", other),
+        }
+        match processor.process_witness(&witness(1)) {
+            CircuitResult::Valid { extracted_value: ExtractedValue::Uint16(flags), .. } => assert_eq!(flags, 0x123),
+            other => panic!("flags: {:?}", other),
+        }
+        assert!(!FieldType::Address.validate_extracted_value(&ExtractedValue::Int256(minus_two)));
+    }
+
+    #[test]
+    fn test_reassemble_long_string() {
+        let text = b"A string long enough to spill past its slot into three data slots";
+        let mut head = [0u8; 32];
+        head[31] = (text.len() * 2 + 1) as u8;
+        let mut data = [[0u8; 32]; 3];
+        for (chunk, bytes) in data.iter_mut().zip(text.chunks(32)) {
+            chunk[..bytes.len()].copy_from_slice(bytes);
+        }
+        assert_eq!(reassemble_bytes(&head, &data).unwrap(), text.to_vec());
+
+        // Missing chunk, or bytes past the end
+        assert!(reassemble_bytes(&head, &data[..2]).is_none());
+        let mut dirty = data;
+        dirty[2][31] = 1;
+        assert!(reassemble_bytes(&head, &dirty).is_none());
+
+        // Short strings live in the head
+        let mut short = [0u8; 32];
+        short[..5].copy_from_slice(b"Ether");
+        short[31] = 10;
+        assert_eq!(reassemble_bytes(&short, &[]).unwrap(), b"Ether".to_vec());
+        assert!(reassemble_bytes(&short, &data[..1]).is_none());
+
+        // Through the processor, data witnesses must carry their expected slots
+        let layout_commitment = [1u8; 32];
+        let processor = CircuitProcessor::new(layout_commitment, vec![FieldType::String], vec![ZeroSemantics::ValidZero]);
+        let witness = |key: u8, value: [u8; 32]| CircuitWitness {
+            key: [key; 32],
+            value,
+            proof: vec![1, 2, 3],
+            layout_commitment,
+            field_index: 0,
+            semantics: ZeroSemantics::ValidZero,
+            expected_slot: [key; 32],
+            block_height: 0,
+            block_hash: [0u8; 32],
+        };
+        let chunks: Vec<CircuitWitness> = data.iter().enumerate().map(|(i, chunk)| witness(3 + i as u8, *chunk)).collect();
+        assert_eq!(processor.process_bytes(&witness(2, head), &chunks).unwrap(), text.to_vec());
+        let mut spliced = chunks.clone();
+        spliced[1].expected_slot = [9u8; 32];
+        assert!(processor.process_bytes(&witness(2, head), &spliced).is_none());
+    }
+
     #[test]
     fn test_semantic_validation_invalid_zero_semantics() {
         let layout_commitment = [1u8; 32];
@@ -71,6 +71,12 @@ pub struct LayoutInfo {
     pub field_types: Vec<String>,
     /// Field semantics in order
     pub field_semantics: Vec<String>,
+    /// Byte offset of each field within its slot, in order
+    ///
+    /// Empty for layouts without packed fields; generated circuits then read
+    /// every field from its whole slot.
+    #[serde(default)]
+    pub field_offsets: Vec<u8>,
     /// Storage queries this layout supports
     pub queries: Vec<QueryInfo>,
 }
@@ -349,7 +355,10 @@ pub const FIELD_TYPES: &[FieldType] = &[
 pub const FIELD_SEMANTICS: &[ZeroSemantics] = &[
 {% for semantic in layout.field_semantics %}    ZeroSemantics::{{ semantic }},
 {% endfor %}];
-
+{% if layout.field_offsets | length > 0 %}
+/// Byte offsets of packed fields within their slots
+pub const FIELD_OFFSETS: &[u8] = &[{{ layout.field_offsets | join(sep=", ") }}];
+{% endif %}
 {% if options.include_alloy %}// Define contract-specific output types
 sol! {
     /// Generated output structure for {{ layout.contract_name }}
@@ -378,7 +387,8 @@ pub fn circuit(witnesses: Vec<Witness>) -> Vec<u8> {
         LAYOUT_COMMITMENT,
         FIELD_TYPES.to_vec(),
         FIELD_SEMANTICS.to_vec(),
-    );
+    ){% if layout.field_offsets | length > 0 %}
+    .with_field_offsets(FIELD_OFFSETS.to_vec()){% endif %};
     
     // Parse witnesses from bytes (graceful error handling)
     let mut circuit_witnesses = Vec::with_capacity(witnesses.len());
@@ -669,6 +679,7 @@ mod tests {
                 "Bytes".to_string(),
             ],
             field_semantics: vec!["ValidZero".to_string(); 10],
+            field_offsets: Vec::new(),
             queries: vec![
                 QueryInfo {
                     query: "is_enabled".to_string(),
@@ -745,6 +756,7 @@ mod tests {
             contract_name: "TestContract".to_string(),
             field_types: vec!["Bool".to_string()],
             field_semantics: vec!["ValidZero".to_string()],
+            field_offsets: Vec::new(),
             queries: vec![QueryInfo {
                 query: "enabled".to_string(),
                 field_type: "Bool".to_string(),
@@ -793,6 +805,29 @@ mod tests {
         assert!(template_code.contains("alloc::vec![0x05, error_code]"));
     }
 
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_packed_field_offsets_template() {
+        let layout = LayoutInfo {
+            commitment: "f6dc3c4a79e95565b3cf38993f1a120c6a6b467796264e7fd9a9c8675616dd7a".to_string(),
+            contract_name: "Packed".to_string(),
+            field_types: vec!["Address".to_string(), "Bool".to_string()],
+            field_semantics: vec!["NeverWritten".to_string(), "ValidZero".to_string()],
+            field_offsets: vec![0, 20],
+            queries: vec![],
+        };
+
+        // Generated circuits mask packed fields when the layout has offsets
+        assert!(CIRCUIT_LIB_TEMPLATE.contains("pub const FIELD_OFFSETS: &[u8]"));
+        assert!(CIRCUIT_LIB_TEMPLATE.contains(".with_field_offsets(FIELD_OFFSETS.to_vec())"));
+
+        // Layouts written before offsets existed still deserialize
+        let mut json = serde_json::to_value(&layout).unwrap();
+        json.as_object_mut().unwrap().remove("field_offsets");
+        let layout: LayoutInfo = serde_json::from_value(json).unwrap();
+        assert!(layout.field_offsets.is_empty());
+    }
+
     #[test]
     fn test_security_layout_commitment_injection() {
         // Security Test: Layout commitment injection prevention
@@ -841,6 +876,7 @@ mod tests {
             contract_name: "'; rm -rf /; echo 'pwned".to_string(), // Command injection attempt
             field_types: vec!["<script>alert(1)</script>".to_string()], // XSS attempt
             field_semantics: vec!["{{7*7}}".to_string()], // Template injection
+            field_offsets: Vec::new(),
             queries: vec![QueryInfo {
                 query: "{{constructor.constructor('return process')().exit()}}".to_string(), // JS injection
                 field_type: "Uint256".to_string(),
@@ -883,6 +919,7 @@ mod tests {
             contract_name: "TestContract".to_string(),
             field_types: vec!["Uint256".to_string(), "Address".to_string()],
             field_semantics: vec!["ValidZero".to_string(), "NeverWritten".to_string()],
+            field_offsets: Vec::new(),
             queries: vec![
                 QueryInfo {
                     query: "balance".to_string(),
@@ -966,6 +1003,7 @@ mod tests {
                 contract_name: "TestContract".to_string(),
                 field_types: vec![malicious_type.to_string()],
                 field_semantics: vec!["ValidZero".to_string()],
+                field_offsets: Vec::new(),
                 queries: vec![QueryInfo {
                     query: "test_field".to_string(),
                     field_type: malicious_type.to_string(),
@@ -1003,6 +1041,7 @@ mod tests {
             contract_name: "../../../etc/passwd".to_string(), // Path traversal attempt
             field_types: vec!["Uint256".to_string()],
             field_semantics: vec!["ValidZero".to_string()],
+            field_offsets: Vec::new(),
             queries: vec![QueryInfo {
                 query: "../../root/.ssh/id_rsa".to_string(), // Path traversal in query
                 field_type: "Uint256".to_string(),
@@ -1051,6 +1090,7 @@ mod tests {
             contract_name: "LargeContract".to_string(),
             field_types: large_field_types,
             field_semantics: large_field_semantics,
+            field_offsets: Vec::new(),
             queries: large_queries,
         };
         
@@ -1078,6 +1118,7 @@ mod tests {
             contract_name: long_string.clone(),
             field_types: vec!["Uint256".to_string()],
             field_semantics: vec!["ValidZero".to_string()],
+            field_offsets: Vec::new(),
             queries: vec![QueryInfo {
                 query: long_string,
                 field_type: "Uint256".to_string(),
@@ -1108,6 +1149,7 @@ mod tests {
             contract_name: "TestContract".to_string(),
             field_types: vec!["Uint256".to_string()],
             field_semantics: vec!["ValidZero".to_string()],
+            field_offsets: Vec::new(),
             queries: vec![QueryInfo {
                 query: "balance".to_string(),
                 field_type: "Uint256".to_string(),
@@ -1132,6 +1174,7 @@ mod tests {
             contract_name: "TestContract".to_string(),
             field_types: vec!["Uint256".to_string()],
             field_semantics: vec!["ValidZero".to_string()],
+            field_offsets: Vec::new(),
             queries: vec![QueryInfo {
                 query: "balance".to_string(),
                 field_type: "Uint256".to_string(),
@@ -1166,6 +1209,7 @@ mod tests {
                 contract_name: "ConcurrentTest".to_string(),
                 field_types: vec!["Uint256".to_string()],
                 field_semantics: vec!["ValidZero".to_string()],
+                field_offsets: Vec::new(),
                 queries: vec![QueryInfo {
                     query: "test_field".to_string(),
                     field_type: "Uint256".to_string(),
@@ -1427,6 +1471,7 @@ mod new_functionality_tests {
             contract_name: "TestContract".to_string(),
             field_types: vec!["uint256".to_string(), "address".to_string()],
             field_semantics: vec!["ValidZero".to_string(), "InvalidZero".to_string()],
+            field_offsets: Vec::new(),
             queries: vec![],
         };
 
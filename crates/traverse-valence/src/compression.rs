@@ -0,0 +1,238 @@
+//! Concurrent Merkle tree verification for compressed Solana state (no_std compatible)
+//!
+//! Checks the leaf proofs `traverse-solana` fetches for SPL account
+//! compression trees: a leaf hashed up its proof must reach a root in the
+//! tree account's change log buffer. The tree account's data must itself be
+//! proven, since the buffer is what ties the root to chain state.
+//!
+//! Proofs may leave out the levels the tree's canopy caches; those are read
+//! from the account data, with empty-subtree hashes where the canopy is zero.
+
+use alloc::vec::Vec;
+use traverse_core::hash::keccak256_concat;
+
+/// Size of a tree account's header
+const HEADER_SIZE: usize = 56;
+/// Deepest tree the account compression program supports
+const MAX_DEPTH: u32 = 30;
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().expect("4 bytes"))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().expect("8 bytes"))
+}
+
+/// Root of an empty subtree `level` levels tall
+pub fn empty_node(level: u32) -> [u8; 32] {
+    (0..level).fold([0u8; 32], |node, _| keccak256_concat(&[&node, &node]))
+}
+
+/// Hash of a Bubblegum `LeafSchema::V1` leaf
+pub fn hash_leaf_v1(
+    asset_id: &[u8; 32],
+    owner: &[u8; 32],
+    delegate: &[u8; 32],
+    nonce: u64,
+    data_hash: &[u8; 32],
+    creator_hash: &[u8; 32],
+) -> [u8; 32] {
+    keccak256_concat(&[&[1], asset_id, owner, delegate, &nonce.to_le_bytes(), data_hash, creator_hash])
+}
+
+/// Root a leaf and its proof hash to, from the leaf's sibling upwards
+pub fn compute_root(leaf: &[u8; 32], leaf_index: u32, proof: &[[u8; 32]]) -> [u8; 32] {
+    proof.iter().enumerate().fold(*leaf, |node, (level, sibling)| {
+        if (leaf_index >> level) & 1 == 0 {
+            keccak256_concat(&[&node, sibling])
+        } else {
+            keccak256_concat(&[sibling, &node])
+        }
+    })
+}
+
+/// Verify a full-depth proof against a root
+pub fn verify_compressed_leaf(
+    leaf: &[u8; 32],
+    leaf_index: u32,
+    proof: &[[u8; 32]],
+    root: &[u8; 32],
+) -> Result<(), &'static str> {
+    if proof.len() > MAX_DEPTH as usize {
+        return Err("Proof is deeper than any concurrent Merkle tree");
+    }
+    if proof.len() < 32 && u64::from(leaf_index) >> proof.len() != 0 {
+        return Err("Leaf index is outside the tree");
+    }
+    if compute_root(leaf, leaf_index, proof) != *root {
+        return Err("Leaf does not hash to the root");
+    }
+    Ok(())
+}
+
+/// The parts of a tree account a leaf proof is checked against
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeAccount<'a> {
+    pub max_depth: u32,
+    /// Roots in the change log buffer, newest first
+    pub roots: Vec<[u8; 32]>,
+    /// Cached top levels below the root, breadth first
+    canopy: &'a [u8],
+}
+
+impl<'a> TreeAccount<'a> {
+    /// Decode a V1 concurrent Merkle tree account's data
+    pub fn parse(data: &'a [u8]) -> Result<Self, &'static str> {
+        if data.len() < HEADER_SIZE {
+            return Err("Tree account data is shorter than its header");
+        }
+        if data[0] != 1 || data[1] != 0 {
+            return Err("Not a V1 concurrent Merkle tree account");
+        }
+        let max_buffer_size = read_u32(data, 2) as usize;
+        let max_depth = read_u32(data, 6);
+        if max_depth == 0 || max_depth > MAX_DEPTH || max_buffer_size == 0 {
+            return Err("Unsupported tree depth or buffer size");
+        }
+
+        // Sequence number, active index and buffer size, then the change logs
+        // (root, path, index and padding) and the rightmost path
+        let depth = max_depth as usize;
+        let change_log_size = 32 + 32 * depth + 8;
+        let logs_start = HEADER_SIZE + 24;
+        let canopy_start = logs_start + max_buffer_size * change_log_size + 32 * depth + 40;
+        if data.len() < canopy_start || !(data.len() - canopy_start).is_multiple_of(32) {
+            return Err("Tree account data does not fit its depth and buffer size");
+        }
+        let active_index = read_u64(data, HEADER_SIZE + 8);
+        let buffer_size = read_u64(data, HEADER_SIZE + 16);
+        if active_index >= max_buffer_size as u64 || buffer_size > max_buffer_size as u64 {
+            return Err("Change log index or size exceeds the buffer");
+        }
+        let roots = (0..buffer_size as usize)
+            .map(|age| {
+                let index = (active_index as usize + max_buffer_size - age) % max_buffer_size;
+                let offset = logs_start + index * change_log_size;
+                data[offset..offset + 32].try_into().expect("32 bytes")
+            })
+            .collect();
+
+        let tree = Self { max_depth, roots, canopy: &data[canopy_start..] };
+        tree.canopy_depth()?;
+        Ok(tree)
+    }
+
+    /// Levels the canopy caches; a canopy of depth `d` holds `2^(d+1) - 2` nodes
+    pub fn canopy_depth(&self) -> Result<u32, &'static str> {
+        let nodes = self.canopy.len() / 32 + 2;
+        if !nodes.is_power_of_two() {
+            return Err("Canopy is not a whole number of levels");
+        }
+        let depth = nodes.trailing_zeros() - 1;
+        if depth > self.max_depth {
+            return Err("Canopy is deeper than its tree");
+        }
+        Ok(depth)
+    }
+
+    /// Extend a canopy-truncated proof to the tree's full depth
+    pub fn complete_proof(&self, proof: &[[u8; 32]], leaf_index: u32) -> Result<Vec<[u8; 32]>, &'static str> {
+        let canopy_depth = self.canopy_depth()?;
+        if proof.len() + (canopy_depth as usize) < self.max_depth as usize || proof.len() > self.max_depth as usize {
+            return Err("Proof and canopy do not reach the top of the tree");
+        }
+        if u64::from(leaf_index) >= 1u64 << self.max_depth {
+            return Err("Leaf index is outside the tree");
+        }
+
+        // Heap index (root 1) of the leaf's ancestor at the canopy's bottom
+        // level; the canopy stores heap index `i` at node `i - 2`
+        let mut node = ((1u64 << self.max_depth) + u64::from(leaf_index)) >> (self.max_depth - canopy_depth);
+        let mut inferred = Vec::new();
+        while node > 1 {
+            let offset = ((node ^ 1) as usize - 2) * 32;
+            let cached: [u8; 32] = self.canopy[offset..offset + 32].try_into().expect("32 bytes");
+            let level = self.max_depth - (63 - node.leading_zeros());
+            inferred.push(if cached == [0u8; 32] { empty_node(level) } else { cached });
+            node >>= 1;
+        }
+        let overlap = (proof.len() + inferred.len()).saturating_sub(self.max_depth as usize);
+        Ok(proof.iter().copied().chain(inferred.into_iter().skip(overlap)).collect())
+    }
+
+    /// Verify a leaf against a root still in the change log buffer
+    pub fn verify_leaf(
+        &self,
+        leaf: &[u8; 32],
+        leaf_index: u32,
+        proof: &[[u8; 32]],
+        root: &[u8; 32],
+    ) -> Result<(), &'static str> {
+        if !self.roots.contains(root) {
+            return Err("Root is not in the tree's change log buffer");
+        }
+        let proof = self.complete_proof(proof, leaf_index)?;
+        verify_compressed_leaf(leaf, leaf_index, &proof, root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tree account data for `leaves` in a depth-3 tree with a one-level canopy
+    fn tree_account(leaves: &[[u8; 32]]) -> (Vec<u8>, Vec<Vec<[u8; 32]>>) {
+        let mut levels = alloc::vec![(0..8).map(|i| leaves.get(i).copied().unwrap_or([0; 32])).collect::<Vec<_>>()];
+        for level in 0..3 {
+            let next = levels[level].chunks(2).map(|pair| keccak256_concat(&[&pair[0], &pair[1]])).collect();
+            levels.push(next);
+        }
+
+        let mut data = alloc::vec![1, 0];
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&3u32.to_le_bytes());
+        data.extend_from_slice(&[0u8; 46]);
+        data.extend_from_slice(&1u64.to_le_bytes());
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.extend_from_slice(&1u64.to_le_bytes());
+        data.extend_from_slice(&levels[3][0]);
+        data.extend_from_slice(&[0u8; 32 * 3 + 8]);
+        data.extend_from_slice(&[0u8; 32 * 3 + 40]);
+        data.extend_from_slice(&levels[2][0]);
+        data.extend_from_slice(&levels[2][1]);
+        (data, levels)
+    }
+
+    #[test]
+    fn test_verify_compressed_leaf() {
+        let leaf = hash_leaf_v1(&[1; 32], &[2; 32], &[2; 32], 0, &[3; 32], &[4; 32]);
+        let (data, levels) = tree_account(&[leaf]);
+        let root = levels[3][0];
+        let proof = [levels[0][1], levels[1][1], levels[2][1]];
+        verify_compressed_leaf(&leaf, 0, &proof, &root).unwrap();
+        assert!(verify_compressed_leaf(&leaf, 1, &proof, &root).is_err());
+        assert!(verify_compressed_leaf(&leaf, 8, &proof, &root).is_err());
+
+        let tree = TreeAccount::parse(&data).unwrap();
+        assert_eq!(tree.canopy_depth().unwrap(), 1);
+        tree.verify_leaf(&leaf, 0, &proof, &root).unwrap();
+        tree.verify_leaf(&leaf, 0, &proof[..2], &root).unwrap();
+        assert!(tree.verify_leaf(&leaf, 0, &proof[..1], &root).is_err());
+        assert!(tree.verify_leaf(&leaf, 0, &proof, &[9; 32]).is_err());
+    }
+
+    #[test]
+    fn test_empty_canopy_nodes() {
+        let (mut data, levels) = tree_account(&[]);
+        assert_eq!(levels[3][0], empty_node(3));
+        // A zeroed canopy node stands for an empty subtree
+        let len = data.len();
+        data[len - 64..].fill(0);
+        let tree = TreeAccount::parse(&data).unwrap();
+        assert_eq!(tree.complete_proof(&[empty_node(0), empty_node(1)], 5).unwrap()[2], empty_node(2));
+
+        data.truncate(len - 32);
+        assert!(TreeAccount::parse(&data).is_err());
+    }
+}
@@ -46,12 +46,24 @@ use valence_coprocessor::Witness;
 // Import serde_json::Value for JSON handling functions  
 #[cfg(feature = "std")]
 use serde_json::Value;
+#[cfg(feature = "std")]
+use crate::messages::decimal;
 
 use crate::{
-    BatchStorageVerificationRequest, StorageVerificationRequest, 
+    BatchStorageVerificationRequest, StorageVerificationRequest, MultiSlotVerificationRequest,
     SolanaAccountVerificationRequest, BatchSolanaAccountVerificationRequest,
+    SuiObjectVerificationRequest, BatchSuiObjectVerificationRequest,
+    SubstrateStorageVerificationRequest, BatchSubstrateStorageVerificationRequest,
+    BitcoinTxVerificationRequest, BatchBitcoinTxVerificationRequest,
+    TonBlockId, TonFieldVerificationRequest, BatchTonFieldVerificationRequest,
     TraverseValenceError
 };
+use crate::bitcoin::{self, parse_transaction};
+use crate::{substrate, sui, ton, witness};
+#[cfg(feature = "starknet")]
+use crate::{starknet, BatchStarknetStorageVerificationRequest, StarknetStorageVerificationRequest, StarknetTrieNode};
+#[cfg(feature = "starknet")]
+use starknet_types_core::felt::Felt;
 
 // Conditional import of domain module (only when domain feature is enabled)
 #[cfg(feature = "domain")]
@@ -135,6 +147,24 @@ fn create_witness_from_request_internal(
     request: &StorageVerificationRequest,
     block_height: u64,
     block_hash: [u8; 32],
+) -> Result<Witness, TraverseValenceError> {
+    // Parse and concatenate proof nodes
+    let mut proof_data = Vec::new();
+    for node in &request.storage_proof.proof {
+        let node_bytes = parse_hex_bytes_variable(node)
+            .ok_or_else(|| TraverseValenceError::ProofVerificationFailed("Invalid proof node format".into()))?;
+        proof_data.extend_from_slice(&node_bytes);
+    }
+
+    create_slot_witness(request, &proof_data, block_height, block_hash)
+}
+
+/// Create the semantic witness of one slot with the given proof data
+fn create_slot_witness(
+    request: &StorageVerificationRequest,
+    proof_data: &[u8],
+    block_height: u64,
+    block_hash: [u8; 32],
 ) -> Result<Witness, TraverseValenceError> {
     let storage_query = &request.storage_query;
     let storage_proof = &request.storage_proof;
@@ -151,14 +181,6 @@ fn create_witness_from_request_internal(
     let value = parse_hex_bytes(&storage_proof.value, 32)
         .ok_or_else(|| TraverseValenceError::InvalidWitness("Invalid storage value format".into()))?;
 
-    // Parse and concatenate proof nodes
-    let mut proof_data = Vec::new();
-    for node in &storage_proof.proof {
-        let node_bytes = parse_hex_bytes_variable(node)
-            .ok_or_else(|| TraverseValenceError::ProofVerificationFailed("Invalid proof node format".into()))?;
-        proof_data.extend_from_slice(&node_bytes);
-    }
-
     // Use semantic defaults for structured data
     let zero_semantics = derive_zero_semantics(&value);
     let semantic_source = 0u8; // Declared via structured data
@@ -169,7 +191,7 @@ fn create_witness_from_request_internal(
         &value,
         zero_semantics,
         semantic_source,
-        &proof_data,
+        proof_data,
         block_height,
         &block_hash,
         derive_field_index_from_layout(&layout_commitment, &storage_key)?, // field_index - derived from layout
@@ -225,26 +247,101 @@ pub fn create_witnesses_from_batch_request(
     Ok(witnesses)
 }
 
-
-/// Create a semantic witness from raw byte data (no_std compatible)
+/// Create witnesses for many slots of one contract from one account proof (no_std compatible)
 ///
-/// Creates a semantic witness with full extended format including all security fields.
-/// This is the primary witness creation function for raw data.
+/// The first witness is the bundle: the contract address, its storage
+/// root, the account proof, and every storage trie node the slots' proofs
+/// use, each once. A witness per slot follows in batch order, in the
+/// extended format with its proof data replaced by the 2-byte indices of
+/// its nodes in the bundle. Parse the bundle in the circuit with
+/// [`MultiSlotBundle::parse`](crate::circuit::MultiSlotBundle::parse).
 ///
-/// ## Extended Witness Format (176+ bytes)
+/// ## Bundle Witness Format
 /// ```text
-/// [32 bytes storage_key] +
-/// [32 bytes layout_commitment] + 
-/// [32 bytes value] +
-/// [1 byte zero_semantics] +
-/// [1 byte semantic_source] +
-/// [8 bytes block_height] +
-/// [32 bytes block_hash] +
-/// [4 bytes proof_len] +
-/// [variable proof_data] +
-/// [2 bytes field_index] +
-/// [32 bytes expected_slot]
+/// [20 bytes contract_address] +
+/// [32 bytes storage_hash] +
+/// [2 bytes account_node_count] + account nodes +
+/// [2 bytes storage_node_count] + storage nodes
 /// ```
+/// Each node is `[4 bytes length] + bytes`. Counts, lengths and indices are
+/// little-endian, like the lengths of the extended format.
+pub fn create_witnesses_from_multi_slot_request(
+    request: &MultiSlotVerificationRequest,
+) -> Result<Vec<Witness>, TraverseValenceError> {
+    let contract_address = parse_hex_bytes(&request.contract_address, 20)
+        .ok_or_else(|| TraverseValenceError::InvalidWitness("Invalid contract address format".into()))?;
+    let storage_hash = parse_hex_bytes(&request.storage_hash, 32)
+        .ok_or_else(|| TraverseValenceError::ProofVerificationFailed("Invalid storage hash format".into()))?;
+    let parse_nodes = |nodes: &[alloc::string::String]| {
+        nodes
+            .iter()
+            .map(|node| {
+                parse_hex_bytes_variable(node)
+                    .ok_or_else(|| TraverseValenceError::ProofVerificationFailed("Invalid proof node format".into()))
+            })
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let account_proof = parse_nodes(&request.account_proof)?;
+
+    // Slots share the upper levels of the storage trie; each node goes in once
+    let mut storage_nodes: Vec<Vec<u8>> = Vec::new();
+    let mut slot_indices = Vec::with_capacity(request.storage_batch.len());
+    for (index, slot) in request.storage_batch.iter().enumerate() {
+        let query_key = parse_hex_bytes(&slot.storage_query.storage_key, 32);
+        if query_key.is_none() || query_key != parse_hex_bytes(&slot.storage_proof.key, 32) {
+            return Err(TraverseValenceError::InvalidStorageKey(format!(
+                "Batch item {}: proof is not for the query's storage key",
+                index
+            )));
+        }
+        let mut indices = Vec::new();
+        for node in parse_nodes(&slot.storage_proof.proof)? {
+            let position = match storage_nodes.iter().position(|known| *known == node) {
+                Some(position) => position,
+                None => {
+                    storage_nodes.push(node);
+                    storage_nodes.len() - 1
+                }
+            };
+            let position = u16::try_from(position)
+                .map_err(|_| TraverseValenceError::InvalidWitness("Too many storage trie nodes for one bundle".into()))?;
+            indices.extend_from_slice(&position.to_le_bytes());
+        }
+        slot_indices.push(indices);
+    }
+
+    let count = |nodes: &[Vec<u8>]| {
+        u16::try_from(nodes.len())
+            .map_err(|_| TraverseValenceError::InvalidWitness("Too many proof nodes for one bundle".into()))
+    };
+    let mut bundle = Vec::new();
+    bundle.extend_from_slice(&contract_address);
+    bundle.extend_from_slice(&storage_hash);
+    for nodes in [&account_proof, &storage_nodes] {
+        bundle.extend_from_slice(&count(nodes)?.to_le_bytes());
+        for node in nodes {
+            bundle.extend_from_slice(&(node.len() as u32).to_le_bytes());
+            bundle.extend_from_slice(node);
+        }
+    }
+
+    let block_height = request.block_number.unwrap_or(0);
+    let mut witnesses = Vec::with_capacity(request.storage_batch.len() + 1);
+    witnesses.push(Witness::Data(bundle));
+    for (index, (slot, indices)) in request.storage_batch.iter().zip(&slot_indices).enumerate() {
+        let witness = create_slot_witness(slot, indices, block_height, [0u8; 32])
+            .map_err(|e| TraverseValenceError::InvalidWitness(format!("Batch item {}: {}", index, e)))?;
+        witnesses.push(witness);
+    }
+
+    Ok(witnesses)
+}
+
+/// Create a semantic witness from raw byte data (no_std compatible)
+///
+/// Creates a semantic witness with full extended format including all security fields.
+/// This is the primary witness creation function for raw data, written in
+/// the storage witness format [`crate::witness`] documents.
 #[allow(clippy::too_many_arguments)]
 pub fn create_semantic_witness_from_raw_data(
     storage_key: &[u8],
@@ -298,24 +395,19 @@ pub fn create_semantic_witness_from_raw_data(
         ));
     }
 
-    // Calculate total witness size (includes block data and extended fields)
-    let witness_size = 32 + 32 + 32 + 1 + 1 + 8 + 32 + 4 + proof_data.len() + 2 + 32;
-    let mut witness_data = Vec::with_capacity(witness_size);
-
-    // Serialize witness data in extended format
-    witness_data.extend_from_slice(storage_key);
-    witness_data.extend_from_slice(layout_commitment);
-    witness_data.extend_from_slice(value);
-    witness_data.push(zero_semantics);
-    witness_data.push(semantic_source);
-    witness_data.extend_from_slice(&block_height.to_le_bytes()); // 8 bytes block height
-    witness_data.extend_from_slice(block_hash); // 32 bytes block hash
-    witness_data.extend_from_slice(&(proof_data.len() as u32).to_le_bytes());
-    witness_data.extend_from_slice(proof_data);
-    witness_data.extend_from_slice(&field_index.to_le_bytes()); // 2 bytes field index
-    witness_data.extend_from_slice(expected_slot); // 32 bytes expected slot
-
-    Ok(Witness::Data(witness_data))
+    let word = |bytes: &[u8]| -> [u8; 32] { bytes.try_into().expect("checked to be 32 bytes") };
+    let witness = witness::StorageWitness {
+        storage_key: word(storage_key),
+        layout_commitment: word(layout_commitment),
+        value: word(value),
+        zero_semantics,
+        semantic_source,
+        block: Some(witness::BlockBinding { height: block_height, hash: word(block_hash) }),
+        proof: proof_data.to_vec(),
+        field: Some(witness::FieldBinding { index: field_index, expected_slot: word(expected_slot) }),
+    };
+
+    Ok(Witness::Data(witness.to_bytes()))
 }
 
 // === Utility Functions (no_std compatible) ===
@@ -566,23 +658,11 @@ pub fn create_witnesses_from_batch_solana_request(
 
 /// Create a Solana witness from raw account data (no_std compatible)
 ///
-/// Creates a witness in a format compatible with traverse-valence circuits.
-/// The witness format is similar to Ethereum storage proofs but adapted for Solana accounts.
-/// 
-/// ## Solana Witness Format (144+ bytes)
-/// ```text
-/// [20 bytes account_address] + [12 bytes padding] +
-/// [20 bytes owner_program] + [12 bytes padding] +
-/// [32 bytes extracted_value] +
-/// [8 bytes lamports] +
-/// [8 bytes rent_epoch] +
-/// [8 bytes slot] +
-/// [32 bytes block_hash] +
-/// [4 bytes field_offset]
-/// ```
+/// Creates a witness in a format compatible with traverse-valence circuits,
+/// the Solana account witness format [`crate::witness`] documents.
 #[allow(clippy::too_many_arguments)]
 pub fn create_solana_witness_from_account_data(
-    account_address: &[u8; 32], // Padded to 32 bytes for compatibility
+    account_address: &[u8; 32],
     owner_program: &str,
     extracted_value: &[u8; 32],
     lamports: u64,
@@ -597,56 +677,69 @@ pub fn create_solana_witness_from_account_data(
     // Parse block hash
     let block_hash_bytes = parse_base58_hash(block_hash)?;
 
-    // Calculate total witness size
-    let witness_size = 32 + 32 + 32 + 8 + 8 + 8 + 32 + 4;
-    let mut witness_data = Vec::with_capacity(witness_size);
-
-    // Serialize witness data in Solana-specific format
-    witness_data.extend_from_slice(account_address); // 32 bytes account address (padded)
-    witness_data.extend_from_slice(&owner_bytes); // 32 bytes owner program (padded)
-    witness_data.extend_from_slice(extracted_value); // 32 bytes extracted value
-    witness_data.extend_from_slice(&lamports.to_le_bytes()); // 8 bytes lamports
-    witness_data.extend_from_slice(&rent_epoch.to_le_bytes()); // 8 bytes rent epoch
-    witness_data.extend_from_slice(&slot.to_le_bytes()); // 8 bytes slot
-    witness_data.extend_from_slice(&block_hash_bytes); // 32 bytes block hash
-    witness_data.extend_from_slice(&field_offset.to_le_bytes()); // 4 bytes field offset
-
-    Ok(Witness::Data(witness_data))
+    let witness = witness::SolanaAccountWitness {
+        account_address: *account_address,
+        owner: owner_bytes,
+        value: *extracted_value,
+        lamports,
+        rent_epoch,
+        slot,
+        block_hash: block_hash_bytes,
+        field_offset,
+    };
+    Ok(Witness::Data(witness.to_bytes()))
 }
 
 // === Solana Utility Functions (no_std compatible) ===
 
-/// Parse base58 address to padded byte array (no_std compatible)
-fn parse_base58_address(address: &str) -> Result<[u8; 32], TraverseValenceError> {
+/// Parse base58 address to its 32-byte public key (no_std compatible)
+pub(crate) fn parse_base58_address(address: &str) -> Result<[u8; 32], TraverseValenceError> {
     parse_base58_address_from_str(address)
 }
 
-/// Parse base58 address from string to padded 32-byte array (no_std compatible)
+/// Parse base58 address from string to its 32-byte public key (no_std compatible)
 fn parse_base58_address_from_str(address: &str) -> Result<[u8; 32], TraverseValenceError> {
-    // For now, use a simple conversion since we don't have base58 decoding in no_std
-    // In a real implementation, you'd use a base58 decoder
-    let mut result = [0u8; 32];
-    let address_bytes = address.as_bytes();
-    let copy_len = core::cmp::min(address_bytes.len(), 32);
-    result[..copy_len].copy_from_slice(&address_bytes[..copy_len]);
-    Ok(result)
+    decode_base58_word(address)
+        .map_err(|e| TraverseValenceError::InvalidWitness(format!("Invalid base58 address: {}", e)))
 }
 
 /// Parse base58 hash to byte array (no_std compatible)
-fn parse_base58_hash(hash: &str) -> Result<[u8; 32], TraverseValenceError> {
-    // Similar to address parsing, simplified for no_std
-    let mut result = [0u8; 32];
-    let hash_bytes = hash.as_bytes();
-    let copy_len = core::cmp::min(hash_bytes.len(), 32);
-    result[..copy_len].copy_from_slice(&hash_bytes[..copy_len]);
-    Ok(result)
+pub(crate) fn parse_base58_hash(hash: &str) -> Result<[u8; 32], TraverseValenceError> {
+    decode_base58_word(hash).map_err(|e| TraverseValenceError::InvalidWitness(format!("Invalid base58 hash: {}", e)))
+}
+
+/// Decode base58 that must be exactly 32 bytes, as Solana keys and hashes are
+fn decode_base58_word(encoded: &str) -> Result<[u8; 32], &'static str> {
+    // 32 bytes never take more than 44 characters
+    if encoded.is_empty() || encoded.len() > 44 {
+        return Err("expected 32 to 44 characters");
+    }
+    let mut out = [0u8; 32];
+    match bs58::decode(encoded).onto(&mut out[..]) {
+        Ok(32) => Ok(out),
+        Ok(_) | Err(bs58::decode::Error::BufferTooSmall) => Err("does not decode to 32 bytes"),
+        Err(_) => Err("not base58"),
+    }
 }
 
+/// Largest account data Solana allows, 10 MiB
+const MAX_ACCOUNT_DATA_LEN: usize = 10 * 1024 * 1024;
+
 /// Parse base64 encoded account data (no_std compatible)
-fn parse_base64_data(data: &str) -> Result<Vec<u8>, TraverseValenceError> {
-    // Simplified base64 decoding for no_std
-    // In a real implementation, you'd use a proper base64 decoder
-    Ok(data.as_bytes().to_vec())
+///
+/// Account data comes from `getAccountInfo` with `base64` encoding; field
+/// offsets index the decoded bytes.
+pub(crate) fn parse_base64_data(data: &str) -> Result<Vec<u8>, TraverseValenceError> {
+    use base64::Engine;
+
+    if data.len() > MAX_ACCOUNT_DATA_LEN.div_ceil(3) * 4 {
+        return Err(TraverseValenceError::InvalidWitness(
+            "Account data exceeds the 10 MiB Solana account limit".into(),
+        ));
+    }
+    base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .map_err(|e| TraverseValenceError::InvalidWitness(format!("Invalid base64 account data: {}", e)))
 }
 
 /// Extract specific field from account data (no_std compatible)
@@ -668,6 +761,803 @@ fn extract_field_from_account_data(
     Ok(result)
 }
 
+// === Sui Object Verification APIs ===
+
+/// Create a witness from Sui object verification request (no_std compatible)
+///
+/// The object's BCS is hashed back to the checkpoint digest through the
+/// effects and the checkpoint contents and summary, and the field is read
+/// from the contents inside it at the offset the layout resolved. The
+/// proof's object ID, version, digest and contents must be the ones the BCS
+/// yields.
+pub fn create_witness_from_sui_request(
+    request: &SuiObjectVerificationRequest,
+) -> Result<Witness, TraverseValenceError> {
+    let object_query = &request.object_query;
+    let object_proof = &request.object_proof;
+    let invalid = |e: &str| TraverseValenceError::InvalidWitness(e.into());
+
+    let object_id = parse_sui_object_id(&object_proof.object_id)?;
+    if parse_sui_object_id(&object_query.object_id)? != object_id {
+        return Err(TraverseValenceError::InvalidWitness(format!(
+            "Query is for object {}, but the proof is for {}",
+            object_query.object_id, object_proof.object_id
+        )));
+    }
+
+    // A field size of 0 selects the object ID in the witness
+    let (field_offset, field_size) = match (object_query.field_offset, object_query.field_size) {
+        (Some(_), Some(0)) => return Err(invalid("Field size must be nonzero")),
+        (Some(offset), Some(size)) => (offset, size),
+        _ => (0, 0),
+    };
+    let bcs = |hex: &str, what: &str| {
+        parse_hex_bytes_variable(hex).ok_or_else(|| TraverseValenceError::InvalidWitness(format!("Invalid {} hex", what)))
+    };
+    let witness = sui::SuiWitness {
+        checkpoint: object_proof.checkpoint,
+        checkpoint_digest: parse_hex_hash(&object_proof.checkpoint_digest, "checkpoint digest")?,
+        field_offset,
+        field_size,
+        object: bcs(&object_proof.object_bcs, "object BCS")?,
+        effects: bcs(&object_proof.effects, "effects")?,
+        checkpoint_contents: bcs(&object_proof.checkpoint_contents, "checkpoint contents")?,
+        checkpoint_summary: bcs(&object_proof.checkpoint_summary, "checkpoint summary")?,
+    };
+
+    let object = witness.verify().map_err(invalid)?;
+    if object.object_id != object_id {
+        return Err(invalid("Object contents do not start with the object ID"));
+    }
+    if object.version != object_proof.version {
+        return Err(invalid("Object BCS is not of the proof's version"));
+    }
+    if object.object_digest != parse_hex_hash(&object_proof.digest, "object digest")? {
+        return Err(invalid("Object BCS does not hash to the proof's digest"));
+    }
+    let contents = sui::parse_object(&witness.object).map_err(invalid)?.contents;
+    if Some(contents) != parse_hex_bytes_variable(&object_proof.contents).as_deref() {
+        return Err(invalid("Proof's contents are not the object BCS's"));
+    }
+
+    Ok(Witness::Data(witness.to_bytes()))
+}
+
+/// Create witnesses from batch Sui object verification request (no_std compatible)
+pub fn create_witnesses_from_batch_sui_request(
+    request: &BatchSuiObjectVerificationRequest,
+) -> Result<Vec<Witness>, TraverseValenceError> {
+    let mut witnesses = Vec::with_capacity(request.object_batch.len());
+
+    for (index, object_request) in request.object_batch.iter().enumerate() {
+        if let Some(checkpoint) = request.checkpoint {
+            if object_request.object_proof.checkpoint != checkpoint {
+                return Err(TraverseValenceError::InvalidWitness(format!(
+                    "Batch item {}: proof is anchored to checkpoint {}, not {}",
+                    index, object_request.object_proof.checkpoint, checkpoint
+                )));
+            }
+        }
+        let witness = create_witness_from_sui_request(object_request)
+            .map_err(|e| TraverseValenceError::InvalidWitness(format!("Batch item {}: {}", index, e)))?;
+        witnesses.push(witness);
+    }
+
+    Ok(witnesses)
+}
+
+/// Create a Sui witness from object data (no_std compatible)
+///
+/// The data is checked as [`create_witness_from_sui_request`] checks it,
+/// and written in the format [`crate::sui`] documents. A `field_size` of 0
+/// reads the object ID.
+#[allow(clippy::too_many_arguments)]
+pub fn create_sui_witness_from_object_data(
+    checkpoint: u64,
+    checkpoint_digest: &[u8; 32],
+    field_offset: u32,
+    field_size: u32,
+    object_bcs: &[u8],
+    effects: &[u8],
+    checkpoint_contents: &[u8],
+    checkpoint_summary: &[u8],
+) -> Result<Witness, TraverseValenceError> {
+    let witness = sui::SuiWitness {
+        checkpoint,
+        checkpoint_digest: *checkpoint_digest,
+        field_offset,
+        field_size,
+        object: object_bcs.to_vec(),
+        effects: effects.to_vec(),
+        checkpoint_contents: checkpoint_contents.to_vec(),
+        checkpoint_summary: checkpoint_summary.to_vec(),
+    };
+    witness.verify().map_err(|e| TraverseValenceError::InvalidWitness(e.into()))?;
+    Ok(Witness::Data(witness.to_bytes()))
+}
+
+/// Parse a Sui object ID, left-padding short forms such as `0x6` (no_std compatible)
+fn parse_sui_object_id(object_id: &str) -> Result<[u8; 32], TraverseValenceError> {
+    let invalid = || TraverseValenceError::InvalidWitness(format!("Invalid Sui object ID: {}", object_id));
+    let digits = object_id.strip_prefix("0x").unwrap_or(object_id);
+    if digits.is_empty() || digits.len() > 64 {
+        return Err(invalid());
+    }
+    let padded = format!("{:0>64}", digits);
+    let bytes = parse_hex_bytes(&padded, 32).ok_or_else(invalid)?;
+    let mut result = [0u8; 32];
+    result.copy_from_slice(&bytes);
+    Ok(result)
+}
+
+// === Substrate Storage Verification APIs ===
+
+/// Create a witness from Substrate storage verification request (no_std compatible)
+///
+/// The header is hashed to the block hash, and the value is followed from
+/// its state root through the main trie's nodes, and the child trie's for
+/// a value in one. The field is read from the SCALE-encoded value at the
+/// offset the layout resolved; without one, the whole value is taken, which
+/// must then fit in 32 bytes. The proof's block number, state root, child
+/// trie root and value must be the ones the header and nodes yield.
+pub fn create_witness_from_substrate_request(
+    request: &SubstrateStorageVerificationRequest,
+) -> Result<Witness, TraverseValenceError> {
+    let storage_query = &request.storage_query;
+    let storage_proof = &request.storage_proof;
+    let invalid = |e: &str| TraverseValenceError::InvalidWitness(e.into());
+    let items = |items: &[alloc::string::String]| {
+        items
+            .iter()
+            .map(|item| parse_hex_bytes_variable(item).ok_or_else(|| invalid("Invalid trie item hex")))
+            .collect::<Result<Vec<_>, _>>()
+    };
+
+    let key = parse_hex_bytes_variable(&storage_proof.key).ok_or_else(|| invalid("Invalid storage key hex"))?;
+    if parse_hex_bytes_variable(&storage_query.storage_key).as_deref() != Some(&key[..]) {
+        return Err(TraverseValenceError::InvalidWitness(format!(
+            "Query is for key {}, but the proof is for {}",
+            storage_query.storage_key, storage_proof.key
+        )));
+    }
+
+    let (child_storage_key, child_proof) = match (&storage_query.child_storage_key, &storage_proof.child) {
+        (None, None) => (None, Vec::new()),
+        (Some(child_key), Some(child)) => {
+            let child_storage_key = parse_hex_bytes_variable(&child.storage_key)
+                .filter(|key| !key.is_empty())
+                .ok_or_else(|| invalid("Invalid child trie key hex"))?;
+            if parse_hex_bytes_variable(child_key).as_ref() != Some(&child_storage_key) {
+                return Err(TraverseValenceError::InvalidWitness(format!(
+                    "Query is for child trie {}, but the proof is for {}",
+                    child_key, child.storage_key
+                )));
+            }
+            (Some(child_storage_key), items(&child.proof)?)
+        }
+        _ => return Err(invalid("Query and proof disagree on whether the value is in a child trie")),
+    };
+
+    let (field_offset, field_size) = match (storage_query.field_offset, storage_query.field_size) {
+        (Some(_), Some(0)) => return Err(invalid("Field size must be nonzero")),
+        (Some(offset), Some(size)) => (offset, size),
+        _ => (0, 0),
+    };
+    let witness = substrate::SubstrateWitness {
+        block_hash: parse_hex_hash(&storage_proof.block_hash, "block hash")?,
+        header: parse_hex_bytes_variable(&storage_proof.header).ok_or_else(|| invalid("Invalid header hex"))?,
+        storage_key: key,
+        child_storage_key,
+        proof: items(&storage_proof.proof)?,
+        child_proof,
+        field_offset,
+        field_size,
+    };
+
+    let proven = witness.verify().map_err(invalid)?;
+    if proven.block_number != storage_proof.block_number {
+        return Err(invalid("Header is not of the proof's block number"));
+    }
+    if proven.state_root != parse_hex_hash(&storage_proof.state_root, "state root")? {
+        return Err(invalid("Header does not hold the proof's state root"));
+    }
+    if let Some(child) = &storage_proof.child {
+        if proven.child_root != parse_hex_hash(&child.root, "child trie root")? {
+            return Err(invalid("State does not hold the proof's child trie root"));
+        }
+    }
+    let value = match &storage_proof.value {
+        Some(value) => Some(parse_hex_bytes_variable(value).ok_or_else(|| invalid("Invalid storage value hex"))?),
+        None => None,
+    };
+    if proven.value != value {
+        return Err(invalid("Proof's value is not the one the trie holds"));
+    }
+
+    Ok(Witness::Data(witness.to_bytes()))
+}
+
+/// Create witnesses from batch Substrate storage verification request (no_std compatible)
+pub fn create_witnesses_from_batch_substrate_request(
+    request: &BatchSubstrateStorageVerificationRequest,
+) -> Result<Vec<Witness>, TraverseValenceError> {
+    let block_hash = match &request.block_hash {
+        Some(block_hash) => Some(parse_hex_hash(block_hash, "batch block hash")?),
+        None => None,
+    };
+    let mut witnesses = Vec::with_capacity(request.storage_batch.len());
+
+    for (index, storage_request) in request.storage_batch.iter().enumerate() {
+        if let Some(block_hash) = block_hash {
+            let proof_block = parse_hex_bytes(&storage_request.storage_proof.block_hash, 32);
+            if proof_block.as_deref() != Some(&block_hash[..]) {
+                return Err(TraverseValenceError::InvalidWitness(format!(
+                    "Batch item {}: proof is read at block {}, not the batch's",
+                    index, storage_request.storage_proof.block_hash
+                )));
+            }
+        }
+        let witness = create_witness_from_substrate_request(storage_request)
+            .map_err(|e| TraverseValenceError::InvalidWitness(format!("Batch item {}: {}", index, e)))?;
+        witnesses.push(witness);
+    }
+
+    Ok(witnesses)
+}
+
+/// Create a Substrate witness from storage data (no_std compatible)
+///
+/// The data is checked as [`create_witness_from_substrate_request`] checks
+/// it, and written in the format [`crate::substrate`] documents. A
+/// `field_size` of 0 takes the whole value.
+#[allow(clippy::too_many_arguments)]
+pub fn create_substrate_witness_from_storage_data(
+    block_hash: &[u8; 32],
+    header: &[u8],
+    storage_key: &[u8],
+    child_storage_key: Option<&[u8]>,
+    proof: &[Vec<u8>],
+    child_proof: &[Vec<u8>],
+    field_offset: u32,
+    field_size: u32,
+) -> Result<Witness, TraverseValenceError> {
+    let witness = substrate::SubstrateWitness {
+        block_hash: *block_hash,
+        header: header.to_vec(),
+        storage_key: storage_key.to_vec(),
+        child_storage_key: child_storage_key.map(<[u8]>::to_vec),
+        proof: proof.to_vec(),
+        child_proof: child_proof.to_vec(),
+        field_offset,
+        field_size,
+    };
+    witness.verify().map_err(|e| TraverseValenceError::InvalidWitness(e.into()))?;
+    Ok(Witness::Data(witness.to_bytes()))
+}
+
+/// Parse a 32-byte hex hash (no_std compatible)
+fn parse_hex_hash(hex_str: &str, what: &str) -> Result<[u8; 32], TraverseValenceError> {
+    let bytes = parse_hex_bytes(hex_str, 32)
+        .ok_or_else(|| TraverseValenceError::InvalidWitness(format!("Invalid {}", what)))?;
+    let mut result = [0u8; 32];
+    result.copy_from_slice(&bytes);
+    Ok(result)
+}
+
+// === Starknet Storage Verification APIs ===
+//
+// These need the `starknet` feature, which brings the Pedersen and Poseidon
+// hashes the tries are built with.
+
+/// The Stark field's order, big-endian
+#[cfg(feature = "starknet")]
+const STARK_PRIME: [u8; 32] = [
+    0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x11, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+];
+
+/// Create a witness from Starknet storage verification request (no_std compatible)
+///
+/// The trie roots must make up the state commitment, the contracts trie
+/// must hold the contract's leaf and its storage trie each felt of the
+/// value, and each felt must be the value the proof states. A one-felt
+/// value is taken as it is. A `u256` spans two felts, its low and high 128
+/// bits, which are joined into one big-endian word. Starknet does not tell
+/// unwritten storage from a stored zero, so there is no presence flag.
+#[cfg(feature = "starknet")]
+pub fn create_witness_from_starknet_request(
+    request: &StarknetStorageVerificationRequest,
+) -> Result<Witness, TraverseValenceError> {
+    let invalid = |e: &str| TraverseValenceError::InvalidWitness(e.into());
+    let storage_query = &request.storage_query;
+    let storage_proof = &request.storage_proof;
+
+    let contract_address = parse_felt_hex(&storage_proof.contract_address, "contract address")?;
+    if parse_felt_hex(&storage_query.contract_address, "query contract address")? != contract_address {
+        return Err(TraverseValenceError::InvalidWitness(format!(
+            "Query is for contract {}, but the proof is for {}",
+            storage_query.contract_address, storage_proof.contract_address
+        )));
+    }
+
+    if !(1..=2).contains(&storage_query.felts) {
+        return Err(TraverseValenceError::InvalidWitness(format!(
+            "A value of {} felts cannot be put in a witness",
+            storage_query.felts
+        )));
+    }
+
+    // Each felt of the value, found by its address in the proof
+    let storage_address = parse_felt_hex(&storage_query.storage_address, "storage address")?;
+    let mut stated = Vec::with_capacity(2);
+    let mut value_nodes = Vec::with_capacity(2);
+    for index in 0..storage_query.felts {
+        let address = add_to_felt(&storage_address, index as u8)
+            .ok_or_else(|| invalid("Storage address overflows the field"))?;
+        let mut found = None;
+        for stored in &storage_proof.storage {
+            if parse_felt_hex(&stored.address, "proof storage address")? == address {
+                found = Some(stored);
+            }
+        }
+        let stored = found.ok_or_else(|| {
+            TraverseValenceError::InvalidWitness(format!("Proof has no value for felt {} of {}", index, storage_query.query))
+        })?;
+        stated.push(parse_felt(&stored.value, "storage value")?);
+        value_nodes.push(parse_starknet_nodes(&stored.nodes)?);
+    }
+
+    let contract = &storage_proof.contract;
+    let witness = starknet::StarknetWitness {
+        block_number: storage_proof.block_number,
+        state_commitment: parse_felt(&storage_proof.state_commitment, "state commitment")?,
+        contracts_root: parse_felt(&storage_proof.contracts_root, "contracts root")?,
+        classes_root: parse_felt(&storage_proof.classes_root, "classes root")?,
+        contract_address: Felt::from_bytes_be(&contract_address),
+        contract: starknet::ContractLeaf {
+            class_hash: parse_felt(&contract.class_hash, "class hash")?,
+            nonce: parse_felt(&contract.nonce, "nonce")?,
+            storage_root: parse_felt(&contract.storage_root, "storage root")?,
+        },
+        contract_nodes: parse_starknet_nodes(&storage_proof.contract_nodes)?,
+        storage_address: Felt::from_bytes_be(&storage_address),
+        value_nodes,
+    };
+    let proven = witness.verify().map_err(invalid)?;
+    if proven.felts != stated {
+        return Err(invalid("Proof states a value its storage trie does not hold"));
+    }
+
+    Ok(Witness::Data(witness.to_bytes()))
+}
+
+/// Create witnesses from batch Starknet storage verification request (no_std compatible)
+#[cfg(feature = "starknet")]
+pub fn create_witnesses_from_batch_starknet_request(
+    request: &BatchStarknetStorageVerificationRequest,
+) -> Result<Vec<Witness>, TraverseValenceError> {
+    let mut witnesses = Vec::with_capacity(request.storage_batch.len());
+
+    for (index, storage_request) in request.storage_batch.iter().enumerate() {
+        if let Some(block_number) = request.block_number {
+            if storage_request.storage_proof.block_number != block_number {
+                return Err(TraverseValenceError::InvalidWitness(format!(
+                    "Batch item {}: proof is read at block {}, not {}",
+                    index, storage_request.storage_proof.block_number, block_number
+                )));
+            }
+        }
+        let witness = create_witness_from_starknet_request(storage_request)
+            .map_err(|e| TraverseValenceError::InvalidWitness(format!("Batch item {}: {}", index, e)))?;
+        witnesses.push(witness);
+    }
+
+    Ok(witnesses)
+}
+
+/// Create a Starknet witness from storage data (no_std compatible)
+///
+/// The tries are checked as [`create_witness_from_starknet_request`] checks
+/// them, and written in the format [`crate::starknet`] documents.
+/// `value_nodes` holds the storage trie nodes of each felt of the value,
+/// one entry for a felt and two for a u256.
+#[cfg(feature = "starknet")]
+#[allow(clippy::too_many_arguments)]
+pub fn create_starknet_witness_from_storage_data(
+    block_number: u64,
+    state_commitment: &Felt,
+    contracts_root: &Felt,
+    classes_root: &Felt,
+    contract_address: &Felt,
+    contract: &starknet::ContractLeaf,
+    contract_nodes: &[starknet::TrieNode],
+    storage_address: &Felt,
+    value_nodes: &[Vec<starknet::TrieNode>],
+) -> Result<Witness, TraverseValenceError> {
+    let witness = starknet::StarknetWitness {
+        block_number,
+        state_commitment: *state_commitment,
+        contracts_root: *contracts_root,
+        classes_root: *classes_root,
+        contract_address: *contract_address,
+        contract: contract.clone(),
+        contract_nodes: contract_nodes.to_vec(),
+        storage_address: *storage_address,
+        value_nodes: value_nodes.to_vec(),
+    };
+    witness.verify().map_err(|e| TraverseValenceError::InvalidWitness(e.into()))?;
+    Ok(Witness::Data(witness.to_bytes()))
+}
+
+/// Parse a hex felt, which may have leading zeros left out (no_std compatible)
+#[cfg(feature = "starknet")]
+fn parse_felt_hex(hex_str: &str, what: &str) -> Result<[u8; 32], TraverseValenceError> {
+    let invalid = || TraverseValenceError::InvalidWitness(format!("Invalid {}", what));
+    let digits = hex_str.strip_prefix("0x").unwrap_or(hex_str);
+    if digits.is_empty() || digits.len() > 64 {
+        return Err(invalid());
+    }
+    let padded = format!("{:0>64}", digits);
+    let mut result = [0u8; 32];
+    hex::decode_to_slice(padded, &mut result).map_err(|_| invalid())?;
+    if result >= STARK_PRIME {
+        return Err(invalid());
+    }
+    Ok(result)
+}
+
+/// [`parse_felt_hex`], as a field element
+#[cfg(feature = "starknet")]
+fn parse_felt(hex_str: &str, what: &str) -> Result<Felt, TraverseValenceError> {
+    parse_felt_hex(hex_str, what).map(|bytes| Felt::from_bytes_be(&bytes))
+}
+
+/// Parse trie nodes as traverse-starknet writes them
+#[cfg(feature = "starknet")]
+fn parse_starknet_nodes(nodes: &[StarknetTrieNode]) -> Result<Vec<starknet::TrieNode>, TraverseValenceError> {
+    nodes
+        .iter()
+        .map(|node| match node {
+            StarknetTrieNode::Binary { left, right } => Ok(starknet::TrieNode::Binary {
+                left: parse_felt(left, "trie node")?,
+                right: parse_felt(right, "trie node")?,
+            }),
+            StarknetTrieNode::Edge { child, path } => Ok(starknet::TrieNode::Edge {
+                child: parse_felt(child, "trie node")?,
+                path: parse_felt(&path.value, "edge path")?,
+                length: path.len,
+            }),
+        })
+        .collect()
+}
+
+/// `felt + offset`, if it stays in the field
+#[cfg(feature = "starknet")]
+fn add_to_felt(felt: &[u8; 32], offset: u8) -> Option<[u8; 32]> {
+    let mut result = *felt;
+    let mut carry = offset as u16;
+    for byte in result.iter_mut().rev() {
+        let sum = *byte as u16 + carry;
+        *byte = sum as u8;
+        carry = sum >> 8;
+    }
+    (carry == 0 && result < STARK_PRIME).then_some(result)
+}
+
+// === Bitcoin Transaction Verification APIs ===
+
+/// Create a witness from Bitcoin transaction verification request (no_std compatible)
+///
+/// The witness carries the raw transaction, its Merkle branch and every
+/// header from its block to the tip, so the circuit can redo the SPV checks
+/// of [`crate::bitcoin`]. The controller does them first and rejects the
+/// proof when the txid, block hash, chain work or outputs it states are not
+/// what its raw data hashes to. Only an output's creation is proven; SPV
+/// cannot show that it is still unspent.
+pub fn create_witness_from_bitcoin_request(
+    request: &BitcoinTxVerificationRequest,
+) -> Result<Witness, TraverseValenceError> {
+    let tx_query = &request.tx_query;
+    let tx_proof = &request.tx_proof;
+    let invalid = |e: &str| TraverseValenceError::InvalidWitness(e.into());
+
+    let txid = parse_display_hash_hex(&tx_proof.txid, "txid")?;
+    if parse_display_hash_hex(&tx_query.txid, "query txid")? != txid {
+        return Err(TraverseValenceError::InvalidWitness(format!(
+            "Query is for transaction {}, but the proof is for {}",
+            tx_query.txid, tx_proof.txid
+        )));
+    }
+
+    let headers = tx_proof
+        .headers
+        .iter()
+        .map(|header| {
+            parse_hex_bytes(header, bitcoin::HEADER_SIZE)
+                .map(|bytes| bytes.try_into().expect("80 bytes"))
+                .ok_or_else(|| invalid("Invalid block header"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let branch = tx_proof
+        .merkle
+        .branch
+        .iter()
+        .map(|sibling| parse_display_hash_hex(sibling, "Merkle branch"))
+        .collect::<Result<Vec<_>, _>>()?;
+    let transaction =
+        parse_hex_bytes_variable(&tx_proof.transaction).ok_or_else(|| invalid("Invalid raw transaction"))?;
+    let witness = bitcoin::BitcoinWitness {
+        block_height: tx_proof.block_height,
+        headers,
+        merkle_index: tx_proof.merkle.index,
+        tx_count: tx_proof.merkle.tx_count,
+        branch,
+        transaction,
+        vout: tx_query.vout,
+    };
+
+    let confirmation = witness.verify().map_err(invalid)?;
+    if confirmation.txid != txid {
+        return Err(invalid("Raw transaction does not hash to the proof's txid"));
+    }
+    if confirmation.block_hash != parse_display_hash_hex(&tx_proof.block_hash, "block hash")? {
+        return Err(invalid("First header does not hash to the proof's block hash"));
+    }
+    if confirmation.chain_work != parse_work_hex(&tx_proof.chain_work)? {
+        return Err(invalid("Headers do not carry the proof's chain work"));
+    }
+    let outputs = parse_transaction(&witness.transaction).map_err(invalid)?.outputs;
+    let stated_outputs = tx_proof
+        .outputs
+        .iter()
+        .map(|output| {
+            let script_pubkey =
+                parse_hex_bytes_variable(&output.script_pubkey).ok_or_else(|| invalid("Invalid output script"))?;
+            Ok(bitcoin::TxOutput { value: output.value, script_pubkey })
+        })
+        .collect::<Result<Vec<_>, TraverseValenceError>>()?;
+    if stated_outputs != outputs {
+        return Err(invalid("Proof's outputs are not the raw transaction's"));
+    }
+    if confirmation.confirmations < tx_query.min_confirmations {
+        return Err(TraverseValenceError::InvalidWitness(format!(
+            "Transaction has {} confirmations, fewer than {}",
+            confirmation.confirmations, tx_query.min_confirmations
+        )));
+    }
+
+    Ok(Witness::Data(witness.to_bytes()))
+}
+
+/// Create witnesses from batch Bitcoin transaction verification request (no_std compatible)
+pub fn create_witnesses_from_batch_bitcoin_request(
+    request: &BatchBitcoinTxVerificationRequest,
+) -> Result<Vec<Witness>, TraverseValenceError> {
+    let mut witnesses = Vec::with_capacity(request.tx_batch.len());
+
+    for (index, tx_request) in request.tx_batch.iter().enumerate() {
+        if let Some(tip_height) = request.tip_height {
+            let proof = &tx_request.tx_proof;
+            let proof_tip = (proof.block_height + proof.headers.len() as u64).checked_sub(1);
+            if proof_tip != Some(tip_height) {
+                return Err(TraverseValenceError::InvalidWitness(format!(
+                    "Batch item {}: proof does not run to height {}",
+                    index, tip_height
+                )));
+            }
+        }
+        let witness = create_witness_from_bitcoin_request(tx_request)
+            .map_err(|e| TraverseValenceError::InvalidWitness(format!("Batch item {}: {}", index, e)))?;
+        witnesses.push(witness);
+    }
+
+    Ok(witnesses)
+}
+
+/// Create a Bitcoin witness from transaction data (no_std compatible)
+///
+/// Hashes are in internal byte order, the reverse of what RPCs show. The
+/// data is checked as [`create_witness_from_bitcoin_request`] checks it,
+/// and written in the format [`crate::bitcoin`] documents.
+pub fn create_bitcoin_witness_from_tx_data(
+    block_height: u64,
+    headers: &[[u8; bitcoin::HEADER_SIZE]],
+    merkle_index: u32,
+    tx_count: u32,
+    branch: &[[u8; 32]],
+    transaction: &[u8],
+    vout: Option<u32>,
+) -> Result<Witness, TraverseValenceError> {
+    let witness = bitcoin::BitcoinWitness {
+        block_height,
+        headers: headers.to_vec(),
+        merkle_index,
+        tx_count,
+        branch: branch.to_vec(),
+        transaction: transaction.to_vec(),
+        vout,
+    };
+    witness.verify().map_err(|e| TraverseValenceError::InvalidWitness(e.into()))?;
+    Ok(Witness::Data(witness.to_bytes()))
+}
+
+/// Parse a hash shown in reversed byte order into internal order (no_std compatible)
+fn parse_display_hash_hex(hex_str: &str, what: &str) -> Result<[u8; 32], TraverseValenceError> {
+    let mut hash = parse_hex_hash(hex_str, what)?;
+    hash.reverse();
+    Ok(hash)
+}
+
+/// Parse nonzero chain work, which may have leading zeros left out (no_std compatible)
+fn parse_work_hex(hex_str: &str) -> Result<[u8; 32], TraverseValenceError> {
+    let invalid = || TraverseValenceError::InvalidWitness("Invalid chain work".into());
+    let digits = hex_str.strip_prefix("0x").unwrap_or(hex_str);
+    if digits.is_empty() || digits.len() > 64 {
+        return Err(invalid());
+    }
+    let mut result = [0u8; 32];
+    hex::decode_to_slice(format!("{:0>64}", digits), &mut result).map_err(|_| invalid())?;
+    if result == [0u8; 32] {
+        return Err(invalid());
+    }
+    Ok(result)
+}
+
+// === TON Contract Data Verification APIs ===
+
+/// Create a witness from TON contract data verification request (no_std compatible)
+///
+/// The proof's bags of cells are checked from the masterchain block down
+/// to the account's data, and the field is read at the location
+/// traverse-ton gives for it; the value read must be the proof's. Where a
+/// TL-B field starts depends on the values before it, so the location is
+/// the prover's, and a circuit that knows the layout checks it.
+pub fn create_witness_from_ton_request(
+    request: &TonFieldVerificationRequest,
+) -> Result<Witness, TraverseValenceError> {
+    let field_query = &request.field_query;
+    let account_proof = &request.account_proof;
+    let invalid = |e: &str| TraverseValenceError::InvalidWitness(e.into());
+    let bytes = |hex: &str, what: &str| {
+        parse_hex_bytes_variable(hex).ok_or_else(|| TraverseValenceError::InvalidWitness(format!("Invalid {} hex", what)))
+    };
+
+    let (workchain, account_id) = parse_ton_address(&account_proof.address)?;
+    if parse_ton_address(&field_query.address)? != (workchain, account_id) {
+        return Err(TraverseValenceError::InvalidWitness(format!(
+            "Query is for account {}, but the proof is for {}",
+            field_query.address, account_proof.address
+        )));
+    }
+
+    let field = account_proof
+        .fields
+        .iter()
+        .find(|field| field.query == field_query.query)
+        .ok_or_else(|| {
+            TraverseValenceError::InvalidWitness(format!("Proof has no value for {}", field_query.query))
+        })?;
+    if field.present && field.location.field_type != field_query.field_type {
+        return Err(TraverseValenceError::InvalidWitness(format!(
+            "Query is for a {}, but the proof reads a {}",
+            field_query.field_type, field.location.field_type
+        )));
+    }
+    let word = match (&field.word, field.present) {
+        (Some(word), true) => Some(parse_hex_hash(word, "field value")?),
+        (None, true) => {
+            return Err(TraverseValenceError::InvalidWitness(format!(
+                "{} does not fit in 32 bytes",
+                field_query.query
+            )))
+        }
+        (_, false) => None,
+    };
+
+    let witness = ton::TonWitness {
+        mc_block: parse_ton_block_id(&account_proof.mc_block)?,
+        shard_block: parse_ton_block_id(&account_proof.shard_block)?,
+        workchain,
+        account_id,
+        shard_proof: bytes(&account_proof.shard_proof, "shard proof")?,
+        proof: bytes(&account_proof.proof, "proof")?,
+        state: bytes(&account_proof.state, "state")?,
+        location: ton::FieldLocation {
+            refs: field.location.refs.clone(),
+            bit: field.location.bit,
+            next_ref: field.location.next_ref,
+            type_name: field.location.field_type.clone(),
+        },
+    };
+
+    let proven = witness.verify().map_err(invalid)?;
+    if proven.account_hash != parse_hex_hash(&account_proof.account_hash, "account hash")? {
+        return Err(invalid("Proof's account hash is not the one the shard state holds"));
+    }
+    if proven.last_trans_hash != parse_hex_hash(&account_proof.last_trans_hash, "last transaction hash")?
+        || proven.last_trans_lt != account_proof.last_trans_lt
+    {
+        return Err(invalid("Proof's last transaction is not the one the shard state holds"));
+    }
+    if proven.value != word {
+        return Err(invalid("Proof's value is not the one the account's data holds"));
+    }
+
+    Ok(Witness::Data(witness.to_bytes()))
+}
+
+/// Create witnesses from batch TON contract data verification request (no_std compatible)
+pub fn create_witnesses_from_batch_ton_request(
+    request: &BatchTonFieldVerificationRequest,
+) -> Result<Vec<Witness>, TraverseValenceError> {
+    let mut witnesses = Vec::with_capacity(request.field_batch.len());
+
+    for (index, field_request) in request.field_batch.iter().enumerate() {
+        if let Some(mc_seqno) = request.mc_seqno {
+            if field_request.account_proof.mc_block.seqno != mc_seqno {
+                return Err(TraverseValenceError::InvalidWitness(format!(
+                    "Batch item {}: proof is read at masterchain block {}, not {}",
+                    index, field_request.account_proof.mc_block.seqno, mc_seqno
+                )));
+            }
+        }
+        let witness = create_witness_from_ton_request(field_request)
+            .map_err(|e| TraverseValenceError::InvalidWitness(format!("Batch item {}: {}", index, e)))?;
+        witnesses.push(witness);
+    }
+
+    Ok(witnesses)
+}
+
+/// Create a TON witness from account data (no_std compatible)
+///
+/// The data is checked as [`create_witness_from_ton_request`] checks it,
+/// and written in the format [`crate::ton`] documents.
+#[allow(clippy::too_many_arguments)]
+pub fn create_ton_witness_from_account_data(
+    mc_block: &ton::BlockIdExt,
+    shard_block: &ton::BlockIdExt,
+    workchain: i32,
+    account_id: &[u8; 32],
+    shard_proof: &[u8],
+    proof: &[u8],
+    state: &[u8],
+    location: &ton::FieldLocation,
+) -> Result<Witness, TraverseValenceError> {
+    let witness = ton::TonWitness {
+        mc_block: *mc_block,
+        shard_block: *shard_block,
+        workchain,
+        account_id: *account_id,
+        shard_proof: shard_proof.to_vec(),
+        proof: proof.to_vec(),
+        state: state.to_vec(),
+        location: location.clone(),
+    };
+    witness.verify().map_err(|e| TraverseValenceError::InvalidWitness(e.into()))?;
+    Ok(Witness::Data(witness.to_bytes()))
+}
+
+/// Parse a TON block ID, its shard in hex (no_std compatible)
+fn parse_ton_block_id(block: &TonBlockId) -> Result<ton::BlockIdExt, TraverseValenceError> {
+    Ok(ton::BlockIdExt {
+        workchain: block.workchain,
+        shard: u64::from_str_radix(&block.shard, 16)
+            .map_err(|_| TraverseValenceError::InvalidWitness(format!("Invalid shard {}", block.shard)))?,
+        seqno: block.seqno,
+        root_hash: parse_hex_hash(&block.root_hash, "block root hash")?,
+        file_hash: parse_hex_hash(&block.file_hash, "block file hash")?,
+    })
+}
+
+/// Parse a raw TON address, `workchain:hex` (no_std compatible)
+fn parse_ton_address(address: &str) -> Result<(i32, [u8; 32]), TraverseValenceError> {
+    let invalid = || TraverseValenceError::InvalidWitness(format!("Invalid TON address {}", address));
+    let (workchain, account_id) = address.split_once(':').ok_or_else(invalid)?;
+    let workchain = workchain.parse::<i32>().map_err(|_| invalid())?;
+    Ok((workchain, parse_hex_hash(account_id, "account ID")?))
+}
+
 /// Derive field index from layout commitment and storage key
 fn derive_field_index_from_layout(layout_commitment: &[u8], storage_key: &[u8]) -> Result<u16, TraverseValenceError> {
     // Simple derivation: XOR first few bytes of layout commitment with storage key
@@ -684,9 +1574,9 @@ fn derive_field_index_from_layout(layout_commitment: &[u8], storage_key: &[u8])
 #[cfg(feature = "std")]
 fn extract_block_height_from_json(json_args: &Value) -> Option<u64> {
     json_args.get("block_height")
-        .and_then(|v| v.as_u64())
-        .or_else(|| json_args.get("blockHeight").and_then(|v| v.as_u64()))
-        .or_else(|| json_args.get("slot").and_then(|v| v.as_u64()))
+        .and_then(decimal::from_json)
+        .or_else(|| json_args.get("blockHeight").and_then(decimal::from_json))
+        .or_else(|| json_args.get("slot").and_then(decimal::from_json))
 }
 
 /// Extract block hash from JSON if available
@@ -864,6 +1754,88 @@ mod tests {
         assert_eq!(witnesses.len(), 2);
     }
 
+    #[test]
+    fn test_multi_slot_bundle() {
+        use crate::circuit::{CircuitProcessor, CircuitResult, FieldType, MultiSlotBundle, ZeroSemantics};
+
+        // Eight slots whose proofs share a 500-byte root and branch node
+        let root = alloc::format!("f9{}", "11".repeat(500));
+        let branch = alloc::format!("f9{}", "22".repeat(500));
+        let commitment = "f6dc3c4a79e95565b3cf38993f1a120c6a6b467796264e7fd9a9c8675616dd7a";
+        let slot = |i: u8| {
+            let key = alloc::format!("{:064x}", i);
+            StorageVerificationRequest {
+                storage_query: CoprocessorStorageQuery {
+                    query: alloc::format!("values[{}]", i),
+                    storage_key: key.clone(),
+                    layout_commitment: commitment.to_string(),
+                    field_size: Some(32),
+                    offset: Some(0),
+                },
+                storage_proof: StorageProof {
+                    key,
+                    value: alloc::format!("{:064x}", 100 + u32::from(i)),
+                    proof: alloc::vec![root.clone(), branch.clone(), alloc::format!("e2{:02x}", i)],
+                },
+                contract_address: None,
+                block_number: Some(7),
+            }
+        };
+        let storage_batch: Vec<StorageVerificationRequest> = (0..8).map(slot).collect();
+        let request = MultiSlotVerificationRequest {
+            storage_batch: storage_batch.clone(),
+            contract_address: alloc::format!("0x{}", "ab".repeat(20)),
+            block_number: Some(7),
+            account_proof: alloc::vec![alloc::format!("f9{}", "33".repeat(500))],
+            storage_hash: alloc::format!("0x{}", "44".repeat(32)),
+        };
+
+        let size = |witnesses: &[Witness]| {
+            witnesses
+                .iter()
+                .map(|w| match w {
+                    Witness::Data(data) => data.len(),
+                    _ => panic!("Expected Data witness"),
+                })
+                .sum::<usize>()
+        };
+        let witnesses = create_witnesses_from_multi_slot_request(&request).unwrap();
+        assert_eq!(witnesses.len(), 9);
+        let batch = BatchStorageVerificationRequest { storage_batch, contract_address: None, block_number: None };
+        assert!(size(&witnesses) * 3 < size(&create_witnesses_from_batch_request(&batch).unwrap()));
+
+        let Witness::Data(data) = &witnesses[0] else { panic!("Expected Data witness") };
+        let bundle = MultiSlotBundle::parse(data).unwrap();
+        assert_eq!(bundle.contract_address, [0xab; 20]);
+        assert_eq!(bundle.storage_hash, [0x44; 32]);
+        assert_eq!((bundle.account_proof.len(), bundle.storage_nodes.len()), (1, 10));
+        assert!(MultiSlotBundle::parse(&data[..data.len() - 1]).is_none());
+
+        let slots: Vec<_> = witnesses[1..]
+            .iter()
+            .map(|w| match w {
+                Witness::Data(data) => CircuitProcessor::parse_witness_from_bytes(data).unwrap(),
+                _ => panic!("Expected Data witness"),
+            })
+            .collect();
+        let proof = bundle.storage_proof(&slots[3]).unwrap();
+        assert_eq!(proof.len(), 3);
+        assert_eq!(proof[2], [0xe2, 0x03]);
+
+        let mut layout_commitment = [0u8; 32];
+        layout_commitment.copy_from_slice(&hex::decode(commitment).unwrap());
+        let processor = CircuitProcessor::new(layout_commitment, alloc::vec![FieldType::Uint256; 8], alloc::vec![ZeroSemantics::ValidZero; 8]);
+        let mut dangling = slots[0].clone();
+        dangling.proof = alloc::vec![0xff, 0x00];
+        let results = processor.process_multi_slot(&bundle, &[dangling]);
+        assert!(matches!(results[0], CircuitResult::Invalid));
+
+        // A proof for another key is rejected before any witness is built
+        let mut mismatched = request.clone();
+        mismatched.storage_batch[2].storage_proof.key = alloc::format!("{:064x}", 9);
+        assert!(create_witnesses_from_multi_slot_request(&mismatched).is_err());
+    }
+
     #[cfg(feature = "std")]
     #[test]
     fn test_std_json_compatibility() {
@@ -1304,6 +2276,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_base58_address() {
+        // The system program is the all-zero key
+        assert_eq!(parse_base58_address("11111111111111111111111111111111").unwrap(), [0u8; 32]);
+        assert_eq!(
+            hex::encode(parse_base58_address("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap()),
+            "06ddf6e1d765a193d9cbe146ceeb79ac1cb485ed5f5b37913a8cf5857eff00a9"
+        );
+        let hash = bs58::encode([0xab; 32]).into_string();
+        assert_eq!(parse_base58_hash(&hash).unwrap(), [0xab; 32]);
+
+        // Not base58, too short, too long, or not the raw bytes of the string
+        assert!(parse_base58_address("0OIl1111111111111111111111111111").is_err());
+        assert!(parse_base58_address("2").is_err());
+        assert!(parse_base58_address(&bs58::encode([0xab; 33]).into_string()).is_err());
+        assert!(parse_base58_hash(&"1".repeat(33)).is_err());
+    }
+
     #[test]
     fn test_security_solana_address_parsing() {
         // Security Test: Solana address parsing security
@@ -1452,6 +2442,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_base64_data() {
+        assert_eq!(parse_base64_data("dGVzdGRhdGE=").unwrap(), b"testdata".to_vec());
+        assert!(parse_base64_data("").unwrap().is_empty());
+        assert!(parse_base64_data("dGVzdGRhdGE").is_err());
+        assert!(parse_base64_data("0x1234").is_err());
+
+        // Field offsets index decoded bytes, not the base64 text
+        let data = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, [0u8, 1, 2, 3, 0x2a, 5]);
+        let decoded = parse_base64_data(&data).unwrap();
+        assert_eq!(extract_field_from_account_data(&decoded, 4, 1).unwrap()[0], 0x2a);
+    }
+
     #[test]
     fn test_security_solana_base64_data_parsing() {
         // Security Test: Base64 data parsing security
@@ -1659,6 +2662,596 @@ mod tests {
         }
     }
 
+    fn sui_request(object_id: &str, field: Option<(u32, u32)>) -> SuiObjectVerificationRequest {
+        use crate::{SuiObjectQuery, SuiObjectProof};
+
+        // Object 0xef holding a u64 of 1000 after its ID
+        let witness = sui::tests::pool_witness(0, 0);
+        let contents = &witness.object[12..52];
+        SuiObjectVerificationRequest {
+            object_query: SuiObjectQuery {
+                query: "coin::Coin[0xef].balance".to_string(),
+                object_id: object_id.to_string(),
+                object_type: "0x2::coin::Coin<0x2::sui::SUI>".to_string(),
+                field_offset: field.map(|f| f.0),
+                field_size: field.map(|f| f.1),
+            },
+            object_proof: SuiObjectProof {
+                object_id: format!("0x{:0>64}", "ef"),
+                version: 7,
+                digest: format!("0x{}", hex::encode(sui::digest("Object", &witness.object))),
+                object_bcs: hex::encode(&witness.object),
+                contents: format!("0x{}", hex::encode(contents)),
+                effects: hex::encode(&witness.effects),
+                checkpoint_contents: hex::encode(&witness.checkpoint_contents),
+                checkpoint_summary: hex::encode(&witness.checkpoint_summary),
+                checkpoint: 42,
+                checkpoint_digest: format!("0x{}", hex::encode(witness.checkpoint_digest)),
+            },
+        }
+    }
+
+    #[test]
+    fn test_sui_witness_generation() {
+        let witness = create_witness_from_sui_request(&sui_request("0xef", Some((32, 8)))).unwrap();
+        let Witness::Data(data) = witness else { panic!("Expected Witness::Data") };
+        let parsed = sui::SuiWitness::parse(&data).unwrap();
+        assert_eq!((parsed.checkpoint, parsed.field_offset, parsed.field_size), (42, 32, 8));
+        assert_eq!(parsed.verify().unwrap().value[..8], 1_000u64.to_le_bytes());
+
+        // Without a field the value is the object ID
+        let Witness::Data(data) = create_witness_from_sui_request(&sui_request("0xef", None)).unwrap() else {
+            panic!("Expected Witness::Data")
+        };
+        let object = sui::SuiWitness::parse(&data).unwrap().verify().unwrap();
+        assert_eq!(object.value, object.object_id);
+
+        let from_data = create_sui_witness_from_object_data(
+            42,
+            &parsed.checkpoint_digest,
+            32,
+            8,
+            &parsed.object,
+            &parsed.effects,
+            &parsed.checkpoint_contents,
+            &parsed.checkpoint_summary,
+        )
+        .unwrap();
+        assert!(matches!(from_data, Witness::Data(bytes) if bytes == parsed.to_bytes()));
+    }
+
+    #[test]
+    fn test_security_sui_witness_validation() {
+        // The query and proof must name the same object
+        assert!(create_witness_from_sui_request(&sui_request("0xdead", Some((32, 8)))).is_err());
+
+        // Fields past the end of the contents are rejected
+        assert!(create_witness_from_sui_request(&sui_request("0xef", Some((36, 8)))).is_err());
+        assert!(create_witness_from_sui_request(&sui_request("0xef", Some((u32::MAX, 8)))).is_err());
+        assert!(create_witness_from_sui_request(&sui_request("0xef", Some((32, 0)))).is_err());
+
+        // The stated contents, version and digest must be what the BCS holds
+        let mut wrong_contents = sui_request("0xef", Some((32, 8)));
+        wrong_contents.object_proof.contents = format!("0x{:0>64}e903000000000000", "ef");
+        assert!(create_witness_from_sui_request(&wrong_contents).is_err());
+        let mut wrong_version = sui_request("0xef", None);
+        wrong_version.object_proof.version = 8;
+        assert!(create_witness_from_sui_request(&wrong_version).is_err());
+        let mut wrong_digest = sui_request("0xef", None);
+        wrong_digest.object_proof.digest = format!("0x{}", "11".repeat(32));
+        assert!(create_witness_from_sui_request(&wrong_digest).is_err());
+
+        // A BCS edited to hold another value no longer hashes to the checkpoint
+        let mut edited = sui_request("0xef", Some((32, 8)));
+        edited.object_proof.object_bcs = edited.object_proof.object_bcs.replace("e803", "e903");
+        edited.object_proof.contents = format!("0x{:0>64}e903000000000000", "ef");
+        assert!(create_witness_from_sui_request(&edited).is_err());
+
+        let mut other_checkpoint = sui_request("0xef", None);
+        other_checkpoint.object_proof.checkpoint_digest = format!("0x{}", "22".repeat(32));
+        assert!(create_witness_from_sui_request(&other_checkpoint).is_err());
+
+        // Batch items must share the batch's checkpoint
+        let batch = BatchSuiObjectVerificationRequest {
+            object_batch: alloc::vec![sui_request("0xef", None), sui_request("0xef", Some((32, 8)))],
+            checkpoint: Some(42),
+        };
+        assert_eq!(create_witnesses_from_batch_sui_request(&batch).unwrap().len(), 2);
+        let other = BatchSuiObjectVerificationRequest { checkpoint: Some(43), ..batch };
+        assert!(create_witnesses_from_batch_sui_request(&other).is_err());
+    }
+
+    fn substrate_request(storage_key: &str, field: Option<(u32, u32)>) -> SubstrateStorageVerificationRequest {
+        use crate::{SubstrateStorageQuery, SubstrateStorageProof};
+
+        // A timestamp under 0x10, in a trie that also holds a child trie's root under 0x20
+        let (witness, _, _, _) = substrate::tests::timestamp_witness();
+        let hex_list = |items: &[Vec<u8>]| items.iter().map(hex::encode).collect();
+        SubstrateStorageVerificationRequest {
+            storage_query: SubstrateStorageQuery {
+                query: "Timestamp::Now".to_string(),
+                storage_key: storage_key.to_string(),
+                child_storage_key: None,
+                field_offset: field.map(|f| f.0),
+                field_size: field.map(|f| f.1),
+            },
+            storage_proof: SubstrateStorageProof {
+                key: "0x10".to_string(),
+                value: Some("0x0068e5cf8b010000".to_string()),
+                proof: hex_list(&witness.proof),
+                child: None,
+                block_number: 9,
+                block_hash: format!("0x{}", hex::encode(witness.block_hash)),
+                header: hex::encode(&witness.header),
+                state_root: format!("0x{}", hex::encode(&witness.header[33..65])),
+            },
+        }
+    }
+
+    /// The request for the child trie value under 0x10, 36 bytes of 0x42
+    fn substrate_child_request(field: Option<(u32, u32)>) -> SubstrateStorageVerificationRequest {
+        let (_, child_key, child_items, child_root) = substrate::tests::timestamp_witness();
+        let mut request = substrate_request("0x10", field);
+        request.storage_query.child_storage_key = Some(hex::encode(&child_key));
+        request.storage_proof.value = Some(hex::encode([0x42; 36]));
+        request.storage_proof.child = Some(crate::SubstrateChildTrieRoot {
+            storage_key: hex::encode(&child_key),
+            root: hex::encode(child_root),
+            proof: child_items.iter().map(hex::encode).collect(),
+        });
+        request
+    }
+
+    #[test]
+    fn test_substrate_witness_generation() {
+        let Witness::Data(data) = create_witness_from_substrate_request(&substrate_request("0x10", None)).unwrap() else {
+            panic!("Expected Witness::Data")
+        };
+        let value = substrate::SubstrateWitness::parse(&data).unwrap().verify().unwrap();
+        assert_eq!((value.block_number, value.child_root), (9, [0; 32]));
+        assert_eq!(value.field[..8], 1_700_000_000_000u64.to_le_bytes());
+
+        // A field of a value in a child trie
+        let Witness::Data(data) = create_witness_from_substrate_request(&substrate_child_request(Some((2, 2)))).unwrap()
+        else {
+            panic!("Expected Witness::Data")
+        };
+        let witness = substrate::SubstrateWitness::parse(&data).unwrap();
+        assert_eq!((witness.field_offset, witness.field_size), (2, 2));
+        assert_eq!(witness.verify().unwrap().field[..3], [0x42, 0x42, 0]);
+
+        // A missing value is zero, with no value proven
+        let mut absent = substrate_request("0x30", None);
+        absent.storage_proof.key = "0x30".to_string();
+        absent.storage_proof.value = None;
+        let Witness::Data(data) = create_witness_from_substrate_request(&absent).unwrap() else {
+            panic!("Expected Witness::Data")
+        };
+        let value = substrate::SubstrateWitness::parse(&data).unwrap().verify().unwrap();
+        assert_eq!((value.value, value.field), (None, [0; 32]));
+    }
+
+    #[test]
+    fn test_security_substrate_witness_validation() {
+        // The query and proof must name the same key, in the same trie
+        assert!(create_witness_from_substrate_request(&substrate_request("0x20", None)).is_err());
+        let mut child_query = substrate_request("0x10", None);
+        child_query.storage_query.child_storage_key = Some("0x20".to_string());
+        assert!(create_witness_from_substrate_request(&child_query).is_err());
+
+        // Fields past the end of the value are rejected, and values longer than 32 bytes need one
+        assert!(create_witness_from_substrate_request(&substrate_request("0x10", Some((4, 8)))).is_err());
+        assert!(create_witness_from_substrate_request(&substrate_request("0x10", Some((u32::MAX, 1)))).is_err());
+        assert!(create_witness_from_substrate_request(&substrate_child_request(None)).is_err());
+
+        // The stated value, root and block must be what the header and nodes yield
+        let mut other_value = substrate_request("0x10", None);
+        other_value.storage_proof.value = Some("0x0168e5cf8b010000".to_string());
+        assert!(create_witness_from_substrate_request(&other_value).is_err());
+        let mut claimed_absent = substrate_request("0x10", None);
+        claimed_absent.storage_proof.value = None;
+        assert!(create_witness_from_substrate_request(&claimed_absent).is_err());
+        let mut other_root = substrate_request("0x10", None);
+        other_root.storage_proof.state_root = format!("0x{}", "22".repeat(32));
+        assert!(create_witness_from_substrate_request(&other_root).is_err());
+        let mut other_block = substrate_request("0x10", None);
+        other_block.storage_proof.block_number = 10;
+        assert!(create_witness_from_substrate_request(&other_block).is_err());
+        let mut other_child_root = substrate_child_request(Some((0, 8)));
+        other_child_root.storage_proof.child.as_mut().unwrap().root = format!("0x{}", "33".repeat(32));
+        assert!(create_witness_from_substrate_request(&other_child_root).is_err());
+        let mut missing_node = substrate_request("0x10", None);
+        missing_node.storage_proof.proof.remove(1);
+        assert!(create_witness_from_substrate_request(&missing_node).is_err());
+
+        // Batch items must be read at the batch's block
+        let request = substrate_request("0x10", None);
+        let batch = BatchSubstrateStorageVerificationRequest {
+            block_hash: Some(request.storage_proof.block_hash.clone()),
+            storage_batch: alloc::vec![request, substrate_request("0x10", Some((0, 8)))],
+        };
+        assert_eq!(create_witnesses_from_batch_substrate_request(&batch).unwrap().len(), 2);
+        let other = BatchSubstrateStorageVerificationRequest { block_hash: Some(format!("0x{}", "12".repeat(32))), ..batch };
+        assert!(create_witnesses_from_batch_substrate_request(&other).is_err());
+    }
+
+    #[cfg(feature = "starknet")]
+    fn starknet_request(storage_address: &str, felts: u32) -> StarknetStorageVerificationRequest {
+        use crate::{StarknetContractLeaf, StarknetEdgePath, StarknetStorageProof, StarknetStorageQuery, StarknetStorageValue};
+
+        let witness = starknet::tests::token_witness(0x5ff, 1);
+        let hex = |felt: &Felt| format!("{:#x}", felt);
+        let nodes = |nodes: &[starknet::TrieNode]| -> Vec<StarknetTrieNode> {
+            nodes
+                .iter()
+                .map(|node| match node {
+                    starknet::TrieNode::Binary { left, right } => StarknetTrieNode::Binary { left: hex(left), right: hex(right) },
+                    starknet::TrieNode::Edge { child, path, length } => StarknetTrieNode::Edge {
+                        child: hex(child),
+                        path: StarknetEdgePath { value: hex(path), len: *length },
+                    },
+                })
+                .collect()
+        };
+        let value = |address: &str, value: &str| StarknetStorageValue {
+            address: address.to_string(),
+            value: value.to_string(),
+            nodes: nodes(&witness.value_nodes[0]),
+        };
+        StarknetStorageVerificationRequest {
+            storage_query: StarknetStorageQuery {
+                query: "ERC20_balances[0x1234]".to_string(),
+                contract_address: hex(&witness.contract_address),
+                storage_address: storage_address.to_string(),
+                felts,
+            },
+            storage_proof: StarknetStorageProof {
+                contract_address: hex(&witness.contract_address),
+                contract: StarknetContractLeaf {
+                    class_hash: hex(&witness.contract.class_hash),
+                    nonce: hex(&witness.contract.nonce),
+                    storage_root: hex(&witness.contract.storage_root),
+                },
+                contract_nodes: nodes(&witness.contract_nodes),
+                storage: alloc::vec![value("0x5ff", "0x1f4"), value("0x600", "0x2")],
+                contracts_root: hex(&witness.contracts_root),
+                classes_root: hex(&witness.classes_root),
+                state_commitment: hex(&witness.state_commitment),
+                block_number: 900_000,
+                block_hash: "0xb10c".to_string(),
+            },
+        }
+    }
+
+    #[cfg(feature = "starknet")]
+    #[test]
+    fn test_starknet_witness_generation() {
+        let Witness::Data(data) = create_witness_from_starknet_request(&starknet_request("0x5ff", 1)).unwrap() else {
+            panic!("Expected Witness::Data")
+        };
+        let witness = starknet::StarknetWitness::parse(&data).unwrap();
+        assert_eq!(witness, starknet::tests::token_witness(0x5ff, 1));
+        assert_eq!(&witness.verify().unwrap().value[30..], &[0x01, 0xf4]);
+
+        // A u256 joins its high and low halves
+        let Witness::Data(data) = create_witness_from_starknet_request(&starknet_request("0x5ff", 2)).unwrap() else {
+            panic!("Expected Witness::Data")
+        };
+        let value = starknet::StarknetWitness::parse(&data).unwrap().verify().unwrap();
+        assert_eq!(value.value[15], 0x02);
+        assert_eq!(&value.value[30..], &[0x01, 0xf4]);
+    }
+
+    #[cfg(feature = "starknet")]
+    #[test]
+    fn test_security_starknet_witness_validation() {
+        // Every felt of the value must be in the proof
+        assert!(create_witness_from_starknet_request(&starknet_request("0x600", 2)).is_err());
+        assert!(create_witness_from_starknet_request(&starknet_request("0x601", 1)).is_err());
+        assert!(create_witness_from_starknet_request(&starknet_request("0x5ff", 0)).is_err());
+        assert!(create_witness_from_starknet_request(&starknet_request("0x5ff", u32::MAX)).is_err());
+
+        let mut other_contract = starknet_request("0x5ff", 1);
+        other_contract.storage_query.contract_address = "0x1".to_string();
+        assert!(create_witness_from_starknet_request(&other_contract).is_err());
+
+        // Stated values, contract leaves and roots must be the ones the tries hold
+        let mut wrong_value = starknet_request("0x5ff", 2);
+        wrong_value.storage_proof.storage[1].value = "0x3".to_string();
+        assert!(create_witness_from_starknet_request(&wrong_value).is_err());
+        let mut wrong_nonce = starknet_request("0x5ff", 1);
+        wrong_nonce.storage_proof.contract.nonce = "0x1".to_string();
+        assert!(create_witness_from_starknet_request(&wrong_nonce).is_err());
+        let mut no_nodes = starknet_request("0x5ff", 1);
+        no_nodes.storage_proof.contract_nodes.clear();
+        assert!(create_witness_from_starknet_request(&no_nodes).is_err());
+        let mut wrong_commitment = starknet_request("0x5ff", 1);
+        wrong_commitment.storage_proof.state_commitment = wrong_commitment.storage_proof.contracts_root.clone();
+        assert!(create_witness_from_starknet_request(&wrong_commitment).is_err());
+
+        // Felts are below the field's order
+        let mut out_of_field = starknet_request("0x5ff", 1);
+        out_of_field.storage_proof.state_commitment = format!("0x0800000000000011{}01", "0".repeat(46));
+        assert!(create_witness_from_starknet_request(&out_of_field).is_err());
+        assert!(parse_felt_hex(&format!("0x{}", "0".repeat(65)), "felt").is_err());
+        assert_eq!(add_to_felt(&[0xff; 32], 1), None);
+
+        // Batch items must be read at the batch's block
+        let batch = BatchStarknetStorageVerificationRequest {
+            storage_batch: alloc::vec![starknet_request("0x5ff", 1), starknet_request("0x5ff", 2)],
+            block_number: Some(900_000),
+        };
+        assert_eq!(create_witnesses_from_batch_starknet_request(&batch).unwrap().len(), 2);
+        let other = BatchStarknetStorageVerificationRequest { block_number: Some(900_001), ..batch };
+        assert!(create_witnesses_from_batch_starknet_request(&other).is_err());
+    }
+
+    fn bitcoin_request(vout: Option<u32>, min_confirmations: u32) -> BitcoinTxVerificationRequest {
+        use crate::bitcoin::{double_sha256, header_hash, verify_header_chain, HEADER_SIZE};
+        use crate::{BitcoinMerkleBranch, BitcoinTxOutput, BitcoinTxProof, BitcoinTxQuery};
+
+        let display = |hash: &[u8; 32]| {
+            let mut hash = *hash;
+            hash.reverse();
+            hex::encode(hash)
+        };
+        let pair = |left: &[u8; 32], right: &[u8; 32]| double_sha256(&[left.as_slice(), right].concat());
+
+        // A payment to two P2WPKH scripts, second of three transactions in its block
+        let mut transaction = hex::decode("0100000001").unwrap();
+        transaction.extend_from_slice(&[0x11; 32]);
+        transaction.extend_from_slice(&[0, 0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 2]);
+        for (value, key) in [(4_000_000_000u64, 0x11u8), (999_990_000, 0x22)] {
+            transaction.extend_from_slice(&value.to_le_bytes());
+            transaction.extend_from_slice(&[22, 0x00, 0x14]);
+            transaction.extend_from_slice(&[key; 20]);
+        }
+        transaction.extend_from_slice(&[0; 4]);
+        let txid = double_sha256(&transaction);
+        let (coinbase, other) = (double_sha256(b"coinbase"), double_sha256(b"other"));
+        let merkle_root = pair(&pair(&coinbase, &txid), &pair(&other, &other));
+
+        // At regtest difficulty a few nonces find a header meeting its target
+        let mine = |prev: [u8; 32], merkle_root: [u8; 32]| {
+            let mut header = [0u8; HEADER_SIZE];
+            header[0] = 4;
+            header[4..36].copy_from_slice(&prev);
+            header[36..68].copy_from_slice(&merkle_root);
+            header[72..76].copy_from_slice(&0x207fffffu32.to_le_bytes());
+            (0u32..)
+                .map(|nonce| {
+                    header[76..].copy_from_slice(&nonce.to_le_bytes());
+                    header
+                })
+                .find(|header| verify_header_chain(&[*header]).is_ok())
+                .unwrap()
+        };
+        let mut headers = alloc::vec![mine([0x44; 32], merkle_root)];
+        while headers.len() < 3 {
+            let prev = header_hash(headers.last().unwrap());
+            headers.push(mine(prev, [headers.len() as u8; 32]));
+        }
+
+        BitcoinTxVerificationRequest {
+            tx_query: BitcoinTxQuery { txid: display(&txid), vout, min_confirmations },
+            tx_proof: BitcoinTxProof {
+                txid: display(&txid),
+                transaction: hex::encode(&transaction),
+                block_hash: display(&header_hash(&headers[0])),
+                block_height: 840_000,
+                merkle: BitcoinMerkleBranch {
+                    index: 1,
+                    tx_count: 3,
+                    branch: alloc::vec![display(&coinbase), display(&pair(&other, &other))],
+                },
+                headers: headers.iter().map(hex::encode).collect(),
+                // Each regtest header carries two hashes of work
+                chain_work: "0x6".to_string(),
+                outputs: alloc::vec![
+                    BitcoinTxOutput { value: 4_000_000_000, script_pubkey: "0014".to_string() + &"11".repeat(20) },
+                    BitcoinTxOutput { value: 999_990_000, script_pubkey: "0014".to_string() + &"22".repeat(20) },
+                ],
+            },
+        }
+    }
+
+    #[test]
+    fn test_bitcoin_witness_generation() {
+        use crate::bitcoin::BitcoinWitness;
+
+        let request = bitcoin_request(Some(1), 3);
+        let Witness::Data(data) = create_witness_from_bitcoin_request(&request).unwrap() else {
+            panic!("Expected Witness::Data")
+        };
+        let witness = BitcoinWitness::parse(&data).unwrap();
+        assert_eq!((witness.headers.len(), witness.branch.len(), witness.vout), (3, 2, Some(1)));
+        let confirmation = witness.verify().unwrap();
+        assert_eq!((confirmation.block_height, confirmation.confirmations), (840_000, 3));
+        assert_eq!(confirmation.chain_work[31], 6);
+        let output = confirmation.output.unwrap();
+        assert_eq!((output.value, &output.script_pubkey[..2]), (999_990_000, &[0x00, 0x14][..]));
+
+        // Without an output, only the confirmation is witnessed
+        let Witness::Data(data) = create_witness_from_bitcoin_request(&bitcoin_request(None, 0)).unwrap() else {
+            panic!("Expected Witness::Data")
+        };
+        assert_eq!(BitcoinWitness::parse(&data).unwrap().verify().unwrap().output, None);
+    }
+
+    #[test]
+    fn test_security_bitcoin_witness_validation() {
+        assert!(create_witness_from_bitcoin_request(&bitcoin_request(Some(2), 1)).is_err());
+        assert!(create_witness_from_bitcoin_request(&bitcoin_request(None, 4)).is_err());
+
+        let mut other_tx = bitcoin_request(None, 1);
+        other_tx.tx_query.txid = "c5".repeat(32);
+        assert!(create_witness_from_bitcoin_request(&other_tx).is_err());
+
+        // Every stated value must be what the raw data hashes to
+        let mut other_raw = bitcoin_request(None, 1);
+        other_raw.tx_proof.transaction.replace_range(10..12, "12");
+        assert!(create_witness_from_bitcoin_request(&other_raw).is_err());
+
+        let mut other_block = bitcoin_request(None, 1);
+        other_block.tx_proof.block_hash = "00".repeat(31) + "ac";
+        assert!(create_witness_from_bitcoin_request(&other_block).is_err());
+
+        let mut other_work = bitcoin_request(None, 1);
+        other_work.tx_proof.chain_work = "0x7".to_string();
+        assert!(create_witness_from_bitcoin_request(&other_work).is_err());
+
+        let mut other_output = bitcoin_request(Some(0), 1);
+        other_output.tx_proof.outputs[0].value += 1;
+        assert!(create_witness_from_bitcoin_request(&other_output).is_err());
+
+        let mut other_index = bitcoin_request(None, 1);
+        other_index.tx_proof.merkle.index = 2;
+        assert!(create_witness_from_bitcoin_request(&other_index).is_err());
+
+        let mut broken_chain = bitcoin_request(None, 1);
+        broken_chain.tx_proof.headers.remove(1);
+        broken_chain.tx_proof.chain_work = "0x4".to_string();
+        assert!(create_witness_from_bitcoin_request(&broken_chain).is_err());
+
+        let mut short_header = bitcoin_request(None, 1);
+        short_header.tx_proof.headers[2].truncate(158);
+        assert!(create_witness_from_bitcoin_request(&short_header).is_err());
+
+        let mut no_headers = bitcoin_request(None, 0);
+        no_headers.tx_proof.headers.clear();
+        assert!(create_witness_from_bitcoin_request(&no_headers).is_err());
+
+        // Batch items must run to the batch's tip
+        let batch = BatchBitcoinTxVerificationRequest {
+            tx_batch: alloc::vec![bitcoin_request(None, 1), bitcoin_request(Some(0), 2)],
+            tip_height: Some(840_002),
+        };
+        assert_eq!(create_witnesses_from_batch_bitcoin_request(&batch).unwrap().len(), 2);
+        let other = BatchBitcoinTxVerificationRequest { tip_height: Some(840_003), ..batch };
+        assert!(create_witnesses_from_batch_bitcoin_request(&other).is_err());
+    }
+
+    /// A request for `query` of the wallet in [`ton::tests::wallet_witness`]
+    fn ton_request(query: &str, with_config: bool) -> TonFieldVerificationRequest {
+        use crate::{TonAccountProof, TonFieldLocation, TonFieldQuery, TonFieldValue};
+
+        let config = ton::FieldLocation { bit: 36, type_name: "(Maybe ^Config)".into(), ..ton::tests::balance() };
+        let fee_bps = if with_config { ton::tests::fee_bps() } else { config };
+        let fields = [("balance", "Coins", ton::tests::balance()), ("config.fee.bps", "uint16", fee_bps)];
+        let witness = ton::tests::wallet_witness(with_config, ton::tests::balance());
+        let proven = witness.verify().unwrap();
+        let block = |block: &ton::BlockIdExt| TonBlockId {
+            workchain: block.workchain,
+            shard: alloc::format!("{:016x}", block.shard),
+            seqno: block.seqno,
+            root_hash: hex::encode(block.root_hash),
+            file_hash: hex::encode(block.file_hash),
+        };
+        let address = alloc::format!("0:{}", hex::encode(witness.account_id));
+        TonFieldVerificationRequest {
+            field_query: TonFieldQuery {
+                query: query.to_string(),
+                address: address.clone(),
+                field_type: fields.iter().find(|f| f.0 == query).map_or("Coins", |f| f.1).to_string(),
+            },
+            account_proof: TonAccountProof {
+                address,
+                mc_block: block(&witness.mc_block),
+                shard_block: block(&witness.shard_block),
+                shard_proof: hex::encode(&witness.shard_proof),
+                proof: hex::encode(&witness.proof),
+                state: hex::encode(&witness.state),
+                account_hash: hex::encode(proven.account_hash),
+                last_trans_hash: hex::encode(proven.last_trans_hash),
+                last_trans_lt: proven.last_trans_lt,
+                fields: fields
+                    .into_iter()
+                    .map(|(query, _, location)| {
+                        let value = ton::TonWitness { location: location.clone(), ..witness.clone() }.verify().unwrap().value;
+                        TonFieldValue {
+                            query: query.to_string(),
+                            present: value.is_some(),
+                            word: value.map(hex::encode),
+                            location: TonFieldLocation {
+                                refs: location.refs,
+                                bit: location.bit,
+                                next_ref: location.next_ref,
+                                field_type: location.type_name,
+                            },
+                        }
+                    })
+                    .collect(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_ton_witness_generation() {
+        let witness = create_witness_from_ton_request(&ton_request("config.fee.bps", true)).unwrap();
+        let Witness::Data(data) = witness else { panic!("Expected Data witness") };
+        let witness = ton::TonWitness::parse(&data).unwrap();
+        assert_eq!(witness.location, ton::tests::fee_bps());
+        assert_eq!(witness.verify().unwrap().value.unwrap()[30..], [0, 250]);
+
+        // An unset field is proven absent
+        let Witness::Data(data) = create_witness_from_ton_request(&ton_request("config.fee.bps", false)).unwrap() else {
+            panic!("Expected Data witness")
+        };
+        assert_eq!(ton::TonWitness::parse(&data).unwrap().verify().unwrap().value, None);
+    }
+
+    #[test]
+    fn test_security_ton_witness_validation() {
+        // Fields the proof did not decode
+        assert!(create_witness_from_ton_request(&ton_request("nonce", true)).is_err());
+
+        let mut other_account = ton_request("balance", true);
+        other_account.field_query.address = alloc::format!("0:{}", "cd".repeat(32));
+        assert!(create_witness_from_ton_request(&other_account).is_err());
+
+        let mut bad_address = ton_request("balance", true);
+        bad_address.account_proof.address = "0:1234".to_string();
+        assert!(create_witness_from_ton_request(&bad_address).is_err());
+
+        let mut too_wide = ton_request("balance", true);
+        too_wide.account_proof.fields[0].word = None;
+        assert!(create_witness_from_ton_request(&too_wide).is_err());
+
+        // A value, type or location the account's data does not back
+        let mut wrong_value = ton_request("balance", true);
+        wrong_value.account_proof.fields[0].word = Some("00".repeat(31) + "01");
+        assert!(create_witness_from_ton_request(&wrong_value).is_err());
+        let mut wrong_type = ton_request("balance", true);
+        wrong_type.field_query.field_type = "uint64".to_string();
+        assert!(create_witness_from_ton_request(&wrong_type).is_err());
+        let mut moved = ton_request("config.fee.bps", true);
+        moved.account_proof.fields[1].location.bit = 24;
+        assert!(create_witness_from_ton_request(&moved).is_err());
+
+        let mut wrong_lt = ton_request("balance", true);
+        wrong_lt.account_proof.last_trans_lt += 1;
+        assert!(create_witness_from_ton_request(&wrong_lt).is_err());
+
+        let mut wrong_shard = ton_request("balance", true);
+        wrong_shard.account_proof.shard_block.workchain = -1;
+        assert!(create_witness_from_ton_request(&wrong_shard).is_err());
+
+        // A masterchain account's shard block is the masterchain block
+        let mut masterchain = ton_request("balance", true);
+        masterchain.account_proof.address = alloc::format!("-1:{}", "44".repeat(32));
+        masterchain.field_query.address = masterchain.account_proof.address.clone();
+        assert!(create_witness_from_ton_request(&masterchain).is_err());
+
+        // Batch items must be read at the batch's masterchain block
+        let batch = BatchTonFieldVerificationRequest {
+            field_batch: alloc::vec![ton_request("balance", true), ton_request("config.fee.bps", false)],
+            mc_seqno: Some(100),
+        };
+        assert_eq!(create_witnesses_from_batch_ton_request(&batch).unwrap().len(), 2);
+        let other = BatchTonFieldVerificationRequest { mc_seqno: Some(101), ..batch };
+        assert!(create_witnesses_from_batch_ton_request(&other).is_err());
+    }
+
     #[test]
     fn test_derive_field_index_from_layout() {
         let layout_commitment = [0x12, 0x34, 0x56, 0x78];
@@ -171,7 +171,7 @@ pub fn parse_block_data(args: &Value) -> Result<ParsedBlockData, TraverseValence
     // Extract block information from JSON arguments
     let block_number = args
         .get("block_number")
-        .and_then(|v| v.as_u64())
+        .and_then(crate::messages::decimal::from_json)
         .unwrap_or(0);
 
     let block_hash_str = args
@@ -402,21 +402,17 @@ fn verify_storage_proof_inclusion(
 /// 3. Verifies each node's hash matches the expected parent hash
 /// 4. Ensures the final leaf contains the expected value
 #[cfg(feature = "mpt-verification")]
-fn verify_mpt_proof(
+pub(crate) fn verify_mpt_proof(
     key: &[u8],
     expected_value: &[u8],
     proof_nodes: &[Vec<u8>],
     expected_root: &[u8; 32],
 ) -> Result<bool, TraverseValenceError> {
     use rlp::Rlp;
-    use tiny_keccak::{Hasher, Keccak};
+    use traverse_core::hash::{decode_compact, keccak256, nibbles};
 
     // Convert key to nibble path (each byte becomes 2 nibbles)
-    let mut key_nibbles = Vec::with_capacity(key.len() * 2);
-    for byte in key {
-        key_nibbles.push(byte >> 4);      // High nibble
-        key_nibbles.push(byte & 0x0F);    // Low nibble
-    }
+    let key_nibbles = nibbles(key);
 
     // Start verification from the root
     let mut current_hash = *expected_root;
@@ -425,15 +421,8 @@ fn verify_mpt_proof(
     // Traverse each proof node
     for node_data in proof_nodes {
         // Verify that the current node hash matches what we expect
-        if node_data.len() >= 32 {
-            let mut keccak = Keccak::v256();
-            keccak.update(node_data);
-            let mut computed_hash = [0u8; 32];
-            keccak.finalize(&mut computed_hash);
-            
-            if computed_hash != current_hash {
-                return Ok(false); // Hash mismatch
-            }
+        if node_data.len() >= 32 && keccak256(node_data) != current_hash {
+            return Ok(false); // Hash mismatch
         }
 
         // Parse the RLP-encoded node
@@ -459,7 +448,8 @@ fn verify_mpt_proof(
                     .map_err(|_| TraverseValenceError::Json("Cannot decode node second item".into()))?;
 
                 // Decode the path and check if it's a leaf or extension
-                let (decoded_path, is_leaf) = decode_path(&encoded_path);
+                let (decoded_path, is_leaf) = decode_compact(&encoded_path)
+                    .map_err(|e| TraverseValenceError::Json(e.into()))?;
                 
                 if is_leaf {
                     // This is a leaf node [encodedPath, value]
@@ -542,32 +532,6 @@ fn verify_mpt_proof(
 
 /// Decode hex-encoded path for MPT nodes
 /// Returns (decoded_nibbles, is_leaf)
-#[cfg(feature = "mpt-verification")]
-fn decode_path(encoded_path: &[u8]) -> (Vec<u8>, bool) {
-    if encoded_path.is_empty() {
-        return (Vec::new(), false);
-    }
-
-    let flag = encoded_path[0] >> 4;
-    let is_leaf = flag >= 2;
-    let is_odd = flag % 2 == 1;
-
-    let mut nibbles = Vec::new();
-    
-    if is_odd {
-        // Include the second nibble of the first byte
-        nibbles.push(encoded_path[0] & 0x0F);
-    }
-
-    // Process remaining bytes
-    for &byte in &encoded_path[1..] {
-        nibbles.push(byte >> 4);      // High nibble
-        nibbles.push(byte & 0x0F);    // Low nibble
-    }
-
-    (nibbles, is_leaf)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
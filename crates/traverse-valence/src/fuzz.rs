@@ -0,0 +1,51 @@
+//! Entry points for the fuzz targets
+//!
+//! Re-exports the parsers the cargo-fuzz targets in `fuzz/` exercise but that
+//! are otherwise private to their modules. Only compiled with the `fuzzing`
+//! feature; nothing here is part of the supported API.
+
+use crate::TraverseValenceError;
+use alloc::vec::Vec;
+
+/// Decode a hex-prefix encoded MPT node path into nibbles and its leaf flag
+pub fn decode_mpt_path(encoded_path: &[u8]) -> Result<(Vec<u8>, bool), &'static str> {
+    traverse_core::hash::decode_compact(encoded_path)
+}
+
+/// Walk `proof_nodes` for `key` from `expected_root`
+pub fn verify_mpt_proof(
+    key: &[u8],
+    expected_value: &[u8],
+    proof_nodes: &[Vec<u8>],
+    expected_root: &[u8; 32],
+) -> Result<bool, TraverseValenceError> {
+    crate::domain::verify_mpt_proof(key, expected_value, proof_nodes, expected_root)
+}
+
+/// Walk `proof_nodes` for `key` from the hash of the first node
+///
+/// Arbitrary input almost never hashes to a fixed root, so rooting the proof
+/// at its own first node lets the fuzzer reach the node decoder below it.
+pub fn verify_mpt_proof_rooted(
+    key: &[u8],
+    expected_value: &[u8],
+    proof_nodes: &[Vec<u8>],
+) -> Result<bool, TraverseValenceError> {
+    let root = proof_nodes.first().map(|first| traverse_core::hash::keccak256(first)).unwrap_or_default();
+    verify_mpt_proof(key, expected_value, proof_nodes, &root)
+}
+
+/// Solana base58 address parsing used by the controller
+pub fn parse_base58_address(address: &str) -> Result<[u8; 32], TraverseValenceError> {
+    crate::controller::parse_base58_address(address)
+}
+
+/// Solana base58 block hash parsing used by the controller
+pub fn parse_base58_hash(hash: &str) -> Result<[u8; 32], TraverseValenceError> {
+    crate::controller::parse_base58_hash(hash)
+}
+
+/// Solana base64 account data parsing used by the controller
+pub fn parse_base64_data(data: &str) -> Result<Vec<u8>, TraverseValenceError> {
+    crate::controller::parse_base64_data(data)
+}
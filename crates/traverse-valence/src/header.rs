@@ -0,0 +1,138 @@
+//! Block header verification (no_std compatible)
+//!
+//! A storage proof verifies against a state root, but a light client proves
+//! block hashes. A block hash is `keccak256(rlp(header))`, so a circuit given
+//! the raw header hashes it, compares the result with the hash it trusts, and
+//! only then takes the state root from it. `traverse_ethereum::encode_header`
+//! builds the raw header from a node's header object.
+
+use rlp::Rlp;
+use traverse_core::hash::keccak256;
+
+/// Fields present in every header since genesis
+const BASE_FIELD_COUNT: usize = 15;
+
+/// Header fields a circuit checks proofs against
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockHeader {
+    /// `keccak256` of the raw header
+    pub hash: [u8; 32],
+    pub parent_hash: [u8; 32],
+    pub state_root: [u8; 32],
+    pub transactions_root: [u8; 32],
+    pub receipts_root: [u8; 32],
+    pub number: u64,
+}
+
+fn root(header: &Rlp, index: usize) -> Result<[u8; 32], &'static str> {
+    header
+        .at(index)
+        .ok()
+        .and_then(|item| item.data().ok())
+        .and_then(|data| data.try_into().ok())
+        .ok_or("bad 32-byte header field")
+}
+
+/// Decode a raw RLP header and compute its hash
+pub fn decode_header(header: &[u8]) -> Result<BlockHeader, &'static str> {
+    let rlp = Rlp::new(header);
+    if !rlp.is_list() || rlp.item_count().map_or(true, |count| count < BASE_FIELD_COUNT) {
+        return Err("block header is not a list of at least 15 fields");
+    }
+    // Trailing bytes would hash differently from the header they follow
+    let encoded_len = rlp.payload_info().map(|info| info.header_len + info.value_len).map_err(|_| "bad block header")?;
+    if encoded_len != header.len() {
+        return Err("trailing bytes after block header");
+    }
+    let number = rlp.at(8).and_then(|item| item.data().map(<[u8]>::to_vec)).map_err(|_| "bad header number")?;
+    if number.len() > 8 || number.first() == Some(&0) {
+        return Err("bad header number");
+    }
+    Ok(BlockHeader {
+        hash: keccak256(header),
+        parent_hash: root(&rlp, 0)?,
+        state_root: root(&rlp, 3)?,
+        transactions_root: root(&rlp, 4)?,
+        receipts_root: root(&rlp, 5)?,
+        number: number.iter().fold(0u64, |acc, b| (acc << 8) | u64::from(*b)),
+    })
+}
+
+/// Verify a raw header hashes to `block_hash`, returning its fields
+pub fn verify_header(header: &[u8], block_hash: &[u8; 32]) -> Result<BlockHeader, &'static str> {
+    let decoded = decode_header(header)?;
+    if decoded.hash != *block_hash {
+        return Err("block header does not hash to the block hash");
+    }
+    Ok(decoded)
+}
+
+/// Verify a raw header hashes to `block_hash`, returning its state root
+pub fn verify_state_root(header: &[u8], block_hash: &[u8; 32]) -> Result<[u8; 32], &'static str> {
+    verify_header(header, block_hash).map(|decoded| decoded.state_root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use rlp::RlpStream;
+
+    /// A post-London header: 15 base fields and `baseFeePerGas`
+    fn header(number: u64) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(16);
+        stream.append(&[0x01u8; 32].as_slice());
+        stream.append(&[0x02u8; 32].as_slice());
+        stream.append(&[0x03u8; 20].as_slice());
+        stream.append(&[0x04u8; 32].as_slice());
+        stream.append(&[0x05u8; 32].as_slice());
+        stream.append(&[0x06u8; 32].as_slice());
+        stream.append(&[0u8; 256].as_slice());
+        stream.append(&0u8);
+        stream.append(&number);
+        stream.append(&30_000_000u64);
+        stream.append(&21_000u64);
+        stream.append(&1_700_000_000u64);
+        stream.append(&[0xeeu8; 4].as_slice());
+        stream.append(&[0x07u8; 32].as_slice());
+        stream.append(&[0u8; 8].as_slice());
+        stream.append(&7u64);
+        stream.out().to_vec()
+    }
+
+    #[test]
+    fn test_verify_header() {
+        let raw = header(19_000_000);
+        let hash = keccak256(&raw);
+
+        let verified = verify_header(&raw, &hash).unwrap();
+        assert_eq!(verified.parent_hash, [0x01; 32]);
+        assert_eq!(verified.state_root, [0x04; 32]);
+        assert_eq!(verified.transactions_root, [0x05; 32]);
+        assert_eq!(verified.receipts_root, [0x06; 32]);
+        assert_eq!(verified.number, 19_000_000);
+        assert_eq!(verify_state_root(&raw, &hash).unwrap(), [0x04; 32]);
+
+        // Another block's hash, or a tampered header, does not verify
+        assert!(verify_header(&header(19_000_001), &hash).is_err());
+        let mut tampered = raw.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(verify_state_root(&tampered, &hash).is_err());
+    }
+
+    #[test]
+    fn test_decode_header_rejects_malformed() {
+        let mut short = RlpStream::new_list(4);
+        for _ in 0..4 {
+            short.append(&[0u8; 32].as_slice());
+        }
+        assert!(decode_header(&short.out()).is_err());
+
+        let mut trailing = header(1);
+        trailing.push(0x80);
+        assert!(decode_header(&trailing).is_err());
+
+        assert_eq!(decode_header(&header(0)).unwrap().number, 0);
+    }
+}
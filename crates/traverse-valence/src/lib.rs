@@ -143,6 +143,49 @@ pub mod domain;
 // Always include messages as they're shared types
 pub mod messages;
 
+// Storage slot and Solana account witness formats, shared by the controller and circuits
+pub mod witness;
+
+// Bitcoin SPV checks, shared by the controller and circuits
+pub mod bitcoin;
+
+// Sui object digest chains, shared by the controller and circuits
+pub mod sui;
+
+// Substrate trie proofs, shared by the controller and circuits
+pub mod substrate;
+
+// TON account state proofs, shared by the controller and circuits
+pub mod ton;
+
+// Starknet storage and contracts trie proofs
+#[cfg(feature = "starknet")]
+pub mod starknet;
+
+// Block headers tying state roots to block hashes
+#[cfg(feature = "ethereum")]
+pub mod header;
+
+// OP Stack output roots chaining L2 state to L1
+#[cfg(feature = "ethereum")]
+pub mod optimism;
+
+// Receipt trie proofs for log inclusion
+#[cfg(feature = "ethereum")]
+pub mod receipts;
+
+// Solana accounts anchored to a slot's bank hash
+#[cfg(feature = "solana")]
+pub mod bank_hash;
+
+// Concurrent Merkle tree proofs for compressed Solana state
+#[cfg(feature = "solana")]
+pub mod compression;
+
+// Verkle witnesses for the post-transition state tree
+#[cfg(feature = "verkle")]
+pub mod verkle;
+
 // Lightweight ABI support
 #[cfg(any(feature = "lightweight-alloy", feature = "full-alloy"))]
 pub mod abi;
@@ -155,13 +198,28 @@ pub mod codegen;
 #[cfg(feature = "codegen")]
 pub mod minimal_codegen;
 
+// TypeScript type generation for the JSON contract
+#[cfg(feature = "codegen")]
+pub mod typescript_codegen;
+
+// Property-based testing support
+#[cfg(feature = "test-utils")]
+pub mod testing;
+
+// Entry points for the fuzz targets
+#[cfg(feature = "fuzzing")]
+pub mod fuzz;
+
 // Conditional re-exports based on enabled features
 #[cfg(feature = "circuit")]
 pub use circuit::{
     CircuitProcessor, CircuitResult, CircuitWitness,
-    ExtractedValue, FieldType, ZeroSemantics
+    ExtractedValue, FieldType, MultiSlotBundle, ZeroSemantics, verify_substrate_witness, verify_sui_witness, verify_ton_witness
 };
 
+#[cfg(all(feature = "circuit", feature = "starknet"))]
+pub use circuit::verify_starknet_witness;
+
 #[cfg(feature = "controller")]
 pub use controller::*;
 
@@ -176,6 +234,36 @@ pub use messages::{
     BatchSolanaAccountVerificationRequest, SolanaAccountValidationResult,
 };
 
+// Re-export Sui types
+pub use messages::{
+    SuiObjectQuery, SuiObjectProof, SuiObjectVerificationRequest, BatchSuiObjectVerificationRequest,
+};
+
+// Re-export Substrate types
+pub use messages::{
+    SubstrateStorageQuery, SubstrateChildTrieRoot, SubstrateStorageProof,
+    SubstrateStorageVerificationRequest, BatchSubstrateStorageVerificationRequest,
+};
+
+// Re-export Starknet types
+pub use messages::{
+    StarknetStorageQuery, StarknetTrieNode, StarknetEdgePath, StarknetContractLeaf, StarknetStorageValue,
+    StarknetStorageProof,
+    StarknetStorageVerificationRequest, BatchStarknetStorageVerificationRequest,
+};
+
+// Re-export Bitcoin types
+pub use messages::{
+    BitcoinTxQuery, BitcoinTxOutput, BitcoinMerkleBranch, BitcoinTxProof,
+    BitcoinTxVerificationRequest, BatchBitcoinTxVerificationRequest,
+};
+
+// Re-export TON types
+pub use messages::{
+    TonFieldQuery, TonFieldValue, TonFieldLocation, TonBlockId, TonAccountProof,
+    TonFieldVerificationRequest, BatchTonFieldVerificationRequest,
+};
+
 // Re-export lightweight ABI when available
 #[cfg(any(feature = "lightweight-alloy", feature = "full-alloy"))]
 pub use abi::{AlloyAbiTypes, AbiValue, AbiType};
@@ -267,6 +355,7 @@ pub struct StorageVerificationRequest {
     /// Optional contract address for additional validation
     pub contract_address: Option<String>,
     /// Optional block number for proof validation
+    #[serde(default, with = "messages::decimal::option")]
     pub block_number: Option<u64>,
 }
 
@@ -278,7 +367,30 @@ pub struct BatchStorageVerificationRequest {
     /// Common contract address (if all queries are for same contract)
     pub contract_address: Option<String>,
     /// Common block number (if all proofs are from same block)
+    #[serde(default, with = "messages::decimal::option")]
+    pub block_number: Option<u64>,
+}
+
+/// Storage verification for many slots of one contract sharing one account proof
+///
+/// `eth_getProof` with several keys proves the account once and every slot
+/// against the account's storage root. The controller sends that account
+/// proof once, and each storage trie node once however many slots pass
+/// through it, so the witnesses are a fraction of a
+/// [`BatchStorageVerificationRequest`] carrying a full proof per slot.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MultiSlotVerificationRequest {
+    /// Storage queries and proofs, every one against `storage_hash`
+    pub storage_batch: Vec<StorageVerificationRequest>,
+    /// Contract whose storage is proven
+    pub contract_address: String,
+    /// Block number the proofs were fetched at
+    #[serde(default, with = "messages::decimal::option")]
     pub block_number: Option<u64>,
+    /// Account proof nodes from the state root to the contract (hex encoded)
+    pub account_proof: Vec<String>,
+    /// Storage root the account proof commits to (hex encoded)
+    pub storage_hash: String,
 }
 
 // Constrained environment prelude
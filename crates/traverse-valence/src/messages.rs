@@ -17,6 +17,98 @@ use alloy_primitives::{Address as AlloyAddress, Bytes as AlloyBytes};
 pub type Address = String;
 pub type Bytes = Vec<u8>;
 
+/// `u64` request fields as decimal strings
+///
+/// `JSON.parse` rounds integers above 2^53, which block numbers rarely reach
+/// but lamports and a rent-exempt account's `u64::MAX` rent epoch do. The
+/// request types the TypeScript bindings cover therefore write these fields
+/// as strings. Numbers are still read, so documents written before the
+/// change parse.
+pub mod decimal {
+    use core::fmt;
+    use serde::{de, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        deserializer.deserialize_any(DecimalVisitor)
+    }
+
+    struct DecimalVisitor;
+
+    impl de::Visitor<'_> for DecimalVisitor {
+        type Value = u64;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a u64 as a decimal string or a number")
+        }
+
+        fn visit_u64<E: de::Error>(self, value: u64) -> Result<u64, E> {
+            Ok(value)
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<u64, E> {
+            parse(value).ok_or_else(|| E::invalid_value(de::Unexpected::Str(value), &self))
+        }
+    }
+
+    /// Digits only: no sign, whitespace or hex prefix
+    pub fn parse(value: &str) -> Option<u64> {
+        if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        value.parse().ok()
+    }
+
+    /// A `u64` JSON value written either way
+    pub fn from_json(value: &serde_json::Value) -> Option<u64> {
+        value.as_u64().or_else(|| value.as_str().and_then(parse))
+    }
+
+    /// `Option<u64>` fields, with `null` for `None`
+    ///
+    /// Use with `#[serde(default)]` so a missing field still reads as `None`.
+    pub mod option {
+        use core::fmt;
+        use serde::{de, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(value: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error> {
+            match value {
+                Some(value) => serializer.collect_str(value),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+            deserializer.deserialize_option(OptionVisitor)
+        }
+
+        struct OptionVisitor;
+
+        impl<'de> de::Visitor<'de> for OptionVisitor {
+            type Value = Option<u64>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("null or a u64 as a decimal string or a number")
+            }
+
+            fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+
+            fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+                super::deserialize(deserializer).map(Some)
+            }
+        }
+    }
+}
+
 /// Duration type for Valence messages
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum DurationType {
@@ -177,10 +269,13 @@ pub struct SolanaAccountProof {
     /// Account owner program (base58 encoded)
     pub owner: String,
     /// Lamports balance
+    #[serde(with = "decimal")]
     pub lamports: u64,
     /// Rent epoch
+    #[serde(with = "decimal")]
     pub rent_epoch: u64,
     /// Slot when proof was generated
+    #[serde(with = "decimal")]
     pub slot: u64,
     /// Block hash for the slot
     pub block_hash: String,
@@ -196,6 +291,7 @@ pub struct SolanaAccountVerificationRequest {
     /// Optional program address for additional validation
     pub program_address: Option<String>,
     /// Optional slot number for proof validation
+    #[serde(default, with = "decimal::option")]
     pub slot: Option<u64>,
 }
 
@@ -207,6 +303,7 @@ pub struct BatchSolanaAccountVerificationRequest {
     /// Common program address (if all queries are for same program)
     pub program_address: Option<String>,
     /// Common slot (if all proofs are from same slot)
+    #[serde(default, with = "decimal::option")]
     pub slot: Option<u64>,
 }
 
@@ -222,11 +319,426 @@ pub struct SolanaAccountValidationResult {
     /// Program ID that owns the account (base58 encoded)
     pub program_id: String,
     /// Slot when proof was generated
+    #[serde(with = "decimal")]
     pub slot: u64,
     /// Additional validation metadata
     pub metadata: Option<String>,
 }
 
+/// Sui object query format for coprocessor integration
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SuiObjectQuery {
+    /// Original query string (e.g., "pool::Pool[0x5a1e...].reserve")
+    pub query: String,
+    /// Object ID (hex encoded)
+    pub object_id: String,
+    /// Object type the layout declares (e.g., "0x5a1e...::pool::Pool<T0>")
+    pub object_type: String,
+    /// Field offset within the object's BCS contents
+    pub field_offset: Option<u32>,
+    /// Field size in bytes
+    pub field_size: Option<u32>,
+}
+
+/// Sui object proof data, as traverse-sui writes it
+///
+/// The object's BCS, the effects, and the checkpoint contents and summary
+/// are hashed back to the checkpoint digest when the witness is built and
+/// again in the circuit. The object ID, version, digest and contents are
+/// derived from them and checked against the BCS.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SuiObjectProof {
+    /// Object ID (hex encoded)
+    pub object_id: String,
+    /// Object version
+    pub version: u64,
+    /// Object digest (hex encoded)
+    pub digest: String,
+    /// BCS of the whole object (hex encoded)
+    pub object_bcs: String,
+    /// BCS contents of the object (hex encoded)
+    pub contents: String,
+    /// BCS of the effects of the transaction that wrote the object (hex encoded)
+    pub effects: String,
+    /// BCS of the checkpoint contents (hex encoded)
+    pub checkpoint_contents: String,
+    /// BCS of the checkpoint summary (hex encoded)
+    pub checkpoint_summary: String,
+    /// Checkpoint sequence number the proof is anchored to
+    pub checkpoint: u64,
+    /// Digest of the checkpoint summary (hex encoded)
+    pub checkpoint_digest: String,
+}
+
+/// Complete Sui object verification request
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SuiObjectVerificationRequest {
+    /// Object query generated by traverse-cli
+    pub object_query: SuiObjectQuery,
+    /// Object proof from traverse-sui
+    pub object_proof: SuiObjectProof,
+}
+
+/// Batch Sui object verification for multiple queries
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BatchSuiObjectVerificationRequest {
+    /// Multiple object verification requests
+    pub object_batch: Vec<SuiObjectVerificationRequest>,
+    /// Common checkpoint (if all proofs are anchored to the same one)
+    pub checkpoint: Option<u64>,
+}
+
+/// Substrate storage query format for coprocessor integration
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SubstrateStorageQuery {
+    /// Original query string (e.g., "System::Account[5Grw...]")
+    pub query: String,
+    /// Storage key (hex encoded), within the child trie if there is one
+    pub storage_key: String,
+    /// Main trie key holding the child trie's root (hex encoded), for values in a child trie
+    pub child_storage_key: Option<String>,
+    /// Field offset within the SCALE-encoded value
+    pub field_offset: Option<u32>,
+    /// Field size in bytes
+    pub field_size: Option<u32>,
+}
+
+/// Root of the child trie a Substrate value is proven in
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SubstrateChildTrieRoot {
+    /// Main trie key holding the root (hex encoded)
+    pub storage_key: String,
+    /// Child trie root (hex encoded)
+    pub root: String,
+    /// Nodes and values of the child trie (hex encoded), in any order
+    pub proof: Vec<String>,
+}
+
+/// Substrate storage proof data, as traverse-substrate writes it
+///
+/// The header is hashed to the block hash and the trie nodes are followed
+/// from its state root when the witness is built and again in the circuit.
+/// The block number, state root, child trie root and value are checked
+/// against what the header and nodes yield.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SubstrateStorageProof {
+    /// Storage key (hex encoded), within the child trie if there is one
+    pub key: String,
+    /// SCALE-encoded value (hex encoded); `None` when nothing is stored
+    pub value: Option<String>,
+    /// Nodes and values of the main trie (hex encoded), in any order
+    pub proof: Vec<String>,
+    /// Child trie the value is in, if any
+    pub child: Option<SubstrateChildTrieRoot>,
+    /// Block number the state was read at
+    pub block_number: u64,
+    /// Block hash (hex encoded)
+    pub block_hash: String,
+    /// SCALE-encoded header of the block (hex encoded)
+    pub header: String,
+    /// State root in the block's header (hex encoded)
+    pub state_root: String,
+}
+
+/// Complete Substrate storage verification request
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SubstrateStorageVerificationRequest {
+    /// Storage query generated by traverse-cli
+    pub storage_query: SubstrateStorageQuery,
+    /// Storage proof from traverse-substrate
+    pub storage_proof: SubstrateStorageProof,
+}
+
+/// Batch Substrate storage verification for multiple queries
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BatchSubstrateStorageVerificationRequest {
+    /// Multiple storage verification requests
+    pub storage_batch: Vec<SubstrateStorageVerificationRequest>,
+    /// Common block hash (if all proofs are read at the same block)
+    pub block_hash: Option<String>,
+}
+
+/// Starknet storage query format for coprocessor integration
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StarknetStorageQuery {
+    /// Original query string (e.g., "ERC20_balances[0x1234]")
+    pub query: String,
+    /// Contract address (hex encoded felt)
+    pub contract_address: String,
+    /// Address of the value's first felt (hex encoded)
+    pub storage_address: String,
+    /// Number of felts the value spans: 1, or 2 for a u256
+    pub felts: u32,
+}
+
+/// A node of a Starknet trie, as pathfinder writes it
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum StarknetTrieNode {
+    /// Inner node with two children (hex encoded felts)
+    Binary { left: String, right: String },
+    /// Node skipping a run of key bits shared by everything below it
+    Edge { child: String, path: StarknetEdgePath },
+}
+
+/// The key bits an edge node skips
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StarknetEdgePath {
+    /// Bits of the path (hex encoded felt)
+    pub value: String,
+    /// Number of bits in the path
+    pub len: u8,
+}
+
+/// A contract's leaf in the contracts trie
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StarknetContractLeaf {
+    /// Class hash (hex encoded felt)
+    pub class_hash: String,
+    /// Nonce (hex encoded felt)
+    pub nonce: String,
+    /// Root of the contract's storage trie (hex encoded felt)
+    pub storage_root: String,
+}
+
+/// A storage felt of a Starknet contract
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StarknetStorageValue {
+    /// Storage address (hex encoded felt)
+    pub address: String,
+    /// Value, zero when nothing is stored (hex encoded felt)
+    pub value: String,
+    /// Storage trie nodes leading to the address
+    pub nodes: Vec<StarknetTrieNode>,
+}
+
+/// Starknet storage proof data, as traverse-starknet writes it
+///
+/// The contracts and classes trie roots make up the state commitment, the
+/// contracts trie's nodes prove the contract's leaf and each value's nodes
+/// prove it under the leaf's storage root. The controller checks them, and
+/// the witness carries them so the circuit checks them again against a
+/// state commitment it trusts.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StarknetStorageProof {
+    /// Contract address (hex encoded felt)
+    pub contract_address: String,
+    /// The contract's leaf in the contracts trie
+    pub contract: StarknetContractLeaf,
+    /// Contracts trie nodes leading to the contract's leaf
+    pub contract_nodes: Vec<StarknetTrieNode>,
+    /// Proven storage felts
+    pub storage: Vec<StarknetStorageValue>,
+    /// Root of the contracts trie (hex encoded felt)
+    pub contracts_root: String,
+    /// Root of the classes trie, zero before it existed (hex encoded felt)
+    pub classes_root: String,
+    /// State commitment in the block's header (hex encoded felt)
+    pub state_commitment: String,
+    /// Block number the state was read at
+    pub block_number: u64,
+    /// Block hash (hex encoded felt)
+    pub block_hash: String,
+}
+
+/// Complete Starknet storage verification request
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StarknetStorageVerificationRequest {
+    /// Storage query generated by traverse-cli
+    pub storage_query: StarknetStorageQuery,
+    /// Storage proof from traverse-starknet
+    pub storage_proof: StarknetStorageProof,
+}
+
+/// Batch Starknet storage verification for multiple queries
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BatchStarknetStorageVerificationRequest {
+    /// Multiple storage verification requests
+    pub storage_batch: Vec<StarknetStorageVerificationRequest>,
+    /// Common block number (if all proofs are read at the same block)
+    pub block_number: Option<u64>,
+}
+
+/// Bitcoin transaction query format for coprocessor integration
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BitcoinTxQuery {
+    /// Txid, in the byte order RPCs show (hex encoded)
+    pub txid: String,
+    /// Output to put in the witness, if any
+    pub vout: Option<u32>,
+    /// Headers, the transaction's block included, the proof must carry
+    pub min_confirmations: u32,
+}
+
+/// An output of a Bitcoin transaction
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BitcoinTxOutput {
+    /// Value in satoshis
+    pub value: u64,
+    /// Output script (hex encoded)
+    pub script_pubkey: String,
+}
+
+/// A transaction's place in its block's Merkle tree
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BitcoinMerkleBranch {
+    /// Position of the transaction in its block
+    pub index: u32,
+    /// Number of transactions in the block
+    pub tx_count: u32,
+    /// Sibling at each level, from the txid up, in the byte order RPCs show (hex encoded)
+    pub branch: Vec<String>,
+}
+
+/// Bitcoin transaction inclusion proof data, as traverse-bitcoin writes it
+///
+/// The raw transaction, its Merkle branch and the headers go into the
+/// witness for the circuit to hash. The txid, block hash, chain work and
+/// outputs are derived from them, and the controller rejects a proof whose
+/// derived values disagree with them.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BitcoinTxProof {
+    /// Txid, in the byte order RPCs show (hex encoded)
+    pub txid: String,
+    /// The raw transaction, with witnesses if it has any (hex encoded)
+    pub transaction: String,
+    /// Hash of the transaction's block, in the byte order RPCs show (hex encoded)
+    pub block_hash: String,
+    /// Height of the transaction's block
+    pub block_height: u64,
+    /// The transaction's branch in its block's Merkle tree
+    pub merkle: BitcoinMerkleBranch,
+    /// Serialized 80-byte headers, the transaction's block first (hex encoded)
+    pub headers: Vec<String>,
+    /// Work of the headers (hex encoded)
+    pub chain_work: String,
+    /// The transaction's outputs
+    pub outputs: Vec<BitcoinTxOutput>,
+}
+
+/// Complete Bitcoin transaction verification request
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BitcoinTxVerificationRequest {
+    /// Transaction query generated by traverse-cli
+    pub tx_query: BitcoinTxQuery,
+    /// Inclusion proof from traverse-bitcoin
+    pub tx_proof: BitcoinTxProof,
+}
+
+/// Batch Bitcoin transaction verification for multiple queries
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BatchBitcoinTxVerificationRequest {
+    /// Multiple transaction verification requests
+    pub tx_batch: Vec<BitcoinTxVerificationRequest>,
+    /// Common tip height (if all proofs run to the same tip)
+    pub tip_height: Option<u64>,
+}
+
+/// TON contract data query format for coprocessor integration
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TonFieldQuery {
+    /// Field path in the contract's data (e.g., "config.fee.bps")
+    pub query: String,
+    /// Account address in raw form ("workchain:hex")
+    pub address: String,
+    /// TL-B type of the field (e.g., "Coins")
+    pub field_type: String,
+}
+
+/// A field decoded from a TON account's data
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TonFieldValue {
+    /// Field path in the contract's data
+    pub query: String,
+    /// Whether the field is set; a field behind an unset `Maybe` is not
+    pub present: bool,
+    /// The value as a 32-byte word, numbers right-aligned (hex encoded); absent when it does not fit
+    pub word: Option<String>,
+    /// Where the field starts in the account's data
+    pub location: TonFieldLocation,
+}
+
+/// Where a field starts in a TON account's data, as traverse-ton finds it
+///
+/// A reader that follows `refs` from the data cell, then reads `bit` bits
+/// and `next_ref` references, is at the field. For a field in a record
+/// behind an unset `Maybe`, it is at the `Maybe`, and `field_type` is the
+/// record's.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TonFieldLocation {
+    /// Reference indexes from the data cell to the field's cell
+    pub refs: Vec<u8>,
+    /// Bits before the field in its cell
+    pub bit: u16,
+    /// References before the field in its cell
+    #[serde(rename = "ref")]
+    pub next_ref: u8,
+    /// TL-B type to read at the location (e.g., "uint16")
+    #[serde(rename = "type")]
+    pub field_type: String,
+}
+
+/// A TON block's identity
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TonBlockId {
+    pub workchain: i32,
+    /// Shard ID (hex encoded u64)
+    pub shard: String,
+    pub seqno: u32,
+    /// Root cell hash (hex encoded)
+    pub root_hash: String,
+    /// File hash (hex encoded)
+    pub file_hash: String,
+}
+
+/// TON account state proof data, as traverse-ton writes it
+///
+/// The bags of cells are the liteserver's: `shard_proof` links the shard
+/// block to the masterchain block, `proof` leads from the shard block to
+/// the account's leaf, and `state` is the account. The controller checks
+/// them down to the account's data and reads each field at its location.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TonAccountProof {
+    /// Account address in raw form ("workchain:hex")
+    pub address: String,
+    /// Masterchain block the state was read at
+    pub mc_block: TonBlockId,
+    /// Block of the account's shard; the masterchain block for masterchain accounts
+    pub shard_block: TonBlockId,
+    /// Bag of cells linking the shard block to the masterchain block (hex encoded); empty for masterchain accounts
+    pub shard_proof: String,
+    /// Bag of cells proving the account's leaf in the shard block's state (hex encoded)
+    pub proof: String,
+    /// Bag of cells holding the account (hex encoded)
+    pub state: String,
+    /// Hash of the account cell (hex encoded)
+    pub account_hash: String,
+    /// Hash of the account's last transaction (hex encoded)
+    pub last_trans_hash: String,
+    /// Logical time of the account's last transaction
+    pub last_trans_lt: u64,
+    /// Fields decoded from the account's data
+    pub fields: Vec<TonFieldValue>,
+}
+
+/// Complete TON contract data verification request
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TonFieldVerificationRequest {
+    /// Field query generated by traverse-cli
+    pub field_query: TonFieldQuery,
+    /// Account proof from traverse-ton
+    pub account_proof: TonAccountProof,
+}
+
+/// Batch TON contract data verification for multiple queries
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BatchTonFieldVerificationRequest {
+    /// Multiple field verification requests
+    pub field_batch: Vec<TonFieldVerificationRequest>,
+    /// Common masterchain seqno (if all proofs are read at the same block)
+    pub mc_seqno: Option<u32>,
+}
+
 /// Create default retry logic for atomic execution (no retry)
 pub fn create_no_retry_logic() -> RetryLogic {
     RetryLogic {
@@ -352,4 +864,27 @@ mod tests {
         assert_eq!(retry_logic.times.amount, 0);
         assert_eq!(retry_logic.interval.value, 0);
     }
+
+    #[test]
+    fn test_decimal_fields() {
+        use serde_json::json;
+
+        let batch = json!({ "account_batch": [], "program_address": null, "slot": "18446744073709551615" });
+        let batch: BatchSolanaAccountVerificationRequest = serde_json::from_value(batch).unwrap();
+        assert_eq!(batch.slot, Some(u64::MAX));
+        assert_eq!(serde_json::to_value(&batch).unwrap()["slot"], "18446744073709551615");
+
+        // Numbers from older documents still read, and a missing field is `None`
+        let batch: BatchSolanaAccountVerificationRequest =
+            serde_json::from_value(json!({ "account_batch": [], "program_address": null, "slot": 7 })).unwrap();
+        assert_eq!(batch.slot, Some(7));
+        let batch: BatchSolanaAccountVerificationRequest =
+            serde_json::from_value(json!({ "account_batch": [], "program_address": null })).unwrap();
+        assert_eq!(batch.slot, None);
+
+        for malformed in ["", "0x10", "-1", " 1", "18446744073709551616"] {
+            let batch = json!({ "account_batch": [], "program_address": null, "slot": malformed });
+            assert!(serde_json::from_value::<BatchSolanaAccountVerificationRequest>(batch).is_err(), "{:?}", malformed);
+        }
+    }
 }
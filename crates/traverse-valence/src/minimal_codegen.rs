@@ -4,10 +4,124 @@
 //! to the provided schema without any unnecessary abstractions or dependencies.
 
 use alloc::{format, string::String, vec::Vec};
-use traverse_core::{LayoutInfo, StorageEntry, ZeroSemantics};
+use traverse_core::{ConstrainedKeyResolver, ConstrainedLayoutInfo, LayoutInfo, StorageEntry};
+
+/// Fields and code paths required by a set of queries
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryUsage {
+    /// Indices of the storage entries referenced by the queries, in layout order
+    pub fields: Vec<usize>,
+    /// Whether any referenced field is a mapping (requires keccak hashing)
+    pub needs_mapping: bool,
+    /// Whether any referenced field decodes as a bool
+    pub needs_bool: bool,
+    /// Whether any referenced field decodes as a uint8
+    pub needs_uint8: bool,
+    /// Whether any referenced field decodes as a uint256
+    pub needs_uint256: bool,
+    /// Whether any referenced field decodes as an address
+    pub needs_address: bool,
+}
+
+impl QueryUsage {
+    fn include(&mut self, index: usize, entry: &StorageEntry) {
+        if self.fields.contains(&index) {
+            return;
+        }
+        self.fields.push(index);
+        if entry.type_name.contains("mapping") {
+            self.needs_mapping = true;
+        }
+        match value_kind(&entry.type_name) {
+            ValueKind::Bool => self.needs_bool = true,
+            ValueKind::Uint8 => self.needs_uint8 = true,
+            ValueKind::Uint256 => self.needs_uint256 = true,
+            ValueKind::Address => self.needs_address = true,
+        }
+    }
+
+    /// Usage covering every field in the layout
+    pub fn all(layout: &LayoutInfo) -> Self {
+        let mut usage = Self::default();
+        for (index, entry) in layout.storage.iter().enumerate() {
+            usage.include(index, entry);
+        }
+        usage
+    }
+}
+
+/// Decoded value representation used by the generated verifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValueKind {
+    Bool,
+    Uint8,
+    Uint256,
+    Address,
+}
+
+fn value_kind(type_name: &str) -> ValueKind {
+    match type_name {
+        "t_bool" => ValueKind::Bool,
+        "t_uint8" => ValueKind::Uint8,
+        "t_address" => ValueKind::Address,
+        _ => ValueKind::Uint256, // Default to uint256
+    }
+}
+
+/// Determine which fields and code paths a query list requires
+///
+/// Queries are matched on their base label (`balances[0x..]` and `config.owner`
+/// resolve to `balances` and `config`). Queries that do not match any storage
+/// entry are ignored.
+pub fn analyze_queries(layout: &LayoutInfo, queries: &[&str]) -> QueryUsage {
+    let mut usage = QueryUsage::default();
+    for query in queries {
+        let label = query.split(['[', '.']).next().unwrap_or(query).trim();
+        if let Some(index) = layout.storage.iter().position(|e| e.label == label) {
+            usage.include(index, &layout.storage[index]);
+        }
+    }
+    usage.fields.sort_unstable();
+    usage
+}
+
+/// Usage for a query list that must match the layout
+///
+/// Unlike [`analyze_queries`], an empty list or a query that matches no
+/// storage entry is an error, so generated code never silently drops a field.
+fn queried_usage(layout: &LayoutInfo, queries: &[&str]) -> Result<QueryUsage, crate::TraverseValenceError> {
+    if queries.is_empty() {
+        return Err(crate::TraverseValenceError::CodegenError("No queries to generate code for".into()));
+    }
+    for query in queries {
+        if analyze_queries(layout, &[query]).fields.is_empty() {
+            return Err(crate::TraverseValenceError::CodegenError(format!(
+                "Query '{}' does not match any field of {}",
+                query, layout.contract_name
+            )));
+        }
+    }
+    Ok(analyze_queries(layout, queries))
+}
 
 /// Generate minimal query code for a specific storage layout
 pub fn generate_minimal_query_code(layout: &LayoutInfo) -> String {
+    generate_query_code(layout, &QueryUsage::all(layout))
+}
+
+/// Generate minimal query code containing only what the given queries need
+///
+/// Fields that are not queried are dropped, and the keccak path is omitted
+/// entirely when no mapping is queried. Field indices match the full layout.
+/// Fails when `queries` is empty or a query matches no field.
+pub fn generate_minimal_query_code_for_queries(
+    layout: &LayoutInfo,
+    queries: &[&str],
+) -> Result<String, crate::TraverseValenceError> {
+    Ok(generate_query_code(layout, &queried_usage(layout, queries)?))
+}
+
+fn generate_query_code(layout: &LayoutInfo, usage: &QueryUsage) -> String {
     let mut code = String::new();
     
     // Header
@@ -26,8 +140,9 @@ pub fn compute_storage_key(field_index: u16, key_data: Option<&[u8]>) -> [u8; 32
     match field_index {{
 "#, layout.contract_name));
 
-    // Generate direct computation for each field
-    for (index, entry) in layout.storage.iter().enumerate() {
+    // Generate direct computation for each queried field
+    for &index in &usage.fields {
+        let entry = &layout.storage[index];
         code.push_str(&format!(
             "        {} => {{\n",
             index
@@ -66,7 +181,10 @@ pub fn compute_storage_key(field_index: u16, key_data: Option<&[u8]>) -> [u8; 32
     }
 }
 
-/// Minimal keccak256 implementation
+"#);
+
+    if usage.needs_mapping {
+        code.push_str(r#"/// Minimal keccak256 implementation
 fn keccak256(data: &[u8]) -> [u8; 32] {
     use tiny_keccak::{Hasher, Keccak};
     let mut hasher = Keccak::v256();
@@ -77,13 +195,14 @@ fn keccak256(data: &[u8]) -> [u8; 32] {
 }
 
 "#);
+    }
 
     // Generate field constants
     code.push_str("/// Field indices for direct access\n");
-    for (index, entry) in layout.storage.iter().enumerate() {
+    for &index in &usage.fields {
         code.push_str(&format!(
             "pub const FIELD_{}: u16 = {};\n",
-            entry.label.to_uppercase(),
+            layout.storage[index].label.to_uppercase(),
             index
         ));
     }
@@ -99,6 +218,22 @@ fn keccak256(data: &[u8]) -> [u8; 32] {
 
 /// Generate minimal verifier code for a specific storage layout
 pub fn generate_minimal_verifier_code(layout: &LayoutInfo) -> String {
+    generate_verifier_code(layout, &QueryUsage::all(layout))
+}
+
+/// Generate minimal verifier code containing only what the given queries need
+///
+/// Only the verification branches for queried fields and the `ExtractedValue`
+/// variants they decode into are emitted. Fails when `queries` is empty or a
+/// query matches no field.
+pub fn generate_minimal_verifier_code_for_queries(
+    layout: &LayoutInfo,
+    queries: &[&str],
+) -> Result<String, crate::TraverseValenceError> {
+    Ok(generate_verifier_code(layout, &queried_usage(layout, queries)?))
+}
+
+fn generate_verifier_code(layout: &LayoutInfo, usage: &QueryUsage) -> String {
     let mut code = String::new();
     
     // Header
@@ -158,8 +293,9 @@ pub fn verify_witness(
     match field_index {{
 "#, layout.contract_name));
 
-    // Generate extraction for each field
-    for (index, entry) in layout.storage.iter().enumerate() {
+    // Generate extraction for each queried field
+    for &index in &usage.fields {
+        let entry = &layout.storage[index];
         code.push_str(&format!("        {} => {{\n", index));
         
         // Field-specific validation
@@ -179,11 +315,11 @@ pub fn verify_witness(
         ));
         
         // Extract value based on type
-        let extraction = match entry.type_name.as_str() {
-            "t_bool" => "ExtractedValue::Bool(value[31] != 0)",
-            "t_uint8" => "ExtractedValue::Uint8(value[31])",
-            "t_uint256" => "ExtractedValue::Uint256(*value)",
-            "t_address" => {
+        let extraction = match value_kind(&entry.type_name) {
+            ValueKind::Bool => "ExtractedValue::Bool(value[31] != 0)",
+            ValueKind::Uint8 => "ExtractedValue::Uint8(value[31])",
+            ValueKind::Uint256 => "ExtractedValue::Uint256(*value)",
+            ValueKind::Address => {
                 code.push_str(r#"            let mut addr = [0u8; 20];
             addr.copy_from_slice(&value[12..32]);
             if addr == [0u8; 20] {
@@ -192,7 +328,6 @@ pub fn verify_witness(
             "#);
                 "ExtractedValue::Address(addr)"
             },
-            _ => "ExtractedValue::Uint256(*value)", // Default to uint256
         };
         
         code.push_str(&format!("            Ok({})\n", extraction));
@@ -206,11 +341,24 @@ pub fn verify_witness(
 /// Minimal extracted value types
 #[derive(Debug, Clone, Copy)]
 pub enum ExtractedValue {
-    Bool(bool),
-    Uint8(u8),
-    Uint256([u8; 32]),
-    Address([u8; 20]),
-}
+"#);
+    if usage.needs_bool {
+        code.push_str("    Bool(bool),\n");
+    }
+    if usage.needs_uint8 {
+        code.push_str("    Uint8(u8),\n");
+    }
+    if usage.needs_uint256 {
+        code.push_str("    Uint256([u8; 32]),\n");
+    }
+    if usage.needs_address {
+        code.push_str("    Address([u8; 20]),\n");
+    }
+    if usage.fields.is_empty() {
+        // A layout without fields still needs an inhabited value type
+        code.push_str("    Raw([u8; 32]),\n");
+    }
+    code.push_str(r#"}
 
 /// Expected layout commitment
 const EXPECTED_COMMITMENT: &[u8] = &[
@@ -226,10 +374,10 @@ const EXPECTED_COMMITMENT: &[u8] = &[
 
     // Add field constants
     code.push_str("\n/// Field indices\n");
-    for (index, entry) in layout.storage.iter().enumerate() {
+    for &index in &usage.fields {
         code.push_str(&format!(
             "pub const FIELD_{}: u16 = {};\n",
-            entry.label.to_uppercase(),
+            layout.storage[index].label.to_uppercase(),
             index
         ));
     }
@@ -364,9 +512,150 @@ pub const FIELDS: Fields = Fields {
     code
 }
 
+/// Format a 32-byte array as a Rust array literal
+fn format_byte_array(bytes: &[u8; 32]) -> String {
+    let literals: Vec<String> = bytes.iter().map(|b| format!("0x{:02x}", b)).collect();
+    format!("[{}]", literals.join(", "))
+}
+
+/// Generate const-embedded layout data for no_std controllers
+///
+/// The layout is converted to a `ConstrainedLayoutInfo` at generation time and
+/// emitted as `const` arrays, so constrained builds never parse layout JSON at
+/// runtime. Storage keys for fields that do not depend on a runtime key
+/// (everything except mappings and dynamic arrays) are pre-resolved.
+pub fn generate_const_layout_code(layout: &LayoutInfo) -> Result<String, crate::TraverseValenceError> {
+    let constrained = ConstrainedLayoutInfo::try_from(layout).map_err(|e| {
+        crate::TraverseValenceError::CodegenError(format!("Failed to constrain layout: {}", e))
+    })?;
+    let mut resolver = ConstrainedKeyResolver::new();
+    let count = constrained.storage.len();
+
+    let mut code = String::new();
+    code.push_str(&format!(
+        "/// Layout commitment for {}\npub const LAYOUT_COMMITMENT: [u8; 32] = {};\n\n",
+        layout.contract_name,
+        format_byte_array(&constrained.commitment)
+    ));
+    code.push_str(&format!(
+        "/// Number of storage entries in the embedded layout\npub const ENTRY_COUNT: u16 = {};\n\n",
+        constrained.entry_count
+    ));
+
+    code.push_str(&format!("/// Field labels in layout order\npub const FIELD_LABELS: [&str; {}] = [\n", count));
+    for entry in &layout.storage {
+        code.push_str(&format!("    {:?},\n", entry.label));
+    }
+    code.push_str("];\n\n");
+
+    code.push_str(&format!(
+        "/// Constrained storage entries in layout order\npub const STORAGE: [ConstrainedStorageEntry; {}] = [\n",
+        count
+    ));
+    for entry in &constrained.storage {
+        code.push_str(&format!(
+            "    ConstrainedStorageEntry {{\n        slot: {},\n        offset: {},\n        size: {},\n        field_type: ConstrainedFieldType::{:?},\n        zero_semantics: ZeroSemantics::{:?},\n    }},\n",
+            format_byte_array(&entry.slot),
+            entry.offset,
+            entry.size,
+            entry.field_type,
+            entry.zero_semantics
+        ));
+    }
+    code.push_str("];\n\n");
+
+    code.push_str(&format!(
+        "/// Pre-resolved storage keys (`None` where a runtime key is required)\npub const RESOLVED_KEYS: [Option<[u8; 32]>; {}] = [\n",
+        count
+    ));
+    for index in 0..count {
+        match resolver.resolve_constrained(&constrained, index as u16) {
+            Ok(key) => code.push_str(&format!("    Some({}),\n", format_byte_array(&key))),
+            Err(_) => code.push_str("    None,\n"),
+        }
+    }
+    code.push_str("];\n\n");
+
+    code.push_str(r#"/// Find the field index for a query by its base label
+pub fn field_index(query: &str) -> Option<u16> {
+    let label = query.split(['[', '.']).next().unwrap_or(query);
+    FIELD_LABELS.iter().position(|l| *l == label).map(|i| i as u16)
+}
+
+/// Build the constrained layout from the embedded constants
+pub fn layout() -> ConstrainedLayoutInfo {
+    ConstrainedLayoutInfo {
+        storage: STORAGE.to_vec(),
+        commitment: LAYOUT_COMMITMENT,
+        entry_count: ENTRY_COUNT,
+    }
+}
+"#);
+
+    Ok(code)
+}
+
+/// Generate a no_std controller with the layout embedded as const data
+///
+/// Requests are checked against the embedded commitment and, where available,
+/// the pre-resolved storage key before the witness is created.
+pub fn generate_const_controller_code(layout: &LayoutInfo) -> Result<String, crate::TraverseValenceError> {
+    let mut code = String::new();
+
+    code.push_str(&format!(r#"//! Generated const-embedded controller for {}
+//!
+//! The storage layout is compiled into this crate as constant data, so no
+//! layout JSON is parsed at runtime.
+
+#![no_std]
+
+extern crate alloc;
+
+use traverse_valence::constrained_prelude::{{
+    ConstrainedFieldType, ConstrainedLayoutInfo, ConstrainedStorageEntry, ZeroSemantics,
+}};
+use traverse_valence::{{create_witness_from_request, StorageVerificationRequest, TraverseValenceError}};
+use valence_coprocessor::Witness;
+
+"#, layout.contract_name));
+
+    code.push_str(&generate_const_layout_code(layout)?);
+
+    code.push_str(r#"
+/// Create a witness after checking the request against the embedded layout
+pub fn create_witness(request: &StorageVerificationRequest) -> Result<Witness, TraverseValenceError> {
+    let query = &request.storage_query;
+
+    let mut commitment = [0u8; 32];
+    hex::decode_to_slice(query.layout_commitment.trim_start_matches("0x"), &mut commitment)
+        .map_err(|_| TraverseValenceError::InvalidStorageKey("Invalid layout commitment hex".into()))?;
+    if commitment != LAYOUT_COMMITMENT {
+        return Err(TraverseValenceError::LayoutMismatch("Layout commitment mismatch".into()));
+    }
+
+    let index = field_index(&query.query)
+        .ok_or_else(|| TraverseValenceError::LayoutMismatch("Query not in embedded layout".into()))?;
+
+    if let Some(expected) = RESOLVED_KEYS[index as usize] {
+        let mut key = [0u8; 32];
+        hex::decode_to_slice(query.storage_key.trim_start_matches("0x"), &mut key)
+            .map_err(|_| TraverseValenceError::InvalidStorageKey("Invalid storage key hex".into()))?;
+        if key != expected {
+            return Err(TraverseValenceError::InvalidStorageKey("Storage key does not match embedded layout".into()));
+        }
+    }
+
+    create_witness_from_request(request)
+}
+"#);
+
+    Ok(code)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use traverse_core::ZeroSemantics;
     
     #[test]
     fn test_minimal_query_generation() {
@@ -382,10 +671,109 @@ mod tests {
                 },
             ],
             types: alloc::vec![],
+            code_variables: Vec::new(),
         };
         
         let code = generate_minimal_query_code(&layout);
         assert!(code.contains("compute_storage_key"));
         assert!(code.contains("FIELD_BALANCE"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_const_layout_generation() {
+        let layout = LayoutInfo {
+            contract_name: "TestContract".into(),
+            storage: alloc::vec![
+                StorageEntry {
+                    label: "owner".into(),
+                    slot: "1".into(),
+                    offset: 0,
+                    type_name: "t_address".into(),
+                    zero_semantics: ZeroSemantics::NeverWritten,
+                },
+                StorageEntry {
+                    label: "balances".into(),
+                    slot: "0x2".into(),
+                    offset: 0,
+                    type_name: "t_mapping(t_address,t_uint256)".into(),
+                    zero_semantics: ZeroSemantics::ValidZero,
+                },
+            ],
+            types: alloc::vec![],
+            code_variables: Vec::new(),
+        };
+
+        let code = generate_const_layout_code(&layout).unwrap();
+        assert!(code.contains("pub const ENTRY_COUNT: u16 = 2;"));
+        assert!(code.contains("field_type: ConstrainedFieldType::Address"));
+        assert!(code.contains("zero_semantics: ZeroSemantics::NeverWritten"));
+        assert!(code.contains("field_type: ConstrainedFieldType::Mapping"));
+        // Simple field is pre-resolved, mapping needs a runtime key
+        let keys = code.split("RESOLVED_KEYS").nth(1).unwrap();
+        assert!(keys.contains("Some(["));
+        assert!(keys.contains("None,"));
+
+        let controller = generate_const_controller_code(&layout).unwrap();
+        assert!(controller.contains("#![no_std]"));
+        assert!(controller.contains("pub fn create_witness"));
+        assert!(!controller.contains("serde_json"));
+    }
+
+    #[test]
+    fn test_query_driven_elimination() {
+        let layout = LayoutInfo {
+            contract_name: "Token".into(),
+            storage: alloc::vec![
+                StorageEntry {
+                    label: "owner".into(),
+                    slot: "0".into(),
+                    offset: 0,
+                    type_name: "t_address".into(),
+                    zero_semantics: ZeroSemantics::NeverWritten,
+                },
+                StorageEntry {
+                    label: "balances".into(),
+                    slot: "1".into(),
+                    offset: 0,
+                    type_name: "t_mapping(t_address,t_uint256)".into(),
+                    zero_semantics: ZeroSemantics::ValidZero,
+                },
+                StorageEntry {
+                    label: "paused".into(),
+                    slot: "2".into(),
+                    offset: 0,
+                    type_name: "t_bool".into(),
+                    zero_semantics: ZeroSemantics::ValidZero,
+                },
+            ],
+            types: alloc::vec![],
+            code_variables: Vec::new(),
+        };
+
+        let usage = analyze_queries(&layout, &["paused", "owner", "unknown"]);
+        assert_eq!(usage.fields, alloc::vec![0, 2]);
+        assert!(!usage.needs_mapping);
+        assert!(usage.needs_bool && usage.needs_address);
+        assert!(!usage.needs_uint256 && !usage.needs_uint8);
+
+        let full = generate_minimal_query_code(&layout);
+        let pruned = generate_minimal_query_code_for_queries(&layout, &["paused", "owner"]).unwrap();
+        assert!(full.contains("fn keccak256"));
+        assert!(!pruned.contains("fn keccak256"));
+        assert!(!pruned.contains("FIELD_BALANCES"));
+        assert!(pruned.contains("pub const FIELD_PAUSED: u16 = 2;"));
+        assert!(pruned.len() < full.len());
+
+        let verifier = generate_minimal_verifier_code_for_queries(&layout, &["balances[0xabc]"]).unwrap();
+        assert!(verifier.contains("Uint256([u8; 32])"));
+        assert!(!verifier.contains("Address([u8; 20])"));
+        assert!(!verifier.contains("Bool(bool)"));
+        assert!(verifier.len() < generate_minimal_verifier_code(&layout).len());
+
+        // Nothing to generate is an error, not an empty `ExtractedValue`
+        assert!(generate_minimal_verifier_code_for_queries(&layout, &[]).is_err());
+        assert!(generate_minimal_query_code_for_queries(&layout, &["owner", "unknown"]).is_err());
+        let empty = LayoutInfo { storage: alloc::vec![], ..layout };
+        assert!(generate_minimal_verifier_code(&empty).contains("Raw([u8; 32])"));
+    }
+}
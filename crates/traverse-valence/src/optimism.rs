@@ -0,0 +1,92 @@
+//! OP Stack output root verification (no_std compatible)
+//!
+//! OP Stack chains post output roots to L1 instead of state roots. A circuit
+//! that reads an output root from L1 checks the [`OutputRootProof`]
+//! `traverse-ethereum` emits next to an OP Stack storage proof, and takes the
+//! L2 state root the storage proof verifies against from it.
+
+/// Preimage of a version 0 output root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputRootProof {
+    pub version: [u8; 32],
+    pub state_root: [u8; 32],
+    /// Storage root of the `L2ToL1MessagePasser` predeploy
+    pub message_passer_storage_root: [u8; 32],
+    pub latest_block_hash: [u8; 32],
+}
+
+impl OutputRootProof {
+    /// Encode as the four words in hashing order
+    pub fn to_bytes(&self) -> [u8; 128] {
+        let mut out = [0u8; 128];
+        out[..32].copy_from_slice(&self.version);
+        out[32..64].copy_from_slice(&self.state_root);
+        out[64..96].copy_from_slice(&self.message_passer_storage_root);
+        out[96..].copy_from_slice(&self.latest_block_hash);
+        out
+    }
+
+    /// Decode the [`OutputRootProof::to_bytes`] encoding
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        if bytes.len() != 128 {
+            return Err("output root proof must be 128 bytes");
+        }
+        let word = |index: usize| bytes[index * 32..(index + 1) * 32].try_into().unwrap();
+        Ok(Self {
+            version: word(0),
+            state_root: word(1),
+            message_passer_storage_root: word(2),
+            latest_block_hash: word(3),
+        })
+    }
+
+    /// `keccak256(version ‖ stateRoot ‖ messagePasserStorageRoot ‖ blockHash)`
+    pub fn output_root(&self) -> [u8; 32] {
+        traverse_core::hash::keccak256(&self.to_bytes())
+    }
+}
+
+/// Verify `proof` against an output root posted on L1, returning the L2 state root
+pub fn verify_output_root(output_root: &[u8; 32], proof: &OutputRootProof) -> Result<[u8; 32], &'static str> {
+    if proof.version != [0u8; 32] {
+        return Err("unsupported output root version");
+    }
+    if proof.output_root() != *output_root {
+        return Err("output root proof does not hash to the output root");
+    }
+    Ok(proof.state_root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proof() -> OutputRootProof {
+        OutputRootProof {
+            version: [0u8; 32],
+            state_root: [0x11; 32],
+            message_passer_storage_root: [0x22; 32],
+            latest_block_hash: [0x33; 32],
+        }
+    }
+
+    #[test]
+    fn test_output_root_proof_bytes_round_trip() {
+        assert_eq!(OutputRootProof::from_bytes(&proof().to_bytes()).unwrap(), proof());
+        assert!(OutputRootProof::from_bytes(&[0u8; 127]).is_err());
+    }
+
+    #[test]
+    fn test_verify_output_root() {
+        let output_root = proof().output_root();
+        assert_eq!(verify_output_root(&output_root, &proof()), Ok([0x11; 32]));
+
+        let mut other_block = proof();
+        other_block.latest_block_hash = [0x34; 32];
+        assert!(verify_output_root(&output_root, &other_block).is_err());
+
+        let mut version_one = proof();
+        version_one.version[31] = 1;
+        assert!(verify_output_root(&version_one.output_root(), &version_one).is_err());
+    }
+}
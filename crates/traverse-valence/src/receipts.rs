@@ -0,0 +1,227 @@
+//! Receipt trie verification for log inclusion (no_std compatible)
+//!
+//! Checks the receipt proofs `traverse-ethereum` builds: a Merkle-Patricia
+//! proof of the receipt at `rlp(tx_index)` in a block's receipts trie, then
+//! the requested log inside that receipt. The receipts root must come from a
+//! header the circuit already trusts.
+//!
+//! Unlike the state and storage tries, the receipts trie is keyed by the
+//! unhashed `rlp(tx_index)`, and its short nodes can be embedded in their
+//! parent rather than referenced by hash.
+
+use alloc::vec::Vec;
+use rlp::Rlp;
+use traverse_core::hash::{decode_compact, keccak256, nibbles};
+
+/// `keccak256("Transfer(address,address,uint256)")`, topic 0 of ERC-20 and ERC-721 transfers
+pub const TRANSFER_TOPIC: [u8; 32] = [
+    0xdd, 0xf2, 0x52, 0xad, 0x1b, 0xe2, 0xc8, 0x9b, 0x69, 0xc2, 0xb0, 0x68, 0xfc, 0x37, 0x8d, 0xaa,
+    0x95, 0x2b, 0xa7, 0xf1, 0x63, 0xc4, 0xa1, 0x16, 0x28, 0xf5, 0x5a, 0x4d, 0xf5, 0x23, 0xb3, 0xef,
+];
+
+/// A log proven to be in a receipt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProvenLog {
+    pub address: [u8; 20],
+    pub topics: Vec<[u8; 32]>,
+    pub data: Vec<u8>,
+    /// Whether the transaction that emitted the log succeeded
+    pub success: bool,
+}
+
+impl ProvenLog {
+    /// Whether the log was emitted by `address` with `topic0` as its event signature
+    pub fn is_event(&self, address: &[u8; 20], topic0: &[u8; 32]) -> bool {
+        self.address == *address && self.topics.first() == Some(topic0)
+    }
+}
+
+/// Trie key of transaction `index`: `rlp(index)` as nibbles
+fn index_path(index: u64) -> Vec<u8> {
+    let key = match index {
+        0 => alloc::vec![0x80],
+        1..=0x7f => alloc::vec![index as u8],
+        _ => {
+            let bytes = index.to_be_bytes();
+            let start = bytes.iter().position(|b| *b != 0).unwrap_or(7);
+            let mut key = alloc::vec![0x80 + (8 - start) as u8];
+            key.extend_from_slice(&bytes[start..]);
+            key
+        }
+    };
+    nibbles(&key)
+}
+
+/// Walk `proof` from `root` along `path` to the leaf value
+fn trie_value(root: &[u8; 32], path: &[u8], proof: &[Vec<u8>]) -> Result<Vec<u8>, &'static str> {
+    let mut nodes = proof.iter();
+    let mut node = nodes.next().ok_or("empty proof")?.clone();
+    if keccak256(&node) != *root {
+        return Err("proof root does not match the receipts root");
+    }
+    let mut position = 0;
+
+    loop {
+        let rlp = Rlp::new(&node);
+        let child = match rlp.item_count().map_err(|_| "trie node is not an RLP list")? {
+            17 => {
+                if position == path.len() {
+                    return Err("receipt key ends at a branch");
+                }
+                let child = rlp.at(path[position] as usize).map_err(|_| "bad branch child")?;
+                position += 1;
+                child
+            }
+            2 => {
+                let encoded = rlp.at(0).and_then(|p| p.data().map(<[u8]>::to_vec)).map_err(|_| "bad node path")?;
+                let (segment, is_leaf) = decode_compact(&encoded)?;
+                if !path[position..].starts_with(&segment) {
+                    return Err("receipt is not in the trie");
+                }
+                position += segment.len();
+                if is_leaf {
+                    if position != path.len() {
+                        return Err("receipt is not in the trie");
+                    }
+                    return rlp.at(1).and_then(|v| v.data().map(<[u8]>::to_vec)).map_err(|_| "bad leaf value");
+                }
+                rlp.at(1).map_err(|_| "bad extension child")?
+            }
+            _ => return Err("trie node is neither a branch, extension nor leaf"),
+        };
+
+        // Nodes shorter than 32 bytes are embedded in their parent
+        node = if child.is_list() {
+            child.as_raw().to_vec()
+        } else {
+            let hash = child.data().map_err(|_| "bad child reference")?;
+            if hash.is_empty() {
+                return Err("receipt is not in the trie");
+            }
+            let next = nodes.next().ok_or("proof ends before the receipt")?;
+            if hash.len() != 32 || keccak256(next) != hash {
+                return Err("node hash does not match its parent reference");
+            }
+            next.clone()
+        };
+    }
+}
+
+fn fixed<const N: usize>(item: Rlp) -> Result<[u8; N], &'static str> {
+    item.data().ok().and_then(|data| data.try_into().ok()).ok_or("bad fixed-size receipt field")
+}
+
+/// Verify that log `log_index` of transaction `tx_index` is in the receipts trie
+///
+/// `proof` is the list of trie nodes from the receipts root to the receipt,
+/// root first. `log_index` counts the transaction's own logs, not the block's.
+pub fn verify_log_inclusion(
+    receipts_root: &[u8; 32],
+    tx_index: u64,
+    log_index: usize,
+    proof: &[Vec<u8>],
+) -> Result<ProvenLog, &'static str> {
+    let receipt = trie_value(receipts_root, &index_path(tx_index), proof)?;
+
+    // Typed receipts are prefixed by their transaction type
+    let body = match receipt.first() {
+        Some(first) if *first < 0x80 => &receipt[1..],
+        Some(_) => &receipt[..],
+        None => return Err("empty receipt"),
+    };
+    let rlp = Rlp::new(body);
+    if rlp.item_count().ok() != Some(4) {
+        return Err("receipt is not [status, cumulativeGasUsed, logsBloom, logs]");
+    }
+    let status = rlp.at(0).and_then(|s| s.data().map(<[u8]>::to_vec)).map_err(|_| "bad receipt status")?;
+    // Pre-Byzantium receipts carry a state root and no status
+    let success = status.len() == 32 || status == [1];
+
+    let log = rlp.at(3).and_then(|logs| logs.at(log_index)).map_err(|_| "receipt has no such log")?;
+    if log.item_count().ok() != Some(3) {
+        return Err("log is not [address, topics, data]");
+    }
+    let topics = log.at(1).map_err(|_| "bad log topics")?;
+    Ok(ProvenLog {
+        address: fixed(log.at(0).map_err(|_| "bad log address")?)?,
+        topics: topics.iter().map(fixed).collect::<Result<_, _>>()?,
+        data: log.at(2).and_then(|d| d.data().map(<[u8]>::to_vec)).map_err(|_| "bad log data")?,
+        success,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rlp::RlpStream;
+
+    fn receipt(amount: u8) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(4);
+        stream.append(&1u8);
+        stream.append(&21_000u64);
+        stream.append(&[0u8; 256].to_vec());
+        stream.begin_list(1);
+        stream.begin_list(3);
+        stream.append(&[0xa0u8; 20].to_vec());
+        stream.begin_list(2);
+        stream.append(&TRANSFER_TOPIC.to_vec());
+        stream.append(&[amount; 32].to_vec());
+        stream.append(&alloc::vec![amount; 32]);
+        let mut encoded = alloc::vec![2u8];
+        encoded.extend_from_slice(&stream.out());
+        encoded
+    }
+
+    fn leaf(path: u8, value: &[u8]) -> Vec<u8> {
+        // One remaining nibble: odd leaf prefix
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&alloc::vec![0x30 | path]);
+        stream.append(&value.to_vec());
+        stream.out().to_vec()
+    }
+
+    /// Receipts of transactions 0 (key 0x80) and 1 (key 0x01) under one branch
+    fn two_receipt_trie() -> ([u8; 32], Vec<u8>, Vec<u8>, Vec<u8>) {
+        let leaf_0 = leaf(0, &receipt(7));
+        let leaf_1 = leaf(1, &receipt(9));
+        let mut branch = RlpStream::new_list(17);
+        for nibble in 0..16 {
+            match nibble {
+                0 => branch.append(&keccak256(&leaf_1).to_vec()),
+                8 => branch.append(&keccak256(&leaf_0).to_vec()),
+                _ => branch.append_empty_data(),
+            };
+        }
+        branch.append_empty_data();
+        let branch = branch.out().to_vec();
+        (keccak256(&branch), branch, leaf_0, leaf_1)
+    }
+
+    #[test]
+    fn test_verify_transfer_log() {
+        let (root, branch, leaf_0, leaf_1) = two_receipt_trie();
+
+        let log = verify_log_inclusion(&root, 1, 0, &[branch.clone(), leaf_1.clone()]).unwrap();
+        assert!(log.is_event(&[0xa0; 20], &TRANSFER_TOPIC));
+        assert!(log.success);
+        assert_eq!(log.data, alloc::vec![9u8; 32]);
+
+        let log = verify_log_inclusion(&root, 0, 0, &[branch.clone(), leaf_0]).unwrap();
+        assert_eq!(log.topics[1], [7u8; 32]);
+
+        assert!(verify_log_inclusion(&root, 1, 1, &[branch.clone(), leaf_1.clone()]).is_err());
+        assert!(verify_log_inclusion(&root, 0, 0, &[branch.clone(), leaf_1.clone()]).is_err());
+        assert!(verify_log_inclusion(&root, 1, 0, core::slice::from_ref(&branch)).is_err());
+        let mut wrong_root = root;
+        wrong_root[0] ^= 1;
+        assert!(verify_log_inclusion(&wrong_root, 1, 0, &[branch, leaf_1]).is_err());
+    }
+
+    #[test]
+    fn test_index_path() {
+        assert_eq!(index_path(0), alloc::vec![8, 0]);
+        assert_eq!(index_path(0x7f), alloc::vec![7, 15]);
+        assert_eq!(index_path(0x80), alloc::vec![8, 1, 8, 0]);
+        assert_eq!(index_path(0x1234), alloc::vec![8, 2, 1, 2, 3, 4]);
+    }
+}
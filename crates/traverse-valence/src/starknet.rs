@@ -0,0 +1,452 @@
+//! Starknet storage proof checks (no_std compatible)
+//!
+//! Redoes the checks `traverse_starknet` makes when it verifies a storage
+//! proof, on the witness the controller writes. The contracts and classes
+//! trie roots make up the state commitment; the contracts trie's nodes
+//! prove the contract's leaf, which holds its storage root; the storage
+//! trie's nodes prove each felt of the value. A circuit compares the state
+//! commitment with one it trusts for the block, such as the one the block's
+//! state update posts to L1.
+//!
+//! The tries are binary, of height 251, keyed by the big-endian bits of a
+//! felt and hashed with Pedersen. A binary node hashes as `H(left, right)`;
+//! an edge node, which skips a run of bits shared by everything below it,
+//! as `H(child, path) + length`. Zero values are not stored, so a key the
+//! trie does not hold reads as zero.
+//!
+//! ## Witness Format
+//! ```text
+//! [8 bytes block_number] +
+//! [32 bytes state_commitment] +
+//! [32 bytes contracts_root] + [32 bytes classes_root] +
+//! [32 bytes contract_address] +
+//! [32 bytes class_hash] + [32 bytes nonce] + [32 bytes storage_root] +
+//! [4 bytes node_count] + node_count * node +
+//! [32 bytes storage_address] +
+//! [1 byte felt_count, 1 or 2 for a u256] +
+//! felt_count * ([4 bytes node_count] + node_count * node)
+//! ```
+//!
+//! A node is `[1 byte 0] + [32 bytes left] + [32 bytes right]` for a binary
+//! node, or `[1 byte 1] + [32 bytes child] + [32 bytes path] + [1 byte length]`
+//! for an edge. Felts are big-endian and below the field's order; other
+//! integers are little-endian.
+
+use alloc::vec::Vec;
+use starknet_types_core::felt::Felt;
+use starknet_types_core::hash::{Pedersen, Poseidon, StarkHash};
+
+/// Length of a witness with no trie nodes and a one-felt value
+pub const WITNESS_FIXED_LEN: usize = 8 + 32 * 7 + 4 + 32 + 1 + 4;
+
+/// Number of key bits a trie branches on
+pub const TRIE_HEIGHT: usize = 251;
+
+/// `'STARKNET_STATE_V0'` as a short string
+pub const STATE_COMMITMENT_VERSION: Felt = Felt::from_hex_unchecked("0x535441524b4e45545f53544154455f5630");
+
+/// An inner node of a storage or contracts trie
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrieNode {
+    Binary { left: Felt, right: Felt },
+    /// Leads to `child` along the `length` bits of `path`
+    Edge { child: Felt, path: Felt, length: u8 },
+}
+
+impl TrieNode {
+    /// Pedersen hash of the node, which its parent refers to it by
+    pub fn hash(&self) -> Felt {
+        match self {
+            Self::Binary { left, right } => Pedersen::hash(left, right),
+            Self::Edge { child, path, length } => Pedersen::hash(child, path) + Felt::from(*length),
+        }
+    }
+}
+
+/// A contract's leaf in the contracts trie
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractLeaf {
+    pub class_hash: Felt,
+    pub nonce: Felt,
+    pub storage_root: Felt,
+}
+
+impl ContractLeaf {
+    /// Value of the leaf in the contracts trie
+    pub fn hash(&self) -> Felt {
+        let hash = Pedersen::hash(&Pedersen::hash(&self.class_hash, &self.storage_root), &self.nonce);
+        Pedersen::hash(&hash, &Felt::ZERO)
+    }
+}
+
+/// State commitment of a block with these trie roots
+pub fn state_commitment(contracts_root: &Felt, classes_root: &Felt) -> Felt {
+    if *classes_root == Felt::ZERO {
+        *contracts_root
+    } else {
+        Poseidon::hash_array(&[STATE_COMMITMENT_VERSION, *contracts_root, *classes_root])
+    }
+}
+
+/// Bit `index` of a trie key, counting from the most significant of its 251
+fn key_bit(bits: &[bool; 256], index: usize) -> bool {
+    bits[TRIE_HEIGHT - 1 - index]
+}
+
+/// Check that `nodes` lead from `root` to `key`, returning the value there
+///
+/// Nodes may come in any order, and nodes off the key's path are ignored.
+/// `None` means the trie holds no value under the key, which the nodes
+/// show by an edge leaving the key's path, or by an empty trie.
+pub fn verify_trie_proof(root: &Felt, key: &Felt, nodes: &[TrieNode]) -> Result<Option<Felt>, &'static str> {
+    if key.bits() > TRIE_HEIGHT {
+        return Err("Trie key is longer than 251 bits");
+    }
+    if *root == Felt::ZERO {
+        return Ok(None);
+    }
+    let hashes: Vec<_> = nodes.iter().map(TrieNode::hash).collect();
+    let key_bits = key.to_bits_le();
+    let mut expected = *root;
+    let mut depth = 0;
+
+    while depth < TRIE_HEIGHT {
+        let node = hashes
+            .iter()
+            .position(|hash| *hash == expected)
+            .map(|index| &nodes[index])
+            .ok_or("Trie proof is missing a node")?;
+        match node {
+            TrieNode::Binary { left, right } => {
+                expected = if key_bit(&key_bits, depth) { *right } else { *left };
+                depth += 1;
+            }
+            TrieNode::Edge { child, path, length } => {
+                let length = *length as usize;
+                if length == 0 || depth + length > TRIE_HEIGHT || path.bits() > length {
+                    return Err("Malformed edge in trie proof");
+                }
+                let path_bits = path.to_bits_le();
+                if (0..length).any(|i| path_bits[length - 1 - i] != key_bit(&key_bits, depth + i)) {
+                    return Ok(None);
+                }
+                expected = *child;
+                depth += length;
+            }
+        }
+    }
+    Ok(Some(expected))
+}
+
+/// A cursor over witness data
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], &'static str> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or("Data ends early")?;
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, &'static str> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, &'static str> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().expect("4 bytes")))
+    }
+
+    /// A felt, which must be written below the field's order
+    fn felt(&mut self) -> Result<Felt, &'static str> {
+        let bytes: &[u8; 32] = self.take(32)?.try_into().expect("32 bytes");
+        let felt = Felt::from_bytes_be(bytes);
+        if felt.to_bytes_be() != *bytes {
+            return Err("Felt is not below the field's order");
+        }
+        Ok(felt)
+    }
+
+    fn nodes(&mut self) -> Result<Vec<TrieNode>, &'static str> {
+        let count = self.u32()?;
+        (0..count)
+            .map(|_| match self.u8()? {
+                0 => Ok(TrieNode::Binary { left: self.felt()?, right: self.felt()? }),
+                1 => Ok(TrieNode::Edge { child: self.felt()?, path: self.felt()?, length: self.u8()? }),
+                _ => Err("Unknown trie node tag"),
+            })
+            .collect()
+    }
+}
+
+/// A Starknet storage value witness
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StarknetWitness {
+    pub block_number: u64,
+    pub state_commitment: Felt,
+    pub contracts_root: Felt,
+    pub classes_root: Felt,
+    pub contract_address: Felt,
+    pub contract: ContractLeaf,
+    /// Contracts trie nodes leading to the contract's leaf
+    pub contract_nodes: Vec<TrieNode>,
+    /// Address of the value's first felt
+    pub storage_address: Felt,
+    /// Storage trie nodes for each felt of the value, from `storage_address` up
+    pub value_nodes: Vec<Vec<TrieNode>>,
+}
+
+/// What a valid witness proves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StarknetFieldValue {
+    pub block_number: u64,
+    pub state_commitment: Felt,
+    pub contract_address: Felt,
+    pub storage_address: Felt,
+    /// Each felt of the value, zero when nothing is stored
+    pub felts: Vec<Felt>,
+    /// The value as one big-endian word; a u256's halves are joined
+    pub value: [u8; 32],
+}
+
+impl StarknetWitness {
+    /// Serialize in the witness format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let write_nodes = |bytes: &mut Vec<u8>, nodes: &[TrieNode]| {
+            bytes.extend_from_slice(&(nodes.len() as u32).to_le_bytes());
+            for node in nodes {
+                match node {
+                    TrieNode::Binary { left, right } => {
+                        bytes.push(0);
+                        bytes.extend_from_slice(&left.to_bytes_be());
+                        bytes.extend_from_slice(&right.to_bytes_be());
+                    }
+                    TrieNode::Edge { child, path, length } => {
+                        bytes.push(1);
+                        bytes.extend_from_slice(&child.to_bytes_be());
+                        bytes.extend_from_slice(&path.to_bytes_be());
+                        bytes.push(*length);
+                    }
+                }
+            }
+        };
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.block_number.to_le_bytes());
+        for felt in [
+            &self.state_commitment,
+            &self.contracts_root,
+            &self.classes_root,
+            &self.contract_address,
+            &self.contract.class_hash,
+            &self.contract.nonce,
+            &self.contract.storage_root,
+        ] {
+            bytes.extend_from_slice(&felt.to_bytes_be());
+        }
+        write_nodes(&mut bytes, &self.contract_nodes);
+        bytes.extend_from_slice(&self.storage_address.to_bytes_be());
+        bytes.push(self.value_nodes.len() as u8);
+        for nodes in &self.value_nodes {
+            write_nodes(&mut bytes, nodes);
+        }
+        bytes
+    }
+
+    /// Parse a witness; every byte must be accounted for
+    pub fn parse(bytes: &[u8]) -> Result<Self, &'static str> {
+        let mut reader = Reader { bytes, position: 0 };
+        let block_number = u64::from_le_bytes(reader.take(8)?.try_into().expect("8 bytes"));
+        let state_commitment = reader.felt()?;
+        let contracts_root = reader.felt()?;
+        let classes_root = reader.felt()?;
+        let contract_address = reader.felt()?;
+        let contract = ContractLeaf { class_hash: reader.felt()?, nonce: reader.felt()?, storage_root: reader.felt()? };
+        let contract_nodes = reader.nodes()?;
+        let storage_address = reader.felt()?;
+        let felt_count = reader.u8()?;
+        if !(1..=2).contains(&felt_count) {
+            return Err("A value spans 1 or 2 felts");
+        }
+        let value_nodes = (0..felt_count).map(|_| reader.nodes()).collect::<Result<_, _>>()?;
+        if reader.position != bytes.len() {
+            return Err("Data continues after the witness");
+        }
+        Ok(Self {
+            block_number,
+            state_commitment,
+            contracts_root,
+            classes_root,
+            contract_address,
+            contract,
+            contract_nodes,
+            storage_address,
+            value_nodes,
+        })
+    }
+
+    /// Check the tries from the state commitment down to each felt, and join the felts into the value
+    pub fn verify(&self) -> Result<StarknetFieldValue, &'static str> {
+        if !(1..=2).contains(&self.value_nodes.len()) {
+            return Err("A value spans 1 or 2 felts");
+        }
+        if state_commitment(&self.contracts_root, &self.classes_root) != self.state_commitment {
+            return Err("Trie roots do not make up the state commitment");
+        }
+        match verify_trie_proof(&self.contracts_root, &self.contract_address, &self.contract_nodes)? {
+            Some(leaf) if leaf == self.contract.hash() => {}
+            Some(_) => return Err("Contract leaf does not match the contracts trie"),
+            None => return Err("No contract is deployed at the address"),
+        }
+
+        let mut felts = Vec::with_capacity(self.value_nodes.len());
+        for (index, nodes) in self.value_nodes.iter().enumerate() {
+            let address = self.storage_address + Felt::from(index as u64);
+            let felt = verify_trie_proof(&self.contract.storage_root, &address, nodes)?;
+            felts.push(felt.unwrap_or(Felt::ZERO));
+        }
+
+        let value = match felts.as_slice() {
+            [value] => value.to_bytes_be(),
+            [low, high] => {
+                let (low, high) = (low.to_bytes_be(), high.to_bytes_be());
+                if low[..16].iter().chain(&high[..16]).any(|b| *b != 0) {
+                    return Err("u256 halves must fit in 128 bits");
+                }
+                let mut value = [0u8; 32];
+                value[..16].copy_from_slice(&high[16..]);
+                value[16..].copy_from_slice(&low[16..]);
+                value
+            }
+            _ => unreachable!("the felt count is checked above"),
+        };
+        Ok(StarknetFieldValue {
+            block_number: self.block_number,
+            state_commitment: self.state_commitment,
+            contract_address: self.contract_address,
+            storage_address: self.storage_address,
+            felts,
+            value,
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Build a trie over `entries`, returning its root and every inner node
+    pub(crate) fn small_trie(entries: &[(Felt, Felt)]) -> (Felt, Vec<TrieNode>) {
+        let mut nodes = Vec::new();
+        let mut sorted: Vec<_> = entries.iter().map(|(key, value)| (key.to_bits_le(), *value)).collect();
+        sorted.sort_by_key(|(bits, _)| (0..TRIE_HEIGHT).map(|i| key_bit(bits, i)).collect::<Vec<_>>());
+        let root = build(&sorted, 0, &mut nodes);
+        (root, nodes)
+    }
+
+    fn build(entries: &[([bool; 256], Felt)], depth: usize, nodes: &mut Vec<TrieNode>) -> Felt {
+        if depth == TRIE_HEIGHT {
+            return entries[0].1;
+        }
+        let (first, last) = (&entries[0].0, &entries[entries.len() - 1].0);
+        let shared = (depth..TRIE_HEIGHT).take_while(|i| key_bit(first, *i) == key_bit(last, *i)).count();
+        let node = if shared > 0 {
+            let path = (depth..depth + shared).fold(Felt::ZERO, |path, i| path.double() + Felt::from(key_bit(first, i) as u64));
+            let child = build(entries, depth + shared, nodes);
+            TrieNode::Edge { child, path, length: shared as u8 }
+        } else {
+            let split = entries.iter().position(|(bits, _)| key_bit(bits, depth)).unwrap();
+            let left = build(&entries[..split], depth + 1, nodes);
+            let right = build(&entries[split..], depth + 1, nodes);
+            TrieNode::Binary { left, right }
+        };
+        nodes.push(node.clone());
+        node.hash()
+    }
+
+    /// A token contract holding 0x1f4 at 0x5ff, 0x2 at 0x600, 0x7 at 0x9000 and 2^128 at 0x9001, next to another contract
+    ///
+    /// Returns the witness for the `felts` felts from `storage_address`.
+    pub(crate) fn token_witness(storage_address: u64, felts: u64) -> StarknetWitness {
+        let (storage_root, storage_nodes) = small_trie(&[
+            (Felt::from(0x5ffu64), Felt::from(0x1f4u64)),
+            (Felt::from(0x600u64), Felt::from(0x2u64)),
+            (Felt::from(0x9000u64), Felt::from(0x7u64)),
+            (Felt::from(0x9001u64), Felt::from_hex_unchecked("0x100000000000000000000000000000000")),
+        ]);
+        let contract = ContractLeaf { class_hash: Felt::from(0xc1a55u64), nonce: Felt::ZERO, storage_root };
+        let contract_address = Felt::from_hex_unchecked("0x49d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7");
+        let (contracts_root, contract_nodes) =
+            small_trie(&[(contract_address, contract.hash()), (Felt::from(0x1234u64), Felt::from(0x99u64))]);
+        let classes_root = Felt::from(0xc1a55e5u64);
+
+        StarknetWitness {
+            block_number: 900_000,
+            state_commitment: state_commitment(&contracts_root, &classes_root),
+            contracts_root,
+            classes_root,
+            contract_address,
+            contract,
+            contract_nodes,
+            storage_address: Felt::from(storage_address),
+            value_nodes: (0..felts).map(|_| storage_nodes.clone()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_witness_verification() {
+        let witness = token_witness(0x5ff, 1);
+        assert_eq!(StarknetWitness::parse(&witness.to_bytes()).unwrap(), witness);
+        assert_eq!(witness.verify().unwrap().felts, [Felt::from(0x1f4u64)]);
+
+        // A u256 joins its low and high halves
+        let value = token_witness(0x5ff, 2).verify().unwrap();
+        assert_eq!(value.value[15], 0x02);
+        assert_eq!(&value.value[30..], &[0x01, 0xf4]);
+
+        // An unwritten address reads as zero, proven by an edge leaving its path
+        assert_eq!(token_witness(0x601, 1).verify().unwrap().value, [0; 32]);
+    }
+
+    #[test]
+    fn test_broken_links_are_rejected() {
+        // Roots that do not make up the state commitment
+        let mut witness = token_witness(0x5ff, 1);
+        witness.classes_root = Felt::ZERO;
+        assert!(witness.verify().is_err());
+
+        // A contract leaf that is not the one in the trie
+        let mut witness = token_witness(0x5ff, 1);
+        witness.contract.nonce = Felt::ONE;
+        assert!(witness.verify().is_err());
+
+        // A contract the trie does not hold
+        let mut witness = token_witness(0x5ff, 1);
+        witness.contract_address = Felt::from(0x1235u64);
+        assert!(witness.verify().is_err());
+
+        // Storage nodes missing the value's leaf
+        let mut witness = token_witness(0x5ff, 1);
+        witness.value_nodes[0].remove(0);
+        assert!(witness.verify().is_err());
+
+        // u256 halves above 128 bits are not a u256
+        assert!(token_witness(0x9000, 2).verify().is_err());
+
+        // Felts at or above the field's order, and trailing bytes
+        let mut bytes = token_witness(0x5ff, 1).to_bytes();
+        let mut out_of_field = bytes.clone();
+        out_of_field[8..40].copy_from_slice(&[0xff; 32]);
+        assert!(StarknetWitness::parse(&out_of_field).is_err());
+        bytes.push(0);
+        assert!(StarknetWitness::parse(&bytes).is_err());
+        assert!(StarknetWitness::parse(&[0; WITNESS_FIXED_LEN]).is_err());
+    }
+}
@@ -0,0 +1,437 @@
+//! Substrate storage proof checks (no_std compatible)
+//!
+//! Redoes the checks `traverse_substrate` makes when it builds a read
+//! proof, on the witness the controller writes. The header hashes to the
+//! block hash with Blake2-256 and holds the state root; the main trie's
+//! nodes prove the value under the storage key, or, for a value in a child
+//! trie, the child trie's root, whose own nodes then prove the value. A
+//! circuit compares the block hash with one it trusts.
+//!
+//! The trie is base-16 without extension nodes, its nodes hashed with
+//! Blake2-256. A child shorter than a hash is inlined in its parent, and
+//! values of 33 bytes or more may be stored apart and referenced by hash,
+//! so a proof is an unordered set of nodes and values.
+//!
+//! ## Witness Format
+//! ```text
+//! [32 bytes block_hash] +
+//! [4 bytes header_length] + [header_length bytes SCALE header] +
+//! [4 bytes key_length] + [key_length bytes storage_key] +
+//! [4 bytes child_key_length] + [child_key_length bytes child_storage_key, empty for the main trie] +
+//! [4 bytes item_count] + item_count * ([4 bytes length] + [length bytes main trie item]) +
+//! [4 bytes child_item_count] + child_item_count * ([4 bytes length] + [length bytes child trie item]) +
+//! [4 bytes field_offset] +
+//! [4 bytes field_size, 0 to take the whole value]
+//! ```
+//!
+//! Integers are little-endian.
+
+use alloc::vec::Vec;
+use blake2::{digest::consts::U32, Blake2b, Digest};
+
+/// Length of a witness with an empty header, key and proof
+pub const WITNESS_FIXED_LEN: usize = 32 + 4 * 7;
+
+/// Blake2-256, which hashes Substrate headers and trie nodes
+pub fn blake2_256(data: &[u8]) -> [u8; 32] {
+    Blake2b::<U32>::digest(data).into()
+}
+
+/// Decode a SCALE compact integer, returning it with the bytes it took
+pub fn decode_compact(bytes: &[u8]) -> Option<(u128, usize)> {
+    let first = *bytes.first()?;
+    let le = |bytes: &[u8]| bytes.iter().rev().fold(0u128, |value, b| value << 8 | *b as u128);
+    match first & 0b11 {
+        0 => Some(((first >> 2) as u128, 1)),
+        1 => Some((le(bytes.get(..2)?) >> 2, 2)),
+        2 => Some((le(bytes.get(..4)?) >> 2, 4)),
+        _ => {
+            let len = (first >> 2) as usize + 4;
+            if len > 16 {
+                return None;
+            }
+            Some((le(bytes.get(1..1 + len)?), 1 + len))
+        }
+    }
+}
+
+/// Number and state root of a SCALE-encoded header: parent hash, compact number, state root, ...
+pub fn decode_header(header: &[u8]) -> Option<(u64, [u8; 32])> {
+    let (number, read) = decode_compact(header.get(32..)?)?;
+    let state_root = header.get(32 + read..64 + read)?.try_into().ok()?;
+    Some((u64::try_from(number).ok()?, state_root))
+}
+
+/// A cursor over trie nodes and witness data
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], &'static str> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or("Data ends early")?;
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32, &'static str> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().expect("4 bytes")))
+    }
+
+    fn prefixed(&mut self) -> Result<&'a [u8], &'static str> {
+        let length = self.u32()? as usize;
+        self.take(length)
+    }
+
+    fn items(&mut self) -> Result<Vec<Vec<u8>>, &'static str> {
+        let count = self.u32()?;
+        (0..count).map(|_| self.prefixed().map(<[u8]>::to_vec)).collect()
+    }
+
+    fn compact_prefixed(&mut self) -> Result<&'a [u8], &'static str> {
+        let (length, read) = decode_compact(&self.bytes[self.position..]).ok_or("Invalid length in trie node")?;
+        self.position += read;
+        self.take(usize::try_from(length).map_err(|_| "Length in trie node is too large")?)
+    }
+}
+
+/// A value, stored in its node or referenced by hash
+enum NodeValue<'a> {
+    Inline(&'a [u8]),
+    Hashed(&'a [u8]),
+}
+
+/// What a path needs from a trie node; a child is its hash, or its encoding when shorter
+struct Node<'a> {
+    partial: Vec<u8>,
+    value: Option<NodeValue<'a>>,
+    children: [Option<&'a [u8]>; 16],
+}
+
+/// Decode a trie node, `None` for the empty node
+fn decode_node<'a>(bytes: &'a [u8]) -> Result<Option<Node<'a>>, &'static str> {
+    let mut reader = Reader { bytes, position: 0 };
+    let first = reader.take(1)?[0];
+    if first == 0 {
+        return if bytes.len() == 1 { Ok(None) } else { Err("Data continues after a trie node") };
+    }
+    // (is a branch, has a value, value is hashed, prefix bits)
+    let (branch, has_value, hashed, prefix_bits) = match first >> 6 {
+        0b01 => (false, true, false, 2),
+        0b10 => (true, false, false, 2),
+        0b11 => (true, true, false, 2),
+        _ if first >> 5 == 0b001 => (false, true, true, 3),
+        _ if first >> 4 == 0b0001 => (true, true, true, 4),
+        _ => return Err("Unknown trie node header"),
+    };
+
+    // The nibble count spills from the header into following bytes
+    let max = 255u8 >> prefix_bits;
+    let mut count = (first & max) as usize;
+    if count == max as usize {
+        count -= 1;
+        loop {
+            let next = reader.take(1)?[0] as usize;
+            count += next;
+            if next < 255 {
+                count += 1;
+                break;
+            }
+            if count > u16::MAX as usize {
+                return Err("Trie node partial key is too long");
+            }
+        }
+    }
+    let mut partial: Vec<u8> = reader.take(count.div_ceil(2))?.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect();
+    if count % 2 == 1 {
+        if partial[0] != 0 {
+            return Err("Trie node partial key has bad padding");
+        }
+        partial.remove(0);
+    }
+
+    let value = |reader: &mut Reader<'a>| -> Result<NodeValue<'a>, &'static str> {
+        Ok(match hashed {
+            true => NodeValue::Hashed(reader.take(32)?),
+            false => NodeValue::Inline(reader.compact_prefixed()?),
+        })
+    };
+    let mut children = [None; 16];
+    let value = if branch {
+        let bitmap = u16::from_le_bytes(reader.take(2)?.try_into().expect("2 bytes"));
+        let value = if has_value { Some(value(&mut reader)?) } else { None };
+        for (i, child) in children.iter_mut().enumerate() {
+            if bitmap & (1 << i) != 0 {
+                *child = Some(reader.compact_prefixed()?);
+            }
+        }
+        value
+    } else {
+        Some(value(&mut reader)?)
+    };
+    if reader.position != bytes.len() {
+        return Err("Data continues after a trie node");
+    }
+    Ok(Some(Node { partial, value, children }))
+}
+
+/// Follow `key` from `root` through the nodes and values of a read proof
+///
+/// Returns the value stored under the key, or `None` when the proof shows
+/// there is none. Items the path does not reach are ignored.
+pub fn verify_trie_proof(root: &[u8; 32], key: &[u8], proof: &[Vec<u8>]) -> Result<Option<Vec<u8>>, &'static str> {
+    if *root == blake2_256(&[0]) {
+        return Ok(None);
+    }
+    let hashed: Vec<_> = proof.iter().map(|item| (blake2_256(item), item.as_slice())).collect();
+    let lookup = |hash: &[u8]| {
+        hashed.iter().find(|(h, _)| h == hash).map(|(_, item)| *item).ok_or("Proof is missing a trie item")
+    };
+    let resolve = |value: NodeValue| match value {
+        NodeValue::Inline(value) => Ok(value.to_vec()),
+        NodeValue::Hashed(hash) => lookup(hash).map(<[u8]>::to_vec),
+    };
+    let nibbles: Vec<u8> = key.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect();
+    let (mut node, mut position) = (lookup(root)?, 0);
+
+    loop {
+        let Some(Node { partial, value, children }) = decode_node(node)? else {
+            return Ok(None);
+        };
+        let Some(rest) = nibbles[position..].strip_prefix(partial.as_slice()) else {
+            return Ok(None);
+        };
+        let Some(next) = rest.first() else {
+            return value.map(resolve).transpose();
+        };
+        node = match children[*next as usize] {
+            Some(hash) if hash.len() == 32 => lookup(hash)?,
+            Some(inline) => inline,
+            None => return Ok(None),
+        };
+        position += partial.len() + 1;
+    }
+}
+
+/// A Substrate storage field witness
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubstrateWitness {
+    pub block_hash: [u8; 32],
+    /// SCALE-encoded header of the block
+    pub header: Vec<u8>,
+    /// Storage key, within the child trie if there is one
+    pub storage_key: Vec<u8>,
+    /// Main trie key holding the child trie's root, with its `:child_storage:default:` prefix
+    pub child_storage_key: Option<Vec<u8>>,
+    /// Nodes and values of the main trie
+    pub proof: Vec<Vec<u8>>,
+    /// Nodes and values of the child trie
+    pub child_proof: Vec<Vec<u8>>,
+    /// Field offset within the SCALE-encoded value
+    pub field_offset: u32,
+    /// Field size in bytes; 0 takes the whole value, which must then fit in 32 bytes
+    pub field_size: u32,
+}
+
+/// What a valid witness proves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubstrateStorageValue {
+    pub block_hash: [u8; 32],
+    pub block_number: u64,
+    pub state_root: [u8; 32],
+    /// Zero for values in the main trie
+    pub child_root: [u8; 32],
+    /// Stored value; `None` when nothing is stored under the key
+    pub value: Option<Vec<u8>>,
+    /// The field's bytes, left-aligned; zero when nothing is stored
+    pub field: [u8; 32],
+}
+
+impl SubstrateWitness {
+    /// Serialize in the witness format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.block_hash);
+        let child_key = self.child_storage_key.as_deref().unwrap_or_default();
+        for part in [&self.header[..], &self.storage_key, child_key] {
+            bytes.extend_from_slice(&(part.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(part);
+        }
+        for items in [&self.proof, &self.child_proof] {
+            bytes.extend_from_slice(&(items.len() as u32).to_le_bytes());
+            for item in items {
+                bytes.extend_from_slice(&(item.len() as u32).to_le_bytes());
+                bytes.extend_from_slice(item);
+            }
+        }
+        bytes.extend_from_slice(&self.field_offset.to_le_bytes());
+        bytes.extend_from_slice(&self.field_size.to_le_bytes());
+        bytes
+    }
+
+    /// Parse a witness; every byte must be accounted for
+    pub fn parse(bytes: &[u8]) -> Result<Self, &'static str> {
+        let mut reader = Reader { bytes, position: 0 };
+        let block_hash = reader.take(32)?.try_into().expect("32 bytes");
+        let header = reader.prefixed()?.to_vec();
+        let storage_key = reader.prefixed()?.to_vec();
+        let child_storage_key = Some(reader.prefixed()?.to_vec()).filter(|key| !key.is_empty());
+        let witness = Self {
+            block_hash,
+            header,
+            storage_key,
+            child_storage_key,
+            proof: reader.items()?,
+            child_proof: reader.items()?,
+            field_offset: reader.u32()?,
+            field_size: reader.u32()?,
+        };
+        if reader.position != bytes.len() {
+            return Err("Data continues after the witness");
+        }
+        Ok(witness)
+    }
+
+    /// Check the header against the block hash and the value against its state root, and read the field
+    pub fn verify(&self) -> Result<SubstrateStorageValue, &'static str> {
+        if blake2_256(&self.header) != self.block_hash {
+            return Err("Header does not hash to the block hash");
+        }
+        let (block_number, state_root) = decode_header(&self.header).ok_or("Header cannot be decoded")?;
+
+        let (child_root, value) = match &self.child_storage_key {
+            Some(child_key) => {
+                let root = verify_trie_proof(&state_root, child_key, &self.proof)?
+                    .ok_or("State holds no child trie under the key")?;
+                let root: [u8; 32] = root.try_into().map_err(|_| "Child trie root is not 32 bytes")?;
+                (root, verify_trie_proof(&root, &self.storage_key, &self.child_proof)?)
+            }
+            None if self.child_proof.is_empty() => {
+                ([0; 32], verify_trie_proof(&state_root, &self.storage_key, &self.proof)?)
+            }
+            None => return Err("Child trie items without a child trie"),
+        };
+
+        let mut field = [0u8; 32];
+        if let Some(value) = &value {
+            let bytes = match self.field_size {
+                0 if value.len() <= 32 => &value[..],
+                0 => return Err("A value over 32 bytes needs a field"),
+                size => (self.field_offset as usize)
+                    .checked_add(size as usize)
+                    .and_then(|end| value.get(self.field_offset as usize..end))
+                    .ok_or("Field lies outside the value")?,
+            };
+            let length = bytes.len().min(32);
+            field[..length].copy_from_slice(&bytes[..length]);
+        }
+        Ok(SubstrateStorageValue { block_hash: self.block_hash, block_number, state_root, child_root, value, field })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A leaf or branch header for `nibbles` nibbles, short enough to fit the prefix
+    fn node_header(prefix: u8, nibbles: usize) -> u8 {
+        prefix | nibbles as u8
+    }
+
+    fn compact(length: usize) -> u8 {
+        (length as u8) << 2
+    }
+
+    /// A trie of Timestamp::Now and a child trie root, with a child trie holding two entries
+    ///
+    /// Returns the witness for `Timestamp::Now` and the child trie's key, items and root.
+    pub(crate) fn timestamp_witness() -> (SubstrateWitness, Vec<u8>, Vec<Vec<u8>>, [u8; 32]) {
+        // Child trie: a branch at the root, a hashed value under 0x10 and an inline node under 0x20
+        let contribution = [0x42u8; 36];
+        let leaf = |first: u8, value: &[u8]| -> Vec<u8> {
+            let mut leaf = alloc::vec![node_header(0b01 << 6, 1), first];
+            leaf.push(compact(value.len()));
+            leaf.extend_from_slice(value);
+            leaf
+        };
+        let mut hashed_leaf = alloc::vec![node_header(0b001 << 5, 1), 0x00];
+        hashed_leaf.extend(blake2_256(&contribution));
+        let mut child_root_node = alloc::vec![node_header(0b10 << 6, 0), 0b0110, 0];
+        child_root_node.push(compact(32));
+        child_root_node.extend(blake2_256(&hashed_leaf));
+        let small = leaf(0x00, &[1]);
+        child_root_node.push(compact(small.len()));
+        child_root_node.extend(&small);
+        let child_root = blake2_256(&child_root_node);
+        let child_items = alloc::vec![child_root_node, hashed_leaf, contribution.to_vec()];
+
+        // Main trie: keys 0x10 (the timestamp) and 0x20 (the child trie's root) under a branch
+        let now = 1_700_000_000_000u64.to_le_bytes();
+        let now_leaf = leaf(0x00, &now);
+        let root_leaf = leaf(0x00, &child_root);
+        let mut main_root = alloc::vec![node_header(0b10 << 6, 0), 0b0110, 0];
+        for child in [&now_leaf, &root_leaf] {
+            main_root.push(compact(32));
+            main_root.extend(blake2_256(child));
+        }
+        let state_root = blake2_256(&main_root);
+
+        let header = [&[5u8; 32][..], &[compact(9)], &state_root, &[6; 32], &[0]].concat();
+        let witness = SubstrateWitness {
+            block_hash: blake2_256(&header),
+            header,
+            storage_key: alloc::vec![0x10],
+            child_storage_key: None,
+            proof: alloc::vec![main_root, now_leaf, root_leaf],
+            child_proof: Vec::new(),
+            field_offset: 0,
+            field_size: 0,
+        };
+        (witness, alloc::vec![0x20], child_items, child_root)
+    }
+
+    #[test]
+    fn test_witness_verification() {
+        let (main, child_key, child_items, child_root) = timestamp_witness();
+        assert_eq!(SubstrateWitness::parse(&main.to_bytes()).unwrap(), main);
+        let value = main.verify().unwrap();
+        assert_eq!((value.block_number, value.child_root), (9, [0; 32]));
+        assert_eq!(value.field[..8], 1_700_000_000_000u64.to_le_bytes());
+
+        // A hashed value in the child trie, and a key absent from it
+        let mut child = SubstrateWitness {
+            storage_key: alloc::vec![0x10],
+            child_storage_key: Some(child_key),
+            child_proof: child_items,
+            field_offset: 2,
+            field_size: 4,
+            ..main.clone()
+        };
+        assert_eq!(SubstrateWitness::parse(&child.to_bytes()).unwrap(), child);
+        let value = child.verify().unwrap();
+        assert_eq!((value.child_root, value.value.unwrap().len()), (child_root, 36));
+        assert_eq!(value.field[..5], [0x42, 0x42, 0x42, 0x42, 0]);
+        child.storage_key = alloc::vec![0x30];
+        assert_eq!(child.verify().unwrap().value, None);
+
+        // Each link is checked
+        let mut other_header = main.clone();
+        other_header.header[40] ^= 1;
+        assert!(other_header.verify().is_err());
+        let mut missing_node = main.clone();
+        missing_node.proof.remove(1);
+        assert!(missing_node.verify().is_err());
+        let mut wide_field = main.clone();
+        wide_field.field_size = 9;
+        assert!(wide_field.verify().is_err());
+        let mut stray_items = main;
+        stray_items.child_proof = alloc::vec![alloc::vec![0]];
+        assert!(stray_items.verify().is_err());
+    }
+}
@@ -0,0 +1,397 @@
+//! Sui object proof checks (no_std compatible)
+//!
+//! Redoes the digest chain `traverse_sui` checks when it builds an object
+//! proof, on the witness the controller writes. Every Sui digest is
+//! BLAKE2b-256 over the value's type name, `::`, and its BCS bytes: the
+//! object's BCS hashes to its digest, the effects of the transaction that
+//! wrote it record that digest, the checkpoint contents record the effects'
+//! digest, and the checkpoint summary, whose digest validators certify,
+//! records the contents' digest. The field is read from the contents inside
+//! the object's BCS, so it is bound to the checkpoint digest a circuit
+//! compares with one it trusts.
+//!
+//! Effects, contents and summary are searched for the digests they must
+//! commit to rather than decoded, as `traverse_sui` does, since their
+//! formats change between protocol versions.
+//!
+//! ## Witness Format
+//! ```text
+//! [8 bytes checkpoint] +
+//! [32 bytes checkpoint_digest] +
+//! [4 bytes field_offset] +
+//! [4 bytes field_size, 0 to read the object ID] +
+//! [4 bytes object_length] + [object_length bytes object BCS] +
+//! [4 bytes effects_length] + [effects_length bytes effects BCS] +
+//! [4 bytes contents_length] + [contents_length bytes checkpoint contents BCS] +
+//! [4 bytes summary_length] + [summary_length bytes checkpoint summary BCS]
+//! ```
+//!
+//! Integers are little-endian.
+
+use alloc::vec::Vec;
+use blake2::{digest::consts::U32, Blake2b, Digest};
+
+type Blake2b256 = Blake2b<U32>;
+
+/// Length of a witness with no BCS data
+pub const WITNESS_FIXED_LEN: usize = 8 + 32 + 4 + 4 + 4 * 4;
+
+/// Digest of a value of the Sui type `type_name` with BCS encoding `bcs`
+pub fn digest(type_name: &str, bcs: &[u8]) -> [u8; 32] {
+    Blake2b256::new()
+        .chain_update(type_name.as_bytes())
+        .chain_update(b"::")
+        .chain_update(bcs)
+        .finalize()
+        .into()
+}
+
+/// What an object proof needs from an object's BCS
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedObject<'a> {
+    pub version: u64,
+    /// BCS encoding of the object's struct, starting with its ID
+    pub contents: &'a [u8],
+    /// Transaction that wrote this version
+    pub previous_transaction: [u8; 32],
+}
+
+/// A cursor over BCS data
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], &'static str> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or("Data ends early")?;
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, &'static str> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, &'static str> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().expect("4 bytes")))
+    }
+
+    fn u64(&mut self) -> Result<u64, &'static str> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().expect("8 bytes")))
+    }
+
+    fn uleb128(&mut self) -> Result<usize, &'static str> {
+        let mut value = 0usize;
+        for shift in (0..32).step_by(7) {
+            let byte = self.u8()?;
+            value |= ((byte & 0x7f) as usize) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("Length does not fit in 32 bits")
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], &'static str> {
+        let length = self.uleb128()?;
+        self.take(length)
+    }
+
+    fn prefixed(&mut self) -> Result<&'a [u8], &'static str> {
+        let length = self.u32()? as usize;
+        self.take(length)
+    }
+
+    fn type_tag(&mut self, depth: usize) -> Result<(), &'static str> {
+        if depth > 16 {
+            return Err("Type is nested too deeply");
+        }
+        match self.u8()? {
+            0..=5 | 8..=10 => Ok(()),
+            6 => self.type_tag(depth + 1),
+            7 => self.struct_tag(depth + 1),
+            _ => Err("Unknown type tag"),
+        }
+    }
+
+    fn struct_tag(&mut self, depth: usize) -> Result<(), &'static str> {
+        self.take(32)?;
+        self.bytes()?;
+        self.bytes()?;
+        for _ in 0..self.uleb128()? {
+            self.type_tag(depth)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a Move object's BCS, as its digest hashes it
+pub fn parse_object(bcs: &[u8]) -> Result<ParsedObject<'_>, &'static str> {
+    let mut reader = Reader { bytes: bcs, position: 0 };
+    if reader.u8()? != 0 {
+        return Err("Only Move objects hold fields");
+    }
+    match reader.u8()? {
+        0 => reader.struct_tag(0)?,
+        1 | 2 => {}
+        3 => reader.type_tag(0)?,
+        _ => return Err("Unknown object type"),
+    }
+    reader.u8()?;
+    let version = reader.u64()?;
+    let contents = reader.bytes()?;
+    match reader.u8()? {
+        0 | 1 => {
+            reader.take(32)?;
+        }
+        2 => {
+            reader.u64()?;
+        }
+        3 => {}
+        4 => {
+            reader.u64()?;
+            reader.take(32)?;
+        }
+        _ => return Err("Unknown owner"),
+    }
+    let previous_transaction = reader.bytes()?.try_into().map_err(|_| "Transaction digest is not 32 bytes")?;
+    reader.u64()?;
+    if reader.position != bcs.len() {
+        return Err("Data continues after the object");
+    }
+    Ok(ParsedObject { version, contents, previous_transaction })
+}
+
+/// A digest as BCS writes it: a 32-byte string
+fn digest_bcs(digest: &[u8; 32]) -> [u8; 33] {
+    let mut out = [32u8; 33];
+    out[1..].copy_from_slice(digest);
+    out
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|window| window == needle)
+}
+
+/// A Sui object field witness
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuiWitness {
+    /// Checkpoint sequence number the proof is anchored to
+    pub checkpoint: u64,
+    /// Digest of the checkpoint summary, as validators certify it
+    pub checkpoint_digest: [u8; 32],
+    /// Field offset within the object's contents
+    pub field_offset: u32,
+    /// Field size in bytes; 0 reads the object ID
+    pub field_size: u32,
+    /// BCS of the object
+    pub object: Vec<u8>,
+    /// BCS of the effects of the transaction that wrote the object
+    pub effects: Vec<u8>,
+    /// BCS of the contents of the checkpoint that executed the transaction
+    pub checkpoint_contents: Vec<u8>,
+    /// BCS of the checkpoint's summary
+    pub checkpoint_summary: Vec<u8>,
+}
+
+/// What a valid witness proves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuiObjectValue {
+    pub object_id: [u8; 32],
+    pub version: u64,
+    pub object_digest: [u8; 32],
+    /// The field's bytes, left-aligned; the object ID when no field is named
+    pub value: [u8; 32],
+    pub checkpoint: u64,
+    pub checkpoint_digest: [u8; 32],
+}
+
+impl SuiWitness {
+    /// Serialize in the witness format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let parts = [&self.object, &self.effects, &self.checkpoint_contents, &self.checkpoint_summary];
+        let mut bytes = Vec::with_capacity(WITNESS_FIXED_LEN + parts.iter().map(|part| part.len()).sum::<usize>());
+        bytes.extend_from_slice(&self.checkpoint.to_le_bytes());
+        bytes.extend_from_slice(&self.checkpoint_digest);
+        bytes.extend_from_slice(&self.field_offset.to_le_bytes());
+        bytes.extend_from_slice(&self.field_size.to_le_bytes());
+        for part in parts {
+            bytes.extend_from_slice(&(part.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(part);
+        }
+        bytes
+    }
+
+    /// Parse a witness; every byte must be accounted for
+    pub fn parse(bytes: &[u8]) -> Result<Self, &'static str> {
+        let mut reader = Reader { bytes, position: 0 };
+        let checkpoint = reader.u64()?;
+        let checkpoint_digest = reader.take(32)?.try_into().expect("32 bytes");
+        let field_offset = reader.u32()?;
+        let field_size = reader.u32()?;
+        let witness = Self {
+            checkpoint,
+            checkpoint_digest,
+            field_offset,
+            field_size,
+            object: reader.prefixed()?.to_vec(),
+            effects: reader.prefixed()?.to_vec(),
+            checkpoint_contents: reader.prefixed()?.to_vec(),
+            checkpoint_summary: reader.prefixed()?.to_vec(),
+        };
+        if reader.position != bytes.len() {
+            return Err("Data continues after the witness");
+        }
+        Ok(witness)
+    }
+
+    /// Check the digest chain from the object to the checkpoint digest and read the field
+    pub fn verify(&self) -> Result<SuiObjectValue, &'static str> {
+        let object = parse_object(&self.object)?;
+        let object_id: [u8; 32] = object
+            .contents
+            .get(..32)
+            .ok_or("Object contents are shorter than an ID")?
+            .try_into()
+            .expect("32 bytes");
+        let object_digest = digest("Object", &self.object);
+        if !contains(&self.effects, &object_id) || !contains(&self.effects, &digest_bcs(&object_digest)) {
+            return Err("Effects do not record the object's version");
+        }
+        if !contains(&self.effects, &digest_bcs(&object.previous_transaction)) {
+            return Err("Effects are not those of the object's transaction");
+        }
+
+        let effects_digest = digest("TransactionEffects", &self.effects);
+        let execution = [digest_bcs(&object.previous_transaction), digest_bcs(&effects_digest)].concat();
+        if !contains(&self.checkpoint_contents, &execution) {
+            return Err("Checkpoint did not execute the object's transaction");
+        }
+
+        // The summary starts with the epoch, then the sequence number
+        let sequence = self.checkpoint_summary.get(8..16).map(|s| u64::from_le_bytes(s.try_into().expect("8 bytes")));
+        if sequence != Some(self.checkpoint) {
+            return Err("Summary is not that of the witness's checkpoint");
+        }
+        let contents_digest = digest("CheckpointContents", &self.checkpoint_contents);
+        if !contains(&self.checkpoint_summary, &digest_bcs(&contents_digest)) {
+            return Err("Summary does not commit to the checkpoint contents");
+        }
+        if digest("CheckpointSummary", &self.checkpoint_summary) != self.checkpoint_digest {
+            return Err("Summary does not hash to the checkpoint digest");
+        }
+
+        let value = if self.field_size == 0 {
+            object_id
+        } else {
+            let (offset, size) = (self.field_offset as usize, self.field_size as usize);
+            let field = offset
+                .checked_add(size)
+                .and_then(|end| object.contents.get(offset..end))
+                .ok_or("Field lies outside the object's contents")?;
+            let mut value = [0u8; 32];
+            let length = field.len().min(32);
+            value[..length].copy_from_slice(&field[..length]);
+            value
+        };
+        Ok(SuiObjectValue {
+            object_id,
+            version: object.version,
+            object_digest,
+            value,
+            checkpoint: self.checkpoint,
+            checkpoint_digest: self.checkpoint_digest,
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A witness whose effects, contents and summary hold just the digests they commit to
+    pub(crate) fn pool_witness(field_offset: u32, field_size: u32) -> SuiWitness {
+        let mut contents = [0u8; 32].to_vec();
+        contents[31] = 0xef;
+        contents.extend(1_000u64.to_le_bytes());
+        let previous_transaction = [0x77; 32];
+
+        // A `0x2::sui::SUI` coin: Move data, GasCoin, public transfer, version 7, shared
+        let mut object = alloc::vec![0, 1, 1];
+        object.extend(7u64.to_le_bytes());
+        object.push(contents.len() as u8);
+        object.extend(&contents);
+        object.push(2);
+        object.extend(3u64.to_le_bytes());
+        object.extend(digest_bcs(&previous_transaction));
+        object.extend(0u64.to_le_bytes());
+
+        let effects = [&contents[..32], &digest_bcs(&digest("Object", &object)), &digest_bcs(&previous_transaction)].concat();
+        let execution = [digest_bcs(&previous_transaction), digest_bcs(&digest("TransactionEffects", &effects))].concat();
+        let checkpoint_contents = [&[0u8, 1][..], &execution, &[1, 0]].concat();
+        let checkpoint_summary = [
+            &5u64.to_le_bytes()[..],
+            &42u64.to_le_bytes(),
+            &digest_bcs(&digest("CheckpointContents", &checkpoint_contents)),
+        ]
+        .concat();
+        SuiWitness {
+            checkpoint: 42,
+            checkpoint_digest: digest("CheckpointSummary", &checkpoint_summary),
+            field_offset,
+            field_size,
+            object,
+            effects,
+            checkpoint_contents,
+            checkpoint_summary,
+        }
+    }
+
+    #[test]
+    fn test_witness_verification() {
+        let witness = pool_witness(32, 8);
+        assert_eq!(SuiWitness::parse(&witness.to_bytes()).unwrap(), witness);
+        let value = witness.verify().unwrap();
+        assert_eq!((value.version, value.object_id[31]), (7, 0xef));
+        assert_eq!(value.value[..8], 1_000u64.to_le_bytes());
+        assert_eq!(pool_witness(0, 0).verify().unwrap().value, value.object_id);
+
+        // Every link of the chain is checked
+        let mut other_contents = witness.clone();
+        other_contents.object[20] ^= 1;
+        assert!(other_contents.verify().is_err());
+        let mut other_checkpoint = witness.clone();
+        other_checkpoint.checkpoint = 43;
+        assert!(other_checkpoint.verify().is_err());
+        let mut other_digest = witness.clone();
+        other_digest.checkpoint_digest[0] ^= 1;
+        assert!(other_digest.verify().is_err());
+        let mut other_effects = witness.clone();
+        other_effects.checkpoint_contents[5] ^= 1;
+        assert!(other_effects.verify().is_err());
+        assert!(pool_witness(36, 8).verify().is_err());
+    }
+
+    #[test]
+    fn test_object_type_tags() {
+        // `0x2::coin::Coin<vector<0x1::string::String>>`, immutable
+        let mut object = alloc::vec![0, 3, 6, 7];
+        object.extend([1; 32]);
+        object.extend([6, b's', b't', b'r', b'i', b'n', b'g', 6, b'S', b't', b'r', b'i', b'n', b'g', 0]);
+        object.push(0);
+        object.extend(1u64.to_le_bytes());
+        object.extend([32; 33]);
+        object.push(3);
+        object.extend(digest_bcs(&[0; 32]));
+        object.extend(0u64.to_le_bytes());
+        assert_eq!(parse_object(&object).unwrap().contents, &[32; 32]);
+        object.push(0);
+        assert!(parse_object(&object).is_err());
+    }
+}
@@ -0,0 +1,244 @@
+//! Property-based testing strategies for witness encodings
+//!
+//! Enabled with the `test-utils` feature, which also enables
+//! `traverse_core::testing` for layouts and queries. [`arb_witness_fields`]
+//! generates the raw inputs of
+//! [`create_semantic_witness_from_raw_data`]; [`check_witness_round_trip`]
+//! encodes them and checks the circuit parses back exactly what went in.
+//! [`check_request_witness_round_trip`] runs a query through a resolver,
+//! the controller and the circuit and checks the key and commitment survive.
+
+use crate::{
+    create_semantic_witness_from_raw_data, create_witness_from_request, CircuitProcessor, CoprocessorStorageQuery,
+    StorageProof, StorageVerificationRequest, ZeroSemantics,
+};
+use alloc::{format, string::ToString, vec::Vec};
+use proptest::prelude::*;
+use proptest::test_runner::TestCaseError;
+use traverse_core::{Key, KeyResolver, LayoutInfo};
+use valence_coprocessor::Witness;
+
+/// The raw inputs of one storage witness
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WitnessFields {
+    pub storage_key: [u8; 32],
+    pub layout_commitment: [u8; 32],
+    pub value: [u8; 32],
+    /// Wire encoding of [`ZeroSemantics`], 0-3
+    pub zero_semantics: u8,
+    /// Wire encoding of the semantic source, 0-2
+    pub semantic_source: u8,
+    pub proof: Vec<u8>,
+    pub block_height: u64,
+    pub block_hash: [u8; 32],
+    pub field_index: u16,
+    pub expected_slot: [u8; 32],
+}
+
+impl WitnessFields {
+    /// Encode with the controller
+    pub fn encode(&self) -> Result<Vec<u8>, TestCaseError> {
+        let witness = create_semantic_witness_from_raw_data(
+            &self.storage_key,
+            &self.layout_commitment,
+            &self.value,
+            self.zero_semantics,
+            self.semantic_source,
+            &self.proof,
+            self.block_height,
+            &self.block_hash,
+            self.field_index,
+            &self.expected_slot,
+        )
+        .map_err(|e| TestCaseError::fail(format!("controller rejected witness: {}", e)))?;
+        witness_bytes(witness)
+    }
+}
+
+/// Valid witness inputs, with proofs up to 2 KiB
+pub fn arb_witness_fields() -> impl Strategy<Value = WitnessFields> {
+    (
+        (any::<[u8; 32]>(), any::<[u8; 32]>(), any::<[u8; 32]>()),
+        (0u8..=3, 0u8..=2),
+        proptest::collection::vec(any::<u8>(), 0..2048),
+        (any::<u64>(), any::<[u8; 32]>(), any::<u16>(), any::<[u8; 32]>()),
+    )
+        .prop_map(
+            |(
+                (storage_key, layout_commitment, value),
+                (zero_semantics, semantic_source),
+                proof,
+                (block_height, block_hash, field_index, expected_slot),
+            )| WitnessFields {
+                storage_key,
+                layout_commitment,
+                value,
+                zero_semantics,
+                semantic_source,
+                proof,
+                block_height,
+                block_hash,
+                field_index,
+                expected_slot,
+            },
+        )
+}
+
+/// RLP-shaped proof nodes: 1 to 8 nodes of 1 to 532 bytes
+pub fn arb_proof_nodes() -> impl Strategy<Value = Vec<Vec<u8>>> {
+    proptest::collection::vec(proptest::collection::vec(any::<u8>(), 1..=532), 1..=8)
+}
+
+fn witness_bytes(witness: Witness) -> Result<Vec<u8>, TestCaseError> {
+    match witness {
+        Witness::Data(bytes) => Ok(bytes),
+        other => Err(TestCaseError::fail(format!("expected a data witness, got {:?}", other))),
+    }
+}
+
+fn zero_semantics_code(semantics: ZeroSemantics) -> u8 {
+    match semantics {
+        ZeroSemantics::NeverWritten => 0,
+        ZeroSemantics::ExplicitlyZero => 1,
+        ZeroSemantics::Cleared => 2,
+        ZeroSemantics::ValidZero => 3,
+    }
+}
+
+/// Check that the circuit parses an encoded witness back to its inputs
+///
+/// Also checks that truncations of the encoding are rejected, so a parser
+/// that reads past the end of a short witness cannot pass.
+pub fn check_witness_round_trip(fields: &WitnessFields) -> Result<(), TestCaseError> {
+    let bytes = fields.encode()?;
+    let parsed = CircuitProcessor::parse_witness_from_bytes(&bytes).map_err(TestCaseError::fail)?;
+
+    prop_assert_eq!(parsed.key, fields.storage_key);
+    prop_assert_eq!(parsed.layout_commitment, fields.layout_commitment);
+    prop_assert_eq!(parsed.value, fields.value);
+    prop_assert_eq!(zero_semantics_code(parsed.semantics), fields.zero_semantics);
+    prop_assert_eq!(&parsed.proof, &fields.proof);
+    prop_assert_eq!(parsed.block_height, fields.block_height);
+    prop_assert_eq!(parsed.block_hash, fields.block_hash);
+    prop_assert_eq!(parsed.field_index, fields.field_index);
+    prop_assert_eq!(parsed.expected_slot, fields.expected_slot);
+
+    for len in [0, bytes.len() / 2, bytes.len() - 33, bytes.len() - 1] {
+        prop_assert!(
+            CircuitProcessor::parse_witness_from_bytes(&bytes[..len]).is_err(),
+            "accepted a witness truncated to {} of {} bytes",
+            len,
+            bytes.len()
+        );
+    }
+    Ok(())
+}
+
+/// Check a query's path through resolver, controller and circuit
+///
+/// Resolves `query`, builds the storage verification request a proof fetcher
+/// would, survives a JSON round trip of it, creates the witness and parses
+/// it in the circuit. The parsed key, expected slot and commitment must be
+/// the resolved ones and the value and proof must be unchanged.
+pub fn check_request_witness_round_trip<R: KeyResolver>(
+    resolver: &R,
+    layout: &LayoutInfo,
+    query: &str,
+    value: [u8; 32],
+    proof_nodes: &[Vec<u8>],
+) -> Result<(), TestCaseError> {
+    let path = resolver
+        .resolve(layout, query)
+        .map_err(|e| TestCaseError::fail(format!("{} did not resolve: {}", query, e)))?;
+    let key: [u8; 32] = match &path.key {
+        Key::Fixed(key) => *key,
+        Key::Variable(key) => key
+            .as_slice()
+            .try_into()
+            .map_err(|_| TestCaseError::reject("witnesses carry 32-byte keys"))?,
+    };
+
+    let request = StorageVerificationRequest {
+        storage_query: CoprocessorStorageQuery {
+            query: query.to_string(),
+            storage_key: hex::encode(key),
+            layout_commitment: hex::encode(path.layout_commitment),
+            field_size: path.field_size,
+            offset: path.offset,
+        },
+        storage_proof: StorageProof {
+            key: format!("0x{}", hex::encode(key)),
+            value: format!("0x{}", hex::encode(value)),
+            proof: proof_nodes.iter().map(|node| format!("0x{}", hex::encode(node))).collect(),
+        },
+        contract_address: None,
+        block_number: None,
+    };
+    let json = serde_json::to_string(&request).map_err(|e| TestCaseError::fail(e.to_string()))?;
+    let request: StorageVerificationRequest =
+        serde_json::from_str(&json).map_err(|e| TestCaseError::fail(e.to_string()))?;
+
+    let witness = create_witness_from_request(&request)
+        .map_err(|e| TestCaseError::fail(format!("controller rejected {}: {}", query, e)))?;
+    let parsed = CircuitProcessor::parse_witness_from_bytes(&witness_bytes(witness)?).map_err(TestCaseError::fail)?;
+
+    prop_assert_eq!(parsed.key, key);
+    prop_assert_eq!(parsed.expected_slot, key);
+    prop_assert_eq!(parsed.layout_commitment, layout.commitment());
+    prop_assert_eq!(parsed.value, value);
+    let proof: Vec<u8> = proof_nodes.concat();
+    prop_assert_eq!(parsed.proof, proof);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use traverse_core::testing::arb_layout;
+    use traverse_core::{StaticKeyPath, TraverseError};
+
+    /// Resolves plain fields to their slot, as every EVM resolver does
+    struct SlotResolver;
+
+    impl KeyResolver for SlotResolver {
+        fn resolve(&self, layout: &LayoutInfo, query: &str) -> Result<StaticKeyPath, TraverseError> {
+            let entry = layout
+                .storage
+                .iter()
+                .find(|entry| entry.label == query)
+                .ok_or_else(|| TraverseError::KeyResolution(query.to_string()))?;
+            let mut key = [0u8; 32];
+            key[24..].copy_from_slice(&entry.slot.parse::<u64>().unwrap().to_be_bytes());
+            Ok(StaticKeyPath {
+                name: "field",
+                key: Key::Fixed(key),
+                offset: Some(entry.offset),
+                field_size: None,
+                layout_commitment: layout.commitment(),
+                zero_semantics: entry.zero_semantics,
+            })
+        }
+
+        fn resolve_all(&self, _layout: &LayoutInfo) -> Result<Vec<StaticKeyPath>, TraverseError> {
+            Ok(Vec::new())
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_witness_round_trip(fields in arb_witness_fields()) {
+            check_witness_round_trip(&fields)?;
+        }
+
+        #[test]
+        fn test_request_witness_round_trip(
+            layout in arb_layout(),
+            index in any::<prop::sample::Index>(),
+            value in any::<[u8; 32]>(),
+            nodes in arb_proof_nodes(),
+        ) {
+            let entry = &layout.storage[index.index(layout.storage.len())];
+            check_request_witness_round_trip(&SlotResolver, &layout, &entry.label, value, &nodes)?;
+        }
+    }
+}
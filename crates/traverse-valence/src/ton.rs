@@ -0,0 +1,1366 @@
+//! TON account state proof checks (no_std compatible)
+//!
+//! Redoes the checks `traverse_ton` makes on a liteserver's account state
+//! proof, on the witness the controller writes, and reads a field out of
+//! the account's data. All TON state is cells, each up to 1023 bits and
+//! four references, hashed over their data and their children's hashes; a
+//! Merkle proof cell wraps a tree with subtrees pruned to their hashes and
+//! commits to the hash of the full tree. From the masterchain block's root
+//! hash, proofs of that block and its state lead, for a basechain account,
+//! to the shard block the masterchain records; proofs of the shard block
+//! and its state lead to the account's leaf in the accounts dictionary,
+//! which commits to the account cell. The account cell holds the data cell.
+//!
+//! Where a TL-B field starts depends on the values before it, and finding
+//! it takes the contract's schema, which the witness does not carry. The
+//! witness gives the field's location in the data instead, as
+//! `traverse_ton` finds it, with the type to read there. The value is then
+//! proven to be at that location in the account's data; a circuit that
+//! knows the layout checks the location, and compares the masterchain
+//! root hash with one it trusts.
+//!
+//! ## Witness Format
+//! ```text
+//! [80 bytes mc_block] + [80 bytes shard_block] +
+//! [4 bytes workchain] + [32 bytes account_id] +
+//! [4 bytes shard_proof_length] + [shard_proof_length bytes bag of cells, empty for masterchain accounts] +
+//! [4 bytes proof_length] + [proof_length bytes bag of cells] +
+//! [4 bytes state_length] + [state_length bytes bag of cells] +
+//! [1 byte ref_count] + [ref_count bytes refs] +
+//! [2 bytes bit] + [1 byte next_ref] +
+//! [4 bytes type_length] + [type_length bytes field type]
+//! ```
+//!
+//! A block is `[4 bytes workchain] + [8 bytes shard] + [4 bytes seqno] +
+//! [32 bytes root_hash] + [32 bytes file_hash]`. Integers are little-endian.
+
+use alloc::{boxed::Box, string::String, vec, vec::Vec};
+use sha2::{Digest, Sha256};
+
+/// Length of a witness with empty bags of cells, location and type
+pub const WITNESS_FIXED_LEN: usize = BLOCK_ID_LEN * 2 + 4 + 32 + 4 * 3 + 1 + 2 + 1 + 4;
+
+/// Length of an encoded block ID
+pub const BLOCK_ID_LEN: usize = 4 + 8 + 4 + 32 + 32;
+
+/// The masterchain's workchain ID
+pub const MASTERCHAIN: i32 = -1;
+
+/// The shard that covers a whole workchain
+pub const FULL_SHARD: u64 = 0x8000_0000_0000_0000;
+
+const BOC_MAGIC: [u8; 4] = [0xb5, 0xee, 0x9c, 0x72];
+const BLOCK_TAG: u64 = 0x11ef_55aa;
+const BLOCK_INFO_TAG: u64 = 0x9bc7_a987;
+const SHARD_STATE_TAG: u64 = 0x9023_afe2;
+const MC_STATE_EXTRA_TAG: u64 = 0xcc26;
+
+/// A block's full identity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockIdExt {
+    pub workchain: i32,
+    /// The shard's prefix, followed by a 1 bit and zeros
+    pub shard: u64,
+    pub seqno: u32,
+    pub root_hash: [u8; 32],
+    pub file_hash: [u8; 32],
+}
+
+/// Where a field starts: follow `refs` from the data cell, then read `bit` bits and `next_ref` references
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldLocation {
+    pub refs: Vec<u8>,
+    pub bit: u16,
+    pub next_ref: u8,
+    /// TL-B type to read there, e.g. `Coins` or `(Maybe ^Config)`
+    pub type_name: String,
+}
+
+/// A TON account data field witness
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TonWitness {
+    pub mc_block: BlockIdExt,
+    /// The block of the account's shard; the masterchain block for masterchain accounts
+    pub shard_block: BlockIdExt,
+    pub workchain: i32,
+    pub account_id: [u8; 32],
+    /// Bag of cells linking the shard block to the masterchain block
+    pub shard_proof: Vec<u8>,
+    /// Bag of cells proving the account's leaf in the shard block's state
+    pub proof: Vec<u8>,
+    /// Bag of cells holding the account
+    pub state: Vec<u8>,
+    pub location: FieldLocation,
+}
+
+/// What a valid witness proves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TonAccountField {
+    pub mc_block: BlockIdExt,
+    pub shard_block: BlockIdExt,
+    pub workchain: i32,
+    pub account_id: [u8; 32],
+    /// Hash of the account cell
+    pub account_hash: [u8; 32],
+    pub last_trans_hash: [u8; 32],
+    pub last_trans_lt: u64,
+    /// Hash of the account's data cell
+    pub data_hash: [u8; 32],
+    /// The value as a 32-byte word, numbers right-aligned; `None` behind an unset `Maybe`
+    pub value: Option<[u8; 32]>,
+}
+
+impl BlockIdExt {
+    fn write(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.workchain.to_le_bytes());
+        bytes.extend_from_slice(&self.shard.to_le_bytes());
+        bytes.extend_from_slice(&self.seqno.to_le_bytes());
+        bytes.extend_from_slice(&self.root_hash);
+        bytes.extend_from_slice(&self.file_hash);
+    }
+
+    fn read(reader: &mut Reader<'_>) -> Result<Self, &'static str> {
+        Ok(Self {
+            workchain: reader.u32()? as i32,
+            shard: u64::from_le_bytes(reader.take(8)?.try_into().expect("8 bytes")),
+            seqno: reader.u32()?,
+            root_hash: reader.take(32)?.try_into().expect("32 bytes"),
+            file_hash: reader.take(32)?.try_into().expect("32 bytes"),
+        })
+    }
+}
+
+/// A cursor over witness data
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], &'static str> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or("Data ends early")?;
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32, &'static str> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().expect("4 bytes")))
+    }
+
+    fn prefixed(&mut self) -> Result<&'a [u8], &'static str> {
+        let length = self.u32()? as usize;
+        self.take(length)
+    }
+}
+
+impl TonWitness {
+    /// Serialize in the witness format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.mc_block.write(&mut bytes);
+        self.shard_block.write(&mut bytes);
+        bytes.extend_from_slice(&self.workchain.to_le_bytes());
+        bytes.extend_from_slice(&self.account_id);
+        for part in [&self.shard_proof, &self.proof, &self.state] {
+            bytes.extend_from_slice(&(part.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(part);
+        }
+        bytes.push(self.location.refs.len() as u8);
+        bytes.extend_from_slice(&self.location.refs);
+        bytes.extend_from_slice(&self.location.bit.to_le_bytes());
+        bytes.push(self.location.next_ref);
+        bytes.extend_from_slice(&(self.location.type_name.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(self.location.type_name.as_bytes());
+        bytes
+    }
+
+    /// Parse a witness; every byte must be accounted for
+    pub fn parse(bytes: &[u8]) -> Result<Self, &'static str> {
+        let mut reader = Reader { bytes, position: 0 };
+        let mc_block = BlockIdExt::read(&mut reader)?;
+        let shard_block = BlockIdExt::read(&mut reader)?;
+        let workchain = reader.u32()? as i32;
+        let account_id = reader.take(32)?.try_into().expect("32 bytes");
+        let shard_proof = reader.prefixed()?.to_vec();
+        let proof = reader.prefixed()?.to_vec();
+        let state = reader.prefixed()?.to_vec();
+        let ref_count = reader.take(1)?[0] as usize;
+        let refs = reader.take(ref_count)?.to_vec();
+        let bit = u16::from_le_bytes(reader.take(2)?.try_into().expect("2 bytes"));
+        let next_ref = reader.take(1)?[0];
+        let type_name = core::str::from_utf8(reader.prefixed()?).map_err(|_| "Field type is not UTF-8")?.into();
+        if reader.position != bytes.len() {
+            return Err("Data continues after the witness");
+        }
+        Ok(Self {
+            mc_block,
+            shard_block,
+            workchain,
+            account_id,
+            shard_proof,
+            proof,
+            state,
+            location: FieldLocation { refs, bit, next_ref, type_name },
+        })
+    }
+
+    /// Check the proofs from the masterchain block down to the account, and read the field
+    pub fn verify(&self) -> Result<TonAccountField, &'static str> {
+        if self.workchain == MASTERCHAIN || self.shard_block == self.mc_block {
+            if self.shard_block != self.mc_block || self.mc_block.workchain != MASTERCHAIN {
+                return Err("A masterchain account must be read at a masterchain block");
+            }
+        } else {
+            self.verify_shard_proof()?;
+        }
+        if self.shard_block.workchain != self.workchain || !shard_contains(self.shard_block.shard, &self.account_id) {
+            return Err("Block is not of the account's shard");
+        }
+
+        let proof = BagOfCells::parse(&self.proof)?;
+        let state_hash = verify_block_proof(&proof, proof.root(0)?, &self.shard_block)?;
+        let state = proof.open_merkle_proof(proof.root(1)?, &state_hash)?;
+        let (accounts, _) = parse_shard_state(&proof, state, &self.shard_block)?;
+
+        let mut accounts = proof.slice(accounts)?;
+        let leaf = if accounts.load_bit()? {
+            hashmap_get(&proof, accounts.load_ref()?, &self.account_id, 256)?
+        } else {
+            None
+        };
+        let mut leaf = leaf.ok_or("The shard state holds no such account")?;
+
+        // DepthBalanceInfo, then ShardAccount
+        leaf.load_uint_leq(30)?;
+        leaf.load_var_uint(16)?;
+        if leaf.load_bit()? {
+            leaf.load_ref()?;
+        }
+        let account_hash = proof.cell(leaf.load_ref()?).hash(0);
+        let last_trans_hash = leaf.load_hash()?;
+        let last_trans_lt = leaf.load_uint(64)?;
+
+        let state = BagOfCells::parse(&self.state)?;
+        let root = state.root(0)?;
+        if state.cell(root).hash(0) != account_hash {
+            return Err("The state is not the account's");
+        }
+        if state.cells.iter().any(|cell| cell.kind != CellKind::Ordinary) {
+            return Err("The account's state is pruned");
+        }
+        let (workchain, account_id, data) = parse_account(&state, root)?;
+        if (workchain, account_id) != (self.workchain, self.account_id) {
+            return Err("The state is another account's");
+        }
+        let data = data.ok_or("The account has no data")?;
+
+        let mut cell = data;
+        for index in &self.location.refs {
+            cell = *state.cell(cell).refs.get(*index as usize).ok_or("The field's location has no such reference")?;
+        }
+        let mut slice = state.slice(cell)?;
+        slice.skip_bits(self.location.bit as usize)?;
+        for _ in 0..self.location.next_ref {
+            slice.load_ref()?;
+        }
+        let value = read_word(&state, &mut slice, &FieldType::parse(&self.location.type_name)?)?;
+
+        Ok(TonAccountField {
+            mc_block: self.mc_block,
+            shard_block: self.shard_block,
+            workchain: self.workchain,
+            account_id: self.account_id,
+            account_hash,
+            last_trans_hash,
+            last_trans_lt,
+            data_hash: state.cell(data).hash(0),
+            value,
+        })
+    }
+
+    /// Check that the masterchain block records the shard block
+    fn verify_shard_proof(&self) -> Result<(), &'static str> {
+        if self.mc_block.workchain != MASTERCHAIN {
+            return Err("Proofs must start at a masterchain block");
+        }
+        let boc = BagOfCells::parse(&self.shard_proof)?;
+        let state_hash = verify_block_proof(&boc, boc.root(0)?, &self.mc_block)?;
+        let state = boc.open_merkle_proof(boc.root(1)?, &state_hash)?;
+        let (_, extra) = parse_shard_state(&boc, state, &self.mc_block)?;
+        let mut extra = boc.slice(extra.ok_or("The masterchain state has no shard list")?)?;
+        if extra.load_uint(16)? != MC_STATE_EXTRA_TAG || !extra.load_bit()? {
+            return Err("The masterchain state has no shard list");
+        }
+        let workchain = self.shard_block.workchain.to_be_bytes();
+        let mut shards = hashmap_get(&boc, extra.load_ref()?, &workchain, 32)?.ok_or("The workchain has no shards")?;
+
+        // Walk the workchain's binary tree of shards along the account's address
+        let mut node = boc.slice(shards.load_ref()?)?;
+        let mut depth = 0;
+        while node.load_bit()? {
+            if depth >= 60 {
+                return Err("The shard tree is too deep");
+            }
+            let bit = self.account_id[depth / 8] >> (7 - depth % 8) & 1;
+            let left = node.load_ref()?;
+            let right = node.load_ref()?;
+            node = boc.slice(if bit == 1 { right } else { left })?;
+            depth += 1;
+        }
+        let prefix = u64::from_be_bytes(self.account_id[..8].try_into().expect("8 bytes"));
+        let shard = (prefix & !(u64::MAX >> depth)) | (FULL_SHARD >> depth);
+
+        let tag = node.load_uint(4)?;
+        if tag != 0xa && tag != 0xb {
+            return Err("Unknown shard description tag");
+        }
+        let seqno = node.load_uint(32)? as u32;
+        node.skip_bits(32 + 64 + 64)?;
+        let root_hash = node.load_hash()?;
+        let file_hash = node.load_hash()?;
+        let recorded = BlockIdExt { workchain: self.shard_block.workchain, shard, seqno, root_hash, file_hash };
+        if recorded != self.shard_block {
+            return Err("The masterchain block records another block of the account's shard");
+        }
+        Ok(())
+    }
+}
+
+/// Check a Merkle proof of `block` and return the hash of the state it leads to
+fn verify_block_proof(boc: &BagOfCells, root: usize, block: &BlockIdExt) -> Result<[u8; 32], &'static str> {
+    let cell = boc.open_merkle_proof(root, &block.root_hash)?;
+    let mut slice = boc.slice(cell)?;
+    if slice.load_uint(32)? != BLOCK_TAG {
+        return Err("Expected a block");
+    }
+    let mut info = boc.slice(slice.load_ref()?)?;
+    if info.load_uint(32)? != BLOCK_INFO_TAG {
+        return Err("Expected a block's info");
+    }
+    // version, then eight flag bits and the flags byte
+    info.skip_bits(32 + 8 + 8)?;
+    let seqno = info.load_uint(32)? as u32;
+    info.skip_bits(32)?;
+    let (workchain, shard) = load_shard_ident(&mut info)?;
+    if (seqno, workchain, shard) != (block.seqno, block.workchain, block.shard) {
+        return Err("The block proven is another block");
+    }
+
+    let _value_flow = slice.load_ref()?;
+    let update = boc.cell(slice.load_ref()?);
+    if update.kind != CellKind::MerkleUpdate {
+        return Err("A block's state update must be a Merkle update");
+    }
+    let new_state = boc.cell(update.refs[1]).hash(0);
+    if update.merkle_hash(1) != Some(new_state) {
+        return Err("The state update's hashes disagree");
+    }
+    Ok(new_state)
+}
+
+/// The accounts dictionary of a shard state, and for the masterchain its extra
+fn parse_shard_state(
+    boc: &BagOfCells,
+    cell: usize,
+    block: &BlockIdExt,
+) -> Result<(usize, Option<usize>), &'static str> {
+    let mut slice = boc.slice(cell)?;
+    if slice.load_uint(32)? != SHARD_STATE_TAG {
+        return Err("Expected an unsplit shard state");
+    }
+    slice.skip_bits(32)?;
+    let (workchain, shard) = load_shard_ident(&mut slice)?;
+    let seqno = slice.load_uint(32)? as u32;
+    if (seqno, workchain, shard) != (block.seqno, block.workchain, block.shard) {
+        return Err("The state proven is another block's");
+    }
+    // vert_seq_no, gen_utime, gen_lt, min_ref_mc_seqno
+    slice.skip_bits(32 + 32 + 64 + 32)?;
+    let _out_msg_queue_info = slice.load_ref()?;
+    slice.skip_bits(1)?;
+    let accounts = slice.load_ref()?;
+    let _overload_history = slice.load_ref()?;
+    let custom = if slice.load_bit()? { Some(slice.load_ref()?) } else { None };
+    slice.expect_end()?;
+    Ok((accounts, custom))
+}
+
+fn load_shard_ident(slice: &mut CellSlice<'_>) -> Result<(i32, u64), &'static str> {
+    if slice.load_uint(2)? != 0 {
+        return Err("Expected a shard identifier");
+    }
+    let prefix_bits = slice.load_uint_leq(60)? as u32;
+    let workchain = slice.load_uint(32)? as u32 as i32;
+    let prefix = slice.load_uint(64)?;
+    Ok((workchain, prefix | (FULL_SHARD >> prefix_bits)))
+}
+
+/// Whether the shard `shard` holds the account with ID `account_id`
+fn shard_contains(shard: u64, account_id: &[u8; 32]) -> bool {
+    let prefix_bits = 63 - shard.trailing_zeros();
+    let mask = !(u64::MAX >> prefix_bits);
+    let account = u64::from_be_bytes(account_id[..8].try_into().expect("8 bytes"));
+    shard != 0 && (account & mask) == (shard & mask)
+}
+
+/// The address and data cell of an existing account, in either storage statistics shape
+fn parse_account(boc: &BagOfCells, cell: usize) -> Result<(i32, [u8; 32], Option<usize>), &'static str> {
+    let mut slice = boc.slice(cell)?;
+    if !slice.load_bit()? {
+        return Err("The account does not exist");
+    }
+    parse_account_shape(slice, true).or_else(|_| parse_account_shape(slice, false))
+}
+
+/// The account after its leading bit; the storage statistics dropped `public_cells` and gained `storage_extra` in 2024
+fn parse_account_shape(
+    mut slice: CellSlice<'_>,
+    current: bool,
+) -> Result<(i32, [u8; 32], Option<usize>), &'static str> {
+    let (workchain, account_id) = load_address(&mut slice)?.ok_or("An account has no address")?;
+
+    // storage_stat:StorageInfo
+    for _ in 0..if current { 2 } else { 3 } {
+        slice.load_var_uint(7)?;
+    }
+    if current {
+        match slice.load_uint(3)? {
+            0b000 => {}
+            0b001 => slice.skip_bits(256)?,
+            _ => return Err("Unknown storage_extra tag"),
+        }
+    }
+    slice.skip_bits(32)?;
+    if slice.load_bit()? {
+        slice.load_var_uint(16)?;
+    }
+
+    // storage:AccountStorage: last_trans_lt, balance, extra currencies, state
+    slice.skip_bits(64)?;
+    slice.load_var_uint(16)?;
+    if slice.load_bit()? {
+        slice.load_ref()?;
+    }
+    let mut data = None;
+    if slice.load_bit()? {
+        // StateInit: fixed_prefix_length, special, code, data, library
+        if slice.load_bit()? {
+            slice.skip_bits(5)?;
+        }
+        if slice.load_bit()? {
+            slice.skip_bits(2)?;
+        }
+        if slice.load_bit()? {
+            slice.load_ref()?;
+        }
+        if slice.load_bit()? {
+            data = Some(slice.load_ref()?);
+        }
+        if slice.load_bit()? {
+            slice.load_ref()?;
+        }
+    } else if slice.load_bit()? {
+        // Frozen: the state's hash
+        slice.skip_bits(256)?;
+    }
+    slice.expect_end()?;
+    Ok((workchain, account_id, data))
+}
+
+/// Read a `MsgAddress` as its workchain and hash, `None` being `addr_none`
+fn load_address(slice: &mut CellSlice<'_>) -> Result<Option<(i32, [u8; 32])>, &'static str> {
+    let kind = slice.load_uint(2)?;
+    if kind == 0b00 {
+        return Ok(None);
+    }
+    if kind == 0b01 {
+        return Err("External addresses are not supported");
+    }
+    if slice.load_bit()? {
+        // Anycast: a rewrite prefix of up to 30 bits
+        let depth = slice.load_uint_leq(30)? as usize;
+        slice.skip_bits(depth)?;
+    }
+    let workchain = if kind == 0b10 {
+        slice.load_uint(8)? as u8 as i8 as i32
+    } else {
+        if slice.load_uint(9)? != 256 {
+            return Err("Variable-length addresses are not supported");
+        }
+        slice.load_uint(32)? as u32 as i32
+    };
+    Ok(Some((workchain, slice.load_hash()?)))
+}
+
+/// The TL-B types a field can be read as
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FieldType {
+    Uint(usize),
+    Int(usize),
+    Bits(usize),
+    Bool,
+    /// `VarUInteger n`, `Coins`, `Grams`
+    VarUint(usize),
+    /// `MsgAddress`, `MsgAddressInt`; the hash only
+    Address,
+    /// `^Cell`, or a reference to a record: the referenced cell's hash
+    CellRef,
+    /// `^X` for a value `X`
+    Ref(Box<FieldType>),
+    Maybe(Box<FieldType>),
+    /// `HashmapE n X`: the hash of the dictionary's root, zero when empty
+    Dict,
+    /// A record the schema declares, stored inline
+    Record,
+}
+
+impl FieldType {
+    /// Parse a type expression as `traverse_ton` writes it, e.g. `uint64` or `(Maybe ^Config)`
+    fn parse(expr: &str) -> Result<Self, &'static str> {
+        let expr = expr.trim();
+        let invalid = "Unsupported field type";
+        if let Some(inner) = expr.strip_prefix('(').and_then(|e| e.strip_suffix(')')) {
+            return Self::parse(inner);
+        }
+        if let Some(inner) = expr.strip_prefix('^') {
+            return Ok(match inner.trim() {
+                "Cell" | "Any" => Self::CellRef,
+                inner => match Self::parse(inner)? {
+                    inner if inner.holds_record() => Self::CellRef,
+                    inner => Self::Ref(Box::new(inner)),
+                },
+            });
+        }
+        let words: Vec<&str> = expr.split_whitespace().collect();
+        let number = |s: &str| s.parse::<usize>().map_err(|_| invalid);
+        let sized = |prefix: &str, max: usize| {
+            expr.strip_prefix(prefix)
+                .and_then(|n| n.parse::<usize>().ok())
+                .filter(|n| (1..=max).contains(n))
+        };
+        Ok(match words.as_slice() {
+            ["Bool"] => Self::Bool,
+            ["Coins"] | ["Grams"] => Self::VarUint(16),
+            ["MsgAddress"] | ["MsgAddressInt"] => Self::Address,
+            ["##", n] => Self::Uint(number(n)?).checked_width(256)?,
+            ["VarUInteger", n] => match number(n)? {
+                n @ 2..=32 => Self::VarUint(n),
+                _ => return Err(invalid),
+            },
+            ["HashmapE", n, _] => {
+                number(n)?;
+                Self::Dict
+            }
+            ["Maybe", rest @ ..] if !rest.is_empty() => {
+                Self::Maybe(Box::new(Self::parse(&rest.join(" "))?))
+            }
+            [_] => {
+                if let Some(bits) = sized("uint", 256) {
+                    Self::Uint(bits)
+                } else if let Some(bits) = sized("int", 257) {
+                    Self::Int(bits)
+                } else if let Some(bits) = sized("bits", 1023) {
+                    Self::Bits(bits)
+                } else if expr.starts_with(|c: char| c.is_ascii_uppercase())
+                    && expr.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                {
+                    Self::Record
+                } else {
+                    return Err(invalid);
+                }
+            }
+            _ => return Err(invalid),
+        })
+    }
+
+    fn checked_width(self, max: usize) -> Result<Self, &'static str> {
+        match self {
+            Self::Uint(bits) if bits == 0 || bits > max => Err("Unsupported field type"),
+            ty => Ok(ty),
+        }
+    }
+
+    fn holds_record(&self) -> bool {
+        match self {
+            Self::Record => true,
+            Self::Ref(inner) | Self::Maybe(inner) => inner.holds_record(),
+            _ => false,
+        }
+    }
+}
+
+/// Read a value as a 32-byte word, numbers right-aligned; `None` behind an unset `Maybe`
+fn read_word(boc: &BagOfCells, slice: &mut CellSlice<'_>, ty: &FieldType) -> Result<Option<[u8; 32]>, &'static str> {
+    let right_aligned = |bytes: &[u8], fill: u8| {
+        let skip = bytes.len().saturating_sub(32);
+        if bytes[..skip].iter().any(|b| *b != fill) {
+            return Err("The value does not fit in 32 bytes");
+        }
+        let mut word = [fill; 32];
+        word[32 - (bytes.len() - skip)..].copy_from_slice(&bytes[skip..]);
+        Ok(word)
+    };
+    let word = match ty {
+        FieldType::Uint(bits) => right_aligned(&load_right_aligned(slice, *bits, false)?, 0)?,
+        FieldType::Int(bits) => {
+            let bytes = load_right_aligned(slice, *bits, true)?;
+            right_aligned(&bytes, if bytes[0] & 0x80 != 0 { 0xff } else { 0 })?
+        }
+        FieldType::Bits(bits) => right_aligned(&slice.load_bits(*bits)?, 0)?,
+        FieldType::Bool => right_aligned(&[slice.load_bit()? as u8], 0)?,
+        FieldType::VarUint(n) => right_aligned(&slice.load_var_uint(*n)?.to_be_bytes(), 0)?,
+        FieldType::Address => load_address(slice)?.map_or([0; 32], |(_, hash)| hash),
+        FieldType::CellRef => boc.cell(slice.load_ref()?).hash(0),
+        FieldType::Ref(inner) => return read_word(boc, &mut boc.slice(slice.load_ref()?)?, inner),
+        FieldType::Maybe(inner) if slice.load_bit()? => return read_word(boc, slice, inner),
+        FieldType::Maybe(_) => return Ok(None),
+        FieldType::Dict if slice.load_bit()? => boc.cell(slice.load_ref()?).hash(0),
+        FieldType::Dict => [0; 32],
+        FieldType::Record => return Err("A record stored inline has no value"),
+    };
+    Ok(Some(word))
+}
+
+/// `bits` bits as a big-endian number in whole bytes, sign-extended if `signed`
+fn load_right_aligned(slice: &mut CellSlice<'_>, bits: usize, signed: bool) -> Result<Vec<u8>, &'static str> {
+    let len = bits.div_ceil(8);
+    let pad = len * 8 - bits;
+    let mut bytes = vec![0u8; len];
+    for i in 0..bits {
+        if slice.load_bit()? {
+            let at = pad + i;
+            bytes[at / 8] |= 0x80 >> (at % 8);
+        }
+    }
+    if signed && pad > 0 && bytes[0] & (0x80 >> pad) != 0 {
+        bytes[0] |= !(0xffu8 >> pad);
+    }
+    Ok(bytes)
+}
+
+/// Look up `key`, a `key_bits`-bit key left-aligned in its bytes, in the
+/// dictionary whose root edge is the cell `root`
+///
+/// Returns the leaf positioned after its label, or `None` when the
+/// dictionary shows the key is absent.
+fn hashmap_get<'a>(
+    boc: &'a BagOfCells,
+    root: usize,
+    key: &[u8],
+    key_bits: usize,
+) -> Result<Option<CellSlice<'a>>, &'static str> {
+    let key: Vec<bool> = (0..key_bits).map(|i| key[i / 8] >> (7 - i % 8) & 1 == 1).collect();
+    let mut slice = boc.slice(root)?;
+    let mut at = 0;
+    loop {
+        let label = load_label(&mut slice, key_bits - at)?;
+        if key[at..at + label.len()] != label[..] {
+            return Ok(None);
+        }
+        at += label.len();
+        if at == key_bits {
+            return Ok(Some(slice));
+        }
+        let left = slice.load_ref()?;
+        let right = slice.load_ref()?;
+        slice = boc.slice(if key[at] { right } else { left })?;
+        at += 1;
+    }
+}
+
+/// An edge label, for an edge with at most `max` key bits left
+fn load_label(slice: &mut CellSlice<'_>, max: usize) -> Result<Vec<bool>, &'static str> {
+    let length_bits = |slice: &mut CellSlice<'_>| slice.load_uint_leq(max as u64).map(|n| n as usize);
+    let label = if !slice.load_bit()? {
+        // hml_short$0: the length in unary, then the bits
+        let mut len = 0;
+        while slice.load_bit()? {
+            len += 1;
+        }
+        (0..len).map(|_| slice.load_bit()).collect::<Result<Vec<_>, _>>()?
+    } else if !slice.load_bit()? {
+        // hml_long$10: the length in binary, then the bits
+        let len = length_bits(slice)?;
+        (0..len).map(|_| slice.load_bit()).collect::<Result<Vec<_>, _>>()?
+    } else {
+        // hml_same$11: one bit, repeated
+        let bit = slice.load_bit()?;
+        vec![bit; length_bits(slice)?]
+    };
+    if label.len() > max {
+        return Err("A dictionary label is longer than its key");
+    }
+    Ok(label)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CellKind {
+    Ordinary,
+    PrunedBranch,
+    LibraryReference,
+    MerkleProof,
+    MerkleUpdate,
+}
+
+/// A cell in a bag, its references given as indexes into the bag
+#[derive(Debug, Clone)]
+struct Cell {
+    kind: CellKind,
+    /// Data, with any bits past `bit_len` zero
+    data: Vec<u8>,
+    bit_len: usize,
+    refs: Vec<usize>,
+    level_mask: u8,
+    hashes: Vec<[u8; 32]>,
+    depths: Vec<u16>,
+}
+
+/// A parsed bag of cells, every cell's hashes computed
+#[derive(Debug, Clone)]
+struct BagOfCells {
+    cells: Vec<Cell>,
+    roots: Vec<usize>,
+}
+
+/// Index of the hash that serves `level` under `mask`
+fn hash_index(mask: u8, level: u8) -> usize {
+    (mask & ((1u8 << level) - 1)).count_ones() as usize
+}
+
+impl Cell {
+    /// Hash of the cell at `level`; level 0 is the hash of the full, unpruned tree
+    fn hash(&self, level: u8) -> [u8; 32] {
+        self.hashes[hash_index(self.level_mask, level.min(3)).min(self.hashes.len() - 1)]
+    }
+
+    fn depth(&self, level: u8) -> u16 {
+        self.depths[hash_index(self.level_mask, level.min(3)).min(self.depths.len() - 1)]
+    }
+
+    /// Hash a Merkle proof or update commits to, read from its data
+    fn merkle_hash(&self, which: usize) -> Option<[u8; 32]> {
+        let start = 1 + 32 * which;
+        self.data.get(start..start + 32).map(|hash| hash.try_into().expect("32 bytes"))
+    }
+
+    fn descriptors(&self, level_mask: u8) -> [u8; 2] {
+        let exotic = self.kind != CellKind::Ordinary;
+        let d1 = self.refs.len() as u8 + 8 * exotic as u8 + 32 * level_mask;
+        let d2 = (self.bit_len / 8 + self.bit_len.div_ceil(8)) as u8;
+        [d1, d2]
+    }
+
+    /// Data as it is hashed: whole bytes, an incomplete last byte ending in a 1 bit
+    fn padded_data(&self) -> Vec<u8> {
+        let mut data = self.data[..self.bit_len.div_ceil(8)].to_vec();
+        if !self.bit_len.is_multiple_of(8) {
+            let last = data.len() - 1;
+            data[last] |= 0x80 >> (self.bit_len % 8);
+        }
+        data
+    }
+}
+
+impl BagOfCells {
+    fn parse(bytes: &[u8]) -> Result<Self, &'static str> {
+        if bytes.get(..4) != Some(&BOC_MAGIC[..]) {
+            return Err("Bag of cells has an unknown magic");
+        }
+        let truncated = "Bag of cells is truncated";
+        let flags = *bytes.get(4).ok_or(truncated)?;
+        let has_index = flags & 0x80 != 0;
+        let has_crc = flags & 0x40 != 0;
+        let ref_size = (flags & 0x07) as usize;
+        let offset_size = *bytes.get(5).ok_or(truncated)? as usize;
+        if !(1..=4).contains(&ref_size) || !(1..=8).contains(&offset_size) {
+            return Err("Bag of cells has bad sizes");
+        }
+        if has_crc {
+            let (body, crc) = bytes.split_at(bytes.len().checked_sub(4).ok_or(truncated)?);
+            if crc32c(body).to_le_bytes() != crc {
+                return Err("Bag of cells checksum mismatch");
+            }
+        }
+
+        let mut position = 6;
+        let mut read = |size: usize| -> Result<usize, &'static str> {
+            let field = bytes.get(position..position + size).ok_or(truncated)?;
+            position += size;
+            Ok(field.iter().fold(0usize, |acc, b| (acc << 8) | *b as usize))
+        };
+        let cell_count = read(ref_size)?;
+        let root_count = read(ref_size)?;
+        let _absent = read(ref_size)?;
+        let data_size = read(offset_size)?;
+        let roots = (0..root_count).map(|_| read(ref_size)).collect::<Result<Vec<_>, _>>()?;
+        if has_index {
+            read(cell_count.checked_mul(offset_size).ok_or(truncated)?)?;
+        }
+        let data = bytes.get(position..position.checked_add(data_size).ok_or(truncated)?).ok_or(truncated)?;
+
+        let mut cells = Vec::new();
+        let mut at = 0;
+        while cells.len() < cell_count {
+            let (cell, used) = parse_cell(&data[at..], ref_size, cells.len(), cell_count)?;
+            cells.push(cell);
+            at += used;
+        }
+        if at != data.len() {
+            return Err("Bag of cells has trailing cell data");
+        }
+        if roots.is_empty() || roots.iter().any(|root| *root >= cell_count) {
+            return Err("Bag of cells has bad roots");
+        }
+
+        // Children come after their parents, so hash from the end
+        for index in (0..cells.len()).rev() {
+            let (hashes, depths, level_mask) = compute_hashes(&cells, index)?;
+            let cell = &mut cells[index];
+            cell.hashes = hashes;
+            cell.depths = depths;
+            cell.level_mask = level_mask;
+        }
+        Ok(Self { cells, roots })
+    }
+
+    fn root(&self, index: usize) -> Result<usize, &'static str> {
+        self.roots.get(index).copied().ok_or("Bag of cells is missing a root")
+    }
+
+    fn cell(&self, index: usize) -> &Cell {
+        &self.cells[index]
+    }
+
+    fn slice(&self, index: usize) -> Result<CellSlice<'_>, &'static str> {
+        if self.cells[index].kind == CellKind::PrunedBranch {
+            return Err("The proof prunes a cell it is needed for");
+        }
+        Ok(CellSlice { boc: self, cell: index, bit: 0, next_ref: 0 })
+    }
+
+    /// The tree a Merkle proof root wraps, after checking it has the hash `expected`
+    fn open_merkle_proof(&self, index: usize, expected: &[u8; 32]) -> Result<usize, &'static str> {
+        let cell = &self.cells[index];
+        if cell.kind != CellKind::MerkleProof {
+            return Err("Expected a Merkle proof cell");
+        }
+        let child = cell.refs[0];
+        if cell.merkle_hash(0) != Some(*expected) || self.cells[child].hash(0) != *expected {
+            return Err("The Merkle proof is for another tree");
+        }
+        Ok(child)
+    }
+}
+
+fn parse_cell(data: &[u8], ref_size: usize, index: usize, cell_count: usize) -> Result<(Cell, usize), &'static str> {
+    let truncated = "Cell is truncated";
+    let [d1, d2] = *data.first_chunk::<2>().ok_or(truncated)?;
+    let ref_count = (d1 & 7) as usize;
+    let exotic = d1 & 8 != 0;
+    let level_mask = d1 >> 5;
+    if ref_count > 4 {
+        return Err("Cell has more than four references");
+    }
+    let mut at = 2;
+    if d1 & 16 != 0 {
+        // Stored hashes and depths, recomputed below
+        at += (level_mask.count_ones() as usize + 1) * (32 + 2);
+    }
+    let byte_len = (d2 as usize).div_ceil(2);
+    let mut cell_data = data.get(at..at + byte_len).ok_or(truncated)?.to_vec();
+    at += byte_len;
+    let bit_len = if d2.is_multiple_of(2) {
+        byte_len * 8
+    } else {
+        // The last byte ends in a 1 bit marking where the data stops
+        let last = *cell_data.last().ok_or("Cell has no data")?;
+        if last == 0 {
+            return Err("Cell has bad padding");
+        }
+        let trailing = last.trailing_zeros() as usize;
+        let len = cell_data.len();
+        cell_data[len - 1] &= !(1u8 << trailing);
+        byte_len * 8 - trailing - 1
+    };
+    let mut refs = Vec::with_capacity(ref_count);
+    for _ in 0..ref_count {
+        let field = data.get(at..at + ref_size).ok_or(truncated)?;
+        let target = field.iter().fold(0usize, |acc, b| (acc << 8) | *b as usize);
+        if target <= index || target >= cell_count {
+            return Err("Cell references a cell before it");
+        }
+        refs.push(target);
+        at += ref_size;
+    }
+
+    let kind = if !exotic {
+        CellKind::Ordinary
+    } else {
+        match cell_data.first() {
+            Some(1) => CellKind::PrunedBranch,
+            Some(2) => CellKind::LibraryReference,
+            Some(3) if bit_len == 8 + 256 + 16 && ref_count == 1 => CellKind::MerkleProof,
+            Some(4) if bit_len == 8 + 512 + 32 && ref_count == 2 => CellKind::MerkleUpdate,
+            _ => return Err("Unknown exotic cell"),
+        }
+    };
+    let cell = Cell { kind, data: cell_data, bit_len, refs, level_mask, hashes: Vec::new(), depths: Vec::new() };
+    Ok((cell, at))
+}
+
+/// A cell's hashes and depths, one per level it has, and its level mask
+type LevelHashes = (Vec<[u8; 32]>, Vec<u16>, u8);
+
+/// Hashes and depths of `cells[index]` at each of its levels, children already hashed
+fn compute_hashes(cells: &[Cell], index: usize) -> Result<LevelHashes, &'static str> {
+    let cell = &cells[index];
+    let children: Vec<&Cell> = cell.refs.iter().map(|r| &cells[*r]).collect();
+    let merkle = matches!(cell.kind, CellKind::MerkleProof | CellKind::MerkleUpdate);
+
+    let level_mask = match cell.kind {
+        CellKind::PrunedBranch => {
+            let mask = *cell.data.get(1).ok_or("Pruned branch has no level mask")?;
+            let stored = mask.count_ones() as usize;
+            if mask == 0 || mask > 7 || !cell.refs.is_empty() || cell.bit_len != 16 + stored * (256 + 16) {
+                return Err("Malformed pruned branch");
+            }
+            mask
+        }
+        CellKind::MerkleProof | CellKind::MerkleUpdate => children.iter().fold(0, |m, c| m | c.level_mask) >> 1,
+        _ => children.iter().fold(0, |m, c| m | c.level_mask),
+    };
+    if cell.level_mask != 0 && cell.level_mask != level_mask && cell.kind != CellKind::PrunedBranch {
+        return Err("Cell's stated level mask does not match its children");
+    }
+
+    let mut hashes = Vec::new();
+    let mut depths = Vec::new();
+    if cell.kind == CellKind::PrunedBranch {
+        // The lower levels' hashes and depths are stored in the cell
+        let stored = level_mask.count_ones() as usize;
+        for i in 0..stored {
+            hashes.push(cell.data[2 + 32 * i..2 + 32 * (i + 1)].try_into().expect("32 bytes"));
+        }
+        for i in 0..stored {
+            let at = 2 + 32 * stored + 2 * i;
+            depths.push(u16::from_be_bytes([cell.data[at], cell.data[at + 1]]));
+        }
+    }
+
+    let total = level_mask.count_ones() as usize + 1;
+    for level in 0..=3u8 {
+        // Only the levels the mask marks, and level 0, get their own hash
+        if level != 0 && level_mask & (1 << (level - 1)) == 0 {
+            continue;
+        }
+        let hash_i = hash_index(level_mask, level);
+        if cell.kind == CellKind::PrunedBranch && hash_i != total - 1 {
+            continue;
+        }
+        let mut hasher = Sha256::new();
+        hasher.update(cell.descriptors(level_mask & ((1u8 << level) - 1)));
+        if hash_i == 0 || cell.kind == CellKind::PrunedBranch {
+            hasher.update(cell.padded_data());
+        } else {
+            hasher.update(hashes[hash_i - 1]);
+        }
+        let child_level = if merkle { level + 1 } else { level };
+        let mut depth = 0u16;
+        for child in &children {
+            let child_depth = child.depth(child_level);
+            hasher.update(child_depth.to_be_bytes());
+            depth = depth.max(child_depth.checked_add(1).ok_or("Cell tree is too deep")?);
+        }
+        for child in &children {
+            hasher.update(child.hash(child_level));
+        }
+        hashes.push(hasher.finalize().into());
+        depths.push(depth);
+    }
+    Ok((hashes, depths, level_mask))
+}
+
+/// A reader over a cell
+#[derive(Clone, Copy)]
+struct CellSlice<'a> {
+    boc: &'a BagOfCells,
+    cell: usize,
+    bit: usize,
+    next_ref: usize,
+}
+
+impl<'a> CellSlice<'a> {
+    fn cell_ref(&self) -> &'a Cell {
+        &self.boc.cells[self.cell]
+    }
+
+    fn remaining_bits(&self) -> usize {
+        self.cell_ref().bit_len - self.bit
+    }
+
+    fn load_bit(&mut self) -> Result<bool, &'static str> {
+        if self.remaining_bits() == 0 {
+            return Err("Read past the end of a cell");
+        }
+        let byte = self.cell_ref().data[self.bit / 8];
+        let bit = byte >> (7 - self.bit % 8) & 1 == 1;
+        self.bit += 1;
+        Ok(bit)
+    }
+
+    /// An unsigned integer of up to 64 bits
+    fn load_uint(&mut self, bits: usize) -> Result<u64, &'static str> {
+        if bits > 64 || bits > self.remaining_bits() {
+            return Err("Read past the end of a cell");
+        }
+        (0..bits).try_fold(0u64, |acc, _| Ok((acc << 1) | self.load_bit()? as u64))
+    }
+
+    /// `bits` bits, big-endian and left-aligned in whole bytes
+    fn load_bits(&mut self, bits: usize) -> Result<Vec<u8>, &'static str> {
+        if bits > self.remaining_bits() {
+            return Err("Read past the end of a cell");
+        }
+        let mut bytes = vec![0u8; bits.div_ceil(8)];
+        for i in 0..bits {
+            if self.load_bit()? {
+                bytes[i / 8] |= 0x80 >> (i % 8);
+            }
+        }
+        Ok(bytes)
+    }
+
+    fn load_hash(&mut self) -> Result<[u8; 32], &'static str> {
+        Ok(self.load_bits(256)?.try_into().expect("32 bytes"))
+    }
+
+    fn skip_bits(&mut self, bits: usize) -> Result<(), &'static str> {
+        if bits > self.remaining_bits() {
+            return Err("Read past the end of a cell");
+        }
+        self.bit += bits;
+        Ok(())
+    }
+
+    /// Index of the next referenced cell
+    fn load_ref(&mut self) -> Result<usize, &'static str> {
+        let target = *self.cell_ref().refs.get(self.next_ref).ok_or("Read past the end of a cell's references")?;
+        self.next_ref += 1;
+        Ok(target)
+    }
+
+    /// A `VarUInteger n`: a length in bytes, then the value
+    fn load_var_uint(&mut self, n: usize) -> Result<u128, &'static str> {
+        let len_bits = usize::BITS as usize - (n - 1).leading_zeros() as usize;
+        let len = self.load_uint(len_bits)? as usize;
+        if len > 16 {
+            return Err("VarUInteger does not fit in 128 bits");
+        }
+        (0..len).try_fold(0u128, |acc, _| Ok((acc << 8) | self.load_uint(8)? as u128))
+    }
+
+    /// A number of at most `max`, `#<= max` in TL-B
+    fn load_uint_leq(&mut self, max: u64) -> Result<u64, &'static str> {
+        let bits = u64::BITS as usize - max.leading_zeros() as usize;
+        let value = self.load_uint(bits)?;
+        if value > max {
+            return Err("Number is above its bound");
+        }
+        Ok(value)
+    }
+
+    /// Fail unless every bit and reference has been read
+    fn expect_end(&self) -> Result<(), &'static str> {
+        if self.remaining_bits() != 0 || self.cell_ref().refs.len() != self.next_ref {
+            return Err("Cell has data left over");
+        }
+        Ok(())
+    }
+}
+
+/// CRC32-C, as bags of cells are checksummed
+fn crc32c(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0x82f6_3b78 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A cell to serialize into a bag
+    #[derive(Clone)]
+    struct TestCell {
+        exotic: bool,
+        bits: Vec<bool>,
+        refs: Vec<TestCell>,
+    }
+
+    impl TestCell {
+        fn new() -> Self {
+            Self { exotic: false, bits: Vec::new(), refs: Vec::new() }
+        }
+
+        fn uint(mut self, value: u64, bits: usize) -> Self {
+            self.bits.extend((0..bits).rev().map(|i| value >> i & 1 == 1));
+            self
+        }
+
+        fn bytes(mut self, bytes: &[u8]) -> Self {
+            for byte in bytes {
+                self = self.uint(*byte as u64, 8);
+            }
+            self
+        }
+
+        fn child(mut self, child: TestCell) -> Self {
+            self.refs.push(child);
+            self
+        }
+
+        fn hash_and_depth(&self) -> ([u8; 32], u16) {
+            let boc = BagOfCells::parse(&to_boc(core::slice::from_ref(self))).unwrap();
+            (boc.cell(0).hash(0), boc.cell(0).depth(0))
+        }
+
+        /// A pruned branch of level `level` standing in for `cell`
+        fn pruned(cell: &TestCell, level: u8) -> Self {
+            let (hash, depth) = cell.hash_and_depth();
+            let mut pruned = TestCell::new().uint(1, 8).uint(1 << (level - 1), 8).bytes(&hash).uint(depth as u64, 16);
+            pruned.exotic = true;
+            pruned
+        }
+
+        /// A Merkle proof wrapping `self`
+        fn merkle_proof(self) -> Self {
+            let (hash, depth) = self.hash_and_depth();
+            let mut proof = TestCell::new().uint(3, 8).bytes(&hash).uint(depth as u64, 16).child(self);
+            proof.exotic = true;
+            proof
+        }
+
+        fn address(self, workchain: i32, account_id: &[u8; 32]) -> Self {
+            self.uint(0b100, 3).uint(workchain as u8 as u64, 8).bytes(account_id)
+        }
+
+        /// A dictionary edge label in the long form
+        fn label(self, key: &[u8], bits: usize) -> Self {
+            let width = usize::BITS as usize - bits.leading_zeros() as usize;
+            (0..bits).fold(self.uint(0b10, 2).uint(bits as u64, width), |cell, i| {
+                cell.uint((key[i / 8] >> (7 - i % 8) & 1) as u64, 1)
+            })
+        }
+
+        fn flatten(&self, out: &mut Vec<(bool, Vec<bool>, Vec<usize>)>) -> usize {
+            let index = out.len();
+            out.push((self.exotic, self.bits.clone(), Vec::new()));
+            let refs = self.refs.iter().map(|child| child.flatten(out)).collect();
+            out[index].2 = refs;
+            index
+        }
+    }
+
+    /// Serialize trees as the roots of one bag, with a checksum
+    fn to_boc(roots: &[TestCell]) -> Vec<u8> {
+        let mut cells = Vec::new();
+        let root_indexes: Vec<usize> = roots.iter().map(|root| root.flatten(&mut cells)).collect();
+        let mut data = Vec::new();
+        for (exotic, bits, refs) in &cells {
+            data.push(refs.len() as u8 + 8 * *exotic as u8);
+            data.push((bits.len() / 8 + bits.len().div_ceil(8)) as u8);
+            let mut bytes = vec![0u8; bits.len().div_ceil(8)];
+            for (i, bit) in bits.iter().enumerate() {
+                bytes[i / 8] |= (*bit as u8) << (7 - i % 8);
+            }
+            if !bits.len().is_multiple_of(8) {
+                bytes[bits.len() / 8] |= 0x80 >> (bits.len() % 8);
+            }
+            data.extend_from_slice(&bytes);
+            data.extend(refs.iter().map(|r| *r as u8));
+        }
+        let mut boc = BOC_MAGIC.to_vec();
+        boc.extend_from_slice(&[0x41, 2, cells.len() as u8, roots.len() as u8, 0]);
+        boc.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        boc.extend(root_indexes.iter().map(|r| *r as u8));
+        boc.extend_from_slice(&data);
+        let crc = crc32c(&boc);
+        boc.extend_from_slice(&crc.to_le_bytes());
+        boc
+    }
+
+    fn shard_ident(cell: TestCell, workchain: i32, shard: u64) -> TestCell {
+        let prefix_bits = 63 - shard.trailing_zeros();
+        let cell = cell.uint(0, 2).uint(prefix_bits as u64, 6).uint(workchain as u32 as u64, 32);
+        cell.uint(shard & !(FULL_SHARD >> prefix_bits), 64)
+    }
+
+    /// A shard state holding one account, or a masterchain state recording a shard block
+    fn shard_state(block: &BlockIdExt, account: Option<(&[u8; 32], &TestCell)>, shard: Option<&BlockIdExt>) -> TestCell {
+        let mut state = shard_ident(TestCell::new().uint(SHARD_STATE_TAG, 32).uint(239, 32), block.workchain, block.shard);
+        state = state.uint(block.seqno as u64, 32).uint(0, 32).uint(1_700_000_000, 32).uint(42, 64).uint(1, 32);
+        state = state.child(TestCell::pruned(&TestCell::new().bytes(b"out messages"), 1)).uint(0, 1);
+        let accounts = match account {
+            Some((account_id, account)) => {
+                let leaf = TestCell::new().label(account_id, 256).uint(0, 5).uint(0, 4).uint(0, 1);
+                let leaf = leaf.child(account.clone()).bytes(&[0x77; 32]).uint(41_000_000_000_001, 64);
+                TestCell::new().uint(1, 1).child(leaf).uint(0, 10)
+            }
+            None => TestCell::new().uint(0, 1).uint(0, 10),
+        };
+        state = state.child(accounts).child(TestCell::pruned(&TestCell::new().bytes(b"overload history"), 1));
+        match shard {
+            Some(shard) => {
+                let mut descr = TestCell::new().uint(0, 1).uint(0xb, 4).uint(shard.seqno as u64, 32).uint(7, 32);
+                descr = descr.uint(0, 64).uint(0, 64).bytes(&shard.root_hash).bytes(&shard.file_hash);
+                let leaf = TestCell::new().label(&shard.workchain.to_be_bytes(), 32).child(descr);
+                state.uint(1, 1).child(TestCell::new().uint(MC_STATE_EXTRA_TAG, 16).uint(1, 1).child(leaf))
+            }
+            None => state.uint(0, 1),
+        }
+    }
+
+    /// A block whose state update leads to `state`, its root hash filled into `block`
+    fn block(block: &mut BlockIdExt, state: &TestCell) -> TestCell {
+        let mut info = TestCell::new().uint(BLOCK_INFO_TAG, 32).uint(0, 32).uint(0, 16).uint(block.seqno as u64, 32);
+        info = shard_ident(info.uint(0, 32), block.workchain, block.shard);
+        let old = TestCell::new().bytes(b"previous state");
+        let ((old_hash, old_depth), (new_hash, new_depth)) = (old.hash_and_depth(), state.hash_and_depth());
+        let mut update = TestCell::new().uint(4, 8).bytes(&old_hash).bytes(&new_hash);
+        update = update.uint(old_depth as u64, 16).uint(new_depth as u64, 16);
+        update = update.child(TestCell::pruned(&old, 2)).child(TestCell::pruned(state, 2));
+        update.exotic = true;
+        let value_flow = TestCell::pruned(&TestCell::new().bytes(b"value flow"), 1);
+        let block_cell = TestCell::new().uint(BLOCK_TAG, 32).uint(239, 32).child(info).child(value_flow).child(update);
+        block.root_hash = block_cell.hash_and_depth().0;
+        block_cell
+    }
+
+    /// Wallet data: balance:Coins, then config:(Maybe ^Config) with fee_bps:uint16 after a 32-bit tag
+    fn wallet_data(with_config: bool) -> TestCell {
+        let data = TestCell::new().uint(4, 4).uint(1_000_000_000, 32);
+        match with_config {
+            true => data.uint(1, 1).child(TestCell::new().uint(0x3f54_76ca, 32).uint(250, 16)),
+            false => data.uint(0, 1),
+        }
+    }
+
+    /// An active account holding `data`, its storage statistics in the current shape
+    fn account(workchain: i32, account_id: &[u8; 32], data: TestCell) -> TestCell {
+        let mut account = TestCell::new().uint(1, 1).address(workchain, account_id);
+        account = account.uint(1, 3).uint(12, 8).uint(2, 3).uint(4000, 16).uint(0b000, 3);
+        account = account.uint(1_700_000_000, 32).uint(0, 1).uint(41_000_000_000_001, 64);
+        account = account.uint(4, 4).uint(2_500_000_000, 32).uint(0, 1);
+        account = account.uint(0b1001, 4).child(TestCell::new().bytes(b"wallet code"));
+        account.uint(0b10, 2).child(data)
+    }
+
+    fn block_id(workchain: i32, seqno: u32) -> BlockIdExt {
+        BlockIdExt { workchain, shard: FULL_SHARD, seqno, root_hash: [0; 32], file_hash: [seqno as u8; 32] }
+    }
+
+    /// The witness for a field of a basechain wallet at masterchain block 100
+    pub(crate) fn wallet_witness(with_config: bool, location: FieldLocation) -> TonWitness {
+        let account_id = [0x44; 32];
+        let account = account(0, &account_id, wallet_data(with_config));
+
+        let mut shard_block = block_id(0, 5000);
+        let state = shard_state(&shard_block, Some((&account_id, &account)), None);
+        let block_cell = block(&mut shard_block, &state);
+        let proof = to_boc(&[block_cell.merkle_proof(), state.merkle_proof()]);
+
+        let mut mc_block = block_id(MASTERCHAIN, 100);
+        let mc_state = shard_state(&mc_block, None, Some(&shard_block));
+        let mc_block_cell = block(&mut mc_block, &mc_state);
+        let shard_proof = to_boc(&[mc_block_cell.merkle_proof(), mc_state.merkle_proof()]);
+
+        TonWitness {
+            mc_block,
+            shard_block,
+            workchain: 0,
+            account_id,
+            shard_proof,
+            proof,
+            state: to_boc(&[account]),
+            location,
+        }
+    }
+
+    /// The wallet's balance, at the start of its data
+    pub(crate) fn balance() -> FieldLocation {
+        FieldLocation { refs: Vec::new(), bit: 0, next_ref: 0, type_name: "Coins".into() }
+    }
+
+    /// The config's fee, past the config's tag
+    pub(crate) fn fee_bps() -> FieldLocation {
+        FieldLocation { refs: alloc::vec![0], bit: 32, next_ref: 0, type_name: "uint16".into() }
+    }
+
+    #[test]
+    fn test_witness_verification() {
+        let witness = wallet_witness(true, fee_bps());
+        assert_eq!(TonWitness::parse(&witness.to_bytes()).unwrap(), witness);
+        let field = witness.verify().unwrap();
+        assert_eq!((field.last_trans_lt, field.last_trans_hash), (41_000_000_000_001, [0x77; 32]));
+        assert_eq!(field.value.unwrap()[30..], [0, 250]);
+        let coins = wallet_witness(true, balance()).verify().unwrap().value.unwrap();
+        assert_eq!(coins[28..], 1_000_000_000u32.to_be_bytes());
+
+        // The config behind its Maybe, set and unset
+        let config = FieldLocation { bit: 36, type_name: "(Maybe ^Config)".into(), ..balance() };
+        assert_eq!(wallet_witness(false, config.clone()).verify().unwrap().value, None);
+        assert!(wallet_witness(true, config).verify().unwrap().value.is_some());
+
+        // Another account's state, and a shard block the masterchain does not record
+        let mut other_state = witness.clone();
+        other_state.state = wallet_witness(false, fee_bps()).state;
+        assert!(other_state.verify().is_err());
+        let mut other_shard = witness.clone();
+        other_shard.shard_block.file_hash[0] ^= 1;
+        assert!(other_shard.verify().is_err());
+        let mut other_account = witness.clone();
+        other_account.account_id[31] ^= 1;
+        assert!(other_account.verify().is_err());
+        let mut no_link = witness.clone();
+        no_link.shard_proof.clear();
+        assert!(no_link.verify().is_err());
+
+        // Locations outside the data, and types that cannot be read there
+        let mut past_end = witness.clone();
+        past_end.location.bit = 40;
+        assert!(past_end.verify().is_err());
+        let mut no_ref = witness;
+        no_ref.location.refs = alloc::vec![1];
+        assert!(no_ref.verify().is_err());
+        assert!(wallet_witness(true, FieldLocation { type_name: "Config".into(), ..balance() }).verify().is_err());
+    }
+
+    #[test]
+    fn test_cell_hashes() {
+        let empty = BagOfCells::parse(&to_boc(&[TestCell::new()])).unwrap();
+        assert_eq!(
+            hex::encode(empty.cell(0).hash(0)),
+            "96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7"
+        );
+        assert_eq!(crc32c(b"123456789"), 0xe306_9283);
+
+        // A pruned tree keeps the full tree's level-0 hash
+        let subtree = TestCell::new().uint(5, 3).child(TestCell::new().bytes(&[7; 40]));
+        let full = TestCell::new().uint(0x1234, 16).child(subtree.clone());
+        let pruned = TestCell::new().uint(0x1234, 16).child(TestCell::pruned(&subtree, 1));
+        let proof = BagOfCells::parse(&to_boc(&[pruned.merkle_proof()])).unwrap();
+        let inner = proof.open_merkle_proof(0, &full.hash_and_depth().0).unwrap();
+        assert_eq!(proof.cell(inner).level_mask, 1);
+        assert!(proof.slice(proof.cell(inner).refs[0]).is_err());
+    }
+}
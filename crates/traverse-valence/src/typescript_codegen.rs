@@ -0,0 +1,430 @@
+//! TypeScript type generation for the JSON request contract
+//!
+//! Frontends and relayers build the JSON documents consumed by the controller
+//! (`StorageVerificationRequest`, `SolanaAccountVerificationRequest`, ...). This
+//! module emits matching TypeScript interfaces, and optionally zod validators,
+//! from a single description of those types so both sides stay in sync.
+//!
+//! `u64` fields (block numbers, slots, lamports) can exceed
+//! `Number.MAX_SAFE_INTEGER`, so the Rust types write them as decimal strings
+//! (see [`crate::messages::decimal`]) and they are typed `string`; the zod
+//! validators accept digits only. Convert with `BigInt(value)` to do
+//! arithmetic.
+
+use alloc::{format, string::String};
+
+/// TypeScript representation of a JSON field type
+#[derive(Debug, Clone, Copy)]
+enum TsType {
+    String,
+    /// `u8` and `u32` fields
+    Number,
+    /// `u64` fields, written as decimal strings
+    Decimal,
+    Boolean,
+    Ref(&'static str),
+    Array(&'static TsType),
+}
+
+impl TsType {
+    fn ts(&self) -> String {
+        match self {
+            TsType::String => "string".into(),
+            TsType::Number => "number".into(),
+            TsType::Decimal => "string".into(),
+            TsType::Boolean => "boolean".into(),
+            TsType::Ref(name) => (*name).into(),
+            TsType::Array(inner) => format!("{}[]", inner.ts()),
+        }
+    }
+
+    fn zod(&self) -> String {
+        match self {
+            TsType::String => "z.string()".into(),
+            TsType::Number => "z.number().int().nonnegative()".into(),
+            TsType::Decimal => "z.string().regex(/^\\d+$/)".into(),
+            TsType::Boolean => "z.boolean()".into(),
+            TsType::Ref(name) => format!("{}Schema", name),
+            TsType::Array(inner) => format!("z.array({})", inner.zod()),
+        }
+    }
+}
+
+/// A single interface field
+struct TsField {
+    name: &'static str,
+    ty: TsType,
+    /// `Option<T>` on the Rust side: serialized as `null`, accepted when missing
+    optional: bool,
+    doc: &'static str,
+}
+
+/// A generated interface
+struct TsInterface {
+    name: &'static str,
+    doc: &'static str,
+    fields: &'static [TsField],
+}
+
+/// A type alias for a shape shared between ecosystems
+struct TsAlias {
+    name: &'static str,
+    target: &'static str,
+    doc: &'static str,
+}
+
+const fn field(name: &'static str, ty: TsType, doc: &'static str) -> TsField {
+    TsField { name, ty, optional: false, doc }
+}
+
+const fn optional(name: &'static str, ty: TsType, doc: &'static str) -> TsField {
+    TsField { name, ty, optional: true, doc }
+}
+
+/// Interfaces in dependency order (referenced types come first)
+const INTERFACES: &[TsInterface] = &[
+    TsInterface {
+        name: "CoprocessorStorageQuery",
+        doc: "Storage query generated by traverse-cli",
+        fields: &[
+            field("query", TsType::String, "Original query string"),
+            field("storage_key", TsType::String, "Pre-computed storage key (hex encoded)"),
+            field("layout_commitment", TsType::String, "Layout commitment for verification (hex encoded)"),
+            optional("field_size", TsType::Number, "Field size in bytes"),
+            optional("offset", TsType::Number, "Byte offset within storage slot"),
+        ],
+    },
+    TsInterface {
+        name: "StorageProof",
+        doc: "Storage proof returned by eth_getProof",
+        fields: &[
+            field("key", TsType::String, "Storage key (hex encoded)"),
+            field("value", TsType::String, "Storage value (hex encoded)"),
+            field("proof", TsType::Array(&TsType::String), "Merkle proof nodes (hex encoded)"),
+        ],
+    },
+    TsInterface {
+        name: "StorageVerificationRequest",
+        doc: "Complete storage verification request",
+        fields: &[
+            field("storage_query", TsType::Ref("CoprocessorStorageQuery"), "Storage query generated by traverse-cli"),
+            field("storage_proof", TsType::Ref("StorageProof"), "Storage proof from the chain RPC"),
+            optional("contract_address", TsType::String, "Contract address for additional validation"),
+            optional("block_number", TsType::Decimal, "Block number the proof was fetched at"),
+        ],
+    },
+    TsInterface {
+        name: "BatchStorageVerificationRequest",
+        doc: "Batch of storage verification requests",
+        fields: &[
+            field("storage_batch", TsType::Array(&TsType::Ref("StorageVerificationRequest")), "Individual verification requests"),
+            optional("contract_address", TsType::String, "Common contract address"),
+            optional("block_number", TsType::Decimal, "Common block number"),
+        ],
+    },
+    TsInterface {
+        name: "MultiSlotVerificationRequest",
+        doc: "Storage verification for many slots of one contract sharing one account proof",
+        fields: &[
+            field("storage_batch", TsType::Array(&TsType::Ref("StorageVerificationRequest")), "Storage queries and proofs against storage_hash"),
+            field("contract_address", TsType::String, "Contract whose storage is proven"),
+            optional("block_number", TsType::Decimal, "Block number the proofs were fetched at"),
+            field("account_proof", TsType::Array(&TsType::String), "Account proof nodes from the state root (hex encoded)"),
+            field("storage_hash", TsType::String, "Storage root the account proof commits to (hex encoded)"),
+        ],
+    },
+    TsInterface {
+        name: "SolanaAccountQuery",
+        doc: "Solana account query generated by traverse-cli",
+        fields: &[
+            field("query", TsType::String, "Original query string"),
+            field("account_address", TsType::String, "Account address (base58 encoded)"),
+            field("program_id", TsType::String, "Owning program id (base58 encoded)"),
+            optional("discriminator", TsType::String, "Account discriminator (hex encoded)"),
+            optional("field_offset", TsType::Number, "Byte offset of the field within account data"),
+            optional("field_size", TsType::Number, "Field size in bytes"),
+        ],
+    },
+    TsInterface {
+        name: "SolanaAccountProof",
+        doc: "Solana account data and context from RPC",
+        fields: &[
+            field("address", TsType::String, "Account address (base58 encoded)"),
+            field("data", TsType::String, "Account data (base64 encoded)"),
+            field("owner", TsType::String, "Account owner (base58 encoded)"),
+            field("lamports", TsType::Decimal, "Account balance in lamports"),
+            field("rent_epoch", TsType::Decimal, "Rent epoch"),
+            field("slot", TsType::Decimal, "Slot the account was read at"),
+            field("block_hash", TsType::String, "Block hash for the slot (base58 encoded)"),
+        ],
+    },
+    TsInterface {
+        name: "SolanaAccountVerificationRequest",
+        doc: "Complete Solana account verification request",
+        fields: &[
+            field("account_query", TsType::Ref("SolanaAccountQuery"), "Account query generated by traverse-cli"),
+            field("account_proof", TsType::Ref("SolanaAccountProof"), "Account proof from Solana RPC"),
+            optional("program_address", TsType::String, "Program address for additional validation"),
+            optional("slot", TsType::Decimal, "Slot number for proof validation"),
+        ],
+    },
+    TsInterface {
+        name: "BatchSolanaAccountVerificationRequest",
+        doc: "Batch of Solana account verification requests",
+        fields: &[
+            field("account_batch", TsType::Array(&TsType::Ref("SolanaAccountVerificationRequest")), "Individual verification requests"),
+            optional("program_address", TsType::String, "Common program address"),
+            optional("slot", TsType::Decimal, "Common slot"),
+        ],
+    },
+    TsInterface {
+        name: "StorageProofValidationResult",
+        doc: "Storage proof validation result",
+        fields: &[
+            field("is_valid", TsType::Boolean, "Whether the storage proof is valid"),
+            field("storage_value", TsType::String, "Extracted storage value (hex encoded)"),
+            field("storage_key", TsType::String, "Storage key that was verified (hex encoded)"),
+            field("layout_commitment", TsType::String, "Layout commitment used for verification"),
+            optional("metadata", TsType::String, "Additional validation metadata"),
+        ],
+    },
+    TsInterface {
+        name: "SolanaAccountValidationResult",
+        doc: "Solana account proof validation result",
+        fields: &[
+            field("is_valid", TsType::Boolean, "Whether the account proof is valid"),
+            field("account_value", TsType::String, "Extracted value from account data (hex encoded)"),
+            field("account_address", TsType::String, "Account address that was verified (base58 encoded)"),
+            field("program_id", TsType::String, "Program id that owns the account (base58 encoded)"),
+            field("slot", TsType::Decimal, "Slot when the proof was generated"),
+            optional("metadata", TsType::String, "Additional validation metadata"),
+        ],
+    },
+];
+
+/// Cosmos requests reuse the storage request shape with raw store keys
+const ALIASES: &[TsAlias] = &[
+    TsAlias {
+        name: "CosmosStorageVerificationRequest",
+        target: "StorageVerificationRequest",
+        doc: "Cosmos storage verification request (storage_key is the hex encoded raw store key)",
+    },
+    TsAlias {
+        name: "BatchCosmosStorageVerificationRequest",
+        target: "BatchStorageVerificationRequest",
+        doc: "Batch of Cosmos storage verification requests",
+    },
+];
+
+/// Generate TypeScript interfaces for the coprocessor JSON contract
+///
+/// When `include_zod` is set, a zod schema named `<Type>Schema` is emitted for
+/// each interface as well; the generated module then imports `zod`.
+pub fn generate_typescript_types(include_zod: bool) -> String {
+    let mut code = String::new();
+
+    code.push_str("// Generated by traverse-valence. Do not edit by hand.\n\n");
+    if include_zod {
+        code.push_str("import { z } from \"zod\";\n\n");
+    }
+
+    for interface in INTERFACES {
+        code.push_str(&format!("/** {} */\nexport interface {} {{\n", interface.doc, interface.name));
+        for f in interface.fields {
+            code.push_str(&format!("  /** {} */\n", f.doc));
+            if f.optional {
+                code.push_str(&format!("  {}?: {} | null;\n", f.name, f.ty.ts()));
+            } else {
+                code.push_str(&format!("  {}: {};\n", f.name, f.ty.ts()));
+            }
+        }
+        code.push_str("}\n\n");
+    }
+
+    for alias in ALIASES {
+        code.push_str(&format!("/** {} */\nexport type {} = {};\n\n", alias.doc, alias.name, alias.target));
+    }
+
+    if include_zod {
+        for interface in INTERFACES {
+            code.push_str(&format!(
+                "export const {}Schema: z.ZodType<{}> = z.object({{\n",
+                interface.name, interface.name
+            ));
+            for f in interface.fields {
+                if f.optional {
+                    code.push_str(&format!("  {}: {}.nullish(),\n", f.name, f.ty.zod()));
+                } else {
+                    code.push_str(&format!("  {}: {},\n", f.name, f.ty.zod()));
+                }
+            }
+            code.push_str("});\n\n");
+        }
+
+        for alias in ALIASES {
+            code.push_str(&format!("export const {}Schema = {}Schema;\n\n", alias.name, alias.target));
+        }
+    }
+
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        BatchSolanaAccountVerificationRequest, BatchStorageVerificationRequest, CoprocessorStorageQuery,
+        MultiSlotVerificationRequest, SolanaAccountProof, SolanaAccountQuery, SolanaAccountValidationResult,
+        SolanaAccountVerificationRequest, StorageProof, StorageProofValidationResult, StorageVerificationRequest,
+    };
+    use alloc::{collections::BTreeSet, string::ToString, vec, vec::Vec};
+    use serde_json::Value;
+
+    /// Check an interface against two serialized Rust values, recursing into references
+    ///
+    /// In `full` every `Option` is `Some`, every vector has an element and every
+    /// integer is its type's maximum; in `empty` every `Option` is `None`.
+    fn check_interface(name: &str, full: &Value, empty: &Value, checked: &mut BTreeSet<String>) {
+        let interface = INTERFACES.iter().find(|i| i.name == name).unwrap_or_else(|| panic!("no interface {}", name));
+        checked.insert(name.to_string());
+
+        let keys: BTreeSet<&str> = full.as_object().unwrap().keys().map(String::as_str).collect();
+        let fields: BTreeSet<&str> = interface.fields.iter().map(|f| f.name).collect();
+        assert_eq!(fields, keys, "{} fields do not match the Rust type", name);
+
+        for f in interface.fields {
+            assert_eq!(f.optional, empty[f.name].is_null(), "{}.{} optionality", name, f.name);
+            check_type(&format!("{}.{}", name, f.name), &f.ty, &full[f.name], &empty[f.name], checked);
+        }
+    }
+
+    fn check_type(path: &str, ty: &TsType, full: &Value, empty: &Value, checked: &mut BTreeSet<String>) {
+        match ty {
+            TsType::String => assert!(full.is_string(), "{} is not a string", path),
+            TsType::Boolean => assert!(full.is_boolean(), "{} is not a boolean", path),
+            // Maximum values of u8 and u32 fit a number, u64's does not
+            TsType::Number => assert!(full.as_u64().is_some_and(|n| n <= u32::MAX as u64), "{} is not a number", path),
+            TsType::Decimal => assert_eq!(full.as_str(), Some("18446744073709551615"), "{} is not a decimal u64", path),
+            TsType::Ref(name) => check_interface(name, full, empty, checked),
+            TsType::Array(inner) => {
+                let element = &full.as_array().unwrap_or_else(|| panic!("{} is not an array", path))[0];
+                let empty_element = empty.as_array().and_then(|a| a.first()).unwrap_or(element);
+                check_type(path, inner, element, empty_element, checked);
+            }
+        }
+    }
+
+    fn storage_request(full: bool) -> StorageVerificationRequest {
+        StorageVerificationRequest {
+            storage_query: CoprocessorStorageQuery {
+                query: "balances[0x0]".into(),
+                storage_key: "00".into(),
+                layout_commitment: "00".into(),
+                field_size: full.then_some(u8::MAX),
+                offset: full.then_some(u8::MAX),
+            },
+            storage_proof: StorageProof { key: "00".into(), value: "00".into(), proof: vec!["00".into()] },
+            contract_address: full.then(|| "0x00".into()),
+            block_number: full.then_some(u64::MAX),
+        }
+    }
+
+    fn solana_request(full: bool) -> SolanaAccountVerificationRequest {
+        SolanaAccountVerificationRequest {
+            account_query: SolanaAccountQuery {
+                query: "amount".into(),
+                account_address: "addr".into(),
+                program_id: "prog".into(),
+                discriminator: full.then(|| "00".into()),
+                field_offset: full.then_some(u32::MAX),
+                field_size: full.then_some(u32::MAX),
+            },
+            account_proof: SolanaAccountProof {
+                address: "addr".into(),
+                data: "".into(),
+                owner: "prog".into(),
+                lamports: u64::MAX,
+                rent_epoch: u64::MAX,
+                slot: u64::MAX,
+                block_hash: "hash".into(),
+            },
+            program_address: full.then(|| "prog".into()),
+            slot: full.then_some(u64::MAX),
+        }
+    }
+
+    fn json<T: serde::Serialize>(value: &T) -> Value {
+        serde_json::to_value(value).unwrap()
+    }
+
+    /// Serialized `full` and `empty` samples of every interface's Rust type
+    fn samples() -> Vec<(&'static str, Value, Value)> {
+        let storage_batch = |full: bool| BatchStorageVerificationRequest {
+            storage_batch: vec![storage_request(full)],
+            contract_address: full.then(|| "0x00".into()),
+            block_number: full.then_some(u64::MAX),
+        };
+        let multi_slot = |full: bool| MultiSlotVerificationRequest {
+            storage_batch: vec![storage_request(full)],
+            contract_address: "0x00".into(),
+            block_number: full.then_some(u64::MAX),
+            account_proof: vec!["00".into()],
+            storage_hash: "00".into(),
+        };
+        let solana_batch = |full: bool| BatchSolanaAccountVerificationRequest {
+            account_batch: vec![solana_request(full)],
+            program_address: full.then(|| "prog".into()),
+            slot: full.then_some(u64::MAX),
+        };
+        let storage_result = |full: bool| StorageProofValidationResult {
+            is_valid: true,
+            storage_value: "00".into(),
+            storage_key: "00".into(),
+            layout_commitment: "00".into(),
+            metadata: full.then(|| "ok".into()),
+        };
+        let solana_result = |full: bool| SolanaAccountValidationResult {
+            is_valid: true,
+            account_value: "00".into(),
+            account_address: "addr".into(),
+            program_id: "prog".into(),
+            slot: u64::MAX,
+            metadata: full.then(|| "ok".into()),
+        };
+
+        vec![
+            ("BatchStorageVerificationRequest", json(&storage_batch(true)), json(&storage_batch(false))),
+            ("MultiSlotVerificationRequest", json(&multi_slot(true)), json(&multi_slot(false))),
+            ("BatchSolanaAccountVerificationRequest", json(&solana_batch(true)), json(&solana_batch(false))),
+            ("StorageProofValidationResult", json(&storage_result(true)), json(&storage_result(false))),
+            ("SolanaAccountValidationResult", json(&solana_result(true)), json(&solana_result(false))),
+        ]
+    }
+
+    #[test]
+    fn test_interfaces_match_rust_types() {
+        let mut checked = BTreeSet::new();
+        for (name, full, empty) in samples() {
+            check_interface(name, &full, &empty, &mut checked);
+        }
+        let all: BTreeSet<String> = INTERFACES.iter().map(|i| i.name.to_string()).collect();
+        assert_eq!(checked, all, "every interface is checked against its Rust type");
+
+        let code = generate_typescript_types(false);
+        assert!(code.contains("  block_number?: string | null;"));
+        assert!(code.contains("  lamports: string;"));
+        assert!(code.contains("  field_size?: number | null;"));
+        assert!(code.contains("export type CosmosStorageVerificationRequest = StorageVerificationRequest;"));
+        assert!(!code.contains("zod"));
+    }
+
+    #[test]
+    fn test_zod_validators() {
+        let code = generate_typescript_types(true);
+        assert!(code.contains("import { z } from \"zod\";"));
+        assert!(code.contains("export const StorageVerificationRequestSchema: z.ZodType<StorageVerificationRequest>"));
+        assert!(code.contains("storage_query: CoprocessorStorageQuerySchema,"));
+        assert!(code.contains("block_number: z.string().regex(/^\\d+$/).nullish(),"));
+        assert!(code.contains("storage_batch: z.array(StorageVerificationRequestSchema),"));
+    }
+}
@@ -0,0 +1,236 @@
+//! Verkle witness verification for circuits (no_std compatible)
+//!
+//! After the Verkle transition a storage value is proven by a block's
+//! execution witness rather than an MPT proof. A [`VerkleWitness`] carries
+//! the leaf the circuit reads, the claimed pre-state value and the serialized
+//! execution witness from `traverse_ethereum::verkle::ExecutionWitness::to_bytes`.
+//!
+//! The multiproof is an IPA over Bandersnatch commitments, which the circuit
+//! environment supplies through [`VerkleVerifier`]; this module checks
+//! everything around it: that the witness is for the expected leaf and root,
+//! and that the execution witness really contains the claimed value. Until a
+//! Bandersnatch implementation is available, [`UnavailableBandersnatch`]
+//! rejects every multiproof with an error saying so.
+
+use alloc::vec::Vec;
+
+/// Fixed part of an encoded witness: stem, suffix, presence, value, root, height, proof length
+const FIXED_LEN: usize = 31 + 1 + 1 + 32 + 32 + 8 + 4;
+
+/// Multiproof verification supplied by the circuit's Bandersnatch implementation
+pub trait VerkleVerifier {
+    /// Check that `execution_witness` proves its pre-state values against `state_root`
+    fn verify_multiproof(&self, state_root: &[u8; 32], execution_witness: &[u8]) -> Result<(), &'static str>;
+}
+
+/// Verifier used when the circuit has no Bandersnatch implementation
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnavailableBandersnatch;
+
+impl VerkleVerifier for UnavailableBandersnatch {
+    fn verify_multiproof(&self, _state_root: &[u8; 32], _execution_witness: &[u8]) -> Result<(), &'static str> {
+        Err("Bandersnatch backend unavailable: cannot verify the Verkle multiproof")
+    }
+}
+
+/// Proof of one Verkle leaf for a circuit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerkleWitness {
+    /// Stem of the leaf, from the slot's tree key
+    pub stem: [u8; 31],
+    pub suffix: u8,
+    /// Pre-state value, `None` if the leaf is absent
+    pub value: Option<[u8; 32]>,
+    /// State root of the parent block, which the execution witness proves against
+    pub state_root: [u8; 32],
+    pub block_height: u64,
+    /// Serialized execution witness of the block
+    pub execution_witness: Vec<u8>,
+}
+
+impl VerkleWitness {
+    /// Encode as stem, suffix, presence byte, value (zero when absent),
+    /// state root, block height and witness length (little-endian), witness
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(FIXED_LEN + self.execution_witness.len());
+        out.extend_from_slice(&self.stem);
+        out.push(self.suffix);
+        out.push(self.value.is_some() as u8);
+        out.extend_from_slice(&self.value.unwrap_or([0u8; 32]));
+        out.extend_from_slice(&self.state_root);
+        out.extend_from_slice(&self.block_height.to_le_bytes());
+        out.extend_from_slice(&(self.execution_witness.len() as u32).to_le_bytes());
+        out.extend_from_slice(&self.execution_witness);
+        out
+    }
+
+    /// Decode the [`VerkleWitness::to_bytes`] encoding
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        if bytes.len() < FIXED_LEN {
+            return Err("Verkle witness too short");
+        }
+        let fixed = |start: usize, end: usize| &bytes[start..end];
+        let proof_len = u32::from_le_bytes(fixed(105, 109).try_into().unwrap()) as usize;
+        if bytes.len() != FIXED_LEN + proof_len {
+            return Err("Verkle witness length does not match its execution witness length");
+        }
+
+        let value = match bytes[32] {
+            0 => None,
+            1 => Some(fixed(33, 65).try_into().unwrap()),
+            _ => return Err("invalid Verkle value presence byte"),
+        };
+        Ok(Self {
+            stem: fixed(0, 31).try_into().unwrap(),
+            suffix: bytes[31],
+            value,
+            state_root: fixed(65, 97).try_into().unwrap(),
+            block_height: u64::from_le_bytes(fixed(97, 105).try_into().unwrap()),
+            execution_witness: bytes[FIXED_LEN..].to_vec(),
+        })
+    }
+}
+
+/// Reader over a serialized execution witness
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], &'static str> {
+        let end = self.offset.checked_add(len).filter(|end| *end <= self.bytes.len());
+        let slice = &self.bytes[self.offset..end.ok_or("execution witness truncated")?];
+        self.offset += len;
+        Ok(slice)
+    }
+}
+
+/// Pre-state value of (`stem`, `suffix`) in a serialized execution witness
+///
+/// The outer `Option` is whether the witness covers the leaf, the inner one
+/// whether the leaf was present.
+fn witness_pre_state(execution_witness: &[u8], stem: &[u8; 31], suffix: u8) -> Result<Option<Option<[u8; 32]>>, &'static str> {
+    let mut reader = Reader { bytes: execution_witness, offset: 0 };
+    let stems = u32::from_le_bytes(reader.take(4)?.try_into().unwrap());
+
+    let mut found = None;
+    for _ in 0..stems {
+        let this_stem = reader.take(31)?;
+        let suffixes = u16::from_le_bytes(reader.take(2)?.try_into().unwrap());
+        for _ in 0..suffixes {
+            let this_suffix = reader.take(1)?[0];
+            let presence = reader.take(1)?[0];
+            if presence > 3 {
+                return Err("invalid presence byte in execution witness");
+            }
+            let current = if presence & 1 == 1 { Some(<[u8; 32]>::try_from(reader.take(32)?).unwrap()) } else { None };
+            if presence & 2 == 2 {
+                reader.take(32)?;
+            }
+            if this_stem == stem && this_suffix == suffix {
+                found = Some(current);
+            }
+        }
+    }
+    Ok(found)
+}
+
+/// Verify a Verkle witness for the leaf at (`expected_stem`, `expected_suffix`)
+///
+/// Checks the witness is for the expected leaf and parent state root, that
+/// its execution witness covers the leaf with the claimed value, and finally
+/// the multiproof. Returns the proven value, zero for an absent leaf.
+pub fn verify_verkle_witness(
+    witness: &VerkleWitness,
+    expected_stem: &[u8; 31],
+    expected_suffix: u8,
+    expected_state_root: &[u8; 32],
+    verifier: &impl VerkleVerifier,
+) -> Result<[u8; 32], &'static str> {
+    if witness.stem != *expected_stem || witness.suffix != expected_suffix {
+        return Err("Verkle witness is for a different leaf");
+    }
+    if witness.state_root != *expected_state_root {
+        return Err("Verkle witness is for a different state root");
+    }
+
+    let proven = witness_pre_state(&witness.execution_witness, expected_stem, expected_suffix)?
+        .ok_or("execution witness does not cover the leaf")?;
+    if proven != witness.value {
+        return Err("claimed value differs from the execution witness");
+    }
+    verifier.verify_multiproof(expected_state_root, &witness.execution_witness)?;
+    Ok(proven.unwrap_or([0u8; 32]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct AcceptRoot([u8; 32]);
+
+    impl VerkleVerifier for AcceptRoot {
+        fn verify_multiproof(&self, state_root: &[u8; 32], _execution_witness: &[u8]) -> Result<(), &'static str> {
+            if *state_root == self.0 { Ok(()) } else { Err("Verkle multiproof does not verify") }
+        }
+    }
+
+    /// One stem with a present leaf at suffix 64 and an absent one at 65, and a dummy proof tail
+    fn execution_witness() -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&1u32.to_le_bytes());
+        out.extend_from_slice(&[0x11; 31]);
+        out.extend_from_slice(&2u16.to_le_bytes());
+        out.extend_from_slice(&[64, 0b11]);
+        out.extend_from_slice(&[7u8; 32]);
+        out.extend_from_slice(&[8u8; 32]);
+        out.extend_from_slice(&[65, 0]);
+        out.extend_from_slice(&[0xee; 40]);
+        out
+    }
+
+    fn witness(suffix: u8, value: Option<[u8; 32]>) -> VerkleWitness {
+        VerkleWitness {
+            stem: [0x11; 31],
+            suffix,
+            value,
+            state_root: [0xaa; 32],
+            block_height: 100,
+            execution_witness: execution_witness(),
+        }
+    }
+
+    #[test]
+    fn test_witness_bytes_round_trip() {
+        let witness = witness(64, Some([7u8; 32]));
+        assert_eq!(VerkleWitness::from_bytes(&witness.to_bytes()).unwrap(), witness);
+
+        let mut truncated = witness.to_bytes();
+        truncated.pop();
+        assert!(VerkleWitness::from_bytes(&truncated).is_err());
+    }
+
+    #[test]
+    fn test_verify_verkle_witness() {
+        let verifier = AcceptRoot([0xaa; 32]);
+        let stem = [0x11; 31];
+        let root = [0xaa; 32];
+
+        assert_eq!(verify_verkle_witness(&witness(64, Some([7u8; 32])), &stem, 64, &root, &verifier), Ok([7u8; 32]));
+        assert_eq!(verify_verkle_witness(&witness(65, None), &stem, 65, &root, &verifier), Ok([0u8; 32]));
+
+        // The post-state value is not the proven one
+        assert!(verify_verkle_witness(&witness(64, Some([8u8; 32])), &stem, 64, &root, &verifier).is_err());
+        assert!(verify_verkle_witness(&witness(66, None), &stem, 66, &root, &verifier).is_err());
+        assert!(verify_verkle_witness(&witness(64, Some([7u8; 32])), &stem, 65, &root, &verifier).is_err());
+        assert!(verify_verkle_witness(&witness(64, Some([7u8; 32])), &stem, 64, &[0xbb; 32], &verifier).is_err());
+
+        let rejecting = AcceptRoot([0xbb; 32]);
+        assert!(verify_verkle_witness(&witness(64, Some([7u8; 32])), &stem, 64, &root, &rejecting).is_err());
+        assert_eq!(
+            verify_verkle_witness(&witness(64, Some([7u8; 32])), &stem, 64, &root, &UnavailableBandersnatch),
+            Err("Bandersnatch backend unavailable: cannot verify the Verkle multiproof")
+        );
+    }
+}
@@ -0,0 +1,276 @@
+//! Storage slot and Solana account witness formats (no_std compatible)
+//!
+//! The chain modules document the witnesses they check; these are the two
+//! the controller writes for EVM storage slots and Solana accounts, which
+//! the circuit and the CLI's witness inspector read back.
+//!
+//! ## Storage Witness Format
+//! ```text
+//! [32 bytes storage_key] +
+//! [32 bytes layout_commitment] +
+//! [32 bytes value] +
+//! [1 byte zero_semantics] +
+//! [1 byte semantic_source] +
+//! [8 bytes block_height] +
+//! [32 bytes block_hash] +
+//! [4 bytes proof_length] + [proof_length bytes proof] +
+//! [2 bytes field_index] +
+//! [32 bytes expected_slot]
+//! ```
+//!
+//! Legacy storage witnesses, written before the block binding, stop after
+//! the proof and have no block height, block hash, field index or expected
+//! slot.
+//!
+//! ## Solana Account Witness Format
+//! ```text
+//! [32 bytes account_address] + [32 bytes owner] + [32 bytes value] +
+//! [8 bytes lamports] + [8 bytes rent_epoch] + [8 bytes slot] +
+//! [32 bytes block_hash] + [4 bytes field_offset]
+//! ```
+//!
+//! Integers are little-endian.
+
+use alloc::vec::Vec;
+
+/// Offset of the proof length in a storage witness
+pub const STORAGE_PROOF_LEN_OFFSET: usize = 32 * 3 + 1 + 1 + 8 + 32;
+
+/// Length of a storage witness with no proof data
+pub const STORAGE_WITNESS_FIXED_LEN: usize = STORAGE_PROOF_LEN_OFFSET + 4 + 2 + 32;
+
+/// Offset of the proof length in a legacy storage witness
+pub const LEGACY_PROOF_LEN_OFFSET: usize = 32 * 3 + 1 + 1;
+
+/// Length of a legacy storage witness with no proof data
+pub const LEGACY_WITNESS_FIXED_LEN: usize = LEGACY_PROOF_LEN_OFFSET + 4;
+
+/// Length of a Solana account witness
+pub const SOLANA_WITNESS_LEN: usize = 32 * 3 + 8 * 3 + 32 + 4;
+
+/// Block a storage witness is read at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockBinding {
+    pub height: u64,
+    pub hash: [u8; 32],
+}
+
+/// Field a storage witness proves, checked against the slot the layout gives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldBinding {
+    pub index: u16,
+    pub expected_slot: [u8; 32],
+}
+
+/// An EVM storage slot witness
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageWitness {
+    pub storage_key: [u8; 32],
+    pub layout_commitment: [u8; 32],
+    pub value: [u8; 32],
+    /// `ZeroSemantics` discriminant, 0 to 3
+    pub zero_semantics: u8,
+    /// `SemanticSource` discriminant, 0 to 2
+    pub semantic_source: u8,
+    /// Absent in legacy witnesses
+    pub block: Option<BlockBinding>,
+    pub proof: Vec<u8>,
+    /// Absent in legacy witnesses
+    pub field: Option<FieldBinding>,
+}
+
+/// A Solana account field witness
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SolanaAccountWitness {
+    pub account_address: [u8; 32],
+    pub owner: [u8; 32],
+    /// The field's bytes, left-aligned
+    pub value: [u8; 32],
+    pub lamports: u64,
+    pub rent_epoch: u64,
+    pub slot: u64,
+    pub block_hash: [u8; 32],
+    /// Field offset within the account's data
+    pub field_offset: u32,
+}
+
+impl StorageWitness {
+    /// Whether this is a legacy witness, without block or field binding
+    pub fn is_legacy(&self) -> bool {
+        self.block.is_none()
+    }
+
+    /// Serialize in the storage witness format, or the legacy one when unbound
+    ///
+    /// A witness must have both bindings or neither.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(STORAGE_WITNESS_FIXED_LEN + self.proof.len());
+        bytes.extend_from_slice(&self.storage_key);
+        bytes.extend_from_slice(&self.layout_commitment);
+        bytes.extend_from_slice(&self.value);
+        bytes.push(self.zero_semantics);
+        bytes.push(self.semantic_source);
+        if let Some(block) = &self.block {
+            bytes.extend_from_slice(&block.height.to_le_bytes());
+            bytes.extend_from_slice(&block.hash);
+        }
+        bytes.extend_from_slice(&(self.proof.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.proof);
+        if let Some(field) = &self.field {
+            bytes.extend_from_slice(&field.index.to_le_bytes());
+            bytes.extend_from_slice(&field.expected_slot);
+        }
+        bytes
+    }
+
+    /// Parse a storage witness; every byte must be accounted for
+    pub fn parse(bytes: &[u8]) -> Result<Self, &'static str> {
+        Self::parse_with(bytes, true)
+    }
+
+    /// Parse a legacy storage witness; every byte must be accounted for
+    pub fn parse_legacy(bytes: &[u8]) -> Result<Self, &'static str> {
+        Self::parse_with(bytes, false)
+    }
+
+    fn parse_with(bytes: &[u8], bound: bool) -> Result<Self, &'static str> {
+        let mut reader = Reader { bytes, position: 0 };
+        let storage_key = reader.word()?;
+        let layout_commitment = reader.word()?;
+        let value = reader.word()?;
+        let zero_semantics = reader.u8()?;
+        if zero_semantics > 3 {
+            return Err("Invalid zero semantics byte");
+        }
+        let semantic_source = reader.u8()?;
+        if semantic_source > 2 {
+            return Err("Invalid semantic source byte");
+        }
+        let block = if bound {
+            Some(BlockBinding { height: reader.u64()?, hash: reader.word()? })
+        } else {
+            None
+        };
+        let proof_len = reader.u32()? as usize;
+        let proof = reader.take(proof_len)?.to_vec();
+        let field = if bound {
+            Some(FieldBinding { index: reader.u16()?, expected_slot: reader.word()? })
+        } else {
+            None
+        };
+        if reader.position != bytes.len() {
+            return Err("Data continues after the witness");
+        }
+        Ok(Self { storage_key, layout_commitment, value, zero_semantics, semantic_source, block, proof, field })
+    }
+}
+
+impl SolanaAccountWitness {
+    /// Serialize in the Solana account witness format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(SOLANA_WITNESS_LEN);
+        bytes.extend_from_slice(&self.account_address);
+        bytes.extend_from_slice(&self.owner);
+        bytes.extend_from_slice(&self.value);
+        bytes.extend_from_slice(&self.lamports.to_le_bytes());
+        bytes.extend_from_slice(&self.rent_epoch.to_le_bytes());
+        bytes.extend_from_slice(&self.slot.to_le_bytes());
+        bytes.extend_from_slice(&self.block_hash);
+        bytes.extend_from_slice(&self.field_offset.to_le_bytes());
+        bytes
+    }
+
+    /// Parse a Solana account witness, which must be exactly [`SOLANA_WITNESS_LEN`] bytes
+    pub fn parse(bytes: &[u8]) -> Result<Self, &'static str> {
+        if bytes.len() != SOLANA_WITNESS_LEN {
+            return Err("Solana account witness has the wrong length");
+        }
+        let mut reader = Reader { bytes, position: 0 };
+        Ok(Self {
+            account_address: reader.word()?,
+            owner: reader.word()?,
+            value: reader.word()?,
+            lamports: reader.u64()?,
+            rent_epoch: reader.u64()?,
+            slot: reader.u64()?,
+            block_hash: reader.word()?,
+            field_offset: reader.u32()?,
+        })
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], &'static str> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or("Data ends early")?;
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, &'static str> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, &'static str> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().expect("2 bytes")))
+    }
+
+    fn u32(&mut self) -> Result<u32, &'static str> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().expect("4 bytes")))
+    }
+
+    fn u64(&mut self) -> Result<u64, &'static str> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().expect("8 bytes")))
+    }
+
+    fn word(&mut self) -> Result<[u8; 32], &'static str> {
+        Ok(self.take(32)?.try_into().expect("32 bytes"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_storage_witness_offsets() {
+        let witness = StorageWitness {
+            storage_key: [0x11; 32],
+            layout_commitment: [0x22; 32],
+            value: [0; 32],
+            zero_semantics: 3,
+            semantic_source: 1,
+            block: Some(BlockBinding { height: 19_000_000, hash: [0xbb; 32] }),
+            proof: vec![1, 2, 3],
+            field: Some(FieldBinding { index: 7, expected_slot: [0x11; 32] }),
+        };
+        let bytes = witness.to_bytes();
+        assert_eq!(bytes.len(), STORAGE_WITNESS_FIXED_LEN + 3);
+        assert_eq!(bytes[STORAGE_PROOF_LEN_OFFSET..STORAGE_PROOF_LEN_OFFSET + 4], 3u32.to_le_bytes());
+        assert_eq!(StorageWitness::parse(&bytes), Ok(witness.clone()));
+
+        // The same bytes are not a legacy witness, nor is the storage witness with data after it
+        assert!(StorageWitness::parse_legacy(&bytes).is_err());
+        assert_eq!(StorageWitness::parse(&[bytes.as_slice(), &[0]].concat()), Err("Data continues after the witness"));
+
+        let legacy = StorageWitness { block: None, field: None, ..witness };
+        let bytes = legacy.to_bytes();
+        assert_eq!(bytes.len(), LEGACY_WITNESS_FIXED_LEN + 3);
+        assert_eq!(bytes[LEGACY_PROOF_LEN_OFFSET..LEGACY_PROOF_LEN_OFFSET + 4], 3u32.to_le_bytes());
+        assert!(StorageWitness::parse_legacy(&bytes).unwrap().is_legacy());
+
+        let mut bad = bytes;
+        bad[96] = 4;
+        assert_eq!(StorageWitness::parse_legacy(&bad), Err("Invalid zero semantics byte"));
+    }
+}
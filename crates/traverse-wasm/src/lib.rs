@@ -0,0 +1,92 @@
+//! WASM bindings for building storage verification requests and witnesses
+//!
+//! Browser-based relayers and dashboards can do everything between "I have
+//! a layout and an `eth_getProof` response" and "I have witnesses for the
+//! coprocessor" without a backend: resolve a query to its storage key,
+//! combine it with the proof into a `StorageVerificationRequest`, create
+//! the witness the controller would, and decode a witness to check it.
+//!
+//! Inputs and outputs are JSON strings in the formats the CLIs and
+//! `traverse-valence` use, and witnesses are hex encoded. Errors are thrown
+//! as JS `Error`s.
+//!
+//! # Usage
+//!
+//! ```js
+//! import init, { resolveQuery, buildVerificationRequest, createWitness, inspectWitness } from "traverse-wasm";
+//!
+//! await init();
+//! const query = resolveQuery(layoutJson, "balances[0x742d35Cc6634C0532925a3b8D97C2e0D8b2D9C53]");
+//! const proof = await provider.send("eth_getProof", [token, ["0x" + JSON.parse(query).storage_key], "latest"]);
+//! const request = buildVerificationRequest(query, JSON.stringify(proof.storageProof[0]), token, blockNumber);
+//! const witness = createWitness(request);
+//! console.log(JSON.parse(inspectWitness(witness)).zero_semantics);
+//! ```
+//!
+//! Build with `wasm-pack build crates/traverse-wasm --target web`.
+
+pub mod request;
+pub mod witness;
+
+use traverse_valence::{BatchStorageVerificationRequest, CoprocessorStorageQuery, StorageVerificationRequest};
+use wasm_bindgen::prelude::*;
+
+/// Error types for the bindings, thrown to JS as `Error`s
+#[derive(Debug, thiserror::Error)]
+pub enum WasmError {
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Invalid hex: {0}")]
+    Hex(#[from] hex::FromHexError),
+
+    #[error(transparent)]
+    Traverse(#[from] traverse_core::TraverseError),
+
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+
+    #[error("Witness error: {0}")]
+    Witness(String),
+}
+
+/// Resolve a query against a layout; returns the coprocessor storage query as JSON
+#[wasm_bindgen(js_name = resolveQuery)]
+pub fn resolve_query(layout_json: &str, query: &str) -> Result<String, JsError> {
+    Ok(serde_json::to_string(&request::resolve_query(layout_json, query)?)?)
+}
+
+/// Combine a storage query with its `eth_getProof` storage proof entry into a verification request
+#[wasm_bindgen(js_name = buildVerificationRequest)]
+pub fn build_verification_request(
+    storage_query_json: &str,
+    storage_proof_json: &str,
+    contract_address: Option<String>,
+    block_number: Option<u64>,
+) -> Result<String, JsError> {
+    let storage_query: CoprocessorStorageQuery = serde_json::from_str(storage_query_json)?;
+    let proof: request::ProofEntry = serde_json::from_str(storage_proof_json)?;
+    Ok(serde_json::to_string(&request::build_request(storage_query, proof, contract_address, block_number)?)?)
+}
+
+/// Create the witness for a verification request; returns it hex encoded
+#[wasm_bindgen(js_name = createWitness)]
+pub fn create_witness(request_json: &str) -> Result<String, JsError> {
+    let request: StorageVerificationRequest = serde_json::from_str(request_json)?;
+    Ok(hex::encode(witness::create_witness(&request)?))
+}
+
+/// Create the witnesses for a batch request; returns a JSON array of hex witnesses
+#[wasm_bindgen(js_name = createWitnesses)]
+pub fn create_witnesses(batch_json: &str) -> Result<String, JsError> {
+    let request: BatchStorageVerificationRequest = serde_json::from_str(batch_json)?;
+    let witnesses: Vec<String> = witness::create_witnesses(&request)?.iter().map(hex::encode).collect();
+    Ok(serde_json::to_string(&witnesses)?)
+}
+
+/// Decode a hex witness; returns its fields as JSON
+#[wasm_bindgen(js_name = inspectWitness)]
+pub fn inspect_witness(witness_hex: &str) -> Result<String, JsError> {
+    let data = hex::decode(witness_hex.strip_prefix("0x").unwrap_or(witness_hex)).map_err(WasmError::from)?;
+    Ok(serde_json::to_string(&witness::inspect_witness(&data)?)?)
+}
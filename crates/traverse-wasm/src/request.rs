@@ -0,0 +1,123 @@
+//! Storage queries and verification requests
+
+use crate::WasmError;
+use serde::Deserialize;
+use traverse_core::{Key, KeyResolver, LayoutInfo};
+use traverse_ethereum::EthereumKeyResolver;
+use traverse_valence::{CoprocessorStorageQuery, StorageProof, StorageVerificationRequest};
+
+/// One entry of `storageProof` in an `eth_getProof` response
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProofEntry {
+    pub key: String,
+    pub value: String,
+    pub proof: Vec<String>,
+}
+
+impl ProofEntry {
+    /// Whether the entry proves `storage_key`, compared as 32-byte words
+    pub fn is_for(&self, storage_key: &str) -> Result<bool, WasmError> {
+        Ok(slot_bytes(&self.key)? == slot_bytes(storage_key)?)
+    }
+}
+
+/// Resolve a query against a layout, as `traverse-ethereum resolve --format coprocessor-json` does
+pub fn resolve_query(layout_json: &str, query: &str) -> Result<CoprocessorStorageQuery, WasmError> {
+    resolve_in_layout(&serde_json::from_str(layout_json)?, query)
+}
+
+/// Resolve a query against a parsed layout
+pub fn resolve_in_layout(layout: &LayoutInfo, query: &str) -> Result<CoprocessorStorageQuery, WasmError> {
+    let path = EthereumKeyResolver.resolve(layout, query)?;
+    let key = match &path.key {
+        Key::Fixed(bytes) => bytes.as_slice(),
+        Key::Variable(bytes) => bytes.as_slice(),
+    };
+
+    Ok(CoprocessorStorageQuery {
+        query: query.to_string(),
+        storage_key: hex::encode(key),
+        layout_commitment: hex::encode(path.layout_commitment),
+        field_size: path.field_size,
+        offset: path.offset,
+    })
+}
+
+/// Left-pad a hex quantity to a 32-byte word; nodes trim leading zeros from storage values
+fn pad_word(value: &str) -> Result<String, WasmError> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    if digits.len() > 64 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(WasmError::InvalidInput(format!("Storage value is not a 32-byte word: {}", value)));
+    }
+    Ok(format!("0x{:0>64}", digits.to_lowercase()))
+}
+
+fn slot_bytes(key: &str) -> Result<Vec<u8>, WasmError> {
+    let digits = key.strip_prefix("0x").unwrap_or(key);
+    let word = pad_word(digits)?;
+    hex::decode(&word[2..]).map_err(|e| WasmError::InvalidInput(format!("Invalid storage key {}: {}", key, e)))
+}
+
+/// Combine a resolved query with the matching `eth_getProof` entry
+///
+/// The proof entry must be for the query's storage key, compared as 32-byte words.
+pub fn build_request(
+    storage_query: CoprocessorStorageQuery,
+    proof: ProofEntry,
+    contract_address: Option<String>,
+    block_number: Option<u64>,
+) -> Result<StorageVerificationRequest, WasmError> {
+    if !proof.is_for(&storage_query.storage_key)? {
+        return Err(WasmError::InvalidInput(format!(
+            "Proof is for storage key {}, but the query resolved to {}",
+            proof.key, storage_query.storage_key
+        )));
+    }
+
+    Ok(StorageVerificationRequest {
+        storage_proof: StorageProof {
+            key: storage_query.storage_key.clone(),
+            value: pad_word(&proof.value)?,
+            proof: proof.proof,
+        },
+        storage_query,
+        contract_address,
+        block_number,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAYOUT: &str = r#"{
+        "contract_name": "Token",
+        "storage": [
+            { "label": "totalSupply", "slot": "2", "offset": 0, "type_name": "t_uint256", "zero_semantics": "ValidZero" },
+            { "label": "balances", "slot": "0", "offset": 0, "type_name": "t_mapping(t_address,t_uint256)", "zero_semantics": "NeverWritten" }
+        ],
+        "types": [
+            { "label": "t_uint256", "number_of_bytes": "32", "encoding": "inplace", "base": null, "key": null, "value": null },
+            { "label": "t_mapping(t_address,t_uint256)", "number_of_bytes": "32", "encoding": "mapping",
+              "base": null, "key": "t_address", "value": "t_uint256" }
+        ]
+    }"#;
+
+    #[test]
+    fn test_resolve_and_build_request() {
+        let query = resolve_query(LAYOUT, "totalSupply").unwrap();
+        assert_eq!(query.storage_key, format!("{:0>64}", "2"));
+        assert_eq!(query.field_size, Some(32));
+
+        let balance = resolve_query(LAYOUT, "balances[0x742d35Cc6634C0532925a3b8D97C2e0D8b2D9C53]").unwrap();
+        assert_ne!(balance.storage_key, query.storage_key);
+        assert!(resolve_query(LAYOUT, "allowances[0x1]").is_err());
+
+        let proof = ProofEntry { key: "0x2".to_string(), value: "0x3e8".to_string(), proof: vec!["0xf8".to_string()] };
+        let request = build_request(query.clone(), proof, None, Some(19_000_000)).unwrap();
+        assert_eq!(request.storage_proof.value, format!("0x{:0>64}", "3e8"));
+
+        let wrong_slot = ProofEntry { key: "0x3".to_string(), value: "0x0".to_string(), proof: vec![] };
+        assert!(build_request(query, wrong_slot, None, None).is_err());
+    }
+}
@@ -0,0 +1,102 @@
+//! Witness creation and inspection
+
+use crate::WasmError;
+use serde::Serialize;
+use traverse_valence::circuit::{CircuitProcessor, ZeroSemantics as CircuitSemantics};
+use traverse_valence::controller;
+use traverse_valence::{BatchStorageVerificationRequest, StorageVerificationRequest};
+use valence_coprocessor::Witness;
+use traverse_core::ZeroSemantics;
+
+/// The fields of a storage witness, hex encoded
+#[derive(Debug, Serialize)]
+pub struct WitnessSummary {
+    pub storage_key: String,
+    pub layout_commitment: String,
+    pub value: String,
+    pub zero_semantics: ZeroSemantics,
+    pub block_height: u64,
+    pub block_hash: String,
+    pub proof_len: usize,
+    pub field_index: u16,
+    pub expected_slot: String,
+}
+
+fn witness_bytes(witness: Witness) -> Result<Vec<u8>, WasmError> {
+    match witness {
+        Witness::Data(data) => Ok(data),
+        _ => Err(WasmError::Witness("Controller returned a state proof witness".to_string())),
+    }
+}
+
+/// Create a storage witness, returning its bytes
+pub fn create_witness(request: &StorageVerificationRequest) -> Result<Vec<u8>, WasmError> {
+    let witness = controller::create_witness_from_request(request).map_err(|e| WasmError::Witness(e.to_string()))?;
+    witness_bytes(witness)
+}
+
+/// Create a witness per request in a batch
+pub fn create_witnesses(request: &BatchStorageVerificationRequest) -> Result<Vec<Vec<u8>>, WasmError> {
+    controller::create_witnesses_from_batch_request(request)
+        .map_err(|e| WasmError::Witness(e.to_string()))?
+        .into_iter()
+        .map(witness_bytes)
+        .collect()
+}
+
+/// Decode a storage witness as the circuit reads it
+pub fn inspect_witness(data: &[u8]) -> Result<WitnessSummary, WasmError> {
+    let witness = CircuitProcessor::parse_witness_from_bytes(data).map_err(|e| WasmError::Witness(e.to_string()))?;
+    let zero_semantics = match witness.semantics {
+        CircuitSemantics::NeverWritten => ZeroSemantics::NeverWritten,
+        CircuitSemantics::ExplicitlyZero => ZeroSemantics::ExplicitlyZero,
+        CircuitSemantics::Cleared => ZeroSemantics::Cleared,
+        CircuitSemantics::ValidZero => ZeroSemantics::ValidZero,
+    };
+
+    Ok(WitnessSummary {
+        storage_key: hex::encode(witness.key),
+        layout_commitment: hex::encode(witness.layout_commitment),
+        value: hex::encode(witness.value),
+        zero_semantics,
+        block_height: witness.block_height,
+        block_hash: hex::encode(witness.block_hash),
+        proof_len: witness.proof.len(),
+        field_index: witness.field_index,
+        expected_slot: hex::encode(witness.expected_slot),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::{build_request, resolve_query, ProofEntry};
+
+    const LAYOUT: &str = r#"{
+        "contract_name": "Vault",
+        "storage": [{ "label": "owner", "slot": "0", "offset": 0, "type_name": "t_address", "zero_semantics": "NeverWritten" }],
+        "types": [{ "label": "t_address", "number_of_bytes": "20", "encoding": "inplace", "base": null, "key": null, "value": null }]
+    }"#;
+
+    #[test]
+    fn test_witness_round_trip() {
+        let query = resolve_query(LAYOUT, "owner").unwrap();
+        let proof = ProofEntry {
+            key: "0x0".to_string(),
+            value: "0x742d35cc6634c0532925a3b8d97c2e0d8b2d9c53".to_string(),
+            proof: vec!["0xf851".to_string(), "0x80".to_string()],
+        };
+        let request = build_request(query.clone(), proof, None, Some(100)).unwrap();
+
+        let data = create_witness(&request).unwrap();
+        let summary = inspect_witness(&data).unwrap();
+        assert_eq!(summary.storage_key, query.storage_key);
+        assert_eq!(summary.layout_commitment, query.layout_commitment);
+        assert_eq!(summary.value, format!("{:0>64}", "742d35cc6634c0532925a3b8d97c2e0d8b2d9c53"));
+        assert_eq!(summary.proof_len, 3);
+
+        let batch = BatchStorageVerificationRequest { storage_batch: vec![request.clone(), request], contract_address: None, block_number: None };
+        assert_eq!(create_witnesses(&batch).unwrap().len(), 2);
+        assert!(inspect_witness(&data[..100]).is_err());
+    }
+}
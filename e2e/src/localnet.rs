@@ -0,0 +1,415 @@
+//! Local chain orchestration for integration runs
+//!
+//! Spins up anvil, solana-test-validator and wasmd on free local ports,
+//! loads the fixture state each suite proves against, and tears the chains
+//! down when dropped. A chain whose tools are not installed is reported as
+//! unavailable so the suite can skip it:
+//!
+//! - anvil: a contract holding an ERC20 `totalSupply` in slot 0 is deployed
+//!   from the first dev account
+//! - solana-test-validator: the recorded SPL Token mint is preloaded at
+//!   genesis with `--account`
+//! - wasmd: a single-validator chain is initialised, and the CW20 contract at
+//!   `TRAVERSE_E2E_CW20_WASM` is stored and instantiated
+//!
+//! These run only when the suite is started with `--integration`.
+
+use crate::mock_rpc::SOLANA_RECORDING;
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::fs;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+use tempfile::TempDir;
+
+/// Init code for the Ethereum fixture: stores 1,000,000 tokens (6 decimals)
+/// in slot 0, as `totalSupply` in `erc20.layout.json`, and deploys `STOP`
+pub const ETHEREUM_FIXTURE_INITCODE: &str = "0x64e8d4a510006000556001601560003960016000f300";
+
+/// The slot 0 value the Ethereum fixture stores
+pub const ETHEREUM_FIXTURE_TOTAL_SUPPLY: &str = "e8d4a51000";
+
+/// Chain ID the wasmd localnet is initialised with
+pub const WASMD_CHAIN_ID: &str = "traverse-localnet";
+
+/// Environment variable naming the CW20 contract wasmd deploys
+pub const CW20_WASM_ENV: &str = "TRAVERSE_E2E_CW20_WASM";
+
+/// How long a chain gets to answer RPC calls after it is spawned
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A running local chain
+pub struct Localnet {
+    name: &'static str,
+    process: Option<Child>,
+    rpc_url: String,
+    dir: TempDir,
+}
+
+impl Localnet {
+    fn spawn(name: &'static str, mut command: Command, rpc_url: String, dir: TempDir) -> Result<Self> {
+        let log = fs::File::create(dir.path().join(format!("{}.log", name)))?;
+        let process = command
+            .stdout(Stdio::from(log.try_clone()?))
+            .stderr(Stdio::from(log))
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("Failed to start {}: {}", name, e))?;
+        Ok(Self { name, process: Some(process), rpc_url, dir })
+    }
+
+    /// RPC endpoint of the chain
+    pub fn rpc_url(&self) -> &str {
+        &self.rpc_url
+    }
+
+    /// Poll `method` until `ready` accepts its result or the chain exits
+    async fn wait_until(&mut self, method: &str, params: Value, ready: impl Fn(&Value) -> bool) -> Result<()> {
+        let deadline = tokio::time::Instant::now() + STARTUP_TIMEOUT;
+        while tokio::time::Instant::now() < deadline {
+            if let Some(status) = self.process.as_mut().and_then(|p| p.try_wait().ok().flatten()) {
+                return Err(anyhow::anyhow!("{} exited with {}; see {}", self.name, status, self.log_path().display()));
+            }
+            if let Ok(result) = rpc_call(&self.rpc_url, method, params.clone()).await {
+                if ready(&result) {
+                    return Ok(());
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+        Err(anyhow::anyhow!(
+            "{} did not become ready within {}s; see {}",
+            self.name,
+            STARTUP_TIMEOUT.as_secs(),
+            self.log_path().display()
+        ))
+    }
+
+    fn log_path(&self) -> PathBuf {
+        self.dir.path().join(format!("{}.log", self.name))
+    }
+
+    /// Stop the chain
+    pub fn stop(&mut self) {
+        if let Some(mut process) = self.process.take() {
+            let _ = process.kill();
+            let _ = process.wait();
+        }
+    }
+}
+
+impl Drop for Localnet {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// An anvil chain with the Ethereum fixture contract deployed
+pub struct AnvilLocalnet {
+    pub chain: Localnet,
+    /// Address of the fixture contract
+    pub contract: String,
+}
+
+impl AnvilLocalnet {
+    /// Whether anvil is installed
+    pub fn available() -> bool {
+        tool_available("anvil", &["--version"])
+    }
+
+    /// Start anvil and deploy the fixture contract
+    pub async fn start() -> Result<Self> {
+        let port = free_port()?;
+        let mut command = Command::new("anvil");
+        command.args(["--host", "127.0.0.1", "--port", &port.to_string(), "--silent"]);
+        let mut chain = Localnet::spawn("anvil", command, format!("http://127.0.0.1:{}", port), TempDir::new()?)?;
+        chain.wait_until("eth_chainId", json!([]), Value::is_string).await?;
+
+        let accounts = rpc_call(chain.rpc_url(), "eth_accounts", json!([])).await?;
+        let from = accounts[0].as_str().ok_or_else(|| anyhow::anyhow!("anvil has no dev accounts"))?;
+        let tx_hash = rpc_call(
+            chain.rpc_url(),
+            "eth_sendTransaction",
+            json!([{ "from": from, "data": ETHEREUM_FIXTURE_INITCODE }]),
+        )
+        .await?;
+        let receipt = rpc_call(chain.rpc_url(), "eth_getTransactionReceipt", json!([tx_hash])).await?;
+        let contract = receipt["contractAddress"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Fixture deployment has no contract address: {}", receipt))?
+            .to_string();
+
+        Ok(Self { chain, contract })
+    }
+}
+
+/// A solana-test-validator with the recorded SPL Token mint preloaded
+pub struct SolanaLocalnet {
+    pub chain: Localnet,
+    /// Address of the preloaded mint
+    pub account: String,
+    /// Owner of the preloaded mint
+    pub program_id: String,
+}
+
+impl SolanaLocalnet {
+    /// Whether solana-test-validator is installed
+    pub fn available() -> bool {
+        tool_available("solana-test-validator", &["--version"])
+    }
+
+    /// Start a validator with the fixture mint in its genesis
+    pub async fn start() -> Result<Self> {
+        let recording: Value = serde_json::from_str(SOLANA_RECORDING)?;
+        let account = recording["account"].as_str().unwrap_or_default().to_string();
+        let program_id = recording["program_id"].as_str().unwrap_or_default().to_string();
+        let mint = &recording["responses"]["getAccountInfo"]["value"];
+
+        // The format `solana account --output json` writes and `--account` reads
+        let dir = TempDir::new()?;
+        let account_file = dir.path().join("mint.json");
+        fs::write(
+            &account_file,
+            serde_json::to_string_pretty(&json!({
+                "pubkey": account,
+                "account": {
+                    "lamports": mint["lamports"],
+                    "data": mint["data"],
+                    "owner": mint["owner"],
+                    "executable": false,
+                    "rentEpoch": 0,
+                    "space": mint["space"],
+                }
+            }))?,
+        )?;
+
+        let (rpc_port, faucet_port, gossip_port) = (free_port()?, free_port()?, free_port()?);
+        let mut command = Command::new("solana-test-validator");
+        command
+            .arg("--ledger")
+            .arg(dir.path().join("ledger"))
+            .args(["--bind-address", "127.0.0.1", "--reset", "--quiet"])
+            .args(["--rpc-port", &rpc_port.to_string()])
+            .args(["--faucet-port", &faucet_port.to_string()])
+            .args(["--gossip-port", &gossip_port.to_string()])
+            .arg("--account")
+            .arg(&account)
+            .arg(&account_file);
+        let mut chain = Localnet::spawn(
+            "solana-test-validator",
+            command,
+            format!("http://127.0.0.1:{}", rpc_port),
+            dir,
+        )?;
+        // The first blocks are produced a few slots after the RPC answers
+        chain
+            .wait_until("getSlot", json!([{ "commitment": "finalized" }]), |slot| {
+                slot.as_u64().is_some_and(|slot| slot > 1)
+            })
+            .await?;
+
+        Ok(Self { chain, account, program_id })
+    }
+}
+
+/// A single-validator wasmd chain with the CW20 fixture instantiated
+pub struct WasmdLocalnet {
+    pub chain: Localnet,
+    /// Address of the CW20 contract
+    pub contract: String,
+}
+
+impl WasmdLocalnet {
+    /// Whether wasmd is installed and a CW20 contract to deploy is configured
+    pub fn available() -> bool {
+        tool_available("wasmd", &["version"]) && Self::cw20_wasm().is_some()
+    }
+
+    fn cw20_wasm() -> Option<PathBuf> {
+        std::env::var_os(CW20_WASM_ENV).map(PathBuf::from).filter(|path| path.exists())
+    }
+
+    /// Initialise and start the chain, then store and instantiate the CW20 contract
+    pub async fn start() -> Result<Self> {
+        let wasm = Self::cw20_wasm().ok_or_else(|| anyhow::anyhow!("Set {} to a cw20_base.wasm", CW20_WASM_ENV))?;
+        let dir = TempDir::new()?;
+        let home = dir.path().join("wasmd");
+        let home = home.to_str().unwrap().to_string();
+        let wasmd = |args: &[&str]| wasmd_cli(&home, args);
+
+        wasmd(&["init", "traverse", "--chain-id", WASMD_CHAIN_ID])?;
+        wasmd(&["keys", "add", "validator", "--keyring-backend", "test"])?;
+        let validator = wasmd(&["keys", "show", "validator", "-a", "--keyring-backend", "test"])?;
+        wasmd(&["genesis", "add-genesis-account", validator.trim(), "1000000000000stake"])?;
+        wasmd(&[
+            "genesis",
+            "gentx",
+            "validator",
+            "1000000000stake",
+            "--chain-id",
+            WASMD_CHAIN_ID,
+            "--keyring-backend",
+            "test",
+        ])?;
+        wasmd(&["genesis", "collect-gentxs"])?;
+
+        // One-second blocks keep deployment and the next-header lookup quick
+        let config_path = Path::new(&home).join("config/config.toml");
+        let config = fs::read_to_string(&config_path)?;
+        fs::write(&config_path, config.replace("timeout_commit = \"5s\"", "timeout_commit = \"1s\""))?;
+
+        let (rpc_port, p2p_port, grpc_port) = (free_port()?, free_port()?, free_port()?);
+        let rpc_url = format!("http://127.0.0.1:{}", rpc_port);
+        let mut command = Command::new("wasmd");
+        command
+            .args(["start", "--home", &home, "--minimum-gas-prices", "0stake"])
+            .args(["--rpc.laddr", &format!("tcp://127.0.0.1:{}", rpc_port)])
+            .args(["--p2p.laddr", &format!("tcp://127.0.0.1:{}", p2p_port)])
+            .args(["--grpc.address", &format!("127.0.0.1:{}", grpc_port)]);
+        let mut chain = Localnet::spawn("wasmd", command, rpc_url.clone(), dir)?;
+        chain
+            .wait_until("status", json!({}), |status| {
+                status["sync_info"]["latest_block_height"]
+                    .as_str()
+                    .and_then(|height| height.parse::<u64>().ok())
+                    .is_some_and(|height| height > 1)
+            })
+            .await?;
+
+        let tx = |args: &[&str]| -> Result<()> {
+            let mut full = vec!["tx", "wasm"];
+            full.extend_from_slice(args);
+            full.extend_from_slice(&[
+                "--from",
+                "validator",
+                "--keyring-backend",
+                "test",
+                "--chain-id",
+                WASMD_CHAIN_ID,
+                "--node",
+                &rpc_url,
+                "--gas",
+                "auto",
+                "--gas-adjustment",
+                "1.5",
+                "--yes",
+            ]);
+            wasmd(&full).map(|_| ())
+        };
+
+        tx(&["store", wasm.to_str().unwrap()])?;
+        let code_id = poll_query(&home, &rpc_url, &["list-code"], |codes| {
+            codes["code_infos"].as_array()?.last()?["code_id"].as_str().map(str::to_string)
+        })
+        .await?;
+
+        let init_msg = json!({
+            "name": "Traverse Test Token",
+            "symbol": "TTT",
+            "decimals": 6,
+            "initial_balances": [{ "address": validator.trim(), "amount": "1000000000" }]
+        });
+        tx(&[
+            "instantiate",
+            &code_id,
+            &init_msg.to_string(),
+            "--label",
+            "traverse-e2e",
+            "--no-admin",
+        ])?;
+        let contract = poll_query(&home, &rpc_url, &["list-contract-by-code", &code_id], |contracts| {
+            contracts["contracts"].as_array()?.first()?.as_str().map(str::to_string)
+        })
+        .await?;
+
+        Ok(Self { chain, contract })
+    }
+}
+
+/// Run a wasmd CLI command against `home`, returning its stdout
+fn wasmd_cli(home: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new("wasmd").args(args).args(["--home", home]).output()?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "wasmd {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// Repeat a `wasmd query wasm` until `extract` finds what a transaction created
+async fn poll_query(
+    home: &str,
+    rpc_url: &str,
+    args: &[&str],
+    extract: impl Fn(&Value) -> Option<String>,
+) -> Result<String> {
+    let mut full = vec!["query", "wasm"];
+    full.extend_from_slice(args);
+    full.extend_from_slice(&["--node", rpc_url, "--output", "json"]);
+    for _attempt in 0..30 {
+        if let Ok(output) = wasmd_cli(home, &full) {
+            if let Some(found) = serde_json::from_str(&output).ok().as_ref().and_then(&extract) {
+                return Ok(found);
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+    Err(anyhow::anyhow!("wasmd query wasm {} found nothing within 30s", args.join(" ")))
+}
+
+/// Send one JSON-RPC call and return its result
+pub async fn rpc_call(url: &str, method: &str, params: Value) -> Result<Value> {
+    let response: Value = reqwest::Client::new()
+        .post(url)
+        .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await?
+        .json()
+        .await?;
+    if let Some(error) = response.get("error") {
+        return Err(anyhow::anyhow!("{} failed: {}", method, error));
+    }
+    Ok(response["result"].clone())
+}
+
+/// Whether `program` runs successfully with `args`
+pub fn tool_available(program: &str, args: &[&str]) -> bool {
+    Command::new(program)
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// A local TCP port nothing is listening on
+fn free_port() -> Result<u16> {
+    Ok(TcpListener::bind("127.0.0.1:0")?.local_addr()?.port())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ethereum_fixture_initcode() {
+        let code = hex::decode(ETHEREUM_FIXTURE_INITCODE.trim_start_matches("0x")).unwrap();
+        // PUSH5 <total supply>
+        assert_eq!(code[0], 0x64);
+        assert_eq!(hex::encode(&code[1..6]), ETHEREUM_FIXTURE_TOTAL_SUPPLY);
+        // CODECOPY copies the final byte, which is the deployed code
+        assert_eq!(code[12] as usize, code.len() - 1);
+        assert_eq!(code[code.len() - 1], 0x00);
+    }
+
+    #[test]
+    fn test_free_port() {
+        assert_ne!(free_port().unwrap(), 0);
+    }
+}
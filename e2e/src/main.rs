@@ -2,14 +2,20 @@
 //!
 //! This module runs comprehensive integration tests against the traverse CLI
 //! to ensure all commands work correctly in real-world scenarios.
+//!
+//! Pass `--integration` to also run the proof pipeline against local chains
+//! (anvil, solana-test-validator, wasmd) where they are installed.
 
 use anyhow::Result;
 use std::process::Command;
 
 mod fixtures;
+mod localnet;
+mod mock_rpc;
 mod test_core;
 mod test_cosmos;
 mod test_ethereum;
+mod test_localnet;
 mod test_solana;
 mod test_unified;
 mod utils;
@@ -18,6 +24,8 @@ use fixtures::TestFixtures;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let integration = std::env::args().any(|arg| arg == "--integration");
+
     println!("Starting traverse CLI end-to-end test suite");
     println!("==========================================");
 
@@ -79,6 +87,19 @@ async fn main() -> Result<()> {
     )
     .await;
 
+    // Test the proof pipeline against local chains
+    if integration {
+        run_test_suite(
+            "Localnet Integration",
+            test_localnet::run_tests(&fixtures),
+            &mut passed,
+            &mut failed,
+        )
+        .await;
+    } else {
+        println!("\nSkipping localnet integration tests (pass --integration to enable)");
+    }
+
     // Print summary
     println!("\nTest Results Summary:");
     println!("====================");
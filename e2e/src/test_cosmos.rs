@@ -8,6 +8,7 @@
 //! - traverse cosmos auto-generate
 
 use crate::fixtures::TestFixtures;
+use crate::mock_rpc::MockRpcServer;
 use crate::utils::*;
 use anyhow::Result;
 use std::fs;
@@ -37,6 +38,9 @@ pub async fn run_tests(fixtures: &TestFixtures) -> Result<()> {
     println!("     performance...");
     test_performance(&cli, fixtures).await?;
 
+    println!("     generate-proof against recorded RPC responses...");
+    test_generate_proof_with_mock_rpc(&cli, fixtures).await?;
+
     Ok(())
 }
 
@@ -422,6 +426,61 @@ async fn test_performance(cli: &CliRunner, fixtures: &TestFixtures) -> Result<()
     Ok(())
 }
 
+/// Test `traverse cosmos generate-proof` through the real fetch path
+///
+/// The wasm store read and both ICS23 proof layers come from the mock RPC
+/// server and are checked against the recorded header's app hash.
+async fn test_generate_proof_with_mock_rpc(cli: &CliRunner, fixtures: &TestFixtures) -> Result<()> {
+    let rpc = MockRpcServer::cosmos().await?;
+    let proof_path = fixtures.path("outputs/mock_rpc_cosmos_proof.json");
+
+    cli.run_success(&[
+        "cosmos",
+        "generate-proof",
+        "--address",
+        rpc.recorded("contract_address")?,
+        "--query",
+        "token_info",
+        "--rpc",
+        &rpc.uri(),
+        "--chain-id",
+        rpc.recorded("chain_id")?,
+        "--output",
+        proof_path.to_str().unwrap(),
+    ])?;
+
+    let artifact = FileValidator::validate_json(&proof_path)?;
+    assert_eq!(artifact["exists"], true);
+    assert_eq!(artifact["height"], 12345);
+    assert_eq!(artifact["decoded_value"]["symbol"], "TTT");
+    assert_eq!(artifact["decoded_value"]["decimals"], 6);
+    assert_eq!(artifact["header"]["chain_id"], "neutron-1");
+
+    let methods = rpc.received_methods().await;
+    for method in ["status", "abci_query", "block"] {
+        if !methods.iter().any(|m| m == method) {
+            return Err(anyhow::anyhow!("generate-proof never called {}", method));
+        }
+    }
+
+    // The header's chain ID must match the one asked for
+    cli.run_failure(&[
+        "cosmos",
+        "generate-proof",
+        "--address",
+        rpc.recorded("contract_address")?,
+        "--query",
+        "token_info",
+        "--rpc",
+        &rpc.uri(),
+        "--chain-id",
+        "osmosis-1",
+    ])?;
+
+    println!("      generate-proof with mock RPC tests passed");
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
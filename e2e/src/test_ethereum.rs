@@ -7,8 +7,10 @@
 //! - traverse ethereum resolve-query
 //! - traverse ethereum verify-layout
 //! - traverse ethereum auto-generate
+//! - traverse ethereum generate-proof / verify-proof (against the mock RPC server)
 
-use crate::fixtures::TestFixtures;
+use crate::fixtures::{get_fixture_path, TestFixtures};
+use crate::mock_rpc::MockRpcServer;
 use crate::utils::*;
 use anyhow::Result;
 use std::fs;
@@ -65,6 +67,9 @@ pub async fn run_tests(fixtures: &TestFixtures) -> Result<()> {
     println!("    🔷 Testing Ethereum event validation with real blockchain data...");
     test_event_validation_with_real_blockchain_data(&cli, fixtures).await?;
 
+    println!("    🔷 Testing Ethereum generate-proof against recorded RPC responses...");
+    test_generate_proof_with_mock_rpc(&cli, fixtures).await?;
+
     Ok(())
 }
 
@@ -2036,6 +2041,86 @@ async fn test_event_validation_with_real_blockchain_data(
     Ok(())
 }
 
+/// Test `traverse ethereum generate-proof` and `verify-proof` through the real fetch path
+///
+/// The proof is fetched from the mock RPC server, so the chain ID check,
+/// block lookup and `eth_getProof` call all run without network access.
+async fn test_generate_proof_with_mock_rpc(cli: &CliRunner, fixtures: &TestFixtures) -> Result<()> {
+    let rpc = MockRpcServer::ethereum().await?;
+    let layout_path = get_fixture_path("ethereum/erc20.layout.json");
+    let proof_path = fixtures.path("outputs/mock_rpc_proof.json");
+    let block = rpc.response("eth_getBlockByNumber");
+    let state_root = block["stateRoot"].as_str().unwrap();
+
+    cli.run_success(&[
+        "ethereum",
+        "generate-proof",
+        "--address",
+        rpc.recorded("contract_address")?,
+        "--query",
+        "totalSupply",
+        "--layout",
+        layout_path.to_str().unwrap(),
+        "--rpc",
+        &rpc.uri(),
+        "--block",
+        block["number"].as_str().unwrap(),
+        "--output",
+        proof_path.to_str().unwrap(),
+    ])?;
+
+    let proof_json = FileValidator::validate_json(&proof_path)?;
+    assert_eq!(proof_json["block"]["state_root"], state_root);
+    assert_eq!(proof_json["storage_key"], "0".repeat(64));
+    let value = proof_json["value"].as_str().unwrap_or_default();
+    assertions::assert_output_contains(value, "e8d4a51000")?;
+
+    let methods = rpc.received_methods().await;
+    for method in ["eth_chainId", "eth_getBlockByNumber", "eth_getProof"] {
+        if !methods.iter().any(|m| m == method) {
+            return Err(anyhow::anyhow!("generate-proof never called {}", method));
+        }
+    }
+
+    // The embedded eth_getProof response verifies offline against the recorded state root
+    let rpc_proof_path = fixtures.path("outputs/mock_rpc_eth_get_proof.json");
+    fs::write(&rpc_proof_path, serde_json::to_string_pretty(&proof_json["proof"])?)?;
+    let output = cli.run_success(&[
+        "ethereum",
+        "verify-proof",
+        "--proof",
+        rpc_proof_path.to_str().unwrap(),
+        "--layout",
+        layout_path.to_str().unwrap(),
+        "--state-root",
+        state_root,
+        "--query",
+        "totalSupply",
+    ])?;
+    assertions::assert_output_contains(&output, "totalSupply")?;
+
+    // A different state root does not
+    let wrong_root = format!("0x{}", "11".repeat(32));
+    cli.run_with_exit_code(
+        &[
+            "ethereum",
+            "verify-proof",
+            "--proof",
+            rpc_proof_path.to_str().unwrap(),
+            "--layout",
+            layout_path.to_str().unwrap(),
+            "--state-root",
+            &wrong_root,
+            "--query",
+            "totalSupply",
+        ],
+        2,
+    )?;
+
+    println!("      generate-proof with mock RPC tests passed");
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
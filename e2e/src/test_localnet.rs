@@ -0,0 +1,152 @@
+//! Integration tests against live local chains
+//!
+//! Runs the proof pipeline end to end on each localnet the harness can
+//! start, skipping chains whose tools are not installed:
+//! - anvil: traverse ethereum generate-proof, then verify-proof offline
+//! - solana-test-validator: traverse solana generate-proof
+//! - wasmd: traverse cosmos generate-proof
+//!
+//! Enabled with `--integration`.
+
+use crate::fixtures::{get_fixture_path, TestFixtures};
+use crate::localnet::{
+    AnvilLocalnet, SolanaLocalnet, WasmdLocalnet, CW20_WASM_ENV, ETHEREUM_FIXTURE_TOTAL_SUPPLY, WASMD_CHAIN_ID,
+};
+use crate::utils::*;
+use anyhow::Result;
+use std::fs;
+
+/// Run the proof pipeline on every available localnet
+pub async fn run_tests(fixtures: &TestFixtures) -> Result<()> {
+    let cli = CliRunner::new(fixtures.cli_path());
+
+    if AnvilLocalnet::available() {
+        println!("    Testing Ethereum proof pipeline on anvil...");
+        test_ethereum_pipeline(&cli, fixtures).await?;
+    } else {
+        println!("    Skipping anvil - install Foundry to enable");
+    }
+
+    if SolanaLocalnet::available() {
+        println!("    Testing Solana proof pipeline on solana-test-validator...");
+        test_solana_pipeline(&cli, fixtures).await?;
+    } else {
+        println!("    Skipping solana-test-validator - install the Solana CLI tools to enable");
+    }
+
+    if WasmdLocalnet::available() {
+        println!("    Testing Cosmos proof pipeline on wasmd...");
+        test_cosmos_pipeline(&cli, fixtures).await?;
+    } else {
+        println!("    Skipping wasmd - install wasmd and set {} to a cw20_base.wasm to enable", CW20_WASM_ENV);
+    }
+
+    Ok(())
+}
+
+/// Prove the fixture contract's `totalSupply` and verify the proof offline
+async fn test_ethereum_pipeline(cli: &CliRunner, fixtures: &TestFixtures) -> Result<()> {
+    let anvil = AnvilLocalnet::start().await?;
+    let layout_path = get_fixture_path("ethereum/erc20.layout.json");
+    let proof_path = fixtures.path("outputs/localnet_ethereum_proof.json");
+
+    cli.run_success(&[
+        "ethereum",
+        "generate-proof",
+        "--address",
+        &anvil.contract,
+        "--query",
+        "totalSupply",
+        "--layout",
+        layout_path.to_str().unwrap(),
+        "--rpc",
+        anvil.chain.rpc_url(),
+        "--output",
+        proof_path.to_str().unwrap(),
+    ])?;
+
+    let proof_json = FileValidator::validate_json(&proof_path)?;
+    assertions::assert_output_contains(proof_json["value"].as_str().unwrap_or_default(), ETHEREUM_FIXTURE_TOTAL_SUPPLY)?;
+    let state_root = proof_json["block"]["state_root"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Proof has no state root"))?;
+
+    let rpc_proof_path = fixtures.path("outputs/localnet_eth_get_proof.json");
+    fs::write(&rpc_proof_path, serde_json::to_string_pretty(&proof_json["proof"])?)?;
+    let output = cli.run_success(&[
+        "ethereum",
+        "verify-proof",
+        "--proof",
+        rpc_proof_path.to_str().unwrap(),
+        "--layout",
+        layout_path.to_str().unwrap(),
+        "--state-root",
+        state_root,
+        "--query",
+        "totalSupply",
+    ])?;
+    assertions::assert_output_contains(&output, "\"valid\": true")?;
+
+    println!("      anvil pipeline passed");
+    Ok(())
+}
+
+/// Prove the preloaded mint account
+async fn test_solana_pipeline(cli: &CliRunner, fixtures: &TestFixtures) -> Result<()> {
+    let validator = SolanaLocalnet::start().await?;
+    let proof_path = fixtures.path("outputs/localnet_solana_proof.json");
+
+    cli.run_success(&[
+        "solana",
+        "generate-proof",
+        "--account",
+        &validator.account,
+        "--query",
+        "mint",
+        "--program-id",
+        &validator.program_id,
+        "--rpc",
+        validator.chain.rpc_url(),
+        "--output",
+        proof_path.to_str().unwrap(),
+    ])?;
+
+    let request = FileValidator::validate_json(&proof_path)?;
+    assert_eq!(request["account_proof"]["address"], validator.account.as_str());
+    assert_eq!(request["account_proof"]["owner"], validator.program_id.as_str());
+    if request["account_proof"]["block_hash"].as_str().unwrap_or_default().is_empty() {
+        return Err(anyhow::anyhow!("Solana proof has no block hash"));
+    }
+
+    println!("      solana-test-validator pipeline passed");
+    Ok(())
+}
+
+/// Prove the CW20 contract's `token_info` against the next header's app hash
+async fn test_cosmos_pipeline(cli: &CliRunner, fixtures: &TestFixtures) -> Result<()> {
+    let wasmd = WasmdLocalnet::start().await?;
+    let proof_path = fixtures.path("outputs/localnet_cosmos_proof.json");
+
+    cli.run_success(&[
+        "cosmos",
+        "generate-proof",
+        "--address",
+        &wasmd.contract,
+        "--query",
+        "token_info",
+        "--rpc",
+        wasmd.chain.rpc_url(),
+        "--chain-id",
+        WASMD_CHAIN_ID,
+        "--output",
+        proof_path.to_str().unwrap(),
+    ])?;
+
+    let artifact = FileValidator::validate_json(&proof_path)?;
+    assert_eq!(artifact["exists"], true);
+    assert_eq!(artifact["decoded_value"]["symbol"], "TTT");
+    assert_eq!(artifact["header"]["chain_id"], WASMD_CHAIN_ID);
+
+    println!("      wasmd pipeline passed");
+    Ok(())
+}
@@ -9,12 +9,17 @@ use tokio::time::sleep;
 use anyhow::Result;
 
 use crate::fixtures::{get_fixture_path, SolanaTestContext, TestFixtures};
+use crate::mock_rpc::MockRpcServer;
+use crate::utils::{CliRunner, FileValidator};
 use crate::utils::{run_cli_command, check_command_success};
 
 /// Run all Solana e2e tests
 pub async fn run_tests(fixtures: &TestFixtures) -> Result<()> {
     println!("Running Solana end-to-end tests...");
     
+    // The mock RPC server stands in for a cluster, so this needs no Solana tools
+    test_generate_proof_with_mock_rpc(fixtures).await?;
+    
     // Check if Solana tools are available
     if !solana_tools_available() {
         println!("  Skipping Solana tests - tools not available");
@@ -36,6 +41,43 @@ pub async fn run_tests(fixtures: &TestFixtures) -> Result<()> {
     Ok(())
 }
 
+/// Test `traverse solana generate-proof` through the real fetch path
+///
+/// The account and the block it was read at come from the mock RPC server.
+async fn test_generate_proof_with_mock_rpc(fixtures: &TestFixtures) -> Result<()> {
+    println!("    Testing Solana generate-proof against recorded RPC responses...");
+    
+    let cli = CliRunner::new(fixtures.cli_path());
+    let rpc = MockRpcServer::solana().await?;
+    let proof_path = fixtures.path("outputs/mock_rpc_solana_proof.json");
+    
+    cli.run_success(&[
+        "solana",
+        "generate-proof",
+        "--account",
+        rpc.recorded("account")?,
+        "--query",
+        "mint",
+        "--program-id",
+        rpc.recorded("program_id")?,
+        "--rpc",
+        &rpc.uri(),
+        "--output",
+        proof_path.to_str().unwrap(),
+    ])?;
+    
+    let request = FileValidator::validate_json(&proof_path)?;
+    let account_proof = &request["account_proof"];
+    let recorded = &rpc.response("getAccountInfo")["value"];
+    assert_eq!(account_proof["address"], rpc.recorded("account")?);
+    assert_eq!(account_proof["owner"], recorded["owner"]);
+    assert_eq!(account_proof["data"], recorded["data"][0]);
+    assert_eq!(account_proof["block_hash"], rpc.response("getBlock")["blockhash"]);
+    
+    println!("      generate-proof with mock RPC tests passed");
+    Ok(())
+}
+
 /// Check if Solana tools are available
 fn solana_tools_available() -> bool {
     std::process::Command::new("solana")
@@ -685,6 +685,7 @@ fn create_defi_semantic_layout() -> LayoutInfo {
         contract_name: "DeFiProtocol".to_string(),
         storage: Vec::new(),
         types: Vec::new(),
+        code_variables: Vec::new(),
     };
 
     // Protocol-level storage
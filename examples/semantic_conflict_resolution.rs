@@ -372,6 +372,7 @@ fn create_semantic_layout() -> LayoutInfo {
         contract_name: "SemanticExample".to_string(),
         storage: Vec::new(),
         types: Vec::new(),
+        code_variables: Vec::new(),
     };
 
     // Add storage entries with different semantic specifications
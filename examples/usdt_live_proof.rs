@@ -122,6 +122,7 @@ fn create_usdt_semantic_layout() -> LayoutInfo {
             },
         ],
         types,
+        code_variables: Vec::new(),
     }
 }
 
@@ -361,6 +361,7 @@ fn create_valence_vault_semantic_layout() -> LayoutInfo {
             },
         ],
         types,
+        code_variables: Vec::new(),
     }
 }
 